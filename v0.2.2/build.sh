@@ -54,6 +54,24 @@ APPS=(
     slowbreath
     slowsolitaire
     slowdesign
+    slowmonitor
+    slowport
+    slowweb
+    slowchat
+    slowdrop
+    slowsync
+    slowbackup
+    slowkeyboard
+    slowlogs
+    slowcam
+    slowrec
+    slowbeat
+    slowtype
+    slowjournal
+    slowcards
+    slowfont
+    slowstore
+    slowctl
     credits
 )
 
@@ -5,8 +5,11 @@
 BOARD_DIR="$(dirname $0)"
 ROOTFS="$TARGET_DIR"
 
-# Make init script executable
+# Make init scripts executable
 chmod 755 "$ROOTFS/etc/init.d/S99slowos"
+chmod 755 "$ROOTFS/etc/init.d/S40wifi"
+chmod 755 "$ROOTFS/etc/init.d/S45ntp"
+chmod 755 "$ROOTFS/usr/sbin/slowos-time"
 
 # Set hostname
 echo "slowbook" > "$ROOTFS/etc/hostname"
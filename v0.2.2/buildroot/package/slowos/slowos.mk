@@ -30,7 +30,25 @@ SLOWOS_BINARIES = \
 	settings \
 	slowcalc \
 	slowsolitaire \
-	slowdesign
+	slowdesign \
+	slowmonitor \
+	slowport \
+	slowweb \
+	slowchat \
+	slowdrop \
+	slowsync \
+	slowbackup \
+	slowkeyboard \
+	slowlogs \
+	slowcam \
+	slowrec \
+	slowbeat \
+	slowtype \
+	slowjournal \
+	slowcards \
+	slowfont \
+	slowstore \
+	slowctl
 
 define SLOWOS_BUILD_CMDS
 	# Set up Rust cross-compilation environment
@@ -3,7 +3,16 @@
 use egui::{Context, ScrollArea};
 use slowcore::repaint::RepaintController;
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, WindowAction};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "credits",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "open source credits viewer",
+    sections: &[("shows", &["attribution for every open source", "component in slowOS"])],
+    credits: &["egui/eframe (MIT)"],
+    ..AboutInfo::EMPTY
+};
 
 /// Credit category for organizing attributions
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -386,40 +395,8 @@ impl eframe::App for CreditsApp {
                 self.render_content(ui);
             });
 
-        if self.show_about {
-            let screen = ctx.screen_rect();
-            let max_h = (screen.height() - 60.0).max(120.0);
-
-            let resp = egui::Window::new("about credits")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(300.0)
-                .max_height(max_h)
-                .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                        ui.vertical_centered(|ui| {
-                            ui.heading("credits");
-                            ui.label("version 0.2.2");
-                            ui.add_space(8.0);
-                            ui.label("open source credits viewer");
-                        });
-                        ui.add_space(8.0);
-                        ui.separator();
-                        ui.add_space(4.0);
-                        ui.label("displays attribution information for");
-                        ui.label("all open source components in slowOS");
-                        ui.add_space(4.0);
-                        ui.label("frameworks:");
-                        ui.label("  egui/eframe (MIT)");
-                        ui.add_space(8.0);
-                    });
-                    ui.vertical_centered(|ui| {
-                        if ui.button("ok").clicked() {
-                            self.show_about = false;
-                        }
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
         self.repaint.end_frame(ctx);
     }
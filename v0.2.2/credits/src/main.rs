@@ -4,11 +4,11 @@ mod app;
 
 use app::CreditsApp;
 use slowcore::theme::SlowTheme;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([500.0, 400.0])
+        viewport: window_layout::viewport("credits", Placement::Center, [500.0, 400.0])
             .with_min_inner_size([400.0, 300.0]),
         ..Default::default()
     };
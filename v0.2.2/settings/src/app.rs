@@ -1,14 +1,27 @@
 //! Settings application for slowOS
 
-use chrono::Local;
+use crate::bluetooth::{self, Device};
+use crate::wifi::{self, KnownNetwork, Network};
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, Timelike};
 use egui::{ColorImage, Context, Rect, Sense, Stroke, TextureHandle, TextureOptions, Vec2};
 use serde::{Deserialize, Serialize};
+use slowcore::accounts::{self, Accounts, AUTO_LOCK_CHOICES};
+use slowcore::autocorrect::{self, AutocorrectSettings};
+use slowcore::clock;
+use slowcore::display::{self, Output, Rotation};
+use slowcore::night_shift::{self, Daylight, NightShift, Schedule};
 use slowcore::repaint::RepaintController;
+use slowcore::restrictions::{self, Restrictions};
+use slowcore::sound::{self, Earcon, Mixer, PlayerInfo, SoundTheme};
 use slowcore::storage::config_dir;
-use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
-use std::collections::HashMap;
+use slowcore::sync::{Folder, SyncConfig, SyncState, SyncStatus};
+use slowcore::theme::{menu_bar, SlowColors, GREY_LEVEL_CHOICES};
+use slowcore::update::{self, Available, Release, UpdateConfig};
+use slowcore::widgets::{alert, status_bar, window_control_buttons, WindowAction};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
 /// Get the path to the fun_icons folder
 fn fun_icons_dir() -> PathBuf {
@@ -50,6 +63,60 @@ pub struct SystemSettings {
     /// User's selected icon filename (from fun_icons folder)
     #[serde(default)]
     pub user_icon: String,
+    /// Show the frame-time overlay in every app
+    #[serde(default)]
+    pub perf_overlay: bool,
+    /// UI and text size for every app, in percent (90-200)
+    #[serde(default = "default_ui_scale")]
+    pub ui_scale_percent: u16,
+    /// Tab moves focus between buttons, lists and fields in every app
+    #[serde(default)]
+    pub keyboard_navigation: bool,
+    /// No sliding or fading, shorter animations and fewer frames in every app
+    #[serde(default)]
+    pub reduce_motion: bool,
+    /// Greys photos are drawn with: 2 (black and white), 4 or 16
+    #[serde(default = "default_grey_levels")]
+    pub grey_levels: u8,
+    /// When the screen turns warm and dim for the night
+    #[serde(default)]
+    pub night_shift: NightShift,
+    /// Which set of system sounds to play
+    #[serde(default)]
+    pub sound_theme: SoundTheme,
+    /// Sound theme installed from slowStore, played instead of `sound_theme`
+    #[serde(default)]
+    pub sound_pack: Option<String>,
+    /// How the screen is turned (saved when kept, not via "save changes")
+    #[serde(default)]
+    pub display_rotation: Rotation,
+    /// Resolution per external screen, "HDMI-1" → "1280x720"
+    #[serde(default)]
+    pub display_modes: BTreeMap<String, String>,
+    /// Frame rate limit for every app (0 = none)
+    #[serde(default)]
+    pub fps_cap: u16,
+    /// Open the on-screen keyboard when a text field gains focus
+    #[serde(default)]
+    pub onscreen_keyboard: bool,
+    /// Typo fixes and capitals while typing, and the apps that leave it off
+    #[serde(default)]
+    pub autocorrect: AutocorrectSettings,
+    /// How much every app writes to its log: "error", "warn", "info" or "debug"
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+}
+
+fn default_ui_scale() -> u16 {
+    100
+}
+
+fn default_grey_levels() -> u8 {
+    2
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
 }
 
 impl Default for SystemSettings {
@@ -65,6 +132,20 @@ impl Default for SystemSettings {
             volume: 80,
             user_name: String::new(),
             user_icon: String::new(),
+            perf_overlay: false,
+            ui_scale_percent: default_ui_scale(),
+            keyboard_navigation: false,
+            reduce_motion: false,
+            grey_levels: default_grey_levels(),
+            night_shift: NightShift::default(),
+            sound_theme: SoundTheme::default(),
+            sound_pack: None,
+            display_rotation: Rotation::default(),
+            display_modes: BTreeMap::new(),
+            fps_cap: 0,
+            onscreen_keyboard: false,
+            autocorrect: AutocorrectSettings::default(),
+            log_level: default_log_level(),
         }
     }
 }
@@ -97,13 +178,150 @@ impl SystemSettings {
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum SettingsPane {
     Profile,
+    Accounts,
+    Restrictions,
     DateTime,
     Mouse,
+    Typing,
     Display,
     Sound,
+    Network,
+    Bluetooth,
+    Sync,
+    Updates,
     About,
 }
 
+/// A Wi-Fi scan or join finishing in the background
+enum WifiDone {
+    Scanned(Result<Vec<Network>, String>),
+    Joined(Result<(), String>),
+}
+
+/// An update check or download finishing in the background
+enum UpdateDone {
+    Checked(Result<Available, String>),
+    /// What was downloaded ("slowWrite 0.2.3")
+    Staged(Result<String, String>),
+}
+
+/// A Bluetooth search, pairing or connection finishing in the background
+enum BluetoothDone {
+    Scanned(Result<Vec<Device>, String>),
+    /// What was done ("connected headphones")
+    Finished(Result<String, String>),
+}
+
+/// Something to do with a Bluetooth device
+#[derive(Clone, Copy)]
+enum BluetoothAction {
+    Pair,
+    Connect,
+    Disconnect,
+    Forget,
+}
+
+impl BluetoothAction {
+    fn run(self, address: &str) -> Result<(), String> {
+        match self {
+            BluetoothAction::Pair => bluetooth::pair(address),
+            BluetoothAction::Connect => bluetooth::connect(address),
+            BluetoothAction::Disconnect => bluetooth::disconnect(address),
+            BluetoothAction::Forget => bluetooth::forget(address),
+        }
+    }
+
+    /// What it says while running and after: ("pairing with", "paired")
+    fn words(self) -> (&'static str, &'static str) {
+        match self {
+            BluetoothAction::Pair => ("pairing with", "paired"),
+            BluetoothAction::Connect => ("connecting to", "connected"),
+            BluetoothAction::Disconnect => ("disconnecting", "disconnected"),
+            BluetoothAction::Forget => ("forgetting", "forgot"),
+        }
+    }
+}
+
+/// A change to the system clock, asked about before it's made
+enum TimeChange {
+    Zone(String),
+    Ntp(bool),
+    Clock(NaiveDateTime),
+}
+
+impl TimeChange {
+    fn question(&self) -> String {
+        match self {
+            TimeChange::Zone(zone) => format!("change the time zone to {}?", clock::city_name(zone)),
+            TimeChange::Ntp(true) => "set the time from the internet?".to_string(),
+            TimeChange::Ntp(false) => "stop setting the time from the internet?".to_string(),
+            TimeChange::Clock(time) => format!("set the clock to {}?", time.format("%b %d, %Y %H:%M")),
+        }
+    }
+
+    fn run(&self) -> Result<(), String> {
+        match self {
+            TimeChange::Zone(zone) => clock::set_timezone(zone),
+            TimeChange::Ntp(on) => clock::set_ntp(*on),
+            TimeChange::Clock(time) => clock::set_time(&time.format("%Y-%m-%d %H:%M:00").to_string()),
+        }
+    }
+}
+
+/// How long a new rotation or resolution stays without "keep"
+const DISPLAY_REVERT: Duration = Duration::from_secs(15);
+
+/// Frame rate limits offered, in fps (0 = none)
+const FPS_CAPS: [u16; 5] = [0, 30, 15, 10, 4];
+
+/// A rotation or resolution change
+#[derive(Clone)]
+enum DisplayChange {
+    Rotation(Rotation),
+    Mode { output: String, mode: String },
+}
+
+impl DisplayChange {
+    fn apply(&self) -> Result<(), String> {
+        match self {
+            DisplayChange::Rotation(rotation) => display::set_rotation(*rotation),
+            DisplayChange::Mode { output, mode } => display::set_mode(output, mode),
+        }
+    }
+
+    fn save(&self, settings: &mut SystemSettings) {
+        match self {
+            DisplayChange::Rotation(rotation) => {
+                display::save_rotation(*rotation);
+                settings.display_rotation = *rotation;
+            }
+            DisplayChange::Mode { output, mode } => {
+                display::save_mode(output, mode);
+                settings.display_modes.insert(output.clone(), mode.clone());
+            }
+        }
+    }
+}
+
+/// A display change on trial: undone unless kept in time, in case the
+/// screen can't show it
+struct DisplayTrial {
+    change: DisplayChange,
+    undo: DisplayChange,
+    started: Instant,
+}
+
+/// Run slow work off the UI thread, waking `ctx` when it's done
+fn run_in_background<T: Send + 'static>(ctx: &Context, task: impl FnOnce() -> T + Send + 'static) -> mpsc::Receiver<T> {
+    let (tx, rx) = mpsc::channel();
+    let ctx = ctx.clone();
+    std::thread::spawn(move || {
+        let _ = tx.send(task());
+        ctx.request_repaint();
+    });
+    rx
+}
+
 pub struct SettingsApp {
     settings: SystemSettings,
     current_pane: SettingsPane,
@@ -112,6 +330,98 @@ pub struct SettingsApp {
     icon_textures: HashMap<String, TextureHandle>,
     /// Available icon files from fun_icons folder
     available_icons: Vec<String>,
+    /// Master mute and per-app volumes (saved immediately, not via "save changes")
+    mixer: Mixer,
+    /// Apps registered with the mixer
+    players: Vec<PlayerInfo>,
+    players_checked: Option<Instant>,
+    /// Sync setup (saved immediately, not via "save changes")
+    sync: SyncConfig,
+    /// Remote being typed, applied with "apply"
+    sync_remote: String,
+    sync_status: SyncStatus,
+    sync_checked: Option<Instant>,
+    /// Where updates come from (saved immediately)
+    update_config: UpdateConfig,
+    /// Manifest address being typed, applied with "apply"
+    update_url: String,
+    /// Publisher's key being typed, applied with "apply"
+    update_key: String,
+    /// What the last check found
+    update_available: Option<Available>,
+    /// A check or download running in the background
+    update_task: Option<mpsc::Receiver<UpdateDone>>,
+    /// What's going on, or what went wrong
+    update_message: String,
+    /// Release whose changes are being shown
+    update_changes: Option<Release>,
+    /// Wi-Fi as wpa_supplicant sees it, read while the network pane is open
+    wifi_status: Result<wifi::Status, String>,
+    wifi_networks: Vec<Network>,
+    wifi_known: Vec<KnownNetwork>,
+    wifi_checked: Option<Instant>,
+    /// A scan or join running in the background
+    wifi_task: Option<mpsc::Receiver<WifiDone>>,
+    /// What's going on, or what went wrong
+    wifi_message: String,
+    /// Password dialog: the network, and the password being typed
+    wifi_join: Option<(String, String)>,
+    /// Bluetooth as BlueZ sees it, read while the bluetooth pane is open
+    bt_powered: Result<bool, String>,
+    bt_paired: Vec<Device>,
+    bt_nearby: Vec<Device>,
+    bt_checked: Option<Instant>,
+    /// A search, pairing or connection running in the background
+    bt_task: Option<mpsc::Receiver<BluetoothDone>>,
+    /// What's going on, or what went wrong
+    bt_message: String,
+    /// User accounts (saved immediately, not via "save changes")
+    accounts: Accounts,
+    /// New account being typed: name, password, password again
+    account_new: (String, String, String),
+    /// Password change being typed: current, new, new again
+    account_password: (String, String, String),
+    /// Account whose removal waits to be confirmed, and the password (or
+    /// restrictions passcode) typed to allow it
+    account_removing: Option<(String, String)>,
+    /// What happened, or what went wrong
+    account_message: String,
+    /// Hidden and time-limited apps (saved immediately)
+    restrictions: Restrictions,
+    /// The passcode has been given since settings opened
+    restrictions_unlocked: bool,
+    /// Passcode typed to unlock the pane
+    restriction_passcode: String,
+    /// Passcode change being typed: current, new, new again
+    restriction_new: (String, String, String),
+    /// What happened, or what went wrong
+    restriction_message: String,
+    /// Words autocorrect leaves alone (saved immediately)
+    learned_words: Vec<String>,
+    /// Word being typed to add to them
+    word_new: String,
+    /// Time zone and network time as the system has them, read while the
+    /// date & time pane is open
+    time_zone: Option<String>,
+    time_ntp: bool,
+    time_checked: Option<Instant>,
+    /// Every time zone, read once
+    time_zones: Vec<String>,
+    /// Area shown in the city list ("Europe")
+    time_area: String,
+    /// Date and time typed for setting the clock by hand
+    time_date: (i32, u32, u32),
+    time_hm: (u32, u32),
+    /// A change waiting for "yes"
+    time_pending: Option<TimeChange>,
+    /// Why the last change didn't work
+    time_message: String,
+    /// Connected screens, read while the display pane is open
+    display_outputs: Result<Vec<Output>, String>,
+    display_checked: Option<Instant>,
+    display_trial: Option<DisplayTrial>,
+    /// Why the last rotation or resolution change didn't work
+    display_message: String,
     repaint: RepaintController,
 }
 
@@ -131,6 +441,8 @@ impl SettingsApp {
             }
         }
         available_icons.sort();
+        let sync = SyncConfig::load();
+        let update_config = UpdateConfig::load();
 
         Self {
             settings: SystemSettings::load(),
@@ -138,11 +450,67 @@ impl SettingsApp {
             modified: false,
             icon_textures: HashMap::new(),
             available_icons,
+            mixer: Mixer::load(),
+            players: Vec::new(),
+            players_checked: None,
+            sync_remote: sync.remote.clone(),
+            sync,
+            sync_status: SyncStatus::load(),
+            sync_checked: None,
+            update_url: update_config.url.clone(),
+            update_key: update_config.key.clone(),
+            update_config,
+            update_available: None,
+            update_task: None,
+            update_message: String::new(),
+            update_changes: None,
+            wifi_status: Ok(wifi::Status::default()),
+            wifi_networks: Vec::new(),
+            wifi_known: Vec::new(),
+            wifi_checked: None,
+            wifi_task: None,
+            wifi_message: String::new(),
+            wifi_join: None,
+            bt_powered: Ok(false),
+            bt_paired: Vec::new(),
+            bt_nearby: Vec::new(),
+            bt_checked: None,
+            bt_task: None,
+            bt_message: String::new(),
+            accounts: Accounts::load(),
+            account_new: Default::default(),
+            account_password: Default::default(),
+            account_removing: None,
+            account_message: String::new(),
+            restrictions: Restrictions::load(),
+            restrictions_unlocked: false,
+            restriction_passcode: String::new(),
+            restriction_new: Default::default(),
+            restriction_message: String::new(),
+            learned_words: autocorrect::learned_words(),
+            word_new: String::new(),
+            time_zone: None,
+            time_ntp: true,
+            time_checked: None,
+            time_zones: Vec::new(),
+            time_area: String::new(),
+            time_date: (2024, 1, 1),
+            time_hm: (0, 0),
+            time_pending: None,
+            time_message: String::new(),
+            display_outputs: Ok(Vec::new()),
+            display_checked: None,
+            display_trial: None,
+            display_message: String::new(),
             repaint: RepaintController::new(),
         }
     }
 
     fn save_settings(&mut self) {
+        // Rotation and resolution are saved as they're kept; another app
+        // (slowReader) may have changed them since
+        self.settings.display_rotation = display::saved_rotation();
+        self.settings.display_modes = display::saved_modes();
         self.settings.save();
         self.modified = false;
     }
@@ -177,10 +545,17 @@ impl SettingsApp {
 
             let panes = [
                 (SettingsPane::Profile, "profile"),
+                (SettingsPane::Accounts, "accounts"),
+                (SettingsPane::Restrictions, "restrictions"),
                 (SettingsPane::DateTime, "date & time"),
                 (SettingsPane::Mouse, "mouse"),
+                (SettingsPane::Typing, "typing"),
                 (SettingsPane::Display, "display"),
                 (SettingsPane::Sound, "sound"),
+                (SettingsPane::Network, "network"),
+                (SettingsPane::Bluetooth, "bluetooth"),
+                (SettingsPane::Sync, "sync"),
+                (SettingsPane::Updates, "updates"),
                 (SettingsPane::About, "about"),
             ];
 
@@ -243,6 +618,104 @@ impl SettingsApp {
 
         ui.add_space(15.0);
 
+        if !matches!(self.time_checked, Some(t) if t.elapsed() < Duration::from_secs(5)) {
+            if self.time_checked.is_none() {
+                let now = Local::now().naive_local();
+                self.time_date = (now.year(), now.month(), now.day());
+                self.time_hm = (now.hour(), now.minute());
+            }
+            if self.time_zones.is_empty() {
+                self.time_zones = clock::timezones();
+            }
+            self.time_zone = clock::timezone();
+            self.time_ntp = clock::ntp_enabled();
+            self.time_checked = Some(Instant::now());
+            if self.time_area.is_empty() {
+                let zone = self.time_zone.as_deref().unwrap_or("Etc/UTC");
+                self.time_area = zone.split('/').next().unwrap_or_default().to_string();
+            }
+        }
+        let asking = self.time_pending.is_some();
+
+        // Time zone
+        ui.group(|ui| {
+            ui.strong("time zone");
+            ui.add_space(5.0);
+            let current = self.time_zone.clone().unwrap_or_else(|| "Etc/UTC".to_string());
+            ui.label(format!("{} ({})", clock::city_name(&current), current));
+            if self.time_zones.is_empty() {
+                ui.label("no time zones installed.");
+                return;
+            }
+            let mut areas: Vec<&str> = self.time_zones.iter().filter_map(|z| z.split('/').next()).collect();
+            areas.dedup();
+            let mut chosen = None;
+            ui.add_enabled_ui(!asking, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("time_area").selected_text(&self.time_area).show_ui(ui, |ui| {
+                        for area in &areas {
+                            ui.selectable_value(&mut self.time_area, area.to_string(), *area);
+                        }
+                    });
+                    let prefix = format!("{}/", self.time_area);
+                    egui::ComboBox::from_id_source("time_city")
+                        .selected_text(if current.starts_with(&prefix) { clock::city_name(&current) } else { "choose...".to_string() })
+                        .show_ui(ui, |ui| {
+                            for zone in self.time_zones.iter().filter(|z| z.starts_with(&prefix)) {
+                                if ui.selectable_label(*zone == current, clock::city_name(zone)).clicked() && *zone != current {
+                                    chosen = Some(zone.clone());
+                                }
+                            }
+                        });
+                });
+            });
+            if let Some(zone) = chosen {
+                self.time_pending = Some(TimeChange::Zone(zone));
+            }
+        });
+
+        ui.add_space(10.0);
+
+        // Network time, or setting the clock by hand
+        ui.group(|ui| {
+            ui.strong("setting the time");
+            ui.add_space(5.0);
+            let mut ntp = self.time_ntp;
+            if ui.add_enabled(!asking, egui::Checkbox::new(&mut ntp, "set the time from the internet")).changed() {
+                self.time_pending = Some(TimeChange::Ntp(ntp));
+            }
+            if self.time_ntp {
+                return;
+            }
+            ui.add_space(5.0);
+            ui.add_enabled_ui(!asking, |ui| {
+                ui.horizontal(|ui| {
+                    let (year, month, day) = &mut self.time_date;
+                    ui.add(egui::DragValue::new(year).clamp_range(2024..=2099));
+                    ui.label("-");
+                    ui.add(egui::DragValue::new(month).clamp_range(1..=12));
+                    ui.label("-");
+                    ui.add(egui::DragValue::new(day).clamp_range(1..=31));
+                    ui.add_space(10.0);
+                    let (hour, minute) = &mut self.time_hm;
+                    ui.add(egui::DragValue::new(hour).clamp_range(0..=23));
+                    ui.label(":");
+                    ui.add(egui::DragValue::new(minute).clamp_range(0..=59));
+                });
+                let (year, month, day) = self.time_date;
+                let time = NaiveDate::from_ymd_opt(year, month, day).and_then(|d| d.and_hms_opt(self.time_hm.0, self.time_hm.1, 0));
+                if ui.add_enabled(time.is_some(), egui::Button::new("set clock")).clicked() {
+                    self.time_pending = time.map(TimeChange::Clock);
+                }
+            });
+        });
+        if !self.time_message.is_empty() {
+            ui.add_space(5.0);
+            ui.add(egui::Label::new(&self.time_message).wrap(true));
+        }
+
+        ui.add_space(15.0);
+
         // Time format settings
         ui.group(|ui| {
             ui.strong("time format");
@@ -273,7 +746,327 @@ impl SettingsApp {
         });
 
         ui.add_space(15.0);
-        ui.label("note: date and time are read from the system clock.");
+        ui.label("note: the time zone and clock are shared by every app.");
+    }
+
+    fn save_accounts(&mut self, done: String) {
+        self.account_message = match self.accounts.save() {
+            Ok(()) => done,
+            Err(e) => format!("couldn't save accounts: {}", e),
+        };
+    }
+
+    /// Whether `secret` is this session's password or the restrictions
+    /// passcode, one of which removing an account needs
+    fn may_remove_account(&self, secret: &str) -> bool {
+        self.accounts.current().is_some_and(|a| a.check_password(secret))
+            || (self.restrictions.has_passcode() && self.restrictions.check_passcode(secret))
+    }
+
+    fn render_accounts(&mut self, ui: &mut egui::Ui) {
+        ui.heading("accounts");
+        ui.add_space(10.0);
+
+        let current = self.accounts.current().map(|a| a.name.clone());
+        ui.group(|ui| {
+            ui.strong("people");
+            ui.add_space(5.0);
+            if !self.accounts.enabled() {
+                ui.label("no accounts yet: slowOS starts without asking who's there.");
+                ui.label("the first account keeps the documents and settings in use now.");
+            }
+            let mut remove = None;
+            for account in &self.accounts.users {
+                ui.horizontal(|ui| {
+                    if current.as_deref() == Some(account.name.as_str()) {
+                        ui.label(format!("{} (you)", account.name));
+                    } else {
+                        ui.label(&account.name);
+                        if ui.small_button("remove").clicked() {
+                            remove = Some(account.name.clone());
+                        }
+                    }
+                });
+            }
+            if let Some(name) = remove {
+                self.account_removing = Some((name, String::new()));
+                self.account_message.clear();
+            }
+
+            // Removing someone takes a confirmation and a password
+            let mut confirmed = None;
+            if let Some((name, secret)) = &mut self.account_removing {
+                ui.add_space(5.0);
+                ui.label(format!("remove {}? their files are kept.", name));
+                ui.horizontal(|ui| {
+                    ui.label("your password or the restrictions passcode:");
+                    ui.add(egui::TextEdit::singleline(secret).password(true));
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("remove").clicked() {
+                        confirmed = Some(true);
+                    }
+                    if ui.button("cancel").clicked() {
+                        confirmed = Some(false);
+                    }
+                });
+            }
+            match (confirmed, self.account_removing.take()) {
+                (Some(true), Some((name, secret))) if self.may_remove_account(&secret) => {
+                    self.accounts.remove(&name);
+                    self.save_accounts(format!("removed {} (their files are kept)", name));
+                }
+                (Some(true), Some((name, _))) => {
+                    self.account_message = "wrong password or passcode".to_string();
+                    self.account_removing = Some((name, String::new()));
+                }
+                (None, removing) => self.account_removing = removing,
+                (Some(false), _) | (Some(true), None) => {}
+            }
+        });
+
+        ui.add_space(15.0);
+
+        ui.group(|ui| {
+            ui.strong("add an account");
+            ui.add_space(5.0);
+            let (name, password, again) = &mut self.account_new;
+            egui::Grid::new("account_new").num_columns(2).show(ui, |ui| {
+                ui.label("name:");
+                ui.text_edit_singleline(name);
+                ui.end_row();
+                ui.label("password:");
+                ui.add(egui::TextEdit::singleline(password).password(true));
+                ui.end_row();
+                ui.label("again:");
+                ui.add(egui::TextEdit::singleline(again).password(true));
+                ui.end_row();
+            });
+            let ready = !name.trim().is_empty() && password == again;
+            if ui.add_enabled(ready, egui::Button::new("add")).clicked() {
+                let (name, password, _) = std::mem::take(&mut self.account_new);
+                let name = name.trim().to_string();
+                match self.accounts.add(&name, &password) {
+                    Ok(()) => {
+                        // The first account is this session's own
+                        let home = std::env::var_os("HOME").map(PathBuf::from);
+                        if let Some(account) = self.accounts.find(&name).filter(|a| Some(&a.home) == home.as_ref()) {
+                            std::env::set_var(accounts::USER_VAR, &account.name);
+                        }
+                        self.save_accounts(format!("added {}", name));
+                    }
+                    Err(e) => self.account_message = e,
+                }
+            }
+        });
+
+        if let Some(name) = current {
+            ui.add_space(15.0);
+            ui.group(|ui| {
+                ui.strong("your password");
+                ui.add_space(5.0);
+                let (old, new, again) = &mut self.account_password;
+                egui::Grid::new("account_password").num_columns(2).show(ui, |ui| {
+                    ui.label("current:");
+                    ui.add(egui::TextEdit::singleline(old).password(true));
+                    ui.end_row();
+                    ui.label("new:");
+                    ui.add(egui::TextEdit::singleline(new).password(true));
+                    ui.end_row();
+                    ui.label("again:");
+                    ui.add(egui::TextEdit::singleline(again).password(true));
+                    ui.end_row();
+                });
+                if ui.add_enabled(new == again, egui::Button::new("change password")).clicked() {
+                    let (old, new, _) = std::mem::take(&mut self.account_password);
+                    match self.accounts.set_password(&name, &old, &new) {
+                        Ok(()) => self.save_accounts("password changed".to_string()),
+                        Err(e) => self.account_message = e,
+                    }
+                }
+            });
+        }
+
+        if !self.account_message.is_empty() {
+            ui.add_space(5.0);
+            ui.add(egui::Label::new(&self.account_message).wrap(true));
+        }
+
+        if self.accounts.enabled() {
+            ui.add_space(15.0);
+            ui.group(|ui| {
+                ui.strong("lock the screen");
+                ui.add_space(5.0);
+                for minutes in AUTO_LOCK_CHOICES {
+                    let label = if minutes == 0 { "never by itself".to_string() } else { format!("after {} minutes idle", minutes) };
+                    if ui.radio(self.accounts.auto_lock_minutes == minutes, label).clicked() && self.accounts.auto_lock_minutes != minutes {
+                        self.accounts.auto_lock_minutes = minutes;
+                        self.save_accounts(String::new());
+                    }
+                }
+                ui.label("⌘L locks it right away.");
+            });
+        }
+
+        ui.add_space(15.0);
+        ui.label("note: each account has its own documents and settings. passwords can be left empty.");
+    }
+
+    fn save_restrictions(&mut self, done: String) {
+        self.restriction_message = match self.restrictions.save() {
+            Ok(()) => done,
+            Err(e) => format!("couldn't save restrictions: {}", e),
+        };
+    }
+
+    fn render_restrictions(&mut self, ui: &mut egui::Ui) {
+        ui.heading("restrictions");
+        ui.add_space(10.0);
+
+        if self.restrictions.has_passcode() && !self.restrictions_unlocked {
+            ui.group(|ui| {
+                ui.strong("locked");
+                ui.add_space(5.0);
+                ui.label("enter the passcode to change restrictions.");
+                let field = ui.add(egui::TextEdit::singleline(&mut self.restriction_passcode).password(true));
+                let entered = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("unlock").clicked() || entered {
+                    let passcode = std::mem::take(&mut self.restriction_passcode);
+                    if self.restrictions.check_passcode(&passcode) {
+                        self.restrictions_unlocked = true;
+                        self.restriction_message.clear();
+                    } else {
+                        self.restriction_message = "wrong passcode".to_string();
+                    }
+                }
+            });
+            if !self.restriction_message.is_empty() {
+                ui.add_space(5.0);
+                ui.label(&self.restriction_message);
+            }
+            return;
+        }
+
+        let mut changed = false;
+        ui.group(|ui| {
+            ui.strong("apps");
+            ui.add_space(5.0);
+            egui::Grid::new("restrictions").num_columns(4).spacing([12.0, 4.0]).show(ui, |ui| {
+                ui.label("");
+                ui.label("hide");
+                ui.label("only from");
+                ui.label("until");
+                ui.end_row();
+                for app in restrictions::apps() {
+                    let rule = self.restrictions.rule_mut(&app.binary);
+                    ui.label(&app.name);
+                    changed |= ui.checkbox(&mut rule.hidden, "").changed();
+                    let mut limited = rule.hours.is_some();
+                    if ui.checkbox(&mut limited, "").changed() {
+                        rule.hours = limited.then_some((8 * 60, 21 * 60));
+                        changed = true;
+                    }
+                    if let Some((from, until)) = &mut rule.hours {
+                        let mut hours = [*from / 60, *until / 60];
+                        ui.horizontal(|ui| {
+                            for hour in &mut hours {
+                                changed |= ui
+                                    .add(egui::DragValue::new(hour).clamp_range(0..=23).custom_formatter(|h, _| format!("{:02}:00", h as u16)))
+                                    .changed();
+                            }
+                        });
+                        (*from, *until) = (hours[0] * 60, hours[1] * 60);
+                    } else {
+                        ui.label("any time");
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if changed {
+            self.save_restrictions(String::new());
+        }
+
+        ui.add_space(15.0);
+
+        ui.group(|ui| {
+            ui.strong("passcode");
+            ui.add_space(5.0);
+            let has_passcode = self.restrictions.has_passcode();
+            let (old, new, again) = &mut self.restriction_new;
+            egui::Grid::new("restriction_passcode").num_columns(2).show(ui, |ui| {
+                if has_passcode {
+                    ui.label("current:");
+                    ui.add(egui::TextEdit::singleline(old).password(true));
+                    ui.end_row();
+                }
+                ui.label("new:");
+                ui.add(egui::TextEdit::singleline(new).password(true));
+                ui.end_row();
+                ui.label("again:");
+                ui.add(egui::TextEdit::singleline(again).password(true));
+                ui.end_row();
+            });
+            if ui.add_enabled(new == again, egui::Button::new("set passcode")).clicked() {
+                let (old, new, _) = std::mem::take(&mut self.restriction_new);
+                match self.restrictions.set_passcode(&old, &new) {
+                    Ok(()) if new.is_empty() => self.save_restrictions("passcode removed".to_string()),
+                    Ok(()) => self.save_restrictions("passcode set".to_string()),
+                    Err(e) => self.restriction_message = e,
+                }
+            }
+        });
+
+        if !self.restriction_message.is_empty() {
+            ui.add_space(5.0);
+            ui.add(egui::Label::new(&self.restriction_message).wrap(true));
+        }
+
+        ui.add_space(15.0);
+        ui.label(format!(
+            "note: restrictions apply to every account. at a restricted app the passcode lifts them all for {} minutes.",
+            restrictions::OVERRIDE_TIME.as_secs() / 60
+        ));
+    }
+
+    /// "are you sure?" before changing the system clock
+    fn render_time_confirm(&mut self, ctx: &Context) {
+        let Some(change) = &self.time_pending else { return };
+        let mut answer = None;
+        let resp = egui::Window::new("date & time")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.label(change.question());
+                ui.label("this changes the time for every app.");
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        answer = Some(false);
+                    }
+                    if ui.button("change").clicked() {
+                        answer = Some(true);
+                    }
+                });
+            });
+        if let Some(r) = &resp {
+            slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            answer = Some(false);
+        }
+        let Some(yes) = answer else { return };
+        if let Some(change) = self.time_pending.take() {
+            if yes {
+                match change.run() {
+                    Ok(()) => self.time_message.clear(),
+                    Err(e) => self.time_message = e,
+                }
+                self.time_checked = None;
+            }
+        }
     }
 
     fn render_mouse(&mut self, ui: &mut egui::Ui) {
@@ -320,73 +1113,1171 @@ impl SettingsApp {
         });
 
         ui.add_space(15.0);
-        ui.label("note: these settings affect system behavior.");
-    }
-
-    fn render_display(&mut self, ui: &mut egui::Ui) {
-        ui.heading("display");
-        ui.add_space(10.0);
 
-        // Cursor blink rate
         ui.group(|ui| {
-            ui.strong("cursor blink rate");
+            ui.strong("keyboard navigation");
             ui.add_space(5.0);
+            if ui.checkbox(&mut self.settings.keyboard_navigation, "use tab to move between controls").changed() {
+                self.modified = true;
+                self.apply_theme(ui.ctx());
+            }
+            ui.label("the focused control gets a thick outline;");
+            ui.label("enter or space presses it.");
+        });
 
-            ui.horizontal(|ui| {
-                ui.label("off");
-                let mut blink = self.settings.cursor_blink_ms as i32;
-                if ui.add(egui::Slider::new(&mut blink, 0..=1000).show_value(false)).changed() {
-                    self.settings.cursor_blink_ms = blink as u32;
-                    self.modified = true;
-                }
-                ui.label("slow");
-            });
+        ui.add_space(15.0);
 
-            let desc = if self.settings.cursor_blink_ms == 0 {
-                "cursor does not blink".to_string()
-            } else {
-                format!("blink every {}ms", self.settings.cursor_blink_ms)
-            };
-            ui.label(desc);
+        ui.group(|ui| {
+            ui.strong("on-screen keyboard");
+            ui.add_space(5.0);
+            if ui.checkbox(&mut self.settings.onscreen_keyboard, "show it when typing").changed() {
+                self.modified = true;
+            }
+            ui.label("for touch screens: the keyboard opens when");
+            ui.label("a text field is tapped, and closes after.");
         });
 
         ui.add_space(15.0);
+        ui.label("note: these settings affect system behavior.");
     }
 
-    fn render_sound(&mut self, ui: &mut egui::Ui) {
-        ui.heading("sound");
+    fn render_typing(&mut self, ui: &mut egui::Ui) {
+        ui.heading("typing");
         ui.add_space(10.0);
 
-        // Sound enabled
+        let autocorrect = &mut self.settings.autocorrect;
         ui.group(|ui| {
-            if ui.checkbox(&mut self.settings.sound_enabled, "enable system sounds").changed() {
+            ui.strong("autocorrect");
+            ui.add_space(5.0);
+            if ui.checkbox(&mut autocorrect.correct, "fix common typos").changed() {
+                self.modified = true;
+            }
+            if ui.checkbox(&mut autocorrect.capitalize, "capitalize sentences and \"i\"").changed() {
                 self.modified = true;
             }
+            ui.label("backspace straight after a fix puts the word");
+            ui.label("back, and learns it.");
         });
 
         ui.add_space(15.0);
 
-        // Volume
         ui.group(|ui| {
-            ui.strong("volume");
+            ui.strong("apps");
             ui.add_space(5.0);
-
-            ui.add_enabled_ui(self.settings.sound_enabled, |ui| {
-                let val = self.settings.volume as f32 / 100.0;
-                if let Some(new_val) = Self::draw_slider(ui, val, &format!("{}%", self.settings.volume)) {
-                    self.settings.volume = (new_val * 100.0) as u8;
+            for (app, name) in autocorrect::APPS {
+                let mut on = !autocorrect.off_in.iter().any(|a| a == app);
+                if ui.checkbox(&mut on, name).changed() {
+                    autocorrect.off_in.retain(|a| a != app);
+                    if !on {
+                        autocorrect.off_in.push(app.to_string());
+                    }
                     self.modified = true;
                 }
-            });
+            }
         });
 
         ui.add_space(15.0);
-        ui.label("note: volume affects all slowOS applications.");
-    }
-
-    fn render_about(&self, ui: &mut egui::Ui) {
-        ui.heading("about slowOS");
-        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.strong("learned words");
+            ui.add_space(5.0);
+            ui.label("never corrected, and typed in small letters");
+            ui.label("they get their own capitals back.");
+            ui.add_space(5.0);
+            ui.horizontal(|ui| {
+                let field = ui.text_edit_singleline(&mut self.word_new);
+                let entered = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let word = self.word_new.trim().to_string();
+                let new = !word.is_empty() && !word.contains(char::is_whitespace) && !self.learned_words.contains(&word);
+                if (ui.add_enabled(new, egui::Button::new("add")).clicked() || entered) && new {
+                    self.learned_words.push(word);
+                    autocorrect::save_learned_words(&self.learned_words);
+                    self.word_new.clear();
+                }
+            });
+            ui.add_space(5.0);
+            if self.learned_words.is_empty() {
+                ui.label("none yet");
+            }
+            let mut remove = None;
+            for (i, word) in self.learned_words.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.button("remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.label(word);
+                });
+            }
+            if let Some(i) = remove {
+                self.learned_words.remove(i);
+                autocorrect::save_learned_words(&self.learned_words);
+            }
+        });
+    }
+
+    /// Preview text size and keyboard navigation in this window
+    fn apply_theme(&self, ctx: &egui::Context) {
+        slowcore::SlowTheme {
+            scale: self.settings.ui_scale_percent as f32 / 100.0,
+            keyboard_navigation: self.settings.keyboard_navigation,
+            reduce_motion: self.settings.reduce_motion,
+            grey_levels: self.settings.grey_levels,
+            ..Default::default()
+        }
+        .apply(ctx);
+    }
+
+    fn render_display(&mut self, ui: &mut egui::Ui) {
+        ui.heading("display");
+        ui.add_space(10.0);
+
+        // Text size
+        ui.group(|ui| {
+            ui.strong("text size");
+            ui.add_space(5.0);
+
+            ui.horizontal(|ui| {
+                ui.label("small");
+                let mut pct = self.settings.ui_scale_percent as i32;
+                let r = ui.add(egui::Slider::new(&mut pct, 90..=200).step_by(10.0).show_value(false));
+                if r.changed() {
+                    self.settings.ui_scale_percent = pct as u16;
+                    self.modified = true;
+                }
+                // Re-apply once the slider is let go, not on every step
+                if r.drag_stopped() || (r.changed() && !r.dragged()) {
+                    self.apply_theme(ui.ctx());
+                }
+                ui.label("large");
+            });
+            ui.label(format!("{}% — applies to apps opened after saving", self.settings.ui_scale_percent));
+        });
+
+        ui.add_space(15.0);
+
+        // Cursor blink rate
+        ui.group(|ui| {
+            ui.strong("cursor blink rate");
+            ui.add_space(5.0);
+
+            ui.horizontal(|ui| {
+                ui.label("off");
+                let mut blink = self.settings.cursor_blink_ms as i32;
+                if ui.add(egui::Slider::new(&mut blink, 0..=1000).show_value(false)).changed() {
+                    self.settings.cursor_blink_ms = blink as u32;
+                    self.modified = true;
+                }
+                ui.label("slow");
+            });
+
+            let desc = if self.settings.cursor_blink_ms == 0 {
+                "cursor does not blink".to_string()
+            } else {
+                format!("blink every {}ms", self.settings.cursor_blink_ms)
+            };
+            ui.label(desc);
+        });
+
+        ui.add_space(15.0);
+
+        // Screen rotation
+        if !matches!(self.display_checked, Some(t) if t.elapsed() < Duration::from_secs(5)) {
+            self.display_outputs = display::outputs();
+            self.display_checked = Some(Instant::now());
+        }
+        let trying = self.display_trial.is_some();
+        ui.group(|ui| {
+            ui.strong("screen rotation");
+            ui.add_space(5.0);
+            let current = self.settings.display_rotation;
+            ui.add_enabled_ui(!trying, |ui| {
+                for rotation in Rotation::ALL {
+                    if ui.radio(current == rotation, rotation.name()).clicked() && rotation != current {
+                        self.try_display(DisplayChange::Rotation(rotation), DisplayChange::Rotation(current));
+                    }
+                }
+            });
+            ui.label("portrait suits reading in slowReader.");
+        });
+
+        ui.add_space(15.0);
+
+        // Resolution, for external monitors
+        ui.group(|ui| {
+            ui.strong("resolution");
+            ui.add_space(5.0);
+            let outputs = match &self.display_outputs {
+                Ok(outputs) => outputs.iter().filter(|o| !o.internal).cloned().collect(),
+                Err(e) => {
+                    ui.label(e);
+                    Vec::new()
+                }
+            };
+            if outputs.is_empty() && self.display_outputs.is_ok() {
+                ui.label("no external monitor connected.");
+            }
+            for output in outputs {
+                let Some(current) = output.current.clone() else { continue };
+                let mut chosen = current.clone();
+                ui.add_enabled_ui(!trying, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(&output.name);
+                        egui::ComboBox::from_id_source(&output.name).selected_text(&current).show_ui(ui, |ui| {
+                            for mode in &output.modes {
+                                ui.selectable_value(&mut chosen, mode.clone(), mode);
+                            }
+                        });
+                    });
+                });
+                if chosen != current {
+                    let name = output.name.clone();
+                    self.try_display(
+                        DisplayChange::Mode { output: name.clone(), mode: chosen },
+                        DisplayChange::Mode { output: name, mode: current },
+                    );
+                }
+            }
+            if !self.display_message.is_empty() {
+                ui.add(egui::Label::new(&self.display_message).wrap(true));
+            }
+        });
+
+        ui.add_space(15.0);
+
+        ui.group(|ui| {
+            ui.strong("motion");
+            ui.add_space(5.0);
+            if ui.checkbox(&mut self.settings.reduce_motion, "reduce motion").changed() {
+                self.modified = true;
+                self.apply_theme(ui.ctx());
+            }
+            ui.label("nothing slides or fades, animations are shorter,");
+            ui.label("and moving things draw twice a second at most.");
+        });
+
+        ui.add_space(15.0);
+
+        ui.group(|ui| {
+            ui.strong("greys");
+            ui.add_space(5.0);
+            for levels in GREY_LEVEL_CHOICES {
+                let label = if levels == 2 { "black and white".to_string() } else { format!("{} greys", levels) };
+                if ui.radio(self.settings.grey_levels == levels, label).clicked() && self.settings.grey_levels != levels {
+                    self.settings.grey_levels = levels;
+                    self.modified = true;
+                    self.apply_theme(ui.ctx());
+                }
+            }
+            ui.label("for screens that can show grey: photos in");
+            ui.label("slowView and previews are dithered to these.");
+        });
+
+        ui.add_space(15.0);
+
+        self.render_night_shift(ui);
+
+        ui.add_space(15.0);
+
+        // Frame rate limit
+        ui.group(|ui| {
+            ui.strong("frame rate limit");
+            ui.add_space(5.0);
+            for fps in FPS_CAPS {
+                let label = if fps == 0 { "no limit".to_string() } else { format!("{} fps", fps) };
+                if ui.radio(self.settings.fps_cap == fps, label).clicked() && self.settings.fps_cap != fps {
+                    self.settings.fps_cap = fps;
+                    self.modified = true;
+                }
+            }
+            ui.label("lower saves battery and e-ink refreshes.");
+            ui.label("typing and clicking always answer right away.");
+        });
+
+        ui.add_space(15.0);
+
+        ui.group(|ui| {
+            ui.strong("performance overlay");
+            ui.add_space(5.0);
+            if ui.checkbox(&mut self.settings.perf_overlay, "show frame times in every app").changed() {
+                self.modified = true;
+            }
+            ui.label("a small graph of how long each frame takes.");
+            ui.label("solid bars are frames slower than 30 fps.");
+        });
+
+        ui.add_space(15.0);
+    }
+
+    fn render_night_shift(&mut self, ui: &mut egui::Ui) {
+        let shift = &mut self.settings.night_shift;
+        let mut changed = false;
+        ui.group(|ui| {
+            ui.strong("night shift");
+            ui.add_space(5.0);
+            for schedule in Schedule::ALL {
+                if ui.radio(shift.schedule == schedule, schedule.name()).clicked() && shift.schedule != schedule {
+                    shift.schedule = schedule;
+                    changed = true;
+                }
+            }
+            match shift.schedule {
+                Schedule::Off => {}
+                Schedule::Times => {
+                    let mut hours = [shift.from / 60, shift.until / 60];
+                    ui.horizontal(|ui| {
+                        ui.label("from");
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut hours[0]).clamp_range(0..=23).custom_formatter(|h, _| format!("{:02}:00", h as u16)))
+                            .changed();
+                        ui.label("until");
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut hours[1]).clamp_range(0..=23).custom_formatter(|h, _| format!("{:02}:00", h as u16)))
+                            .changed();
+                    });
+                    (shift.from, shift.until) = (hours[0] * 60, hours[1] * 60);
+                }
+                Schedule::Sunset => {
+                    ui.horizontal(|ui| {
+                        ui.label("latitude");
+                        changed |= ui.add(egui::DragValue::new(&mut shift.latitude).clamp_range(-90.0..=90.0).speed(0.1).max_decimals(2)).changed();
+                        ui.label("longitude");
+                        changed |= ui.add(egui::DragValue::new(&mut shift.longitude).clamp_range(-180.0..=180.0).speed(0.1).max_decimals(2)).changed();
+                    });
+                    ui.label("north and east are positive: london is 51.5, -0.1.");
+                    let now = Local::now();
+                    let offset = now.offset().local_minus_utc() / 60;
+                    let today = match night_shift::daylight(now.date_naive(), shift.latitude, shift.longitude, offset) {
+                        Daylight::Normal { sunrise, sunset } => format!(
+                            "today the sun rises at {} and sets at {}.",
+                            restrictions::format_minute(sunrise),
+                            restrictions::format_minute(sunset)
+                        ),
+                        Daylight::MidnightSun => "the sun doesn't set today.".to_string(),
+                        Daylight::PolarNight => "the sun doesn't rise today.".to_string(),
+                    };
+                    ui.label(today);
+                }
+            }
+            if shift.schedule != Schedule::Off {
+                ui.horizontal(|ui| {
+                    ui.label("warmth");
+                    let label = format!("{}%", shift.warmth);
+                    if let Some(v) = Self::draw_slider(ui, shift.warmth as f32 / 100.0, &label) {
+                        shift.warmth = (v * 100.0).round() as u8;
+                        changed = true;
+                    }
+                });
+                if shift.is_night(Local::now()) {
+                    ui.label("on now.");
+                }
+            }
+            ui.label("warms and dims the screen in every app at once.");
+        });
+        if changed {
+            self.modified = true;
+        }
+    }
+
+    /// Apply a rotation or resolution and ask whether to keep it
+    fn try_display(&mut self, change: DisplayChange, undo: DisplayChange) {
+        match change.apply() {
+            Ok(()) => {
+                self.display_message.clear();
+                self.display_trial = Some(DisplayTrial { change, undo, started: Instant::now() });
+            }
+            Err(e) => self.display_message = e,
+        }
+        self.display_checked = None;
+    }
+
+    /// "keep these settings?" after a display change, going back by itself
+    /// if nobody answers (the screen may be showing nothing)
+    fn render_display_trial(&mut self, ctx: &Context) {
+        let Some(trial) = &self.display_trial else { return };
+        let left = DISPLAY_REVERT.saturating_sub(trial.started.elapsed());
+        let mut keep = false;
+        let mut revert = left.is_zero();
+        let resp = egui::Window::new("keep these settings?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.label(format!("going back in {} seconds.", left.as_secs() + 1));
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui.button("go back").clicked() {
+                        revert = true;
+                    }
+                    if ui.button("keep").clicked() {
+                        keep = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp {
+            slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            revert = true;
+        }
+        if keep {
+            if let Some(trial) = self.display_trial.take() {
+                trial.change.save(&mut self.settings);
+            }
+        } else if revert {
+            if let Some(trial) = self.display_trial.take() {
+                if let Err(e) = trial.undo.apply() {
+                    self.display_message = e;
+                }
+                self.display_checked = None;
+            }
+        } else {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+    }
+
+    fn render_sound(&mut self, ui: &mut egui::Ui) {
+        ui.heading("sound");
+        ui.add_space(10.0);
+
+        // Master mute (applies right away to every app)
+        ui.group(|ui| {
+            if ui.checkbox(&mut self.mixer.muted, "mute all sound").changed() {
+                self.mixer.save();
+            }
+        });
+
+        ui.add_space(15.0);
+
+        // Sound enabled
+        ui.group(|ui| {
+            if ui.checkbox(&mut self.settings.sound_enabled, "enable system sounds").changed() {
+                self.modified = true;
+            }
+        });
+
+        ui.add_space(15.0);
+
+        // Volume
+        ui.group(|ui| {
+            ui.strong("volume");
+            ui.add_space(5.0);
+
+            ui.add_enabled_ui(self.settings.sound_enabled, |ui| {
+                let val = self.settings.volume as f32 / 100.0;
+                if let Some(new_val) = Self::draw_slider(ui, val, &format!("{}%", self.settings.volume)) {
+                    self.settings.volume = (new_val * 100.0) as u8;
+                    self.modified = true;
+                }
+            });
+        });
+
+        ui.add_space(15.0);
+
+        // Sound theme
+        ui.group(|ui| {
+            ui.strong("sound theme");
+            ui.add_space(5.0);
+
+            ui.add_enabled_ui(self.settings.sound_enabled, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for theme in SoundTheme::ALL {
+                        let chosen = self.settings.sound_pack.is_none() && self.settings.sound_theme == theme;
+                        if ui.radio(chosen, theme.name()).clicked() {
+                            self.settings.sound_theme = theme;
+                            self.settings.sound_pack = None;
+                            self.modified = true;
+                            sound::play_themed(Earcon::Alert, theme);
+                        }
+                    }
+                    // Themes installed from slowStore
+                    for pack in sound::installed_packs() {
+                        if ui.radio(self.settings.sound_pack.as_ref() == Some(&pack), &pack).clicked() {
+                            sound::play_pack(Earcon::Alert, &pack);
+                            self.settings.sound_pack = Some(pack);
+                            self.modified = true;
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("preview:");
+                    for (earcon, name) in [
+                        (Earcon::Beep, "beep"),
+                        (Earcon::Click, "click"),
+                        (Earcon::Alert, "alert"),
+                        (Earcon::Trash, "trash"),
+                    ] {
+                        if ui.button(name).clicked() {
+                            let played = self.settings.sound_pack.as_ref().is_some_and(|p| sound::play_pack(earcon, p));
+                            if !played {
+                                sound::play_themed(earcon, self.settings.sound_theme);
+                            }
+                        }
+                    }
+                });
+            });
+        });
+
+        ui.add_space(15.0);
+        self.render_mixer(ui);
+
+        ui.add_space(15.0);
+        ui.label("note: volume affects all slowOS applications.");
+    }
+
+    /// Per-app volume for apps that registered with slowcore::sound
+    fn render_mixer(&mut self, ui: &mut egui::Ui) {
+        if !matches!(self.players_checked, Some(t) if t.elapsed() < Duration::from_secs(1)) {
+            self.players = sound::read_players();
+            self.players_checked = Some(Instant::now());
+        }
+        // Keep the list current while this pane is open
+        ui.ctx().request_repaint_after(Duration::from_secs(1));
+
+        ui.group(|ui| {
+            ui.strong("apps");
+            ui.add_space(5.0);
+            if self.players.is_empty() {
+                ui.label("no apps are playing sound.");
+                return;
+            }
+            ui.add_enabled_ui(!self.mixer.muted, |ui| {
+                let players = self.players.clone();
+                for player in &players {
+                    ui.horizontal(|ui| {
+                        let name = if player.playing {
+                            format!("{} (playing)", player.app)
+                        } else {
+                            player.app.clone()
+                        };
+                        ui.add_sized([140.0, 20.0], egui::Label::new(name));
+                        let pct = self.mixer.app_volume(&player.app);
+                        if let Some(v) = Self::draw_slider(ui, pct as f32 / 100.0, &format!("{}%", pct)) {
+                            self.mixer.volumes.insert(player.app.clone(), (v * 100.0).round() as u8);
+                            self.mixer.save();
+                        }
+                    });
+                }
+            });
+        });
+    }
+
+    /// Run a Wi-Fi scan or join off the UI thread; both take seconds
+    fn start_wifi_task(&mut self, ctx: &Context, message: String, task: impl FnOnce() -> WifiDone + Send + 'static) {
+        if self.wifi_task.is_some() {
+            return;
+        }
+        self.wifi_message = message;
+        self.wifi_task = Some(run_in_background(ctx, task));
+    }
+
+    fn scan_wifi(&mut self, ctx: &Context) {
+        self.start_wifi_task(ctx, "looking for networks...".to_string(), || WifiDone::Scanned(wifi::scan()));
+    }
+
+    fn join_wifi(&mut self, ctx: &Context, ssid: String, password: Option<String>) {
+        if let Some(Err(e)) = password.as_deref().map(wifi::psk) {
+            self.wifi_message = e;
+            return;
+        }
+        let message = format!("joining {}...", ssid);
+        self.start_wifi_task(ctx, message, move || WifiDone::Joined(wifi::join(&ssid, password.as_deref())));
+    }
+
+    fn poll_wifi(&mut self) {
+        let Some(rx) = &self.wifi_task else { return };
+        let Ok(done) = rx.try_recv() else { return };
+        self.wifi_task = None;
+        match done {
+            WifiDone::Scanned(Ok(networks)) => {
+                self.wifi_networks = networks;
+                self.wifi_message.clear();
+            }
+            WifiDone::Joined(Ok(())) => {
+                self.wifi_message.clear();
+                // Show the new connection right away
+                self.wifi_checked = None;
+            }
+            WifiDone::Scanned(Err(e)) | WifiDone::Joined(Err(e)) => self.wifi_message = e,
+        }
+    }
+
+    /// Four bars, filled up to the signal strength
+    fn draw_signal_bars(ui: &mut egui::Ui, bars: u8) {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(18.0, 12.0), Sense::hover());
+        let painter = ui.painter();
+        for i in 0..4u8 {
+            let height = 3.0 * (i + 1) as f32;
+            let bar = Rect::from_min_size(
+                egui::pos2(rect.min.x + i as f32 * 4.5, rect.max.y - height),
+                egui::vec2(3.0, height),
+            );
+            if i < bars {
+                painter.rect_filled(bar, 0.0, SlowColors::BLACK);
+            } else {
+                painter.rect_stroke(bar, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+            }
+        }
+    }
+
+    fn render_network(&mut self, ui: &mut egui::Ui) {
+        ui.heading("network");
+        ui.add_space(10.0);
+
+        // Keep the status current while this pane is open
+        self.poll_wifi();
+        if !matches!(self.wifi_checked, Some(t) if t.elapsed() < Duration::from_secs(2)) {
+            let first = self.wifi_checked.is_none() && self.wifi_networks.is_empty();
+            self.wifi_status = wifi::status();
+            self.wifi_known = wifi::known().unwrap_or_default();
+            self.wifi_checked = Some(Instant::now());
+            if first && self.wifi_status.is_ok() {
+                self.scan_wifi(ui.ctx());
+            }
+        }
+        ui.ctx().request_repaint_after(Duration::from_secs(2));
+
+        let current = match &self.wifi_status {
+            Ok(status) if status.connected() => status.ssid.clone(),
+            _ => None,
+        };
+
+        ui.group(|ui| {
+            ui.strong("wi-fi");
+            ui.add_space(5.0);
+            match &self.wifi_status {
+                Err(e) => {
+                    ui.label(e.as_str());
+                }
+                Ok(status) if status.connected() => {
+                    ui.label(format!("connected to {}", current.as_deref().unwrap_or_default()));
+                    if let Some(ip) = &status.ip_address {
+                        ui.label(format!("address: {}", ip));
+                    }
+                }
+                Ok(_) => {
+                    ui.label("not connected");
+                }
+            }
+            if !self.wifi_message.is_empty() {
+                ui.add(egui::Label::new(&self.wifi_message).wrap(true));
+            }
+        });
+        if self.wifi_status.is_err() {
+            return;
+        }
+
+        ui.add_space(15.0);
+
+        let busy = self.wifi_task.is_some();
+        let mut join: Option<Network> = None;
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.strong("networks");
+                if ui.add_enabled(!busy, egui::Button::new("scan")).clicked() {
+                    self.scan_wifi(ui.ctx());
+                }
+            });
+            ui.add_space(5.0);
+            if self.wifi_networks.is_empty() {
+                ui.label(if busy { "looking..." } else { "no networks found" });
+            }
+            for network in &self.wifi_networks {
+                ui.horizontal(|ui| {
+                    Self::draw_signal_bars(ui, network.bars());
+                    let here = current.as_deref() == Some(network.ssid.as_str());
+                    let mut text = network.ssid.clone();
+                    if network.secured {
+                        text.push_str("  (secured)");
+                    }
+                    let row = ui.add_enabled(!busy, egui::SelectableLabel::new(here, text));
+                    if row.clicked() && !here {
+                        join = Some(network.clone());
+                    }
+                });
+            }
+        });
+        if let Some(network) = join {
+            let known = self.wifi_known.iter().any(|k| k.ssid == network.ssid);
+            if known || !network.secured {
+                self.join_wifi(ui.ctx(), network.ssid, None);
+            } else {
+                self.wifi_join = Some((network.ssid, String::new()));
+            }
+        }
+
+        ui.add_space(15.0);
+
+        let mut forget: Option<KnownNetwork> = None;
+        ui.group(|ui| {
+            ui.strong("known networks");
+            ui.add_space(5.0);
+            if self.wifi_known.is_empty() {
+                ui.label("none yet");
+            }
+            for known in &self.wifi_known {
+                ui.horizontal(|ui| {
+                    let name = if known.current {
+                        format!("{} (connected)", known.ssid)
+                    } else {
+                        known.ssid.clone()
+                    };
+                    ui.add_sized([180.0, 20.0], egui::Label::new(name));
+                    if ui.add_enabled(!busy, egui::Button::new("forget")).clicked() {
+                        forget = Some(known.clone());
+                    }
+                });
+            }
+        });
+        if let Some(known) = forget {
+            match wifi::forget(&known) {
+                Ok(()) => self.wifi_message = format!("forgot {}", known.ssid),
+                Err(e) => self.wifi_message = e,
+            }
+            self.wifi_checked = None;
+        }
+
+        ui.add_space(15.0);
+        ui.label("note: known networks are joined automatically when in range.");
+    }
+
+    /// Password dialog for joining a secured network
+    fn render_wifi_join(&mut self, ctx: &Context) {
+        let Some((ssid, password)) = &mut self.wifi_join else { return };
+        let mut close = false;
+        let mut submit = false;
+        let resp = egui::Window::new("join network")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.label(format!("password for {}:", ssid));
+                let field = ui.add(egui::TextEdit::singleline(password).password(true));
+                field.request_focus();
+                // WPA passwords are 8 to 63 characters
+                let valid = (8..=63).contains(&password.chars().count());
+                if field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) && valid {
+                    submit = true;
+                }
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        close = true;
+                    }
+                    if ui.add_enabled(valid, egui::Button::new("join")).clicked() {
+                        submit = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp {
+            slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            close = true;
+        }
+        if submit {
+            if let Some((ssid, password)) = self.wifi_join.take() {
+                self.join_wifi(ctx, ssid, Some(password));
+            }
+        } else if close {
+            self.wifi_join = None;
+        }
+    }
+
+    fn start_bt_task(&mut self, ctx: &Context, message: String, task: impl FnOnce() -> BluetoothDone + Send + 'static) {
+        if self.bt_task.is_some() {
+            return;
+        }
+        self.bt_message = message;
+        self.bt_task = Some(run_in_background(ctx, task));
+    }
+
+    /// Run a pairing or connection step, saying what it did when it worked
+    fn bt_action(&mut self, ctx: &Context, action: BluetoothAction, device: &Device) {
+        let (doing, done) = action.words();
+        let address = device.address.clone();
+        let done = format!("{} {}", done, device.name);
+        self.start_bt_task(ctx, format!("{} {}...", doing, device.name), move || {
+            BluetoothDone::Finished(action.run(&address).map(|_| done))
+        });
+    }
+
+    fn poll_bluetooth(&mut self) {
+        let Some(rx) = &self.bt_task else { return };
+        let Ok(done) = rx.try_recv() else { return };
+        self.bt_task = None;
+        match done {
+            BluetoothDone::Scanned(Ok(devices)) => {
+                self.bt_message = if devices.is_empty() { "nothing found nearby".to_string() } else { String::new() };
+                self.bt_nearby = devices;
+            }
+            BluetoothDone::Finished(Ok(message)) => {
+                self.bt_message = message;
+                // Show the change right away
+                self.bt_checked = None;
+            }
+            BluetoothDone::Scanned(Err(e)) | BluetoothDone::Finished(Err(e)) => self.bt_message = e,
+        }
+    }
+
+    fn render_bluetooth(&mut self, ui: &mut egui::Ui) {
+        ui.heading("bluetooth");
+        ui.add_space(10.0);
+
+        // Keep the device list current while this pane is open
+        self.poll_bluetooth();
+        let busy = self.bt_task.is_some();
+        if !busy && !matches!(self.bt_checked, Some(t) if t.elapsed() < Duration::from_secs(3)) {
+            self.bt_powered = bluetooth::powered();
+            self.bt_paired = bluetooth::paired().unwrap_or_default();
+            self.bt_nearby.retain(|d| !self.bt_paired.iter().any(|p| p.address == d.address));
+            self.bt_checked = Some(Instant::now());
+        }
+        ui.ctx().request_repaint_after(Duration::from_secs(3));
+
+        let mut power: Option<bool> = None;
+        ui.group(|ui| {
+            ui.strong("bluetooth");
+            ui.add_space(5.0);
+            match self.bt_powered {
+                Err(ref e) => {
+                    ui.label(e.as_str());
+                }
+                Ok(on) => {
+                    let mut on_now = on;
+                    if ui.add_enabled(!busy, egui::Checkbox::new(&mut on_now, "on")).changed() {
+                        power = Some(on_now);
+                    }
+                }
+            }
+            if !self.bt_message.is_empty() {
+                ui.add(egui::Label::new(&self.bt_message).wrap(true));
+            }
+        });
+        if let Some(on) = power {
+            match bluetooth::set_powered(on) {
+                Ok(()) => self.bt_powered = Ok(on),
+                Err(e) => self.bt_message = e,
+            }
+            self.bt_checked = None;
+        }
+        if self.bt_powered != Ok(true) {
+            return;
+        }
+
+        ui.add_space(15.0);
+
+        let mut action: Option<(BluetoothAction, Device)> = None;
+        ui.group(|ui| {
+            ui.strong("paired devices");
+            ui.add_space(5.0);
+            if self.bt_paired.is_empty() {
+                ui.label("none yet");
+            }
+            for device in &self.bt_paired {
+                ui.horizontal(|ui| {
+                    let mut name = format!("{} ({})", device.name, device.kind.name());
+                    if let Some(pct) = device.battery {
+                        name.push_str(&format!("  {}%", pct));
+                    }
+                    ui.add_sized([180.0, 20.0], egui::Label::new(name));
+                    ui.add_enabled_ui(!busy, |ui| {
+                        if device.connected {
+                            if ui.button("disconnect").clicked() {
+                                action = Some((BluetoothAction::Disconnect, device.clone()));
+                            }
+                        } else if ui.button("connect").clicked() {
+                            action = Some((BluetoothAction::Connect, device.clone()));
+                        }
+                        if ui.button("forget").clicked() {
+                            action = Some((BluetoothAction::Forget, device.clone()));
+                        }
+                    });
+                });
+            }
+        });
+
+        ui.add_space(15.0);
+
+        let mut search = false;
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.strong("nearby");
+                if ui.add_enabled(!busy, egui::Button::new("search")).clicked() {
+                    search = true;
+                }
+            });
+            ui.add_space(5.0);
+            for device in &self.bt_nearby {
+                ui.horizontal(|ui| {
+                    ui.add_sized([180.0, 20.0], egui::Label::new(format!("{} ({})", device.name, device.kind.name())));
+                    if ui.add_enabled(!busy, egui::Button::new("pair")).clicked() {
+                        action = Some((BluetoothAction::Pair, device.clone()));
+                    }
+                });
+            }
+        });
+        if search {
+            self.start_bt_task(ui.ctx(), "searching...".to_string(), || BluetoothDone::Scanned(bluetooth::scan()));
+        }
+        if let Some((action, device)) = action {
+            self.bt_action(ui.ctx(), action, &device);
+        }
+
+        ui.add_space(15.0);
+        ui.label("note: put headphones or a keyboard in pairing mode before searching. paired devices reconnect by themselves when turned on.");
+    }
+
+    fn render_sync(&mut self, ui: &mut egui::Ui) {
+        ui.heading("sync");
+        ui.add_space(10.0);
+
+        // Where the other copy lives
+        ui.group(|ui| {
+            ui.strong("other computer");
+            ui.add_space(5.0);
+            ui.label("user@host:folder, reached with ssh");
+            let field = ui.text_edit_singleline(&mut self.sync_remote);
+            let entered = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            ui.horizontal(|ui| {
+                let changed = self.sync_remote.trim() != self.sync.remote;
+                if ui.add_enabled(changed, egui::Button::new("apply")).clicked() || (entered && changed) {
+                    self.sync.remote = self.sync_remote.trim().to_string();
+                    self.sync.save();
+                }
+                if !self.sync.remote.is_empty() && ui.button("turn off").clicked() {
+                    self.sync.remote.clear();
+                    self.sync_remote.clear();
+                    self.sync.save();
+                }
+            });
+        });
+
+        ui.add_space(15.0);
+
+        ui.group(|ui| {
+            ui.strong("folders");
+            ui.add_space(5.0);
+            for folder in Folder::ALL {
+                let label = match folder {
+                    Folder::Documents => "documents",
+                    Folder::Notes => "notes (slowNotes)",
+                };
+                let mut enabled = self.sync.is_enabled(folder);
+                if ui.checkbox(&mut enabled, label).changed() {
+                    self.sync.set_enabled(folder, enabled);
+                    self.sync.save();
+                }
+            }
+        });
+
+        ui.add_space(15.0);
+
+        ui.group(|ui| {
+            ui.strong("how often");
+            ui.add_space(5.0);
+            ui.horizontal(|ui| {
+                for (mins, label) in [(5, "5 minutes"), (15, "15 minutes"), (60, "hourly")] {
+                    if ui.radio(self.sync.interval_mins == mins, label).clicked() {
+                        self.sync.interval_mins = mins;
+                        self.sync.save();
+                    }
+                }
+            });
+        });
+
+        ui.add_space(15.0);
+
+        // Keep the status current while this pane is open
+        if !matches!(self.sync_checked, Some(t) if t.elapsed() < Duration::from_secs(1)) {
+            self.sync_status = SyncStatus::load();
+            self.sync_checked = Some(Instant::now());
+        }
+        ui.ctx().request_repaint_after(Duration::from_secs(1));
+
+        ui.group(|ui| {
+            ui.strong("status");
+            ui.add_space(5.0);
+            let last = self
+                .sync_status
+                .last_sync
+                .and_then(|secs| chrono::DateTime::from_timestamp(secs as i64, 0))
+                .map(|t| t.with_timezone(&Local).format("%b %d, %H:%M").to_string());
+            match self.sync_status.state {
+                SyncState::Off => ui.label("sync is off"),
+                SyncState::Syncing => ui.label("syncing..."),
+                SyncState::Idle => ui.label(format!("last synced {}", last.unwrap_or_default())),
+                SyncState::Error => ui.label("the last sync failed"),
+            };
+            if !self.sync_status.message.is_empty() {
+                ui.add(egui::Label::new(&self.sync_status.message).wrap(true));
+            }
+            ui.add_space(5.0);
+            if ui.add_enabled(self.sync.is_active(), egui::Button::new("sync now")).clicked() {
+                slowcore::sync::sync_now();
+            }
+        });
+
+        ui.add_space(15.0);
+        ui.label("note: ssh needs a key for the other computer; sync never asks for a password. changed on both sides, the older copy is kept as a conflict copy.");
+    }
+
+    fn check_updates(&mut self, ctx: &Context) {
+        if self.update_task.is_some() {
+            return;
+        }
+        let config = self.update_config.clone();
+        self.update_message = "checking...".to_string();
+        self.update_task = Some(run_in_background(ctx, move || UpdateDone::Checked(update::check(&config))));
+    }
+
+    /// Download `releases` one after another, off the UI thread
+    fn download_updates(&mut self, ctx: &Context, releases: Vec<Release>) {
+        let Some(available) = &self.update_available else { return };
+        if self.update_task.is_some() || releases.is_empty() {
+            return;
+        }
+        let source = available.source.clone();
+        self.update_message = "downloading...".to_string();
+        self.update_task = Some(run_in_background(ctx, move || {
+            let mut done = Vec::new();
+            for release in &releases {
+                if let Err(e) = update::stage(&source, release) {
+                    return UpdateDone::Staged(Err(format!("{}: {}", release.display_name(), e)));
+                }
+                done.push(format!("{} {}", release.display_name(), release.version));
+            }
+            UpdateDone::Staged(Ok(done.join(", ")))
+        }));
+    }
+
+    fn poll_updates(&mut self) {
+        let Some(rx) = &self.update_task else { return };
+        let Ok(done) = rx.try_recv() else { return };
+        self.update_task = None;
+        match done {
+            UpdateDone::Checked(Ok(available)) => {
+                self.update_message = if available.releases.is_empty() {
+                    "everything is up to date".to_string()
+                } else {
+                    String::new()
+                };
+                self.update_available = Some(available);
+            }
+            UpdateDone::Staged(Ok(done)) => self.update_message = format!("downloaded {}", done),
+            UpdateDone::Checked(Err(e)) | UpdateDone::Staged(Err(e)) => self.update_message = e,
+        }
+    }
+
+    fn render_updates(&mut self, ui: &mut egui::Ui) {
+        self.poll_updates();
+        let busy = self.update_task.is_some();
+        ui.heading("updates");
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.strong("look for updates on");
+            ui.add_space(5.0);
+            if ui.radio(!self.update_config.usb, "the web").clicked() {
+                self.update_config.usb = false;
+                self.update_config.save();
+                self.update_available = None;
+            }
+            ui.add_enabled_ui(!self.update_config.usb, |ui| {
+                ui.label("address of the update list (manifest.json)");
+                let field = ui.text_edit_singleline(&mut self.update_url);
+                let entered = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let changed = self.update_url.trim() != self.update_config.url;
+                if ui.add_enabled(changed, egui::Button::new("apply")).clicked() || (entered && changed) {
+                    self.update_config.url = self.update_url.trim().to_string();
+                    self.update_config.save();
+                    self.update_available = None;
+                }
+            });
+            ui.add_space(5.0);
+            if ui.radio(self.update_config.usb, "a USB stick").clicked() {
+                self.update_config.usb = true;
+                self.update_config.save();
+                self.update_available = None;
+            }
+            ui.label(format!("the update list and apps go in a folder called {} on the stick", update::USB_FOLDER));
+            ui.add_space(5.0);
+            ui.label("publisher's key (the update list must be signed with it; needed for USB sticks)");
+            let field = ui.text_edit_singleline(&mut self.update_key);
+            let entered = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let changed = self.update_key.trim() != self.update_config.key;
+            if ui.add_enabled(changed, egui::Button::new("apply")).clicked() || (entered && changed) {
+                self.update_config.key = self.update_key.trim().to_string();
+                self.update_config.save();
+                self.update_available = None;
+            }
+        });
+
+        ui.add_space(15.0);
+
+        let mut download = Vec::new();
+        ui.group(|ui| {
+            ui.strong("available");
+            ui.add_space(5.0);
+            if ui.add_enabled(!busy, egui::Button::new("check now")).clicked() {
+                self.check_updates(ui.ctx());
+            }
+            ui.add_space(5.0);
+            let staged = update::staged();
+            if let Some(available) = &self.update_available {
+                for release in &available.releases {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "{} {} → {}",
+                            release.display_name(),
+                            update::installed_version(&release.binary),
+                            release.version
+                        ));
+                        if !release.changes.is_empty() && ui.button("changes...").clicked() {
+                            self.update_changes = Some(release.clone());
+                        }
+                        if staged.get(&release.binary) == Some(&release.version) {
+                            ui.label("installs when next opened");
+                            if ui.add_enabled(!busy, egui::Button::new("don't install")).clicked() {
+                                update::unstage(&release.binary);
+                            }
+                        } else if ui.add_enabled(!busy, egui::Button::new("download")).clicked() {
+                            download.push(release.clone());
+                        }
+                    });
+                }
+                let waiting: Vec<Release> =
+                    available.releases.iter().filter(|r| staged.get(&r.binary) != Some(&r.version)).cloned().collect();
+                if waiting.len() > 1 {
+                    ui.add_space(5.0);
+                    if ui.add_enabled(!busy, egui::Button::new("download all")).clicked() {
+                        download = waiting;
+                    }
+                }
+            }
+            if !self.update_message.is_empty() {
+                ui.add(egui::Label::new(&self.update_message).wrap(true));
+            }
+        });
+        self.download_updates(ui.ctx(), download);
+
+        ui.add_space(15.0);
+        ui.label("note: a downloaded update takes the old version's place the next time its app is opened from the desktop.");
+    }
+
+    fn render_update_changes(&mut self, ctx: &Context) {
+        let Some(release) = &self.update_changes else { return };
+        let title = format!("changes in {} {}", release.display_name(), release.version);
+        let body = release.changes.iter().map(|c| format!("• {}", c)).collect::<Vec<_>>().join("\n");
+        if alert(ctx, &title, &body, &["ok"]).is_some() {
+            self.update_changes = None;
+        }
+    }
+
+    fn render_about(&mut self, ui: &mut egui::Ui) {
+        ui.heading("about slowOS");
+        ui.add_space(10.0);
 
         ui.group(|ui| {
             ui.heading("slowOS");
@@ -418,6 +2309,23 @@ impl SettingsApp {
 
         ui.add_space(15.0);
 
+        ui.group(|ui| {
+            ui.strong("logs");
+            ui.add_space(5.0);
+            ui.label("what apps write to their logs:");
+            for &(level, label) in slowcore::log::LEVELS {
+                let name = level.as_str().to_lowercase();
+                if ui.radio(self.settings.log_level == name, label).clicked() {
+                    self.settings.log_level = name;
+                    self.modified = true;
+                }
+            }
+            ui.add_space(5.0);
+            ui.label("read them, and crash reports, in slowLogs.");
+        });
+
+        ui.add_space(15.0);
+
         ui.group(|ui| {
             ui.strong("the slow computer company");
             ui.add_space(5.0);
@@ -429,10 +2337,17 @@ impl SettingsApp {
     fn render_content(&mut self, ui: &mut egui::Ui, ctx: &Context) {
         match self.current_pane {
             SettingsPane::Profile => self.render_profile(ui, ctx),
+            SettingsPane::Accounts => self.render_accounts(ui),
+            SettingsPane::Restrictions => self.render_restrictions(ui),
             SettingsPane::DateTime => self.render_datetime(ui),
             SettingsPane::Mouse => self.render_mouse(ui),
+            SettingsPane::Typing => self.render_typing(ui),
             SettingsPane::Display => self.render_display(ui),
             SettingsPane::Sound => self.render_sound(ui),
+            SettingsPane::Network => self.render_network(ui),
+            SettingsPane::Bluetooth => self.render_bluetooth(ui),
+            SettingsPane::Sync => self.render_sync(ui),
+            SettingsPane::Updates => self.render_updates(ui),
             SettingsPane::About => self.render_about(ui),
         }
     }
@@ -621,6 +2536,10 @@ impl eframe::App for SettingsApp {
                     self.render_content(ui, &ctx_clone);
                 });
             });
+        self.render_wifi_join(ctx);
+        self.render_display_trial(ctx);
+        self.render_time_confirm(ctx);
+        self.render_update_changes(ctx);
         self.repaint.end_frame(ctx);
     }
 }
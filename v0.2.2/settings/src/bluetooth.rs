@@ -0,0 +1,181 @@
+//! Bluetooth through BlueZ
+//!
+//! A thin wrapper over `bluetoothctl`, like wifi.rs over wpa_cli. BlueZ
+//! remembers paired devices itself; trusted ones reconnect on their own.
+
+use std::process::Command;
+
+/// How long a search for nearby devices runs
+const SCAN_SECS: &str = "8";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Audio,
+    Keyboard,
+    Mouse,
+    Other,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Audio => "audio",
+            Kind::Keyboard => "keyboard",
+            Kind::Mouse => "mouse",
+            Kind::Other => "device",
+        }
+    }
+
+    /// From BlueZ's icon name ("audio-headset", "input-keyboard"...)
+    fn from_icon(icon: &str) -> Self {
+        match icon {
+            i if i.starts_with("audio") => Kind::Audio,
+            "input-keyboard" => Kind::Keyboard,
+            "input-mouse" | "input-tablet" => Kind::Mouse,
+            _ => Kind::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    /// "00:11:22:33:44:55"
+    pub address: String,
+    pub name: String,
+    pub kind: Kind,
+    pub paired: bool,
+    pub connected: bool,
+    /// Percent, for devices that report it
+    pub battery: Option<u8>,
+}
+
+fn bluetoothctl(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("bluetoothctl")
+        .args(args)
+        .output()
+        .map_err(|_| "bluetooth isn't available (bluetoothctl not found)".to_string())?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if stdout.contains("No default controller available") {
+        return Err("no bluetooth hardware found".to_string());
+    }
+    if !output.status.success() || stdout.lines().any(|l| l.starts_with("Failed to")) {
+        let reason = stdout.lines().find(|l| l.starts_with("Failed to")).unwrap_or("bluetooth didn't answer");
+        return Err(reason.to_lowercase());
+    }
+    Ok(stdout)
+}
+
+/// Whether the radio is on
+pub fn powered() -> Result<bool, String> {
+    let out = bluetoothctl(&["show"])?;
+    Ok(out.lines().any(|l| l.trim() == "Powered: yes"))
+}
+
+pub fn set_powered(on: bool) -> Result<(), String> {
+    bluetoothctl(&["power", if on { "on" } else { "off" }]).map(|_| ())
+}
+
+/// Addresses from "Device 00:11:22:33:44:55 Name" lines
+fn parse_addresses(out: &str) -> Vec<String> {
+    out.lines()
+        .filter_map(|l| l.strip_prefix("Device "))
+        .filter_map(|l| l.split_whitespace().next())
+        .map(String::from)
+        .collect()
+}
+
+fn info(address: &str) -> Result<Device, String> {
+    bluetoothctl(&["info", address]).map(|out| parse_info(address, &out))
+}
+
+fn parse_info(address: &str, out: &str) -> Device {
+    let mut device = Device {
+        address: address.to_string(),
+        name: address.to_string(),
+        kind: Kind::Other,
+        paired: false,
+        connected: false,
+        battery: None,
+    };
+    for line in out.lines() {
+        let Some((key, value)) = line.trim().split_once(": ") else { continue };
+        match key {
+            "Alias" => device.name = value.to_string(),
+            "Icon" => device.kind = Kind::from_icon(value),
+            "Paired" => device.paired = value == "yes",
+            "Connected" => device.connected = value == "yes",
+            // "0x46 (70)"
+            "Battery Percentage" => {
+                device.battery = value
+                    .split_once('(')
+                    .and_then(|(_, pct)| pct.trim_end_matches(')').parse().ok());
+            }
+            _ => {}
+        }
+    }
+    device
+}
+
+/// Paired devices, by name
+pub fn paired() -> Result<Vec<Device>, String> {
+    let out = bluetoothctl(&["devices", "Paired"])?;
+    let mut devices: Vec<Device> = parse_addresses(&out).iter().filter_map(|a| info(a).ok()).collect();
+    devices.sort_by_key(|d| d.name.to_lowercase());
+    Ok(devices)
+}
+
+/// Look for nearby devices that aren't paired yet. Blocks for a few seconds.
+pub fn scan() -> Result<Vec<Device>, String> {
+    bluetoothctl(&["--timeout", SCAN_SECS, "scan", "on"])?;
+    let out = bluetoothctl(&["devices"])?;
+    let mut devices: Vec<Device> = parse_addresses(&out)
+        .iter()
+        .filter_map(|a| info(a).ok())
+        // Nameless devices are beacons and the like, not things to pair
+        .filter(|d| !d.paired && d.name.replace('-', ":") != d.address)
+        .collect();
+    devices.sort_by_key(|d| d.name.to_lowercase());
+    Ok(devices)
+}
+
+/// Pair, trust (so it reconnects by itself) and connect
+pub fn pair(address: &str) -> Result<(), String> {
+    bluetoothctl(&["pair", address])?;
+    bluetoothctl(&["trust", address])?;
+    connect(address)
+}
+
+pub fn connect(address: &str) -> Result<(), String> {
+    bluetoothctl(&["connect", address]).map(|_| ())
+}
+
+pub fn disconnect(address: &str) -> Result<(), String> {
+    bluetoothctl(&["disconnect", address]).map(|_| ())
+}
+
+/// Unpair
+pub fn forget(address: &str) -> Result<(), String> {
+    bluetoothctl(&["remove", address]).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_info() {
+        let out = "Device 00:11:22:33:44:55 (public)\n\
+                   \tName: WH-1000XM4\n\
+                   \tAlias: headphones\n\
+                   \tIcon: audio-headset\n\
+                   \tPaired: yes\n\
+                   \tConnected: no\n\
+                   \tBattery Percentage: 0x46 (70)\n";
+        let device = parse_info("00:11:22:33:44:55", out);
+        assert_eq!(device.name, "headphones");
+        assert_eq!(device.kind, Kind::Audio);
+        assert!(device.paired && !device.connected);
+        assert_eq!(device.battery, Some(70));
+        assert_eq!(parse_addresses("Device 00:11:22:33:44:55 headphones\n"), ["00:11:22:33:44:55"]);
+    }
+}
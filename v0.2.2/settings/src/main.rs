@@ -1,14 +1,16 @@
 //! settings — System settings for slowOS
 
 mod app;
+mod bluetooth;
+mod wifi;
 
 use app::SettingsApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([440.0, 380.0])
+        viewport: window_layout::viewport("settings", Placement::Center, [440.0, 380.0])
             .with_title("settings"),
         ..Default::default()
     };
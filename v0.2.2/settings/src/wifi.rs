@@ -0,0 +1,278 @@
+//! Wi-Fi through wpa_supplicant
+//!
+//! A thin wrapper over `wpa_cli`. wpa_supplicant keeps the known networks
+//! (and their passwords) in its own config file, written with save_config.
+
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// The Slowbook's only wireless interface
+const INTERFACE: &str = "wlan0";
+
+/// How long a scan or a join gets before giving up
+const SCAN_WAIT: Duration = Duration::from_secs(4);
+const JOIN_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// A network seen in a scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct Network {
+    pub ssid: String,
+    /// dBm, e.g. -60
+    pub signal: i32,
+    pub secured: bool,
+}
+
+impl Network {
+    /// Signal strength as 0-4 bars
+    pub fn bars(&self) -> u8 {
+        match self.signal {
+            s if s >= -55 => 4,
+            s if s >= -65 => 3,
+            s if s >= -75 => 2,
+            s if s >= -85 => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// A network wpa_supplicant remembers
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnownNetwork {
+    /// wpa_supplicant's network id
+    pub id: String,
+    pub ssid: String,
+    pub current: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Status {
+    /// Joined network, once connected
+    pub ssid: Option<String>,
+    pub ip_address: Option<String>,
+    /// wpa_supplicant's state: "COMPLETED", "SCANNING", "DISCONNECTED"...
+    pub state: String,
+}
+
+impl Status {
+    pub fn connected(&self) -> bool {
+        self.state == "COMPLETED"
+    }
+}
+
+fn wpa_cli(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("wpa_cli")
+        .arg("-i")
+        .arg(INTERFACE)
+        .args(args)
+        .output()
+        .map_err(|_| "wi-fi isn't available (wpa_cli not found)".to_string())?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if !output.status.success() || stdout.starts_with("Failed to connect") {
+        return Err("wi-fi isn't available (wpa_supplicant isn't running)".to_string());
+    }
+    if stdout.trim() == "FAIL" {
+        return Err(format!("wpa_supplicant refused '{}'", args.first().copied().unwrap_or_default()));
+    }
+    Ok(stdout)
+}
+
+pub fn status() -> Result<Status, String> {
+    wpa_cli(&["status"]).map(|out| parse_status(&out))
+}
+
+fn parse_status(out: &str) -> Status {
+    let mut status = Status::default();
+    for line in out.lines() {
+        match line.split_once('=') {
+            Some(("wpa_state", v)) => status.state = v.to_string(),
+            Some(("ssid", v)) => status.ssid = Some(decode(v)),
+            Some(("ip_address", v)) => status.ip_address = Some(v.to_string()),
+            _ => {}
+        }
+    }
+    status
+}
+
+/// Scan and wait for the results. Blocks for a few seconds.
+pub fn scan() -> Result<Vec<Network>, String> {
+    wpa_cli(&["scan"])?;
+    std::thread::sleep(SCAN_WAIT);
+    wpa_cli(&["scan_results"]).map(|out| parse_scan_results(&out))
+}
+
+/// "bssid / frequency / signal level / flags / ssid", tab separated, one
+/// line per access point. Each network once, at its strongest.
+fn parse_scan_results(out: &str) -> Vec<Network> {
+    let mut networks: Vec<Network> = Vec::new();
+    for line in out.lines().skip(1) {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [_, _, signal, flags, ssid] = fields[..] else { continue };
+        let ssid = decode(ssid);
+        // Hidden networks don't say their name
+        if ssid.is_empty() || ssid.starts_with('\0') {
+            continue;
+        }
+        let network = Network {
+            ssid,
+            signal: signal.parse().unwrap_or(-100),
+            secured: flags.contains("WPA") || flags.contains("WEP"),
+        };
+        match networks.iter_mut().find(|n| n.ssid == network.ssid) {
+            Some(seen) if seen.signal < network.signal => *seen = network,
+            Some(_) => {}
+            None => networks.push(network),
+        }
+    }
+    networks.sort_by_key(|n| std::cmp::Reverse(n.signal));
+    networks
+}
+
+pub fn known() -> Result<Vec<KnownNetwork>, String> {
+    wpa_cli(&["list_networks"]).map(|out| parse_known(&out))
+}
+
+/// "network id / ssid / bssid / flags"
+fn parse_known(out: &str) -> Vec<KnownNetwork> {
+    out.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let id = fields.next()?.to_string();
+            let ssid = decode(fields.next()?);
+            let flags = fields.nth(1).unwrap_or_default();
+            Some(KnownNetwork { id, ssid, current: flags.contains("[CURRENT]") })
+        })
+        .collect()
+}
+
+/// The psk value wpa_supplicant takes for `password`: a quoted WPA
+/// passphrase of 8–63 printable ASCII characters, or a 64-digit hex key
+/// as it is. Anything else is refused, as a quote or a line break would
+/// end up in wpa_supplicant's config.
+pub fn psk(password: &str) -> Result<String, String> {
+    if password.len() == 64 && password.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Ok(password.to_string());
+    }
+    if !(8..=63).contains(&password.len()) {
+        return Err("a Wi-Fi password has 8 to 63 characters".to_string());
+    }
+    if !password.chars().all(|c| (' '..='~').contains(&c) && c != '"') {
+        return Err("a Wi-Fi password can only have letters, digits, spaces and punctuation other than \"".to_string());
+    }
+    Ok(format!("\"{}\"", password))
+}
+
+/// Join a network, remembering it once connected. A known network can be
+/// joined without a password; a new password replaces the old one.
+pub fn join(ssid: &str, password: Option<&str>) -> Result<(), String> {
+    let psk = password.map(psk).transpose()?;
+    let existing = known()?.into_iter().find(|k| k.ssid == ssid).map(|k| k.id);
+    let id = match &existing {
+        Some(id) => id.clone(),
+        None => {
+            let id = wpa_cli(&["add_network"])?.trim().to_string();
+            // Hex, so any name works without quoting
+            let hex: String = ssid.bytes().map(|b| format!("{:02x}", b)).collect();
+            wpa_cli(&["set_network", &id, "ssid", &hex])?;
+            id
+        }
+    };
+    let setup = match &psk {
+        Some(psk) => wpa_cli(&["set_network", &id, "psk", psk]),
+        None if existing.is_none() => wpa_cli(&["set_network", &id, "key_mgmt", "NONE"]),
+        None => Ok(String::new()),
+    };
+    let result = setup.and_then(|_| {
+        wpa_cli(&["select_network", &id])?;
+        wait_for_connection(ssid)
+    });
+    // Don't keep a network that never worked
+    if result.is_err() && existing.is_none() {
+        let _ = wpa_cli(&["remove_network", &id]);
+    }
+    // select_network disabled every other network; let them back in for later
+    let _ = wpa_cli(&["enable_network", "all"]);
+    result?;
+    wpa_cli(&["save_config"])?;
+    Ok(())
+}
+
+fn wait_for_connection(ssid: &str) -> Result<(), String> {
+    let started = Instant::now();
+    while started.elapsed() < JOIN_TIMEOUT {
+        std::thread::sleep(Duration::from_millis(500));
+        let status = status()?;
+        if status.connected() && status.ssid.as_deref() == Some(ssid) {
+            return Ok(());
+        }
+    }
+    Err(format!("couldn't join {} — check the password", ssid))
+}
+
+pub fn forget(network: &KnownNetwork) -> Result<(), String> {
+    wpa_cli(&["remove_network", &network.id])?;
+    wpa_cli(&["save_config"])?;
+    Ok(())
+}
+
+/// wpa_cli writes unusual bytes in names as "\xNN", and "\\" and "\""
+fn decode(text: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = text.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        match rest {
+            [b'x', hi, lo, tail @ ..] => {
+                let hex = [*hi, *lo];
+                match std::str::from_utf8(&hex).ok().and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(byte) => {
+                        bytes.push(byte);
+                        rest = tail;
+                    }
+                    None => bytes.push(b'\\'),
+                }
+            }
+            [escaped @ (b'\\' | b'"'), tail @ ..] => {
+                bytes.push(*escaped);
+                rest = tail;
+            }
+            _ => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scan_results() {
+        let out = "bssid / frequency / signal level / flags / ssid\n\
+                   aa:bb:cc:00:00:01\t2412\t-70\t[WPA2-PSK-CCMP][ESS]\thome\n\
+                   aa:bb:cc:00:00:02\t5180\t-52\t[WPA2-PSK-CCMP][ESS]\thome\n\
+                   aa:bb:cc:00:00:03\t2437\t-80\t[ESS]\tcaf\\xc3\\xa9 wifi\n\
+                   aa:bb:cc:00:00:04\t2462\t-60\t[WPA2-PSK-CCMP][ESS]\t\n";
+        let networks = parse_scan_results(out);
+        assert_eq!(networks.len(), 2);
+        assert_eq!(networks[0], Network { ssid: "home".into(), signal: -52, secured: true });
+        assert_eq!(networks[0].bars(), 4);
+        assert_eq!(networks[1].ssid, "café wifi");
+        assert!(!networks[1].secured);
+    }
+
+    #[test]
+    fn test_psk() {
+        assert_eq!(psk("correct horse").unwrap(), "\"correct horse\"");
+        assert_eq!(psk(&"ab".repeat(32)).unwrap(), "ab".repeat(32));
+        assert!(psk("short").is_err());
+        assert!(psk(&"x".repeat(64)).is_err());
+        assert!(psk("pass\"word\"").is_err());
+        assert!(psk("password\nssid=1").is_err());
+        assert!(psk("pässwörd1").is_err());
+    }
+}
@@ -0,0 +1,615 @@
+//! slowBackup application
+
+use crate::schedule::{self, BackupSettings, Schedule};
+use crate::snapshot::{self, Entry, Snapshot};
+use egui::{Align2, CentralPanel, Context, Key, Rect, Sense, Stroke, TopBottomPanel, Vec2};
+use slowcore::repaint::RepaintController;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::volumes::{self, Volume};
+use slowcore::widgets::{about_dialog, alert, status_bar, window_control_buttons, AboutInfo, WindowAction};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowBackup",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "keep a copy of your work on a USB drive",
+    features: &["compressed snapshots", "daily or weekly backups", "check a snapshot reads back", "put single files back"],
+    ..AboutInfo::EMPTY
+};
+
+/// Home folders offered in the "folders" menu
+const FOLDERS: &[&str] = &["Documents", "Pictures", "Music", "Books", "MIDI"];
+
+/// How often the list of drives is refreshed
+const DRIVE_CHECK: Duration = Duration::from_secs(2);
+
+/// What the worker thread reports
+enum Event {
+    Progress { done: u64, total: u64 },
+    BackedUp(Result<Snapshot, String>),
+    Verified(Result<usize, String>),
+    Listed(Result<(Snapshot, Vec<Entry>), String>),
+    Restored(Result<Vec<PathBuf>, String>),
+}
+
+/// A long job in the background
+struct Job {
+    label: &'static str,
+    done: u64,
+    total: u64,
+}
+
+/// The "restore files" window
+struct RestoreBrowser {
+    snapshot: Snapshot,
+    entries: Vec<Entry>,
+    checked: BTreeSet<String>,
+    filter: String,
+}
+
+pub struct SlowBackupApp {
+    ctx: Context,
+    settings: BackupSettings,
+    drives: Vec<Volume>,
+    drives_checked: Instant,
+    /// Mount point of the drive in use
+    drive: Option<PathBuf>,
+    snapshots: Vec<Snapshot>,
+    selected: Option<usize>,
+    job: Option<Job>,
+    tx: Sender<Event>,
+    rx: Receiver<Event>,
+    restore: Option<RestoreBrowser>,
+    confirm_delete: bool,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowBackupApp {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let mut app = Self {
+            ctx: cc.egui_ctx.clone(),
+            settings: BackupSettings::load(),
+            drives: Vec::new(),
+            drives_checked: Instant::now(),
+            drive: None,
+            snapshots: Vec::new(),
+            selected: None,
+            job: None,
+            tx,
+            rx,
+            restore: None,
+            confirm_delete: false,
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        };
+        app.refresh_drives();
+        app
+    }
+
+    fn save_settings(&mut self) {
+        // The background mode may have taken a backup meanwhile
+        let saved = BackupSettings::load().last_backup;
+        self.settings.last_backup = self.settings.last_backup.max(saved);
+        self.settings.save();
+    }
+
+    fn refresh_drives(&mut self) {
+        self.drives = volumes::removable();
+        self.drives_checked = Instant::now();
+        let still_there = self.drive.as_ref().is_some_and(|d| self.drives.iter().any(|v| &v.mount_point == d));
+        if !still_there {
+            // The scheduled drive if it's here, else the first one
+            let preferred = self
+                .drives
+                .iter()
+                .find(|v| Some(&v.name) == self.settings.drive.as_ref())
+                .or_else(|| self.drives.first());
+            let drive = preferred.map(|v| v.mount_point.clone());
+            self.use_drive(drive);
+        }
+    }
+
+    fn use_drive(&mut self, drive: Option<PathBuf>) {
+        self.drive = drive;
+        self.refresh_snapshots();
+    }
+
+    fn refresh_snapshots(&mut self) {
+        self.snapshots = self.drive.as_deref().map(snapshot::list).unwrap_or_default();
+        self.selected = None;
+    }
+
+    fn drive_name(&self) -> Option<String> {
+        let drive = self.drive.as_ref()?;
+        self.drives.iter().find(|v| &v.mount_point == drive).map(|v| v.name.clone())
+    }
+
+    fn selected_snapshot(&self) -> Option<&Snapshot> {
+        self.snapshots.get(self.selected?)
+    }
+
+    fn poll(&mut self) {
+        while let Ok(event) = self.rx.try_recv() {
+            match event {
+                Event::Progress { done, total } => {
+                    if let Some(job) = &mut self.job {
+                        job.done = done;
+                        job.total = total;
+                    }
+                }
+                Event::BackedUp(result) => {
+                    self.job = None;
+                    match result {
+                        Ok(snapshot) => {
+                            self.settings.last_backup = Some(schedule::unix_now());
+                            self.save_settings();
+                            self.message = Some(format!(
+                                "backed up {} files, {}",
+                                snapshot.summary.files,
+                                format_size(snapshot.summary.bytes)
+                            ));
+                            self.refresh_snapshots();
+                        }
+                        Err(e) => self.message = Some(format!("backup failed: {}", e)),
+                    }
+                }
+                Event::Verified(result) => {
+                    self.job = None;
+                    self.message = Some(match result {
+                        Ok(files) => format!("snapshot is good: {} files read back", files),
+                        Err(e) => format!("snapshot is damaged: {}", e),
+                    });
+                }
+                Event::Listed(result) => {
+                    self.job = None;
+                    match result {
+                        Ok((snapshot, entries)) => {
+                            self.restore = Some(RestoreBrowser {
+                                snapshot,
+                                entries,
+                                checked: BTreeSet::new(),
+                                filter: String::new(),
+                            });
+                        }
+                        Err(e) => self.message = Some(format!("can't open snapshot: {}", e)),
+                    }
+                }
+                Event::Restored(result) => {
+                    self.job = None;
+                    match result {
+                        Ok(files) => {
+                            let message = match files.len() {
+                                1 => "restored 1 file".to_string(),
+                                n => format!("restored {} files", n),
+                            };
+                            if let Some(first) = files.first() {
+                                slowcore::notify::post_with_reveal("slowbackup", &message, first);
+                            }
+                            self.message = Some(message);
+                        }
+                        Err(e) => self.message = Some(format!("restore failed: {}", e)),
+                    }
+                }
+            }
+        }
+        if self.job.is_none() && self.drives_checked.elapsed() >= DRIVE_CHECK {
+            self.refresh_drives();
+        }
+        // Keep noticing drives coming and going
+        self.ctx.request_repaint_after(DRIVE_CHECK);
+    }
+
+    /// Run `work` on a worker thread as the current job
+    fn start(&mut self, label: &'static str, work: impl FnOnce(Sender<Event>) + Send + 'static) {
+        if self.job.is_some() {
+            return;
+        }
+        self.job = Some(Job { label, done: 0, total: 0 });
+        self.message = None;
+        let tx = self.tx.clone();
+        let ctx = self.ctx.clone();
+        std::thread::spawn(move || {
+            work(tx);
+            ctx.request_repaint();
+        });
+    }
+
+    /// A progress callback that forwards to the window, a few times a second
+    fn progress(tx: &Sender<Event>, ctx: &Context) -> impl FnMut(u64, u64) {
+        let tx = tx.clone();
+        let ctx = ctx.clone();
+        let mut last = Instant::now();
+        move |done, total| {
+            if last.elapsed() >= Duration::from_millis(200) || done == total {
+                let _ = tx.send(Event::Progress { done, total });
+                ctx.request_repaint();
+                last = Instant::now();
+            }
+        }
+    }
+
+    fn back_up(&mut self) {
+        let Some(drive) = self.drive.clone() else {
+            self.message = Some("plug in a drive to back up to".into());
+            return;
+        };
+        if self.settings.folders.is_empty() {
+            self.message = Some("choose folders to back up first".into());
+            return;
+        }
+        let folders = self.settings.folders.clone();
+        let ctx = self.ctx.clone();
+        self.start("backing up", move |tx| {
+            let progress = Self::progress(&tx, &ctx);
+            let result = snapshot::create(&schedule::home_dir(), &folders, &drive, progress);
+            let _ = tx.send(Event::BackedUp(result.map_err(|e| e.to_string())));
+        });
+    }
+
+    fn verify(&mut self) {
+        let Some(snapshot) = self.selected_snapshot().cloned() else { return };
+        let ctx = self.ctx.clone();
+        self.start("checking", move |tx| {
+            let progress = Self::progress(&tx, &ctx);
+            let result = snapshot::verify(&snapshot, progress);
+            let _ = tx.send(Event::Verified(result.map_err(|e| e.to_string())));
+        });
+    }
+
+    fn browse(&mut self) {
+        let Some(snapshot) = self.selected_snapshot().cloned() else { return };
+        self.start("reading", move |tx| {
+            let result = snapshot::entries(&snapshot).map(|entries| (snapshot, entries));
+            let _ = tx.send(Event::Listed(result.map_err(|e| e.to_string())));
+        });
+    }
+
+    fn restore_checked(&mut self) {
+        let Some(browser) = self.restore.take() else { return };
+        if browser.checked.is_empty() {
+            return;
+        }
+        self.start("restoring", move |tx| {
+            let result = snapshot::restore(&browser.snapshot, &browser.checked, &schedule::home_dir());
+            let _ = tx.send(Event::Restored(result.map_err(|e| e.to_string())));
+        });
+    }
+
+    fn delete_selected(&mut self) {
+        let Some(snapshot) = self.selected_snapshot().cloned() else { return };
+        match snapshot::delete(&snapshot) {
+            Ok(()) => self.message = Some(format!("deleted snapshot of {}", snapshot.title())),
+            Err(e) => self.message = Some(format!("can't delete: {}", e)),
+        }
+        self.refresh_snapshots();
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        let back_up = ctx.input(|i| i.modifiers.command && i.key_pressed(Key::B));
+        if back_up {
+            self.back_up();
+        }
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let busy = self.job.is_some();
+        let has_selection = self.selected.is_some();
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("file", |ui| {
+                    if ui.add_enabled(!busy && self.drive.is_some(), egui::Button::new("back up now   ⌘b")).clicked() {
+                        self.back_up();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.add_enabled(!busy && has_selection, egui::Button::new("restore files...")).clicked() {
+                        self.browse();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(!busy && has_selection, egui::Button::new("verify snapshot")).clicked() {
+                        self.verify();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(!busy && has_selection, egui::Button::new("delete snapshot...")).clicked() {
+                        self.confirm_delete = true;
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("folders", |ui| {
+                    for &folder in FOLDERS {
+                        let mut on = self.settings.folders.iter().any(|f| f == folder);
+                        if ui.checkbox(&mut on, folder.to_lowercase()).changed() {
+                            self.settings.folders.retain(|f| f != folder);
+                            if on {
+                                self.settings.folders.push(folder.to_string());
+                            }
+                            self.save_settings();
+                        }
+                    }
+                });
+                ui.menu_button("schedule", |ui| {
+                    for schedule in Schedule::ALL {
+                        if ui.radio(self.settings.schedule == schedule, schedule.name()).clicked() {
+                            self.settings.schedule = schedule;
+                            // Scheduled backups go to the drive in use now
+                            if schedule != Schedule::Off {
+                                if let Some(name) = self.drive_name() {
+                                    self.settings.drive = Some(name);
+                                }
+                            }
+                            self.save_settings();
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowbackup", "slowBackup");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_main(&mut self, ctx: &Context) {
+        let mut pick_drive = None;
+        let mut back_up = false;
+        let mut browse = false;
+        let mut verify = false;
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(12.0)))
+            .show(ctx, |ui| {
+                if self.drives.is_empty() {
+                    ui.label("plug in a USB drive to back up to");
+                    return;
+                }
+                ui.horizontal(|ui| {
+                    ui.label("drive:");
+                    let current = self.drive_name().unwrap_or_default();
+                    egui::ComboBox::from_id_source("drive").selected_text(current).show_ui(ui, |ui| {
+                        for volume in &self.drives {
+                            let selected = self.drive.as_ref() == Some(&volume.mount_point);
+                            if ui.selectable_label(selected, &volume.name).clicked() {
+                                pick_drive = Some(volume.mount_point.clone());
+                            }
+                        }
+                    });
+                    if ui.add_enabled(self.job.is_none(), egui::Button::new("back up now")).clicked() {
+                        back_up = true;
+                    }
+                });
+                let folders: Vec<String> = self.settings.folders.iter().map(|f| f.to_lowercase()).collect();
+                ui.label(format!("backs up: {}", if folders.is_empty() { "nothing".into() } else { folders.join(", ") }));
+
+                if let Some(job) = &self.job {
+                    ui.add_space(8.0);
+                    let fraction = if job.total == 0 { 0.0 } else { job.done as f32 / job.total as f32 };
+                    ui.label(format!("{}... {} of {}", job.label, format_size(job.done), format_size(job.total)));
+                    progress_bar(ui, fraction);
+                }
+
+                ui.add_space(8.0);
+                ui.separator();
+                ui.label(egui::RichText::new("snapshots").strong());
+                if self.snapshots.is_empty() {
+                    ui.label("none on this drive yet");
+                    return;
+                }
+                egui::ScrollArea::vertical().auto_shrink([false, true]).max_height(ui.available_height() - 40.0).show(ui, |ui| {
+                    for (i, snapshot) in self.snapshots.iter().enumerate() {
+                        let text = format!(
+                            "{}   {} files, {}",
+                            snapshot.title(),
+                            snapshot.summary.files,
+                            format_size(snapshot.summary.bytes)
+                        );
+                        let resp = ui.selectable_label(self.selected == Some(i), text);
+                        if resp.clicked() {
+                            self.selected = Some(i);
+                        }
+                        if resp.double_clicked() {
+                            browse = true;
+                        }
+                    }
+                });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    let enabled = self.job.is_none() && self.selected.is_some();
+                    if ui.add_enabled(enabled, egui::Button::new("restore files...")).clicked() {
+                        browse = true;
+                    }
+                    if ui.add_enabled(enabled, egui::Button::new("verify")).clicked() {
+                        verify = true;
+                    }
+                    if ui.add_enabled(enabled, egui::Button::new("delete...")).clicked() {
+                        self.confirm_delete = true;
+                    }
+                });
+            });
+
+        if let Some(drive) = pick_drive {
+            self.use_drive(Some(drive));
+        }
+        if back_up {
+            self.back_up();
+        }
+        if browse {
+            self.browse();
+        }
+        if verify {
+            self.verify();
+        }
+    }
+
+    fn draw_restore(&mut self, ctx: &Context) {
+        let Some(browser) = &mut self.restore else { return };
+        let mut restore = false;
+        let mut cancel = false;
+        let resp = egui::Window::new(format!("restore from {}", browser.snapshot.title()))
+            .collapsible(false)
+            .resizable(false)
+            .default_width(420.0)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("find:");
+                    ui.text_edit_singleline(&mut browser.filter);
+                });
+                let filter = browser.filter.to_lowercase();
+                let shown: Vec<&Entry> = browser
+                    .entries
+                    .iter()
+                    .filter(|e| filter.is_empty() || e.path.to_lowercase().contains(&filter))
+                    .collect();
+                egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+                    for entry in &shown {
+                        let mut on = browser.checked.contains(&entry.path);
+                        let label = format!("{}  ({})", entry.path, format_size(entry.size));
+                        if ui.checkbox(&mut on, label).changed() {
+                            if on {
+                                browser.checked.insert(entry.path.clone());
+                            } else {
+                                browser.checked.remove(&entry.path);
+                            }
+                        }
+                    }
+                });
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui.button("check all shown").clicked() {
+                        browser.checked.extend(shown.iter().map(|e| e.path.clone()));
+                    }
+                    if ui.button("uncheck all").clicked() {
+                        browser.checked.clear();
+                    }
+                });
+                ui.label("files go back where they were; existing files are kept");
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        cancel = true;
+                    }
+                    let count = browser.checked.len();
+                    let label = if count == 1 { "restore 1 file".to_string() } else { format!("restore {} files", count) };
+                    if ui.add_enabled(count > 0, egui::Button::new(label)).clicked() {
+                        restore = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+
+        if restore {
+            self.restore_checked();
+        } else if cancel {
+            self.restore = None;
+        }
+    }
+
+    fn draw_confirm_delete(&mut self, ctx: &Context) {
+        if !self.confirm_delete {
+            return;
+        }
+        let title = self.selected_snapshot().map(|s| s.title()).unwrap_or_default();
+        let body = format!("delete the snapshot of {} from the drive?\nthis can't be undone.", title);
+        if let Some(answer) = alert(ctx, "delete snapshot", &body, &["cancel", "delete"]) {
+            self.confirm_delete = false;
+            if answer == 1 {
+                self.delete_selected();
+            }
+        }
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+
+    fn status_text(&self) -> String {
+        if let Some(message) = &self.message {
+            return message.clone();
+        }
+        let last = self
+            .settings
+            .last_backup
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs as i64, 0))
+            .map(|t| t.with_timezone(&chrono::Local).format("%b %d, %H:%M").to_string())
+            .unwrap_or_else(|| "never".into());
+        let scheduled = match (self.settings.schedule, &self.settings.drive) {
+            (Schedule::Off, _) | (_, None) => String::new(),
+            (schedule, Some(drive)) => format!("  |  {} to {}", schedule.name(), drive),
+        };
+        format!("last backup: {}{}", last, scheduled)
+    }
+}
+
+/// Outlined bar filled in black
+fn progress_bar(ui: &mut egui::Ui, fraction: f32) {
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), 12.0), Sense::hover());
+    let painter = ui.painter();
+    painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+    let inner = rect.shrink(2.0);
+    let filled = Rect::from_min_size(inner.min, Vec2::new(inner.width() * fraction.clamp(0.0, 1.0), inner.height()));
+    painter.rect_filled(filled, 0.0, SlowColors::BLACK);
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 { format!("{} B", bytes) }
+    else if bytes < 1024 * 1024 { format!("{:.1} KB", bytes as f64 / 1024.0) }
+    else if bytes < 1024 * 1024 * 1024 { format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0)) }
+    else { format!("{:.1} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0)) }
+}
+
+impl eframe::App for SlowBackupApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowbackup") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.poll();
+        self.handle_keys(ctx);
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::top("title_bar").show(ctx, |ui| {
+            slowcore::theme::SlowTheme::title_bar_frame().show(ui, |ui| {
+                ui.centered_and_justified(|ui| {
+                    ui.label("slowBackup");
+                });
+            });
+        });
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            status_bar(ui, &self.status_text());
+        });
+
+        self.draw_main(ctx);
+        self.draw_restore(ctx);
+        self.draw_confirm_delete(ctx);
+        self.draw_about(ctx);
+        self.repaint.end_frame(ctx);
+    }
+}
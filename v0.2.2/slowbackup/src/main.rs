@@ -0,0 +1,38 @@
+//! slowBackup - Backup and restore to a USB drive for the Slow Computer
+//!
+//! Snapshots of the home folders go to a plugged-in drive as tar.zst
+//! archives. They can be checked, browsed and picked from to put single
+//! files back. Run with `--scheduled` it takes backups in the background
+//! instead of opening a window.
+
+mod app;
+mod schedule;
+mod snapshot;
+
+use app::SlowBackupApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    if std::env::args().any(|a| a == "--scheduled") {
+        schedule::run_scheduled();
+        return Ok(());
+    }
+
+    let viewport = window_layout::viewport("slowbackup", Placement::RememberLast, [520.0, 440.0])
+        .with_title("slowBackup");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowBackup",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowBackupApp::new(cc))
+        }),
+    )
+}
@@ -0,0 +1,140 @@
+//! What to back up, and when
+//!
+//! Scheduled backups are taken by `slowbackup --scheduled`, which slowDesktop
+//! keeps running in the background. It wakes now and then, and when a
+//! backup is due and the chosen drive is plugged in, it takes one.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often the background mode looks for a due backup
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    #[default]
+    Off,
+    Daily,
+    Weekly,
+}
+
+impl Schedule {
+    pub const ALL: [Schedule; 3] = [Schedule::Off, Schedule::Daily, Schedule::Weekly];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Schedule::Off => "off",
+            Schedule::Daily => "daily",
+            Schedule::Weekly => "weekly",
+        }
+    }
+
+    fn period(self) -> Option<u64> {
+        match self {
+            Schedule::Off => None,
+            Schedule::Daily => Some(24 * 60 * 60),
+            Schedule::Weekly => Some(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BackupSettings {
+    /// Folders in home to back up
+    pub folders: Vec<String>,
+    #[serde(default)]
+    pub schedule: Schedule,
+    /// Name of the drive scheduled backups go to
+    #[serde(default)]
+    pub drive: Option<String>,
+    /// Unix seconds of the last backup, by hand or scheduled
+    #[serde(default)]
+    pub last_backup: Option<u64>,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            folders: ["Documents", "Pictures", "Music", "Books", "MIDI"].map(String::from).to_vec(),
+            schedule: Schedule::Off,
+            drive: None,
+            last_backup: None,
+        }
+    }
+}
+
+impl BackupSettings {
+    fn path() -> PathBuf {
+        slowcore::storage::config_dir("slowbackup").join("settings.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    pub fn is_due(&self, now: u64) -> bool {
+        match (self.schedule.period(), self.last_backup) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(period), Some(last)) => now.saturating_sub(last) >= period,
+        }
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub fn home_dir() -> PathBuf {
+    std::env::var_os("HOME").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("/"))
+}
+
+/// Background mode: take scheduled backups until killed
+pub fn run_scheduled() {
+    // After a failure, wait this long before trying (and telling) again
+    const RETRY_AFTER: Duration = Duration::from_secs(6 * 60 * 60);
+    let mut failed_at: Option<std::time::Instant> = None;
+    loop {
+        if failed_at.is_some_and(|t| t.elapsed() < RETRY_AFTER) {
+            std::thread::sleep(CHECK_INTERVAL);
+            continue;
+        }
+        let mut settings = BackupSettings::load();
+        let drive = settings
+            .drive
+            .as_ref()
+            .and_then(|name| slowcore::volumes::removable().into_iter().find(|v| &v.name == name));
+        if let (true, Some(drive)) = (settings.is_due(unix_now()), drive) {
+            let home = home_dir();
+            match crate::snapshot::create(&home, &settings.folders, &drive.mount_point, |_, _| {}) {
+                Ok(snapshot) => {
+                    failed_at = None;
+                    settings.last_backup = Some(unix_now());
+                    settings.save();
+                    let message = format!("backed up {} files to {}", snapshot.summary.files, drive.name);
+                    slowcore::notify::post("slowbackup", &message);
+                }
+                Err(e) => {
+                    failed_at = Some(std::time::Instant::now());
+                    slowcore::notify::post("slowbackup", &format!("scheduled backup failed: {}", e));
+                }
+            }
+        }
+        std::thread::sleep(CHECK_INTERVAL);
+    }
+}
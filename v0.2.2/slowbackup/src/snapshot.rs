@@ -0,0 +1,280 @@
+//! Snapshots: one tar.zst archive per backup
+//!
+//! Snapshots live in "slowOS backups" on the drive, named by when they were
+//! taken ("2026-10-16 140512.tar.zst"), with a small JSON summary beside
+//! each so the list shows without opening the archives. Paths inside are
+//! relative to the home folder ("Documents/letter.txt").
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read};
+use std::path::{Component, Path, PathBuf};
+
+/// Folder on the drive that holds the snapshots
+pub const BACKUP_FOLDER: &str = "slowOS backups";
+
+const EXTENSION: &str = ".tar.zst";
+
+/// zstd level: quick enough for a slow computer, still a good squeeze
+const LEVEL: i32 = 3;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Summary {
+    pub files: usize,
+    /// Bytes before compression
+    pub bytes: u64,
+    pub folders: Vec<String>,
+    /// Unix seconds
+    pub taken: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub summary: Summary,
+}
+
+impl Snapshot {
+    /// "Oct 16, 2026 14:05"
+    pub fn title(&self) -> String {
+        chrono::DateTime::from_timestamp(self.summary.taken as i64, 0)
+            .map(|t| t.with_timezone(&chrono::Local).format("%b %d, %Y %H:%M").to_string())
+            .unwrap_or_else(|| self.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default())
+    }
+
+    fn summary_path(archive: &Path) -> PathBuf {
+        let name = archive.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        archive.with_file_name(format!("{}.json", name.trim_end_matches(EXTENSION)))
+    }
+}
+
+/// A file inside a snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub path: String,
+    pub size: u64,
+}
+
+pub fn backup_dir(drive: &Path) -> PathBuf {
+    drive.join(BACKUP_FOLDER)
+}
+
+/// Snapshots on the drive, newest first
+pub fn list(drive: &Path) -> Vec<Snapshot> {
+    let Ok(entries) = std::fs::read_dir(backup_dir(drive)) else { return Vec::new() };
+    let mut snapshots: Vec<Snapshot> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.to_string_lossy().ends_with(EXTENSION))
+        .map(|path| {
+            let summary = std::fs::read_to_string(Snapshot::summary_path(&path))
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
+            Snapshot { path, summary }
+        })
+        .collect();
+    snapshots.sort_by(|a, b| b.path.cmp(&a.path));
+    snapshots
+}
+
+/// Every file under the chosen home folders: (full path, path in the archive, size)
+fn gather(home: &Path, folders: &[String]) -> Vec<(PathBuf, String, u64)> {
+    fn walk(dir: &Path, rel: &str, out: &mut Vec<(PathBuf, String, u64)>) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else { continue };
+            let rel = format!("{}/{}", rel, entry.file_name().to_string_lossy());
+            if file_type.is_dir() {
+                walk(&entry.path(), &rel, out);
+            } else if file_type.is_file() {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                out.push((entry.path(), rel, size));
+            }
+        }
+    }
+    let mut files = Vec::new();
+    for folder in folders {
+        walk(&home.join(folder), folder, &mut files);
+    }
+    files
+}
+
+/// Back up `folders` (names in `home`) to the drive.
+/// `progress` hears bytes done and bytes in all.
+pub fn create(home: &Path, folders: &[String], drive: &Path, mut progress: impl FnMut(u64, u64)) -> io::Result<Snapshot> {
+    let files = gather(home, folders);
+    let total: u64 = files.iter().map(|(_, _, size)| size).sum();
+
+    let dir = backup_dir(drive);
+    std::fs::create_dir_all(&dir)?;
+    let stamp = chrono::Local::now().format("%Y-%m-%d %H%M%S").to_string();
+    let path = dir.join(format!("{}{}", stamp, EXTENSION));
+    // Written under another name until it's complete
+    let partial = dir.join(format!("{}.partial", stamp));
+
+    let mut encoder = zstd::Encoder::new(BufWriter::new(File::create(&partial)?), LEVEL)?;
+    encoder.include_checksum(true)?;
+    let mut archive = tar::Builder::new(encoder);
+    let mut done = 0u64;
+    let mut count = 0usize;
+    let result = (|| -> io::Result<()> {
+        for (full, rel, size) in &files {
+            // Files that vanished or can't be read since gather() are skipped
+            let Ok(mut file) = File::open(full) else { continue };
+            archive.append_file(rel, &mut file)?;
+            done += size;
+            count += 1;
+            progress(done, total);
+        }
+        let writer = archive.into_inner()?.finish()?;
+        writer.into_inner().map_err(|e| e.into_error())?.sync_all()
+    })();
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, &path)?;
+
+    let summary = Summary {
+        files: count,
+        bytes: done,
+        folders: folders.to_vec(),
+        taken: chrono::Local::now().timestamp() as u64,
+    };
+    if let Ok(json) = serde_json::to_string_pretty(&summary) {
+        std::fs::write(Snapshot::summary_path(&path), json)?;
+    }
+    Ok(Snapshot { path, summary })
+}
+
+/// Counts compressed bytes read, for progress
+struct Counted<'a, R> {
+    inner: R,
+    read: u64,
+    report: &'a mut dyn FnMut(u64),
+}
+
+impl<R: Read> Read for Counted<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        (self.report)(self.read);
+        Ok(n)
+    }
+}
+
+type Reader<'a> = tar::Archive<zstd::Decoder<'static, BufReader<Counted<'a, File>>>>;
+
+fn open<'a>(path: &Path, report: &'a mut dyn FnMut(u64)) -> io::Result<Reader<'a>> {
+    let counted = Counted { inner: File::open(path)?, read: 0, report };
+    Ok(tar::Archive::new(zstd::Decoder::new(counted)?))
+}
+
+/// Read the whole snapshot back, checking every byte against zstd's
+/// checksum and the file count against the summary. Returns the file count.
+pub fn verify(snapshot: &Snapshot, mut progress: impl FnMut(u64, u64)) -> io::Result<usize> {
+    let total = std::fs::metadata(&snapshot.path)?.len();
+    let mut report = |read: u64| progress(read, total);
+    let mut archive = open(&snapshot.path, &mut report)?;
+    let mut count = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let size = entry.size();
+        if io::copy(&mut entry, &mut io::sink())? != size {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "snapshot is cut short"));
+        }
+        count += 1;
+    }
+    if snapshot.summary.files != 0 && count != snapshot.summary.files {
+        return Err(io::Error::other(format!("{} of {} files found", count, snapshot.summary.files)));
+    }
+    Ok(count)
+}
+
+/// The files in a snapshot
+pub fn entries(snapshot: &Snapshot) -> io::Result<Vec<Entry>> {
+    let mut quiet = |_: u64| {};
+    let mut archive = open(&snapshot.path, &mut quiet)?;
+    let mut files = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if entry.header().entry_type().is_file() {
+            let path = entry.path()?.to_string_lossy().to_string();
+            files.push(Entry { path, size: entry.size() });
+        }
+    }
+    Ok(files)
+}
+
+/// Only plain relative paths, never "../" or "/etc"
+fn is_safe(path: &Path) -> bool {
+    path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Put `wanted` files back where they were under `home`. Files that exist
+/// there now are left alone and the restored copy gets the next free name.
+pub fn restore(snapshot: &Snapshot, wanted: &BTreeSet<String>, home: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut quiet = |_: u64| {};
+    let mut archive = open(&snapshot.path, &mut quiet)?;
+    let mut restored = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let rel = entry.path()?.to_path_buf();
+        if !wanted.contains(rel.to_string_lossy().as_ref()) || !is_safe(&rel) {
+            continue;
+        }
+        let mut target = home.join(&rel);
+        let Some(dir) = target.parent().map(|d| d.to_path_buf()) else { continue };
+        std::fs::create_dir_all(&dir)?;
+        if target.exists() {
+            let name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            target = slowcore::storage::unique_path(&dir, &name);
+        }
+        entry.unpack(&target)?;
+        restored.push(target);
+        if restored.len() == wanted.len() {
+            break;
+        }
+    }
+    Ok(restored)
+}
+
+pub fn delete(snapshot: &Snapshot) -> io::Result<()> {
+    std::fs::remove_file(&snapshot.path)?;
+    let _ = std::fs::remove_file(Snapshot::summary_path(&snapshot.path));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_verify_restore() {
+        let root = std::env::temp_dir().join(format!("slowbackup-test-{}", std::process::id()));
+        let home = root.join("home");
+        let drive = root.join("drive");
+        std::fs::create_dir_all(home.join("Documents/letters")).unwrap();
+        std::fs::write(home.join("Documents/letters/june.txt"), "dear june").unwrap();
+        std::fs::write(home.join("Documents/list.txt"), "eggs").unwrap();
+
+        let snapshot = create(&home, &["Documents".to_string()], &drive, |_, _| {}).unwrap();
+        assert_eq!(snapshot.summary.files, 2);
+        assert_eq!(list(&drive).len(), 1);
+        assert_eq!(verify(&snapshot, |_, _| {}).unwrap(), 2);
+
+        let mut names: Vec<String> = entries(&snapshot).unwrap().into_iter().map(|e| e.path).collect();
+        names.sort();
+        assert_eq!(names, ["Documents/letters/june.txt", "Documents/list.txt"]);
+
+        // An existing file is kept; the restored one gets a new name
+        let wanted: BTreeSet<String> = ["Documents/list.txt".to_string()].into();
+        let restored = restore(&snapshot, &wanted, &home).unwrap();
+        assert_eq!(restored, [home.join("Documents/list 2.txt")]);
+        assert_eq!(std::fs::read_to_string(&restored[0]).unwrap(), "eggs");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
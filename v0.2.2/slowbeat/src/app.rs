@@ -0,0 +1,374 @@
+//! slowBeat application
+
+use crate::metronome::{Accent, Metronome, MAX_BEATS, MAX_BPM, MIN_BPM};
+use crate::tuner::{self, Note, Tuner};
+use egui::{Align2, CentralPanel, Context, FontId, Key, Pos2, Rect, Sense, Stroke, TopBottomPanel, Vec2};
+use slowcore::dither;
+use slowcore::repaint::RepaintController;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, WindowAction};
+use std::time::{Duration, Instant};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowBeat",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "metronome and tuner for slowOS",
+    features: &["tap tempo (T)", "subdivisions and accents", "chromatic tuner"],
+    ..AboutInfo::EMPTY
+};
+
+/// How often the tuner listens
+const TUNER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The note stays up this long after the sound stops
+const NOTE_HOLD: Duration = Duration::from_secs(1);
+
+/// Within this many cents the note counts as in tune
+const IN_TUNE: f32 = 5.0;
+
+/// How far the needle moves toward a new reading each time; less is steadier
+const NEEDLE_EASE: f32 = 0.5;
+
+const TUNINGS: [f32; 3] = [432.0, 440.0, 442.0];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Metronome,
+    Tuner,
+}
+
+pub struct SlowBeatApp {
+    mode: Mode,
+    metronome: Metronome,
+    tuner: Option<Tuner>,
+    /// The note heard, where the needle is, and when it was last heard
+    heard: Option<(Note, f32, Instant)>,
+    last_listen: Instant,
+    a4: f32,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowBeatApp {
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        Self {
+            mode: Mode::Metronome,
+            metronome: Metronome::default(),
+            tuner: None,
+            heard: None,
+            last_listen: Instant::now(),
+            a4: 440.0,
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        }
+    }
+
+    fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+        self.message = None;
+        match mode {
+            Mode::Metronome => self.tuner = None,
+            Mode::Tuner => {
+                self.metronome.stop();
+                match Tuner::start() {
+                    Ok(tuner) => self.tuner = Some(tuner),
+                    Err(e) => self.message = Some(format!("can't listen: {}", e)),
+                }
+            }
+        }
+    }
+
+    fn toggle_metronome(&mut self, ctx: &Context) {
+        if self.metronome.is_running() {
+            self.metronome.stop();
+        } else {
+            self.metronome.start(ctx);
+        }
+    }
+
+    fn change_bpm(&mut self, by: i32) {
+        let mut pattern = self.metronome.pattern();
+        pattern.bpm = (pattern.bpm as i32 + by).clamp(MIN_BPM as i32, MAX_BPM as i32) as u32;
+        self.metronome.set_pattern(pattern);
+    }
+
+    fn listen(&mut self) {
+        let Some(tuner) = &self.tuner else { return };
+        if self.last_listen.elapsed() < TUNER_INTERVAL {
+            return;
+        }
+        self.last_listen = Instant::now();
+        match tuner.pitch().map(|freq| tuner::note_of(freq, self.a4)) {
+            Some(note) => {
+                let needle = match self.heard {
+                    Some((last, needle, _)) if last.name == note.name && last.octave == note.octave => {
+                        needle + (note.cents - needle) * NEEDLE_EASE
+                    }
+                    _ => note.cents,
+                };
+                self.heard = Some((note, needle, Instant::now()));
+            }
+            None => {
+                if self.heard.is_some_and(|(_, _, at)| at.elapsed() > NOTE_HOLD) {
+                    self.heard = None;
+                }
+            }
+        }
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        if ctx.memory(|m| m.focused().is_some()) || self.mode != Mode::Metronome {
+            return;
+        }
+        if ctx.input(|i| i.key_pressed(Key::Space)) {
+            self.toggle_metronome(ctx);
+        }
+        if ctx.input(|i| i.key_pressed(Key::T)) {
+            self.metronome.tap();
+        }
+        if ctx.input(|i| i.key_pressed(Key::ArrowUp)) {
+            self.change_bpm(1);
+        }
+        if ctx.input(|i| i.key_pressed(Key::ArrowDown)) {
+            self.change_bpm(-1);
+        }
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("mode", |ui| {
+                    if ui.radio(self.mode == Mode::Metronome, "metronome").clicked() {
+                        self.set_mode(Mode::Metronome);
+                        ui.close_menu();
+                    }
+                    if ui.radio(self.mode == Mode::Tuner, "tuner").clicked() {
+                        self.set_mode(Mode::Tuner);
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("tuning", |ui| {
+                    for a4 in TUNINGS {
+                        if ui.radio(self.a4 == a4, format!("A = {} Hz", a4)).clicked() {
+                            self.a4 = a4;
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowbeat", "slowBeat");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_metronome(&mut self, ui: &mut egui::Ui) {
+        let mut pattern = self.metronome.pattern();
+        let heard = self.metronome.tick().map(|tick| pattern.position(tick));
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(8.0);
+            ui.label(egui::RichText::new(pattern.bpm.to_string()).size(48.0).color(SlowColors::BLACK));
+            ui.weak("beats per minute");
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                ui.add_space((ui.available_width() - 230.0).max(0.0) / 2.0);
+                if ui.button("−").clicked() {
+                    pattern.bpm = pattern.bpm.saturating_sub(1).max(MIN_BPM);
+                }
+                ui.add(egui::Slider::new(&mut pattern.bpm, MIN_BPM..=MAX_BPM).show_value(false));
+                if ui.button("+").clicked() {
+                    pattern.bpm = (pattern.bpm + 1).min(MAX_BPM);
+                }
+            });
+            ui.add_space(12.0);
+
+            // One box per beat: click to change its accent
+            let size = 28.0;
+            let beats = pattern.accents.len();
+            let width = beats as f32 * (size + 6.0);
+            let (row, _) = ui.allocate_exact_size(Vec2::new(width, size), Sense::hover());
+            for beat in 0..beats {
+                let rect = Rect::from_min_size(Pos2::new(row.left() + beat as f32 * (size + 6.0), row.top()), Vec2::splat(size));
+                let response = ui.interact(rect, ui.id().with(("beat", beat)), Sense::click());
+                if response.clicked() {
+                    pattern.accents[beat] = pattern.accents[beat].next();
+                }
+                let painter = ui.painter();
+                painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+                match pattern.accents[beat] {
+                    Accent::Strong => {
+                        painter.rect_filled(rect.shrink(4.0), 0.0, SlowColors::BLACK);
+                    }
+                    Accent::Normal => dither::draw_dither_rect(painter, rect.shrink(4.0), SlowColors::BLACK, 2),
+                    Accent::Silent => {}
+                }
+                // The beat being heard, marked underneath
+                if heard.is_some_and(|(b, _)| b == beat) {
+                    let mark = Rect::from_min_size(Pos2::new(rect.left(), rect.bottom() + 3.0), Vec2::new(size, 4.0));
+                    ui.painter().rect_filled(mark, 0.0, SlowColors::BLACK);
+                }
+            }
+            ui.add_space(10.0);
+            ui.weak("click a beat: accented, plain or silent");
+            ui.add_space(8.0);
+
+            ui.horizontal(|ui| {
+                ui.add_space((ui.available_width() - 300.0).max(0.0) / 2.0);
+                ui.label("beats:");
+                if ui.button("−").clicked() {
+                    pattern.set_beats(beats.saturating_sub(1));
+                }
+                ui.label(beats.to_string());
+                if ui.button("+").clicked() && beats < MAX_BEATS {
+                    pattern.set_beats(beats + 1);
+                }
+                ui.separator();
+                ui.label("ticks per beat:");
+                for subdivision in 1..=4 {
+                    if ui.selectable_label(pattern.subdivision == subdivision, subdivision.to_string()).clicked() {
+                        pattern.subdivision = subdivision;
+                    }
+                }
+            });
+            ui.add_space(16.0);
+
+            ui.horizontal(|ui| {
+                ui.add_space((ui.available_width() - 140.0).max(0.0) / 2.0);
+                let running = self.metronome.is_running();
+                if ui.button(if running { "stop" } else { "start" }).clicked() {
+                    self.toggle_metronome(ui.ctx());
+                }
+                if ui.button("tap").clicked() {
+                    self.metronome.tap();
+                    pattern = self.metronome.pattern();
+                }
+            });
+        });
+
+        if pattern != self.metronome.pattern() {
+            self.metronome.set_pattern(pattern);
+        }
+    }
+
+    fn draw_tuner(&mut self, ui: &mut egui::Ui) {
+        if self.tuner.is_none() {
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 3.0);
+                ui.label("the tuner needs a microphone");
+                ui.add_space(8.0);
+                if ui.button("try again").clicked() {
+                    self.set_mode(Mode::Tuner);
+                }
+            });
+            return;
+        }
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(8.0);
+            let (name, detail) = match &self.heard {
+                Some((note, _, _)) => (format!("{}{}", note.name, note.octave), format!("{:+.0} cents", note.cents)),
+                None => ("–".to_string(), "play a note".to_string()),
+            };
+            ui.label(egui::RichText::new(name).size(48.0).color(SlowColors::BLACK));
+            ui.weak(detail);
+        });
+        ui.add_space(8.0);
+
+        // The meter: a half circle from 50 cents flat to 50 sharp
+        let width = ui.available_width().min(360.0);
+        let (area, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), width / 2.0 + 16.0), Sense::hover());
+        let centre = Pos2::new(area.center().x, area.bottom() - 8.0);
+        let radius = width / 2.0 - 8.0;
+        let painter = ui.painter_at(area);
+        let at = |cents: f32, r: f32| {
+            let angle = (cents / 50.0).clamp(-1.0, 1.0) * std::f32::consts::FRAC_PI_2 * 0.9;
+            Pos2::new(centre.x + r * angle.sin(), centre.y - r * angle.cos())
+        };
+        for cents in (-50..=50).step_by(10) {
+            let long = cents % 50 == 0 || cents == 0;
+            let inner = if long { radius * 0.8 } else { radius * 0.88 };
+            painter.line_segment([at(cents as f32, inner), at(cents as f32, radius)], Stroke::new(if cents == 0 { 2.0 } else { 1.0 }, SlowColors::BLACK));
+        }
+        painter.text(at(-50.0, radius + 2.0), Align2::RIGHT_BOTTOM, "♭", FontId::proportional(16.0), SlowColors::BLACK);
+        painter.text(at(50.0, radius + 2.0), Align2::LEFT_BOTTOM, "♯", FontId::proportional(16.0), SlowColors::BLACK);
+
+        if let Some((_, needle, _)) = self.heard {
+            let tip = at(needle, radius * 0.95);
+            let in_tune = needle.abs() <= IN_TUNE;
+            if in_tune {
+                // Dithered behind the needle when in tune, to read at a glance
+                let zone = Rect::from_center_size(Pos2::new(centre.x, centre.y - radius * 0.5), Vec2::new(radius * 0.3, radius));
+                dither::draw_dither_rect(&painter, zone, SlowColors::BLACK, 2);
+            }
+            painter.line_segment([centre, tip], Stroke::new(if in_tune { 4.0 } else { 2.0 }, SlowColors::BLACK));
+        }
+        painter.circle_filled(centre, 5.0, SlowColors::BLACK);
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+impl eframe::App for SlowBeatApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowbeat") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.handle_keys(ctx);
+        self.listen();
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| match self.mode {
+                Mode::Metronome => {
+                    let pattern = self.metronome.pattern();
+                    let state = if self.metronome.is_running() { "playing" } else { "space to start, T to tap" };
+                    format!("{} beats to the bar  |  {}", pattern.accents.len(), state)
+                }
+                Mode::Tuner => format!("tuner  |  A = {} Hz", self.a4),
+            });
+            status_bar(ui, &status);
+        });
+
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(12.0)))
+            .show(ctx, |ui| match self.mode {
+                Mode::Metronome => self.draw_metronome(ui),
+                Mode::Tuner => self.draw_tuner(ui),
+            });
+
+        self.draw_about(ctx);
+
+        // The metronome's thread wakes the window on each tick
+        if self.tuner.is_some() {
+            ctx.request_repaint_after(TUNER_INTERVAL);
+        }
+        self.repaint.end_frame(ctx);
+    }
+}
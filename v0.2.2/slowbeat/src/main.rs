@@ -0,0 +1,31 @@
+//! slowBeat - A metronome and tuner for the Slow Computer
+//!
+//! A metronome with tap tempo, subdivisions and accents, and a chromatic
+//! tuner that listens to the microphone.
+
+mod app;
+mod metronome;
+mod tuner;
+
+use app::SlowBeatApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let viewport = window_layout::viewport("slowbeat", Placement::RememberLast, [460.0, 400.0])
+        .with_title("slowBeat");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowBeat",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowBeatApp::new(cc))
+        }),
+    )
+}
@@ -0,0 +1,223 @@
+//! The metronome
+//!
+//! A thread keeps a few ticks queued on a sink, each a tone and the
+//! silence after it, so the beat stays exact however busy the window is,
+//! and keeps going while it is minimized.
+
+use egui::Context;
+use rodio::Sink;
+use slowcore::sound::{self, Player};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const MIN_BPM: u32 = 30;
+pub const MAX_BPM: u32 = 250;
+pub const MAX_BEATS: usize = 12;
+
+/// Tone of an accented beat, a plain beat, and the ticks between beats
+const ACCENT_FREQ: f32 = 1760.0;
+const BEAT_FREQ: f32 = 1320.0;
+const SUBDIVISION_FREQ: f32 = 880.0;
+
+/// How far ahead ticks are queued; tempo changes are heard after this
+const LOOKAHEAD: Duration = Duration::from_millis(300);
+
+/// How often the thread tops up the queue
+const TOP_UP_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Taps further apart than this start a new tempo
+const TAP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Taps averaged for tap tempo
+const TAPS_KEPT: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Accent {
+    Strong,
+    Normal,
+    Silent,
+}
+
+impl Accent {
+    /// The next kind, for clicking through them
+    pub fn next(self) -> Self {
+        match self {
+            Accent::Strong => Accent::Normal,
+            Accent::Normal => Accent::Silent,
+            Accent::Silent => Accent::Strong,
+        }
+    }
+}
+
+/// What the metronome plays
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub bpm: u32,
+    /// One per beat of the bar
+    pub accents: Vec<Accent>,
+    /// Ticks per beat: 1, 2 for eighths, 3 for triplets, 4 for sixteenths
+    pub subdivision: u32,
+}
+
+impl Default for Pattern {
+    fn default() -> Self {
+        Self { bpm: 90, accents: vec![Accent::Strong, Accent::Normal, Accent::Normal, Accent::Normal], subdivision: 1 }
+    }
+}
+
+impl Pattern {
+    /// Length of one tick
+    pub fn tick_length(&self) -> Duration {
+        Duration::from_secs_f64(60.0 / (self.bpm.max(1) * self.subdivision.max(1)) as f64)
+    }
+
+    /// The beat of the bar tick `n` falls in, and whether it is on the beat
+    pub fn position(&self, n: u64) -> (usize, bool) {
+        let subdivision = self.subdivision.max(1) as u64;
+        let beat = (n / subdivision) as usize % self.accents.len().max(1);
+        (beat, n.is_multiple_of(subdivision))
+    }
+
+    /// The tone of tick `n`, 0 for silence
+    fn freq(&self, n: u64) -> f32 {
+        let (beat, on_beat) = self.position(n);
+        match (on_beat, self.accents.get(beat)) {
+            (_, Some(Accent::Silent)) | (_, None) => 0.0,
+            (false, _) => SUBDIVISION_FREQ,
+            (true, Some(Accent::Strong)) => ACCENT_FREQ,
+            (true, Some(Accent::Normal)) => BEAT_FREQ,
+        }
+    }
+
+    pub fn set_beats(&mut self, beats: usize) {
+        self.accents.resize(beats.clamp(1, MAX_BEATS), Accent::Normal);
+    }
+}
+
+/// The ticks of a metronome that is playing
+struct Running {
+    stop: Arc<AtomicBool>,
+    /// The tick being heard now
+    tick: Arc<AtomicU64>,
+}
+
+#[derive(Default)]
+pub struct Metronome {
+    pattern: Arc<Mutex<Pattern>>,
+    running: Option<Running>,
+    taps: Vec<Instant>,
+}
+
+impl Metronome {
+    pub fn pattern(&self) -> Pattern {
+        self.pattern.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Change the pattern; a playing metronome follows within LOOKAHEAD
+    pub fn set_pattern(&self, pattern: Pattern) {
+        *self.pattern.lock().unwrap_or_else(|e| e.into_inner()) = pattern;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// The tick being heard, while playing
+    pub fn tick(&self) -> Option<u64> {
+        self.running.as_ref().map(|r| r.tick.load(Ordering::Relaxed))
+    }
+
+    /// Start playing, waking `ctx` on every tick
+    pub fn start(&mut self, ctx: &Context) {
+        if self.running.is_some() {
+            return;
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let tick = Arc::new(AtomicU64::new(0));
+        let (pattern, thread_stop, thread_tick, ctx) = (self.pattern.clone(), stop.clone(), tick.clone(), ctx.clone());
+        let _ = std::thread::Builder::new().name("slowbeat-metronome".into()).spawn(move || {
+            let Some(sink) = sound::output().and_then(|handle| Sink::try_new(handle).ok()) else { return };
+            let mut player = Player::register("slowbeat");
+            player.set_playing(true);
+            let mut queued: u64 = 0;
+            while !thread_stop.load(Ordering::Relaxed) {
+                let pattern = pattern.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                sink.set_volume(player.volume());
+                // Two sources per tick: the tone, then its silence
+                let waiting = |sink: &Sink| (sink.len() as u32).div_ceil(2);
+                // At least two, so the next is there when one ends
+                while waiting(&sink) < 2 || pattern.tick_length() * waiting(&sink) < LOOKAHEAD {
+                    sound::queue_tick(&sink, pattern.freq(queued), pattern.tick_length());
+                    queued += 1;
+                }
+                let heard = queued.saturating_sub(waiting(&sink) as u64);
+                if thread_tick.swap(heard, Ordering::Relaxed) != heard {
+                    ctx.request_repaint();
+                }
+                std::thread::sleep(TOP_UP_INTERVAL);
+            }
+            sink.stop();
+        });
+        self.running = Some(Running { stop, tick });
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(running) = self.running.take() {
+            running.stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a tap; after two, the tempo follows the taps
+    pub fn tap(&mut self) {
+        let now = Instant::now();
+        if self.taps.last().is_some_and(|last| now.duration_since(*last) > TAP_TIMEOUT) {
+            self.taps.clear();
+        }
+        self.taps.push(now);
+        if self.taps.len() > TAPS_KEPT {
+            self.taps.remove(0);
+        }
+        if let Some(bpm) = tapped_bpm(&self.taps) {
+            let mut pattern = self.pattern();
+            pattern.bpm = bpm;
+            self.set_pattern(pattern);
+        }
+    }
+}
+
+impl Drop for Metronome {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// The tempo of the taps, from the average time between them
+fn tapped_bpm(taps: &[Instant]) -> Option<u32> {
+    let (first, last) = (taps.first()?, taps.last()?);
+    let gaps = taps.len().checked_sub(1).filter(|&n| n > 0)?;
+    let gap = last.duration_since(*first).as_secs_f64() / gaps as f64;
+    (gap > 0.0).then(|| ((60.0 / gap).round() as u32).clamp(MIN_BPM, MAX_BPM))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_ticks() {
+        let mut pattern = Pattern { bpm: 120, subdivision: 2, ..Pattern::default() };
+        pattern.accents[1] = Accent::Silent;
+        assert_eq!(pattern.tick_length(), Duration::from_millis(250));
+        assert_eq!(pattern.freq(0), ACCENT_FREQ);
+        assert_eq!(pattern.freq(1), SUBDIVISION_FREQ);
+        assert_eq!(pattern.freq(2), 0.0);
+        assert_eq!(pattern.freq(4), BEAT_FREQ);
+        assert_eq!(pattern.position(9), (0, false));
+
+        let start = Instant::now();
+        let taps: Vec<Instant> = (0..4).map(|i| start + Duration::from_millis(500 * i)).collect();
+        assert_eq!(tapped_bpm(&taps), Some(120));
+        assert_eq!(tapped_bpm(&taps[..1]), None);
+    }
+}
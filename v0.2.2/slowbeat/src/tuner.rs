@@ -0,0 +1,160 @@
+//! The tuner
+//!
+//! Listens to the microphone and finds the pitch of the last tenth of a
+//! second or so with the YIN method: the lag at which the sound best
+//! matches a shifted copy of itself is one period of the note.
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Pitches listened for, from below a bass's low E to above a violin's E
+const MIN_FREQ: f32 = 38.0;
+const MAX_FREQ: f32 = 1400.0;
+
+/// Samples kept for each look at the pitch
+const BUFFER: usize = 4096;
+
+/// How clearly the sound must repeat to count as a note; lower is stricter
+const THRESHOLD: f32 = 0.15;
+
+/// Quieter than this (RMS) is taken as silence
+const SILENCE: f32 = 0.01;
+
+pub const NOTE_NAMES: [&str; 12] = ["C", "C♯", "D", "D♯", "E", "F", "F♯", "G", "G♯", "A", "A♯", "B"];
+
+/// The nearest note to a pitch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Note {
+    pub name: &'static str,
+    pub octave: i32,
+    /// How far the pitch is from the note, -50 to 50
+    pub cents: f32,
+}
+
+/// The note nearest `freq`, tuned to `a4`
+pub fn note_of(freq: f32, a4: f32) -> Note {
+    let midi = 69.0 + 12.0 * (freq / a4).log2();
+    let nearest = midi.round();
+    let number = nearest as i32;
+    Note { name: NOTE_NAMES[number.rem_euclid(12) as usize], octave: number.div_euclid(12) - 1, cents: (midi - nearest) * 100.0 }
+}
+
+/// The microphone, being listened to
+pub struct Tuner {
+    _stream: cpal::Stream,
+    samples: Arc<Mutex<VecDeque<f32>>>,
+    sample_rate: u32,
+}
+
+impl Tuner {
+    pub fn start() -> Result<Self, String> {
+        let device = cpal::default_host().default_input_device().ok_or("no microphone found")?;
+        let config = device.default_input_config().map_err(|e| e.to_string())?;
+        let sample_format = config.sample_format();
+        let config: cpal::StreamConfig = config.into();
+        let samples = Arc::new(Mutex::new(VecDeque::with_capacity(BUFFER)));
+        let stream = match sample_format {
+            cpal::SampleFormat::F32 => build::<f32>(&device, &config, &samples),
+            cpal::SampleFormat::I16 => build::<i16>(&device, &config, &samples),
+            cpal::SampleFormat::U16 => build::<u16>(&device, &config, &samples),
+            cpal::SampleFormat::I32 => build::<i32>(&device, &config, &samples),
+            other => return Err(format!("the microphone's {} samples aren't supported", other)),
+        }?;
+        stream.play().map_err(|e| e.to_string())?;
+        Ok(Self { _stream: stream, samples, sample_rate: config.sample_rate.0 })
+    }
+
+    /// The pitch heard now, if there is one
+    pub fn pitch(&self) -> Option<f32> {
+        let samples: Vec<f32> = self.samples.lock().unwrap_or_else(|e| e.into_inner()).iter().copied().collect();
+        detect_pitch(&samples, self.sample_rate)
+    }
+}
+
+fn build<T>(device: &cpal::Device, config: &cpal::StreamConfig, samples: &Arc<Mutex<VecDeque<f32>>>) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    let samples = samples.clone();
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let mut samples = samples.lock().unwrap_or_else(|e| e.into_inner());
+                for frame in data.chunks(channels) {
+                    samples.push_back(frame.iter().map(|&s| f32::from_sample_(s)).sum::<f32>() / frame.len() as f32);
+                }
+                let extra = samples.len().saturating_sub(BUFFER);
+                samples.drain(..extra);
+            },
+            |e| slowcore::log::warn!("tuner: {}", e),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// The pitch of `samples` in Hz, or `None` for silence or noise
+pub fn detect_pitch(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt();
+    if rms < SILENCE {
+        return None;
+    }
+    let window = samples.len() / 2;
+    let min_lag = (sample_rate as f32 / MAX_FREQ) as usize;
+    let max_lag = ((sample_rate as f32 / MIN_FREQ) as usize).min(window.checked_sub(1)?);
+    if min_lag < 2 || min_lag >= max_lag {
+        return None;
+    }
+
+    // How unlike itself the sound is at each lag, over the running mean
+    let mut normalized = vec![1.0f32; max_lag + 1];
+    let mut total = 0.0;
+    for lag in 1..=max_lag {
+        let difference: f32 = (0..window).map(|i| (samples[i] - samples[i + lag]).powi(2)).sum();
+        total += difference;
+        normalized[lag] = if total > 0.0 { difference * lag as f32 / total } else { 1.0 };
+    }
+
+    // The first dip below the threshold, at the bottom of that dip
+    let mut lag = (min_lag..max_lag).find(|&lag| normalized[lag] < THRESHOLD)?;
+    while lag + 1 < max_lag && normalized[lag + 1] < normalized[lag] {
+        lag += 1;
+    }
+
+    // Between samples, from a parabola through the dip
+    let (before, at, after) = (normalized[lag - 1], normalized[lag], normalized[lag + 1]);
+    let curve = before + after - 2.0 * at;
+    let shift = if curve.abs() > f32::EPSILON { (before - after) / (2.0 * curve) } else { 0.0 };
+    Some(sample_rate as f32 / (lag as f32 + shift.clamp(-1.0, 1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_pitch() {
+        let rate = 44_100;
+        let tone: Vec<f32> = (0..BUFFER)
+            .map(|n| {
+                let t = n as f32 / rate as f32;
+                // A with an octave above it, like a plucked string
+                0.5 * (t * 110.0 * std::f32::consts::TAU).sin() + 0.25 * (t * 220.0 * std::f32::consts::TAU).sin()
+            })
+            .collect();
+        let pitch = detect_pitch(&tone, rate).unwrap();
+        assert!((pitch - 110.0).abs() < 0.5, "heard {}", pitch);
+        assert_eq!(detect_pitch(&vec![0.0; BUFFER], rate), None);
+
+        let note = note_of(pitch, 440.0);
+        assert_eq!((note.name, note.octave), ("A", 2));
+        assert!(note.cents.abs() < 5.0);
+        let sharp = note_of(261.63 * 1.01, 440.0);
+        assert_eq!((sharp.name, sharp.octave), ("C", 4));
+        assert!((sharp.cents - 17.2).abs() < 0.5);
+    }
+}
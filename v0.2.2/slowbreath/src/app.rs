@@ -1,13 +1,55 @@
 //! slowBreath - Mindful breathing timer
 //!
 //! A simple app to guide slow, deep breathing for relaxation and focus.
+//!
+//! Pick a program (or set your own durations), optionally a session
+//! length, and follow the circle. Soft tones mark each phase change.
+//! Finished sessions go into a history with a daily streak.
+//!
+//! The focus timer mode runs work/break cycles instead. Phase changes are
+//! posted as desktop notifications, the countdown shows in the desktop
+//! menu bar as a menu extra, and work time adds up per day.
 
+use crate::focus::{FocusPhase, FocusSettings, FocusTimer, PhaseEnd};
+use crate::history::History;
 use egui::{Context, Key, Pos2, Stroke};
+use serde::{Deserialize, Serialize};
+use slowcore::animation::{self, Easing, FrameBudget};
+use slowcore::menu_extra::MenuExtra;
 use slowcore::repaint::RepaintController;
+use slowcore::sound::Player;
+use slowcore::storage::config_dir;
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, WindowAction};
 use std::time::Instant;
 
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowBreath",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "mindful breathing timer for slowOS",
+    sections: &[
+        (
+            "breathing patterns",
+            &["4-7-8: relaxation technique", "box: focus and calm", "slow deep: general wellness", "custom: your own timing"],
+        ),
+        ("focus timer", &["work rounds with short breaks", "and a long break every few rounds"]),
+        ("controls", &["click or space: start/stop", "esc: stop session"]),
+    ],
+    ..AboutInfo::EMPTY
+};
+
+/// Session lengths offered in the menu, in minutes (0 = until stopped)
+const SESSION_LENGTHS: [u32; 5] = [0, 1, 3, 5, 10];
+
+/// Longest phase allowed in the custom program, in seconds
+const MAX_PHASE_SECS: f32 = 20.0;
+
+/// The circle is watched closely, so it gets the fast repaint rate
+const CIRCLE_BUDGET: FrameBudget = FrameBudget { fps: 30.0 };
+
+/// One swell of the ring shown while holding the breath
+const HOLD_PULSE_SECS: f32 = 2.0;
+
 /// Breathing phase
 #[derive(Clone, Copy, PartialEq)]
 enum Phase {
@@ -38,7 +80,7 @@ impl Phase {
 }
 
 /// Breathing pattern (durations in seconds)
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 struct BreathPattern {
     name: String,
     inhale: f32,
@@ -60,6 +102,73 @@ impl BreathPattern {
     fn total_cycle(&self) -> f32 {
         self.inhale + self.hold + self.exhale + self.rest
     }
+
+    /// The user's own program, edited from the pattern menu
+    fn custom() -> Self {
+        BreathPattern {
+            name: "custom".into(),
+            inhale: 4.0,
+            hold: 2.0,
+            exhale: 6.0,
+            rest: 0.0,
+        }
+    }
+}
+
+/// What the window is for
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Mode {
+    #[default]
+    Breathe,
+    Focus,
+}
+
+/// Saved preferences
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct BreathSettings {
+    mode: Mode,
+    /// Name of the selected pattern
+    pattern: String,
+    custom: BreathPattern,
+    /// Session length in minutes, 0 = until stopped
+    session_minutes: u32,
+    sound_cues: bool,
+    focus: FocusSettings,
+}
+
+impl Default for BreathSettings {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Breathe,
+            pattern: String::new(),
+            custom: BreathPattern::custom(),
+            session_minutes: 0,
+            sound_cues: true,
+            focus: FocusSettings::default(),
+        }
+    }
+}
+
+impl BreathSettings {
+    fn load() -> Self {
+        let path = config_dir("slowbreath").join("settings.json");
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let path = config_dir("slowbreath").join("settings.json");
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
 }
 
 fn default_patterns() -> Vec<BreathPattern> {
@@ -98,28 +207,47 @@ fn default_patterns() -> Vec<BreathPattern> {
 pub struct SlowBreathApp {
     patterns: Vec<BreathPattern>,
     selected_pattern: usize,
+    settings: BreathSettings,
+    history: History,
     running: bool,
+    /// The last session ran to its full length
+    finished: bool,
     phase: Phase,
     phase_elapsed: f32,
     total_breaths: u32,
     session_start: Option<Instant>,
     last_update: Instant,
     show_about: bool,
+    show_custom: bool,
+    show_history: bool,
+    focus: FocusTimer,
+    player: Player,
     repaint: RepaintController,
 }
 
 impl SlowBreathApp {
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        let settings = BreathSettings::load();
+        let mut patterns = default_patterns();
+        patterns.push(settings.custom.clone());
+        let selected_pattern = patterns.iter().position(|p| p.name == settings.pattern).unwrap_or(0);
         Self {
-            patterns: default_patterns(),
-            selected_pattern: 0,
+            patterns,
+            selected_pattern,
+            settings,
+            history: History::load(),
             running: false,
+            finished: false,
             phase: Phase::Inhale,
             phase_elapsed: 0.0,
             total_breaths: 0,
             session_start: None,
             last_update: Instant::now(),
             show_about: false,
+            show_custom: false,
+            show_history: false,
+            focus: FocusTimer::new(),
+            player: Player::register("slowbreath"),
             repaint: RepaintController::with_fast_interval(),
         }
     }
@@ -142,20 +270,73 @@ impl SlowBreathApp {
     }
 
     fn start(&mut self) {
+        if self.running {
+            self.record_session();
+        }
         self.running = true;
+        self.finished = false;
         self.phase = Phase::Inhale;
         self.phase_elapsed = 0.0;
         self.total_breaths = 0;
         self.session_start = Some(Instant::now());
+        self.cue(Phase::Inhale);
     }
 
     fn stop(&mut self) {
+        self.record_session();
         self.running = false;
         self.session_start = None;
     }
 
-    fn toggle(&mut self) {
+    /// The session reached its length: stop on a low, longer tone
+    fn finish(&mut self) {
+        self.stop();
+        self.finished = true;
+        if self.settings.sound_cues {
+            slowcore::sound::chime(262.0, 1200, self.player.volume());
+        }
+    }
+
+    fn record_session(&mut self) {
+        if self.session_start.is_some() {
+            let seconds = self.session_duration() as u32;
+            let name = self.current_pattern().name.clone();
+            self.history.record(&name, seconds, self.total_breaths);
+        }
+    }
+
+    /// Soft tone at the start of a phase
+    fn cue(&self, phase: Phase) {
+        if !self.settings.sound_cues {
+            return;
+        }
+        let freq = match phase {
+            Phase::Inhale => 440.0,
+            Phase::Hold => 392.0,
+            Phase::Exhale => 330.0,
+            Phase::Rest => return,
+        };
+        slowcore::sound::chime(freq, 600, self.player.volume());
+    }
+
+    fn select_pattern(&mut self, idx: usize) {
+        self.selected_pattern = idx;
+        self.settings.pattern = self.patterns[idx].name.clone();
+        self.settings.save();
         if self.running {
+            self.start();
+        }
+    }
+
+    /// Session length in seconds, if one is set
+    fn session_length(&self) -> Option<f32> {
+        (self.settings.session_minutes > 0).then_some(self.settings.session_minutes as f32 * 60.0)
+    }
+
+    fn toggle(&mut self) {
+        if self.settings.mode == Mode::Focus {
+            self.toggle_focus();
+        } else if self.running {
             self.stop();
         } else {
             self.start();
@@ -182,12 +363,80 @@ impl SlowBreathApp {
             }
 
             // Count completed breath cycles
-            if old_phase == Phase::Exhale || old_phase == Phase::Rest {
-                if self.phase == Phase::Inhale {
-                    self.total_breaths += 1;
+            if (old_phase == Phase::Exhale || old_phase == Phase::Rest) && self.phase == Phase::Inhale {
+                self.total_breaths += 1;
+                // End on a full breath once the time is up
+                if matches!(self.session_length(), Some(len) if self.session_duration() >= len) {
+                    self.finish();
+                    return;
+                }
+            }
+            self.cue(self.phase);
+        }
+    }
+
+    fn toggle_focus(&mut self) {
+        if self.focus.is_running() {
+            self.focus.pause();
+        } else {
+            self.focus.start();
+        }
+        self.publish_countdown();
+    }
+
+    fn update_focus(&mut self) {
+        if let Some(end) = self.focus.tick(&self.settings.focus) {
+            self.end_focus_phase(end);
+        }
+    }
+
+    /// Count work time, and announce the next phase if this one ran out
+    fn end_focus_phase(&mut self, end: PhaseEnd) {
+        if end.phase == FocusPhase::Work {
+            self.history.record_focus(end.elapsed.as_secs() as u32, end.completed);
+        }
+        if end.completed {
+            let focus = &self.settings.focus;
+            let message = match self.focus.phase {
+                FocusPhase::Work => "break's over, back to focus".to_string(),
+                FocusPhase::ShortBreak => {
+                    format!("focus round done, take a {} min break", focus.short_break_minutes)
                 }
+                FocusPhase::LongBreak => {
+                    format!("{} rounds done, take a {} min break", focus.rounds, focus.long_break_minutes)
+                }
+            };
+            slowcore::notify::post("slowbreath", &message);
+            if self.settings.sound_cues {
+                let freq = if self.focus.phase == FocusPhase::Work { 440.0 } else { 330.0 };
+                slowcore::sound::chime(freq, 900, self.player.volume());
             }
         }
+        self.publish_countdown();
+    }
+
+    /// Show the countdown in the desktop menu bar while the timer runs
+    fn publish_countdown(&self) {
+        if self.focus.is_running() {
+            let label = if self.focus.phase == FocusPhase::Work { "focus" } else { "break" };
+            MenuExtra::countdown(
+                "slowbreath",
+                label,
+                self.focus.remaining(&self.settings.focus),
+                self.settings.focus.length(self.focus.phase),
+            )
+            .publish();
+        } else {
+            slowcore::menu_extra::clear("slowbreath");
+        }
+    }
+
+    fn set_mode(&mut self, mode: Mode) {
+        if mode == Mode::Focus && self.running {
+            self.stop();
+        }
+        self.settings.mode = mode;
+        self.settings.save();
     }
 
     fn session_duration(&self) -> f32 {
@@ -196,6 +445,188 @@ impl SlowBreathApp {
             .unwrap_or(0.0)
     }
 
+    fn draw_custom_editor(&mut self, ctx: &Context) {
+        let custom = self.patterns.len() - 1;
+        let mut changed = false;
+        let mut open = true;
+        let resp = egui::Window::new("custom program")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let pattern = &mut self.patterns[custom];
+                egui::Grid::new("custom_grid").num_columns(2).spacing([12.0, 6.0]).show(ui, |ui| {
+                    // Breathing in and out can't be skipped
+                    for (label, value, min) in [
+                        ("inhale", &mut pattern.inhale, 1.0),
+                        ("hold", &mut pattern.hold, 0.0),
+                        ("exhale", &mut pattern.exhale, 1.0),
+                        ("rest", &mut pattern.rest, 0.0),
+                    ] {
+                        ui.label(label);
+                        changed |= ui
+                            .add(egui::DragValue::new(value).clamp_range(min..=MAX_PHASE_SECS).speed(0.1).suffix("s"))
+                            .changed();
+                        ui.end_row();
+                    }
+                });
+                ui.add_space(4.0);
+                ui.label(format!("{:.0}s per breath", pattern.total_cycle()));
+            });
+        if let Some(r) = &resp {
+            slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+        }
+        if changed {
+            let pattern = &mut self.patterns[custom];
+            for value in [&mut pattern.inhale, &mut pattern.hold, &mut pattern.exhale, &mut pattern.rest] {
+                *value = value.round();
+            }
+            self.settings.custom = self.patterns[custom].clone();
+            self.settings.save();
+            if self.selected_pattern != custom {
+                self.select_pattern(custom);
+            }
+        }
+        if !open || ctx.input(|i| i.key_pressed(Key::Escape)) {
+            self.show_custom = false;
+        }
+    }
+
+    fn timer_menu(&mut self, ui: &mut egui::Ui) {
+        if ui.button("skip to next phase").clicked() {
+            let end = self.focus.skip(&self.settings.focus);
+            self.end_focus_phase(end);
+            ui.close_menu();
+        }
+        ui.separator();
+        let mut changed = false;
+        let focus = &mut self.settings.focus;
+        egui::Grid::new("timer_grid").num_columns(2).spacing([12.0, 4.0]).show(ui, |ui| {
+            for (label, value, max, unit) in [
+                ("focus", &mut focus.work_minutes, 120, " min"),
+                ("short break", &mut focus.short_break_minutes, 30, " min"),
+                ("long break", &mut focus.long_break_minutes, 60, " min"),
+                ("rounds", &mut focus.rounds, 8, ""),
+            ] {
+                ui.label(label);
+                changed |= ui.add(egui::DragValue::new(value).clamp_range(1..=max).suffix(unit)).changed();
+                ui.end_row();
+            }
+        });
+        ui.separator();
+        changed |= ui.checkbox(&mut self.settings.sound_cues, "sound cues").changed();
+        if changed {
+            self.settings.save();
+            self.publish_countdown();
+        }
+    }
+
+    fn draw_focus(&self, ui: &egui::Ui, rect: egui::Rect) {
+        let painter = ui.painter();
+        let center_x = rect.center().x;
+        let focus = &self.settings.focus;
+
+        painter.text(
+            Pos2::new(center_x, rect.min.y + 30.0),
+            egui::Align2::CENTER_CENTER,
+            self.focus.phase.name(),
+            egui::FontId::proportional(18.0),
+            SlowColors::BLACK,
+        );
+
+        // Pie of the time left, like a kitchen timer
+        let radius = (rect.width().min(rect.height() - 160.0) * 0.30).max(20.0);
+        let center = Pos2::new(center_x, rect.min.y + 70.0 + radius);
+        let length = focus.length(self.focus.phase).as_secs_f32();
+        let remaining = self.focus.remaining(focus);
+        let left = (remaining.as_secs_f32() / length).clamp(0.0, 1.0);
+        if self.focus.is_running() {
+            animation::progress_pie(painter, center, radius, left, SlowColors::BLACK);
+        } else {
+            animation::progress_arc(painter, center, radius, left, Stroke::new(2.0, SlowColors::BLACK));
+        }
+        painter.circle_stroke(center, radius + 8.0, Stroke::new(1.0, SlowColors::BLACK));
+
+        let text_y = center.y + radius + 30.0;
+        let secs = remaining.as_secs_f32().ceil() as u32;
+        painter.text(
+            Pos2::new(center_x, text_y),
+            egui::Align2::CENTER_CENTER,
+            format!("{}:{:02}", secs / 60, secs % 60),
+            egui::FontId::proportional(22.0),
+            SlowColors::BLACK,
+        );
+
+        // One dot per round in the set, filled once done
+        let dot_y = text_y + 25.0;
+        let spacing = 14.0;
+        let first_x = center_x - spacing * (focus.rounds as f32 - 1.0) / 2.0;
+        for i in 0..focus.rounds {
+            let dot = Pos2::new(first_x + spacing * i as f32, dot_y);
+            if i < self.focus.round {
+                painter.circle_filled(dot, 4.0, SlowColors::BLACK);
+            } else {
+                painter.circle_stroke(dot, 4.0, Stroke::new(1.0, SlowColors::BLACK));
+            }
+        }
+
+        let today = self.history.focus_on(chrono::Local::now().date_naive());
+        let hint = if self.focus.is_running() {
+            format!("today: {} min, {} rounds", today.seconds / 60, today.rounds)
+        } else if self.focus.elapsed().is_zero() && self.focus.round == 0 {
+            "press space to start".to_string()
+        } else {
+            "paused".to_string()
+        };
+        painter.text(
+            Pos2::new(center_x, dot_y + 22.0),
+            egui::Align2::CENTER_CENTER,
+            hint,
+            egui::FontId::proportional(12.0),
+            SlowColors::BLACK,
+        );
+    }
+
+    fn draw_history(&mut self, ctx: &Context) {
+        let today = chrono::Local::now().date_naive();
+        let mut open = true;
+        let resp = egui::Window::new("history")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(280.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let minutes_today = self.history.seconds_on(today) / 60;
+                ui.label(format!("today: {} min", minutes_today));
+                ui.label(format!("streak: {} days", self.history.current_streak(today)));
+                ui.label(format!("longest streak: {} days", self.history.longest_streak()));
+                ui.label(format!("sessions: {}", self.history.sessions.len()));
+                let focus = self.history.focus_on(today);
+                ui.label(format!("focus today: {} min, {} rounds", focus.seconds / 60, focus.rounds));
+                ui.separator();
+                if self.history.sessions.is_empty() {
+                    ui.label("no sessions yet");
+                }
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for session in self.history.recent(50) {
+                        ui.label(format!(
+                            "{}  {}  {}:{:02}  {} breaths",
+                            session.date.format("%Y-%m-%d"),
+                            session.program,
+                            session.seconds / 60,
+                            session.seconds % 60,
+                            session.breaths
+                        ));
+                    }
+                });
+            });
+        if let Some(r) = &resp {
+            slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+        }
+        if !open || ctx.input(|i| i.key_pressed(Key::Escape)) {
+            self.show_history = false;
+        }
+    }
 }
 
 impl eframe::App for SlowBreathApp {
@@ -216,19 +647,35 @@ impl eframe::App for SlowBreathApp {
 
         // Update breathing
         self.update_breathing(dt);
+        self.update_focus();
+        slowcore::session::close_on_quit(ctx);
+        let timing = self.running || self.focus.is_running();
+        self.player.set_playing(timing && self.settings.sound_cues);
+        if ctx.input(|i| i.viewport().close_requested()) {
+            self.record_session();
+            self.session_start = None;
+            if self.focus.is_running() {
+                let end = self.focus.reset();
+                self.end_focus_phase(end);
+            }
+        }
 
-        // Enable continuous repaint while breathing animation is running
-        self.repaint.set_continuous(self.running);
+        // Enable continuous repaint while breathing animation or timer is running
+        self.repaint.set_continuous(timing);
 
         // Handle keyboard and mouse
-        ctx.input(|i| {
-            if i.key_pressed(Key::Space) {
-                self.toggle();
-            }
-            if i.key_pressed(Key::Escape) && self.running {
+        let (space, escape) = ctx.input(|i| (i.key_pressed(Key::Space), i.key_pressed(Key::Escape)));
+        if space {
+            self.toggle();
+        }
+        if escape {
+            if self.settings.mode == Mode::Focus {
+                let end = self.focus.reset();
+                self.end_focus_phase(end);
+            } else if self.running {
                 self.stop();
             }
-        });
+        }
         // Mouse click anywhere in the central area to start/stop
         let clicked = ctx.input(|i| i.pointer.any_click());
         if clicked {
@@ -239,7 +686,8 @@ impl eframe::App for SlowBreathApp {
                 let screen = ctx.screen_rect();
                 let content_top = screen.min.y + 30.0;
                 let content_bottom = screen.max.y - 25.0;
-                if pos.y > content_top && pos.y < content_bottom && !self.show_about {
+                let dialog_open = self.show_about || self.show_custom || self.show_history;
+                if pos.y > content_top && pos.y < content_bottom && !dialog_open {
                     self.toggle();
                 }
             }
@@ -251,41 +699,95 @@ impl eframe::App for SlowBreathApp {
             menu_bar(ui, |ui| {
                 win_action = window_control_buttons(ui);
                 ui.menu_button("file", |ui| {
-                    if ui.button("start      space").clicked() {
-                        self.start();
-                        ui.close_menu();
+                    if self.settings.mode == Mode::Focus {
+                        let label = if self.focus.is_running() { "pause      space" } else { "start      space" };
+                        if ui.button(label).clicked() {
+                            self.toggle_focus();
+                            ui.close_menu();
+                        }
+                        if ui.button("reset      esc").clicked() {
+                            let end = self.focus.reset();
+                            self.end_focus_phase(end);
+                            ui.close_menu();
+                        }
+                    } else {
+                        if ui.button("start      space").clicked() {
+                            self.start();
+                            ui.close_menu();
+                        }
+                        if ui.button("stop       esc").clicked() {
+                            self.stop();
+                            ui.close_menu();
+                        }
                     }
-                    if ui.button("stop       esc").clicked() {
-                        self.stop();
+                    ui.separator();
+                    if ui.button("history...").clicked() {
+                        self.show_history = true;
                         ui.close_menu();
                     }
                 });
 
-                ui.menu_button("pattern", |ui| {
-                    let pattern_names: Vec<_> = self.patterns.iter()
-                        .map(|p| p.name.clone())
-                        .collect();
-                    let mut new_selection = None;
-                    for (idx, name) in pattern_names.iter().enumerate() {
-                        let selected = idx == self.selected_pattern;
-                        let label = if selected {
-                            format!("* {}", name)
-                        } else {
-                            format!("  {}", name)
-                        };
-                        if ui.button(&label).clicked() {
-                            new_selection = Some(idx);
+                ui.menu_button("mode", |ui| {
+                    for (mode, name) in [(Mode::Breathe, "breathe"), (Mode::Focus, "focus timer")] {
+                        let mark = if self.settings.mode == mode { "*" } else { " " };
+                        if ui.button(format!("{} {}", mark, name)).clicked() {
+                            self.set_mode(mode);
                             ui.close_menu();
                         }
                     }
-                    if let Some(idx) = new_selection {
-                        self.selected_pattern = idx;
-                        if self.running {
-                            self.start();
-                        }
-                    }
                 });
 
+                if self.settings.mode == Mode::Focus {
+                    ui.menu_button("timer", |ui| self.timer_menu(ui));
+                } else {
+                    ui.menu_button("pattern", |ui| {
+                        let pattern_names: Vec<_> = self.patterns.iter()
+                            .map(|p| p.name.clone())
+                            .collect();
+                        let mut new_selection = None;
+                        for (idx, name) in pattern_names.iter().enumerate() {
+                            let selected = idx == self.selected_pattern;
+                            let label = if selected {
+                                format!("* {}", name)
+                            } else {
+                                format!("  {}", name)
+                            };
+                            if ui.button(&label).clicked() {
+                                new_selection = Some(idx);
+                                ui.close_menu();
+                            }
+                        }
+                        if let Some(idx) = new_selection {
+                            self.select_pattern(idx);
+                        }
+                        ui.separator();
+                        if ui.button("  edit custom...").clicked() {
+                            self.show_custom = true;
+                            ui.close_menu();
+                        }
+                    });
+
+                    ui.menu_button("session", |ui| {
+                        for minutes in SESSION_LENGTHS {
+                            let name = if minutes == 0 {
+                                "until stopped".to_string()
+                            } else {
+                                format!("{} min", minutes)
+                            };
+                            let mark = if self.settings.session_minutes == minutes { "*" } else { " " };
+                            if ui.button(format!("{} {}", mark, name)).clicked() {
+                                self.settings.session_minutes = minutes;
+                                self.settings.save();
+                                ui.close_menu();
+                            }
+                        }
+                        ui.separator();
+                        if ui.checkbox(&mut self.settings.sound_cues, "sound cues").changed() {
+                            self.settings.save();
+                        }
+                    });
+                }
+
                 ui.menu_button("help", |ui| {
                     if ui.button("about").clicked() {
                         self.show_about = true;
@@ -309,16 +811,31 @@ impl eframe::App for SlowBreathApp {
         egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
             let pattern = self.current_pattern();
             let cycle_time = pattern.total_cycle();
-            let status = if self.running {
+            let status = if self.settings.mode == Mode::Focus {
+                let focus = &self.settings.focus;
+                let today = self.history.focus_on(chrono::Local::now().date_naive());
+                format!(
+                    "{}  |  round {} of {}  |  today {} min",
+                    self.focus.phase.name(),
+                    (self.focus.round + 1).min(focus.rounds),
+                    focus.rounds,
+                    today.seconds / 60
+                )
+            } else if self.running {
                 let session = self.session_duration();
                 let mins = (session / 60.0) as u32;
                 let secs = (session % 60.0) as u32;
+                let length = match self.settings.session_minutes {
+                    0 => String::new(),
+                    m => format!(" / {}:00", m),
+                };
                 format!(
-                    "{}  |  {} breaths  |  {}:{:02}",
-                    pattern.name, self.total_breaths, mins, secs
+                    "{}  |  {} breaths  |  {}:{:02}{}",
+                    pattern.name, self.total_breaths, mins, secs, length
                 )
             } else {
-                format!("{}  |  {:.0}s cycle", pattern.name, cycle_time)
+                let streak = self.history.current_streak(chrono::Local::now().date_naive());
+                format!("{}  |  {:.0}s cycle  |  {} day streak", pattern.name, cycle_time, streak)
             };
             status_bar(ui, &status);
         });
@@ -329,6 +846,10 @@ impl eframe::App for SlowBreathApp {
             .show(ctx, |ui| {
                 let full_rect = ui.available_rect_before_wrap();
                 ui.allocate_rect(full_rect, egui::Sense::hover());
+                if self.settings.mode == Mode::Focus {
+                    self.draw_focus(ui, full_rect);
+                    return;
+                }
 
                 let painter = ui.painter();
                 let center_x = full_rect.center().x;
@@ -366,13 +887,14 @@ impl eframe::App for SlowBreathApp {
                 // Breathing circle - centered in remaining space
                 let circle_center = Pos2::new(center_x, full_rect.min.y + 80.0 + circle_area_size / 2.0);
 
-                // Calculate current radius based on phase
-                let progress = self.phase_progress();
-                let radius = match self.phase {
-                    Phase::Inhale => min_radius + (max_radius - min_radius) * progress,
-                    Phase::Hold => max_radius,
-                    Phase::Exhale => max_radius - (max_radius - min_radius) * progress,
-                    Phase::Rest => min_radius,
+                // How far the circle has grown, eased like a breath
+                let length = std::time::Duration::from_secs_f32(self.phase_duration().max(0.0));
+                let progress = Easing::InOut.apply(CIRCLE_BUDGET.effective().step(self.phase_progress(), length));
+                let grown = match self.phase {
+                    Phase::Inhale => progress,
+                    Phase::Hold => 1.0,
+                    Phase::Exhale => 1.0 - progress,
+                    Phase::Rest => 0.0,
                 };
 
                 // Draw outer guide circle
@@ -391,15 +913,22 @@ impl eframe::App for SlowBreathApp {
 
                 // Draw breathing circle
                 if self.running {
-                    painter.circle_filled(circle_center, radius, SlowColors::BLACK);
+                    animation::expanding_circle(painter, circle_center, min_radius, max_radius, grown);
+                    // The pulse is only there for show
+                    if self.phase == Phase::Hold && !animation::reduce_motion() {
+                        let pulse = (self.phase_elapsed / HOLD_PULSE_SECS).fract();
+                        animation::pulsing_ring(painter, circle_center, max_radius, 8.0, Easing::Out.apply(pulse));
+                    }
                 } else {
-                    painter.circle_stroke(circle_center, radius, Stroke::new(2.0, SlowColors::BLACK));
+                    painter.circle_stroke(circle_center, min_radius, Stroke::new(2.0, SlowColors::BLACK));
                 }
 
                 // Phase text below circle
                 let text_y = circle_center.y + max_radius + 30.0;
                 let phase_text = if self.running {
                     self.phase.name()
+                } else if self.finished {
+                    "session complete"
                 } else {
                     "press space to start"
                 };
@@ -424,43 +953,16 @@ impl eframe::App for SlowBreathApp {
                 }
             });
 
+        if self.show_custom {
+            self.draw_custom_editor(ctx);
+        }
+        if self.show_history {
+            self.draw_history(ctx);
+        }
+
         // About dialog
-        if self.show_about {
-            let screen = ctx.screen_rect();
-            let max_h = (screen.height() - 60.0).max(120.0);
-            let resp = egui::Window::new("about slowBreath")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(300.0)
-                .max_height(max_h)
-                .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                        ui.vertical_centered(|ui| {
-                            ui.heading("slowBreath");
-                            ui.label("version 0.2.2");
-                            ui.add_space(8.0);
-                            ui.label("mindful breathing timer for slowOS");
-                        });
-                        ui.add_space(8.0);
-                        ui.separator();
-                        ui.add_space(4.0);
-                        ui.label("breathing patterns:");
-                        ui.label("  4-7-8: relaxation technique");
-                        ui.label("  box: focus and calm");
-                        ui.label("  slow deep: general wellness");
-                        ui.add_space(4.0);
-                        ui.label("controls:");
-                        ui.label("  click or space: start/stop");
-                        ui.label("  esc: stop session");
-                        ui.add_space(8.0);
-                        ui.vertical_centered(|ui| {
-                            if ui.button("ok").clicked() {
-                                self.show_about = false;
-                            }
-                        });
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
 
         self.repaint.end_frame(ctx);
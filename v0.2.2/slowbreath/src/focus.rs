@@ -0,0 +1,147 @@
+//! Focus timer (pomodoro)
+//!
+//! Work rounds alternate with short breaks; every few rounds the break is
+//! a long one. The timer only measures time — slowBreath decides what to
+//! show, play and record when a phase ends.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl FocusPhase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FocusPhase::Work => "focus",
+            FocusPhase::ShortBreak => "short break",
+            FocusPhase::LongBreak => "long break",
+        }
+    }
+}
+
+/// Phase lengths in minutes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusSettings {
+    pub work_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    /// Work rounds before a long break
+    pub rounds: u32,
+}
+
+impl Default for FocusSettings {
+    fn default() -> Self {
+        Self { work_minutes: 25, short_break_minutes: 5, long_break_minutes: 15, rounds: 4 }
+    }
+}
+
+impl FocusSettings {
+    pub fn length(&self, phase: FocusPhase) -> Duration {
+        let minutes = match phase {
+            FocusPhase::Work => self.work_minutes,
+            FocusPhase::ShortBreak => self.short_break_minutes,
+            FocusPhase::LongBreak => self.long_break_minutes,
+        };
+        Duration::from_secs(minutes.max(1) as u64 * 60)
+    }
+}
+
+/// A phase that just ended
+pub struct PhaseEnd {
+    pub phase: FocusPhase,
+    /// Time actually spent in it
+    pub elapsed: Duration,
+    /// It ran to the end rather than being skipped or reset
+    pub completed: bool,
+}
+
+pub struct FocusTimer {
+    pub phase: FocusPhase,
+    /// Work rounds finished since the last long break
+    pub round: u32,
+    /// Set while counting down
+    running_since: Option<Instant>,
+    /// Time counted in this phase before the last pause
+    elapsed_before: Duration,
+}
+
+impl FocusTimer {
+    pub fn new() -> Self {
+        Self { phase: FocusPhase::Work, round: 0, running_since: None, elapsed_before: Duration::ZERO }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_before + self.running_since.map(|t| t.elapsed()).unwrap_or_default()
+    }
+
+    pub fn remaining(&self, settings: &FocusSettings) -> Duration {
+        settings.length(self.phase).saturating_sub(self.elapsed())
+    }
+
+    pub fn start(&mut self) {
+        if self.running_since.is_none() {
+            self.running_since = Some(Instant::now());
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.elapsed_before = self.elapsed();
+        self.running_since = None;
+    }
+
+    /// Move on when the current phase has run out; keeps running
+    pub fn tick(&mut self, settings: &FocusSettings) -> Option<PhaseEnd> {
+        if self.is_running() && self.remaining(settings).is_zero() {
+            Some(self.advance(settings, true))
+        } else {
+            None
+        }
+    }
+
+    /// Skip to the next phase, keeping the running state
+    pub fn skip(&mut self, settings: &FocusSettings) -> PhaseEnd {
+        self.advance(settings, false)
+    }
+
+    /// Back to the first work round, paused
+    pub fn reset(&mut self) -> PhaseEnd {
+        let end = PhaseEnd { phase: self.phase, elapsed: self.elapsed(), completed: false };
+        *self = Self::new();
+        end
+    }
+
+    fn advance(&mut self, settings: &FocusSettings, completed: bool) -> PhaseEnd {
+        let elapsed = self.elapsed().min(settings.length(self.phase));
+        let ended = self.phase;
+        self.phase = match ended {
+            FocusPhase::Work => {
+                self.round += 1;
+                if self.round >= settings.rounds.max(1) {
+                    FocusPhase::LongBreak
+                } else {
+                    FocusPhase::ShortBreak
+                }
+            }
+            FocusPhase::LongBreak => {
+                self.round = 0;
+                FocusPhase::Work
+            }
+            FocusPhase::ShortBreak => FocusPhase::Work,
+        };
+        self.elapsed_before = Duration::ZERO;
+        if self.running_since.is_some() {
+            self.running_since = Some(Instant::now());
+        }
+        PhaseEnd { phase: ended, elapsed, completed }
+    }
+}
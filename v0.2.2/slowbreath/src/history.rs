@@ -0,0 +1,173 @@
+//! Session history and streaks
+//!
+//! Every finished session is appended to ~/.config/slowbreath/history.json.
+//! A streak is the run of consecutive days with at least one session; it
+//! stays alive until the end of the day after the last session.
+//!
+//! Focus-timer work is kept alongside as one total per day.
+
+use chrono::{Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use slowcore::storage::config_dir;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// Sessions shorter than this aren't recorded
+pub const MIN_SESSION_SECS: u32 = 30;
+
+/// One finished breathing session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub date: NaiveDate,
+    /// Program name at the time
+    pub program: String,
+    pub seconds: u32,
+    pub breaths: u32,
+}
+
+/// Focus-timer totals for one day
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct FocusDay {
+    /// Seconds spent in work phases, finished or not
+    pub seconds: u32,
+    /// Work phases that ran to the end
+    pub rounds: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    pub sessions: Vec<SessionRecord>,
+    #[serde(default)]
+    pub focus: BTreeMap<NaiveDate, FocusDay>,
+}
+
+fn history_path() -> PathBuf {
+    config_dir("slowbreath").join("history.json")
+}
+
+impl History {
+    pub fn load() -> Self {
+        std::fs::read_to_string(history_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = history_path();
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    /// Record a session dated today and save. Too-short sessions are ignored.
+    pub fn record(&mut self, program: &str, seconds: u32, breaths: u32) {
+        if seconds < MIN_SESSION_SECS {
+            return;
+        }
+        self.sessions.push(SessionRecord {
+            date: Local::now().date_naive(),
+            program: program.to_string(),
+            seconds,
+            breaths,
+        });
+        self.save();
+    }
+
+    fn days(&self) -> BTreeSet<NaiveDate> {
+        self.sessions.iter().map(|s| s.date).collect()
+    }
+
+    /// Days in a row with a session, counting back from `today` (or from
+    /// yesterday if there is nothing yet today)
+    pub fn current_streak(&self, today: NaiveDate) -> u32 {
+        let days = self.days();
+        let mut day = if days.contains(&today) { today } else { today - Duration::days(1) };
+        let mut streak = 0;
+        while days.contains(&day) {
+            streak += 1;
+            day -= Duration::days(1);
+        }
+        streak
+    }
+
+    pub fn longest_streak(&self) -> u32 {
+        let mut longest = 0;
+        let mut run = 0;
+        let mut prev: Option<NaiveDate> = None;
+        for day in self.days() {
+            run = if prev == Some(day - Duration::days(1)) { run + 1 } else { 1 };
+            longest = longest.max(run);
+            prev = Some(day);
+        }
+        longest
+    }
+
+    /// Total seconds breathed on `date`
+    pub fn seconds_on(&self, date: NaiveDate) -> u32 {
+        self.sessions.iter().filter(|s| s.date == date).map(|s| s.seconds).sum()
+    }
+
+    /// Add focus-timer work to today's total and save
+    pub fn record_focus(&mut self, seconds: u32, completed: bool) {
+        if seconds == 0 && !completed {
+            return;
+        }
+        let day = self.focus.entry(Local::now().date_naive()).or_default();
+        day.seconds += seconds;
+        if completed {
+            day.rounds += 1;
+        }
+        self.save();
+    }
+
+    pub fn focus_on(&self, date: NaiveDate) -> FocusDay {
+        self.focus.get(&date).copied().unwrap_or_default()
+    }
+
+    /// Most recent sessions first
+    pub fn recent(&self, count: usize) -> impl Iterator<Item = &SessionRecord> {
+        self.sessions.iter().rev().take(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(days_ago: &[i64], today: NaiveDate) -> History {
+        let sessions = days_ago
+            .iter()
+            .map(|&n| SessionRecord { date: today - Duration::days(n), program: "calm".into(), seconds: 60, breaths: 6 })
+            .collect();
+        History { sessions, focus: BTreeMap::new() }
+    }
+
+    #[test]
+    fn test_streaks() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+
+        let empty = History::default();
+        assert_eq!(empty.current_streak(today), 0);
+        assert_eq!(empty.longest_streak(), 0);
+
+        // Ending today, or yesterday with nothing yet today
+        assert_eq!(history(&[0, 1, 2], today).current_streak(today), 3);
+        assert_eq!(history(&[1, 2], today).current_streak(today), 2);
+        // Two days ago the streak has lapsed
+        assert_eq!(history(&[2, 3], today).current_streak(today), 0);
+
+        // A gap day splits the run
+        let gap = history(&[0, 1, 3, 4, 5], today);
+        assert_eq!(gap.current_streak(today), 2);
+        assert_eq!(gap.longest_streak(), 3);
+
+        // Several sessions on one day count once, in any order
+        let same_day = history(&[1, 0, 0, 1, 0], today);
+        assert_eq!(same_day.current_streak(today), 2);
+        assert_eq!(same_day.longest_streak(), 2);
+    }
+}
@@ -1,14 +1,16 @@
 //! slowBreath - Mindful breathing timer for the Slow Computer
 
 mod app;
+mod focus;
+mod history;
 
 use app::SlowBreathApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([340.0, 420.0])
+        viewport: window_layout::viewport("slowbreath", Placement::Center, [340.0, 420.0])
             .with_title("slowBreath"),
         ..Default::default()
     };
@@ -3,7 +3,15 @@
 use egui::{Context, Key};
 use slowcore::repaint::RepaintController;
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{window_control_buttons, WindowAction};
+use slowcore::widgets::{about_dialog, window_control_buttons_with_pin, AboutInfo, WindowAction};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "calculator",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "calculator for slowOS",
+    sections: &[("modes", &["basic / scientific"]), ("keys", &["0-9 +-*/ Enter Esc"])],
+    ..AboutInfo::EMPTY
+};
 
 #[derive(PartialEq, Clone, Copy)]
 enum CalcMode {
@@ -387,7 +395,7 @@ impl eframe::App for SlowCalcApp {
         let mut win_action = WindowAction::None;
         egui::TopBottomPanel::top("menu").show(ctx, |ui| {
             menu_bar(ui, |ui| {
-                win_action = window_control_buttons(ui);
+                win_action = window_control_buttons_with_pin(ui);
                 ui.menu_button("mode", |ui| {
                     if ui.selectable_label(self.mode == CalcMode::Basic, "basic").clicked() {
                         self.mode = CalcMode::Basic;
@@ -452,37 +460,8 @@ impl eframe::App for SlowCalcApp {
                 }
             });
 
-        if self.show_about {
-            let screen_rect = ctx.screen_rect();
-            let max_h = (screen_rect.height() - 40.0).max(120.0);
-            let resp = egui::Window::new("about calculator")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(240.0)
-                .max_height(max_h)
-                .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                        ui.vertical_centered(|ui| {
-                            ui.heading("calculator");
-                            ui.label("version 0.2.2");
-                            ui.add_space(4.0);
-                            ui.label("calculator for slowOS");
-                        });
-                        ui.add_space(4.0);
-                        ui.separator();
-                        ui.add_space(2.0);
-                        ui.label("modes:");
-                        ui.label("  basic / scientific");
-                        ui.add_space(2.0);
-                        ui.label("keys: 0-9 +-*/ Enter Esc");
-                    });
-                    ui.vertical_centered(|ui| {
-                        if ui.button("ok").clicked() {
-                            self.show_about = false;
-                        }
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
         self.repaint.end_frame(ctx);
     }
@@ -6,11 +6,11 @@ mod app;
 
 use app::SlowCalcApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([260.0, 350.0])
+        viewport: window_layout::viewport("slowcalc", Placement::Center, [260.0, 350.0])
             .with_title("calculator"),
         ..Default::default()
     };
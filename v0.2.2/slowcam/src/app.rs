@@ -0,0 +1,365 @@
+//! slowCam application
+
+use crate::camera::{self, Camera};
+use chrono::Local;
+use egui::{Align2, CentralPanel, ColorImage, Context, FontId, Key, Rect, TextureHandle, TextureOptions, TopBottomPanel, Vec2};
+use image::GrayImage;
+use slowcore::dither;
+use slowcore::repaint::RepaintController;
+use slowcore::storage::pictures_dir;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, WindowAction};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowCam",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "black and white camera for slowOS",
+    features: &["live dithered view", "USB and Pi cameras", "pictures saved to Pictures", "timer and burst modes"],
+    ..AboutInfo::EMPTY
+};
+
+/// Pictures taken by one press in burst mode
+const BURST_SHOTS: u32 = 5;
+
+/// Time between pictures in a burst
+const BURST_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often the countdown is redrawn
+const COUNTDOWN_TICK: Duration = Duration::from_millis(200);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Timer {
+    Off,
+    Three,
+    Ten,
+}
+
+impl Timer {
+    const ALL: [Timer; 3] = [Timer::Off, Timer::Three, Timer::Ten];
+
+    fn delay(self) -> Duration {
+        match self {
+            Timer::Off => Duration::ZERO,
+            Timer::Three => Duration::from_secs(3),
+            Timer::Ten => Duration::from_secs(10),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Timer::Off => "no timer",
+            Timer::Three => "3 seconds",
+            Timer::Ten => "10 seconds",
+        }
+    }
+}
+
+pub struct SlowCamApp {
+    cameras: Vec<PathBuf>,
+    camera: Option<Camera>,
+    /// The live view, dithered
+    preview: Option<TextureHandle>,
+    /// Frame number and pixel size of the live view, to redo it only when
+    /// either changes
+    preview_of: Option<(u64, [u32; 2])>,
+    timer: Timer,
+    burst: bool,
+    /// Pictures still to take, and when the next is due
+    pending: Option<(u32, Instant)>,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowCamApp {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self {
+            cameras: Vec::new(),
+            camera: None,
+            preview: None,
+            preview_of: None,
+            timer: Timer::Off,
+            burst: false,
+            pending: None,
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        };
+        app.look_for_cameras(&cc.egui_ctx);
+        app
+    }
+
+    /// Find the cameras plugged in and open the first, unless one is open
+    fn look_for_cameras(&mut self, ctx: &Context) {
+        self.cameras = camera::find_cameras();
+        let open = self.camera.as_ref().is_some_and(|c| c.error().is_none() && self.cameras.iter().any(|p| p == c.path()));
+        if !open {
+            self.camera = None;
+            if let Some(path) = self.cameras.first().cloned() {
+                self.switch_camera(ctx, &path);
+            }
+        }
+    }
+
+    fn switch_camera(&mut self, ctx: &Context, path: &Path) {
+        // The old camera's thread lets go of it before the new one opens
+        self.camera = None;
+        self.camera = Some(Camera::open(ctx, path));
+        self.preview_of = None;
+        self.pending = None;
+    }
+
+    /// Press the shutter: start the timer, or take the picture now
+    fn shutter(&mut self) {
+        if self.camera.is_none() {
+            return;
+        }
+        let shots = if self.burst { BURST_SHOTS } else { 1 };
+        self.message = None;
+        self.pending = Some((shots, Instant::now() + self.timer.delay()));
+    }
+
+    /// Take the pictures that are due
+    fn take_due(&mut self) {
+        let Some((left, due)) = self.pending else { return };
+        if Instant::now() < due {
+            return;
+        }
+        self.save_picture();
+        self.pending = (left > 1).then(|| (left - 1, Instant::now() + BURST_INTERVAL));
+    }
+
+    /// Save the newest frame to Pictures, dithered in full
+    fn save_picture(&mut self) {
+        let Some((_, mut frame)) = self.camera.as_ref().and_then(Camera::latest) else {
+            self.message = Some("no picture from the camera yet".into());
+            return;
+        };
+        dither::atkinson(&mut frame);
+        let path = picture_path(&pictures_dir());
+        match frame.save(&path) {
+            Ok(()) => {
+                slowcore::sound::click();
+                let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                self.message = Some(format!("saved {}", name));
+            }
+            Err(e) => self.message = Some(format!("could not save picture: {}", e)),
+        }
+    }
+
+    /// Dither the newest frame to fit `size` points, if it or the size changed
+    fn update_preview(&mut self, ctx: &Context, size: Vec2) {
+        let Some(camera) = &self.camera else { return };
+        let ppp = ctx.pixels_per_point();
+        let fit = [(size.x * ppp) as u32, (size.y * ppp) as u32];
+        let serial = camera.serial();
+        if serial == 0 || self.preview_of == Some((serial, fit)) {
+            return;
+        }
+        let Some((serial, frame)) = camera.latest() else { return };
+        let mut view = fit_frame(&frame, fit);
+        dither::ordered(&mut view);
+        let pixels = view.pixels().map(|p| egui::Color32::from_gray(p.0[0])).collect();
+        let image = ColorImage { size: [view.width() as usize, view.height() as usize], pixels };
+        match &mut self.preview {
+            Some(texture) => texture.set(image, TextureOptions::NEAREST),
+            None => self.preview = Some(ctx.load_texture("slowcam_preview", image, TextureOptions::NEAREST)),
+        }
+        self.preview_of = Some((serial, fit));
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        if ctx.memory(|m| m.focused().is_some()) {
+            return;
+        }
+        if ctx.input(|i| i.key_pressed(Key::Space)) {
+            self.shutter();
+        }
+        if ctx.input(|i| i.key_pressed(Key::Escape)) && self.pending.take().is_some() {
+            self.message = Some("cancelled".into());
+        }
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("camera", |ui| {
+                    if ui.button("take picture  space").clicked() {
+                        self.shutter();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    let current = self.camera.as_ref().map(|c| c.path().to_path_buf());
+                    let mut picked = None;
+                    for path in &self.cameras {
+                        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                        if ui.radio(current.as_ref() == Some(path), name).clicked() {
+                            picked = Some(path.clone());
+                            ui.close_menu();
+                        }
+                    }
+                    if let Some(path) = picked {
+                        self.switch_camera(ui.ctx(), &path);
+                    }
+                    if ui.button("look for cameras").clicked() {
+                        self.look_for_cameras(ui.ctx());
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("timer", |ui| {
+                    for timer in Timer::ALL {
+                        if ui.radio(self.timer == timer, timer.label()).clicked() {
+                            self.timer = timer;
+                            ui.close_menu();
+                        }
+                    }
+                    ui.separator();
+                    if ui.checkbox(&mut self.burst, format!("burst of {}", BURST_SHOTS)).clicked() {
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowcam", "slowCam");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_view(&mut self, ui: &mut egui::Ui) {
+        let Some(camera) = &self.camera else {
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 3.0);
+                ui.label("no camera found");
+                ui.weak("plug in a USB camera, or connect the Pi camera");
+                ui.add_space(8.0);
+                if ui.button("look again").clicked() {
+                    self.look_for_cameras(ui.ctx());
+                }
+            });
+            return;
+        };
+        if let Some(error) = camera.error() {
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 3.0);
+                ui.label("the camera stopped");
+                ui.weak(error);
+                ui.add_space(8.0);
+                if ui.button("look again").clicked() {
+                    self.camera = None;
+                    self.look_for_cameras(ui.ctx());
+                }
+            });
+            return;
+        }
+
+        let area = ui.available_rect_before_wrap();
+        self.update_preview(ui.ctx(), area.size());
+        let Some(texture) = &self.preview else {
+            ui.centered_and_justified(|ui| ui.weak("starting the camera…"));
+            return;
+        };
+        let size = texture.size_vec2() / ui.ctx().pixels_per_point();
+        let rect = Rect::from_center_size(area.center(), size);
+        ui.painter().image(texture.id(), rect, Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)), SlowColors::WHITE);
+        ui.painter().rect_stroke(rect, 0.0, egui::Stroke::new(1.0, SlowColors::BLACK));
+
+        // The seconds left on the timer, over the picture
+        if let Some((_, due)) = self.pending {
+            let left = due.saturating_duration_since(Instant::now());
+            if !left.is_zero() {
+                let text = format!("{}", left.as_secs() + 1);
+                let badge = Rect::from_center_size(rect.center(), Vec2::splat(96.0));
+                ui.painter().rect_filled(badge, 0.0, SlowColors::WHITE);
+                ui.painter().rect_stroke(badge, 0.0, egui::Stroke::new(2.0, SlowColors::BLACK));
+                ui.painter().text(badge.center(), Align2::CENTER_CENTER, text, FontId::proportional(64.0), SlowColors::BLACK);
+            }
+        }
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+/// Shrink `frame` to fit within `fit` pixels, or grow it by whole steps so
+/// the dither pattern stays crisp
+fn fit_frame(frame: &GrayImage, fit: [u32; 2]) -> GrayImage {
+    let (w, h) = frame.dimensions();
+    if w <= fit[0] && h <= fit[1] {
+        let scale = (fit[0] / w).min(fit[1] / h).max(1);
+        return image::imageops::resize(frame, w * scale, h * scale, image::imageops::FilterType::Nearest);
+    }
+    let ratio = (fit[0] as f32 / w as f32).min(fit[1] as f32 / h as f32);
+    let (fw, fh) = (((w as f32 * ratio) as u32).max(1), ((h as f32 * ratio) as u32).max(1));
+    image::imageops::thumbnail(frame, fw, fh)
+}
+
+/// A new file in `dir` named for the time: slowcam-2024-05-01-093012.png,
+/// with -2, -3… for more in the same second
+fn picture_path(dir: &Path) -> PathBuf {
+    let stem = format!("slowcam-{}", Local::now().format("%Y-%m-%d-%H%M%S"));
+    let mut path = dir.join(format!("{}.png", stem));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.png", stem, n));
+        n += 1;
+    }
+    path
+}
+
+impl eframe::App for SlowCamApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowcam") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.handle_keys(ctx);
+        self.take_due();
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| {
+                let name = self.camera.as_ref().map(Camera::name).unwrap_or_else(|| "no camera".into());
+                let burst = if self.burst { format!("  |  burst of {}", BURST_SHOTS) } else { String::new() };
+                format!("{}  |  {}{}", name, self.timer.label(), burst)
+            });
+            status_bar(ui, &status);
+        });
+
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(8.0)))
+            .show(ctx, |ui| self.draw_view(ui));
+
+        self.draw_about(ctx);
+
+        // The camera thread wakes the window for frames; only the
+        // countdown and bursts need waking here
+        if let Some((_, due)) = self.pending {
+            ctx.request_repaint_after(due.saturating_duration_since(Instant::now()).min(COUNTDOWN_TICK));
+        }
+        self.repaint.end_frame(ctx);
+    }
+}
@@ -0,0 +1,195 @@
+//! Camera capture over V4L2
+//!
+//! A thread reads frames from the camera and keeps the newest, in
+//! greyscale, for the window to show and save. Cameras are asked for YUYV,
+//! where every other byte is already the brightness; ones that only send
+//! MJPEG are decoded, and greyscale cameras are taken as they are.
+//!
+//! The Pi camera appears as /dev/video0 with the bcm2835-v4l2 driver
+//! loaded, like any USB camera.
+
+use egui::Context;
+use image::GrayImage;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use v4l::buffer::Type;
+use v4l::io::mmap::Stream;
+use v4l::io::traits::CaptureStream;
+use v4l::video::Capture;
+use v4l::{Device, FourCC};
+
+/// Size asked of the camera; it may choose another
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 480;
+
+/// Buffers the driver fills while a frame is converted
+const BUFFERS: u32 = 4;
+
+/// The window is woken for a new frame at most this often
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What the capture thread hands over
+#[derive(Default)]
+struct Shared {
+    name: String,
+    frame: Option<GrayImage>,
+    /// Counts frames, so the window converts each only once
+    serial: u64,
+    error: Option<String>,
+}
+
+/// A camera being read on its own thread. Dropping it stops the thread.
+pub struct Camera {
+    path: PathBuf,
+    shared: Arc<Mutex<Shared>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Camera {
+    /// Start reading the camera at `path`, waking `ctx` for new frames
+    pub fn open(ctx: &Context, path: &Path) -> Self {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (thread_shared, thread_stop) = (shared.clone(), stop.clone());
+        let (ctx, device) = (ctx.clone(), path.to_path_buf());
+        let _ = std::thread::Builder::new().name("slowcam-capture".into()).spawn(move || {
+            if let Err(e) = capture(&ctx, &device, &thread_shared, &thread_stop) {
+                thread_shared.lock().unwrap_or_else(|e| e.into_inner()).error = Some(e.to_string());
+                ctx.request_repaint();
+            }
+        });
+        Self { path: path.to_path_buf(), shared, stop }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The camera's name, once it has been opened
+    pub fn name(&self) -> String {
+        let shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        if shared.name.is_empty() { self.path.display().to_string() } else { shared.name.clone() }
+    }
+
+    /// The newest frame and its number, if one has arrived
+    pub fn latest(&self) -> Option<(u64, GrayImage)> {
+        let shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        shared.frame.clone().map(|frame| (shared.serial, frame))
+    }
+
+    /// The newest frame's number, 0 before the first
+    pub fn serial(&self) -> u64 {
+        self.shared.lock().unwrap_or_else(|e| e.into_inner()).serial
+    }
+
+    /// Why the camera stopped, if it did
+    pub fn error(&self) -> Option<String> {
+        self.shared.lock().unwrap_or_else(|e| e.into_inner()).error.clone()
+    }
+}
+
+impl Drop for Camera {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The cameras plugged in, /dev/video0 first
+pub fn find_cameras() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir("/dev") else { return Vec::new() };
+    let mut cameras: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|e| {
+            let number = e.file_name().to_str()?.strip_prefix("video")?.parse().ok()?;
+            Some((number, e.path()))
+        })
+        .filter(|(_, path)| is_capture_device(path))
+        .collect();
+    cameras.sort();
+    cameras.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Cameras also add nodes for metadata, which can't give pictures
+fn is_capture_device(path: &Path) -> bool {
+    Device::with_path(path)
+        .and_then(|dev| dev.query_caps())
+        .is_ok_and(|caps| caps.capabilities.contains(v4l::capability::Flags::VIDEO_CAPTURE))
+}
+
+/// Read frames until told to stop or the camera fails
+fn capture(ctx: &Context, path: &Path, shared: &Mutex<Shared>, stop: &AtomicBool) -> std::io::Result<()> {
+    let dev = Device::with_path(path)?;
+    shared.lock().unwrap_or_else(|e| e.into_inner()).name = dev.query_caps()?.card;
+
+    let mut format = dev.format()?;
+    format.width = WIDTH;
+    format.height = HEIGHT;
+    format.fourcc = FourCC::new(b"YUYV");
+    let format = match dev.set_format(&format) {
+        Ok(format) if is_supported(format.fourcc) => format,
+        _ => {
+            let mut mjpeg = format;
+            mjpeg.fourcc = FourCC::new(b"MJPG");
+            dev.set_format(&mjpeg)?
+        }
+    };
+    if !is_supported(format.fourcc) {
+        return Err(std::io::Error::other(format!("the camera sends {}, which slowCam can't read", format.fourcc)));
+    }
+
+    let mut stream = Stream::with_buffers(&dev, Type::VideoCapture, BUFFERS)?;
+    let mut last_wake: Option<Instant> = None;
+    while !stop.load(Ordering::Relaxed) {
+        let (buf, _) = stream.next()?;
+        let Some(frame) = to_grey(buf, format.width, format.height, format.stride, format.fourcc) else {
+            continue;
+        };
+        {
+            let mut shared = shared.lock().unwrap_or_else(|e| e.into_inner());
+            shared.frame = Some(frame);
+            shared.serial += 1;
+        }
+        if last_wake.is_none_or(|t| t.elapsed() >= FRAME_INTERVAL) {
+            last_wake = Some(Instant::now());
+            ctx.request_repaint();
+        }
+    }
+    Ok(())
+}
+
+fn is_supported(fourcc: FourCC) -> bool {
+    [b"YUYV", b"MJPG", b"GREY"].iter().any(|code| fourcc == FourCC::new(code))
+}
+
+/// The brightness of one frame, or `None` if it is short or won't decode
+fn to_grey(buf: &[u8], width: u32, height: u32, stride: u32, fourcc: FourCC) -> Option<GrayImage> {
+    if fourcc == FourCC::new(b"MJPG") {
+        let frame = image::load_from_memory_with_format(buf, image::ImageFormat::Jpeg).ok()?;
+        return Some(frame.to_luma8());
+    }
+    // Bytes per pixel: Y then U or V for YUYV, just Y for GREY
+    let step = if fourcc == FourCC::new(b"YUYV") { 2 } else { 1 };
+    let stride = (stride as usize).max(width as usize * step);
+    let mut grey = Vec::with_capacity(width as usize * height as usize);
+    for row in 0..height as usize {
+        let line = buf.get(row * stride..row * stride + width as usize * step)?;
+        grey.extend(line.iter().step_by(step));
+    }
+    GrayImage::from_raw(width, height, grey)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yuyv_to_grey() {
+        // Two rows of two pixels, each row padded to six bytes
+        let buf = [10, 128, 20, 128, 0, 0, 30, 128, 40, 128, 0, 0];
+        let grey = to_grey(&buf, 2, 2, 6, FourCC::new(b"YUYV")).unwrap();
+        assert_eq!(grey.into_raw(), vec![10, 20, 30, 40]);
+        assert!(to_grey(&buf[..8], 2, 2, 6, FourCC::new(b"YUYV")).is_none());
+    }
+}
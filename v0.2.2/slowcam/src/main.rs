@@ -0,0 +1,30 @@
+//! slowCam - A black and white camera for the Slow Computer
+//!
+//! Shows a USB or Pi camera live, dithered to black and white, and saves
+//! pictures to ~/Pictures, on a timer or in bursts if asked.
+
+mod app;
+mod camera;
+
+use app::SlowCamApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let viewport = window_layout::viewport("slowcam", Placement::RememberLast, [680.0, 560.0])
+        .with_title("slowCam");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowCam",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowCamApp::new(cc))
+        }),
+    )
+}
@@ -0,0 +1,588 @@
+//! slowCards application
+
+use crate::deck::{self, Card, Deck};
+use crate::schedule::{self, Grade};
+use chrono::{Duration, Local, NaiveDate};
+use egui::{Align2, CentralPanel, Context, Key, Rect, Sense, SidePanel, Stroke, TopBottomPanel, Vec2};
+use slowcore::repaint::RepaintController;
+use slowcore::storage::{self, documents_dir};
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, FileDialog, FileDialogResult, WindowAction};
+use std::collections::VecDeque;
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowCards",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "flashcards for slowOS",
+    features: &["spaced repetition (SM-2)", "decks from text or CSV files", "grading from the keyboard", "progress and forecast"],
+    ..AboutInfo::EMPTY
+};
+
+/// Days shown in the forecast of cards coming due
+const FORECAST_DAYS: usize = 14;
+
+/// Days the share of right answers is taken over
+const ACCURACY_DAYS: i64 = 30;
+
+const CHART_HEIGHT: f32 = 56.0;
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Studying the open deck
+struct Review {
+    queue: VecDeque<usize>,
+    showing_answer: bool,
+    answered: usize,
+}
+
+enum Dialog {
+    NewDeck(String),
+    RenameDeck(String),
+    DeleteDeck,
+    /// A card being written: its index, or `None` for a new one
+    EditCard { index: Option<usize>, front: String, back: String },
+    DeleteCard(usize),
+}
+
+pub struct SlowCardsApp {
+    decks: Vec<Deck>,
+    selected: Option<usize>,
+    review: Option<Review>,
+    dialog: Option<Dialog>,
+    file_dialog: Option<FileDialog>,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowCardsApp {
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        let decks = Deck::load_all();
+        let selected = if decks.is_empty() { None } else { Some(0) };
+        Self {
+            decks,
+            selected,
+            review: None,
+            dialog: None,
+            file_dialog: None,
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        }
+    }
+
+    fn deck(&self) -> Option<&Deck> {
+        self.selected.and_then(|i| self.decks.get(i))
+    }
+
+    fn save_deck(&mut self) {
+        let Some(deck) = self.selected.and_then(|i| self.decks.get(i)) else { return };
+        if let Err(e) = deck.save() {
+            self.message = Some(format!("could not save deck: {}", e));
+        }
+    }
+
+    /// Add `deck` under a name no other deck has, and open it
+    fn add_deck(&mut self, mut deck: Deck) {
+        let base = if deck.name.is_empty() { "deck".to_string() } else { deck.name.clone() };
+        deck.name = base.clone();
+        let mut n = 1;
+        while self.decks.iter().any(|d| d.name.eq_ignore_ascii_case(&deck.name)) {
+            n += 1;
+            deck.name = format!("{} {}", base, n);
+        }
+        let name = deck.name.clone();
+        self.decks.push(deck);
+        self.select(&name);
+        self.save_deck();
+    }
+
+    /// Keep the decks in order and `name` open
+    fn select(&mut self, name: &str) {
+        self.decks.sort_by_key(|d| d.name.to_lowercase());
+        self.selected = self.decks.iter().position(|d| d.name == name);
+        self.review = None;
+    }
+
+    fn import(&mut self, path: std::path::PathBuf) {
+        match deck::import(&path) {
+            Ok(cards) => {
+                let count = cards.len();
+                let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+                let mut deck = Deck::new(&name);
+                deck.cards = cards;
+                self.add_deck(deck);
+                self.message = Some(format!("imported {} cards", count));
+            }
+            Err(e) => self.message = Some(format!("could not import: {}", e)),
+        }
+    }
+
+    fn start_review(&mut self) {
+        let Some(deck) = self.deck() else { return };
+        let queue: VecDeque<usize> = deck.queue(today()).into();
+        if queue.is_empty() {
+            self.message = Some("nothing to study today".into());
+            return;
+        }
+        self.message = None;
+        self.review = Some(Review { queue, showing_answer: false, answered: 0 });
+    }
+
+    fn grade(&mut self, grade: Grade) {
+        let (Some(review), Some(deck)) = (&mut self.review, self.selected.and_then(|i| self.decks.get_mut(i))) else { return };
+        if !review.showing_answer {
+            return;
+        }
+        let Some(index) = review.queue.pop_front() else { return };
+        deck.answer(index, grade, today());
+        review.answered += 1;
+        review.showing_answer = false;
+        // Forgotten cards come round again before the end
+        if !grade.is_pass() {
+            review.queue.push_back(index);
+        }
+        if review.queue.is_empty() {
+            self.review = None;
+            self.message = Some("done for today".into());
+        }
+        self.save_deck();
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        if self.dialog.is_some() || self.file_dialog.is_some() || self.show_about || ctx.memory(|m| m.focused().is_some()) {
+            return;
+        }
+        let Some(review) = &mut self.review else {
+            if ctx.input(|i| i.modifiers.command && i.key_pressed(Key::N)) && self.deck().is_some() {
+                self.dialog = Some(Dialog::EditCard { index: None, front: String::new(), back: String::new() });
+            } else if ctx.input(|i| i.key_pressed(Key::Enter)) {
+                self.start_review();
+            }
+            return;
+        };
+        if ctx.input(|i| i.key_pressed(Key::Escape)) {
+            self.review = None;
+            return;
+        }
+        if !review.showing_answer {
+            if ctx.input(|i| i.key_pressed(Key::Space) || i.key_pressed(Key::Enter)) {
+                review.showing_answer = true;
+            }
+            return;
+        }
+        let keys = [Key::Num1, Key::Num2, Key::Num3, Key::Num4];
+        if let Some(grade) = keys.iter().zip(Grade::ALL).find(|(key, _)| ctx.input(|i| i.key_pressed(**key))).map(|(_, g)| g) {
+            self.grade(grade);
+        } else if ctx.input(|i| i.key_pressed(Key::Space) || i.key_pressed(Key::Enter)) {
+            self.grade(Grade::Good);
+        }
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let has_deck = self.deck().is_some();
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("deck", |ui| {
+                    if ui.button("new deck…").clicked() {
+                        self.dialog = Some(Dialog::NewDeck(String::new()));
+                        ui.close_menu();
+                    }
+                    if ui.button("import…").clicked() {
+                        self.file_dialog = Some(FileDialog::open(documents_dir()).title("import cards").filter(&["txt", "csv"]));
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.add_enabled(has_deck, egui::Button::new("add card     ⌘N")).clicked() {
+                        self.dialog = Some(Dialog::EditCard { index: None, front: String::new(), back: String::new() });
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(has_deck, egui::Button::new("rename…")).clicked() {
+                        self.dialog = self.deck().map(|d| Dialog::RenameDeck(d.name.clone()));
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(has_deck, egui::Button::new("delete deck…")).clicked() {
+                        self.dialog = Some(Dialog::DeleteDeck);
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowcards", "slowCards");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_decks(&mut self, ui: &mut egui::Ui) {
+        let today = today();
+        let mut picked = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if self.decks.is_empty() {
+                ui.weak("no decks yet");
+            }
+            for (i, deck) in self.decks.iter().enumerate() {
+                let label = match deck.to_study(today) {
+                    0 => deck.name.clone(),
+                    n => format!("{}  ({})", deck.name, n),
+                };
+                if ui.selectable_label(self.selected == Some(i), label).clicked() {
+                    picked = Some(i);
+                }
+            }
+        });
+        if let Some(i) = picked {
+            self.selected = Some(i);
+            self.review = None;
+            self.message = None;
+        }
+    }
+
+    fn draw_overview(&mut self, ui: &mut egui::Ui) {
+        let today = today();
+        let Some(deck) = self.deck() else {
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 3.0);
+                if ui.button("new deck").clicked() {
+                    self.dialog = Some(Dialog::NewDeck(String::new()));
+                }
+                ui.weak("or import one from a text or CSV file");
+            });
+            return;
+        };
+        let counts = deck.counts(today);
+        let to_study = deck.to_study(today);
+        let today_stats = deck.history.get(&today).copied().unwrap_or_default();
+        let since = today - Duration::days(ACCURACY_DAYS);
+        let (reviewed, correct) = deck.history.range(since..).fold((0, 0), |(r, c), (_, d)| (r + d.reviewed, c + d.correct));
+        let forecast = deck.forecast(today, FORECAST_DAYS);
+
+        ui.label(egui::RichText::new(&deck.name).strong().size(16.0));
+        ui.weak(format!(
+            "{} cards: {} new, {} learning, {} learned",
+            deck.cards.len(),
+            counts.new,
+            counts.learning,
+            counts.mature
+        ));
+        ui.add_space(8.0);
+        let (study, add) = ui
+            .horizontal(|ui| {
+                let study = ui.add_enabled(to_study > 0, egui::Button::new(format!("study now ({})", to_study))).clicked();
+                (study, ui.button("add card").clicked())
+            })
+            .inner;
+        ui.add_space(12.0);
+
+        ui.label(egui::RichText::new("progress").strong());
+        ui.label(format!("today: {} reviewed, {} of them new", today_stats.reviewed, today_stats.new));
+        if reviewed > 0 {
+            ui.label(format!("last {} days: {} reviews, {}% right", ACCURACY_DAYS, reviewed, correct * 100 / reviewed));
+        }
+        ui.add_space(4.0);
+        ui.weak(format!("coming due over the next {} days", FORECAST_DAYS));
+        draw_bars(ui, &forecast);
+        ui.add_space(12.0);
+
+        ui.label(egui::RichText::new("cards").strong());
+        let mut edit = None;
+        let mut delete = None;
+        egui::ScrollArea::vertical().id_source("cards").show(ui, |ui| {
+            for (i, card) in deck.cards.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.small_button("edit").clicked() {
+                        edit = Some(i);
+                    }
+                    if ui.small_button("delete").clicked() {
+                        delete = Some(i);
+                    }
+                    let due = match card.due {
+                        None => "new".to_string(),
+                        Some(due) if due <= today => "due".to_string(),
+                        Some(due) => format!("in {}", schedule::describe_days((due - today).num_days() as u32)),
+                    };
+                    ui.weak(due);
+                    ui.label(format!("{}  —  {}", first_line(&card.front), first_line(&card.back)));
+                });
+            }
+        });
+        if study {
+            self.start_review();
+        }
+        if add {
+            self.dialog = Some(Dialog::EditCard { index: None, front: String::new(), back: String::new() });
+        }
+        if let Some(i) = edit {
+            let card = &self.decks[self.selected.unwrap_or_default()].cards[i];
+            self.dialog = Some(Dialog::EditCard { index: Some(i), front: card.front.clone(), back: card.back.clone() });
+        }
+        if let Some(i) = delete {
+            self.dialog = Some(Dialog::DeleteCard(i));
+        }
+    }
+
+    fn draw_review(&mut self, ui: &mut egui::Ui) {
+        let (Some(review), Some(deck)) = (&self.review, self.deck()) else { return };
+        let Some(card) = review.queue.front().and_then(|&i| deck.cards.get(i)) else { return };
+        let showing = review.showing_answer;
+        let mut grade = None;
+        let mut reveal = false;
+        ui.vertical_centered(|ui| {
+            ui.add_space(24.0);
+            ui.label(egui::RichText::new(&card.front).size(24.0).color(SlowColors::BLACK));
+            ui.add_space(16.0);
+            if showing {
+                ui.separator();
+                ui.add_space(16.0);
+                ui.label(egui::RichText::new(&card.back).size(20.0).color(SlowColors::BLACK));
+                ui.add_space(24.0);
+                ui.horizontal(|ui| {
+                    ui.add_space((ui.available_width() - 4.0 * 84.0).max(0.0) / 2.0);
+                    for (n, g) in Grade::ALL.into_iter().enumerate() {
+                        let label = format!("{} {}\n{}", n + 1, g.label(), schedule::describe_days(schedule::next_interval(card, g)));
+                        if ui.add_sized(Vec2::new(78.0, 40.0), egui::Button::new(label)).clicked() {
+                            grade = Some(g);
+                        }
+                    }
+                });
+                ui.add_space(8.0);
+                ui.weak("1 to 4 to grade, space for good");
+            } else {
+                if ui.button("show answer").clicked() {
+                    reveal = true;
+                }
+                ui.add_space(8.0);
+                ui.weak("space to show the answer, esc to stop");
+            }
+        });
+        if reveal {
+            if let Some(review) = &mut self.review {
+                review.showing_answer = true;
+            }
+        }
+        if let Some(grade) = grade {
+            self.grade(grade);
+        }
+    }
+
+    fn draw_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.dialog else { return };
+        let title = match dialog {
+            Dialog::NewDeck(_) => "new deck",
+            Dialog::RenameDeck(_) => "rename deck",
+            Dialog::DeleteDeck => "delete deck",
+            Dialog::EditCard { index: None, .. } => "add card",
+            Dialog::EditCard { .. } => "edit card",
+            Dialog::DeleteCard(_) => "delete card",
+        };
+        let mut ok = false;
+        let mut cancel = false;
+        let resp = egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                let action = match dialog {
+                    Dialog::NewDeck(name) | Dialog::RenameDeck(name) => {
+                        ui.label("name:");
+                        let field = ui.text_edit_singleline(name);
+                        if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                            ok = true;
+                        }
+                        "save"
+                    }
+                    Dialog::EditCard { front, back, .. } => {
+                        ui.label("front:");
+                        ui.add(egui::TextEdit::multiline(front).desired_rows(2).desired_width(280.0));
+                        ui.label("back:");
+                        ui.add(egui::TextEdit::multiline(back).desired_rows(3).desired_width(280.0));
+                        "save"
+                    }
+                    Dialog::DeleteDeck => {
+                        ui.label("delete this deck and all its cards?");
+                        ui.weak("this can't be undone");
+                        "delete"
+                    }
+                    Dialog::DeleteCard(_) => {
+                        ui.label("delete this card?");
+                        "delete"
+                    }
+                };
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        cancel = true;
+                    }
+                    if ui.button(action).clicked() {
+                        ok = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+
+        if cancel {
+            self.dialog = None;
+        } else if ok {
+            if let Some(dialog) = self.dialog.take() {
+                self.apply(dialog);
+            }
+        }
+    }
+
+    fn apply(&mut self, dialog: Dialog) {
+        let selected = self.selected.filter(|&i| i < self.decks.len());
+        match (dialog, selected) {
+            (Dialog::NewDeck(name), _) if !storage::clean_name(&name).is_empty() => self.add_deck(Deck::new(&name)),
+            (Dialog::RenameDeck(name), Some(i)) => match self.decks[i].rename(&name) {
+                Ok(()) => {
+                    let name = self.decks[i].name.clone();
+                    self.select(&name);
+                }
+                Err(e) => self.message = Some(format!("could not rename: {}", e)),
+            },
+            (Dialog::DeleteDeck, Some(i)) => match self.decks[i].delete() {
+                Ok(()) => {
+                    self.decks.remove(i);
+                    self.selected = if self.decks.is_empty() { None } else { Some(i.min(self.decks.len() - 1)) };
+                    self.review = None;
+                }
+                Err(e) => self.message = Some(format!("could not delete deck: {}", e)),
+            },
+            (Dialog::EditCard { index, front, back }, Some(i)) => {
+                if front.trim().is_empty() || back.trim().is_empty() {
+                    self.message = Some("a card needs a front and a back".into());
+                    return;
+                }
+                let cards = &mut self.decks[i].cards;
+                match index.and_then(|c| cards.get_mut(c)) {
+                    Some(card) => {
+                        card.front = front.trim().into();
+                        card.back = back.trim().into();
+                    }
+                    None => cards.push(Card::new(&front, &back)),
+                }
+                self.save_deck();
+            }
+            (Dialog::DeleteCard(c), Some(i)) if c < self.decks[i].cards.len() => {
+                self.decks[i].cards.remove(c);
+                self.save_deck();
+            }
+            _ => {}
+        }
+    }
+
+    fn draw_file_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.file_dialog else { return };
+        match dialog.show(ctx) {
+            FileDialogResult::Picked(path) => {
+                self.file_dialog = None;
+                self.import(path);
+            }
+            FileDialogResult::Cancelled => self.file_dialog = None,
+            FileDialogResult::Pending => {}
+        }
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+/// The first line of a card side, for the list
+fn first_line(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("");
+    match line.char_indices().nth(40) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None if text.lines().nth(1).is_some() => format!("{}…", line),
+        None => line.to_string(),
+    }
+}
+
+/// A bar per value, tallest to the top, with the count over each bar
+fn draw_bars(ui: &mut egui::Ui, values: &[usize]) {
+    let width = ui.available_width().min(360.0);
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(width, CHART_HEIGHT), Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.line_segment([rect.left_bottom(), rect.right_bottom()], Stroke::new(1.0, SlowColors::BLACK));
+    let max = values.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let slot = rect.width() / values.len().max(1) as f32;
+    for (i, &value) in values.iter().enumerate() {
+        if value == 0 {
+            continue;
+        }
+        let height = (rect.height() - 14.0) * value as f32 / max;
+        let bar = Rect::from_min_max(
+            egui::pos2(rect.left() + i as f32 * slot + 2.0, rect.bottom() - height),
+            egui::pos2(rect.left() + (i + 1) as f32 * slot - 2.0, rect.bottom()),
+        );
+        // Today's bar solid, the days after dithered
+        if i == 0 {
+            painter.rect_filled(bar, 0.0, SlowColors::BLACK);
+        } else {
+            slowcore::dither::draw_dither_rect(&painter, bar, SlowColors::BLACK, 1);
+            painter.rect_stroke(bar, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+        }
+        painter.text(egui::pos2(bar.center().x, bar.top() - 2.0), Align2::CENTER_BOTTOM, value.to_string(), egui::FontId::proportional(10.0), SlowColors::BLACK);
+    }
+}
+
+impl eframe::App for SlowCardsApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowcards") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.handle_keys(ctx);
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| match (&self.review, self.deck()) {
+                (Some(review), _) => format!("{} answered  |  {} to go", review.answered, review.queue.len()),
+                (None, Some(deck)) => format!("{}  |  {} to study today", deck.name, deck.to_study(today())),
+                (None, None) => format!("{} decks", self.decks.len()),
+            });
+            status_bar(ui, &status);
+        });
+
+        SidePanel::left("decks")
+            .resizable(false)
+            .exact_width(170.0)
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(6.0)))
+            .show(ctx, |ui| self.draw_decks(ui));
+
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(12.0)))
+            .show(ctx, |ui| {
+                if self.review.is_some() {
+                    self.draw_review(ui);
+                } else {
+                    self.draw_overview(ui);
+                }
+            });
+
+        self.draw_dialog(ctx);
+        self.draw_file_dialog(ctx);
+        self.draw_about(ctx);
+        self.repaint.end_frame(ctx);
+    }
+}
@@ -0,0 +1,280 @@
+//! Decks of cards, kept as one JSON file per deck in
+//! ~/.config/slowcards/decks, and read in from plain text or CSV
+//!
+//! A plain text file has a card per line, front and back split by a tab
+//! or by " :: ". A CSV file has the front in its first column and the back
+//! in its second; quoted fields may hold commas and line breaks.
+
+use crate::schedule::{self, Grade, MATURE_DAYS, START_EASE};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use slowcore::storage::{clean_name, config_dir};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// New cards brought into review each day, per deck
+pub const NEW_PER_DAY: u32 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Card {
+    pub front: String,
+    pub back: String,
+    /// When it next comes up; `None` until first studied
+    pub due: Option<NaiveDate>,
+    /// Days between the last two reviews
+    pub interval: u32,
+    pub ease: f32,
+    /// Right answers in a row
+    pub reps: u32,
+    /// Times forgotten after being learned
+    pub lapses: u32,
+}
+
+impl Card {
+    pub fn new(front: &str, back: &str) -> Self {
+        Self { front: front.trim().into(), back: back.trim().into(), due: None, interval: 0, ease: START_EASE, reps: 0, lapses: 0 }
+    }
+
+    pub fn is_new(&self) -> bool {
+        self.due.is_none()
+    }
+}
+
+/// Reviews on one day
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct DayStats {
+    pub reviewed: u32,
+    pub correct: u32,
+    /// Cards seen for the first time
+    pub new: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Deck {
+    #[serde(skip)]
+    pub name: String,
+    pub cards: Vec<Card>,
+    #[serde(default)]
+    pub history: BTreeMap<NaiveDate, DayStats>,
+}
+
+/// How many cards of a deck are where
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Counts {
+    pub new: usize,
+    pub learning: usize,
+    pub mature: usize,
+    pub due: usize,
+}
+
+pub fn decks_dir() -> PathBuf {
+    config_dir("slowcards").join("decks")
+}
+
+fn deck_path(name: &str) -> PathBuf {
+    decks_dir().join(format!("{}.json", name))
+}
+
+impl Deck {
+    pub fn new(name: &str) -> Self {
+        Self { name: clean_name(name), ..Default::default() }
+    }
+
+    /// Every deck, by name
+    pub fn load_all() -> Vec<Deck> {
+        let mut decks: Vec<Deck> = std::fs::read_dir(decks_dir())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .filter_map(|path| {
+                let mut deck: Deck = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+                deck.name = path.file_stem()?.to_string_lossy().into_owned();
+                Some(deck)
+            })
+            .collect();
+        decks.sort_by_key(|d| d.name.to_lowercase());
+        decks
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        std::fs::create_dir_all(decks_dir()).map_err(|e| e.to_string())?;
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        let path = deck_path(&self.name);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+    }
+
+    pub fn rename(&mut self, name: &str) -> Result<(), String> {
+        let name = clean_name(name);
+        if name.is_empty() || name == self.name {
+            return Ok(());
+        }
+        if deck_path(&name).exists() {
+            return Err(format!("there is already a deck called \"{}\"", name));
+        }
+        std::fs::rename(deck_path(&self.name), deck_path(&name)).map_err(|e| e.to_string())?;
+        self.name = name;
+        Ok(())
+    }
+
+    pub fn delete(&self) -> Result<(), String> {
+        std::fs::remove_file(deck_path(&self.name)).map_err(|e| e.to_string())
+    }
+
+    pub fn counts(&self, today: NaiveDate) -> Counts {
+        let mut counts = Counts::default();
+        for card in &self.cards {
+            match card.due {
+                None => counts.new += 1,
+                Some(due) => {
+                    if card.interval >= MATURE_DAYS { counts.mature += 1 } else { counts.learning += 1 }
+                    if due <= today {
+                        counts.due += 1;
+                    }
+                }
+            }
+        }
+        counts
+    }
+
+    /// New cards still to be brought in today
+    fn new_left(&self, today: NaiveDate) -> usize {
+        NEW_PER_DAY.saturating_sub(self.history.get(&today).map_or(0, |d| d.new)) as usize
+    }
+
+    /// Cards to study today: the due ones, most overdue first, then new ones
+    pub fn queue(&self, today: NaiveDate) -> Vec<usize> {
+        let mut due: Vec<usize> = (0..self.cards.len()).filter(|&i| self.cards[i].due.is_some_and(|d| d <= today)).collect();
+        due.sort_by_key(|&i| self.cards[i].due);
+        due.extend((0..self.cards.len()).filter(|&i| self.cards[i].is_new()).take(self.new_left(today)));
+        due
+    }
+
+    /// How many study today would bring up
+    pub fn to_study(&self, today: NaiveDate) -> usize {
+        let counts = self.counts(today);
+        counts.due + counts.new.min(self.new_left(today))
+    }
+
+    /// Grade card `index` and note it in today's history
+    pub fn answer(&mut self, index: usize, grade: Grade, today: NaiveDate) {
+        let Some(card) = self.cards.get_mut(index) else { return };
+        let day = self.history.entry(today).or_default();
+        day.reviewed += 1;
+        if grade.is_pass() {
+            day.correct += 1;
+        }
+        if card.is_new() {
+            day.new += 1;
+        }
+        schedule::review(card, grade, today);
+    }
+
+    /// Cards coming due on each of the `days` days from `today`; the
+    /// first day includes any overdue
+    pub fn forecast(&self, today: NaiveDate, days: usize) -> Vec<usize> {
+        let mut counts = vec![0; days];
+        for due in self.cards.iter().filter_map(|c| c.due) {
+            let ahead = (due - today).num_days().max(0) as usize;
+            if let Some(count) = counts.get_mut(ahead) {
+                *count += 1;
+            }
+        }
+        counts
+    }
+}
+
+/// Cards from a plain text or CSV file
+pub fn import(path: &Path) -> Result<Vec<Card>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let is_csv = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+    let pairs = if is_csv { parse_csv(&text) } else { parse_text(&text) };
+    let cards: Vec<Card> = pairs
+        .into_iter()
+        .filter(|(front, back)| !front.trim().is_empty() && !back.trim().is_empty())
+        .map(|(front, back)| Card::new(&front, &back))
+        .collect();
+    if cards.is_empty() {
+        return Err("no cards found; put the front and back on one line, split by a tab or \" :: \"".into());
+    }
+    Ok(cards)
+}
+
+fn parse_text(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| line.split_once('\t').or_else(|| line.split_once(" :: ")))
+        .map(|(front, back)| (front.to_string(), back.to_string()))
+        .collect()
+}
+
+fn parse_csv(text: &str) -> Vec<(String, String)> {
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            '\r' if !quoted => {}
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    // A header row naming the columns isn't a card
+    if rows.first().is_some_and(|r| r.first().is_some_and(|f| f.trim().eq_ignore_ascii_case("front"))) {
+        rows.remove(0);
+    }
+    rows.into_iter()
+        .filter(|r| r.len() >= 2)
+        .map(|mut r| {
+            let back = r.swap_remove(1);
+            (r.swap_remove(0), back)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_imports() {
+        let text = "# french\nbonjour\thello\nmerci :: thank you\nno separator here\n";
+        assert_eq!(parse_text(text), vec![("bonjour".into(), "hello".into()), ("merci".into(), "thank you".into())]);
+
+        let csv = "front,back\r\nchat,cat\r\n\"un, deux\",\"one, two\"\n\"dire \"\"oui\"\"\",\"to say\nyes\",extra\n";
+        assert_eq!(
+            parse_csv(csv),
+            vec![
+                ("chat".into(), "cat".into()),
+                ("un, deux".into(), "one, two".into()),
+                ("dire \"oui\"".into(), "to say\nyes".into()),
+            ]
+        );
+
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let mut deck = Deck::new("french");
+        deck.cards = (0..30).map(|i| Card::new(&i.to_string(), "x")).collect();
+        assert_eq!(deck.queue(today).len(), NEW_PER_DAY as usize);
+        deck.answer(0, Grade::Good, today);
+        assert_eq!(deck.to_study(today), NEW_PER_DAY as usize - 1);
+        assert_eq!(deck.forecast(today, 3), vec![0, 1, 0]);
+    }
+}
@@ -0,0 +1,32 @@
+//! slowCards - Flashcards for the Slow Computer
+//!
+//! Decks of cards studied with spaced repetition: each card comes back
+//! just before it would be forgotten. Decks can be written in the app or
+//! imported from plain text and CSV files, and reviewed from the keyboard.
+
+mod app;
+mod deck;
+mod schedule;
+
+use app::SlowCardsApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let viewport = window_layout::viewport("slowcards", Placement::RememberLast, [640.0, 480.0])
+        .with_title("slowCards");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowCards",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowCardsApp::new(cc))
+        }),
+    )
+}
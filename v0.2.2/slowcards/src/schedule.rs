@@ -0,0 +1,120 @@
+//! When each card comes back: the SM-2 method
+//!
+//! A card answered well comes back after 1 day, then 6, then each time
+//! after the last gap times its ease. The ease starts at 2.5, goes up a
+//! little for easy answers and down for hard ones, never below 1.3. A
+//! forgotten card starts again at 1 day, its ease kept.
+
+use crate::deck::Card;
+use chrono::{Duration, NaiveDate};
+
+pub const START_EASE: f32 = 2.5;
+const MIN_EASE: f32 = 1.3;
+
+/// Cards with a gap this long or longer count as learned
+pub const MATURE_DAYS: u32 = 21;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Grade {
+    pub const ALL: [Grade; 4] = [Grade::Again, Grade::Hard, Grade::Good, Grade::Easy];
+
+    /// SM-2's 0–5 answer quality; below 3 is forgotten
+    fn quality(self) -> f32 {
+        match self {
+            Grade::Again => 1.0,
+            Grade::Hard => 3.0,
+            Grade::Good => 4.0,
+            Grade::Easy => 5.0,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Grade::Again => "again",
+            Grade::Hard => "hard",
+            Grade::Good => "good",
+            Grade::Easy => "easy",
+        }
+    }
+
+    pub fn is_pass(self) -> bool {
+        self != Grade::Again
+    }
+}
+
+/// Days until `card` comes back if answered `grade`
+pub fn next_interval(card: &Card, grade: Grade) -> u32 {
+    if !grade.is_pass() {
+        return 1;
+    }
+    match card.reps {
+        0 => 1,
+        1 => 6,
+        _ => ((card.interval as f32 * next_ease(card.ease, grade)).round() as u32).max(card.interval + 1),
+    }
+}
+
+fn next_ease(ease: f32, grade: Grade) -> f32 {
+    let miss = 5.0 - grade.quality();
+    (ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MIN_EASE)
+}
+
+/// Answer `card` on `today`
+pub fn review(card: &mut Card, grade: Grade, today: NaiveDate) {
+    card.interval = next_interval(card, grade);
+    if grade.is_pass() {
+        card.ease = next_ease(card.ease, grade);
+        card.reps += 1;
+    } else {
+        if card.reps > 0 {
+            card.lapses += 1;
+        }
+        card.reps = 0;
+    }
+    card.due = Some(today + Duration::days(card.interval as i64));
+}
+
+/// "1d", "3w", "2mo", "1y": how soon, for the grade buttons
+pub fn describe_days(days: u32) -> String {
+    match days {
+        0..=13 => format!("{}d", days),
+        14..=59 => format!("{}w", days / 7),
+        60..=364 => format!("{}mo", days / 30),
+        _ => format!("{:.1}y", days as f32 / 365.0).replace(".0y", "y"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sm2_intervals() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let mut card = Card::new("bonjour", "hello");
+        review(&mut card, Grade::Good, today);
+        assert_eq!((card.interval, card.due), (1, Some(today + Duration::days(1))));
+        review(&mut card, Grade::Good, today);
+        assert_eq!(card.interval, 6);
+        // Good keeps the ease where it was
+        assert!((card.ease - START_EASE).abs() < 1e-4);
+        review(&mut card, Grade::Good, today);
+        assert_eq!(card.interval, 15);
+        assert_eq!(next_interval(&card, Grade::Hard), 35);
+
+        review(&mut card, Grade::Again, today);
+        assert_eq!((card.interval, card.reps, card.lapses), (1, 0, 1));
+        for _ in 0..10 {
+            review(&mut card, Grade::Hard, today);
+        }
+        assert_eq!(card.ease, MIN_EASE);
+        assert_eq!(describe_days(45), "6w");
+    }
+}
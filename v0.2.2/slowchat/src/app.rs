@@ -0,0 +1,450 @@
+//! slowChat application
+
+use crate::chat::{self, Buddy, Message, Received};
+use egui::{Align, Align2, CentralPanel, Context, Key, Layout, SidePanel, TopBottomPanel, Vec2};
+use slowcore::lan::{Device, Presence};
+use slowcore::repaint::RepaintController;
+use slowcore::storage::documents_dir;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, FileDialog, FileDialogResult, SlowButton, WindowAction};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowChat",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "talk to slowbooks in the same room",
+    features: &["finds others on the local network", "text and small files", "history kept on this computer only"],
+    ..AboutInfo::EMPTY
+};
+
+/// What a background send reports back
+struct SendFailed {
+    buddy: String,
+    error: String,
+}
+
+pub struct SlowChatApp {
+    ctx: Context,
+    me: Device,
+    presence: Option<Presence>,
+    buddies: Vec<Buddy>,
+    selected: Option<String>,
+    /// Conversations loaded so far, by buddy id
+    histories: HashMap<String, Vec<Message>>,
+    unread: HashSet<String>,
+    draft: String,
+    incoming: Receiver<Received>,
+    send_errors: Sender<SendFailed>,
+    send_results: Receiver<SendFailed>,
+    file_dialog: Option<FileDialog>,
+    /// Name being edited in the "your name" dialog
+    rename: Option<String>,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowChatApp {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let ctx = cc.egui_ctx.clone();
+        let (tx, incoming) = mpsc::channel();
+        let (send_errors, send_results) = mpsc::channel();
+
+        let mut message = None;
+        let presence = match chat::listen(tx, ctx.clone()) {
+            Ok(port) => match Presence::start("slowchat", port, ctx.clone()) {
+                Ok(presence) => Some(presence),
+                Err(e) => {
+                    message = Some(format!("can't look for others: {}", e));
+                    None
+                }
+            },
+            Err(e) => {
+                message = Some(format!("can't receive messages: {}", e));
+                None
+            }
+        };
+
+        Self {
+            ctx,
+            me: slowcore::lan::device(),
+            presence,
+            buddies: chat::load_buddies(),
+            selected: None,
+            histories: HashMap::new(),
+            unread: HashSet::new(),
+            draft: String::new(),
+            incoming,
+            send_errors,
+            send_results,
+            file_dialog: None,
+            rename: None,
+            message,
+            show_about: false,
+            repaint: RepaintController::new(),
+        }
+    }
+
+    fn history(&mut self, id: &str) -> &mut Vec<Message> {
+        self.histories.entry(id.to_string()).or_insert_with(|| chat::load_history(id))
+    }
+
+    fn push_message(&mut self, id: &str, message: Message) {
+        let history = self.history(id);
+        history.push(message);
+        chat::save_history(id, history);
+    }
+
+    /// Remember a buddy, updating their name if it changed
+    fn know(&mut self, buddy: &Buddy) {
+        match self.buddies.iter_mut().find(|b| b.id == buddy.id) {
+            Some(known) if known.name == buddy.name => return,
+            Some(known) => known.name = buddy.name.clone(),
+            None => self.buddies.push(buddy.clone()),
+        }
+        chat::save_buddies(&self.buddies);
+    }
+
+    fn poll(&mut self, ctx: &Context) {
+        while let Ok(received) = self.incoming.try_recv() {
+            let Received { from, message } = received;
+            self.know(&from);
+            let focused = ctx.input(|i| i.viewport().focused.unwrap_or(true));
+            if !focused || self.selected.as_deref() != Some(from.id.as_str()) {
+                self.unread.insert(from.id.clone());
+                match &message.file {
+                    Some(path) => {
+                        let preview = format!("{} sent you {}", from.name, message.text);
+                        slowcore::notify::post_with_reveal("slowchat", &preview, path);
+                    }
+                    None => slowcore::notify::post("slowchat", &format!("{}: {}", from.name, message.text)),
+                }
+            }
+            self.push_message(&from.id, message);
+        }
+        while let Ok(failed) = self.send_results.try_recv() {
+            self.message = Some(format!("couldn't reach {}: {}", failed.buddy, failed.error));
+        }
+        // Peers we see online become buddies
+        let online = self.presence.as_ref().map(|p| p.peers()).unwrap_or_default();
+        for peer in online {
+            self.know(&Buddy { id: peer.id, name: peer.name });
+        }
+    }
+
+    fn select(&mut self, id: String) {
+        self.unread.remove(&id);
+        self.history(&id);
+        self.selected = Some(id);
+    }
+
+    fn selected_buddy(&self) -> Option<&Buddy> {
+        let id = self.selected.as_ref()?;
+        self.buddies.iter().find(|b| &b.id == id)
+    }
+
+    fn send_draft(&mut self) {
+        let text = self.draft.trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        if self.send(None, text) {
+            self.draft.clear();
+        }
+    }
+
+    /// Send text, or a file when `file` is set; false if the buddy is offline
+    fn send(&mut self, file: Option<PathBuf>, text: String) -> bool {
+        let Some(buddy) = self.selected_buddy().cloned() else { return false };
+        let Some(peer) = self.presence.as_ref().and_then(|p| p.peer(&buddy.id)) else {
+            self.message = Some(format!("{} is not online", buddy.name));
+            return false;
+        };
+        self.push_message(&buddy.id, Message::now(true, text.clone(), file.clone()));
+
+        let errors = self.send_errors.clone();
+        let ctx = self.ctx.clone();
+        std::thread::spawn(move || {
+            let result = match &file {
+                Some(path) => chat::send_file(peer.addr, path),
+                None => chat::send_text(peer.addr, &text),
+            };
+            if let Err(e) = result {
+                let _ = errors.send(SendFailed { buddy: buddy.name, error: e.to_string() });
+                ctx.request_repaint();
+            }
+        });
+        true
+    }
+
+    fn send_file(&mut self, path: PathBuf) {
+        match std::fs::metadata(&path) {
+            Ok(meta) if meta.len() > chat::MAX_FILE => {
+                self.message = Some("files over 10 MB can't be sent in chat".into());
+            }
+            Ok(_) => {
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                self.send(Some(path), name);
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("chat", |ui| {
+                    if ui.add_enabled(self.selected.is_some(), egui::Button::new("send file...")).clicked() {
+                        self.file_dialog = Some(FileDialog::open(documents_dir()).title("send file"));
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("your name...").clicked() {
+                        self.rename = Some(self.me.name.clone());
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowchat", "slowChat");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_buddy_list(&mut self, ctx: &Context) {
+        let online: HashSet<String> = self
+            .presence
+            .as_ref()
+            .map(|p| p.peers().into_iter().map(|p| p.id).collect())
+            .unwrap_or_default();
+        let mut buddies = self.buddies.clone();
+        // Online first, then by name
+        buddies.sort_by_key(|b| (!online.contains(&b.id), b.name.to_lowercase()));
+
+        let mut clicked = None;
+        SidePanel::left("buddies")
+            .resizable(false)
+            .exact_width(150.0)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new("buddies").strong());
+                ui.separator();
+                if buddies.is_empty() {
+                    ui.label("looking for other slowbooks on this network...");
+                }
+                for buddy in &buddies {
+                    let dot = if online.contains(&buddy.id) { "\u{25cf}" } else { "\u{25cb}" };
+                    let unread = if self.unread.contains(&buddy.id) { " *" } else { "" };
+                    let selected = self.selected.as_deref() == Some(buddy.id.as_str());
+                    let label = format!("{} {}{}", dot, buddy.name, unread);
+                    if ui.add(SlowButton::new(&label).selected(selected)).clicked() {
+                        clicked = Some(buddy.id.clone());
+                    }
+                }
+            });
+        if let Some(id) = clicked {
+            self.select(id);
+        }
+    }
+
+    fn draw_conversation(&mut self, ctx: &Context) {
+        let Some(id) = self.selected.clone() else {
+            CentralPanel::default()
+                .frame(egui::Frame::none().fill(SlowColors::WHITE))
+                .show(ctx, |ui| {
+                    ui.centered_and_justified(|ui| ui.label("choose someone to talk to"));
+                });
+            return;
+        };
+
+        let mut send = false;
+        let mut pick_file = false;
+        TopBottomPanel::bottom("compose").show(ctx, |ui| {
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                if ui.add(SlowButton::new("file...")).clicked() {
+                    pick_file = true;
+                }
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    if ui.add(SlowButton::new("send")).clicked() {
+                        send = true;
+                    }
+                    let field = ui.add(
+                        egui::TextEdit::singleline(&mut self.draft)
+                            .hint_text("message")
+                            .desired_width(f32::INFINITY),
+                    );
+                    if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                        send = true;
+                        field.request_focus();
+                    }
+                });
+            });
+            ui.add_space(4.0);
+        });
+
+        let me = self.me.name.clone();
+        let them = self.selected_buddy().map(|b| b.name.clone()).unwrap_or_default();
+        let messages = self.history(&id).clone();
+        let mut open_folder = None;
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(8.0)))
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for message in &messages {
+                            let who = if message.from_me { &me } else { &them };
+                            ui.label(egui::RichText::new(who).strong());
+                            match &message.file {
+                                Some(path) => {
+                                    ui.horizontal(|ui| {
+                                        ui.label(format!("file: {}", message.text));
+                                        if !message.from_me && ui.add(SlowButton::new("show")).clicked() {
+                                            open_folder = path.parent().map(|p| p.to_path_buf());
+                                        }
+                                    });
+                                }
+                                None => {
+                                    ui.add(egui::Label::new(&message.text).wrap(true));
+                                }
+                            }
+                            ui.add_space(6.0);
+                        }
+                    });
+            });
+
+        if send {
+            self.send_draft();
+        }
+        if pick_file {
+            self.file_dialog = Some(FileDialog::open(documents_dir()).title("send file"));
+        }
+        if let Some(dir) = open_folder {
+            show_in_files(&dir);
+        }
+    }
+
+    fn draw_file_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.file_dialog else { return };
+        match dialog.show(ctx) {
+            FileDialogResult::Picked(path) => {
+                self.file_dialog = None;
+                self.send_file(path);
+            }
+            FileDialogResult::Cancelled => self.file_dialog = None,
+            FileDialogResult::Pending => {}
+        }
+    }
+
+    fn draw_rename(&mut self, ctx: &Context) {
+        let Some(name) = &mut self.rename else { return };
+        let mut save = false;
+        let mut cancel = false;
+        let resp = egui::Window::new("your name")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("the name others on this network see:");
+                let field = ui.text_edit_singleline(name);
+                if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    save = true;
+                }
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        cancel = true;
+                    }
+                    if ui.button("save").clicked() {
+                        save = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+
+        if save {
+            if let Some(name) = self.rename.take().filter(|n| !n.trim().is_empty()) {
+                self.me = slowcore::lan::set_device_name(&name);
+                if let Some(presence) = &mut self.presence {
+                    let _ = presence.rename();
+                }
+            }
+        } else if cancel {
+            self.rename = None;
+        }
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+/// Open a folder in slowFiles
+fn show_in_files(dir: &std::path::Path) {
+    let sibling = std::env::current_exe().ok().and_then(|e| e.parent().map(|p| p.join("slowfiles")));
+    let binary = sibling.filter(|p| p.exists()).unwrap_or_else(|| PathBuf::from("slowfiles"));
+    let _ = std::process::Command::new(binary)
+        .arg(dir)
+        .env("SLOWOS_MANAGED", "1")
+        .stdin(std::process::Stdio::null())
+        .spawn();
+}
+
+impl eframe::App for SlowChatApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowchat") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.poll(ctx);
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::top("title_bar").show(ctx, |ui| {
+            slowcore::theme::SlowTheme::title_bar_frame().show(ui, |ui| {
+                ui.centered_and_justified(|ui| {
+                    let title = self.selected_buddy().map(|b| b.name.clone()).unwrap_or_else(|| "slowChat".into());
+                    ui.label(title);
+                });
+            });
+        });
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| {
+                let online = self.presence.as_ref().map(|p| p.peers().len()).unwrap_or(0);
+                format!("you are {}  |  {} online", self.me.name, online)
+            });
+            status_bar(ui, &status);
+        });
+
+        self.draw_buddy_list(ctx);
+        self.draw_conversation(ctx);
+        self.draw_file_dialog(ctx);
+        self.draw_rename(ctx);
+        self.draw_about(ctx);
+        self.repaint.end_frame(ctx);
+    }
+}
@@ -0,0 +1,203 @@
+//! Messages on the wire and on disk
+//!
+//! One TCP connection per message: a JSON header line, then for files the
+//! raw bytes. Peers are found with `slowcore::lan`, so the listener picks
+//! any free port and mDNS tells everyone which.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// "Small files": anything bigger belongs in slowDrop
+pub const MAX_FILE: u64 = 10 * 1024 * 1024;
+
+/// Longest header line accepted
+const MAX_HEADER: u64 = 64 * 1024;
+
+/// Messages kept per conversation
+const HISTORY_LEN: usize = 500;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Header {
+    Text { from: String, name: String, text: String },
+    File { from: String, name: String, file_name: String, size: u64 },
+}
+
+/// Someone we've talked to
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Buddy {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Message {
+    pub from_me: bool,
+    /// The text, or the file's name
+    pub text: String,
+    /// Where a received file was saved, or the file that was sent
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+    /// Unix seconds
+    pub time: u64,
+}
+
+impl Message {
+    pub fn now(from_me: bool, text: String, file: Option<PathBuf>) -> Self {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Self { from_me, text, file, time }
+    }
+}
+
+/// A message that came in over the network
+pub struct Received {
+    pub from: Buddy,
+    pub message: Message,
+}
+
+fn data_dir() -> PathBuf {
+    slowcore::storage::config_dir("slowchat")
+}
+
+pub fn load_buddies() -> Vec<Buddy> {
+    std::fs::read_to_string(data_dir().join("buddies.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_buddies(buddies: &[Buddy]) {
+    let _ = std::fs::create_dir_all(data_dir());
+    if let Ok(json) = serde_json::to_string_pretty(buddies) {
+        let _ = std::fs::write(data_dir().join("buddies.json"), json);
+    }
+}
+
+fn history_path(id: &str) -> PathBuf {
+    // Ids are hex, but don't trust what came over the network
+    let safe: String = id.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    data_dir().join("history").join(format!("{}.json", safe))
+}
+
+pub fn load_history(id: &str) -> Vec<Message> {
+    std::fs::read_to_string(history_path(id))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_history(id: &str, messages: &mut Vec<Message>) {
+    if messages.len() > HISTORY_LEN {
+        messages.drain(..messages.len() - HISTORY_LEN);
+    }
+    let path = history_path(id);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string(messages) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+fn connect(addr: SocketAddr) -> io::Result<TcpStream> {
+    let stream = TcpStream::connect_timeout(&addr, Duration::from_secs(5))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    Ok(stream)
+}
+
+fn write_header(stream: &mut TcpStream, header: &Header) -> io::Result<()> {
+    let mut line = serde_json::to_string(header).map_err(io::Error::other)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+pub fn send_text(addr: SocketAddr, text: &str) -> io::Result<()> {
+    let me = slowcore::lan::device();
+    let mut stream = connect(addr)?;
+    write_header(&mut stream, &Header::Text { from: me.id, name: me.name, text: text.to_string() })
+}
+
+pub fn send_file(addr: SocketAddr, path: &Path) -> io::Result<()> {
+    let size = std::fs::metadata(path)?.len();
+    if size > MAX_FILE {
+        return Err(io::Error::other("file is larger than 10 MB"));
+    }
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "file".into());
+    let me = slowcore::lan::device();
+    let mut stream = connect(addr)?;
+    write_header(&mut stream, &Header::File { from: me.id, name: me.name, file_name, size })?;
+    io::copy(&mut std::fs::File::open(path)?, &mut stream)?;
+    Ok(())
+}
+
+fn receive(stream: TcpStream) -> io::Result<Received> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    (&mut reader).take(MAX_HEADER).read_line(&mut line)?;
+    let header: Header = serde_json::from_str(&line).map_err(io::Error::other)?;
+    match header {
+        Header::Text { from, name, text } => Ok(Received { from: Buddy { id: from, name }, message: Message::now(false, text, None) }),
+        Header::File { from, name, file_name, size } => {
+            if size > MAX_FILE {
+                return Err(io::Error::other("file too large"));
+            }
+            // Only the name; never a path from the sender
+            let file_name = Path::new(&file_name)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .filter(|n| !n.starts_with('.'))
+                .unwrap_or_else(|| "received file".into());
+            let mut bytes = Vec::with_capacity(size as usize);
+            reader.take(size).read_to_end(&mut bytes)?;
+            if bytes.len() as u64 != size {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "transfer cut short"));
+            }
+            let path = slowcore::storage::unique_path(&slowcore::storage::downloads_dir(), &file_name);
+            std::fs::write(&path, bytes)?;
+            Ok(Received { from: Buddy { id: from, name }, message: Message::now(false, file_name, Some(path)) })
+        }
+    }
+}
+
+/// Accept messages in the background, returning the port listened on.
+/// Connections from outside the local network are ignored.
+pub fn listen(tx: Sender<Received>, ctx: egui::Context) -> io::Result<u16> {
+    let listener = TcpListener::bind(("0.0.0.0", 0))?;
+    let port = listener.local_addr()?.port();
+    std::thread::Builder::new().name("slowchat-listen".into()).spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if !stream.peer_addr().is_ok_and(|a| slowcore::lan::is_local(a.ip())) {
+                continue;
+            }
+            let tx = tx.clone();
+            let ctx = ctx.clone();
+            std::thread::spawn(move || {
+                if let Ok(received) = receive(stream) {
+                    let _ = tx.send(received);
+                    ctx.request_repaint();
+                }
+            });
+        }
+    })?;
+    Ok(port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_format() {
+        let header = Header::File { from: "ab12".into(), name: "desk".into(), file_name: "a.txt".into(), size: 3 };
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(json, r#"{"kind":"file","from":"ab12","name":"desk","file_name":"a.txt","size":3}"#);
+        assert_eq!(serde_json::from_str::<Header>(&json).unwrap(), header);
+    }
+}
@@ -0,0 +1,30 @@
+//! slowChat - LAN messaging for the Slow Computer
+//!
+//! Finds other Slowbooks on the same network and trades text and small
+//! files with them. Nothing leaves the room.
+
+mod app;
+mod chat;
+
+use app::SlowChatApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let viewport = window_layout::viewport("slowchat", Placement::RememberLast, [520.0, 420.0])
+        .with_title("slowChat");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowChat",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowChatApp::new(cc))
+        }),
+    )
+}
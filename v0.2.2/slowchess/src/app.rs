@@ -5,11 +5,20 @@ use egui::{ColorImage, Context, Rect, Sense, Stroke, TextureHandle, TextureOptio
 use serde::{Deserialize, Serialize};
 use slowcore::repaint::RepaintController;
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, WindowAction};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowChess",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "chess game for slowOS",
+    features: &["play against AI opponent", "legal move highlighting", "undo moves"],
+    credits: &["egui/eframe (MIT)"],
+    ..AboutInfo::EMPTY
+};
+
 /// Saved game state for persistence
 #[derive(Serialize, Deserialize)]
 struct SavedState {
@@ -752,7 +761,7 @@ impl eframe::App for SlowChessApp {
         self.update_ai_thinking();
 
         // Enable continuous repaint while AI is thinking (for smooth progress bar)
-        self.repaint.set_continuous(self.ai_thinking);
+        self.repaint.set_background(self.ai_thinking);
 
         slowcore::theme::consume_special_keys(ctx);
         let mut win_action = WindowAction::None;
@@ -849,39 +858,8 @@ impl eframe::App for SlowChessApp {
             self.render_board(ui);
         });
 
-        if self.show_about {
-            let screen = ctx.screen_rect();
-            let max_h = (screen.height() - 60.0).max(120.0);
-            let resp = egui::Window::new("about slowChess")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(300.0)
-                .max_height(max_h)
-                .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                        ui.vertical_centered(|ui| {
-                            ui.heading("slowChess");
-                            ui.label("version 0.2.2");
-                            ui.add_space(8.0);
-                            ui.label("chess game for slowOS");
-                        });
-                        ui.add_space(8.0);
-                        ui.separator();
-                        ui.add_space(4.0);
-                        ui.label("features:");
-                        ui.label("  play against AI opponent");
-                        ui.label("  legal move highlighting");
-                        ui.label("  undo moves");
-                        ui.add_space(4.0);
-                        ui.label("frameworks:");
-                        ui.label("  egui/eframe (MIT)");
-                        ui.add_space(8.0);
-                        ui.vertical_centered(|ui| {
-                            if ui.button("ok").clicked() { self.show_about = false; }
-                        });
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
 
         self.repaint.end_frame(ctx);
@@ -2,11 +2,11 @@ mod chess;
 mod app;
 use app::SlowChessApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([480.0, 560.0])
+        viewport: window_layout::viewport("slowchess", Placement::RememberLast, [480.0, 560.0])
             .with_title("chess"),
         ..Default::default()
     };
@@ -4,12 +4,21 @@
 
 use chrono::Local;
 use eframe::NativeOptions;
-use egui::{Align2, CentralPanel, Context, FontId, Key, Pos2, Sense, Stroke, TopBottomPanel, Vec2};
+use egui::{Align2, CentralPanel, Context, FontId, Key, Pos2, Sense, Stroke, TopBottomPanel};
 use slowcore::repaint::RepaintController;
 use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons_with_pin, AboutInfo, WindowAction};
+use slowcore::window_layout::{self, Placement};
 use std::time::{Duration, Instant};
 
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowClock",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "clock for slowOS",
+    features: &["analog clock face", "12/24 hour formats", "full-screen display", "stopwatch"],
+    ..AboutInfo::EMPTY
+};
+
 /// Clock view mode
 #[derive(Clone, Copy, PartialEq)]
 enum ViewMode {
@@ -38,6 +47,8 @@ struct SlowClockApp {
     cached_time: (i64, String),
     /// Cached formatted date string and the day it was computed for
     cached_date: (u32, String),
+    /// City of the system time zone (set in Settings), re-read each second
+    zone: String,
     repaint: RepaintController,
 }
 
@@ -54,6 +65,7 @@ impl SlowClockApp {
             show_about: false,
             cached_time: (-1, String::new()),
             cached_date: (0, String::new()),
+            zone: String::new(),
             repaint: RepaintController::with_fast_interval(),
         }
     }
@@ -63,6 +75,7 @@ impl SlowClockApp {
         let sec = now.timestamp();
         if sec != self.cached_time.0 {
             self.cached_time.0 = sec;
+            self.zone = slowcore::clock::timezone().map(|z| slowcore::clock::city_name(&z)).unwrap_or_default();
             self.cached_time.1 = match (self.use_24h, self.show_seconds) {
                 (true, true) => now.format("%H:%M:%S").to_string(),
                 (true, false) => now.format("%H:%M").to_string(),
@@ -209,7 +222,7 @@ impl SlowClockApp {
     fn draw_analog_view(&mut self, ctx: &Context) {
         let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             menu_bar(ui, |ui| {
-                let action = window_control_buttons(ui);
+                let action = window_control_buttons_with_pin(ui);
                 ui.menu_button("clock", |ui| {
                     if ui.button("full screen    ⌘F").clicked() {
                         self.view_mode = ViewMode::FullScreen;
@@ -262,7 +275,11 @@ impl SlowClockApp {
             } else {
                 "⌘F full screen  |  space stopwatch"
             };
-            status_bar(ui, status);
+            if self.zone.is_empty() {
+                status_bar(ui, status);
+            } else {
+                status_bar(ui, &format!("{}  |  {}", self.zone, status));
+            }
         });
 
         CentralPanel::default()
@@ -380,38 +397,9 @@ impl SlowClockApp {
     }
 
     fn draw_about(&mut self, ctx: &Context) {
-        if !self.show_about {
-            return;
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
-        let screen = ctx.screen_rect();
-        let max_h = (screen.height() - 40.0).max(120.0);
-        let resp = egui::Window::new("about slowClock")
-            .collapsible(false)
-            .resizable(false)
-            .default_width(280.0)
-            .max_height(max_h)
-            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
-            .show(ctx, |ui| {
-                ui.vertical_centered(|ui| {
-                    ui.add_space(8.0);
-                    ui.heading("slowClock");
-                    ui.label("version 0.2.2");
-                    ui.add_space(8.0);
-                    ui.label("clock for slowOS");
-                    ui.add_space(4.0);
-                    ui.label("features:");
-                    ui.label("  analog clock face");
-                    ui.label("  12/24 hour formats");
-                    ui.label("  full-screen display");
-                    ui.label("  stopwatch");
-                    ui.add_space(12.0);
-                    if ui.button("ok").clicked() {
-                        self.show_about = false;
-                    }
-                    ui.add_space(4.0);
-                });
-            });
-        if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
     }
 }
 
@@ -467,14 +455,9 @@ impl eframe::App for SlowClockApp {
 }
 
 fn main() -> eframe::Result<()> {
-    let mut viewport = egui::ViewportBuilder::default()
-        .with_inner_size([360.0, 500.0])
+    let viewport = window_layout::viewport("slowclock", Placement::RememberLast, [360.0, 500.0])
         .with_title("slowClock");
 
-    if let Some(pos) = slowcore::cascade_position() {
-        viewport = viewport.with_position(pos);
-    }
-
     let options = NativeOptions {
         viewport,
         ..Default::default()
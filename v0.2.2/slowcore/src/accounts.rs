@@ -0,0 +1,178 @@
+//! User accounts for the login and lock screen
+//!
+//! Kept for the whole machine in /etc/slowos/accounts.json, not in a home
+//! folder, since each account has its own. Passwords are stored as argon2
+//! hashes. With no accounts the desktop starts straight away and never
+//! locks, as before accounts existed.
+//!
+//! A session is an environment: slowDesktop restarts itself with the
+//! account's HOME (and XDG folders) and everything it opens inherits them,
+//! so documents and settings are per account without any app knowing.
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const ACCOUNTS_FILE: &str = "/etc/slowos/accounts.json";
+
+/// Where new accounts' home folders go
+const HOME_ROOT: &str = "/home";
+
+/// Set in a logged-in session's environment
+pub const USER_VAR: &str = "SLOWOS_USER";
+
+/// Auto-lock choices in settings, in minutes (0 = never)
+pub const AUTO_LOCK_CHOICES: [u32; 5] = [0, 2, 5, 15, 30];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Account {
+    pub name: String,
+    pub home: PathBuf,
+    /// argon2 hash in PHC form; empty for no password
+    #[serde(default)]
+    password: String,
+}
+
+impl Account {
+    pub fn has_password(&self) -> bool {
+        !self.password.is_empty()
+    }
+
+    /// Accounts without a password open for anything
+    pub fn check_password(&self, password: &str) -> bool {
+        self.password.is_empty() || verify_password(&self.password, password)
+    }
+
+    /// The environment a session for this account runs with
+    pub fn session_env(&self) -> Vec<(&'static str, PathBuf)> {
+        vec![
+            ("HOME", self.home.clone()),
+            ("XDG_CONFIG_HOME", self.home.join(".config")),
+            ("XDG_DATA_HOME", self.home.join(".local/share")),
+            (USER_VAR, PathBuf::from(&self.name)),
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Accounts {
+    pub users: Vec<Account>,
+    /// Lock the screen after this many idle minutes (0 = never)
+    #[serde(default = "default_auto_lock")]
+    pub auto_lock_minutes: u32,
+}
+
+fn default_auto_lock() -> u32 {
+    5
+}
+
+impl Default for Accounts {
+    fn default() -> Self {
+        Self { users: Vec::new(), auto_lock_minutes: default_auto_lock() }
+    }
+}
+
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Whether `password` matches `hash`, one made by `hash_password`
+pub fn verify_password(hash: &str, password: &str) -> bool {
+    PasswordHash::new(hash).is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+}
+
+/// Lowercase letters, digits, "-" and "_", starting with a letter
+fn valid_name(name: &str) -> bool {
+    name.len() <= 32
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+impl Accounts {
+    pub fn load() -> Self {
+        std::fs::read_to_string(ACCOUNTS_FILE)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Path::new(ACCOUNTS_FILE);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        // Password hashes: readable by root alone, and never half written
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        crate::storage::save_atomic_private(path, json.as_bytes()).map_err(|e| e.to_string())
+    }
+
+    /// Whether there's anyone to log in as
+    pub fn enabled(&self) -> bool {
+        !self.users.is_empty()
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Account> {
+        self.users.iter().find(|a| a.name == name)
+    }
+
+    /// The account of this session, if logged in
+    pub fn current(&self) -> Option<&Account> {
+        std::env::var(USER_VAR).ok().and_then(|name| self.find(&name))
+    }
+
+    /// Add an account. The first one keeps the home folder in use now, so
+    /// existing documents and settings become its own; later ones get a
+    /// new folder under /home.
+    pub fn add(&mut self, name: &str, password: &str) -> Result<(), String> {
+        if !valid_name(name) {
+            return Err("names are lowercase letters, digits, - and _".to_string());
+        }
+        if self.find(name).is_some() {
+            return Err(format!("there's already an account called {}", name));
+        }
+        let current_home = directories::BaseDirs::new().map(|d| d.home_dir().to_path_buf());
+        let home = match (self.users.is_empty(), current_home) {
+            (true, Some(home)) => home,
+            _ => Path::new(HOME_ROOT).join(name),
+        };
+        std::fs::create_dir_all(&home).map_err(|e| e.to_string())?;
+        let password = if password.is_empty() { String::new() } else { hash_password(password)? };
+        self.users.push(Account { name: name.to_string(), home, password });
+        Ok(())
+    }
+
+    pub fn set_password(&mut self, name: &str, old: &str, new: &str) -> Result<(), String> {
+        let account = self.users.iter_mut().find(|a| a.name == name).ok_or("no such account")?;
+        if !account.check_password(old) {
+            return Err("the current password is wrong".to_string());
+        }
+        account.password = if new.is_empty() { String::new() } else { hash_password(new)? };
+        Ok(())
+    }
+
+    /// Remove an account. Its home folder and files are left where they are.
+    pub fn remove(&mut self, name: &str) {
+        self.users.retain(|a| a.name != name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passwords() {
+        let hash = hash_password("correct horse").unwrap();
+        let account = Account { name: "ada".into(), home: PathBuf::from("/home/ada"), password: hash };
+        assert!(account.check_password("correct horse"));
+        assert!(!account.check_password("wrong"));
+        assert!(valid_name("ada_2"));
+        assert!(!valid_name("Ada") && !valid_name("../x") && !valid_name("2ada"));
+    }
+}
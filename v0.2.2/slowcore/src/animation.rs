@@ -0,0 +1,281 @@
+//! Visual pacing primitives
+//!
+//! Shared drawing for things that move on their own: an expanding dithered
+//! circle (slowBreath), progress arcs and pies (timers, boot progress) and
+//! pulsing rings. Each takes a progress value `t` in 0.0–1.0; where that
+//! comes from is up to the app, usually an [`Animation`] or a timer.
+//!
+//! Every animation has a frame-rate budget. Progress is stepped to it, so
+//! on an e-ink panel a 4 fps animation really only changes 4 times a
+//! second, and [`Animation::request_repaint`] schedules exactly those
+//! frames instead of repainting continuously.
+//!
+//! With "reduce motion" on in Settings, budgets are held to
+//! [`FrameBudget::REDUCED`] and animations that run once are over in a
+//! quarter of the time. Looping ones keep their pace, which often means
+//! something (a breath). Apps stepping progress themselves should use
+//! [`FrameBudget::effective`], and leave out movement that's only there
+//! for show when [`reduce_motion`] says so.
+//!
+//! ```ignore
+//! let anim = Animation::new(Duration::from_secs(2), Easing::InOut).budget(FrameBudget::EINK);
+//! animation::expanding_circle(&painter, center, 20.0, 80.0, anim.value());
+//! anim.request_repaint(ctx);
+//! ```
+
+use crate::theme::SlowColors;
+use egui::{Color32, Painter, Pos2, Rect, Shape, Stroke, Vec2};
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a read of the reduce motion setting is reused
+const SETTING_CACHE: Duration = Duration::from_secs(2);
+
+/// Animations that run once last this many times shorter with reduced motion
+const REDUCED_LENGTH: u32 = 4;
+
+/// Whether "reduce motion" is on in Settings. Cheap enough to ask every
+/// frame; a change is picked up within a couple of seconds.
+pub fn reduce_motion() -> bool {
+    static CACHE: Mutex<Option<(Instant, bool)>> = Mutex::new(None);
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    match *cache {
+        Some((read_at, reduce)) if read_at.elapsed() < SETTING_CACHE => reduce,
+        _ => {
+            let reduce = crate::theme::preferred_reduce_motion();
+            *cache = Some((Instant::now(), reduce));
+            reduce
+        }
+    }
+}
+
+/// Easing curves for progress values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    /// Starts slow
+    In,
+    /// Ends slow
+    Out,
+    /// Slow at both ends, like a breath
+    InOut,
+}
+
+impl Easing {
+    /// Map linear progress (clamped to 0.0–1.0) onto the curve
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::In => t * t * t,
+            Easing::Out => 1.0 - (1.0 - t).powi(3),
+            Easing::InOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// How many distinct frames per second an animation may draw
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameBudget {
+    pub fps: f32,
+}
+
+impl FrameBudget {
+    /// Gentle on e-ink panels (matches the default repaint interval)
+    pub const EINK: FrameBudget = FrameBudget { fps: 4.0 };
+    /// For LCDs and things the user is watching closely
+    pub const SMOOTH: FrameBudget = FrameBudget { fps: 30.0 };
+    /// The most anything moves with "reduce motion" on
+    pub const REDUCED: FrameBudget = FrameBudget { fps: 2.0 };
+
+    pub fn fps(fps: f32) -> Self {
+        Self { fps: fps.max(0.1) }
+    }
+
+    /// This budget, held to [`FrameBudget::REDUCED`] while motion is reduced
+    pub fn effective(self) -> Self {
+        self.reduced_if(reduce_motion())
+    }
+
+    fn reduced_if(self, reduce: bool) -> Self {
+        if reduce && self.fps > Self::REDUCED.fps { Self::REDUCED } else { self }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs_f32(1.0 / self.fps.max(0.1))
+    }
+
+    /// Step linear progress `t` of an animation lasting `length` down to
+    /// this budget's frames
+    pub fn step(&self, t: f32, length: Duration) -> f32 {
+        let frames = (length.as_secs_f32() * self.fps).floor().max(1.0);
+        ((t.clamp(0.0, 1.0) * frames).floor() / frames).min(1.0)
+    }
+}
+
+/// A timed animation: eased, stepped progress from when it started
+#[derive(Debug, Clone)]
+pub struct Animation {
+    start: Instant,
+    length: Duration,
+    easing: Easing,
+    budget: FrameBudget,
+    looping: bool,
+}
+
+impl Animation {
+    pub fn new(length: Duration, easing: Easing) -> Self {
+        Self { start: Instant::now(), length, easing, budget: FrameBudget::EINK, looping: false }
+    }
+
+    pub fn budget(mut self, budget: FrameBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Start over from 0.0 each time it finishes
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    pub fn restart(&mut self) {
+        self.start = Instant::now();
+    }
+
+    /// How long it runs for, shortened while motion is reduced
+    fn length(&self) -> Duration {
+        if !self.looping && reduce_motion() {
+            self.length / REDUCED_LENGTH
+        } else {
+            self.length
+        }
+    }
+
+    /// Linear progress before easing and stepping
+    fn raw(&self) -> f32 {
+        let length = self.length().as_secs_f32();
+        if length <= 0.0 {
+            return 1.0;
+        }
+        let t = self.start.elapsed().as_secs_f32() / length;
+        if self.looping { t.fract() } else { t.min(1.0) }
+    }
+
+    /// Current progress, 0.0–1.0
+    pub fn value(&self) -> f32 {
+        self.easing.apply(self.budget.effective().step(self.raw(), self.length()))
+    }
+
+    pub fn is_done(&self) -> bool {
+        !self.looping && self.start.elapsed() >= self.length()
+    }
+
+    /// Ask for the next budgeted frame, if there is one
+    pub fn request_repaint(&self, ctx: &egui::Context) {
+        if !self.is_done() {
+            ctx.request_repaint_after(self.budget.effective().interval());
+        }
+    }
+}
+
+/// A circle growing from `min_radius` to `max_radius` as `t` goes 0→1,
+/// filled with a dither so it reads as grey, with a solid edge
+pub fn expanding_circle(painter: &Painter, center: Pos2, min_radius: f32, max_radius: f32, t: f32) {
+    let radius = min_radius + (max_radius - min_radius) * t.clamp(0.0, 1.0);
+    dither_circle(painter, center, radius, SlowColors::BLACK);
+    painter.circle_stroke(center, radius, Stroke::new(2.0, SlowColors::BLACK));
+}
+
+/// An arc from twelve o'clock, clockwise, covering `fraction` of the circle
+pub fn progress_arc(painter: &Painter, center: Pos2, radius: f32, fraction: f32, stroke: Stroke) {
+    let points = arc_points(center, radius, fraction);
+    if points.len() > 1 {
+        painter.add(Shape::line(points, stroke));
+    }
+}
+
+/// A filled wedge from twelve o'clock, clockwise, like a kitchen timer
+pub fn progress_pie(painter: &Painter, center: Pos2, radius: f32, fraction: f32, color: Color32) {
+    let mut points = arc_points(center, radius, fraction);
+    if points.len() > 1 {
+        points.insert(0, center);
+        painter.add(Shape::convex_polygon(points, color, Stroke::NONE));
+    }
+}
+
+/// A ring that swells outward from `radius` by `spread` and thins as `t`
+/// goes 0→1; loop `t` for a steady pulse
+pub fn pulsing_ring(painter: &Painter, center: Pos2, radius: f32, spread: f32, t: f32) {
+    let t = t.clamp(0.0, 1.0);
+    let width = 3.0 * (1.0 - t);
+    if width >= 0.5 {
+        painter.circle_stroke(center, radius + spread * t, Stroke::new(width, SlowColors::BLACK));
+    }
+}
+
+fn arc_points(center: Pos2, radius: f32, fraction: f32) -> Vec<Pos2> {
+    let fraction = fraction.clamp(0.0, 1.0);
+    if fraction <= 0.0 {
+        return Vec::new();
+    }
+    // About one segment per 4px of arc
+    let steps = ((TAU * radius * fraction) / 4.0).ceil().clamp(2.0, 128.0) as usize;
+    (0..=steps)
+        .map(|i| {
+            let angle = TAU * fraction * i as f32 / steps as f32 - FRAC_PI_2;
+            center + Vec2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+/// Checkerboard fill of a circle. Pixels are picked by screen parity, so
+/// the pattern stays put while the circle grows.
+fn dither_circle(painter: &Painter, center: Pos2, radius: f32, color: Color32) {
+    let pixel = Vec2::splat(1.0);
+    let y0 = (center.y - radius).ceil() as i32;
+    let y1 = (center.y + radius).floor() as i32;
+    for y in y0..y1 {
+        let dy = y as f32 + 0.5 - center.y;
+        let half = (radius * radius - dy * dy).max(0.0).sqrt();
+        let mut x = (center.x - half).ceil() as i32;
+        let x1 = (center.x + half).floor() as i32;
+        if (x + y).rem_euclid(2) != 0 {
+            x += 1;
+        }
+        while x < x1 {
+            painter.rect_filled(Rect::from_min_size(Pos2::new(x as f32, y as f32), pixel), 0.0, color);
+            x += 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easing_and_steps() {
+        for easing in [Easing::Linear, Easing::In, Easing::Out, Easing::InOut] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6);
+        }
+        assert!(Easing::InOut.apply(0.25) < 0.25);
+        // 2 s at 4 fps is 8 frames
+        let budget = FrameBudget::EINK;
+        assert_eq!(budget.step(0.1, Duration::from_secs(2)), 0.0);
+        assert_eq!(budget.step(0.13, Duration::from_secs(2)), 0.125);
+        assert_eq!(budget.step(1.0, Duration::from_secs(2)), 1.0);
+        // Reduced motion slows fast budgets down, never slow ones up
+        assert_eq!(FrameBudget::SMOOTH.reduced_if(true), FrameBudget::REDUCED);
+        assert_eq!(FrameBudget::fps(1.0).reduced_if(true), FrameBudget::fps(1.0));
+        assert_eq!(FrameBudget::SMOOTH.reduced_if(false), FrameBudget::SMOOTH);
+    }
+}
@@ -0,0 +1,160 @@
+//! Apps — the ones that come with slowOS, those added from slowStore, and
+//! the kinds of file they open
+//!
+//! The apps that come with slowOS are listed in [`BUILT_IN`]. Apps
+//! installed later put their binary in ~/Applications and an entry here,
+//! which slowDesktop reads to show them on the desktop and slowFiles and
+//! slowDesktop read to open files no built-in app takes:
+//!
+//! ```ignore
+//! if let Some(binary) = slowcore::app_registry::app_for_extension("csv") {
+//!     // launch it with the file
+//! }
+//! ```
+//!
+//! The list lives in ~/.config/slowos/apps.json.
+
+use crate::storage::{config_dir, save_atomic, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// The apps that come with slowOS, in desktop order, as
+/// (binary, name, description, icon label)
+pub const BUILT_IN: &[(&str, &str, &str, &str)] = &[
+    ("slowwrite",     "slowWrite",  "word processor",      "W"),
+    ("slowpaint",     "slowPaint",  "bitmap editor",       "P"),
+    ("slowdesign",    "slowDesign", "document design",     "D"),
+    ("slowreader",    "slowReader", "ebook reader",        "R"),
+    ("slownotes",     "slowNotes",  "notes",               "N"),
+    ("slowchess",     "chess",      "chess",               "c"),
+    ("slowfiles",     "slowFiles",  "file manager",        "F"),
+    ("slowmusic",     "slowMusic",  "music player",        "M"),
+    ("slowclock",     "slowClock",  "clock",               "⏱"),
+    ("trash",         "trash",      "trash bin",           "X"),
+    ("slowterm",      "terminal",   "terminal emulator",   ">"),
+    ("slowview",      "slowView",   "image & PDF viewer",  "V"),
+    ("credits",       "credits",    "open source credits", "C"),
+    ("slowmidi",      "slowMidi",   "MIDI sequencer",      "m"),
+    ("slowbreath",    "slowBreath", "breathing timer",     "~"),
+    ("settings",      "settings",   "system settings",     "*"),
+    ("slowcalc",      "calculator", "calculator",          "="),
+    ("slowsolitaire", "solitaire",  "solitaire",           "\u{2660}"),
+    ("slowmonitor",   "slowMonitor", "resource monitor",   "%"),
+    ("slowport",      "slowPort",   "gemini and gopher",   "\u{2302}"),
+    ("slowweb",       "slowWeb",    "web in reader mode",  "@"),
+    ("slowchat",      "slowChat",   "LAN messaging",       "\""),
+    ("slowdrop",      "slowDrop",   "send files nearby",   "\u{2193}"),
+    ("slowbackup",    "slowBackup", "backup and restore",  "B"),
+    ("slowkeyboard",  "keyboard",   "on-screen keyboard",  "K"),
+    ("slowlogs",      "slowLogs",   "crash reports, logs", "L"),
+    ("slowcam",       "slowCam",    "camera",              "\u{25C9}"),
+    ("slowrec",       "slowRec",    "sound recorder",      "\u{25CF}"),
+    ("slowbeat",      "slowBeat",   "metronome and tuner", "\u{2669}"),
+    ("slowtype",      "slowType",   "typing tutor",        "T"),
+    ("slowjournal",   "slowJournal", "daily journal",      "J"),
+    ("slowcards",     "slowCards",  "flashcards",          "\u{25A4}"),
+    ("slowfont",      "slowFont",   "pixel font editor",   "F"),
+    ("slowstore",     "slowStore",  "apps, fonts, sounds", "+"),
+];
+
+/// An app on the desktop, built in or installed
+#[derive(Debug, Clone, PartialEq)]
+pub struct App {
+    /// Binary name ("slowwrite")
+    pub binary: String,
+    /// Display name ("slowWrite")
+    pub name: String,
+    pub description: String,
+    /// Text glyph shown on the desktop
+    pub icon_label: String,
+}
+
+/// An app installed from slowStore
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstalledApp {
+    /// Binary name, in ~/Applications
+    pub binary: String,
+    /// Display name ("slowSheets")
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Text glyph shown on the desktop
+    #[serde(default)]
+    pub icon_label: String,
+    pub version: String,
+    /// File extensions it opens, lowercase without the dot
+    #[serde(default)]
+    pub opens: Vec<String>,
+}
+
+/// ~/Applications, where installed app binaries go
+pub fn applications_dir() -> PathBuf {
+    directories::BaseDirs::new()
+        .map(|d| d.home_dir().join("Applications"))
+        .unwrap_or_else(|| PathBuf::from("Applications"))
+}
+
+fn registry_path() -> PathBuf {
+    config_dir("slowos").join("apps.json")
+}
+
+/// Every installed app, in the order they were installed
+pub fn installed() -> Vec<InstalledApp> {
+    std::fs::read_to_string(registry_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Every app: the built-in ones, then those installed with slowStore in
+/// the order they were installed
+pub fn all() -> Vec<App> {
+    let mut apps: Vec<App> = BUILT_IN
+        .iter()
+        .map(|&(binary, name, description, icon_label)| App {
+            binary: binary.into(),
+            name: name.into(),
+            description: description.into(),
+            icon_label: icon_label.into(),
+        })
+        .collect();
+    let installed = installed().into_iter().filter(|a| !BUILT_IN.iter().any(|&(binary, ..)| binary == a.binary));
+    apps.extend(installed.map(|a| App {
+        icon_label: a.icon_label.chars().next().or_else(|| a.name.chars().next()).unwrap_or('?').to_string(),
+        binary: a.binary,
+        name: a.name,
+        description: a.description,
+    }));
+    apps
+}
+
+fn save(apps: &[InstalledApp]) -> Result<()> {
+    let path = registry_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    save_atomic(&path, serde_json::to_string_pretty(apps)?.as_bytes())
+}
+
+/// Add an app, or replace the entry for the same binary
+pub fn register(app: InstalledApp) -> Result<()> {
+    let mut apps = installed();
+    match apps.iter_mut().find(|a| a.binary == app.binary) {
+        Some(existing) => *existing = app,
+        None => apps.push(app),
+    }
+    save(&apps)
+}
+
+pub fn unregister(binary: &str) -> Result<()> {
+    let mut apps = installed();
+    apps.retain(|a| a.binary != binary);
+    save(&apps)
+}
+
+/// The installed app that opens files ending in `ext`; the most recently
+/// installed one when several do
+pub fn app_for_extension(ext: &str) -> Option<String> {
+    let ext = ext.trim_start_matches('.').to_lowercase();
+    installed().into_iter().rev().find(|a| a.opens.contains(&ext)).map(|a| a.binary)
+}
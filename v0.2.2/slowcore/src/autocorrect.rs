@@ -0,0 +1,383 @@
+//! Autocorrect — fixing words as they're typed
+//!
+//! Shared by the writing apps. When a word is finished with a space,
+//! return or punctuation, a common typo is put right ("teh" → "the"), a
+//! learned word typed in small letters gets its own capitals back
+//! ("slowos" → "slowOS"), and the first word of a sentence and a lone "i"
+//! get a capital. Backspace straight after a fix puts the word back as
+//! typed, and learns it so it's left alone from then on.
+//!
+//! The learned words are the user's own, one per line in words.txt in the
+//! slowOS config folder, shared by every app and edited in settings.
+//! Whether typos are fixed, whether sentences are capitalized and which
+//! apps leave typing alone are in the system settings file
+//! ("autocorrect").
+//!
+//! Call `before()` ahead of `TextEdit::show()` (after an `InputMethod`'s,
+//! so composed text is seen as typed) and `after()` with its output and
+//! the text; `after()` says whether it changed the text.
+
+use crate::storage::config_dir;
+use crate::theme::system_setting;
+use egui::text::{CCursor, CCursorRange};
+use egui::{Context, Event, Key, Ui};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Apps that type through autocorrect: (name, as shown)
+pub const APPS: [(&str, &str); 2] = [("slowwrite", "slowWrite"), ("slownotes", "slowNotes")];
+
+/// How often the settings and learned words are looked at again
+const RELOAD_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Characters that finish a word
+const WORD_ENDS: &str = ".,!?;:)";
+
+/// Characters a word may follow and still be fixed; anything else (a
+/// slash, a dot, an @) means it's part of an address or a path
+const WORD_STARTS: &str = "\"'([“‘";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AutocorrectSettings {
+    /// Fix common typos and the capitals of learned words
+    #[serde(default = "default_on")]
+    pub correct: bool,
+    /// Capitalize the first word of a sentence, and "i"
+    #[serde(default = "default_on")]
+    pub capitalize: bool,
+    /// Apps that leave typing alone, by name ("slownotes")
+    #[serde(default)]
+    pub off_in: Vec<String>,
+}
+
+fn default_on() -> bool {
+    true
+}
+
+impl Default for AutocorrectSettings {
+    fn default() -> Self {
+        Self { correct: true, capitalize: true, off_in: Vec::new() }
+    }
+}
+
+impl AutocorrectSettings {
+    pub fn load() -> Self {
+        system_setting("autocorrect")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether `app` fixes anything at all
+    pub fn is_on_in(&self, app: &str) -> bool {
+        (self.correct || self.capitalize) && !self.off_in.iter().any(|a| a == app)
+    }
+}
+
+fn words_path() -> PathBuf {
+    config_dir("slowos").join("words.txt")
+}
+
+/// The user's learned words
+pub fn learned_words() -> Vec<String> {
+    std::fs::read_to_string(words_path())
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Replace the learned words with `words`
+pub fn save_learned_words(words: &[String]) {
+    let path = words_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let mut text = words.join("\n");
+    text.push('\n');
+    let _ = std::fs::write(path, text);
+}
+
+/// Add `word` to the learned words, if it isn't there already
+pub fn learn_word(word: &str) {
+    let mut words = learned_words();
+    if !words.iter().any(|w| w == word) {
+        words.push(word.to_string());
+        save_learned_words(&words);
+    }
+}
+
+/// A fix just made, kept until the next key in case it's undone
+#[derive(Debug, Clone)]
+struct Correction {
+    /// Where the word starts, in characters
+    start: usize,
+    typed: String,
+    fixed: String,
+    /// Where the cursor was left
+    cursor: usize,
+    /// Undoing it learns the word as typed
+    learn: bool,
+}
+
+/// Autocorrect for one TextEdit
+#[derive(Debug, Clone)]
+pub struct Autocorrect {
+    app: String,
+    settings: AutocorrectSettings,
+    words: Vec<String>,
+    loaded: Option<Instant>,
+    /// The TextEdit this works for, from the last `after()`
+    editor: Option<egui::Id>,
+    /// Character that finished a word this frame
+    finished: Option<char>,
+    last: Option<Correction>,
+    /// Backspace was pressed to undo `last`
+    undo: bool,
+}
+
+impl Autocorrect {
+    /// Autocorrect for `app`, by the name it's turned off with
+    pub fn new(app: &str) -> Self {
+        Self {
+            app: app.to_string(),
+            settings: AutocorrectSettings::default(),
+            words: Vec::new(),
+            loaded: None,
+            editor: None,
+            finished: None,
+            last: None,
+            undo: false,
+        }
+    }
+
+    fn reload(&mut self) {
+        if self.loaded.is_none_or(|t| t.elapsed() >= RELOAD_INTERVAL) {
+            self.settings = AutocorrectSettings::load();
+            self.words = learned_words();
+            self.loaded = Some(Instant::now());
+        }
+    }
+
+    /// Call before `TextEdit::show()`: notes a word being finished, and
+    /// takes a backspace that undoes the last fix
+    pub fn before(&mut self, ctx: &Context) {
+        self.finished = None;
+        let Some(id) = self.editor else { return };
+        self.reload();
+        if !self.settings.is_on_in(&self.app) || !ctx.memory(|m| m.has_focus(id)) {
+            self.last = None;
+            return;
+        }
+        // Undo only while the cursor is still where the fix left it
+        let cursor = egui::text_edit::TextEditState::load(ctx, id).and_then(|s| s.cursor.char_range());
+        if let (Some(last), Some(range)) = (&self.last, cursor) {
+            if range.primary.index != last.cursor || range.secondary.index != last.cursor {
+                self.last = None;
+            }
+        }
+        ctx.input_mut(|i| {
+            if i.modifiers.command {
+                return;
+            }
+            let mut undo = false;
+            let mut finished = None;
+            let mut typed = false;
+            let can_undo = self.last.is_some();
+            i.events.retain(|event| match event {
+                Event::Key { key: Key::Backspace, pressed: true, .. } if can_undo && !undo && !typed => {
+                    undo = true;
+                    false
+                }
+                Event::Text(text) => {
+                    typed = true;
+                    finished = text.chars().last().filter(|&c| c.is_whitespace() || WORD_ENDS.contains(c));
+                    true
+                }
+                Event::Key { key: Key::Enter, pressed: true, .. } => {
+                    typed = true;
+                    finished = Some('\n');
+                    true
+                }
+                _ => true,
+            });
+            self.undo = undo;
+            self.finished = finished;
+            if typed && !undo {
+                self.last = None;
+            }
+        });
+    }
+
+    /// Call after `TextEdit::show()` with its output and the text it edits:
+    /// fixes the word just finished, or puts back the one just fixed.
+    /// True if the text changed.
+    pub fn after(&mut self, ui: &Ui, output: &egui::text_edit::TextEditOutput, text: &mut String) -> bool {
+        self.editor = Some(output.response.id);
+        if std::mem::take(&mut self.undo) {
+            let Some(last) = self.last.take() else { return false };
+            let fixed_len = last.fixed.chars().count();
+            if !text.chars().skip(last.start).take(fixed_len).eq(last.fixed.chars()) {
+                return false;
+            }
+            replace_chars(text, last.start, fixed_len, &last.typed);
+            if last.learn {
+                learn_word(&last.typed);
+                self.words.push(last.typed.clone());
+            }
+            let cursor = last.cursor - fixed_len + last.typed.chars().count();
+            set_cursor(ui, output, cursor);
+            return true;
+        }
+
+        let Some(end_char) = self.finished.take() else { return false };
+        let Some(cursor) = output.cursor_range.map(|r| r.primary.ccursor.index) else { return false };
+        let chars: Vec<char> = text.chars().collect();
+        if cursor == 0 || chars.get(cursor - 1) != Some(&end_char) {
+            return false;
+        }
+        let end = cursor - 1;
+        let mut start = end;
+        while start > 0 && is_word_char(chars[start - 1]) {
+            start -= 1;
+        }
+        if start == end || (start > 0 && !chars[start - 1].is_whitespace() && !WORD_STARTS.contains(chars[start - 1])) {
+            return false;
+        }
+        let typed: String = chars[start..end].iter().collect();
+        let Some((fixed, learn)) = fix_word(&typed, starts_sentence(&chars[..start]), &self.words, &self.settings) else {
+            return false;
+        };
+        replace_chars(text, start, end - start, &fixed);
+        let cursor = cursor - (end - start) + fixed.chars().count();
+        set_cursor(ui, output, cursor);
+        self.last = Some(Correction { start, typed, fixed, cursor, learn });
+        true
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '\''
+}
+
+/// Whether a word after `before` starts a sentence: at the start of the
+/// text or a line, or after . ! or ?
+fn starts_sentence(before: &[char]) -> bool {
+    matches!(before.iter().rev().find(|&&c| c != ' ' && c != '\t'), None | Some('\n' | '.' | '!' | '?'))
+}
+
+/// What `typed` becomes, and whether it was a fix worth learning from if
+/// undone; `None` to leave it
+fn fix_word(typed: &str, sentence: bool, learned: &[String], settings: &AutocorrectSettings) -> Option<(String, bool)> {
+    if learned.iter().any(|w| w == typed) {
+        return None;
+    }
+    let lower = typed.to_lowercase();
+    let mut fixed = typed.to_string();
+    let mut learn = false;
+    let mut keep_case = false;
+    if settings.correct {
+        if let Some(word) = learned.iter().find(|w| w.to_lowercase() == lower).filter(|_| typed == lower) {
+            fixed = word.clone();
+            learn = true;
+            keep_case = true;
+        } else if let Some(&(_, right)) = TYPOS.iter().find(|&&(typo, _)| typo == lower) {
+            fixed = match_case(typed, right);
+            learn = true;
+        }
+    }
+    let lone_i = matches!(fixed.as_str(), "i" | "i'm" | "i'd" | "i'll" | "i've");
+    if settings.capitalize && (lone_i || (sentence && !keep_case)) {
+        fixed = capitalized(&fixed);
+    }
+    (fixed != typed).then_some((fixed, learn))
+}
+
+/// `word` with a capital first letter
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// `right` in the capitals `typed` was in: all, the first, or none
+fn match_case(typed: &str, right: &str) -> String {
+    if typed.chars().count() > 1 && typed.chars().all(|c| !c.is_lowercase()) {
+        right.to_uppercase()
+    } else if typed.chars().next().is_some_and(char::is_uppercase) {
+        capitalized(right)
+    } else {
+        right.to_string()
+    }
+}
+
+/// Replace `len` characters of `text` from character `start` with `with`
+fn replace_chars(text: &mut String, start: usize, len: usize, with: &str) {
+    let byte = |n: usize| text.char_indices().nth(n).map_or(text.len(), |(b, _)| b);
+    let range = byte(start)..byte(start + len);
+    text.replace_range(range, with);
+}
+
+fn set_cursor(ui: &Ui, output: &egui::text_edit::TextEditOutput, index: usize) {
+    let mut state = output.state.clone();
+    state.cursor.set_char_range(Some(CCursorRange::one(CCursor::new(index))));
+    state.store(ui.ctx(), output.response.id);
+    ui.ctx().request_repaint();
+}
+
+/// Common typos, and what they should be
+const TYPOS: &[(&str, &str)] = &[
+    ("teh", "the"), ("hte", "the"), ("adn", "and"), ("nad", "and"), ("taht", "that"), ("thta", "that"),
+    ("wiht", "with"), ("jsut", "just"), ("waht", "what"), ("wnat", "want"), ("becuase", "because"),
+    ("becasue", "because"), ("beacuse", "because"), ("recieve", "receive"), ("recieved", "received"),
+    ("beleive", "believe"), ("acheive", "achieve"), ("wierd", "weird"), ("freind", "friend"),
+    ("freinds", "friends"), ("thier", "their"), ("theyre", "they're"), ("dont", "don't"), ("doesnt", "doesn't"),
+    ("didnt", "didn't"), ("isnt", "isn't"), ("wasnt", "wasn't"), ("arent", "aren't"), ("couldnt", "couldn't"),
+    ("shouldnt", "shouldn't"), ("wouldnt", "wouldn't"), ("im", "I'm"), ("ive", "I've"), ("youre", "you're"),
+    ("thats", "that's"), ("alot", "a lot"), ("definately", "definitely"), ("seperate", "separate"),
+    ("occured", "occurred"), ("untill", "until"), ("tommorow", "tomorrow"), ("tomorow", "tomorrow"),
+    ("goverment", "government"), ("enviroment", "environment"), ("neccessary", "necessary"),
+    ("necesary", "necessary"), ("occassion", "occasion"), ("accomodate", "accommodate"),
+    ("begining", "beginning"), ("calender", "calendar"), ("comming", "coming"), ("existance", "existence"),
+    ("foward", "forward"), ("futher", "further"), ("greatful", "grateful"), ("happend", "happened"),
+    ("independant", "independent"), ("knowlege", "knowledge"), ("libary", "library"), ("noticable", "noticeable"),
+    ("persue", "pursue"), ("posession", "possession"), ("prefered", "preferred"), ("probaly", "probably"),
+    ("realy", "really"), ("remeber", "remember"), ("sentance", "sentence"), ("succesful", "successful"),
+    ("suprise", "surprise"), ("thnak", "thank"), ("thnaks", "thanks"), ("truely", "truly"), ("wich", "which"),
+    ("writting", "writing"), ("yeild", "yield"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_word() {
+        let settings = AutocorrectSettings::default();
+        let learned = vec!["slowOS".to_string(), "teh".to_string()];
+        let fix = |word: &str, sentence: bool| fix_word(word, sentence, &[], &settings).map(|(w, _)| w);
+        assert_eq!(fix("adn", false).as_deref(), Some("and"));
+        assert_eq!(fix("Recieve", false).as_deref(), Some("Receive"));
+        assert_eq!(fix("WIHT", false).as_deref(), Some("WITH"));
+        assert_eq!(fix("hello", true).as_deref(), Some("Hello"));
+        assert_eq!(fix("hello", false), None);
+        assert_eq!(fix("i'll", false).as_deref(), Some("I'll"));
+        // Learned words keep their capitals, and aren't corrected
+        assert_eq!(fix_word("slowos", true, &learned, &settings), Some(("slowOS".to_string(), true)));
+        assert_eq!(fix_word("teh", false, &learned, &settings), None);
+
+        let off = AutocorrectSettings { correct: false, ..Default::default() };
+        assert_eq!(fix_word("teh", true, &[], &off), Some(("Teh".to_string(), false)));
+
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert!(starts_sentence(&chars("")));
+        assert!(starts_sentence(&chars("it rained. ")));
+        assert!(starts_sentence(&chars("a list:\n  ")));
+        assert!(!starts_sentence(&chars("it rained, ")));
+
+        let mut text = "naïve teh end".to_string();
+        replace_chars(&mut text, 6, 3, "the");
+        assert_eq!(text, "naïve the end");
+    }
+}
@@ -0,0 +1,106 @@
+//! System time zone, network time and the clock itself
+//!
+//! Reading is done here; every change goes through the `slowos-time`
+//! helper, the one place allowed to touch /etc/localtime, network time and
+//! the hardware clock. Apps showing the time (slowClock, the desktop's
+//! menu bar clock) follow the time zone through `chrono::Local`.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Privileged helper, from the root filesystem overlay
+const HELPER: &str = "/usr/sbin/slowos-time";
+
+const ZONEINFO: &str = "/usr/share/zoneinfo";
+
+/// Present when network time is turned off
+const NTP_OFF: &str = "/etc/slowos/ntp-off";
+
+/// Continents and oceans zone names start with; the rest of zoneinfo is
+/// aliases ("US/Eastern", "posix/...")
+const AREAS: &[&str] = &[
+    "Africa", "America", "Antarctica", "Arctic", "Asia", "Atlantic", "Australia", "Europe", "Indian", "Pacific",
+];
+
+/// The system time zone ("Europe/Berlin"), if set
+pub fn timezone() -> Option<String> {
+    if let Ok(target) = std::fs::read_link("/etc/localtime") {
+        let target = target.to_string_lossy().to_string();
+        if let Some((_, zone)) = target.split_once("zoneinfo/") {
+            return Some(zone.to_string());
+        }
+    }
+    std::fs::read_to_string("/etc/timezone").ok().map(|z| z.trim().to_string()).filter(|z| !z.is_empty())
+}
+
+/// Every time zone there is, as "Area/City", sorted
+pub fn timezones() -> Vec<String> {
+    fn walk(dir: &Path, prefix: &str, out: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => walk(&entry.path(), &name, out),
+                Ok(_) => out.push(name),
+                Err(_) => {}
+            }
+        }
+    }
+    let mut zones = Vec::new();
+    for area in AREAS {
+        walk(&Path::new(ZONEINFO).join(area), area, &mut zones);
+    }
+    zones.push("Etc/UTC".to_string());
+    zones.sort();
+    zones
+}
+
+/// "New York" for "America/New_York", "Indiana / Knox" for
+/// "America/Indiana/Knox"
+pub fn city_name(zone: &str) -> String {
+    let city = zone.split_once('/').map(|(_, c)| c).unwrap_or(zone);
+    city.replace('_', " ").replace('/', " / ")
+}
+
+pub fn ntp_enabled() -> bool {
+    !Path::new(NTP_OFF).exists()
+}
+
+fn helper(args: &[&str]) -> Result<(), String> {
+    let output = Command::new(HELPER)
+        .args(args)
+        .output()
+        .map_err(|_| "changing the clock isn't available here (slowos-time not found)".to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(if stderr.is_empty() { "the clock couldn't be changed".to_string() } else { stderr });
+    }
+    Ok(())
+}
+
+pub fn set_timezone(zone: &str) -> Result<(), String> {
+    helper(&["zone", zone])
+}
+
+/// Turn setting the time from the internet on or off
+pub fn set_ntp(on: bool) -> Result<(), String> {
+    helper(&["ntp", if on { "on" } else { "off" }])
+}
+
+/// Set the clock by hand to a local time, "2026-10-16 14:05:00". Only
+/// while network time is off.
+pub fn set_time(local: &str) -> Result<(), String> {
+    helper(&["set", local])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_city_name() {
+        assert_eq!(city_name("America/New_York"), "New York");
+        assert_eq!(city_name("America/Indiana/Knox"), "Indiana / Knox");
+        assert_eq!(city_name("UTC"), "UTC");
+    }
+}
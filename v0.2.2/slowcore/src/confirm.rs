@@ -0,0 +1,137 @@
+//! Unsaved-changes guard for closing a window
+//!
+//! Every document app needs the same flow: when the window is asked to
+//! close with unsaved changes, cancel the close and ask
+//! "save / don't save / cancel".  `CloseGuard` owns that state.
+//!
+//! ```ignore
+//! // once per frame, after drawing the app
+//! if self.close_guard.update(ctx, self.modified) {
+//!     self.save();
+//!     self.close_guard.close_if_saved(ctx, !self.modified);
+//! }
+//!
+//! // from the close button / menu item
+//! self.close_guard.request_close(ctx, self.modified);
+//! ```
+//!
+//! When slowDesktop shuts down (see `session`), `update` returns `true`
+//! without asking if there is anything to save, and closes otherwise.
+
+use egui::{Align2, Context, ViewportCommand};
+
+#[derive(Default)]
+pub struct CloseGuard {
+    /// The "unsaved changes" dialog is open
+    showing: bool,
+    /// The user already agreed to close; let the next close request through
+    confirmed: bool,
+    /// Give "save" keyboard focus when the dialog appears
+    focus_save: bool,
+    /// The desktop's quit request has been acted on
+    quitting: bool,
+}
+
+impl CloseGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the dialog is currently shown (apps may want to ignore
+    /// shortcuts while it is up)
+    pub fn is_showing(&self) -> bool {
+        self.showing
+    }
+
+    /// Close the window now if there is nothing to save, otherwise ask.
+    pub fn request_close(&mut self, ctx: &Context, modified: bool) {
+        if modified && !self.confirmed {
+            self.show();
+        } else {
+            ctx.send_viewport_cmd(ViewportCommand::Close);
+        }
+    }
+
+    /// Intercept window-close requests and draw the dialog.
+    ///
+    /// Returns `true` when the user chose "save": the app should save,
+    /// then call [`close_if_saved`](Self::close_if_saved).
+    pub fn update(&mut self, ctx: &Context, modified: bool) -> bool {
+        crate::session::watch(ctx);
+        if !self.quitting && crate::session::quit_requested() {
+            self.quitting = true;
+            if !modified {
+                self.close_now(ctx);
+                return false;
+            }
+            // Save without asking; close_if_saved closes afterwards
+            self.confirmed = true;
+            return true;
+        }
+        if ctx.input(|i| i.viewport().close_requested()) && modified && !self.confirmed {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+            self.show();
+        }
+        if !self.showing {
+            return false;
+        }
+
+        let mut save = false;
+        let resp = egui::Window::new("unsaved changes")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(300.0)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("you have unsaved changes.");
+                ui.label("do you want to save before closing?");
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("don't save").clicked() {
+                        self.close_now(ctx);
+                    }
+                    if ui.button("cancel").clicked() {
+                        self.showing = false;
+                    }
+                    let save_button = ui.button("save");
+                    if std::mem::take(&mut self.focus_save) {
+                        save_button.request_focus();
+                    }
+                    if save_button.clicked() {
+                        save = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp {
+            crate::dither::draw_window_shadow(ctx, r.response.rect);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.showing = false;
+        }
+        save
+    }
+
+    /// After "save": close if the save went through. If it didn't (or a
+    /// save dialog opened instead), the window stays open.
+    pub fn close_if_saved(&mut self, ctx: &Context, saved: bool) {
+        if saved {
+            self.close_now(ctx);
+        } else {
+            self.showing = false;
+        }
+    }
+
+    fn show(&mut self) {
+        if !self.showing {
+            self.showing = true;
+            self.focus_save = true;
+            crate::sound::alert();
+        }
+    }
+
+    fn close_now(&mut self, ctx: &Context) {
+        self.confirmed = true;
+        self.showing = false;
+        ctx.send_viewport_cmd(ViewportCommand::Close);
+    }
+}
@@ -0,0 +1,277 @@
+//! Screen rotation, resolution and tint
+//!
+//! Changed with xrandr under X11 and wlr-randr under Wayland (cage). The
+//! choices are kept in the system settings file ("display_rotation",
+//! "display_modes") and slowDesktop puts them back at startup. The tint
+//! is night shift's (see [`night_shift`](crate::night_shift)).
+
+use crate::theme::{set_system_setting, system_setting};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::process::Command;
+
+/// Output names of built-in panels; anything else is an external monitor
+const INTERNAL_PREFIXES: &[&str] = &["eDP", "LVDS", "DSI", "DPI"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Rotation {
+    #[default]
+    Normal,
+    /// Turned a quarter counter-clockwise: portrait
+    Left,
+    /// Turned a quarter clockwise: portrait
+    Right,
+    Inverted,
+}
+
+impl Rotation {
+    pub const ALL: [Rotation; 4] = [Rotation::Normal, Rotation::Left, Rotation::Right, Rotation::Inverted];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rotation::Normal => "landscape",
+            Rotation::Left => "portrait",
+            Rotation::Right => "portrait (flipped)",
+            Rotation::Inverted => "upside down",
+        }
+    }
+
+    pub fn is_portrait(self) -> bool {
+        matches!(self, Rotation::Left | Rotation::Right)
+    }
+
+    fn xrandr(self) -> &'static str {
+        match self {
+            Rotation::Normal => "normal",
+            Rotation::Left => "left",
+            Rotation::Right => "right",
+            Rotation::Inverted => "inverted",
+        }
+    }
+
+    /// wlroots turns counter-clockwise
+    fn wlr(self) -> &'static str {
+        match self {
+            Rotation::Normal => "normal",
+            Rotation::Left => "90",
+            Rotation::Right => "270",
+            Rotation::Inverted => "180",
+        }
+    }
+}
+
+/// A connected screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Output {
+    /// "HDMI-1", "DSI-1"
+    pub name: String,
+    pub internal: bool,
+    /// "1920x1080", best first, each once
+    pub modes: Vec<String>,
+    pub current: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum Backend {
+    X11,
+    Wayland,
+}
+
+fn backend() -> Result<Backend, String> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        Ok(Backend::Wayland)
+    } else if std::env::var_os("DISPLAY").is_some() {
+        Ok(Backend::X11)
+    } else {
+        Err("no display to change".to_string())
+    }
+}
+
+fn run(backend: Backend, args: &[&str]) -> Result<String, String> {
+    let program = match backend {
+        Backend::X11 => "xrandr",
+        Backend::Wayland => "wlr-randr",
+    };
+    let output = Command::new(program).args(args).output().map_err(|_| format!("{} isn't installed", program))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Connected screens
+pub fn outputs() -> Result<Vec<Output>, String> {
+    let backend = backend()?;
+    run(backend, &[]).map(|out| match backend {
+        Backend::X11 => parse_xrandr(&out),
+        Backend::Wayland => parse_wlr_randr(&out),
+    })
+}
+
+fn new_output(name: &str) -> Output {
+    Output {
+        name: name.to_string(),
+        internal: INTERNAL_PREFIXES.iter().any(|p| name.starts_with(p)),
+        modes: Vec::new(),
+        current: None,
+    }
+}
+
+fn add_mode(output: &mut Output, mode: &str, current: bool) {
+    if !output.modes.iter().any(|m| m == mode) {
+        output.modes.push(mode.to_string());
+    }
+    if current {
+        output.current = Some(mode.to_string());
+    }
+}
+
+/// "HDMI-1 connected primary 1920x1080+0+0 ..." then indented
+/// "   1920x1080     60.00*+  50.00" mode lines
+fn parse_xrandr(out: &str) -> Vec<Output> {
+    let mut outputs: Vec<Output> = Vec::new();
+    let mut in_connected = false;
+    for line in out.lines() {
+        if !line.starts_with(' ') {
+            let mut fields = line.split_whitespace();
+            let name = fields.next().unwrap_or_default();
+            in_connected = fields.next() == Some("connected");
+            if in_connected {
+                outputs.push(new_output(name));
+            }
+        } else if in_connected {
+            let Some(output) = outputs.last_mut() else { continue };
+            let mut fields = line.split_whitespace();
+            let Some(mode) = fields.next().filter(|m| m.contains('x')) else { continue };
+            add_mode(output, mode, fields.any(|rate| rate.contains('*')));
+        }
+    }
+    outputs
+}
+
+/// "HDMI-A-1 \"Dell ...\"" then indented details, with mode lines like
+/// "    1920x1080 px, 60.000000 Hz (preferred, current)"
+fn parse_wlr_randr(out: &str) -> Vec<Output> {
+    let mut outputs: Vec<Output> = Vec::new();
+    for line in out.lines() {
+        if !line.starts_with(' ') {
+            if let Some(name) = line.split_whitespace().next() {
+                outputs.push(new_output(name));
+            }
+        } else if line.contains(" px, ") {
+            let Some(output) = outputs.last_mut() else { continue };
+            let Some(mode) = line.split_whitespace().next() else { continue };
+            add_mode(output, mode, line.contains("current"));
+        }
+    }
+    outputs
+}
+
+/// Turn every screen
+pub fn set_rotation(rotation: Rotation) -> Result<(), String> {
+    let backend = backend()?;
+    for output in outputs()? {
+        match backend {
+            Backend::X11 => run(backend, &["--output", &output.name, "--rotate", rotation.xrandr()])?,
+            Backend::Wayland => run(backend, &["--output", &output.name, "--transform", rotation.wlr()])?,
+        };
+    }
+    Ok(())
+}
+
+/// Tint every screen: each color channel and the overall brightness
+/// scaled by 0.0–1.0, all 1.0 for none. X11 only; wlr-randr can't.
+pub fn set_tint(red: f32, green: f32, blue: f32, brightness: f32) -> Result<(), String> {
+    let backend = backend()?;
+    if matches!(backend, Backend::Wayland) {
+        return Err("the screen can only be tinted under X11".to_string());
+    }
+    let gamma = format!("{:.2}:{:.2}:{:.2}", red, green, blue);
+    let brightness = format!("{:.2}", brightness);
+    for output in outputs()? {
+        run(backend, &["--output", &output.name, "--gamma", &gamma, "--brightness", &brightness])?;
+    }
+    Ok(())
+}
+
+/// Change one screen's resolution ("1280x720")
+pub fn set_mode(output: &str, mode: &str) -> Result<(), String> {
+    run(backend()?, &["--output", output, "--mode", mode]).map(|_| ())
+}
+
+pub fn saved_rotation() -> Rotation {
+    system_setting("display_rotation")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Resolutions chosen per output name
+pub fn saved_modes() -> BTreeMap<String, String> {
+    system_setting("display_modes")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_rotation(rotation: Rotation) {
+    if let Ok(value) = serde_json::to_value(rotation) {
+        set_system_setting("display_rotation", value);
+    }
+}
+
+pub fn save_mode(output: &str, mode: &str) {
+    let mut modes = saved_modes();
+    modes.insert(output.to_string(), mode.to_string());
+    if let Ok(value) = serde_json::to_value(modes) {
+        set_system_setting("display_modes", value);
+    }
+}
+
+/// Put the saved rotation and resolutions back (slowDesktop, at startup)
+pub fn apply_saved() {
+    let modes = saved_modes();
+    if let Ok(outputs) = outputs() {
+        for output in outputs {
+            if let Some(mode) = modes.get(&output.name).filter(|m| output.modes.contains(m)) {
+                if let Err(e) = set_mode(&output.name, mode) {
+                    crate::log::warn!("couldn't set {} to {}: {}", output.name, mode, e);
+                }
+            }
+        }
+    }
+    let rotation = saved_rotation();
+    if rotation != Rotation::Normal {
+        if let Err(e) = set_rotation(rotation) {
+            crate::log::warn!("couldn't rotate the screen: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_outputs() {
+        let xrandr = "Screen 0: minimum 320 x 200, current 1920 x 1080, maximum 8192 x 8192\n\
+                      HDMI-1 connected primary 1920x1080+0+0 (normal left inverted right) 527mm x 296mm\n   \
+                      1920x1080     60.00*+  50.00\n   \
+                      1280x720      60.00    50.00\n\
+                      HDMI-2 disconnected (normal left inverted right)\n";
+        let outputs = parse_xrandr(xrandr);
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].modes, ["1920x1080", "1280x720"]);
+        assert_eq!(outputs[0].current.as_deref(), Some("1920x1080"));
+        assert!(!outputs[0].internal);
+
+        let wlr = "DSI-1 \"Unknown (DSI-1)\"\n  \
+                   Enabled: yes\n  \
+                   Modes:\n    \
+                   800x480 px, 60.000000 Hz (preferred, current)\n  \
+                   Transform: normal\n";
+        let outputs = parse_wlr_randr(wlr);
+        assert_eq!(outputs.len(), 1);
+        assert!(outputs[0].internal);
+        assert_eq!(outputs[0].current.as_deref(), Some("800x480"));
+    }
+}
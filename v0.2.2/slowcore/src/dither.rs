@@ -3,10 +3,17 @@
 //! Instead of opaque black boxes, we draw a checkerboard dither
 //! pattern so the user can still see content underneath selections and highlights.
 //!
+//! Pictures are dithered with [`atkinson`] to black and white, or with
+//! [`photo`] to the greys chosen in Settings for screens that can show them.
+//!
+//! [`eink`] dithers a whole frame for an e-paper panel; see the e-ink
+//! section of [`repaint`](crate::repaint).
+//!
 //! v0.2.2: Streamlined inner loop — bounds are clamped once up front so no
 //! per-pixel check is needed inside the loop.
 
 use egui::{Color32, Painter, Pos2, Rect};
+use image::{GrayImage, Luma};
 
 /// Draw a checkerboard dither pattern over a rectangle.
 /// Every other pixel is colored, creating a translucent overlay effect.
@@ -68,6 +75,25 @@ pub fn draw_dither_hover(painter: &Painter, rect: Rect) {
     draw_dither_rect(painter, rect, Color32::BLACK, 2);
 }
 
+/// Fade whatever is under `rect` to a dithered grey by whiting out every
+/// other pixel. It's one repeating texture, so unlike
+/// [`draw_dither_rect`] it stays cheap over a screenful of text.
+pub fn draw_dither_fade(painter: &Painter, rect: Rect) {
+    let ctx = painter.ctx();
+    let id = egui::Id::new("dither_fade");
+    let texture = ctx.data(|d| d.get_temp::<egui::TextureHandle>(id)).unwrap_or_else(|| {
+        let (w, t) = (Color32::WHITE, Color32::TRANSPARENT);
+        let image = egui::ColorImage { size: [2, 2], pixels: vec![w, t, t, w] };
+        let texture = ctx.load_texture("dither_fade", image, egui::TextureOptions::NEAREST_REPEAT);
+        ctx.data_mut(|d| d.insert_temp(id, texture.clone()));
+        texture
+    });
+    // In screen pixels, so the pattern lines up wherever the rect is
+    let scale = ctx.pixels_per_point() / 2.0;
+    let uv = Rect::from_min_max((rect.min.to_vec2() * scale).to_pos2(), (rect.max.to_vec2() * scale).to_pos2());
+    painter.image(texture.id(), rect, uv, Color32::WHITE);
+}
+
 /// Draw a dithered drop shadow for a window.
 /// Call after egui::Window::show() with the window rect.
 /// Uses Order::PanelResizeLine so the shadow renders between panels and windows.
@@ -121,3 +147,125 @@ pub fn draw_dither_outline(painter: &Painter, rect: Rect, thickness: f32) {
     );
     draw_dither_rect(painter, right, Color32::BLACK, 1);
 }
+
+/// Draw the keyboard focus ring around a widget: a 1px white gap then a
+/// solid 2px black frame, so it reads on both white and dithered content.
+/// Drawn just outside `rect`.
+pub fn draw_focus_ring(painter: &Painter, rect: Rect) {
+    painter.rect_stroke(rect.expand(1.0), 0.0, egui::Stroke::new(1.0, Color32::WHITE));
+    painter.rect_stroke(rect.expand(2.5), 0.0, egui::Stroke::new(2.0, Color32::BLACK));
+}
+
+/// Dither `image` in place to pure black and white.
+///
+/// Atkinson dithering: it spreads only three quarters of the error, so
+/// highlights and shadows stay clean on a black and white screen.
+pub fn atkinson(image: &mut GrayImage) {
+    atkinson_levels(image, 2);
+}
+
+/// Dither `image` in place to `levels` evenly spaced greys (2 is black
+/// and white), the same way as [`atkinson`].
+pub fn atkinson_levels(image: &mut GrayImage, levels: u8) {
+    let step = 255 / (levels.clamp(2, 16) as i32 - 1);
+    let (w, h) = (image.width() as i64, image.height() as i64);
+    let mut levels: Vec<i32> = image.pixels().map(|p| p.0[0] as i32).collect();
+    for y in 0..h {
+        for x in 0..w {
+            let i = (y * w + x) as usize;
+            let old = levels[i];
+            let new = ((old + step / 2).div_euclid(step) * step).clamp(0, 255);
+            levels[i] = new;
+            let error = (old - new) / 8;
+            for (dx, dy) in [(1, 0), (2, 0), (-1, 1), (0, 1), (1, 1), (0, 2)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx >= 0 && nx < w && ny < h {
+                    levels[(ny * w + nx) as usize] += error;
+                }
+            }
+        }
+    }
+    for (pixel, level) in image.pixels_mut().zip(levels) {
+        *pixel = Luma([level.clamp(0, 255) as u8]);
+    }
+}
+
+/// Dither a photo in place for this screen: to black and white, or to the
+/// 4 or 16 greys picked in Settings (see [`theme::grey_levels`](crate::theme::grey_levels)).
+pub fn photo(image: &mut GrayImage) {
+    atkinson_levels(image, crate::theme::grey_levels());
+}
+
+/// 4×4 Bayer matrix: the order pixels in a block turn white as it lightens
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Dither `image` in place to pure black and white with a fixed pattern.
+///
+/// Ordered dithering: each pixel is compared with its place in a 4×4
+/// pattern, no error is carried, so it is quick enough for live video and
+/// still parts of a picture don't shimmer from frame to frame.
+pub fn ordered(image: &mut GrayImage) {
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let threshold = BAYER_4X4[(y % 4) as usize][(x % 4) as usize] as u32 * 16 + 8;
+        pixel.0[0] = if pixel.0[0] as u32 >= threshold { 255 } else { 0 };
+    }
+}
+
+/// E-paper prints mid-tones darker than a screen; they are lifted by this power
+const EINK_GAMMA: f32 = 0.8;
+
+/// Dither `image` in place for an e-paper panel showing `levels` greys
+/// (2 in its fast one-bit mode, 16 in full greyscale).
+///
+/// The same 4×4 pattern as [`ordered`], between neighbouring greys. Pure
+/// black and white are left alone so text and the page carry no pattern,
+/// and parts of the screen that didn't change dither exactly as before,
+/// which keeps partial refreshes small.
+pub fn eink(image: &mut GrayImage, levels: u8) {
+    let steps = levels.max(2) as u32 - 1;
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let value = pixel.0[0];
+        if value == 0 || value == 255 {
+            continue;
+        }
+        let level = (value as f32 / 255.0).powf(EINK_GAMMA) * steps as f32;
+        let threshold = (BAYER_4X4[(y % 4) as usize][(x % 4) as usize] as f32 + 0.5) / 16.0;
+        let level = level.floor() as u32 + (level.fract() >= threshold) as u32;
+        pixel.0[0] = (level.min(steps) * 255 / steps) as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atkinson_is_one_bit() {
+        let mut image = GrayImage::from_fn(16, 16, |x, y| Luma([(x * 8 + y * 8) as u8]));
+        atkinson(&mut image);
+        assert!(image.pixels().all(|p| p.0[0] == 0 || p.0[0] == 255));
+        // A mid-grey area comes out as a mix of both
+        let mut grey = GrayImage::from_pixel(8, 8, Luma([128]));
+        atkinson(&mut grey);
+        let white = grey.pixels().filter(|p| p.0[0] == 255).count();
+        assert!(white > 8 && white < 56);
+        let mut grey = GrayImage::from_pixel(8, 8, Luma([128]));
+        ordered(&mut grey);
+        assert_eq!(grey.pixels().filter(|p| p.0[0] == 255).count(), 32);
+
+        // E-ink: only the two greys either side of the lifted level, and
+        // black and white untouched
+        let mut grey = GrayImage::from_fn(8, 8, |x, _| Luma([if x == 0 { 0 } else if x == 7 { 255 } else { 128 }]));
+        eink(&mut grey, 16);
+        assert!(grey.enumerate_pixels().all(|(x, _, p)| match x {
+            0 => p.0[0] == 0,
+            7 => p.0[0] == 255,
+            _ => p.0[0] == 136 || p.0[0] == 153,
+        }));
+
+        // Four greys: a grey between two of them mixes only those two
+        let mut grey = GrayImage::from_pixel(8, 8, Luma([128]));
+        atkinson_levels(&mut grey, 4);
+        assert!(grey.pixels().all(|p| p.0[0] == 85 || p.0[0] == 170));
+    }
+}
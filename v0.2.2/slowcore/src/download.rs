@@ -0,0 +1,173 @@
+//! Downloads — files fetched over https or copied from a disk, checked
+//! against a SHA-256 before anything uses them
+//!
+//! Updates and slowStore both put programs on the computer from a list
+//! someone else wrote, so both go through here. Plain `http://` is refused:
+//! anyone on the way could change what arrives. A list can also be signed
+//! with an ed25519 key, for lists that come on a USB stick.
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+/// Gives up on a silent server after this long
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where a file comes from
+#[derive(Debug, Clone, Copy)]
+pub enum Origin<'a> {
+    /// An https address
+    Web(&'a str),
+    /// A file on this computer or a disk plugged into it
+    File(&'a Path),
+}
+
+/// Whether `source` is a web address rather than a path
+pub fn is_web(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// Refuse anything but an https address
+pub fn check_https(url: &str) -> Result<(), String> {
+    if url.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(format!("{} isn't a secure (https://) address", url))
+    }
+}
+
+/// Address of `file`, named in the list at `base`: relative to it, or a
+/// full address
+pub fn resolve(base: &str, file: &str) -> String {
+    if file.contains("://") {
+        return file.to_string();
+    }
+    let base = base.rfind('/').map_or(base, |i| &base[..=i]);
+    format!("{}{}", base, file.trim_start_matches('/'))
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout_connect(TIMEOUT).timeout_read(TIMEOUT).build()
+}
+
+fn error_text(e: ureq::Error) -> String {
+    match e {
+        ureq::Error::Status(code, _) => format!("the server answered {}", code),
+        ureq::Error::Transport(t) => t.to_string(),
+    }
+}
+
+/// Read the text at https address `url`. Slow: call off the UI thread.
+pub fn get_text(url: &str) -> Result<String, String> {
+    check_https(url)?;
+    agent().get(url).call().map_err(error_text)?.into_string().map_err(|e| e.to_string())
+}
+
+/// SHA-256 of the file at `path`, in lowercase hex
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Copy `from` to `dest`, refusing more than `max` bytes, then check it
+/// against `sha256` when there is one. Nothing is left at `dest` if it
+/// fails. Slow: call off the UI thread.
+pub fn fetch(from: Origin, dest: &Path, max: u64, sha256: Option<&str>) -> Result<(), String> {
+    let result = copy_to(from, dest, max).and_then(|_| match sha256 {
+        Some(expected) if sha256_file(dest).map_err(|e| e.to_string())? != expected.trim().to_lowercase() => {
+            Err("the download doesn't match its checksum".into())
+        }
+        _ => Ok(()),
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(dest);
+    }
+    result
+}
+
+fn copy_to(from: Origin, dest: &Path, max: u64) -> Result<(), String> {
+    let body: Box<dyn Read> = match from {
+        Origin::Web(url) => {
+            check_https(url)?;
+            Box::new(agent().get(url).call().map_err(error_text)?.into_reader())
+        }
+        Origin::File(path) => Box::new(std::fs::File::open(path).map_err(|e| e.to_string())?),
+    };
+    let mut out = std::fs::File::create(dest).map_err(|e| e.to_string())?;
+    let copied = std::io::copy(&mut body.take(max + 1), &mut out).map_err(|e| e.to_string())?;
+    if copied > max {
+        return Err("the download is too big".into());
+    }
+    Ok(())
+}
+
+/// Check the hex ed25519 `signature` over `data` against the hex public `key`
+pub fn verify_signature(data: &[u8], signature: &str, key: &str) -> Result<(), String> {
+    let key: [u8; 32] = from_hex(key)
+        .and_then(|k| k.try_into().ok())
+        .ok_or("the signing key isn't a 64-digit hex key")?;
+    let key = VerifyingKey::from_bytes(&key).map_err(|_| "the signing key isn't valid")?;
+    let signature: [u8; 64] = from_hex(signature)
+        .and_then(|s| s.try_into().ok())
+        .ok_or("the signature can't be read")?;
+    key.verify_strict(data, &Signature::from_bytes(&signature))
+        .map_err(|_| "the signature doesn't match the signing key".to_string())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim();
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn test_fetch_checks_sha256() {
+        let dir = std::env::temp_dir().join(format!("slowcore_download_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (from, dest) = (dir.join("abc"), dir.join("abc.part"));
+        std::fs::write(&from, "abc").unwrap();
+        let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(sha256_file(&from).unwrap(), abc);
+
+        assert!(fetch(Origin::File(&from), &dest, 1024, Some(&abc.to_uppercase())).is_ok());
+        assert_eq!(std::fs::read(&dest).unwrap(), b"abc");
+        assert!(fetch(Origin::File(&from), &dest, 1024, Some("00")).is_err());
+        assert!(!dest.exists());
+        assert!(fetch(Origin::File(&from), &dest, 2, None).is_err());
+        assert!(fetch(Origin::Web("http://example.org/abc"), &dest, 1024, None).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let list = "https://example.org/slowos/manifest.json";
+        assert_eq!(resolve(list, "slowwrite"), "https://example.org/slowos/slowwrite");
+        assert_eq!(resolve(list, "https://cdn.example.org/w"), "https://cdn.example.org/w");
+        assert!(check_https(list).is_ok());
+        assert!(check_https("http://example.org/manifest.json").is_err());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let signer = SigningKey::from_bytes(&[7; 32]);
+        let key = to_hex(signer.verifying_key().as_bytes());
+        let data = br#"{"apps": []}"#;
+        let signature = to_hex(&signer.sign(data).to_bytes());
+        assert!(verify_signature(data, &signature, &key).is_ok());
+        assert!(verify_signature(br#"{"apps": [1]}"#, &signature, &key).is_err());
+        assert!(verify_signature(data, "00", &key).is_err());
+        assert!(verify_signature(data, &signature, "not a key").is_err());
+    }
+}
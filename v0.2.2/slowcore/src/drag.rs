@@ -3,9 +3,24 @@
 //! Uses a temp file to communicate drag state between slowOS applications.
 //! When one app starts dragging files, it writes their paths to a temp file.
 //! Other apps can check for this file to accept drops.
+//!
+//! Apps taking drops use a [`DropTarget`], which also takes files dropped
+//! from outside slowOS:
+//!
+//! ```ignore
+//! for path in DropTarget::new(&["txt", "md"]).show(ctx) {
+//!     self.open_file(path);
+//! }
+//! ```
 
+use crate::theme::ui_scale;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set while a drag started in this process, so its own window doesn't
+/// offer to take it
+static LOCAL_DRAG: AtomicBool = AtomicBool::new(false);
 
 /// Get the path to the drag state file
 fn drag_state_path() -> PathBuf {
@@ -23,12 +38,14 @@ pub fn start_drag(paths: &[PathBuf]) {
         .map(|s| s.to_string())
         .collect();
     let _ = fs::write(drag_state_path(), content.join("\n"));
+    LOCAL_DRAG.store(true, Ordering::Relaxed);
 }
 
 /// End/cancel a drag operation
 /// Called when drag ends (drop or cancel)
 pub fn end_drag() {
     let _ = fs::remove_file(drag_state_path());
+    LOCAL_DRAG.store(false, Ordering::Relaxed);
 }
 
 /// Check if there's an active drag operation and get the paths
@@ -66,3 +83,83 @@ pub fn get_drag_paths() -> Option<Vec<PathBuf>> {
         Some(paths)
     }
 }
+
+/// The files a window takes when they're dropped on it
+pub struct DropTarget<'a> {
+    extensions: &'a [&'a str],
+    folders: bool,
+}
+
+impl<'a> DropTarget<'a> {
+    /// Files with one of `extensions` (lowercase, without the dot). With
+    /// none, any file.
+    pub fn new(extensions: &'a [&'a str]) -> Self {
+        Self { extensions, folders: false }
+    }
+
+    /// Take folders too
+    pub fn folders(mut self, folders: bool) -> Self {
+        self.folders = folders;
+        self
+    }
+
+    pub fn accepts(&self, path: &Path) -> bool {
+        if path.is_dir() {
+            return self.folders;
+        }
+        if self.extensions.is_empty() {
+            return true;
+        }
+        let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default();
+        self.extensions.contains(&ext.as_str())
+    }
+
+    /// Call once a frame. Highlights the window while files it takes are
+    /// dragged over it, from another slowOS app or from outside, and
+    /// returns the ones dropped this frame.
+    pub fn show(&self, ctx: &egui::Context) -> Vec<PathBuf> {
+        let (mut hovering, dropped, in_window, released) = ctx.input(|i| {
+            (
+                // Some systems don't say what's being dragged until it's dropped
+                i.raw.hovered_files.iter().any(|f| f.path.as_ref().is_none_or(|p| self.accepts(p))),
+                i.raw.dropped_files.iter().filter_map(|f| f.path.clone()).filter(|p| self.accepts(p)).collect::<Vec<_>>(),
+                i.pointer.has_pointer(),
+                i.pointer.primary_released(),
+            )
+        });
+        if !dropped.is_empty() {
+            return dropped;
+        }
+
+        if in_window && !LOCAL_DRAG.load(Ordering::Relaxed) {
+            if let Some(paths) = get_drag_paths() {
+                let paths: Vec<PathBuf> = paths.into_iter().filter(|p| self.accepts(p)).collect();
+                if released && !paths.is_empty() {
+                    end_drag();
+                    return paths;
+                }
+                hovering |= !paths.is_empty();
+            }
+        }
+
+        if hovering {
+            let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("slowcore_drop_target")));
+            crate::dither::draw_dither_outline(&painter, ctx.screen_rect(), 6.0 * ui_scale());
+        }
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_target_extensions() {
+        let text = DropTarget::new(&["txt", "md"]);
+        assert!(text.accepts(Path::new("/nonexistent/notes.TXT")));
+        assert!(!text.accepts(Path::new("/nonexistent/song.mp3")));
+        assert!(!text.accepts(&std::env::temp_dir()));
+        assert!(DropTarget::new(&[]).folders(true).accepts(&std::env::temp_dir()));
+    }
+}
@@ -0,0 +1,279 @@
+//! Scripting — commands sent to running apps
+//!
+//! `slowctl` (or any program) [`send`]s a [`Command`] to a running app and
+//! waits for its [`Reply`]:
+//!
+//! ```ignore
+//! let pid = ipc::find_app("slownotes").ok_or("slowNotes isn't running")?;
+//! ipc::send(pid, &Command::Type { text: "hello".into() }, ipc::REPLY_TIMEOUT);
+//! ```
+//!
+//! Inside the app, `RepaintController` picks commands up each frame
+//! ([`deliver`]). Text to type goes straight to the field that has the
+//! keyboard, as if typed. Everything else waits for the app to [`take`]
+//! and answer it; whatever the app leaves is answered "not supported"
+//! when the frame ends ([`finish`]).
+//!
+//! Same file-based IPC as the on-screen keyboard, in the runtime dir:
+//! `slowos-ipc/<pid>/<id>.json` for commands and
+//! `slowos-ipc/replies/<id>.json` for the answers.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How often apps look for commands
+const WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often [`send`] looks for the answer
+const REPLY_POLL: Duration = Duration::from_millis(20);
+
+/// How long to wait for an app to answer
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Commands are waiting for this process
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Commands delivered this frame, for the app to take
+static INBOX: Mutex<Vec<Request>> = Mutex::new(Vec::new());
+
+/// Something a script asks an app to do
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    /// Open a file and bring the window to the front
+    Open { path: PathBuf },
+    /// Type text, as if on the keyboard
+    Type { text: String },
+    /// Choose a menu item by its menu and label, without the shortcut:
+    /// `["file", "save"]`
+    Menu { item: Vec<String> },
+    /// Report some of the app's state, such as "document" or "modified";
+    /// each app documents its own keys
+    Query { key: String },
+}
+
+impl Command {
+    /// The command as it reads in messages: "menu file → save"
+    fn describe(&self) -> String {
+        match self {
+            Command::Open { .. } => "open".into(),
+            Command::Type { .. } => "type".into(),
+            Command::Menu { item } => format!("menu {}", item.join(" → ")),
+            Command::Query { key } => format!("query {}", key),
+        }
+    }
+}
+
+/// An app's answer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply {
+    Done,
+    /// The answer to a query
+    Value(serde_json::Value),
+    /// Why it couldn't be done
+    Failed(String),
+}
+
+/// A command waiting for an answer. Dropping it without one leaves the
+/// sender to time out, so answer every command taken.
+#[derive(Debug)]
+pub struct Request {
+    id: String,
+    pub command: Command,
+}
+
+impl Request {
+    pub fn reply(self, reply: Reply) {
+        let dir = replies_dir();
+        let Ok(json) = serde_json::to_string(&reply) else { return };
+        let tmp = dir.join(format!("{}.tmp", self.id));
+        if std::fs::write(&tmp, json).is_ok() {
+            let _ = std::fs::rename(&tmp, dir.join(format!("{}.json", self.id)));
+        }
+    }
+
+    /// Whether this is the menu item `menu` → `label`, ignoring case
+    pub fn is_menu(&self, menu: &str, label: &str) -> bool {
+        match &self.command {
+            Command::Menu { item } => {
+                item.len() == 2 && item[0].eq_ignore_ascii_case(menu) && item[1].eq_ignore_ascii_case(label)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn ipc_dir() -> PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("slowos-ipc");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+fn commands_dir(pid: u32) -> PathBuf {
+    ipc_dir().join(pid.to_string())
+}
+
+fn replies_dir() -> PathBuf {
+    let dir = ipc_dir().join("replies");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// The process running `binary`: one the desktop started if there is one,
+/// else any
+pub fn find_app(binary: &str) -> Option<u32> {
+    if let Some(app) = crate::running::read_all().into_iter().find(|a| a.binary == binary) {
+        return Some(app.pid);
+    }
+    // The kernel keeps only the first 15 bytes of the name
+    let short = binary.get(..15).unwrap_or(binary);
+    std::fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|&pid| pid != std::process::id())
+        .find(|pid| std::fs::read_to_string(format!("/proc/{}/comm", pid)).is_ok_and(|comm| comm.trim_end() == short))
+}
+
+/// Send `command` to process `pid` and wait up to `timeout` for the answer
+pub fn send(pid: u32, command: &Command, timeout: Duration) -> Reply {
+    static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+    let dir = commands_dir(pid);
+    let _ = std::fs::create_dir_all(&dir);
+    // Time first so commands are taken in order
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let id = format!("{:020}_{}_{}", nanos, std::process::id(), SEQUENCE.fetch_add(1, Ordering::Relaxed));
+    let Ok(json) = serde_json::to_string(command) else {
+        return Reply::Failed("could not encode the command".into());
+    };
+    // Written aside and renamed, so the app never reads half a command
+    let tmp = dir.join(format!("{}.tmp", id));
+    let path = dir.join(format!("{}.json", id));
+    if let Err(e) = std::fs::write(&tmp, json).and_then(|_| std::fs::rename(&tmp, &path)) {
+        return Reply::Failed(format!("could not send: {}", e));
+    }
+
+    let answer = replies_dir().join(format!("{}.json", id));
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if let Ok(json) = std::fs::read_to_string(&answer) {
+            let _ = std::fs::remove_file(&answer);
+            return serde_json::from_str(&json).unwrap_or_else(|e| Reply::Failed(format!("unreadable answer: {}", e)));
+        }
+        std::thread::sleep(REPLY_POLL);
+    }
+    // Don't leave it to be done later, out of the blue
+    let _ = std::fs::remove_file(&path);
+    Reply::Failed("no answer".into())
+}
+
+/// Commands waiting for this process, oldest first; they are removed
+fn take_waiting() -> Vec<Request> {
+    let dir = commands_dir(std::process::id());
+    let Ok(entries) = std::fs::read_dir(&dir) else { return Vec::new() };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let json = std::fs::read_to_string(&path).ok();
+            let _ = std::fs::remove_file(&path);
+            let command = serde_json::from_str(&json?).ok()?;
+            let id = path.file_stem()?.to_string_lossy().into_owned();
+            Some(Request { id, command })
+        })
+        .collect()
+}
+
+/// Wake `ctx` when commands arrive. Only the first call starts a thread.
+fn watch(ctx: &egui::Context) {
+    static WATCHING: AtomicBool = AtomicBool::new(false);
+    if WATCHING.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    let ctx = ctx.clone();
+    let dir = commands_dir(std::process::id());
+    let _ = std::thread::Builder::new()
+        .name("slowcore-ipc".into())
+        .spawn(move || loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let waiting = std::fs::read_dir(&dir)
+                .is_ok_and(|mut d| d.any(|e| e.is_ok_and(|e| e.path().extension().is_some_and(|x| x == "json"))));
+            if waiting && !PENDING.swap(true, Ordering::Relaxed) {
+                ctx.request_repaint();
+            }
+        });
+}
+
+/// Pick up this frame's commands. Text goes to the field with the
+/// keyboard, if one has it. Called by `RepaintController::begin_frame`.
+pub(crate) fn deliver(ctx: &egui::Context) {
+    watch(ctx);
+    if !PENDING.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    let typing = ctx.memory(|m| m.focused().is_some());
+    let mut inbox = INBOX.lock().unwrap_or_else(|e| e.into_inner());
+    for request in take_waiting() {
+        match &request.command {
+            Command::Type { text } if typing => {
+                let text = text.clone();
+                ctx.input_mut(|i| i.events.push(egui::Event::Text(text)));
+                request.reply(Reply::Done);
+            }
+            _ => inbox.push(request),
+        }
+    }
+}
+
+/// Commands for the app to carry out this frame. Answer each with
+/// [`Request::reply`].
+pub fn take() -> Vec<Request> {
+    std::mem::take(&mut *INBOX.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Answer the commands the app didn't take. Called by
+/// `RepaintController::end_frame`.
+pub(crate) fn finish() {
+    let left = take();
+    if left.is_empty() {
+        return;
+    }
+    let exe = std::env::current_exe().ok();
+    let app = exe.as_ref().and_then(|p| p.file_stem()).map(|s| s.to_string_lossy().into_owned());
+    for request in left {
+        let reason = match &request.command {
+            Command::Type { .. } => "nothing to type into".to_string(),
+            command => format!("{} doesn't support \"{}\"", app.as_deref().unwrap_or("this app"), command.describe()),
+        };
+        request.reply(Reply::Failed(reason));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_format() {
+        let command = Command::Menu { item: vec!["file".into(), "save".into()] };
+        let json = serde_json::to_string(&command).unwrap();
+        assert_eq!(json, r#"{"command":"menu","item":["file","save"]}"#);
+        let request = Request { id: "1".into(), command };
+        assert!(request.is_menu("File", "save"));
+        assert!(!request.is_menu("file", "save as"));
+        let reply: Reply = serde_json::from_str(r#"{"value":true}"#).unwrap();
+        assert_eq!(reply, Reply::Value(serde_json::Value::Bool(true)));
+    }
+}
@@ -0,0 +1,217 @@
+//! On-screen keyboard — key presses from slowKeyboard to the app in use
+//!
+//! Every app notes whether its window has the focus and whether a text
+//! field has the keyboard ([`note_focus`], called by `RepaintController`).
+//! slowKeyboard leaves that note alone ([`ignore_focus`]), so it always
+//! names the app that was last typed into, even while the keyboard's own
+//! window is being tapped. The keyboard [`send`]s presses to that app,
+//! whose `RepaintController` turns them into ordinary egui events
+//! ([`deliver`]).
+//!
+//! slowDesktop opens the keyboard by itself when a text field gains focus,
+//! if the user turned that on in settings ([`auto_show`]).
+//!
+//! Same file-based IPC as minimize, but in the runtime dir (tmpfs), as a
+//! press is written for every tap: `slowos-keyboard/focus.json` and
+//! `slowos-keyboard/<pid>/<n>.json`.
+
+use crate::process;
+use egui::{Event, Key, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often apps look for presses from the keyboard
+const WATCH_INTERVAL: Duration = Duration::from_millis(60);
+
+/// Set in slowKeyboard, whose window never counts as the app in use
+static IGNORE_FOCUS: AtomicBool = AtomicBool::new(false);
+
+/// Presses are waiting for this process
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+/// One tap on the on-screen keyboard
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum KeyPress {
+    /// Typed text
+    Text(String),
+    /// A key that doesn't type, or any key with ⌘; named as in `egui::Key::name`
+    Key { key: String, shift: bool, command: bool },
+}
+
+impl KeyPress {
+    pub fn key(key: Key) -> Self {
+        KeyPress::Key { key: key.name().to_string(), shift: false, command: false }
+    }
+
+    /// The events egui would see for this press from a real keyboard
+    pub fn events(&self) -> Vec<Event> {
+        match self {
+            KeyPress::Text(text) => vec![Event::Text(text.clone())],
+            KeyPress::Key { key, shift, command } => {
+                let Some(key) = Key::from_name(key) else { return Vec::new() };
+                let modifiers = Modifiers { shift: *shift, command: *command, ctrl: *command, ..Default::default() };
+                [true, false]
+                    .into_iter()
+                    .map(|pressed| Event::Key { key, physical_key: Some(key), pressed, repeat: false, modifiers })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// The app last in use
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Focus {
+    pub pid: u32,
+    /// A text field has the keyboard
+    pub typing: bool,
+}
+
+fn keyboard_dir() -> PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("slowos-keyboard");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+fn presses_dir(pid: u32) -> PathBuf {
+    keyboard_dir().join(pid.to_string())
+}
+
+/// Whether the on-screen keyboard opens by itself for text fields
+pub fn auto_show() -> bool {
+    crate::theme::system_setting("onscreen_keyboard")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Keep this app's window from counting as the app in use (slowKeyboard)
+pub fn ignore_focus() {
+    IGNORE_FOCUS.store(true, Ordering::Relaxed);
+}
+
+/// Note whether this app is in use and typing. Called by
+/// `RepaintController::end_frame`; writes only when something changed.
+pub(crate) fn note_focus(ctx: &egui::Context) {
+    // Whether a text field had the keyboard, while the window has the focus
+    static LAST: Mutex<Option<bool>> = Mutex::new(None);
+    if IGNORE_FOCUS.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut last = LAST.lock().unwrap_or_else(|e| e.into_inner());
+    if !ctx.input(|i| i.viewport().focused.unwrap_or(false)) {
+        // Written again when the window comes back
+        *last = None;
+        return;
+    }
+    let typing = ctx.wants_keyboard_input();
+    if *last == Some(typing) {
+        return;
+    }
+    *last = Some(typing);
+    let focus = Focus { pid: std::process::id(), typing };
+    if let Ok(json) = serde_json::to_string(&focus) {
+        let path = keyboard_dir().join("focus.json");
+        let tmp = path.with_extension("tmp");
+        if std::fs::write(&tmp, json).is_ok() {
+            let _ = std::fs::rename(&tmp, &path);
+        }
+    }
+}
+
+/// The app last in use, if it is still running
+pub fn focus() -> Option<Focus> {
+    let json = std::fs::read_to_string(keyboard_dir().join("focus.json")).ok()?;
+    let focus: Focus = serde_json::from_str(&json).ok()?;
+    process::is_alive(focus.pid).then_some(focus)
+}
+
+/// Send a press to the app last in use. False if there is none.
+pub fn send(press: &KeyPress) -> bool {
+    static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+    let Some(focus) = focus() else { return false };
+    let dir = presses_dir(focus.pid);
+    let _ = std::fs::create_dir_all(&dir);
+    // Time first so presses sort in order across keyboard restarts
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let name = format!("{:020}_{:06}", nanos, SEQUENCE.fetch_add(1, Ordering::Relaxed));
+    let Ok(json) = serde_json::to_string(press) else { return false };
+    // Written aside and renamed, so the app never reads half a press
+    let tmp = dir.join(format!("{}.tmp", name));
+    std::fs::write(&tmp, json).is_ok() && std::fs::rename(&tmp, dir.join(format!("{}.json", name))).is_ok()
+}
+
+/// Presses waiting for this process, oldest first; they are removed
+fn take_presses() -> Vec<KeyPress> {
+    let dir = presses_dir(std::process::id());
+    let Ok(entries) = std::fs::read_dir(&dir) else { return Vec::new() };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let json = std::fs::read_to_string(&path).ok();
+            let _ = std::fs::remove_file(&path);
+            serde_json::from_str(&json?).ok()
+        })
+        .collect()
+}
+
+/// Wake `ctx` when presses arrive. Only the first call starts a thread.
+fn watch(ctx: &egui::Context) {
+    static WATCHING: AtomicBool = AtomicBool::new(false);
+    if WATCHING.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    let ctx = ctx.clone();
+    let dir = presses_dir(std::process::id());
+    let _ = std::thread::Builder::new()
+        .name("slowcore-keyboard".into())
+        .spawn(move || loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let waiting = std::fs::read_dir(&dir)
+                .is_ok_and(|mut d| d.any(|e| e.is_ok_and(|e| e.path().extension().is_some_and(|x| x == "json"))));
+            if waiting && !PENDING.swap(true, Ordering::Relaxed) {
+                ctx.request_repaint();
+            }
+        });
+}
+
+/// Turn presses from the on-screen keyboard into this frame's input.
+/// Called by `RepaintController::begin_frame`.
+pub(crate) fn deliver(ctx: &egui::Context) {
+    watch(ctx);
+    if !PENDING.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    let events: Vec<Event> = take_presses().iter().flat_map(KeyPress::events).collect();
+    if !events.is_empty() {
+        ctx.input_mut(|i| i.events.extend(events));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_press_events() {
+        let press = KeyPress::Key { key: "Backspace".into(), shift: false, command: false };
+        let json = serde_json::to_string(&press).unwrap();
+        assert_eq!(serde_json::from_str::<KeyPress>(&json).unwrap(), press);
+        let events = press.events();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], Event::Key { key: Key::Backspace, pressed: true, .. }));
+        assert_eq!(KeyPress::Text("é".into()).events(), vec![Event::Text("é".into())]);
+        assert_eq!(KeyPress::key(Key::Enter), KeyPress::Key { key: "Enter".into(), shift: false, command: false });
+    }
+}
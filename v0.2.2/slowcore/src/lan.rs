@@ -0,0 +1,178 @@
+//! LAN presence — find other Slowbooks in the room
+//!
+//! Each app that talks over the network advertises itself with mDNS under
+//! its own service type ("_slowchat._tcp") and browses for the same type.
+//! Devices are told apart by a random id made on first use; the name shown
+//! to others is the user's choice and shared by every app.
+//!
+//! The identity lives in ~/.config/slowos/device.json.
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// This device as others see it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Device {
+    pub id: String,
+    pub name: String,
+}
+
+fn device_path() -> PathBuf {
+    directories::ProjectDirs::from("", "", "slowos")
+        .map(|p| p.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/slowos"))
+        .join("device.json")
+}
+
+/// This device's id and name, created on first use
+pub fn device() -> Device {
+    if let Some(device) = std::fs::read_to_string(device_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+    {
+        return device;
+    }
+    // RandomState is seeded from the OS, which is random enough for an id
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos());
+    let id = format!("{:016x}", hasher.finish());
+    let hostname = std::fs::read_to_string("/etc/hostname").map(|h| h.trim().to_string()).unwrap_or_default();
+    let name = if hostname.is_empty() { format!("slowbook-{}", &id[..4]) } else { hostname };
+    let device = Device { id, name };
+    save_device(&device);
+    device
+}
+
+fn save_device(device: &Device) {
+    let path = device_path();
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(device) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// Change the name other devices see. Apps already advertising pick it up
+/// when they next call [`Presence::rename`] or restart.
+pub fn set_device_name(name: &str) -> Device {
+    let mut device = device();
+    device.name = name.trim().to_string();
+    save_device(&device);
+    device
+}
+
+/// Another device offering the same service
+#[derive(Debug, Clone, PartialEq)]
+pub struct Peer {
+    pub id: String,
+    pub name: String,
+    pub addr: SocketAddr,
+}
+
+/// Advertises this device and keeps a live list of peers
+pub struct Presence {
+    daemon: ServiceDaemon,
+    service_type: String,
+    port: u16,
+    /// Registered full name, so it can be withdrawn
+    fullname: Option<String>,
+    /// Peers by mDNS full name
+    peers: Arc<Mutex<HashMap<String, Peer>>>,
+}
+
+impl Presence {
+    /// Start advertising `service` ("slowchat") on `port` and browsing for
+    /// other devices; `ctx` is repainted whenever the peer list changes
+    pub fn start(service: &str, port: u16, ctx: egui::Context) -> Result<Self, String> {
+        let daemon = ServiceDaemon::new().map_err(|e| e.to_string())?;
+        let service_type = format!("_{}._tcp.local.", service);
+        let own_id = device().id;
+        let peers = Arc::new(Mutex::new(HashMap::new()));
+
+        let events = daemon.browse(&service_type).map_err(|e| e.to_string())?;
+        let list = peers.clone();
+        let _ = std::thread::Builder::new()
+            .name(format!("slowcore-lan-{}", service))
+            .spawn(move || {
+                while let Ok(event) = events.recv() {
+                    let Ok(mut peers) = list.lock() else { break };
+                    match event {
+                        ServiceEvent::ServiceResolved(info) => {
+                            let id = info.get_property_val_str("id").unwrap_or_default().to_string();
+                            let name = info.get_property_val_str("name").unwrap_or("slowbook").to_string();
+                            // Prefer IPv4; link-local IPv6 needs a scope id we don't track
+                            let addrs = info.get_addresses();
+                            let ip = addrs.iter().find(|a| a.is_ipv4()).or_else(|| addrs.iter().next());
+                            if let (Some(ip), false) = (ip, id.is_empty() || id == own_id) {
+                                let addr = SocketAddr::new(*ip, info.get_port());
+                                peers.insert(info.get_fullname().to_string(), Peer { id, name, addr });
+                            }
+                        }
+                        ServiceEvent::ServiceRemoved(_, fullname) => {
+                            peers.remove(&fullname);
+                        }
+                        _ => continue,
+                    }
+                    ctx.request_repaint();
+                }
+            });
+
+        let mut presence = Self { daemon, service_type, port, fullname: None, peers };
+        presence.register()?;
+        Ok(presence)
+    }
+
+    fn register(&mut self) -> Result<(), String> {
+        if let Some(old) = self.fullname.take() {
+            let _ = self.daemon.unregister(&old);
+        }
+        let device = device();
+        let host = format!("{}.local.", device.id);
+        let properties = [("id", device.id.as_str()), ("name", device.name.as_str())];
+        let info = ServiceInfo::new(&self.service_type, &device.id, &host, "", self.port, &properties[..])
+            .map_err(|e| e.to_string())?
+            .enable_addr_auto();
+        self.fullname = Some(info.get_fullname().to_string());
+        self.daemon.register(info).map_err(|e| e.to_string())
+    }
+
+    /// Re-advertise after the device name changed
+    pub fn rename(&mut self) -> Result<(), String> {
+        self.register()
+    }
+
+    /// Peers online now, by name
+    pub fn peers(&self) -> Vec<Peer> {
+        let mut peers: Vec<Peer> = self.peers.lock().map(|p| p.values().cloned().collect()).unwrap_or_default();
+        peers.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        peers.dedup_by(|a, b| a.id == b.id);
+        peers
+    }
+
+    pub fn peer(&self, id: &str) -> Option<Peer> {
+        self.peers().into_iter().find(|p| p.id == id)
+    }
+}
+
+impl Drop for Presence {
+    fn drop(&mut self) {
+        if let Some(fullname) = self.fullname.take() {
+            let _ = self.daemon.unregister(&fullname);
+        }
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// Whether `ip` is on a private network, where LAN apps accept connections
+pub fn is_local(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_loopback() || v4.is_link_local(),
+        IpAddr::V6(v6) => v6.is_loopback() || (v6.segments()[0] & 0xffc0) == 0xfe80 || (v6.segments()[0] & 0xfe00) == 0xfc00,
+    }
+}
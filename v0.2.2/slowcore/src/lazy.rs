@@ -0,0 +1,173 @@
+//! Lazy startup — slow work after the first frame, and how long startup took
+//!
+//! An app's window should show something straight away. Work that can
+//! wait (opening the sound card, scanning a folder of books) goes in a
+//! [`Lazy`], which starts it on a thread of its own once the first frame
+//! is on screen:
+//!
+//! ```ignore
+//! // in new():
+//! books: Lazy::new("library scan", scan_library),
+//! // each frame:
+//! self.books.poll(ctx);
+//! for book in self.books.get().map_or(&[][..], Vec::as_slice) { ... }
+//! // when it's needed right now (the user pressed play):
+//! if let Some(audio) = self.audio.wait() { ... }
+//! ```
+//!
+//! Startup is timed with [`mark`]s, counted from the first one, which
+//! [`window_layout::viewport`](crate::window_layout::viewport) makes before
+//! the window opens. The repaint controller marks the first paint and logs
+//! the marks, and a `Lazy` marks when its work is done. The performance
+//! overlay shows how long the first paint took and when the last work
+//! finished.
+
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Startup marks so far, in the order they were made
+static MARKS: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+
+fn start() -> Instant {
+    static START: OnceLock<Instant> = OnceLock::new();
+    *START.get_or_init(Instant::now)
+}
+
+/// Note that startup reached `name`, timed from the first mark
+pub fn mark(name: &'static str) {
+    let at = start().elapsed();
+    MARKS.lock().unwrap_or_else(|e| e.into_inner()).push((name, at));
+}
+
+/// Every mark so far, with its time since the first
+pub fn marks() -> Vec<(&'static str, Duration)> {
+    MARKS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// When `name` was first marked
+pub fn marked(name: &str) -> Option<Duration> {
+    MARKS.lock().unwrap_or_else(|e| e.into_inner()).iter().find(|(n, _)| *n == name).map(|(_, at)| *at)
+}
+
+/// "window 3ms, first paint 412ms, library scan 930ms"
+pub fn summary() -> String {
+    marks().iter().map(|(name, at)| format!("{} {}ms", name, at.as_millis())).collect::<Vec<_>>().join(", ")
+}
+
+type Init<T> = Box<dyn FnOnce() -> T + Send>;
+
+enum State<T> {
+    /// Not started yet
+    Waiting(Init<T>),
+    /// On its thread
+    Running(Receiver<T>),
+    Ready(T),
+    /// The work panicked
+    Failed,
+}
+
+/// A value made in the background once the app's first frame is painted
+pub struct Lazy<T> {
+    name: &'static str,
+    state: State<T>,
+}
+
+impl<T: Send + 'static> Lazy<T> {
+    /// `name` is the startup mark made when `init` is done
+    pub fn new(name: &'static str, init: impl FnOnce() -> T + Send + 'static) -> Self {
+        Self { name, state: State::Waiting(Box::new(init)) }
+    }
+
+    /// Call once a frame. Starts the work once the first frame is on
+    /// screen, and picks up the value when it's done (waking `ctx`).
+    pub fn poll(&mut self, ctx: &egui::Context) {
+        match &self.state {
+            // Frame 0 is painted once this frame starts
+            State::Waiting(_) if ctx.frame_nr() > 0 => {
+                let State::Waiting(init) = std::mem::replace(&mut self.state, State::Failed) else { return };
+                let (tx, rx) = mpsc::channel();
+                let ctx = ctx.clone();
+                let name = self.name;
+                let spawned = std::thread::Builder::new().name(format!("slowcore-lazy-{}", name)).spawn(move || {
+                    let value = init();
+                    mark(name);
+                    let _ = tx.send(value);
+                    ctx.request_repaint();
+                });
+                self.state = match spawned {
+                    Ok(_) => State::Running(rx),
+                    Err(e) => {
+                        crate::log::warn!("couldn't start {} in the background: {}", name, e);
+                        State::Failed
+                    }
+                };
+            }
+            State::Running(rx) => match rx.try_recv() {
+                Ok(value) => self.state = State::Ready(value),
+                Err(mpsc::TryRecvError::Disconnected) => self.state = State::Failed,
+                Err(mpsc::TryRecvError::Empty) => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// The value, once it's ready
+    pub fn get(&self) -> Option<&T> {
+        match &self.state {
+            State::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        match &mut self.state {
+            State::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        matches!(self.state, State::Ready(_))
+    }
+
+    /// The value now: the work is done here if it hasn't started, or
+    /// waited for if it's running. `None` if it panicked.
+    pub fn wait(&mut self) -> Option<&mut T> {
+        match std::mem::replace(&mut self.state, State::Failed) {
+            State::Waiting(init) => {
+                self.state = State::Ready(init());
+                mark(self.name);
+            }
+            State::Running(rx) => {
+                if let Ok(value) = rx.recv() {
+                    self.state = State::Ready(value);
+                }
+            }
+            state => self.state = state,
+        }
+        self.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lazy_waits_for_first_paint() {
+        let ctx = egui::Context::default();
+        let mut lazy = Lazy::new("test", || 42);
+        // Nothing runs before the first frame is painted
+        lazy.poll(&ctx);
+        assert!(matches!(lazy.state, State::Waiting(_)));
+        let _ = ctx.run(Default::default(), |_| {});
+        lazy.poll(&ctx);
+        assert_eq!(lazy.wait().copied(), Some(42));
+        assert!(marked("test").is_some());
+
+        // Asked for before it started: done on the spot
+        let mut eager = Lazy::new("eager", || "now");
+        assert_eq!(eager.wait().copied(), Some("now"));
+    }
+}
@@ -1,26 +1,50 @@
 //! slowcore — shared library for slow computer applications
 
+pub mod accounts;
+pub mod animation;
+pub mod app_registry;
+pub mod autocorrect;
+pub mod clock;
+pub mod confirm;
+pub mod display;
 pub mod dither;
+pub mod download;
 pub mod drag;
+pub mod ipc;
+pub mod keyboard;
+pub mod lan;
+pub mod lazy;
+pub mod lifecycle;
+pub mod log;
+pub mod logs;
+pub mod menu_extra;
 pub mod minimize;
+pub mod night_shift;
+pub mod notify;
+pub mod plugins;
+pub mod preview;
+pub mod print;
+pub mod process;
 pub mod repaint;
+pub mod restrictions;
+pub mod running;
 pub mod safety;
+pub mod session;
+pub mod shortcuts;
+pub mod sound;
 pub mod storage;
+pub mod sync;
+pub mod testkit;
 pub mod text_edit;
+pub mod textures;
 pub mod theme;
+pub mod thumbnails;
+pub mod tts;
+pub mod undo;
+pub mod update;
+pub mod volumes;
 pub mod widgets;
+pub mod window_layout;
 
 pub use repaint::RepaintController;
 pub use theme::SlowTheme;
-
-/// Get cascade window position offset from environment variable
-/// Returns (x_offset, y_offset) based on SLOWOS_CASCADE env var
-/// Used for staggering multiple window instances
-pub fn cascade_position() -> Option<egui::Pos2> {
-    std::env::var("SLOWOS_CASCADE").ok()
-        .and_then(|s| s.parse::<u32>().ok())
-        .map(|n| {
-            let offset = (n as f32) * 30.0;
-            egui::Pos2::new(100.0 + offset, 100.0 + offset)
-        })
-}
@@ -0,0 +1,219 @@
+//! Saved state — keeping the user's place when the app is killed or the
+//! battery is about to run out
+//!
+//! slowDesktop asks apps to quit before powering off (see `session`), but
+//! not every ending goes through it: the system sends SIGTERM when it
+//! shuts down by itself, and a flat battery gives no warning at all. Apps
+//! that implement [`SaveState`] and call [`update`] once per frame get a
+//! snapshot written when a SIGTERM, SIGHUP or SIGPWR arrives (the app then
+//! exits), and every minute while the battery is critically low. On the
+//! next launch [`update`] asks whether to carry on from it:
+//!
+//! ```ignore
+//! impl SaveState for MyApp {
+//!     type State = Game;
+//!     fn save_state(&self) -> Option<Game> { Some(self.game.clone()) }
+//!     fn restore_state(&mut self, game: Game) { self.game = game; }
+//! }
+//! // once per frame:
+//! slowcore::lifecycle::update(ctx, "slowsolitaire", self);
+//! // in on_exit, as a normal close leaves nothing to restore:
+//! slowcore::lifecycle::discard("slowsolitaire");
+//! ```
+//!
+//! The snapshot lives in ~/.config/<app>/saved_state.json.
+
+use crate::safety;
+use crate::storage::{config_dir, save_atomic, Result};
+use egui::{Align2, Context};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Battery percentage at which snapshots start being taken
+pub const CRITICAL_BATTERY_PERCENT: u8 = 5;
+
+/// How often the battery is looked at, and a snapshot retaken while it's critical
+const BATTERY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long the app has to save after a signal before it exits regardless
+const EXIT_GRACE: Duration = Duration::from_secs(3);
+
+/// An app whose state can be saved and brought back
+pub trait SaveState {
+    type State: Serialize + DeserializeOwned;
+
+    /// What the user would lose, or `None` if there's nothing worth keeping
+    fn save_state(&self) -> Option<Self::State>;
+
+    fn restore_state(&mut self, state: Self::State);
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Reason {
+    /// The system asked the app to stop
+    Stopped,
+    LowBattery,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot<S> {
+    /// Unix seconds
+    saved_at: u64,
+    reason: Reason,
+    state: S,
+}
+
+/// What [`update`] keeps between frames
+struct Lifecycle {
+    /// The snapshot from last time has been looked for
+    checked: bool,
+    /// A snapshot from last time, waiting for an answer
+    offer: Option<Snapshot<serde_json::Value>>,
+    battery_checked: Option<Instant>,
+    /// This run wrote a low battery snapshot
+    saved_for_battery: bool,
+}
+
+static LIFECYCLE: Mutex<Lifecycle> =
+    Mutex::new(Lifecycle { checked: false, offer: None, battery_checked: None, saved_for_battery: false });
+
+/// Set by the signal handler
+static STOPPING: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_signal(_signal: libc::c_int) {
+    STOPPING.store(true, Ordering::SeqCst);
+}
+
+fn snapshot_path(app: &str) -> PathBuf {
+    config_dir(app).join("saved_state.json")
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Catch the signals, and wake `ctx` when one comes so the next frame
+/// saves. If no frame runs in time, exit without saving.
+fn install(ctx: &Context) {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // SAFETY: the handler only stores to an atomic
+        unsafe {
+            libc::signal(libc::SIGTERM, handler);
+            libc::signal(libc::SIGHUP, handler);
+            #[cfg(target_os = "linux")]
+            libc::signal(libc::SIGPWR, handler);
+        }
+        let ctx = ctx.clone();
+        let _ = std::thread::Builder::new().name("slowcore-lifecycle".into()).spawn(move || {
+            while !STOPPING.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            ctx.request_repaint();
+            std::thread::sleep(EXIT_GRACE);
+            crate::log::warn!("asked to stop and no frame ran to save; exiting");
+            std::process::exit(0);
+        });
+    });
+}
+
+fn save<A: SaveState>(app_name: &str, app: &A, reason: Reason) {
+    let Some(state) = app.save_state() else {
+        discard(app_name);
+        return;
+    };
+    let snapshot = Snapshot { saved_at: unix_now(), reason, state };
+    if let Err(e) = write(app_name, &snapshot) {
+        crate::log::warn!("couldn't save state for {}: {}", app_name, e);
+    }
+}
+
+fn write<S: Serialize>(app_name: &str, snapshot: &Snapshot<S>) -> Result<()> {
+    let json = serde_json::to_vec(snapshot)?;
+    std::fs::create_dir_all(config_dir(app_name))?;
+    save_atomic(&snapshot_path(app_name), &json)
+}
+
+/// Forget the snapshot, after a normal close or when it's no longer needed
+pub fn discard(app: &str) {
+    let _ = std::fs::remove_file(snapshot_path(app));
+}
+
+/// Save on a signal or a critically low battery, and offer to restore
+/// last time's snapshot. Call once per frame.
+pub fn update<A: SaveState>(ctx: &Context, app_name: &str, app: &mut A) {
+    install(ctx);
+    if STOPPING.load(Ordering::SeqCst) {
+        save(app_name, app, Reason::Stopped);
+        crate::log::info!("saved state before stopping");
+        std::process::exit(0);
+    }
+
+    let mut lifecycle = LIFECYCLE.lock().unwrap_or_else(|e| e.into_inner());
+    if !lifecycle.checked {
+        lifecycle.checked = true;
+        lifecycle.offer = std::fs::read_to_string(snapshot_path(app_name))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok());
+    }
+
+    if !matches!(lifecycle.battery_checked, Some(t) if t.elapsed() < BATTERY_INTERVAL) {
+        lifecycle.battery_checked = Some(Instant::now());
+        let power = safety::power_state();
+        let critical = power.on_battery && power.battery_percent.is_some_and(|p| p <= CRITICAL_BATTERY_PERCENT);
+        // Not over a snapshot that hasn't been answered yet
+        if critical && lifecycle.offer.is_none() {
+            save(app_name, app, Reason::LowBattery);
+            lifecycle.saved_for_battery = true;
+        } else if !critical && std::mem::take(&mut lifecycle.saved_for_battery) {
+            discard(app_name);
+        }
+    }
+
+    let Some(snapshot) = &lifecycle.offer else { return };
+    let when = chrono::DateTime::from_timestamp(snapshot.saved_at as i64, 0)
+        .map(|t| t.with_timezone(&chrono::Local).format("%-d %b, %H:%M").to_string())
+        .unwrap_or_default();
+    let why = match snapshot.reason {
+        Reason::Stopped => "the system stopped it",
+        Reason::LowBattery => "the battery ran low",
+    };
+    let mut answer = None;
+    let resp = egui::Window::new("carry on?")
+        .collapsible(false)
+        .resizable(false)
+        .default_width(300.0)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(format!("this app was closed on {} because {}.", when, why));
+            ui.label("carry on where you left off?");
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("start afresh").clicked() {
+                    answer = Some(false);
+                }
+                if ui.button("carry on").clicked() {
+                    answer = Some(true);
+                }
+            });
+        });
+    if let Some(r) = &resp {
+        crate::dither::draw_window_shadow(ctx, r.response.rect);
+    }
+    let Some(restore) = answer else { return };
+    if let Some(snapshot) = lifecycle.offer.take() {
+        if restore {
+            match serde_json::from_value(snapshot.state) {
+                Ok(state) => app.restore_state(state),
+                Err(e) => crate::log::warn!("couldn't restore saved state: {}", e),
+            }
+        }
+    }
+    discard(app_name);
+}
@@ -0,0 +1,168 @@
+//! Logging — one rotating file per app
+//!
+//! Apps log through the `log` crate's macros, re-exported here:
+//!
+//! ```ignore
+//! slowcore::log::warn!("could not save {}: {}", path.display(), e);
+//! ```
+//!
+//! Lines go to `<app>.log` in [`logs::logs_dir`]
+//! (~/.local/state/slowos/logs), rotated like the rest of the logs, and
+//! to stderr too when an app runs outside slowOS. `RepaintController`
+//! sets this up on the first frame, named after the binary; call [`init`]
+//! first thing in `main` to log before that.
+//!
+//! How much is kept is a system setting ("log_level", changed in
+//! settings → about), which running apps pick up within a few seconds.
+//! `SLOWOS_LOG=debug` overrides it, for development.
+
+pub use ::log::{debug, error, info, trace, warn, LevelFilter};
+
+use crate::logs;
+use ::log::{Log, Metadata, Record};
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How often running apps look at the log level setting
+const LEVEL_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Level kept when nothing is set
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// A logger is installed
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Levels offered in settings, with their labels
+pub const LEVELS: &[(LevelFilter, &str)] = &[
+    (LevelFilter::Error, "errors only"),
+    (LevelFilter::Warn, "warnings"),
+    (LevelFilter::Info, "information"),
+    (LevelFilter::Debug, "debugging detail"),
+];
+
+/// The log file being written and its size
+struct Output {
+    file: Option<File>,
+    written: u64,
+}
+
+struct FileLogger {
+    app: String,
+    path: PathBuf,
+    output: Mutex<Output>,
+    /// Copy lines to stderr: outside slowOS, where it is a terminal
+    echo: bool,
+}
+
+impl FileLogger {
+    fn open(&self, output: &mut Output) {
+        logs::rotate_if_full(&self.path);
+        output.file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path).ok();
+        output.written = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Below warnings, only this app's and slowcore's own lines: egui,
+        // winit and the rest are chatty
+        metadata.level() <= ::log::max_level()
+            && (metadata.level() <= ::log::Level::Warn
+                || metadata.target().starts_with(&self.app)
+                || metadata.target().starts_with("slowcore"))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} {}: {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if self.echo {
+            eprint!("{}", line);
+        }
+        let mut output = self.output.lock().unwrap_or_else(|e| e.into_inner());
+        if output.file.is_none() || output.written >= logs::MAX_LOG_BYTES {
+            self.open(&mut output);
+        }
+        if let Some(file) = output.file.as_mut() {
+            if file.write_all(line.as_bytes()).is_ok() {
+                output.written += line.len() as u64;
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.output.lock().unwrap_or_else(|e| e.into_inner()).file.as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Send this app's log lines to `<app>.log`. Only the first call (or the
+/// first frame) does anything.
+pub fn init(app: &str) {
+    if INSTALLED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    let logger = FileLogger {
+        app: app.to_string(),
+        path: logs::app_log(app),
+        output: Mutex::new(Output { file: None, written: 0 }),
+        echo: std::env::var_os("SLOWOS_MANAGED").is_none(),
+    };
+    if ::log::set_boxed_logger(Box::new(logger)).is_ok() {
+        ::log::set_max_level(level());
+    }
+}
+
+fn parse_level(name: &str) -> Option<LevelFilter> {
+    name.trim().parse().ok()
+}
+
+/// How much is logged: `SLOWOS_LOG`, else the system setting
+pub fn level() -> LevelFilter {
+    std::env::var("SLOWOS_LOG")
+        .ok()
+        .and_then(|v| parse_level(&v))
+        .or_else(|| crate::theme::system_setting("log_level").and_then(|v| v.as_str().and_then(parse_level)))
+        .unwrap_or(DEFAULT_LEVEL)
+}
+
+/// Install the logger named after this binary if `main` didn't, and follow
+/// the level setting. Called by `RepaintController::begin_frame`.
+pub(crate) fn refresh() {
+    static LAST_CHECK: Mutex<Option<Instant>> = Mutex::new(None);
+    if !INSTALLED.load(Ordering::Relaxed) {
+        let exe = std::env::current_exe().ok();
+        let app = exe.as_ref().and_then(|p| p.file_stem()).map(|s| s.to_string_lossy().to_string());
+        init(app.as_deref().unwrap_or("slowos"));
+    }
+    let mut last = LAST_CHECK.lock().unwrap_or_else(|e| e.into_inner());
+    if last.is_none_or(|t| t.elapsed() >= LEVEL_CHECK_INTERVAL) {
+        *last = Some(Instant::now());
+        ::log::set_max_level(level());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("debug"), Some(LevelFilter::Debug));
+        assert_eq!(parse_level(" WARN\n"), Some(LevelFilter::Warn));
+        assert_eq!(parse_level("loud"), None);
+        assert!(LEVELS.iter().any(|&(level, _)| level == DEFAULT_LEVEL));
+    }
+}
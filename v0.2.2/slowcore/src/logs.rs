@@ -0,0 +1,201 @@
+//! System and app logs, and crash reports
+//!
+//! Each app logs to `<app>.log` in [`logs_dir`] through [`crate::log`],
+//! and slowDesktop sends the apps' other output there too
+//! ([`open_app_log`]); logs are rotated once they grow past
+//! [`MAX_LOG_BYTES`]. slowDesktop adds an entry to `crash.log` whenever it
+//! or an app panics. On its next start it shows the newest crash it
+//! hasn't shown yet ([`unseen_crash`]). slowLogs browses all of them
+//! ([`log_files`]).
+//!
+//! A crash entry starts with a line `[YYYY-MM-DD HH:MM:SS] <summary>`;
+//! the lines after it, up to the next entry, are its details.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Logs are rotated once they reach this size
+pub const MAX_LOG_BYTES: u64 = 256 * 1024;
+
+/// Rotated copies kept of each log: `slowpaint.log.1`, `slowpaint.log.2`
+const KEEP_ROTATED: usize = 2;
+
+/// Logs written outside slowOS's own folder: the startup script's, and the
+/// system log
+const SYSTEM_LOGS: &[(&str, &str)] = &[
+    ("/var/log/slowos.log", "startup"),
+    ("/var/log/messages", "system"),
+];
+
+/// Folder holding the app logs and crash reports:
+/// ~/.local/state/slowos/logs
+pub fn logs_dir() -> PathBuf {
+    let dir = directories::ProjectDirs::from("co", "slowcomputer", "slowos")
+        .map(|dirs| dirs.state_dir().unwrap_or(dirs.data_local_dir()).join("logs"))
+        .unwrap_or_else(|| std::env::temp_dir().join("slowos-logs"));
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// Where crashes are written
+pub fn crash_log() -> PathBuf {
+    logs_dir().join("crash.log")
+}
+
+/// The log of `app` (its binary name)
+pub fn app_log(app: &str) -> PathBuf {
+    logs_dir().join(format!("{}.log", app))
+}
+
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", n));
+    path.with_file_name(name)
+}
+
+/// Rotate `path` if it has grown past [`MAX_LOG_BYTES`]
+pub fn rotate_if_full(path: &Path) {
+    if std::fs::metadata(path).map(|m| m.len()).unwrap_or(0) < MAX_LOG_BYTES {
+        return;
+    }
+    let _ = std::fs::remove_file(rotated(path, KEEP_ROTATED));
+    for n in (1..KEEP_ROTATED).rev() {
+        let _ = std::fs::rename(rotated(path, n), rotated(path, n + 1));
+    }
+    let _ = std::fs::rename(path, rotated(path, 1));
+}
+
+/// Open the log of `app` for appending, for a child process's output
+pub fn open_app_log(app: &str) -> Option<File> {
+    let path = app_log(app);
+    rotate_if_full(&path);
+    std::fs::OpenOptions::new().create(true).append(true).open(path).ok()
+}
+
+/// Up to the last `max_bytes` of `path`, starting at a whole line
+pub fn read_tail(path: &Path, max_bytes: u64) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    if start > 0 {
+        // Drop the line cut in half
+        if let Some(newline) = text.find('\n') {
+            return Ok(text[newline + 1..].to_string());
+        }
+    }
+    Ok(text)
+}
+
+/// A log slowLogs can show
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFile {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Every log there is: crashes first, then apps by name, then the
+/// system's. Rotated copies follow the log they came from.
+pub fn log_files() -> Vec<LogFile> {
+    let dir = logs_dir();
+    let mut files = Vec::new();
+    let mut add = |path: PathBuf, name: String| {
+        for n in 0..=KEEP_ROTATED {
+            let path = if n == 0 { path.clone() } else { rotated(&path, n) };
+            if path.is_file() {
+                let name = if n == 0 { name.clone() } else { format!("{} (older {})", name, n) };
+                files.push(LogFile { name, path });
+            }
+        }
+    };
+    add(crash_log(), "crashes".to_string());
+    let mut apps: Vec<String> = std::fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.strip_suffix(".log").map(str::to_string))
+        .filter(|name| name != "crash")
+        .collect();
+    apps.sort();
+    for app in apps {
+        add(app_log(&app), app);
+    }
+    for (path, name) in SYSTEM_LOGS {
+        add(PathBuf::from(path), name.to_string());
+    }
+    files
+}
+
+/// One entry of the crash log
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crash {
+    /// When it happened, as written: "2026-03-04 10:15:02"
+    pub time: String,
+    /// The rest of the first line: what crashed and where
+    pub summary: String,
+    /// The whole entry, for "view details" and "copy"
+    pub details: String,
+}
+
+/// Whether `line` starts a crash entry
+fn entry_header(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (time, summary) = rest.split_once("] ")?;
+    time.starts_with(|c: char| c.is_ascii_digit()).then_some((time, summary))
+}
+
+/// The entries of a crash log, oldest first
+pub fn parse_crashes(log: &str) -> Vec<Crash> {
+    let mut crashes: Vec<Crash> = Vec::new();
+    for line in log.lines() {
+        if let Some((time, summary)) = entry_header(line) {
+            crashes.push(Crash { time: time.to_string(), summary: summary.to_string(), details: String::new() });
+        }
+        if let Some(crash) = crashes.last_mut() {
+            crash.details.push_str(line);
+            crash.details.push('\n');
+        }
+    }
+    crashes
+}
+
+fn seen_path() -> PathBuf {
+    logs_dir().join("crash.seen")
+}
+
+/// The newest crash, unless it was already shown
+pub fn unseen_crash() -> Option<Crash> {
+    let log = read_tail(&crash_log(), MAX_LOG_BYTES).ok()?;
+    let crash = parse_crashes(&log).pop()?;
+    let seen = std::fs::read_to_string(seen_path()).unwrap_or_default();
+    (seen.trim_end() != crash.details.lines().next().unwrap_or_default()).then_some(crash)
+}
+
+/// Don't show `crash` again
+pub fn mark_seen(crash: &Crash) {
+    let _ = std::fs::write(seen_path(), crash.details.lines().next().unwrap_or_default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_crashes() {
+        let log = "[2026-03-04 10:15:02] slowdesktop PANIC at src/desktop.rs:10:5: oops\n\
+                   Backtrace:\n   0: main\n\
+                   [2026-03-05 09:00:00] slowpaint quit unexpectedly (exit status: 101)\n\
+                   thread 'main' panicked\n";
+        let crashes = parse_crashes(log);
+        assert_eq!(crashes.len(), 2);
+        assert_eq!(crashes[0].time, "2026-03-04 10:15:02");
+        assert_eq!(crashes[0].summary, "slowdesktop PANIC at src/desktop.rs:10:5: oops");
+        assert_eq!(crashes[0].details.lines().count(), 3);
+        assert_eq!(crashes[1].summary, "slowpaint quit unexpectedly (exit status: 101)");
+        assert!(parse_crashes("[not a time] hi\n").is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+//! Menu extras — small status items apps put in the desktop menu bar
+//!
+//! An app publishes one extra (a label, optionally with a countdown) and
+//! slowDesktop draws it next to the clock until the app clears it or exits:
+//!
+//! ```ignore
+//! MenuExtra::countdown("slowbreath", "focus", remaining, total).publish();
+//! slowcore::menu_extra::clear("slowbreath");
+//! ```
+//!
+//! Countdowns are published as an end time, so the desktop can keep them
+//! current without hearing from the app every second.
+//!
+//! Same file-based IPC as minimize: one JSON file per app and process in
+//! ~/.config/slowos/menu_extras/.
+
+use crate::process::{read_per_process, PerProcess};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often the watcher thread looks for changes
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MenuExtra {
+    /// Binary name of the app
+    pub app: String,
+    pub pid: u32,
+    /// Short lowercase label ("focus", "break")
+    pub label: String,
+    /// When the countdown runs out, in unix seconds
+    #[serde(default)]
+    pub ends_at: Option<u64>,
+    /// Full length of the countdown in seconds, for the pie glyph
+    #[serde(default)]
+    pub total_secs: u64,
+}
+
+impl MenuExtra {
+    /// A plain label
+    pub fn label(app: &str, label: &str) -> Self {
+        Self {
+            app: app.to_string(),
+            pid: std::process::id(),
+            label: label.to_string(),
+            ends_at: None,
+            total_secs: 0,
+        }
+    }
+
+    /// A label with a countdown that ends `remaining` from now
+    pub fn countdown(app: &str, label: &str, remaining: Duration, total: Duration) -> Self {
+        Self {
+            ends_at: Some(unix_now() + remaining.as_secs()),
+            total_secs: total.as_secs(),
+            ..Self::label(app, label)
+        }
+    }
+
+    /// Seconds left on the countdown, if there is one
+    pub fn remaining_secs(&self) -> Option<u64> {
+        self.ends_at.map(|end| end.saturating_sub(unix_now()))
+    }
+
+    /// Fraction of the countdown left, 1.0 at the start
+    pub fn fraction_left(&self) -> Option<f32> {
+        let remaining = self.remaining_secs()?;
+        if self.total_secs == 0 {
+            return Some(0.0);
+        }
+        Some((remaining as f32 / self.total_secs as f32).min(1.0))
+    }
+
+    /// Show (or replace) this app's extra
+    pub fn publish(&self) {
+        if let Ok(json) = serde_json::to_string(self) {
+            let path = extra_path(&self.app, self.pid);
+            // Write and rename, so the desktop never reads half a file and
+            // the directory's mtime changes for the watcher
+            let tmp = path.with_extension("tmp");
+            if std::fs::write(&tmp, json).is_ok() {
+                let _ = std::fs::rename(&tmp, &path);
+            }
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn extras_dir() -> PathBuf {
+    let dir = directories::ProjectDirs::from("", "", "slowos")
+        .map(|p| p.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/slowos"))
+        .join("menu_extras");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+fn extra_path(app: &str, pid: u32) -> PathBuf {
+    extras_dir().join(format!("{}_{}.json", app, pid))
+}
+
+/// Remove this process's extra
+pub fn clear(app: &str) {
+    let _ = std::fs::remove_file(extra_path(app, std::process::id()));
+}
+
+impl PerProcess for MenuExtra {
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
+/// Extras of all running apps, by app (used by slowDesktop)
+pub fn read_all() -> Vec<MenuExtra> {
+    let mut results = read_per_process::<MenuExtra>(&extras_dir());
+    results.sort_by(|a, b| a.app.cmp(&b.app).then(a.pid.cmp(&b.pid)));
+    results
+}
+
+/// Wake `ctx` whenever an extra is published or cleared
+pub fn watch(ctx: egui::Context) {
+    let dir = extras_dir();
+    let _ = std::thread::Builder::new()
+        .name("slowcore-menu-extras".into())
+        .spawn(move || {
+            let modified = || std::fs::metadata(&dir).and_then(|m| m.modified()).ok();
+            let mut last = modified();
+            loop {
+                std::thread::sleep(WATCH_INTERVAL);
+                let now = modified();
+                if now != last {
+                    last = now;
+                    ctx.request_repaint();
+                }
+            }
+        });
+}
@@ -4,6 +4,7 @@
 //! The desktop polls this directory to show minimized apps in the status bar.
 //! When the user clicks a minimized app in the status bar, the desktop restores it.
 
+use crate::process::{read_per_process, PerProcess};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -48,29 +49,15 @@ pub fn clear_minimized(binary: &str) {
     let _ = std::fs::remove_file(path);
 }
 
+impl PerProcess for MinimizedApp {
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
 /// Read all minimized apps (used by the desktop)
 pub fn read_all_minimized() -> Vec<MinimizedApp> {
-    let dir = minimized_dir();
-    let mut results = Vec::new();
-    if let Ok(entries) = std::fs::read_dir(&dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().map(|e| e == "json").unwrap_or(false) {
-                if let Ok(json) = std::fs::read_to_string(&path) {
-                    if let Ok(state) = serde_json::from_str::<MinimizedApp>(&json) {
-                        // Verify the process is still alive
-                        if is_process_alive(state.pid) {
-                            results.push(state);
-                        } else {
-                            // Stale file — process died without cleaning up
-                            let _ = std::fs::remove_file(&path);
-                        }
-                    }
-                }
-            }
-        }
-    }
-    results
+    read_per_process(&minimized_dir())
 }
 
 /// Remove a specific minimized entry (used by the desktop when restoring).
@@ -95,9 +82,3 @@ pub fn check_restore_signal(binary: &str) -> bool {
         false
     }
 }
-
-/// Check if a process is still running
-fn is_process_alive(pid: u32) -> bool {
-    // Check /proc/{pid} on Linux
-    std::path::Path::new(&format!("/proc/{}", pid)).exists()
-}
@@ -0,0 +1,231 @@
+//! Night shift — a warmer, dimmer screen in the evening
+//!
+//! slowOS draws in black and white, so its night variant is a tint over
+//! the whole screen: white turns warm and less bright in every app at the
+//! same moment, with nothing for apps to redraw. It comes on between set
+//! times, or from sunset to sunrise where the user lives.
+//!
+//! Settings keeps the schedule in the system settings file
+//! ("night_shift"). slowDesktop runs [`watch`], which reads it every few
+//! seconds and tints the screen when night starts or ends or the schedule
+//! changes. Apps that want to follow along (a darker page, say) ask
+//! [`is_active`].
+
+use crate::display;
+use crate::theme::system_setting;
+use chrono::{DateTime, Datelike, Local, NaiveDate, Timelike};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How often the watcher looks at the clock and the settings
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The sun's centre this far below the horizon counts as set (refraction
+/// and the size of the disc)
+const SUNSET_ZENITH: f64 = 90.833;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    #[default]
+    Off,
+    /// Between `from` and `until`
+    Times,
+    /// From sunset to sunrise at `latitude`, `longitude`
+    Sunset,
+}
+
+impl Schedule {
+    pub const ALL: [Schedule; 3] = [Schedule::Off, Schedule::Times, Schedule::Sunset];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Schedule::Off => "off",
+            Schedule::Times => "at set times",
+            Schedule::Sunset => "sunset to sunrise",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct NightShift {
+    #[serde(default)]
+    pub schedule: Schedule,
+    /// Minutes after midnight it comes on and goes off
+    #[serde(default = "default_from")]
+    pub from: u16,
+    #[serde(default = "default_until")]
+    pub until: u16,
+    /// Degrees north
+    #[serde(default)]
+    pub latitude: f64,
+    /// Degrees east
+    #[serde(default)]
+    pub longitude: f64,
+    /// How warm and dim the screen gets, in percent
+    #[serde(default = "default_warmth")]
+    pub warmth: u8,
+}
+
+fn default_from() -> u16 {
+    21 * 60
+}
+
+fn default_until() -> u16 {
+    7 * 60
+}
+
+fn default_warmth() -> u8 {
+    50
+}
+
+impl Default for NightShift {
+    fn default() -> Self {
+        Self {
+            schedule: Schedule::Off,
+            from: default_from(),
+            until: default_until(),
+            latitude: 0.0,
+            longitude: 0.0,
+            warmth: default_warmth(),
+        }
+    }
+}
+
+impl NightShift {
+    pub fn load() -> Self {
+        system_setting("night_shift")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether it's night at `now`
+    pub fn is_night(&self, now: DateTime<Local>) -> bool {
+        let minute = (now.hour() * 60 + now.minute()) as u16;
+        match self.schedule {
+            Schedule::Off => false,
+            Schedule::Times => in_span(minute, self.from, self.until),
+            Schedule::Sunset => {
+                let offset = now.offset().local_minus_utc() / 60;
+                match daylight(now.date_naive(), self.latitude, self.longitude, offset) {
+                    Daylight::Normal { sunrise, sunset } => !in_span(minute, sunrise, sunset),
+                    Daylight::MidnightSun => false,
+                    Daylight::PolarNight => true,
+                }
+            }
+        }
+    }
+
+    /// Red, green, blue and brightness at night, 0.0–1.0
+    pub fn tint(&self) -> [f32; 4] {
+        let warmth = self.warmth.min(100) as f32 / 100.0;
+        [1.0, 1.0 - 0.2 * warmth, 1.0 - 0.5 * warmth, 1.0 - 0.3 * warmth]
+    }
+}
+
+/// Whether `minute` falls from `from` up to `until`; the span may cross midnight
+fn in_span(minute: u16, from: u16, until: u16) -> bool {
+    if from <= until {
+        minute >= from && minute < until
+    } else {
+        minute >= from || minute < until
+    }
+}
+
+/// When the sun is up on a day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Daylight {
+    /// Minutes after local midnight
+    Normal { sunrise: u16, sunset: u16 },
+    /// The sun doesn't set
+    MidnightSun,
+    /// The sun doesn't rise
+    PolarNight,
+}
+
+/// Sunrise and sunset on `date` at `latitude`, `longitude`, for a clock
+/// `offset` minutes ahead of UTC
+pub fn daylight(date: NaiveDate, latitude: f64, longitude: f64, offset: i32) -> Daylight {
+    let local = |hours: f64| ((hours * 60.0).round() as i32 + offset).rem_euclid(24 * 60) as u16;
+    match (sun_event(date.ordinal(), latitude, longitude, true), sun_event(date.ordinal(), latitude, longitude, false)) {
+        (Ok(rise), Ok(set)) => Daylight::Normal { sunrise: local(rise), sunset: local(set) },
+        (Err(true), _) | (_, Err(true)) => Daylight::MidnightSun,
+        _ => Daylight::PolarNight,
+    }
+}
+
+/// Hours after UTC midnight the sun rises (or sets) on day `day` of the
+/// year, from the Almanac for Computers. `Err(true)` when it stays up all
+/// day, `Err(false)` when it stays down.
+fn sun_event(day: u32, latitude: f64, longitude: f64, rising: bool) -> Result<f64, bool> {
+    let (sin, cos) = (|d: f64| d.to_radians().sin(), |d: f64| d.to_radians().cos());
+    let lng_hour = longitude / 15.0;
+    let t = day as f64 + ((if rising { 6.0 } else { 18.0 }) - lng_hour) / 24.0;
+    // The sun's mean anomaly, then its true longitude
+    let m = 0.9856 * t - 3.289;
+    let l = (m + 1.916 * sin(m) + 0.020 * sin(2.0 * m) + 282.634).rem_euclid(360.0);
+    // Right ascension, in the same quadrant as the longitude, in hours
+    let ra = (0.91764 * l.to_radians().tan()).atan().to_degrees().rem_euclid(360.0);
+    let ra = (ra + (l / 90.0).floor() * 90.0 - (ra / 90.0).floor() * 90.0) / 15.0;
+    let sin_dec = 0.39782 * sin(l);
+    let cos_dec = sin_dec.asin().cos();
+    let cos_h = (cos(SUNSET_ZENITH) - sin_dec * sin(latitude)) / (cos_dec * cos(latitude));
+    if cos_h > 1.0 {
+        return Err(false);
+    }
+    if cos_h < -1.0 {
+        return Err(true);
+    }
+    let h = cos_h.acos().to_degrees();
+    let h = if rising { 360.0 - h } else { h } / 15.0;
+    let local_mean = h + ra - 0.06571 * t - 6.622;
+    Ok((local_mean - lng_hour).rem_euclid(24.0))
+}
+
+/// Whether night shift is on now. Reads the settings file, so not every frame.
+pub fn is_active() -> bool {
+    NightShift::load().is_night(Local::now())
+}
+
+/// Keep the screen's tint in step with the schedule, on a thread of its
+/// own (slowDesktop, at startup)
+pub fn watch() {
+    std::thread::spawn(|| {
+        // Put right whatever a previous session left, the first time round
+        let mut applied = None;
+        loop {
+            let shift = NightShift::load();
+            let tint = if shift.is_night(Local::now()) { shift.tint() } else { [1.0; 4] };
+            if applied != Some(tint) {
+                let [red, green, blue, brightness] = tint;
+                if let Err(e) = display::set_tint(red, green, blue, brightness) {
+                    crate::log::warn!("couldn't tint the screen: {}", e);
+                }
+                applied = Some(tint);
+            }
+            std::thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daylight() {
+        // London at midsummer: up at 04:43 and down at 21:21, summer time
+        let midsummer = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+        let Daylight::Normal { sunrise, sunset } = daylight(midsummer, 51.5, -0.13, 60) else { panic!() };
+        assert!(sunrise.abs_diff(4 * 60 + 43) <= 5, "sunrise {}", sunrise);
+        assert!(sunset.abs_diff(21 * 60 + 21) <= 5, "sunset {}", sunset);
+
+        // Tromsø has the sun all day in June and none in December
+        assert_eq!(daylight(midsummer, 69.65, 18.96, 120), Daylight::MidnightSun);
+        let midwinter = NaiveDate::from_ymd_opt(2024, 12, 21).unwrap();
+        assert_eq!(daylight(midwinter, 69.65, 18.96, 60), Daylight::PolarNight);
+
+        assert!(in_span(22 * 60, 21 * 60, 7 * 60) && in_span(60, 21 * 60, 7 * 60));
+        assert!(!in_span(12 * 60, 21 * 60, 7 * 60));
+    }
+}
@@ -0,0 +1,153 @@
+//! Desktop notifications
+//!
+//! Apps post short messages that slowDesktop shows as toasts in the
+//! bottom-right corner for [`TOAST_DURATION`]. A notification can carry an
+//! [`Undo`]: the toast then gets an "undo" button, and the desktop reverses
+//! the file operation itself, so it still works after the app has quit.
+//! One can also name a file to reveal, for a "show" button that opens its
+//! folder in slowFiles, or a file to open, for an "open now" button.
+//!
+//! Same file-based IPC as minimize: one JSON file per notification in
+//! ~/.config/slowos/notifications/, removed by the desktop once shown.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a toast stays up
+pub const TOAST_DURATION: Duration = Duration::from_secs(10);
+
+/// Notifications nobody picked up within this long are dropped
+const STALE_AFTER: Duration = Duration::from_secs(60);
+
+/// How often the watcher thread looks for new notifications
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A file that was moved, so it can be moved back
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MovedFile {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// How to reverse the operation a notification reports
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Undo {
+    /// Files were moved to the trash; restore them by original path
+    Trash { originals: Vec<PathBuf> },
+    /// Files were moved; move each `to` back to `from`
+    Move { moves: Vec<MovedFile> },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// Unique id (also the file name)
+    pub id: String,
+    /// Binary name of the app that posted it
+    pub app: String,
+    pub message: String,
+    #[serde(default)]
+    pub undo: Option<Undo>,
+    /// File or folder the toast's "show" button opens in slowFiles
+    #[serde(default)]
+    pub reveal: Option<PathBuf>,
+    /// File the toast's "open now" button opens in the app for its kind
+    #[serde(default)]
+    pub open: Option<PathBuf>,
+    /// Unix seconds
+    pub posted_at: u64,
+}
+
+fn notifications_dir() -> PathBuf {
+    let dir = directories::ProjectDirs::from("", "", "slowos")
+        .map(|p| p.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/slowos"))
+        .join("notifications");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// Show a toast on the desktop
+pub fn post(app: &str, message: &str) {
+    write(app, message, None, None);
+}
+
+/// Show a toast with an "undo" button
+pub fn post_with_undo(app: &str, message: &str, undo: Undo) {
+    write(app, message, Some(undo), None);
+}
+
+/// Show a toast with a "show" button that opens `path` in slowFiles
+pub fn post_with_reveal(app: &str, message: &str, path: &std::path::Path) {
+    write(app, message, None, Some(path.to_path_buf()));
+}
+
+/// Show a toast with an "open now" button for `path`
+pub fn post_with_open(app: &str, message: &str, path: &std::path::Path) {
+    write_notification(app, message, None, None, Some(path.to_path_buf()));
+}
+
+fn write(app: &str, message: &str, undo: Option<Undo>, reveal: Option<PathBuf>) {
+    write_notification(app, message, undo, reveal, None);
+}
+
+fn write_notification(app: &str, message: &str, undo: Option<Undo>, reveal: Option<PathBuf>, open: Option<PathBuf>) {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let notification = Notification {
+        id: format!("{}_{}_{}", app, std::process::id(), now.as_nanos()),
+        app: app.to_string(),
+        message: message.to_string(),
+        undo,
+        reveal,
+        open,
+        posted_at: now.as_secs(),
+    };
+    if let Ok(json) = serde_json::to_string_pretty(&notification) {
+        let path = notifications_dir().join(format!("{}.json", notification.id));
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// Take every waiting notification, oldest first (used by slowDesktop).
+/// Each one is returned once; stale ones are discarded.
+pub fn take_all() -> Vec<Notification> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let mut results = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(notifications_dir()) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map(|e| e == "json").unwrap_or(false) {
+                if let Ok(json) = std::fs::read_to_string(&path) {
+                    if let Ok(n) = serde_json::from_str::<Notification>(&json) {
+                        if now.saturating_sub(n.posted_at) <= STALE_AFTER.as_secs() {
+                            results.push(n);
+                        }
+                    }
+                }
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+    results.sort_by(|a, b| a.posted_at.cmp(&b.posted_at).then_with(|| a.id.cmp(&b.id)));
+    results
+}
+
+/// Wake `ctx` whenever a notification is waiting, so an idle desktop
+/// (which doesn't repaint on a timer) still shows toasts promptly.
+pub fn watch(ctx: egui::Context) {
+    let dir = notifications_dir();
+    let _ = std::thread::Builder::new()
+        .name("slowcore-notify".into())
+        .spawn(move || loop {
+            let waiting = std::fs::read_dir(&dir)
+                .map(|mut entries| entries.any(|e| {
+                    e.map(|e| e.path().extension().map(|x| x == "json").unwrap_or(false)).unwrap_or(false)
+                }))
+                .unwrap_or(false);
+            if waiting {
+                ctx.request_repaint();
+            }
+            std::thread::sleep(WATCH_INTERVAL);
+        });
+}
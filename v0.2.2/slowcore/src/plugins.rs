@@ -0,0 +1,283 @@
+//! Plugins — tools, instruments and panels from outside the workspace
+//!
+//! A plugin is a shared library in `~/.local/share/slowos/plugins` or
+//! `/usr/lib/slowos/plugins`, named for its kind — `tool-*.so`,
+//! `instrument-*.so` or `panel-*.so` — that exports one C function,
+//!
+//! ```c
+//! const SlowPlugin *slowos_plugin(void);
+//! ```
+//!
+//! returning a [`SlowPlugin`] that lives as long as the library. It says
+//! which [`ABI_VERSION`] the plugin was built for, what kind of plugin it
+//! is, its name, and the function that kind needs:
+//!
+//! - **paint tools** (slowPaint's toolbar) paint a dab at each point of a
+//!   stroke, straight onto the RGBA canvas;
+//! - **instruments** (slowMidi's instrument menu) render a note as mono
+//!   samples;
+//! - **panels** (slowDesktop's menu bar) give a line of text, asked for
+//!   again every few seconds.
+//!
+//! In Rust, a plugin is a `cdylib` using the types here:
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub extern "C" fn slowos_plugin() -> *const SlowPlugin {
+//!     static PLUGIN: SlowPlugin = SlowPlugin {
+//!         render: Some(render_square),
+//!         ..SlowPlugin::new(KIND_INSTRUMENT, c"square")
+//!     };
+//!     &PLUGIN
+//! }
+//! ```
+//!
+//! An app only loads the plugins named for the kind it uses, so a library
+//! meant for another app never runs inside it. Plugins built for another
+//! ABI version, or of another kind than their name says, are skipped and
+//! noted in the log.
+//! A plugin runs inside the app, so one that crashes takes the app with it.
+
+use libloading::Library;
+use std::ffi::{c_char, CStr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Version of [`SlowPlugin`]. Bumped whenever its layout changes.
+pub const ABI_VERSION: u32 = 1;
+
+/// Name of the function every plugin exports
+const ENTRY_SYMBOL: &[u8] = b"slowos_plugin\0";
+
+/// Plugins installed with the system
+const SYSTEM_PLUGINS: &str = "/usr/lib/slowos/plugins";
+
+pub const KIND_PAINT_TOOL: u32 = 1;
+pub const KIND_INSTRUMENT: u32 = 2;
+pub const KIND_PANEL: u32 = 3;
+
+/// Paint a dab of `size` pixels centred on `x`, `y` into the
+/// `width` × `height` RGBA canvas at `pixels`
+pub type PaintFn = extern "C" fn(pixels: *mut u8, width: u32, height: u32, x: i32, y: i32, size: u32);
+
+/// Fill `out` with `len` mono samples, between -1 and 1, of MIDI note
+/// `pitch` at `velocity` (0–127), at `sample_rate`
+pub type RenderFn = extern "C" fn(pitch: u8, velocity: u8, sample_rate: u32, out: *mut f32, len: usize);
+
+/// Write up to `capacity` bytes of UTF-8 text into `buf`; return how many
+pub type PanelTextFn = extern "C" fn(buf: *mut u8, capacity: usize) -> usize;
+
+/// What a plugin hands over, as laid out in C. Only the function for its
+/// kind is used; leave the others null.
+#[repr(C)]
+pub struct SlowPlugin {
+    pub abi_version: u32,
+    /// `KIND_PAINT_TOOL`, `KIND_INSTRUMENT` or `KIND_PANEL`
+    pub kind: u32,
+    /// Short lowercase name, NUL-terminated UTF-8
+    pub name: *const c_char,
+    pub paint: Option<PaintFn>,
+    pub render: Option<RenderFn>,
+    pub panel_text: Option<PanelTextFn>,
+}
+
+// Plugins hand over a static description; the name points into it
+unsafe impl Sync for SlowPlugin {}
+
+impl SlowPlugin {
+    /// A description of this ABI version with no functions yet
+    pub const fn new(kind: u32, name: &'static CStr) -> Self {
+        Self { abi_version: ABI_VERSION, kind, name: name.as_ptr(), paint: None, render: None, panel_text: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginKind {
+    PaintTool,
+    Instrument,
+    Panel,
+}
+
+impl PluginKind {
+    /// How the file names of plugins of this kind start
+    fn file_prefix(self) -> &'static str {
+        match self {
+            PluginKind::PaintTool => "tool-",
+            PluginKind::Instrument => "instrument-",
+            PluginKind::Panel => "panel-",
+        }
+    }
+
+    /// Whether the file name of `path` says it is a plugin of this kind
+    pub fn names(self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "so")
+            && path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(self.file_prefix()))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PluginError {
+    #[error("could not load: {0}")]
+    Load(#[from] libloading::Error),
+    #[error("built for plugin version {0}, this is version {ABI_VERSION}")]
+    Version(u32),
+    #[error("{0}")]
+    Invalid(&'static str),
+}
+
+/// A loaded plugin
+#[derive(Clone)]
+pub struct Plugin {
+    name: String,
+    path: PathBuf,
+    kind: PluginKind,
+    info: *const SlowPlugin,
+    /// Keeps `info` and the functions loaded
+    _library: Arc<Library>,
+}
+
+impl Plugin {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn kind(&self) -> PluginKind {
+        self.kind
+    }
+
+    fn info(&self) -> &SlowPlugin {
+        // Valid while the library is loaded, which is as long as self
+        unsafe { &*self.info }
+    }
+
+    /// Paint tools: paint a dab into an RGBA canvas
+    pub fn paint(&self, pixels: &mut [u8], width: u32, height: u32, x: i32, y: i32, size: u32) {
+        if let Some(paint) = self.info().paint {
+            if pixels.len() >= width as usize * height as usize * 4 {
+                paint(pixels.as_mut_ptr(), width, height, x, y, size);
+            }
+        }
+    }
+
+    /// Instruments: render a note, filling `out`
+    pub fn render(&self, pitch: u8, velocity: u8, sample_rate: u32, out: &mut [f32]) {
+        if let Some(render) = self.info().render {
+            render(pitch, velocity, sample_rate, out.as_mut_ptr(), out.len());
+        }
+        // Keep a misbehaving instrument from hurting ears or speakers
+        for sample in out.iter_mut() {
+            *sample = if sample.is_finite() { sample.clamp(-1.0, 1.0) } else { 0.0 };
+        }
+    }
+
+    /// Panels: the text to show now
+    pub fn panel_text(&self) -> String {
+        let Some(panel_text) = self.info().panel_text else { return String::new() };
+        let mut buf = vec![0u8; 256];
+        let len = panel_text(buf.as_mut_ptr(), buf.len()).min(buf.len());
+        String::from_utf8_lossy(&buf[..len]).into_owned()
+    }
+}
+
+/// Where plugins are looked for: the user's first, then the system's
+pub fn plugin_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(project) = directories::ProjectDirs::from("co", "slowcomputer", "slowos") {
+        dirs.push(project.data_dir().join("plugins"));
+    }
+    dirs.push(PathBuf::from(SYSTEM_PLUGINS));
+    dirs
+}
+
+/// Load the plugin at `path`
+pub fn load_plugin(path: &Path) -> Result<Plugin, PluginError> {
+    // Running a library's initialisers is the point of loading a plugin
+    let library = unsafe { Library::new(path)? };
+    let info = unsafe {
+        let entry = library.get::<extern "C" fn() -> *const SlowPlugin>(ENTRY_SYMBOL)?;
+        entry()
+    };
+    if info.is_null() {
+        return Err(PluginError::Invalid("no plugin description"));
+    }
+    let described = unsafe { &*info };
+    if described.abi_version != ABI_VERSION {
+        return Err(PluginError::Version(described.abi_version));
+    }
+    let (kind, has_function) = match described.kind {
+        KIND_PAINT_TOOL => (PluginKind::PaintTool, described.paint.is_some()),
+        KIND_INSTRUMENT => (PluginKind::Instrument, described.render.is_some()),
+        KIND_PANEL => (PluginKind::Panel, described.panel_text.is_some()),
+        _ => return Err(PluginError::Invalid("unknown kind of plugin")),
+    };
+    if !has_function {
+        return Err(PluginError::Invalid("missing the function for its kind"));
+    }
+    if described.name.is_null() {
+        return Err(PluginError::Invalid("no name"));
+    }
+    let name = unsafe { CStr::from_ptr(described.name) }.to_string_lossy().into_owned();
+    Ok(Plugin { name, path: path.to_path_buf(), kind, info, _library: Arc::new(library) })
+}
+
+/// Every plugin of `kind` installed, in file name order. Only files named
+/// for `kind` are loaded. A name the user's folder has hides the system's
+/// plugin of that name.
+pub fn load(kind: PluginKind) -> Vec<Plugin> {
+    let mut plugins: Vec<Plugin> = Vec::new();
+    for dir in plugin_dirs() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| kind.names(p))
+            .collect();
+        paths.sort();
+        for path in paths {
+            // The user's copy of a file hides the system's without loading it
+            if plugins.iter().any(|p| p.path.file_name() == path.file_name()) {
+                continue;
+            }
+            match load_plugin(&path) {
+                Ok(plugin) if plugin.kind != kind => {
+                    crate::log::warn!("skipped plugin {}: named as {:?} but is {:?}", path.display(), kind, plugin.kind)
+                }
+                Ok(plugin) if plugins.iter().any(|p| p.name == plugin.name) => {}
+                Ok(plugin) => {
+                    crate::log::info!("loaded plugin \"{}\" from {}", plugin.name, path.display());
+                    plugins.push(plugin);
+                }
+                Err(e) => crate::log::warn!("skipped plugin {}: {}", path.display(), e),
+            }
+        }
+    }
+    plugins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_plugin() {
+        assert!(matches!(load_plugin(Path::new("/nonexistent/tool.so")), Err(PluginError::Load(_))));
+        let plugin = SlowPlugin::new(KIND_PANEL, c"clock");
+        assert_eq!(plugin.abi_version, ABI_VERSION);
+        assert!(plugin.panel_text.is_none());
+    }
+
+    #[test]
+    fn test_kind_from_file_name() {
+        assert!(PluginKind::Instrument.names(Path::new("/plugins/instrument-square.so")));
+        assert!(!PluginKind::PaintTool.names(Path::new("/plugins/instrument-square.so")));
+        assert!(PluginKind::PaintTool.names(Path::new("tool-spray.so")));
+        assert!(PluginKind::Panel.names(Path::new("panel-clock.so")));
+        assert!(!PluginKind::Panel.names(Path::new("panel-clock.txt")));
+        assert!(!PluginKind::Panel.names(Path::new("clock.so")));
+    }
+}
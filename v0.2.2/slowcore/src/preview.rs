@@ -0,0 +1,174 @@
+//! Previews — a quick look at a file without opening its app
+//!
+//! Loaders shared by slowFiles' preview window and the apps that open
+//! these files in full:
+//!
+//! - text: the start of the file, if it reads as UTF-8;
+//! - pictures: scaled to fit and dithered to black and white, or to the
+//!   greys chosen in Settings;
+//! - PDFs: the first page, rendered like a picture;
+//! - sounds: played through the shared output ([`play_sound`]).
+//!
+//! ```ignore
+//! match preview::load(&path, 480) {
+//!     Preview::Picture(image) => texture = Some(ctx.load_texture("preview", image, TextureOptions::NEAREST)),
+//!     Preview::Text(text) => ...,
+//!     ...
+//! }
+//! ```
+
+use crate::dither;
+use egui::ColorImage;
+use image::imageops::FilterType;
+use rodio::{Decoder, Sink};
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// How much of a text file is shown
+pub const TEXT_LIMIT: usize = 64 * 1024;
+
+/// Pictures whose extensions the loaders read
+pub const PICTURE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp"];
+
+/// Sounds [`play_sound`] plays
+pub const SOUND_EXTENSIONS: &[&str] = &["mp3", "wav", "flac", "ogg"];
+
+/// PDF pages are rendered at this many pixels per point before fitting
+const PDF_SCALE: f32 = 1.0;
+
+/// What a file looks like, ready to show
+pub enum Preview {
+    Text(String),
+    /// A picture or a PDF's first page, dithered for the screen
+    Picture(ColorImage),
+    /// Something [`play_sound`] can play
+    Sound,
+    /// Nothing to show: a folder, or a file the loaders can't read
+    Nothing,
+}
+
+fn extension(path: &Path) -> String {
+    path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default()
+}
+
+/// Preview `path`, fitting pictures within `max_size` pixels square
+pub fn load(path: &Path, max_size: u32) -> Preview {
+    if path.is_dir() {
+        return Preview::Nothing;
+    }
+    let ext = extension(path);
+    let picture = if PICTURE_EXTENSIONS.contains(&ext.as_str()) {
+        load_picture(path, max_size)
+    } else if ext == "pdf" {
+        std::fs::read(path).ok().and_then(|data| pdf_first_page(&data, max_size))
+    } else if SOUND_EXTENSIONS.contains(&ext.as_str()) {
+        return Preview::Sound;
+    } else {
+        return load_text(path).map(Preview::Text).unwrap_or(Preview::Nothing);
+    };
+    picture.map(Preview::Picture).unwrap_or(Preview::Nothing)
+}
+
+/// The start of a text file, or `None` if it isn't text
+pub fn load_text(path: &Path) -> Option<String> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(path).ok()?.take(TEXT_LIMIT as u64).read_to_end(&mut bytes).ok()?;
+    if bytes.contains(&0) {
+        return None;
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) => Some(text),
+        // Cut off in the middle of a character
+        Err(e) if e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).ok()
+        }
+        Err(_) => None,
+    }
+}
+
+/// A picture, fitted within `max_size` pixels square and dithered
+pub fn load_picture(path: &Path, max_size: u32) -> Option<ColorImage> {
+    let picture = image::open(path).ok()?;
+    Some(dithered(picture, max_size))
+}
+
+fn dithered(picture: image::DynamicImage, max_size: u32) -> ColorImage {
+    let picture = if picture.width() > max_size || picture.height() > max_size {
+        picture.resize(max_size, max_size, FilterType::Triangle)
+    } else {
+        picture
+    };
+    let mut grey = picture.to_luma8();
+    dither::photo(&mut grey);
+    let size = [grey.width() as usize, grey.height() as usize];
+    let pixels = grey.pixels().map(|p| egui::Color32::from_gray(p.0[0])).collect();
+    ColorImage { size, pixels }
+}
+
+/// A PDF's first page, fitted within `max_size` pixels square and dithered
+pub fn pdf_first_page(pdf_data: &[u8], max_size: u32) -> Option<ColorImage> {
+    let (w, h, rgba) = render_pdf_page(pdf_data, 0, PDF_SCALE)?;
+    let page = image::RgbaImage::from_raw(w, h, rgba)?;
+    Some(dithered(page.into(), max_size))
+}
+
+/// Render one page to greyscale RGBA at the given scale (1.0 = 72 DPI).
+/// Returns (width, height, rgba) or None if hayro can't render the page.
+pub fn render_pdf_page(pdf_data: &[u8], page: usize, scale: f32) -> Option<(u32, u32, Vec<u8>)> {
+    use hayro::hayro_interpret::InterpreterSettings;
+    use hayro::hayro_syntax::Pdf;
+    use hayro::RenderSettings;
+    use std::sync::Arc;
+
+    let arc_data: Arc<dyn AsRef<[u8]> + Send + Sync> = Arc::new(pdf_data.to_vec());
+    let pdf_doc = Pdf::new(arc_data).ok()?;
+    let pdf_page = pdf_doc.pages().get(page)?;
+
+    let interpreter_settings = InterpreterSettings::default();
+    let render_settings = RenderSettings {
+        x_scale: scale,
+        y_scale: scale,
+        ..Default::default()
+    };
+    let pixmap = hayro::render(pdf_page, &interpreter_settings, &render_settings);
+    let png_data = pixmap.into_png().ok()?;
+    let img = image::load_from_memory(&png_data).ok()?;
+
+    // Greyscale for the e-ink display
+    let rgba = img.grayscale().to_rgba8();
+    let (w, h) = rgba.dimensions();
+    Some((w, h, rgba.into_raw()))
+}
+
+/// Start playing a sound file at the master volume. Playing stops when
+/// the returned sink is dropped or stopped.
+pub fn play_sound(path: &Path) -> Option<Sink> {
+    let file = std::fs::File::open(path).ok()?;
+    let source = Decoder::new(BufReader::new(file)).ok()?;
+    let sink = Sink::try_new(crate::sound::output()?).ok()?;
+    sink.set_volume(crate::sound::master_volume());
+    sink.append(source);
+    Some(sink)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_text() {
+        let dir = std::env::temp_dir().join(format!("slowcore_preview_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let text = dir.join("notes.txt");
+        std::fs::write(&text, "café").unwrap();
+        assert!(matches!(load(&text, 64), Preview::Text(t) if t == "café"));
+        let binary = dir.join("data.bin");
+        std::fs::write(&binary, [0u8, 159, 146, 150]).unwrap();
+        assert!(matches!(load(&binary, 64), Preview::Nothing));
+        assert!(matches!(load(&dir, 64), Preview::Nothing));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
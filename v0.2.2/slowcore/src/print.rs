@@ -0,0 +1,288 @@
+//! Printing — paginated page output, and "print to image" for users
+//! without a printer
+//!
+//! Text is laid out onto US Letter pages at 150 dpi with one-inch margins
+//! and a page number at the foot. Pages are pure black and white, so they
+//! print cleanly and each exported PNG is a 1-bit image a few kB in size.
+//!
+//! Exported pages go to ~/Documents/Printouts as numbered files
+//! ("letter-01.png", "letter-02.png", …).
+
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Body font for printed pages
+const FONT_DATA: &[u8] = include_bytes!("../fonts/IBMPlexSans-Text.otf");
+
+/// Glyph coverage above this prints as black
+const INK_THRESHOLD: f32 = 0.5;
+
+/// Page geometry, in device pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSetup {
+    pub width: u32,
+    pub height: u32,
+    pub margin: u32,
+    /// Body text size in pixels
+    pub font_size: f32,
+}
+
+impl PageSetup {
+    /// US Letter at 150 dpi, one-inch margins, 12pt text
+    pub const LETTER: PageSetup = PageSetup { width: 1275, height: 1650, margin: 150, font_size: 25.0 };
+
+    /// Half a letter sheet, the page of a folded booklet, with 0.6-inch
+    /// margins and 12pt text
+    pub const HALF_LETTER: PageSetup = PageSetup { width: 825, height: 1275, margin: 90, font_size: 25.0 };
+
+    fn text_width(&self) -> f32 {
+        self.width.saturating_sub(self.margin * 2) as f32
+    }
+}
+
+impl Default for PageSetup {
+    fn default() -> Self {
+        Self::LETTER
+    }
+}
+
+/// A rendered 1-bit page; `true` is ink
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub width: u32,
+    pub height: u32,
+    ink: Vec<bool>,
+}
+
+impl Page {
+    fn blank(width: u32, height: u32) -> Self {
+        Self { width, height, ink: vec![false; (width * height) as usize] }
+    }
+
+    pub fn is_ink(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.ink[(y * self.width + x) as usize]
+    }
+
+    fn set_ink(&mut self, x: i32, y: i32) {
+        if x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height {
+            self.ink[(y as u32 * self.width + x as u32) as usize] = true;
+        }
+    }
+
+    /// The page as 1-bit grayscale rows, each padded to a whole byte,
+    /// as PNG and PDF both take them
+    pub fn packed_rows(&self) -> Vec<u8> {
+        let row_bytes = self.width.div_ceil(8) as usize;
+        let mut data = vec![0u8; row_bytes * self.height as usize];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                // In 1-bit grayscale a set bit is white
+                if !self.is_ink(x, y) {
+                    data[y as usize * row_bytes + x as usize / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        data
+    }
+
+    /// Encode as a 1-bit grayscale PNG
+    pub fn to_png(&self) -> io::Result<Vec<u8>> {
+        let data = self.packed_rows();
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::One);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer.write_image_data(&data).map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)?;
+        Ok(out)
+    }
+}
+
+/// Word-wrap `text` to `max_width` using `measure`, keeping blank lines.
+/// Words wider than a whole line are broken between characters.
+pub fn wrap_lines(text: &str, max_width: f32, measure: impl Fn(&str) -> f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if measure(&candidate) <= max_width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for ch in word.chars() {
+                line.push(ch);
+                if measure(&line) > max_width && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, ch.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Split wrapped lines into pages of at most `per_page` lines.
+/// An empty document still prints one blank page.
+pub fn paginate(lines: Vec<String>, per_page: usize) -> Vec<Vec<String>> {
+    if lines.is_empty() {
+        return vec![Vec::new()];
+    }
+    lines.chunks(per_page.max(1)).map(|c| c.to_vec()).collect()
+}
+
+/// Lays text out onto pages and renders them
+pub struct Printer {
+    font: FontRef<'static>,
+    setup: PageSetup,
+}
+
+impl Printer {
+    pub fn new(setup: PageSetup) -> Self {
+        let font = FontRef::try_from_slice(FONT_DATA).expect("bundled print font is valid");
+        Self { font, setup }
+    }
+
+    fn line_height(&self) -> f32 {
+        let scaled = self.font.as_scaled(PxScale::from(self.setup.font_size));
+        scaled.height() + scaled.line_gap()
+    }
+
+    fn measure(&self, text: &str) -> f32 {
+        let scaled = self.font.as_scaled(PxScale::from(self.setup.font_size));
+        text.chars().map(|c| scaled.h_advance(scaled.glyph_id(c))).sum()
+    }
+
+    /// The text of each page, after wrapping
+    pub fn layout(&self, text: &str) -> Vec<Vec<String>> {
+        // Leave two lines at the foot for the page number
+        let body_height = self.setup.height.saturating_sub(self.setup.margin * 2) as f32;
+        let per_page = (body_height / self.line_height()) as usize;
+        let lines = wrap_lines(text, self.setup.text_width(), |s| self.measure(s));
+        paginate(lines, per_page.saturating_sub(2))
+    }
+
+    /// Lay out and render every page of `text`
+    pub fn render(&self, text: &str) -> Vec<Page> {
+        let pages = self.layout(text);
+        let total = pages.len();
+        pages.iter().enumerate().map(|(i, lines)| self.render_page(lines, i + 1, total)).collect()
+    }
+
+    fn render_page(&self, lines: &[String], number: usize, total: usize) -> Page {
+        let setup = self.setup;
+        let mut page = Page::blank(setup.width, setup.height);
+        let ascent = self.font.as_scaled(PxScale::from(setup.font_size)).ascent();
+        let mut y = setup.margin as f32;
+        for line in lines {
+            self.draw_text(&mut page, line, setup.margin as f32, y + ascent);
+            y += self.line_height();
+        }
+        if total > 1 {
+            let footer = format!("{} / {}", number, total);
+            let x = (setup.width as f32 - self.measure(&footer)) / 2.0;
+            let baseline = (setup.height - setup.margin) as f32;
+            self.draw_text(&mut page, &footer, x, baseline);
+        }
+        page
+    }
+
+    fn draw_text(&self, page: &mut Page, text: &str, x: f32, baseline: f32) {
+        let scale = PxScale::from(self.setup.font_size);
+        let scaled = self.font.as_scaled(scale);
+        let mut cx = x;
+        for ch in text.chars() {
+            let id = scaled.glyph_id(ch);
+            let glyph = id.with_scale_and_position(scale, ab_glyph::point(cx, baseline));
+            if let Some(outlined) = self.font.outline_glyph(glyph) {
+                let bounds = outlined.px_bounds();
+                outlined.draw(|px, py, coverage| {
+                    if coverage > INK_THRESHOLD {
+                        page.set_ink(bounds.min.x as i32 + px as i32, bounds.min.y as i32 + py as i32);
+                    }
+                });
+            }
+            cx += scaled.h_advance(id);
+        }
+    }
+}
+
+/// Where exported pages go
+pub fn printouts_dir() -> PathBuf {
+    crate::storage::documents_dir().join("Printouts")
+}
+
+/// A file stem for `title` that no earlier printout uses, so printing the
+/// same document twice keeps both copies. `first` is what follows the
+/// stem in the printout's first file name.
+pub fn free_stem(dir: &Path, title: &str, first: &str) -> String {
+    let base: String = title
+        .trim()
+        .chars()
+        .map(|c| if c == '/' || c.is_control() { '-' } else { c })
+        .collect();
+    let base = if base.is_empty() { "untitled".to_string() } else { base };
+    let mut stem = base.clone();
+    let mut n = 2;
+    while dir.join(format!("{}{}", stem, first)).exists() {
+        stem = format!("{} {}", base, n);
+        n += 1;
+    }
+    stem
+}
+
+/// Print `text` to numbered PNG files in [`printouts_dir`], returning the
+/// paths written
+pub fn export_page_images(title: &str, text: &str) -> io::Result<Vec<PathBuf>> {
+    export_documents(title, &[text])
+}
+
+/// Print several documents, such as the letters of a mail merge, to one
+/// run of numbered PNG files. Each starts on a new page and numbers its
+/// own pages.
+pub fn export_documents(title: &str, texts: &[&str]) -> io::Result<Vec<PathBuf>> {
+    let dir = printouts_dir();
+    std::fs::create_dir_all(&dir)?;
+    let stem = free_stem(&dir, title, "-01.png");
+    let printer = Printer::new(PageSetup::LETTER);
+    let mut written = Vec::new();
+    for page in texts.iter().flat_map(|text| printer.render(text)) {
+        let path = dir.join(format!("{}-{:02}.png", stem, written.len() + 1));
+        std::fs::write(&path, page.to_png()?)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_and_paginate() {
+        let measure = |s: &str| s.chars().count() as f32;
+        let lines = wrap_lines("the quick brown fox\n\nabcdefghij", 9.0, measure);
+        assert_eq!(lines, vec!["the quick", "brown fox", "", "abcdefghi", "j"]);
+        let pages = paginate(lines, 2);
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[2], vec!["j"]);
+        assert_eq!(paginate(Vec::new(), 2).len(), 1);
+    }
+
+    #[test]
+    fn test_page_png() {
+        let mut page = Page::blank(10, 2);
+        page.set_ink(0, 0);
+        page.set_ink(9, 1);
+        page.set_ink(20, 0);
+        assert!(page.is_ink(0, 0) && page.is_ink(9, 1) && !page.is_ink(1, 0));
+        let png = page.to_png().unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+    }
+}
@@ -0,0 +1,76 @@
+//! Per-process files — what running apps publish about themselves
+//!
+//! Minimized windows, menu-bar extras, shortcut tables, mixer entries and
+//! window claims are each a folder with a JSON file per running process.
+//! An app that crashes leaves its file behind, so readers go through
+//! [`read_per_process`], which skips those files and removes them.
+
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+/// Something a process publishes about itself
+pub trait PerProcess {
+    /// The process it belongs to
+    fn pid(&self) -> u32;
+}
+
+/// Whether process `pid` is still running
+pub fn is_alive(pid: u32) -> bool {
+    Path::new(&format!("/proc/{}", pid)).exists()
+}
+
+/// Every `T` in the `.json` files of `dir` whose process is running, in
+/// no particular order. Files of exited processes are removed; files that
+/// aren't a `T` are left alone.
+pub fn read_per_process<T: DeserializeOwned + PerProcess>(dir: &Path) -> Vec<T> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut results = Vec::new();
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.extension().is_none_or(|e| e != "json") {
+            continue;
+        }
+        let Some(item) = std::fs::read_to_string(&path).ok().and_then(|json| serde_json::from_str::<T>(&json).ok()) else {
+            continue;
+        };
+        if is_alive(item.pid()) {
+            results.push(item);
+        } else {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Entry {
+        pid: u32,
+    }
+
+    impl PerProcess for Entry {
+        fn pid(&self) -> u32 {
+            self.pid
+        }
+    }
+
+    #[test]
+    fn test_read_per_process() {
+        let dir = std::env::temp_dir().join(format!("slowcore_per_process_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("me.json"), format!(r#"{{"pid": {}}}"#, std::process::id())).unwrap();
+        // No process gets a pid this high
+        std::fs::write(dir.join("gone.json"), r#"{"pid": 4294967295}"#).unwrap();
+        std::fs::write(dir.join("settings.json"), r#"{"muted": true}"#).unwrap();
+
+        let entries = read_per_process::<Entry>(&dir);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].pid, std::process::id());
+        assert!(!dir.join("gone.json").exists());
+        assert!(dir.join("settings.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -18,7 +18,76 @@
 //! For apps that need continuous repainting (slowMidi playback, slowBreath
 //! animation), call `rc.set_continuous(true)` to keep the repaint timer
 //! running.  Call `rc.set_continuous(false)` when the activity stops.
+//!
+//! ## Power profiles
+//!
+//! Every few seconds the controller asks `safety::power_state()` whether
+//! the machine is on battery.  On battery the continuous interval is
+//! lengthened, and more so when the battery is low.  Apps say how much a
+//! timed repaint matters with [`RepaintNeed`]:
+//!
+//! - `Interactive` — the user is watching it (playback cursor, breathing
+//!   circle).  Throttled gently.
+//! - `Background` — progress that can lag (terminal output, AI thinking,
+//!   a playing track's progress bar).  Throttled hard and never faster
+//!   than the default ~4 Hz.
+//!
+//! ## Performance overlay
+//!
+//! Set `SLOWOS_PERF_OVERLAY=1` (or turn on "performance overlay" in
+//! Settings → display) and every app using the controller records how
+//! long each `update()` took and why it ran, and paints a small dithered
+//! sparkline in the bottom-right corner.  Solid bars are frames that blew
+//! the ~30 fps budget — the ones worth chasing on the Pi. Below the graph
+//! is the app's texture memory ([`textures`](crate::textures)), marked and
+//! logged once when it goes over budget, and then how long the app took
+//! to paint its first frame and to finish its [`lazy`](crate::lazy) work.
+//!
+//! ## Frame rate limit
+//!
+//! Settings → display can cap every app's frame rate ("fps_cap" in the
+//! system settings, 0 for none).  Timed and app-requested repaints are
+//! held back to the cap; input still paints straight away.
+//!
+//! ## Reduced motion
+//!
+//! With "reduce motion" on, continuous repaints run no faster than
+//! [`FrameBudget::REDUCED`], whatever the app asked for.
+//!
+//! ## E-ink output
+//!
+//! With `SLOWOS_EINK=1` (an e-paper Slowbook) the controller also feeds
+//! the panel. After each painted frame it asks egui for a screenshot,
+//! dithers it with [`dither::eink`], and passes the part that changed
+//! since the last refresh to the [`EinkPanel`] set with
+//! [`set_eink_panel`] as a partial refresh. Partial refreshes leave faint
+//! ghosts of what was there before, so every
+//! [`EINK_FULL_REFRESH_EVERY`]th refresh redraws the whole screen.
+//! Continuous repaints run no faster than [`FrameBudget::EINK`].
+//!
+//! ## Damage tracking
+//!
+//! With `SLOWOS_DAMAGE=1` the controller hashes what each panel painted,
+//! compares it with the frame before and records the rectangles that
+//! changed ([`RepaintController::damage`]). eframe redraws a whole window
+//! whenever it paints, so the saving is in not painting: once
+//! [`QUIET_FRAMES`] `Background` repaints in a row change nothing, the
+//! interval doubles for every further [`QUIET_FRAMES`], up to
+//! [`MAX_THROTTLED_INTERVAL`], until something changes again.
+//! `Interactive` repaints are never stretched this way.
 
+use crate::animation::{self, FrameBudget};
+use crate::dither::{self, draw_dither_rect};
+use crate::safety::{self, PowerState};
+use crate::storage::config_dir;
+use crate::textures::{self, TextureUsage};
+use crate::theme::SlowColors;
+use egui::{ColorImage, Pos2, Rect, Stroke, Vec2};
+use image::{GrayImage, Luma};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 /// Default repaint interval for timed updates (e-ink friendly ~4 Hz).
@@ -27,6 +96,377 @@ const DEFAULT_REPAINT_INTERVAL: Duration = Duration::from_millis(250);
 /// Repaint interval for apps that explicitly need faster updates.
 const FAST_REPAINT_INTERVAL: Duration = Duration::from_millis(33);
 
+/// How often the power source (and overlay and frame rate settings) is re-read.
+const POWER_POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Longest interval throttling may stretch a continuous repaint to.
+const MAX_THROTTLED_INTERVAL: Duration = Duration::from_secs(4);
+
+/// How much a continuous repaint matters to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepaintNeed {
+    /// The user is watching this update (animation, playback cursor).
+    Interactive,
+    /// Progress that may lag behind (polling output, status text).
+    Background,
+}
+
+/// Power profile derived from the machine's power source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerProfile {
+    /// On mains power (or no battery present) — no throttling.
+    Normal,
+    /// Running on battery.
+    Battery,
+    /// Running on battery at or below `safety::LOW_BATTERY_PERCENT`.
+    LowBattery,
+}
+
+impl PowerProfile {
+    pub fn from_power_state(state: PowerState) -> Self {
+        if state.is_low_battery() {
+            PowerProfile::LowBattery
+        } else if state.on_battery {
+            PowerProfile::Battery
+        } else {
+            PowerProfile::Normal
+        }
+    }
+
+    /// Multiplier applied to the continuous interval for a given need.
+    fn factor(self, need: RepaintNeed) -> u32 {
+        match (self, need) {
+            (PowerProfile::Normal, _) => 1,
+            (PowerProfile::Battery, RepaintNeed::Interactive) => 2,
+            (PowerProfile::Battery, RepaintNeed::Background) => 4,
+            (PowerProfile::LowBattery, RepaintNeed::Interactive) => 4,
+            (PowerProfile::LowBattery, RepaintNeed::Background) => 8,
+        }
+    }
+}
+
+/// Interval between continuous repaints after power throttling.
+///
+/// Background repaints never run faster than the default ~4 Hz, even in a
+/// controller created with [`RepaintController::with_fast_interval`].
+pub fn throttled_interval(base: Duration, need: RepaintNeed, profile: PowerProfile) -> Duration {
+    let base = match need {
+        RepaintNeed::Interactive => base,
+        RepaintNeed::Background => base.max(DEFAULT_REPAINT_INTERVAL),
+    };
+    let factor = profile.factor(need);
+    if factor == 1 {
+        return base;
+    }
+    (base * factor).min(MAX_THROTTLED_INTERVAL)
+}
+
+/// Number of frames kept for the performance overlay.
+const PERF_HISTORY: usize = 60;
+
+/// Frame time considered on budget (~30 fps).
+const PERF_BUDGET: Duration = FAST_REPAINT_INTERVAL;
+
+/// Whether the performance overlay was asked for, via the
+/// `SLOWOS_PERF_OVERLAY` environment variable or the system settings.
+pub fn perf_overlay_requested() -> bool {
+    if let Ok(v) = std::env::var("SLOWOS_PERF_OVERLAY") {
+        return !(v.is_empty() || v == "0");
+    }
+    let path = config_dir("slowos").join("settings.json");
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .and_then(|v| v.get("perf_overlay").and_then(|b| b.as_bool()))
+        .unwrap_or(false)
+}
+
+/// Shortest time between repaints under the system frame rate limit
+/// (`None` when there's no limit).
+pub fn frame_rate_cap() -> Option<Duration> {
+    let fps = crate::theme::system_setting("fps_cap").and_then(|v| v.as_u64()).unwrap_or(0);
+    cap_interval(fps)
+}
+
+fn cap_interval(fps: u64) -> Option<Duration> {
+    (fps > 0).then(|| Duration::from_secs(1) / fps.min(1000) as u32)
+}
+
+/// Greys an e-paper panel shows in a refresh.
+const EINK_LEVELS: u8 = 16;
+
+/// Partial refreshes between two full ones.
+pub const EINK_FULL_REFRESH_EVERY: u32 = 20;
+
+/// Whether the e-ink output profile was asked for (`SLOWOS_EINK`).
+pub fn eink_requested() -> bool {
+    std::env::var("SLOWOS_EINK").is_ok_and(|v| !(v.is_empty() || v == "0"))
+}
+
+/// Part of the screen to refresh, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EinkRefresh {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Redraw everything, flashing to clear ghosting.
+    pub full: bool,
+}
+
+/// The e-paper panel's driver.
+pub trait EinkPanel: Send {
+    /// Show `frame`, already dithered, redrawing only `refresh`'s region
+    /// unless it's a full refresh.
+    fn refresh(&mut self, frame: &GrayImage, refresh: EinkRefresh);
+}
+
+static EINK_PANEL: Mutex<Option<Box<dyn EinkPanel>>> = Mutex::new(None);
+
+/// Send e-ink refreshes from every controller in this process to `panel`.
+pub fn set_eink_panel(panel: Box<dyn EinkPanel>) {
+    *EINK_PANEL.lock().unwrap_or_else(|e| e.into_inner()) = Some(panel);
+}
+
+/// Works out what to refresh from one dithered frame to the next.
+#[derive(Debug, Clone)]
+pub struct EinkTracker {
+    /// The frame on the panel now.
+    shown: Option<GrayImage>,
+    /// Partial refreshes since the last full one.
+    partials: u32,
+    full_every: u32,
+}
+
+impl Default for EinkTracker {
+    fn default() -> Self {
+        Self::new(EINK_FULL_REFRESH_EVERY)
+    }
+}
+
+impl EinkTracker {
+    pub fn new(full_every: u32) -> Self {
+        Self { shown: None, partials: 0, full_every: full_every.max(1) }
+    }
+
+    /// The refresh that shows `frame`, or `None` if nothing changed.
+    /// `frame` is kept as the frame on the panel (see [`Self::shown`]).
+    pub fn next(&mut self, frame: GrayImage) -> Option<EinkRefresh> {
+        let (width, height) = frame.dimensions();
+        let full = EinkRefresh { x: 0, y: 0, width, height, full: true };
+        let refresh = match &self.shown {
+            Some(shown) if shown.dimensions() == frame.dimensions() => {
+                let (mut min, mut max) = ((width, height), (0, 0));
+                for ((x, y, new), old) in frame.enumerate_pixels().zip(shown.pixels()) {
+                    if new != old {
+                        min = (min.0.min(x), min.1.min(y));
+                        max = (max.0.max(x), max.1.max(y));
+                    }
+                }
+                if min.0 > max.0 {
+                    return None;
+                }
+                if self.partials + 1 >= self.full_every {
+                    full
+                } else {
+                    EinkRefresh { x: min.0, y: min.1, width: max.0 - min.0 + 1, height: max.1 - min.1 + 1, full: false }
+                }
+            }
+            _ => full,
+        };
+        self.partials = if refresh.full { 0 } else { self.partials + 1 };
+        self.shown = Some(frame);
+        Some(refresh)
+    }
+
+    /// The frame on the panel now.
+    pub fn shown(&self) -> Option<&GrayImage> {
+        self.shown.as_ref()
+    }
+}
+
+/// `image` in greys, dithered for the panel.
+fn eink_frame(image: &ColorImage) -> GrayImage {
+    let [width, height] = image.size;
+    let mut frame = GrayImage::from_fn(width as u32, height as u32, |x, y| {
+        let c = image.pixels[y as usize * width + x as usize];
+        Luma([((c.r() as u32 * 299 + c.g() as u32 * 587 + c.b() as u32 * 114) / 1000) as u8])
+    });
+    dither::eink(&mut frame, EINK_LEVELS);
+    frame
+}
+
+/// `Background` repaints in a row that change nothing before the interval
+/// doubles.
+pub const QUIET_FRAMES: u32 = 4;
+
+/// Whether damage tracking was asked for (`SLOWOS_DAMAGE`).
+pub fn damage_tracking_requested() -> bool {
+    std::env::var("SLOWOS_DAMAGE").is_ok_and(|v| !(v.is_empty() || v == "0"))
+}
+
+/// Shapes painted into one clip rectangle of one layer: near enough, a
+/// panel. The shapes are kept only as a hash.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaintedPanel {
+    pub layer: egui::LayerId,
+    pub clip: Rect,
+    pub hash: u64,
+}
+
+/// The panels painted so far this frame, in painting order.
+pub fn painted_panels(ctx: &egui::Context) -> Vec<PaintedPanel> {
+    let layers: Vec<_> = ctx.memory(|m| m.layer_ids().collect());
+    ctx.graphics(|graphics| {
+        let mut panels: Vec<(egui::LayerId, Rect, DefaultHasher)> = Vec::new();
+        for layer in layers {
+            let Some(list) = graphics.get(layer) else { continue };
+            for clipped in list.all_entries() {
+                match panels.last_mut() {
+                    Some((l, clip, _)) if *l == layer && *clip == clipped.clip_rect => {}
+                    _ => panels.push((layer, clipped.clip_rect, DefaultHasher::new())),
+                }
+                if let Some((_, _, hasher)) = panels.last_mut() {
+                    hash_shape(&clipped.shape, hasher);
+                }
+            }
+        }
+        panels.into_iter().map(|(layer, clip, hasher)| PaintedPanel { layer, clip, hash: hasher.finish() }).collect()
+    })
+}
+
+/// Feed what `shape` looks like into `h`, without copying it. A paint
+/// callback can draw anything, so it hashes differently every frame.
+fn hash_shape(shape: &egui::Shape, h: &mut impl Hasher) {
+    use egui::Shape;
+    fn floats(h: &mut impl Hasher, fs: &[f32]) {
+        fs.iter().for_each(|f| h.write_u32(f.to_bits()));
+    }
+    fn points(h: &mut impl Hasher, ps: &[Pos2]) {
+        ps.iter().for_each(|p| floats(h, &[p.x, p.y]));
+    }
+    fn rect(h: &mut impl Hasher, r: Rect) {
+        points(h, &[r.min, r.max]);
+    }
+    std::mem::discriminant(shape).hash(h);
+    match shape {
+        Shape::Noop => {}
+        Shape::Vec(shapes) => shapes.iter().for_each(|s| hash_shape(s, h)),
+        Shape::Circle(c) => {
+            floats(h, &[c.center.x, c.center.y, c.radius]);
+            (c.fill, c.stroke).hash(h);
+        }
+        Shape::Ellipse(e) => {
+            floats(h, &[e.center.x, e.center.y, e.radius.x, e.radius.y]);
+            (e.fill, e.stroke).hash(h);
+        }
+        Shape::LineSegment { points: ps, stroke } => {
+            points(h, ps);
+            stroke.hash(h);
+        }
+        Shape::Path(p) => {
+            points(h, &p.points);
+            (p.closed, p.fill, p.stroke).hash(h);
+        }
+        Shape::Rect(r) => {
+            rect(h, r.rect);
+            floats(h, &[r.rounding.nw, r.rounding.ne, r.rounding.sw, r.rounding.se]);
+            (r.fill, r.stroke, r.fill_texture_id).hash(h);
+            rect(h, r.uv);
+        }
+        Shape::Text(t) => {
+            points(h, &[t.pos]);
+            t.galley.job.hash(h);
+            (t.underline, t.fallback_color, t.override_text_color).hash(h);
+            floats(h, &[t.opacity_factor, t.angle]);
+        }
+        Shape::Mesh(m) => {
+            (m.texture_id, &m.indices).hash(h);
+            for v in &m.vertices {
+                points(h, &[v.pos, v.uv]);
+                v.color.hash(h);
+            }
+        }
+        Shape::QuadraticBezier(b) => {
+            points(h, &b.points);
+            (b.closed, b.fill, b.stroke).hash(h);
+        }
+        Shape::CubicBezier(b) => {
+            points(h, &b.points);
+            (b.closed, b.fill, b.stroke).hash(h);
+        }
+        Shape::Callback(c) => {
+            rect(h, c.rect);
+            std::sync::Arc::as_ptr(&c.callback).cast::<()>().hash(h);
+        }
+    }
+}
+
+/// Which panels changed from one frame to the next.
+#[derive(Debug, Clone, Default)]
+pub struct DamageTracker {
+    last: Vec<PaintedPanel>,
+}
+
+impl DamageTracker {
+    /// The rectangles that differ between the last frame and `panels`.
+    pub fn next(&mut self, panels: Vec<PaintedPanel>) -> Vec<Rect> {
+        let same_place = |a: &PaintedPanel, b: &PaintedPanel| a.layer == b.layer && a.clip == b.clip;
+        let mut damage: Vec<Rect> = panels
+            .iter()
+            .filter(|panel| !self.last.iter().any(|old| old == *panel))
+            .map(|panel| panel.clip)
+            .collect();
+        // Panels that went away leave their area to redraw too
+        damage.extend(
+            self.last.iter().filter(|old| !panels.iter().any(|panel| same_place(old, panel))).map(|old| old.clip),
+        );
+        self.last = panels;
+        damage
+    }
+}
+
+/// One recorded frame.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameSample {
+    /// Time spent between `begin_frame` and `end_frame`.
+    pub cpu: Duration,
+    pub reason: RepaintReason,
+}
+
+/// Rolling history of recent frames.
+#[derive(Debug, Clone, Default)]
+pub struct FrameStats {
+    samples: VecDeque<FrameSample>,
+}
+
+impl FrameStats {
+    pub fn push(&mut self, sample: FrameSample) {
+        if self.samples.len() == PERF_HISTORY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Recorded frames, oldest first.
+    pub fn samples(&self) -> impl Iterator<Item = &FrameSample> {
+        self.samples.iter()
+    }
+
+    pub fn last(&self) -> Option<&FrameSample> {
+        self.samples.back()
+    }
+
+    pub fn slowest(&self) -> Duration {
+        self.samples.iter().map(|s| s.cpu).max().unwrap_or_default()
+    }
+
+    /// Number of recorded frames over the ~30 fps budget.
+    pub fn over_budget(&self) -> usize {
+        self.samples.iter().filter(|s| s.cpu > PERF_BUDGET).count()
+    }
+}
+
 /// Why this frame is being painted.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RepaintReason {
@@ -40,13 +480,25 @@ pub enum RepaintReason {
     Continuous,
 }
 
+impl RepaintReason {
+    /// Short label for the performance overlay.
+    pub fn label(self) -> &'static str {
+        match self {
+            RepaintReason::Init => "init",
+            RepaintReason::Input => "input",
+            RepaintReason::StateChange => "state",
+            RepaintReason::Continuous => "timer",
+        }
+    }
+}
+
 /// Controls when the egui context should request repaints.
 ///
 /// Drop this into your app struct and call [`begin_frame`] at the top of
 /// `update()` and [`end_frame`] at the bottom.
 pub struct RepaintController {
-    /// Whether continuous (timed) repainting is active.
-    continuous: bool,
+    /// Active continuous (timed) repainting, and how much it matters.
+    continuous: Option<RepaintNeed>,
     /// Whether a one-shot repaint has been requested.
     needs_repaint: bool,
     /// Repaint interval when continuous is active.
@@ -59,6 +511,33 @@ pub struct RepaintController {
     reason: RepaintReason,
     /// Whether any input events were present this frame.
     had_input: bool,
+    /// Current power profile (re-read every POWER_POLL_INTERVAL).
+    power: PowerProfile,
+    /// Fixed profile set by the app, bypassing sysfs polling.
+    power_override: Option<PowerProfile>,
+    /// Last time the power source was read.
+    last_power_check: Instant,
+    /// Shortest time between repaints, from the system frame rate limit.
+    frame_cap: Option<Duration>,
+    /// When the current frame's begin_frame ran.
+    frame_start: Instant,
+    /// Frame history, present while the performance overlay is on.
+    perf: Option<FrameStats>,
+    /// Overlay forced on/off by the app, bypassing env/settings.
+    perf_override: Option<bool>,
+    /// The texture budget warning has been logged since the app last
+    /// went over it.
+    texture_warned: bool,
+    /// What the e-paper panel shows, with the e-ink profile on.
+    eink: Option<EinkTracker>,
+    /// This frame only ran to deliver a screenshot for the panel.
+    eink_delivery: bool,
+    /// What was painted last frame, with damage tracking on.
+    damage_tracker: Option<DamageTracker>,
+    /// The rectangles this frame changed.
+    damage: Vec<Rect>,
+    /// Timed repaints in a row that changed nothing.
+    quiet: u32,
 }
 
 impl Default for RepaintController {
@@ -70,13 +549,26 @@ impl Default for RepaintController {
 impl RepaintController {
     pub fn new() -> Self {
         Self {
-            continuous: false,
+            continuous: None,
             needs_repaint: false,
             interval: DEFAULT_REPAINT_INTERVAL,
             last_repaint: Instant::now(),
             frame: 0,
             reason: RepaintReason::Init,
             had_input: false,
+            power: PowerProfile::from_power_state(safety::power_state()),
+            power_override: None,
+            last_power_check: Instant::now(),
+            frame_cap: frame_rate_cap(),
+            frame_start: Instant::now(),
+            perf: perf_overlay_requested().then(FrameStats::default),
+            perf_override: None,
+            texture_warned: false,
+            eink: eink_requested().then(EinkTracker::default),
+            eink_delivery: false,
+            damage_tracker: damage_tracking_requested().then(DamageTracker::default),
+            damage: Vec::new(),
+            quiet: 0,
         }
     }
 
@@ -99,15 +591,115 @@ impl RepaintController {
     /// - Active playback (slowMidi, slowMusic)
     /// - Running animations (slowBreath circle, slowClock stopwatch)
     /// - Any time-driven display update
+    ///
+    /// This registers an [`RepaintNeed::Interactive`] need; use
+    /// [`set_background`](Self::set_background) for updates that can lag.
     pub fn set_continuous(&mut self, continuous: bool) {
-        self.continuous = continuous;
+        self.set_need(continuous.then_some(RepaintNeed::Interactive));
+    }
+
+    /// Enable or disable continuous repainting for background progress
+    /// (terminal output, AI thinking, a track's progress bar).
+    ///
+    /// Background repaints run at most at the default ~4 Hz and are
+    /// throttled harder than interactive ones when on battery.
+    pub fn set_background(&mut self, background: bool) {
+        self.set_need(background.then_some(RepaintNeed::Background));
+    }
+
+    /// Register the current continuous repaint need directly
+    /// (`None` stops continuous repainting).
+    pub fn set_need(&mut self, need: Option<RepaintNeed>) {
+        self.continuous = need;
     }
 
     /// Returns whether continuous mode is active.
     pub fn is_continuous(&self) -> bool {
+        self.continuous.is_some()
+    }
+
+    /// The continuous repaint need currently registered, if any.
+    pub fn need(&self) -> Option<RepaintNeed> {
         self.continuous
     }
 
+    /// The power profile currently applied to continuous repaints.
+    pub fn power_profile(&self) -> PowerProfile {
+        self.power
+    }
+
+    /// Pin the power profile instead of reading it from the system
+    /// (`None` resumes automatic detection).
+    pub fn set_power_override(&mut self, profile: Option<PowerProfile>) {
+        self.power_override = profile;
+        if let Some(p) = profile {
+            self.power = p;
+        } else {
+            self.refresh_power();
+        }
+    }
+
+    /// Interval the next continuous repaint will be scheduled after.
+    pub fn effective_interval(&self) -> Duration {
+        let need = self.continuous.unwrap_or(RepaintNeed::Interactive);
+        let mut interval = throttled_interval(self.interval, need, self.power);
+        if animation::reduce_motion() {
+            interval = interval.max(FrameBudget::REDUCED.interval());
+        }
+        if self.eink.is_some() {
+            interval = interval.max(FrameBudget::EINK.interval());
+        }
+        if need == RepaintNeed::Background && self.quiet >= QUIET_FRAMES {
+            let doublings = (self.quiet / QUIET_FRAMES).min(8);
+            interval = (interval * (1 << doublings)).min(MAX_THROTTLED_INTERVAL).max(interval);
+        }
+        self.frame_cap.map_or(interval, |cap| interval.max(cap))
+    }
+
+    fn refresh_power(&mut self) {
+        self.last_power_check = Instant::now();
+        if self.power_override.is_none() {
+            self.power = PowerProfile::from_power_state(safety::power_state());
+        }
+        if self.perf_override.is_none() {
+            self.apply_perf_overlay(perf_overlay_requested());
+        }
+        self.frame_cap = frame_rate_cap();
+    }
+
+    /// Force the performance overlay on or off (`None` follows the
+    /// environment variable and system settings again).
+    pub fn set_perf_overlay(&mut self, enabled: Option<bool>) {
+        self.perf_override = enabled;
+        self.apply_perf_overlay(enabled.unwrap_or_else(perf_overlay_requested));
+    }
+
+    fn apply_perf_overlay(&mut self, enabled: bool) {
+        match (enabled, self.perf.is_some()) {
+            (true, false) => self.perf = Some(FrameStats::default()),
+            (false, true) => self.perf = None,
+            _ => {}
+        }
+    }
+
+    /// Turn damage tracking on or off, whatever the environment says.
+    pub fn set_damage_tracking(&mut self, enabled: bool) {
+        self.damage_tracker = enabled.then(DamageTracker::default);
+        self.damage.clear();
+        self.quiet = 0;
+    }
+
+    /// The rectangles the last frame changed, in points (empty with damage
+    /// tracking off).
+    pub fn damage(&self) -> &[Rect] {
+        &self.damage
+    }
+
+    /// Recorded frame history, if the performance overlay is on.
+    pub fn frame_stats(&self) -> Option<&FrameStats> {
+        self.perf.as_ref()
+    }
+
     /// Request a single repaint on the next opportunity.
     ///
     /// Call this when internal state changes outside of user input — for
@@ -132,9 +724,24 @@ impl RepaintController {
     /// Inspects the egui input to determine why this frame is running
     /// and sets the repaint reason accordingly.
     pub fn begin_frame(&mut self, ctx: &egui::Context) {
+        self.frame_start = Instant::now();
+        crate::log::refresh();
+        // Taps on the on-screen keyboard count as input like any other
+        crate::keyboard::deliver(ctx);
+        // Commands from scripts (slowctl)
+        crate::ipc::deliver(ctx);
+        // A screenshot for the e-ink panel isn't input
+        let mut screenshot = None;
         self.had_input = ctx.input(|i| {
+            let mut events = 0;
+            for event in &i.events {
+                match event {
+                    egui::Event::Screenshot { image, .. } => screenshot = Some(image.clone()),
+                    _ => events += 1,
+                }
+            }
             // Any mouse movement, button press, scroll, or key event counts
-            !i.events.is_empty()
+            events > 0
                 || i.pointer.any_pressed()
                 || i.pointer.any_released()
                 || i.pointer.any_click()
@@ -142,13 +749,33 @@ impl RepaintController {
                 || i.pointer.is_moving()
         });
 
+        if self.had_input {
+            crate::session::note_activity();
+        }
+
+        // The first frame has just reached the screen
+        if self.frame == 1 {
+            crate::lazy::mark("first paint");
+            crate::log::info!("startup: {}", crate::lazy::summary());
+        }
+
+        self.eink_delivery = false;
+        if let (Some(tracker), Some(image)) = (&mut self.eink, screenshot) {
+            self.eink_delivery = !self.had_input && !self.needs_repaint;
+            if let (Some(refresh), Some(frame)) = (tracker.next(eink_frame(&image)), tracker.shown()) {
+                if let Some(panel) = EINK_PANEL.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                    panel.refresh(frame, refresh);
+                }
+            }
+        }
+
         self.reason = if self.frame == 0 {
             RepaintReason::Init
         } else if self.had_input {
             RepaintReason::Input
         } else if self.needs_repaint {
             RepaintReason::StateChange
-        } else if self.continuous {
+        } else if self.continuous.is_some() {
             RepaintReason::Continuous
         } else {
             // Shouldn't normally get here (frame was triggered by
@@ -163,20 +790,293 @@ impl RepaintController {
     /// Call at the **end** of your `update()` method.
     ///
     /// Schedules the next repaint if needed:
-    /// - Continuous mode → repaint after the configured interval.
-    /// - One-shot request pending → immediate repaint.
+    /// - Continuous mode → repaint after the configured interval,
+    ///   lengthened according to the power profile.
+    /// - One-shot request pending → immediate repaint, or as soon as the
+    ///   frame rate limit allows.
     /// - Otherwise → no repaint (egui will wake on next input event).
+    ///
+    /// With the performance overlay on, also records the frame and paints
+    /// the sparkline. Also keeps the window's layout claim current (see
+    /// [`window_layout`](crate::window_layout)).
     pub fn end_frame(&mut self, ctx: &egui::Context) {
         self.frame += 1;
 
-        if self.continuous {
-            ctx.request_repaint_after(self.interval);
+        if let Some(tracker) = &mut self.damage_tracker {
+            self.damage = tracker.next(painted_panels(ctx));
+            if !self.damage.is_empty() {
+                self.quiet = 0;
+            } else if self.reason == RepaintReason::Continuous {
+                self.quiet += 1;
+            }
+        }
+
+        if let Some(stats) = &mut self.perf {
+            stats.push(FrameSample {
+                cpu: self.frame_start.elapsed(),
+                reason: self.reason,
+            });
+            let usage = textures::usage(ctx);
+            if usage.over_budget() && !self.texture_warned {
+                let largest: Vec<String> = textures::largest(ctx, 3)
+                    .into_iter()
+                    .map(|(name, bytes)| format!("{} {}", name, textures::format_bytes(bytes)))
+                    .collect();
+                crate::log::warn!(
+                    "textures use {}, over the {} budget (largest: {})",
+                    textures::format_bytes(usage.total),
+                    textures::format_bytes(textures::budget()),
+                    largest.join(", ")
+                );
+            }
+            self.texture_warned = usage.over_budget();
+            paint_perf_overlay(ctx, stats, &usage);
+        }
+
+        if self.last_power_check.elapsed() >= POWER_POLL_INTERVAL {
+            self.refresh_power();
+        }
+
+        crate::window_layout::track(ctx);
+        crate::keyboard::note_focus(ctx);
+        crate::ipc::finish();
+
+        // Capture what was just painted for the e-ink panel, unless this
+        // frame only ran to hand over the last capture
+        if self.eink.is_some() && !self.eink_delivery {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+        }
+
+        if self.continuous.is_some() {
+            ctx.request_repaint_after(self.effective_interval());
             self.last_repaint = Instant::now();
         } else if self.needs_repaint {
             // Something was marked dirty during this frame's UI code.
-            ctx.request_repaint();
+            match self.frame_cap.and_then(|cap| cap.checked_sub(self.frame_start.elapsed())) {
+                Some(wait) if !wait.is_zero() => ctx.request_repaint_after(wait),
+                _ => ctx.request_repaint(),
+            }
             self.last_repaint = Instant::now();
         }
         // else: no scheduled repaint — egui sleeps until next input.
     }
 }
+
+/// Paint the frame-time sparkline in the bottom-right corner.
+///
+/// Bars are scaled so the top of the box is twice the budget; the dotted
+/// line marks the budget itself.  Frames under budget are dithered,
+/// frames over it are solid black.  Texture memory goes underneath, in
+/// white on black when over its budget.
+fn paint_perf_overlay(ctx: &egui::Context, stats: &FrameStats, usage: &TextureUsage) {
+    const BAR_W: f32 = 2.0;
+    const GRAPH_H: f32 = 24.0;
+    const PAD: f32 = 4.0;
+
+    let screen = ctx.screen_rect();
+    const TEXT_H: f32 = 14.0;
+    let size = Vec2::new(PERF_HISTORY as f32 * BAR_W + PAD * 2.0, GRAPH_H + TEXT_H * 3.0 + PAD * 2.0);
+    // Sit just above the status bar
+    let rect = Rect::from_min_size(screen.max - size - Vec2::new(4.0, 28.0), size);
+
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Tooltip,
+        egui::Id::new("slowcore_perf_overlay"),
+    ));
+    painter.rect_filled(rect, 0.0, SlowColors::WHITE);
+    painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+
+    let graph = Rect::from_min_size(
+        rect.min + Vec2::new(PAD, TEXT_H + PAD),
+        Vec2::new(PERF_HISTORY as f32 * BAR_W, GRAPH_H),
+    );
+    let full_scale = PERF_BUDGET.as_secs_f32() * 2.0;
+    for (i, sample) in stats.samples().enumerate() {
+        let frac = (sample.cpu.as_secs_f32() / full_scale).min(1.0);
+        let h = (frac * GRAPH_H).max(1.0).round();
+        let x = graph.min.x + i as f32 * BAR_W;
+        let bar = Rect::from_min_max(Pos2::new(x, graph.max.y - h), Pos2::new(x + BAR_W, graph.max.y));
+        if sample.cpu > PERF_BUDGET {
+            painter.rect_filled(bar, 0.0, SlowColors::BLACK);
+        } else {
+            draw_dither_rect(&painter, bar, SlowColors::BLACK, 1);
+        }
+    }
+
+    // Budget line, dotted
+    let budget_y = (graph.max.y - GRAPH_H / 2.0).round();
+    let mut x = graph.min.x;
+    while x < graph.max.x {
+        painter.rect_filled(Rect::from_min_size(Pos2::new(x, budget_y), Vec2::splat(1.0)), 0.0, SlowColors::BLACK);
+        x += 3.0;
+    }
+
+    let text = match stats.last() {
+        Some(last) => format!(
+            "{:.1}ms {}  max {:.0}  slow {}",
+            last.cpu.as_secs_f32() * 1000.0,
+            last.reason.label(),
+            stats.slowest().as_secs_f32() * 1000.0,
+            stats.over_budget(),
+        ),
+        None => String::new(),
+    };
+    painter.text(
+        rect.min + Vec2::new(PAD, PAD - 1.0),
+        egui::Align2::LEFT_TOP,
+        text,
+        egui::FontId::monospace(9.0),
+        SlowColors::BLACK,
+    );
+
+    let line = Rect::from_min_size(Pos2::new(rect.min.x, graph.max.y + 2.0), Vec2::new(rect.width(), TEXT_H - 2.0));
+    let over = usage.over_budget();
+    if over {
+        painter.rect_filled(line, 0.0, SlowColors::BLACK);
+    }
+    let text = format!(
+        "tex {} of {}{}  fonts {}",
+        textures::format_bytes(usage.total),
+        textures::format_bytes(textures::budget()),
+        if over { "!" } else { "" },
+        textures::format_bytes(usage.fonts),
+    );
+    painter.text(
+        line.min + Vec2::new(PAD, 1.0),
+        egui::Align2::LEFT_TOP,
+        text,
+        egui::FontId::monospace(9.0),
+        if over { SlowColors::WHITE } else { SlowColors::BLACK },
+    );
+
+    let mut text = match crate::lazy::marked("first paint") {
+        Some(paint) => format!("start {}ms", paint.as_millis()),
+        None => "start -".to_string(),
+    };
+    if let Some((name, ready)) = crate::lazy::marks().last().filter(|(name, _)| *name != "first paint") {
+        text += &format!("  {} {}ms", name, ready.as_millis());
+    }
+    painter.text(
+        line.min + Vec2::new(PAD, TEXT_H),
+        egui::Align2::LEFT_TOP,
+        text,
+        egui::FontId::monospace(9.0),
+        SlowColors::BLACK,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttled_interval() {
+        let fast = FAST_REPAINT_INTERVAL;
+        // Mains power: unchanged for interactive, floored for background
+        assert_eq!(throttled_interval(fast, RepaintNeed::Interactive, PowerProfile::Normal), fast);
+        assert_eq!(
+            throttled_interval(fast, RepaintNeed::Background, PowerProfile::Normal),
+            DEFAULT_REPAINT_INTERVAL
+        );
+        // Battery stretches background more than interactive
+        assert_eq!(throttled_interval(fast, RepaintNeed::Interactive, PowerProfile::Battery), fast * 2);
+        assert_eq!(
+            throttled_interval(DEFAULT_REPAINT_INTERVAL, RepaintNeed::Background, PowerProfile::LowBattery),
+            Duration::from_secs(2)
+        );
+        // Never beyond the cap
+        assert_eq!(
+            throttled_interval(Duration::from_secs(1), RepaintNeed::Background, PowerProfile::LowBattery),
+            MAX_THROTTLED_INTERVAL
+        );
+    }
+
+    #[test]
+    fn test_frame_rate_cap() {
+        assert_eq!(cap_interval(0), None);
+        assert_eq!(cap_interval(4), Some(DEFAULT_REPAINT_INTERVAL));
+        let mut rc = RepaintController::with_fast_interval();
+        rc.set_power_override(Some(PowerProfile::Normal));
+        rc.frame_cap = cap_interval(10);
+        rc.set_continuous(true);
+        assert_eq!(rc.effective_interval(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_eink_tracker() {
+        let mut tracker = EinkTracker::new(3);
+        let mut frame = GrayImage::from_pixel(16, 8, Luma([255]));
+        // The first frame, and a frame the same as the last
+        assert!(tracker.next(frame.clone()).unwrap().full);
+        assert_eq!(tracker.next(frame.clone()), None);
+        // Only the changed block is refreshed
+        frame.put_pixel(4, 2, Luma([0]));
+        frame.put_pixel(6, 5, Luma([0]));
+        assert_eq!(
+            tracker.next(frame.clone()),
+            Some(EinkRefresh { x: 4, y: 2, width: 3, height: 4, full: false })
+        );
+        frame.put_pixel(0, 0, Luma([0]));
+        assert!(!tracker.next(frame.clone()).unwrap().full);
+        // Then a full refresh clears the ghosting
+        frame.put_pixel(1, 0, Luma([0]));
+        assert!(tracker.next(frame.clone()).unwrap().full);
+    }
+
+    #[test]
+    fn test_damage_tracker() {
+        let panel = |x: f32, text: &str| {
+            let mut hasher = DefaultHasher::new();
+            hash_shape(&egui::Shape::Vec(vec![egui::Shape::Noop; text.len()]), &mut hasher);
+            PaintedPanel {
+                layer: egui::LayerId::background(),
+                clip: Rect::from_min_size(Pos2::new(x, 0.0), Vec2::splat(100.0)),
+                hash: hasher.finish(),
+            }
+        };
+        let mut tracker = DamageTracker::default();
+        assert_eq!(tracker.next(vec![panel(0.0, "a"), panel(100.0, "b")]).len(), 2);
+        assert!(tracker.next(vec![panel(0.0, "a"), panel(100.0, "b")]).is_empty());
+        // Only the panel that changed, then the one that went away
+        assert_eq!(tracker.next(vec![panel(0.0, "a"), panel(100.0, "bb")]), vec![panel(100.0, "").clip]);
+        assert_eq!(tracker.next(vec![panel(0.0, "a")]), vec![panel(100.0, "").clip]);
+
+        // The same shapes hash the same; a moved or recoloured one doesn't
+        let hash = |shape: egui::Shape| {
+            let mut hasher = DefaultHasher::new();
+            hash_shape(&shape, &mut hasher);
+            hasher.finish()
+        };
+        let square = |x: f32, fill| egui::Shape::rect_filled(Rect::from_min_size(Pos2::new(x, 0.0), Vec2::splat(10.0)), 0.0, fill);
+        assert_eq!(hash(square(0.0, SlowColors::BLACK)), hash(square(0.0, SlowColors::BLACK)));
+        assert_ne!(hash(square(0.0, SlowColors::BLACK)), hash(square(1.0, SlowColors::BLACK)));
+        assert_ne!(hash(square(0.0, SlowColors::BLACK)), hash(square(0.0, SlowColors::WHITE)));
+
+        // Idle background repaints back off; interactive ones never do
+        let mut rc = RepaintController::new();
+        rc.set_power_override(Some(PowerProfile::Normal));
+        rc.frame_cap = None;
+        rc.set_continuous(true);
+        rc.quiet = QUIET_FRAMES * 2;
+        assert_eq!(rc.effective_interval(), DEFAULT_REPAINT_INTERVAL);
+        rc.set_background(true);
+        let background = throttled_interval(DEFAULT_REPAINT_INTERVAL, RepaintNeed::Background, PowerProfile::Normal);
+        assert_eq!(rc.effective_interval(), background * 4);
+    }
+
+    #[test]
+    fn test_frame_stats_history() {
+        let mut stats = FrameStats::default();
+        for ms in 0..(PERF_HISTORY as u64 + 10) {
+            stats.push(FrameSample {
+                cpu: Duration::from_millis(ms),
+                reason: RepaintReason::Input,
+            });
+        }
+        assert_eq!(stats.samples().count(), PERF_HISTORY);
+        assert_eq!(stats.last().unwrap().cpu, Duration::from_millis(PERF_HISTORY as u64 + 9));
+        assert_eq!(stats.slowest(), Duration::from_millis(PERF_HISTORY as u64 + 9));
+        // Frames from 34ms up are over the 33ms budget
+        assert_eq!(stats.over_budget(), PERF_HISTORY + 10 - 34);
+    }
+}
@@ -0,0 +1,153 @@
+//! Restrictions on apps, for children or for focus
+//!
+//! An app can be hidden from the desktop, or allowed only between two
+//! times of day ("no games after 9pm"). The desktop's process manager
+//! refuses to start an app outside its hours and greys out its icon; the
+//! passcode set in settings lifts every restriction for a while.
+//!
+//! Kept for the whole machine in /etc/slowos/restrictions.json, like the
+//! accounts, so that no account's own settings can undo them.
+
+use crate::accounts::{hash_password, verify_password};
+use crate::app_registry::{self, App};
+use crate::storage::save_atomic_private;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+const RESTRICTIONS_FILE: &str = "/etc/slowos/restrictions.json";
+
+/// How long the passcode lifts restrictions for
+pub const OVERRIDE_TIME: Duration = Duration::from_secs(30 * 60);
+
+/// Apps never restricted: settings (the passcode guards its restrictions
+/// pane instead), and the trash and credits
+const NEVER_RESTRICTED: &[&str] = &["settings", "trash", "credits"];
+
+/// Apps that can be restricted: every app on the desktop, built in or
+/// installed, but the few above
+pub fn apps() -> Vec<App> {
+    app_registry::all().into_iter().filter(|a| !NEVER_RESTRICTED.contains(&a.binary.as_str())).collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AppRule {
+    /// Left off the desktop and out of search
+    #[serde(default)]
+    pub hidden: bool,
+    /// Only allowed from the first time to the second, in minutes after
+    /// midnight; the span may cross midnight. None = any time.
+    #[serde(default)]
+    pub hours: Option<(u16, u16)>,
+}
+
+impl AppRule {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether the app may run at `minute` after midnight
+    pub fn allows(&self, minute: u16) -> bool {
+        match self.hours {
+            None => true,
+            Some((from, until)) if from <= until => (from..until).contains(&minute),
+            Some((from, until)) => minute >= from || minute < until,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Restrictions {
+    /// Keyed by binary name
+    #[serde(default)]
+    pub apps: BTreeMap<String, AppRule>,
+    /// argon2 hash of the passcode; empty for none
+    #[serde(default)]
+    passcode: String,
+}
+
+/// "21:00" for 1260 minutes after midnight
+pub fn format_minute(minute: u16) -> String {
+    format!("{:02}:{:02}", minute / 60 % 24, minute % 60)
+}
+
+impl Restrictions {
+    pub fn load() -> Self {
+        std::fs::read_to_string(RESTRICTIONS_FILE)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&mut self) -> Result<(), String> {
+        self.apps.retain(|_, rule| !rule.is_default());
+        let path = Path::new(RESTRICTIONS_FILE);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        // It holds the passcode's hash
+        save_atomic_private(path, json.as_bytes()).map_err(|e| e.to_string())
+    }
+
+    pub fn rule(&self, binary: &str) -> Option<&AppRule> {
+        self.apps.get(binary)
+    }
+
+    pub fn rule_mut(&mut self, binary: &str) -> &mut AppRule {
+        self.apps.entry(binary.to_string()).or_default()
+    }
+
+    pub fn hidden(&self, binary: &str) -> bool {
+        self.rule(binary).is_some_and(|r| r.hidden)
+    }
+
+    /// Whether `binary` may run at `minute` after midnight
+    pub fn allows(&self, binary: &str, minute: u16) -> bool {
+        match self.rule(binary) {
+            Some(rule) => rule.allows(minute),
+            None => true,
+        }
+    }
+
+    pub fn has_passcode(&self) -> bool {
+        !self.passcode.is_empty()
+    }
+
+    /// With no passcode set, anything goes
+    pub fn check_passcode(&self, passcode: &str) -> bool {
+        self.passcode.is_empty() || verify_password(&self.passcode, passcode)
+    }
+
+    pub fn set_passcode(&mut self, old: &str, new: &str) -> Result<(), String> {
+        if !self.check_passcode(old) {
+            return Err("the current passcode is wrong".to_string());
+        }
+        self.passcode = if new.is_empty() { String::new() } else { hash_password(new)? };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hours() {
+        let day = AppRule { hidden: false, hours: Some((8 * 60, 21 * 60)) };
+        assert!(day.allows(12 * 60) && day.allows(8 * 60));
+        assert!(!day.allows(21 * 60) && !day.allows(3 * 60));
+        let night = AppRule { hidden: false, hours: Some((22 * 60, 6 * 60)) };
+        assert!(night.allows(23 * 60) && night.allows(60));
+        assert!(!night.allows(12 * 60));
+        assert_eq!(format_minute(21 * 60 + 5), "21:05");
+    }
+
+    #[test]
+    fn test_apps() {
+        let apps = apps();
+        assert!(apps.iter().any(|a| a.binary == "slowstore") && apps.iter().any(|a| a.binary == "slowkeyboard"));
+        assert!(!apps.iter().any(|a| a.binary == "settings"));
+    }
+}
@@ -0,0 +1,105 @@
+//! Running apps — the desktop's list of the processes it manages
+//!
+//! slowDesktop publishes every app it has started (one entry per
+//! instance) so tools like slowMonitor know which processes belong to
+//! SlowOS. Those tools never kill anything themselves: they file a quit
+//! request and the desktop ends the process through its process manager,
+//! which also cleans up cgroups and running state.
+//!
+//! Same file-based IPC as minimize: the list is ~/.config/slowos/running.json
+//! and each request is a file in ~/.config/slowos/quit_requests/.
+
+use crate::process;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How often the watcher thread looks for quit requests
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A process started by the desktop
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RunningApp {
+    /// Process manager key ("slowwrite", or "slowwrite_2" for a second instance)
+    pub key: String,
+    /// Binary name
+    pub binary: String,
+    /// Display name ("slowWrite")
+    pub name: String,
+    pub pid: u32,
+}
+
+fn slowos_dir() -> PathBuf {
+    directories::ProjectDirs::from("", "", "slowos")
+        .map(|p| p.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/slowos"))
+}
+
+fn requests_dir() -> PathBuf {
+    let dir = slowos_dir().join("quit_requests");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// Replace the published list (used by slowDesktop)
+pub fn publish(apps: &[RunningApp]) {
+    let dir = slowos_dir();
+    let _ = std::fs::create_dir_all(&dir);
+    if let Ok(json) = serde_json::to_string(apps) {
+        let path = dir.join("running.json");
+        let tmp = path.with_extension("tmp");
+        if std::fs::write(&tmp, json).is_ok() {
+            let _ = std::fs::rename(&tmp, &path);
+        }
+    }
+}
+
+/// Apps the desktop is managing. Entries whose process has gone are
+/// skipped, since the desktop only republishes when it next polls.
+pub fn read_all() -> Vec<RunningApp> {
+    std::fs::read_to_string(slowos_dir().join("running.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str::<Vec<RunningApp>>(&json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|app| process::is_alive(app.pid))
+        .collect()
+}
+
+/// Ask the desktop to quit a managed process
+pub fn request_quit(pid: u32) {
+    let _ = std::fs::write(requests_dir().join(pid.to_string()), "");
+}
+
+/// Take all pending quit requests (used by slowDesktop)
+pub fn take_quit_requests() -> Vec<u32> {
+    let mut pids = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(requests_dir()) {
+        for entry in entries.flatten() {
+            if let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse().ok()) {
+                pids.push(pid);
+            }
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+    pids
+}
+
+/// Wake `ctx` whenever a quit request comes in
+pub fn watch(ctx: egui::Context) {
+    let dir = requests_dir();
+    let _ = std::thread::Builder::new()
+        .name("slowcore-quit-requests".into())
+        .spawn(move || {
+            let modified = || std::fs::metadata(&dir).and_then(|m| m.modified()).ok();
+            let mut last = modified();
+            loop {
+                std::thread::sleep(WATCH_INTERVAL);
+                let now = modified();
+                if now != last {
+                    last = now;
+                    ctx.request_repaint();
+                }
+            }
+        });
+}
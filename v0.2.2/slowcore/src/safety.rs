@@ -2,6 +2,8 @@
 //!
 //! These helpers eliminate common panic sources: string slicing on
 //! non-UTF-8 boundaries and unhandled panics in per-frame rendering.
+//! They also report the power source, so apps can back off when the
+//! Slowbook is running low on battery, and guard switching it off.
 
 use std::path::Path;
 
@@ -46,7 +48,7 @@ pub fn catch_or<T>(fallback: T, f: impl FnOnce() -> T) -> T {
     match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
         Ok(val) => val,
         Err(_) => {
-            eprintln!("[slowos] caught panic in frame — recovered");
+            crate::log::error!("caught panic in frame — recovered");
             fallback
         }
     }
@@ -104,6 +106,120 @@ pub fn is_system_path(path: &Path) -> bool {
     false
 }
 
+/// Battery percentage at or below which the machine counts as low on power.
+pub const LOW_BATTERY_PERCENT: u8 = 20;
+
+/// Snapshot of the machine's power source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PowerState {
+    /// Running from the battery (no mains/USB power connected).
+    pub on_battery: bool,
+    /// Battery charge in percent, if a battery was found.
+    pub battery_percent: Option<u8>,
+}
+
+impl PowerState {
+    /// On battery and at or below [`LOW_BATTERY_PERCENT`].
+    pub fn is_low_battery(&self) -> bool {
+        self.on_battery
+            && self.battery_percent.is_some_and(|p| p <= LOW_BATTERY_PERCENT)
+    }
+}
+
+/// Read the current power state from `/sys/class/power_supply`.
+///
+/// Machines without a battery (desktop dev boxes, a Pi on a wall adapter)
+/// report `on_battery: false`. Never fails — unreadable entries are skipped.
+pub fn power_state() -> PowerState {
+    let mut supplies = Vec::new();
+    if let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") {
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            let read = |name: &str| {
+                std::fs::read_to_string(dir.join(name))
+                    .map(|s| s.trim().to_string())
+                    .unwrap_or_default()
+            };
+            supplies.push(SupplyInfo {
+                kind: read("type"),
+                online: read("online"),
+                status: read("status"),
+                capacity: read("capacity"),
+            });
+        }
+    }
+    power_state_from(&supplies)
+}
+
+/// Raw sysfs fields for one power supply.
+struct SupplyInfo {
+    kind: String,
+    online: String,
+    status: String,
+    capacity: String,
+}
+
+fn power_state_from(supplies: &[SupplyInfo]) -> PowerState {
+    let mut state = PowerState::default();
+    let mut has_battery = false;
+    let mut external_online = false;
+    let mut discharging = false;
+
+    for s in supplies {
+        if s.kind == "Battery" {
+            has_battery = true;
+            if let Ok(pct) = s.capacity.parse::<u8>() {
+                state.battery_percent = Some(pct.min(100));
+            }
+            if s.status == "Discharging" {
+                discharging = true;
+            }
+        } else if s.online == "1" {
+            // Mains, USB, USB-C…
+            external_online = true;
+        }
+    }
+
+    state.on_battery = has_battery && (discharging || !external_online);
+    state
+}
+
+/// Whether we're running on the Slowbook itself (a device tree model is
+/// present, as on the Pi, or `SLOWOS_EMBEDDED` is set).
+pub fn is_embedded() -> bool {
+    Path::new("/proc/device-tree/model").exists() || std::env::var("SLOWOS_EMBEDDED").is_ok()
+}
+
+/// What to do with the machine at the end of a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction {
+    PowerOff,
+    Reboot,
+}
+
+/// Power off or reboot the machine, after flushing disks.
+///
+/// Refuses on anything but the Slowbook ([`is_embedded`]), so a
+/// development run never switches off the host. Returns why it didn't
+/// happen; on success the call normally doesn't get to return.
+pub fn power_action(action: PowerAction) -> Result<(), String> {
+    if !is_embedded() {
+        return Err("not on slowbook hardware".into());
+    }
+    let binary = match action {
+        PowerAction::PowerOff => "/sbin/poweroff",
+        PowerAction::Reboot => "/sbin/reboot",
+    };
+    if !Path::new(binary).exists() {
+        return Err(format!("{} not found", binary));
+    }
+    let _ = std::process::Command::new("sync").status();
+    std::process::Command::new(binary)
+        .status()
+        .map_err(|e| format!("{}: {}", binary, e))
+        .and_then(|status| if status.success() { Ok(()) } else { Err(format!("{} failed: {}", binary, status)) })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -150,6 +266,37 @@ mod tests {
         assert_eq!(safe_slice_from(s, 3), "fé");
     }
 
+    fn supply(kind: &str, online: &str, status: &str, capacity: &str) -> SupplyInfo {
+        SupplyInfo {
+            kind: kind.into(),
+            online: online.into(),
+            status: status.into(),
+            capacity: capacity.into(),
+        }
+    }
+
+    #[test]
+    fn test_power_state() {
+        // No supplies at all (desktop / dev machine)
+        assert_eq!(power_state_from(&[]), PowerState::default());
+
+        // Plugged in and charging
+        let plugged = power_state_from(&[
+            supply("Mains", "1", "", ""),
+            supply("Battery", "", "Charging", "55"),
+        ]);
+        assert!(!plugged.on_battery);
+        assert_eq!(plugged.battery_percent, Some(55));
+
+        // Unplugged and low
+        let low = power_state_from(&[
+            supply("Mains", "0", "", ""),
+            supply("Battery", "", "Discharging", "12"),
+        ]);
+        assert!(low.on_battery);
+        assert!(low.is_low_battery());
+    }
+
     #[test]
     fn test_empty_string() {
         let s = "";
@@ -0,0 +1,141 @@
+//! Session end — slowDesktop asking running apps to quit
+//!
+//! Before powering off, the desktop calls [`request_quit`]. Apps notice
+//! within half a second through [`quit_requested`], save, and close their
+//! window. `CloseGuard` does this on its own: with unsaved changes it
+//! answers as if "save" was chosen, otherwise it closes straight away.
+//! Apps that don't use it can call [`close_on_quit`] once per frame.
+//!
+//! Apps still running when the desktop's timeout runs out are terminated.
+//!
+//! Same file-based IPC as minimize: ~/.config/slowos/session/quit.json.
+//!
+//! Apps also note when the user last did something ([`note_activity`],
+//! called by `RepaintController`), so the desktop can lock the screen
+//! after a while of nobody using any app.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How often apps look for a quit request
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Requests older than this are left over from a crash and ignored
+const STALE_AFTER: Duration = Duration::from_secs(120);
+
+/// Activity is written down at most this often
+const ACTIVITY_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct QuitRequest {
+    /// Unix seconds
+    requested_at: u64,
+}
+
+fn quit_path() -> PathBuf {
+    let dir = directories::ProjectDirs::from("", "", "slowos")
+        .map(|p| p.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/slowos"))
+        .join("session");
+    let _ = std::fs::create_dir_all(&dir);
+    dir.join("quit.json")
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// In the runtime dir (tmpfs), so it doesn't wear the SD card
+fn activity_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("slowos-activity")
+}
+
+/// Ask every app to save and quit (used by slowDesktop)
+pub fn request_quit() {
+    let request = QuitRequest { requested_at: unix_now() };
+    if let Ok(json) = serde_json::to_string(&request) {
+        let _ = std::fs::write(quit_path(), json);
+    }
+}
+
+/// Withdraw the request, after shutdown was cancelled or on the next boot
+pub fn clear_quit_request() {
+    let _ = std::fs::remove_file(quit_path());
+}
+
+/// Whether the desktop has asked apps to quit. Cheap enough to call
+/// every frame; the file is looked at no more than every half second.
+pub fn quit_requested() -> bool {
+    static CACHE: Mutex<Option<(Instant, bool)>> = Mutex::new(None);
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((checked_at, requested)) = *cache {
+        if checked_at.elapsed() < CHECK_INTERVAL {
+            return requested;
+        }
+    }
+    let requested = std::fs::read_to_string(quit_path())
+        .ok()
+        .and_then(|s| serde_json::from_str::<QuitRequest>(&s).ok())
+        .is_some_and(|r| unix_now().saturating_sub(r.requested_at) <= STALE_AFTER.as_secs());
+    *cache = Some((Instant::now(), requested));
+    requested
+}
+
+/// Wake `ctx` when a quit request arrives, so idle apps (which don't
+/// repaint on a timer) still see it. Only the first call starts a thread.
+pub fn watch(ctx: &egui::Context) {
+    static WATCHING: AtomicBool = AtomicBool::new(false);
+    if WATCHING.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    let ctx = ctx.clone();
+    let _ = std::thread::Builder::new()
+        .name("slowcore-session".into())
+        .spawn(move || {
+            let mut was_requested = false;
+            loop {
+                std::thread::sleep(CHECK_INTERVAL);
+                let requested = quit_requested();
+                if requested && !was_requested {
+                    ctx.request_repaint();
+                }
+                was_requested = requested;
+            }
+        });
+}
+
+/// Close the window when the desktop asks, for apps with nothing to save.
+/// Call once per frame.
+pub fn close_on_quit(ctx: &egui::Context) {
+    watch(ctx);
+    if quit_requested() {
+        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+    }
+}
+
+/// The user just did something in this app. Cheap to call on every input
+/// frame; the time is written no more than every ten seconds.
+pub fn note_activity() {
+    static LAST: Mutex<Option<Instant>> = Mutex::new(None);
+    let mut last = LAST.lock().unwrap_or_else(|e| e.into_inner());
+    if last.is_some_and(|t| t.elapsed() < ACTIVITY_INTERVAL) {
+        return;
+    }
+    *last = Some(Instant::now());
+    let _ = std::fs::write(activity_path(), unix_now().to_string());
+}
+
+/// How long since the user last did anything in any app (zero if unknown)
+pub fn idle_for() -> Duration {
+    let last = std::fs::read_to_string(activity_path()).ok().and_then(|s| s.trim().parse::<u64>().ok());
+    match last {
+        Some(last) => Duration::from_secs(unix_now().saturating_sub(last)),
+        None => Duration::ZERO,
+    }
+}
@@ -0,0 +1,249 @@
+//! Keyboard shortcut registry
+//!
+//! Apps declare their bindings once:
+//!
+//! ```ignore
+//! let shortcuts = Shortcuts::new("slowpaint")
+//!     .add(Action::Save, "save", KeyCombo::cmd(Key::S))
+//!     .add(Action::SaveAs, "save as", KeyCombo::cmd(Key::S).shift());
+//! shortcuts.publish();
+//! ```
+//!
+//! and each frame call `handle(ctx)`, which consumes matching key presses
+//! and returns the triggered actions.  Modifiers are matched exactly, so
+//! ⌘S never fires for ⇧⌘S regardless of declaration order.
+//!
+//! `publish()` writes the list to ~/.config/slowos/shortcuts/ (same
+//! file-based IPC as minimize) so keyMacros can show every running app's
+//! bindings, and warns on stderr about keys bound twice.
+
+use crate::process::{read_per_process, PerProcess};
+use egui::{Context, Event, Key, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A key plus modifiers, e.g. ⇧⌘S
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    #[serde(with = "key_name")]
+    pub key: Key,
+    pub command: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyCombo {
+    /// The key on its own
+    pub fn key(key: Key) -> Self {
+        Self { key, command: false, shift: false, alt: false }
+    }
+
+    /// ⌘ + key
+    pub fn cmd(key: Key) -> Self {
+        Self { command: true, ..Self::key(key) }
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub fn alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Whether a key event with `modifiers` triggers this combo
+    pub fn matches(&self, key: Key, modifiers: Modifiers) -> bool {
+        key == self.key
+            && modifiers.alt == self.alt
+            && modifiers.shift == self.shift
+            && modifiers.command == self.command
+    }
+
+    /// Menu-style label: "⇧⌘S", "⌥X", "Space"
+    pub fn label(&self) -> String {
+        let mut s = String::new();
+        if self.alt { s.push('⌥'); }
+        if self.shift { s.push('⇧'); }
+        if self.command { s.push('⌘'); }
+        s.push_str(self.key.symbol_or_name());
+        s
+    }
+}
+
+/// One declared binding
+#[derive(Debug, Clone)]
+pub struct Binding<A> {
+    pub action: A,
+    /// Human-readable name, lowercase ("save as")
+    pub name: String,
+    pub combo: KeyCombo,
+}
+
+/// An app's shortcut table
+pub struct Shortcuts<A> {
+    app: String,
+    bindings: Vec<Binding<A>>,
+    /// Stop handling (e.g. while a text field has focus)
+    pub enabled: bool,
+}
+
+impl<A: Copy> Shortcuts<A> {
+    /// `app` is the binary name, used for the published file
+    pub fn new(app: &str) -> Self {
+        Self { app: app.to_string(), bindings: Vec::new(), enabled: true }
+    }
+
+    pub fn add(mut self, action: A, name: &str, combo: KeyCombo) -> Self {
+        self.bindings.push(Binding { action, name: name.to_string(), combo });
+        self
+    }
+
+    pub fn bindings(&self) -> &[Binding<A>] {
+        &self.bindings
+    }
+
+    /// Label for the first binding of `action`, for menu hints
+    pub fn label_for(&self, action: A) -> Option<String>
+    where
+        A: PartialEq,
+    {
+        self.bindings.iter().find(|b| b.action == action).map(|b| b.combo.label())
+    }
+
+    /// Pairs of binding names that share a key combo
+    pub fn conflicts(&self) -> Vec<(String, String, KeyCombo)> {
+        let mut out = Vec::new();
+        for (i, a) in self.bindings.iter().enumerate() {
+            for b in &self.bindings[i + 1..] {
+                if a.combo == b.combo {
+                    out.push((a.name.clone(), b.name.clone(), a.combo));
+                }
+            }
+        }
+        out
+    }
+
+    /// Consume key presses matching a binding and return their actions,
+    /// in the order they were pressed.
+    pub fn handle(&self, ctx: &Context) -> Vec<A> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let mut fired = Vec::new();
+        ctx.input_mut(|i| {
+            i.events.retain(|event| {
+                let Event::Key { key, pressed: true, modifiers, .. } = event else {
+                    return true;
+                };
+                match self.bindings.iter().find(|b| b.combo.matches(*key, *modifiers)) {
+                    Some(b) => {
+                        fired.push(b.action);
+                        false
+                    }
+                    None => true,
+                }
+            });
+        });
+        fired
+    }
+
+    /// Write this app's bindings for keyMacros and warn about conflicts.
+    /// Call once at startup; `unpublish` on exit.
+    pub fn publish(&self) {
+        for (a, b, combo) in self.conflicts() {
+            crate::log::warn!("shortcut {} is bound to both \"{}\" and \"{}\"", combo.label(), a, b);
+        }
+        let table = PublishedShortcuts {
+            app: self.app.clone(),
+            pid: std::process::id(),
+            shortcuts: self.bindings.iter()
+                .map(|b| PublishedShortcut { name: b.name.clone(), combo: b.combo })
+                .collect(),
+        };
+        if let Ok(json) = serde_json::to_string_pretty(&table) {
+            let _ = std::fs::write(published_path(&self.app, table.pid), json);
+        }
+    }
+
+    pub fn unpublish(&self) {
+        let _ = std::fs::remove_file(published_path(&self.app, std::process::id()));
+    }
+}
+
+/// A binding as seen by other processes
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishedShortcut {
+    pub name: String,
+    pub combo: KeyCombo,
+}
+
+/// One running app's shortcut table
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishedShortcuts {
+    pub app: String,
+    pub pid: u32,
+    pub shortcuts: Vec<PublishedShortcut>,
+}
+
+/// Directory for published shortcut tables
+fn shortcuts_dir() -> PathBuf {
+    let dir = directories::ProjectDirs::from("", "", "slowos")
+        .map(|p| p.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/slowos"))
+        .join("shortcuts");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+fn published_path(app: &str, pid: u32) -> PathBuf {
+    shortcuts_dir().join(format!("{}_{}.json", app, pid))
+}
+
+impl PerProcess for PublishedShortcuts {
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
+/// Read the tables of all running apps, by app (used by keyMacros)
+pub fn read_all_published() -> Vec<PublishedShortcuts> {
+    let mut results = read_per_process::<PublishedShortcuts>(&shortcuts_dir());
+    results.sort_by(|a, b| a.app.cmp(&b.app));
+    results
+}
+
+/// Store keys by name ("S", "Space") rather than egui's enum index
+mod key_name {
+    use egui::Key;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(key: &Key, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(key.name())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Key, D::Error> {
+        let name = String::deserialize(d)?;
+        Key::from_name(&name).ok_or_else(|| serde::de::Error::custom(format!("unknown key {}", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflicts_and_labels() {
+        let s = Shortcuts::new("test")
+            .add(1, "save", KeyCombo::cmd(Key::S))
+            .add(2, "save as", KeyCombo::cmd(Key::S).shift())
+            .add(3, "sync", KeyCombo::cmd(Key::S));
+        let conflicts = s.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].0, "save");
+        assert_eq!(conflicts[0].1, "sync");
+        assert_eq!(s.label_for(2).as_deref(), Some("⇧⌘S"));
+        assert!(!KeyCombo::cmd(Key::S).matches(Key::S, Modifiers::COMMAND | Modifiers::SHIFT));
+    }
+}
@@ -0,0 +1,475 @@
+//! System sounds
+//!
+//! A handful of short earcons, synthesized on the fly in the user's chosen
+//! sound theme (or read from one installed with slowStore) and played at
+//! the system volume from Settings:
+//!
+//! ```ignore
+//! slowcore::sound::alert();           // something needs attention
+//! slowcore::sound::play(Earcon::Trash);
+//! ```
+//!
+//! All apps share one audio output per process. Apps that play their own
+//! audio should use [`output`] instead of opening an `OutputStream`:
+//!
+//! ```ignore
+//! if let Some(handle) = slowcore::sound::output() {
+//!     let sink = rodio::Sink::try_new(handle)?;
+//! }
+//! ```
+//!
+//! Apps with their own playback also register a [`Player`], which lists
+//! them in the Settings mixer while they make sound; multiply sink volumes
+//! by `Player::volume()` so master mute and the per-app slider apply.
+//! Mixer state lives in ~/.config/slowos/audio/ (same file-based IPC as
+//! minimize) and is picked up within a second.
+
+use crate::process::{read_per_process, PerProcess};
+use crate::theme::system_setting;
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const SAMPLE_RATE: u32 = 44_100;
+
+/// Earcons play at this fraction of the system volume
+const EARCON_LEVEL: f32 = 0.5;
+
+/// Length of a metronome tick
+const TICK_MS: u32 = 25;
+
+/// How long a read of the volume settings is reused
+const VOLUME_CACHE: Duration = Duration::from_secs(1);
+
+/// The system sounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Earcon {
+    /// General-purpose "no" / attention beep
+    Beep,
+    /// Tiny tick for toggles and key feedback
+    Click,
+    /// Two-tone alert for dialogs that need an answer
+    Alert,
+    /// Falling tones when files are thrown away
+    Trash,
+}
+
+impl Earcon {
+    /// The earcon's file in an installed sound theme
+    fn file_name(self) -> &'static str {
+        match self {
+            Earcon::Beep => "beep.wav",
+            Earcon::Click => "click.wav",
+            Earcon::Alert => "alert.wav",
+            Earcon::Trash => "trash.wav",
+        }
+    }
+}
+
+/// How the earcons sound, chosen in Settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SoundTheme {
+    /// Square-wave tones, like an old compact Mac
+    #[default]
+    Classic,
+    /// Quieter sine tones
+    Soft,
+}
+
+impl SoundTheme {
+    pub const ALL: [SoundTheme; 2] = [SoundTheme::Classic, SoundTheme::Soft];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SoundTheme::Classic => "classic",
+            SoundTheme::Soft => "soft",
+        }
+    }
+}
+
+/// The shared audio output, opened on first use.
+///
+/// `OutputStream` has to stay on the thread that made it, so it lives on a
+/// parked background thread for the life of the process. Returns `None`
+/// when there is no audio device.
+pub fn output() -> Option<&'static OutputStreamHandle> {
+    static OUTPUT: OnceLock<Option<OutputStreamHandle>> = OnceLock::new();
+    OUTPUT
+        .get_or_init(|| {
+            let (tx, rx) = mpsc::channel();
+            std::thread::Builder::new()
+                .name("slowcore-audio".into())
+                .spawn(move || match OutputStream::try_default() {
+                    Ok((_stream, handle)) => {
+                        let _ = tx.send(Some(handle));
+                        loop {
+                            std::thread::park();
+                        }
+                    }
+                    Err(_) => {
+                        let _ = tx.send(None);
+                    }
+                })
+                .ok()?;
+            rx.recv().ok().flatten()
+        })
+        .as_ref()
+}
+
+/// Volume settings as last read from disk
+#[derive(Clone)]
+struct Levels {
+    volume: f32,
+    system_sounds: bool,
+    mixer: Mixer,
+}
+
+/// Current volume settings, re-read at most once per VOLUME_CACHE
+fn levels() -> Levels {
+    static CACHE: Mutex<Option<(Instant, Levels)>> = Mutex::new(None);
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((read_at, levels)) = &*cache {
+        if read_at.elapsed() < VOLUME_CACHE {
+            return levels.clone();
+        }
+    }
+    let levels = Levels {
+        volume: system_setting("volume").and_then(|v| v.as_u64()).unwrap_or(80).min(100) as f32 / 100.0,
+        system_sounds: system_setting("sound_enabled").and_then(|v| v.as_bool()).unwrap_or(true),
+        mixer: Mixer::load(),
+    };
+    *cache = Some((Instant::now(), levels.clone()));
+    levels
+}
+
+/// Master volume from Settings, 0.0–1.0 (0.0 when muted)
+pub fn master_volume() -> f32 {
+    let levels = levels();
+    if levels.mixer.muted { 0.0 } else { levels.volume }
+}
+
+/// Volume for system sounds, 0.0–1.0 (0.0 when muted or turned off)
+pub fn system_volume() -> f32 {
+    if levels().system_sounds { master_volume() } else { 0.0 }
+}
+
+/// Master mute and per-app volumes, edited live from Settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Mixer {
+    #[serde(default)]
+    pub muted: bool,
+    /// Per-app volume in percent, by binary name; missing apps are at 100
+    #[serde(default)]
+    pub volumes: BTreeMap<String, u8>,
+}
+
+impl Mixer {
+    pub fn load() -> Self {
+        std::fs::read_to_string(audio_dir().join("mixer.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(audio_dir().join("mixer.json"), json);
+        }
+    }
+
+    /// Volume of one app in percent
+    pub fn app_volume(&self, app: &str) -> u8 {
+        self.volumes.get(app).copied().unwrap_or(100).min(100)
+    }
+}
+
+/// Directory for mixer state and registered players
+fn audio_dir() -> PathBuf {
+    let dir = directories::ProjectDirs::from("", "", "slowos")
+        .map(|p| p.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/slowos"))
+        .join("audio");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+fn player_path(app: &str, pid: u32) -> PathBuf {
+    audio_dir().join(format!("{}_{}.json", app, pid))
+}
+
+/// An app that plays its own audio, as seen by the mixer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub app: String,
+    pub pid: u32,
+    /// Currently making sound
+    pub playing: bool,
+}
+
+/// Registration of this process's own playback with the mixer.
+/// Unregisters when dropped.
+pub struct Player {
+    info: PlayerInfo,
+}
+
+impl Player {
+    /// `app` is the binary name, which the mixer's per-app volume is keyed by
+    pub fn register(app: &str) -> Self {
+        let player = Self {
+            info: PlayerInfo { app: app.to_string(), pid: std::process::id(), playing: false },
+        };
+        player.write();
+        player
+    }
+
+    /// Tell the mixer whether this app is making sound (cheap to call
+    /// every frame; only writes when it changes)
+    pub fn set_playing(&mut self, playing: bool) {
+        if self.info.playing != playing {
+            self.info.playing = playing;
+            self.write();
+        }
+    }
+
+    /// Multiplier for this app's sinks: master volume × the app's slider
+    pub fn volume(&self) -> f32 {
+        let levels = levels();
+        if levels.mixer.muted {
+            return 0.0;
+        }
+        levels.volume * levels.mixer.app_volume(&self.info.app) as f32 / 100.0
+    }
+
+    fn write(&self) {
+        if let Ok(json) = serde_json::to_string(&self.info) {
+            let _ = std::fs::write(player_path(&self.info.app, self.info.pid), json);
+        }
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(player_path(&self.info.app, self.info.pid));
+    }
+}
+
+impl PerProcess for PlayerInfo {
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
+/// Apps registered with the mixer, by app (used by Settings). mixer.json,
+/// in the same folder, isn't a player and is passed over.
+pub fn read_players() -> Vec<PlayerInfo> {
+    let mut results = read_per_process::<PlayerInfo>(&audio_dir());
+    results.sort_by(|a, b| a.app.cmp(&b.app).then(a.pid.cmp(&b.pid)));
+    results
+}
+
+/// Sound theme from Settings
+pub fn preferred_theme() -> SoundTheme {
+    system_setting("sound_theme")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Where sound themes installed from slowStore live: a folder each,
+/// holding beep.wav, click.wav, alert.wav and trash.wav
+pub fn sound_packs_dir() -> PathBuf {
+    crate::storage::config_dir("slowos").join("sounds")
+}
+
+/// Names of the installed sound themes, sorted
+pub fn installed_packs() -> Vec<String> {
+    let mut packs: Vec<String> = std::fs::read_dir(sound_packs_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    packs.sort();
+    packs
+}
+
+/// Installed sound theme chosen in Settings, played instead of the
+/// built-in tones
+pub fn preferred_pack() -> Option<String> {
+    system_setting("sound_pack").and_then(|v| v.as_str().map(str::to_string)).filter(|p| !p.is_empty())
+}
+
+/// Play an earcon in the user's theme. Returns immediately.
+pub fn play(earcon: Earcon) {
+    if let Some(pack) = preferred_pack() {
+        if play_pack(earcon, &pack) {
+            return;
+        }
+    }
+    play_themed(earcon, preferred_theme());
+}
+
+/// Play an earcon from an installed sound theme. Returns false, having
+/// played nothing, when the theme has no sound for it.
+pub fn play_pack(earcon: Earcon, pack: &str) -> bool {
+    let Ok(file) = std::fs::File::open(sound_packs_dir().join(pack).join(earcon.file_name())) else {
+        return false;
+    };
+    let Ok(source) = rodio::Decoder::new(std::io::BufReader::new(file)) else { return false };
+    let volume = system_volume();
+    if volume <= 0.0 {
+        return true;
+    }
+    let Some(handle) = output() else { return true };
+    let Ok(sink) = Sink::try_new(handle) else { return true };
+    sink.set_volume(volume * EARCON_LEVEL);
+    sink.append(source);
+    sink.detach();
+    true
+}
+
+/// Play an earcon in a specific theme (for previews in Settings)
+pub fn play_themed(earcon: Earcon, theme: SoundTheme) {
+    let volume = system_volume();
+    if volume <= 0.0 {
+        return;
+    }
+    let Some(handle) = output() else { return };
+    let Ok(sink) = Sink::try_new(handle) else { return };
+    sink.set_volume(volume * EARCON_LEVEL);
+    let wave = match theme {
+        SoundTheme::Classic => Wave::Square,
+        SoundTheme::Soft => Wave::Sine,
+    };
+    for &(freq, ms) in tones(earcon) {
+        sink.append(Tone::new(wave, freq, ms));
+    }
+    sink.detach();
+}
+
+pub fn beep() {
+    play(Earcon::Beep);
+}
+
+pub fn click() {
+    play(Earcon::Click);
+}
+
+pub fn alert() {
+    play(Earcon::Alert);
+}
+
+pub fn trash() {
+    play(Earcon::Trash);
+}
+
+/// Play a soft sine tone with a slow swell, for an app's own cues (such
+/// as slowBreath's phase changes). `volume` is usually `Player::volume()`.
+pub fn chime(freq: f32, ms: u32, volume: f32) {
+    if volume <= 0.0 {
+        return;
+    }
+    let Some(handle) = output() else { return };
+    let Ok(sink) = Sink::try_new(handle) else { return };
+    sink.set_volume(volume * EARCON_LEVEL);
+    let mut tone = Tone::new(Wave::Sine, freq, ms);
+    tone.fade = tone.len / 4;
+    sink.append(tone);
+    sink.detach();
+}
+
+/// Queue one metronome tick on an app's own `sink`: a short tone at
+/// `freq` in the user's sound theme (0 for a silent beat), then silence
+/// to make it `beat` long. Ticks queued back to back keep exact time
+/// however late the app gets to queue the next.
+pub fn queue_tick(sink: &Sink, freq: f32, beat: Duration) {
+    let wave = match preferred_theme() {
+        SoundTheme::Classic => Wave::Square,
+        SoundTheme::Soft => Wave::Sine,
+    };
+    let len = (beat.as_secs_f32() * SAMPLE_RATE as f32) as u32;
+    let tick = Tone::new(wave, freq, TICK_MS);
+    let rest = len.saturating_sub(tick.len);
+    sink.append(tick);
+    sink.append(Tone { len: rest, ..Tone::new(wave, 0.0, 0) });
+}
+
+/// (frequency Hz, milliseconds) steps; frequency 0 is a rest
+fn tones(earcon: Earcon) -> &'static [(f32, u32)] {
+    match earcon {
+        Earcon::Beep => &[(880.0, 140)],
+        Earcon::Click => &[(1800.0, 10)],
+        Earcon::Alert => &[(660.0, 90), (0.0, 40), (880.0, 140)],
+        Earcon::Trash => &[(700.0, 45), (520.0, 45), (380.0, 45), (260.0, 90)],
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Wave {
+    Sine,
+    Square,
+}
+
+/// One mono tone with a fade in and out so it doesn't click
+struct Tone {
+    wave: Wave,
+    freq: f32,
+    len: u32,
+    /// Fade length in samples
+    fade: u32,
+    n: u32,
+}
+
+impl Tone {
+    /// Default fade length in samples (~3 ms)
+    const FADE: u32 = SAMPLE_RATE / 330;
+
+    fn new(wave: Wave, freq: f32, ms: u32) -> Self {
+        Self { wave, freq, len: SAMPLE_RATE * ms / 1000, fade: Self::FADE, n: 0 }
+    }
+}
+
+impl Iterator for Tone {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.n >= self.len {
+            return None;
+        }
+        let n = self.n;
+        self.n += 1;
+        if self.freq <= 0.0 {
+            return Some(0.0);
+        }
+        let phase = (n as f32 * self.freq / SAMPLE_RATE as f32).fract();
+        let sample = match self.wave {
+            Wave::Sine => (phase * std::f32::consts::TAU).sin(),
+            // Squares sound much louder than sines at the same amplitude
+            Wave::Square => if phase < 0.5 { 0.4 } else { -0.4 },
+        };
+        let envelope = (n.min(self.len - n) as f32 / self.fade.max(1) as f32).min(1.0);
+        Some(sample * envelope)
+    }
+}
+
+impl Source for Tone {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(self.len as f32 / SAMPLE_RATE as f32))
+    }
+}
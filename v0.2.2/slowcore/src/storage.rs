@@ -2,6 +2,8 @@
 //! 
 //! Handles file dialogs, recent files, and preferences.
 
+use crate::widgets::ListSelection;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -14,15 +16,37 @@ pub enum StorageError {
     Json(#[from] serde_json::Error),
     #[error("File not found: {0}")]
     NotFound(PathBuf),
+    #[error("Made by a newer version (format {0})")]
+    NewerFormat(u64),
 }
 
 pub type Result<T> = std::result::Result<T, StorageError>;
 
-/// Recent files tracking
+/// Most files kept in the list shared by every app
+const MAX_RECENT_EVERYWHERE: usize = 30;
+
+/// Files recently opened in one app
+///
+/// `RecentFiles::for_app("slowwrite")` keeps the app's own list in its
+/// config dir. [`record`](Self::record) also adds the file to the list
+/// shared by every app, which slowDesktop's search shows before anything
+/// is typed ([`recent_everywhere`]). Files deleted since drop out when
+/// the lists are read.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct RecentFiles {
     pub files: Vec<PathBuf>,
     pub max_entries: usize,
+    /// Binary name of the app, for lists from `for_app`
+    #[serde(skip)]
+    app: String,
+}
+
+/// A file recently opened, and the app it was opened in
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    /// Binary name (e.g. "slowwrite")
+    pub app: String,
 }
 
 impl RecentFiles {
@@ -30,8 +54,21 @@ impl RecentFiles {
         Self {
             files: Vec::new(),
             max_entries,
+            app: String::new(),
         }
     }
+
+    /// The list of `app` (its binary name), without files since deleted
+    pub fn for_app(app: &str) -> Self {
+        let mut recent = Self::load(&Self::app_path(app)).unwrap_or_else(|_| Self::new(10));
+        recent.app = app.to_string();
+        recent.prune();
+        recent
+    }
+
+    fn app_path(app: &str) -> PathBuf {
+        config_dir(app).join("recent.json")
+    }
     
     pub fn add(&mut self, path: PathBuf) {
         // Remove if already exists
@@ -43,6 +80,60 @@ impl RecentFiles {
         // Trim to max
         self.files.truncate(self.max_entries);
     }
+
+    /// Note that `path` was just opened or saved: first in this app's
+    /// list and in the shared one, both saved
+    pub fn record(&mut self, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.add(path.clone());
+        if !self.app.is_empty() {
+            let _ = self.save(&Self::app_path(&self.app));
+            record_everywhere(&self.app, path);
+        }
+    }
+
+    /// Drop files that no longer exist. Returns whether any were dropped.
+    pub fn prune(&mut self) -> bool {
+        let before = self.files.len();
+        self.files.retain(|p| p.exists());
+        self.files.len() != before
+    }
+
+    /// Empty this app's list (the shared one keeps its files)
+    pub fn clear(&mut self) {
+        self.files.clear();
+        if !self.app.is_empty() {
+            let _ = self.save(&Self::app_path(&self.app));
+        }
+    }
+
+    /// The "open recent" submenu, for an app's file menu. Returns the file
+    /// picked.
+    pub fn menu(&mut self, ui: &mut egui::Ui) -> Option<PathBuf> {
+        let mut picked = None;
+        ui.menu_button("open recent", |ui| {
+            if self.files.is_empty() {
+                ui.label("no recent files");
+                return;
+            }
+            for path in &self.files {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                if ui.button(name).on_hover_text(path.display().to_string()).clicked() {
+                    picked = Some(path.clone());
+                    ui.close_menu();
+                }
+            }
+            ui.separator();
+            if ui.button("clear menu").clicked() {
+                self.clear();
+                ui.close_menu();
+            }
+        });
+        picked
+    }
     
     pub fn load(config_path: &Path) -> Result<Self> {
         let contents = std::fs::read_to_string(config_path)?;
@@ -59,12 +150,50 @@ impl RecentFiles {
     }
 }
 
+fn everywhere_path() -> PathBuf {
+    config_dir("slowos").join("recent.json")
+}
+
+fn read_everywhere() -> Vec<RecentFile> {
+    std::fs::read_to_string(everywhere_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn record_everywhere(app: &str, path: PathBuf) {
+    let mut files = read_everywhere();
+    files.retain(|f| f.path != path && f.path.exists());
+    files.insert(0, RecentFile { path, app: app.to_string() });
+    files.truncate(MAX_RECENT_EVERYWHERE);
+    let target = everywhere_path();
+    if let Some(parent) = target.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    // Several apps write this; a rename keeps readers from half a file
+    if let Ok(json) = serde_json::to_string_pretty(&files) {
+        let tmp = target.with_extension("tmp");
+        if std::fs::write(&tmp, json).is_ok() {
+            let _ = std::fs::rename(&tmp, &target);
+        }
+    }
+}
+
+/// Files recently opened in any app, newest first, without files since
+/// deleted
+pub fn recent_everywhere() -> Vec<RecentFile> {
+    let mut files = read_everywhere();
+    files.retain(|f| f.path.exists());
+    files
+}
+
 /// Simple file browser state
 #[derive(Debug, Clone)]
 pub struct FileBrowser {
     pub current_dir: PathBuf,
     pub entries: Vec<FileEntry>,
-    pub selected_index: Option<usize>,
+    /// Selected rows of `entries`, driven by a `VirtualList`
+    pub selection: ListSelection,
     pub filter_extensions: Vec<String>,
 }
 
@@ -80,7 +209,7 @@ impl FileBrowser {
         let mut browser = Self {
             current_dir: start_dir,
             entries: Vec::new(),
-            selected_index: None,
+            selection: ListSelection::default(),
             filter_extensions: Vec::new(),
         };
         browser.refresh();
@@ -95,7 +224,7 @@ impl FileBrowser {
     
     pub fn refresh(&mut self) {
         self.entries.clear();
-        self.selected_index = None;
+        self.selection.clear();
         
         // Add parent directory entry
         if let Some(parent) = self.current_dir.parent() {
@@ -164,11 +293,19 @@ impl FileBrowser {
     }
     
     pub fn selected_entry(&self) -> Option<&FileEntry> {
-        self.selected_index.and_then(|i| self.entries.get(i))
+        self.selection.primary().and_then(|i| self.entries.get(i))
+    }
+
+    /// All selected entries, in list order
+    pub fn selected_entries(&self) -> impl Iterator<Item = &FileEntry> {
+        self.selection.rows.iter().filter_map(|&i| self.entries.get(i))
     }
     
     pub fn select_by_name(&mut self, name: &str) {
-        self.selected_index = self.entries.iter().position(|e| e.name == name);
+        match self.entries.iter().position(|e| e.name == name) {
+            Some(i) => self.selection.select_only(i),
+            None => self.selection.clear(),
+        }
     }
 
     /// Get the directory to save to - if a directory is selected, use that; otherwise use current_dir
@@ -182,6 +319,61 @@ impl FileBrowser {
     }
 }
 
+/// Check a file or folder name typed by the user.
+/// Returns a short lowercase reason when the name can't be used.
+pub fn validate_filename(name: &str) -> std::result::Result<(), &'static str> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("name is empty");
+    }
+    if trimmed != name {
+        return Err("name starts or ends with a space");
+    }
+    if name == "." || name == ".." {
+        return Err("name is reserved");
+    }
+    if name.starts_with('.') {
+        return Err("names starting with . are hidden");
+    }
+    if name.contains(['/', '\\', '\0']) {
+        return Err("name contains / or \\");
+    }
+    if name.chars().any(|c| c.is_control()) {
+        return Err("name contains control characters");
+    }
+    if name.len() > 255 {
+        return Err("name is too long");
+    }
+    Ok(())
+}
+
+/// Turn a name for something an app keeps (a deck, a font, a template)
+/// into one that's safe as a file name: no path separators, no leading
+/// dot. Empty if nothing usable is left.
+pub fn clean_name(name: &str) -> String {
+    name.trim().trim_start_matches('.').replace(['/', '\\'], "-")
+}
+
+/// Recently used folders, shared by every app's file dialog
+pub fn recent_locations() -> RecentFiles {
+    RecentFiles::load(&recent_locations_path()).unwrap_or_else(|_| RecentFiles::new(8))
+}
+
+/// Move `dir` to the front of the recent folders list
+pub fn add_recent_location(dir: &Path) {
+    let mut recent = recent_locations();
+    if recent.max_entries == 0 {
+        recent.max_entries = 8;
+    }
+    recent.files.retain(|p| p.is_dir());
+    recent.add(dir.to_path_buf());
+    let _ = recent.save(&recent_locations_path());
+}
+
+fn recent_locations_path() -> PathBuf {
+    config_dir("slowos").join("recent_locations.json")
+}
+
 /// Get the config directory for Slow Computer apps
 pub fn config_dir(app_name: &str) -> PathBuf {
     directories::ProjectDirs::from("co", "slowcomputer", app_name)
@@ -196,6 +388,180 @@ pub fn documents_dir() -> PathBuf {
         .unwrap_or_else(|| PathBuf::from("."))
 }
 
+/// Get the downloads directory, creating ~/Downloads if needed
+pub fn downloads_dir() -> PathBuf {
+    if let Some(p) = directories::UserDirs::new().and_then(|d| d.download_dir().map(|p| p.to_path_buf())) {
+        return p;
+    }
+    if let Some(dirs) = directories::BaseDirs::new() {
+        let p = dirs.home_dir().join("Downloads");
+        let _ = std::fs::create_dir_all(&p);
+        if p.is_dir() { return p; }
+    }
+    documents_dir()
+}
+
+/// A path in `dir` for a file called `name` that doesn't exist yet:
+/// "notes.txt", then "notes 2.txt", "notes 3.txt", …
+pub fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+        _ => (name, String::new()),
+    };
+    let mut path = dir.join(name);
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{} {}{}", stem, n, ext));
+        n += 1;
+    }
+    path
+}
+
+/// Write `bytes` to `path` so that a power cut leaves either the old file
+/// or the new one, never half of each: the bytes go to a hidden file
+/// beside it, are synced to disk, and only then renamed over it.
+pub fn save_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    write_atomic(path, bytes, None)
+}
+
+/// [`save_atomic`] for secrets: the file can only be read by its owner
+/// (mode 0600), from the moment it's created.
+pub fn save_atomic_private(path: &Path, bytes: &[u8]) -> Result<()> {
+    write_atomic(path, bytes, Some(0o600))
+}
+
+fn write_atomic(path: &Path, bytes: &[u8], mode: Option<u32>) -> Result<()> {
+    use std::io::Write;
+    // Replace the file a link points to, not the link
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| StorageError::NotFound(path.clone()))?
+        .to_string_lossy()
+        .to_string();
+    let tmp = dir.join(format!(".{}.tmp", name));
+    // A mode only applies to a new file, so clear out any left behind
+    let _ = std::fs::remove_file(&tmp);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if let Some(mode) = mode {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(mode);
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+    let written = options.open(&tmp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| std::fs::rename(&tmp, &path)) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+    // The rename itself is only safe once the folder is synced
+    if let Ok(dir) = std::fs::File::open(&dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Backups kept by [`save_versioned`] unless an app asks otherwise
+pub const KEEP_VERSIONS: usize = 3;
+
+/// The `n`th backup of `path`: "notes.txt.~1" is the newest
+pub fn version_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".~{}", n));
+    path.with_file_name(name)
+}
+
+/// Like [`save_atomic`], first keeping the file as it was as backup 1,
+/// and the backups before it up to `keep`
+pub fn save_versioned(path: &Path, bytes: &[u8], keep: usize) -> Result<()> {
+    if keep > 0 && path.is_file() {
+        let _ = std::fs::remove_file(version_path(path, keep));
+        for n in (1..keep).rev() {
+            let _ = std::fs::rename(version_path(path, n), version_path(path, n + 1));
+        }
+        // A link keeps the file in place until the rename; drives that
+        // can't link (FAT) get a copy
+        let newest = version_path(path, 1);
+        if std::fs::hard_link(path, &newest).is_err() {
+            std::fs::copy(path, &newest)?;
+        }
+    }
+    save_atomic(path, bytes)
+}
+
+/// Backups of `path` from [`save_versioned`], newest first
+pub fn versions(path: &Path) -> Vec<PathBuf> {
+    (1..)
+        .map(|n| version_path(path, n))
+        .take_while(|p| p.is_file())
+        .collect()
+}
+
+/// Put backup `n` of `path` back in its place. The file as it was becomes
+/// backup 1, so restoring can itself be undone.
+pub fn restore_version(path: &Path, n: usize) -> Result<()> {
+    let backup = version_path(path, n);
+    if !backup.is_file() {
+        return Err(StorageError::NotFound(backup));
+    }
+    let bytes = std::fs::read(&backup)?;
+    save_versioned(path, &bytes, versions(path).len().max(1))
+}
+
+/// One step from a document format version to the next, made on the
+/// file's JSON before it is read
+pub type Migration = fn(&mut serde_json::Value);
+
+/// Key holding the format version in a document's JSON
+const FORMAT_VERSION_KEY: &str = "format_version";
+
+/// A JSON document format that changes over time (a slowMidi project, a
+/// slowDesign layout)
+///
+/// Files are written with a `format_version`. Reading an older file runs
+/// it through every migration since, so the struct only ever has to read
+/// the newest format. Files written before there were versions are
+/// version 0.
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// `MIGRATIONS[n]` turns version n into n + 1; the version written is
+    /// their count. Append to change the format, never edit.
+    const MIGRATIONS: &'static [Migration];
+}
+
+/// `doc` as JSON, marked with its format version
+pub fn to_versioned_json<T: Versioned>(doc: &T) -> Result<String> {
+    let mut value = serde_json::to_value(doc)?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert(FORMAT_VERSION_KEY.into(), T::MIGRATIONS.len().into());
+    }
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+/// Read a document of any format version up to the current one
+pub fn from_versioned_json<T: Versioned>(json: &str) -> Result<T> {
+    let mut value: serde_json::Value = serde_json::from_str(json)?;
+    let version = value.get(FORMAT_VERSION_KEY).and_then(|v| v.as_u64()).unwrap_or(0);
+    if version > T::MIGRATIONS.len() as u64 {
+        return Err(StorageError::NewerFormat(version));
+    }
+    for migrate in &T::MIGRATIONS[version as usize..] {
+        migrate(&mut value);
+    }
+    if let Some(object) = value.as_object_mut() {
+        object.remove(FORMAT_VERSION_KEY);
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
 /// Get the pictures directory
 pub fn pictures_dir() -> PathBuf {
     if let Some(dirs) = directories::UserDirs::new() {
@@ -210,3 +576,127 @@ pub fn pictures_dir() -> PathBuf {
     }
     documents_dir()
 }
+
+/// Get the music directory, creating ~/Music if needed
+pub fn music_dir() -> PathBuf {
+    if let Some(p) = directories::UserDirs::new().and_then(|d| d.audio_dir().map(|p| p.to_path_buf())) {
+        return p;
+    }
+    if let Some(dirs) = directories::BaseDirs::new() {
+        let p = dirs.home_dir().join("Music");
+        let _ = std::fs::create_dir_all(&p);
+        if p.is_dir() { return p; }
+    }
+    documents_dir()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_filename() {
+        assert!(validate_filename("notes.txt").is_ok());
+        assert!(validate_filename("my song.mid").is_ok());
+        assert!(validate_filename("").is_err());
+        assert!(validate_filename("  ").is_err());
+        assert!(validate_filename(" padded").is_err());
+        assert!(validate_filename("..").is_err());
+        assert!(validate_filename(".hidden").is_err());
+        assert!(validate_filename("a/b").is_err());
+        assert!(validate_filename(&"x".repeat(256)).is_err());
+
+        assert_eq!(clean_name(" ../my letter "), "-my letter");
+        assert_eq!(clean_name("a\\b/c"), "a-b-c");
+        assert!(clean_name(" .. ").is_empty());
+    }
+
+    #[test]
+    fn test_recent_files_prune() {
+        let dir = std::env::temp_dir().join(format!("slowcore-recent-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let kept = dir.join("kept.txt");
+        std::fs::write(&kept, "").unwrap();
+        let mut recent = RecentFiles::new(2);
+        recent.add(dir.join("gone.txt"));
+        recent.add(kept.clone());
+        recent.add(kept.clone());
+        assert_eq!(recent.files.len(), 2);
+        assert!(recent.prune());
+        assert_eq!(recent.files, vec![kept]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_versioned_saves() {
+        let dir = std::env::temp_dir().join(format!("slowcore-versions-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.txt");
+        for text in ["one", "two", "three", "four"] {
+            save_versioned(&path, text.as_bytes(), 2).unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "four");
+        assert_eq!(versions(&path), vec![dir.join("notes.txt.~1"), dir.join("notes.txt.~2")]);
+        assert_eq!(std::fs::read_to_string(version_path(&path, 2)).unwrap(), "two");
+
+        restore_version(&path, 2).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "two");
+        assert_eq!(std::fs::read_to_string(version_path(&path, 1)).unwrap(), "four");
+        assert!(restore_version(&path, 5).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_atomic_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("slowcore-private-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("accounts.json");
+        std::fs::write(&path, "old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        save_atomic_private(&path, b"new").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Song {
+        title: String,
+        bpm: u32,
+    }
+
+    impl Versioned for Song {
+        const MIGRATIONS: &'static [Migration] = &[
+            // 0 → 1: "name" became "title"
+            |v| {
+                if let Some(name) = v.as_object_mut().and_then(|o| o.remove("name")) {
+                    v["title"] = name;
+                }
+            },
+            // 1 → 2: tempo became a whole number of beats per minute
+            |v| v["bpm"] = (v["tempo"].as_f64().unwrap_or(120.0).round() as u64).into(),
+        ];
+    }
+
+    #[test]
+    fn test_versioned_json() {
+        let song = Song { title: "rain".into(), bpm: 90 };
+        let fixtures = [
+            r#"{"name": "rain", "tempo": 89.6}"#,
+            r#"{"format_version": 1, "title": "rain", "tempo": 90.2}"#,
+            r#"{"format_version": 2, "title": "rain", "bpm": 90}"#,
+        ];
+        for json in fixtures {
+            assert_eq!(from_versioned_json::<Song>(json).unwrap(), song);
+        }
+        let json = to_versioned_json(&song).unwrap();
+        assert!(json.contains(r#""format_version": 2"#));
+        assert_eq!(from_versioned_json::<Song>(&json).unwrap(), song);
+        assert!(matches!(
+            from_versioned_json::<Song>(r#"{"format_version": 3, "title": "rain", "bpm": 90}"#),
+            Err(StorageError::NewerFormat(3))
+        ));
+    }
+}
@@ -0,0 +1,160 @@
+//! Sync settings shared by slowSync and the settings app
+//!
+//! slowSync runs in the background and keeps Documents and slowNotes' notes
+//! in step with a copy on another machine, reached with rsync over ssh.
+//! Settings writes the configuration; slowSync writes back how the last run
+//! went and shows it in the menu bar.
+//!
+//! Everything lives in ~/.config/slowos/sync/.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A folder slowSync can keep in step
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Folder {
+    Documents,
+    Notes,
+}
+
+impl Folder {
+    pub const ALL: [Folder; 2] = [Folder::Documents, Folder::Notes];
+
+    /// Lowercase name, also the folder's name on the other machine
+    pub fn name(self) -> &'static str {
+        match self {
+            Folder::Documents => "documents",
+            Folder::Notes => "notes",
+        }
+    }
+
+    /// Where the folder lives on this computer
+    pub fn local_path(self) -> PathBuf {
+        match self {
+            Folder::Documents => crate::storage::documents_dir(),
+            Folder::Notes => crate::storage::config_dir("slownote"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SyncConfig {
+    /// Where the other copy lives, as "user@host:path"; empty turns sync off
+    #[serde(default)]
+    pub remote: String,
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+    /// Minutes between runs
+    #[serde(default = "default_interval")]
+    pub interval_mins: u32,
+}
+
+fn default_interval() -> u32 {
+    15
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self { remote: String::new(), folders: Folder::ALL.to_vec(), interval_mins: default_interval() }
+    }
+}
+
+impl SyncConfig {
+    pub fn load() -> Self {
+        std::fs::read_to_string(sync_dir().join("config.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            write_atomic("config.json", &json);
+        }
+    }
+
+    pub fn is_enabled(&self, folder: Folder) -> bool {
+        self.folders.contains(&folder)
+    }
+
+    pub fn set_enabled(&mut self, folder: Folder, enabled: bool) {
+        self.folders.retain(|&f| f != folder);
+        if enabled {
+            self.folders.push(folder);
+        }
+    }
+
+    /// Whether there is anything to do
+    pub fn is_active(&self) -> bool {
+        !self.remote.trim().is_empty() && !self.folders.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    #[default]
+    Off,
+    Idle,
+    Syncing,
+    Error,
+}
+
+/// How slowSync is doing, written after every change of state
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SyncStatus {
+    pub state: SyncState,
+    /// Last run that finished without errors, in unix seconds
+    #[serde(default)]
+    pub last_sync: Option<u64>,
+    /// What went wrong, or what was done
+    #[serde(default)]
+    pub message: String,
+    /// Conflict copies made in the last run
+    #[serde(default)]
+    pub conflicts: usize,
+}
+
+impl SyncStatus {
+    pub fn load() -> Self {
+        std::fs::read_to_string(sync_dir().join("status.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            write_atomic("status.json", &json);
+        }
+    }
+}
+
+/// ~/.config/slowos/sync, where slowSync keeps its config, status and indexes
+pub fn sync_dir() -> PathBuf {
+    let dir = directories::ProjectDirs::from("", "", "slowos")
+        .map(|p| p.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/slowos"))
+        .join("sync");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+fn write_atomic(name: &str, contents: &str) {
+    let path = sync_dir().join(name);
+    let tmp = path.with_extension("tmp");
+    if std::fs::write(&tmp, contents).is_ok() {
+        let _ = std::fs::rename(&tmp, &path);
+    }
+}
+
+/// Ask slowSync to run now instead of waiting for the interval
+pub fn sync_now() {
+    let _ = std::fs::write(sync_dir().join("now"), b"");
+}
+
+/// Used by slowSync: whether a run was asked for, clearing the request
+pub fn take_sync_request() -> bool {
+    std::fs::remove_file(sync_dir().join("now")).is_ok()
+}
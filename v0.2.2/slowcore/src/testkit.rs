@@ -0,0 +1,424 @@
+//! Testing UI without a screen
+//!
+//! [`Harness`] runs a UI off-screen for as many frames as a test needs.
+//! It feeds in clicks, keys and typing, and draws the result into a
+//! [`Screenshot`] on the CPU. That makes regression tests possible for
+//! widgets, dialogs and whole app views on a machine with no display or
+//! GPU.
+//!
+//! ```ignore
+//! let mut harness = Harness::new(400.0, 300.0);
+//! let mut answer = None;
+//! let mut ui = |ctx: &Context| answer = answer.or(alert(ctx, "delete", "delete it?", &["cancel", "delete"]));
+//! harness.step(&mut ui);
+//! harness.key(Modifiers::NONE, Key::Enter);
+//! harness.settle(&mut ui);
+//! assert_eq!(answer, Some(1));
+//! harness.screenshot().assert_golden("tests/golden/delete_alert.png");
+//! ```
+//!
+//! Only eframe can make the `eframe::Frame` that `App::update` takes, so
+//! the harness runs a closure given the `egui::Context` instead. An app
+//! view is tested by calling whatever its `update` calls with that
+//! context.
+//!
+//! A golden image is recorded the first time its test runs. After that
+//! the screenshot must match it, give or take anti-aliasing. On a mismatch
+//! the new rendering is saved beside it as `<name>.new.png`. Run the tests
+//! with `SLOWOS_UPDATE_GOLDEN=1` to accept the new renderings.
+
+use crate::theme::SlowTheme;
+use egui::epaint::{ClippedPrimitive, ImageData, Mesh, Primitive};
+use egui::{
+    Color32, Context, Event, Key, Modifiers, PointerButton, Pos2, RawInput, Rect, TextureId, Vec2, ViewportId,
+};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Time between frames, as far as the UI can tell
+const FRAME_TIME: f64 = 1.0 / 60.0;
+
+/// Most frames [`Harness::settle`] runs before giving up
+const MAX_SETTLE_FRAMES: usize = 200;
+
+/// Set to record golden images again instead of comparing with them
+const UPDATE_VAR: &str = "SLOWOS_UPDATE_GOLDEN";
+
+/// A channel may be off by this much and the pixel still match
+const CHANNEL_TOLERANCE: u8 = 24;
+
+/// Share of pixels that may differ from a golden image: text edges
+const MAX_DIFFERING: f64 = 0.002;
+
+/// Input for one frame
+#[derive(Default)]
+struct Batch {
+    modifiers: Modifiers,
+    events: Vec<Event>,
+}
+
+/// A texture egui uploaded, kept to draw from
+struct Texture {
+    size: [usize; 2],
+    pixels: Vec<Color32>,
+}
+
+/// Runs a UI off-screen. See the [module docs](self).
+pub struct Harness {
+    ctx: Context,
+    size: Vec2,
+    time: f64,
+    /// Input waiting for the coming frames, one batch per frame
+    queued: VecDeque<Batch>,
+    textures: HashMap<TextureId, Texture>,
+    /// The last frame, ready to draw
+    primitives: Vec<ClippedPrimitive>,
+    pixels_per_point: f32,
+    /// Whether the last frame asked to be followed straight away
+    wants_repaint: bool,
+}
+
+impl Harness {
+    /// A `width` × `height` point screen with the slow computer theme
+    pub fn new(width: f32, height: f32) -> Self {
+        let ctx = Context::default();
+        SlowTheme::default().apply(&ctx);
+        Self {
+            ctx,
+            size: Vec2::new(width, height),
+            time: 0.0,
+            queued: VecDeque::new(),
+            textures: HashMap::new(),
+            primitives: Vec::new(),
+            pixels_per_point: 1.0,
+            wants_repaint: true,
+        }
+    }
+
+    pub fn ctx(&self) -> &Context {
+        &self.ctx
+    }
+
+    /// Run one frame of `ui` with the next input queued
+    pub fn step(&mut self, mut ui: impl FnMut(&Context)) {
+        let batch = self.queued.pop_front().unwrap_or_default();
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, self.size)),
+            time: Some(self.time),
+            predicted_dt: FRAME_TIME as f32,
+            modifiers: batch.modifiers,
+            events: batch.events,
+            focused: true,
+            ..Default::default()
+        };
+        self.time += FRAME_TIME;
+
+        let output = self.ctx.run(input, |ctx| ui(ctx));
+        for (id, delta) in output.textures_delta.set {
+            let (size, pixels) = match &delta.image {
+                ImageData::Color(image) => (image.size, image.pixels.clone()),
+                ImageData::Font(image) => (image.size, image.srgba_pixels(None).collect()),
+            };
+            match (delta.pos, self.textures.get_mut(&id)) {
+                (Some([x0, y0]), Some(texture)) => {
+                    for y in 0..size[1] {
+                        for x in 0..size[0] {
+                            let i = (y0 + y) * texture.size[0] + x0 + x;
+                            if let Some(pixel) = texture.pixels.get_mut(i) {
+                                *pixel = pixels[y * size[0] + x];
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    self.textures.insert(id, Texture { size, pixels });
+                }
+            }
+        }
+        self.pixels_per_point = output.pixels_per_point;
+        self.primitives = self.ctx.tessellate(output.shapes, output.pixels_per_point);
+        for id in output.textures_delta.free {
+            self.textures.remove(&id);
+        }
+        self.wants_repaint = output
+            .viewport_output
+            .get(&ViewportId::ROOT)
+            .is_some_and(|v| v.repaint_delay == Duration::ZERO);
+    }
+
+    /// Run `frames` frames of `ui`
+    pub fn run(&mut self, frames: usize, mut ui: impl FnMut(&Context)) {
+        for _ in 0..frames {
+            self.step(&mut ui);
+        }
+    }
+
+    /// Run `ui` until the queued input is used up and nothing is moving
+    /// (windows fading in, menus opening)
+    pub fn settle(&mut self, mut ui: impl FnMut(&Context)) {
+        for _ in 0..MAX_SETTLE_FRAMES {
+            if self.queued.is_empty() && !self.wants_repaint {
+                return;
+            }
+            self.step(&mut ui);
+        }
+    }
+
+    /// Give `event` to the UI in a frame of its own, after anything
+    /// already queued
+    pub fn event(&mut self, event: Event) {
+        self.queued.push_back(Batch { modifiers: Modifiers::NONE, events: vec![event] });
+    }
+
+    /// Move the pointer to `pos`
+    pub fn move_to(&mut self, pos: Pos2) {
+        self.event(Event::PointerMoved(pos));
+    }
+
+    /// Click at `pos`: move there, then press and release over three frames
+    pub fn click(&mut self, pos: Pos2) {
+        self.move_to(pos);
+        for pressed in [true, false] {
+            self.event(Event::PointerButton {
+                pos,
+                button: PointerButton::Primary,
+                pressed,
+                modifiers: Modifiers::NONE,
+            });
+        }
+    }
+
+    /// Press and release `key` with `modifiers` held
+    pub fn key(&mut self, modifiers: Modifiers, key: Key) {
+        let events = [true, false]
+            .into_iter()
+            .map(|pressed| Event::Key { key, physical_key: None, pressed, repeat: false, modifiers })
+            .collect();
+        self.queued.push_back(Batch { modifiers, events });
+    }
+
+    /// Type `text` into whatever has the keyboard
+    pub fn type_text(&mut self, text: &str) {
+        self.event(Event::Text(text.to_string()));
+    }
+
+    /// Draw the last frame
+    pub fn screenshot(&self) -> Screenshot {
+        let width = (self.size.x * self.pixels_per_point).round() as usize;
+        let height = (self.size.y * self.pixels_per_point).round() as usize;
+        let background = self.ctx.style().visuals.panel_fill;
+        let mut shot = Screenshot { width, height, pixels: vec![background; width * height] };
+        for primitive in &self.primitives {
+            if let Primitive::Mesh(mesh) = &primitive.primitive {
+                let clip = Rect::from_min_max(
+                    (primitive.clip_rect.min.to_vec2() * self.pixels_per_point).to_pos2(),
+                    (primitive.clip_rect.max.to_vec2() * self.pixels_per_point).to_pos2(),
+                );
+                if let Some(texture) = self.textures.get(&mesh.texture_id) {
+                    shot.draw_mesh(mesh, texture, clip, self.pixels_per_point);
+                }
+            }
+        }
+        shot
+    }
+}
+
+/// Which side of the line `a`→`b` `p` is on, times twice the area
+fn edge(a: Pos2, b: Pos2, p: Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Whether pixels exactly on the edge `a`→`b` belong to its triangle.
+/// Triangles sharing an edge run it in opposite directions, so exactly
+/// one of them gets them, and nothing is drawn twice.
+fn owns_edge(a: Pos2, b: Pos2) -> bool {
+    b.y > a.y || (b.y == a.y && b.x < a.x)
+}
+
+/// A rendered frame, in premultiplied sRGB
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screenshot {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color32>,
+}
+
+impl Screenshot {
+    pub fn pixel(&self, x: usize, y: usize) -> Color32 {
+        self.pixels[y * self.width + x]
+    }
+
+    fn draw_mesh(&mut self, mesh: &Mesh, texture: &Texture, clip: Rect, pixels_per_point: f32) {
+        let clip = clip.intersect(Rect::from_min_size(Pos2::ZERO, Vec2::new(self.width as f32, self.height as f32)));
+        for triangle in mesh.indices.chunks_exact(3) {
+            let mut v = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+            let mut p = v.map(|v| (v.pos.to_vec2() * pixels_per_point).to_pos2());
+            let mut area = edge(p[0], p[1], p[2]);
+            if area == 0.0 {
+                continue;
+            }
+            if area < 0.0 {
+                v.swap(1, 2);
+                p.swap(1, 2);
+                area = -area;
+            }
+            let bounds = Rect::from_points(&p).intersect(clip);
+            if bounds.is_negative() {
+                continue;
+            }
+            for y in bounds.min.y.floor() as usize..bounds.max.y.ceil() as usize {
+                for x in bounds.min.x.floor() as usize..bounds.max.x.ceil() as usize {
+                    let center = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                    if !clip.contains(center) {
+                        continue;
+                    }
+                    let mut w = [0.0; 3];
+                    let mut inside = true;
+                    for i in 0..3 {
+                        let (a, b) = (p[(i + 1) % 3], p[(i + 2) % 3]);
+                        let e = edge(a, b, center);
+                        inside &= e > 0.0 || (e == 0.0 && owns_edge(a, b));
+                        w[i] = e / area;
+                    }
+                    if !inside {
+                        continue;
+                    }
+                    let uv = v[0].uv.to_vec2() * w[0] + v[1].uv.to_vec2() * w[1] + v[2].uv.to_vec2() * w[2];
+                    let tx = ((uv.x * texture.size[0] as f32) as usize).min(texture.size[0].saturating_sub(1));
+                    let ty = ((uv.y * texture.size[1] as f32) as usize).min(texture.size[1].saturating_sub(1));
+                    let texel = texture.pixels.get(ty * texture.size[0] + tx).copied().unwrap_or(Color32::WHITE);
+                    let mut src = [0.0f32; 4];
+                    for (c, channel) in src.iter_mut().enumerate() {
+                        let tint: f32 = v.iter().zip(w).map(|(v, w)| v.color[c] as f32 * w).sum();
+                        *channel = tint * texel[c] as f32 / 255.0;
+                    }
+                    let dst = &mut self.pixels[y * self.width + x];
+                    let keep = 1.0 - src[3] / 255.0;
+                    let blend = |s: f32, d: u8| (s + d as f32 * keep).round().clamp(0.0, 255.0) as u8;
+                    *dst = Color32::from_rgba_premultiplied(
+                        blend(src[0], dst.r()),
+                        blend(src[1], dst.g()),
+                        blend(src[2], dst.b()),
+                        blend(src[3], dst.a()),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Encode as an RGBA PNG
+    pub fn to_png(&self) -> io::Result<Vec<u8>> {
+        let data: Vec<u8> = self.pixels.iter().flat_map(|p| p.to_array()).collect();
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer.write_image_data(&data).map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)?;
+        Ok(out)
+    }
+
+    /// Decode an RGBA PNG written by [`to_png`](Self::to_png)
+    pub fn from_png(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = png::Decoder::new(bytes).read_info().map_err(io::Error::other)?;
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).map_err(io::Error::other)?;
+        if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not an 8-bit RGBA image"));
+        }
+        let pixels = data[..info.buffer_size()]
+            .chunks_exact(4)
+            .map(|p| Color32::from_rgba_premultiplied(p[0], p[1], p[2], p[3]))
+            .collect();
+        Ok(Self { width: info.width as usize, height: info.height as usize, pixels })
+    }
+
+    /// How many pixels are noticeably different from `other`'s, or `None`
+    /// if the sizes differ
+    pub fn differing_pixels(&self, other: &Screenshot) -> Option<usize> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return None;
+        }
+        let differs = |a: &Color32, b: &Color32| (0..4).any(|c| a[c].abs_diff(b[c]) > CHANNEL_TOLERANCE);
+        Some(self.pixels.iter().zip(&other.pixels).filter(|(a, b)| differs(a, b)).count())
+    }
+
+    /// Check this against the golden image at `path`, recording it if
+    /// there is none yet. Panics on a mismatch, saving this rendering as
+    /// `<name>.new.png` to look at.
+    pub fn assert_golden(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let write = |path: &Path| {
+            if let Some(dir) = path.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            let png = self.to_png().expect("encode screenshot");
+            std::fs::write(path, png).unwrap_or_else(|e| panic!("could not write {}: {}", path.display(), e));
+        };
+        if std::env::var_os(UPDATE_VAR).is_some() || !path.exists() {
+            write(path);
+            return;
+        }
+        let golden = std::fs::read(path)
+            .and_then(|bytes| Screenshot::from_png(&bytes))
+            .unwrap_or_else(|e| panic!("could not read golden image {}: {}", path.display(), e));
+        let allowed = (self.pixels.len() as f64 * MAX_DIFFERING) as usize;
+        let problem = match self.differing_pixels(&golden) {
+            Some(n) if n <= allowed => return,
+            Some(n) => format!("{} pixels differ", n),
+            None => format!("size is {}×{}, not {}×{}", self.width, self.height, golden.width, golden.height),
+        };
+        let new = path.with_extension("new.png");
+        write(&new);
+        panic!(
+            "screenshot doesn't match {}: {}. The new rendering is in {}; run with {}=1 to accept it.",
+            path.display(),
+            problem,
+            new.display(),
+            UPDATE_VAR
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::SlowColors;
+
+    #[test]
+    fn test_click_and_screenshot() {
+        let mut harness = Harness::new(200.0, 100.0);
+        let mut clicks = 0;
+        let button = std::cell::Cell::new(Rect::NOTHING);
+        let mut ui = |ctx: &Context| {
+            egui::CentralPanel::default().frame(egui::Frame::none().fill(SlowColors::WHITE)).show(ctx, |ui| {
+                ui.painter().rect_filled(Rect::from_min_size(Pos2::new(150.0, 50.0), Vec2::splat(20.0)), 0.0, SlowColors::BLACK);
+                let r = ui.button("press me");
+                if r.clicked() {
+                    clicks += 1;
+                }
+                button.set(r.rect);
+            });
+        };
+        harness.step(&mut ui);
+        harness.click(button.get().center());
+        harness.settle(&mut ui);
+        assert_eq!(clicks, 1);
+
+        let button = button.get();
+        let shot = harness.screenshot();
+        assert_eq!((shot.width, shot.height), (200, 100));
+        assert_eq!(shot.pixel(160, 60), SlowColors::BLACK);
+        assert_eq!(shot.pixel(190, 10), SlowColors::WHITE);
+        // The label's text was drawn from the font atlas
+        let label = (button.min.y as usize..button.max.y as usize)
+            .flat_map(|y| (button.min.x as usize..button.max.x as usize).map(move |x| (x, y)));
+        assert!(label.into_iter().any(|(x, y)| shot.pixel(x, y) == SlowColors::BLACK));
+
+        let decoded = Screenshot::from_png(&shot.to_png().unwrap()).unwrap();
+        assert_eq!(decoded.differing_pixels(&shot), Some(0));
+    }
+}
@@ -1,9 +1,21 @@
-//! Double-click-drag word selection for egui TextEdit widgets.
+//! Double-click-drag word selection and input methods for egui TextEdit
+//! widgets.
 //!
 //! Call `WordDragState::update()` after `TextEdit::show()` to get
 //! double-click-hold-drag to extend selection by whole words.
+//!
+//! `InputMethod` types what the keyboard can't: accented letters through
+//! dead keys, and Chinese (pinyin) or Japanese (romaji) through a candidate
+//! popup. Call `before()` ahead of `TextEdit::show()` and `after()` with
+//! its output; chosen text goes to the TextEdit as ordinary typing.
+//!
+//! The built-in pinyin table covers the commonest characters only. More
+//! go in /usr/share/slowos/input/pinyin.txt and kanji for romaji in
+//! romaji.txt beside it, one reading per line: `nihao 你好 妮好`.
 
-use egui::Ui;
+use egui::{Context, Event, Key, Ui};
+use std::collections::HashMap;
+use std::sync::OnceLock;
 
 /// Tracks word-level drag-selection state for a TextEdit widget.
 #[derive(Debug, Clone, Default)]
@@ -97,3 +109,468 @@ pub fn word_boundaries(text: &str, char_idx: usize) -> (usize, usize) {
 fn is_word_char(c: char) -> bool {
     c.is_alphanumeric() || c == '_' || c == '\''
 }
+
+/// Extra dictionaries for pinyin and romaji
+const DICTIONARY_DIR: &str = "/usr/share/slowos/input";
+
+/// Candidates shown at once (chosen with 1–9)
+const MAX_CANDIDATES: usize = 9;
+
+/// How typed keys become text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputMode {
+    /// Keys type what they say
+    #[default]
+    Direct,
+    /// ' ` ^ " ~ then a letter make an accented letter (é è ê ë ñ)
+    Accents,
+    /// Chinese from pinyin
+    Pinyin,
+    /// Japanese kana (and kanji from the dictionary) from romaji
+    Romaji,
+}
+
+impl InputMode {
+    pub const ALL: [InputMode; 4] = [InputMode::Direct, InputMode::Accents, InputMode::Pinyin, InputMode::Romaji];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputMode::Direct => "english",
+            InputMode::Accents => "accented letters",
+            InputMode::Pinyin => "pinyin (chinese)",
+            InputMode::Romaji => "romaji (japanese)",
+        }
+    }
+
+    /// The next mode, for a "switch input" shortcut
+    pub fn next(&self) -> InputMode {
+        let i = Self::ALL.iter().position(|m| m == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// Dead keys and pinyin/romaji composition for one TextEdit
+#[derive(Debug, Clone, Default)]
+pub struct InputMethod {
+    pub mode: InputMode,
+    /// The TextEdit this works for, from the last `after()`
+    editor: Option<egui::Id>,
+    /// Dead key waiting for its letter
+    dead_key: Option<char>,
+    /// Letters typed so far (pinyin or romaji)
+    composing: String,
+    /// (text, letters of `composing` it stands for)
+    candidates: Vec<(String, usize)>,
+    selected: usize,
+}
+
+impl InputMethod {
+    pub fn new() -> Self { Self::default() }
+
+    /// Something is being composed (keys go to the popup, not the text)
+    pub fn is_composing(&self) -> bool {
+        self.dead_key.is_some() || !self.composing.is_empty()
+    }
+
+    pub fn set_mode(&mut self, mode: InputMode) {
+        self.mode = mode;
+        self.dead_key = None;
+        self.composing.clear();
+        self.candidates.clear();
+    }
+
+    /// Call before `TextEdit::show()`: turns this frame's keys into
+    /// composed text while the TextEdit has focus
+    pub fn before(&mut self, ctx: &Context) {
+        let Some(id) = self.editor else { return };
+        if self.mode == InputMode::Direct || !ctx.memory(|m| m.has_focus(id)) {
+            return;
+        }
+        ctx.input_mut(|i| {
+            if i.modifiers.command {
+                return;
+            }
+            let events = std::mem::take(&mut i.events);
+            let mut out = Vec::with_capacity(events.len());
+            for event in events {
+                self.handle(event, &mut out);
+            }
+            i.events = out;
+        });
+    }
+
+    fn handle(&mut self, event: Event, out: &mut Vec<Event>) {
+        match (&event, self.mode) {
+            (Event::Text(text), InputMode::Accents) => {
+                let mut typed = String::new();
+                for c in text.chars() {
+                    match self.dead_key.take() {
+                        Some(dead) => match compose_accent(dead, c) {
+                            Some(accented) => typed.push(accented),
+                            None if c == ' ' => typed.push(dead),
+                            None => {
+                                typed.push(dead);
+                                typed.push(c);
+                            }
+                        },
+                        None if is_dead_key(c) => self.dead_key = Some(c),
+                        None => typed.push(c),
+                    }
+                }
+                if !typed.is_empty() {
+                    out.push(Event::Text(typed));
+                }
+            }
+            (Event::Key { key: Key::Backspace, pressed: true, .. }, InputMode::Accents) if self.dead_key.is_some() => {
+                self.dead_key = None;
+            }
+            (Event::Text(text), InputMode::Pinyin | InputMode::Romaji) => {
+                let mut typed = String::new();
+                for c in text.chars() {
+                    if c.is_ascii_alphabetic() || (c == '\'' && !self.composing.is_empty()) {
+                        self.composing.push(c.to_ascii_lowercase());
+                        self.update_candidates();
+                    } else if self.composing.is_empty() {
+                        typed.push(c);
+                    } else if c == ' ' {
+                        typed.push_str(&self.commit(self.selected));
+                    } else if let Some(n) = c.to_digit(10).filter(|n| (1..=MAX_CANDIDATES as u32).contains(n)) {
+                        typed.push_str(&self.commit(n as usize - 1));
+                    } else {
+                        // Punctuation commits what's there and is typed too
+                        typed.push_str(&self.commit(self.selected));
+                        typed.push(c);
+                    }
+                }
+                if !typed.is_empty() {
+                    out.push(Event::Text(typed));
+                }
+            }
+            (Event::Key { key, pressed, .. }, InputMode::Pinyin | InputMode::Romaji) if !self.composing.is_empty() => {
+                if !pressed {
+                    return;
+                }
+                match key {
+                    Key::Backspace => {
+                        self.composing.pop();
+                        self.update_candidates();
+                    }
+                    Key::Escape => {
+                        self.composing.clear();
+                        self.candidates.clear();
+                    }
+                    Key::Enter => {
+                        // The letters as typed (kana for romaji)
+                        let raw = match self.mode {
+                            InputMode::Romaji => romaji_to_kana(&self.composing, true),
+                            _ => self.composing.clone(),
+                        };
+                        self.composing.clear();
+                        self.candidates.clear();
+                        out.push(Event::Text(raw));
+                    }
+                    Key::ArrowRight | Key::ArrowDown | Key::Tab => {
+                        self.selected = (self.selected + 1) % self.candidates.len().max(1);
+                    }
+                    Key::ArrowLeft | Key::ArrowUp => {
+                        self.selected = self.selected.checked_sub(1).unwrap_or(self.candidates.len().saturating_sub(1));
+                    }
+                    // Space and digits arrive as text too
+                    _ => {}
+                }
+            }
+            _ => out.push(event),
+        }
+    }
+
+    /// Use candidate `index`; letters it doesn't stand for stay composing
+    fn commit(&mut self, index: usize) -> String {
+        let Some((text, consumed)) = self.candidates.get(index).cloned() else {
+            return std::mem::take(&mut self.composing);
+        };
+        self.composing.drain(..consumed.min(self.composing.len()));
+        self.update_candidates();
+        text
+    }
+
+    fn update_candidates(&mut self) {
+        self.selected = 0;
+        self.candidates = match self.mode {
+            InputMode::Pinyin => pinyin_candidates(&self.composing),
+            InputMode::Romaji => romaji_candidates(&self.composing),
+            _ => Vec::new(),
+        };
+    }
+
+    /// Call after `TextEdit::show()` with its output: shows what's being
+    /// composed and the candidates beside the cursor
+    pub fn after(&mut self, ui: &Ui, output: &egui::text_edit::TextEditOutput) {
+        self.editor = Some(output.response.id);
+        if !self.is_composing() {
+            return;
+        }
+        if !output.response.has_focus() {
+            self.set_mode(self.mode);
+            return;
+        }
+        let cursor = output
+            .cursor_range
+            .map(|r| output.galley.pos_from_cursor(&r.primary).translate(output.galley_pos.to_vec2()))
+            .unwrap_or(output.response.rect);
+        egui::Area::new(output.response.id.with("input_method"))
+            .order(egui::Order::Tooltip)
+            .fixed_pos(cursor.left_bottom() + egui::vec2(0.0, 2.0))
+            .show(ui.ctx(), |ui| {
+                egui::Frame::none()
+                    .fill(crate::theme::SlowColors::WHITE)
+                    .stroke(egui::Stroke::new(1.0, crate::theme::SlowColors::BLACK))
+                    .inner_margin(egui::Margin::symmetric(4.0, 2.0))
+                    .show(ui, |ui| {
+                        if let Some(dead) = self.dead_key {
+                            ui.label(egui::RichText::new(dead.to_string()).underline());
+                            return;
+                        }
+                        ui.label(egui::RichText::new(&self.composing).underline());
+                        ui.horizontal(|ui| {
+                            for (i, (text, _)) in self.candidates.iter().enumerate() {
+                                let label = format!("{} {}", i + 1, text);
+                                ui.add(egui::SelectableLabel::new(i == self.selected, label));
+                            }
+                        });
+                    });
+            });
+    }
+}
+
+fn is_dead_key(c: char) -> bool {
+    matches!(c, '\'' | '`' | '^' | '"' | '~')
+}
+
+/// The letter a dead key and `c` make, if any
+fn compose_accent(dead: char, c: char) -> Option<char> {
+    let lower = c.to_ascii_lowercase();
+    let accented = match (dead, lower) {
+        ('\'', 'a') => 'á', ('\'', 'e') => 'é', ('\'', 'i') => 'í', ('\'', 'o') => 'ó', ('\'', 'u') => 'ú',
+        ('\'', 'y') => 'ý', ('\'', 'c') => 'ć', ('\'', 'n') => 'ń', ('\'', 's') => 'ś', ('\'', 'z') => 'ź',
+        ('`', 'a') => 'à', ('`', 'e') => 'è', ('`', 'i') => 'ì', ('`', 'o') => 'ò', ('`', 'u') => 'ù',
+        ('^', 'a') => 'â', ('^', 'e') => 'ê', ('^', 'i') => 'î', ('^', 'o') => 'ô', ('^', 'u') => 'û',
+        ('"', 'a') => 'ä', ('"', 'e') => 'ë', ('"', 'i') => 'ï', ('"', 'o') => 'ö', ('"', 'u') => 'ü',
+        ('"', 'y') => 'ÿ', ('"', 's') => 'ß',
+        ('~', 'a') => 'ã', ('~', 'n') => 'ñ', ('~', 'o') => 'õ',
+        _ => return None,
+    };
+    if c.is_ascii_uppercase() && accented != 'ß' {
+        accented.to_uppercase().next()
+    } else {
+        Some(accented)
+    }
+}
+
+/// Readings from the built-in table plus the dictionary file, by reading
+fn dictionary(name: &str, builtin: &[(&str, &str)]) -> HashMap<String, Vec<String>> {
+    let mut words: HashMap<String, Vec<String>> = HashMap::new();
+    let extra = std::fs::read_to_string(std::path::Path::new(DICTIONARY_DIR).join(name)).unwrap_or_default();
+    let lines = builtin.iter().map(|&(r, w)| (r.to_string(), w.to_string())).chain(
+        extra.lines().filter_map(|l| l.split_once(char::is_whitespace).map(|(r, w)| (r.to_string(), w.to_string()))),
+    );
+    for (reading, found) in lines {
+        let entry = words.entry(reading).or_default();
+        for word in found.split_whitespace() {
+            if !entry.iter().any(|w| w == word) {
+                entry.push(word.to_string());
+            }
+        }
+    }
+    words
+}
+
+fn pinyin_words() -> &'static HashMap<String, Vec<String>> {
+    static WORDS: OnceLock<HashMap<String, Vec<String>>> = OnceLock::new();
+    WORDS.get_or_init(|| dictionary("pinyin.txt", PINYIN))
+}
+
+fn kanji_words() -> &'static HashMap<String, Vec<String>> {
+    static WORDS: OnceLock<HashMap<String, Vec<String>>> = OnceLock::new();
+    WORDS.get_or_init(|| dictionary("romaji.txt", &[]))
+}
+
+/// Characters for the whole of `pinyin` if it's a known word, otherwise for
+/// its longest known start; the letters themselves come last
+fn pinyin_candidates(pinyin: &str) -> Vec<(String, usize)> {
+    let words = pinyin_words();
+    let plain: String = pinyin.chars().filter(|&c| c != '\'').collect();
+    let mut candidates: Vec<(String, usize)> = Vec::new();
+    if let Some(found) = words.get(&plain) {
+        candidates.extend(found.iter().map(|w| (w.clone(), pinyin.len())));
+    }
+    // The longest start that's a word, up to a syllable separator
+    let head = pinyin.split('\'').next().unwrap_or(pinyin);
+    if let Some(len) = (1..=head.len()).rev().find(|&n| n < plain.len() && words.contains_key(&head[..n])) {
+        let consumed = if pinyin[len..].starts_with('\'') { len + 1 } else { len };
+        candidates.extend(words[&head[..len]].iter().map(|w| (w.clone(), consumed)));
+    }
+    candidates.truncate(MAX_CANDIDATES - 1);
+    candidates.push((plain, pinyin.len()));
+    candidates
+}
+
+/// Hiragana, katakana and any kanji with that reading
+fn romaji_candidates(romaji: &str) -> Vec<(String, usize)> {
+    let hiragana = romaji_to_kana(romaji, false);
+    let mut candidates = Vec::new();
+    if let Some(found) = kanji_words().get(&hiragana) {
+        candidates.extend(found.iter().map(|w| (w.clone(), romaji.len())));
+    }
+    candidates.truncate(MAX_CANDIDATES - 2);
+    let done = romaji_to_kana(romaji, true);
+    candidates.insert(0, (done.clone(), romaji.len()));
+    candidates.push((katakana(&done), romaji.len()));
+    candidates
+}
+
+/// Romaji to hiragana. `finished` turns a trailing "n" into ん; otherwise
+/// it may still become な and so on.
+fn romaji_to_kana(romaji: &str, finished: bool) -> String {
+    let bytes = romaji.as_bytes();
+    let mut kana = String::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let next = bytes.get(i + 1).copied();
+        // Doubled consonant: small tsu
+        if next == Some(c) && c.is_ascii_alphabetic() && !b"aeioun".contains(&c) {
+            kana.push('っ');
+            i += 1;
+            continue;
+        }
+        // n before a consonant (or doubled, or at the end when finished)
+        if c == b'n' {
+            match next {
+                // "nn" is ん, unless the second n starts な etc. ("konnichiha")
+                Some(b'n') => {
+                    let vowel = bytes.get(i + 2).is_some_and(|n| b"aeiouy".contains(n));
+                    kana.push('ん');
+                    i += if vowel { 1 } else { 2 };
+                    continue;
+                }
+                Some(b'\'') => {
+                    kana.push('ん');
+                    i += 2;
+                    continue;
+                }
+                Some(n) if !b"aeiouy".contains(&n) => {
+                    kana.push('ん');
+                    i += 1;
+                    continue;
+                }
+                None if finished => {
+                    kana.push('ん');
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        let found = (1..=3.min(bytes.len() - i))
+            .rev()
+            .find_map(|n| KANA.iter().find(|(r, _)| r.as_bytes() == &bytes[i..i + n]).map(|&(_, k)| (n, k)));
+        match found {
+            Some((n, k)) => {
+                kana.push_str(k);
+                i += n;
+            }
+            None => {
+                kana.push(c as char);
+                i += 1;
+            }
+        }
+    }
+    kana
+}
+
+/// Hiragana to katakana
+fn katakana(hiragana: &str) -> String {
+    hiragana
+        .chars()
+        .map(|c| match c {
+            'ぁ'..='ゖ' => char::from_u32(c as u32 + 0x60).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// Romaji to hiragana, longest spellings matched first
+const KANA: &[(&str, &str)] = &[
+    ("a", "あ"), ("i", "い"), ("u", "う"), ("e", "え"), ("o", "お"),
+    ("ka", "か"), ("ki", "き"), ("ku", "く"), ("ke", "け"), ("ko", "こ"),
+    ("sa", "さ"), ("shi", "し"), ("si", "し"), ("su", "す"), ("se", "せ"), ("so", "そ"),
+    ("ta", "た"), ("chi", "ち"), ("ti", "ち"), ("tsu", "つ"), ("tu", "つ"), ("te", "て"), ("to", "と"),
+    ("na", "な"), ("ni", "に"), ("nu", "ぬ"), ("ne", "ね"), ("no", "の"),
+    ("ha", "は"), ("hi", "ひ"), ("fu", "ふ"), ("hu", "ふ"), ("he", "へ"), ("ho", "ほ"),
+    ("ma", "ま"), ("mi", "み"), ("mu", "む"), ("me", "め"), ("mo", "も"),
+    ("ya", "や"), ("yu", "ゆ"), ("yo", "よ"),
+    ("ra", "ら"), ("ri", "り"), ("ru", "る"), ("re", "れ"), ("ro", "ろ"),
+    ("wa", "わ"), ("wo", "を"),
+    ("ga", "が"), ("gi", "ぎ"), ("gu", "ぐ"), ("ge", "げ"), ("go", "ご"),
+    ("za", "ざ"), ("ji", "じ"), ("zi", "じ"), ("zu", "ず"), ("ze", "ぜ"), ("zo", "ぞ"),
+    ("da", "だ"), ("di", "ぢ"), ("du", "づ"), ("de", "で"), ("do", "ど"),
+    ("ba", "ば"), ("bi", "び"), ("bu", "ぶ"), ("be", "べ"), ("bo", "ぼ"),
+    ("pa", "ぱ"), ("pi", "ぴ"), ("pu", "ぷ"), ("pe", "ぺ"), ("po", "ぽ"),
+    ("kya", "きゃ"), ("kyu", "きゅ"), ("kyo", "きょ"),
+    ("sha", "しゃ"), ("shu", "しゅ"), ("sho", "しょ"),
+    ("cha", "ちゃ"), ("chu", "ちゅ"), ("cho", "ちょ"),
+    ("nya", "にゃ"), ("nyu", "にゅ"), ("nyo", "にょ"),
+    ("hya", "ひゃ"), ("hyu", "ひゅ"), ("hyo", "ひょ"),
+    ("mya", "みゃ"), ("myu", "みゅ"), ("myo", "みょ"),
+    ("rya", "りゃ"), ("ryu", "りゅ"), ("ryo", "りょ"),
+    ("gya", "ぎゃ"), ("gyu", "ぎゅ"), ("gyo", "ぎょ"),
+    ("ja", "じゃ"), ("ju", "じゅ"), ("jo", "じょ"),
+    ("bya", "びゃ"), ("byu", "びゅ"), ("byo", "びょ"),
+    ("pya", "ぴゃ"), ("pyu", "ぴゅ"), ("pyo", "ぴょ"),
+    ("-", "ー"),
+];
+
+/// The commonest characters by pinyin, without tones
+const PINYIN: &[(&str, &str)] = &[
+    ("de", "的 得 地 德"), ("shi", "是 时 事 十 市 师 使 室"), ("yi", "一 以 已 意 义 亿"),
+    ("bu", "不 部 步 布"), ("le", "了 乐"), ("ren", "人 认 任"), ("wo", "我 握"),
+    ("zai", "在 再 载"), ("you", "有 又 由 友 右 油"), ("ta", "他 她 它 塔"),
+    ("zhe", "这 着 者"), ("zhong", "中 种 重 众"), ("da", "大 打 达"), ("lai", "来"),
+    ("shang", "上 商"), ("guo", "国 过 果"), ("ge", "个 各 歌 哥"), ("dao", "到 道 倒 刀"),
+    ("shuo", "说"), ("men", "们 门"), ("wei", "为 位 未 委 卫"), ("zi", "子 自 字 资"),
+    ("he", "和 合 何 河 喝"), ("ni", "你 尼 泥"), ("hao", "好 号"), ("xie", "谢 写 些 鞋"),
+    ("ma", "吗 妈 马 码"), ("me", "么"), ("na", "那 拿 哪"), ("ke", "可 课 科 克"),
+    ("yao", "要 药"), ("jiu", "就 九 酒 旧 久"), ("hui", "会 回"), ("kan", "看"),
+    ("xiang", "想 向 相 像 香"), ("zhi", "只 知 之 直 值"), ("qu", "去 取 区 趣"),
+    ("shui", "水 谁 睡"), ("chi", "吃 池 迟"), ("fan", "饭 反 范"), ("jia", "家 加 假 价"),
+    ("xue", "学 雪 血"), ("sheng", "生 声 省 胜"), ("tian", "天 田 甜"), ("nian", "年 念"),
+    ("ri", "日"), ("yue", "月 越"), ("hen", "很 恨"), ("xiao", "小 笑 校"), ("duo", "多"),
+    ("shao", "少"), ("ai", "爱"), ("mei", "没 美 每 妹"), ("gong", "工 公 共"),
+    ("zuo", "做 作 坐 左"), ("nan", "男 难 南"), ("nv", "女"), ("hai", "还 孩 海"),
+    ("dian", "电 点 店"), ("hua", "话 花 画 化"), ("ming", "明 名 命"), ("bai", "白 百"),
+    ("lao", "老"), ("xian", "先 现 线"), ("huan", "欢 换"), ("xi", "喜 西 系 习"),
+    ("qing", "请 情 清 轻"), ("wen", "问 文"), ("shu", "书 数 树"), ("ji", "几 机 记 级"),
+    ("nihao", "你好"), ("xiexie", "谢谢"), ("zhongguo", "中国"), ("pengyou", "朋友"),
+    ("xihuan", "喜欢"), ("women", "我们"), ("nimen", "你们"), ("tamen", "他们"),
+    ("shenme", "什么"), ("meiyou", "没有"), ("keyi", "可以"), ("xuesheng", "学生"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compose() {
+        assert_eq!(compose_accent('\'', 'e'), Some('é'));
+        assert_eq!(compose_accent('~', 'N'), Some('Ñ'));
+        assert_eq!(compose_accent('^', 'x'), None);
+        assert_eq!(romaji_to_kana("konnichiha", true), "こんにちは");
+        assert_eq!(romaji_to_kana("kitte", true), "きって");
+        assert_eq!(romaji_to_kana("hon", false), "ほn");
+        assert_eq!(katakana("こんにちは"), "コンニチハ");
+        let candidates = pinyin_candidates("nihao");
+        assert_eq!(candidates[0], ("你好".to_string(), 5));
+        // Unknown as a whole: the first syllable, leaving the rest
+        assert!(pinyin_candidates("nibu").contains(&("你".to_string(), 2)));
+    }
+}
@@ -0,0 +1,233 @@
+//! Texture memory — how much an app holds, and a budget for what it loads
+//!
+//! Every picture an app shows (thumbnails, icons, pages, photos) is an egui
+//! texture, and on the Pi they share the GPU's small slice of memory with
+//! the font atlas. [`usage`] counts what an app holds. [`TextureCache`]
+//! keeps loaded textures under a budget shared by every cache in the app,
+//! dropping the ones used longest ago first:
+//!
+//! ```ignore
+//! let mut pages = TextureCache::new();
+//! // each frame:
+//! if !pages.contains_key(&page) {
+//!     pages.insert(page, ctx.load_texture(name, image, TextureOptions::NEAREST));
+//! }
+//! if let Some(texture) = pages.get(&page) { painter.image(texture.id(), ...) }
+//! ```
+//!
+//! The performance overlay shows the total and warns when an app goes over
+//! [`budget`].
+
+use egui::{Context, TextureHandle, TextureId};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// Texture memory an app may use unless `SLOWOS_TEXTURE_BUDGET_MB` says otherwise
+const DEFAULT_BUDGET_MB: usize = 64;
+
+/// Bytes held by every `TextureCache` in this app
+static CACHED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Texture memory this app may use, in bytes
+pub fn budget() -> usize {
+    static BUDGET: OnceLock<usize> = OnceLock::new();
+    *BUDGET.get_or_init(|| {
+        let mb = std::env::var("SLOWOS_TEXTURE_BUDGET_MB")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_BUDGET_MB);
+        mb * 1024 * 1024
+    })
+}
+
+/// "12.5M" for a byte count
+pub fn format_bytes(bytes: usize) -> String {
+    format!("{:.1}M", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// The texture memory an app holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextureUsage {
+    /// Bytes in every texture, the font atlas included
+    pub total: usize,
+    /// Bytes in the font atlas
+    pub fonts: usize,
+    /// Bytes held by texture caches
+    pub cached: usize,
+    /// Number of textures
+    pub count: usize,
+}
+
+impl TextureUsage {
+    pub fn over_budget(&self) -> bool {
+        self.total > budget()
+    }
+}
+
+/// What `ctx` holds in textures now
+pub fn usage(ctx: &Context) -> TextureUsage {
+    let manager = ctx.tex_manager();
+    let manager = manager.read();
+    let mut usage = TextureUsage { cached: CACHED_BYTES.load(Ordering::Relaxed), ..Default::default() };
+    for (id, meta) in manager.allocated() {
+        usage.total += meta.bytes_used();
+        usage.count += 1;
+        // egui allocates the font atlas first
+        if *id == TextureId::default() {
+            usage.fonts += meta.bytes_used();
+        }
+    }
+    usage
+}
+
+/// The `n` biggest textures, by name, for working out what went over budget
+pub fn largest(ctx: &Context, n: usize) -> Vec<(String, usize)> {
+    let manager = ctx.tex_manager();
+    let manager = manager.read();
+    let mut textures: Vec<_> = manager.allocated().map(|(_, meta)| (meta.name.clone(), meta.bytes_used())).collect();
+    textures.sort_by_key(|(_, bytes)| std::cmp::Reverse(*bytes));
+    textures.truncate(n);
+    textures
+}
+
+/// Loaded textures kept under the app's budget, least recently used
+/// dropped first
+pub struct TextureCache<K> {
+    entries: HashMap<K, (TextureHandle, u64)>,
+    /// Counts uses, to tell which entry was used longest ago
+    tick: u64,
+    /// Bytes in this cache
+    bytes: usize,
+    budget: usize,
+}
+
+impl<K: Eq + Hash + Clone> Default for TextureCache<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash + Clone> TextureCache<K> {
+    pub fn new() -> Self {
+        Self::with_budget(budget())
+    }
+
+    /// A cache that evicts once all the app's caches hold `budget` bytes
+    pub fn with_budget(budget: usize) -> Self {
+        Self { entries: HashMap::new(), tick: 0, bytes: 0, budget }
+    }
+
+    pub fn contains_key<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.entries.contains_key(key)
+    }
+
+    /// The texture for `key`, counting as a use
+    pub fn get<Q: Eq + Hash + ?Sized>(&mut self, key: &Q) -> Option<&TextureHandle>
+    where
+        K: Borrow<Q>,
+    {
+        self.tick += 1;
+        let (texture, used) = self.entries.get_mut(key)?;
+        *used = self.tick;
+        Some(texture)
+    }
+
+    /// The texture for `key`, without counting as a use
+    pub fn peek<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> Option<&TextureHandle>
+    where
+        K: Borrow<Q>,
+    {
+        self.entries.get(key).map(|(texture, _)| texture)
+    }
+
+    /// Keep `texture`, dropping the least recently used others while the
+    /// app's caches are over budget
+    pub fn insert(&mut self, key: K, texture: TextureHandle) -> &TextureHandle {
+        self.remove(&key);
+        let bytes = texture.byte_size();
+        self.bytes += bytes;
+        CACHED_BYTES.fetch_add(bytes, Ordering::Relaxed);
+        self.tick += 1;
+        self.entries.insert(key.clone(), (texture, self.tick));
+        while CACHED_BYTES.load(Ordering::Relaxed) > self.budget {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(k, _)| **k != key)
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(k, _)| k.clone());
+            match oldest {
+                Some(oldest) => self.remove(&oldest),
+                None => break,
+            }
+        }
+        &self.entries[&key].0
+    }
+
+    pub fn remove<Q: Eq + Hash + ?Sized>(&mut self, key: &Q)
+    where
+        K: Borrow<Q>,
+    {
+        if let Some((texture, _)) = self.entries.remove(key) {
+            let bytes = texture.byte_size();
+            self.bytes -= bytes;
+            CACHED_BYTES.fetch_sub(bytes, Ordering::Relaxed);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        CACHED_BYTES.fetch_sub(self.bytes, Ordering::Relaxed);
+        self.bytes = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Bytes in this cache
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+impl<K> Drop for TextureCache<K> {
+    fn drop(&mut self) {
+        CACHED_BYTES.fetch_sub(self.bytes, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{ColorImage, TextureOptions};
+
+    #[test]
+    fn test_texture_cache_evicts_least_recent() {
+        let ctx = Context::default();
+        let load = |name: &str| ctx.load_texture(name, ColorImage::new([16, 16], egui::Color32::WHITE), TextureOptions::NEAREST);
+        // Room for two 16×16 textures
+        let mut cache = TextureCache::with_budget(2 * 16 * 16 * 4);
+        cache.insert(1, load("one"));
+        cache.insert(2, load("two"));
+        assert!(cache.get(&1).is_some());
+        cache.insert(3, load("three"));
+        assert!(cache.contains_key(&1) && cache.contains_key(&3));
+        assert!(!cache.contains_key(&2));
+        assert_eq!(cache.bytes(), 2 * 16 * 16 * 4);
+
+        assert!(usage(&ctx).total >= cache.bytes());
+        cache.clear();
+        assert_eq!(cache.bytes(), 0);
+    }
+}
@@ -2,8 +2,127 @@
 //!
 //! Pure black and white. No grays. 1px black outlines.
 //! IBM Plex Sans as the system font.
+//!
+//! Every size is multiplied by the system UI scale ("text size" in
+//! Settings → display, 90%–200%).  `SlowTheme::apply` records the scale so
+//! slowcore widgets can size themselves with [`ui_scale`]; apps drawing
+//! their own fixed-size chrome should do the same.
+//!
+//! With "keyboard navigation" turned on in Settings, Tab moves focus
+//! between widgets instead of being swallowed, and the focused widget gets
+//! a thick outline.
+//!
+//! A font made in slowFont and chosen as the system font replaces IBM Plex
+//! Sans for text; Plex stays behind it for any characters it lacks.
 
+use crate::storage::config_dir;
 use egui::{Color32, FontData, FontDefinitions, FontFamily, FontId, Rounding, Stroke, Style, TextStyle, Visuals};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
+
+/// Smallest and largest allowed UI scale
+pub const UI_SCALE_MIN: f32 = 0.9;
+pub const UI_SCALE_MAX: f32 = 2.0;
+
+/// Scale applied by the last `SlowTheme::apply`, stored as f32 bits (1.0)
+static APPLIED_SCALE: AtomicU32 = AtomicU32::new(0x3f80_0000);
+
+/// Whether Tab focus traversal was enabled by the last `SlowTheme::apply`
+static KEYBOARD_NAVIGATION: AtomicBool = AtomicBool::new(false);
+
+/// Greys the screen can show, as set by the last `SlowTheme::apply`
+static GREY_LEVELS: AtomicU8 = AtomicU8::new(2);
+
+/// The grey rendering modes: black and white, 4 greys and 16 greys
+pub const GREY_LEVEL_CHOICES: [u8; 3] = [2, 4, 16];
+
+/// Whether full keyboard navigation (Tab between widgets) is on.
+pub fn keyboard_navigation() -> bool {
+    KEYBOARD_NAVIGATION.load(Ordering::Relaxed)
+}
+
+/// Scale factor currently in effect for this app (1.0 = 100%).
+pub fn ui_scale() -> f32 {
+    f32::from_bits(APPLIED_SCALE.load(Ordering::Relaxed))
+}
+
+/// How many greys pictures are drawn with (2 = black and white), see
+/// [`dither::photo`](crate::dither::photo).
+pub fn grey_levels() -> u8 {
+    GREY_LEVELS.load(Ordering::Relaxed)
+}
+
+/// One value from the system settings file, if set
+pub(crate) fn system_setting(key: &str) -> Option<serde_json::Value> {
+    let path = config_dir("slowos").join("settings.json");
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .and_then(|v| v.get(key).cloned())
+}
+
+/// Write one key of the system settings file, keeping the rest.
+pub(crate) fn set_system_setting(key: &str, value: serde_json::Value) {
+    let path = config_dir("slowos").join("settings.json");
+    let mut settings: serde_json::Value = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    if let Some(obj) = settings.as_object_mut() {
+        obj.insert(key.into(), value);
+    }
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&settings) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// Read the preferred UI scale from the system settings (1.0 if unset).
+pub fn preferred_ui_scale() -> f32 {
+    system_setting("ui_scale_percent")
+        .and_then(|p| p.as_u64())
+        .map(|p| p as f32 / 100.0)
+        .unwrap_or(1.0)
+        .clamp(UI_SCALE_MIN, UI_SCALE_MAX)
+}
+
+/// Read the keyboard navigation preference from the system settings.
+pub fn preferred_keyboard_navigation() -> bool {
+    system_setting("keyboard_navigation").and_then(|b| b.as_bool()).unwrap_or(false)
+}
+
+/// Read the reduce motion preference from the system settings. Apps
+/// should ask [`animation::reduce_motion`](crate::animation::reduce_motion),
+/// which doesn't read the file every frame.
+pub fn preferred_reduce_motion() -> bool {
+    system_setting("reduce_motion").and_then(|b| b.as_bool()).unwrap_or(false)
+}
+
+/// The grey rendering mode chosen in Settings. Only worth changing on a
+/// screen that can show grey; the default is black and white.
+pub fn preferred_grey_levels() -> u8 {
+    system_setting("grey_levels")
+        .and_then(|l| l.as_u64())
+        .and_then(|l| GREY_LEVEL_CHOICES.into_iter().find(|c| *c as u64 == l))
+        .unwrap_or(2)
+}
+
+/// Where installed user fonts live: ~/.config/slowos/fonts
+pub fn fonts_dir() -> std::path::PathBuf {
+    config_dir("slowos").join("fonts")
+}
+
+/// File name, within [`fonts_dir`], of the font chosen as the system font
+pub fn preferred_system_font() -> Option<String> {
+    system_setting("system_font").and_then(|f| f.as_str().map(str::to_string)).filter(|f| !f.is_empty())
+}
+
+/// Choose an installed font as the system font, or `None` for IBM Plex
+/// Sans. Apps pick it up when they next start.
+pub fn set_preferred_system_font(file_name: Option<&str>) {
+    set_system_setting("system_font", file_name.map_or(serde_json::Value::Null, |f| f.into()));
+}
 
 /// Only two colors exist on this machine.
 pub struct SlowColors;
@@ -20,6 +139,14 @@ pub struct SlowTheme {
     pub font_size_small: f32,
     pub window_padding: f32,
     pub item_spacing: f32,
+    /// Multiplier for every size above (and for slowcore widgets)
+    pub scale: f32,
+    /// Let Tab move focus between widgets
+    pub keyboard_navigation: bool,
+    /// Turn off egui's own transitions (menus and sections sliding open)
+    pub reduce_motion: bool,
+    /// Greys photos are drawn with: 2, 4 or 16
+    pub grey_levels: u8,
 }
 
 impl Default for SlowTheme {
@@ -30,6 +157,10 @@ impl Default for SlowTheme {
             font_size_small: 11.0,
             window_padding: 8.0,
             item_spacing: 4.0,
+            scale: preferred_ui_scale(),
+            keyboard_navigation: preferred_keyboard_navigation(),
+            reduce_motion: preferred_reduce_motion(),
+            grey_levels: preferred_grey_levels(),
         }
     }
 }
@@ -66,6 +197,15 @@ impl SlowTheme {
         None
     }
 
+    /// The chosen system font, if there is one and it can be read.
+    /// A broken file would bring down every app, so it's parsed first.
+    fn load_system_font() -> Option<Vec<u8>> {
+        let name = preferred_system_font()?;
+        let data = std::fs::read(fonts_dir().join(name)).ok()?;
+        ab_glyph::FontRef::try_from_slice(&data).ok()?;
+        Some(data)
+    }
+
     /// Apply the slow computer theme to an egui context
     pub fn apply(&self, ctx: &egui::Context) {
         // --- load fonts ---
@@ -100,6 +240,14 @@ impl SlowTheme {
             .entry(FontFamily::Proportional)
             .or_default()
             .insert(0, "IBMPlexSans".to_owned());
+        // A font from slowFont goes in front, with Plex as its fallback
+        if let Some(data) = Self::load_system_font() {
+            fonts.font_data.insert("SystemFont".to_owned(), FontData::from_owned(data));
+            fonts.families
+                .entry(FontFamily::Proportional)
+                .or_default()
+                .insert(0, "SystemFont".to_owned());
+        }
         // Monospace: JetBrains Mono
         fonts.families
             .entry(FontFamily::Monospace)
@@ -108,14 +256,19 @@ impl SlowTheme {
         ctx.set_fonts(fonts);
 
         // --- style ---
+        let scale = self.scale.clamp(UI_SCALE_MIN, UI_SCALE_MAX);
+        APPLIED_SCALE.store(scale.to_bits(), Ordering::Relaxed);
+        KEYBOARD_NAVIGATION.store(self.keyboard_navigation, Ordering::Relaxed);
+        GREY_LEVELS.store(self.grey_levels.clamp(2, 16), Ordering::Relaxed);
+        let px = |v: f32| (v * scale).round();
         let mut style = Style::default();
 
         style.text_styles = [
-            (TextStyle::Small, FontId::new(self.font_size_small, FontFamily::Proportional)),
-            (TextStyle::Body, FontId::new(self.font_size_body, FontFamily::Proportional)),
-            (TextStyle::Button, FontId::new(self.font_size_body, FontFamily::Proportional)),
-            (TextStyle::Heading, FontId::new(self.font_size_heading, FontFamily::Proportional)),
-            (TextStyle::Monospace, FontId::new(self.font_size_body, FontFamily::Monospace)),
+            (TextStyle::Small, FontId::new(px(self.font_size_small), FontFamily::Proportional)),
+            (TextStyle::Body, FontId::new(px(self.font_size_body), FontFamily::Proportional)),
+            (TextStyle::Button, FontId::new(px(self.font_size_body), FontFamily::Proportional)),
+            (TextStyle::Heading, FontId::new(px(self.font_size_heading), FontFamily::Proportional)),
+            (TextStyle::Monospace, FontId::new(px(self.font_size_body), FontFamily::Monospace)),
         ]
         .into();
 
@@ -143,6 +296,9 @@ impl SlowTheme {
         bw(&mut visuals.widgets.hovered);
         bw(&mut visuals.widgets.active);
         bw(&mut visuals.widgets.open);
+        // egui draws focused widgets with the "active" visuals: make that
+        // a thick outline so keyboard focus is easy to spot on e-ink
+        visuals.widgets.active.bg_stroke = Stroke::new(2.0, SlowColors::BLACK);
 
         // Disable smooth shadows (we draw dithered shadows manually)
         visuals.window_shadow = egui::epaint::Shadow::NONE;
@@ -154,9 +310,22 @@ impl SlowTheme {
 
         style.visuals = visuals;
 
-        style.spacing.window_margin = egui::Margin::same(self.window_padding);
-        style.spacing.item_spacing = egui::vec2(self.item_spacing, self.item_spacing);
-        style.spacing.button_padding = egui::vec2(8.0, 4.0);
+        style.spacing.window_margin = egui::Margin::same(px(self.window_padding));
+        style.spacing.item_spacing = egui::vec2(px(self.item_spacing), px(self.item_spacing));
+        style.spacing.button_padding = egui::vec2(px(8.0), px(4.0));
+        style.spacing.interact_size *= scale;
+        style.spacing.icon_width = px(style.spacing.icon_width);
+        style.spacing.icon_width_inner = px(style.spacing.icon_width_inner);
+        style.spacing.icon_spacing = px(style.spacing.icon_spacing);
+        style.spacing.slider_width = px(style.spacing.slider_width);
+        style.spacing.combo_width = px(style.spacing.combo_width);
+        style.spacing.text_edit_width = px(style.spacing.text_edit_width);
+        style.spacing.menu_margin = egui::Margin::same(px(style.spacing.menu_margin.left));
+        style.spacing.scroll.bar_width = px(style.spacing.scroll.bar_width);
+
+        if self.reduce_motion {
+            style.animation_time = 0.0;
+        }
 
         ctx.set_style(style);
     }
@@ -171,22 +340,36 @@ impl SlowTheme {
 
     /// Title bar: white fill, 1px black bottom border
     pub fn title_bar_frame() -> egui::Frame {
+        let s = ui_scale();
         egui::Frame::none()
             .fill(SlowColors::WHITE)
             .stroke(Stroke::new(1.0, SlowColors::BLACK))
-            .inner_margin(egui::Margin::symmetric(8.0, 4.0))
+            .inner_margin(egui::Margin::symmetric(8.0 * s, 4.0 * s))
     }
 }
 
-/// Menu bar styling helper
+/// Menu bar styling helper.
+///
+/// The menu bar doubles as the window's title bar: double-clicking its
+/// empty space rolls the window up (see [`toggle_window_shade`](crate::widgets::toggle_window_shade)).
 pub fn menu_bar<R>(ui: &mut egui::Ui, add_contents: impl FnOnce(&mut egui::Ui) -> R) -> egui::InnerResponse<R> {
+    let s = ui_scale();
+    // Sense the bar's background before its contents, so menus and
+    // buttons drawn on top keep their clicks. Uses last frame's rect.
+    let bar_id = ui.id().with("slowcore_menu_bar");
+    if let Some(rect) = ui.ctx().data(|d| d.get_temp::<egui::Rect>(bar_id)) {
+        if ui.interact(rect, bar_id, egui::Sense::click()).double_clicked() {
+            crate::widgets::toggle_window_shade(ui.ctx(), rect.bottom());
+        }
+    }
     let frame_resp = egui::Frame::none()
         .fill(SlowColors::WHITE)
         .stroke(Stroke::new(1.0, SlowColors::BLACK))
-        .inner_margin(egui::Margin::symmetric(4.0, 2.0))
+        .inner_margin(egui::Margin::symmetric(4.0 * s, 2.0 * s))
         .show(ui, |ui| {
             ui.horizontal(add_contents).inner
         });
+    ui.ctx().data_mut(|d| d.insert_temp(bar_id, frame_resp.response.rect));
     egui::InnerResponse {
         inner: frame_resp.inner,
         response: frame_resp.response,
@@ -195,7 +378,8 @@ pub fn menu_bar<R>(ui: &mut egui::Ui, add_contents: impl FnOnce(&mut egui::Ui) -
 
 /// Consume problematic key events to prevent unwanted egui behaviors.
 /// Call this at the start of your app's update() function.
-/// - Tab: prevents menu focus navigation and focus cycling
+/// - Tab: prevents menu focus navigation and focus cycling (unless
+///   keyboard navigation is turned on)
 /// - Cmd+/Cmd-: prevents zoom scaling
 pub fn consume_special_keys(ctx: &egui::Context) {
     consume_special_keys_with_tab(ctx, 0);
@@ -211,8 +395,12 @@ pub fn consume_special_keys(ctx: &egui::Context) {
 /// 2. Re-request focus on the currently focused widget, so any Tab-caused
 ///    focus change is reverted next frame
 pub fn consume_special_keys_with_tab(ctx: &egui::Context, tab_spaces: usize) {
+    // With keyboard navigation on, Tab moves focus unless the app uses it
+    // for indentation
+    let keep_tab = keyboard_navigation() && tab_spaces == 0;
+
     // Detect Tab press before stripping events
-    let tab_pressed = ctx.input(|i| {
+    let tab_pressed = !keep_tab && ctx.input(|i| {
         i.events.iter().any(|e| matches!(e,
             egui::Event::Key { key: egui::Key::Tab, pressed: true, .. }
         ))
@@ -238,7 +426,7 @@ pub fn consume_special_keys_with_tab(ctx: &egui::Context, tab_spaces: usize) {
         for event in i.events.iter() {
             match event {
                 // Strip Tab Key events entirely
-                egui::Event::Key { key: egui::Key::Tab, .. } => {}
+                egui::Event::Key { key: egui::Key::Tab, .. } if !keep_tab => {}
                 // Replace tab characters with spaces in text input, or strip
                 egui::Event::Text(text) if text.contains('\t') => {
                     if tab_spaces > 0 {
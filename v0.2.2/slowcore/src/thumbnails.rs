@@ -0,0 +1,248 @@
+//! Thumbnails — small black and white pictures of image files
+//!
+//! Made once and kept in ~/.cache/slowos/thumbnails as 1-bit PNGs, named
+//! by a hash of the file's path, size and modification time, so an edited
+//! picture gets a new one. Decoding a photo takes the Pi a good fraction
+//! of a second; reading a cached thumbnail doesn't.
+//!
+//! [`Thumbnails`] makes them on a background thread, newest request
+//! first, so the pictures scrolled to come before the ones scrolled past:
+//!
+//! ```ignore
+//! let mut thumbnails = Thumbnails::new(&cc.egui_ctx, thumbnails::ICON_SIZE);
+//! // each frame:
+//! if let Some(texture) = thumbnails.get(ctx, &path) { painter.image(texture.id(), ...) }
+//! ```
+
+use crate::dither;
+use crate::preview::PICTURE_EXTENSIONS;
+use crate::textures::TextureCache;
+use egui::{ColorImage, Context, TextureHandle, TextureOptions};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::UNIX_EPOCH;
+
+/// Size of slowFiles' icons, and of the thumbnails the desktop makes ahead
+pub const ICON_SIZE: u32 = 48;
+
+/// Cached thumbnails kept before the oldest are removed
+const MAX_CACHED: usize = 4000;
+
+/// Thumbnails written between looks at the cache's size
+const PRUNE_EVERY: usize = 64;
+
+/// Requests waiting for the thread, newest last
+type Queue = Arc<(Mutex<Vec<PathBuf>>, Condvar)>;
+
+/// Thumbnails of one size, as textures, made in the background
+pub struct Thumbnails {
+    size: u32,
+    /// Made and loaded, the least recently shown dropped over budget
+    textures: TextureCache<PathBuf>,
+    /// Files that aren't pictures the loaders can read
+    failed: HashSet<PathBuf>,
+    /// Asked for and not back yet
+    waiting: HashSet<PathBuf>,
+    queue: Queue,
+    done: Receiver<(PathBuf, Option<ColorImage>)>,
+}
+
+impl Thumbnails {
+    /// Start the thread making `size`-pixel thumbnails. It wakes `ctx`
+    /// each time one is ready.
+    pub fn new(ctx: &Context, size: u32) -> Self {
+        let queue: Queue = Arc::new((Mutex::new(Vec::new()), Condvar::new()));
+        let (tx, done) = mpsc::channel();
+        let ctx = ctx.clone();
+        let requests = queue.clone();
+        let _ = std::thread::Builder::new().name("slowcore-thumbnails".into()).spawn(move || loop {
+            let path = {
+                let (lock, ready) = &*requests;
+                let mut waiting = lock.lock().unwrap_or_else(|e| e.into_inner());
+                loop {
+                    match waiting.pop() {
+                        Some(path) => break path,
+                        None => waiting = ready.wait(waiting).unwrap_or_else(|e| e.into_inner()),
+                    }
+                }
+            };
+            let image = thumbnail(&path, size);
+            if tx.send((path, image)).is_err() {
+                return;
+            }
+            ctx.request_repaint();
+        });
+        Self { size, textures: TextureCache::new(), failed: HashSet::new(), waiting: HashSet::new(), queue, done }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// The thumbnail of `path`, or `None` while it's being made or if
+    /// `path` isn't a picture
+    pub fn get(&mut self, ctx: &Context, path: &Path) -> Option<&TextureHandle> {
+        while let Ok((done, image)) = self.done.try_recv() {
+            self.waiting.remove(&done);
+            match image {
+                Some(image) => {
+                    let name = format!("thumbnail_{}", done.display());
+                    self.textures.insert(done, ctx.load_texture(name, image, TextureOptions::NEAREST));
+                }
+                None => {
+                    self.failed.insert(done);
+                }
+            }
+        }
+        if !self.textures.contains_key(path) && !self.failed.contains(path) && self.waiting.insert(path.to_path_buf()) {
+            let (lock, ready) = &*self.queue;
+            lock.lock().unwrap_or_else(|e| e.into_inner()).push(path.to_path_buf());
+            ready.notify_one();
+        }
+        self.textures.get(path)
+    }
+
+    /// Drop the textures, and requests not started, when leaving a folder
+    pub fn clear(&mut self) {
+        self.textures.clear();
+        self.failed.clear();
+        let (lock, _) = &*self.queue;
+        let mut queued = lock.lock().unwrap_or_else(|e| e.into_inner());
+        for path in queued.drain(..) {
+            self.waiting.remove(&path);
+        }
+    }
+}
+
+/// Whether the loaders can make a thumbnail of `path`
+pub fn is_picture(path: &Path) -> bool {
+    let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default();
+    PICTURE_EXTENSIONS.contains(&ext.as_str())
+}
+
+/// Folder the thumbnails are kept in
+pub fn cache_dir() -> PathBuf {
+    let dir = directories::ProjectDirs::from("co", "slowcomputer", "slowos")
+        .map(|dirs| dirs.cache_dir().join("thumbnails"))
+        .unwrap_or_else(|| std::env::temp_dir().join("slowos-thumbnails"));
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// Where the `size`-pixel thumbnail of `path`, as it is now, is kept
+fn cache_path(path: &Path, size: u32) -> Option<PathBuf> {
+    let meta = std::fs::metadata(path).ok()?;
+    let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    // FNV-1a: unlike std's hasher, the same in every build
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let key = format!("{}\0{}\0{}\0{}", path.display(), meta.len(), modified.as_nanos(), size);
+    for byte in key.bytes() {
+        hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+    }
+    Some(cache_dir().join(format!("{:016x}.png", hash)))
+}
+
+/// The `size`-pixel thumbnail of `path`, from the cache or made and cached
+/// now. Blocks; [`Thumbnails`] calls it on its own thread.
+pub fn thumbnail(path: &Path, size: u32) -> Option<ColorImage> {
+    if !is_picture(path) {
+        return None;
+    }
+    let cached = cache_path(path, size)?;
+    if let Some(image) = read_png(&cached) {
+        return Some(image);
+    }
+    let mut grey = image::open(path).ok()?.thumbnail(size, size).to_luma8();
+    dither::atkinson(&mut grey);
+    let (width, height) = grey.dimensions();
+    static WRITTEN: AtomicUsize = AtomicUsize::new(0);
+    let written = write_png(&cached, width, height, grey.as_raw()).is_ok();
+    if written && WRITTEN.fetch_add(1, Ordering::Relaxed).is_multiple_of(PRUNE_EVERY) {
+        prune();
+    }
+    let pixels = grey.pixels().map(|p| egui::Color32::from_gray(p.0[0])).collect();
+    Some(ColorImage { size: [width as usize, height as usize], pixels })
+}
+
+/// Make thumbnails for the pictures in `dir` that don't have one yet, on
+/// a background thread. The desktop does this for Pictures when it starts.
+pub fn prepare(dir: &Path, size: u32) {
+    let dir = dir.to_path_buf();
+    let _ = std::thread::Builder::new().name("slowcore-thumbnails-ahead".into()).spawn(move || {
+        let Ok(entries) = std::fs::read_dir(&dir) else { return };
+        for path in entries.flatten().map(|e| e.path()).filter(|p| is_picture(p)) {
+            thumbnail(&path, size);
+        }
+    });
+}
+
+/// Write one-bit greyscale: a 48-pixel thumbnail is a few hundred bytes
+fn write_png(path: &Path, width: u32, height: u32, grey: &[u8]) -> Result<(), png::EncodingError> {
+    let row_bytes = width.div_ceil(8) as usize;
+    let mut packed = vec![0u8; row_bytes * height as usize];
+    for (i, level) in grey.iter().enumerate() {
+        let (x, y) = (i % width as usize, i / width as usize);
+        if *level >= 128 {
+            packed[y * row_bytes + x / 8] |= 0x80 >> (x % 8);
+        }
+    }
+    // Written aside and renamed, so another app never reads half of one
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    let mut encoder = png::Encoder::new(std::fs::File::create(&tmp)?, width, height);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::One);
+    encoder.write_header()?.write_image_data(&packed)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn read_png(path: &Path) -> Option<ColorImage> {
+    let mut decoder = png::Decoder::new(std::fs::File::open(path).ok()?);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().ok()?;
+    let mut buf = vec![0u8; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buf).ok()?;
+    if frame.color_type != png::ColorType::Grayscale || frame.bit_depth != png::BitDepth::Eight {
+        return None;
+    }
+    let size = [frame.width as usize, frame.height as usize];
+    let pixels = buf[..frame.buffer_size()].iter().map(|&v| egui::Color32::from_gray(v)).collect();
+    Some(ColorImage { size, pixels })
+}
+
+/// Keep the cache under MAX_CACHED thumbnails, removing the oldest
+fn prune() {
+    let Ok(entries) = std::fs::read_dir(cache_dir()) else { return };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect();
+    if files.len() <= MAX_CACHED {
+        return;
+    }
+    files.sort();
+    for (_, path) in &files[..files.len() - MAX_CACHED * 3 / 4] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_bit_png_round_trip() {
+        let path = std::env::temp_dir().join(format!("slowcore_thumb_{}.png", std::process::id()));
+        let grey: Vec<u8> = (0..10 * 3).map(|i| if i % 3 == 0 { 255 } else { 0 }).collect();
+        write_png(&path, 10, 3, &grey).unwrap();
+        let image = read_png(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(image.size, [10, 3]);
+        let back: Vec<u8> = image.pixels.iter().map(|c| c.r()).collect();
+        assert_eq!(back, grey);
+        assert!(!is_picture(Path::new("notes.txt")));
+    }
+}
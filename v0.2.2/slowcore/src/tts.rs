@@ -0,0 +1,401 @@
+//! Reading text aloud
+//!
+//! Speech comes from piper when a voice is installed in /usr/share/piper,
+//! otherwise from espeak-ng (or the older espeak). Text is spoken a sentence at a time, so the
+//! app can highlight the sentence being read:
+//!
+//! ```ignore
+//! let mut speaker = Speaker::new("slowreader");
+//! speaker.play(&text, 0);
+//! // every frame:
+//! if let Some(range) = speaker.current() {
+//!     // highlight text[range]
+//! }
+//! ```
+//!
+//! Sentences are synthesized a little ahead of the one playing and queued
+//! on the shared audio output, so the mixer's volume for the app applies.
+
+use crate::sound::{self, Player};
+use rodio::buffer::SamplesBuffer;
+use rodio::Sink;
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Piper voices (.onnx with their .onnx.json) are looked for here
+const PIPER_VOICES: &str = "/usr/share/piper";
+
+/// espeak's words per minute at normal speed
+const ESPEAK_WPM: f32 = 175.0;
+
+/// Sentences synthesized ahead of the one playing
+const QUEUE_AHEAD: usize = 2;
+
+/// Speeds offered in apps
+pub const SPEEDS: [f32; 5] = [0.75, 1.0, 1.25, 1.5, 2.0];
+
+/// A speech synthesizer on this machine
+#[derive(Debug, Clone, PartialEq)]
+pub enum Engine {
+    /// A piper voice model
+    Piper(PathBuf),
+    /// espeak-ng or espeak, by program name
+    Espeak(&'static str),
+}
+
+/// The best synthesizer installed, if any
+pub fn engine() -> Option<Engine> {
+    if let Some(model) = piper_voice() {
+        if installed("piper") {
+            return Some(Engine::Piper(model));
+        }
+    }
+    ["espeak-ng", "espeak"].into_iter().find(|p| installed(p)).map(Engine::Espeak)
+}
+
+fn installed(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+fn piper_voice() -> Option<PathBuf> {
+    let mut voices: Vec<PathBuf> = std::fs::read_dir(PIPER_VOICES)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "onnx"))
+        .collect();
+    voices.sort();
+    voices.into_iter().next()
+}
+
+/// Byte ranges of the sentences in `text`, without surrounding whitespace.
+/// A sentence ends after ".", "!" or "?" followed by a space, or at a line
+/// break.
+pub fn sentences(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, n)| n);
+        let end = match c {
+            '\n' => Some(i),
+            '.' | '!' | '?' => match next {
+                None => None,
+                Some(n) if n.is_whitespace() => Some(i + 1),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(end) = end {
+            push_trimmed(text, start..end, &mut ranges);
+            start = end;
+        }
+    }
+    push_trimmed(text, start..text.len(), &mut ranges);
+    ranges
+}
+
+fn push_trimmed(text: &str, range: Range<usize>, out: &mut Vec<Range<usize>>) {
+    let part = &text[range.clone()];
+    let trimmed = part.trim_start();
+    let start = range.start + part.len() - trimmed.len();
+    let end = start + trimmed.trim_end().len();
+    if text[start..end].chars().any(char::is_alphanumeric) {
+        out.push(start..end);
+    }
+}
+
+/// Samples from a WAV file as espeak writes it: 16-bit PCM, with a
+/// data size that may be left unset when streaming
+fn parse_wav(bytes: &[u8]) -> Option<(u16, u32, Vec<i16>)> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return None;
+    }
+    let mut format = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().ok()?) as usize;
+        let body = pos + 8;
+        match id {
+            b"fmt " if body + 8 <= bytes.len() => {
+                let channels = u16::from_le_bytes([bytes[body + 2], bytes[body + 3]]);
+                let rate = u32::from_le_bytes(bytes[body + 4..body + 8].try_into().ok()?);
+                format = Some((channels, rate));
+            }
+            b"data" => {
+                let (channels, rate) = format?;
+                let end = body.saturating_add(size).min(bytes.len());
+                return Some((channels, rate, pcm_samples(&bytes[body..end])));
+            }
+            _ => {}
+        }
+        pos = body.saturating_add(size + size % 2);
+    }
+    None
+}
+
+fn pcm_samples(bytes: &[u8]) -> Vec<i16> {
+    bytes.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect()
+}
+
+/// Speak `text` into samples at `speed` (1.0 = normal)
+fn synthesize(engine: &Engine, text: &str, speed: f32) -> Result<SamplesBuffer<i16>, String> {
+    match engine {
+        Engine::Espeak(program) => {
+            let wpm = (ESPEAK_WPM * speed).round() as u32;
+            let output = Command::new(program)
+                .args(["--stdout", "-s", &wpm.to_string(), "--"])
+                .arg(text)
+                .stderr(Stdio::null())
+                .output()
+                .map_err(|e| format!("{}: {}", program, e))?;
+            let (channels, rate, samples) =
+                parse_wav(&output.stdout).ok_or_else(|| format!("{} gave no sound", program))?;
+            Ok(SamplesBuffer::new(channels, rate, samples))
+        }
+        Engine::Piper(model) => {
+            let mut child = Command::new("piper")
+                .arg("--model")
+                .arg(model)
+                .args(["--output_raw", "--length_scale", &format!("{:.2}", 1.0 / speed)])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|e| format!("piper: {}", e))?;
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            let output = child.wait_with_output().map_err(|e| format!("piper: {}", e))?;
+            if output.stdout.is_empty() {
+                return Err("piper gave no sound".to_string());
+            }
+            Ok(SamplesBuffer::new(1, piper_rate(model), pcm_samples(&output.stdout)))
+        }
+    }
+}
+
+/// Sample rate of a piper voice, from the config beside the model
+fn piper_rate(model: &Path) -> u32 {
+    let mut config = model.as_os_str().to_owned();
+    config.push(".json");
+    std::fs::read_to_string(config)
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .and_then(|v| v["audio"]["sample_rate"].as_u64())
+        .unwrap_or(22_050) as u32
+}
+
+/// One reading, from the sentence it started at to the end of the text
+struct Run {
+    sink: Arc<Sink>,
+    /// Sentence the run started at
+    first: usize,
+    /// Sentences queued on the sink so far
+    queued: Arc<AtomicUsize>,
+    /// All sentences are queued (or synthesis failed)
+    done: Arc<AtomicBool>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Run {
+    /// Index of the sentence playing now
+    fn current(&self) -> usize {
+        self.first + self.queued.load(Ordering::Relaxed).saturating_sub(self.sink.len())
+    }
+
+    fn finished(&self) -> bool {
+        self.done.load(Ordering::Relaxed) && self.sink.empty()
+    }
+
+    fn stop(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.sink.stop();
+    }
+}
+
+/// Reads text aloud a sentence at a time
+pub struct Speaker {
+    player: Player,
+    text: Arc<String>,
+    sentences: Arc<Vec<Range<usize>>>,
+    run: Option<Run>,
+    paused: bool,
+    speed: f32,
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl Speaker {
+    /// `app` is the binary name, for the mixer
+    pub fn new(app: &str) -> Self {
+        Self {
+            player: Player::register(app),
+            text: Arc::new(String::new()),
+            sentences: Arc::new(Vec::new()),
+            run: None,
+            paused: false,
+            speed: 1.0,
+            error: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Start reading `text` from the sentence containing byte `from`
+    pub fn play(&mut self, text: &str, from: usize) {
+        self.stop();
+        self.text = Arc::new(text.to_string());
+        self.sentences = Arc::new(sentences(text));
+        let first = self.sentences.iter().position(|r| r.end > from).unwrap_or(self.sentences.len());
+        self.start(first);
+    }
+
+    fn start(&mut self, first: usize) {
+        *self.error.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        let Some(engine) = engine() else {
+            self.set_error("no speech synthesizer is installed (espeak-ng or piper)");
+            return;
+        };
+        let Some(sink) = sound::output().and_then(|handle| Sink::try_new(handle).ok()) else {
+            self.set_error("there's no sound output");
+            return;
+        };
+        sink.set_volume(self.player.volume());
+        let run = Run {
+            sink: Arc::new(sink),
+            first,
+            queued: Arc::new(AtomicUsize::new(0)),
+            done: Arc::new(AtomicBool::new(false)),
+            cancel: Arc::new(AtomicBool::new(false)),
+        };
+        let (sink, queued, done, cancel) = (run.sink.clone(), run.queued.clone(), run.done.clone(), run.cancel.clone());
+        let (text, sentences, error, speed) = (self.text.clone(), self.sentences.clone(), self.error.clone(), self.speed);
+        let spawned = std::thread::Builder::new().name("slowcore-tts".into()).spawn(move || {
+            for range in &sentences[first.min(sentences.len())..] {
+                while sink.len() > QUEUE_AHEAD && !cancel.load(Ordering::Relaxed) {
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                match synthesize(&engine, &text[range.clone()], speed) {
+                    Ok(samples) => {
+                        sink.append(samples);
+                        queued.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(e) => {
+                        *error.lock().unwrap_or_else(|e| e.into_inner()) = Some(e);
+                        break;
+                    }
+                }
+            }
+            done.store(true, Ordering::Relaxed);
+        });
+        if spawned.is_ok() {
+            self.run = Some(run);
+            self.paused = false;
+        }
+    }
+
+    fn set_error(&self, message: &str) {
+        *self.error.lock().unwrap_or_else(|e| e.into_inner()) = Some(message.to_string());
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(run) = &self.run {
+            run.sink.pause();
+            self.paused = true;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(run) = &self.run {
+            run.sink.play();
+            self.paused = false;
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(run) = self.run.take() {
+            run.stop();
+        }
+        self.paused = false;
+        self.player.set_playing(false);
+    }
+
+    /// Reading, paused or not
+    pub fn is_active(&self) -> bool {
+        self.run.is_some()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Change the speed; a reading in progress goes on at the new speed
+    /// from the start of the sentence being read
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.clamp(0.5, 3.0);
+        if let Some(current) = self.run.as_ref().map(Run::current) {
+            let paused = self.paused;
+            self.stop();
+            self.start(current);
+            if paused {
+                self.pause();
+            }
+        }
+    }
+
+    /// The sentence being read, as a byte range of the text; None once the
+    /// reading is over. Call every frame while reading: it also keeps the
+    /// volume in step with the mixer.
+    pub fn current(&mut self) -> Option<Range<usize>> {
+        let run = self.run.as_ref()?;
+        if run.finished() {
+            self.stop();
+            return None;
+        }
+        run.sink.set_volume(self.player.volume());
+        let current = run.current();
+        self.player.set_playing(!self.paused);
+        self.sentences.get(current).cloned()
+    }
+
+    /// What went wrong with the last reading, if anything
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentences() {
+        let text = "Call me Ishmael. Some years ago, never mind how long.\n\nChapter 2\n3.14 is pi! Really? ";
+        let found: Vec<&str> = sentences(text).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(
+            found,
+            ["Call me Ishmael.", "Some years ago, never mind how long.", "Chapter 2", "3.14 is pi!", "Really?"]
+        );
+        let mut wav = b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0\x01\0\x01\0\x22\x56\0\0\x44\xac\0\0\x02\0\x10\0".to_vec();
+        wav.extend_from_slice(b"data\xff\xff\xff\xff\x01\0\xff\xff");
+        assert_eq!(parse_wav(&wav), Some((1, 22_050, vec![1, -1])));
+    }
+}
@@ -0,0 +1,242 @@
+//! Undo and redo
+//!
+//! [`UndoStack`] keeps an app's history in either of two ways, which can
+//! be mixed:
+//!
+//! - **Snapshots.** Before changing the state, [`save`](UndoStack::save)
+//!   a copy of it. Simple, and right when the state is small or changes
+//!   all over (slowMidi's notes, slowDesign's document, slowPaint's
+//!   canvas).
+//! - **Commands.** After changing the state, [`push`](UndoStack::push) an
+//!   [`Edit`] that knows how to take the change back and make it again.
+//!   Right when copies would be big and changes are small.
+//!
+//! ```ignore
+//! self.history.save(&self.notes);
+//! self.notes.push(note);
+//! // ⌘Z
+//! self.history.undo(&mut self.notes);
+//! ```
+//!
+//! The history keeps a limited number of steps, dropping the oldest.
+//! Changes given the same coalescing key in quick succession (typing into
+//! a field, nudging with the arrow keys) become one step.
+//! [`take_shortcut`] handles ⌘Z and ⇧⌘Z.
+
+use egui::{Key, KeyboardShortcut, Modifiers};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Changes with the same key closer together than this are one step
+const COALESCE_WINDOW: Duration = Duration::from_millis(1000);
+
+/// A change that can be taken back and made again
+pub trait Edit<T> {
+    /// Take the change back out of `state`
+    fn undo(&self, state: &mut T);
+    /// Make the change to `state` again
+    fn redo(&self, state: &mut T);
+}
+
+enum Step<T> {
+    /// The whole state as it was
+    Snapshot(T),
+    /// Changes made since, oldest first
+    Commands(Vec<Box<dyn Edit<T>>>),
+}
+
+/// Undo and redo history for a state of type `T`
+pub struct UndoStack<T> {
+    undo: VecDeque<Step<T>>,
+    redo: Vec<Step<T>>,
+    /// Most steps kept
+    limit: usize,
+    /// Coalescing key and time of the last change
+    last: Option<(&'static str, Instant)>,
+}
+
+impl<T> UndoStack<T> {
+    /// History of at most `limit` steps
+    pub fn new(limit: usize) -> Self {
+        Self { undo: VecDeque::new(), redo: Vec::new(), limit: limit.max(1), last: None }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forget everything (a new or newly opened document)
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last = None;
+    }
+
+    /// Make the next change a step of its own, however soon it comes
+    pub fn break_coalescing(&mut self) {
+        self.last = None;
+    }
+
+    /// Whether a change with `key` joins the last step
+    fn coalesces(&mut self, key: Option<&'static str>) -> bool {
+        let now = Instant::now();
+        let joins = match (key, self.last) {
+            (Some(key), Some((last, at))) => key == last && now.duration_since(at) < COALESCE_WINDOW,
+            _ => false,
+        };
+        self.last = key.map(|key| (key, now));
+        joins && self.redo.is_empty() && !self.undo.is_empty()
+    }
+
+    fn record(&mut self, step: Step<T>) {
+        self.undo.push_back(step);
+        self.redo.clear();
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Remember `state` as it is, before changing it
+    pub fn save(&mut self, state: &T)
+    where
+        T: Clone,
+    {
+        if !self.coalesces(None) {
+            self.record(Step::Snapshot(state.clone()));
+        }
+    }
+
+    /// Like [`save`](Self::save), but changes with the same `key` in
+    /// quick succession undo together
+    pub fn save_coalesced(&mut self, state: &T, key: &'static str)
+    where
+        T: Clone,
+    {
+        // The snapshot from the start of the run already has the state
+        // to go back to
+        if !self.coalesces(Some(key)) {
+            self.record(Step::Snapshot(state.clone()));
+        }
+    }
+
+    /// Remember a change just made
+    pub fn push(&mut self, edit: impl Edit<T> + 'static) {
+        self.coalesces(None);
+        self.record(Step::Commands(vec![Box::new(edit)]));
+    }
+
+    /// Like [`push`](Self::push), but changes with the same `key` in
+    /// quick succession undo together
+    pub fn push_coalesced(&mut self, edit: impl Edit<T> + 'static, key: &'static str) {
+        if self.coalesces(Some(key)) {
+            if let Some(Step::Commands(edits)) = self.undo.back_mut() {
+                edits.push(Box::new(edit));
+                return;
+            }
+        }
+        self.record(Step::Commands(vec![Box::new(edit)]));
+    }
+
+    /// Take back the last step. False if there was nothing to undo.
+    pub fn undo(&mut self, state: &mut T) -> bool {
+        let Some(step) = self.undo.pop_back() else { return false };
+        let step = match step {
+            Step::Snapshot(before) => Step::Snapshot(std::mem::replace(state, before)),
+            Step::Commands(edits) => {
+                edits.iter().rev().for_each(|e| e.undo(state));
+                Step::Commands(edits)
+            }
+        };
+        self.redo.push(step);
+        self.last = None;
+        true
+    }
+
+    /// Make the last undone step again. False if there was nothing to redo.
+    pub fn redo(&mut self, state: &mut T) -> bool {
+        let Some(step) = self.redo.pop() else { return false };
+        let step = match step {
+            Step::Snapshot(after) => Step::Snapshot(std::mem::replace(state, after)),
+            Step::Commands(edits) => {
+                edits.iter().for_each(|e| e.redo(state));
+                Step::Commands(edits)
+            }
+        };
+        self.undo.push_back(step);
+        self.last = None;
+        true
+    }
+}
+
+/// The standard undo shortcuts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoAction {
+    /// ⌘Z
+    Undo,
+    /// ⇧⌘Z
+    Redo,
+}
+
+/// Consume ⌘Z or ⇧⌘Z if pressed this frame. Leaves them alone while a
+/// text field has the keyboard, as the field undoes its own typing.
+pub fn take_shortcut(ctx: &egui::Context) -> Option<UndoAction> {
+    if ctx.wants_keyboard_input() {
+        return None;
+    }
+    ctx.input_mut(|i| {
+        // Redo first: consuming ⌘Z would also match ⇧⌘Z
+        if i.consume_shortcut(&KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z)) {
+            Some(UndoAction::Redo)
+        } else if i.consume_shortcut(&KeyboardShortcut::new(Modifiers::COMMAND, Key::Z)) {
+            Some(UndoAction::Undo)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Append(i32);
+
+    impl Edit<Vec<i32>> for Append {
+        fn undo(&self, state: &mut Vec<i32>) {
+            state.pop();
+        }
+        fn redo(&self, state: &mut Vec<i32>) {
+            state.push(self.0);
+        }
+    }
+
+    #[test]
+    fn test_snapshots_and_commands() {
+        let mut history = UndoStack::new(2);
+        let mut state = vec![1];
+        for n in 2..=4 {
+            history.save(&state);
+            state.push(n);
+        }
+        // Only two steps are kept
+        assert!(history.undo(&mut state) && history.undo(&mut state));
+        assert!(!history.undo(&mut state));
+        assert_eq!(state, vec![1, 2]);
+        assert!(history.redo(&mut state));
+        assert_eq!(state, vec![1, 2, 3]);
+
+        state.push(5);
+        history.push_coalesced(Append(5), "type");
+        state.push(6);
+        history.push_coalesced(Append(6), "type");
+        assert!(!history.can_redo());
+        history.undo(&mut state);
+        assert_eq!(state, vec![1, 2, 3]);
+        history.redo(&mut state);
+        assert_eq!(state, vec![1, 2, 3, 5, 6]);
+    }
+}
@@ -0,0 +1,326 @@
+//! Updates — newer app binaries from a manifest on the web or a USB stick
+//!
+//! The settings app reads a manifest listing the latest version of each
+//! app, what changed and where its binary is:
+//!
+//! ```json
+//! { "apps": [ { "binary": "slowwrite", "name": "slowWrite", "version": "0.2.3",
+//!               "file": "slowwrite", "sha256": "…", "changes": ["mail merge from CSV"] } ] }
+//! ```
+//!
+//! `file` is relative to the manifest, or a full address. The manifest is
+//! either at an https address the user gives, or at
+//! `slowos-updates/manifest.json` on a USB stick. Every app needs a
+//! `sha256`; one without is left out. When the user has entered the
+//! publisher's key, the manifest must be signed with it, in
+//! `manifest.json.sig` next to it; a USB stick's manifest always must.
+//! Binaries picked for updating are downloaded (or copied), checked and
+//! staged; slowDesktop's process manager swaps a staged binary in the
+//! next time its app is launched, into a folder it searches before the
+//! system's own copies.
+//!
+//! Everything lives in ~/.config/slowos/update/: `config.json`,
+//! `installed.json` (versions swapped in so far), `staged/` and `bin/`.
+
+use crate::download::{self, Origin};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Folder on a USB stick that holds the manifest and binaries
+pub const USB_FOLDER: &str = "slowos-updates";
+
+/// Largest binary accepted
+const MAX_BINARY: u64 = 256 * 1024 * 1024;
+
+/// ~/.config/slowos/update
+fn update_dir() -> PathBuf {
+    let dir = directories::ProjectDirs::from("", "", "slowos")
+        .map(|p| p.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/slowos"))
+        .join("update");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+fn staged_dir() -> PathBuf {
+    update_dir().join("staged")
+}
+
+/// Where updated binaries are swapped in; searched before the system's
+pub fn bin_dir() -> PathBuf {
+    update_dir().join("bin")
+}
+
+fn read_json<T: for<'de> Deserialize<'de> + Default>(name: &str) -> T {
+    std::fs::read_to_string(update_dir().join(name))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn write_json<T: Serialize>(name: &str, value: &T) {
+    let path = update_dir().join(name);
+    let tmp = path.with_extension("tmp");
+    if let Ok(json) = serde_json::to_string_pretty(value) {
+        if std::fs::write(&tmp, json).is_ok() {
+            let _ = std::fs::rename(&tmp, &path);
+        }
+    }
+}
+
+/// Where updates come from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct UpdateConfig {
+    /// Address of the manifest
+    #[serde(default)]
+    pub url: String,
+    /// Look on USB sticks instead of the web
+    #[serde(default)]
+    pub usb: bool,
+    /// The publisher's ed25519 public key, in hex; manifests must be
+    /// signed with it
+    #[serde(default)]
+    pub key: String,
+}
+
+impl UpdateConfig {
+    pub fn load() -> Self {
+        read_json("config.json")
+    }
+
+    pub fn save(&self) {
+        write_json("config.json", self);
+    }
+}
+
+/// One app's entry in the manifest
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Release {
+    pub binary: String,
+    /// As the app writes it; the binary's name if missing
+    #[serde(default)]
+    pub name: String,
+    pub version: String,
+    /// The binary, relative to the manifest or a full address
+    pub file: String,
+    /// Checked after downloading; a release without one is left out
+    #[serde(default)]
+    pub sha256: String,
+    /// What changed, a line each
+    #[serde(default)]
+    pub changes: Vec<String>,
+}
+
+impl Release {
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() { &self.binary } else { &self.name }
+    }
+
+    /// Whether it can be installed: a plain binary name (one with a path
+    /// in it could put a binary anywhere) and a checksum
+    fn is_valid(&self) -> bool {
+        !self.binary.is_empty()
+            && !self.binary.contains(['/', '\\'])
+            && !self.binary.starts_with('.')
+            && !self.sha256.trim().is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct Manifest {
+    #[serde(default)]
+    apps: Vec<Release>,
+}
+
+/// Where a manifest was found
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// The manifest's address
+    Web(String),
+    Folder(PathBuf),
+}
+
+/// Updates found by [`check`]
+#[derive(Debug, Clone)]
+pub struct Available {
+    pub source: Source,
+    /// Apps with a newer version than the one installed
+    pub releases: Vec<Release>,
+}
+
+/// Versions swapped in so far, by binary
+fn installed() -> BTreeMap<String, String> {
+    read_json("installed.json")
+}
+
+/// The version of `binary` in use: the last one swapped in, or the
+/// version slowOS came with
+pub fn installed_version(binary: &str) -> String {
+    installed().remove(binary).unwrap_or_else(|| env!("CARGO_PKG_VERSION").to_string())
+}
+
+/// Whether version `a` comes after `b`, comparing dotted numbers ("0.10" > "0.9")
+pub fn is_newer(a: &str, b: &str) -> bool {
+    let parts = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v').split(['.', '-']).map(|p| p.parse().unwrap_or(0)).collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+    for i in 0..a.len().max(b.len()) {
+        let (x, y) = (a.get(i).copied().unwrap_or(0), b.get(i).copied().unwrap_or(0));
+        if x != y {
+            return x > y;
+        }
+    }
+    false
+}
+
+/// Read the manifest and list what is newer than what's installed. Slow:
+/// call off the UI thread.
+pub fn check(config: &UpdateConfig) -> Result<Available, String> {
+    let (source, json, signature) = if config.usb {
+        let folder = crate::volumes::removable()
+            .into_iter()
+            .map(|v| v.mount_point.join(USB_FOLDER))
+            .find(|f| f.join("manifest.json").is_file())
+            .ok_or_else(|| format!("no USB stick with a {} folder", USB_FOLDER))?;
+        if config.key.trim().is_empty() {
+            return Err("updates from a USB stick need the publisher's key".into());
+        }
+        let json = std::fs::read_to_string(folder.join("manifest.json")).map_err(|e| e.to_string())?;
+        let signature = std::fs::read_to_string(folder.join("manifest.json.sig")).unwrap_or_default();
+        (Source::Folder(folder), json, signature)
+    } else {
+        let url = config.url.trim();
+        if url.is_empty() {
+            return Err("no address to check".into());
+        }
+        let json = download::get_text(url)?;
+        let signature = if config.key.trim().is_empty() {
+            String::new()
+        } else {
+            download::get_text(&format!("{}.sig", url))?
+        };
+        (Source::Web(url.to_string()), json, signature)
+    };
+    if !config.key.trim().is_empty() {
+        download::verify_signature(json.as_bytes(), &signature, &config.key)
+            .map_err(|e| format!("the manifest isn't signed by the publisher: {}", e))?;
+    }
+    let manifest: Manifest = serde_json::from_str(&json).map_err(|e| format!("the manifest can't be read: {}", e))?;
+    let installed = installed();
+    let releases = manifest
+        .apps
+        .into_iter()
+        .filter(|r| {
+            if !r.is_valid() {
+                crate::log::warn!("left out update \"{}\": no checksum or a bad name", r.binary);
+            }
+            r.is_valid()
+        })
+        .filter(|r| {
+            let current = installed.get(&r.binary).map_or(env!("CARGO_PKG_VERSION"), String::as_str);
+            is_newer(&r.version, current)
+        })
+        .collect();
+    Ok(Available { source, releases })
+}
+
+/// Updates downloaded and waiting for their app's next launch: binary → version
+pub fn staged() -> BTreeMap<String, String> {
+    read_json("staged.json")
+}
+
+/// Download (or copy) `release` from `source` and stage it for the next
+/// launch. Slow: call off the UI thread.
+pub fn stage(source: &Source, release: &Release) -> Result<(), String> {
+    if !release.is_valid() {
+        return Err("the manifest gives no checksum for it".into());
+    }
+    let dir = staged_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let part = dir.join(format!("{}.part", release.binary));
+    let result = fetch(source, &release.file, &part, &release.sha256).and_then(|_| {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&part, std::fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+        }
+        std::fs::rename(&part, dir.join(&release.binary)).map_err(|e| e.to_string())
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&part);
+        return result;
+    }
+    let mut staged = staged();
+    staged.insert(release.binary.clone(), release.version.clone());
+    write_json("staged.json", &staged);
+    Ok(())
+}
+
+fn fetch(source: &Source, file: &str, dest: &Path, sha256: &str) -> Result<(), String> {
+    match source {
+        Source::Folder(folder) => {
+            let from = folder.join(file);
+            if !from.starts_with(folder) || file.contains("..") {
+                return Err("the manifest points outside the update folder".into());
+            }
+            download::fetch(Origin::File(&from), dest, MAX_BINARY, Some(sha256))
+        }
+        Source::Web(manifest) => download::fetch(Origin::Web(&download::resolve(manifest, file)), dest, MAX_BINARY, Some(sha256)),
+    }
+}
+
+/// Throw away a staged update
+pub fn unstage(binary: &str) {
+    let _ = std::fs::remove_file(staged_dir().join(binary));
+    let mut staged = staged();
+    staged.remove(binary);
+    write_json("staged.json", &staged);
+}
+
+/// Used by slowDesktop as it launches `binary`: swap in a staged update.
+/// Returns the version swapped in.
+pub fn apply_staged(binary: &str) -> Option<String> {
+    let mut staged = staged();
+    let version = staged.remove(binary)?;
+    let from = staged_dir().join(binary);
+    let bin = bin_dir();
+    let _ = std::fs::create_dir_all(&bin);
+    // A running copy keeps the old file open, so this is safe mid-use
+    let moved = std::fs::rename(&from, bin.join(binary)).is_ok();
+    write_json("staged.json", &staged);
+    if !moved {
+        return None;
+    }
+    let mut installed = installed();
+    installed.insert(binary.to_string(), version.clone());
+    write_json("installed.json", &installed);
+    Some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_and_manifest() {
+        assert!(is_newer("0.2.3", "0.2.2"));
+        assert!(is_newer("0.10.0", "0.9.9"));
+        assert!(is_newer("1.0", "0.9.9"));
+        assert!(!is_newer("0.2.2", "0.2.2"));
+        assert!(!is_newer("0.2", "0.2.0"));
+        assert!(!is_newer("0.2.1", "0.2.2"));
+
+        let manifest: Manifest = serde_json::from_str(
+            r#"{"apps": [{"binary": "slowwrite", "version": "0.2.3", "file": "slowwrite", "changes": ["faster"]},
+                         {"binary": "slowpaint", "version": "0.2.3", "file": "slowpaint", "sha256": "ab12"},
+                         {"binary": "../bin/sh", "version": "0.2.3", "file": "sh", "sha256": "ab12"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.apps[0].display_name(), "slowwrite");
+        // No checksum, or a path for a name, and it's left out
+        let valid: Vec<bool> = manifest.apps.iter().map(Release::is_valid).collect();
+        assert_eq!(valid, [false, true, false]);
+    }
+}
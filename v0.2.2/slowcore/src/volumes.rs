@@ -0,0 +1,255 @@
+//! Removable drives — USB sticks and SD cards the system has mounted
+//!
+//! Read from /proc/mounts: anything mounted under /media, /run/media or
+//! /mnt counts as removable. slowDesktop mounts USB drives under /media as
+//! they're plugged in (`automount`); any app can eject one.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// Where removable drives get mounted
+const REMOVABLE_ROOTS: &[&str] = &["/media/", "/run/media/", "/mnt/"];
+
+/// Where `automount` puts drives
+const MOUNT_ROOT: &str = "/media";
+
+/// How often the watchers look for drives coming and going
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Volume {
+    /// The drive's label, or its mount folder's name
+    pub name: String,
+    pub mount_point: PathBuf,
+    /// "/dev/sda1"
+    pub device: String,
+}
+
+/// Mounted removable drives, by name
+pub fn removable() -> Vec<Volume> {
+    std::fs::read_to_string("/proc/mounts")
+        .map(|mounts| parse_mounts(&mounts))
+        .unwrap_or_default()
+}
+
+fn parse_mounts(mounts: &str) -> Vec<Volume> {
+    let mut volumes: Vec<Volume> = mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = unescape(fields.next()?);
+            let mount_point = unescape(fields.next()?);
+            if !device.starts_with("/dev/") || !REMOVABLE_ROOTS.iter().any(|root| mount_point.starts_with(root)) {
+                return None;
+            }
+            let mount_point = PathBuf::from(mount_point);
+            let name = mount_point.file_name()?.to_string_lossy().to_string();
+            Some(Volume { name, mount_point, device })
+        })
+        .collect();
+    volumes.sort_by_key(|v| v.name.to_lowercase());
+    volumes
+}
+
+/// /proc/mounts writes spaces and tabs in names as octal escapes ("\040")
+fn unescape(field: &str) -> String {
+    let mut out = String::new();
+    let mut rest = field;
+    while let Some(i) = rest.find('\\') {
+        out.push_str(&rest[..i]);
+        let code = rest.get(i + 1..i + 4).and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match code {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[i + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The removable drive `path` is on, if any
+pub fn volume_for(path: &Path) -> Option<Volume> {
+    removable().into_iter().filter(|v| path.starts_with(&v.mount_point)).max_by_key(|v| v.mount_point.as_os_str().len())
+}
+
+/// Wake `ctx` whenever a drive is mounted or unmounted
+pub fn watch(ctx: egui::Context) {
+    let _ = std::thread::Builder::new()
+        .name("slowcore-volumes".into())
+        .spawn(move || {
+            let mut last = removable();
+            loop {
+                std::thread::sleep(WATCH_INTERVAL);
+                let now = removable();
+                if now != last {
+                    last = now;
+                    ctx.request_repaint();
+                }
+            }
+        });
+}
+
+/// Mount USB drives under /media as they're plugged in. Only slowDesktop
+/// calls this. A drive that was ejected but is still plugged in isn't
+/// mounted again until it's pulled out and put back.
+pub fn automount() {
+    let _ = std::thread::Builder::new()
+        .name("slowcore-automount".into())
+        .spawn(|| {
+            let mut known: HashSet<String> = HashSet::new();
+            loop {
+                let present = usb_partitions();
+                let mounted: HashSet<String> = removable().into_iter().map(|v| v.device).collect();
+                for device in present.iter().filter(|d| !known.contains(*d) && !mounted.contains(*d)) {
+                    if let Err(e) = mount(device) {
+                        crate::log::warn!("couldn't mount {}: {}", device, e);
+                    }
+                }
+                known = present.into_iter().collect();
+                std::thread::sleep(WATCH_INTERVAL);
+            }
+        });
+}
+
+/// Partitions on USB disks ("/dev/sda1"), or the whole disk when it has no
+/// partition table
+fn usb_partitions() -> Vec<String> {
+    let Ok(disks) = std::fs::read_dir("/sys/block") else { return Vec::new() };
+    let mut devices = Vec::new();
+    for disk in disks.flatten() {
+        let name = disk.file_name().to_string_lossy().to_string();
+        let on_usb = std::fs::canonicalize(disk.path())
+            .map(|p| p.to_string_lossy().contains("/usb"))
+            .unwrap_or(false);
+        if !on_usb {
+            continue;
+        }
+        let mut partitions: Vec<String> = std::fs::read_dir(disk.path())
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.file_name().to_string_lossy().to_string())
+                    .filter(|p| p.starts_with(&name))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if partitions.is_empty() {
+            partitions.push(name);
+        }
+        partitions.sort();
+        devices.extend(partitions.into_iter().map(|p| format!("/dev/{}", p)));
+    }
+    devices
+}
+
+/// The device's filesystem label, from the links eudev makes
+fn label_of(device: &str) -> Option<String> {
+    let device = Path::new(device);
+    std::fs::read_dir("/dev/disk/by-label").ok()?.flatten().find_map(|link| {
+        let target = std::fs::canonicalize(link.path()).ok()?;
+        (target == device).then(|| unescape_label(&link.file_name().to_string_lossy()))
+    })
+}
+
+/// udev writes unsafe characters in label links as hex escapes ("\x20")
+fn unescape_label(name: &str) -> String {
+    let mut out = String::new();
+    let mut rest = name;
+    while let Some(i) = rest.find("\\x") {
+        out.push_str(&rest[..i]);
+        match rest.get(i + 2..i + 4).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[i + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn mount(device: &str) -> Result<PathBuf, String> {
+    let fallback = device.trim_start_matches("/dev/").to_string();
+    let name = label_of(device).filter(|l| !l.trim().is_empty() && !l.contains('/')).unwrap_or(fallback);
+    // Two drives with the same label get "STICK" and "STICK 2"
+    let taken: HashSet<PathBuf> = removable().into_iter().map(|v| v.mount_point).collect();
+    let mut mount_point = Path::new(MOUNT_ROOT).join(&name);
+    let mut n = 2;
+    while taken.contains(&mount_point) {
+        mount_point = Path::new(MOUNT_ROOT).join(format!("{} {}", name, n));
+        n += 1;
+    }
+    std::fs::create_dir_all(&mount_point).map_err(|e| e.to_string())?;
+    let output = Command::new("mount").arg(device).arg(&mount_point).output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let _ = std::fs::remove_dir(&mount_point);
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(mount_point)
+}
+
+/// Safely remove a drive: write out anything still cached, unmount it, and
+/// once nothing else on the disk is mounted, power it down so it can be
+/// pulled out.
+pub fn eject(volume: &Volume) -> Result<(), String> {
+    let _ = Command::new("sync").status();
+    let output = Command::new("umount").arg(&volume.mount_point).output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(if stderr.contains("busy") {
+            format!("{} is in use — close files on it and try again", volume.name)
+        } else {
+            stderr.trim().to_string()
+        });
+    }
+    if volume.mount_point.starts_with(MOUNT_ROOT) {
+        let _ = std::fs::remove_dir(&volume.mount_point);
+    }
+
+    let Some(disk) = disk_of(&volume.device) else { return Ok(()) };
+    let still_mounted = removable().iter().any(|v| disk_of(&v.device).as_deref() == Some(disk.as_str()));
+    if !still_mounted {
+        // Best effort; the drive is safe to pull out either way
+        let _ = std::fs::write(format!("/sys/block/{}/device/delete", disk), "1");
+    }
+    Ok(())
+}
+
+/// "sda" for "/dev/sda1" (and for "/dev/sda")
+fn disk_of(device: &str) -> Option<String> {
+    let name = device.strip_prefix("/dev/")?;
+    let sys = std::fs::canonicalize(Path::new("/sys/class/block").join(name)).ok()?;
+    let disk = if sys.join("partition").exists() { sys.parent()? } else { &sys };
+    Some(disk.file_name()?.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mounts() {
+        let mounts = "/dev/root / ext4 rw 0 0\n\
+                      proc /proc proc rw 0 0\n\
+                      /dev/sda1 /media/pi/MY\\040STICK vfat rw 0 0\n\
+                      tmpfs /run/media/tmp tmpfs rw 0 0\n";
+        let volumes = parse_mounts(mounts);
+        assert_eq!(volumes.len(), 1);
+        assert_eq!(volumes[0].name, "MY STICK");
+        assert_eq!(volumes[0].mount_point, PathBuf::from("/media/pi/MY STICK"));
+        assert_eq!(volumes[0].device, "/dev/sda1");
+        assert_eq!(unescape_label("MY\\x20STICK"), "MY STICK");
+    }
+}
@@ -1,8 +1,17 @@
 //! Custom widgets — pure black and white, dithered overlays
+//!
+//! Every widget here can be reached and used from the keyboard: the one
+//! with focus gets a thick black ring (`dither::draw_focus_ring`), and
+//! Enter/Space activates it.
 
-use egui::{Response, Ui, Widget};
-use crate::theme::SlowColors;
+use egui::{Event, Key, Modifiers, Response, Ui, Widget};
+use crate::theme::{ui_scale, SlowColors};
 use crate::dither;
+use crate::storage::{self, FileBrowser};
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 /// Action returned by window control buttons
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -17,69 +26,66 @@ pub enum WindowAction {
 ///
 /// Returns the action the user clicked (Close, Minimize, or None).
 pub fn window_control_buttons(ui: &mut Ui) -> WindowAction {
-    let h = ui.spacing().interact_size.y;
-    let btn_size = egui::vec2(h, h);
+    control_buttons(ui, false)
+}
+
+/// Like [`window_control_buttons`], plus a pin that keeps the window above
+/// other windows. For small utilities (slowClock, calculator) that float
+/// over documents.
+pub fn window_control_buttons_with_pin(ui: &mut Ui) -> WindowAction {
+    control_buttons(ui, true)
+}
+
+fn control_buttons(ui: &mut Ui, pin: bool) -> WindowAction {
     let mut action = WindowAction::None;
 
     // Close button [X]
-    let (close_rect, close_resp) = ui.allocate_exact_size(btn_size, egui::Sense::click());
-    if ui.is_rect_visible(close_rect) {
-        let painter = ui.painter();
-        painter.rect_filled(close_rect, 0.0, SlowColors::WHITE);
-        painter.rect_stroke(close_rect, 0.0, egui::Stroke::new(1.0, SlowColors::BLACK));
-        if close_resp.hovered() {
-            dither::draw_dither_hover(painter, close_rect);
-        }
-        // Draw X
-        let m = (h * 0.25).round();
-        painter.line_segment(
-            [
-                close_rect.left_top() + egui::vec2(m, m),
-                close_rect.right_bottom() - egui::vec2(m, m),
-            ],
-            egui::Stroke::new(1.0, SlowColors::BLACK),
-        );
-        painter.line_segment(
-            [
-                close_rect.right_top() + egui::vec2(-m, m),
-                close_rect.left_bottom() + egui::vec2(m, -m),
-            ],
-            egui::Stroke::new(1.0, SlowColors::BLACK),
-        );
-    }
-    if close_resp.clicked() {
+    let close = control_button(ui, |painter, rect, m| {
+        let stroke = egui::Stroke::new(1.0, SlowColors::BLACK);
+        painter.line_segment([rect.left_top() + egui::vec2(m, m), rect.right_bottom() - egui::vec2(m, m)], stroke);
+        painter.line_segment([rect.right_top() + egui::vec2(-m, m), rect.left_bottom() + egui::vec2(m, -m)], stroke);
+    });
+    if close.clicked() {
         action = WindowAction::Close;
     }
 
     ui.add_space(2.0);
 
     // Minimize button [-]
-    let (min_rect, min_resp) = ui.allocate_exact_size(btn_size, egui::Sense::click());
-    if ui.is_rect_visible(min_rect) {
-        let painter = ui.painter();
-        painter.rect_filled(min_rect, 0.0, SlowColors::WHITE);
-        painter.rect_stroke(min_rect, 0.0, egui::Stroke::new(1.0, SlowColors::BLACK));
-        if min_resp.hovered() {
-            dither::draw_dither_hover(painter, min_rect);
-        }
-        // Draw horizontal dash
-        let m = (h * 0.25).round();
+    let minimize = control_button(ui, |painter, rect, m| {
         painter.line_segment(
-            [
-                egui::pos2(min_rect.left() + m, min_rect.center().y),
-                egui::pos2(min_rect.right() - m, min_rect.center().y),
-            ],
+            [egui::pos2(rect.left() + m, rect.center().y), egui::pos2(rect.right() - m, rect.center().y)],
             egui::Stroke::new(1.0, SlowColors::BLACK),
         );
-    }
-    if min_resp.clicked() {
+    });
+    if minimize.clicked() {
         action = WindowAction::Minimize;
     }
 
+    // Keep-on-top pin: a pushpin head, filled while pinned
+    if pin {
+        ui.add_space(2.0);
+        let pinned = keep_on_top(ui.ctx());
+        let resp = control_button(ui, |painter, rect, m| {
+            let head = egui::pos2(rect.center().x, rect.top() + m + 2.0);
+            let stroke = egui::Stroke::new(1.0, SlowColors::BLACK);
+            painter.line_segment([head, egui::pos2(head.x, rect.bottom() - m)], stroke);
+            if pinned {
+                painter.circle_filled(head, m * 0.8, SlowColors::BLACK);
+            } else {
+                painter.circle(head, m * 0.8, SlowColors::WHITE, stroke);
+            }
+        });
+        let resp = resp.on_hover_text(if pinned { "stop keeping on top" } else { "keep on top" });
+        if resp.clicked() {
+            set_keep_on_top(ui.ctx(), !pinned);
+        }
+    }
+
     ui.add_space(4.0);
 
     // Thin vertical separator after the buttons
-    let sep_height = h;
+    let sep_height = ui.spacing().interact_size.y;
     let (sep_rect, _) = ui.allocate_exact_size(egui::vec2(4.0, sep_height), egui::Sense::hover());
     if ui.is_rect_visible(sep_rect) {
         ui.painter().vline(
@@ -94,6 +100,73 @@ pub fn window_control_buttons(ui: &mut Ui) -> WindowAction {
     action
 }
 
+/// One square window control: white with a 1px outline, dithered on
+/// hover. `glyph` gets the painter, the button rect and a margin.
+fn control_button(ui: &mut Ui, glyph: impl FnOnce(&egui::Painter, egui::Rect, f32)) -> Response {
+    let h = ui.spacing().interact_size.y;
+    let (rect, resp) = ui.allocate_exact_size(egui::vec2(h, h), egui::Sense::click());
+    if ui.is_rect_visible(rect) {
+        let painter = ui.painter();
+        painter.rect_filled(rect, 0.0, SlowColors::WHITE);
+        painter.rect_stroke(rect, 0.0, egui::Stroke::new(1.0, SlowColors::BLACK));
+        if resp.hovered() {
+            dither::draw_dither_hover(painter, rect);
+        }
+        glyph(painter, rect, (h * 0.25).round());
+    }
+    if resp.has_focus() {
+        dither::draw_focus_ring(ui.painter(), rect);
+    }
+    resp
+}
+
+fn keep_on_top_id() -> egui::Id {
+    egui::Id::new("slowcore_keep_on_top")
+}
+
+/// Whether this window is being kept above other windows
+pub fn keep_on_top(ctx: &egui::Context) -> bool {
+    ctx.data(|d| d.get_temp::<bool>(keep_on_top_id())).unwrap_or(false)
+}
+
+/// Keep this window above other windows, or stop doing so
+pub fn set_keep_on_top(ctx: &egui::Context, on: bool) {
+    ctx.data_mut(|d| d.insert_temp(keep_on_top_id(), on));
+    let level = if on { egui::WindowLevel::AlwaysOnTop } else { egui::WindowLevel::Normal };
+    ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+}
+
+fn shade_id() -> egui::Id {
+    egui::Id::new("slowcore_window_shade")
+}
+
+/// Whether the window is rolled up to its menu bar
+pub fn is_shaded(ctx: &egui::Context) -> bool {
+    ctx.data(|d| d.get_temp::<f32>(shade_id())).is_some()
+}
+
+/// Window shade, as on the classic Mac: roll the window up so only the
+/// bar ending at `bar_bottom` shows, or unroll it to its old height.
+/// `menu_bar` calls this when its empty space is double-clicked.
+///
+/// Apps with a minimum window size only roll up as far as the window
+/// manager allows.
+pub fn toggle_window_shade(ctx: &egui::Context, bar_bottom: f32) {
+    let Some(inner) = ctx.input(|i| i.viewport().inner_rect) else { return };
+    match ctx.data(|d| d.get_temp::<f32>(shade_id())) {
+        Some(height) => {
+            ctx.data_mut(|d| d.remove::<f32>(shade_id()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(inner.width(), height)));
+        }
+        None => {
+            ctx.data_mut(|d| d.insert_temp(shade_id(), inner.height()));
+            // Plus the panel's bottom margin and separator line
+            let rolled = (bar_bottom - ctx.screen_rect().top()).ceil() + 3.0;
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(inner.width(), rolled)));
+        }
+    }
+}
+
 /// A button: white bg, 1px outline. dithered when pressed/selected.
 pub struct SlowButton<'a> {
     text: &'a str,
@@ -114,10 +187,11 @@ impl<'a> SlowButton<'a> {
 impl<'a> Widget for SlowButton<'a> {
     fn ui(self, ui: &mut Ui) -> Response {
         // Calculate button size based on text content
+        let font = egui::FontId::proportional(14.0 * ui_scale());
         let text_size = ui.fonts(|f| {
-            f.glyph_width(&egui::FontId::proportional(14.0), ' ') * self.text.len() as f32
+            f.glyph_width(&font, ' ') * self.text.len() as f32
         });
-        let padding = egui::vec2(16.0, 4.0);
+        let padding = egui::vec2(16.0, 4.0) * ui_scale();
         let desired_size = egui::vec2(
             text_size + padding.x * 2.0,
             ui.spacing().interact_size.y,
@@ -142,9 +216,13 @@ impl<'a> Widget for SlowButton<'a> {
                 rect.center(),
                 egui::Align2::CENTER_CENTER,
                 self.text,
-                egui::FontId::proportional(14.0),
+                font,
                 if pressed { SlowColors::WHITE } else { SlowColors::BLACK },
             );
+
+            if response.has_focus() {
+                dither::draw_focus_ring(painter, rect);
+            }
         }
 
         response
@@ -170,14 +248,144 @@ pub fn status_bar(ui: &mut Ui, text: &str) {
     egui::Frame::none()
         .fill(SlowColors::WHITE)
         .stroke(egui::Stroke::new(1.0, SlowColors::BLACK))
-        .inner_margin(egui::Margin::symmetric(8.0, 2.0))
+        .inner_margin(egui::Margin::symmetric(8.0 * ui_scale(), 2.0 * ui_scale()))
         .show(ui, |ui| {
             ui.label(text);
         });
 }
 
+/// Title bar for a document window: its title, centred, after a proxy icon
+/// for the document and followed by a dot while it has unsaved changes.
+///
+/// ⌘-click the title for the folders the document is in; choosing one
+/// opens it in slowFiles. Drag the proxy icon onto slowFiles or another app
+/// to hand it the document, as if dragged from slowFiles. Untitled
+/// documents (`path` is `None`) have no icon.
+pub fn document_title_bar(ui: &mut Ui, title: &str, path: Option<&Path>, modified: bool) {
+    crate::theme::SlowTheme::title_bar_frame().show(ui, |ui| {
+        let s = ui_scale();
+        let id = ui.id().with("slowcore_document_title");
+        let font = egui::TextStyle::Body.resolve(ui.style());
+        let galley = ui.painter().layout_no_wrap(title.to_string(), font, SlowColors::BLACK);
+        let icon_size = egui::vec2(9.0 * s, 12.0 * s);
+        let gap = 5.0 * s;
+        let dot_radius = 2.5 * s;
+
+        let icon_w = if path.is_some() { icon_size.x + gap } else { 0.0 };
+        let dot_w = if modified { gap + dot_radius * 2.0 } else { 0.0 };
+        let height = galley.size().y.max(icon_size.y);
+        let (bar, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), height), egui::Sense::hover());
+        let left = bar.center().x - (icon_w + galley.size().x + dot_w) / 2.0;
+        let title_rect = egui::Rect::from_min_size(
+            egui::pos2(left + icon_w, bar.center().y - galley.size().y / 2.0),
+            galley.size(),
+        );
+        let painter = ui.painter();
+        painter.galley(title_rect.min, galley, SlowColors::BLACK);
+        if modified {
+            let center = egui::pos2(title_rect.right() + gap + dot_radius, bar.center().y);
+            painter.circle_filled(center, dot_radius, SlowColors::BLACK);
+        }
+
+        let Some(path) = path else { return };
+        let icon_rect = egui::Rect::from_center_size(egui::pos2(left + icon_size.x / 2.0, bar.center().y), icon_size);
+        draw_document_icon(painter, icon_rect);
+
+        // Proxy icon: drag the document out
+        let icon = ui
+            .interact(icon_rect, id.with("proxy"), egui::Sense::drag())
+            .on_hover_text(path.display().to_string())
+            .on_hover_cursor(egui::CursorIcon::Grab);
+        if icon.drag_started() {
+            crate::drag::start_drag(&[path.to_path_buf()]);
+        }
+        if icon.dragged() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+            if let Some(pointer) = ui.ctx().pointer_interact_pos() {
+                let layer = egui::LayerId::new(egui::Order::Tooltip, id.with("proxy_drag"));
+                let ghost = egui::Rect::from_center_size(pointer + egui::vec2(8.0, 8.0) * s, icon_size);
+                draw_document_icon(&ui.ctx().layer_painter(layer), ghost);
+            }
+        }
+        // Dropped on this window: nothing to hand over. Anywhere else, the
+        // app under the pointer takes it on its own release.
+        if icon.drag_stopped() && ui.input(|i| i.pointer.has_pointer()) {
+            crate::drag::end_drag();
+        }
+
+        // ⌘-click: the folders the document is in
+        let popup_id = id.with("folders");
+        let title_resp = ui.interact(title_rect, id.with("title"), egui::Sense::click());
+        if title_resp.clicked() && ui.input(|i| i.modifiers.command) {
+            ui.memory_mut(|m| m.toggle_popup(popup_id));
+        }
+        if !ui.memory(|m| m.is_popup_open(popup_id)) {
+            return;
+        }
+        let area = egui::Area::new(popup_id)
+            .order(egui::Order::Foreground)
+            .constrain(true)
+            .fixed_pos(title_rect.center_bottom() + egui::vec2(0.0, 4.0 * s))
+            .pivot(egui::Align2::CENTER_TOP)
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui| {
+                        ui.label(path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default());
+                        for dir in path.ancestors().skip(1).filter(|d| !d.as_os_str().is_empty()) {
+                            let name = dir.file_name().map(|n| n.to_string_lossy().into_owned());
+                            if ui.button(name.unwrap_or_else(|| dir.display().to_string())).clicked() {
+                                show_in_files(dir);
+                                ui.memory_mut(|m| m.close_popup());
+                            }
+                        }
+                    });
+                });
+            });
+        dither::draw_window_shadow(ui.ctx(), area.response.rect);
+        if ui.input(|i| i.key_pressed(Key::Escape)) || title_resp.clicked_elsewhere() {
+            ui.memory_mut(|m| m.close_popup());
+        }
+    });
+}
+
+/// A page with its top right corner folded over
+fn draw_document_icon(painter: &egui::Painter, rect: egui::Rect) {
+    let fold = rect.width() * 0.4;
+    let stroke = egui::Stroke::new(1.0, SlowColors::BLACK);
+    let outline = vec![
+        rect.left_top(),
+        rect.right_top() - egui::vec2(fold, 0.0),
+        rect.right_top() + egui::vec2(0.0, fold),
+        rect.right_bottom(),
+        rect.left_bottom(),
+    ];
+    painter.add(egui::Shape::convex_polygon(outline, SlowColors::WHITE, stroke));
+    painter.line_segment(
+        [rect.right_top() - egui::vec2(fold, 0.0), rect.right_top() + egui::vec2(-fold, fold)],
+        stroke,
+    );
+    painter.line_segment(
+        [rect.right_top() + egui::vec2(-fold, fold), rect.right_top() + egui::vec2(0.0, fold)],
+        stroke,
+    );
+}
+
+/// Open a folder in slowFiles
+fn show_in_files(dir: &Path) {
+    let sibling = std::env::current_exe().ok().and_then(|e| e.parent().map(|p| p.join("slowfiles")));
+    let binary = sibling.filter(|p| p.exists()).unwrap_or_else(|| PathBuf::from("slowfiles"));
+    let _ = std::process::Command::new(binary)
+        .arg(dir)
+        .env("SLOWOS_MANAGED", "1")
+        .stdin(std::process::Stdio::null())
+        .spawn();
+}
+
 /// File list item for open/save dialogs.
 /// Selected items get a dithered overlay instead of solid black.
+///
+/// Rows don't take keyboard focus themselves: the [`VirtualList`] they
+/// live in does, and rings its cursor row.
 pub struct FileListItem<'a> {
     name: &'a str,
     is_directory: bool,
@@ -185,6 +393,9 @@ pub struct FileListItem<'a> {
 }
 
 impl<'a> FileListItem<'a> {
+    /// Row height at 100% UI scale
+    pub const HEIGHT: f32 = 20.0;
+
     pub fn new(name: &'a str, is_directory: bool) -> Self {
         Self { name, is_directory, selected: false }
     }
@@ -197,10 +408,12 @@ impl<'a> FileListItem<'a> {
 
 impl<'a> Widget for FileListItem<'a> {
     fn ui(self, ui: &mut Ui) -> Response {
-        let height = 20.0;
+        let s = ui_scale();
+        let height = FileListItem::HEIGHT * s;
+        let sense = egui::Sense { focusable: false, ..egui::Sense::click() };
         let (rect, response) = ui.allocate_exact_size(
             egui::vec2(ui.available_width(), height),
-            egui::Sense::click(),
+            sense,
         );
 
         if ui.is_rect_visible(rect) {
@@ -222,23 +435,23 @@ impl<'a> Widget for FileListItem<'a> {
             // icon
             let icon = if self.is_directory { "📁" } else { "📄" };
             let icon_rect = egui::Rect::from_min_size(
-                rect.min + egui::vec2(4.0, 0.0),
-                egui::vec2(16.0, height),
+                rect.min + egui::vec2(4.0 * s, 0.0),
+                egui::vec2(16.0 * s, height),
             );
             painter.text(
                 icon_rect.center(),
                 egui::Align2::CENTER_CENTER,
                 icon,
-                egui::FontId::proportional(12.0),
+                egui::FontId::proportional(12.0 * s),
                 text_color,
             );
 
             // filename
             painter.text(
-                egui::pos2(rect.min.x + 24.0, rect.center().y),
+                egui::pos2(rect.min.x + 24.0 * s, rect.center().y),
                 egui::Align2::LEFT_CENTER,
                 self.name,
-                egui::FontId::proportional(12.0),
+                egui::FontId::proportional(12.0 * s),
                 text_color,
             );
         }
@@ -246,3 +459,989 @@ impl<'a> Widget for FileListItem<'a> {
         response
     }
 }
+
+/// Typed characters within this window extend the type-ahead prefix
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Selection state for a [`VirtualList`], owned by the caller.
+#[derive(Debug, Clone, Default)]
+pub struct ListSelection {
+    /// Selected row indices
+    pub rows: BTreeSet<usize>,
+    /// Row keyboard navigation moves from (last clicked or moved-to row)
+    pub cursor: Option<usize>,
+    /// Fixed end of a shift-extended range
+    anchor: Option<usize>,
+    typed: String,
+    last_typed: Option<Instant>,
+    scroll_to_cursor: bool,
+    /// Rows visible last frame, for scrolling the cursor into view
+    visible: Range<usize>,
+}
+
+impl ListSelection {
+    /// The row actions apply to: the cursor if selected, else the first selected row
+    pub fn primary(&self) -> Option<usize> {
+        match self.cursor {
+            Some(c) if self.rows.contains(&c) => Some(c),
+            _ => self.rows.iter().next().copied(),
+        }
+    }
+
+    pub fn is_selected(&self, row: usize) -> bool {
+        self.rows.contains(&row)
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+        self.cursor = None;
+        self.anchor = None;
+    }
+
+    /// Select only `row` and move the cursor to it
+    pub fn select_only(&mut self, row: usize) {
+        self.rows.clear();
+        self.rows.insert(row);
+        self.cursor = Some(row);
+        self.anchor = Some(row);
+        self.scroll_to_cursor = true;
+    }
+
+    /// Add or remove `row` from the selection (⌘-click)
+    pub fn toggle(&mut self, row: usize) {
+        if !self.rows.remove(&row) {
+            self.rows.insert(row);
+        }
+        self.cursor = Some(row);
+        self.anchor = Some(row);
+    }
+
+    /// Select everything between the anchor and `row` (shift-click)
+    pub fn extend_to(&mut self, row: usize) {
+        let anchor = self.anchor.unwrap_or(row);
+        self.rows = (anchor.min(row)..=anchor.max(row)).collect();
+        self.cursor = Some(row);
+        self.anchor = Some(anchor);
+        self.scroll_to_cursor = true;
+    }
+
+    /// Add `typed` to the type-ahead prefix (started afresh after a pause)
+    /// and select the first row whose label starts with it. True if a row
+    /// was found.
+    fn type_ahead<'s>(&mut self, typed: &str, now: Instant, count: usize, label: impl Fn(usize) -> &'s str) -> bool {
+        if !matches!(self.last_typed, Some(t) if now.duration_since(t) <= TYPE_AHEAD_TIMEOUT) {
+            self.typed.clear();
+        }
+        self.last_typed = Some(now);
+        self.typed.push_str(&typed.to_lowercase());
+
+        // Repeating one letter ("sss") cycles through rows starting with it
+        let prefix = self.typed.clone();
+        let mut chars = prefix.chars();
+        let first = chars.next().unwrap_or_default();
+        let cycling = prefix.chars().count() > 1 && chars.all(|c| c == first);
+        let (needle, start) = if cycling {
+            (first.to_string(), self.cursor.map_or(0, |c| c + 1))
+        } else {
+            (prefix, 0)
+        };
+        let found = (0..count)
+            .map(|n| (start + n) % count)
+            .find(|&r| label(r).to_lowercase().starts_with(&needle));
+        if let Some(r) = found {
+            self.select_only(r);
+        }
+        found.is_some()
+    }
+
+    /// Keep the selection valid after the list shrank to `count` rows
+    pub fn clamp(&mut self, count: usize) {
+        self.rows.retain(|&r| r < count);
+        if self.cursor.is_some_and(|c| c >= count) {
+            self.cursor = count.checked_sub(1);
+        }
+        if self.anchor.is_some_and(|a| a >= count) {
+            self.anchor = self.cursor;
+        }
+    }
+}
+
+/// What happened in a [`VirtualList`] this frame
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VirtualListResponse {
+    /// Row opened by double-click or Enter
+    pub activated: Option<usize>,
+    /// The selection changed
+    pub changed: bool,
+}
+
+/// A scrolling list that only lays out the rows on screen.
+///
+/// Handles click, ⌘-click and shift-click selection, keyboard navigation
+/// (arrows, Page Up/Down, Home/End, Enter) once the list has focus (by
+/// clicking a row or tabbing to it), and type-ahead: typing a name jumps
+/// to the first row starting with it.
+///
+/// ```ignore
+/// let entries = &self.browser.entries;
+/// let list = VirtualList::new("files").show(
+///     ui, &mut self.browser.selection, entries.len(),
+///     |i| entries[i].name.as_str(),
+///     |ui, i, selected| ui.add(FileListItem::new(&entries[i].name, entries[i].is_directory).selected(selected)),
+/// );
+/// if let Some(i) = list.activated { /* open entries[i] */ }
+/// ```
+pub struct VirtualList {
+    id_source: egui::Id,
+    row_height: f32,
+    max_height: f32,
+    multi_select: bool,
+    request_focus: bool,
+}
+
+impl VirtualList {
+    pub fn new(id_source: impl std::hash::Hash) -> Self {
+        Self {
+            id_source: egui::Id::new(id_source),
+            row_height: FileListItem::HEIGHT * ui_scale(),
+            max_height: 220.0,
+            multi_select: false,
+            request_focus: false,
+        }
+    }
+
+    /// Height of every row; must match what the row closure allocates
+    /// (defaults to a scaled `FileListItem` row)
+    pub fn row_height(mut self, height: f32) -> Self {
+        self.row_height = height;
+        self
+    }
+
+    /// Maximum list height at 100% UI scale
+    pub fn max_height(mut self, height: f32) -> Self {
+        self.max_height = height;
+        self
+    }
+
+    /// Allow ⌘-click, shift-click and shift-arrow selection of several rows
+    pub fn multi_select(mut self, multi: bool) -> Self {
+        self.multi_select = multi;
+        self
+    }
+
+    /// Give the list keyboard focus this frame (e.g. when a dialog opens)
+    pub fn request_focus(mut self, focus: bool) -> Self {
+        self.request_focus = focus;
+        self
+    }
+
+    /// Show `count` rows. `label` gives each row's text for type-ahead;
+    /// `row` paints a row and returns its (clickable) response.
+    pub fn show<'s>(
+        self,
+        ui: &mut Ui,
+        selection: &mut ListSelection,
+        count: usize,
+        label: impl Fn(usize) -> &'s str,
+        mut row: impl FnMut(&mut Ui, usize, bool) -> Response,
+    ) -> VirtualListResponse {
+        let id = ui.make_persistent_id(self.id_source);
+        let mut out = VirtualListResponse::default();
+        selection.clamp(count);
+
+        // Take part in Tab traversal as a single stop
+        ui.memory_mut(|m| {
+            m.interested_in_focus(id);
+            if self.request_focus {
+                m.request_focus(id);
+            }
+        });
+        let focused = ui.memory(|m| m.has_focus(id));
+
+        if focused && count > 0 {
+            ui.memory_mut(|m| {
+                m.set_focus_lock_filter(id, egui::EventFilter {
+                    vertical_arrows: true,
+                    ..Default::default()
+                })
+            });
+            self.handle_keys(ui, selection, count, &label, &mut out);
+        }
+
+        let row_step = self.row_height + ui.spacing().item_spacing.y;
+        let mut scroll = egui::ScrollArea::vertical()
+            .id_source(id)
+            .max_height(self.max_height * ui_scale())
+            .auto_shrink([false, true]);
+        if std::mem::take(&mut selection.scroll_to_cursor) {
+            if let Some(cursor) = selection.cursor {
+                let visible = selection.visible.clone();
+                if cursor < visible.start {
+                    scroll = scroll.vertical_scroll_offset(cursor as f32 * row_step);
+                } else if cursor + 1 >= visible.end && !visible.is_empty() {
+                    let rows_shown = (visible.end - visible.start).saturating_sub(1).max(1);
+                    let first = (cursor + 1).saturating_sub(rows_shown);
+                    scroll = scroll.vertical_scroll_offset(first as f32 * row_step);
+                }
+            }
+        }
+
+        let mut clicked = None;
+        let mut cursor_rect = None;
+        let scroll_out = scroll.show_rows(ui, self.row_height, count, |ui, range| {
+            selection.visible = range.clone();
+            for i in range {
+                let response = row(ui, i, selection.is_selected(i));
+                if selection.cursor == Some(i) {
+                    cursor_rect = Some(response.rect);
+                }
+                if response.clicked() {
+                    clicked = Some(i);
+                }
+                if response.double_clicked() {
+                    out.activated = Some(i);
+                }
+            }
+        });
+        if focused {
+            // Ring the cursor row, or the whole list before anything is picked
+            let clip = scroll_out.inner_rect;
+            let ring = cursor_rect.map_or(clip, |r| r.intersect(clip).shrink(2.0));
+            dither::draw_focus_ring(ui.painter(), ring);
+        }
+
+        if let Some(i) = clicked {
+            ui.memory_mut(|m| m.request_focus(id));
+            let modifiers = ui.input(|inp| inp.modifiers);
+            if self.multi_select && modifiers.command {
+                selection.toggle(i);
+            } else if self.multi_select && modifiers.shift {
+                selection.extend_to(i);
+            } else {
+                selection.select_only(i);
+            }
+            out.changed = true;
+        }
+        out
+    }
+
+    fn handle_keys<'s>(
+        &self,
+        ui: &mut Ui,
+        selection: &mut ListSelection,
+        count: usize,
+        label: &impl Fn(usize) -> &'s str,
+        out: &mut VirtualListResponse,
+    ) {
+        let page = ((self.max_height * ui_scale() / self.row_height) as usize).max(1);
+        let last = count - 1;
+        let cursor = selection.cursor;
+
+        let mut target = None;
+        let mut extend = false;
+        ui.input_mut(|i| {
+            let shift = self.multi_select && i.modifiers.shift;
+            let moves = [
+                (Key::ArrowDown, cursor.map_or(0, |c| (c + 1).min(last))),
+                (Key::ArrowUp, cursor.map_or(0, |c| c.saturating_sub(1))),
+                (Key::PageDown, cursor.map_or(0, |c| (c + page).min(last))),
+                (Key::PageUp, cursor.map_or(0, |c| c.saturating_sub(page))),
+                (Key::Home, 0),
+                (Key::End, last),
+            ];
+            for (key, to) in moves {
+                let mods = if shift { Modifiers::SHIFT } else { Modifiers::NONE };
+                if i.consume_key(mods, key) {
+                    target = Some(to);
+                    extend = shift;
+                }
+            }
+            if i.consume_key(Modifiers::NONE, Key::Enter) {
+                out.activated = selection.primary();
+            }
+            if self.multi_select && i.consume_key(Modifiers::COMMAND, Key::A) {
+                selection.rows = (0..count).collect();
+                out.changed = true;
+            }
+        });
+
+        if let Some(to) = target {
+            if extend {
+                selection.extend_to(to);
+            } else {
+                selection.select_only(to);
+            }
+            out.changed = true;
+            return;
+        }
+
+        // Type-ahead
+        let typed: String = ui.input(|i| {
+            i.events.iter().filter_map(|e| match e {
+                Event::Text(t) => Some(t.as_str()),
+                _ => None,
+            }).collect()
+        });
+        if typed.is_empty() {
+            return;
+        }
+        if selection.type_ahead(&typed, Instant::now(), count, label) {
+            out.changed = true;
+        }
+    }
+}
+
+/// Whether a [`FileDialog`] picks an existing file or a place to save
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileDialogMode {
+    Open,
+    Save,
+}
+
+/// What a [`FileDialog`] returned this frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileDialogResult {
+    /// Still open — call `show` again next frame
+    Pending,
+    /// Closed without choosing
+    Cancelled,
+    /// A file to open, or a validated (and, if it existed, confirmed) save path
+    Picked(PathBuf),
+}
+
+/// Shared open/save window.
+///
+/// Keep it in an `Option` and poll it every frame:
+///
+/// ```ignore
+/// if let Some(dialog) = &mut self.file_dialog {
+///     match dialog.show(ctx) {
+///         FileDialogResult::Picked(path) => { self.file_dialog = None; self.open_file(path); }
+///         FileDialogResult::Cancelled => self.file_dialog = None,
+///         FileDialogResult::Pending => {}
+///     }
+/// }
+/// ```
+pub struct FileDialog {
+    mode: FileDialogMode,
+    title: String,
+    browser: FileBrowser,
+    filename: String,
+    /// Added to typed save names that have no extension
+    default_extension: Option<String>,
+    /// Name being typed for a new folder (None = not creating one)
+    new_folder: Option<String>,
+    /// Existing file waiting for "replace?" confirmation
+    confirm_overwrite: Option<PathBuf>,
+    error: Option<String>,
+    recent: Vec<PathBuf>,
+    /// Move keyboard focus to the field that matters for the current step
+    focus_pending: bool,
+    /// Label of the confirm button, instead of "open" or "save"
+    action: Option<String>,
+    /// Several files can be picked at once (open only)
+    multi_select: bool,
+    /// Every file picked, once `Picked` has been returned
+    picked: Vec<PathBuf>,
+}
+
+impl FileDialog {
+    /// Pick an existing file, starting in `dir`
+    pub fn open(dir: PathBuf) -> Self {
+        Self::new(FileDialogMode::Open, "open file", dir, String::new())
+    }
+
+    /// Pick a save location, starting in `dir` with `filename` suggested
+    pub fn save(dir: PathBuf, filename: &str) -> Self {
+        Self::new(FileDialogMode::Save, "save file", dir, filename.to_string())
+    }
+
+    fn new(mode: FileDialogMode, title: &str, dir: PathBuf, filename: String) -> Self {
+        Self {
+            mode,
+            title: title.to_string(),
+            browser: FileBrowser::new(dir),
+            filename,
+            default_extension: None,
+            new_folder: None,
+            confirm_overwrite: None,
+            error: None,
+            recent: storage::recent_locations().files.into_iter().filter(|p| p.is_dir()).collect(),
+            focus_pending: true,
+            action: None,
+            multi_select: false,
+            picked: Vec::new(),
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Only list files with these extensions (folders are always listed)
+    pub fn filter(mut self, extensions: &[&str]) -> Self {
+        self.browser = self.browser.with_filter(extensions.iter().map(|e| e.to_string()).collect());
+        self
+    }
+
+    /// Extension added to save names typed without one
+    pub fn default_extension(mut self, ext: &str) -> Self {
+        self.default_extension = Some(ext.trim_start_matches('.').to_string());
+        self
+    }
+
+    /// Label the confirm button `action` ("export", "insert"…)
+    pub fn action(mut self, action: &str) -> Self {
+        self.action = Some(action.to_string());
+        self
+    }
+
+    /// Let several files be picked at once, with shift/⌘-click, ⌘A or
+    /// "select all". `Picked` carries the first; [`Self::picked_files`]
+    /// has them all.
+    pub fn multi_select(mut self, multi: bool) -> Self {
+        self.multi_select = multi;
+        self
+    }
+
+    /// Every file picked, in list order, once `show` has returned `Picked`
+    pub fn picked_files(&self) -> &[PathBuf] {
+        &self.picked
+    }
+
+    pub fn mode(&self) -> FileDialogMode {
+        self.mode
+    }
+
+    /// Folder currently shown
+    pub fn current_dir(&self) -> &std::path::Path {
+        &self.browser.current_dir
+    }
+
+    fn navigate(&mut self, dir: PathBuf) {
+        self.browser.navigate_to(dir);
+        self.new_folder = None;
+        self.error = None;
+    }
+
+    /// Resolve the typed save name into a path, or set an error
+    fn save_target(&mut self) -> Option<PathBuf> {
+        let mut name = self.filename.clone();
+        if let Err(e) = storage::validate_filename(&name) {
+            self.error = Some(e.to_string());
+            return None;
+        }
+        if let Some(ext) = &self.default_extension {
+            if std::path::Path::new(&name).extension().is_none() {
+                name = format!("{}.{}", name, ext);
+            }
+        }
+        let path = self.browser.save_directory().join(name);
+        if path.is_dir() {
+            self.error = Some("a folder with that name exists".into());
+            return None;
+        }
+        Some(path)
+    }
+
+    fn create_folder(&mut self) {
+        let Some(name) = self.new_folder.clone() else { return };
+        if let Err(e) = storage::validate_filename(&name) {
+            self.error = Some(e.to_string());
+            return;
+        }
+        let path = self.browser.current_dir.join(&name);
+        if path.exists() {
+            self.error = Some("that name is already taken".into());
+            return;
+        }
+        match std::fs::create_dir(&path) {
+            Ok(()) => {
+                self.new_folder = None;
+                self.error = None;
+                self.browser.refresh();
+                self.browser.select_by_name(&name);
+            }
+            Err(e) => self.error = Some(format!("couldn't create folder: {}", e)),
+        }
+    }
+
+    /// Finish with `paths`, remembering their folder as a recent location
+    fn pick(&mut self, paths: Vec<PathBuf>) -> FileDialogResult {
+        let Some(first) = paths.first().cloned() else { return FileDialogResult::Pending };
+        if let Some(dir) = first.parent() {
+            storage::add_recent_location(dir);
+        }
+        self.picked = paths;
+        FileDialogResult::Picked(first)
+    }
+
+    /// Confirm button / Enter: open the selection (or the folder it is)
+    /// or validate the save name
+    fn confirm(&mut self) -> FileDialogResult {
+        match self.mode {
+            FileDialogMode::Open => {
+                let files: Vec<PathBuf> = self.browser.selected_entries()
+                    .filter(|e| !e.is_directory)
+                    .map(|e| e.path.clone())
+                    .collect();
+                if !files.is_empty() {
+                    return self.pick(files);
+                }
+                if let Some(entry) = self.browser.selected_entry() {
+                    let path = entry.path.clone();
+                    self.navigate(path);
+                }
+            }
+            FileDialogMode::Save => {
+                if let Some(path) = self.save_target() {
+                    if path.exists() {
+                        self.confirm_overwrite = Some(path);
+                        self.focus_pending = true;
+                    } else {
+                        return self.pick(vec![path]);
+                    }
+                }
+            }
+        }
+        FileDialogResult::Pending
+    }
+
+    /// Draw the dialog. Returns `Picked` or `Cancelled` once, then the
+    /// caller should drop it.
+    pub fn show(&mut self, ctx: &egui::Context) -> FileDialogResult {
+        self.show_with_preview(ctx, |_, _| {})
+    }
+
+    /// [`Self::show`], with `preview` drawing under the list for the file
+    /// highlighted in it (`None` for a folder or nothing), so pictures can
+    /// be told apart by more than name.
+    pub fn show_with_preview(
+        &mut self,
+        ctx: &egui::Context,
+        preview: impl FnOnce(&mut Ui, Option<&Path>),
+    ) -> FileDialogResult {
+        let mut result = FileDialogResult::Pending;
+        let mut open = true;
+
+        let resp = egui::Window::new(self.title.clone())
+            .collapsible(false)
+            .resizable(false)
+            .default_width(380.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if let Some(path) = self.confirm_overwrite.clone() {
+                    result = self.overwrite_ui(ui, path);
+                } else {
+                    result = self.browser_ui(ui, preview);
+                }
+            });
+        if let Some(r) = &resp {
+            dither::draw_window_shadow(ctx, r.response.rect);
+        }
+
+        let escape = ctx.input(|i| i.key_pressed(Key::Escape));
+        if !open || (escape && result == FileDialogResult::Pending) {
+            if escape && (self.confirm_overwrite.is_some() || self.new_folder.is_some()) {
+                self.confirm_overwrite = None;
+                self.new_folder = None;
+                self.focus_pending = true;
+                return FileDialogResult::Pending;
+            }
+            return FileDialogResult::Cancelled;
+        }
+        result
+    }
+
+    fn overwrite_ui(&mut self, ui: &mut Ui, path: PathBuf) -> FileDialogResult {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        ui.label(format!("\u{201c}{}\u{201d} already exists.", name));
+        ui.label("do you want to replace it?");
+        ui.add_space(8.0);
+        let mut result = FileDialogResult::Pending;
+        ui.horizontal(|ui| {
+            // Focus the safe choice so a stray Enter doesn't replace the file
+            let cancel = ui.button("cancel");
+            if std::mem::take(&mut self.focus_pending) {
+                cancel.request_focus();
+            }
+            if cancel.clicked() {
+                self.confirm_overwrite = None;
+                self.focus_pending = true;
+            }
+            if ui.button("replace").clicked() {
+                self.confirm_overwrite = None;
+                result = self.pick(vec![path.clone()]);
+            }
+        });
+        result
+    }
+
+    fn browser_ui(&mut self, ui: &mut Ui, preview: impl FnOnce(&mut Ui, Option<&Path>)) -> FileDialogResult {
+        let mut result = FileDialogResult::Pending;
+
+        ui.horizontal(|ui| {
+            ui.label("location:");
+            ui.label(self.browser.current_dir.to_string_lossy().to_string());
+        });
+        if !self.recent.is_empty() {
+            let mut goto = None;
+            egui::ComboBox::from_id_source("slowcore_file_dialog_recent")
+                .selected_text("recent places")
+                .width(200.0)
+                .show_ui(ui, |ui| {
+                    for dir in &self.recent {
+                        let label = dir.file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_else(|| dir.to_string_lossy().to_string());
+                        if ui.selectable_label(false, label).on_hover_text(dir.to_string_lossy()).clicked() {
+                            goto = Some(dir.clone());
+                        }
+                    }
+                });
+            if let Some(dir) = goto {
+                self.navigate(dir);
+            }
+        }
+        ui.separator();
+
+        let focus_list = self.focus_pending && self.mode == FileDialogMode::Open && self.new_folder.is_none();
+        if focus_list {
+            self.focus_pending = false;
+        }
+        let entries = &self.browser.entries;
+        let list = VirtualList::new("slowcore_file_dialog")
+            .request_focus(focus_list)
+            .multi_select(self.multi_select && self.mode == FileDialogMode::Open)
+            .show(
+                ui,
+                &mut self.browser.selection,
+                entries.len(),
+                |i| entries[i].name.as_str(),
+                |ui, i, selected| ui.add(FileListItem::new(&entries[i].name, entries[i].is_directory).selected(selected)),
+            );
+        if list.changed && self.mode == FileDialogMode::Save {
+            // Clicking a file in a save dialog suggests its name
+            if let Some(entry) = self.browser.selected_entry() {
+                if !entry.is_directory {
+                    self.filename = entry.name.clone();
+                }
+            }
+        }
+        preview(ui, self.browser.selected_entry().filter(|e| !e.is_directory).map(|e| e.path.as_path()));
+        if let Some(entry) = list.activated.and_then(|i| self.browser.entries.get(i)) {
+            let path = entry.path.clone();
+            if entry.is_directory {
+                self.navigate(path);
+            } else if self.mode == FileDialogMode::Open {
+                result = self.confirm();
+            }
+        }
+
+        ui.separator();
+        if let Some(name) = &mut self.new_folder {
+            let mut create = false;
+            ui.horizontal(|ui| {
+                ui.label("folder name:");
+                let r = ui.text_edit_singleline(name);
+                if std::mem::take(&mut self.focus_pending) {
+                    r.request_focus();
+                }
+                if r.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    create = true;
+                }
+                if ui.button("create").clicked() {
+                    create = true;
+                }
+            });
+            if create {
+                self.create_folder();
+            }
+        }
+        if self.mode == FileDialogMode::Save {
+            ui.horizontal(|ui| {
+                ui.label("filename:");
+                let r = ui.text_edit_singleline(&mut self.filename);
+                if std::mem::take(&mut self.focus_pending) {
+                    r.request_focus();
+                }
+                if r.changed() {
+                    self.error = None;
+                }
+                if r.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    result = self.confirm();
+                }
+            });
+        }
+        if let Some(err) = &self.error {
+            ui.label(err.as_str());
+        }
+
+        ui.horizontal(|ui| {
+            if self.new_folder.is_none() && ui.button("new folder").clicked() {
+                self.new_folder = Some(String::new());
+                self.error = None;
+                self.focus_pending = true;
+            }
+            if self.multi_select && self.mode == FileDialogMode::Open && ui.button("select all").clicked() {
+                let files = self.browser.entries.iter().enumerate().filter(|(_, e)| !e.is_directory).map(|(i, _)| i);
+                self.browser.selection.rows = files.collect();
+                self.browser.selection.cursor = self.browser.selection.rows.first().copied();
+            }
+            if ui.button("cancel").clicked() {
+                result = FileDialogResult::Cancelled;
+            }
+            let action = match (&self.action, self.mode) {
+                (Some(action), _) => action.as_str(),
+                (None, FileDialogMode::Open) => "open",
+                (None, FileDialogMode::Save) => "save",
+            };
+            if ui.button(action).clicked() {
+                result = self.confirm();
+            }
+        });
+        result
+    }
+}
+
+/// Show an alert: `body` (one line per `\n`) and a row of `buttons`.
+/// Call every frame while it should be up; returns the index of the
+/// button chosen, on the frame it is chosen.
+///
+/// The last button is the default: it has the focus and answers Enter.
+/// A button labelled "cancel", or the only button, answers Escape. Put
+/// "cancel" first, as in "cancel / delete".
+///
+/// ```ignore
+/// if self.confirm_delete {
+///     match alert(ctx, "delete snapshot", "delete it?\nthis can't be undone.", &["cancel", "delete"]) {
+///         Some(1) => self.delete_selected(),
+///         Some(_) => {}
+///         None => return,
+///     }
+///     self.confirm_delete = false;
+/// }
+/// ```
+pub fn alert(ctx: &egui::Context, title: &str, body: &str, buttons: &[&str]) -> Option<usize> {
+    show_alert(ctx, title, body, buttons, false)
+}
+
+/// An alert for something that went wrong, with a warning sign and "ok".
+/// Returns true once dismissed.
+pub fn error_dialog(ctx: &egui::Context, title: &str, message: &str) -> bool {
+    show_alert(ctx, title, message, &["ok"], true).is_some()
+}
+
+/// What an app's about window says. Lines are given without indent; the
+/// dialog lays them out.
+///
+/// ```ignore
+/// const ABOUT: AboutInfo = AboutInfo {
+///     name: "slowView",
+///     version: env!("CARGO_PKG_VERSION"),
+///     description: "image and PDF viewer for slowOS",
+///     formats: &["PNG, JPEG, GIF, BMP, TIFF, WebP, PDF"],
+///     credits: &["egui/eframe (MIT), image-rs (MIT)"],
+///     ..AboutInfo::EMPTY
+/// };
+///
+/// if self.show_about && about_dialog(ctx, ABOUT) {
+///     self.show_about = false;
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AboutInfo<'a> {
+    /// As the app writes it, e.g. "slowWrite"
+    pub name: &'a str,
+    pub version: &'a str,
+    /// What the app is, under its name; one line per `\n`
+    pub description: &'a str,
+    /// Files it opens and saves
+    pub formats: &'a [&'a str],
+    pub features: &'a [&'a str],
+    /// Anything else worth a heading, as (heading, lines)
+    pub sections: &'a [(&'a str, &'a [&'a str])],
+    /// Frameworks it is built on, with their licences
+    pub credits: &'a [&'a str],
+}
+
+impl AboutInfo<'static> {
+    /// Nothing filled in, for `..AboutInfo::EMPTY` in a const
+    pub const EMPTY: Self =
+        Self { name: "", version: "", description: "", formats: &[], features: &[], sections: &[], credits: &[] };
+}
+
+/// Show an app's about window. Call every frame while it should be up;
+/// returns true once dismissed with "ok", Enter or Escape.
+pub fn about_dialog(ctx: &egui::Context, info: AboutInfo) -> bool {
+    let max_h = (ctx.screen_rect().height() - 60.0).max(120.0);
+    let headed = [("supported formats", info.formats), ("features", info.features)]
+        .into_iter()
+        .chain(info.sections.iter().copied())
+        .chain([("frameworks", info.credits)])
+        .filter(|(_, lines)| !lines.is_empty());
+
+    let mut closed = false;
+    let resp = egui::Window::new(format!("about {}", info.name))
+        .collapsible(false)
+        .resizable(false)
+        .default_width(300.0)
+        .max_height(max_h)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.heading(info.name);
+                    ui.label(format!("version {}", info.version));
+                    ui.add_space(8.0);
+                    for line in info.description.lines() {
+                        ui.label(line);
+                    }
+                });
+                ui.add_space(8.0);
+                ui.separator();
+                for (heading, lines) in headed {
+                    ui.add_space(4.0);
+                    ui.label(format!("{}:", heading));
+                    for line in lines {
+                        ui.label(format!("  {}", line));
+                    }
+                }
+                ui.add_space(8.0);
+            });
+            ui.vertical_centered(|ui| {
+                closed = ui.button("ok").clicked();
+            });
+        });
+    if let Some(r) = &resp {
+        dither::draw_window_shadow_large(ctx, r.response.rect);
+    }
+    closed || ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Enter) || i.consume_key(Modifiers::NONE, Key::Escape))
+}
+
+fn show_alert(ctx: &egui::Context, title: &str, body: &str, buttons: &[&str], warning: bool) -> Option<usize> {
+    let id = egui::Id::new("slowcore_alert").with(title);
+    // Beep once when the alert appears, not every frame
+    if !ctx.data(|d| d.get_temp::<bool>(id).unwrap_or(false)) {
+        ctx.data_mut(|d| d.insert_temp(id, true));
+        crate::sound::alert();
+    }
+    let default = buttons.len().checked_sub(1)?;
+    let cancel = buttons.iter().position(|b| b.eq_ignore_ascii_case("cancel"));
+    let cancel = cancel.or((buttons.len() == 1).then_some(0));
+
+    let mut chosen = None;
+    let mut button_focused = false;
+    let resp = egui::Window::new(title)
+        .collapsible(false)
+        .resizable(false)
+        .default_width(300.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if warning {
+                    let (rect, _) = ui.allocate_exact_size(egui::vec2(28.0, 28.0), egui::Sense::hover());
+                    ui.painter().circle_filled(rect.center(), 13.0, SlowColors::BLACK);
+                    ui.painter().text(
+                        rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        "!",
+                        egui::FontId::proportional(18.0),
+                        SlowColors::WHITE,
+                    );
+                    ui.add_space(6.0);
+                }
+                ui.vertical(|ui| {
+                    ui.set_max_width(300.0);
+                    for line in body.lines() {
+                        ui.add(egui::Label::new(line).wrap(true));
+                    }
+                });
+            });
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                for (i, label) in buttons.iter().enumerate() {
+                    let r = ui.button(*label);
+                    if i == default && ctx.memory(|m| m.focused().is_none()) {
+                        r.request_focus();
+                    }
+                    button_focused |= r.has_focus();
+                    if r.clicked() {
+                        chosen = Some(i);
+                    }
+                }
+            });
+        });
+    if let Some(r) = &resp {
+        dither::draw_window_shadow(ctx, r.response.rect);
+    }
+    if chosen.is_none() {
+        // A focused button answers Enter itself
+        chosen = ctx.input_mut(|i| {
+            if !button_focused && i.consume_key(Modifiers::NONE, Key::Enter) {
+                Some(default)
+            } else if i.consume_key(Modifiers::NONE, Key::Escape) {
+                cancel
+            } else {
+                None
+            }
+        });
+    }
+    if chosen.is_some() {
+        ctx.data_mut(|d| d.remove::<bool>(id));
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_selection() {
+        let names = ["apple", "banana", "blueberry", "cherry", "Bean"];
+        let label = |i: usize| names[i];
+        let now = Instant::now();
+
+        // Type-ahead matches a prefix, ignoring case, and builds on itself
+        let mut selection = ListSelection::default();
+        assert!(selection.type_ahead("b", now, names.len(), label));
+        assert_eq!(selection.primary(), Some(1));
+        assert!(selection.type_ahead("L", now, names.len(), label));
+        assert_eq!(selection.primary(), Some(2));
+        assert!(!selection.type_ahead("x", now, names.len(), label));
+        assert_eq!(selection.primary(), Some(2));
+        // After a pause it starts again; a repeated letter cycles
+        let later = now + TYPE_AHEAD_TIMEOUT * 2;
+        assert!(selection.type_ahead("b", later, names.len(), label));
+        assert_eq!(selection.primary(), Some(1));
+        assert!(selection.type_ahead("b", later, names.len(), label));
+        assert_eq!(selection.primary(), Some(2));
+        assert!(selection.type_ahead("b", later, names.len(), label));
+        assert_eq!(selection.primary(), Some(4));
+
+        // Shift-click selects from the anchor, either way
+        let mut selection = ListSelection::default();
+        selection.select_only(3);
+        selection.extend_to(1);
+        assert_eq!(selection.rows, BTreeSet::from([1, 2, 3]));
+        selection.extend_to(5);
+        assert_eq!(selection.rows, BTreeSet::from([3, 4, 5]));
+        assert_eq!(selection.primary(), Some(5));
+        selection.toggle(4);
+        assert!(!selection.is_selected(4));
+
+        // The list shrinking drops rows past its end and pulls the cursor in
+        selection.select_only(2);
+        selection.extend_to(6);
+        selection.clamp(4);
+        assert_eq!(selection.rows, BTreeSet::from([2, 3]));
+        assert_eq!(selection.cursor, Some(3));
+        selection.extend_to(0);
+        assert_eq!(selection.rows, BTreeSet::from([0, 1, 2]));
+        selection.clamp(0);
+        assert!(selection.rows.is_empty());
+        assert_eq!(selection.cursor, None);
+    }
+}
@@ -0,0 +1,582 @@
+//! Window layout — where app windows open
+//!
+//! Apps build their viewport through [`viewport`] with a [`Placement`]
+//! instead of picking a position themselves:
+//!
+//! ```ignore
+//! let viewport = window_layout::viewport("slowwrite", Placement::Cascade, [580.0, 440.0])
+//!     .with_title("slowWrite");
+//! ```
+//!
+//! slowDesktop publishes the work area (the screen between its menu bar
+//! and status bar). Every open window claims its rectangle, and new
+//! windows are placed clear of those claims where there is room,
+//! cascading down from the top-left where there isn't. Once a window is
+//! open, [`RepaintController`](crate::RepaintController) keeps its claim
+//! and saved [`WindowState`] current as it is moved, resized or tiled.
+//! The size passed to [`viewport`] is only for the first time: after that
+//! a window opens at the size it was left at, and
+//! [`Placement::RememberLast`] also puts it back where it was, maximized
+//! or tiled if it was.
+//!
+//! ## Tiling and snapping
+//!
+//! Tracked windows also tile themselves: ⌘← and ⌘→ fill the left or right
+//! half of the work area, ⌘↑ maximizes (again to restore) and ⌘↓
+//! restores, unless a text field has the keyboard. Dropping a window
+//! against the left, right or top edge of the work area does the same,
+//! and dragging a tiled window away gives it back its old size. Other
+//! processes (slowDesktop's window menu) tile a window with
+//! [`request_tile`].
+//!
+//! Same file-based IPC as minimize, under ~/.config/slowos/window_layout/:
+//! `screen.json`, `claims/<app>_<pid>.json`, `geometry/<app>.json` and
+//! `tile_requests/<pid>.json`.
+
+use crate::process::{read_per_process, PerProcess};
+use crate::shortcuts::KeyCombo;
+use egui::{Event, Key};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Distance between cascaded windows
+const CASCADE_STEP: f32 = 30.0;
+
+/// Gap kept between a placed window and the work area's edge
+const MARGIN: f32 = 20.0;
+
+/// A window dropped this close to (or past) an edge of the work area snaps
+const SNAP_DISTANCE: f32 = 6.0;
+
+/// How often the watcher thread looks for tile requests
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Work area used when the desktop isn't running (the Slowbook panel
+/// less the desktop's bars)
+const FALLBACK_SCREEN: WindowRect = WindowRect { x: 0.0, y: 22.0, w: 1024.0, h: 558.0 };
+
+/// How an app would like its window placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// Clear of other windows if possible, otherwise cascaded
+    Cascade,
+    /// Centred in the work area
+    Center,
+    /// Left half of the work area
+    TileLeft,
+    /// Right half of the work area
+    TileRight,
+    /// Where this app's window was last closed, falling back to `Cascade`
+    RememberLast,
+}
+
+/// A tiled window position
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Tile {
+    Left,
+    Right,
+    Maximize,
+    /// Back to the size and place from before tiling
+    Restore,
+}
+
+impl Tile {
+    /// Keyboard shortcut for this tile
+    pub fn combo(self) -> KeyCombo {
+        match self {
+            Tile::Left => KeyCombo::cmd(Key::ArrowLeft),
+            Tile::Right => KeyCombo::cmd(Key::ArrowRight),
+            Tile::Maximize => KeyCombo::cmd(Key::ArrowUp),
+            Tile::Restore => KeyCombo::cmd(Key::ArrowDown),
+        }
+    }
+
+    /// Where this tile puts a window; `None` for `Restore`
+    pub fn rect(self, screen: WindowRect) -> Option<WindowRect> {
+        match self {
+            Tile::Left => Some(screen.half(false)),
+            Tile::Right => Some(screen.half(true)),
+            Tile::Maximize => Some(screen),
+            Tile::Restore => None,
+        }
+    }
+}
+
+/// The tile a window dropped at `rect` snaps to, if it is against an edge
+pub fn snap_target(rect: WindowRect, screen: WindowRect) -> Option<Tile> {
+    if rect.y <= screen.y + SNAP_DISTANCE {
+        Some(Tile::Maximize)
+    } else if rect.x <= screen.x + SNAP_DISTANCE {
+        Some(Tile::Left)
+    } else if rect.x + rect.w >= screen.x + screen.w - SNAP_DISTANCE {
+        Some(Tile::Right)
+    } else {
+        None
+    }
+}
+
+/// A window rectangle in screen points (outer frame)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct WindowRect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl WindowRect {
+    pub fn from_rect(rect: egui::Rect) -> Self {
+        Self { x: rect.min.x, y: rect.min.y, w: rect.width(), h: rect.height() }
+    }
+
+    pub fn to_rect(self) -> egui::Rect {
+        egui::Rect::from_min_size(egui::pos2(self.x, self.y), egui::vec2(self.w, self.h))
+    }
+
+    fn overlaps(&self, other: &WindowRect) -> bool {
+        self.x < other.x + other.w && other.x < self.x + self.w && self.y < other.y + other.h && other.y < self.y + self.h
+    }
+
+    fn same_corner(&self, other: &WindowRect) -> bool {
+        (self.x - other.x).abs() < 4.0 && (self.y - other.y).abs() < 4.0
+    }
+
+    fn contains(&self, other: &WindowRect) -> bool {
+        other.x >= self.x && other.y >= self.y && other.x + other.w <= self.x + self.w && other.y + other.h <= self.y + self.h
+    }
+
+    /// Left or right half of this rectangle
+    pub fn half(&self, right: bool) -> WindowRect {
+        let w = (self.w / 2.0).floor();
+        WindowRect { x: if right { self.x + w } else { self.x }, w, ..*self }
+    }
+}
+
+/// What is kept of an app's window between runs
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct WindowState {
+    /// Outer frame
+    #[serde(flatten)]
+    pub rect: WindowRect,
+    /// Size inside the frame, which the window is opened at
+    #[serde(default)]
+    pub inner: Option<[f32; 2]>,
+    /// Tile the window was in, `Maximize` for maximized
+    #[serde(default)]
+    pub tile: Option<Tile>,
+    /// Rectangle from before tiling
+    #[serde(default)]
+    pub untiled: Option<WindowRect>,
+}
+
+/// A window's claim on the screen
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Claim {
+    app: String,
+    pid: u32,
+    rect: WindowRect,
+}
+
+fn layout_dir(sub: &str) -> PathBuf {
+    let dir = directories::ProjectDirs::from("", "", "slowos")
+        .map(|p| p.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/slowos"))
+        .join("window_layout")
+        .join(sub);
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+fn write_json<T: Serialize>(path: PathBuf, value: &T) {
+    if let Ok(json) = serde_json::to_string(value) {
+        let tmp = path.with_extension("tmp");
+        if std::fs::write(&tmp, json).is_ok() {
+            let _ = std::fs::rename(&tmp, &path);
+        }
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: PathBuf) -> Option<T> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Publish the work area (used by slowDesktop)
+pub fn publish_screen(area: WindowRect) {
+    write_json(layout_dir("").join("screen.json"), &area);
+}
+
+/// The work area windows are placed in
+pub fn screen() -> WindowRect {
+    read_json(layout_dir("").join("screen.json")).unwrap_or(FALLBACK_SCREEN)
+}
+
+impl PerProcess for Claim {
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
+/// Rectangles claimed by other open windows
+fn occupied() -> Vec<WindowRect> {
+    let own = std::process::id();
+    read_per_process::<Claim>(&layout_dir("claims")).into_iter().filter(|c| c.pid != own).map(|c| c.rect).collect()
+}
+
+fn claim_path(app: &str) -> PathBuf {
+    layout_dir("claims").join(format!("{}_{}.json", app, std::process::id()))
+}
+
+fn claim(app: &str, rect: WindowRect) {
+    let claim = Claim { app: app.to_string(), pid: std::process::id(), rect };
+    write_json(claim_path(app), &claim);
+}
+
+/// State this app's window had when last moved, resized or tiled
+pub fn remembered(app: &str) -> Option<WindowState> {
+    read_json(layout_dir("geometry").join(format!("{}.json", app)))
+}
+
+fn remember(app: &str, state: &WindowState) {
+    write_json(layout_dir("geometry").join(format!("{}.json", app)), state);
+}
+
+/// Choose a rectangle for a new window of `size`
+pub fn allocate(
+    placement: Placement,
+    size: [f32; 2],
+    screen: WindowRect,
+    occupied: &[WindowRect],
+    remembered: Option<WindowRect>,
+) -> WindowRect {
+    let w = size[0].min(screen.w);
+    let h = size[1].min(screen.h);
+    match placement {
+        Placement::Center => WindowRect {
+            x: (screen.x + (screen.w - w) / 2.0).round(),
+            y: (screen.y + (screen.h - h) / 2.0).round(),
+            w,
+            h,
+        },
+        Placement::TileLeft => screen.half(false),
+        Placement::TileRight => screen.half(true),
+        // A second window of the app doesn't go on top of the first
+        Placement::RememberLast => match remembered {
+            Some(rect) if screen.contains(&rect) && !occupied.iter().any(|o| o.same_corner(&rect)) => rect,
+            _ => allocate(Placement::Cascade, size, screen, occupied, None),
+        },
+        Placement::Cascade => {
+            let slot = |col: usize, row: usize| WindowRect {
+                x: screen.x + MARGIN + col as f32 * CASCADE_STEP,
+                y: screen.y + MARGIN + row as f32 * CASCADE_STEP,
+                w,
+                h,
+            };
+            let fits = |r: &WindowRect| screen.contains(r);
+            // First a spot clear of every other window, scanning rows then columns
+            let cols = ((screen.w - w - MARGIN * 2.0) / CASCADE_STEP).max(0.0) as usize;
+            let rows = ((screen.h - h - MARGIN * 2.0) / CASCADE_STEP).max(0.0) as usize;
+            for row in 0..=rows {
+                for col in 0..=cols {
+                    let rect = slot(col, row);
+                    if fits(&rect) && !occupied.iter().any(|o| o.overlaps(&rect)) {
+                        return rect;
+                    }
+                }
+            }
+            // Otherwise the first diagonal step whose corner nobody else is using
+            let steps = cols.min(rows);
+            (0..=steps)
+                .map(|n| slot(n, n))
+                .find(|r| !occupied.iter().any(|o| o.same_corner(r)))
+                .unwrap_or_else(|| slot(occupied.len() % (steps + 1), occupied.len() % (steps + 1)))
+        }
+    }
+}
+
+/// The window this process opened through [`viewport`]
+struct Tracked {
+    app: String,
+    /// Last rectangle written out
+    saved: Option<WindowRect>,
+    /// Rectangle seen last frame, written once it stops changing
+    pending: Option<WindowRect>,
+    /// Current tile, if the window is tiled
+    tiled: Option<Tile>,
+    /// Rectangle from before tiling, for `Tile::Restore`
+    untiled: Option<WindowRect>,
+    /// Whether the tile request watcher is running
+    watching: bool,
+    /// Opened tiled: the tile is fitted to the window frame once it is known
+    fit_tile: bool,
+}
+
+impl Tracked {
+    /// Move the window into `tile` (or back out of it)
+    fn tile(&mut self, ctx: &egui::Context, tile: Tile, current: WindowRect) {
+        let tile = if tile == Tile::Maximize && self.tiled == Some(Tile::Maximize) { Tile::Restore } else { tile };
+        match tile.rect(screen()) {
+            Some(rect) => {
+                if self.tiled.is_none() {
+                    self.untiled = Some(current);
+                }
+                self.tiled = Some(tile);
+                move_window(ctx, rect);
+            }
+            None => {
+                if let Some(rect) = self.untiled.take() {
+                    move_window(ctx, rect);
+                }
+                self.tiled = None;
+            }
+        }
+    }
+
+    /// A move or resize has settled at `rect`: snap it if it was dropped
+    /// against an edge, and untile it if it was dragged out of its tile
+    fn settled(&mut self, ctx: &egui::Context, rect: WindowRect) {
+        let Some(prev) = self.saved else { return };
+        let moved = (prev.x, prev.y) != (rect.x, rect.y);
+        let resized = (prev.w, prev.h) != (rect.w, rect.h);
+        if !moved || resized {
+            // Resizing by hand ends the tile
+            if resized && self.tiled.is_some_and(|t| t.rect(screen()) != Some(rect)) {
+                self.tiled = None;
+                self.untiled = None;
+            }
+            return;
+        }
+        let screen = screen();
+        match snap_target(rect, screen) {
+            Some(tile) if tile.rect(screen) != Some(rect) => self.tile(ctx, tile, prev),
+            Some(_) => {}
+            None => {
+                if let (Some(_), Some(untiled)) = (self.tiled.take(), self.untiled.take()) {
+                    move_window(ctx, WindowRect { x: rect.x, y: rect.y, ..untiled });
+                }
+            }
+        }
+    }
+}
+
+static TRACKED: Mutex<Option<Tracked>> = Mutex::new(None);
+
+/// Viewport for this app's main window: sized, placed and claimed.
+/// `size` is for the first run; later runs open at the size last left.
+pub fn viewport(app: &str, placement: Placement, size: [f32; 2]) -> egui::ViewportBuilder {
+    // Startup is timed from here
+    crate::lazy::mark("window");
+    let screen = screen();
+    let state = remembered(app);
+    let size = state.and_then(|s| s.inner).unwrap_or(size);
+    let last = state.map(|s| WindowRect { w: size[0], h: size[1], ..s.rect });
+    let mut rect = allocate(placement, size, screen, &occupied(), last);
+    let (mut tiled, mut untiled) = match placement {
+        Placement::TileLeft => (Some(Tile::Left), None),
+        Placement::TileRight => (Some(Tile::Right), None),
+        _ => (None, None),
+    };
+    // Back into the tile it was in, unless it went somewhere else
+    if let Some(state) = state.filter(|_| placement == Placement::RememberLast && Some(rect) == last) {
+        if let Some(tile_rect) = state.tile.and_then(|t| t.rect(screen)) {
+            rect = tile_rect;
+            tiled = state.tile;
+            untiled = state.untiled;
+        }
+    }
+    claim(app, rect);
+    if let Ok(mut tracked) = TRACKED.lock() {
+        *tracked = Some(Tracked {
+            app: app.to_string(),
+            saved: Some(rect),
+            pending: None,
+            tiled,
+            untiled,
+            watching: false,
+            fit_tile: tiled.is_some(),
+        });
+    }
+    egui::ViewportBuilder::default()
+        .with_inner_size([rect.w, rect.h])
+        .with_position([rect.x, rect.y])
+}
+
+/// Move and resize this window so its outer frame covers `rect`
+fn move_window(ctx: &egui::Context, rect: WindowRect) {
+    let (outer, inner) = ctx.input(|i| (i.viewport().outer_rect, i.viewport().inner_rect));
+    let frame = match (outer, inner) {
+        (Some(outer), Some(inner)) => outer.size() - inner.size(),
+        _ => egui::Vec2::ZERO,
+    };
+    ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(rect.x, rect.y)));
+    ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(rect.w, rect.h) - frame));
+}
+
+/// Consume a tiling shortcut, unless a text field has the keyboard
+fn take_tile_key(ctx: &egui::Context) -> Option<Tile> {
+    if ctx.wants_keyboard_input() {
+        return None;
+    }
+    let tiles = [Tile::Left, Tile::Right, Tile::Maximize, Tile::Restore];
+    let mut fired = None;
+    ctx.input_mut(|i| {
+        i.events.retain(|event| {
+            let Event::Key { key, pressed: true, modifiers, .. } = event else {
+                return true;
+            };
+            match tiles.iter().find(|t| t.combo().matches(*key, *modifiers)) {
+                Some(&tile) => {
+                    fired = Some(tile);
+                    false
+                }
+                None => true,
+            }
+        });
+    });
+    fired
+}
+
+fn tile_request_path(pid: u32) -> PathBuf {
+    layout_dir("tile_requests").join(format!("{}.json", pid))
+}
+
+/// Ask the window of process `pid` to tile itself
+pub fn request_tile(pid: u32, tile: Tile) {
+    write_json(tile_request_path(pid), &tile);
+}
+
+fn take_tile_request() -> Option<Tile> {
+    let path = tile_request_path(std::process::id());
+    let tile = read_json(path.clone())?;
+    let _ = std::fs::remove_file(path);
+    Some(tile)
+}
+
+/// Wake `ctx` when a tile request may have arrived
+fn watch_requests(ctx: egui::Context) {
+    let path = tile_request_path(std::process::id());
+    let _ = std::thread::Builder::new()
+        .name("slowcore-tile-requests".into())
+        .spawn(move || loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            if path.exists() {
+                ctx.request_repaint();
+            }
+        });
+}
+
+/// Keep this window's claim and remembered geometry up to date, and
+/// handle tiling. Called by `RepaintController::end_frame`; does nothing
+/// for windows not opened through [`viewport`].
+pub(crate) fn track(ctx: &egui::Context) {
+    let Ok(mut guard) = TRACKED.lock() else { return };
+    let Some(tracked) = guard.as_mut() else { return };
+    if !tracked.watching {
+        tracked.watching = true;
+        watch_requests(ctx.clone());
+    }
+    let Some(outer) = ctx.input(|i| i.viewport().outer_rect) else { return };
+    let rect = WindowRect::from_rect(outer);
+
+    // The window was opened at the tile's size inside its frame
+    if tracked.fit_tile {
+        tracked.fit_tile = false;
+        if let Some(tile_rect) = tracked.tiled.and_then(|t| t.rect(screen())) {
+            tracked.saved = Some(tile_rect);
+            move_window(ctx, tile_rect);
+            return;
+        }
+    }
+
+    if let Some(tile) = take_tile_key(ctx).or_else(take_tile_request) {
+        tracked.tile(ctx, tile, rect);
+    }
+
+    if tracked.saved == Some(rect) {
+        tracked.pending = None;
+        return;
+    }
+    // Wait until a drag or resize settles before touching the disk
+    if tracked.pending == Some(rect) {
+        tracked.settled(ctx, rect);
+        claim(&tracked.app, rect);
+        // A tiled window is next opened untiled at its old size
+        let base = tracked.untiled.filter(|_| tracked.tiled.is_some()).unwrap_or(rect);
+        let inner = ctx.input(|i| i.viewport().inner_rect).map(|inner| {
+            let frame = outer.size() - inner.size();
+            [base.w - frame.x, base.h - frame.y]
+        });
+        remember(&tracked.app, &WindowState { rect, inner, tile: tracked.tiled, untiled: tracked.untiled });
+        tracked.saved = Some(rect);
+        tracked.pending = None;
+    } else {
+        tracked.pending = Some(rect);
+        // Settling needs one more frame even if nothing else asks for it
+        ctx.request_repaint_after(Duration::from_millis(100));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: WindowRect = WindowRect { x: 0.0, y: 22.0, w: 1000.0, h: 600.0 };
+
+    #[test]
+    fn test_cascade_avoids_open_windows() {
+        let first = allocate(Placement::Cascade, [400.0, 300.0], SCREEN, &[], None);
+        assert_eq!((first.x, first.y), (20.0, 42.0));
+        let second = allocate(Placement::Cascade, [400.0, 300.0], SCREEN, &[first], None);
+        assert!(!second.overlaps(&first));
+        assert!(SCREEN.contains(&second));
+    }
+
+    #[test]
+    fn test_cascade_steps_when_full() {
+        let big = allocate(Placement::Cascade, [900.0, 500.0], SCREEN, &[], None);
+        let next = allocate(Placement::Cascade, [900.0, 500.0], SCREEN, &[big], None);
+        assert_eq!((next.x - big.x, next.y - big.y), (CASCADE_STEP, CASCADE_STEP));
+    }
+
+    #[test]
+    fn test_tiles_and_remembered() {
+        let left = allocate(Placement::TileLeft, [400.0, 300.0], SCREEN, &[], None);
+        let right = allocate(Placement::TileRight, [400.0, 300.0], SCREEN, &[], None);
+        assert_eq!(left.w + right.w, SCREEN.w);
+        assert_eq!(right.x, left.x + left.w);
+
+        let saved = WindowRect { x: 300.0, y: 100.0, w: 400.0, h: 300.0 };
+        assert_eq!(allocate(Placement::RememberLast, [400.0, 300.0], SCREEN, &[], Some(saved)), saved);
+        // Off screen (say, from a larger external display) falls back to cascade
+        let off = WindowRect { x: 1500.0, ..saved };
+        assert_ne!(allocate(Placement::RememberLast, [400.0, 300.0], SCREEN, &[], Some(off)), off);
+        // So does a second window while the first is still there
+        assert_ne!(allocate(Placement::RememberLast, [400.0, 300.0], SCREEN, &[saved], Some(saved)), saved);
+    }
+
+    #[test]
+    fn test_window_state() {
+        // Geometry saved before the state was kept still loads
+        let old: WindowState = serde_json::from_str(r#"{"x":10.0,"y":30.0,"w":400.0,"h":300.0}"#).unwrap();
+        assert_eq!(old.rect, WindowRect { x: 10.0, y: 30.0, w: 400.0, h: 300.0 });
+        assert_eq!((old.inner, old.tile), (None, None));
+
+        let state = WindowState { inner: Some([400.0, 278.0]), tile: Some(Tile::Maximize), untiled: Some(old.rect), ..old };
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<WindowState>(&json).unwrap(), state);
+    }
+
+    #[test]
+    fn test_snap_targets() {
+        let rect = WindowRect { x: 300.0, y: 100.0, w: 400.0, h: 300.0 };
+        assert_eq!(snap_target(rect, SCREEN), None);
+        assert_eq!(snap_target(WindowRect { x: -40.0, ..rect }, SCREEN), Some(Tile::Left));
+        assert_eq!(snap_target(WindowRect { x: 600.0, ..rect }, SCREEN), Some(Tile::Right));
+        assert_eq!(snap_target(WindowRect { y: 22.0, ..rect }, SCREEN), Some(Tile::Maximize));
+        assert_eq!(Tile::Maximize.rect(SCREEN), Some(SCREEN));
+        assert_eq!(Tile::Restore.rect(SCREEN), None);
+        assert_eq!(Tile::Left.combo().label(), KeyCombo::cmd(Key::ArrowLeft).label());
+    }
+}
@@ -0,0 +1,95 @@
+//! slowctl - Script running slowOS apps from the command line
+//!
+//! Sends a command to a running app through `slowcore::ipc` and waits for
+//! it to be done. Queries print their answer; a command the app can't
+//! carry out prints why and exits with status 1, so scripts can stop.
+//!
+//! A journal entry from a template, for instance:
+//!
+//! ```text
+//! slowctl slownotes menu file "new note"
+//! slowctl slownotes type "$(date +%F; cat ~/Documents/journal-template.txt)"
+//! ```
+
+use slowcore::ipc::{self, Command, Reply};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage:
+  slowctl <app> open <file>          open a file, starting the app if needed
+  slowctl <app> type <text>          type text into the app
+  slowctl <app> menu <menu> <item>   choose a menu item
+  slowctl <app> query <key>          print some of the app's state
+  slowctl list                       list the running apps";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [help] if help == "help" || help == "--help" || help == "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        [list] if list == "list" => {
+            for app in slowcore::running::read_all() {
+                println!("{}\t{}\t{}", app.binary, app.pid, app.name);
+            }
+            Ok(())
+        }
+        [app, rest @ ..] => {
+            let command = parse(rest)?;
+            let Some(pid) = ipc::find_app(app) else {
+                return match &command {
+                    Command::Open { path } => start(app, path),
+                    _ => Err(format!("slowctl: {} isn't running", app)),
+                };
+            };
+            match ipc::send(pid, &command, ipc::REPLY_TIMEOUT) {
+                Reply::Done => {}
+                Reply::Value(serde_json::Value::String(text)) => println!("{}", text),
+                Reply::Value(value) => println!("{}", value),
+                Reply::Failed(reason) => return Err(format!("slowctl: {}: {}", app, reason)),
+            }
+            Ok(())
+        }
+        [] => Err(USAGE.to_string()),
+    }
+}
+
+fn parse(args: &[String]) -> Result<Command, String> {
+    let command = match args {
+        [verb, path] if verb == "open" => Command::Open { path: absolute(Path::new(path)) },
+        [verb, text @ ..] if verb == "type" && !text.is_empty() => Command::Type { text: text.join(" ") },
+        [verb, item @ ..] if verb == "menu" && !item.is_empty() => Command::Menu { item: item.to_vec() },
+        [verb, key] if verb == "query" => Command::Query { key: key.clone() },
+        _ => return Err(USAGE.to_string()),
+    };
+    Ok(command)
+}
+
+/// The app runs somewhere else, so paths can't be relative to here
+fn absolute(path: &Path) -> PathBuf {
+    std::env::current_dir().map(|dir| dir.join(path)).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Start `app` on `path`, as opening a file from slowFiles does
+fn start(app: &str, path: &Path) -> Result<(), String> {
+    let sibling = std::env::current_exe().ok().and_then(|e| e.parent().map(|p| p.join(app)));
+    let binary = sibling.filter(|p| p.exists()).unwrap_or_else(|| PathBuf::from(app));
+    std::process::Command::new(binary)
+        .arg(path)
+        .env("SLOWOS_MANAGED", "1")
+        .stdin(std::process::Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("slowctl: could not start {}: {}", app, e))
+}
@@ -5,13 +5,27 @@ use egui::{
     TextureHandle, TextureOptions, Vec2,
 };
 use serde::{Deserialize, Serialize};
+use slowcore::confirm::CloseGuard;
 use slowcore::repaint::RepaintController;
-use slowcore::storage::{documents_dir, FileBrowser};
+use slowcore::storage::{self, documents_dir, Migration, RecentFiles, Versioned};
 use slowcore::text_edit::WordDragState;
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, FileListItem, window_control_buttons, WindowAction};
+use slowcore::undo::{self, UndoAction, UndoStack};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, FileDialog, FileDialogResult, WindowAction};
+use crate::measure::{self, Anchor, Dimension, Unit};
+use crate::typeset::{self, TextAlign, TextPath, DEFAULT_LEADING};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowDesign",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "layout program for slowOS",
+    ..AboutInfo::EMPTY
+};
+
+/// Typeface for exported text; the canvas draws the same Plex
+const FONT_DATA: &[u8] = include_bytes!("../../fonts/ibm-plex-sans/IBMPlexSans-Regular.ttf");
 
 // ---------------------------------------------------------------
 // Serializable rectangle (egui::Rect doesn't impl serde)
@@ -57,6 +71,18 @@ pub enum ShapeType {
 pub struct TextBox {
     pub text: String,
     pub font_size: f32,
+    #[serde(default)]
+    pub align: TextAlign,
+    /// Line spacing as a multiple of the font size
+    #[serde(default = "default_leading")]
+    pub leading: f32,
+    /// Set along this path across the box instead of in lines
+    #[serde(default)]
+    pub path: Option<TextPath>,
+}
+
+fn default_leading() -> f32 {
+    DEFAULT_LEADING
 }
 
 impl Default for TextBox {
@@ -64,6 +90,9 @@ impl Default for TextBox {
         Self {
             text: "Text".to_string(),
             font_size: 14.0,
+            align: TextAlign::Left,
+            leading: DEFAULT_LEADING,
+            path: None,
         }
     }
 }
@@ -97,6 +126,9 @@ pub enum ElementContent {
     TextBox(TextBox),
     Image(ImageElement),
     Shape(ShapeElement),
+    /// A measured distance between two points, kept up to date as the
+    /// elements it's pinned to move
+    Dimension(Dimension),
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -109,7 +141,15 @@ pub struct DesignElement {
     pub locked: bool,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+impl DesignElement {
+    /// Whether the element can be dragged and resized; dimensions go
+    /// where their ends are
+    fn movable(&self) -> bool {
+        !self.locked && !matches!(self.content, ElementContent::Dimension(_))
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct SerVec2 {
     pub x: f32,
     pub y: f32,
@@ -123,40 +163,201 @@ impl From<SerVec2> for Vec2 {
     fn from(v: SerVec2) -> Self { Vec2::new(v.x, v.y) }
 }
 
+/// One page of a document, or one artboard of a mockup
 #[derive(Clone, Serialize, Deserialize)]
-pub struct Document {
+pub struct Page {
+    pub name: String,
+    pub size: SerVec2,
     pub elements: Vec<DesignElement>,
+}
+
+impl Page {
+    fn new(name: String, size: SerVec2) -> Self {
+        Self { name, size, elements: Vec::new() }
+    }
+
+    fn frame(&self, id: u64) -> Option<Rect> {
+        self.elements.iter().find(|e| e.id == id).map(|e| e.rect.into())
+    }
+
+    /// Where a dimension's ends are now
+    fn ends(&self, dimension: &Dimension) -> Option<(Pos2, Pos2)> {
+        let frame = |id| self.frame(id);
+        Some((dimension.from.resolve(frame)?, dimension.to.resolve(frame)?))
+    }
+
+    /// Frames of the elements a dimension can be pinned to, topmost first
+    fn snap_frames(&self) -> Vec<(u64, Rect)> {
+        self.elements.iter().rev()
+            .filter(|e| !matches!(e.content, ElementContent::Dimension(_)))
+            .map(|e| (e.id, e.rect.into()))
+            .collect()
+    }
+
+    /// Fit each dimension's frame round where it's drawn now, so it can
+    /// be clicked
+    fn place_dimensions(&mut self) {
+        let frames: Vec<Option<Rect>> = self.elements.iter()
+            .map(|e| match &e.content {
+                ElementContent::Dimension(d) => self.ends(d).map(|(a, b)| {
+                    let drawn = measure::draw(a, b, d.offset);
+                    let label = Rect::from_center_size(drawn.label, Vec2::new(measure::LABEL_SIZE * 4.0, measure::LABEL_SIZE));
+                    drawn.lines.iter().fold(label, |r, [p, q]| r.union(Rect::from_two_pos(*p, *q)))
+                }),
+                _ => None,
+            })
+            .collect();
+        for (element, frame) in self.elements.iter_mut().zip(frames) {
+            if let Some(frame) = frame {
+                element.rect = frame.expand(2.0).into();
+            }
+        }
+    }
+
+    /// Leave dimension ends pinned to `id` where they are, before it goes
+    fn unpin(&mut self, id: u64) {
+        let Some(r) = self.frame(id) else { return };
+        for element in &mut self.elements {
+            if let ElementContent::Dimension(d) = &mut element.content {
+                for end in [&mut d.from, &mut d.to] {
+                    if let Anchor::Pinned { element, .. } = *end {
+                        if element == id {
+                            let at = end.resolve(|_| Some(r)).unwrap_or_default();
+                            *end = Anchor::Fixed { x: at.x, y: at.y };
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Page sizes offered in the page properties, in points
+const PAGE_SIZES: &[(&str, f32, f32)] = &[
+    ("letter", 612.0, 792.0),
+    ("A4", 595.0, 842.0),
+    ("A5 zine", 420.0, 595.0),
+    ("half letter", 396.0, 612.0),
+    ("phone", 390.0, 844.0),
+    ("tablet", 820.0, 1180.0),
+    ("desktop", 1280.0, 800.0),
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Document {
+    pub pages: Vec<Page>,
+    /// Unit of the rulers and dimensions
+    pub unit: Unit,
+    /// Element ids are unique across all pages
     pub next_id: u64,
-    pub page_size: SerVec2,
 }
 
+impl Versioned for Document {
+    const MIGRATIONS: &'static [Migration] = &[
+        // 0 → 1: elements from before locking have no `locked`
+        |v| {
+            for element in v["elements"].as_array_mut().into_iter().flatten() {
+                if element.get("locked").is_none() {
+                    element["locked"] = false.into();
+                }
+            }
+        },
+        // 1 → 2: text boxes gain alignment, leading and a path
+        |v| {
+            for element in v["elements"].as_array_mut().into_iter().flatten() {
+                if let Some(text) = element["content"].get_mut("TextBox") {
+                    text["align"] = "Left".into();
+                    text["leading"] = DEFAULT_LEADING.into();
+                    text["path"] = serde_json::Value::Null;
+                }
+            }
+        },
+        // 2 → 3: the elements and page size become the first of a list
+        // of pages
+        |v| {
+            let page = serde_json::json!({
+                "name": "page 1",
+                "size": v["page_size"].take(),
+                "elements": v["elements"].take(),
+            });
+            if let Some(object) = v.as_object_mut() {
+                object.remove("page_size");
+                object.remove("elements");
+                object.insert("pages".into(), serde_json::json!([page]));
+            }
+        },
+        // 3 → 4: rulers and dimensions gain a unit
+        |v| {
+            v["unit"] = "Px".into();
+        },
+    ];
+}
+
+/// How close, in screen pixels, a measured point has to come to an
+/// element's corner or middle to snap to it
+const SNAP_REACH: f32 = 6.0;
+
+/// Width of the rulers along the canvas
+const RULER_WIDTH: f32 = 18.0;
+
 /// Page margin in points (1 inch)
 const PAGE_MARGIN: f32 = 72.0;
 
 impl Default for Document {
     fn default() -> Self {
         Self {
-            elements: Vec::new(),
+            pages: vec![Page::new("page 1".into(), SerVec2 { x: 612.0, y: 792.0 })], // Letter size
+            unit: Unit::Px,
             next_id: 1,
-            page_size: SerVec2 { x: 612.0, y: 792.0 }, // Letter size
         }
     }
 }
 
 impl Document {
     fn get(&self, id: u64) -> Option<&DesignElement> {
-        self.elements.iter().find(|e| e.id == id)
+        self.pages.iter().flat_map(|p| &p.elements).find(|e| e.id == id)
     }
 
     fn get_mut(&mut self, id: u64) -> Option<&mut DesignElement> {
-        self.elements.iter_mut().find(|e| e.id == id)
+        self.pages.iter_mut().flat_map(|p| &mut p.elements).find(|e| e.id == id)
+    }
+
+    /// Put a blank page after `index`, the same size, and return where it went
+    fn insert_page(&mut self, index: usize) -> usize {
+        let size = self.pages[index].size;
+        self.pages.insert(index + 1, Page::new(format!("page {}", self.pages.len() + 1), size));
+        index + 1
+    }
+
+    /// Copy the page at `index` to just after it, with new element ids
+    fn duplicate_page(&mut self, index: usize) -> usize {
+        let mut page = self.pages[index].clone();
+        page.name = format!("{} copy", page.name);
+        let mut ids = HashMap::new();
+        for element in &mut page.elements {
+            ids.insert(element.id, self.next_id);
+            element.id = self.next_id;
+            self.next_id += 1;
+        }
+        // Dimensions on the copy measure the copies
+        for element in &mut page.elements {
+            if let ElementContent::Dimension(d) = &mut element.content {
+                for end in [&mut d.from, &mut d.to] {
+                    if let Anchor::Pinned { element, .. } = end {
+                        *element = ids.get(element).copied().unwrap_or(*element);
+                    }
+                }
+            }
+        }
+        self.pages.insert(index + 1, page);
+        index + 1
     }
 
     /// Create a new document with a full-page text box (1 inch margins)
     fn with_initial_text_box() -> Self {
         let mut doc = Self::default();
-        let ps = &doc.page_size;
-        doc.elements.push(DesignElement {
+        let ps = doc.pages[0].size;
+        doc.pages[0].elements.push(DesignElement {
             id: 1,
             rect: SerRect {
                 min_x: PAGE_MARGIN,
@@ -166,7 +367,7 @@ impl Document {
             },
             content: ElementContent::TextBox(TextBox {
                 text: String::new(),
-                font_size: 14.0,
+                ..Default::default()
             }),
             locked: true,
         });
@@ -175,6 +376,65 @@ impl Document {
     }
 }
 
+// ---------------------------------------------------------------
+// Export drawing
+// ---------------------------------------------------------------
+
+/// Draw a one pixel black line into a PNG export
+fn put_line(img: &mut image::RgbaImage, from: Pos2, to: Pos2) {
+    let (w, h) = img.dimensions();
+    let (x0, y0, x1, y1) = (from.x as i32, from.y as i32, to.x as i32, to.y as i32);
+    // Bresenham line
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+    let mut cx = x0;
+    let mut cy = y0;
+    loop {
+        if cx >= 0 && cx < w as i32 && cy >= 0 && cy < h as i32 {
+            img.put_pixel(cx as u32, cy as u32, image::Rgba([0, 0, 0, 255]));
+        }
+        if cx == x1 && cy == y1 { break; }
+        let e2 = 2 * err;
+        if e2 >= dy { err += dy; cx += sx; }
+        if e2 <= dx { err += dx; cy += sy; }
+    }
+}
+
+/// Draw a character into a PNG export, its baseline starting at
+/// `origin` and turned clockwise by `angle`
+fn put_glyph(img: &mut image::RgbaImage, font: &ab_glyph::FontRef, ch: char, size: f32, origin: Pos2, angle: f32) {
+    use ab_glyph::{Font as AbFont, PxScale};
+    let (w, h) = img.dimensions();
+    // Turned letters are drawn at twice the size and mapped back, so
+    // they come out without gaps
+    let supersample = if angle == 0.0 { 1.0 } else { 2.0 };
+    let glyph = font.glyph_id(ch).with_scale(PxScale::from(size * supersample));
+    let turn = egui::emath::Rot2::from_angle(angle);
+    if let Some(outlined) = font.outline_glyph(glyph) {
+        let bounds = outlined.px_bounds();
+        outlined.draw(|px, py, cov| {
+            if cov > 0.5 {
+                let offset = Vec2::new(bounds.min.x + px as f32, bounds.min.y + py as f32) / supersample;
+                let p = origin + turn * offset;
+                if p.x >= 0.0 && p.y >= 0.0 && (p.x as u32) < w && (p.y as u32) < h {
+                    img.put_pixel(p.x as u32, p.y as u32, image::Rgba([0, 0, 0, 255]));
+                }
+            }
+        });
+    }
+}
+
+/// PDF font size for text `size` pixels high. Sizes here are whole line
+/// heights, PDF's are em squares in points; the page is laid out at 96 dpi.
+fn pdf_font_size(font: &ab_glyph::FontRef, size: f32) -> f32 {
+    use ab_glyph::Font as AbFont;
+    let em = size * font.units_per_em().unwrap_or(1000.0) / font.height_unscaled();
+    em * 72.0 / 96.0
+}
+
 // ---------------------------------------------------------------
 // Tool types
 // ---------------------------------------------------------------
@@ -183,10 +443,13 @@ impl Document {
 pub enum Tool {
     Select,
     TextBox,
+    PathText,
     Image,
     Rectangle,
     Ellipse,
     Line,
+    Measure,
+    Dimension,
 }
 
 // ---------------------------------------------------------------
@@ -218,29 +481,32 @@ pub struct SlowDesignApp {
     // Textures
     image_textures: HashMap<String, TextureHandle>,
 
-    // File browser
-    show_file_browser: bool,
-    file_browser: FileBrowser,
+    // File dialog, and what it's for
+    file_dialog: Option<FileDialog>,
     fb_mode: FbMode,
-    save_filename: String,
 
-    // Image picker
-    show_image_picker: bool,
-    image_browser: FileBrowser,
+    // Where a picked image goes
     pending_image_rect: Option<Rect>,
 
     // Dialogs
     show_about: bool,
-    show_close_confirm: bool,
-    close_confirmed: bool,
+    close_guard: CloseGuard,
 
     // Undo/redo
-    undo_stack: Vec<Document>,
-    redo_stack: Vec<Document>,
+    history: UndoStack<Document>,
+    recent_files: RecentFiles,
+
+    // Pages
+    current_page: usize,
+    /// Page whose name is being edited in the pages panel
+    renaming_page: Option<(usize, String)>,
 
     // Canvas
     scroll_offset: Vec2,
     zoom: f32,
+    show_rulers: bool,
+    /// Ends of the line being measured or dimensioned, on the page
+    measuring: Option<(Pos2, Pos2)>,
 
     // Status message (for export feedback)
     status_message: Option<String>,
@@ -254,7 +520,9 @@ enum FbMode {
     Open,
     Save,
     ExportPng,
+    ExportAllPng,
     ExportPdf,
+    InsertImage,
 }
 
 impl SlowDesignApp {
@@ -272,129 +540,171 @@ impl SlowDesignApp {
             drawing_start: None,
             editing_text: true, // Start in editing mode
             image_textures: HashMap::new(),
-            show_file_browser: false,
-            file_browser: FileBrowser::new(documents_dir())
-                .with_filter(vec!["sld".to_string()]),
+            file_dialog: None,
             fb_mode: FbMode::Open,
-            save_filename: String::new(),
-            show_image_picker: false,
-            image_browser: FileBrowser::new(documents_dir())
-                .with_filter(vec!["png".to_string(), "jpg".to_string(), "jpeg".to_string(), "gif".to_string(), "bmp".to_string()]),
             pending_image_rect: None,
             show_about: false,
-            show_close_confirm: false,
-            close_confirmed: false,
-            undo_stack: Vec::new(),
-            redo_stack: Vec::new(),
+            close_guard: CloseGuard::new(),
+            history: UndoStack::new(50),
+            recent_files: RecentFiles::for_app("slowdesign"),
+            current_page: 0,
+            renaming_page: None,
             scroll_offset: Vec2::ZERO,
             zoom: 1.0,
+            show_rulers: true,
+            measuring: None,
             status_message: None,
             word_drag: WordDragState::new(),
         }
     }
 
     fn save_undo_state(&mut self) {
-        self.undo_stack.push(self.document.clone());
-        self.redo_stack.clear();
-        if self.undo_stack.len() > 50 {
-            self.undo_stack.remove(0);
-        }
+        self.history.save(&self.document);
     }
 
     fn undo(&mut self) {
-        if let Some(state) = self.undo_stack.pop() {
-            self.redo_stack.push(self.document.clone());
-            self.document = state;
+        if self.history.undo(&mut self.document) {
             self.selected_id = None;
+            self.current_page = self.current_page.min(self.document.pages.len() - 1);
         }
     }
 
     fn redo(&mut self) {
-        if let Some(state) = self.redo_stack.pop() {
-            self.undo_stack.push(self.document.clone());
-            self.document = state;
+        if self.history.redo(&mut self.document) {
             self.selected_id = None;
+            self.current_page = self.current_page.min(self.document.pages.len() - 1);
+        }
+    }
+
+    /// The page on screen
+    fn page(&self) -> &Page {
+        &self.document.pages[self.current_page]
+    }
+
+    fn page_mut(&mut self) -> &mut Page {
+        &mut self.document.pages[self.current_page]
+    }
+
+    fn go_to_page(&mut self, index: usize) {
+        if index < self.document.pages.len() && index != self.current_page {
+            self.current_page = index;
+            self.selected_id = None;
+            self.editing_text = false;
+            self.renaming_page = None;
+            self.measuring = None;
+            self.scroll_offset = Vec2::ZERO;
+        }
+    }
+
+    /// Add a blank page the size of this one, after it
+    fn add_page(&mut self) {
+        self.save_undo_state();
+        let index = self.document.insert_page(self.current_page + 1);
+        self.go_to_page(index);
+        self.modified = true;
+    }
+
+    fn duplicate_page(&mut self) {
+        self.save_undo_state();
+        let index = self.document.duplicate_page(self.current_page);
+        self.go_to_page(index);
+        self.modified = true;
+    }
+
+    /// Delete this page, unless it's the only one
+    fn delete_page(&mut self) {
+        if self.document.pages.len() < 2 {
+            return;
+        }
+        self.save_undo_state();
+        self.document.pages.remove(self.current_page);
+        self.current_page = self.current_page.min(self.document.pages.len() - 1);
+        self.selected_id = None;
+        self.editing_text = false;
+        self.modified = true;
+    }
+
+    /// Move this page one place earlier (-1) or later (1)
+    fn move_page(&mut self, by: isize) {
+        let Some(to) = self.current_page.checked_add_signed(by).filter(|&to| to < self.document.pages.len()) else { return };
+        self.save_undo_state();
+        self.document.pages.swap(self.current_page, to);
+        self.current_page = to;
+        self.modified = true;
+    }
+
+    fn set_unit(&mut self, unit: Unit) {
+        if self.document.unit != unit {
+            self.save_undo_state();
+            self.document.unit = unit;
+            self.modified = true;
         }
     }
 
     fn new_document(&mut self) {
         self.document = Document::with_initial_text_box();
+        self.current_page = 0;
+        self.renaming_page = None;
         self.current_file = None;
         self.modified = false;
         self.selected_id = Some(1);
         self.editing_text = true;
-        self.undo_stack.clear();
-        self.redo_stack.clear();
+        self.history.clear();
     }
 
     fn save(&mut self) {
         if let Some(path) = self.current_file.clone() {
             self.save_to_path(path);
         } else {
-            self.fb_mode = FbMode::Save;
-            self.show_file_browser = true;
+            self.show_file_dialog(FbMode::Save);
         }
     }
 
     fn save_to_path(&mut self, path: PathBuf) {
-        if let Ok(json) = serde_json::to_string_pretty(&self.document) {
+        if let Ok(json) = storage::to_versioned_json(&self.document) {
             let path = if path.extension().is_none() {
                 path.with_extension("sld")
             } else {
                 path
             };
             if std::fs::write(&path, json).is_ok() {
+                self.recent_files.record(&path);
                 self.current_file = Some(path);
                 self.modified = false;
             }
         }
     }
 
-    fn export_png(&mut self, path: &PathBuf) {
-        let w = self.document.page_size.x as u32;
-        let h = self.document.page_size.y as u32;
+    /// Draw a page as it exports to PNG
+    fn render_page(page: &Page, unit: Unit) -> image::RgbaImage {
+        let w = page.size.x as u32;
+        let h = page.size.y as u32;
         let mut img = image::RgbaImage::from_pixel(w, h, image::Rgba([255, 255, 255, 255]));
         // Render elements
-        for elem in &self.document.elements {
+        for elem in &page.elements {
             let r: Rect = elem.rect.into();
             match &elem.content {
                 ElementContent::TextBox(tb) => {
                     use ab_glyph::{FontRef, PxScale, Font as AbFont, ScaleFont};
-                    let font_data = include_bytes!("../../fonts/ibm-plex-sans/IBMPlexSans-Regular.ttf");
-                    let font = FontRef::try_from_slice(font_data).unwrap();
-                    let scale = PxScale::from(tb.font_size);
-                    let scaled_font = font.as_scaled(scale);
-                    let x0 = r.min.x;
-                    let y0 = r.min.y;
-                    let max_x = r.max.x;
-                    let max_y = r.max.y;
-                    let line_height = scaled_font.height() + scaled_font.line_gap();
-                    let ascent = scaled_font.ascent();
-                    let mut cy = y0 + 4.0;
-                    for line in tb.text.split('\n') {
-                        let baseline_y = cy + ascent;
-                        if baseline_y > max_y { break; }
-                        let mut cx = x0 + 4.0;
-                        for ch in line.chars() {
-                            let glyph_id = scaled_font.glyph_id(ch);
-                            let advance = scaled_font.h_advance(glyph_id);
-                            if cx + advance > max_x { break; }
-                            let glyph = glyph_id.with_scale_and_position(scale, ab_glyph::point(cx, baseline_y));
-                            if let Some(outlined) = font.outline_glyph(glyph) {
-                                let bounds = outlined.px_bounds();
-                                outlined.draw(|px, py, cov| {
-                                    if cov > 0.5 {
-                                        let ix = (bounds.min.x as i32 + px as i32) as u32;
-                                        let iy = (bounds.min.y as i32 + py as i32) as u32;
-                                        if ix < w && iy < h {
-                                            img.put_pixel(ix, iy, image::Rgba([0, 0, 0, 255]));
-                                        }
-                                    }
-                                });
+                    let font = FontRef::try_from_slice(FONT_DATA).unwrap();
+                    let scaled_font = font.as_scaled(PxScale::from(tb.font_size));
+                    let advance = |ch: char| scaled_font.h_advance(scaled_font.glyph_id(ch));
+                    let mut draw = |ch: char, origin: Pos2, angle: f32| put_glyph(&mut img, &font, ch, tb.font_size, origin, angle);
+                    if let Some(path) = tb.path {
+                        let points = typeset::path_points(path, r, tb.font_size);
+                        for g in typeset::layout_path(&tb.text, &points, tb.align, advance) {
+                            draw(g.ch, g.pos, g.angle);
+                        }
+                    } else {
+                        let measure = |s: &str| s.chars().map(advance).sum::<f32>();
+                        let runs = typeset::layout_frame(&tb.text, r.width(), r.height(), tb.font_size, tb.leading, tb.align, measure);
+                        for run in runs {
+                            let mut origin = r.min + run.pos.to_vec2() + Vec2::new(0.0, scaled_font.ascent());
+                            for ch in run.text.chars() {
+                                draw(ch, origin, 0.0);
+                                origin.x += advance(ch);
                             }
-                            cx += advance;
                         }
-                        cy += line_height;
                     }
                 }
                 ElementContent::Image(ie) => {
@@ -433,25 +743,7 @@ impl SlowDesignApp {
                                 }
                             }
                         }
-                        ShapeType::Line => {
-                            // Bresenham line
-                            let dx = (x1 - x0).abs();
-                            let dy = -(y1 - y0).abs();
-                            let sx = if x0 < x1 { 1 } else { -1 };
-                            let sy = if y0 < y1 { 1 } else { -1 };
-                            let mut err = dx + dy;
-                            let mut cx = x0;
-                            let mut cy = y0;
-                            loop {
-                                if cx >= 0 && cx < w as i32 && cy >= 0 && cy < h as i32 {
-                                    img.put_pixel(cx as u32, cy as u32, black);
-                                }
-                                if cx == x1 && cy == y1 { break; }
-                                let e2 = 2 * err;
-                                if e2 >= dy { err += dy; cx += sx; }
-                                if e2 <= dx { err += dx; cy += sy; }
-                            }
-                        }
+                        ShapeType::Line => put_line(&mut img, r.min, r.max),
                         _ => {
                             // Ellipse or other: draw bounding rect outline as fallback
                             for x in x0.max(0)..x1.min(w as i32) {
@@ -465,8 +757,33 @@ impl SlowDesignApp {
                         }
                     }
                 }
+                ElementContent::Dimension(d) => {
+                    use ab_glyph::{FontRef, PxScale, Font as AbFont, ScaleFont};
+                    if let Some((a, b)) = page.ends(d) {
+                        let drawn = measure::draw(a, b, d.offset);
+                        for [p, q] in drawn.lines {
+                            put_line(&mut img, p, q);
+                        }
+                        let font = FontRef::try_from_slice(FONT_DATA).unwrap();
+                        let scaled = font.as_scaled(PxScale::from(measure::LABEL_SIZE));
+                        let advance = |ch: char| scaled.h_advance(scaled.glyph_id(ch));
+                        let label = unit.format(a.distance(b));
+                        let width: f32 = label.chars().map(advance).sum();
+                        let mut origin = drawn.label + Vec2::new(-width / 2.0, (scaled.ascent() + scaled.descent()) / 2.0);
+                        for ch in label.chars() {
+                            put_glyph(&mut img, &font, ch, measure::LABEL_SIZE, origin, 0.0);
+                            origin.x += advance(ch);
+                        }
+                    }
+                }
             }
         }
+        img
+    }
+
+    /// Export the page on screen as a PNG
+    fn export_png(&mut self, path: &PathBuf) {
+        let img = Self::render_page(self.page(), self.document.unit);
         let path = if path.extension().is_none() { path.with_extension("png") } else { path.clone() };
         if let Some(parent) = path.parent() {
             let _ = std::fs::create_dir_all(parent);
@@ -481,91 +798,143 @@ impl SlowDesignApp {
         }
     }
 
+    /// Export every page as a PNG, numbered after the name given
+    /// ("zine-01.png", "zine-02.png", …)
+    fn export_all_png(&mut self, path: &Path) {
+        let stem = path.file_stem().map_or("untitled".into(), |s| s.to_string_lossy().into_owned());
+        let dir = path.parent().map_or_else(documents_dir, Path::to_path_buf);
+        let _ = std::fs::create_dir_all(&dir);
+        for (i, page) in self.document.pages.iter().enumerate() {
+            let file = dir.join(format!("{}-{:02}.png", stem, i + 1));
+            if let Err(e) = Self::render_page(page, self.document.unit).save(&file) {
+                self.status_message = Some(format!("export failed: {}", e));
+                return;
+            }
+        }
+        self.status_message = Some(format!("exported {} pages as {}-01.png…", self.document.pages.len(), stem));
+    }
+
+    /// Export every page into one PDF, each at its own size
     fn export_pdf(&mut self, path: &PathBuf) {
         use printpdf::{BuiltinFont, Mm, PdfDocument};
 
         let pdf_path = if path.extension().is_none() { path.with_extension("pdf") } else { path.clone() };
         let to_mm = |px: f32| -> f32 { px * 25.4 / 96.0 };
-        let pw = to_mm(self.document.page_size.x);
-        let ph = to_mm(self.document.page_size.y);
+        let first = self.document.pages[0].size;
 
-        let (doc, page1, layer1) = PdfDocument::new("slowDesign Export", Mm(pw), Mm(ph), "Layer 1");
-        let font = doc.add_builtin_font(BuiltinFont::Helvetica).unwrap();
+        let (doc, page1, layer1) = PdfDocument::new("slowDesign Export", Mm(to_mm(first.x)), Mm(to_mm(first.y)), "Layer 1");
+        // Plex, as on screen, so lines break where they do there
+        let font = doc.add_external_font(FONT_DATA)
+            .or_else(|_| doc.add_builtin_font(BuiltinFont::Helvetica))
+            .unwrap();
 
-        let layer = doc.get_page(page1).get_layer(layer1);
+        for (i, page) in self.document.pages.iter().enumerate() {
+            let pw = to_mm(page.size.x);
+            let ph = to_mm(page.size.y);
+            let (page_index, layer_index) = if i == 0 { (page1, layer1) } else { doc.add_page(Mm(pw), Mm(ph), "Layer 1") };
+            let layer = doc.get_page(page_index).get_layer(layer_index);
 
-        for elem in &self.document.elements {
-            let r: egui::Rect = elem.rect.into();
+            for elem in &page.elements {
+                let r: egui::Rect = elem.rect.into();
 
-            match &elem.content {
-                ElementContent::TextBox(tb) => {
-                    let font_size_pt = tb.font_size;
-                    let line_height_pt = font_size_pt * 1.3;
-                    let mut y_offset_pt = 0.0_f32;
-
-                    for line in tb.text.split('\n') {
-                        if line.is_empty() {
-                            y_offset_pt += line_height_pt;
-                            continue;
+                match &elem.content {
+                    ElementContent::TextBox(tb) => {
+                        use ab_glyph::{FontRef, PxScale, Font as AbFont, ScaleFont};
+                        let metrics = FontRef::try_from_slice(FONT_DATA).unwrap();
+                        let scaled = metrics.as_scaled(PxScale::from(tb.font_size));
+                        let advance = |ch: char| scaled.h_advance(scaled.glyph_id(ch));
+                        let font_size_pt = pdf_font_size(&metrics, tb.font_size);
+                        let at = |p: Pos2| (Mm(to_mm(p.x)), Mm(ph - to_mm(p.y)));
+                        if let Some(path) = tb.path {
+                            let points = typeset::path_points(path, r, tb.font_size);
+                            for g in typeset::layout_path(&tb.text, &points, tb.align, advance) {
+                                let (x, y) = at(g.pos);
+                                layer.begin_text_section();
+                                layer.set_font(&font, font_size_pt);
+                                layer.set_text_matrix(printpdf::TextMatrix::TranslateRotate(x.into(), y.into(), -g.angle.to_degrees()));
+                                layer.write_text(g.ch.to_string(), &font);
+                                layer.end_text_section();
+                            }
+                        } else {
+                            let measure = |s: &str| s.chars().map(advance).sum::<f32>();
+                            let runs = typeset::layout_frame(&tb.text, r.width(), r.height(), tb.font_size, tb.leading, tb.align, measure);
+                            for run in runs {
+                                let (x, y) = at(r.min + run.pos.to_vec2() + Vec2::new(0.0, scaled.ascent()));
+                                layer.use_text(run.text, font_size_pt, x, y, &font);
+                            }
                         }
-                        let x = to_mm(r.min.x) + 1.0;
-                        let y = ph - to_mm(r.min.y) - y_offset_pt * 25.4 / 72.0 - font_size_pt * 25.4 / 72.0;
-                        layer.use_text(line, font_size_pt, Mm(x), Mm(y), &font);
-                        y_offset_pt += line_height_pt;
                     }
-                }
-                ElementContent::Image(ie) => {
-                    if let Ok(file_img) = image::open(&ie.path) {
-                        let rgb = file_img.to_rgb8();
-                        let (iw, ih) = rgb.dimensions();
-                        let image_data = rgb.into_raw();
-
-                        let pdf_img = printpdf::Image::from(printpdf::ImageXObject {
-                            width: printpdf::Px(iw as usize),
-                            height: printpdf::Px(ih as usize),
-                            color_space: printpdf::ColorSpace::Rgb,
-                            bits_per_component: printpdf::ColorBits::Bit8,
-                            interpolate: true,
-                            image_data,
-                            image_filter: None,
-                            smask: None,
-                            clipping_bbox: None,
-                        });
-                        {
-                            let native_w_mm = iw as f32 * 25.4 / 96.0;
-                            let native_h_mm = ih as f32 * 25.4 / 96.0;
-                            pdf_img.add_to_layer(
-                                layer.clone(), printpdf::ImageTransform {
-                                    translate_x: Some(Mm(to_mm(r.min.x))),
-                                    translate_y: Some(Mm(ph - to_mm(r.max.y))),
-                                    scale_x: Some(to_mm(r.width()) / native_w_mm),
-                                    scale_y: Some(to_mm(r.height()) / native_h_mm),
-                                    ..Default::default()
-                                },
-                            );
+                    ElementContent::Image(ie) => {
+                        if let Ok(file_img) = image::open(&ie.path) {
+                            let rgb = file_img.to_rgb8();
+                            let (iw, ih) = rgb.dimensions();
+                            let image_data = rgb.into_raw();
+
+                            let pdf_img = printpdf::Image::from(printpdf::ImageXObject {
+                                width: printpdf::Px(iw as usize),
+                                height: printpdf::Px(ih as usize),
+                                color_space: printpdf::ColorSpace::Rgb,
+                                bits_per_component: printpdf::ColorBits::Bit8,
+                                interpolate: true,
+                                image_data,
+                                image_filter: None,
+                                smask: None,
+                                clipping_bbox: None,
+                            });
+                            {
+                                let native_w_mm = iw as f32 * 25.4 / 96.0;
+                                let native_h_mm = ih as f32 * 25.4 / 96.0;
+                                pdf_img.add_to_layer(
+                                    layer.clone(), printpdf::ImageTransform {
+                                        translate_x: Some(Mm(to_mm(r.min.x))),
+                                        translate_y: Some(Mm(ph - to_mm(r.max.y))),
+                                        scale_x: Some(to_mm(r.width()) / native_w_mm),
+                                        scale_y: Some(to_mm(r.height()) / native_h_mm),
+                                        ..Default::default()
+                                    },
+                                );
+                            }
                         }
                     }
-                }
-                ElementContent::Shape(shape) => {
-                    let x0 = to_mm(r.min.x);
-                    let y0 = ph - to_mm(r.max.y);
-                    let x1 = to_mm(r.max.x);
-                    let y1 = ph - to_mm(r.min.y);
-                    let outline = printpdf::Line {
-                        points: vec![
-                            (printpdf::Point::new(Mm(x0), Mm(y0)), false),
-                            (printpdf::Point::new(Mm(x1), Mm(y0)), false),
-                            (printpdf::Point::new(Mm(x1), Mm(y1)), false),
-                            (printpdf::Point::new(Mm(x0), Mm(y1)), false),
-                        ],
-                        is_closed: true,
-                    };
-                    layer.set_outline_thickness(1.0);
-                    if shape.fill {
-                        layer.set_fill_color(printpdf::Color::Rgb(printpdf::Rgb::new(0.0, 0.0, 0.0, None)));
+                    ElementContent::Shape(shape) => {
+                        let x0 = to_mm(r.min.x);
+                        let y0 = ph - to_mm(r.max.y);
+                        let x1 = to_mm(r.max.x);
+                        let y1 = ph - to_mm(r.min.y);
+                        let outline = printpdf::Line {
+                            points: vec![
+                                (printpdf::Point::new(Mm(x0), Mm(y0)), false),
+                                (printpdf::Point::new(Mm(x1), Mm(y0)), false),
+                                (printpdf::Point::new(Mm(x1), Mm(y1)), false),
+                                (printpdf::Point::new(Mm(x0), Mm(y1)), false),
+                            ],
+                            is_closed: true,
+                        };
+                        layer.set_outline_thickness(1.0);
+                        if shape.fill {
+                            layer.set_fill_color(printpdf::Color::Rgb(printpdf::Rgb::new(0.0, 0.0, 0.0, None)));
+                        }
+                        layer.set_outline_color(printpdf::Color::Rgb(printpdf::Rgb::new(0.0, 0.0, 0.0, None)));
+                        layer.add_line(outline);
+                    }
+                    ElementContent::Dimension(d) => {
+                        use ab_glyph::{FontRef, PxScale, Font as AbFont, ScaleFont};
+                        if let Some((a, b)) = page.ends(d) {
+                            let at = |p: Pos2| printpdf::Point::new(Mm(to_mm(p.x)), Mm(ph - to_mm(p.y)));
+                            let drawn = measure::draw(a, b, d.offset);
+                            layer.set_outline_thickness(1.0);
+                            layer.set_outline_color(printpdf::Color::Rgb(printpdf::Rgb::new(0.0, 0.0, 0.0, None)));
+                            for [p, q] in drawn.lines {
+                                layer.add_line(printpdf::Line { points: vec![(at(p), false), (at(q), false)], is_closed: false });
+                            }
+                            let metrics = FontRef::try_from_slice(FONT_DATA).unwrap();
+                            let scaled = metrics.as_scaled(PxScale::from(measure::LABEL_SIZE));
+                            let label = self.document.unit.format(a.distance(b));
+                            let width: f32 = label.chars().map(|ch| scaled.h_advance(scaled.glyph_id(ch))).sum();
+                            let origin = drawn.label + Vec2::new(-width / 2.0, (scaled.ascent() + scaled.descent()) / 2.0);
+                            layer.use_text(label, pdf_font_size(&metrics, measure::LABEL_SIZE), Mm(to_mm(origin.x)), Mm(ph - to_mm(origin.y)), &font);
+                        }
                     }
-                    layer.set_outline_color(printpdf::Color::Rgb(printpdf::Rgb::new(0.0, 0.0, 0.0, None)));
-                    layer.add_line(outline);
                 }
             }
         }
@@ -592,13 +961,19 @@ impl SlowDesignApp {
 
     fn open(&mut self, path: PathBuf) {
         if let Ok(content) = std::fs::read_to_string(&path) {
-            if let Ok(doc) = serde_json::from_str::<Document>(&content) {
+            if let Ok(doc) = storage::from_versioned_json::<Document>(&content) {
                 self.document = doc;
+                if self.document.pages.is_empty() {
+                    self.document.pages = Document::default().pages;
+                }
+                self.current_page = 0;
+                self.renaming_page = None;
+                self.scroll_offset = Vec2::ZERO;
+                self.recent_files.record(&path);
                 self.current_file = Some(path);
                 self.modified = false;
                 self.selected_id = None;
-                self.undo_stack.clear();
-                self.redo_stack.clear();
+                self.history.clear();
             }
         }
     }
@@ -607,15 +982,15 @@ impl SlowDesignApp {
         self.save_undo_state();
         let id = self.document.next_id;
         self.document.next_id += 1;
-        self.document.elements.push(DesignElement { id, rect: rect.into(), content, locked: false });
+        self.page_mut().elements.push(DesignElement { id, rect: rect.into(), content, locked: false });
         self.selected_id = Some(id);
         self.modified = true;
     }
 
     /// Calculate required height for a text box based on content
-    fn calculate_text_height(text: &str, font_size: f32, box_width: f32) -> f32 {
+    fn calculate_text_height(text: &str, font_size: f32, leading: f32, box_width: f32) -> f32 {
         let char_width = font_size * 0.6; // Approximate character width
-        let line_height = font_size * 1.4; // Line height with spacing
+        let line_height = font_size * leading; // Line height with spacing
         let padding = 8.0; // Padding inside box
 
         let chars_per_line = ((box_width - padding * 2.0) / char_width).max(1.0) as usize;
@@ -651,8 +1026,12 @@ impl SlowDesignApp {
     fn auto_resize_text_box(&mut self, element_id: u64) {
         if let Some(elem) = self.document.get_mut(element_id) {
             if let ElementContent::TextBox(ref tb) = elem.content {
+                // Text on a path keeps the box it's drawn across
+                if tb.path.is_some() {
+                    return;
+                }
                 let current_rect: Rect = elem.rect.into();
-                let new_height = Self::calculate_text_height(&tb.text, tb.font_size, current_rect.width());
+                let new_height = Self::calculate_text_height(&tb.text, tb.font_size, tb.leading, current_rect.width());
                 let new_max_y = current_rect.min.y + new_height;
 
                 // Only grow, don't shrink below minimum
@@ -675,7 +1054,8 @@ impl SlowDesignApp {
                 return;
             }
             self.save_undo_state();
-            self.document.elements.retain(|e| e.id != id);
+            self.page_mut().unpin(id);
+            self.page_mut().elements.retain(|e| e.id != id);
             self.selected_id = None;
             self.modified = true;
         }
@@ -707,6 +1087,75 @@ impl SlowDesignApp {
         None
     }
 
+    fn show_file_dialog(&mut self, mode: FbMode) {
+        let dir = self.current_file.as_ref()
+            .and_then(|p| p.parent())
+            .map_or_else(documents_dir, Path::to_path_buf);
+        let stem = self.current_file.as_ref()
+            .and_then(|p| p.file_stem())
+            .map_or("untitled".to_string(), |s| s.to_string_lossy().into_owned());
+        let dialog = match mode {
+            FbMode::Open => FileDialog::open(dir).title("open document").filter(&["sld"]),
+            FbMode::InsertImage => FileDialog::open(dir)
+                .title("insert image")
+                .filter(&["png", "jpg", "jpeg", "gif", "bmp"])
+                .action("insert"),
+            FbMode::Save => FileDialog::save(dir, &format!("{}.sld", stem))
+                .title("save document")
+                .filter(&["sld"])
+                .default_extension("sld"),
+            FbMode::ExportPng | FbMode::ExportAllPng => {
+                let title = if mode == FbMode::ExportPng { "export page as PNG" } else { "export all pages as PNG" };
+                FileDialog::save(dir, &format!("{}.png", stem))
+                    .title(title)
+                    .filter(&["png"])
+                    .default_extension("png")
+                    .action("export")
+            }
+            FbMode::ExportPdf => FileDialog::save(dir, &format!("{}.pdf", stem))
+                .title("export as PDF")
+                .filter(&["pdf"])
+                .default_extension("pdf")
+                .action("export"),
+        };
+        self.fb_mode = mode;
+        self.file_dialog = Some(dialog);
+    }
+
+    /// Place a picked image in the pending marquee
+    fn insert_image(&mut self, ctx: &Context, path: PathBuf) {
+        if let Some(marquee_rect) = self.pending_image_rect.take() {
+            let texture_id = self.load_image_texture(ctx, &path);
+
+            // Scale image to fit vertically within the marquee without squishing.
+            // Load the image to get its native dimensions.
+            let final_rect = if let Ok(bytes) = std::fs::read(&path) {
+                if let Ok(img) = image::load_from_memory(&bytes) {
+                    let (iw, ih) = (img.width() as f32, img.height() as f32);
+                    if ih > 0.0 {
+                        let aspect = iw / ih;
+                        let target_h = marquee_rect.height();
+                        let target_w = target_h * aspect;
+                        // Center horizontally within the marquee
+                        let cx = marquee_rect.center().x;
+                        Rect::from_min_size(
+                            Pos2::new(cx - target_w / 2.0, marquee_rect.min.y),
+                            Vec2::new(target_w, target_h),
+                        )
+                    } else {
+                        marquee_rect
+                    }
+                } else {
+                    marquee_rect
+                }
+            } else {
+                marquee_rect
+            };
+
+            self.add_element(ElementContent::Image(ImageElement { path, texture_id }), final_rect);
+        }
+    }
+
     fn handle_keyboard(&mut self, ctx: &Context) {
         slowcore::theme::consume_special_keys(ctx);
 
@@ -721,34 +1170,52 @@ impl SlowDesignApp {
         if zoom_in { self.zoom = (self.zoom + 0.25).min(4.0); }
         if zoom_out { self.zoom = (self.zoom - 0.25).max(0.25); }
 
+        match undo::take_shortcut(ctx) {
+            Some(UndoAction::Undo) => self.undo(),
+            Some(UndoAction::Redo) => self.redo(),
+            None => {}
+        }
+
         ctx.input(|i| {
             let cmd = i.modifiers.command;
 
-            if cmd && i.key_pressed(Key::N) { self.new_document(); }
+            if cmd && i.modifiers.shift && i.key_pressed(Key::N) { self.add_page(); }
+            if cmd && !i.modifiers.shift && i.key_pressed(Key::N) { self.new_document(); }
             if cmd && i.key_pressed(Key::O) {
-                self.fb_mode = FbMode::Open;
-                self.show_file_browser = true;
+                self.show_file_dialog(FbMode::Open);
             }
             if cmd && i.key_pressed(Key::S) { self.save(); }
-            if cmd && i.key_pressed(Key::Z) && !i.modifiers.shift { self.undo(); }
-            if cmd && i.key_pressed(Key::Z) && i.modifiers.shift { self.redo(); }
             if (i.key_pressed(Key::Delete) || i.key_pressed(Key::Backspace)) && !self.editing_text {
                 self.delete_selected();
             }
+            if cmd && i.key_pressed(Key::R) { self.show_rulers = !self.show_rulers; }
             if i.key_pressed(Key::Escape) {
+                self.measuring = None;
                 self.selected_id = None;
                 self.editing_text = false;
                 self.tool = Tool::Select;
             }
 
-            // Tool shortcuts (only when not editing text)
-            if !self.editing_text {
+            // Tool shortcuts (only when not editing text, and not ⌘R for the rulers)
+            if !self.editing_text && !cmd {
                 if i.key_pressed(Key::V) { self.tool = Tool::Select; }
                 if i.key_pressed(Key::T) { self.tool = Tool::TextBox; }
+                if i.key_pressed(Key::P) { self.tool = Tool::PathText; }
                 if i.key_pressed(Key::I) { self.tool = Tool::Image; }
                 if i.key_pressed(Key::R) { self.tool = Tool::Rectangle; }
                 if i.key_pressed(Key::E) { self.tool = Tool::Ellipse; }
                 if i.key_pressed(Key::L) { self.tool = Tool::Line; }
+                if i.key_pressed(Key::M) { self.tool = Tool::Measure; }
+                if i.key_pressed(Key::D) { self.tool = Tool::Dimension; }
+            }
+
+            // Page navigation, unless a page name is being typed
+            if !self.editing_text && self.renaming_page.is_none() {
+                let last = self.document.pages.len() - 1;
+                if i.key_pressed(Key::PageUp) { self.go_to_page(self.current_page.saturating_sub(1)); }
+                if i.key_pressed(Key::PageDown) { self.go_to_page((self.current_page + 1).min(last)); }
+                if i.key_pressed(Key::Home) { self.go_to_page(0); }
+                if i.key_pressed(Key::End) { self.go_to_page(last); }
             }
         });
     }
@@ -758,10 +1225,13 @@ impl SlowDesignApp {
             let tools = [
                 (Tool::Select, "select (V)"),
                 (Tool::TextBox, "text (T)"),
+                (Tool::PathText, "path text (P)"),
                 (Tool::Image, "image (I)"),
                 (Tool::Rectangle, "rect (R)"),
                 (Tool::Ellipse, "ellipse (E)"),
                 (Tool::Line, "line (L)"),
+                (Tool::Measure, "measure (M)"),
+                (Tool::Dimension, "dimension (D)"),
             ];
 
             for (tool, label) in tools {
@@ -781,7 +1251,7 @@ impl SlowDesignApp {
         painter.rect_filled(canvas_rect, 0.0, SlowColors::WHITE);
 
         // Page
-        let page_size = Vec2::from(self.document.page_size.clone()) * self.zoom;
+        let page_size = Vec2::from(self.page().size) * self.zoom;
         let page_origin = Pos2::new(
             canvas_rect.center().x - page_size.x / 2.0 + self.scroll_offset.x,
             canvas_rect.min.y + 20.0 + self.scroll_offset.y,
@@ -799,7 +1269,7 @@ impl SlowDesignApp {
         painter.rect_stroke(page_rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
 
         // Draw elements
-        for element in &self.document.elements {
+        for element in &self.page().elements {
             let elem_rect: Rect = element.rect.into();
             let screen_rect = self.to_screen_rect(elem_rect, page_origin);
             let is_selected = self.selected_id == Some(element.id);
@@ -809,57 +1279,33 @@ impl SlowDesignApp {
                     if is_selected {
                         painter.rect_stroke(screen_rect, 0.0, Stroke::new(1.0, Color32::BLUE));
                     }
-                    // Render text with word wrapping
-                    let font_size = tb.font_size * self.zoom;
-                    let padding = 4.0 * self.zoom;
-                    let text_width = screen_rect.width() - padding * 2.0;
-                    let line_height = font_size * 1.4;
-                    let char_width = font_size * 0.55; // Approximate
-                    let chars_per_line = (text_width / char_width).max(1.0) as usize;
-
-                    // Word wrap the text (preserving leading/multiple spaces)
-                    let mut lines: Vec<String> = Vec::new();
-                    for paragraph in tb.text.split('\n') {
-                        if paragraph.is_empty() {
-                            lines.push(String::new());
-                        } else {
-                            let mut current_line = String::new();
-                            for word in paragraph.split(' ') {
-                                if word.is_empty() {
-                                    // Consecutive space — preserve it
-                                    if current_line.len() < chars_per_line {
-                                        current_line.push(' ');
-                                    }
-                                } else if current_line.trim().is_empty() {
-                                    // First real word (possibly after leading spaces)
-                                    current_line.push_str(word);
-                                } else if current_line.len() + 1 + word.len() <= chars_per_line {
-                                    current_line.push(' ');
-                                    current_line.push_str(word);
-                                } else {
-                                    lines.push(current_line);
-                                    current_line = word.to_string();
-                                }
-                            }
-                            if !current_line.is_empty() {
-                                lines.push(current_line);
-                            }
+                    // Laid out at page size, drawn at the zoom
+                    let font_id = FontId::proportional(tb.font_size);
+                    let zoomed = FontId::proportional(tb.font_size * self.zoom);
+                    let to_screen = |p: Pos2| screen_rect.min + p.to_vec2() * self.zoom;
+                    if let Some(path) = tb.path {
+                        let points = typeset::path_points(path, Rect::from_min_size(Pos2::ZERO, elem_rect.size()), tb.font_size);
+                        let glyphs = ctx.fonts(|f| typeset::layout_path(&tb.text, &points, tb.align, |c| f.glyph_width(&font_id, c)));
+                        for g in glyphs {
+                            let galley = painter.layout_no_wrap(g.ch.to_string(), zoomed.clone(), SlowColors::BLACK);
+                            // Text shapes turn about their top left corner;
+                            // this puts the baseline on the path
+                            let baseline = galley.rows.first().and_then(|row| row.glyphs.first()).map_or(0.0, |glyph| glyph.pos.y);
+                            let pos = to_screen(g.pos) - egui::emath::Rot2::from_angle(g.angle) * Vec2::new(0.0, baseline);
+                            painter.add(egui::epaint::TextShape::new(pos, galley, SlowColors::BLACK).with_angle(g.angle));
                         }
-                    }
-
-                    // Render each line
-                    for (i, line) in lines.iter().enumerate() {
-                        let y = screen_rect.min.y + padding + i as f32 * line_height;
-                        if y + line_height > screen_rect.max.y {
-                            break; // Stop if we've exceeded the box
+                        if is_selected {
+                            let guide: Vec<Pos2> = points.into_iter().map(to_screen).collect();
+                            painter.extend(egui::Shape::dashed_line(&guide, Stroke::new(1.0, Color32::BLUE), 4.0, 4.0));
+                        }
+                    } else {
+                        let runs = ctx.fonts(|f| {
+                            let measure = |s: &str| s.chars().map(|c| f.glyph_width(&font_id, c)).sum::<f32>();
+                            typeset::layout_frame(&tb.text, elem_rect.width(), elem_rect.height(), tb.font_size, tb.leading, tb.align, measure)
+                        });
+                        for run in runs {
+                            painter.text(to_screen(run.pos), egui::Align2::LEFT_TOP, run.text, zoomed.clone(), SlowColors::BLACK);
                         }
-                        painter.text(
-                            Pos2::new(screen_rect.min.x + padding, y),
-                            egui::Align2::LEFT_TOP,
-                            line,
-                            FontId::proportional(font_size),
-                            SlowColors::BLACK,
-                        );
                     }
                 }
                 ElementContent::Image(img) => {
@@ -907,10 +1353,22 @@ impl SlowDesignApp {
                         painter.rect_stroke(screen_rect.expand(2.0), 0.0, Stroke::new(1.0, Color32::BLUE));
                     }
                 }
+                ElementContent::Dimension(d) => {
+                    if let Some((a, b)) = self.page().ends(d) {
+                        let drawn = measure::draw(a, b, d.offset);
+                        let to_screen = |p: Pos2| page_origin + p.to_vec2() * self.zoom;
+                        let color = if is_selected { Color32::BLUE } else { SlowColors::BLACK };
+                        for [p, q] in drawn.lines {
+                            painter.line_segment([to_screen(p), to_screen(q)], Stroke::new(self.zoom, color));
+                        }
+                        let label = self.document.unit.format(a.distance(b));
+                        painter.text(to_screen(drawn.label), egui::Align2::CENTER_CENTER, label, FontId::proportional(measure::LABEL_SIZE * self.zoom), color);
+                    }
+                }
             }
 
-            // Selection handles (skip for locked elements)
-            if is_selected && !element.locked {
+            // Selection handles (skip for locked elements and dimensions)
+            if is_selected && element.movable() {
                 for corner in [screen_rect.min, Pos2::new(screen_rect.max.x, screen_rect.min.y),
                               screen_rect.max, Pos2::new(screen_rect.min.x, screen_rect.max.y)] {
                     let h = Rect::from_center_size(corner, Vec2::splat(6.0));
@@ -931,9 +1389,98 @@ impl SlowDesignApp {
             }
         }
 
+        // Line being measured
+        if let Some((a, b)) = self.measuring {
+            let length = self.document.unit.format(a.distance(b));
+            let (a, b) = (page_origin + a.to_vec2() * self.zoom, page_origin + b.to_vec2() * self.zoom);
+            painter.extend(egui::Shape::dashed_line(&[a, b], Stroke::new(1.0, Color32::BLUE), 4.0, 4.0));
+            for p in [a, b] {
+                painter.circle_stroke(p, 3.0, Stroke::new(1.0, Color32::BLUE));
+            }
+            painter.text(a + (b - a) / 2.0 + Vec2::new(6.0, -6.0), egui::Align2::LEFT_BOTTOM, length, FontId::proportional(12.0), Color32::BLUE);
+        }
+
+        if self.show_rulers {
+            self.render_rulers(ui, &painter, canvas_rect, page_origin);
+        }
+
         self.handle_canvas_input(&response, page_origin, ctx);
     }
 
+    /// Rulers along the top and left of the canvas, counting from the
+    /// page's top left corner; the box where they meet shows the unit
+    /// and changes it on a click
+    fn render_rulers(&mut self, ui: &mut egui::Ui, painter: &egui::Painter, canvas_rect: Rect, page_origin: Pos2) {
+        let unit = self.document.unit;
+        let (minor, major) = measure::ruler_steps(unit, self.zoom);
+        let per_tick = minor * unit.px() * self.zoom;
+        let ticks_per_label = (major / minor).round() as i64;
+        let font = FontId::proportional(9.0);
+        let stroke = Stroke::new(1.0, SlowColors::BLACK);
+        let hover = ui.ctx().pointer_hover_pos().filter(|p| canvas_rect.contains(*p));
+
+        let corner = Rect::from_min_size(canvas_rect.min, Vec2::splat(RULER_WIDTH));
+        let top = Rect::from_min_max(Pos2::new(corner.max.x, canvas_rect.min.y), Pos2::new(canvas_rect.max.x, corner.max.y));
+        let left = Rect::from_min_max(Pos2::new(canvas_rect.min.x, corner.max.y), Pos2::new(corner.max.x, canvas_rect.max.y));
+        for (ruler, across) in [(top, true), (left, false)] {
+            let painter = painter.with_clip_rect(ruler);
+            painter.rect_filled(ruler, 0.0, SlowColors::WHITE);
+            let (origin, start, end) = if across {
+                (page_origin.x, ruler.min.x, ruler.max.x)
+            } else {
+                (page_origin.y, ruler.min.y, ruler.max.y)
+            };
+            let first = ((start - origin) / per_tick).floor() as i64;
+            let last = ((end - origin) / per_tick).ceil() as i64;
+            for i in first..=last {
+                let at = origin + i as f32 * per_tick;
+                let labelled = i.rem_euclid(ticks_per_label) == 0;
+                let length = if labelled { RULER_WIDTH } else { RULER_WIDTH / 3.0 };
+                if across {
+                    painter.line_segment([Pos2::new(at, ruler.max.y - length), Pos2::new(at, ruler.max.y)], stroke);
+                } else {
+                    painter.line_segment([Pos2::new(ruler.max.x - length, at), Pos2::new(ruler.max.x, at)], stroke);
+                }
+                if labelled {
+                    let value = format!("{}", i as f32 * minor);
+                    let pos = if across { Pos2::new(at + 2.0, ruler.min.y) } else { Pos2::new(ruler.min.x + 1.0, at + 1.0) };
+                    painter.text(pos, egui::Align2::LEFT_TOP, value, font.clone(), SlowColors::BLACK);
+                }
+            }
+            // The pointer's place
+            if let Some(p) = hover {
+                let mark = if across {
+                    [Pos2::new(p.x, ruler.min.y), Pos2::new(p.x, ruler.max.y)]
+                } else {
+                    [Pos2::new(ruler.min.x, p.y), Pos2::new(ruler.max.x, p.y)]
+                };
+                painter.line_segment(mark, Stroke::new(1.0, Color32::BLUE));
+            }
+            let edge = if across {
+                [ruler.left_bottom(), ruler.right_bottom()]
+            } else {
+                [ruler.right_top(), ruler.right_bottom()]
+            };
+            painter.line_segment(edge, stroke);
+        }
+
+        painter.rect_filled(corner, 0.0, SlowColors::WHITE);
+        painter.rect_stroke(corner, 0.0, stroke);
+        painter.text(corner.center(), egui::Align2::CENTER_CENTER, unit.name(), font, SlowColors::BLACK);
+        let corner_resp = ui.interact(corner, ui.id().with("ruler_unit"), Sense::click()).on_hover_text("change units");
+        if corner_resp.clicked() {
+            let next = Unit::ALL[(Unit::ALL.iter().position(|&u| u == unit).unwrap_or(0) + 1) % Unit::ALL.len()];
+            self.set_unit(next);
+        }
+    }
+
+    /// A point on the page, pulled onto an element's corner, edge middle
+    /// or centre if the pointer is close to one
+    fn snapped(&self, page_pos: Pos2) -> Pos2 {
+        let frames = self.page().snap_frames();
+        measure::snap(page_pos, &frames, SNAP_REACH / self.zoom).resolve(|id| self.page().frame(id)).unwrap_or(page_pos)
+    }
+
     fn to_screen_rect(&self, r: Rect, page_origin: Pos2) -> Rect {
         Rect::from_min_max(
             page_origin + r.min.to_vec2() * self.zoom,
@@ -956,7 +1503,7 @@ impl SlowDesignApp {
                 let page_pos = self.to_page_pos(pos, page_origin);
                 if self.tool == Tool::Select {
                     self.selected_id = None;
-                    for element in self.document.elements.iter().rev() {
+                    for element in self.document.pages[self.current_page].elements.iter().rev() {
                         let r: Rect = element.rect.into();
                         if r.contains(page_pos) {
                             self.selected_id = Some(element.id);
@@ -971,7 +1518,7 @@ impl SlowDesignApp {
         if response.double_clicked() {
             if let Some(pos) = pointer_pos {
                 let page_pos = self.to_page_pos(pos, page_origin);
-                for element in self.document.elements.iter().rev() {
+                for element in self.document.pages[self.current_page].elements.iter().rev() {
                     let r: Rect = element.rect.into();
                     if r.contains(page_pos) {
                         if matches!(element.content, ElementContent::TextBox(_)) {
@@ -993,7 +1540,7 @@ impl SlowDesignApp {
                         let mut handled = false;
                         if let Some(id) = self.selected_id {
                             if let Some(elem) = self.document.get(id) {
-                                let is_locked = elem.locked;
+                                let is_locked = !elem.movable();
                                 let r: Rect = elem.rect.into();
                                 if !is_locked {
                                     // Check if clicking on a corner handle (for resizing)
@@ -1019,18 +1566,18 @@ impl SlowDesignApp {
                                         handled = true;
                                     }
                                 } else if r.contains(page_pos) {
-                                    // Locked element clicked — select it but don't drag
+                                    // Locked element or dimension clicked — select it but don't drag
                                     handled = true;
                                 }
                             }
                         }
                         // If not handled, try to select an element under the pointer
                         if !handled {
-                            for element in self.document.elements.iter().rev() {
+                            for element in self.document.pages[self.current_page].elements.iter().rev() {
                                 let r: Rect = element.rect.into();
                                 if r.contains(page_pos) {
                                     self.selected_id = Some(element.id);
-                                    if !element.locked {
+                                    if element.movable() {
                                         self.dragging = true;
                                         self.drag_offset = page_pos - r.min;
                                     }
@@ -1039,11 +1586,22 @@ impl SlowDesignApp {
                             }
                         }
                     }
+                    Tool::Measure | Tool::Dimension => {
+                        let p = self.snapped(page_pos);
+                        self.measuring = Some((p, p));
+                    }
                     _ => { self.drawing_start = Some(pos); }
                 }
             }
         }
 
+        if response.dragged() && matches!(self.tool, Tool::Measure | Tool::Dimension) {
+            if let (Some(pos), Some((start, _))) = (pointer_pos, self.measuring) {
+                let page_pos = self.to_page_pos(pos, page_origin);
+                self.measuring = Some((start, self.snapped(page_pos)));
+            }
+        }
+
         // Handle resizing
         if response.dragged() && self.resizing_corner.is_some() {
             if let Some(pos) = pointer_pos {
@@ -1083,6 +1641,17 @@ impl SlowDesignApp {
         }
 
         if response.drag_stopped() {
+            if self.tool == Tool::Dimension {
+                if let Some((a, b)) = self.measuring.take() {
+                    if a.distance(b) > 5.0 {
+                        let frames = self.page().snap_frames();
+                        let reach = SNAP_REACH / self.zoom;
+                        let dimension = Dimension { from: measure::snap(a, &frames, reach), to: measure::snap(b, &frames, reach), offset: 20.0 };
+                        self.add_element(ElementContent::Dimension(dimension), Rect::from_two_pos(a, b));
+                        self.page_mut().place_dimensions();
+                    }
+                }
+            }
             if self.dragging || self.resizing_corner.is_some() {
                 self.save_undo_state();
                 self.dragging = false;
@@ -1096,12 +1665,18 @@ impl SlowDesignApp {
                     if rect.width() > 5.0 && rect.height() > 5.0 {
                         match self.tool {
                             Tool::TextBox => self.add_element(ElementContent::TextBox(TextBox::default()), rect),
+                            Tool::PathText => self.add_element(ElementContent::TextBox(TextBox {
+                                text: "Text on a path".to_string(),
+                                align: TextAlign::Center,
+                                path: Some(TextPath::Arc),
+                                ..Default::default()
+                            }), rect),
                             Tool::Rectangle => self.add_element(ElementContent::Shape(ShapeElement { shape_type: ShapeType::Rectangle, ..Default::default() }), rect),
                             Tool::Ellipse => self.add_element(ElementContent::Shape(ShapeElement { shape_type: ShapeType::Ellipse, ..Default::default() }), rect),
                             Tool::Line => self.add_element(ElementContent::Shape(ShapeElement { shape_type: ShapeType::Line, ..Default::default() }), rect),
                             Tool::Image => {
                                 self.pending_image_rect = Some(rect);
-                                self.show_image_picker = true;
+                                self.show_file_dialog(FbMode::InsertImage);
                             }
                             _ => {}
                         }
@@ -1114,7 +1689,7 @@ impl SlowDesignApp {
         let scroll = ctx.input(|i| i.raw_scroll_delta);
         if scroll.y != 0.0 {
             self.scroll_offset.y += scroll.y;
-            let page_height = self.document.page_size.y * self.zoom;
+            let page_height = self.page().size.y * self.zoom;
             let canvas_height = response.rect.height();
             let max_scroll = 50.0;
             let min_scroll = -(page_height + 50.0 - canvas_height).max(0.0);
@@ -1122,7 +1697,7 @@ impl SlowDesignApp {
         }
         if scroll.x != 0.0 {
             self.scroll_offset.x += scroll.x;
-            let page_width = self.document.page_size.x * self.zoom;
+            let page_width = self.page().size.x * self.zoom;
             let canvas_width = response.rect.width();
             // Allow just enough scroll to see the page edge + 1px margin
             let limit = ((page_width - canvas_width) / 2.0).max(0.0) + 1.0;
@@ -1138,11 +1713,12 @@ impl SlowDesignApp {
 
         if let Some(id) = self.selected_id {
             // Clone needed data first
-            let elem_data = self.document.elements.iter()
+            let elem_data = self.page().elements.iter()
                 .find(|e| e.id == id)
                 .map(|e| (e.rect.clone(), e.content.clone()));
 
             if let Some((rect, content)) = elem_data {
+                let sized = !matches!(content, ElementContent::Dimension(_));
                 match content {
                     ElementContent::TextBox(tb) => {
                         ui.label("text box");
@@ -1151,29 +1727,59 @@ impl SlowDesignApp {
                         // Text editing
                         let mut text = tb.text.clone();
                         let mut font_size = tb.font_size;
+                        let mut align = tb.align;
+                        let mut leading = tb.leading;
+                        let mut path = tb.path;
 
                         ui.label("text:");
                         let output = egui::TextEdit::multiline(&mut text).show(ui);
                         self.word_drag.update(ui, &output, &text);
                         // Request focus if we just entered editing mode (e.g., from double-click)
-                        // but NOT while file browser is open (so filename input works)
-                        if self.editing_text && !output.response.has_focus() && !self.show_file_browser {
+                        // but NOT while the file dialog is open (so filename input works)
+                        if self.editing_text && !output.response.has_focus() && self.file_dialog.is_none() {
                             output.response.request_focus();
                         }
                         self.editing_text = output.response.has_focus();
 
                         ui.add_space(8.0);
                         ui.label("font size:");
-                        ui.add(egui::Slider::new(&mut font_size, 8.0..=72.0));
+                        ui.add(egui::Slider::new(&mut font_size, 8.0..=144.0));
+
+                        ui.add_space(8.0);
+                        ui.label("align:");
+                        ui.horizontal_wrapped(|ui| {
+                            for a in TextAlign::ALL {
+                                ui.selectable_value(&mut align, a, a.name());
+                            }
+                        });
+                        if path.is_none() {
+                            ui.add_space(8.0);
+                            ui.label("leading:");
+                            ui.add(egui::Slider::new(&mut leading, 0.8..=3.0).step_by(0.1));
+                        }
+                        ui.add_space(8.0);
+                        ui.label("set on:");
+                        ui.horizontal_wrapped(|ui| {
+                            ui.selectable_value(&mut path, None, "lines");
+                            for p in TextPath::ALL {
+                                ui.selectable_value(&mut path, Some(p), p.name());
+                            }
+                        });
 
                         // Apply changes and auto-resize
                         let mut text_changed = false;
                         if let Some(elem) = self.document.get_mut(id) {
                             if let ElementContent::TextBox(ref mut t) = elem.content {
-                                if t.text != text || t.font_size != font_size {
-                                    text_changed = t.text != text || t.font_size != font_size;
+                                if t.text != text || t.font_size != font_size || t.leading != leading {
+                                    text_changed = true;
                                     t.text = text;
                                     t.font_size = font_size;
+                                    t.leading = leading;
+                                    self.modified = true;
+                                }
+                                if t.align != align || t.path != path {
+                                    t.align = align;
+                                    t.path = path;
                                     self.modified = true;
                                 }
                             }
@@ -1224,39 +1830,66 @@ impl SlowDesignApp {
                         let r: Rect = rect.into();
                         ui.label(format!("size: {:.0}x{:.0}", r.width(), r.height()));
                     }
+                    ElementContent::Dimension(d) => {
+                        ui.label("dimension");
+                        ui.separator();
+                        if let Some((a, b)) = self.page().ends(&d) {
+                            ui.label(format!("length: {}", self.document.unit.format(a.distance(b))));
+                        }
+                        let pinned = [d.from, d.to].iter().filter(|end| matches!(end, Anchor::Pinned { .. })).count();
+                        ui.label(match pinned {
+                            0 => "ends fixed on the page",
+                            1 => "one end follows an element",
+                            _ => "both ends follow elements",
+                        });
+
+                        ui.add_space(8.0);
+                        let mut offset = d.offset;
+                        ui.label("offset:");
+                        ui.add(egui::Slider::new(&mut offset, -100.0..=100.0));
+                        if offset != d.offset {
+                            self.history.save_coalesced(&self.document, "dimension offset");
+                            if let Some(ElementContent::Dimension(d)) = self.document.get_mut(id).map(|e| &mut e.content) {
+                                d.offset = offset;
+                                self.modified = true;
+                            }
+                        }
+                    }
                 }
 
-                // Position/size
-                ui.add_space(16.0);
-                ui.separator();
-                let r: Rect = rect.into();
-                let mut x = r.min.x;
-                let mut y = r.min.y;
-                let mut w = r.width();
-                let mut h = r.height();
-
-                ui.label("position:");
-                ui.horizontal(|ui| {
-                    ui.label("x:");
-                    ui.add(egui::DragValue::new(&mut x).speed(1.0));
-                    ui.label("y:");
-                    ui.add(egui::DragValue::new(&mut y).speed(1.0));
-                });
-                ui.label("size:");
-                ui.horizontal(|ui| {
-                    ui.label("w:");
-                    ui.add(egui::DragValue::new(&mut w).speed(1.0).clamp_range(10.0..=1000.0));
-                    ui.label("h:");
-                    ui.add(egui::DragValue::new(&mut h).speed(1.0).clamp_range(10.0..=1000.0));
-                });
+                if sized {
+                    // Position/size; a dimension goes where its ends are
+                    ui.add_space(16.0);
+                    ui.separator();
+                    let r: Rect = rect.into();
+                    let mut x = r.min.x;
+                    let mut y = r.min.y;
+                    let mut w = r.width();
+                    let mut h = r.height();
 
-                // Apply position changes
-                let new_rect = Rect::from_min_size(Pos2::new(x, y), Vec2::new(w, h));
-                if let Some(elem) = self.document.get_mut(id) {
-                    let old: Rect = elem.rect.into();
-                    if old != new_rect {
-                        elem.rect = new_rect.into();
-                        self.modified = true;
+                    ui.label("position:");
+                    ui.horizontal(|ui| {
+                        ui.label("x:");
+                        ui.add(egui::DragValue::new(&mut x).speed(1.0));
+                        ui.label("y:");
+                        ui.add(egui::DragValue::new(&mut y).speed(1.0));
+                    });
+                    ui.label("size:");
+                    ui.horizontal(|ui| {
+                        ui.label("w:");
+                        ui.add(egui::DragValue::new(&mut w).speed(1.0).clamp_range(10.0..=1000.0));
+                        ui.label("h:");
+                        ui.add(egui::DragValue::new(&mut h).speed(1.0).clamp_range(10.0..=1000.0));
+                    });
+
+                    // Apply position changes
+                    let new_rect = Rect::from_min_size(Pos2::new(x, y), Vec2::new(w, h));
+                    if let Some(elem) = self.document.get_mut(id) {
+                        let old: Rect = elem.rect.into();
+                        if old != new_rect {
+                            elem.rect = new_rect.into();
+                            self.modified = true;
+                        }
                     }
                 }
 
@@ -1279,10 +1912,114 @@ impl SlowDesignApp {
                 }
             }
         } else {
-            ui.label("no selection");
-            ui.add_space(8.0);
-            ui.label("select an element\nto edit properties");
+            self.render_page_properties(ui);
+        }
+    }
+
+    /// Outlines of every page, to switch between, add and rename them
+    fn render_pages_panel(&mut self, ui: &mut egui::Ui) {
+        ui.heading("pages");
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("add").clicked() { self.add_page(); }
+            if ui.button("duplicate").clicked() { self.duplicate_page(); }
+            if ui.add_enabled(self.document.pages.len() > 1, egui::Button::new("delete")).clicked() { self.delete_page(); }
+        });
+        ui.separator();
+
+        let mut go_to = None;
+        let mut renamed = None;
+        for (i, page) in self.document.pages.iter().enumerate() {
+            let size = Vec2::from(page.size);
+            let scale = (90.0 / size.x).min(110.0 / size.y);
+            let (response, painter) = ui.allocate_painter(Vec2::new(ui.available_width(), size.y * scale + 8.0), Sense::click());
+            let outline = Rect::from_center_size(response.rect.center(), size * scale);
+            painter.rect_filled(outline, 0.0, SlowColors::WHITE);
+            for element in &page.elements {
+                let r: Rect = element.rect.into();
+                let r = Rect::from_min_max(outline.min + r.min.to_vec2() * scale, outline.min + r.max.to_vec2() * scale);
+                match &element.content {
+                    ElementContent::Shape(shape) if shape.fill => { painter.rect_filled(r, 0.0, SlowColors::BLACK); }
+                    _ => { painter.rect_stroke(r, 0.0, Stroke::new(1.0, SlowColors::BLACK)); }
+                }
+            }
+            let width = if i == self.current_page { 3.0 } else { 1.0 };
+            painter.rect_stroke(outline, 0.0, Stroke::new(width, SlowColors::BLACK));
+            if response.double_clicked() {
+                self.renaming_page = Some((i, page.name.clone()));
+            } else if response.clicked() {
+                go_to = Some(i);
+            }
+
+            match &mut self.renaming_page {
+                Some((index, name)) if *index == i => {
+                    let edit = ui.text_edit_singleline(name);
+                    if !edit.has_focus() && !edit.lost_focus() {
+                        edit.request_focus();
+                    }
+                    if edit.lost_focus() {
+                        renamed = Some((i, name.trim().to_string()));
+                    }
+                }
+                _ => {
+                    ui.vertical_centered(|ui| ui.label(format!("{}. {}", i + 1, page.name)));
+                }
+            }
+            ui.add_space(6.0);
+        }
+
+        if let Some((index, name)) = renamed {
+            self.renaming_page = None;
+            if !name.is_empty() && self.document.pages[index].name != name {
+                self.save_undo_state();
+                self.document.pages[index].name = name;
+                self.modified = true;
+            }
+        }
+        if let Some(index) = go_to {
+            self.go_to_page(index);
+        }
+    }
+
+    /// Name and size of the page on screen, shown when nothing is selected
+    fn render_page_properties(&mut self, ui: &mut egui::Ui) {
+        ui.label("page");
+        ui.separator();
+        let page = self.page();
+        let mut name = page.name.clone();
+        let mut size = page.size;
+
+        ui.label("name:");
+        // Keeps typing out of the tool shortcuts
+        self.editing_text = ui.text_edit_singleline(&mut name).has_focus();
+        ui.add_space(8.0);
+        ui.label("size:");
+        ui.horizontal(|ui| {
+            ui.label("w:");
+            ui.add(egui::DragValue::new(&mut size.x).speed(1.0).clamp_range(72.0..=4000.0));
+            ui.label("h:");
+            ui.add(egui::DragValue::new(&mut size.y).speed(1.0).clamp_range(72.0..=4000.0));
+        });
+        ui.add_space(4.0);
+        for &(preset, w, h) in PAGE_SIZES {
+            let current = size.x == w && size.y == h;
+            if ui.selectable_label(current, format!("{}  {:.0}x{:.0}", preset, w, h)).clicked() {
+                size = SerVec2 { x: w, y: h };
+            }
+        }
+
+        let page = self.page();
+        if page.name != name || (page.size.x, page.size.y) != (size.x, size.y) {
+            // Typing a name or dragging a size undoes in one step
+            self.history.save_coalesced(&self.document, "page");
+            let page = self.page_mut();
+            page.name = name;
+            page.size = size;
+            self.modified = true;
         }
+
+        ui.add_space(16.0);
+        ui.label("select an element\nto edit properties");
     }
 
     fn render_menu_bar(&mut self, ui: &mut egui::Ui) -> WindowAction {
@@ -1292,50 +2029,74 @@ impl SlowDesignApp {
             ui.menu_button("file", |ui| {
                 if ui.button("new          ⌘N").clicked() { self.new_document(); ui.close_menu(); }
                 if ui.button("open...      ⌘O").clicked() {
-                    self.fb_mode = FbMode::Open;
-                    self.file_browser.filter_extensions = vec!["sld".to_string()];
-                    self.file_browser.refresh();
-                    self.show_file_browser = true;
+                    self.show_file_dialog(FbMode::Open);
                     ui.close_menu();
                 }
+                if let Some(path) = self.recent_files.menu(ui) {
+                    self.open(path);
+                }
                 if ui.button("save         ⌘S").clicked() { self.save(); ui.close_menu(); }
                 if ui.button("save as...").clicked() {
-                    self.fb_mode = FbMode::Save;
-                    self.file_browser.filter_extensions = vec!["sld".to_string()];
-                    self.file_browser.refresh();
-                    self.show_file_browser = true;
+                    self.show_file_dialog(FbMode::Save);
                     ui.close_menu();
                 }
                 ui.separator();
-                if ui.button("export as PNG...").clicked() {
-                    self.fb_mode = FbMode::ExportPng;
-                    self.file_browser.filter_extensions = vec!["png".to_string()];
-                    self.file_browser.refresh();
-                    self.show_file_browser = true;
+                if ui.button("export page as PNG...").clicked() {
+                    self.show_file_dialog(FbMode::ExportPng);
+                    ui.close_menu();
+                }
+                if ui.button("export all pages as PNG...").clicked() {
+                    self.show_file_dialog(FbMode::ExportAllPng);
                     ui.close_menu();
                 }
                 if ui.button("export as PDF...").clicked() {
-                    self.fb_mode = FbMode::ExportPdf;
-                    self.file_browser.filter_extensions = vec!["pdf".to_string()];
-                    self.file_browser.refresh();
-                    self.show_file_browser = true;
+                    self.show_file_dialog(FbMode::ExportPdf);
                     ui.close_menu();
                 }
             });
             ui.menu_button("edit", |ui| {
-                if ui.add_enabled(!self.undo_stack.is_empty(), egui::Button::new("undo         ⌘Z")).clicked() { self.undo(); ui.close_menu(); }
-                if ui.add_enabled(!self.redo_stack.is_empty(), egui::Button::new("redo        ⇧⌘Z")).clicked() { self.redo(); ui.close_menu(); }
+                if ui.add_enabled(self.history.can_undo(), egui::Button::new("undo         ⌘Z")).clicked() { self.undo(); ui.close_menu(); }
+                if ui.add_enabled(self.history.can_redo(), egui::Button::new("redo        ⇧⌘Z")).clicked() { self.redo(); ui.close_menu(); }
                 ui.separator();
                 if ui.add_enabled(self.selected_id.is_some(), egui::Button::new("delete       ⌫")).clicked() { self.delete_selected(); ui.close_menu(); }
             });
             ui.menu_button("insert", |ui| {
                 if ui.button("text box     T").clicked() { self.tool = Tool::TextBox; ui.close_menu(); }
+                if ui.button("path text    P").clicked() { self.tool = Tool::PathText; ui.close_menu(); }
                 if ui.button("image        I").clicked() { self.tool = Tool::Image; ui.close_menu(); }
                 if ui.button("rectangle    R").clicked() { self.tool = Tool::Rectangle; ui.close_menu(); }
                 if ui.button("ellipse      E").clicked() { self.tool = Tool::Ellipse; ui.close_menu(); }
                 if ui.button("line         L").clicked() { self.tool = Tool::Line; ui.close_menu(); }
+                if ui.button("dimension    D").clicked() { self.tool = Tool::Dimension; ui.close_menu(); }
+            });
+            ui.menu_button("page", |ui| {
+                let (index, count) = (self.current_page, self.document.pages.len());
+                if ui.button("new page    ⇧⌘N").clicked() { self.add_page(); ui.close_menu(); }
+                if ui.button("duplicate page").clicked() { self.duplicate_page(); ui.close_menu(); }
+                if ui.add_enabled(count > 1, egui::Button::new("delete page")).clicked() { self.delete_page(); ui.close_menu(); }
+                ui.separator();
+                if ui.add_enabled(index > 0, egui::Button::new("move up")).clicked() { self.move_page(-1); ui.close_menu(); }
+                if ui.add_enabled(index + 1 < count, egui::Button::new("move down")).clicked() { self.move_page(1); ui.close_menu(); }
+                ui.separator();
+                if ui.add_enabled(index > 0, egui::Button::new("previous    PgUp")).clicked() { self.go_to_page(index - 1); ui.close_menu(); }
+                if ui.add_enabled(index + 1 < count, egui::Button::new("next        PgDn")).clicked() { self.go_to_page(index + 1); ui.close_menu(); }
+                if ui.button("first       Home").clicked() { self.go_to_page(0); ui.close_menu(); }
+                if ui.button("last         End").clicked() { self.go_to_page(count - 1); ui.close_menu(); }
             });
             ui.menu_button("view", |ui| {
+                if ui.button(if self.show_rulers { "hide rulers  ⌘R" } else { "show rulers  ⌘R" }).clicked() {
+                    self.show_rulers = !self.show_rulers;
+                    ui.close_menu();
+                }
+                ui.menu_button("units", |ui| {
+                    for unit in Unit::ALL {
+                        if ui.radio(self.document.unit == unit, unit.name()).clicked() {
+                            self.set_unit(unit);
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.separator();
                 if ui.button("zoom in       ⌘+").clicked() {
                     self.zoom = (self.zoom + 0.25).min(4.0);
                     ui.close_menu();
@@ -1372,25 +2133,26 @@ impl eframe::App for SlowDesignApp {
             ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
         }
         // Load image textures - collect paths first to avoid borrow conflicts
-        let images_to_load: Vec<(usize, PathBuf)> = self.document.elements.iter()
-            .enumerate()
-            .filter_map(|(idx, e)| {
+        let images_to_load: Vec<(u64, PathBuf)> = self.document.pages.iter()
+            .flat_map(|p| &p.elements)
+            .filter_map(|e| {
                 if let ElementContent::Image(img) = &e.content {
                     if img.texture_id.is_none() {
-                        return Some((idx, img.path.clone()));
+                        return Some((e.id, img.path.clone()));
                     }
                 }
                 None
             })
             .collect();
 
-        for (idx, path) in images_to_load {
+        for (id, path) in images_to_load {
             let texture_id = self.load_image_texture(ctx, &path);
-            if let ElementContent::Image(ref mut img) = self.document.elements[idx].content {
+            if let Some(ElementContent::Image(img)) = self.document.get_mut(id).map(|e| &mut e.content) {
                 img.texture_id = texture_id;
             }
         }
 
+        self.page_mut().place_dimensions();
         self.handle_keyboard(ctx);
 
         // Clear status message when document changes
@@ -1400,11 +2162,7 @@ impl eframe::App for SlowDesignApp {
         egui::TopBottomPanel::top("menu").show(ctx, |ui| { win_action = self.render_menu_bar(ui); });
         match win_action {
             WindowAction::Close => {
-                if self.modified {
-                    self.show_close_confirm = true;
-                } else {
-                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                }
+                self.close_guard.request_close(ctx, self.modified);
             }
             WindowAction::Minimize => {
                 let title = self.current_file.as_ref()
@@ -1422,17 +2180,34 @@ impl eframe::App for SlowDesignApp {
             let tool_name = match self.tool {
                 Tool::Select => "select",
                 Tool::TextBox => "text",
+                Tool::PathText => "path text",
                 Tool::Image => "image",
                 Tool::Rectangle => "rect",
                 Tool::Ellipse => "ellipse",
                 Tool::Line => "line",
+                Tool::Measure => "measure",
+                Tool::Dimension => "dimension",
             };
+            let measured = self.measuring.map(|(a, b)| measure::describe(a, b, self.document.unit));
             let msg = if let Some(ref msg) = self.status_message {
                 msg.as_str()
+            } else if let Some(ref measured) = measured {
+                measured.as_str()
             } else {
                 status
             };
-            status_bar(ui, &format!("tool: {}  |  {}  |  zoom: {:.0}%", tool_name, msg, self.zoom * 100.0));
+            status_bar(ui, &format!(
+                "tool: {}  |  {}  |  page {}/{}  |  zoom: {:.0}%",
+                tool_name,
+                msg,
+                self.current_page + 1,
+                self.document.pages.len(),
+                self.zoom * 100.0
+            ));
+        });
+
+        egui::SidePanel::left("pages").exact_width(120.0).show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| self.render_pages_panel(ui));
         });
 
         egui::SidePanel::right("properties").exact_width(200.0).show(ctx, |ui| {
@@ -1443,238 +2218,121 @@ impl eframe::App for SlowDesignApp {
             .frame(egui::Frame::none().fill(SlowColors::WHITE))
             .show(ctx, |ui| self.render_canvas(ui, ctx));
 
-        // File browser
-        if self.show_file_browser {
-            let title = match self.fb_mode {
-                FbMode::Open => "open document",
-                FbMode::Save => "save document",
-                FbMode::ExportPng => "export as PNG",
-                FbMode::ExportPdf => "export as PDF",
-            };
-            let mut close_browser = false;
-            let mut open_path: Option<PathBuf> = None;
-            let mut save_path: Option<PathBuf> = None;
-
-            let resp = egui::Window::new(title).collapsible(false).resizable(false).default_width(380.0).show(ctx, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label("location:");
-                    ui.label(self.file_browser.current_dir.to_string_lossy().to_string());
-                });
-                ui.separator();
-
-                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
-                    let entries = self.file_browser.entries.clone();
-                    for (idx, entry) in entries.iter().enumerate() {
-                        let selected = self.file_browser.selected_index == Some(idx);
-                        let response = ui.add(FileListItem::new(&entry.name, entry.is_directory).selected(selected));
-                        if response.clicked() { self.file_browser.selected_index = Some(idx); }
-                        if response.double_clicked() {
-                            if entry.is_directory {
-                                self.file_browser.navigate_to(entry.path.clone());
-                            } else if self.fb_mode == FbMode::Open {
-                                open_path = Some(entry.path.clone());
-                                close_browser = true;
-                            }
-                        }
+        // File dialog
+        if let Some(dialog) = &mut self.file_dialog {
+            match dialog.show(ctx) {
+                FileDialogResult::Picked(path) => {
+                    self.file_dialog = None;
+                    match self.fb_mode {
+                        FbMode::Open => self.open(path),
+                        FbMode::Save => self.save_to_path(path),
+                        FbMode::ExportPng => self.export_png(&path),
+                        FbMode::ExportAllPng => self.export_all_png(&path),
+                        FbMode::ExportPdf => self.export_pdf(&path),
+                        FbMode::InsertImage => self.insert_image(ctx, path),
                     }
-                });
-
-                if self.fb_mode != FbMode::Open {
-                    ui.separator();
-                    ui.horizontal(|ui| {
-                        ui.label("filename:");
-                        let fname_resp = ui.text_edit_singleline(&mut self.save_filename);
-                        if fname_resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                            // Enter in filename = click save/export
-                            if !self.save_filename.is_empty() {
-                                save_path = Some(self.file_browser.save_directory().join(&self.save_filename));
-                                close_browser = true;
-                            }
-                        }
-                    });
                 }
-
-                ui.separator();
-                ui.horizontal(|ui| {
-                    if ui.button("cancel").clicked() { close_browser = true; }
-                    let action = match self.fb_mode {
-                        FbMode::Open => "open",
-                        FbMode::Save => "save",
-                        FbMode::ExportPng | FbMode::ExportPdf => "export",
-                    };
-                    if ui.button(action).clicked() {
-                        match self.fb_mode {
-                            FbMode::Open => {
-                                if let Some(entry) = self.file_browser.selected_entry() {
-                                    if !entry.is_directory {
-                                        open_path = Some(entry.path.clone());
-                                        close_browser = true;
-                                    }
-                                }
-                            }
-                            FbMode::Save | FbMode::ExportPng | FbMode::ExportPdf => {
-                                if !self.save_filename.is_empty() {
-                                    save_path = Some(self.file_browser.save_directory().join(&self.save_filename));
-                                    close_browser = true;
-                                }
-                            }
-                        }
-                    }
-                });
-            });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
-
-            if let Some(path) = open_path { self.open(path); }
-            if let Some(path) = save_path {
-                match self.fb_mode {
-                    FbMode::Save => self.save_to_path(path),
-                    FbMode::ExportPng => self.export_png(&path),
-                    FbMode::ExportPdf => self.export_pdf(&path),
-                    _ => {}
+                FileDialogResult::Cancelled => {
+                    self.file_dialog = None;
+                    self.pending_image_rect = None;
                 }
+                FileDialogResult::Pending => {}
             }
-            if close_browser { self.show_file_browser = false; }
-        }
-
-        // Image picker
-        if self.show_image_picker {
-            let mut close_picker = false;
-            let mut picked_path: Option<PathBuf> = None;
-
-            let resp = egui::Window::new("insert image").collapsible(false).resizable(false).default_width(380.0).show(ctx, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label("location:");
-                    ui.label(self.image_browser.current_dir.to_string_lossy().to_string());
-                });
-                ui.separator();
-
-                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
-                    let entries = self.image_browser.entries.clone();
-                    for (idx, entry) in entries.iter().enumerate() {
-                        let selected = self.image_browser.selected_index == Some(idx);
-                        let response = ui.add(FileListItem::new(&entry.name, entry.is_directory).selected(selected));
-                        if response.clicked() { self.image_browser.selected_index = Some(idx); }
-                        if response.double_clicked() {
-                            if entry.is_directory {
-                                self.image_browser.navigate_to(entry.path.clone());
-                            } else {
-                                picked_path = Some(entry.path.clone());
-                                close_picker = true;
-                            }
-                        }
-                    }
-                });
-
-                ui.separator();
-                ui.horizontal(|ui| {
-                    if ui.button("cancel").clicked() { close_picker = true; }
-                    if ui.button("insert").clicked() {
-                        if let Some(entry) = self.image_browser.selected_entry() {
-                            if !entry.is_directory {
-                                picked_path = Some(entry.path.clone());
-                                close_picker = true;
-                            }
-                        }
-                    }
-                });
-            });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
-
-            if let Some(path) = picked_path {
-                if let Some(marquee_rect) = self.pending_image_rect.take() {
-                    let texture_id = self.load_image_texture(ctx, &path);
-
-                    // Scale image to fit vertically within the marquee without squishing.
-                    // Load the image to get its native dimensions.
-                    let final_rect = if let Ok(bytes) = std::fs::read(&path) {
-                        if let Ok(img) = image::load_from_memory(&bytes) {
-                            let (iw, ih) = (img.width() as f32, img.height() as f32);
-                            if ih > 0.0 {
-                                let aspect = iw / ih;
-                                let target_h = marquee_rect.height();
-                                let target_w = target_h * aspect;
-                                // Center horizontally within the marquee
-                                let cx = marquee_rect.center().x;
-                                Rect::from_min_size(
-                                    Pos2::new(cx - target_w / 2.0, marquee_rect.min.y),
-                                    Vec2::new(target_w, target_h),
-                                )
-                            } else {
-                                marquee_rect
-                            }
-                        } else {
-                            marquee_rect
-                        }
-                    } else {
-                        marquee_rect
-                    };
-
-                    self.add_element(ElementContent::Image(ImageElement { path, texture_id }), final_rect);
-                }
-            }
-            if close_picker { self.show_image_picker = false; self.pending_image_rect = None; }
         }
 
         // About
-        if self.show_about {
-            let screen = ctx.screen_rect();
-            let max_h = (screen.height() - 60.0).max(120.0);
-            let resp = egui::Window::new("about slowDesign")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(280.0)
-                .max_height(max_h)
-                .show(ctx, |ui| {
-                    ui.vertical_centered(|ui| {
-                        ui.heading("slowDesign");
-                        ui.label("version 0.2.2");
-                        ui.add_space(8.0);
-                        ui.label("layout program for slowOS");
-                    });
-                    ui.add_space(16.0);
-                    if ui.button("ok").clicked() { self.show_about = false; }
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
 
-        // Close confirmation dialog
-        if self.show_close_confirm {
-            let resp = egui::Window::new("unsaved changes")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(300.0)
-                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
-                .show(ctx, |ui| {
-                    ui.label("you have unsaved changes.");
-                    ui.label("do you want to save before closing?");
-                    ui.add_space(8.0);
-                    ui.horizontal(|ui| {
-                        if ui.button("don't save").clicked() {
-                            self.close_confirmed = true;
-                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                        }
-                        if ui.button("cancel").clicked() {
-                            self.show_close_confirm = false;
-                        }
-                        if ui.button("save").clicked() {
-                            self.save();
-                            if !self.modified {
-                                self.close_confirmed = true;
-                                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                            }
-                        }
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+        // Unsaved-changes guard for close requests
+        if self.close_guard.update(ctx, self.modified) {
+            self.save();
+            self.close_guard.close_if_saved(ctx, !self.modified);
         }
+        self.repaint.end_frame(ctx);
+    }
+}
 
-        // Handle close request
-        if ctx.input(|i| i.viewport().close_requested()) {
-            if self.modified && !self.close_confirmed {
-                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
-                self.show_close_confirm = true;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_formats() {
+        // A layout saved before format versions, and ones from versions 1 and 2
+        let fixtures = [
+            r#"{"elements": [{"id": 1, "rect": {"min_x": 72.0, "min_y": 72.0, "max_x": 272.0, "max_y": 112.0},
+                "content": {"TextBox": {"text": "hello", "font_size": 14.0}}}],
+                "next_id": 2, "page_size": {"x": 612.0, "y": 792.0}}"#,
+            r#"{"format_version": 1, "elements": [{"id": 1, "rect": {"min_x": 72.0, "min_y": 72.0, "max_x": 272.0, "max_y": 112.0},
+                "content": {"TextBox": {"text": "hello", "font_size": 14.0}}, "locked": false}],
+                "next_id": 2, "page_size": {"x": 612.0, "y": 792.0}}"#,
+            r#"{"format_version": 2, "elements": [{"id": 1, "rect": {"min_x": 72.0, "min_y": 72.0, "max_x": 272.0, "max_y": 112.0},
+                "content": {"TextBox": {"text": "hello", "font_size": 14.0, "align": "Left", "leading": 1.4, "path": null}},
+                "locked": false}], "next_id": 2, "page_size": {"x": 612.0, "y": 792.0}}"#,
+        ];
+        for json in fixtures {
+            let doc = storage::from_versioned_json::<Document>(json).unwrap();
+            assert_eq!(doc.next_id, 2);
+            assert_eq!((doc.pages.len(), doc.pages[0].name.as_str(), doc.pages[0].size.y), (1, "page 1", 792.0));
+            assert_eq!(doc.unit, Unit::Px);
+            let elements = &doc.pages[0].elements;
+            assert!(!elements[0].locked);
+            assert!(matches!(&elements[0].content,
+                ElementContent::TextBox(t) if t.text == "hello" && t.leading == DEFAULT_LEADING && t.path.is_none()));
+            let again = storage::to_versioned_json(&doc).unwrap();
+            assert_eq!(storage::from_versioned_json::<Document>(&again).unwrap().pages[0].elements.len(), 1);
         }
-        self.repaint.end_frame(ctx);
+    }
+
+    #[test]
+    fn test_pages() {
+        let mut doc = Document::with_initial_text_box();
+        assert_eq!(doc.insert_page(0), 1);
+        assert_eq!(doc.pages[1].name, "page 2");
+        assert!(doc.pages[1].elements.is_empty());
+        // The copy lands after the page and its elements get ids of their own
+        assert_eq!(doc.duplicate_page(0), 1);
+        assert_eq!(doc.pages.len(), 3);
+        assert_eq!((doc.pages[1].name.as_str(), doc.pages[1].elements[0].id), ("page 1 copy", 2));
+        assert_eq!(doc.next_id, 3);
+        assert!(doc.get(2).is_some() && doc.get(1).is_some());
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let mut doc = Document::with_initial_text_box();
+        let from = Anchor::Pinned { element: 1, x: 0.0, y: 0.0 };
+        let dimension = Dimension { from, to: Anchor::Fixed { x: 72.0, y: 10.0 }, offset: 0.0 };
+        doc.pages[0].elements.push(DesignElement {
+            id: 2,
+            rect: Rect::NOTHING.into(),
+            content: ElementContent::Dimension(dimension),
+            locked: false,
+        });
+        doc.next_id = 3;
+        let ends = |doc: &Document| doc.pages[0].elements.iter().find_map(|e| match &e.content {
+            ElementContent::Dimension(d) => doc.pages[0].ends(d),
+            _ => None,
+        });
+        assert_eq!(ends(&doc), Some((Pos2::new(72.0, 72.0), Pos2::new(72.0, 10.0))));
+
+        // Moving the text box takes the pinned end with it, and the
+        // dimension's frame follows
+        doc.pages[0].elements[0].rect = Rect::from_min_size(Pos2::new(100.0, 72.0), Vec2::splat(200.0)).into();
+        doc.pages[0].place_dimensions();
+        assert_eq!(ends(&doc), Some((Pos2::new(100.0, 72.0), Pos2::new(72.0, 10.0))));
+        assert!(Rect::from(doc.pages[0].elements[1].rect).contains(Pos2::new(90.0, 40.0)));
+
+        // A copied page's dimension measures the copied box
+        doc.duplicate_page(0);
+        assert!(matches!(&doc.pages[1].elements[1].content,
+            ElementContent::Dimension(d) if d.from == Anchor::Pinned { element: 3, x: 0.0, y: 0.0 }));
+
+        // With the box gone the end stays where it was
+        doc.pages[0].unpin(1);
+        doc.pages[0].elements.remove(0);
+        assert_eq!(ends(&doc), Some((Pos2::new(100.0, 72.0), Pos2::new(72.0, 10.0))));
     }
 }
@@ -1,16 +1,14 @@
 mod app;
+mod measure;
+mod typeset;
 use app::SlowDesignApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
-    let mut viewport = egui::ViewportBuilder::default()
-        .with_inner_size([900.0, 640.0])
+    let viewport = window_layout::viewport("slowdesign", Placement::RememberLast, [900.0, 640.0])
         .with_title("slowDesign");
 
-    if let Some(pos) = slowcore::cascade_position() {
-        viewport = viewport.with_position(pos);
-    }
-
     let options = NativeOptions {
         viewport,
         ..Default::default()
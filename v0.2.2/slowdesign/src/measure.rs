@@ -0,0 +1,190 @@
+//! Measuring — units, rulers and dimension annotations
+//!
+//! Documents are laid out in pixels, 96 to the inch as they export, and
+//! rulers and dimensions show lengths in the document's unit. Each end
+//! of a dimension can be pinned to a point on an element, so the
+//! dimension follows the element when it moves or changes size.
+
+use egui::{Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+pub const PX_PER_INCH: f32 = 96.0;
+
+/// Size of dimension labels
+pub const LABEL_SIZE: f32 = 11.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Unit {
+    #[default]
+    Px,
+    Mm,
+    Pt,
+}
+
+impl Unit {
+    pub const ALL: [Unit; 3] = [Unit::Px, Unit::Mm, Unit::Pt];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Px => "px",
+            Unit::Mm => "mm",
+            Unit::Pt => "pt",
+        }
+    }
+
+    /// Pixels in one of this unit
+    pub fn px(self) -> f32 {
+        match self {
+            Unit::Px => 1.0,
+            Unit::Mm => PX_PER_INCH / 25.4,
+            Unit::Pt => PX_PER_INCH / 72.0,
+        }
+    }
+
+    /// A length in pixels, written in this unit
+    pub fn format(self, px: f32) -> String {
+        match self {
+            Unit::Px => format!("{:.0} px", px),
+            _ => format!("{:.1} {}", px / self.px(), self.name()),
+        }
+    }
+}
+
+/// Ruler ticks for `unit` at `zoom`, in that unit: minor ticks far
+/// enough apart to tell from each other, and labelled major ticks on
+/// every few of them
+pub fn ruler_steps(unit: Unit, zoom: f32) -> (f32, f32) {
+    const STEPS: [f32; 10] = [1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0];
+    let on_screen = unit.px() * zoom;
+    let minor = STEPS.into_iter().find(|s| s * on_screen >= 5.0).unwrap_or(1000.0);
+    let major = STEPS.into_iter().find(|&s| s >= minor * 5.0 && s * on_screen >= 40.0).unwrap_or(minor * 10.0);
+    (minor, major)
+}
+
+/// Distance and angle from `a` to `b`, the angle anticlockwise from
+/// pointing right, as on a protractor
+pub fn describe(a: Pos2, b: Pos2, unit: Unit) -> String {
+    let d = b - a;
+    let angle = (-d.y).atan2(d.x).to_degrees();
+    format!("{}  at {:.1}°  (Δx {}, Δy {})", unit.format(d.length()), angle, unit.format(d.x.abs()), unit.format(d.y.abs()))
+}
+
+/// One end of a dimension
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Anchor {
+    /// A point on the page
+    Fixed { x: f32, y: f32 },
+    /// A point on an element, as a fraction of the way across and down
+    /// its frame
+    Pinned { element: u64, x: f32, y: f32 },
+}
+
+impl Anchor {
+    /// Where the anchor is now, given the frames of elements by id
+    pub fn resolve(self, frame: impl Fn(u64) -> Option<Rect>) -> Option<Pos2> {
+        match self {
+            Anchor::Fixed { x, y } => Some(Pos2::new(x, y)),
+            Anchor::Pinned { element, x, y } => frame(element).map(|r| r.min + r.size() * Vec2::new(x, y)),
+        }
+    }
+}
+
+/// The anchor for a point picked at `p`: the nearest corner, middle of
+/// an edge or centre of an element within `reach`, failing that the
+/// point itself on the topmost element under it, or else on the page.
+/// `frames` are topmost first.
+pub fn snap(p: Pos2, frames: &[(u64, Rect)], reach: f32) -> Anchor {
+    const SPOTS: [(f32, f32); 9] =
+        [(0.0, 0.0), (0.5, 0.0), (1.0, 0.0), (0.0, 0.5), (0.5, 0.5), (1.0, 0.5), (0.0, 1.0), (0.5, 1.0), (1.0, 1.0)];
+    let nearest = frames
+        .iter()
+        .flat_map(|&(id, r)| SPOTS.iter().map(move |&(x, y)| (id, x, y, r.min + r.size() * Vec2::new(x, y))))
+        .map(|(id, x, y, at)| (at.distance(p), id, x, y))
+        .filter(|&(d, ..)| d <= reach)
+        .min_by(|a, b| a.0.total_cmp(&b.0));
+    if let Some((_, element, x, y)) = nearest {
+        return Anchor::Pinned { element, x, y };
+    }
+    match frames.iter().find(|(_, r)| r.contains(p) && r.width() > 0.0 && r.height() > 0.0) {
+        Some(&(element, r)) => Anchor::Pinned { element, x: (p.x - r.min.x) / r.width(), y: (p.y - r.min.y) / r.height() },
+        None => Anchor::Fixed { x: p.x, y: p.y },
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Dimension {
+    pub from: Anchor,
+    pub to: Anchor,
+    /// How far the dimension line stands off from the points it
+    /// measures, to the left going from `from` to `to`
+    pub offset: f32,
+}
+
+/// A dimension as drawn between `a` and `b`
+pub struct Drawn {
+    /// Extension lines out from the points, the dimension line, and a
+    /// slash across each end of it
+    pub lines: Vec<[Pos2; 2]>,
+    /// Centre of the label, just off the middle of the dimension line
+    pub label: Pos2,
+}
+
+pub fn draw(a: Pos2, b: Pos2, offset: f32) -> Drawn {
+    let dir = if a == b { Vec2::X } else { (b - a).normalized() };
+    let side = if offset < 0.0 { -1.0 } else { 1.0 };
+    let normal = Vec2::new(dir.y, -dir.x) * side;
+    let reach = offset.abs();
+    let (a2, b2) = (a + normal * reach, b + normal * reach);
+    let mut lines = Vec::new();
+    if reach > 4.0 {
+        // A small gap at the measured point, a little overshoot past
+        // the dimension line
+        lines.push([a + normal * 2.0, a2 + normal * 4.0]);
+        lines.push([b + normal * 2.0, b2 + normal * 4.0]);
+    }
+    lines.push([a2, b2]);
+    let slash = (dir + normal).normalized() * 4.0;
+    lines.push([a2 - slash, a2 + slash]);
+    lines.push([b2 - slash, b2 + slash]);
+    Drawn { lines, label: a2 + (b2 - a2) / 2.0 + normal * LABEL_SIZE * 0.8 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_units_and_snapping() {
+        assert_eq!(Unit::Mm.format(96.0), "25.4 mm");
+        assert_eq!(Unit::Pt.format(96.0), "72.0 pt");
+        assert_eq!(Unit::Px.format(95.6), "96 px");
+        let (minor, major) = ruler_steps(Unit::Px, 1.0);
+        assert_eq!((minor, major), (5.0, 50.0));
+        assert!(describe(Pos2::ZERO, Pos2::new(10.0, -10.0), Unit::Px).contains("at 45.0°"));
+
+        let frames = [(7, Rect::from_min_size(Pos2::new(100.0, 100.0), Vec2::new(50.0, 20.0)))];
+        // Near a corner, it takes the corner
+        let corner = snap(Pos2::new(153.0, 98.0), &frames, 6.0);
+        assert_eq!(corner, Anchor::Pinned { element: 7, x: 1.0, y: 0.0 });
+        // Inside, away from the spots, it pins the point itself
+        let inside = snap(Pos2::new(110.0, 104.0), &frames, 6.0);
+        assert_eq!(inside, Anchor::Pinned { element: 7, x: 0.2, y: 0.2 });
+        assert_eq!(snap(Pos2::new(10.0, 10.0), &frames, 6.0), Anchor::Fixed { x: 10.0, y: 10.0 });
+
+        // A pinned end follows its element
+        let moved = Rect::from_min_size(Pos2::new(0.0, 0.0), Vec2::new(100.0, 40.0));
+        assert_eq!(corner.resolve(|id| (id == 7).then_some(moved)), Some(Pos2::new(100.0, 0.0)));
+        assert_eq!(corner.resolve(|_| None), None);
+    }
+
+    #[test]
+    fn test_draw_dimension() {
+        // Left to right, a positive offset stands the line up above
+        let drawn = draw(Pos2::new(0.0, 50.0), Pos2::new(100.0, 50.0), 20.0);
+        assert!(drawn.lines.contains(&[Pos2::new(0.0, 30.0), Pos2::new(100.0, 30.0)]));
+        assert_eq!(drawn.lines.len(), 5);
+        assert!(drawn.label.y < 30.0 && drawn.label.x == 50.0);
+        // With no offset there are no extension lines
+        assert_eq!(draw(Pos2::ZERO, Pos2::new(0.0, 10.0), 0.0).lines.len(), 3);
+    }
+}
@@ -0,0 +1,245 @@
+//! Typesetting for text frames and text on a path
+//!
+//! The canvas and both exports lay text out here. Lines wrap with
+//! slowcore's print wrapping and each renderer brings its own
+//! measurements, so a frame breaks its lines in the same places on
+//! screen, in PNG and in PDF.
+
+use egui::{Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+use slowcore::print::wrap_lines;
+
+/// Line spacing of frames from before leading could be set, as a
+/// multiple of the font size
+pub const DEFAULT_LEADING: f32 = 1.4;
+
+/// Space between the frame edge and its text
+pub const FRAME_PADDING: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Lines fill the width, apart from the last of each paragraph
+    Justify,
+}
+
+impl TextAlign {
+    pub const ALL: [TextAlign; 4] = [TextAlign::Left, TextAlign::Center, TextAlign::Right, TextAlign::Justify];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TextAlign::Left => "left",
+            TextAlign::Center => "center",
+            TextAlign::Right => "right",
+            TextAlign::Justify => "justify",
+        }
+    }
+}
+
+/// Paths text can follow, each drawn to fill the frame
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TextPath {
+    /// Up from the bottom left corner, over the top and down again
+    Arc,
+    /// Round the inside of the frame, starting at the bottom, so
+    /// centred text sits over the top
+    Circle,
+    /// One swell and dip across the middle
+    Wave,
+}
+
+impl TextPath {
+    pub const ALL: [TextPath; 3] = [TextPath::Arc, TextPath::Circle, TextPath::Wave];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TextPath::Arc => "arc",
+            TextPath::Circle => "circle",
+            TextPath::Wave => "wave",
+        }
+    }
+}
+
+/// Text to draw with its top left corner at `pos`, relative to the frame
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    pub text: String,
+    pub pos: Pos2,
+}
+
+/// Lay out a frame `width` wide. Justified lines come back a word per
+/// run; others a line per run. Lines past `height` are left out.
+pub fn layout_frame(
+    text: &str,
+    width: f32,
+    height: f32,
+    font_size: f32,
+    leading: f32,
+    align: TextAlign,
+    measure: impl Fn(&str) -> f32,
+) -> Vec<Run> {
+    let inner = (width - FRAME_PADDING * 2.0).max(1.0);
+    let pitch = font_size * leading;
+    let mut runs = Vec::new();
+    let mut top = FRAME_PADDING;
+    for paragraph in text.split('\n') {
+        let mut lines = wrap_lines(paragraph, inner, &measure);
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        let last = lines.len() - 1;
+        for (i, line) in lines.into_iter().enumerate() {
+            if top + font_size > height {
+                return runs;
+            }
+            let spare = inner - measure(&line);
+            let words: Vec<&str> = line.split(' ').collect();
+            if align == TextAlign::Justify && i < last && words.len() > 1 {
+                let gap = measure(" ") + spare / (words.len() - 1) as f32;
+                let mut x = FRAME_PADDING;
+                for word in words {
+                    runs.push(Run { text: word.to_string(), pos: Pos2::new(x, top) });
+                    x += measure(word) + gap;
+                }
+            } else if !line.is_empty() {
+                let x = match align {
+                    TextAlign::Left | TextAlign::Justify => 0.0,
+                    TextAlign::Center => spare / 2.0,
+                    TextAlign::Right => spare,
+                };
+                runs.push(Run { text: line, pos: Pos2::new(FRAME_PADDING + x.max(0.0), top) });
+            }
+            top += pitch;
+        }
+    }
+    runs
+}
+
+/// A character on a path: its baseline starts at `pos`, turned
+/// clockwise by `angle` radians
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathGlyph {
+    pub ch: char,
+    pub pos: Pos2,
+    pub angle: f32,
+}
+
+/// Points along the baseline of `path` in `rect`, leaving room above it
+/// for letters `font_size` tall
+pub fn path_points(path: TextPath, rect: Rect, font_size: f32) -> Vec<Pos2> {
+    const STEPS: usize = 96;
+    let center = rect.center();
+    let (w, h) = (rect.width(), rect.height());
+    (0..=STEPS)
+        .map(|i| {
+            let t = i as f32 / STEPS as f32;
+            match path {
+                TextPath::Arc => {
+                    let angle = std::f32::consts::PI * (1.0 + t);
+                    let rise = (h - font_size).max(0.0);
+                    Pos2::new(center.x + w / 2.0 * angle.cos(), rect.max.y + rise * angle.sin())
+                }
+                TextPath::Circle => {
+                    let angle = std::f32::consts::FRAC_PI_2 + std::f32::consts::TAU * t;
+                    let radius = ((rect.size() - Vec2::splat(font_size * 2.0)) / 2.0).max(Vec2::ZERO);
+                    Pos2::new(center.x + radius.x * angle.cos(), center.y + radius.y * angle.sin())
+                }
+                TextPath::Wave => {
+                    let swell = (h - font_size * 1.5).max(0.0) / 2.0;
+                    let baseline = center.y + font_size / 2.0;
+                    Pos2::new(rect.min.x + w * t, baseline - swell * (std::f32::consts::TAU * t).sin())
+                }
+            }
+        })
+        .collect()
+}
+
+/// Set `text` along `points`, one line of it, aligned along the path.
+/// Justified text spreads its letters over the whole path; characters
+/// that run off the end are left out.
+pub fn layout_path(text: &str, points: &[Pos2], align: TextAlign, advance: impl Fn(char) -> f32) -> Vec<PathGlyph> {
+    let mut lengths = vec![0.0];
+    for pair in points.windows(2) {
+        lengths.push(lengths[lengths.len() - 1] + pair[0].distance(pair[1]));
+    }
+    let total = lengths[lengths.len() - 1];
+    let chars: Vec<(char, f32)> = text.chars().map(|c| if c == '\n' { ' ' } else { c }).map(|c| (c, advance(c))).collect();
+    let width: f32 = chars.iter().map(|&(_, a)| a).sum();
+    let spare = total - width;
+    let (mut s, spacing) = match align {
+        TextAlign::Left => (0.0, 0.0),
+        TextAlign::Center => (spare / 2.0, 0.0),
+        TextAlign::Right => (spare, 0.0),
+        TextAlign::Justify if chars.len() > 1 && spare > 0.0 => (0.0, spare / (chars.len() - 1) as f32),
+        TextAlign::Justify => (0.0, 0.0),
+    };
+    let point_at = |s: f32| {
+        let i = lengths.partition_point(|&l| l < s).clamp(1, points.len() - 1);
+        let (a, b) = (points[i - 1], points[i]);
+        let along = ((s - lengths[i - 1]) / (lengths[i] - lengths[i - 1]).max(f32::EPSILON)).clamp(0.0, 1.0);
+        (a + (b - a) * along, (b - a).normalized())
+    };
+    let mut glyphs = Vec::new();
+    for (ch, adv) in chars {
+        let middle = s + adv / 2.0;
+        if points.len() < 2 || middle > total + 0.01 {
+            break;
+        }
+        if middle >= 0.0 {
+            // Centred on the curve, so letters lean with it
+            let (at, dir) = point_at(middle);
+            glyphs.push(PathGlyph { ch, pos: at - dir * adv / 2.0, angle: dir.y.atan2(dir.x) });
+        }
+        s += adv + spacing;
+    }
+    glyphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_frame() {
+        // Every character 10 wide
+        let measure = |s: &str| s.chars().count() as f32 * 10.0;
+        // 100 wide inside the padding
+        let width = 100.0 + FRAME_PADDING * 2.0;
+        let runs = layout_frame("abc de\nfg", width, 200.0, 10.0, 2.0, TextAlign::Right, measure);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].pos, Pos2::new(FRAME_PADDING + 40.0, FRAME_PADDING));
+        assert_eq!(runs[1].pos, Pos2::new(FRAME_PADDING + 80.0, FRAME_PADDING + 20.0));
+
+        // Justified: the first line's words spread to both edges, the
+        // paragraph's last line stays left
+        let runs = layout_frame("aaa bb cc dddd", width, 200.0, 10.0, 1.0, TextAlign::Justify, measure);
+        let texts: Vec<&str> = runs.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, ["aaa", "bb", "cc", "dddd"]);
+        assert_eq!((runs[1].pos.x, runs[2].pos.x), (FRAME_PADDING + 45.0, FRAME_PADDING + 80.0));
+        assert_eq!(runs[3].pos, Pos2::new(FRAME_PADDING, FRAME_PADDING + 10.0));
+
+        // Lines that don't fit the frame are left out
+        assert_eq!(layout_frame("a\nb\nc", width, 30.0, 10.0, 1.0, TextAlign::Left, measure).len(), 2);
+    }
+
+    #[test]
+    fn test_layout_path() {
+        let line = [Pos2::new(0.0, 0.0), Pos2::new(50.0, 0.0), Pos2::new(100.0, 0.0)];
+        let glyphs = layout_path("abc", &line, TextAlign::Center, |_| 10.0);
+        let xs: Vec<f32> = glyphs.iter().map(|g| g.pos.x).collect();
+        assert_eq!(xs, [35.0, 45.0, 55.0]);
+        assert!(glyphs.iter().all(|g| g.angle == 0.0 && g.pos.y == 0.0));
+        // Too long for the path: the end is cut
+        assert_eq!(layout_path("abcdefghijkl", &line, TextAlign::Left, |_| 10.0).len(), 10);
+
+        // Over the top of an arc the text runs level, left to right
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(200.0, 100.0));
+        let points = path_points(TextPath::Arc, rect, 10.0);
+        let glyphs = layout_path("i", &points, TextAlign::Center, |_| 4.0);
+        assert!(glyphs[0].angle.abs() < 0.05);
+        assert!((glyphs[0].pos.y - 10.0).abs() < 0.5);
+    }
+}
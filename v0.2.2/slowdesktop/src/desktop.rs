@@ -8,20 +8,51 @@
 //! - Running app indicators
 //! - Keyboard navigation
 //! - About dialog with system info
-
+//! - Screen magnifier (hold ⌘ once enabled in the slowOS menu)
+//! - Notification toasts, with "undo" for file operations
+//! - Menu extras from apps (e.g. slowBreath's focus countdown), and panels
+//!   from plugins
+//! - Icons for plugged-in USB drives, which open in slowFiles and eject
+//! - Boot splash while startup apps open, and a shutdown that lets apps
+//!   save before powering off
+//! - Login and lock screen once there are user accounts (⌘L to lock)
+//! - Opens the on-screen keyboard for text fields, when turned on
+//! - Recently opened files in search, before anything is typed
+//! - A report of the last crash on the next start, with its log entry
+//! - Thumbnails of Pictures made ahead, for slowFiles
+
+use crate::keyboard;
+use crate::lock::{self, LockResult, LockScreen};
+use crate::magnifier::{Magnifier, MAX_ZOOM, MIN_ZOOM};
 use crate::process_manager::{AppInfo, ProcessManager};
+use crate::splash::{self, BootSplash, SessionEnd, Shutdown};
 use chrono::Local;
 use egui::{
     Align2, ColorImage, Context, FontId, Key, Painter, Pos2, Rect, Response, Sense, Stroke,
     TextureHandle, TextureOptions, Ui, Vec2,
 };
+use slowcore::accounts::{self, Account, Accounts};
+use slowcore::animation;
+use slowcore::app_registry;
 use slowcore::dither;
+use slowcore::ipc;
+use slowcore::log;
+use slowcore::logs::{self, Crash};
+use slowcore::menu_extra::MenuExtra;
 use slowcore::minimize::MinimizedApp;
+use slowcore::notify::{Notification, TOAST_DURATION};
+use slowcore::plugins::{self, Plugin, PluginKind};
+use slowcore::running::RunningApp;
+use slowcore::window_layout::{Tile, WindowRect};
 use slowcore::repaint::RepaintController;
-use slowcore::storage::config_dir;
+use slowcore::restrictions;
+use slowcore::safety::PowerAction;
+use slowcore::storage::{config_dir, recent_everywhere, RecentFile};
 use slowcore::theme::SlowColors;
+use slowcore::volumes::{self, Volume};
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
 /// Load persisted date/time settings from the system settings file.
@@ -71,11 +102,21 @@ const ICON_LABEL_HEIGHT: f32 = 16.0;
 const ICON_TOTAL_HEIGHT: f32 = 52.0 + ICON_LABEL_HEIGHT;
 const DESKTOP_PADDING: f32 = 24.0;
 const MENU_BAR_HEIGHT: f32 = 22.0;
+const STATUS_BAR_HEIGHT: f32 = 20.0;
 const ICONS_PER_COLUMN: usize = 6;
 
 /// Double-click timing threshold in milliseconds
 const DOUBLE_CLICK_MS: u128 = 400;
 
+/// How often plugin panels are asked for their text
+const PANEL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A notification on screen
+struct Toast {
+    notification: Notification,
+    shown_at: Instant,
+}
+
 /// Desktop application state
 pub struct DesktopApp {
     /// Process manager for launching/tracking apps
@@ -92,6 +133,10 @@ pub struct DesktopApp {
     show_about: bool,
     /// Show shutdown dialog
     show_shutdown: bool,
+    /// A crash since the desktop last started, not yet dismissed
+    crash_report: Option<Crash>,
+    /// The crash report shows the whole log entry
+    crash_details: bool,
     /// Status message (bottom of screen)
     status_message: String,
     /// Status message timestamp
@@ -145,14 +190,71 @@ pub struct DesktopApp {
     last_running_count: usize,
     /// Cached search file results: (query, results)
     search_file_cache: Option<(String, Vec<(std::path::PathBuf, String)>)>,
+    /// Files recently opened in any app, read when search opens
+    search_recent: Vec<RecentFile>,
     /// Repaint controller for partial repainting
     repaint: RepaintController,
     /// Cached list of minimized apps (refreshed periodically)
     minimized_apps: Vec<MinimizedApp>,
+    /// ⌘+hover zoom lens
+    magnifier: Magnifier,
+    /// Trash size for the status bar (refreshed with minimized apps)
+    trash_usage: trash::TrashUsage,
+    /// Notifications currently shown, oldest first
+    toasts: Vec<Toast>,
+    /// Status items published by apps, shown left of the date
+    menu_extras: Vec<MenuExtra>,
+    /// Menu bar panels from plugins, with the text they last gave
+    panels: Vec<(Plugin, String)>,
+    /// When the panels were last asked for their text
+    panels_asked: Instant,
+    /// Boot splash, until the startup apps are open
+    splash: Option<BootSplash>,
+    /// Apps opened at boot (binary names)
+    startup_apps: Vec<String>,
+    /// Shutdown in progress, waiting for apps to quit
+    shutdown: Option<Shutdown>,
+    /// Last list written for slowcore::running, to skip unchanged writes
+    published_running: Option<Vec<RunningApp>>,
+    /// Last work area written for slowcore::window_layout
+    published_work_area: Option<WindowRect>,
+    /// Mounted USB drives, shown beside the folders
+    volumes: Vec<Volume>,
+    /// Cached drive icon rects for click detection and marquee selection
+    drive_icon_rects: Vec<Rect>,
+    /// Selected drive index
+    selected_drive: Option<usize>,
+    /// Last drive click, for double-click: (index, time)
+    last_drive_click: Option<(usize, Instant)>,
+    /// An eject running in the background: (drive name, result)
+    eject_result: Option<mpsc::Receiver<(String, Result<(), String>)>>,
+    /// User accounts, re-read when locking (settings may change them)
+    accounts: Accounts,
+    /// Login or lock screen, while shown
+    lock: Option<LockScreen>,
+    /// Background services have been started for this session
+    services_started: bool,
+    /// A restricted app someone tried to open: (binary, passcode typed)
+    restricted_prompt: Option<(String, String)>,
+    /// A text field wanted the on-screen keyboard last frame
+    keyboard_wanted: bool,
 }
 
 impl DesktopApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        // Wake up for notifications even while idle
+        slowcore::notify::watch(cc.egui_ctx.clone());
+        slowcore::menu_extra::watch(cc.egui_ctx.clone());
+        slowcore::running::watch(cc.egui_ctx.clone());
+        volumes::watch(cc.egui_ctx.clone());
+        volumes::automount();
+        // The rotation and resolutions chosen in settings
+        slowcore::display::apply_saved();
+        // Warm and dim the screen at night, if settings asks
+        slowcore::night_shift::watch();
+        // A request left over from before a crash or power cut
+        slowcore::session::clear_quit_request();
+
         let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
         let docs = dirs::document_dir().unwrap_or_else(|| home.join("Documents"));
 
@@ -160,6 +262,8 @@ impl DesktopApp {
         let home_clone = home.clone();
         std::thread::spawn(move || {
             Self::setup_default_content(&home_clone);
+            // So Pictures opens quickly in slowFiles
+            slowcore::thumbnails::prepare(&slowcore::storage::pictures_dir(), slowcore::thumbnails::ICON_SIZE);
         });
 
         let desktop_folders = vec![
@@ -173,6 +277,14 @@ impl DesktopApp {
         // Load persisted date/time settings from system settings
         let (saved_24h, saved_date_fmt) = load_datetime_settings();
 
+        // Sync and scheduled backups run in the background and idle until
+        // set up
+        // With accounts, nobody is logged in until the login screen says so
+        let accounts = Accounts::load();
+        let lock = (accounts.enabled() && accounts.current().is_none()).then(LockScreen::login);
+        lock::watch_idle(&cc.egui_ctx);
+        keyboard::watch(&cc.egui_ctx);
+
         Self {
             process_manager: ProcessManager::new(),
             selected_icons: HashSet::new(),
@@ -181,6 +293,8 @@ impl DesktopApp {
             hovered_icon: None,
             show_about: false,
             show_shutdown: false,
+            crash_report: logs::unseen_crash(),
+            crash_details: false,
             status_message: "welcome to slowOS v0.2.2".to_string(),
             status_time: Instant::now(),
             frame_count: 0,
@@ -208,11 +322,115 @@ impl DesktopApp {
             cached_app_indices: None,
             last_running_count: 0,
             search_file_cache: None,
+            search_recent: Vec::new(),
             repaint: RepaintController::new(),
             minimized_apps: Vec::new(),
+            magnifier: Magnifier::new(),
+            trash_usage: trash::trash_usage(),
+            toasts: Vec::new(),
+            menu_extras: Vec::new(),
+            panels: plugins::load(PluginKind::Panel)
+                .into_iter()
+                .map(|plugin| {
+                    let text = plugin.panel_text();
+                    (plugin, text)
+                })
+                .collect(),
+            panels_asked: Instant::now(),
+            splash: Some(BootSplash::new()),
+            startup_apps: splash::load_startup_apps(),
+            shutdown: None,
+            published_running: None,
+            published_work_area: None,
+            volumes: volumes::removable(),
+            drive_icon_rects: Vec::new(),
+            selected_drive: None,
+            last_drive_click: None,
+            eject_result: None,
+            accounts,
+            lock,
+            services_started: false,
+            restricted_prompt: None,
+            keyboard_wanted: false,
         }
     }
 
+    /// Background services run once someone is logged in, with their files
+    fn start_services(&mut self) {
+        if self.services_started {
+            return;
+        }
+        self.services_started = true;
+        for (service, args) in [("slowsync", &[][..]), ("slowbackup", &["--scheduled"][..])] {
+            if let Err(e) = self.process_manager.start_service(service, args) {
+                log::warn!("{}: {}", service, e);
+            }
+        }
+    }
+
+    /// Lock the screen, if there's an account to unlock it with
+    fn lock_screen(&mut self, ctx: &Context) {
+        self.accounts = Accounts::load();
+        let Some(account) = self.accounts.current() else { return };
+        self.lock = Some(LockScreen::locked(&account.name));
+        self.show_search = false;
+        // Stay above app windows until unlocked
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(egui::WindowLevel::AlwaysOnTop));
+        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+    }
+
+    /// Draw the login or lock screen, or lock for being idle. Returns false
+    /// when neither is showing.
+    fn update_lock(&mut self, ctx: &Context) -> bool {
+        if self.lock.is_none() && self.frame_count % 30 == 0 {
+            self.refresh_accounts();
+        }
+        if self.lock.is_none() && self.shutdown.is_none() && lock::idle_too_long(&self.accounts) {
+            self.lock_screen(ctx);
+        }
+        let Some(screen) = &mut self.lock else { return false };
+        match screen.show(ctx, &self.accounts) {
+            Some(LockResult::Unlocked) => {
+                self.lock = None;
+                ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(egui::WindowLevel::Normal));
+            }
+            Some(LockResult::LoggedIn(account)) => self.log_in(&account),
+            None => {}
+        }
+        true
+    }
+
+    /// Pick up accounts added in settings. The first account is made with
+    /// this session's home folder, so this session becomes its.
+    fn refresh_accounts(&mut self) {
+        self.accounts = Accounts::load();
+        if std::env::var_os(accounts::USER_VAR).is_some() {
+            return;
+        }
+        let home = dirs::home_dir();
+        if let Some(account) = self.accounts.users.iter().find(|a| Some(&a.home) == home.as_ref()) {
+            for (key, value) in account.session_env() {
+                std::env::set_var(key, value);
+            }
+        }
+    }
+
+    /// Start `account`'s session: in place when it uses this home folder
+    /// already, otherwise by restarting the desktop in its environment
+    fn log_in(&mut self, account: &Account) {
+        let here = dirs::home_dir().is_some_and(|home| home == account.home);
+        if !here {
+            switch_session(Some(account));
+            self.set_status("couldn't start the session");
+            return;
+        }
+        for (key, value) in account.session_env() {
+            std::env::set_var(key, value);
+        }
+        self.lock = None;
+        self.set_status(format!("welcome, {}", account.name));
+    }
+
     /// Setup default content folders (slowLibrary books, slowMuseum pictures)
     /// This runs on first launch to populate user folders with bundled content.
     fn setup_default_content(home: &PathBuf) {
@@ -487,6 +705,10 @@ impl DesktopApp {
 
     /// Launch an app directly
     fn launch_app_direct(&mut self, binary: &str) {
+        if self.process_manager.is_blocked(binary) {
+            self.restricted_prompt = Some((binary.to_string(), String::new()));
+            return;
+        }
         match self.process_manager.launch(binary) {
             Ok(true) => {
                 self.set_status(format!("{} launched", binary));
@@ -496,7 +718,7 @@ impl DesktopApp {
             }
             Err(e) => {
                 self.set_status(format!("error: {}", e));
-                eprintln!("[slowdesktop] launch error: {}", e);
+                log::error!("launch error: {}", e);
             }
         }
     }
@@ -603,8 +825,17 @@ impl DesktopApp {
             );
         }
 
+        // Restricted right now: greyed out with a white dither
+        let blocked = self.process_manager.is_blocked(&app.binary);
+        if blocked {
+            dither::draw_dither_rect(painter, icon_rect, SlowColors::WHITE, 1);
+        }
+
         Self::draw_icon_label(painter, pos, &app.display_name, is_selected);
 
+        if blocked {
+            return response.clone().on_hover_text(format!("{} (not allowed right now)", app.description));
+        }
         response.clone().on_hover_text(&app.description)
     }
 
@@ -679,6 +910,104 @@ impl DesktopApp {
         }
     }
 
+    /// Pick up drives plugged in or pulled out since the last frame
+    fn refresh_volumes(&mut self) {
+        let now = volumes::removable();
+        if now == self.volumes {
+            return;
+        }
+        if let Some(added) = now.iter().find(|v| !self.volumes.contains(v)) {
+            self.set_status(format!("{} is ready", added.name));
+        }
+        self.volumes = now;
+        self.selected_drive = None;
+        self.last_drive_click = None;
+    }
+
+    /// Draw a USB drive icon (drawn rather than a PNG: a stick with its plug)
+    fn draw_drive_icon(&self, ui: &mut Ui, pos: Pos2, index: usize) -> Response {
+        let total_rect = Rect::from_min_size(
+            Pos2::new(pos.x - 8.0, pos.y),
+            Vec2::new(ICON_SIZE + 16.0, ICON_TOTAL_HEIGHT + 4.0),
+        );
+        let response = ui.allocate_rect(total_rect, Sense::click());
+        let painter = ui.painter();
+        let is_selected = self.selected_drive == Some(index);
+
+        let icon_rect = Rect::from_min_size(
+            Pos2::new(pos.x + (ICON_SIZE - 48.0) / 2.0, pos.y),
+            Vec2::new(48.0, 48.0),
+        );
+        painter.rect_filled(icon_rect, 0.0, SlowColors::WHITE);
+        if response.hovered() && !is_selected {
+            dither::draw_dither_hover(painter, icon_rect);
+        }
+        if is_selected {
+            dither::draw_dither_selection(painter, icon_rect);
+        }
+
+        let body = Rect::from_center_size(icon_rect.center() + Vec2::new(4.0, 0.0), Vec2::new(32.0, 18.0));
+        let plug = Rect::from_min_max(
+            Pos2::new(body.min.x - 9.0, body.center().y - 5.0),
+            Pos2::new(body.min.x, body.center().y + 5.0),
+        );
+        painter.rect_filled(body, 2.0, SlowColors::WHITE);
+        painter.rect_stroke(body, 2.0, Stroke::new(1.5, SlowColors::BLACK));
+        painter.rect_stroke(plug, 0.0, Stroke::new(1.5, SlowColors::BLACK));
+        let light = Rect::from_center_size(Pos2::new(body.max.x - 6.0, body.center().y), Vec2::splat(4.0));
+        painter.rect_filled(light, 0.0, SlowColors::BLACK);
+
+        let name = &self.volumes[index].name;
+        let label = if name.chars().count() > 12 {
+            format!("{}...", name.chars().take(11).collect::<String>())
+        } else {
+            name.clone()
+        };
+        Self::draw_icon_label(painter, pos, &label, is_selected);
+
+        response.on_hover_text(self.volumes[index].mount_point.to_string_lossy().to_string())
+    }
+
+    /// Open a drive in slowFiles
+    fn open_drive(&mut self, index: usize) {
+        let Some(volume) = self.volumes.get(index) else { return };
+        let name = volume.name.clone();
+        let path_str = volume.mount_point.to_string_lossy().to_string();
+        match self.process_manager.launch_with_args("slowfiles", &[&path_str]) {
+            Ok(true) => self.set_status(format!("opening {}...", name)),
+            Ok(false) => self.set_status("files is already running".to_string()),
+            Err(e) => self.set_status(format!("error: {}", e)),
+        }
+    }
+
+    /// Eject a drive in the background; flushing a slow stick can take a while
+    fn eject_drive(&mut self, ctx: &Context, index: usize) {
+        let Some(volume) = self.volumes.get(index).cloned() else { return };
+        if self.eject_result.is_some() {
+            return;
+        }
+        self.set_status(format!("ejecting {}...", volume.name));
+        let (tx, rx) = mpsc::channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = volumes::eject(&volume);
+            let _ = tx.send((volume.name, result));
+            ctx.request_repaint();
+        });
+        self.eject_result = Some(rx);
+    }
+
+    fn check_eject(&mut self) {
+        let Some(rx) = &self.eject_result else { return };
+        let Ok((name, result)) = rx.try_recv() else { return };
+        self.eject_result = None;
+        match result {
+            Ok(()) => self.set_status(format!("{} can be removed", name)),
+            Err(e) => self.set_status(format!("couldn't eject: {}", e)),
+        }
+        self.refresh_volumes();
+    }
+
     /// Draw the menu bar
     fn draw_menu_bar(&mut self, ctx: &Context) {
         egui::TopBottomPanel::top("menu_bar")
@@ -701,6 +1030,52 @@ impl DesktopApp {
                             ui.close_menu();
                         }
                         ui.separator();
+                        let mut enabled = self.magnifier.settings.enabled;
+                        if ui.checkbox(&mut enabled, "magnifier (hold ⌘)").changed() {
+                            self.magnifier.set_enabled(enabled);
+                        }
+                        if enabled {
+                            ui.horizontal(|ui| {
+                                ui.add_space(16.0);
+                                for zoom in MIN_ZOOM..=MAX_ZOOM {
+                                    let current = self.magnifier.settings.zoom == zoom;
+                                    if ui.selectable_label(current, format!("{}x", zoom)).clicked() {
+                                        self.magnifier.set_zoom(zoom);
+                                    }
+                                }
+                            });
+                        }
+                        ui.menu_button("open at startup", |ui| {
+                            let apps: Vec<(String, String)> = self
+                                .process_manager
+                                .apps()
+                                .iter()
+                                .filter(|a| a.binary != "credits")
+                                .map(|a| (a.binary.clone(), a.display_name.clone()))
+                                .collect();
+                            for (binary, display_name) in apps {
+                                let mut on = self.startup_apps.contains(&binary);
+                                if ui.checkbox(&mut on, display_name).changed() {
+                                    if on {
+                                        self.startup_apps.push(binary);
+                                    } else {
+                                        self.startup_apps.retain(|b| b != &binary);
+                                    }
+                                    splash::save_startup_apps(&self.startup_apps);
+                                }
+                            }
+                        });
+                        ui.separator();
+                        if self.accounts.current().is_some() {
+                            if ui.button("lock screen  ⌘L").clicked() {
+                                self.lock_screen(ui.ctx());
+                                ui.close_menu();
+                            }
+                            if ui.button("log out").clicked() {
+                                self.begin_shutdown(SessionEnd::LogOut);
+                                ui.close_menu();
+                            }
+                        }
                         if ui.button("shut down...").clicked() {
                             self.show_shutdown = true;
                             ui.close_menu();
@@ -732,6 +1107,40 @@ impl DesktopApp {
                         }
                     });
 
+                    // Window menu: tile running apps' windows
+                    ui.menu_button("window", |ui| {
+                        let running = self.process_manager.running_apps();
+                        if running.is_empty() {
+                            ui.label("no open windows");
+                            return;
+                        }
+                        if running.len() >= 2 && ui.button("side by side").clicked() {
+                            // The two most recently opened windows
+                            let mut newest: Vec<&RunningApp> = running.iter().collect();
+                            newest.sort_by_key(|a| std::cmp::Reverse(a.pid));
+                            slowcore::window_layout::request_tile(newest[1].pid, Tile::Left);
+                            slowcore::window_layout::request_tile(newest[0].pid, Tile::Right);
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        for app in &running {
+                            ui.menu_button(&app.name, |ui| {
+                                for (tile, label) in [
+                                    (Tile::Left, "left half"),
+                                    (Tile::Right, "right half"),
+                                    (Tile::Maximize, "maximize"),
+                                    (Tile::Restore, "restore"),
+                                ] {
+                                    let text = format!("{}    {}", label, tile.combo().label());
+                                    if ui.button(text).clicked() {
+                                        slowcore::window_layout::request_tile(app.pid, tile);
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
+                        }
+                    });
+
                     // Date, clock, and search on the right
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         // Padding from right edge
@@ -747,6 +1156,7 @@ impl DesktopApp {
                             if self.show_search {
                                 self.search_query.clear();
                                 self.search_opened_frame = self.frame_count;
+                                self.search_recent = recent_everywhere();
                             }
                         }
 
@@ -847,6 +1257,19 @@ impl DesktopApp {
                             self.date_format = (self.date_format + 1) % 4;
                             save_datetime_settings(self.use_24h_time, self.date_format);
                         }
+
+                        for extra in &self.menu_extras {
+                            ui.add_space(12.0);
+                            draw_menu_extra(ui, extra);
+                        }
+                        for (_, text) in &self.panels {
+                            ui.add_space(12.0);
+                            ui.label(
+                                egui::RichText::new(text)
+                                    .font(FontId::proportional(12.0))
+                                    .color(SlowColors::BLACK),
+                            );
+                        }
                     });
                 });
             });
@@ -859,7 +1282,7 @@ impl DesktopApp {
         let minimized = self.minimized_apps.clone();
 
         egui::TopBottomPanel::bottom("status_bar")
-            .exact_height(20.0)
+            .exact_height(STATUS_BAR_HEIGHT)
             .frame(
                 egui::Frame::none()
                     .fill(SlowColors::WHITE)
@@ -908,6 +1331,15 @@ impl DesktopApp {
                                 .font(FontId::proportional(11.0))
                                 .color(SlowColors::BLACK),
                         );
+
+                        if self.trash_usage.items > 0 {
+                            ui.separator();
+                            ui.label(
+                                egui::RichText::new(format!("trash: {}", self.trash_usage.label()))
+                                    .font(FontId::proportional(11.0))
+                                    .color(SlowColors::BLACK),
+                            );
+                        }
                     });
                 });
             });
@@ -921,6 +1353,119 @@ impl DesktopApp {
         }
     }
 
+    /// Carry out quit requests from slowMonitor and publish the running list
+    fn sync_running_apps(&mut self) {
+        for pid in slowcore::running::take_quit_requests() {
+            if let Some(name) = self.process_manager.quit_pid(pid) {
+                self.set_status(format!("{} was force quit", name));
+            }
+        }
+        let running = self.process_manager.running_apps();
+        if self.published_running.as_ref() != Some(&running) {
+            slowcore::running::publish(&running);
+            self.published_running = Some(running);
+        }
+    }
+
+    /// Tell apps where windows may go: the screen between our menu bar
+    /// and status bar
+    fn publish_work_area(&mut self, ctx: &Context) {
+        let Some(outer) = ctx.input(|i| i.viewport().outer_rect) else { return };
+        let area = WindowRect {
+            x: outer.min.x,
+            y: outer.min.y + MENU_BAR_HEIGHT,
+            w: outer.width(),
+            h: outer.height() - MENU_BAR_HEIGHT - STATUS_BAR_HEIGHT,
+        };
+        if self.published_work_area != Some(area) {
+            slowcore::window_layout::publish_screen(area);
+            self.published_work_area = Some(area);
+        }
+    }
+
+    /// Show new notifications and draw the toasts in the bottom-right corner
+    fn draw_toasts(&mut self, ctx: &Context) {
+        for notification in slowcore::notify::take_all() {
+            self.toasts.push(Toast { notification, shown_at: Instant::now() });
+        }
+        self.toasts.retain(|t| t.shown_at.elapsed() < TOAST_DURATION);
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        let mut undo_index = None;
+        let mut reveal_index = None;
+        let mut open_index = None;
+        let mut close_index = None;
+        let screen = ctx.screen_rect();
+        // Above the status bar, newest at the bottom
+        let mut bottom = screen.max.y - STATUS_BAR_HEIGHT - 8.0;
+        for (i, toast) in self.toasts.iter().enumerate().rev() {
+            let resp = egui::Area::new(egui::Id::new(("toast", &toast.notification.id)))
+                .order(egui::Order::Foreground)
+                .pivot(Align2::RIGHT_BOTTOM)
+                .fixed_pos(Pos2::new(screen.max.x - 8.0, bottom))
+                .show(ctx, |ui| {
+                    egui::Frame::none()
+                        .fill(SlowColors::WHITE)
+                        .stroke(Stroke::new(1.0, SlowColors::BLACK))
+                        .inner_margin(egui::Margin::symmetric(8.0, 6.0))
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(&toast.notification.message);
+                                if toast.notification.undo.is_some() && ui.button("undo").clicked() {
+                                    undo_index = Some(i);
+                                }
+                                if toast.notification.reveal.is_some() && ui.button("show").clicked() {
+                                    reveal_index = Some(i);
+                                }
+                                if toast.notification.open.is_some() && ui.button("open now").clicked() {
+                                    open_index = Some(i);
+                                }
+                                if ui.small_button("×").clicked() {
+                                    close_index = Some(i);
+                                }
+                            });
+                        });
+                });
+            dither::draw_window_shadow(ctx, resp.response.rect);
+            bottom = resp.response.rect.min.y - 8.0;
+        }
+
+        if let Some(i) = undo_index {
+            let toast = self.toasts.remove(i);
+            if let Some(undo) = &toast.notification.undo {
+                let restored = trash::perform_undo(undo);
+                self.set_status(if restored == 1 {
+                    "undo: 1 item put back".to_string()
+                } else {
+                    format!("undo: {} items put back", restored)
+                });
+                self.trash_usage = trash::trash_usage();
+            }
+        } else if let Some(i) = reveal_index {
+            let toast = self.toasts.remove(i);
+            if let Some(path) = &toast.notification.reveal {
+                // Files are shown in the folder that holds them
+                let dir = if path.is_dir() { path.as_path() } else { path.parent().unwrap_or(path) };
+                let dir_str = dir.to_string_lossy().to_string();
+                let _ = self.process_manager.launch_with_args("slowfiles", &[&dir_str]);
+            }
+        } else if let Some(i) = open_index {
+            let toast = self.toasts.remove(i);
+            if let Some(path) = &toast.notification.open {
+                self.open_file_with_app(path);
+            }
+        } else if let Some(i) = close_index {
+            self.toasts.remove(i);
+        }
+
+        // Come back to take down the oldest toast
+        if let Some(first) = self.toasts.first() {
+            ctx.request_repaint_after(TOAST_DURATION.saturating_sub(first.shown_at.elapsed()));
+        }
+    }
+
     /// Restore a minimized window.
     /// The actual unminimize happens via the signal file written by
     /// `remove_minimized()` — each app polls `check_restore_signal()`
@@ -992,6 +1537,224 @@ impl DesktopApp {
         if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
     }
 
+    /// Draw the report of the last crash, once after it happened
+    fn draw_crash_report(&mut self, ctx: &Context) {
+        let Some(crash) = &self.crash_report else { return };
+        // Summaries start with the binary that crashed
+        let binary = crash.summary.split_whitespace().next().unwrap_or_default();
+        let name = match binary {
+            "slowdesktop" => "slowOS".to_string(),
+            _ => self.process_manager.apps().iter()
+                .find(|a| a.binary == binary)
+                .map(|a| a.display_name.clone())
+                .unwrap_or_else(|| binary.to_string()),
+        };
+        let mut close = false;
+        let mut copy = false;
+        let mut open_logs = false;
+        let resp = egui::Window::new("crash report")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(340.0)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.add_space(8.0);
+                ui.label(format!("{} quit unexpectedly on {}.", name, crash.time));
+                ui.add_space(4.0);
+                ui.weak(&crash.summary);
+                if self.crash_details {
+                    ui.add_space(8.0);
+                    egui::ScrollArea::both().max_height(200.0).show(ui, |ui| {
+                        ui.label(egui::RichText::new(&crash.details).monospace().size(11.0));
+                    });
+                    if ui.link("open the log viewer").clicked() {
+                        open_logs = true;
+                    }
+                }
+                ui.add_space(12.0);
+                ui.horizontal(|ui| {
+                    let details = if self.crash_details { "hide details" } else { "view details" };
+                    if ui.button(details).clicked() {
+                        self.crash_details = !self.crash_details;
+                    }
+                    if ui.button("copy").clicked() {
+                        copy = true;
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("ok").clicked() {
+                            close = true;
+                        }
+                    });
+                });
+                ui.add_space(4.0);
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+        if copy {
+            let details = crash.details.clone();
+            ctx.output_mut(|o| o.copied_text = details);
+            self.set_status("crash report copied");
+        }
+        if open_logs {
+            self.launch_app_animated("slowlogs");
+        }
+        if close || open_logs {
+            if let Some(crash) = self.crash_report.take() {
+                logs::mark_seen(&crash);
+            }
+        }
+    }
+
+    /// Ask running apps to save and quit; the shutdown screen takes over
+    fn begin_shutdown(&mut self, action: SessionEnd) {
+        self.show_shutdown = false;
+        slowcore::session::request_quit();
+        self.shutdown = Some(Shutdown::new(action));
+    }
+
+    /// Shut down or restart once every app has quit or the timeout ran
+    /// out. Returns false if there is no shutdown in progress.
+    fn update_shutdown(&mut self, ctx: &Context) -> bool {
+        let Some(shutdown) = &self.shutdown else { return false };
+        self.process_manager.poll();
+        let waiting = self.process_manager.running_names();
+        let title = match shutdown.action {
+            SessionEnd::Power(PowerAction::PowerOff) => "shutting down",
+            SessionEnd::Power(PowerAction::Reboot) => "restarting",
+            SessionEnd::LogOut => "logging out",
+        };
+        let detail = if waiting.is_empty() {
+            "goodbye".to_string()
+        } else {
+            format!("waiting for {} to quit...", waiting.join(", "))
+        };
+        let choice = splash::draw_screen(
+            ctx,
+            self.icon_textures.get("hourglass_large"),
+            title,
+            &detail,
+            shutdown.progress(),
+            &["cancel", "quit now"],
+        );
+        match choice {
+            Some(0) => {
+                slowcore::session::clear_quit_request();
+                self.shutdown = None;
+                self.set_status("shutdown cancelled");
+                return true;
+            }
+            Some(_) => {}
+            None if waiting.is_empty() || shutdown.timed_out() => {}
+            None => {
+                ctx.request_repaint_after(Duration::from_millis(250));
+                return true;
+            }
+        }
+
+        let action = shutdown.action;
+        self.process_manager.shutdown_all();
+        slowcore::session::clear_quit_request();
+        let SessionEnd::Power(action) = action else {
+            switch_session(None);
+            std::process::exit(1);
+        };
+        if let Err(e) = slowcore::safety::power_action(action) {
+            log::error!("{:?}: {}", action, e);
+            if action == PowerAction::Reboot {
+                restart_desktop();
+            }
+        }
+        std::process::exit(0);
+    }
+
+    /// Draw the boot splash while startup apps open. Returns false once
+    /// it is done.
+    fn update_splash(&mut self, ctx: &Context) -> bool {
+        let Some(boot) = &mut self.splash else { return false };
+        if boot.is_done() {
+            self.splash = None;
+            return false;
+        }
+        let next = boot.next_launch();
+        let detail = match &next {
+            Some(binary) => {
+                let name = self.process_manager.apps().iter()
+                    .find(|a| &a.binary == binary)
+                    .map(|a| a.display_name.clone())
+                    .unwrap_or_else(|| binary.clone());
+                format!("opening {}...", name)
+            }
+            None => "starting up...".to_string(),
+        };
+        splash::draw_screen(ctx, self.icon_textures.get("hourglass_large"), "slowOS", &detail, boot.progress(), &[]);
+        ctx.request_repaint_after(slowcore::animation::FrameBudget::EINK.effective().interval());
+        if let Some(binary) = next {
+            self.launch_app_direct(&binary);
+        }
+        true
+    }
+
+    /// Draw the "not allowed right now" dialog for a restricted app, with
+    /// the passcode that lifts restrictions
+    fn draw_restricted(&mut self, ctx: &Context) {
+        let Some((binary, passcode)) = &mut self.restricted_prompt else { return };
+        let name = self.process_manager.apps().iter()
+            .find(|a| &a.binary == binary)
+            .map(|a| a.display_name.clone())
+            .unwrap_or_else(|| binary.clone());
+        let hours = self.process_manager.allowed_hours(binary);
+        let has_passcode = self.process_manager.has_passcode();
+        let mut close = false;
+        let mut entered = false;
+        let resp = egui::Window::new("restricted")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(280.0)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(8.0);
+                    ui.label(format!("{} isn't allowed right now.", name));
+                    if let Some(hours) = &hours {
+                        ui.label(format!("it can be used {}.", hours));
+                    }
+                    if has_passcode {
+                        ui.add_space(8.0);
+                        ui.label("passcode:");
+                        let field = ui.add(egui::TextEdit::singleline(passcode).password(true).desired_width(140.0));
+                        field.request_focus();
+                        entered = field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                    }
+                    ui.add_space(12.0);
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("ok").clicked() {
+                        close = true;
+                    }
+                    if has_passcode {
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.button("allow").clicked() {
+                                entered = true;
+                            }
+                        });
+                    }
+                });
+                ui.add_space(4.0);
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+        if entered {
+            let (binary, passcode) = self.restricted_prompt.take().unwrap_or_default();
+            if self.process_manager.override_restrictions(&passcode) {
+                self.cached_app_indices = None;
+                self.set_status(format!("restrictions lifted for {} minutes", restrictions::OVERRIDE_TIME.as_secs() / 60));
+                self.launch_app_direct(&binary);
+            } else {
+                self.set_status("wrong passcode");
+            }
+        } else if close {
+            self.restricted_prompt = None;
+        }
+    }
+
     /// Draw the shutdown confirmation dialog
     fn draw_shutdown(&mut self, ctx: &Context) {
         if !self.show_shutdown {
@@ -1012,7 +1775,7 @@ impl DesktopApp {
                             running,
                             if running == 1 { " is" } else { "s are" }
                         ));
-                        ui.label("these will be closed.");
+                        ui.label("they will be asked to save and quit.");
                     } else {
                         ui.label("choose an action:");
                     }
@@ -1024,38 +1787,10 @@ impl DesktopApp {
                     }
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         if ui.button("shut down").clicked() {
-                            self.process_manager.shutdown_all();
-                            if std::path::Path::new("/sbin/poweroff").exists() {
-                                let _ = std::process::Command::new("/sbin/poweroff").spawn();
-                            }
-                            std::process::exit(0);
+                            self.begin_shutdown(SessionEnd::Power(PowerAction::PowerOff));
                         }
                         if ui.button("restart").clicked() {
-                            self.process_manager.shutdown_all();
-                            // Try system reboot first (for embedded/buildroot)
-                            if std::path::Path::new("/sbin/reboot").exists() {
-                                let _ = std::process::Command::new("/sbin/reboot").spawn();
-                            } else {
-                                // Restart the desktop app itself
-                                if let Ok(exe) = std::env::current_exe() {
-                                    #[cfg(unix)]
-                                    {
-                                        use std::os::unix::process::CommandExt;
-                                        // Fork a new process that's fully detached
-                                        let _ = std::process::Command::new(&exe)
-                                            .stdin(std::process::Stdio::null())
-                                            .stdout(std::process::Stdio::null())
-                                            .stderr(std::process::Stdio::null())
-                                            .process_group(0)
-                                            .spawn();
-                                    }
-                                    #[cfg(not(unix))]
-                                    {
-                                        let _ = std::process::Command::new(&exe).spawn();
-                                    }
-                                }
-                            }
-                            std::process::exit(0);
+                            self.begin_shutdown(SessionEnd::Power(PowerAction::Reboot));
                         }
                     });
                 });
@@ -1106,18 +1841,33 @@ impl DesktopApp {
 
                 let mut launch_binary: Option<String> = None;
                 let mut open_file: Option<std::path::PathBuf> = None;
+                let mut open_recent: Option<RecentFile> = None;
 
                 egui::ScrollArea::vertical()
                     .max_height(256.0)
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
-                    if query.is_empty() {
+                    if query.is_empty() && self.search_recent.is_empty() {
                         ui.weak("type to search apps and files...");
+                    } else if query.is_empty() {
+                        ui.label("recent:");
+                        for recent in self.search_recent.iter().take(12) {
+                            let name = recent.path.file_name()
+                                .map(|n| n.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            if ui.selectable_label(false, format!("  {}", name))
+                                .on_hover_text(recent.path.display().to_string())
+                                .clicked()
+                            {
+                                open_recent = Some(recent.clone());
+                            }
+                        }
                     } else {
                         // Search apps (terminal hidden from search — use ⌘⌥T)
                         let app_matches: Vec<(String, String, bool)> = self.process_manager.apps().iter()
                             .filter(|a| {
                                 a.binary != "slowterm" &&
+                                !self.process_manager.is_hidden(&a.binary) &&
                                 self.process_manager.binary_exists(&a.binary) && (
                                     a.display_name.to_lowercase().contains(&query) ||
                                     a.description.to_lowercase().contains(&query) ||
@@ -1178,6 +1928,7 @@ impl DesktopApp {
                         let first_app = self.process_manager.apps().iter()
                             .find(|a| {
                                 a.binary != "slowterm" &&
+                                !self.process_manager.is_hidden(&a.binary) &&
                                 self.process_manager.binary_exists(&a.binary) && (
                                     a.display_name.to_lowercase().contains(&query) ||
                                     a.description.to_lowercase().contains(&query) ||
@@ -1206,6 +1957,17 @@ impl DesktopApp {
                     self.search_query.clear();
                     self.open_file_with_app(&path);
                 }
+
+                // Reopen in the app it was last opened in
+                if let Some(recent) = open_recent {
+                    self.show_search = false;
+                    let path = recent.path.to_string_lossy().to_string();
+                    if !self.process_manager.binary_exists(&recent.app)
+                        || self.process_manager.launch_with_args(&recent.app, &[&path]).is_err()
+                    {
+                        self.open_file_with_app(&recent.path);
+                    }
+                }
             });
 
         // Draw dithered shadow
@@ -1314,17 +2076,28 @@ impl DesktopApp {
             .unwrap_or_default();
 
         let app = match ext.as_str() {
-            "epub" => Some("slowreader"),
+            "epub" | "cbz" | "cbr" => Some("slowreader"),
             "txt" | "rtf" => Some("slowwrite"),
             "mp3" | "wav" => Some("slowmusic"),
             "midi" | "mid" => Some("slowmidi"),
             "png" | "jpg" | "jpeg" | "gif" | "bmp" | "pdf" => Some("slowview"),
             _ => None,
         };
+        // Kinds of file no built-in app opens may have an installed one
+        let app = app.map(str::to_string).or_else(|| app_registry::app_for_extension(&ext));
 
         if let Some(app_name) = app {
             let path_str = path.to_string_lossy().to_string();
-            let _ = self.process_manager.launch_with_args(app_name, &[&path_str]);
+            // An app that is already open was only brought forward, so hand
+            // it the file; off the UI thread, as the answer can take a while
+            if let Ok(false) = self.process_manager.launch_with_args(&app_name, &[&path_str]) {
+                let path = path.to_path_buf();
+                std::thread::spawn(move || {
+                    if let Some(pid) = ipc::find_app(&app_name) {
+                        ipc::send(pid, &ipc::Command::Open { path }, ipc::REPLY_TIMEOUT);
+                    }
+                });
+            }
         }
     }
 
@@ -1350,6 +2123,7 @@ impl DesktopApp {
                 if self.show_search {
                     self.search_query.clear();
                     self.search_opened_frame = self.frame_count;
+                    self.search_recent = recent_everywhere();
                 }
             }
 
@@ -1367,6 +2141,7 @@ impl DesktopApp {
                 } else {
                     self.selected_icons.clear();
                     self.selected_folders.clear();
+                    self.selected_drive = None;
                 }
             }
 
@@ -1386,16 +2161,31 @@ impl DesktopApp {
             }
         });
 
+        // Cmd+L: lock the screen
+        if ctx.input(|i| i.modifiers.command && i.key_pressed(Key::L)) {
+            self.lock_screen(ctx);
+        }
+
         // Cmd+Opt+T: launch terminal
         let launch_term = ctx.input(|i| i.modifiers.command && i.modifiers.alt && i.key_pressed(Key::T));
         if launch_term {
             self.launch_app_direct("slowterm");
         }
 
+        // Cmd+E: eject the selected drive
+        if let Some(index) = self.selected_drive {
+            if ctx.input(|i| i.modifiers.command && i.key_pressed(Key::E)) {
+                self.eject_drive(ctx, index);
+            }
+        }
+
         // Handle Enter key outside of input closure
         let enter_pressed = ctx.input(|i| i.key_pressed(Key::Enter));
 
         if enter_pressed {
+            if let Some(index) = self.selected_drive.take() {
+                self.open_drive(index);
+            }
             // Open all selected folders
             let folder_indices: Vec<usize> = self.selected_folders.iter().copied().collect();
             for index in &folder_indices {
@@ -1453,6 +2243,17 @@ impl eframe::App for DesktopApp {
         // Load icon textures on first frame
         self.load_icon_textures(ctx);
 
+        if self.update_lock(ctx) {
+            self.repaint.end_frame(ctx);
+            return;
+        }
+        self.start_services();
+
+        if self.update_splash(ctx) || self.update_shutdown(ctx) {
+            self.repaint.end_frame(ctx);
+            return;
+        }
+
         // Consume Tab key to prevent menu focus issues
         slowcore::theme::consume_special_keys(ctx);
 
@@ -1469,10 +2270,51 @@ impl eframe::App for DesktopApp {
             }
         }
 
+        // Menu extras are few and small; the watcher wakes us for changes
+        self.menu_extras = slowcore::menu_extra::read_all();
+        // Repaint when a countdown's minute changes
+        if let Some(secs) = self.menu_extras.iter().filter_map(|e| e.remaining_secs()).filter(|&r| r > 0).min() {
+            ctx.request_repaint_after(Duration::from_secs((secs - 1) % 60 + 1));
+        }
+        if !self.panels.is_empty() {
+            if self.panels_asked.elapsed() >= PANEL_INTERVAL {
+                self.panels_asked = Instant::now();
+                for (plugin, text) in &mut self.panels {
+                    *text = plugin.panel_text();
+                }
+            }
+            ctx.request_repaint_after(PANEL_INTERVAL.saturating_sub(self.panels_asked.elapsed()));
+        }
+
         // Poll minimized apps periodically
         if self.frame_count % 30 == 0 {
             self.minimized_apps = slowcore::minimize::read_all_minimized();
+            self.trash_usage = trash::trash_usage();
+            // Settings may have hidden an app, or the passcode run out
+            if self.process_manager.refresh_restrictions() {
+                self.cached_app_indices = None;
+            }
+            // slowStore may have installed or removed an app
+            if self.process_manager.refresh_installed_apps() {
+                self.cached_app_indices = None;
+                self.selected_icons.clear();
+            }
+        }
+
+        // Open the on-screen keyboard when a text field gains focus
+        let keyboard_wanted = keyboard::wanted();
+        if keyboard_wanted && !self.keyboard_wanted && !self.process_manager.is_running("slowkeyboard") {
+            let _ = self.process_manager.launch_with_args("slowkeyboard", &["--auto"]);
         }
+        self.keyboard_wanted = keyboard_wanted;
+
+        // Drives come and go rarely; the watcher wakes us for them
+        self.refresh_volumes();
+        self.check_eject();
+
+        // Quit requests are rare; the watcher wakes us for them
+        self.sync_running_apps();
+        self.publish_work_area(ctx);
 
         // No continuous repainting — the e-ink display holds its image,
         // so the clock updates on next interaction.
@@ -1505,6 +2347,7 @@ impl eframe::App for DesktopApp {
                     self.cached_app_indices = Some(self.process_manager.apps()
                         .iter().enumerate()
                         .filter(|(_, a)| !hidden_from_desktop.contains(&a.binary.as_str()))
+                        .filter(|(_, a)| !self.process_manager.is_hidden(&a.binary))
                         .map(|(i, _)| i)
                         .collect());
                     self.last_running_count = running_count;
@@ -1550,6 +2393,7 @@ impl eframe::App for DesktopApp {
                     let is_double_click = self.last_click_index == Some(index)
                         && now.duration_since(self.last_click_time).as_millis() < DOUBLE_CLICK_MS;
 
+                    self.selected_drive = None;
                     if is_double_click {
                         // If multiple icons selected, open all of them
                         if self.selected_icons.len() > 1 && self.selected_icons.contains(&index) {
@@ -1661,6 +2505,60 @@ impl eframe::App for DesktopApp {
 
                 self.hovered_folder = new_hovered_folder;
 
+                // === LEFT SIDE: USB drives (top-aligned, in the column past the folders) ===
+                let drive_x = folder_start_x
+                    + ((total_folder_items - 1) / ICONS_PER_COLUMN + 1) as f32 * ICON_SPACING;
+                let mut clicked_drive: Option<usize> = None;
+                let mut drive_menu_action: Option<(usize, bool)> = None;
+                self.drive_icon_rects.clear();
+                for index in 0..self.volumes.len() {
+                    let pos = Pos2::new(
+                        drive_x,
+                        available.min.y + DESKTOP_PADDING + index as f32 * (ICON_TOTAL_HEIGHT + 8.0),
+                    );
+                    let response = self.draw_drive_icon(ui, pos, index);
+                    self.drive_icon_rects.push(Rect::from_min_size(
+                        Pos2::new(pos.x + (ICON_SIZE - 48.0) / 2.0, pos.y),
+                        Vec2::new(48.0, 48.0),
+                    ));
+                    response.context_menu(|ui| {
+                        if ui.button("open").clicked() {
+                            drive_menu_action = Some((index, false));
+                            ui.close_menu();
+                        }
+                        if ui.button("eject  ⌘E").clicked() {
+                            drive_menu_action = Some((index, true));
+                            ui.close_menu();
+                        }
+                    });
+                    if response.clicked() {
+                        clicked_drive = Some(index);
+                    }
+                }
+                match drive_menu_action {
+                    Some((index, true)) => self.eject_drive(ui.ctx(), index),
+                    Some((index, false)) => self.open_drive(index),
+                    None => {}
+                }
+                let drive_was_clicked = if let Some(index) = clicked_drive {
+                    let now = Instant::now();
+                    let is_double_click = self.last_drive_click.is_some_and(|(last, at)| {
+                        last == index && now.duration_since(at).as_millis() < DOUBLE_CLICK_MS
+                    });
+                    if is_double_click {
+                        self.selected_drive = None;
+                        self.open_drive(index);
+                    } else {
+                        self.selected_drive = Some(index);
+                        self.selected_icons.clear();
+                        self.selected_folders.clear();
+                    }
+                    self.last_drive_click = Some((index, now));
+                    true
+                } else {
+                    false
+                };
+
                 // Handle folder clicks
                 let folder_was_clicked = if let Some(index) = clicked_folder {
                     let now = Instant::now();
@@ -1691,6 +2589,7 @@ impl eframe::App for DesktopApp {
                         self.selected_folders.clear();
                         self.selected_folders.insert(index);
                         self.selected_icons.clear();
+                        self.selected_drive = None;
                     }
 
                     self.last_folder_click_time = now;
@@ -1707,15 +2606,17 @@ impl eframe::App for DesktopApp {
                 let primary_released = ui.input(|i| i.pointer.primary_released());
 
                 // Start marquee when clicking on empty space
-                if primary_pressed && !icon_was_clicked && !folder_was_clicked {
+                if primary_pressed && !icon_was_clicked && !folder_was_clicked && !drive_was_clicked {
                     if let Some(pos) = pointer_pos {
                         // Check if click is on any icon
                         let on_app_icon = self.icon_rects.iter().any(|(_, r)| r.contains(pos));
                         let on_folder_icon = self.folder_icon_rects.iter().any(|r| r.contains(pos));
-                        if !on_app_icon && !on_folder_icon {
+                        let on_drive_icon = self.drive_icon_rects.iter().any(|r| r.contains(pos));
+                        if !on_app_icon && !on_folder_icon && !on_drive_icon {
                             self.marquee_start = Some(pos);
                             self.selected_icons.clear();
                             self.selected_folders.clear();
+                            self.selected_drive = None;
                         }
                     }
                 }
@@ -1765,7 +2666,7 @@ impl eframe::App for DesktopApp {
                 }
 
                 // Deselect when clicking empty space (only if not marquee)
-                if !icon_was_clicked && !folder_was_clicked && self.marquee_start.is_none() {
+                if !icon_was_clicked && !folder_was_clicked && !drive_was_clicked && self.marquee_start.is_none() {
                     if !self.selected_icons.is_empty() || !self.selected_folders.is_empty() {
                         let pointer_clicked = ui.input(|i| i.pointer.any_click());
                         if pointer_clicked {
@@ -1786,8 +2687,14 @@ impl eframe::App for DesktopApp {
 
         // Dialogs
         self.draw_about(ctx);
+        self.draw_crash_report(ctx);
         self.draw_shutdown(ctx);
+        self.draw_restricted(ctx);
         self.draw_search(ctx);
+        self.draw_toasts(ctx);
+
+        // Drawn last so the lens sits above everything else
+        self.magnifier.update(ctx);
 
         self.repaint.end_frame(ctx);
     }
@@ -1796,3 +2703,65 @@ impl eframe::App for DesktopApp {
         self.process_manager.shutdown_all();
     }
 }
+
+/// One app's menu extra: label, and for countdowns a pie glyph of the time
+/// left and whole minutes remaining
+fn draw_menu_extra(ui: &mut egui::Ui, extra: &MenuExtra) {
+    let text = match extra.remaining_secs() {
+        Some(secs) => format!("{} {}m", extra.label, secs.div_ceil(60)),
+        None => extra.label.clone(),
+    };
+    ui.label(
+        egui::RichText::new(text)
+            .font(FontId::proportional(12.0))
+            .color(SlowColors::BLACK),
+    );
+    if let Some(left) = extra.fraction_left() {
+        let (rect, _) = ui.allocate_exact_size(Vec2::splat(12.0), Sense::hover());
+        let painter = ui.painter();
+        animation::progress_pie(painter, rect.center(), 5.5, left, SlowColors::BLACK);
+        painter.circle_stroke(rect.center(), 5.5, Stroke::new(1.0, SlowColors::BLACK));
+    }
+}
+
+/// Replace this desktop with one for `account`'s session, or with the
+/// login screen for `None`. exec keeps the process, so cage stays up.
+/// Returns only if that failed.
+fn switch_session(account: Option<&Account>) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        let Ok(exe) = std::env::current_exe() else { return };
+        let mut command = std::process::Command::new(exe);
+        match account {
+            Some(account) => command.envs(account.session_env()),
+            None => command.env_remove(accounts::USER_VAR),
+        };
+        let e = command.exec();
+        log::error!("couldn't switch session: {}", e);
+    }
+    #[cfg(not(unix))]
+    let _ = account;
+}
+
+/// Start a fresh copy of the desktop, detached, for "restart" where the
+/// machine itself can't be rebooted (development runs)
+fn restart_desktop() {
+    if let Ok(exe) = std::env::current_exe() {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            // Fork a new process that's fully detached
+            let _ = std::process::Command::new(&exe)
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .process_group(0)
+                .spawn();
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = std::process::Command::new(&exe).spawn();
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Opening the on-screen keyboard by itself
+//!
+//! With "show the on-screen keyboard when typing" on in settings, a text
+//! field gaining focus in any app opens slowKeyboard, which closes itself
+//! again once nobody is typing.
+
+use egui::Context;
+use slowcore::keyboard;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// How often the watcher looks
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Whether some app's text field wants the on-screen keyboard
+pub fn wanted() -> bool {
+    keyboard::auto_show() && keyboard::focus().is_some_and(|f| f.typing)
+}
+
+/// Wake `ctx` when a text field starts wanting the keyboard. Only the
+/// first call starts a thread.
+pub fn watch(ctx: &Context) {
+    static WATCHING: AtomicBool = AtomicBool::new(false);
+    if WATCHING.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    let ctx = ctx.clone();
+    let _ = std::thread::Builder::new()
+        .name("slowdesktop-keyboard".into())
+        .spawn(move || {
+            let mut was_wanted = false;
+            loop {
+                std::thread::sleep(CHECK_INTERVAL);
+                let wanted = wanted();
+                if wanted && !was_wanted {
+                    ctx.request_repaint();
+                }
+                was_wanted = wanted;
+            }
+        });
+}
@@ -0,0 +1,210 @@
+//! Per-app launch profiles
+//!
+//! How each app is started: working directory, extra environment, nice
+//! level and (on the Pi) a memory limit, so heavy apps can't starve the
+//! desktop. Profiles come from ~/.config/slowdesktop/launch.toml:
+//!
+//! ```toml
+//! [default]
+//! nice = 0
+//!
+//! [apps.slowmusic]
+//! nice = 5
+//! memory_limit_mb = 256
+//! working_dir = "~/Music"
+//! env = { SLOWOS_PERF_OVERLAY = "0" }
+//! ```
+//!
+//! Settings in `[apps.<binary>]` override `[default]`. Without a manifest
+//! the built-in one below applies. Memory limits use a cgroup v2 group
+//! per process under /sys/fs/cgroup/slowos/, and only on the Slowbook.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use slowcore::log;
+use slowcore::storage::config_dir;
+use toml_edit::{Document, Item, TableLike};
+
+/// Used when there is no launch.toml: media and document viewers run a
+/// little below the desktop so it stays responsive while they decode
+const DEFAULT_MANIFEST: &str = r#"
+[apps.slowreader]
+nice = 5
+
+[apps.slowview]
+nice = 5
+
+[apps.slowmusic]
+nice = 5
+
+[apps.slowmidi]
+nice = 5
+"#;
+
+/// Parent cgroup for app memory limits
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaunchProfile {
+    pub working_dir: Option<PathBuf>,
+    /// Set after the process manager's own variables, so they can be overridden
+    pub env: Vec<(String, String)>,
+    /// 0 = normal priority, up to 19 = lowest
+    pub nice: i32,
+    pub memory_limit_mb: Option<u64>,
+}
+
+impl LaunchProfile {
+    /// Apply the settings present in a manifest table over these ones
+    fn merge(&mut self, table: &dyn TableLike) {
+        if let Some(dir) = table.get("working_dir").and_then(Item::as_str) {
+            self.working_dir = Some(expand_home(dir));
+        }
+        if let Some(nice) = table.get("nice").and_then(Item::as_integer) {
+            self.nice = nice.clamp(0, 19) as i32;
+        }
+        if let Some(mb) = table.get("memory_limit_mb").and_then(Item::as_integer) {
+            self.memory_limit_mb = (mb > 0).then_some(mb as u64);
+        }
+        if let Some(env) = table.get("env").and_then(Item::as_table_like) {
+            for (key, value) in env.iter() {
+                if let Some(value) = value.as_str() {
+                    self.env.retain(|(k, _)| k != key);
+                    self.env.push((key.to_string(), value.to_string()));
+                }
+            }
+        }
+    }
+}
+
+/// All launch profiles from the manifest
+#[derive(Debug, Clone, Default)]
+pub struct LaunchProfiles {
+    default: LaunchProfile,
+    apps: HashMap<String, LaunchProfile>,
+}
+
+impl LaunchProfiles {
+    pub fn load() -> Self {
+        let path = config_dir("slowdesktop").join("launch.toml");
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).unwrap_or_else(|e| {
+                log::warn!("{}: {}", path.display(), e);
+                Self::builtin()
+            }),
+            Err(_) => Self::builtin(),
+        }
+    }
+
+    fn builtin() -> Self {
+        Self::parse(DEFAULT_MANIFEST).unwrap_or_default()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let doc: Document = text.parse().map_err(|e: toml_edit::TomlError| e.to_string())?;
+        let mut profiles = Self::default();
+        if let Some(default) = doc.get("default").and_then(Item::as_table_like) {
+            profiles.default.merge(default);
+        }
+        if let Some(apps) = doc.get("apps").and_then(Item::as_table_like) {
+            for (binary, table) in apps.iter() {
+                if let Some(table) = table.as_table_like() {
+                    let mut profile = profiles.default.clone();
+                    profile.merge(table);
+                    profiles.apps.insert(binary.to_string(), profile);
+                }
+            }
+        }
+        Ok(profiles)
+    }
+
+    pub fn profile_for(&self, binary: &str) -> &LaunchProfile {
+        self.apps.get(binary).unwrap_or(&self.default)
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Whether the `nice` wrapper is available to lower priority with
+pub fn nice_binary() -> Option<&'static Path> {
+    ["/usr/bin/nice", "/bin/nice"].into_iter().map(Path::new).find(|p| p.exists())
+}
+
+/// Put a just-started process in its own memory-limited cgroup.
+/// Only on the Slowbook with cgroup v2; failures are logged and ignored.
+pub fn apply_memory_limit(key: &str, pid: u32, limit_mb: u64) {
+    if !slowcore::safety::is_embedded() {
+        return;
+    }
+    let root = Path::new(CGROUP_ROOT);
+    let has_memory = std::fs::read_to_string(root.join("cgroup.controllers"))
+        .map(|c| c.split_whitespace().any(|c| c == "memory"))
+        .unwrap_or(false);
+    if !has_memory {
+        return;
+    }
+    let parent = root.join("slowos");
+    let group = parent.join(format!("{}_{}", key, pid));
+    let result = (|| -> std::io::Result<()> {
+        std::fs::create_dir_all(&parent)?;
+        // Hand the memory controller down to our groups; the root may
+        // already have it enabled, so an error there isn't fatal
+        let _ = std::fs::write(root.join("cgroup.subtree_control"), "+memory");
+        std::fs::write(parent.join("cgroup.subtree_control"), "+memory")?;
+        std::fs::create_dir_all(&group)?;
+        std::fs::write(group.join("memory.max"), (limit_mb * 1024 * 1024).to_string())?;
+        std::fs::write(group.join("cgroup.procs"), pid.to_string())
+    })();
+    if let Err(e) = result {
+        log::warn!("memory limit for {}: {}", key, e);
+    }
+}
+
+/// Remove a process's cgroup once it has exited
+pub fn release_memory_limit(key: &str, pid: u32) {
+    let group = Path::new(CGROUP_ROOT).join("slowos").join(format!("{}_{}", key, pid));
+    if group.exists() {
+        let _ = std::fs::remove_dir(group);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_overrides_default() {
+        let profiles = LaunchProfiles::parse(
+            r#"
+            [default]
+            nice = 2
+            env = { SLOWOS_PERF_OVERLAY = "1" }
+
+            [apps.slowmusic]
+            nice = 40
+            memory_limit_mb = 256
+            env = { SLOWOS_PERF_OVERLAY = "0" }
+            "#,
+        )
+        .unwrap();
+
+        let music = profiles.profile_for("slowmusic");
+        assert_eq!(music.nice, 19);
+        assert_eq!(music.memory_limit_mb, Some(256));
+        assert_eq!(music.env, vec![("SLOWOS_PERF_OVERLAY".to_string(), "0".to_string())]);
+
+        let write = profiles.profile_for("slowwrite");
+        assert_eq!(write.nice, 2);
+        assert_eq!(write.memory_limit_mb, None);
+    }
+
+    #[test]
+    fn test_builtin_manifest() {
+        assert_eq!(LaunchProfiles::builtin().profile_for("slowview").nice, 5);
+    }
+}
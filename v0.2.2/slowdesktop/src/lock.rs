@@ -0,0 +1,174 @@
+//! Login and lock screen
+//!
+//! Shown only once accounts exist (Settings → accounts). At boot it asks
+//! who's there; ⌘L, the slowOS menu or a while of nobody using any app
+//! (`slowcore::session::idle_for`) locks the screen until the password is
+//! given again.
+
+use egui::{Align2, Context, FontId, Key, Pos2, Rect, Vec2};
+use slowcore::accounts::{Account, Accounts};
+use slowcore::theme::SlowColors;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// After a wrong password the field waits this long
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// How often the idle watcher looks
+const IDLE_CHECK: Duration = Duration::from_secs(15);
+
+enum LockKind {
+    /// Nobody logged in yet: pick an account
+    Login,
+    /// The session's own account, to unlock
+    Locked(String),
+}
+
+pub struct LockScreen {
+    kind: LockKind,
+    /// Account being logged in to (login screen)
+    chosen: Option<String>,
+    password: String,
+    message: String,
+    wrong_at: Option<Instant>,
+}
+
+/// What the user did on the lock screen
+pub enum LockResult {
+    Unlocked,
+    LoggedIn(Account),
+}
+
+impl LockScreen {
+    pub fn login() -> Self {
+        Self::new(LockKind::Login)
+    }
+
+    pub fn locked(name: &str) -> Self {
+        Self::new(LockKind::Locked(name.to_string()))
+    }
+
+    fn new(kind: LockKind) -> Self {
+        Self { kind, chosen: None, password: String::new(), message: String::new(), wrong_at: None }
+    }
+
+    /// Draw the screen. Returns what happened once the right password is in.
+    pub fn show(&mut self, ctx: &Context, accounts: &Accounts) -> Option<LockResult> {
+        let waiting = self.wrong_at.is_some_and(|t| t.elapsed() < RETRY_DELAY);
+        if waiting {
+            ctx.request_repaint_after(RETRY_DELAY);
+        }
+        let mut result = None;
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE))
+            .show(ctx, |ui| {
+                let center = ui.max_rect().center();
+                ui.painter().text(
+                    center - Vec2::new(0.0, 90.0),
+                    Align2::CENTER_CENTER,
+                    "slowOS",
+                    FontId::proportional(22.0),
+                    SlowColors::BLACK,
+                );
+                let area = Rect::from_center_size(center + Vec2::new(0.0, 20.0), Vec2::new(240.0, 180.0));
+                ui.allocate_ui_at_rect(area, |ui| {
+                    ui.vertical_centered(|ui| {
+                        let name = match &self.kind {
+                            LockKind::Locked(name) => Some(name.clone()),
+                            LockKind::Login => self.chosen.clone(),
+                        };
+                        let Some(account) = name.and_then(|n| accounts.find(&n)).cloned() else {
+                            // Login: who's there?
+                            ui.label("choose your account:");
+                            ui.add_space(6.0);
+                            for account in &accounts.users {
+                                if ui.add_sized([160.0, 24.0], egui::Button::new(&account.name)).clicked() {
+                                    if account.has_password() {
+                                        self.chosen = Some(account.name.clone());
+                                    } else {
+                                        result = Some(LockResult::LoggedIn(account.clone()));
+                                    }
+                                }
+                            }
+                            return;
+                        };
+                        match self.kind {
+                            LockKind::Locked(_) => ui.label(format!("locked by {}", account.name)),
+                            LockKind::Login => ui.label(format!("password for {}:", account.name)),
+                        };
+                        ui.add_space(6.0);
+                        let field = ui.add_enabled(
+                            !waiting,
+                            egui::TextEdit::singleline(&mut self.password).password(true).desired_width(160.0),
+                        );
+                        if !waiting {
+                            field.request_focus();
+                        }
+                        let entered = field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                        ui.add_space(6.0);
+                        let label = if matches!(self.kind, LockKind::Locked(_)) { "unlock" } else { "log in" };
+                        let pressed = ui.add_enabled(!waiting, egui::Button::new(label)).clicked();
+                        if (entered || pressed) && !waiting {
+                            if account.check_password(&self.password) {
+                                result = Some(match self.kind {
+                                    LockKind::Locked(_) => LockResult::Unlocked,
+                                    LockKind::Login => LockResult::LoggedIn(account.clone()),
+                                });
+                            } else {
+                                self.message = "wrong password".to_string();
+                                self.wrong_at = Some(Instant::now());
+                            }
+                            self.password.clear();
+                        }
+                        if matches!(self.kind, LockKind::Login) && ui.button("other account").clicked() {
+                            self.chosen = None;
+                            self.password.clear();
+                            self.message.clear();
+                        }
+                        if !self.message.is_empty() {
+                            ui.add_space(6.0);
+                            ui.label(&self.message);
+                        }
+                    });
+                });
+                ui.painter().text(
+                    Pos2::new(center.x, ui.max_rect().max.y - 20.0),
+                    Align2::CENTER_CENTER,
+                    chrono::Local::now().format("%H:%M  %A, %B %d").to_string(),
+                    FontId::proportional(12.0),
+                    SlowColors::BLACK,
+                );
+            });
+        result
+    }
+}
+
+/// Whether the screen should lock for being idle
+pub fn idle_too_long(accounts: &Accounts) -> bool {
+    accounts.enabled()
+        && accounts.auto_lock_minutes > 0
+        && slowcore::session::idle_for() >= Duration::from_secs(accounts.auto_lock_minutes as u64 * 60)
+}
+
+/// Wake `ctx` when nobody has used any app for the auto-lock time. Only the
+/// first call starts a thread.
+pub fn watch_idle(ctx: &Context) {
+    static WATCHING: AtomicBool = AtomicBool::new(false);
+    if WATCHING.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    let ctx = ctx.clone();
+    let _ = std::thread::Builder::new()
+        .name("slowdesktop-idle".into())
+        .spawn(move || {
+            let mut was_idle = false;
+            loop {
+                std::thread::sleep(IDLE_CHECK);
+                let idle = idle_too_long(&Accounts::load());
+                if idle && !was_idle {
+                    ctx.request_repaint();
+                }
+                was_idle = idle;
+            }
+        });
+}
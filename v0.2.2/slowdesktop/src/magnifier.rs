@@ -0,0 +1,223 @@
+//! Screen magnifier lens
+//!
+//! When turned on (slowOS menu), holding ⌘ shows a floating lens next to
+//! the pointer with a 2x–4x enlarged copy of the pixels under it.
+//!
+//! The lens works from real screenshots of the desktop window
+//! (`ViewportCommand::Screenshot`), cropped around the pointer and drawn
+//! with nearest-neighbour sampling, so every dither dot stays a crisp
+//! square. A new screenshot is only taken when the pointer moves, which
+//! keeps e-ink refreshes down.
+
+use egui::{Color32, ColorImage, Context, Pos2, Rect, Stroke, TextureHandle, TextureOptions, Vec2};
+use serde::{Deserialize, Serialize};
+use slowcore::dither;
+use slowcore::storage::config_dir;
+use slowcore::theme::SlowColors;
+use std::sync::Arc;
+
+/// Size of the lens on screen, in points
+const LENS_SIZE: f32 = 160.0;
+
+/// Gap between the pointer and the lens. Larger than the captured area's
+/// half-width at 2x, so the lens never shows up in its own screenshot.
+const LENS_OFFSET: f32 = 48.0;
+
+pub const MIN_ZOOM: u8 = 2;
+pub const MAX_ZOOM: u8 = 4;
+
+/// Saved magnifier preferences
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MagnifierSettings {
+    pub enabled: bool,
+    /// Magnification, MIN_ZOOM..=MAX_ZOOM
+    pub zoom: u8,
+}
+
+impl Default for MagnifierSettings {
+    fn default() -> Self {
+        Self { enabled: false, zoom: 2 }
+    }
+}
+
+impl MagnifierSettings {
+    pub fn load() -> Self {
+        let path = config_dir("slowdesktop").join("magnifier.json");
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = config_dir("slowdesktop").join("magnifier.json");
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+}
+
+pub struct Magnifier {
+    pub settings: MagnifierSettings,
+    /// Latest screenshot of the desktop window
+    screenshot: Option<Arc<ColorImage>>,
+    /// Pointer position the current screenshot was requested for
+    captured_at: Option<Pos2>,
+    /// A screenshot has been requested and not yet delivered
+    waiting: bool,
+    texture: Option<TextureHandle>,
+    /// Pointer position and zoom the texture was cropped for
+    texture_key: Option<(Pos2, u8)>,
+}
+
+impl Magnifier {
+    pub fn new() -> Self {
+        Self {
+            settings: MagnifierSettings::load(),
+            screenshot: None,
+            captured_at: None,
+            waiting: false,
+            texture: None,
+            texture_key: None,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.settings.enabled = enabled;
+        self.settings.save();
+        if !enabled {
+            self.release();
+        }
+    }
+
+    pub fn set_zoom(&mut self, zoom: u8) {
+        self.settings.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.settings.save();
+        self.texture_key = None;
+    }
+
+    /// Drop the screenshot and texture while the lens is hidden
+    fn release(&mut self) {
+        self.screenshot = None;
+        self.captured_at = None;
+        self.texture = None;
+        self.texture_key = None;
+    }
+
+    /// Call once per frame after the desktop has been drawn.
+    pub fn update(&mut self, ctx: &Context) {
+        // Collect screenshot replies even if the lens was hidden meanwhile
+        let shot = ctx.input(|i| {
+            i.events.iter().rev().find_map(|e| match e {
+                egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            })
+        });
+        if let Some(image) = shot {
+            self.screenshot = Some(image);
+            self.waiting = false;
+            self.texture_key = None;
+        }
+
+        let (held, pointer) = ctx.input(|i| (i.modifiers.command, i.pointer.hover_pos()));
+        let pointer = match pointer {
+            Some(p) if self.settings.enabled && held => p,
+            _ => {
+                if self.texture.is_some() {
+                    self.release();
+                }
+                return;
+            }
+        };
+
+        // Re-capture when the pointer moves to new pixels
+        if !self.waiting && !matches!(self.captured_at, Some(c) if c.distance(pointer) < 1.0) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+            self.captured_at = Some(pointer);
+            self.waiting = true;
+        }
+        if self.waiting {
+            // The reply arrives as an input event on the next frame
+            ctx.request_repaint();
+        }
+
+        self.update_texture(ctx, pointer);
+        if let Some(tex) = &self.texture {
+            Self::paint_lens(ctx, tex, pointer, self.settings.zoom);
+        }
+    }
+
+    fn update_texture(&mut self, ctx: &Context, pointer: Pos2) {
+        let zoom = self.settings.zoom;
+        if self.texture_key == Some((pointer, zoom)) {
+            return;
+        }
+        let Some(shot) = &self.screenshot else { return };
+        let ppp = ctx.pixels_per_point();
+        let src = ((LENS_SIZE * ppp) / zoom as f32).round().max(1.0) as usize;
+        let crop = crop_around(shot, (pointer.x * ppp) as i64, (pointer.y * ppp) as i64, src);
+        match &mut self.texture {
+            Some(tex) => tex.set(crop, TextureOptions::NEAREST),
+            None => self.texture = Some(ctx.load_texture("slowdesktop_magnifier", crop, TextureOptions::NEAREST)),
+        }
+        self.texture_key = Some((pointer, zoom));
+    }
+
+    fn paint_lens(ctx: &Context, tex: &TextureHandle, pointer: Pos2, zoom: u8) {
+        let screen = ctx.screen_rect();
+        let size = Vec2::splat(LENS_SIZE);
+        // Below-right of the pointer, flipped to stay on screen
+        let mut min = pointer + Vec2::splat(LENS_OFFSET);
+        if min.x + size.x > screen.max.x {
+            min.x = pointer.x - LENS_OFFSET - size.x;
+        }
+        if min.y + size.y > screen.max.y {
+            min.y = pointer.y - LENS_OFFSET - size.y;
+        }
+        let rect = Rect::from_min_size(min, size);
+
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("slowdesktop_magnifier")));
+        dither::draw_dither_rect(&painter, rect.translate(Vec2::splat(3.0)), SlowColors::BLACK, 2);
+        painter.image(
+            tex.id(),
+            rect,
+            Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+            Color32::WHITE,
+        );
+        painter.rect_stroke(rect, 0.0, Stroke::new(2.0, SlowColors::BLACK));
+
+        // Zoom tag in the corner
+        let galley = painter.layout_no_wrap(format!("{}x", zoom), egui::FontId::proportional(10.0), SlowColors::BLACK);
+        let tag = Rect::from_min_size(rect.min + Vec2::splat(2.0), galley.size() + Vec2::new(6.0, 2.0));
+        painter.rect_filled(tag, 0.0, SlowColors::WHITE);
+        painter.rect_stroke(tag, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+        painter.galley(tag.min + Vec2::new(3.0, 1.0), galley, SlowColors::BLACK);
+    }
+}
+
+/// Copy a `size`×`size` pixel square centred on (`cx`, `cy`), shifted to
+/// stay inside the image. Parts outside a too-small image are white.
+fn crop_around(image: &ColorImage, cx: i64, cy: i64, size: usize) -> ColorImage {
+    let [w, h] = image.size;
+    let half = (size / 2) as i64;
+    let x0 = (cx - half).clamp(0, (w as i64 - size as i64).max(0));
+    let y0 = (cy - half).clamp(0, (h as i64 - size as i64).max(0));
+    let mut out = ColorImage::new([size, size], SlowColors::WHITE);
+    for y in 0..size {
+        let sy = y0 as usize + y;
+        if sy >= h {
+            break;
+        }
+        for x in 0..size {
+            let sx = x0 as usize + x;
+            if sx >= w {
+                break;
+            }
+            out.pixels[y * size + x] = image.pixels[sy * w + sx];
+        }
+    }
+    out
+}
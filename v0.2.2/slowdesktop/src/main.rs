@@ -6,10 +6,16 @@
 //! This is the first thing that runs when the Slowbook boots.
 
 mod desktop;
+mod keyboard;
+mod launch_profile;
+mod lock;
+mod magnifier;
 mod process_manager;
+mod splash;
 
 use desktop::DesktopApp;
 use eframe::NativeOptions;
+use slowcore::log;
 use std::io::Write;
 
 /// Maximum number of restart attempts before giving up
@@ -19,6 +25,8 @@ const MAX_RESTART_ATTEMPTS: u32 = 5;
 const RESTART_DELAY_SECS: u64 = 2;
 
 fn main() {
+    log::init("slowdesktop");
+
     // Install panic hook that logs instead of crashing
     setup_panic_handler();
 
@@ -38,26 +46,36 @@ fn setup_panic_handler() {
             .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
             .unwrap_or_else(|| "unknown location".to_string());
 
-        eprintln!("[slowdesktop] PANIC at {}: {}", location, msg);
-
-        // Write to log file for post-mortem analysis
-        if let Ok(mut file) = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("/tmp/slowos-crash.log")
-        {
-            let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
-            let _ = writeln!(file, "[{}] PANIC at {}: {}", timestamp, location, msg);
-
-            // Also log backtrace if available
-            let backtrace = std::backtrace::Backtrace::capture();
-            if backtrace.status() == std::backtrace::BacktraceStatus::Captured {
-                let _ = writeln!(file, "Backtrace:\n{}", backtrace);
-            }
-        }
+        log::error!("PANIC at {}: {}", location, msg);
+
+        // Write to the crash log for post-mortem analysis, with the
+        // backtrace if available
+        let backtrace = std::backtrace::Backtrace::capture();
+        let details = match backtrace.status() {
+            std::backtrace::BacktraceStatus::Captured => format!("Backtrace:\n{}", backtrace),
+            _ => String::new(),
+        };
+        record_crash(&format!("slowdesktop PANIC at {}: {}", location, msg), &details);
     }));
 }
 
+/// Add an entry to the crash log; the desktop reports it on its next start
+pub(crate) fn record_crash(summary: &str, details: &str) {
+    let path = slowcore::logs::crash_log();
+    slowcore::logs::rotate_if_full(&path);
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+    {
+        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        let _ = writeln!(file, "[{}] {}", timestamp, summary);
+        for line in details.lines() {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
 /// Run the desktop shell with automatic restart on failure
 fn run_desktop_loop() {
     let mut restart_count = 0u32;
@@ -68,27 +86,27 @@ fn run_desktop_loop() {
         match result {
             Ok(Ok(())) => {
                 // Clean exit requested
-                eprintln!("[slowdesktop] clean shutdown");
+                log::info!("clean shutdown");
                 break;
             }
             Ok(Err(e)) => {
-                eprintln!("[slowdesktop] eframe error: {}", e);
+                log::error!("eframe error: {}", e);
                 restart_count += 1;
             }
             Err(_) => {
-                eprintln!("[slowdesktop] caught panic, attempting recovery...");
+                log::error!("caught panic, attempting recovery...");
                 restart_count += 1;
             }
         }
 
         // Check if we should restart
         if !should_restart(restart_count) {
-            eprintln!("[slowdesktop] too many failures, giving up");
+            log::error!("too many failures, giving up");
             break;
         }
 
-        eprintln!(
-            "[slowdesktop] restarting in {} seconds (attempt {}/{})",
+        log::warn!(
+            "restarting in {} seconds (attempt {}/{})",
             RESTART_DELAY_SECS, restart_count, MAX_RESTART_ATTEMPTS
         );
         std::thread::sleep(std::time::Duration::from_secs(RESTART_DELAY_SECS));
@@ -130,7 +148,5 @@ fn run_desktop() -> Result<(), eframe::Error> {
 
 /// Detect if we're running on the actual Slowbook hardware
 fn is_embedded() -> bool {
-    // Check for Raspberry Pi or our custom device tree
-    std::path::Path::new("/proc/device-tree/model").exists()
-        || std::env::var("SLOWOS_EMBEDDED").is_ok()
+    slowcore::safety::is_embedded()
 }
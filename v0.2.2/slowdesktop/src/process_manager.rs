@@ -3,9 +3,16 @@
 //! Manages child processes for each app. Tracks running state,
 //! handles clean shutdown, and provides robust error handling.
 
+use crate::launch_profile::{self, LaunchProfiles};
+use chrono::Timelike;
+use slowcore::app_registry;
+use slowcore::log;
+use slowcore::restrictions::{self, Restrictions};
+use slowcore::running::RunningApp;
 use std::collections::HashMap;
+use std::os::unix::process::ExitStatusExt;
 use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
+use std::process::{Child, Command, ExitStatus, Stdio};
 use std::time::{Duration, Instant};
 
 /// Information about a SlowOS application
@@ -28,6 +35,17 @@ pub struct AppInfo {
 struct ProcessState {
     child: Child,
     started_at: Instant,
+    /// Set when the process was placed in a memory-limited cgroup
+    memory_limited: bool,
+}
+
+impl ProcessState {
+    /// Clean up after a process that has exited or been killed
+    fn release(&self, key: &str) {
+        if self.memory_limited {
+            launch_profile::release_memory_limit(key, self.child.id());
+        }
+    }
 }
 
 /// Apps that allow multiple simultaneous instances
@@ -52,8 +70,14 @@ pub struct ProcessManager {
     failed_launches: HashMap<String, String>,
     /// Counter for multi-instance apps
     instance_counter: HashMap<String, u32>,
-    /// Cascade offset for window staggering (cycles 0-9)
-    cascade_offset: u32,
+    /// Per-app working directory, environment, priority and memory limit
+    profiles: LaunchProfiles,
+    /// Background services without a window, keyed by binary name
+    services: HashMap<String, Child>,
+    /// Hidden and time-limited apps, from settings
+    restrictions: Restrictions,
+    /// Restrictions are lifted until then, after the passcode was given
+    override_until: Option<Instant>,
 }
 
 impl ProcessManager {
@@ -64,7 +88,10 @@ impl ProcessManager {
             bin_paths: Self::build_bin_paths(),
             failed_launches: HashMap::new(),
             instance_counter: HashMap::new(),
-            cascade_offset: 0,
+            profiles: LaunchProfiles::load(),
+            services: HashMap::new(),
+            restrictions: Restrictions::load(),
+            override_until: None,
         };
         pm.register_apps();
         pm
@@ -79,6 +106,9 @@ impl ProcessManager {
     fn build_bin_paths() -> Vec<PathBuf> {
         let mut paths = Vec::new();
 
+        // 0. Updates swapped in by slowcore::update come before everything else
+        paths.push(slowcore::update::bin_dir());
+
         // 1. Same directory as current executable (most reliable for development)
         if let Ok(exe) = std::env::current_exe() {
             if let Some(dir) = exe.parent() {
@@ -113,46 +143,89 @@ impl ProcessManager {
         paths.push(PathBuf::from("./target/release"));
         paths.push(PathBuf::from("./target/debug"));
 
+        // 6. Apps installed with slowStore
+        paths.push(app_registry::applications_dir());
+
         paths
     }
 
     fn register_apps(&mut self) {
-        // (binary, display_name, description, icon_label)
-        const APP_DEFS: &[(&str, &str, &str, &str)] = &[
-            ("slowwrite",     "slowWrite",  "word processor",      "W"),
-            ("slowpaint",     "slowPaint",  "bitmap editor",       "P"),
-            ("slowdesign",    "slowDesign", "document design",     "D"),
-            ("slowreader",    "slowReader", "ebook reader",        "R"),
-            ("slownotes",     "slowNotes",  "notes",               "N"),
-            ("slowchess",     "chess",      "chess",               "c"),
-            ("slowfiles",     "slowFiles",  "file manager",        "F"),
-            ("slowmusic",     "slowMusic",  "music player",        "M"),
-            ("slowclock",     "slowClock",  "clock",               "⏱"),
-            ("trash",         "trash",      "trash bin",           "X"),
-            ("slowterm",      "terminal",   "terminal emulator",   ">"),
-            ("slowview",      "slowView",   "image & PDF viewer",  "V"),
-            ("credits",       "credits",    "open source credits", "C"),
-            ("slowmidi",      "slowMidi",   "MIDI sequencer",      "m"),
-            ("slowbreath",    "slowBreath", "breathing timer",     "~"),
-            ("settings",      "settings",   "system settings",     "*"),
-            ("slowcalc",      "calculator", "calculator",          "="),
-            ("slowsolitaire", "solitaire",  "solitaire",           "\u{2660}"),
-        ];
-
-        self.apps = APP_DEFS.iter().map(|&(bin, name, desc, icon)| AppInfo {
-            binary: bin.into(),
-            display_name: name.into(),
-            description: desc.into(),
-            icon_label: icon.into(),
+        self.apps = app_registry::all().into_iter().map(|a| AppInfo {
+            binary: a.binary,
+            display_name: a.name,
+            description: a.description,
+            icon_label: a.icon_label,
             running: false,
         }).collect();
     }
 
+    /// Pick up apps installed or removed with slowStore. Returns whether
+    /// the list of apps changed.
+    pub fn refresh_installed_apps(&mut self) -> bool {
+        let before: Vec<(String, bool)> = self.apps.iter().map(|a| (a.binary.clone(), a.running)).collect();
+        self.register_apps();
+        for app in &mut self.apps {
+            app.running = before.iter().any(|(binary, running)| *binary == app.binary && *running);
+        }
+        !self.apps.iter().map(|a| &a.binary).eq(before.iter().map(|(binary, _)| binary))
+    }
+
     /// Get all registered apps
     pub fn apps(&self) -> &[AppInfo] {
         &self.apps
     }
 
+    /// Reload restrictions and let an override run out. Returns whether
+    /// which apps are hidden may have changed.
+    pub fn refresh_restrictions(&mut self) -> bool {
+        let mut changed = false;
+        if self.override_until.is_some_and(|t| Instant::now() >= t) {
+            self.override_until = None;
+            changed = true;
+        }
+        let restrictions = Restrictions::load();
+        if restrictions != self.restrictions {
+            self.restrictions = restrictions;
+            changed = true;
+        }
+        changed
+    }
+
+    fn overridden(&self) -> bool {
+        self.override_until.is_some_and(|t| Instant::now() < t)
+    }
+
+    /// Left off the desktop and out of search
+    pub fn is_hidden(&self, binary: &str) -> bool {
+        !self.overridden() && self.restrictions.hidden(binary)
+    }
+
+    /// Not allowed to start now: hidden, or outside its hours
+    pub fn is_blocked(&self, binary: &str) -> bool {
+        let now = chrono::Local::now();
+        let minute = (now.hour() * 60 + now.minute()) as u16;
+        !self.overridden() && (self.restrictions.hidden(binary) || !self.restrictions.allows(binary, minute))
+    }
+
+    /// When a time-limited app is allowed, for telling the user
+    pub fn allowed_hours(&self, binary: &str) -> Option<String> {
+        let (from, until) = self.restrictions.rule(binary)?.hours?;
+        Some(format!("{}–{}", restrictions::format_minute(from), restrictions::format_minute(until)))
+    }
+
+    pub fn has_passcode(&self) -> bool {
+        self.restrictions.has_passcode()
+    }
+
+    /// Lift every restriction for a while if `passcode` is right
+    pub fn override_restrictions(&mut self, passcode: &str) -> bool {
+        if !self.restrictions.check_passcode(passcode) {
+            return false;
+        }
+        self.override_until = Some(Instant::now() + restrictions::OVERRIDE_TIME);
+        true
+    }
+
     /// Check if a binary exists and is executable
     pub fn binary_exists(&self, binary: &str) -> bool {
         self.find_binary(binary).is_some()
@@ -190,6 +263,28 @@ impl ProcessManager {
         None
     }
 
+    /// Start a background service (slowsync, scheduled backups) unless it is
+    /// already running.
+    /// Services don't show up as running apps and are stopped with the desktop.
+    pub fn start_service(&mut self, binary: &str, args: &[&str]) -> Result<(), String> {
+        if let Some(child) = self.services.get_mut(binary) {
+            if matches!(child.try_wait(), Ok(None)) {
+                return Ok(());
+            }
+        }
+        let bin_path = self.find_binary(binary).ok_or_else(|| format!("'{}' not found", binary))?;
+        let child = Command::new(&bin_path)
+            .args(args)
+            .env("SLOWOS_MANAGED", "1")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(log_output(binary).1)
+            .spawn()
+            .map_err(|e| format!("failed to start: {}", e))?;
+        self.services.insert(binary.to_string(), child);
+        Ok(())
+    }
+
     /// Launch an application with extra arguments.
     pub fn launch_with_args(&mut self, binary: &str, args: &[&str]) -> Result<bool, String> {
         self.launch_inner(binary, args)
@@ -205,6 +300,10 @@ impl ProcessManager {
         // Clear any previous failure
         self.failed_launches.remove(binary);
 
+        if self.is_blocked(binary) {
+            return Err(format!("{} isn't allowed right now", binary));
+        }
+
         let multi_instance = Self::allows_multi_instance(binary);
 
         // For single-instance apps, check if already running
@@ -213,8 +312,7 @@ impl ProcessManager {
                 match state.child.try_wait() {
                     Ok(Some(_status)) => {
                         // Process exited, remove it and allow relaunch
-                        self.children.remove(binary);
-                        self.update_running_status(binary, false);
+                        self.forget(binary);
                     }
                     Ok(None) => {
                         // Still running - bring window to front
@@ -223,14 +321,18 @@ impl ProcessManager {
                     }
                     Err(e) => {
                         // Error checking status, remove stale entry
-                        eprintln!("[slowdesktop] error checking {}: {}", binary, e);
-                        self.children.remove(binary);
-                        self.update_running_status(binary, false);
+                        log::warn!("error checking {}: {}", binary, e);
+                        self.forget(binary);
                     }
                 }
             }
         }
 
+        // An update downloaded in settings goes in now, before the app starts
+        if let Some(version) = slowcore::update::apply_staged(binary) {
+            log::info!("{} updated to {}", binary, version);
+        }
+
         // Find the binary
         let bin_path = self.find_binary(binary).ok_or_else(|| {
             let err = format!("'{}' not found", binary);
@@ -238,17 +340,28 @@ impl ProcessManager {
             err
         })?;
 
-        // Calculate cascade offset (cycles 0-9, 30 pixels per step)
-        let cascade = self.cascade_offset;
-        self.cascade_offset = (self.cascade_offset + 1) % 10;
+        let profile = self.profiles.profile_for(binary).clone();
 
-        // Launch the process with proper stdio handling
-        let mut cmd = Command::new(&bin_path);
+        // Lower priority through `nice` when the profile asks for it
+        let mut cmd = match launch_profile::nice_binary() {
+            Some(nice) if profile.nice > 0 => {
+                let mut cmd = Command::new(nice);
+                cmd.arg("-n").arg(profile.nice.to_string()).arg(&bin_path);
+                cmd
+            }
+            _ => Command::new(&bin_path),
+        };
+
+        // Launch the process with its output going to its log
+        let (stdout, stderr) = log_output(binary);
         cmd.env("SLOWOS_MANAGED", "1")
-            .env("SLOWOS_CASCADE", cascade.to_string())
+            .envs(profile.env.iter().map(|(k, v)| (k, v)))
             .stdin(Stdio::null())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit());
+            .stdout(stdout)
+            .stderr(stderr);
+        if let Some(dir) = profile.working_dir.as_ref().filter(|d| d.is_dir()) {
+            cmd.current_dir(dir);
+        }
         if !args.is_empty() {
             cmd.args(args);
         }
@@ -264,11 +377,15 @@ impl ProcessManager {
                 } else {
                     binary.to_string()
                 };
+                if let Some(limit) = profile.memory_limit_mb {
+                    launch_profile::apply_memory_limit(&key, child.id(), limit);
+                }
                 self.children.insert(
                     key,
                     ProcessState {
                         child,
                         started_at: Instant::now(),
+                        memory_limited: profile.memory_limit_mb.is_some(),
                     },
                 );
                 self.update_running_status(binary, true);
@@ -282,6 +399,14 @@ impl ProcessManager {
         }
     }
 
+    /// Drop a process that has exited and release its cgroup
+    fn forget(&mut self, key: &str) {
+        if let Some(state) = self.children.remove(key) {
+            state.release(key);
+        }
+        self.update_running_status(key, false);
+    }
+
     /// Update the running status for an app
     fn update_running_status(&mut self, binary: &str, running: bool) {
         if let Some(app) = self.apps.iter_mut().find(|a| a.binary == binary) {
@@ -332,20 +457,23 @@ impl ProcessManager {
                     Ok(Some(status)) => {
                         if !status.success() {
                             let runtime = state.started_at.elapsed();
-                            eprintln!(
-                                "[slowdesktop] {} exited with {} after {:.1}s",
+                            log::warn!(
+                                "{} exited with {} after {:.1}s",
                                 binary,
                                 status,
                                 runtime.as_secs_f32()
                             );
                         }
+                        if crashed(&status) {
+                            record_app_crash(&binary, &status);
+                        }
                         exited.push(binary.clone());
                     }
                     Ok(None) => {
                         // Still running
                     }
                     Err(e) => {
-                        eprintln!("[slowdesktop] error polling {}: {}", binary, e);
+                        log::warn!("error polling {}: {}", binary, e);
                         exited.push(binary.clone());
                     }
                 }
@@ -354,8 +482,7 @@ impl ProcessManager {
 
         // Clean up exited processes
         for binary in &exited {
-            self.children.remove(binary);
-            self.update_running_status(binary, false);
+            self.forget(binary);
         }
 
         exited
@@ -369,7 +496,7 @@ impl ProcessManager {
             if let Some(mut state) = self.children.remove(binary) {
                 // Send termination signal
                 if let Err(e) = state.child.kill() {
-                    eprintln!("[slowdesktop] error killing {}: {}", binary, e);
+                    log::warn!("error killing {}: {}", binary, e);
                 }
 
                 // Wait with timeout
@@ -381,20 +508,28 @@ impl ProcessManager {
                         Ok(Some(_)) => break,
                         Ok(None) => {
                             if start.elapsed() > timeout {
-                                eprintln!("[slowdesktop] {} did not exit in time", binary);
+                                log::warn!("{} did not exit in time", binary);
                                 break;
                             }
                             std::thread::sleep(Duration::from_millis(50));
                         }
                         Err(e) => {
-                            eprintln!("[slowdesktop] error waiting for {}: {}", binary, e);
+                            log::warn!("error waiting for {}: {}", binary, e);
                             break;
                         }
                     }
                 }
+                state.release(binary);
             }
         }
 
+        for (binary, mut child) in self.services.drain() {
+            if let Err(e) = child.kill() {
+                log::warn!("error stopping {}: {}", binary, e);
+            }
+            let _ = child.wait();
+        }
+
         // Reset all running states
         for app in &mut self.apps {
             app.running = false;
@@ -406,6 +541,69 @@ impl ProcessManager {
         self.children.len()
     }
 
+    /// The app a child key belongs to
+    fn app_for_key(&self, key: &str) -> Option<&AppInfo> {
+        // Multi-instance keys are "binary_N"
+        self.apps.iter().find(|a| {
+            key == a.binary
+                || key.strip_prefix(a.binary.as_str()).is_some_and(|rest| rest.starts_with('_'))
+        })
+    }
+
+    /// Display names of running apps, one per instance
+    pub fn running_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .children
+            .keys()
+            .map(|key| {
+                self.app_for_key(key)
+                    .map(|a| a.display_name.clone())
+                    .unwrap_or_else(|| key.clone())
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Every running instance with its pid, for slowcore::running
+    pub fn running_apps(&self) -> Vec<RunningApp> {
+        let mut apps: Vec<RunningApp> = self
+            .children
+            .iter()
+            .map(|(key, state)| {
+                let app = self.app_for_key(key);
+                RunningApp {
+                    key: key.clone(),
+                    binary: app.map(|a| a.binary.clone()).unwrap_or_else(|| key.clone()),
+                    name: app.map(|a| a.display_name.clone()).unwrap_or_else(|| key.clone()),
+                    pid: state.child.id(),
+                }
+            })
+            .collect();
+        apps.sort_by(|a, b| a.key.cmp(&b.key));
+        apps
+    }
+
+    /// Force-quit the instance with this pid.
+    /// Returns its display name, or None if the pid isn't one of ours.
+    pub fn quit_pid(&mut self, pid: u32) -> Option<String> {
+        let key = self
+            .children
+            .iter()
+            .find(|(_, state)| state.child.id() == pid)
+            .map(|(key, _)| key.clone())?;
+        let name = self.app_for_key(&key).map(|a| a.display_name.clone()).unwrap_or_else(|| key.clone());
+        if let Some(state) = self.children.get_mut(&key) {
+            if let Err(e) = state.child.kill() {
+                log::warn!("error killing {}: {}", key, e);
+            }
+            // Reap it; after SIGKILL this returns promptly
+            let _ = state.child.wait();
+        }
+        self.forget(&key);
+        Some(name)
+    }
+
     /// Check if a specific app is running (with actual process state verification)
     /// For multi-instance apps, always returns false to allow launching additional instances
     pub fn is_running(&mut self, binary: &str) -> bool {
@@ -418,8 +616,7 @@ impl ProcessManager {
             match state.child.try_wait() {
                 Ok(Some(_status)) => {
                     // Process has exited - remove it
-                    self.children.remove(binary);
-                    self.update_running_status(binary, false);
+                    self.forget(binary);
                     false
                 }
                 Ok(None) => {
@@ -428,8 +625,7 @@ impl ProcessManager {
                 }
                 Err(_) => {
                     // Error checking - assume dead
-                    self.children.remove(binary);
-                    self.update_running_status(binary, false);
+                    self.forget(binary);
                     false
                 }
             }
@@ -440,6 +636,36 @@ impl ProcessManager {
 
 }
 
+/// Stdout and stderr for a child: its log, or the desktop's own output if
+/// the log can't be opened
+fn log_output(binary: &str) -> (Stdio, Stdio) {
+    match slowcore::logs::open_app_log(binary).and_then(|log| Some((log.try_clone().ok()?, log))) {
+        Some((stdout, stderr)) => (stdout.into(), stderr.into()),
+        None => (Stdio::inherit(), Stdio::inherit()),
+    }
+}
+
+/// Whether an app died of a panic or a fatal signal, rather than quitting
+/// or being stopped
+fn crashed(status: &ExitStatus) -> bool {
+    // 101 is what a Rust panic exits with; then SIGILL, SIGABRT, SIGBUS,
+    // SIGFPE and SIGSEGV
+    status.code() == Some(101) || matches!(status.signal(), Some(4 | 6 | 7 | 8 | 11))
+}
+
+/// Lines of an app's log kept with its crash report
+const CRASH_LOG_LINES: usize = 40;
+
+/// Add a crashed app to the crash log, with the end of its output
+fn record_app_crash(key: &str, status: &ExitStatus) {
+    // Extra instances are keyed "slowpaint_2"
+    let binary = key.split('_').next().unwrap_or(key);
+    let output = slowcore::logs::read_tail(&slowcore::logs::app_log(binary), 16 * 1024).unwrap_or_default();
+    let lines: Vec<&str> = output.lines().collect();
+    let tail = lines[lines.len().saturating_sub(CRASH_LOG_LINES)..].join("\n");
+    crate::record_crash(&format!("{} quit unexpectedly ({})", binary, status), &tail);
+}
+
 impl Default for ProcessManager {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,153 @@
+//! Boot splash and shutdown screen
+//!
+//! At boot the desktop shows the logo and a dithered progress bar while
+//! it opens the user's startup apps, one per frame. On shutdown the same
+//! screen waits for apps to save and quit (see `slowcore::session`),
+//! with a timeout after which the stragglers are terminated. Logging out
+//! waits the same way.
+
+use egui::{Align2, Context, FontId, Pos2, Rect, Stroke, TextureHandle, Vec2};
+use slowcore::animation::{Easing, FrameBudget};
+use slowcore::dither;
+use slowcore::safety::PowerAction;
+use slowcore::storage::config_dir;
+use slowcore::theme::SlowColors;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The splash stays up at least this long, so it doesn't just flash
+const MIN_SPLASH: Duration = Duration::from_millis(1500);
+
+/// How long apps get to save and quit before they are terminated
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Apps opened at boot, by binary name
+pub fn load_startup_apps() -> Vec<String> {
+    let path = config_dir("slowdesktop").join("startup.json");
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_startup_apps(apps: &[String]) {
+    let path = config_dir("slowdesktop").join("startup.json");
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(apps) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+pub struct BootSplash {
+    pending: VecDeque<String>,
+    total: usize,
+    started: Instant,
+    /// Frames drawn; nothing is launched before the splash is on screen
+    frames: u32,
+}
+
+impl BootSplash {
+    pub fn new() -> Self {
+        let pending: VecDeque<String> = load_startup_apps().into();
+        Self { total: pending.len(), pending, started: Instant::now(), frames: 0 }
+    }
+
+    /// The next startup app to open this frame, if any
+    pub fn next_launch(&mut self) -> Option<String> {
+        self.frames += 1;
+        if self.frames < 2 {
+            return None;
+        }
+        self.pending.pop_front()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty() && self.started.elapsed() >= MIN_SPLASH
+    }
+
+    /// Apps opened plus the minimum display time, as one 0.0–1.0 value
+    pub fn progress(&self) -> f32 {
+        let opened = (self.total - self.pending.len()) as f32;
+        let time = (self.started.elapsed().as_secs_f32() / MIN_SPLASH.as_secs_f32()).min(1.0);
+        let raw = (opened + time) / (self.total as f32 + 1.0);
+        Easing::Out.apply(FrameBudget::EINK.effective().step(raw, MIN_SPLASH))
+    }
+}
+
+/// How the session ends once apps have quit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionEnd {
+    Power(PowerAction),
+    /// Back to the login screen
+    LogOut,
+}
+
+/// A shutdown, restart or log out waiting for apps to quit
+pub struct Shutdown {
+    pub action: SessionEnd,
+    started: Instant,
+}
+
+impl Shutdown {
+    pub fn new(action: SessionEnd) -> Self {
+        Self { action, started: Instant::now() }
+    }
+
+    pub fn timed_out(&self) -> bool {
+        self.started.elapsed() >= SHUTDOWN_TIMEOUT
+    }
+
+    /// How much of the timeout has passed, 0.0–1.0
+    pub fn progress(&self) -> f32 {
+        FrameBudget::EINK.effective().step(self.started.elapsed().as_secs_f32() / SHUTDOWN_TIMEOUT.as_secs_f32(), SHUTDOWN_TIMEOUT)
+    }
+}
+
+/// Full-screen logo, title, detail line and dithered progress bar, with
+/// optional buttons underneath. Returns the index of a clicked button.
+pub fn draw_screen(
+    ctx: &Context,
+    logo: Option<&TextureHandle>,
+    title: &str,
+    detail: &str,
+    progress: f32,
+    buttons: &[&str],
+) -> Option<usize> {
+    let mut clicked = None;
+    egui::CentralPanel::default()
+        .frame(egui::Frame::none().fill(SlowColors::WHITE))
+        .show(ctx, |ui| {
+            let screen = ui.max_rect();
+            let painter = ui.painter();
+            let center = screen.center();
+
+            if let Some(tex) = logo {
+                let size = tex.size_vec2().min(Vec2::splat(96.0));
+                let rect = Rect::from_center_size(center - Vec2::new(0.0, 90.0), size);
+                painter.image(tex.id(), rect, Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)), SlowColors::WHITE);
+            }
+            painter.text(center - Vec2::new(0.0, 20.0), Align2::CENTER_CENTER, title, FontId::proportional(22.0), SlowColors::BLACK);
+            painter.text(center + Vec2::new(0.0, 8.0), Align2::CENTER_CENTER, detail, FontId::proportional(12.0), SlowColors::BLACK);
+
+            // Dithered fill inside a 1px frame, like the classic boot bar
+            let bar = Rect::from_center_size(center + Vec2::new(0.0, 36.0), Vec2::new(200.0, 12.0));
+            painter.rect_stroke(bar, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+            let inner = bar.shrink(2.0);
+            let fill = Rect::from_min_size(inner.min, Vec2::new(inner.width() * progress.clamp(0.0, 1.0), inner.height()));
+            dither::draw_dither_rect(painter, fill, SlowColors::BLACK, 1);
+
+            // Buttons in a centred row
+            let (width, gap) = (110.0, 12.0);
+            let row = buttons.len() as f32 * (width + gap) - gap;
+            for (i, label) in buttons.iter().enumerate() {
+                let x = center.x - row / 2.0 + i as f32 * (width + gap);
+                let rect = Rect::from_min_size(Pos2::new(x, center.y + 60.0), Vec2::new(width, 24.0));
+                if ui.put(rect, egui::Button::new(*label)).clicked() {
+                    clicked = Some(i);
+                }
+            }
+        });
+    clicked
+}
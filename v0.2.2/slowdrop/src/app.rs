@@ -0,0 +1,465 @@
+//! slowDrop application
+
+use crate::transfer::{self, Direction, Offer, Shared, State};
+use egui::{Align2, CentralPanel, Context, Key, Rect, Sense, SidePanel, Stroke, TopBottomPanel, Vec2};
+use slowcore::drag::DropTarget;
+use slowcore::lan::{Device, Presence};
+use slowcore::repaint::RepaintController;
+use slowcore::storage::documents_dir;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, FileDialog, FileDialogResult, SlowButton, WindowAction};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowDrop",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "hand files to slowbooks in the same room",
+    features: &["finds others on the local network", "nothing arrives without your ok", "files land in Downloads"],
+    ..AboutInfo::EMPTY
+};
+
+/// Files listed in the confirm dialog before "and N more"
+const OFFER_LIST_LEN: usize = 8;
+
+pub struct SlowDropApp {
+    ctx: Context,
+    me: Device,
+    presence: Option<Presence>,
+    /// Peer id files go to
+    selected: Option<String>,
+    /// Files waiting for someone to send them to
+    queued: Vec<PathBuf>,
+    transfers: Vec<Shared>,
+    offers: Receiver<Offer>,
+    /// Incoming offers, oldest first; the first one is being asked about
+    pending: VecDeque<Offer>,
+    file_dialog: Option<FileDialog>,
+    /// Name being edited in the "your name" dialog
+    rename: Option<String>,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowDropApp {
+    pub fn new(cc: &eframe::CreationContext<'_>, files: Vec<PathBuf>) -> Self {
+        let ctx = cc.egui_ctx.clone();
+        let (tx, offers) = mpsc::channel();
+
+        let mut message = None;
+        let presence = match transfer::listen(tx, ctx.clone()) {
+            Ok(port) => match Presence::start("slowdrop", port, ctx.clone()) {
+                Ok(presence) => Some(presence),
+                Err(e) => {
+                    message = Some(format!("can't look for others: {}", e));
+                    None
+                }
+            },
+            Err(e) => {
+                message = Some(format!("can't receive files: {}", e));
+                None
+            }
+        };
+
+        Self {
+            ctx,
+            me: slowcore::lan::device(),
+            presence,
+            selected: None,
+            queued: files,
+            transfers: Vec::new(),
+            offers,
+            pending: VecDeque::new(),
+            file_dialog: None,
+            rename: None,
+            message,
+            show_about: false,
+            repaint: RepaintController::new(),
+        }
+    }
+
+    fn poll(&mut self, ctx: &Context) {
+        while let Ok(offer) = self.offers.try_recv() {
+            let focused = ctx.input(|i| i.viewport().focused.unwrap_or(true));
+            if !focused {
+                if let Ok(t) = offer.transfer.lock() {
+                    slowcore::notify::post("slowdrop", &format!("{} wants to send you {}", t.peer, t.describe()));
+                }
+            }
+            self.pending.push_back(offer);
+        }
+        // Offers nobody answered in time have been declined already
+        self.pending
+            .retain(|o| o.transfer.lock().map(|t| t.state == State::Waiting).unwrap_or(false));
+
+        let dropped = DropTarget::new(&[]).show(ctx);
+        if !dropped.is_empty() {
+            self.queued.extend(dropped);
+            if self.selected.is_some() {
+                self.send_queued();
+            }
+        }
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        ctx.input(|i| {
+            if i.modifiers.command && i.key_pressed(Key::O) && self.selected.is_some() {
+                self.file_dialog = Some(FileDialog::open(documents_dir()).title("send file"));
+            }
+        });
+    }
+
+    /// Offer every queued file to the selected peer
+    fn send_queued(&mut self) {
+        if self.queued.is_empty() {
+            return;
+        }
+        let Some(peer) = self.selected.as_ref().and_then(|id| self.presence.as_ref()?.peer(id)) else {
+            self.message = Some("choose someone to send to".into());
+            return;
+        };
+        let files = std::mem::take(&mut self.queued);
+        match transfer::offer(peer.addr, &peer.name, files, self.ctx.clone()) {
+            Ok(transfer) => {
+                self.transfers.push(transfer);
+                self.message = None;
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("file", |ui| {
+                    if ui.add_enabled(self.selected.is_some(), egui::Button::new("send file...   ⌘o")).clicked() {
+                        self.file_dialog = Some(FileDialog::open(documents_dir()).title("send file"));
+                        ui.close_menu();
+                    }
+                    if ui.button("show downloads").clicked() {
+                        show_in_files(&slowcore::storage::downloads_dir());
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.add_enabled(self.transfers.iter().any(is_finished), egui::Button::new("clear finished")).clicked() {
+                        self.transfers.retain(|t| !is_finished(t));
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("your name...").clicked() {
+                        self.rename = Some(self.me.name.clone());
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowdrop", "slowDrop");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_nearby(&mut self, ctx: &Context) {
+        let peers = self.presence.as_ref().map(|p| p.peers()).unwrap_or_default();
+        let mut clicked = None;
+        SidePanel::left("nearby")
+            .resizable(false)
+            .exact_width(150.0)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new("nearby").strong());
+                ui.separator();
+                if peers.is_empty() {
+                    ui.label("looking for other slowbooks on this network...");
+                }
+                for peer in &peers {
+                    let selected = self.selected.as_deref() == Some(peer.id.as_str());
+                    if ui.add(SlowButton::new(&peer.name).selected(selected)).clicked() {
+                        clicked = Some(peer.id.clone());
+                    }
+                }
+            });
+        if let Some(id) = clicked {
+            self.selected = Some(id);
+        }
+    }
+
+    fn draw_transfers(&mut self, ctx: &Context) {
+        let peer_name = self
+            .selected
+            .as_ref()
+            .and_then(|id| self.presence.as_ref()?.peer(id))
+            .map(|p| p.name);
+        let mut send = false;
+        let mut pick_file = false;
+        let mut open = None;
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(8.0)))
+            .show(ctx, |ui| {
+                match &peer_name {
+                    Some(name) => {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("send to {}:", name));
+                            if ui.add(SlowButton::new("file...")).clicked() {
+                                pick_file = true;
+                            }
+                        });
+                        ui.label("or drop files on this window");
+                    }
+                    None => {
+                        ui.label("choose someone nearby to send files to");
+                    }
+                }
+                if !self.queued.is_empty() {
+                    ui.add_space(6.0);
+                    ui.horizontal(|ui| {
+                        let count = self.queued.len();
+                        ui.label(if count == 1 { "1 file ready".to_string() } else { format!("{} files ready", count) });
+                        if ui.add_enabled(peer_name.is_some(), egui::Button::new("send")).clicked() {
+                            send = true;
+                        }
+                        if ui.button("clear").clicked() {
+                            self.queued.clear();
+                        }
+                    });
+                }
+                ui.add_space(8.0);
+                ui.separator();
+
+                if self.transfers.is_empty() {
+                    ui.label("no transfers yet");
+                    return;
+                }
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    // Newest first
+                    for shared in self.transfers.iter().rev() {
+                        let Ok(t) = shared.lock().map(|t| t.clone()) else { continue };
+                        let heading = match t.direction {
+                            Direction::Sending => format!("{} to {}", t.describe(), t.peer),
+                            Direction::Receiving => format!("{} from {}", t.describe(), t.peer),
+                        };
+                        ui.label(egui::RichText::new(heading).strong());
+                        progress_bar(ui, t.fraction(), t.state == State::Waiting);
+                        ui.horizontal(|ui| {
+                            ui.label(match &t.state {
+                                State::Waiting => format!("waiting for {}", t.peer),
+                                State::Moving => format!("{} of {}", format_size(t.done), format_size(t.total())),
+                                State::Done => format!("done, {}", format_size(t.total())),
+                                State::Declined => "declined".to_string(),
+                                State::Failed(e) => format!("failed: {}", e),
+                            });
+                            if let (State::Done, Some(first)) = (&t.state, t.saved.first()) {
+                                if ui.add(SlowButton::new("show")).clicked() {
+                                    open = first.parent().map(|p| p.to_path_buf());
+                                }
+                            }
+                        });
+                        ui.add_space(8.0);
+                    }
+                });
+            });
+
+        if send {
+            self.send_queued();
+        }
+        if pick_file {
+            self.file_dialog = Some(FileDialog::open(documents_dir()).title("send file"));
+        }
+        if let Some(dir) = open {
+            show_in_files(&dir);
+        }
+    }
+
+    /// Ask about the oldest incoming offer
+    fn draw_offer(&mut self, ctx: &Context) {
+        let Some(offer) = self.pending.front() else { return };
+        let Ok(t) = offer.transfer.lock().map(|t| t.clone()) else { return };
+        let mut answer = None;
+        let resp = egui::Window::new("incoming files")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("{} wants to send you:", t.peer));
+                ui.add_space(4.0);
+                for file in t.files.iter().take(OFFER_LIST_LEN) {
+                    ui.label(format!("  {}  ({})", file.name, format_size(file.size)));
+                }
+                if t.files.len() > OFFER_LIST_LEN {
+                    ui.label(format!("  and {} more", t.files.len() - OFFER_LIST_LEN));
+                }
+                ui.add_space(4.0);
+                ui.label(format!("{} in all, saved to Downloads", format_size(t.total())));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("decline").clicked() {
+                        answer = Some(false);
+                    }
+                    if ui.button("accept").clicked() {
+                        answer = Some(true);
+                    }
+                });
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+
+        if let Some(accept) = answer {
+            if let Some(offer) = self.pending.pop_front() {
+                if accept {
+                    self.transfers.push(offer.transfer.clone());
+                }
+                offer.answer(accept);
+            }
+        }
+    }
+
+    fn draw_file_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.file_dialog else { return };
+        match dialog.show(ctx) {
+            FileDialogResult::Picked(path) => {
+                self.file_dialog = None;
+                self.queued.push(path);
+                self.send_queued();
+            }
+            FileDialogResult::Cancelled => self.file_dialog = None,
+            FileDialogResult::Pending => {}
+        }
+    }
+
+    fn draw_rename(&mut self, ctx: &Context) {
+        let Some(name) = &mut self.rename else { return };
+        let mut save = false;
+        let mut cancel = false;
+        let resp = egui::Window::new("your name")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("the name others on this network see:");
+                let field = ui.text_edit_singleline(name);
+                if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    save = true;
+                }
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        cancel = true;
+                    }
+                    if ui.button("save").clicked() {
+                        save = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+
+        if save {
+            if let Some(name) = self.rename.take().filter(|n| !n.trim().is_empty()) {
+                self.me = slowcore::lan::set_device_name(&name);
+                if let Some(presence) = &mut self.presence {
+                    let _ = presence.rename();
+                }
+            }
+        } else if cancel {
+            self.rename = None;
+        }
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+fn is_finished(transfer: &Shared) -> bool {
+    transfer
+        .lock()
+        .map(|t| matches!(t.state, State::Done | State::Declined | State::Failed(_)))
+        .unwrap_or(true)
+}
+
+/// Outlined bar filled in black; dithered while nothing is moving yet
+fn progress_bar(ui: &mut egui::Ui, fraction: f32, waiting: bool) {
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), 12.0), Sense::hover());
+    let painter = ui.painter();
+    painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+    let inner = rect.shrink(2.0);
+    if waiting {
+        slowcore::dither::draw_dither_rect(painter, inner, SlowColors::BLACK, 2);
+    } else {
+        let filled = Rect::from_min_size(inner.min, Vec2::new(inner.width() * fraction.clamp(0.0, 1.0), inner.height()));
+        painter.rect_filled(filled, 0.0, SlowColors::BLACK);
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 { format!("{} B", bytes) }
+    else if bytes < 1024 * 1024 { format!("{:.1} KB", bytes as f64 / 1024.0) }
+    else if bytes < 1024 * 1024 * 1024 { format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0)) }
+    else { format!("{:.1} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0)) }
+}
+
+/// Open a folder in slowFiles
+fn show_in_files(dir: &Path) {
+    let sibling = std::env::current_exe().ok().and_then(|e| e.parent().map(|p| p.join("slowfiles")));
+    let binary = sibling.filter(|p| p.exists()).unwrap_or_else(|| PathBuf::from("slowfiles"));
+    let _ = std::process::Command::new(binary)
+        .arg(dir)
+        .env("SLOWOS_MANAGED", "1")
+        .stdin(std::process::Stdio::null())
+        .spawn();
+}
+
+impl eframe::App for SlowDropApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowdrop") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.poll(ctx);
+        self.handle_keys(ctx);
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::top("title_bar").show(ctx, |ui| {
+            slowcore::theme::SlowTheme::title_bar_frame().show(ui, |ui| {
+                ui.centered_and_justified(|ui| {
+                    ui.label("slowDrop");
+                });
+            });
+        });
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| {
+                let nearby = self.presence.as_ref().map(|p| p.peers().len()).unwrap_or(0);
+                format!("you are {}  |  {} nearby", self.me.name, nearby)
+            });
+            status_bar(ui, &status);
+        });
+
+        self.draw_nearby(ctx);
+        self.draw_transfers(ctx);
+        self.draw_offer(ctx);
+        self.draw_file_dialog(ctx);
+        self.draw_rename(ctx);
+        self.draw_about(ctx);
+        self.repaint.end_frame(ctx);
+    }
+}
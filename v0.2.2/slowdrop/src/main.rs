@@ -0,0 +1,33 @@
+//! slowDrop - File transfer between Slowbooks
+//!
+//! Shows the other Slowbooks on the network; pick one and a file to hand it
+//! over. The receiver is asked first, and accepted files land in Downloads.
+
+mod app;
+mod transfer;
+
+use app::SlowDropApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    // Files named on the command line are ready to send
+    let files = std::env::args().skip(1).map(std::path::PathBuf::from).collect();
+
+    let viewport = window_layout::viewport("slowdrop", Placement::RememberLast, [460.0, 400.0])
+        .with_title("slowDrop");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowDrop",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowDropApp::new(cc, files))
+        }),
+    )
+}
@@ -0,0 +1,334 @@
+//! Offers and transfers on the wire
+//!
+//! One TCP connection per offer. The sender writes a JSON line naming the
+//! files, the receiver answers with a JSON line once the user has decided,
+//! and if accepted the files follow back to back as raw bytes. Progress is
+//! kept in a shared [`Transfer`] the window reads every frame.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Longest offer line accepted
+const MAX_HEADER: u64 = 64 * 1024;
+
+/// How long an offer waits for the receiver to decide
+const ANSWER_TIME: Duration = Duration::from_secs(60);
+
+/// Longest silence while bytes are moving
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Progress updates repaint the window at most this often
+const REPAINT_EVERY: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileInfo {
+    pub name: String,
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct OfferHeader {
+    from: String,
+    name: String,
+    files: Vec<FileInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Reply {
+    accept: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Sending,
+    Receiving,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum State {
+    /// Offer made, no answer yet
+    Waiting,
+    Moving,
+    Done,
+    Declined,
+    Failed(String),
+}
+
+/// One offer and, once accepted, its progress
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    /// The other device's name
+    pub peer: String,
+    pub direction: Direction,
+    pub files: Vec<FileInfo>,
+    /// Bytes moved so far, across all files
+    pub done: u64,
+    pub state: State,
+    /// Where received files were saved
+    pub saved: Vec<PathBuf>,
+}
+
+impl Transfer {
+    fn new(peer: String, direction: Direction, files: Vec<FileInfo>) -> Self {
+        Self { peer, direction, files, done: 0, state: State::Waiting, saved: Vec::new() }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.files.iter().map(|f| f.size).sum()
+    }
+
+    pub fn fraction(&self) -> f32 {
+        match self.state {
+            State::Done => 1.0,
+            _ if self.total() == 0 => 0.0,
+            _ => self.done as f32 / self.total() as f32,
+        }
+    }
+
+    /// "photo.png", or "3 files"
+    pub fn describe(&self) -> String {
+        match self.files.as_slice() {
+            [one] => one.name.clone(),
+            files => format!("{} files", files.len()),
+        }
+    }
+}
+
+pub type Shared = Arc<Mutex<Transfer>>;
+
+fn set_state(transfer: &Shared, state: State) {
+    if let Ok(mut t) = transfer.lock() {
+        t.state = state;
+    }
+}
+
+/// An incoming offer waiting for the user to accept or decline
+pub struct Offer {
+    pub transfer: Shared,
+    reply: Sender<bool>,
+}
+
+impl Offer {
+    pub fn answer(self, accept: bool) {
+        let _ = self.reply.send(accept);
+    }
+}
+
+/// Keep only the last part of a name the sender chose; never a path
+fn safe_name(name: &str) -> String {
+    Path::new(name)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .filter(|n| !n.starts_with('.'))
+        .unwrap_or_else(|| "received file".into())
+}
+
+fn write_line<T: Serialize>(stream: &mut TcpStream, value: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(value).map_err(io::Error::other)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+fn read_line<T: for<'de> Deserialize<'de>>(reader: &mut BufReader<TcpStream>) -> io::Result<T> {
+    let mut line = String::new();
+    reader.take(MAX_HEADER).read_line(&mut line)?;
+    serde_json::from_str(&line).map_err(io::Error::other)
+}
+
+/// Copy `reader` into `writer`, counting bytes into the transfer
+fn copy_counted(reader: &mut impl Read, writer: &mut impl Write, transfer: &Shared, ctx: &egui::Context) -> io::Result<u64> {
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut copied = 0u64;
+    let mut last_repaint = Instant::now();
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buf[..n])?;
+        copied += n as u64;
+        if let Ok(mut t) = transfer.lock() {
+            t.done += n as u64;
+        }
+        if last_repaint.elapsed() >= REPAINT_EVERY {
+            ctx.request_repaint();
+            last_repaint = Instant::now();
+        }
+    }
+    Ok(copied)
+}
+
+/// Offer files to a peer. Returns straight away; the transfer runs in the
+/// background and reports through the returned handle.
+pub fn offer(addr: SocketAddr, peer: &str, paths: Vec<PathBuf>, ctx: egui::Context) -> io::Result<Shared> {
+    let mut files = Vec::new();
+    for path in &paths {
+        let meta = std::fs::metadata(path)?;
+        if !meta.is_file() {
+            return Err(io::Error::other("only files can be sent"));
+        }
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "file".into());
+        files.push(FileInfo { name, size: meta.len() });
+    }
+    let transfer = Arc::new(Mutex::new(Transfer::new(peer.to_string(), Direction::Sending, files.clone())));
+
+    let handle = transfer.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = send(addr, &paths, files, &handle, &ctx) {
+            set_state(&handle, State::Failed(e.to_string()));
+        }
+        ctx.request_repaint();
+    });
+    Ok(transfer)
+}
+
+fn send(addr: SocketAddr, paths: &[PathBuf], files: Vec<FileInfo>, transfer: &Shared, ctx: &egui::Context) -> io::Result<()> {
+    let me = slowcore::lan::device();
+    let mut stream = TcpStream::connect_timeout(&addr, Duration::from_secs(5))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    write_line(&mut stream, &OfferHeader { from: me.id, name: me.name, files: files.clone() })?;
+
+    // Give the other side time to decide
+    stream.set_read_timeout(Some(ANSWER_TIME + Duration::from_secs(10)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let reply: Reply = read_line(&mut reader)?;
+    if !reply.accept {
+        set_state(transfer, State::Declined);
+        return Ok(());
+    }
+
+    set_state(transfer, State::Moving);
+    ctx.request_repaint();
+    for (path, file) in paths.iter().zip(&files) {
+        // Exactly the size offered, even if the file changed since
+        let sent = copy_counted(&mut std::fs::File::open(path)?.take(file.size), &mut stream, transfer, ctx)?;
+        if sent != file.size {
+            return Err(io::Error::other(format!("{} changed while sending", file.name)));
+        }
+    }
+    stream.flush()?;
+    set_state(transfer, State::Done);
+    Ok(())
+}
+
+/// Take one offer: ask the user through `offers`, then receive if accepted
+fn receive(stream: TcpStream, offers: &Sender<Offer>, ctx: &egui::Context) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let header: OfferHeader = read_line(&mut reader)?;
+    if header.files.is_empty() {
+        return Err(io::Error::other("empty offer"));
+    }
+    let files: Vec<FileInfo> = header
+        .files
+        .into_iter()
+        .map(|f| FileInfo { name: safe_name(&f.name), size: f.size })
+        .collect();
+    let transfer = Arc::new(Mutex::new(Transfer::new(header.name, Direction::Receiving, files.clone())));
+
+    let (reply, answer) = mpsc::channel();
+    if offers.send(Offer { transfer: transfer.clone(), reply }).is_err() {
+        return Ok(());
+    }
+    ctx.request_repaint();
+
+    // No answer counts as no
+    let accept = answer.recv_timeout(ANSWER_TIME).unwrap_or(false);
+    let mut stream = stream;
+    write_line(&mut stream, &Reply { accept })?;
+    if !accept {
+        set_state(&transfer, State::Declined);
+        ctx.request_repaint();
+        return Ok(());
+    }
+
+    set_state(&transfer, State::Moving);
+    let result = receive_files(&mut reader, &files, &transfer, ctx);
+    match result {
+        Ok(()) => {
+            set_state(&transfer, State::Done);
+            if let Ok(t) = transfer.lock() {
+                if let Some(first) = t.saved.first() {
+                    let message = format!("{} from {}", t.describe(), t.peer);
+                    slowcore::notify::post_with_reveal("slowdrop", &message, first);
+                }
+            }
+        }
+        Err(e) => set_state(&transfer, State::Failed(e.to_string())),
+    }
+    ctx.request_repaint();
+    Ok(())
+}
+
+fn receive_files(reader: &mut BufReader<TcpStream>, files: &[FileInfo], transfer: &Shared, ctx: &egui::Context) -> io::Result<()> {
+    let downloads = slowcore::storage::downloads_dir();
+    std::fs::create_dir_all(&downloads)?;
+    for file in files {
+        let path = slowcore::storage::unique_path(&downloads, &file.name);
+        let mut part = (&mut *reader).take(file.size);
+        let result = std::fs::File::create(&path).and_then(|mut out| copy_counted(&mut part, &mut out, transfer, ctx));
+        match result {
+            Ok(n) if n == file.size => {
+                if let Ok(mut t) = transfer.lock() {
+                    t.saved.push(path);
+                }
+            }
+            Ok(_) => {
+                let _ = std::fs::remove_file(&path);
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "transfer cut short"));
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&path);
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Listen for offers in the background, returning the port listened on.
+/// Connections from outside the local network are ignored.
+pub fn listen(offers: Sender<Offer>, ctx: egui::Context) -> io::Result<u16> {
+    let listener = TcpListener::bind(("0.0.0.0", 0))?;
+    let port = listener.local_addr()?.port();
+    std::thread::Builder::new().name("slowdrop-listen".into()).spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if !stream.peer_addr().is_ok_and(|a| slowcore::lan::is_local(a.ip())) {
+                continue;
+            }
+            let offers = offers.clone();
+            let ctx = ctx.clone();
+            std::thread::spawn(move || {
+                let _ = receive(stream, &offers, &ctx);
+            });
+        }
+    })?;
+    Ok(port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offer_format() {
+        let header = OfferHeader {
+            from: "ab12".into(),
+            name: "desk".into(),
+            files: vec![FileInfo { name: "a.txt".into(), size: 3 }],
+        };
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(json, r#"{"from":"ab12","name":"desk","files":[{"name":"a.txt","size":3}]}"#);
+        assert_eq!(serde_json::from_str::<OfferHeader>(&json).unwrap(), header);
+
+        assert_eq!(safe_name("../../etc/passwd"), "passwd");
+        assert_eq!(safe_name(".bashrc"), "received file");
+        assert_eq!(safe_name("photo.png"), "photo.png");
+    }
+}
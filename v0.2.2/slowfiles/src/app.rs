@@ -1,13 +1,42 @@
 //! SlowFiles - file explorer
 
-use egui::{ColorImage, Context, Key, Pos2, Rect, TextureHandle, TextureOptions, Vec2};
+use egui::{ColorImage, Context, Key, Modifiers, Pos2, Rect, TextureHandle, TextureOptions, Vec2};
+use slowcore::app_registry;
+use slowcore::drag::DropTarget;
+use slowcore::preview::{self, Preview};
+use slowcore::notify::{MovedFile, Undo};
 use slowcore::repaint::RepaintController;
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
-use std::collections::{HashMap, HashSet};
+use slowcore::thumbnails::{self, Thumbnails};
+use slowcore::volumes::{self, Volume};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, ListSelection, VirtualList, WindowAction};
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::time::SystemTime;
-use trash::{move_to_trash, restore_from_trash};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+use trash::{move_to_trash, notify_trashed, restore_from_trash};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "files",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "file manager for slowOS",
+    features: &["browse, sort, multi-select files", "navigate with ⌘+arrows", "open and eject USB drives", "preview files with space"],
+    credits: &["egui/eframe (MIT), chrono (MIT)", "open (MIT)"],
+    ..AboutInfo::EMPTY
+};
+
+/// Previews fit within this many pixels square
+const QUICK_LOOK_SIZE: u32 = 360;
+
+/// The space bar's preview of the selected file
+struct QuickLook {
+    path: PathBuf,
+    preview: Preview,
+    /// Pictures, once loaded
+    texture: Option<TextureHandle>,
+    /// Sounds, while playing
+    sound: Option<rodio::Sink>,
+}
 
 struct FileEntry {
     name: String,
@@ -21,9 +50,8 @@ struct FileEntry {
 pub struct SlowFilesApp {
     current_dir: PathBuf,
     entries: Vec<FileEntry>,
-    selected: HashSet<usize>,
-    /// Last clicked index for shift+click range selection
-    last_clicked: Option<usize>,
+    /// Selected entries, in both views
+    selection: ListSelection,
     path_input: String,
     show_hidden: bool,
     sort_by: SortBy,
@@ -55,10 +83,13 @@ pub struct SlowFilesApp {
     marquee_start: Option<Pos2>,
     /// Item rects from last render (for marquee hit testing)
     item_rects: Vec<(usize, Rect)>,
-    /// Thumbnail cache for image files (keyed by path string)
-    thumbnails: HashMap<String, TextureHandle>,
-    /// Paths that failed to load as thumbnails (don't retry)
-    thumbnail_failed: HashSet<String>,
+    /// Thumbnails of image files, made in the background
+    thumbnails: Thumbnails,
+    /// Mounted USB drives, listed in the sidebar
+    volumes: Vec<Volume>,
+    /// An eject running in the background: (drive name, result)
+    eject_result: Option<mpsc::Receiver<(String, Result<(), String>)>>,
+    quick_look: Option<QuickLook>,
     repaint: RepaintController,
 }
 
@@ -69,15 +100,15 @@ enum SortBy { Name, Size, Modified }
 enum ViewMode { Icons, List }
 
 impl SlowFilesApp {
-    pub fn new_with_dir(_cc: &eframe::CreationContext<'_>, start_dir: Option<PathBuf>) -> Self {
+    pub fn new_with_dir(cc: &eframe::CreationContext<'_>, start_dir: Option<PathBuf>) -> Self {
+        volumes::watch(cc.egui_ctx.clone());
         let dir = start_dir
             .filter(|p| p.is_dir())
             .unwrap_or_else(|| dirs_home().unwrap_or_else(|| PathBuf::from("/")));
         let mut app = Self {
             current_dir: dir.clone(),
             entries: Vec::new(),
-            selected: HashSet::new(),
-            last_clicked: None,
+            selection: ListSelection::default(),
             path_input: dir.to_string_lossy().to_string(),
             show_hidden: false,
             sort_by: SortBy::Name,
@@ -99,64 +130,16 @@ impl SlowFilesApp {
             focus_new_folder_field: false,
             marquee_start: None,
             item_rects: Vec::new(),
-            thumbnails: HashMap::new(),
-            thumbnail_failed: HashSet::new(),
+            thumbnails: Thumbnails::new(&cc.egui_ctx, thumbnails::ICON_SIZE),
+            volumes: volumes::removable(),
+            eject_result: None,
+            quick_look: None,
             repaint: RepaintController::new(),
         };
         app.refresh();
         app
     }
 
-    /// Generate a 32x32 thumbnail for an image file
-    fn get_or_create_thumbnail(&mut self, ctx: &Context, path: &PathBuf) -> Option<TextureHandle> {
-        let key = path.to_string_lossy().to_string();
-
-        // Check if already cached
-        if let Some(tex) = self.thumbnails.get(&key) {
-            return Some(tex.clone());
-        }
-
-        // Skip if previously failed
-        if self.thumbnail_failed.contains(&key) {
-            return None;
-        }
-
-        // Evict old thumbnails when cache gets large
-        if self.thumbnails.len() >= 64 {
-            self.thumbnails.clear();
-        }
-
-        // Try to load and create thumbnail (black & white to save energy on e-ink)
-        if let Ok(bytes) = std::fs::read(path) {
-            if let Ok(img) = image::load_from_memory(&bytes) {
-                let thumb = img.thumbnail(32, 32);
-                let gray = thumb.to_luma8();
-                let (w, h) = gray.dimensions();
-                // Convert to RGBA B&W: threshold at 128
-                let mut bw_pixels = Vec::with_capacity((w * h * 4) as usize);
-                for pixel in gray.pixels() {
-                    let val = if pixel[0] >= 128 { 255u8 } else { 0u8 };
-                    bw_pixels.extend_from_slice(&[val, val, val, 255]);
-                }
-                let color_image = ColorImage::from_rgba_unmultiplied(
-                    [w as usize, h as usize],
-                    &bw_pixels,
-                );
-                let texture = ctx.load_texture(
-                    format!("thumb_{}", key),
-                    color_image,
-                    TextureOptions::NEAREST,
-                );
-                self.thumbnails.insert(key, texture.clone());
-                return Some(texture);
-            }
-        }
-
-        // Mark as failed
-        self.thumbnail_failed.insert(key);
-        None
-    }
-
     fn create_new_folder(&mut self) {
         let name = self.new_folder_name.trim();
         if name.is_empty() {
@@ -181,6 +164,7 @@ impl SlowFilesApp {
 
     fn move_files_to_folder(&mut self, paths: &[PathBuf], dest_dir: &PathBuf) {
         let mut blocked_names: Vec<String> = Vec::new();
+        let mut moved: Vec<MovedFile> = Vec::new();
         for path in paths {
             if path == dest_dir || path.parent() == Some(dest_dir.as_path()) {
                 continue; // Skip if already in destination
@@ -196,10 +180,20 @@ impl SlowFilesApp {
                 let dest_path = dest_dir.join(name);
                 if let Err(e) = std::fs::rename(path, &dest_path) {
                     self.error_msg = Some(format!("Failed to move file: {}", e));
-                    return;
+                    break;
                 }
+                moved.push(MovedFile { from: path.clone(), to: dest_path });
             }
         }
+        if !moved.is_empty() {
+            let folder = dest_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let message = if moved.len() == 1 {
+                format!("moved 1 item to {}", folder)
+            } else {
+                format!("moved {} items to {}", moved.len(), folder)
+            };
+            slowcore::notify::post_with_undo("slowfiles", &message, Undo::Move { moves: moved });
+        }
         if !blocked_names.is_empty() {
             self.error_msg = Some(format!(
                 "Cannot move system folder(s): {}",
@@ -209,6 +203,91 @@ impl SlowFilesApp {
         self.refresh();
     }
 
+    /// The drive the current folder is on, if it's on one
+    fn current_volume(&self) -> Option<usize> {
+        self.volumes.iter().position(|v| self.current_dir.starts_with(&v.mount_point))
+    }
+
+    /// Eject in the background; flushing a slow stick can take a while
+    fn eject(&mut self, ctx: &Context, index: usize) {
+        let Some(volume) = self.volumes.get(index).cloned() else { return };
+        if self.eject_result.is_some() {
+            return;
+        }
+        // Step off the drive so this window isn't what keeps it busy
+        if self.current_dir.starts_with(&volume.mount_point) {
+            if let Some(h) = dirs_home() { self.navigate(h); }
+        }
+        let (tx, rx) = mpsc::channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = volumes::eject(&volume);
+            let _ = tx.send((volume.name, result));
+            ctx.request_repaint();
+        });
+        self.eject_result = Some(rx);
+    }
+
+    /// Pick up drives coming and going, and the outcome of an eject
+    fn refresh_volumes(&mut self) {
+        if let Some(rx) = &self.eject_result {
+            if let Ok((name, result)) = rx.try_recv() {
+                self.eject_result = None;
+                match result {
+                    Ok(()) => slowcore::notify::post("slowfiles", &format!("{} can be removed", name)),
+                    Err(e) => self.error_msg = Some(format!("Couldn't eject: {}", e)),
+                }
+            }
+        }
+        let now = volumes::removable();
+        if now != self.volumes {
+            self.volumes = now;
+            // The drive we were looking at was pulled out
+            if !self.current_dir.is_dir() {
+                if let Some(h) = dirs_home() { self.navigate(h); }
+            }
+        }
+    }
+
+    fn render_sidebar(&mut self, ui: &mut egui::Ui) {
+        let mut go_to: Option<PathBuf> = None;
+        let mut eject: Option<usize> = None;
+        ui.label(egui::RichText::new("places").strong());
+        let places = [
+            ("home", dirs_home()),
+            ("documents", Some(slowcore::storage::documents_dir())),
+        ];
+        for (name, path) in places {
+            let Some(path) = path else { continue };
+            if ui.selectable_label(self.current_dir == path, name).clicked() {
+                go_to = Some(path);
+            }
+        }
+        ui.add_space(8.0);
+        ui.label(egui::RichText::new("volumes").strong());
+        if self.volumes.is_empty() {
+            ui.label(egui::RichText::new("no drives").italics());
+        }
+        for (index, volume) in self.volumes.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let here = self.current_dir.starts_with(&volume.mount_point);
+                if ui.selectable_label(here, &volume.name).clicked() {
+                    go_to = Some(volume.mount_point.clone());
+                }
+                let ejecting = self.eject_result.is_some();
+                if ui.add_enabled(!ejecting, egui::Button::new("⏏")).on_hover_text("eject").clicked() {
+                    eject = Some(index);
+                }
+            });
+        }
+        if let Some(path) = go_to {
+            self.navigate(path);
+        }
+        if let Some(index) = eject {
+            self.eject(ui.ctx(), index);
+        }
+    }
+
     fn ensure_file_icons(&mut self, ctx: &Context) {
         if self.icons_loaded {
             return;
@@ -249,10 +328,11 @@ impl SlowFilesApp {
         if path.is_dir() {
             self.current_dir = path.clone();
             self.path_input = path.to_string_lossy().to_string();
-            self.selected.clear();
-            self.last_clicked = None;
+            self.selection.clear();
             self.error_msg = None;
 
+            self.thumbnails.clear();
+
             // Update history
             self.history.truncate(self.history_idx + 1);
             self.history.push(path);
@@ -280,8 +360,8 @@ impl SlowFilesApp {
         let path = self.history[self.history_idx].clone();
         self.current_dir = path.clone();
         self.path_input = path.to_string_lossy().to_string();
-        self.selected.clear();
-        self.last_clicked = None;
+        self.selection.clear();
+        self.thumbnails.clear();
         self.refresh();
     }
 
@@ -347,7 +427,7 @@ impl SlowFilesApp {
     }
 
     fn open_selected(&mut self) {
-        if let Some(&idx) = self.selected.iter().next() {
+        if let Some(idx) = self.selection.primary() {
             if let Some(entry) = self.entries.get(idx) {
                 if entry.is_dir {
                     self.navigate(entry.path.clone());
@@ -358,18 +438,101 @@ impl SlowFilesApp {
         }
     }
 
+    /// The selected file the keyboard or mouse last moved to
+    fn primary_selected(&self) -> Option<&FileEntry> {
+        self.entries.get(self.selection.primary()?)
+    }
+
+    /// Preview the selected file, unless the preview already shows it
+    fn update_quick_look(&mut self, ctx: &Context) {
+        let Some(entry) = self.primary_selected() else {
+            self.quick_look = None;
+            return;
+        };
+        if self.quick_look.as_ref().is_some_and(|q| q.path == entry.path) {
+            return;
+        }
+        let path = entry.path.clone();
+        let mut preview = preview::load(&path, QUICK_LOOK_SIZE);
+        let texture = match &mut preview {
+            Preview::Picture(image) => {
+                Some(ctx.load_texture("quick_look", std::mem::take(image), TextureOptions::NEAREST))
+            }
+            _ => None,
+        };
+        self.quick_look = Some(QuickLook { path, preview, texture, sound: None });
+    }
+
+    fn render_quick_look(&mut self, ctx: &Context) {
+        let Some(look) = &mut self.quick_look else { return };
+        let name = look.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let side = QUICK_LOOK_SIZE as f32;
+        let mut close = false;
+        let mut open = false;
+        let resp = egui::Window::new(name)
+            .id(egui::Id::new("quick_look"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                match &look.preview {
+                    Preview::Text(text) => {
+                        egui::ScrollArea::vertical().max_height(side).show(ui, |ui| {
+                            ui.set_width(side);
+                            ui.label(egui::RichText::new(text).monospace());
+                        });
+                    }
+                    Preview::Picture(_) => {
+                        if let Some(texture) = &look.texture {
+                            let size = texture.size_vec2() / ui.ctx().pixels_per_point();
+                            ui.image((texture.id(), size));
+                        }
+                    }
+                    Preview::Sound => {
+                        let playing = look.sound.as_ref().is_some_and(|s| !s.empty());
+                        if ui.button(if playing { "■ stop" } else { "▶ play" }).clicked() {
+                            look.sound = if playing { None } else { preview::play_sound(&look.path) };
+                        }
+                        if playing {
+                            ui.ctx().request_repaint_after(Duration::from_millis(250));
+                        }
+                    }
+                    Preview::Nothing => {
+                        ui.label("no preview");
+                    }
+                }
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("close").clicked() {
+                        close = true;
+                    }
+                    if ui.button("open").clicked() {
+                        open = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp {
+            slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+        }
+        if open {
+            open_in_slow_app(&look.path);
+        }
+        if open || close || ctx.input(|i| i.key_pressed(Key::Escape)) {
+            self.quick_look = None;
+        }
+    }
+
     /// Check if a path is a protected system folder or bundled content
     fn is_system_folder(path: &PathBuf) -> bool {
         slowcore::safety::is_system_path(path)
     }
 
     fn delete_selected(&mut self) {
-        if self.selected.is_empty() {
+        if self.selection.rows.is_empty() {
             return;
         }
-        // Collect paths to delete (sorted descending so indices don't shift)
-        let mut indices: Vec<usize> = self.selected.iter().copied().collect();
-        indices.sort_by(|a, b| b.cmp(a));
+        // Collect paths to delete (descending so indices don't shift)
+        let indices: Vec<usize> = self.selection.rows.iter().rev().copied().collect();
 
         let mut deleted_in_batch: Vec<PathBuf> = Vec::new();
         let mut blocked_names: Vec<String> = Vec::new();
@@ -392,6 +555,7 @@ impl SlowFilesApp {
 
         // Store deleted paths for undo (most recent batch)
         if !deleted_in_batch.is_empty() {
+            notify_trashed("slowfiles", &deleted_in_batch);
             self.deleted_paths = deleted_in_batch;
         }
 
@@ -403,8 +567,7 @@ impl SlowFilesApp {
             ));
         }
 
-        self.selected.clear();
-        self.last_clicked = None;
+        self.selection.clear();
         self.refresh();
     }
 
@@ -447,43 +610,46 @@ impl SlowFilesApp {
                 if i.key_pressed(Key::Num1) { self.view_mode = ViewMode::Icons; }
                 if i.key_pressed(Key::Num2) { self.view_mode = ViewMode::List; }
 
-                if i.key_pressed(Key::ArrowUp) {
-                    // Move selection up - select item before first selected, or first item
-                    let min_selected = self.selected.iter().min().copied();
-                    if let Some(idx) = min_selected {
-                        if idx > 0 {
-                            self.selected.clear();
-                            self.selected.insert(idx - 1);
-                            self.last_clicked = Some(idx - 1);
-                        }
-                    }
-                }
-                if i.key_pressed(Key::ArrowDown) {
-                    let max = self.entries.len().saturating_sub(1);
-                    let max_selected = self.selected.iter().max().copied();
-                    if let Some(idx) = max_selected {
-                        if idx < max {
-                            self.selected.clear();
-                            self.selected.insert(idx + 1);
-                            self.last_clicked = Some(idx + 1);
-                        }
-                    } else if !self.entries.is_empty() {
-                        self.selected.clear();
-                        self.selected.insert(0);
-                        self.last_clicked = Some(0);
-                    }
-                }
             }
         });
 
+        // Arrows move the selection in either view; taken here so the list
+        // view's own keys don't move it a second time
+        let (up, down) = ctx.input_mut(|i| (i.consume_key(Modifiers::NONE, Key::ArrowUp), i.consume_key(Modifiers::NONE, Key::ArrowDown)));
+        if up {
+            // Move selection up - select item before first selected
+            if let Some(&idx) = self.selection.rows.first() {
+                self.selection.select_only(idx.saturating_sub(1));
+            }
+        }
+        if down && !self.entries.is_empty() {
+            let max = self.entries.len() - 1;
+            let next = self.selection.rows.last().map_or(0, |&idx| (idx + 1).min(max));
+            self.selection.select_only(next);
+        }
+
+        // Space: preview the selected file, or put the preview away
+        let typing = ctx.memory(|m| m.focused()).is_some_and(|id| egui::text_edit::TextEditState::load(ctx, id).is_some());
+        let space = !typing && ctx.input(|i| i.key_pressed(Key::Space) && i.modifiers.is_none());
+        if space && self.quick_look.take().is_none() {
+            self.update_quick_look(ctx);
+        }
+
         // Handle delete key outside input closure
         let should_delete = ctx.input(|i| {
-            (i.key_pressed(Key::Backspace) || i.key_pressed(Key::Delete)) && !self.selected.is_empty()
+            (i.key_pressed(Key::Backspace) || i.key_pressed(Key::Delete)) && !self.selection.rows.is_empty()
         });
         if should_delete {
             self.delete_selected();
         }
 
+        // Cmd+E: eject the drive the current folder is on
+        if ctx.input(|i| i.modifiers.command && i.key_pressed(Key::E)) {
+            if let Some(index) = self.current_volume() {
+                self.eject(ctx, index);
+            }
+        }
+
         // Handle undo (Cmd+Z)
         let should_undo = ctx.input(|i| i.modifiers.command && i.key_pressed(Key::Z));
         if should_undo {
@@ -613,26 +779,12 @@ impl SlowFilesApp {
 
     /// Handle a click action (shift/cmd/normal) to update selection.
     fn handle_click_action(&mut self, idx: usize, shift: bool, cmd: bool) {
-        if shift && self.last_clicked.is_some() {
-            let start = self.last_clicked.unwrap();
-            let (from, to) = if start <= idx { (start, idx) } else { (idx, start) };
-            if !cmd {
-                self.selected.clear();
-            }
-            for i in from..=to {
-                self.selected.insert(i);
-            }
+        if shift {
+            self.selection.extend_to(idx);
         } else if cmd {
-            if self.selected.contains(&idx) {
-                self.selected.remove(&idx);
-            } else {
-                self.selected.insert(idx);
-            }
-            self.last_clicked = Some(idx);
+            self.selection.toggle(idx);
         } else {
-            self.selected.clear();
-            self.selected.insert(idx);
-            self.last_clicked = Some(idx);
+            self.selection.select_only(idx);
         }
     }
 
@@ -643,6 +795,22 @@ impl SlowFilesApp {
         self.drag_preview = Some((icon_key, name, count));
     }
 
+    /// Files dragged in from another app, or a title bar's proxy icon:
+    /// show the first in its folder, selected
+    fn handle_outside_drop(&mut self, ctx: &Context) {
+        let dropped = DropTarget::new(&[]).folders(true).show(ctx);
+        let Some(path) = dropped.into_iter().next() else { return };
+        if let Some(dir) = path.parent().filter(|d| d.is_dir()) {
+            if dir != self.current_dir {
+                self.navigate(dir.to_path_buf());
+            }
+            self.selection.clear();
+            if let Some(idx) = self.entries.iter().position(|e| e.path == path) {
+                self.selection.select_only(idx);
+            }
+        }
+    }
+
     /// Handle a drop onto `drop_target` and clear drag state on mouse release.
     fn handle_drop_and_clear_drag(&mut self, drop_target: Option<PathBuf>, primary_released: bool) {
         let did_drop = drop_target.is_some();
@@ -745,172 +913,161 @@ impl SlowFilesApp {
 
         ui.add_space(2.0);
 
-        // Collect entry data to avoid borrow conflict
-        let display_entries: Vec<(usize, String, String, String, String, bool, PathBuf)> =
-            self.entries.iter().enumerate().map(|(idx, entry)| {
-                let icon_key = if entry.is_dir { "folder".to_string() } else { file_icon_key(&entry.name).to_string() };
-                let size_str = if entry.is_dir { "—".into() } else { format_size(entry.size) };
-                (idx, entry.name.clone(), icon_key, size_str, entry.modified.clone(), entry.is_dir, entry.path.clone())
-            }).collect();
-
-        // Get modifier state for shift/cmd click
-        let modifiers = ui.input(|i| i.modifiers);
-
-        // File list
+        // File list: only the rows on screen are laid out
         let mut nav_target: Option<PathBuf> = None;
         let mut open_target: Option<(PathBuf, Rect)> = None;
-        let mut click_action: Option<(usize, bool, bool)> = None; // (idx, shift, cmd)
-        let mut drag_start: Option<(Vec<PathBuf>, String, String, usize)> = None;
+        let mut drag_from: Option<(usize, bool)> = None;
         let mut drop_target: Option<PathBuf> = None;
         let primary_released = ui.input(|i| i.pointer.primary_released());
+        let row_height = 18.0;
+        let entries = &self.entries;
+        let thumbnails = &mut self.thumbnails;
+        let file_icons = &self.file_icons;
+        let dragging = &self.dragging;
+        let drag_hover_idx = &mut self.drag_hover_idx;
+
+        // Enter and double-clicks are handled here and in `handle_keys`,
+        // so what the list reports as activated isn't used
+        VirtualList::new("files_list")
+            .row_height(row_height)
+            .max_height(ui.available_height() / slowcore::theme::ui_scale())
+            .multi_select(true)
+            .show(
+                ui,
+                &mut self.selection,
+                entries.len(),
+                |idx| entries[idx].name.as_str(),
+                |ui, idx, is_selected| {
+                    let entry = &entries[idx];
+                    let icon_key = if entry.is_dir { "folder" } else { file_icon_key(&entry.name) };
+                    let is_drag_hover = *drag_hover_idx == Some(idx) && entry.is_dir;
+                    let total_w = ui.available_width();
+                    let name_w = total_w - 180.0;
+
+                    // Draw the row manually so we control alignment
+                    let sense = egui::Sense { focusable: false, ..egui::Sense::click_and_drag() };
+                    let (rect, response) = ui.allocate_exact_size(egui::vec2(total_w, row_height), sense);
+
+                    if ui.is_rect_visible(rect) {
+                        let painter = ui.painter();
+
+                        // Selection highlight — dithered (darken folders when dragging over)
+                        if is_drag_hover || is_selected {
+                            slowcore::dither::draw_dither_selection(painter, rect);
+                        } else if response.hovered() {
+                            slowcore::dither::draw_dither_hover(painter, rect);
+                        }
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            for (idx, name, icon_key, size_str, modified, is_dir, path) in &display_entries {
-                let is_selected = self.selected.contains(idx);
-                let is_drag_hover = self.drag_hover_idx == Some(*idx) && *is_dir;
-                let row_height = 18.0;
-                let total_w = ui.available_width();
-                let name_w = total_w - 180.0;
-
-                // Draw the row manually so we control alignment
-                let (rect, response) = ui.allocate_exact_size(
-                    egui::vec2(total_w, row_height),
-                    egui::Sense::click_and_drag(),
-                );
-
-                if ui.is_rect_visible(rect) {
-                    let painter = ui.painter();
-
-                    // Selection highlight — dithered (darken folders when dragging over)
-                    if is_drag_hover {
-                        slowcore::dither::draw_dither_selection(painter, rect);
-                    } else if is_selected {
-                        slowcore::dither::draw_dither_selection(painter, rect);
-                    } else if response.hovered() {
-                        slowcore::dither::draw_dither_hover(painter, rect);
-                    }
-
-                    let text_color = if is_selected { SlowColors::WHITE } else { SlowColors::BLACK };
-
-                    // Icon (small, 14px) + filename
-                    let icon_px = 14.0;
-                    let icon_x = rect.min.x + 4.0;
-                    let icon_center = egui::pos2(icon_x + icon_px / 2.0, rect.center().y);
-                    let icon_rect = Rect::from_center_size(icon_center, Vec2::splat(icon_px));
-
-                    // For image files, try to use a thumbnail
-                    let mut drew_thumbnail = false;
-                    if icon_key == "image" && !*is_dir {
-                        if let Some(thumb) = self.get_or_create_thumbnail(ui.ctx(), path) {
-                            let thumb_size = thumb.size_vec2();
-                            let scale = icon_px / thumb_size.x.max(thumb_size.y);
-                            let display_size = Vec2::new(thumb_size.x * scale, thumb_size.y * scale);
-                            let thumb_rect = Rect::from_center_size(icon_center, display_size);
-                            painter.image(
-                                thumb.id(),
-                                thumb_rect,
-                                Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                                egui::Color32::WHITE,
-                            );
-                            drew_thumbnail = true;
+                        let text_color = if is_selected { SlowColors::WHITE } else { SlowColors::BLACK };
+
+                        // Icon (small, 14px) + filename
+                        let icon_px = 14.0;
+                        let icon_x = rect.min.x + 4.0;
+                        let icon_center = egui::pos2(icon_x + icon_px / 2.0, rect.center().y);
+                        let icon_rect = Rect::from_center_size(icon_center, Vec2::splat(icon_px));
+
+                        // For image files, try to use a thumbnail
+                        let mut drew_thumbnail = false;
+                        if icon_key == "image" {
+                            if let Some(thumb) = thumbnails.get(ui.ctx(), &entry.path) {
+                                let thumb_size = thumb.size_vec2();
+                                let scale = icon_px / thumb_size.x.max(thumb_size.y);
+                                let display_size = Vec2::new(thumb_size.x * scale, thumb_size.y * scale);
+                                let thumb_rect = Rect::from_center_size(icon_center, display_size);
+                                painter.image(
+                                    thumb.id(),
+                                    thumb_rect,
+                                    Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                                    egui::Color32::WHITE,
+                                );
+                                drew_thumbnail = true;
+                            }
                         }
-                    }
 
-                    if !drew_thumbnail {
-                        if let Some(tex) = self.file_icons.get(icon_key.as_str()) {
-                            painter.image(
-                                tex.id(),
-                                icon_rect,
-                                Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                                egui::Color32::WHITE,
-                            );
+                        if !drew_thumbnail {
+                            if let Some(tex) = file_icons.get(icon_key) {
+                                painter.image(
+                                    tex.id(),
+                                    icon_rect,
+                                    Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                                    egui::Color32::WHITE,
+                                );
+                            }
                         }
-                    }
 
-                    painter.text(
-                        egui::pos2(icon_x + icon_px + 4.0, rect.center().y),
-                        egui::Align2::LEFT_CENTER,
-                        name,
-                        egui::FontId::proportional(12.0),
-                        text_color,
-                    );
-
-                    // Size column — right side
-                    let size_x = rect.min.x + name_w + 4.0;
-                    painter.text(
-                        egui::pos2(size_x, rect.center().y),
-                        egui::Align2::LEFT_CENTER,
-                        size_str,
-                        egui::FontId::proportional(11.0),
-                        text_color,
-                    );
-
-                    // Modified column
-                    let mod_x = rect.min.x + name_w + 84.0;
-                    painter.text(
-                        egui::pos2(mod_x, rect.center().y),
-                        egui::Align2::LEFT_CENTER,
-                        modified,
-                        egui::FontId::proportional(11.0),
-                        text_color,
-                    );
-                }
+                        painter.text(
+                            egui::pos2(icon_x + icon_px + 4.0, rect.center().y),
+                            egui::Align2::LEFT_CENTER,
+                            &entry.name,
+                            egui::FontId::proportional(12.0),
+                            text_color,
+                        );
 
-                // Start drag - allows dragging unselected items directly
-                if response.drag_started() {
-                    // If dragging an unselected item, select only that item
-                    if !is_selected {
-                        self.selected.clear();
-                        self.selected.insert(*idx);
+                        // Size column — right side
+                        let size_str = if entry.is_dir { "—".into() } else { format_size(entry.size) };
+                        painter.text(
+                            egui::pos2(rect.min.x + name_w + 4.0, rect.center().y),
+                            egui::Align2::LEFT_CENTER,
+                            size_str,
+                            egui::FontId::proportional(11.0),
+                            text_color,
+                        );
+
+                        // Modified column
+                        painter.text(
+                            egui::pos2(rect.min.x + name_w + 84.0, rect.center().y),
+                            egui::Align2::LEFT_CENTER,
+                            &entry.modified,
+                            egui::FontId::proportional(11.0),
+                            text_color,
+                        );
                     }
-                    // Now drag all selected items
-                    let paths: Vec<PathBuf> = self.selected.iter()
-                        .filter_map(|&i| self.entries.get(i).map(|e| e.path.clone()))
-                        .collect();
-                    if !paths.is_empty() {
-                        let count = paths.len();
-                        drag_start = Some((paths, icon_key.clone(), name.clone(), count));
+
+                    // Start drag - allows dragging unselected items directly
+                    if response.drag_started() {
+                        drag_from = Some((idx, is_selected));
                     }
-                }
 
-                // Track hover target for drop (but not if hovering over a dragged item)
-                let is_being_dragged = self.dragging.as_ref()
-                    .map(|paths| paths.iter().any(|p| p == path))
-                    .unwrap_or(false);
-                if self.dragging.is_some() && response.hovered() && *is_dir && !is_being_dragged {
-                    self.drag_hover_idx = Some(*idx);
-                    // Handle drop on folder when mouse released while hovering
-                    if primary_released {
-                        drop_target = Some(path.clone());
+                    // Track hover target for drop (but not if hovering over a dragged item)
+                    let is_being_dragged = dragging.as_ref().is_some_and(|paths| paths.contains(&entry.path));
+                    if dragging.is_some() && response.hovered() && entry.is_dir && !is_being_dragged {
+                        *drag_hover_idx = Some(idx);
+                        // Handle drop on folder when mouse released while hovering
+                        if primary_released {
+                            drop_target = Some(entry.path.clone());
+                        }
                     }
-                }
 
-                if response.clicked() {
-                    click_action = Some((*idx, modifiers.shift, modifiers.command));
-                }
-                if response.double_clicked() {
-                    if *is_dir {
-                        nav_target = Some(path.clone());
-                    } else {
-                        open_target = Some((path.clone(), rect));
+                    if response.double_clicked() {
+                        if entry.is_dir {
+                            nav_target = Some(entry.path.clone());
+                        } else {
+                            open_target = Some((entry.path.clone(), rect));
+                        }
                     }
-                }
-            }
-        });
+                    response
+                },
+            );
 
-        // Start dragging
-        if let Some((paths, icon_key, name, count)) = drag_start {
-            self.apply_drag_start(paths, icon_key, name, count);
+        // Start dragging: an unselected item on its own, otherwise all
+        // the selected items
+        if let Some((idx, was_selected)) = drag_from {
+            if !was_selected {
+                self.selection.select_only(idx);
+            }
+            let paths: Vec<PathBuf> = self.selection.rows.iter()
+                .filter_map(|&i| self.entries.get(i).map(|e| e.path.clone()))
+                .collect();
+            if let Some(entry) = self.entries.get(idx).filter(|_| !paths.is_empty()) {
+                let icon_key = if entry.is_dir { "folder" } else { file_icon_key(&entry.name) };
+                let (icon_key, name, count) = (icon_key.to_string(), entry.name.clone(), paths.len());
+                self.apply_drag_start(paths, icon_key, name, count);
+            }
         }
 
         // Handle drop and clear drag state
         self.handle_drop_and_clear_drag(drop_target, primary_released);
 
-        // Handle click actions after the loop to avoid borrow issues
-        if let Some((idx, shift, cmd)) = click_action {
-            self.handle_click_action(idx, shift, cmd);
-        }
-
         if let Some(path) = nav_target { self.navigate(path); }
         if let Some((path, _rect)) = open_target {
             open_in_slow_app(&path);
@@ -955,7 +1112,7 @@ impl SlowFilesApp {
             for row in chunks {
                 ui.horizontal(|ui| {
                     for (idx, name, icon_key, is_dir, path) in row {
-                        let is_selected = self.selected.contains(idx);
+                        let is_selected = self.selection.is_selected(*idx);
                         let is_drag_hover = self.drag_hover_idx == Some(*idx) && *is_dir;
 
                         let (rect, response) = ui.allocate_exact_size(
@@ -1000,7 +1157,7 @@ impl SlowFilesApp {
                             // For image files, try to use a thumbnail
                             let mut drew_thumbnail = false;
                             if icon_key == "image" && !*is_dir {
-                                if let Some(thumb) = self.get_or_create_thumbnail(ui.ctx(), path) {
+                                if let Some(thumb) = self.thumbnails.get(ui.ctx(), path).cloned() {
                                     // Center the thumbnail (may be smaller than 48x48)
                                     let thumb_size = thumb.size_vec2();
                                     let scale = (icon_size / thumb_size.x.max(thumb_size.y)).min(1.5);
@@ -1054,11 +1211,10 @@ impl SlowFilesApp {
                         if response.drag_started() && self.marquee_start.is_none() {
                             // If dragging an unselected item, select only that item
                             if !is_selected {
-                                self.selected.clear();
-                                self.selected.insert(*idx);
+                                self.selection.select_only(*idx);
                             }
                             // Now drag all selected items
-                            let paths: Vec<PathBuf> = self.selected.iter()
+                            let paths: Vec<PathBuf> = self.selection.rows.iter()
                                 .filter_map(|&i| self.entries.get(i).map(|e| e.path.clone()))
                                 .collect();
                             if !paths.is_empty() {
@@ -1104,7 +1260,7 @@ impl SlowFilesApp {
                         self.marquee_start = Some(pos);
                         // Clear selection unless shift is held
                         if !modifiers.shift {
-                            self.selected.clear();
+                            self.selection.clear();
                         }
                     }
                 }
@@ -1132,7 +1288,7 @@ impl SlowFilesApp {
                 // Select all items that intersect with the marquee
                 for (idx, item_rect) in &self.item_rects {
                     if item_rect.intersects(marquee_rect) {
-                        self.selected.insert(*idx);
+                        self.selection.rows.insert(*idx);
                     }
                 }
             }
@@ -1169,7 +1325,12 @@ impl eframe::App for SlowFilesApp {
             ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
         }
         self.ensure_file_icons(ctx);
+        self.refresh_volumes();
         self.handle_keys(ctx);
+        self.handle_outside_drop(ctx);
+        if self.quick_look.is_some() {
+            self.update_quick_look(ctx);
+        }
 
         let win_action = egui::TopBottomPanel::top("menu").show(ctx, |ui| {
             menu_bar(ui, |ui| {
@@ -1190,10 +1351,17 @@ impl eframe::App for SlowFilesApp {
                         ui.close_menu();
                     }
                     ui.separator();
-                    if ui.add_enabled(!self.selected.is_empty(), egui::Button::new("move to trash  ⌫")).clicked() {
+                    if ui.add_enabled(!self.selection.rows.is_empty(), egui::Button::new("move to trash  ⌫")).clicked() {
                         self.delete_selected();
                         ui.close_menu();
                     }
+                    let volume = self.current_volume();
+                    if ui.add_enabled(volume.is_some() && self.eject_result.is_none(), egui::Button::new("eject  ⌘E")).clicked() {
+                        if let Some(index) = volume {
+                            self.eject(ui.ctx(), index);
+                        }
+                        ui.close_menu();
+                    }
                 });
                 ui.menu_button("view", |ui| {
                     if ui.button(format!("{} show hidden", if self.show_hidden { "✓" } else { " " })).clicked() {
@@ -1238,16 +1406,21 @@ impl eframe::App for SlowFilesApp {
             WindowAction::None => {}
         }
         egui::TopBottomPanel::top("toolbar").show(ctx, |ui| self.render_toolbar(ui));
+        egui::SidePanel::left("sidebar")
+            .resizable(false)
+            .exact_width(120.0)
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).stroke(egui::Stroke::new(1.0, SlowColors::BLACK)).inner_margin(egui::Margin::same(6.0)))
+            .show(ctx, |ui| self.render_sidebar(ui));
         egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
-            let info = if self.selected.is_empty() {
+            let info = if self.selection.rows.is_empty() {
                 format!("{} items", self.entries.len())
-            } else if self.selected.len() == 1 {
-                let idx = *self.selected.iter().next().unwrap();
+            } else if self.selection.rows.len() == 1 {
+                let idx = *self.selection.rows.first().unwrap();
                 if let Some(e) = self.entries.get(idx) {
                     format!("{}  —  {}", e.name, if e.is_dir { "folder".into() } else { format_size(e.size) })
                 } else { String::new() }
             } else {
-                format!("{} items selected", self.selected.len())
+                format!("{} items selected", self.selection.rows.len())
             };
             status_bar(ui, &info);
         });
@@ -1265,43 +1438,10 @@ impl eframe::App for SlowFilesApp {
             }
         });
 
-        if self.show_about {
-            // Calculate max height based on available screen space
-            let screen_rect = ctx.screen_rect();
-            let max_height = (screen_rect.height() - 80.0).max(200.0);
+        self.render_quick_look(ctx);
 
-            let resp = egui::Window::new("about files")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(300.0)
-                .max_height(max_height)
-                .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().max_height(max_height - 60.0).show(ui, |ui| {
-                        ui.vertical_centered(|ui| {
-                            ui.heading("files");
-                            ui.label("version 0.2.2");
-                            ui.add_space(8.0);
-                            ui.label("file manager for slowOS");
-                        });
-                        ui.add_space(8.0);
-                        ui.separator();
-                        ui.add_space(4.0);
-                        ui.label("features:");
-                        ui.label("  browse, sort, multi-select files");
-                        ui.label("  navigate with ⌘+arrows");
-                        ui.add_space(4.0);
-                        ui.label("frameworks:");
-                        ui.label("  egui/eframe (MIT), chrono (MIT)");
-                        ui.label("  open (MIT)");
-                        ui.add_space(8.0);
-                    });
-                    ui.vertical_centered(|ui| {
-                        if ui.button("ok").clicked() { self.show_about = false; }
-                    });
-                });
-            if let Some(r) = &resp {
-                slowcore::dither::draw_window_shadow_large(ctx, r.response.rect);
-            }
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
 
         if self.show_shortcuts {
@@ -1322,6 +1462,7 @@ impl eframe::App for SlowFilesApp {
                         ui.label(egui::RichText::new("Navigation").strong());
                         ui.separator();
                         shortcut_row(ui, "Enter", "Open selected item");
+                        shortcut_row(ui, "Space", "Preview selected item");
                         shortcut_row(ui, "Backspace", "Go to parent folder");
                         shortcut_row(ui, "⌘↑", "Go up one folder");
                         shortcut_row(ui, "⌘←", "Go back");
@@ -1343,6 +1484,7 @@ impl eframe::App for SlowFilesApp {
                         shortcut_row(ui, "⇧⌘N", "New folder");
                         shortcut_row(ui, "⌫", "Move to trash");
                         shortcut_row(ui, "⌘Z", "Undo delete");
+                        shortcut_row(ui, "⌘E", "Eject drive");
                         ui.add_space(8.0);
 
                         ui.label(egui::RichText::new("View").strong());
@@ -1515,6 +1657,7 @@ fn find_slow_binary(name: &str) -> Option<PathBuf> {
         paths.push(dir.clone());
     }
     paths.push(PathBuf::from("/usr/bin"));
+    paths.push(app_registry::applications_dir());
     if let Some(ref dir) = exe_dir {
         let mut search_dir = Some(dir.clone());
         while let Some(d) = search_dir {
@@ -1563,15 +1706,13 @@ fn open_in_slow_app(path: &PathBuf) {
             .unwrap_or_default()
     };
 
-    if let Some(app_name) = slow_app_for_ext(&ext) {
-        if let Some(bin_path) = find_slow_binary(app_name) {
-            use std::sync::atomic::{AtomicU32, Ordering};
-            static CASCADE: AtomicU32 = AtomicU32::new(0);
-            let offset = CASCADE.fetch_add(1, Ordering::Relaxed) % 10;
+    // Kinds of file no built-in app opens may have an app from slowStore
+    let app = slow_app_for_ext(&ext).map(str::to_string).or_else(|| app_registry::app_for_extension(&ext));
+    if let Some(app_name) = app {
+        if let Some(bin_path) = find_slow_binary(&app_name) {
             let _ = std::process::Command::new(bin_path)
                 .arg(path.to_string_lossy().as_ref())
                 .env("SLOWOS_MANAGED", "1")
-                .env("SLOWOS_CASCADE", offset.to_string())
                 .stdin(std::process::Stdio::null())
                 .stdout(std::process::Stdio::inherit())
                 .stderr(std::process::Stdio::inherit())
@@ -1,20 +1,15 @@
 mod app;
 use app::SlowFilesApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 use std::path::PathBuf;
 
 fn main() -> eframe::Result<()> {
     let start_dir = std::env::args().nth(1).map(PathBuf::from);
 
-    let mut viewport = egui::ViewportBuilder::default()
-        .with_inner_size([560.0, 400.0])
+    let viewport = window_layout::viewport("slowfiles", Placement::RememberLast, [560.0, 400.0])
         .with_title("files");
 
-    // Apply cascade position for window staggering
-    if let Some(pos) = slowcore::cascade_position() {
-        viewport = viewport.with_position(pos);
-    }
-
     let options = NativeOptions {
         viewport,
         ..Default::default()
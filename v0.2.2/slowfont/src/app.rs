@@ -0,0 +1,744 @@
+//! slowFont application
+
+use crate::bdf;
+use crate::font::{Glyph, PixelFont, BASIC_CHARS, MAX_HEIGHT, MAX_WIDTH};
+use crate::ttf;
+use egui::{Align2, CentralPanel, Context, Key, Painter, Pos2, Rect, Sense, SidePanel, Stroke, TopBottomPanel, Vec2};
+use slowcore::repaint::RepaintController;
+use slowcore::storage::{self, documents_dir};
+use slowcore::theme::{self, consume_special_keys, menu_bar, SlowColors};
+use slowcore::undo::{self, UndoAction, UndoStack};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, FileDialog, FileDialogResult, WindowAction};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowFont",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "a pixel font editor for slowOS",
+    features: &["glyph grid editor", "live preview and kerning table", "BDF import", "install as the system font"],
+    ..AboutInfo::EMPTY
+};
+
+const DEFAULT_PREVIEW: &str = "The quick brown fox jumps over the lazy dog.";
+
+/// Room kept under the grid for the preview
+const PREVIEW_HEIGHT: f32 = 150.0;
+
+enum Dialog {
+    NewFont(String),
+    RenameFont(String),
+    DeleteFont,
+    Metrics { height: usize, ascent: usize },
+    /// A character outside the basic set, typed or as U+XXXX
+    AddGlyph(String),
+}
+
+pub struct SlowFontApp {
+    fonts: Vec<PixelFont>,
+    selected: Option<usize>,
+    glyph: char,
+    /// Whether the stroke being drawn lights pixels or clears them
+    stroke: Option<bool>,
+    copied: Option<Glyph>,
+    history: UndoStack<PixelFont>,
+    preview: String,
+    preview_scale: usize,
+    show_kerning: bool,
+    /// The pair being added to the kerning table
+    new_pair: (String, String),
+    dialog: Option<Dialog>,
+    file_dialog: Option<FileDialog>,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowFontApp {
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        let fonts = PixelFont::load_all();
+        let selected = if fonts.is_empty() { None } else { Some(0) };
+        Self {
+            fonts,
+            selected,
+            glyph: 'A',
+            stroke: None,
+            copied: None,
+            history: UndoStack::new(100),
+            preview: DEFAULT_PREVIEW.into(),
+            preview_scale: 2,
+            show_kerning: false,
+            new_pair: (String::new(), String::new()),
+            dialog: None,
+            file_dialog: None,
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        }
+    }
+
+    fn font(&self) -> Option<&PixelFont> {
+        self.selected.and_then(|i| self.fonts.get(i))
+    }
+
+    fn font_mut(&mut self) -> Option<&mut PixelFont> {
+        self.selected.and_then(|i| self.fonts.get_mut(i))
+    }
+
+    fn save_font(&mut self) {
+        let Some(font) = self.font() else { return };
+        if let Err(e) = font.save() {
+            self.message = Some(format!("could not save font: {}", e));
+        }
+    }
+
+    /// Note the font as it is for undo, then change it and save
+    fn edit(&mut self, change: impl FnOnce(&mut PixelFont)) {
+        let Some(i) = self.selected.filter(|&i| i < self.fonts.len()) else { return };
+        self.history.save(&self.fonts[i]);
+        change(&mut self.fonts[i]);
+        self.save_font();
+    }
+
+    /// Change the glyph being edited, drawing it in if it isn't yet
+    fn edit_glyph(&mut self, change: impl FnOnce(&mut Glyph)) {
+        let c = self.glyph;
+        self.edit(|font| {
+            let blank = font.blank_glyph();
+            change(font.glyphs.entry(c).or_insert(blank));
+        });
+    }
+
+    /// Add `font` under a name no other font has, and open it
+    fn add_font(&mut self, mut font: PixelFont) {
+        let base = if font.name.is_empty() { "font".to_string() } else { font.name.clone() };
+        font.name = base.clone();
+        let mut n = 1;
+        while self.fonts.iter().any(|f| f.name.eq_ignore_ascii_case(&font.name)) {
+            n += 1;
+            font.name = format!("{} {}", base, n);
+        }
+        let name = font.name.clone();
+        self.fonts.push(font);
+        self.select(&name);
+        self.save_font();
+    }
+
+    /// Keep the fonts in order and `name` open
+    fn select(&mut self, name: &str) {
+        self.fonts.sort_by_key(|f| f.name.to_lowercase());
+        self.selected = self.fonts.iter().position(|f| f.name == name);
+        self.history.clear();
+        self.stroke = None;
+    }
+
+    fn import(&mut self, path: std::path::PathBuf) {
+        match bdf::import(&path) {
+            Ok(font) => {
+                let count = font.glyphs.len();
+                self.add_font(font);
+                self.message = Some(format!("imported {} characters", count));
+            }
+            Err(e) => self.message = Some(format!("could not import: {}", e)),
+        }
+    }
+
+    fn install(&mut self, as_system_font: bool) {
+        let Some(font) = self.font() else { return };
+        match ttf::install(font) {
+            Ok(_) if as_system_font => {
+                theme::set_preferred_system_font(Some(&ttf::file_name(font)));
+                self.message = Some("installed as the system font; apps use it from their next start".into());
+            }
+            Ok(path) => self.message = Some(format!("installed {}", path.display())),
+            Err(e) => self.message = Some(format!("could not install: {}", e)),
+        }
+    }
+
+    fn undo(&mut self) {
+        let Some(i) = self.selected.filter(|&i| i < self.fonts.len()) else { return };
+        if self.history.undo(&mut self.fonts[i]) {
+            self.save_font();
+        }
+    }
+
+    fn redo(&mut self) {
+        let Some(i) = self.selected.filter(|&i| i < self.fonts.len()) else { return };
+        if self.history.redo(&mut self.fonts[i]) {
+            self.save_font();
+        }
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        if self.dialog.is_some() || self.file_dialog.is_some() || self.show_about || self.font().is_none() {
+            return;
+        }
+        match undo::take_shortcut(ctx) {
+            Some(UndoAction::Undo) => self.undo(),
+            Some(UndoAction::Redo) => self.redo(),
+            None => {}
+        }
+        if ctx.memory(|m| m.focused().is_some()) {
+            return;
+        }
+        let (cmd, shift) = ctx.input(|i| (i.modifiers.command, i.modifiers.shift));
+        if cmd {
+            if ctx.input(|i| i.key_pressed(Key::C)) {
+                self.copy_glyph();
+            } else if ctx.input(|i| i.key_pressed(Key::V)) {
+                self.paste_glyph();
+            } else if ctx.input(|i| i.key_pressed(Key::E)) {
+                self.install(false);
+            }
+            return;
+        }
+        // Arrows move the drawing; with shift they step through glyphs
+        let arrows = [(Key::ArrowLeft, -1, 0), (Key::ArrowRight, 1, 0), (Key::ArrowUp, 0, -1), (Key::ArrowDown, 0, 1)];
+        for (key, dx, dy) in arrows {
+            if ctx.input(|i| i.key_pressed(key)) {
+                if shift {
+                    self.step_glyph(dx + dy);
+                } else {
+                    self.edit_glyph(|g| g.shift(dx, dy));
+                }
+            }
+        }
+        // Any other key typed opens its glyph
+        let typed: Option<char> = ctx.input(|i| {
+            i.events.iter().find_map(|e| match e {
+                egui::Event::Text(t) => t.chars().next(),
+                _ => None,
+            })
+        });
+        if let Some(c) = typed {
+            self.glyph = c;
+        }
+    }
+
+    /// Every character in the list: the basic set and any others drawn
+    fn glyph_list(&self) -> Vec<char> {
+        let mut chars: Vec<char> = BASIC_CHARS.collect();
+        if let Some(font) = self.font() {
+            chars.extend(font.glyphs.keys().filter(|&c| !BASIC_CHARS.contains(c)));
+        }
+        chars
+    }
+
+    fn step_glyph(&mut self, by: i32) {
+        let chars = self.glyph_list();
+        let at = chars.iter().position(|&c| c == self.glyph).unwrap_or(0) as i32;
+        self.glyph = chars[(at + by).rem_euclid(chars.len() as i32) as usize];
+    }
+
+    fn copy_glyph(&mut self) {
+        self.copied = self.font().and_then(|f| f.glyphs.get(&self.glyph)).cloned();
+    }
+
+    fn paste_glyph(&mut self) {
+        let Some(copied) = self.copied.clone() else { return };
+        self.edit_glyph(|g| {
+            let mut pasted = copied;
+            // From a font of another height, trimmed or padded at the bottom
+            pasted.rows.resize(g.rows.len(), 0);
+            *g = pasted;
+        });
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let has_font = self.font().is_some();
+        let system_font = theme::preferred_system_font();
+        let is_system_font = self.font().is_some_and(|f| system_font.as_deref() == Some(ttf::file_name(f).as_str()));
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("font", |ui| {
+                    if ui.button("new font…").clicked() {
+                        self.dialog = Some(Dialog::NewFont(String::new()));
+                        ui.close_menu();
+                    }
+                    if ui.button("import BDF…").clicked() {
+                        self.file_dialog = Some(FileDialog::open(documents_dir()).title("import font").filter(&["bdf"]));
+                        ui.close_menu();
+                    }
+                    if !self.fonts.is_empty() {
+                        ui.separator();
+                    }
+                    let mut picked = None;
+                    for (i, font) in self.fonts.iter().enumerate() {
+                        if ui.radio(self.selected == Some(i), &font.name).clicked() {
+                            picked = Some(font.name.clone());
+                            ui.close_menu();
+                        }
+                    }
+                    if let Some(name) = picked {
+                        self.select(&name);
+                    }
+                    ui.separator();
+                    if ui.add_enabled(has_font, egui::Button::new("size and baseline…")).clicked() {
+                        self.dialog = self.font().map(|f| Dialog::Metrics { height: f.height, ascent: f.ascent });
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(has_font, egui::Button::new("rename…")).clicked() {
+                        self.dialog = self.font().map(|f| Dialog::RenameFont(f.name.clone()));
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(has_font, egui::Button::new("delete font…")).clicked() {
+                        self.dialog = Some(Dialog::DeleteFont);
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("edit", |ui| {
+                    if ui.add_enabled(self.history.can_undo(), egui::Button::new("undo         ⌘Z")).clicked() {
+                        self.undo();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.history.can_redo(), egui::Button::new("redo        ⇧⌘Z")).clicked() {
+                        self.redo();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.add_enabled(has_font, egui::Button::new("copy glyph   ⌘C")).clicked() {
+                        self.copy_glyph();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(has_font && self.copied.is_some(), egui::Button::new("paste glyph  ⌘V")).clicked() {
+                        self.paste_glyph();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.add_enabled(has_font, egui::Button::new("clear glyph")).clicked() {
+                        self.edit_glyph(|g| g.rows.iter_mut().for_each(|r| *r = 0));
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(has_font, egui::Button::new("invert glyph")).clicked() {
+                        self.edit_glyph(Glyph::invert);
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(has_font, egui::Button::new("other character…")).clicked() {
+                        self.dialog = Some(Dialog::AddGlyph(String::new()));
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("export", |ui| {
+                    if ui.add_enabled(has_font, egui::Button::new("install font   ⌘E")).clicked() {
+                        self.install(false);
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(has_font && !is_system_font, egui::Button::new("use as system font")).clicked() {
+                        self.install(true);
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(system_font.is_some(), egui::Button::new("use the standard font")).clicked() {
+                        theme::set_preferred_system_font(None);
+                        self.message = Some("apps go back to IBM Plex Sans from their next start".into());
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("view", |ui| {
+                    if ui.checkbox(&mut self.show_kerning, "kerning table").clicked() {
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    for scale in 1..=4 {
+                        if ui.radio(self.preview_scale == scale, format!("preview at {}×", scale)).clicked() {
+                            self.preview_scale = scale;
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowfont", "slowFont");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_glyph_list(&mut self, ui: &mut egui::Ui) {
+        let Some(font) = self.font() else { return };
+        let mut picked = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                ui.spacing_mut().item_spacing = Vec2::splat(2.0);
+                for c in self.glyph_list() {
+                    let label = if c == ' ' { "␣".to_string() } else { c.to_string() };
+                    let text = if font.glyphs.get(&c).is_some_and(|g| !g.is_blank()) {
+                        egui::RichText::new(label).strong()
+                    } else {
+                        egui::RichText::new(label).weak()
+                    };
+                    let button = egui::SelectableLabel::new(self.glyph == c, text);
+                    if ui.add_sized(Vec2::splat(22.0), button).clicked() {
+                        picked = Some(c);
+                    }
+                }
+            });
+        });
+        if let Some(c) = picked {
+            self.glyph = c;
+        }
+    }
+
+    fn draw_editor(&mut self, ui: &mut egui::Ui) {
+        let Some(font) = self.font() else {
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 3.0);
+                if ui.button("new font").clicked() {
+                    self.dialog = Some(Dialog::NewFont(String::new()));
+                }
+                ui.weak("or import one from a BDF file");
+            });
+            return;
+        };
+        let glyph = font.glyphs.get(&self.glyph).cloned().unwrap_or_else(|| font.blank_glyph());
+        let (height, ascent) = (font.height, font.ascent);
+
+        let (narrower, wider) = ui
+            .horizontal(|ui| {
+                ui.label(egui::RichText::new(self.glyph.to_string()).strong().size(16.0));
+                ui.weak(format!("U+{:04X}", self.glyph as u32));
+                ui.add_space(12.0);
+                let narrower = ui.add_enabled(glyph.width > 1, egui::Button::new("−")).clicked();
+                ui.label(format!("{} × {}", glyph.width, height));
+                let wider = ui.add_enabled(glyph.width < MAX_WIDTH, egui::Button::new("+")).clicked();
+                (narrower, wider)
+            })
+            .inner;
+        ui.add_space(6.0);
+
+        let room = Vec2::new(ui.available_width() - 8.0, ui.available_height() - PREVIEW_HEIGHT);
+        let cell = (room.x / glyph.width as f32).min(room.y / height as f32).clamp(6.0, 28.0).floor();
+        let (rect, response) = ui.allocate_exact_size(Vec2::new(cell * glyph.width as f32, cell * height as f32), Sense::click_and_drag());
+        let painter = ui.painter_at(rect.expand(2.0));
+        for y in 0..height {
+            for x in 0..glyph.width {
+                let pixel = Rect::from_min_size(rect.min + Vec2::new(x as f32, y as f32) * cell, Vec2::splat(cell));
+                if glyph.get(x, y) {
+                    painter.rect_filled(pixel, 0.0, SlowColors::BLACK);
+                } else {
+                    painter.rect_stroke(pixel, 0.0, Stroke::new(0.5, SlowColors::BLACK));
+                }
+            }
+        }
+        let baseline = rect.top() + ascent as f32 * cell;
+        painter.line_segment([Pos2::new(rect.left() - 2.0, baseline), Pos2::new(rect.right() + 2.0, baseline)], Stroke::new(2.0, SlowColors::BLACK));
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+
+        // Pressing on a pixel flips it, and dragging on paints the same
+        let at = |pos: Pos2| {
+            let p = (pos - rect.min) / cell;
+            (rect.contains(pos)).then_some((p.x as usize, p.y as usize))
+        };
+        let pointer = ui.input(|i| i.pointer.interact_pos());
+        if response.hovered() && ui.input(|i| i.pointer.primary_pressed()) {
+            if let Some((x, y)) = pointer.and_then(at) {
+                let on = !glyph.get(x, y);
+                self.stroke = Some(on);
+                self.edit_glyph(|g| g.set(x, y, on));
+            }
+        } else if let Some(on) = self.stroke {
+            if ui.input(|i| i.pointer.primary_down()) {
+                let c = self.glyph;
+                if let (Some((x, y)), Some(font)) = (pointer.and_then(at), self.font_mut()) {
+                    if let Some(g) = font.glyphs.get_mut(&c) {
+                        g.set(x, y, on);
+                    }
+                }
+            } else {
+                self.stroke = None;
+                self.save_font();
+            }
+        }
+        if narrower || wider {
+            let width = if wider { glyph.width + 1 } else { glyph.width - 1 };
+            self.edit_glyph(|g| g.set_width(width));
+        }
+
+        ui.add_space(12.0);
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("preview:");
+            ui.add(egui::TextEdit::singleline(&mut self.preview).desired_width(f32::INFINITY));
+        });
+        ui.add_space(8.0);
+        let Some(font) = self.font() else { return };
+        for scale in [self.preview_scale, 1] {
+            let size = Vec2::new(ui.available_width(), (font.height * scale) as f32);
+            let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+            draw_text(&ui.painter_at(rect), font, &self.preview, rect.min, scale as f32);
+            ui.add_space(8.0);
+            if self.preview_scale == 1 {
+                break;
+            }
+        }
+    }
+
+    fn draw_kerning(&mut self, ui: &mut egui::Ui) {
+        let Some(font) = self.font() else { return };
+        ui.label(egui::RichText::new("kerning").strong());
+        ui.weak("pixels added between a pair");
+        ui.add_space(6.0);
+        let mut change = None;
+        egui::ScrollArea::vertical().max_height(ui.available_height() - 60.0).show(ui, |ui| {
+            if font.kerning.is_empty() {
+                ui.weak("no pairs yet");
+            }
+            for k in &font.kerning {
+                ui.horizontal(|ui| {
+                    ui.monospace(format!("{}{}", k.left, k.right));
+                    if ui.small_button("−").clicked() {
+                        change = Some((k.left, k.right, k.adjust - 1));
+                    }
+                    ui.label(format!("{:+}", k.adjust));
+                    if ui.small_button("+").clicked() {
+                        change = Some((k.left, k.right, k.adjust + 1));
+                    }
+                    if ui.small_button("remove").clicked() {
+                        change = Some((k.left, k.right, 0));
+                    }
+                });
+            }
+        });
+        ui.separator();
+        ui.horizontal(|ui| {
+            let (left, right) = &mut self.new_pair;
+            ui.add(egui::TextEdit::singleline(left).desired_width(20.0).char_limit(1));
+            ui.add(egui::TextEdit::singleline(right).desired_width(20.0).char_limit(1));
+            let pair = (left.chars().next(), right.chars().next());
+            if ui.add_enabled(pair.0.is_some() && pair.1.is_some(), egui::Button::new("add")).clicked() {
+                if let (Some(l), Some(r)) = pair {
+                    change = Some((l, r, -1));
+                }
+            }
+        });
+        if let Some((left, right, adjust)) = change {
+            self.edit(|f| f.set_kern(left, right, adjust));
+            self.new_pair = (String::new(), String::new());
+        }
+    }
+
+    fn draw_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.dialog else { return };
+        let title = match dialog {
+            Dialog::NewFont(_) => "new font",
+            Dialog::RenameFont(_) => "rename font",
+            Dialog::DeleteFont => "delete font",
+            Dialog::Metrics { .. } => "size and baseline",
+            Dialog::AddGlyph(_) => "other character",
+        };
+        let mut ok = false;
+        let mut cancel = false;
+        let resp = egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                let action = match dialog {
+                    Dialog::NewFont(name) | Dialog::RenameFont(name) => {
+                        ui.label("name:");
+                        let field = ui.text_edit_singleline(name);
+                        if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                            ok = true;
+                        }
+                        "save"
+                    }
+                    Dialog::DeleteFont => {
+                        ui.label("delete this font and all its glyphs?");
+                        ui.weak("an installed copy stays installed");
+                        "delete"
+                    }
+                    Dialog::Metrics { height, ascent } => {
+                        egui::Grid::new("metrics").num_columns(2).show(ui, |ui| {
+                            ui.label("height:");
+                            ui.add(egui::DragValue::new(height).clamp_range(1..=MAX_HEIGHT).suffix(" px"));
+                            ui.end_row();
+                            ui.label("above baseline:");
+                            ui.add(egui::DragValue::new(ascent).clamp_range(0..=*height).suffix(" px"));
+                            ui.end_row();
+                        });
+                        ui.weak("glyphs keep their place on the baseline");
+                        "save"
+                    }
+                    Dialog::AddGlyph(text) => {
+                        ui.label("character, or its code as U+00E9:");
+                        let field = ui.text_edit_singleline(text);
+                        if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                            ok = true;
+                        }
+                        "open"
+                    }
+                };
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        cancel = true;
+                    }
+                    if ui.button(action).clicked() {
+                        ok = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+
+        if cancel {
+            self.dialog = None;
+        } else if ok {
+            if let Some(dialog) = self.dialog.take() {
+                self.apply(dialog);
+            }
+        }
+    }
+
+    fn apply(&mut self, dialog: Dialog) {
+        let selected = self.selected.filter(|&i| i < self.fonts.len());
+        match (dialog, selected) {
+            (Dialog::NewFont(name), _) if !storage::clean_name(&name).is_empty() => self.add_font(PixelFont::new(&name)),
+            (Dialog::RenameFont(name), Some(i)) => match self.fonts[i].rename(&name) {
+                Ok(()) => {
+                    let name = self.fonts[i].name.clone();
+                    self.select(&name);
+                }
+                Err(e) => self.message = Some(format!("could not rename: {}", e)),
+            },
+            (Dialog::DeleteFont, Some(i)) => match self.fonts[i].delete() {
+                Ok(()) => {
+                    self.fonts.remove(i);
+                    self.selected = if self.fonts.is_empty() { None } else { Some(i.min(self.fonts.len() - 1)) };
+                    self.history.clear();
+                }
+                Err(e) => self.message = Some(format!("could not delete font: {}", e)),
+            },
+            (Dialog::Metrics { height, ascent }, Some(_)) => self.edit(|f| f.set_metrics(height, ascent)),
+            (Dialog::AddGlyph(text), _) => match parse_char(&text) {
+                Some(c) => self.glyph = c,
+                None => self.message = Some("type one character, or a code like U+00E9".into()),
+            },
+            _ => {}
+        }
+    }
+
+    fn draw_file_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.file_dialog else { return };
+        match dialog.show(ctx) {
+            FileDialogResult::Picked(path) => {
+                self.file_dialog = None;
+                self.import(path);
+            }
+            FileDialogResult::Cancelled => self.file_dialog = None,
+            FileDialogResult::Pending => {}
+        }
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+/// "é" or "U+00E9"
+fn parse_char(text: &str) -> Option<char> {
+    let text = text.trim();
+    let code = text.strip_prefix("U+").or_else(|| text.strip_prefix("u+"));
+    match code {
+        Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+        None => {
+            let mut chars = text.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        }
+    }
+}
+
+/// Set `text` in the pixel font with its top left at `origin`, each pixel
+/// `scale` points square. Characters the font lacks show as empty boxes.
+fn draw_text(painter: &Painter, font: &PixelFont, text: &str, origin: Pos2, scale: f32) {
+    let mut x = 0i32;
+    let mut previous = None;
+    for c in text.chars() {
+        if let Some(p) = previous {
+            x += font.kern(p, c);
+        }
+        previous = Some(c);
+        let left = origin.x + x as f32 * scale;
+        match font.glyphs.get(&c) {
+            Some(glyph) => {
+                for (y, row) in glyph.rows.iter().enumerate().filter(|(_, &r)| r != 0) {
+                    for gx in (0..glyph.width).filter(|&gx| row >> gx & 1 == 1) {
+                        let min = Pos2::new(left + gx as f32 * scale, origin.y + y as f32 * scale);
+                        painter.rect_filled(Rect::from_min_size(min, Vec2::splat(scale)), 0.0, SlowColors::BLACK);
+                    }
+                }
+                x += glyph.width as i32;
+            }
+            None => {
+                let width = font.default_width();
+                let top = origin.y + (font.ascent as f32 * 0.3).floor() * scale;
+                let box_rect = Rect::from_min_max(Pos2::new(left, top), Pos2::new(left + (width - 1) as f32 * scale, origin.y + font.ascent as f32 * scale));
+                painter.rect_stroke(box_rect.shrink(0.5), 0.0, Stroke::new(1.0, SlowColors::BLACK));
+                x += width as i32;
+            }
+        }
+    }
+}
+
+impl eframe::App for SlowFontApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowfont") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.handle_keys(ctx);
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| match self.font() {
+                Some(font) => format!(
+                    "{}  |  {} px  |  {} glyphs  |  {} kerning pairs",
+                    font.name,
+                    font.height,
+                    font.glyphs.values().filter(|g| !g.is_blank()).count(),
+                    font.kerning.len()
+                ),
+                None => format!("{} fonts", self.fonts.len()),
+            });
+            status_bar(ui, &status);
+        });
+
+        if self.font().is_some() {
+            SidePanel::left("glyphs")
+                .resizable(false)
+                .exact_width(190.0)
+                .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(6.0)))
+                .show(ctx, |ui| self.draw_glyph_list(ui));
+        }
+        if self.show_kerning && self.font().is_some() {
+            SidePanel::right("kerning")
+                .resizable(false)
+                .exact_width(170.0)
+                .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(6.0)))
+                .show(ctx, |ui| self.draw_kerning(ui));
+        }
+
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(12.0)))
+            .show(ctx, |ui| self.draw_editor(ui));
+
+        self.draw_dialog(ctx);
+        self.draw_file_dialog(ctx);
+        self.draw_about(ctx);
+        self.repaint.end_frame(ctx);
+    }
+}
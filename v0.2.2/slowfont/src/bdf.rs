@@ -0,0 +1,173 @@
+//! Reading X11 BDF bitmap fonts
+//!
+//! A BDF file is plain text: font-wide properties, then for each
+//! character its code (ENCODING), how far the pen moves (DWIDTH), the box
+//! its bitmap fills relative to the baseline (BBX) and the bitmap itself,
+//! a row of hex per line, leftmost pixel in the top bit.
+
+use crate::font::{Glyph, PixelFont, MAX_HEIGHT, MAX_WIDTH};
+use slowcore::storage::clean_name;
+use std::path::Path;
+
+/// One character as the file gives it
+#[derive(Default)]
+struct BdfChar {
+    code: Option<char>,
+    advance: Option<i32>,
+    /// Width, height, left offset and bottom offset from the baseline
+    bbx: (i32, i32, i32, i32),
+    bitmap: Vec<Vec<u8>>,
+}
+
+/// The font in the BDF file at `path`, named after the file
+pub fn import(path: &Path) -> Result<PixelFont, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    parse(&text, &name)
+}
+
+fn numbers(rest: &str) -> Vec<i32> {
+    rest.split_whitespace().filter_map(|n| n.parse().ok()).collect()
+}
+
+pub fn parse(text: &str, name: &str) -> Result<PixelFont, String> {
+    let mut lines = text.lines().map(str::trim);
+    if !lines.next().is_some_and(|l| l.starts_with("STARTFONT")) {
+        return Err("not a BDF font".into());
+    }
+    let mut bounds = (0, 0, 0, 0);
+    let mut ascent = None;
+    let mut descent = None;
+    let mut family = None;
+    let mut chars = Vec::new();
+    let mut current: Option<BdfChar> = None;
+    let mut in_bitmap = false;
+
+    for line in lines {
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        if in_bitmap {
+            if keyword == "ENDCHAR" {
+                in_bitmap = false;
+                chars.extend(current.take());
+            } else if let Some(c) = &mut current {
+                let bytes = (0..line.len() / 2).filter_map(|i| u8::from_str_radix(line.get(i * 2..i * 2 + 2)?, 16).ok()).collect();
+                c.bitmap.push(bytes);
+            }
+            continue;
+        }
+        match keyword {
+            "FONTBOUNDINGBOX" => {
+                if let [w, h, x, y] = numbers(rest)[..] {
+                    bounds = (w, h, x, y);
+                }
+            }
+            "FONT_ASCENT" => ascent = numbers(rest).first().copied(),
+            "FONT_DESCENT" => descent = numbers(rest).first().copied(),
+            "FAMILY_NAME" => family = Some(rest.trim_matches('"').to_string()),
+            "STARTCHAR" => current = Some(BdfChar { bbx: bounds, ..Default::default() }),
+            "ENCODING" => {
+                if let Some(c) = &mut current {
+                    c.code = numbers(rest).first().and_then(|&n| u32::try_from(n).ok()).and_then(char::from_u32);
+                }
+            }
+            "DWIDTH" => {
+                if let Some(c) = &mut current {
+                    c.advance = numbers(rest).first().copied();
+                }
+            }
+            "BBX" => {
+                if let (Some(c), &[w, h, x, y]) = (&mut current, &numbers(rest)[..]) {
+                    c.bbx = (w, h, x, y);
+                }
+            }
+            "BITMAP" => in_bitmap = current.is_some(),
+            "ENDCHAR" => chars.extend(current.take()),
+            _ => {}
+        }
+    }
+
+    let ascent = ascent.unwrap_or(bounds.1 + bounds.3).max(0);
+    let descent = descent.unwrap_or(-bounds.3).max(0);
+    let height = (ascent + descent) as usize;
+    if height == 0 || height > MAX_HEIGHT {
+        return Err(format!("fonts can be 1 to {} pixels tall; this one is {}", MAX_HEIGHT, height));
+    }
+    let mut font = PixelFont::new(family.as_deref().filter(|f| !f.is_empty()).unwrap_or(name));
+    font.name = clean_name(&font.name);
+    font.height = height;
+    font.ascent = ascent as usize;
+    font.glyphs.clear();
+
+    for c in chars {
+        let Some(code) = c.code else { continue };
+        let (w, h, left, bottom) = c.bbx;
+        let width = c.advance.unwrap_or(w + left).clamp(1, MAX_WIDTH as i32) as usize;
+        let mut glyph = Glyph::blank(width, height);
+        // The bitmap's top row sits this many rows below the font's top
+        let top = ascent - (bottom + h);
+        for (row, bytes) in c.bitmap.iter().enumerate().take(h.max(0) as usize) {
+            for col in 0..w.max(0) as usize {
+                let lit = bytes.get(col / 8).is_some_and(|b| b >> (7 - col % 8) & 1 == 1);
+                let (x, y) = (left + col as i32, top + row as i32);
+                if lit && x >= 0 && y >= 0 {
+                    glyph.set(x as usize, y as usize, true);
+                }
+            }
+        }
+        font.glyphs.insert(code, glyph);
+    }
+    if font.glyphs.is_empty() {
+        return Err("no characters found in the font".into());
+    }
+    Ok(font)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bdf() {
+        let bdf = "STARTFONT 2.1
+FONT -slow-tiny-medium-r-normal--6-60-75-75-c-40-iso10646-1
+SIZE 6 75 75
+FONTBOUNDINGBOX 4 6 0 -1
+STARTPROPERTIES 3
+FONT_ASCENT 5
+FONT_DESCENT 1
+FAMILY_NAME \"Tiny\"
+ENDPROPERTIES
+CHARS 2
+STARTCHAR A
+ENCODING 65
+SWIDTH 666 0
+DWIDTH 4 0
+BBX 3 5 0 0
+BITMAP
+40
+A0
+E0
+A0
+A0
+ENDCHAR
+STARTCHAR comma
+ENCODING 44
+DWIDTH 2 0
+BBX 1 2 0 -1
+BITMAP
+80
+80
+ENDCHAR
+ENDFONT
+";
+        let font = parse(bdf, "tiny.bdf").unwrap();
+        assert_eq!((font.name.as_str(), font.height, font.ascent), ("Tiny", 6, 5));
+        let a = &font.glyphs[&'A'];
+        assert_eq!(a.width, 4);
+        assert_eq!(a.rows, vec![0b010, 0b101, 0b111, 0b101, 0b101, 0]);
+        // The comma sits on the baseline and hangs one row below it
+        let comma = &font.glyphs[&','];
+        assert_eq!(comma.rows, vec![0, 0, 0, 0, 1, 1]);
+        assert!(parse("hello", "x").is_err());
+    }
+}
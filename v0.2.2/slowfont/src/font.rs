@@ -0,0 +1,207 @@
+//! Pixel fonts, kept as one JSON file per font in ~/.config/slowfont/fonts
+//!
+//! Every glyph is a grid of pixels as tall as the font and as wide as it
+//! likes; its width is also how far the pen moves on. The top `ascent`
+//! rows sit above the baseline and the rest hang below it. Kerning pairs
+//! move the second character of a pair left or right by whole pixels.
+
+use serde::{Deserialize, Serialize};
+use slowcore::storage::{clean_name, config_dir};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+pub const MAX_WIDTH: usize = 32;
+pub const MAX_HEIGHT: usize = 48;
+
+/// Glyphs listed in the editor whether drawn yet or not
+pub const BASIC_CHARS: std::ops::RangeInclusive<char> = ' '..='~';
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Glyph {
+    pub width: usize,
+    /// A row per pixel line, top first; bit x is column x
+    pub rows: Vec<u32>,
+}
+
+impl Glyph {
+    pub fn blank(width: usize, height: usize) -> Self {
+        Self { width: width.min(MAX_WIDTH), rows: vec![0; height] }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && self.rows.get(y).is_some_and(|row| row >> x & 1 == 1)
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, on: bool) {
+        if x >= self.width {
+            return;
+        }
+        if let Some(row) = self.rows.get_mut(y) {
+            if on { *row |= 1 << x } else { *row &= !(1 << x) }
+        }
+    }
+
+    pub fn is_blank(&self) -> bool {
+        self.rows.iter().all(|&row| row == 0)
+    }
+
+    fn mask(&self) -> u32 {
+        if self.width >= 32 { u32::MAX } else { (1 << self.width) - 1 }
+    }
+
+    /// Narrow or widen from the right edge
+    pub fn set_width(&mut self, width: usize) {
+        self.width = width.clamp(1, MAX_WIDTH);
+        let mask = self.mask();
+        for row in &mut self.rows {
+            *row &= mask;
+        }
+    }
+
+    /// Move the drawing `dx` pixels right and `dy` down; what goes past
+    /// an edge is lost
+    pub fn shift(&mut self, dx: i32, dy: i32) {
+        let mask = self.mask();
+        let height = self.rows.len() as i32;
+        let rows: Vec<u32> = (0..height)
+            .map(|y| {
+                let from = y - dy;
+                let row = if (0..height).contains(&from) { self.rows[from as usize] } else { 0 };
+                let moved = if dx >= 0 { row.checked_shl(dx as u32) } else { row.checked_shr(-dx as u32) };
+                moved.unwrap_or(0) & mask
+            })
+            .collect();
+        self.rows = rows;
+    }
+
+    pub fn invert(&mut self) {
+        let mask = self.mask();
+        for row in &mut self.rows {
+            *row = !*row & mask;
+        }
+    }
+}
+
+/// Space between a pair of characters, in pixels; negative moves the
+/// second one closer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Kern {
+    pub left: char,
+    pub right: char,
+    pub adjust: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PixelFont {
+    #[serde(skip)]
+    pub name: String,
+    /// Pixel rows in every glyph
+    pub height: usize,
+    /// Rows above the baseline
+    pub ascent: usize,
+    pub glyphs: BTreeMap<char, Glyph>,
+    #[serde(default)]
+    pub kerning: Vec<Kern>,
+}
+
+pub fn fonts_dir() -> PathBuf {
+    config_dir("slowfont").join("fonts")
+}
+
+fn font_path(name: &str) -> PathBuf {
+    fonts_dir().join(format!("{}.json", name))
+}
+
+impl PixelFont {
+    /// An empty 12 pixel font with only a space
+    pub fn new(name: &str) -> Self {
+        let mut font = Self { name: clean_name(name), height: 12, ascent: 9, glyphs: BTreeMap::new(), kerning: Vec::new() };
+        font.glyphs.insert(' ', font.blank_glyph());
+        font
+    }
+
+    /// What a glyph not yet drawn starts as
+    pub fn blank_glyph(&self) -> Glyph {
+        Glyph::blank(self.default_width(), self.height)
+    }
+
+    /// Width of a new glyph: the commonest width already drawn, or about
+    /// half the height
+    pub fn default_width(&self) -> usize {
+        let mut widths = BTreeMap::new();
+        for glyph in self.glyphs.values().filter(|g| !g.is_blank()) {
+            *widths.entry(glyph.width).or_insert(0) += 1;
+        }
+        widths.into_iter().max_by_key(|&(_, n)| n).map_or(self.height / 2 + 1, |(w, _)| w)
+    }
+
+    /// Change the height and baseline, keeping every glyph on the baseline
+    pub fn set_metrics(&mut self, height: usize, ascent: usize) {
+        let height = height.clamp(1, MAX_HEIGHT);
+        let ascent = ascent.min(height);
+        for glyph in self.glyphs.values_mut() {
+            glyph.rows = (0..height)
+                .map(|y| (y + self.ascent).checked_sub(ascent).and_then(|from| glyph.rows.get(from)).copied().unwrap_or(0))
+                .collect();
+        }
+        self.height = height;
+        self.ascent = ascent;
+    }
+
+    pub fn kern(&self, left: char, right: char) -> i32 {
+        self.kerning.iter().find(|k| k.left == left && k.right == right).map_or(0, |k| k.adjust)
+    }
+
+    /// Set the space between a pair; 0 takes the pair out of the table
+    pub fn set_kern(&mut self, left: char, right: char, adjust: i32) {
+        self.kerning.retain(|k| k.left != left || k.right != right);
+        if adjust != 0 {
+            self.kerning.push(Kern { left, right, adjust });
+            self.kerning.sort_by_key(|k| (k.left, k.right));
+        }
+    }
+
+    /// Every font, by name
+    pub fn load_all() -> Vec<PixelFont> {
+        let mut fonts: Vec<PixelFont> = std::fs::read_dir(fonts_dir())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .filter_map(|path| {
+                let mut font: PixelFont = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+                font.name = path.file_stem()?.to_string_lossy().into_owned();
+                Some(font)
+            })
+            .collect();
+        fonts.sort_by_key(|f| f.name.to_lowercase());
+        fonts
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        std::fs::create_dir_all(fonts_dir()).map_err(|e| e.to_string())?;
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        let path = font_path(&self.name);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+    }
+
+    pub fn rename(&mut self, name: &str) -> Result<(), String> {
+        let name = clean_name(name);
+        if name.is_empty() || name == self.name {
+            return Ok(());
+        }
+        if font_path(&name).exists() {
+            return Err(format!("there is already a font called \"{}\"", name));
+        }
+        std::fs::rename(font_path(&self.name), font_path(&name)).map_err(|e| e.to_string())?;
+        self.name = name;
+        Ok(())
+    }
+
+    pub fn delete(&self) -> Result<(), String> {
+        std::fs::remove_file(font_path(&self.name)).map_err(|e| e.to_string())
+    }
+}
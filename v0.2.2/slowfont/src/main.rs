@@ -0,0 +1,33 @@
+//! slowFont - Pixel font editor for the Slow Computer
+//!
+//! Bitmap fonts drawn a pixel at a time, with a live preview and a
+//! kerning table. Fonts can be imported from BDF files and installed as
+//! TrueType for slowOS, where they can replace the system font.
+
+mod app;
+mod bdf;
+mod font;
+mod ttf;
+
+use app::SlowFontApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let viewport = window_layout::viewport("slowfont", Placement::RememberLast, [760.0, 540.0])
+        .with_title("slowFont");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowFont",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowFontApp::new(cc))
+        }),
+    )
+}
@@ -0,0 +1,444 @@
+//! Writing pixel fonts out as TrueType, the format SlowTheme loads
+//!
+//! Each run of lit pixels along a row becomes a rectangle outline, so the
+//! font draws crisp at its own size and whole multiples of it. Kerning
+//! pairs go in a `kern` table, which egui reads.
+
+use crate::font::{Glyph, PixelFont};
+use std::path::PathBuf;
+
+/// Font units to a pixel
+const UNIT: i32 = 64;
+
+/// Big-endian writing
+#[derive(Default)]
+struct Buf(Vec<u8>);
+
+impl Buf {
+    fn u8(&mut self, v: u8) {
+        self.0.push(v);
+    }
+    fn u16(&mut self, v: u16) {
+        self.0.extend_from_slice(&v.to_be_bytes());
+    }
+    fn i16(&mut self, v: i16) {
+        self.0.extend_from_slice(&v.to_be_bytes());
+    }
+    fn u32(&mut self, v: u32) {
+        self.0.extend_from_slice(&v.to_be_bytes());
+    }
+    fn i64(&mut self, v: i64) {
+        self.0.extend_from_slice(&v.to_be_bytes());
+    }
+    fn tag(&mut self, tag: &[u8; 4]) {
+        self.0.extend_from_slice(tag);
+    }
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Largest power of two not above `n`, and its log
+fn binary_search_params(n: usize) -> (u16, u16) {
+    let mut power = 1;
+    let mut log = 0;
+    while power * 2 <= n {
+        power *= 2;
+        log += 1;
+    }
+    (power as u16, log)
+}
+
+/// A glyph's outline: rectangles of `(left, top, right, bottom)` in font
+/// units, y up from the baseline
+fn rectangles(glyph: &Glyph, ascent: usize) -> Vec<(i32, i32, i32, i32)> {
+    let mut rects = Vec::new();
+    for (y, _) in glyph.rows.iter().enumerate() {
+        let top = (ascent as i32 - y as i32) * UNIT;
+        let mut x = 0;
+        while x < glyph.width {
+            if !glyph.get(x, y) {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            while x < glyph.width && glyph.get(x, y) {
+                x += 1;
+            }
+            rects.push((start as i32 * UNIT, top, x as i32 * UNIT, top - UNIT));
+        }
+    }
+    rects
+}
+
+/// A `glyf` entry for the rectangles, clockwise as TrueType wants
+fn glyf_entry(rects: &[(i32, i32, i32, i32)]) -> Vec<u8> {
+    if rects.is_empty() {
+        return Vec::new();
+    }
+    let mut b = Buf::default();
+    let x_min = rects.iter().map(|r| r.0).min().unwrap_or(0);
+    let y_max = rects.iter().map(|r| r.1).max().unwrap_or(0);
+    let x_max = rects.iter().map(|r| r.2).max().unwrap_or(0);
+    let y_min = rects.iter().map(|r| r.3).min().unwrap_or(0);
+    b.i16(rects.len() as i16);
+    for v in [x_min, y_min, x_max, y_max] {
+        b.i16(v as i16);
+    }
+    for i in 0..rects.len() {
+        b.u16((i * 4 + 3) as u16);
+    }
+    b.u16(0); // no instructions
+    for _ in 0..rects.len() * 4 {
+        b.u8(0x01); // on the curve, full-size deltas
+    }
+    let points: Vec<(i32, i32)> = rects.iter().flat_map(|&(l, t, r, bt)| [(l, t), (r, t), (r, bt), (l, bt)]).collect();
+    let mut last = 0;
+    for &(x, _) in &points {
+        b.i16((x - last) as i16);
+        last = x;
+    }
+    last = 0;
+    for &(_, y) in &points {
+        b.i16((y - last) as i16);
+        last = y;
+    }
+    while b.0.len() % 4 != 0 {
+        b.u8(0);
+    }
+    b.0
+}
+
+fn name_table(family: &str) -> Vec<u8> {
+    let postscript: String = family.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+    let postscript = if postscript.is_empty() { "SlowFont".to_string() } else { postscript };
+    let names = [
+        (1, family.to_string()),
+        (2, "Regular".to_string()),
+        (3, format!("slowFont: {}", family)),
+        (4, family.to_string()),
+        (5, "Version 1.0".to_string()),
+        (6, postscript),
+    ];
+    let mut b = Buf::default();
+    let mut strings = Buf::default();
+    b.u16(0);
+    b.u16(names.len() as u16);
+    b.u16((6 + names.len() * 12) as u16);
+    for (id, text) in &names {
+        let start = strings.0.len();
+        for unit in text.encode_utf16() {
+            strings.u16(unit);
+        }
+        b.u16(3); // Windows
+        b.u16(1); // Unicode BMP
+        b.u16(0x0409); // English
+        b.u16(*id);
+        b.u16((strings.0.len() - start) as u16);
+        b.u16(start as u16);
+    }
+    b.0.extend(strings.0);
+    b.0
+}
+
+/// Character to glyph mapping, format 4: one segment per character
+fn cmap_table(chars: &[(char, u16)]) -> Vec<u8> {
+    let mut segments: Vec<(u16, u16)> = chars.iter().filter(|(c, _)| (*c as u32) < 0xFFFF).map(|&(c, id)| (c as u16, id)).collect();
+    // The table must end with a segment for 0xFFFF
+    segments.push((0xFFFF, 0));
+    let count = segments.len();
+    let (power, log) = binary_search_params(count);
+    let mut b = Buf::default();
+    b.u16(0); // version
+    b.u16(1);
+    b.u16(3);
+    b.u16(1);
+    b.u32(12);
+    b.u16(4); // format
+    b.u16((16 + count * 8) as u16);
+    b.u16(0); // language
+    b.u16((count * 2) as u16);
+    b.u16(power * 2);
+    b.u16(log);
+    b.u16((count as u16 - power) * 2);
+    for &(code, _) in &segments {
+        b.u16(code);
+    }
+    b.u16(0);
+    for &(code, _) in &segments {
+        b.u16(code);
+    }
+    for &(code, id) in &segments {
+        b.u16(if code == 0xFFFF { 1 } else { id.wrapping_sub(code) });
+    }
+    for _ in &segments {
+        b.u16(0);
+    }
+    b.0
+}
+
+fn kern_table(pairs: &[(u16, u16, i16)]) -> Vec<u8> {
+    let (power, log) = binary_search_params(pairs.len().max(1));
+    let mut b = Buf::default();
+    b.u16(0); // version
+    b.u16(1);
+    b.u16(0); // subtable version
+    b.u16((14 + pairs.len() * 6) as u16);
+    b.u16(0x0001); // horizontal, format 0
+    b.u16(pairs.len() as u16);
+    b.u16(power * 6);
+    b.u16(log);
+    b.u16((pairs.len() as u16).saturating_sub(power) * 6);
+    for &(left, right, value) in pairs {
+        b.u16(left);
+        b.u16(right);
+        b.i16(value);
+    }
+    b.0
+}
+
+/// The whole font file
+pub fn build(font: &PixelFont) -> Vec<u8> {
+    let ascent = font.ascent as i32 * UNIT;
+    let descent = (font.height - font.ascent) as i32 * UNIT;
+    let units_per_em = (font.height as i32 * UNIT) as u16;
+
+    // Glyph 0 is the empty .notdef, then the characters in order
+    let chars: Vec<(char, u16)> = font.glyphs.keys().enumerate().map(|(i, &c)| (c, i as u16 + 1)).collect();
+    let mut glyphs = vec![Glyph::blank(font.height / 2 + 1, font.height)];
+    glyphs.extend(font.glyphs.values().cloned());
+    let outlines: Vec<Vec<(i32, i32, i32, i32)>> = glyphs.iter().map(|g| rectangles(g, font.ascent)).collect();
+
+    let mut glyf = Vec::new();
+    let mut loca = Buf::default();
+    for rects in &outlines {
+        loca.u32(glyf.len() as u32);
+        glyf.extend(glyf_entry(rects));
+    }
+    loca.u32(glyf.len() as u32);
+
+    let mut hmtx = Buf::default();
+    for (glyph, rects) in glyphs.iter().zip(&outlines) {
+        hmtx.u16((glyph.width as i32 * UNIT) as u16);
+        hmtx.i16(rects.iter().map(|r| r.0).min().unwrap_or(0) as i16);
+    }
+    let max_advance = glyphs.iter().map(|g| g.width as i32 * UNIT).max().unwrap_or(0);
+    let all: Vec<_> = outlines.iter().flatten().collect();
+    let x_min = all.iter().map(|r| r.0).min().unwrap_or(0);
+    let y_max = all.iter().map(|r| r.1).max().unwrap_or(0);
+    let x_max = all.iter().map(|r| r.2).max().unwrap_or(0);
+    let y_min = all.iter().map(|r| r.3).min().unwrap_or(0);
+    let max_contours = outlines.iter().map(Vec::len).max().unwrap_or(0);
+    let inked: Vec<&Glyph> = glyphs.iter().filter(|g| !g.is_blank()).collect();
+    let avg_width = if inked.is_empty() { 0 } else { inked.iter().map(|g| g.width as i32 * UNIT).sum::<i32>() / inked.len() as i32 };
+
+    let mut head = Buf::default();
+    head.u32(0x0001_0000);
+    head.u32(0x0001_0000); // revision 1.0
+    head.u32(0); // checksum adjustment, filled in last
+    head.u32(0x5F0F_3CF5);
+    head.u16(0x000B); // baseline at 0, left bearing at 0, whole pixels
+    head.u16(units_per_em);
+    head.i64(0);
+    head.i64(0);
+    for v in [x_min, y_min, x_max, y_max] {
+        head.i16(v as i16);
+    }
+    head.u16(0); // regular style
+    head.u16(font.height as u16); // smallest readable size
+    head.i16(2);
+    head.i16(1); // long loca offsets
+    head.i16(0);
+
+    let mut hhea = Buf::default();
+    hhea.u32(0x0001_0000);
+    hhea.i16(ascent as i16);
+    hhea.i16(-descent as i16);
+    hhea.i16(0);
+    hhea.u16(max_advance as u16);
+    hhea.i16(x_min as i16);
+    hhea.i16(0);
+    hhea.i16(x_max as i16);
+    hhea.i16(1); // upright caret
+    hhea.i16(0);
+    hhea.i16(0);
+    for _ in 0..4 {
+        hhea.i16(0);
+    }
+    hhea.i16(0);
+    hhea.u16(glyphs.len() as u16);
+
+    let mut maxp = Buf::default();
+    maxp.u32(0x0001_0000);
+    maxp.u16(glyphs.len() as u16);
+    maxp.u16((max_contours * 4) as u16);
+    maxp.u16(max_contours as u16);
+    maxp.u16(0);
+    maxp.u16(0);
+    maxp.u16(2); // zones
+    for _ in 0..9 {
+        maxp.u16(0);
+    }
+
+    let first = chars.first().map_or(0, |&(c, _)| (c as u32).min(0xFFFF) as u16);
+    let last = chars.last().map_or(0, |&(c, _)| (c as u32).min(0xFFFF) as u16);
+    let mut os2 = Buf::default();
+    os2.u16(4);
+    os2.i16(avg_width as i16);
+    os2.u16(400); // weight
+    os2.u16(5); // width
+    os2.u16(0); // installable
+    let em = units_per_em as i16;
+    for v in [em / 2, em / 2, 0, em / 8, em / 2, em / 2, 0, em / 3, UNIT as i16, (ascent / 3) as i16] {
+        os2.i16(v);
+    }
+    os2.i16(0);
+    os2.0.extend([0; 10]); // panose
+    for _ in 0..4 {
+        os2.u32(0);
+    }
+    os2.tag(b"SLOW");
+    os2.u16(0x0040); // regular
+    os2.u16(first);
+    os2.u16(last);
+    os2.i16(ascent as i16);
+    os2.i16(-descent as i16);
+    os2.i16(0);
+    os2.u16(ascent as u16);
+    os2.u16(descent as u16);
+    os2.u32(1); // latin 1
+    os2.u32(0);
+    let ink_height = |c: char| font.glyphs.get(&c).and_then(|g| g.rows.iter().position(|&r| r != 0)).map(|top| (font.ascent as i32 - top as i32) * UNIT);
+    os2.i16(ink_height('x').unwrap_or(ascent / 2) as i16);
+    os2.i16(ink_height('H').unwrap_or(ascent) as i16);
+    os2.u16(0);
+    os2.u16(' ' as u16);
+    os2.u16(2);
+
+    let mut post = Buf::default();
+    post.u32(0x0003_0000);
+    post.u32(0);
+    post.i16((-UNIT) as i16);
+    post.i16(UNIT as i16);
+    let widths: Vec<usize> = inked.iter().map(|g| g.width).collect();
+    post.u32(u32::from(widths.windows(2).all(|w| w[0] == w[1])));
+    for _ in 0..4 {
+        post.u32(0);
+    }
+
+    let id_of = |c: char| chars.iter().find(|(ch, _)| *ch == c).map(|&(_, id)| id);
+    let mut pairs: Vec<(u16, u16, i16)> = font
+        .kerning
+        .iter()
+        .filter_map(|k| Some((id_of(k.left)?, id_of(k.right)?, (k.adjust * UNIT) as i16)))
+        .collect();
+    pairs.sort_by_key(|&(l, r, _)| (l, r));
+
+    let mut tables: Vec<([u8; 4], Vec<u8>)> = vec![
+        (*b"OS/2", os2.0),
+        (*b"cmap", cmap_table(&chars)),
+        (*b"glyf", glyf),
+        (*b"head", head.0),
+        (*b"hhea", hhea.0),
+        (*b"hmtx", hmtx.0),
+        (*b"loca", loca.0),
+        (*b"maxp", maxp.0),
+        (*b"name", name_table(&font.name)),
+        (*b"post", post.0),
+    ];
+    if !pairs.is_empty() {
+        tables.push((*b"kern", kern_table(&pairs)));
+    }
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let (power, log) = binary_search_params(tables.len());
+    let mut out = Buf::default();
+    out.u32(0x0001_0000);
+    out.u16(tables.len() as u16);
+    out.u16(power * 16);
+    out.u16(log);
+    out.u16((tables.len() as u16 - power) * 16);
+    let mut offset = 12 + tables.len() * 16;
+    let mut head_offset = 0;
+    for (tag, data) in &tables {
+        out.tag(tag);
+        out.u32(checksum(data));
+        out.u32(offset as u32);
+        out.u32(data.len() as u32);
+        if tag == b"head" {
+            head_offset = offset;
+        }
+        offset += data.len().div_ceil(4) * 4;
+    }
+    for (_, data) in &tables {
+        out.0.extend(data);
+        while out.0.len() % 4 != 0 {
+            out.u8(0);
+        }
+    }
+    let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&out.0));
+    out.0[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    out.0
+}
+
+/// File name the font is installed under
+pub fn file_name(font: &PixelFont) -> String {
+    format!("{}.ttf", font.name)
+}
+
+/// Write the font into the system's fonts folder, where SlowTheme finds it
+pub fn install(font: &PixelFont) -> Result<PathBuf, String> {
+    let data = build(font);
+    ab_glyph::FontRef::try_from_slice(&data).map_err(|e| format!("the font came out unreadable: {}", e))?;
+    let dir = slowcore::theme::fonts_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(file_name(font));
+    let tmp = path.with_extension("ttf.tmp");
+    std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ab_glyph::Font;
+
+    #[test]
+    fn test_build_loads() {
+        let mut font = PixelFont::new("Test Pixel");
+        font.set_metrics(8, 6);
+        let mut a = Glyph::blank(5, 8);
+        for y in 1..6 {
+            a.set(0, y, true);
+            a.set(3, y, true);
+        }
+        for x in 0..4 {
+            a.set(x, 1, true);
+            a.set(x, 3, true);
+        }
+        font.glyphs.insert('A', a);
+        font.glyphs.insert('V', Glyph::blank(5, 8));
+        font.set_kern('A', 'V', -1);
+
+        let data = build(&font);
+        let face = ab_glyph::FontRef::try_from_slice(&data).unwrap();
+        assert_eq!(face.units_per_em(), Some(8.0 * UNIT as f32));
+        assert_eq!(face.ascent_unscaled(), 6.0 * UNIT as f32);
+        let (a, v) = (face.glyph_id('A'), face.glyph_id('V'));
+        assert_eq!((a.0, v.0, face.glyph_id('z').0), (2, 3, 0));
+        assert_eq!(face.h_advance_unscaled(a), 5.0 * UNIT as f32);
+        assert_eq!(face.kern_unscaled(a, v), -UNIT as f32);
+        // Sitting on the baseline, five pixels tall and four across
+        let bounds = face.outline(a).unwrap().bounds;
+        assert_eq!((bounds.min.x, bounds.max.x), (0.0, 4.0 * UNIT as f32));
+        assert_eq!(bounds.height().abs(), 5.0 * UNIT as f32);
+        assert_eq!(bounds.min.y.min(bounds.max.y), 0.0);
+        assert!(face.outline(face.glyph_id(' ')).is_none());
+    }
+}
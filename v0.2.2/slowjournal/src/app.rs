@@ -0,0 +1,387 @@
+//! slowJournal application
+
+use crate::journal::{self, Entry};
+use crate::prompts;
+use chrono::{Datelike, Duration, Local, Months, NaiveDate};
+use egui::{Align2, CentralPanel, Context, FontId, Key, Rect, Sense, SidePanel, Stroke, TopBottomPanel, Vec2};
+use serde::{Deserialize, Serialize};
+use slowcore::ipc::{self, Command, Reply};
+use slowcore::repaint::RepaintController;
+use slowcore::storage::config_dir;
+use slowcore::text_edit::{InputMethod, WordDragState};
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, WindowAction};
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowJournal",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "a page a day for slowOS",
+    features: &["one entry per day", "writing prompts", "streaks", "a year in one document"],
+    sections: &[("storage", &["Markdown in documents/journal"])],
+    ..AboutInfo::EMPTY
+};
+
+/// Typing stops this long before the entry is written
+const SAVE_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+const DAY_SIZE: Vec2 = Vec2::new(26.0, 22.0);
+
+#[derive(Serialize, Deserialize)]
+struct Settings {
+    show_prompts: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self { show_prompts: true }
+    }
+}
+
+impl Settings {
+    fn load() -> Self {
+        let path = config_dir("slowjournal").join("settings.json");
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let path = config_dir("slowjournal").join("settings.json");
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+pub struct SlowJournalApp {
+    entry: Entry,
+    /// Changed since it was last written, and when
+    edited: Option<Instant>,
+    days: BTreeSet<NaiveDate>,
+    /// First day of the month the calendar shows
+    month: NaiveDate,
+    /// How many prompts on from the day's own
+    prompt_offset: usize,
+    settings: Settings,
+    input: InputMethod,
+    word_drag: WordDragState,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowJournalApp {
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        let today = today();
+        Self {
+            entry: Entry::load(today),
+            edited: None,
+            days: journal::dates(),
+            month: today.with_day(1).unwrap_or(today),
+            prompt_offset: 0,
+            settings: Settings::load(),
+            input: InputMethod::new(),
+            word_drag: WordDragState::new(),
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        }
+    }
+
+    fn save(&mut self) {
+        if self.edited.take().is_none() {
+            return;
+        }
+        match self.entry.save() {
+            Ok(()) => {
+                if self.entry.is_empty() {
+                    self.days.remove(&self.entry.date);
+                } else {
+                    self.days.insert(self.entry.date);
+                }
+            }
+            Err(e) => self.message = Some(format!("could not save entry: {}", e)),
+        }
+    }
+
+    fn open_day(&mut self, date: NaiveDate) {
+        if date > today() {
+            return;
+        }
+        self.save();
+        self.entry = Entry::load(date);
+        self.month = date.with_day(1).unwrap_or(date);
+        self.prompt_offset = 0;
+        self.message = None;
+    }
+
+    fn export(&mut self, year: i32) {
+        self.save();
+        self.message = Some(match journal::export_year(year, &self.days) {
+            Ok(path) => format!("saved {} in documents", path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()),
+            Err(e) => format!("could not export: {}", e),
+        });
+    }
+
+    /// Hand the entry to a running slowNotes, which makes a note of it
+    fn copy_to_notes(&mut self) {
+        self.save();
+        let path = journal::entry_path(self.entry.date);
+        self.message = Some(match ipc::find_app("slownotes") {
+            None => "open slowNotes first".into(),
+            Some(_) if !path.exists() => "nothing written yet".into(),
+            Some(pid) => match ipc::send(pid, &Command::Open { path }, ipc::REPLY_TIMEOUT) {
+                Reply::Failed(e) => format!("slowNotes couldn't take it: {}", e),
+                _ => "copied to slowNotes".into(),
+            },
+        });
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        let (today_key, previous, next) = ctx.input(|i| {
+            let cmd = i.modifiers.command;
+            (cmd && i.key_pressed(Key::T), cmd && i.key_pressed(Key::OpenBracket), cmd && i.key_pressed(Key::CloseBracket))
+        });
+        if today_key {
+            self.open_day(today());
+        }
+        if previous {
+            self.open_day(self.entry.date - Duration::days(1));
+        }
+        if next {
+            self.open_day(self.entry.date + Duration::days(1));
+        }
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("file", |ui| {
+                    if ui.button("copy to slowNotes").clicked() {
+                        self.copy_to_notes();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    let mut years: Vec<i32> = self.days.iter().map(|d| d.year()).collect();
+                    years.dedup();
+                    if years.is_empty() {
+                        ui.add_enabled(false, egui::Button::new("export year…"));
+                    }
+                    for year in years.into_iter().rev() {
+                        if ui.button(format!("export {}…", year)).clicked() {
+                            self.export(year);
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.menu_button("go", |ui| {
+                    if ui.button("today         ⌘T").clicked() {
+                        self.open_day(today());
+                        ui.close_menu();
+                    }
+                    if ui.button("previous day  ⌘[").clicked() {
+                        self.open_day(self.entry.date - Duration::days(1));
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.entry.date < today(), egui::Button::new("next day      ⌘]")).clicked() {
+                        self.open_day(self.entry.date + Duration::days(1));
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("prompts", |ui| {
+                    if ui.checkbox(&mut self.settings.show_prompts, "show a prompt each day").clicked() {
+                        self.settings.save();
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => {
+                self.save();
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowjournal", "slowJournal");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    /// The month, days with an entry underlined, the open day filled in
+    fn draw_calendar(&mut self, ui: &mut egui::Ui) {
+        let today = today();
+        ui.horizontal(|ui| {
+            if ui.button("◀").clicked() {
+                self.month = self.month - Months::new(1);
+            }
+            ui.add_sized(Vec2::new(DAY_SIZE.x * 7.0 - 60.0, 20.0), egui::Label::new(self.month.format("%B %Y").to_string()));
+            let later = self.month + Months::new(1) <= today;
+            if ui.add_enabled(later, egui::Button::new("▶")).clicked() {
+                self.month = self.month + Months::new(1);
+            }
+        });
+        ui.add_space(4.0);
+
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(DAY_SIZE.x * 7.0, DAY_SIZE.y * 7.0), Sense::hover());
+        let painter = ui.painter_at(rect);
+        let font = FontId::proportional(12.0);
+        for (column, letter) in ["m", "t", "w", "t", "f", "s", "s"].iter().enumerate() {
+            let cell = Rect::from_min_size(rect.min + Vec2::new(column as f32 * DAY_SIZE.x, 0.0), DAY_SIZE);
+            painter.text(cell.center(), Align2::CENTER_CENTER, letter, font.clone(), SlowColors::BLACK);
+        }
+        let offset = self.month.weekday().num_days_from_monday() as usize;
+        let mut picked = None;
+        let mut date = self.month;
+        while date.month() == self.month.month() {
+            let slot = offset + date.day0() as usize;
+            let cell = Rect::from_min_size(rect.min + Vec2::new((slot % 7) as f32 * DAY_SIZE.x, (slot / 7 + 1) as f32 * DAY_SIZE.y), DAY_SIZE).shrink(1.0);
+            let mut color = SlowColors::BLACK;
+            if date == self.entry.date {
+                painter.rect_filled(cell, 2.0, SlowColors::BLACK);
+                color = SlowColors::WHITE;
+            } else if date == today {
+                painter.rect_stroke(cell, 2.0, Stroke::new(1.0, SlowColors::BLACK));
+            }
+            painter.text(cell.center() - Vec2::new(0.0, 2.0), Align2::CENTER_CENTER, date.day().to_string(), font.clone(), color);
+            if self.days.contains(&date) {
+                let y = cell.bottom() - 3.0;
+                painter.line_segment([egui::pos2(cell.center().x - 5.0, y), egui::pos2(cell.center().x + 5.0, y)], Stroke::new(2.0, color));
+            }
+            if date <= today && ui.interact(cell, ui.id().with(("day", date)), Sense::click()).clicked() {
+                picked = Some(date);
+            }
+            date += Duration::days(1);
+        }
+        if let Some(date) = picked {
+            self.open_day(date);
+        }
+
+        ui.add_space(12.0);
+        ui.label(format!("streak: {} days", journal::current_streak(&self.days, today)));
+        ui.label(format!("longest streak: {} days", journal::longest_streak(&self.days)));
+        let this_year = self.days.iter().filter(|d| d.year() == today.year()).count();
+        ui.label(format!("entries in {}: {}", today.year(), this_year));
+    }
+
+    fn draw_entry(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new(self.entry.date.format("%A %-d %B %Y").to_string()).strong().size(16.0));
+        ui.add_space(6.0);
+
+        match self.entry.prompt.clone() {
+            Some(prompt) => {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(&prompt).italics());
+                    if ui.small_button("×").on_hover_text("remove the prompt").clicked() {
+                        self.entry.prompt = None;
+                        self.edited = Some(Instant::now());
+                    }
+                });
+            }
+            None if self.settings.show_prompts && self.entry.is_empty() => {
+                let prompt = prompts::prompt_for(self.entry.date, self.prompt_offset);
+                ui.horizontal(|ui| {
+                    ui.weak(prompt);
+                    if ui.small_button("use").clicked() {
+                        self.entry.prompt = Some(prompt.to_string());
+                        self.edited = Some(Instant::now());
+                    }
+                    if ui.small_button("another").clicked() {
+                        self.prompt_offset += 1;
+                    }
+                });
+            }
+            None => {}
+        }
+        ui.separator();
+
+        let available = ui.available_size();
+        self.input.before(ui.ctx());
+        let output = egui::TextEdit::multiline(&mut self.entry.body)
+            .font(egui::FontId::proportional(14.0))
+            .frame(false)
+            .hint_text("write about your day…")
+            .desired_width(available.x)
+            .desired_rows((available.y / 20.0).max(4.0) as usize)
+            .show(ui);
+        if output.response.changed() {
+            self.edited = Some(Instant::now());
+        }
+        self.word_drag.update(ui, &output, &self.entry.body);
+        self.input.after(ui, &output);
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+impl eframe::App for SlowJournalApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowjournal") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.handle_keys(ctx);
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| {
+                let words = self.entry.body.split_whitespace().count();
+                let streak = journal::current_streak(&self.days, today());
+                format!("{} words  |  {} day streak", words, streak)
+            });
+            status_bar(ui, &status);
+        });
+
+        SidePanel::left("calendar")
+            .resizable(false)
+            .exact_width(DAY_SIZE.x * 7.0 + 16.0)
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(8.0)))
+            .show(ctx, |ui| self.draw_calendar(ui));
+
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(12.0)))
+            .show(ctx, |ui| self.draw_entry(ui));
+
+        self.draw_about(ctx);
+
+        if let Some(edited) = self.edited {
+            if edited.elapsed() >= SAVE_DELAY {
+                self.save();
+            } else {
+                ctx.request_repaint_after(SAVE_DELAY);
+            }
+        }
+        self.repaint.end_frame(ctx);
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.save();
+    }
+}
@@ -0,0 +1,181 @@
+//! Entries on disk
+//!
+//! One Markdown file per day, ~/Documents/journal/2026/2026-10-16.md: the
+//! date as a heading, the prompt answered (if any) as a quote, then the
+//! entry. They are plain text files, so slowNotes and slowWrite open them
+//! as they are.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use slowcore::storage::documents_dir;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// How the date reads at the top of an entry
+const HEADING_FORMAT: &str = "%A %-d %B %Y";
+
+pub fn journal_dir() -> PathBuf {
+    documents_dir().join("journal")
+}
+
+pub fn entry_path(date: NaiveDate) -> PathBuf {
+    journal_dir().join(date.year().to_string()).join(format!("{}.md", date.format("%Y-%m-%d")))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub date: NaiveDate,
+    pub prompt: Option<String>,
+    pub body: String,
+}
+
+impl Entry {
+    pub fn new(date: NaiveDate) -> Self {
+        Self { date, prompt: None, body: String::new() }
+    }
+
+    /// The entry for `date`, or a blank one
+    pub fn load(date: NaiveDate) -> Self {
+        std::fs::read_to_string(entry_path(date)).map(|text| Self::parse(date, &text)).unwrap_or_else(|_| Self::new(date))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.body.trim().is_empty()
+    }
+
+    /// Write the entry, or remove its file once it's emptied
+    pub fn save(&self) -> Result<(), String> {
+        let path = entry_path(self.date);
+        if self.is_empty() {
+            return match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+                _ => Ok(()),
+            };
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let tmp = path.with_extension("md.tmp");
+        std::fs::write(&tmp, self.to_markdown()).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut text = format!("# {}\n\n", self.date.format(HEADING_FORMAT));
+        if let Some(prompt) = &self.prompt {
+            text.push_str(&format!("> {}\n\n", prompt));
+        }
+        text.push_str(self.body.trim_end());
+        text.push('\n');
+        text
+    }
+
+    /// Read an entry back. A file edited elsewhere may lack the heading or
+    /// the quote; whatever isn't one of them is the entry.
+    pub fn parse(date: NaiveDate, text: &str) -> Self {
+        let mut rest = text.trim_start();
+        if rest.starts_with("# ") {
+            rest = rest.split_once('\n').map(|(_, r)| r).unwrap_or("").trim_start();
+        }
+        let mut prompt = None;
+        if let Some(quote) = rest.strip_prefix("> ") {
+            let (line, after) = quote.split_once('\n').unwrap_or((quote, ""));
+            prompt = Some(line.trim().to_string());
+            rest = after.trim_start_matches(['\r', '\n']);
+        }
+        Self { date, prompt, body: rest.trim_end().to_string() }
+    }
+}
+
+/// The days with an entry
+pub fn dates() -> BTreeSet<NaiveDate> {
+    let mut found = BTreeSet::new();
+    let Ok(years) = std::fs::read_dir(journal_dir()) else { return found };
+    for year in years.flatten().filter(|e| e.path().is_dir()) {
+        let Ok(files) = std::fs::read_dir(year.path()) else { continue };
+        for file in files.flatten() {
+            if let Some(date) = date_of(&file.path()) {
+                found.insert(date);
+            }
+        }
+    }
+    found
+}
+
+fn date_of(path: &Path) -> Option<NaiveDate> {
+    if path.extension()? != "md" {
+        return None;
+    }
+    NaiveDate::parse_from_str(path.file_stem()?.to_str()?, "%Y-%m-%d").ok()
+}
+
+/// Days in a row with an entry, counting back from `today` (or from
+/// yesterday if there is nothing yet today)
+pub fn current_streak(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> u32 {
+    let mut day = if days.contains(&today) { today } else { today - Duration::days(1) };
+    let mut streak = 0;
+    while days.contains(&day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+    streak
+}
+
+pub fn longest_streak(days: &BTreeSet<NaiveDate>) -> u32 {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut prev: Option<NaiveDate> = None;
+    for &day in days {
+        run = if prev == Some(day - Duration::days(1)) { run + 1 } else { 1 };
+        longest = longest.max(run);
+        prev = Some(day);
+    }
+    longest
+}
+
+/// Every entry of `year` in one Markdown document, a section a day
+pub fn compile_year(year: i32, days: &BTreeSet<NaiveDate>) -> String {
+    let mut text = format!("# journal {}\n", year);
+    for &date in days.iter().filter(|d| d.year() == year) {
+        let entry = Entry::load(date);
+        if entry.is_empty() {
+            continue;
+        }
+        text.push_str(&format!("\n## {}\n\n", date.format("%A %-d %B")));
+        if let Some(prompt) = &entry.prompt {
+            text.push_str(&format!("> {}\n\n", prompt));
+        }
+        text.push_str(entry.body.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// Write `year`'s entries to a new document in Documents
+pub fn export_year(year: i32, days: &BTreeSet<NaiveDate>) -> Result<PathBuf, String> {
+    let path = slowcore::storage::unique_path(&documents_dir(), &format!("journal {}.md", year));
+    std::fs::write(&path, compile_year(year, days)).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_markdown() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let entry = Entry { date, prompt: Some("what went well today?".into()), body: "the bread rose.\n\nit rained.".into() };
+        let text = entry.to_markdown();
+        assert_eq!(text, "# Friday 16 October 2026\n\n> what went well today?\n\nthe bread rose.\n\nit rained.\n");
+        assert_eq!(Entry::parse(date, &text), entry);
+        // Written elsewhere, without heading or prompt
+        assert_eq!(Entry::parse(date, "just this\n").body, "just this");
+        assert_eq!(Entry::parse(date, "just this\n").prompt, None);
+
+        let days: BTreeSet<NaiveDate> = [14, 15, 16, 20].iter().map(|&d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap()).collect();
+        assert_eq!(current_streak(&days, date), 3);
+        assert_eq!(current_streak(&days, date + Duration::days(1)), 3);
+        assert_eq!(current_streak(&days, date + Duration::days(2)), 0);
+        assert_eq!(longest_streak(&days), 3);
+    }
+}
@@ -0,0 +1,32 @@
+//! slowJournal - A journal for the Slow Computer
+//!
+//! One entry a day, kept as Markdown files in Documents, with an optional
+//! prompt to start from, a streak of days written, and each year's
+//! entries compiled into a single document.
+
+mod app;
+mod journal;
+mod prompts;
+
+use app::SlowJournalApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let viewport = window_layout::viewport("slowjournal", Placement::RememberLast, [640.0, 460.0])
+        .with_title("slowJournal");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowJournal",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowJournalApp::new(cc))
+        }),
+    )
+}
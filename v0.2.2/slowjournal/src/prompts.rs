@@ -0,0 +1,47 @@
+//! Writing prompts
+//!
+//! Each day has its own prompt, the same every time the day is opened;
+//! "another" steps through the rest from there.
+
+use chrono::{Datelike, NaiveDate};
+
+const PROMPTS: &[&str] = &[
+    "what went well today?",
+    "what are you looking forward to?",
+    "what did you notice today that you usually walk past?",
+    "who made today better, and how?",
+    "what would you like to remember about today?",
+    "what is taking up most of your thoughts right now?",
+    "what did you learn today?",
+    "what was hard today, and how did you meet it?",
+    "describe the room you're sitting in.",
+    "what are three small things you're grateful for?",
+    "what did you eat today, and was it good?",
+    "what would you tell yourself from a year ago?",
+    "what are you putting off, and why?",
+    "what made you laugh recently?",
+    "what did you make or fix today?",
+    "where did your time go today?",
+    "what is something you changed your mind about?",
+    "what would make tomorrow a good day?",
+    "what was the weather like, inside and out?",
+    "write about a conversation you had today.",
+    "what are you reading, listening to or watching?",
+    "what did you do today just for yourself?",
+    "what is a place you'd like to be right now?",
+    "what would you do with a free afternoon?",
+    "what is something you're proud of this week?",
+    "what are you worried about, and what can you do about it?",
+    "what surprised you today?",
+    "write a letter you won't send.",
+    "what habit would you like to start, or stop?",
+    "what did you hear today: a sound, a song, a phrase?",
+    "what are you saying yes to, and what no?",
+    "describe a small moment from today in detail.",
+];
+
+/// The prompt for `date`, or `offset` prompts on from it
+pub fn prompt_for(date: NaiveDate, offset: usize) -> &'static str {
+    let day = date.num_days_from_ce() as usize;
+    PROMPTS[(day + offset) % PROMPTS.len()]
+}
@@ -0,0 +1,405 @@
+//! slowKeyboard application
+
+use egui::{Align2, Context, FontId, Key, Rect, Sense, Stroke, Vec2};
+use serde::{Deserialize, Serialize};
+use slowcore::keyboard::{self, KeyPress};
+use slowcore::repaint::RepaintController;
+use slowcore::storage::config_dir;
+use slowcore::theme::{menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, window_control_buttons, AboutInfo, WindowAction};
+use slowcore::window_layout::{self, WindowRect};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "keyboard",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "on-screen keyboard for slowOS",
+    sections: &[(
+        "using it",
+        &["types into the app last used", "⇧ once for a capital, twice to lock", "⌘ then a letter for a shortcut"],
+    )],
+    ..AboutInfo::EMPTY
+};
+
+/// Height of the keyboard window
+const KEYBOARD_HEIGHT: f32 = 210.0;
+
+/// Width of the floating keyboard
+const FLOATING_WIDTH: f32 = 520.0;
+
+/// Gap between keys
+const KEY_GAP: f32 = 4.0;
+
+/// A held key starts repeating after this long
+const REPEAT_DELAY: Duration = Duration::from_millis(500);
+
+/// And then repeats this often
+const REPEAT_INTERVAL: Duration = Duration::from_millis(80);
+
+/// An auto-shown keyboard closes once nobody has been typing this long
+const AUTO_HIDE_AFTER: Duration = Duration::from_secs(2);
+
+/// What a key on the keyboard does
+#[derive(Clone, Copy, PartialEq)]
+enum Cap {
+    /// Types a character (upper case with shift)
+    Char(char),
+    /// A key that doesn't type
+    Special(Key, &'static str),
+    Shift,
+    Command,
+    /// Letters ⇄ symbols
+    Switch,
+    Space,
+}
+
+impl Cap {
+    /// Width in key units
+    fn width(&self) -> f32 {
+        match self {
+            Cap::Char(_) => 1.0,
+            Cap::Special(Key::Backspace | Key::Enter | Key::Tab, _) => 1.5,
+            Cap::Special(..) => 1.0,
+            Cap::Shift | Cap::Switch => 1.5,
+            Cap::Command => 1.25,
+            Cap::Space => 4.5,
+        }
+    }
+
+    /// Keys that repeat while held
+    fn repeats(&self) -> bool {
+        matches!(
+            self,
+            Cap::Special(Key::Backspace | Key::ArrowLeft | Key::ArrowRight | Key::ArrowUp | Key::ArrowDown, _)
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Layout {
+    Letters,
+    Symbols,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Shift {
+    Off,
+    /// For the next letter
+    Once,
+    Locked,
+}
+
+fn rows(layout: Layout) -> Vec<Vec<Cap>> {
+    let chars = |s: &str| s.chars().map(Cap::Char).collect::<Vec<_>>();
+    let backspace = Cap::Special(Key::Backspace, "⌫");
+    let enter = Cap::Special(Key::Enter, "return");
+    let bottom = vec![
+        Cap::Switch,
+        Cap::Command,
+        Cap::Special(Key::Escape, "esc"),
+        Cap::Space,
+        Cap::Special(Key::ArrowLeft, "←"),
+        Cap::Special(Key::ArrowUp, "↑"),
+        Cap::Special(Key::ArrowDown, "↓"),
+        Cap::Special(Key::ArrowRight, "→"),
+    ];
+    match layout {
+        Layout::Letters => vec![
+            [chars("qwertyuiop"), vec![backspace]].concat(),
+            [chars("asdfghjkl'"), vec![enter]].concat(),
+            [vec![Cap::Shift], chars("zxcvbnm,.?"), vec![Cap::Shift]].concat(),
+            bottom,
+        ],
+        Layout::Symbols => vec![
+            [chars("1234567890"), vec![backspace]].concat(),
+            [chars("-/:;()$&@\""), vec![enter]].concat(),
+            [vec![Cap::Special(Key::Tab, "tab")], chars("_\\|~<>#%*+=!")].concat(),
+            bottom,
+        ],
+    }
+}
+
+/// Remembered between runs
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct Prefs {
+    /// Along the bottom of the screen, rather than floating
+    pub docked: bool,
+}
+
+impl Default for Prefs {
+    fn default() -> Self {
+        Self { docked: true }
+    }
+}
+
+impl Prefs {
+    fn path() -> PathBuf {
+        config_dir("slowkeyboard").join("keyboard.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+}
+
+/// Where the keyboard window goes: across the bottom of the work area
+/// when docked, otherwise a smaller one just above it
+pub fn window_rect(docked: bool) -> WindowRect {
+    let screen = window_layout::screen();
+    let h = KEYBOARD_HEIGHT.min(screen.h);
+    if docked {
+        WindowRect { x: screen.x, y: screen.y + screen.h - h, w: screen.w, h }
+    } else {
+        let w = FLOATING_WIDTH.min(screen.w);
+        WindowRect { x: screen.x + (screen.w - w) / 2.0, y: screen.y + screen.h - h - 40.0, w, h }
+    }
+}
+
+pub struct SlowKeyboardApp {
+    prefs: Prefs,
+    layout: Layout,
+    shift: Shift,
+    /// ⌘ for the next key
+    command: bool,
+    /// Started by the desktop for a text field; closes when typing stops
+    auto: bool,
+    /// Since when nobody has been typing (auto-shown keyboards)
+    idle_since: Option<Instant>,
+    /// Key held down, since when, and when it last repeated
+    held: Option<(Cap, Instant, Instant)>,
+    /// Shown when a tap went nowhere
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowKeyboardApp {
+    pub fn new(_cc: &eframe::CreationContext<'_>, prefs: Prefs, auto: bool) -> Self {
+        Self {
+            prefs,
+            layout: Layout::Letters,
+            shift: Shift::Off,
+            command: false,
+            auto,
+            idle_since: None,
+            held: None,
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        }
+    }
+
+    fn set_docked(&mut self, ctx: &Context, docked: bool) {
+        self.prefs.docked = docked;
+        self.prefs.save();
+        let rect = window_rect(docked);
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(rect.x, rect.y)));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(rect.w, rect.h)));
+    }
+
+    /// What `cap` types, given the shift state
+    fn press(&mut self, cap: Cap) {
+        let press = match cap {
+            Cap::Shift => {
+                self.shift = match self.shift {
+                    Shift::Off => Shift::Once,
+                    Shift::Once => Shift::Locked,
+                    Shift::Locked => Shift::Off,
+                };
+                return;
+            }
+            Cap::Command => {
+                self.command = !self.command;
+                return;
+            }
+            Cap::Switch => {
+                self.layout = match self.layout {
+                    Layout::Letters => Layout::Symbols,
+                    Layout::Symbols => Layout::Letters,
+                };
+                return;
+            }
+            Cap::Space => KeyPress::Text(" ".into()),
+            Cap::Special(key, _) => {
+                KeyPress::Key { key: key.name().into(), shift: self.shift != Shift::Off, command: self.command }
+            }
+            // ⌘ with a letter is a shortcut, not typing
+            Cap::Char(c) if self.command => match Key::from_name(&c.to_ascii_uppercase().to_string()) {
+                Some(key) => KeyPress::Key { key: key.name().into(), shift: self.shift != Shift::Off, command: true },
+                None => KeyPress::Text(c.to_string()),
+            },
+            Cap::Char(c) if self.shift != Shift::Off => KeyPress::Text(c.to_uppercase().to_string()),
+            Cap::Char(c) => KeyPress::Text(c.to_string()),
+        };
+        self.message = if keyboard::send(&press) { None } else { Some("nothing to type into".into()) };
+        self.command = false;
+        if self.shift == Shift::Once {
+            self.shift = Shift::Off;
+        }
+    }
+
+    fn is_active(&self, cap: Cap) -> bool {
+        match cap {
+            Cap::Shift => self.shift != Shift::Off,
+            Cap::Command => self.command,
+            _ => false,
+        }
+    }
+
+    fn label(&self, cap: Cap) -> String {
+        match cap {
+            Cap::Char(c) if self.shift != Shift::Off => c.to_uppercase().to_string(),
+            Cap::Char(c) => c.to_string(),
+            Cap::Special(_, label) => label.to_string(),
+            Cap::Shift if self.shift == Shift::Locked => "⇪".into(),
+            Cap::Shift => "⇧".into(),
+            Cap::Command => "⌘".into(),
+            Cap::Switch if self.layout == Layout::Letters => "123".into(),
+            Cap::Switch => "abc".into(),
+            Cap::Space => String::new(),
+        }
+    }
+
+    fn render_keys(&mut self, ui: &mut egui::Ui) {
+        let rows = rows(self.layout);
+        let area = ui.available_rect_before_wrap();
+        let widest = rows.iter().map(|r| r.iter().map(Cap::width).sum::<f32>()).fold(1.0, f32::max);
+        let unit = ((area.width() + KEY_GAP) / widest).floor();
+        let key_h = ((area.height() + KEY_GAP) / rows.len() as f32 - KEY_GAP).floor();
+        let mut pressed = None;
+        let mut down = None;
+        for (r, row) in rows.iter().enumerate() {
+            let row_w = row.iter().map(Cap::width).sum::<f32>() * unit - KEY_GAP;
+            let mut x = area.left() + ((area.width() - row_w) / 2.0).max(0.0);
+            let y = area.top() + r as f32 * (key_h + KEY_GAP);
+            for (c, &cap) in row.iter().enumerate() {
+                let w = cap.width() * unit - KEY_GAP;
+                let rect = Rect::from_min_size(egui::pos2(x, y), Vec2::new(w, key_h));
+                x += w + KEY_GAP;
+                let resp = ui.interact(rect, ui.id().with((r, c)), Sense::click());
+                let held = resp.is_pointer_button_down_on();
+                if held {
+                    down = Some(cap);
+                }
+                if resp.clicked() {
+                    pressed = Some(cap);
+                }
+                let inverted = held || self.is_active(cap);
+                let (fill, text) = if inverted {
+                    (SlowColors::BLACK, SlowColors::WHITE)
+                } else {
+                    (SlowColors::WHITE, SlowColors::BLACK)
+                };
+                let painter = ui.painter();
+                painter.rect(rect, 3.0, fill, Stroke::new(1.0, SlowColors::BLACK));
+                painter.text(rect.center(), Align2::CENTER_CENTER, self.label(cap), FontId::proportional(16.0), text);
+            }
+        }
+        ui.allocate_rect(area, Sense::hover());
+
+        // Held keys repeat; otherwise a key goes out on release as a click
+        let now = Instant::now();
+        let repeated = self.held.is_some_and(|(_, since, last)| last > since);
+        match (down.filter(Cap::repeats), self.held) {
+            (Some(cap), Some((held, since, last))) if held == cap => {
+                if since.elapsed() >= REPEAT_DELAY && last.elapsed() >= REPEAT_INTERVAL {
+                    self.press(cap);
+                    self.held = Some((cap, since, now));
+                }
+                ui.ctx().request_repaint_after(REPEAT_INTERVAL);
+            }
+            (Some(cap), _) => {
+                self.held = Some((cap, now, now));
+                ui.ctx().request_repaint_after(REPEAT_DELAY);
+            }
+            (None, _) => self.held = None,
+        }
+        if let Some(cap) = pressed.filter(|_| !repeated) {
+            self.press(cap);
+        }
+    }
+
+    /// Auto-shown keyboards go away once nobody is typing
+    fn check_auto_hide(&mut self, ctx: &Context) {
+        if !self.auto {
+            return;
+        }
+        let typing = keyboard::focus().is_some_and(|f| f.typing);
+        if typing || self.held.is_some() {
+            self.idle_since = None;
+        } else if self.idle_since.get_or_insert_with(Instant::now).elapsed() >= AUTO_HIDE_AFTER {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+        ctx.request_repaint_after(Duration::from_millis(500));
+    }
+}
+
+impl eframe::App for SlowKeyboardApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowkeyboard") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+        }
+        slowcore::session::close_on_quit(ctx);
+        self.check_auto_hide(ctx);
+
+        let mut win_action = WindowAction::None;
+        egui::TopBottomPanel::top("menu").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                win_action = window_control_buttons(ui);
+                ui.menu_button("keyboard", |ui| {
+                    if ui.radio(self.prefs.docked, "docked at the bottom").clicked() {
+                        self.set_docked(ui.ctx(), true);
+                        ui.close_menu();
+                    }
+                    if ui.radio(!self.prefs.docked, "floating").clicked() {
+                        self.set_docked(ui.ctx(), false);
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                if let Some(message) = &self.message {
+                    ui.label(message);
+                }
+            });
+        });
+        match win_action {
+            WindowAction::Close => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowkeyboard", "keyboard");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(6.0)))
+            .show(ctx, |ui| self.render_keys(ui));
+
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+        self.repaint.end_frame(ctx);
+    }
+}
@@ -0,0 +1,40 @@
+//! slowKeyboard - An on-screen keyboard for the Slow Computer
+//!
+//! For touch screens without a keyboard. Taps go to the app last typed
+//! into, through `slowcore::keyboard`. The keyboard docks along the bottom
+//! of the screen or floats above the windows. slowDesktop starts it with
+//! `--auto` when a text field gains focus (if turned on in settings); it
+//! then closes itself once nobody is typing.
+
+mod app;
+
+use app::SlowKeyboardApp;
+use eframe::NativeOptions;
+
+fn main() -> eframe::Result<()> {
+    let auto = std::env::args().any(|a| a == "--auto");
+    let prefs = app::Prefs::load();
+    let rect = app::window_rect(prefs.docked);
+
+    // Never takes the focus from the app being typed into
+    slowcore::keyboard::ignore_focus();
+
+    let options = NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([rect.w, rect.h])
+            .with_position([rect.x, rect.y])
+            .with_window_level(egui::WindowLevel::AlwaysOnTop)
+            .with_active(false)
+            .with_title("keyboard"),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "keyboard",
+        options,
+        Box::new(move |cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowKeyboardApp::new(cc, prefs, auto))
+        }),
+    )
+}
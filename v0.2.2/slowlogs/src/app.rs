@@ -0,0 +1,253 @@
+//! slowLogs application
+
+use egui::{CentralPanel, Context, Key, SidePanel, TopBottomPanel};
+use slowcore::logs::{self, LogFile};
+use slowcore::repaint::RepaintController;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, WindowAction};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowLogs",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "log viewer for slowOS",
+    features: &["crash reports", "each app's output", "startup and system logs", "filter, follow and copy"],
+    ..AboutInfo::EMPTY
+};
+
+/// How often the open log is checked for new lines
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Only the end of a log is read; older lines are in the rotated copies
+const MAX_VIEW_BYTES: u64 = 512 * 1024;
+
+const LINE_HEIGHT: f32 = 14.0;
+
+pub struct SlowLogsApp {
+    files: Vec<LogFile>,
+    selected: Option<PathBuf>,
+    lines: Vec<String>,
+    /// Size and modification time of the log as read, to notice new lines
+    read_stamp: Option<(u64, SystemTime)>,
+    last_check: Instant,
+    filter: String,
+    /// Look for new lines every few seconds
+    follow: bool,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowLogsApp {
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        let files = logs::log_files();
+        let mut app = Self {
+            selected: files.first().map(|f| f.path.clone()),
+            files,
+            lines: Vec::new(),
+            read_stamp: None,
+            last_check: Instant::now(),
+            filter: String::new(),
+            follow: true,
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        };
+        app.reload();
+        app
+    }
+
+    fn stamp(path: &PathBuf) -> Option<(u64, SystemTime)> {
+        let meta = std::fs::metadata(path).ok()?;
+        Some((meta.len(), meta.modified().ok()?))
+    }
+
+    /// Read the selected log again
+    fn reload(&mut self) {
+        self.lines.clear();
+        self.read_stamp = None;
+        self.message = None;
+        let Some(path) = &self.selected else { return };
+        match logs::read_tail(path, MAX_VIEW_BYTES) {
+            Ok(text) => {
+                self.lines = text.lines().map(str::to_string).collect();
+                self.read_stamp = Self::stamp(path);
+            }
+            Err(e) => self.message = Some(format!("could not read log: {}", e)),
+        }
+    }
+
+    /// Look for new logs, and new lines in the one shown
+    fn refresh(&mut self) {
+        self.last_check = Instant::now();
+        self.files = logs::log_files();
+        if let Some(path) = &self.selected {
+            if Self::stamp(path) != self.read_stamp {
+                self.reload();
+            }
+        }
+    }
+
+    fn select(&mut self, path: PathBuf) {
+        if self.selected.as_ref() != Some(&path) {
+            self.selected = Some(path);
+            self.reload();
+        }
+    }
+
+    /// Lines matching the filter, ignoring case
+    fn visible_lines(&self) -> Vec<&str> {
+        let filter = self.filter.to_lowercase();
+        self.lines
+            .iter()
+            .filter(|l| filter.is_empty() || l.to_lowercase().contains(&filter))
+            .map(String::as_str)
+            .collect()
+    }
+
+    fn copy_visible(&mut self, ctx: &Context) {
+        let lines = self.visible_lines();
+        let count = lines.len();
+        let text = lines.join("\n");
+        ctx.output_mut(|o| o.copied_text = text);
+        self.message = Some(format!("copied {} lines", count));
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("logs", |ui| {
+                    if ui.button("refresh       ⌘R").clicked() {
+                        self.refresh();
+                        ui.close_menu();
+                    }
+                    if ui.button("copy lines shown").clicked() {
+                        self.copy_visible(ui.ctx());
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.checkbox(&mut self.follow, "follow new lines").clicked() {
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowlogs", "slowLogs");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_log_list(&mut self, ui: &mut egui::Ui) {
+        let mut picked = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if self.files.is_empty() {
+                ui.weak("no logs yet");
+            }
+            for file in &self.files {
+                let selected = self.selected.as_ref() == Some(&file.path);
+                if ui.selectable_label(selected, &file.name).on_hover_text(file.path.display().to_string()).clicked() {
+                    picked = Some(file.path.clone());
+                }
+            }
+        });
+        if let Some(path) = picked {
+            self.select(path);
+        }
+    }
+
+    fn draw_lines(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("filter:");
+            ui.add(egui::TextEdit::singleline(&mut self.filter).desired_width(200.0).hint_text("words to find"));
+            if !self.filter.is_empty() && ui.button("clear").clicked() {
+                self.filter.clear();
+            }
+        });
+        ui.separator();
+        let lines = self.visible_lines();
+        if lines.is_empty() {
+            ui.weak(if self.lines.is_empty() { "this log is empty" } else { "no lines match" });
+            return;
+        }
+        // Stays with the newest line until scrolled up to read
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show_rows(ui, LINE_HEIGHT, lines.len(), |ui, rows| {
+                for line in &lines[rows] {
+                    ui.label(egui::RichText::new(*line).monospace().size(11.0).color(SlowColors::BLACK));
+                }
+            });
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+impl eframe::App for SlowLogsApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowlogs") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+
+        let following = self.follow && self.last_check.elapsed() >= FOLLOW_INTERVAL;
+        if following || ctx.input(|i| i.modifiers.command && i.key_pressed(Key::R)) {
+            self.refresh();
+        }
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| {
+                let name = self.files.iter()
+                    .find(|f| Some(&f.path) == self.selected.as_ref())
+                    .map(|f| f.name.as_str())
+                    .unwrap_or("no log");
+                let follow = if self.follow { "  |  following" } else { "" };
+                format!("{}  |  {} lines{}", name, self.lines.len(), follow)
+            });
+            status_bar(ui, &status);
+        });
+
+        SidePanel::left("logs")
+            .resizable(false)
+            .exact_width(150.0)
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(6.0)))
+            .show(ctx, |ui| self.draw_log_list(ui));
+
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(8.0)))
+            .show(ctx, |ui| self.draw_lines(ui));
+
+        self.draw_about(ctx);
+
+        // Only wake to look for new lines
+        if self.follow {
+            ctx.request_repaint_after(FOLLOW_INTERVAL.saturating_sub(self.last_check.elapsed()));
+        }
+        self.repaint.end_frame(ctx);
+    }
+}
@@ -0,0 +1,28 @@
+//! slowLogs - A log viewer for the Slow Computer
+//!
+//! Crash reports, each app's output and the system logs, newest lines last.
+
+mod app;
+
+use app::SlowLogsApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let viewport = window_layout::viewport("slowlogs", Placement::RememberLast, [620.0, 440.0])
+        .with_title("slowLogs");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowLogs",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowLogsApp::new(cc))
+        }),
+    )
+}
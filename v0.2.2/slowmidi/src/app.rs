@@ -1,16 +1,36 @@
 //! slowMidi — MIDI notation application with piano roll and notation views
 
 use egui::{ColorImage, Context, FontId, Key, Pos2, Rect, Sense, Stroke, TextureHandle, TextureOptions, Vec2};
-use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+use crate::bounce::{AudioFormat, Bounce, ExportOptions, Sound, Voice, BIT_DEPTHS, SAMPLE_RATES};
+use crate::drums::{DrumHit, DrumSound, DRUM_CHANNEL};
+use crate::generators::{self, ArpDirection};
+use crate::synth::{midi_to_freq, SineWave, DEFAULT_VELOCITY, MAX_NOTE_SECS, PLUGIN_SAMPLE_RATE};
+use rodio::{OutputStreamHandle, Sink};
 use serde::{Deserialize, Serialize};
+use slowcore::confirm::CloseGuard;
+use slowcore::drag::DropTarget;
+use slowcore::lazy::Lazy;
+use slowcore::plugins::{self, Plugin, PluginKind};
 use slowcore::repaint::RepaintController;
+use slowcore::sound::Player;
+use slowcore::storage::{self, Migration, RecentFiles, Versioned, KEEP_VERSIONS};
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, FileListItem, WindowAction};
-use slowcore::storage::FileBrowser;
+use slowcore::undo::{self, UndoAction, UndoStack};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, FileDialog, FileDialogMode, FileDialogResult, WindowAction};
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
 use std::collections::{HashMap, HashSet};
 
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowMidi",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "MIDI sequencer for slowOS",
+    formats: &["MIDI (.mid, .midi), JSON project", "exports WAV and FLAC audio"],
+    features: &["piano roll and notation views", "create and edit MIDI sequences", "variable note durations", "a percussion lane on a step grid"],
+    credits: &["egui/eframe (MIT), midly (MIT)", "hound (Apache-2.0), flacenc (Apache-2.0)"],
+    ..AboutInfo::EMPTY
+};
+
 /// Get MIDI directory (~/MIDI)
 fn midi_dir() -> PathBuf {
     if let Some(home) = dirs::home_dir() {
@@ -35,6 +55,14 @@ fn midi_dir() -> PathBuf {
 const KEY_HEIGHT: f32 = 12.0;
 const BEAT_WIDTH: f32 = 80.0;
 const PIANO_WIDTH: f32 = 60.0;
+/// Height of a row in the drum grid
+const DRUM_ROW_HEIGHT: f32 = 20.0;
+/// Step sizes the drum grid offers, in beats
+const DRUM_STEPS: [(&str, f32); 3] = [("1/8", 0.5), ("1/16", 0.25), ("1/32", 0.125)];
+/// Length drum hits are written to MIDI files with, in beats
+const DRUM_HIT_BEATS: f32 = 0.25;
+/// Note lengths and grid steps to choose from: (name, beats)
+const NOTE_DIVISIONS: [(&str, f32); 5] = [("1/16", 0.25), ("1/8", 0.5), ("1/4", 1.0), ("1/2", 2.0), ("whole", 4.0)];
 const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
 
 /// Scale types for quantization: (name, semitone intervals from root)
@@ -132,81 +160,6 @@ fn pitch_has_accidental(pitch: u8) -> Option<bool> {
     }
 }
 
-// ---------------------------------------------------------------
-// Simple sine wave audio source
-// ---------------------------------------------------------------
-
-/// A sine wave audio source for a single note
-struct SineWave {
-    freq: f32,
-    sample_rate: u32,
-    num_samples: usize,
-    current_sample: usize,
-}
-
-impl SineWave {
-    fn new(freq: f32, duration_ms: u32) -> Self {
-        let sample_rate = 44100;
-        let num_samples = (sample_rate * duration_ms / 1000) as usize;
-        Self {
-            freq,
-            sample_rate,
-            num_samples,
-            current_sample: 0,
-        }
-    }
-}
-
-impl Source for SineWave {
-    fn current_frame_len(&self) -> Option<usize> {
-        None
-    }
-
-    fn channels(&self) -> u16 {
-        1
-    }
-
-    fn sample_rate(&self) -> u32 {
-        self.sample_rate
-    }
-
-    fn total_duration(&self) -> Option<Duration> {
-        Some(Duration::from_millis((self.num_samples as u64 * 1000) / self.sample_rate as u64))
-    }
-}
-
-impl Iterator for SineWave {
-    type Item = f32;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.current_sample >= self.num_samples {
-            return None;
-        }
-
-        let t = self.current_sample as f32 / self.sample_rate as f32;
-        self.current_sample += 1;
-
-        // Simple envelope: attack/decay to avoid clicks
-        let envelope = if self.current_sample < 500 {
-            self.current_sample as f32 / 500.0
-        } else if self.current_sample > self.num_samples - 500 {
-            (self.num_samples - self.current_sample) as f32 / 500.0
-        } else {
-            1.0
-        };
-
-        // Apply limiter: sine * envelope * master volume, then soft clip
-        let sample = (t * self.freq * 2.0 * std::f32::consts::PI).sin() * 0.25 * envelope;
-        // Soft limiter to prevent clipping and protect speakers
-        Some(sample.tanh())
-    }
-}
-
-/// Convert MIDI note number to frequency
-fn midi_to_freq(note: u8) -> f32 {
-    440.0 * 2.0_f32.powf((note as f32 - 69.0) / 12.0)
-}
-
 // ---------------------------------------------------------------
 // MIDI Note representation
 // ---------------------------------------------------------------
@@ -371,6 +324,29 @@ pub struct MidiProject {
     /// Mid-piece key signature changes (sorted by beat)
     #[serde(default)]
     pub key_sig_changes: Vec<KeySigChange>,
+    /// The percussion lane
+    #[serde(default)]
+    pub drums: Vec<DrumHit>,
+}
+
+impl Versioned for MidiProject {
+    const MIGRATIONS: &'static [Migration] = &[
+        // 0 → 1: projects from before tempo, time and key changes,
+        // hairpins and dynamics have none of them
+        |v| {
+            for key in ["tempo_changes", "hairpins", "dynamic_marks", "time_sig_changes", "key_sig_changes"] {
+                if v.get(key).is_none() {
+                    v[key] = serde_json::Value::Array(Vec::new());
+                }
+            }
+        },
+        // 1 → 2: projects from before the percussion lane
+        |v| {
+            if v.get("drums").is_none() {
+                v["drums"] = serde_json::Value::Array(Vec::new());
+            }
+        },
+    ];
 }
 
 impl Default for MidiProject {
@@ -386,7 +362,250 @@ impl Default for MidiProject {
             dynamic_marks: Vec::new(),
             time_sig_changes: Vec::new(),
             key_sig_changes: Vec::new(),
+            drums: Vec::new(),
+        }
+    }
+}
+
+impl MidiProject {
+    /// Where each time signature takes over, and the beats in its bars
+    fn meters(&self) -> Vec<(f32, f32)> {
+        let beats_per_bar = |num: u8, den: u8| num.max(1) as f32 * 4.0 / den.max(1) as f32;
+        let mut meters = vec![(0.0, beats_per_bar(self.time_signature_num, self.time_signature_den))];
+        for tsc in &self.time_sig_changes {
+            if tsc.beat <= 0.0 {
+                meters[0].1 = beats_per_bar(tsc.num, tsc.den);
+            } else {
+                meters.push((tsc.beat, beats_per_bar(tsc.num, tsc.den)));
+            }
+        }
+        meters
+    }
+
+    /// Beats where bars start, from `from` to `to`. A time signature change
+    /// starts a new bar, wherever it falls.
+    fn bar_lines(&self, from: f32, to: f32) -> Vec<f32> {
+        let meters = self.meters();
+        let mut lines = Vec::new();
+        for (i, &(start, beats)) in meters.iter().enumerate() {
+            let end = meters.get(i + 1).map_or(f32::INFINITY, |m| m.0);
+            let mut bar = if from > start { start + ((from - start) / beats).ceil() * beats } else { start };
+            while bar < end && bar <= to {
+                lines.push(bar);
+                bar += beats;
+            }
+        }
+        lines
+    }
+
+    /// Start of the last bar before `beat`
+    fn bar_before(&self, beat: f32) -> f32 {
+        self.bar_lines(0.0, beat - 0.001).last().copied().unwrap_or(0.0)
+    }
+
+    /// Start of the first bar after `beat`
+    fn bar_after(&self, beat: f32) -> f32 {
+        let beat = beat.max(0.0) + 0.001;
+        // No bar is longer than 12/2
+        self.bar_lines(beat, beat + 32.0).first().copied().unwrap_or(beat + 4.0)
+    }
+
+    /// The project as a standard MIDI file
+    fn export_midi(&self) -> Result<Vec<u8>, ()> {
+        use midly::{Header, Format, Timing, Smf, Track, TrackEvent, TrackEventKind, MidiMessage};
+        use midly::num::{u4, u7, u28};
+
+        let ticks_per_beat: u16 = 480;
+
+        // Create MIDI events from notes
+        let mut events: Vec<(u32, TrackEventKind)> = Vec::new();
+
+        // Add initial tempo meta event (microseconds per beat = 60_000_000 / BPM)
+        let tempo_us = 60_000_000 / self.tempo;
+        events.push((0, TrackEventKind::Meta(midly::MetaMessage::Tempo(
+            midly::num::u24::new(tempo_us)
+        ))));
+
+        // Add mid-arrangement tempo changes
+        for tc in &self.tempo_changes {
+            let tick = (tc.beat * ticks_per_beat as f32) as u32;
+            let us = 60_000_000 / tc.bpm;
+            events.push((tick, TrackEventKind::Meta(midly::MetaMessage::Tempo(
+                midly::num::u24::new(us)
+            ))));
+        }
+
+        // Time signatures: the opening one, then the changes. The
+        // denominator is written as a power of two; 24 clocks a click and
+        // 8 thirty-seconds a quarter are the usual.
+        let opening = (0.0, self.time_signature_num, self.time_signature_den);
+        let changes = self.time_sig_changes.iter().map(|t| (t.beat, t.num, t.den));
+        for (beat, num, den) in std::iter::once(opening).chain(changes) {
+            let tick = (beat.max(0.0) * ticks_per_beat as f32) as u32;
+            events.push((tick, TrackEventKind::Meta(midly::MetaMessage::TimeSignature(
+                num, den.max(1).trailing_zeros() as u8, 24, 8,
+            ))));
+        }
+
+        // Convert notes to MIDI events
+        for note in &self.notes {
+            let start_tick = (note.start * ticks_per_beat as f32) as u32;
+            let end_tick = ((note.start + note.duration) * ticks_per_beat as f32) as u32;
+            let channel = u4::new(0);
+            let key = u7::new(note.pitch);
+            let vel = u7::new(note.velocity);
+
+            // Note on
+            events.push((start_tick, TrackEventKind::Midi {
+                channel,
+                message: MidiMessage::NoteOn { key, vel },
+            }));
+
+            // Note off
+            events.push((end_tick, TrackEventKind::Midi {
+                channel,
+                message: MidiMessage::NoteOff { key, vel: u7::new(0) },
+            }));
+        }
+
+        // Drum hits, on the percussion channel
+        for hit in &self.drums {
+            let start_tick = (hit.beat * ticks_per_beat as f32) as u32;
+            let end_tick = ((hit.beat + DRUM_HIT_BEATS) * ticks_per_beat as f32) as u32;
+            let channel = u4::new(DRUM_CHANNEL);
+            let key = u7::new(hit.sound.key());
+            events.push((start_tick, TrackEventKind::Midi {
+                channel,
+                message: MidiMessage::NoteOn { key, vel: u7::new(hit.velocity) },
+            }));
+            events.push((end_tick, TrackEventKind::Midi {
+                channel,
+                message: MidiMessage::NoteOff { key, vel: u7::new(0) },
+            }));
+        }
+
+        // Sort by time
+        events.sort_by_key(|(time, _)| *time);
+
+        // Convert to delta times
+        let mut track: Track = Vec::new();
+        let mut last_time: u32 = 0;
+        for (time, kind) in events {
+            let delta = time - last_time;
+            track.push(TrackEvent {
+                delta: u28::new(delta),
+                kind,
+            });
+            last_time = time;
+        }
+
+        // Add end of track
+        track.push(TrackEvent {
+            delta: u28::new(0),
+            kind: TrackEventKind::Meta(midly::MetaMessage::EndOfTrack),
+        });
+
+        let smf = Smf {
+            header: Header {
+                format: Format::SingleTrack,
+                timing: Timing::Metrical(midly::num::u15::new(ticks_per_beat)),
+            },
+            tracks: vec![track],
+        };
+
+        let mut buffer = Vec::new();
+        smf.write(&mut buffer).map_err(|_| ())?;
+        Ok(buffer)
+    }
+
+    /// A project from a standard MIDI file
+    fn import_midi(smf: &midly::Smf) -> Self {
+        let mut project = MidiProject::default();
+        let ticks_per_beat = match smf.header.timing {
+            midly::Timing::Metrical(tpb) => tpb.as_int() as f32,
+            _ => 480.0,
+        };
+
+        for track in &smf.tracks {
+            let mut time: u32 = 0;
+            let mut pending_notes: std::collections::HashMap<u8, (f32, u8)> = std::collections::HashMap::new();
+
+            for event in track {
+                time += event.delta.as_int();
+                let beat = time as f32 / ticks_per_beat;
+
+                match event.kind {
+                    midly::TrackEventKind::Midi { channel, message: midly::MidiMessage::NoteOn { key, vel } }
+                        if channel.as_int() == DRUM_CHANNEL && vel.as_int() > 0 =>
+                    {
+                        // Percussion: a hit where each note starts
+                        if let Some(sound) = DrumSound::from_key(key.as_int()) {
+                            project.drums.push(DrumHit { sound, beat, velocity: vel.as_int() });
+                        }
+                    }
+                    // The rest of the percussion channel is where hits end
+                    midly::TrackEventKind::Midi { channel, .. } if channel.as_int() == DRUM_CHANNEL => {}
+                    midly::TrackEventKind::Midi { message, .. } => {
+                        match message {
+                            midly::MidiMessage::NoteOn { key, vel } => {
+                                if vel.as_int() > 0 {
+                                    pending_notes.insert(key.as_int(), (beat, vel.as_int()));
+                                } else {
+                                    // Note off
+                                    if let Some((start, velocity)) = pending_notes.remove(&key.as_int()) {
+                                        project.notes.push(MidiNote {
+                                            pitch: key.as_int(),
+                                            start,
+                                            duration: (beat - start).max(0.1),
+                                            velocity,
+                                        });
+                                    }
+                                }
+                            }
+                            midly::MidiMessage::NoteOff { key, .. } => {
+                                if let Some((start, velocity)) = pending_notes.remove(&key.as_int()) {
+                                    project.notes.push(MidiNote {
+                                        pitch: key.as_int(),
+                                        start,
+                                        duration: (beat - start).max(0.1),
+                                        velocity,
+                                    });
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    midly::TrackEventKind::Meta(midly::MetaMessage::TimeSignature(num, den_power, ..)) => {
+                        let den = 1_u8 << den_power.min(7);
+                        if time == 0 {
+                            project.time_signature_num = num;
+                            project.time_signature_den = den;
+                        } else {
+                            project.time_sig_changes.push(TimeSigChange { beat, num, den });
+                        }
+                    }
+                    // A zero-length beat has no tempo; skip it
+                    midly::TrackEventKind::Meta(midly::MetaMessage::Tempo(tempo)) if tempo.as_int() > 0 => {
+                        let bpm = (60_000_000 / tempo.as_int()) as u32;
+                        if time == 0 {
+                            // Initial tempo
+                            project.tempo = bpm;
+                        } else {
+                            // Mid-arrangement tempo change
+                            project.tempo_changes.push(TempoChange {
+                                beat,
+                                bpm,
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
         }
+        // Ensure tempo and time signature changes are sorted by beat
+        project.tempo_changes.sort_by(|a, b| a.beat.partial_cmp(&b.beat).unwrap());
+        project.time_sig_changes.sort_by(|a, b| a.beat.partial_cmp(&b.beat).unwrap());
+        project
     }
 }
 
@@ -426,6 +645,20 @@ fn seconds_to_beat(start_beat: f32, elapsed_secs: f32, initial_tempo: u32, chang
     beat + remaining * bpm / 60.0
 }
 
+/// Seconds from the start of the piece to an absolute beat position,
+/// accounting for tempo changes before it
+fn beat_to_seconds(beat: f32, initial_tempo: u32, changes: &[TempoChange]) -> f32 {
+    let mut secs = 0.0;
+    let mut from = 0.0_f32;
+    let mut bpm = initial_tempo as f32;
+    for tc in changes.iter().take_while(|c| c.beat < beat) {
+        secs += (tc.beat - from).max(0.0) * 60.0 / bpm;
+        from = from.max(tc.beat);
+        bpm = tc.bpm as f32;
+    }
+    secs + (beat - from) * 60.0 / bpm
+}
+
 // ---------------------------------------------------------------
 // View modes
 // ---------------------------------------------------------------
@@ -434,6 +667,8 @@ fn seconds_to_beat(start_beat: f32, elapsed_secs: f32, initial_tempo: u32, chang
 pub enum ViewMode {
     PianoRoll,
     Notation,
+    /// The percussion lane's step grid
+    Drums,
 }
 
 // ---------------------------------------------------------------
@@ -448,18 +683,48 @@ pub enum EditTool {
     Erase,
 }
 
+// ---------------------------------------------------------------
+// Generators
+// ---------------------------------------------------------------
+
+/// A generator whose settings dialog is up
+enum Generator {
+    /// A run up or down the scale between two pitches
+    ScaleRun { from: u8, to: u8 },
+    /// `hits` spread over `steps`, played `repeats` times
+    Euclid { pitch: u8, hits: usize, steps: usize, rotation: usize, repeats: usize },
+}
+
+// ---------------------------------------------------------------
+// Audio export
+// ---------------------------------------------------------------
+
+/// File → export audio, from choosing the options to the result
+enum ExportStage {
+    Options,
+    Saving(Box<FileDialog>),
+    Rendering { bounce: Bounce, path: PathBuf },
+    Done(Result<PathBuf, String>),
+}
+
+/// How long a frame spends rendering an export
+const BOUNCE_SLICE: Duration = Duration::from_millis(30);
+
 // ---------------------------------------------------------------
 // Application state
 // ---------------------------------------------------------------
 
+/// What undo takes back: the notes and the drum hits
+type Arrangement = (Vec<MidiNote>, Vec<DrumHit>);
+
 pub struct SlowMidiApp {
     project: MidiProject,
     file_path: Option<PathBuf>,
     modified: bool,
 
     // Undo/Redo
-    undo_stack: Vec<Vec<MidiNote>>,
-    redo_stack: Vec<Vec<MidiNote>>,
+    history: UndoStack<Arrangement>,
+    recent_files: RecentFiles,
 
     // View state
     view_mode: ViewMode,
@@ -479,26 +744,41 @@ pub struct SlowMidiApp {
     last_paint_beat: f32,
     last_paint_pitch: u8,
 
+    // Drum grid
+    /// Beats a step of the drum grid spans
+    drum_step: f32,
+    /// While the mouse is held in the drum grid, whether it adds hits or
+    /// takes them away, as decided by the first step it pressed
+    drum_paint: Option<bool>,
+
     // Playback
     playing: bool,
     playhead: f32, // Position in beats
     play_start_time: Option<Instant>,
     play_start_beat: f32,
 
-    // Audio output
-    _audio_stream: Option<OutputStream>,
-    audio_handle: Option<OutputStreamHandle>,
+    // Audio output (shared with system sounds)
+    audio_handle: Lazy<Option<&'static OutputStreamHandle>>,
+    /// Registration with the system mixer (mute, per-app volume)
+    player: Player,
+    /// Instruments from plugins
+    instruments: Vec<Plugin>,
+    /// The plugin instrument notes play on; the built-in sine if none
+    instrument: Option<usize>,
     /// Tracks which notes have been triggered in current playback (by index)
     triggered_notes: HashSet<usize>,
+    /// Likewise for drum hits
+    triggered_hits: HashSet<usize>,
 
     // UI state
     show_about: bool,
-    show_file_browser: bool,
-    file_browser: FileBrowser,
-    is_saving: bool,
-    save_filename: String,
-    show_close_confirm: bool,
-    close_confirmed: bool,
+    file_dialog: Option<FileDialog>,
+    /// A generator being set up
+    generator: Option<Generator>,
+    /// An audio export, while one is being set up or made
+    export: Option<ExportStage>,
+    export_options: ExportOptions,
+    close_guard: CloseGuard,
     /// Currently pressed piano key (for visual feedback)
     pressed_key: Option<u8>,
     /// Time the key was pressed (auto-release after a short duration)
@@ -513,16 +793,13 @@ pub struct SlowMidiApp {
 
 impl SlowMidiApp {
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        // Initialize audio output
-        let (stream, handle) = OutputStream::try_default().ok().unzip();
-
         Self {
             project: MidiProject::default(),
             file_path: None,
             modified: false,
 
-            undo_stack: Vec::new(),
-            redo_stack: Vec::new(),
+            history: UndoStack::new(50),
+            recent_files: RecentFiles::for_app("slowmidi"),
 
             view_mode: ViewMode::PianoRoll,
             scroll_x: 0.0,
@@ -539,22 +816,28 @@ impl SlowMidiApp {
             last_paint_beat: -1.0,
             last_paint_pitch: 255,
 
+            drum_step: 0.25,
+            drum_paint: None,
+
             playing: false,
             playhead: 0.0,
             play_start_time: None,
             play_start_beat: 0.0,
 
-            _audio_stream: stream,
-            audio_handle: handle,
+            // Opening the sound card can take a while; not before the window shows
+            audio_handle: Lazy::new("audio", slowcore::sound::output),
+            player: Player::register("slowmidi"),
+            instruments: plugins::load(PluginKind::Instrument),
+            instrument: None,
             triggered_notes: HashSet::new(),
+            triggered_hits: HashSet::new(),
 
             show_about: false,
-            show_file_browser: false,
-            file_browser: FileBrowser::new(midi_dir()),
-            is_saving: false,
-            save_filename: String::new(),
-            show_close_confirm: false,
-            close_confirmed: false,
+            file_dialog: None,
+            generator: None,
+            export: None,
+            export_options: ExportOptions::default(),
+            close_guard: CloseGuard::new(),
             pressed_key: None,
             key_press_time: Instant::now(),
             clef_textures: HashMap::new(),
@@ -595,19 +878,21 @@ impl SlowMidiApp {
 
     /// Save current state to undo stack before making changes
     fn save_undo_state(&mut self) {
-        self.undo_stack.push(self.project.notes.clone());
-        self.redo_stack.clear();
-        // Limit undo history to 50 states
-        if self.undo_stack.len() > 50 {
-            self.undo_stack.remove(0);
-        }
+        self.history.save(&(self.project.notes.clone(), self.project.drums.clone()));
+    }
+
+    /// Take a step back or forward through the notes' and drum hits'
+    /// history, returning whether there was one
+    fn step_history(&mut self, redo: bool) -> bool {
+        let mut arrangement = (std::mem::take(&mut self.project.notes), std::mem::take(&mut self.project.drums));
+        let stepped = if redo { self.history.redo(&mut arrangement) } else { self.history.undo(&mut arrangement) };
+        (self.project.notes, self.project.drums) = arrangement;
+        stepped
     }
 
     /// Undo last change
     fn undo(&mut self) {
-        if let Some(prev_notes) = self.undo_stack.pop() {
-            self.redo_stack.push(self.project.notes.clone());
-            self.project.notes = prev_notes;
+        if self.step_history(false) {
             self.selected_notes.clear();
             self.modified = true;
         }
@@ -615,50 +900,77 @@ impl SlowMidiApp {
 
     /// Redo last undone change
     fn redo(&mut self) {
-        if let Some(next_notes) = self.redo_stack.pop() {
-            self.undo_stack.push(self.project.notes.clone());
-            self.project.notes = next_notes;
+        if self.step_history(true) {
             self.selected_notes.clear();
             self.modified = true;
         }
     }
 
-    /// Play a single note as a sine wave
-    fn play_note(&self, pitch: u8, duration_beats: f32) {
-        if let Some(ref handle) = self.audio_handle {
+    /// Play a single note on the chosen instrument
+    fn play_note(&mut self, pitch: u8, velocity: u8, duration_beats: f32) {
+        if let Some(handle) = self.audio_handle.wait().and_then(|handle| *handle) {
             let freq = midi_to_freq(pitch);
             // Convert duration in beats to milliseconds using tempo at current playhead
             let current_bpm = tempo_at_beat(self.playhead, self.project.tempo, &self.project.tempo_changes);
             let duration_ms = (duration_beats * 60.0 * 1000.0 / current_bpm as f32) as u32;
-            let duration_ms = duration_ms.min(8000); // Cap at 8 seconds
-            let source = SineWave::new(freq, duration_ms);
+            let duration_ms = duration_ms.min((MAX_NOTE_SECS * 1000.0) as u32);
             if let Ok(sink) = Sink::try_new(handle) {
                 // Conservative volume to protect speakers
-                sink.set_volume(0.3);
-                sink.append(source);
+                sink.set_volume(0.3 * self.player.volume());
+                match self.instrument.and_then(|i| self.instruments.get(i)) {
+                    Some(plugin) => {
+                        let mut samples = vec![0.0; (PLUGIN_SAMPLE_RATE * duration_ms / 1000) as usize];
+                        plugin.render(pitch, velocity, PLUGIN_SAMPLE_RATE, &mut samples);
+                        sink.append(rodio::buffer::SamplesBuffer::new(1, PLUGIN_SAMPLE_RATE, samples));
+                    }
+                    None => sink.append(SineWave::new(freq, duration_ms, PLUGIN_SAMPLE_RATE)),
+                }
                 sink.detach(); // Let it play without blocking
             }
         }
     }
 
+    /// Play a drum hit
+    fn play_drum(&mut self, sound: DrumSound, velocity: u8) {
+        if let Some(handle) = self.audio_handle.wait().and_then(|handle| *handle) {
+            if let Ok(sink) = Sink::try_new(handle) {
+                sink.set_volume(0.3 * self.player.volume());
+                let mut samples = vec![0.0; (sound.length() * PLUGIN_SAMPLE_RATE as f32) as usize];
+                sound.render(velocity, PLUGIN_SAMPLE_RATE, &mut samples);
+                sink.append(rodio::buffer::SamplesBuffer::new(1, PLUGIN_SAMPLE_RATE, samples));
+                sink.detach();
+            }
+        }
+    }
+
+    /// Switch views, putting the vertical scroll where the new one starts
+    fn set_view(&mut self, mode: ViewMode) {
+        if self.view_mode != mode {
+            match mode {
+                ViewMode::PianoRoll => self.scroll_y = 30.0 * KEY_HEIGHT, // Around middle C
+                ViewMode::Notation => self.scroll_y = 0.0,
+                ViewMode::Drums => {}
+            }
+        }
+        self.view_mode = mode;
+    }
+
     fn handle_keys(&mut self, ctx: &Context) {
         // Consume Tab and Cmd+/- to prevent menu focus and zoom
         slowcore::theme::consume_special_keys(ctx);
 
         // Handle dropped MIDI files (drag-and-drop)
-        let dropped: Vec<PathBuf> = ctx.input(|i| {
-            i.raw.dropped_files.iter()
-                .filter_map(|f| f.path.clone())
-                .filter(|p| {
-                    let ext = p.extension().and_then(|e| e.to_str()).unwrap_or("");
-                    ext == "mid" || ext == "midi" || ext == "json"
-                })
-                .collect()
-        });
+        let dropped = DropTarget::new(&["mid", "midi", "json"]).show(ctx);
         if let Some(path) = dropped.into_iter().next() {
             self.load_from_path(path);
         }
 
+        match undo::take_shortcut(ctx) {
+            Some(UndoAction::Undo) => self.undo(),
+            Some(UndoAction::Redo) => self.redo(),
+            None => {}
+        }
+
         ctx.input(|i| {
             let cmd = i.modifiers.command;
 
@@ -675,9 +987,9 @@ impl SlowMidiApp {
                     self.scroll_y = 0.0;
                 }
             }
-            // Navigate by bar (4 beats = 1 measure)
+            // Navigate by bar, as the time signatures lay bars out
             if i.key_pressed(Key::ArrowLeft) {
-                self.playhead = (self.playhead - 4.0).max(0.0);
+                self.playhead = self.project.bar_before(self.playhead);
                 // Update play start if playing
                 if self.playing {
                     self.play_start_beat = self.playhead;
@@ -685,7 +997,7 @@ impl SlowMidiApp {
                 }
             }
             if i.key_pressed(Key::ArrowRight) {
-                self.playhead += 4.0;
+                self.playhead = self.project.bar_after(self.playhead);
                 // Update play start if playing
                 if self.playing {
                     self.play_start_beat = self.playhead;
@@ -706,16 +1018,13 @@ impl SlowMidiApp {
 
             // View switching
             if i.key_pressed(Key::Num1) {
-                if self.view_mode == ViewMode::Notation {
-                    self.scroll_y = 30.0 * KEY_HEIGHT; // Restore piano roll vertical scroll
-                }
-                self.view_mode = ViewMode::PianoRoll;
+                self.set_view(ViewMode::PianoRoll);
             }
             if i.key_pressed(Key::Num2) {
-                if self.view_mode == ViewMode::PianoRoll {
-                    self.scroll_y = 0.0; // Reset for notation horizontal scroll
-                }
-                self.view_mode = ViewMode::Notation;
+                self.set_view(ViewMode::Notation);
+            }
+            if i.key_pressed(Key::Num3) {
+                self.set_view(ViewMode::Drums);
             }
 
             // Tool switching
@@ -742,15 +1051,6 @@ impl SlowMidiApp {
                 self.select_all();
             }
 
-            // Undo/Redo
-            if cmd && i.key_pressed(Key::Z) {
-                if i.modifiers.shift {
-                    self.redo();
-                } else {
-                    self.undo();
-                }
-            }
-
             // Zoom (+ / = to zoom in, - to zoom out)
             if !cmd {
                 if i.key_pressed(Key::Plus) || i.key_pressed(Key::Equals) {
@@ -773,6 +1073,7 @@ impl SlowMidiApp {
             self.play_start_beat = self.playhead;
             // Clear triggered notes when starting playback
             self.triggered_notes.clear();
+            self.triggered_hits.clear();
         }
     }
 
@@ -789,30 +1090,46 @@ impl SlowMidiApp {
                 );
 
                 // Find notes that the playhead just passed over
-                let notes_to_play: Vec<(usize, u8, f32)> = self.project.notes.iter().enumerate()
+                let notes_to_play: Vec<(usize, u8, u8, f32)> = self.project.notes.iter().enumerate()
                     .filter(|(idx, note)| {
                         // Note starts between old and new playhead position
                         note.start >= old_playhead && note.start < self.playhead
                             && !self.triggered_notes.contains(idx)
                     })
-                    .map(|(idx, note)| (idx, note.pitch, note.duration))
+                    .map(|(idx, note)| (idx, note.pitch, note.velocity, note.duration))
                     .collect();
 
                 // Mark notes as triggered and play them
-                for (idx, pitch, duration) in notes_to_play {
+                for (idx, pitch, velocity, duration) in notes_to_play {
                     self.triggered_notes.insert(idx);
-                    self.play_note(pitch, duration);
+                    self.play_note(pitch, velocity, duration);
                 }
 
-                // Loop at end of content
+                // And the drum hits
+                let hits_to_play: Vec<(usize, DrumSound, u8)> = self.project.drums.iter().enumerate()
+                    .filter(|(idx, hit)| {
+                        hit.beat >= old_playhead && hit.beat < self.playhead && !self.triggered_hits.contains(idx)
+                    })
+                    .map(|(idx, hit)| (idx, hit.sound, hit.velocity))
+                    .collect();
+                for (idx, sound, velocity) in hits_to_play {
+                    self.triggered_hits.insert(idx);
+                    self.play_drum(sound, velocity);
+                }
+
+                // Loop at end of content; drum patterns at the end of their bar
+                let drums_end = self.project.drums.iter()
+                    .map(|h| self.project.bar_after(h.beat))
+                    .fold(0.0_f32, f32::max);
                 let max_beat = self.project.notes.iter()
                     .map(|n| n.start + n.duration)
-                    .fold(4.0_f32, |a, b| a.max(b));
+                    .fold(4.0_f32.max(drums_end), |a, b| a.max(b));
                 if self.playhead > max_beat {
                     self.playhead = 0.0;
                     self.play_start_time = Some(Instant::now());
                     self.play_start_beat = 0.0;
                     self.triggered_notes.clear(); // Reset for loop
+                    self.triggered_hits.clear();
                     // Snap view back to beginning when looping
                     self.scroll_x = 0.0;
                     if self.view_mode == ViewMode::Notation {
@@ -833,18 +1150,16 @@ impl SlowMidiApp {
     }
 
     fn show_open_dialog(&mut self) {
-        self.file_browser = FileBrowser::new(midi_dir())
-            .with_filter(vec!["mid".into(), "midi".into()]);
-        self.show_file_browser = true;
-        self.is_saving = false;
+        self.file_dialog = Some(FileDialog::open(midi_dir()).filter(&["mid", "midi"]));
     }
 
     fn show_save_dialog(&mut self) {
-        self.file_browser = FileBrowser::new(midi_dir())
-            .with_filter(vec!["mid".into(), "midi".into()]);
-        self.show_file_browser = true;
-        self.is_saving = true;
-        self.save_filename = "untitled.mid".into();
+        self.file_dialog = Some(
+            FileDialog::save(midi_dir(), "untitled.mid")
+                .title("save project")
+                .filter(&["mid", "midi"])
+                .default_extension("mid"),
+        );
     }
 
     fn save_project(&mut self) {
@@ -857,190 +1172,49 @@ impl SlowMidiApp {
 
     fn save_to_path(&mut self, path: PathBuf) {
         // Export as standard MIDI file
-        if let Ok(data) = self.export_midi() {
-            if std::fs::write(&path, data).is_ok() {
-                self.file_path = Some(path);
-                self.modified = false;
-            }
-        }
-    }
-
-    fn export_midi(&self) -> Result<Vec<u8>, ()> {
-        use midly::{Header, Format, Timing, Smf, Track, TrackEvent, TrackEventKind, MidiMessage};
-        use midly::num::{u4, u7, u28};
-
-        let ticks_per_beat: u16 = 480;
-
-        // Create MIDI events from notes
-        let mut events: Vec<(u32, TrackEventKind)> = Vec::new();
-
-        // Add initial tempo meta event (microseconds per beat = 60_000_000 / BPM)
-        let tempo_us = 60_000_000 / self.project.tempo;
-        events.push((0, TrackEventKind::Meta(midly::MetaMessage::Tempo(
-            midly::num::u24::new(tempo_us)
-        ))));
-
-        // Add mid-arrangement tempo changes
-        for tc in &self.project.tempo_changes {
-            let tick = (tc.beat * ticks_per_beat as f32) as u32;
-            let us = 60_000_000 / tc.bpm;
-            events.push((tick, TrackEventKind::Meta(midly::MetaMessage::Tempo(
-                midly::num::u24::new(us)
-            ))));
-        }
-
-        // Convert notes to MIDI events
-        for note in &self.project.notes {
-            let start_tick = (note.start * ticks_per_beat as f32) as u32;
-            let end_tick = ((note.start + note.duration) * ticks_per_beat as f32) as u32;
-            let channel = u4::new(0);
-            let key = u7::new(note.pitch);
-            let vel = u7::new(note.velocity);
-
-            // Note on
-            events.push((start_tick, TrackEventKind::Midi {
-                channel,
-                message: MidiMessage::NoteOn { key, vel },
-            }));
-
-            // Note off
-            events.push((end_tick, TrackEventKind::Midi {
-                channel,
-                message: MidiMessage::NoteOff { key, vel: u7::new(0) },
-            }));
-        }
-
-        // Sort by time
-        events.sort_by_key(|(time, _)| *time);
-
-        // Convert to delta times
-        let mut track: Track = Vec::new();
-        let mut last_time: u32 = 0;
-        for (time, kind) in events {
-            let delta = time - last_time;
-            track.push(TrackEvent {
-                delta: u28::new(delta),
-                kind,
-            });
-            last_time = time;
-        }
-
-        // Add end of track
-        track.push(TrackEvent {
-            delta: u28::new(0),
-            kind: TrackEventKind::Meta(midly::MetaMessage::EndOfTrack),
-        });
-
-        let smf = Smf {
-            header: Header {
-                format: Format::SingleTrack,
-                timing: Timing::Metrical(midly::num::u15::new(ticks_per_beat)),
-            },
-            tracks: vec![track],
-        };
-
-        let mut buffer = Vec::new();
-        smf.write(&mut buffer).map_err(|_| ())?;
-        Ok(buffer)
-    }
-
-    pub fn load_from_path(&mut self, path: PathBuf) {
-        // Stop any current playback before loading
-        self.playing = false;
-        self.play_start_time = None;
-        self.triggered_notes.clear();
-
-        // Try loading as JSON first
-        if let Ok(content) = std::fs::read_to_string(&path) {
-            if let Ok(project) = serde_json::from_str::<MidiProject>(&content) {
-                self.project = project;
-                self.file_path = Some(path);
-                self.modified = false;
-                self.selected_notes.clear();
-                self.playhead = 0.0;
-                return;
-            }
-        }
-
-        // Try loading as MIDI file
-        if let Ok(data) = std::fs::read(&path) {
-            if let Ok(smf) = midly::Smf::parse(&data) {
-                self.import_midi(&smf);
+        if let Ok(data) = self.project.export_midi() {
+            if storage::save_versioned(&path, &data, KEEP_VERSIONS).is_ok() {
+                self.recent_files.record(&path);
                 self.file_path = Some(path);
                 self.modified = false;
-                self.selected_notes.clear();
-                self.playhead = 0.0;
             }
         }
-    }
-
-    fn import_midi(&mut self, smf: &midly::Smf) {
-        self.project = MidiProject::default();
-        let ticks_per_beat = match smf.header.timing {
-            midly::Timing::Metrical(tpb) => tpb.as_int() as f32,
-            _ => 480.0,
-        };
-
-        for track in &smf.tracks {
-            let mut time: u32 = 0;
-            let mut pending_notes: std::collections::HashMap<u8, (f32, u8)> = std::collections::HashMap::new();
-
-            for event in track {
-                time += event.delta.as_int();
-                let beat = time as f32 / ticks_per_beat;
-
-                match event.kind {
-                    midly::TrackEventKind::Midi { message, .. } => {
-                        match message {
-                            midly::MidiMessage::NoteOn { key, vel } => {
-                                if vel.as_int() > 0 {
-                                    pending_notes.insert(key.as_int(), (beat, vel.as_int()));
-                                } else {
-                                    // Note off
-                                    if let Some((start, velocity)) = pending_notes.remove(&key.as_int()) {
-                                        self.project.notes.push(MidiNote {
-                                            pitch: key.as_int(),
-                                            start,
-                                            duration: (beat - start).max(0.1),
-                                            velocity,
-                                        });
-                                    }
-                                }
-                            }
-                            midly::MidiMessage::NoteOff { key, .. } => {
-                                if let Some((start, velocity)) = pending_notes.remove(&key.as_int()) {
-                                    self.project.notes.push(MidiNote {
-                                        pitch: key.as_int(),
-                                        start,
-                                        duration: (beat - start).max(0.1),
-                                        velocity,
-                                    });
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                    midly::TrackEventKind::Meta(midly::MetaMessage::Tempo(tempo)) => {
-                        let bpm = (60_000_000 / tempo.as_int()) as u32;
-                        if time == 0 {
-                            // Initial tempo
-                            self.project.tempo = bpm;
-                        } else {
-                            // Mid-arrangement tempo change
-                            self.project.tempo_changes.push(TempoChange {
-                                beat,
-                                bpm,
-                            });
-                        }
-                    }
-                    _ => {}
-                }
+    }
+
+    pub fn load_from_path(&mut self, path: PathBuf) {
+        // Stop any current playback before loading
+        self.playing = false;
+        self.play_start_time = None;
+        self.triggered_notes.clear();
+        self.triggered_hits.clear();
+
+        // Try loading as JSON first
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            if let Ok(project) = storage::from_versioned_json::<MidiProject>(&content) {
+                self.project = project;
+                self.recent_files.record(&path);
+                self.file_path = Some(path);
+                self.modified = false;
+                self.selected_notes.clear();
+                self.playhead = 0.0;
+                return;
+            }
+        }
+
+        // Try loading as MIDI file
+        if let Ok(data) = std::fs::read(&path) {
+            if let Ok(smf) = midly::Smf::parse(&data) {
+                self.project = MidiProject::import_midi(&smf);
+                self.recent_files.record(&path);
+                self.file_path = Some(path);
+                self.modified = false;
+                self.selected_notes.clear();
+                self.playhead = 0.0;
             }
         }
-        // Ensure tempo changes are sorted by beat
-        self.project.tempo_changes.sort_by(|a, b| a.beat.partial_cmp(&b.beat).unwrap());
     }
 
+
     fn delete_selected(&mut self) {
         if !self.selected_notes.is_empty() {
             self.save_undo_state();
@@ -1102,7 +1276,7 @@ impl SlowMidiApp {
                 if ui.button("erase (e)").clicked() { self.edit_tool = EditTool::Erase; ui.close_menu(); }
                 ui.separator();
                 ui.label("note duration:");
-                for &(label, val) in &[("1/16", 0.25_f32), ("1/8", 0.5), ("1/4", 1.0), ("1/2", 2.0), ("whole", 4.0)] {
+                for (label, val) in NOTE_DIVISIONS {
                     let sel = (self.note_duration - val).abs() < 0.01;
                     if ui.button(if sel { format!("> {}", label) } else { format!("  {}", label) }).clicked() {
                         self.note_duration = val; ui.close_menu();
@@ -1110,7 +1284,7 @@ impl SlowMidiApp {
                 }
                 ui.separator();
                 ui.label("grid snap:");
-                for &(label, val) in &[("1/16", 0.25_f32), ("1/8", 0.5), ("1/4", 1.0), ("1/2", 2.0), ("whole", 4.0)] {
+                for (label, val) in NOTE_DIVISIONS {
                     let sel = (self.grid_division - val).abs() < 0.01;
                     if ui.button(if sel { format!("> {}", label) } else { format!("  {}", label) }).clicked() {
                         self.grid_division = val; ui.close_menu();
@@ -1364,15 +1538,8 @@ impl SlowMidiApp {
         let mut beat = visible_start;
         while beat < visible_end {
             let x = grid_rect.min.x + beat * beat_width - self.scroll_x;
-            // Thicker line at measure boundaries (every 4 beats)
-            let stroke_width = if (beat % 4.0).abs() < 0.001 {
-                1.5
-            } else if (beat % 1.0).abs() < 0.001 {
-                // Quarter note boundary
-                1.0
-            } else {
-                0.5
-            };
+            // Quarter note boundaries heavier
+            let stroke_width = if (beat % 1.0).abs() < 0.001 { 1.0 } else { 0.5 };
             painter.vline(
                 x,
                 grid_rect.y_range(),
@@ -1380,6 +1547,11 @@ impl SlowMidiApp {
             );
             beat += grid_step;
         }
+        // Thicker line at bar boundaries, as the time signatures lay them out
+        for bar in self.project.bar_lines(visible_start, visible_end) {
+            let x = grid_rect.min.x + bar * beat_width - self.scroll_x;
+            painter.vline(x, grid_rect.y_range(), Stroke::new(1.5, SlowColors::BLACK));
+        }
 
         // Draw tempo change markers as dashed vertical lines with BPM labels
         for tc in &self.project.tempo_changes {
@@ -1406,6 +1578,20 @@ impl SlowMidiApp {
             }
         }
 
+        // Time signature changes, labelled under the tempo's
+        for tsc in &self.project.time_sig_changes {
+            let ts_x = grid_rect.min.x + tsc.beat * beat_width - self.scroll_x;
+            if ts_x >= grid_rect.min.x && ts_x <= grid_rect.max.x {
+                painter.text(
+                    Pos2::new(ts_x + 2.0, grid_rect.min.y + 14.0),
+                    egui::Align2::LEFT_TOP,
+                    format!("{}/{}", tsc.num, tsc.den),
+                    egui::FontId::proportional(10.0),
+                    SlowColors::BLACK,
+                );
+            }
+        }
+
         // Draw playhead
         let playhead_x = grid_rect.min.x + self.playhead * beat_width - self.scroll_x;
         if playhead_x >= grid_rect.min.x && playhead_x <= grid_rect.max.x {
@@ -1536,7 +1722,7 @@ impl SlowMidiApp {
                                 let pitch = quantize_to_scale(pitch, self.scale_root, SCALE_TYPES[self.scale_type].1);
                                 self.project.notes.push(MidiNote::new(pitch, quantized_beat, self.note_duration));
                                 // Play preview sound
-                                self.play_note(pitch, self.note_duration.min(0.5));
+                                self.play_note(pitch, DEFAULT_VELOCITY, self.note_duration.min(0.5));
                                 // Track for paint tool
                                 self.last_paint_beat = quantized_beat;
                                 self.last_paint_pitch = pitch;
@@ -1588,7 +1774,7 @@ impl SlowMidiApp {
                     let pitch = 127 - ((pos.y - rect.min.y + self.scroll_y) / key_height) as u8;
                     self.pressed_key = Some(pitch);
                     self.key_press_time = Instant::now();
-                    self.play_note(pitch, 0.5);
+                    self.play_note(pitch, DEFAULT_VELOCITY, 0.5);
                 }
             }
         }
@@ -1615,7 +1801,7 @@ impl SlowMidiApp {
                             self.project.notes.push(MidiNote::new(pitch, quantized_beat, self.note_duration));
                             // Play preview when pitch changes
                             if pitch != self.last_paint_pitch {
-                                self.play_note(pitch, self.note_duration.min(0.25));
+                                self.play_note(pitch, DEFAULT_VELOCITY, self.note_duration.min(0.25));
                             }
                             self.last_paint_beat = quantized_beat;
                             self.last_paint_pitch = pitch;
@@ -1854,47 +2040,22 @@ impl SlowMidiApp {
         // Calculate scroll offset (horizontal scrolling)
         let scroll_offset = self.scroll_y; // Reuse scroll_y for horizontal scroll in notation view
 
-        // Build bar line positions accounting for mid-piece time sig changes
-        let initial_bpb = self.project.time_signature_num as f32
-            * (4.0 / self.project.time_signature_den as f32);
+        // Bar lines, accounting for mid-piece time sig changes
         let scroll_beat = scroll_offset / beat_width;
         let end_beat = scroll_beat + visible_beats + 8.0;
-
-        // Collect all time signature regions: (start_beat, beats_per_bar)
-        let mut ts_regions: Vec<(f32, f32)> = vec![(0.0, initial_bpb)];
-        for tsc in &self.project.time_sig_changes {
-            let bpb = tsc.num as f32 * (4.0 / tsc.den as f32);
-            ts_regions.push((tsc.beat, bpb));
-        }
-
-        // Walk through regions and draw bar lines
-        for (region_idx, &(region_start, bpb)) in ts_regions.iter().enumerate() {
-            let region_end = ts_regions.get(region_idx + 1)
-                .map(|r| r.0)
-                .unwrap_or(end_beat + bpb);
-            // First bar line in this region at or after the visible scroll start
-            let first_bar = if region_start >= scroll_beat {
-                region_start
-            } else {
-                let bars_elapsed = ((scroll_beat - region_start) / bpb).floor();
-                region_start + bars_elapsed * bpb
-            };
-            let mut bar_beat = first_bar;
-            while bar_beat < region_end.min(end_beat) {
-                let bar_x = staff_start_x + (bar_beat - scroll_beat) * beat_width;
-                if bar_x >= staff_start_x && bar_x <= staff_end_x {
-                    painter.vline(
-                        bar_x,
-                        treble_start_y..=treble_start_y + 4.0 * staff_spacing,
-                        Stroke::new(1.0, SlowColors::BLACK),
-                    );
-                    painter.vline(
-                        bar_x,
-                        bass_start_y..=bass_start_y + 4.0 * staff_spacing,
-                        Stroke::new(1.0, SlowColors::BLACK),
-                    );
-                }
-                bar_beat += bpb;
+        for bar_beat in self.project.bar_lines(scroll_beat, end_beat) {
+            let bar_x = staff_start_x + (bar_beat - scroll_beat) * beat_width;
+            if bar_x >= staff_start_x && bar_x <= staff_end_x {
+                painter.vline(
+                    bar_x,
+                    treble_start_y..=treble_start_y + 4.0 * staff_spacing,
+                    Stroke::new(1.0, SlowColors::BLACK),
+                );
+                painter.vline(
+                    bar_x,
+                    bass_start_y..=bass_start_y + 4.0 * staff_spacing,
+                    Stroke::new(1.0, SlowColors::BLACK),
+                );
             }
         }
 
@@ -2244,7 +2405,7 @@ impl SlowMidiApp {
                             let raw_pitch = staff_y_to_pitch(click_y, is_treble, staff_base, staff_spacing);
                             let final_pitch = quantize_to_scale(raw_pitch, self.scale_root, SCALE_TYPES[self.scale_type].1);
                             self.project.notes.push(MidiNote::new(final_pitch, quantized_beat, self.note_duration));
-                            self.play_note(final_pitch, self.note_duration.min(0.5));
+                            self.play_note(final_pitch, DEFAULT_VELOCITY, self.note_duration.min(0.5));
                             self.last_paint_beat = quantized_beat;
                             self.last_paint_pitch = final_pitch;
                             self.modified = true;
@@ -2289,7 +2450,7 @@ impl SlowMidiApp {
                         if !exists {
                             self.project.notes.push(MidiNote::new(pitch, quantized_beat, self.note_duration));
                             if pitch != self.last_paint_pitch {
-                                self.play_note(pitch, self.note_duration.min(0.25));
+                                self.play_note(pitch, DEFAULT_VELOCITY, self.note_duration.min(0.25));
                             }
                             self.last_paint_beat = quantized_beat;
                             self.last_paint_pitch = pitch;
@@ -2351,119 +2512,507 @@ impl SlowMidiApp {
         painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
     }
 
-    fn render_close_confirm(&mut self, ctx: &Context) {
-        let resp = egui::Window::new("unsaved changes")
+    /// Name of the grid step, as the tool menu has it
+    fn grid_name(&self) -> String {
+        NOTE_DIVISIONS.iter()
+            .find(|(_, beats)| (beats - self.grid_division).abs() < 0.01)
+            .map(|(name, _)| name.to_string())
+            .unwrap_or_else(|| format!("{} beat", self.grid_division))
+    }
+
+    /// Add notes at `pitches`, a grid step each, from the playhead (on the
+    /// grid); `None` leaves a step empty. The new notes end up selected.
+    fn add_generated(&mut self, pitches: impl IntoIterator<Item = Option<u8>>) {
+        let step = self.grid_division;
+        let start = (self.playhead / step).round() * step;
+        let first = self.project.notes.len();
+        self.save_undo_state();
+        for (i, pitch) in pitches.into_iter().enumerate() {
+            if let Some(pitch) = pitch {
+                self.project.notes.push(MidiNote::new(pitch, start + i as f32 * step, step));
+            }
+        }
+        self.selected_notes = (first..self.project.notes.len()).collect();
+        self.modified = true;
+    }
+
+    /// Replace the selected chord with its notes one at a time, a grid step
+    /// each, over the time it held
+    fn arpeggiate_selection(&mut self, direction: ArpDirection) {
+        let chord: Vec<&MidiNote> = self.selected_notes.iter().filter_map(|&i| self.project.notes.get(i)).collect();
+        if chord.len() < 2 {
+            return;
+        }
+        let start = chord.iter().map(|n| n.start).fold(f32::MAX, f32::min);
+        let end = chord.iter().map(|n| n.start + n.duration).fold(0.0_f32, f32::max);
+        let velocity = chord.iter().map(|n| n.velocity).max().unwrap_or(DEFAULT_VELOCITY);
+        let pitches: Vec<u8> = chord.iter().map(|n| n.pitch).collect();
+        let step = self.grid_division;
+        let steps = ((end - start) / step).round().max(1.0) as usize;
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(1);
+
+        self.save_undo_state();
+        let mut indices: Vec<usize> = self.selected_notes.drain(..).collect();
+        indices.sort_by(|a, b| b.cmp(a));
+        for idx in indices {
+            self.project.notes.remove(idx);
+        }
+        let first = self.project.notes.len();
+        for (i, pitch) in generators::arpeggiate(&pitches, steps, direction, seed).into_iter().enumerate() {
+            self.project.notes.push(MidiNote { velocity, ..MidiNote::new(pitch, start + i as f32 * step, step) });
+        }
+        self.selected_notes = (first..self.project.notes.len()).collect();
+        self.modified = true;
+    }
+
+    /// Open a generator's dialog, starting from the selection where there is one
+    fn open_generator(&mut self, euclid: bool) {
+        let selected: Vec<u8> = self.selected_notes.iter().filter_map(|&i| self.project.notes.get(i)).map(|n| n.pitch).collect();
+        let low = selected.iter().copied().min().unwrap_or(60);
+        let high = selected.iter().copied().max().unwrap_or(72);
+        self.generator = Some(if euclid {
+            Generator::Euclid { pitch: low, hits: 3, steps: 8, rotation: 0, repeats: 4 }
+        } else {
+            Generator::ScaleRun { from: low, to: if high > low { high } else { low.saturating_add(12).min(127) } }
+        });
+    }
+
+    /// The scale run or euclidean rhythm dialog
+    fn render_generator(&mut self, ctx: &Context) {
+        let grid = self.grid_name();
+        let scale = SCALE_TYPES[self.scale_type];
+        let root = SCALE_ROOT_NAMES[self.scale_root as usize % 12];
+        let Some(generator) = &mut self.generator else { return };
+        let pitch_field = |ui: &mut egui::Ui, pitch: &mut u8| {
+            ui.add(egui::DragValue::new(pitch).clamp_range(0..=127).custom_formatter(|v, _| Self::note_name(v as u8)));
+        };
+        let title = match generator {
+            Generator::ScaleRun { .. } => "scale run",
+            Generator::Euclid { .. } => "euclidean rhythm",
+        };
+        let mut add = false;
+        let mut close = false;
+        let resp = egui::Window::new(title)
             .collapsible(false)
             .resizable(false)
-            .default_width(300.0)
+            .default_width(260.0)
             .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
             .show(ctx, |ui| {
-                ui.label("you have unsaved changes.");
-                ui.label("do you want to save before closing?");
+                egui::Grid::new("generator").num_columns(2).spacing([12.0, 6.0]).show(ui, |ui| match generator {
+                    Generator::ScaleRun { from, to } => {
+                        ui.label("from");
+                        pitch_field(ui, from);
+                        ui.end_row();
+                        ui.label("to");
+                        pitch_field(ui, to);
+                        ui.end_row();
+                    }
+                    Generator::Euclid { pitch, hits, steps, rotation, repeats } => {
+                        ui.label("pitch");
+                        pitch_field(ui, pitch);
+                        ui.end_row();
+                        ui.label("steps");
+                        ui.add(egui::DragValue::new(steps).clamp_range(1..=32));
+                        ui.end_row();
+                        ui.label("hits");
+                        ui.add(egui::DragValue::new(hits).clamp_range(1..=*steps));
+                        ui.end_row();
+                        ui.label("rotate");
+                        ui.add(egui::DragValue::new(rotation).clamp_range(0..=*steps - 1));
+                        ui.end_row();
+                        ui.label("repeats");
+                        ui.add(egui::DragValue::new(repeats).clamp_range(1..=16));
+                        ui.end_row();
+                    }
+                });
+                ui.add_space(4.0);
+                match generator {
+                    Generator::ScaleRun { .. } => {
+                        ui.label(format!("in {} {}, {} notes from the playhead", root, scale.0, grid));
+                    }
+                    Generator::Euclid { hits, steps, rotation, .. } => {
+                        let pattern: String = generators::euclidean(*hits, *steps, *rotation)
+                            .into_iter()
+                            .map(|hit| if hit { 'x' } else { '.' })
+                            .collect();
+                        ui.monospace(pattern);
+                        ui.label(format!("{} steps from the playhead", grid));
+                    }
+                }
                 ui.add_space(8.0);
                 ui.horizontal(|ui| {
-                    if ui.button("don't save").clicked() {
-                        self.close_confirmed = true;
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                    }
                     if ui.button("cancel").clicked() {
-                        self.show_close_confirm = false;
+                        close = true;
                     }
-                    if ui.button("save").clicked() {
-                        self.save_project();
-                        if !self.modified {
-                            // Save succeeded
-                            self.close_confirmed = true;
-                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                        }
+                    if ui.button("add").clicked() {
+                        add = true;
                     }
                 });
             });
-        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+        if let Some(r) = &resp {
+            slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+        }
+        if add {
+            match self.generator.take() {
+                Some(Generator::ScaleRun { from, to }) => {
+                    let run = generators::scale_run(from, to, self.scale_root, scale.1);
+                    self.add_generated(run.into_iter().map(Some));
+                }
+                Some(Generator::Euclid { pitch, hits, steps, rotation, repeats }) => {
+                    let pattern = generators::euclidean(hits, steps, rotation);
+                    let bars = std::iter::repeat_n(pattern, repeats).flatten();
+                    self.add_generated(bars.map(|hit| hit.then_some(pitch)));
+                }
+                None => {}
+            }
+        } else if close {
+            self.generator = None;
+        }
     }
 
-    fn render_file_browser(&mut self, ctx: &Context) {
-        let title = if self.is_saving { "save project" } else { "open file" };
+    /// The percussion lane: a row for each drum sound, a column for each
+    /// step. Pressing a step adds a hit or takes one away, and dragging
+    /// carries on doing the same along the row or across rows.
+    fn render_drums(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("step:");
+            for (label, step) in DRUM_STEPS {
+                if ui.radio(self.drum_step == step, label).clicked() {
+                    self.drum_step = step;
+                }
+            }
+        });
 
-        let resp = egui::Window::new(title)
-            .collapsible(false)
-            .resizable(false)
-            .default_width(380.0)
-            .show(ctx, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label("location:");
-                    ui.label(self.file_browser.current_dir.to_string_lossy().to_string());
-                });
-                ui.separator();
+        let available = ui.available_size();
+        let (response, painter) = ui.allocate_painter(available, Sense::click_and_drag());
+        let rect = response.rect;
+        painter.rect_filled(rect, 0.0, SlowColors::WHITE);
+
+        let beat_width = BEAT_WIDTH * self.zoom;
+        let step = self.drum_step;
+        let step_width = step * beat_width;
+        let grid_rect = Rect::from_min_max(
+            Pos2::new(rect.min.x + PIANO_WIDTH, rect.min.y),
+            Pos2::new(rect.max.x, (rect.min.y + DrumSound::ALL.len() as f32 * DRUM_ROW_HEIGHT).min(rect.max.y)),
+        );
+        let row_of = |sound: DrumSound| DrumSound::ALL.iter().position(|&s| s == sound).unwrap_or(0);
+        let cell_rect = |row: usize, beat: f32| Rect::from_min_size(
+            Pos2::new(grid_rect.min.x + beat * beat_width - self.scroll_x, grid_rect.min.y + row as f32 * DRUM_ROW_HEIGHT),
+            Vec2::new(step_width, DRUM_ROW_HEIGHT),
+        );
+
+        // Rows
+        for row in 0..=DrumSound::ALL.len() {
+            let y = grid_rect.min.y + row as f32 * DRUM_ROW_HEIGHT;
+            painter.hline(rect.x_range(), y, Stroke::new(0.5, SlowColors::BLACK));
+        }
+
+        // Steps, heavier on the beat and heavier again on the bar
+        let first = (self.scroll_x / step_width).floor() as i64;
+        let last = first + (grid_rect.width() / step_width).ceil() as i64 + 1;
+        for i in first..=last {
+            let beat = i as f32 * step;
+            let x = grid_rect.min.x + beat * beat_width - self.scroll_x;
+            let width = if (beat % 1.0).abs() < 0.001 { 1.0 } else { 0.5 };
+            painter.vline(x, grid_rect.y_range(), Stroke::new(width, SlowColors::BLACK));
+        }
+        for bar in self.project.bar_lines(first as f32 * step, last as f32 * step) {
+            let x = grid_rect.min.x + bar * beat_width - self.scroll_x;
+            painter.vline(x, grid_rect.y_range(), Stroke::new(2.0, SlowColors::BLACK));
+        }
+
+        // Hits
+        for hit in &self.project.drums {
+            let cell = cell_rect(row_of(hit.sound), (hit.beat / step).round() * step);
+            if cell.intersects(grid_rect) {
+                painter.rect_filled(cell.shrink(3.0).intersect(grid_rect), 0.0, SlowColors::BLACK);
+            }
+        }
+
+        // Playhead
+        let playhead_x = grid_rect.min.x + self.playhead * beat_width - self.scroll_x;
+        if playhead_x >= grid_rect.min.x && playhead_x <= grid_rect.max.x {
+            painter.vline(playhead_x, grid_rect.y_range(), Stroke::new(2.0, SlowColors::BLACK));
+        }
+
+        // Names, over the grid; a row sounding now is inverted
+        painter.rect_filled(Rect::from_min_size(rect.min, Vec2::new(PIANO_WIDTH, rect.height())), 0.0, SlowColors::WHITE);
+        for (row, sound) in DrumSound::ALL.into_iter().enumerate() {
+            let name_rect = Rect::from_min_size(
+                Pos2::new(rect.min.x, grid_rect.min.y + row as f32 * DRUM_ROW_HEIGHT),
+                Vec2::new(PIANO_WIDTH, DRUM_ROW_HEIGHT),
+            );
+            let sounding = self.playing && self.project.drums.iter().any(|h| {
+                h.sound == sound && self.playhead >= h.beat && self.playhead < h.beat + step
+            });
+            let (fill, text) = if sounding { (SlowColors::BLACK, SlowColors::WHITE) } else { (SlowColors::WHITE, SlowColors::BLACK) };
+            painter.rect_filled(name_rect, 0.0, fill);
+            painter.rect_stroke(name_rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+            painter.text(
+                name_rect.left_center() + Vec2::new(4.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                sound.name(),
+                egui::FontId::proportional(10.0),
+                text,
+            );
+        }
 
-                egui::ScrollArea::vertical().max_height(220.0).show(ui, |ui| {
-                    let mut clicked_idx = None;
-                    let mut nav_path = None;
-                    let mut open_path = None;
-                    for (idx, entry) in self.file_browser.entries.iter().enumerate() {
-                        let selected = self.file_browser.selected_index == Some(idx);
-                        let response = ui.add(FileListItem::new(&entry.name, entry.is_directory).selected(selected));
-                        if response.clicked() {
-                            clicked_idx = Some(idx);
+        // Pressing and dragging over the steps
+        let pressed = ui.input(|i| i.pointer.primary_down());
+        if !pressed {
+            self.drum_paint = None;
+        }
+        if pressed && (response.is_pointer_button_down_on() || self.drum_paint.is_some()) {
+            if let Some(pos) = response.interact_pointer_pos().or_else(|| ui.input(|i| i.pointer.hover_pos())) {
+                let row = ((pos.y - grid_rect.min.y) / DRUM_ROW_HEIGHT).floor();
+                if grid_rect.contains(pos) && row >= 0.0 {
+                    let sound = DrumSound::ALL[(row as usize).min(DrumSound::ALL.len() - 1)];
+                    let beat = ((pos.x - grid_rect.min.x + self.scroll_x) / step_width).floor().max(0.0) * step;
+                    let existing = self.project.drums.iter()
+                        .position(|h| h.sound == sound && (h.beat - beat).abs() < step / 2.0);
+                    let adding = match self.drum_paint {
+                        Some(adding) => adding,
+                        None => {
+                            self.save_undo_state();
+                            let adding = existing.is_none();
+                            self.drum_paint = Some(adding);
+                            adding
                         }
-                        if response.double_clicked() {
-                            if entry.is_directory {
-                                nav_path = Some(entry.path.clone());
-                            } else if !self.is_saving {
-                                open_path = Some(entry.path.clone());
-                            }
+                    };
+                    match (adding, existing) {
+                        (true, None) => {
+                            self.project.drums.push(DrumHit { sound, beat, velocity: DEFAULT_VELOCITY });
+                            self.play_drum(sound, DEFAULT_VELOCITY);
+                            self.modified = true;
                         }
+                        (false, Some(idx)) => {
+                            self.project.drums.remove(idx);
+                            self.modified = true;
+                        }
+                        _ => {}
                     }
-                    if let Some(idx) = clicked_idx { self.file_browser.selected_index = Some(idx); }
-                    if let Some(path) = nav_path { self.file_browser.navigate_to(path); }
-                    if let Some(path) = open_path {
-                        self.load_from_path(path);
-                        self.show_file_browser = false;
+                }
+            }
+        }
+
+        // A name plays its sound
+        if response.clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                let row = ((pos.y - grid_rect.min.y) / DRUM_ROW_HEIGHT).floor();
+                if pos.x < grid_rect.min.x && row >= 0.0 {
+                    if let Some(&sound) = DrumSound::ALL.get(row as usize) {
+                        self.play_drum(sound, DEFAULT_VELOCITY);
                     }
-                });
+                }
+            }
+        }
 
-                if self.is_saving {
-                    ui.separator();
-                    ui.horizontal(|ui| {
-                        ui.label("filename:");
-                        ui.text_edit_singleline(&mut self.save_filename);
-                    });
+        // Scroll with drag (right mouse button) or the wheel
+        if response.dragged_by(egui::PointerButton::Secondary) {
+            self.scroll_x = (self.scroll_x - response.drag_delta().x).max(0.0);
+        }
+        if response.hovered() {
+            ui.input(|i| {
+                let scroll = i.raw_scroll_delta;
+                self.scroll_x = (self.scroll_x - (scroll.x + scroll.y) * 2.0).max(0.0);
+            });
+        }
+
+        // Auto-scroll when playhead goes past the view
+        if self.playing {
+            let view_width = grid_rect.width();
+            let playhead_screen_x = self.playhead * beat_width - self.scroll_x;
+            if playhead_screen_x > view_width * 0.9 {
+                self.scroll_x = self.playhead * beat_width - view_width * 0.1;
+            }
+        }
+
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+    }
+
+    fn render_file_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.file_dialog else { return };
+        let mode = dialog.mode();
+        match dialog.show(ctx) {
+            FileDialogResult::Picked(path) => {
+                self.file_dialog = None;
+                match mode {
+                    FileDialogMode::Open => self.load_from_path(path),
+                    FileDialogMode::Save => self.save_to_path(path),
                 }
+            }
+            FileDialogResult::Cancelled => self.file_dialog = None,
+            FileDialogResult::Pending => {}
+        }
+    }
 
-                ui.separator();
-                ui.horizontal(|ui| {
-                    if ui.button("cancel").clicked() {
-                        self.show_file_browser = false;
-                    }
-                    let action = if self.is_saving { "save" } else { "open" };
-                    if ui.button(action).clicked() {
-                        if self.is_saving {
-                            if !self.save_filename.is_empty() {
-                                let path = self.file_browser.save_directory().join(&self.save_filename);
-                                let path = if path.extension().is_none() {
-                                    path.with_extension("mid")
-                                } else {
-                                    path
-                                };
-                                self.save_to_path(path);
-                                self.show_file_browser = false;
+    /// Every note and drum hit, timed in seconds, to bounce
+    fn export_voices(&self) -> Vec<Voice> {
+        let (tempo, changes) = (self.project.tempo, &self.project.tempo_changes);
+        self.project.notes.iter()
+            .map(|note| {
+                let start = beat_to_seconds(note.start, tempo, changes);
+                let end = beat_to_seconds(note.start + note.duration, tempo, changes);
+                Voice { sound: Sound::Note(note.pitch, note.velocity), start, length: end - start }
+            })
+            .chain(self.project.drums.iter().map(|hit| Voice {
+                sound: Sound::Drum(hit.sound, hit.velocity),
+                start: beat_to_seconds(hit.beat, tempo, changes),
+                length: hit.sound.length(),
+            }))
+            .collect()
+    }
+
+    /// The export audio dialogs: options, where to save, progress and result
+    fn render_export_audio(&mut self, ctx: &Context) {
+        let Some(stage) = &mut self.export else { return };
+        let mut next = None;
+        let mut start = None;
+        let mut close = false;
+        match stage {
+            ExportStage::Options => {
+                let options = &mut self.export_options;
+                let mut save = false;
+                let resp = egui::Window::new("export audio")
+                    .collapsible(false)
+                    .resizable(false)
+                    .default_width(280.0)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        egui::Grid::new("export_options").num_columns(2).spacing([12.0, 6.0]).show(ui, |ui| {
+                            ui.label("format");
+                            ui.horizontal(|ui| {
+                                for format in AudioFormat::ALL {
+                                    ui.radio_value(&mut options.format, format, format.name());
+                                }
+                            });
+                            ui.end_row();
+                            ui.label("sample rate");
+                            ui.horizontal(|ui| {
+                                for rate in SAMPLE_RATES {
+                                    ui.radio_value(&mut options.sample_rate, rate, format!("{:.1} kHz", rate as f32 / 1000.0));
+                                }
+                            });
+                            ui.end_row();
+                            ui.label("bit depth");
+                            ui.horizontal(|ui| {
+                                for depth in BIT_DEPTHS {
+                                    ui.radio_value(&mut options.bit_depth, depth, format!("{}-bit", depth));
+                                }
+                            });
+                            ui.end_row();
+                        });
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("cancel").clicked() {
+                                close = true;
                             }
-                        } else if let Some(entry) = self.file_browser.selected_entry() {
-                            if !entry.is_directory {
-                                self.load_from_path(entry.path.clone());
-                                self.show_file_browser = false;
+                            if ui.button("export...").clicked() {
+                                save = true;
                             }
+                        });
+                    });
+                if let Some(r) = &resp {
+                    slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+                }
+                if save {
+                    let ext = self.export_options.format.extension();
+                    let stem = self.file_path.as_ref()
+                        .and_then(|p| p.file_stem())
+                        .map(|s| s.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| self.project.name.clone());
+                    next = Some(ExportStage::Saving(Box::new(
+                        FileDialog::save(midi_dir(), &format!("{}.{}", stem, ext))
+                            .title("export audio")
+                            .filter(&[ext])
+                            .default_extension(ext),
+                    )));
+                }
+            }
+            ExportStage::Saving(dialog) => match dialog.show(ctx) {
+                FileDialogResult::Picked(path) => start = Some(path),
+                FileDialogResult::Cancelled => close = true,
+                FileDialogResult::Pending => {}
+            },
+            ExportStage::Rendering { bounce, path } => {
+                // The finished mix is written the frame after it shows as done
+                if bounce.is_done() {
+                    next = Some(ExportStage::Done(bounce.write(path).map(|_| path.clone())));
+                } else {
+                    bounce.step(self.instrument.and_then(|i| self.instruments.get(i)), BOUNCE_SLICE);
+                }
+                let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                let resp = egui::Window::new("exporting audio")
+                    .collapsible(false)
+                    .resizable(false)
+                    .default_width(280.0)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        if bounce.is_done() {
+                            ui.label(format!("writing {}...", name));
+                        } else {
+                            ui.label(format!("rendering {:.0} seconds of audio...", bounce.seconds()));
                         }
-                    }
-                });
-            });
-        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+                        progress_bar(ui, bounce.progress());
+                        ui.add_space(8.0);
+                        if ui.button("cancel").clicked() {
+                            close = true;
+                        }
+                    });
+                if let Some(r) = &resp {
+                    slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+                }
+            }
+            ExportStage::Done(result) => {
+                let message = match result {
+                    Ok(path) => format!("exported {}", path.file_name().unwrap_or_default().to_string_lossy()),
+                    Err(e) => format!("could not export: {}", e),
+                };
+                let resp = egui::Window::new("export audio")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label(message);
+                        ui.add_space(8.0);
+                        if ui.button("ok").clicked() {
+                            close = true;
+                        }
+                    });
+                if let Some(r) = &resp {
+                    slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+                }
+            }
+        }
+        if let Some(path) = start {
+            let bounce = Bounce::new(self.export_voices(), self.export_options);
+            next = Some(ExportStage::Rendering { bounce, path });
+        }
+        if close {
+            self.export = None;
+        } else if let Some(next) = next {
+            self.export = Some(next);
+        }
     }
 }
 
+/// Outlined bar filled in black
+fn progress_bar(ui: &mut egui::Ui, fraction: f32) {
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), 12.0), Sense::hover());
+    let painter = ui.painter();
+    painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+    let inner = rect.shrink(2.0);
+    let filled = Rect::from_min_size(inner.min, Vec2::new(inner.width() * fraction.clamp(0.0, 1.0), inner.height()));
+    painter.rect_filled(filled, 0.0, SlowColors::BLACK);
+}
+
 impl eframe::App for SlowMidiApp {
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
         self.repaint.begin_frame(ctx);
+        self.audio_handle.poll(ctx);
         if slowcore::minimize::check_restore_signal("slowmidi") {
             ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
             ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
@@ -2472,6 +3021,7 @@ impl eframe::App for SlowMidiApp {
         self.load_clef_textures(ctx);
         self.handle_keys(ctx);
         self.update_playback();
+        self.player.set_playing(self.playing);
 
         // Auto-release pressed piano key after 500ms
         if self.pressed_key.is_some() && self.key_press_time.elapsed().as_millis() > 500 {
@@ -2479,7 +3029,9 @@ impl eframe::App for SlowMidiApp {
         }
 
         // Enable fast continuous repaint during playback or key press animation
-        self.repaint.set_continuous(self.playing || self.pressed_key.is_some());
+        // and while an export renders
+        let exporting = matches!(self.export, Some(ExportStage::Rendering { .. }));
+        self.repaint.set_continuous(self.playing || self.pressed_key.is_some() || exporting);
 
         // Menu bar
         let win_action = egui::TopBottomPanel::top("menu").show(ctx, |ui| {
@@ -2494,6 +3046,9 @@ impl eframe::App for SlowMidiApp {
                         self.show_open_dialog();
                         ui.close_menu();
                     }
+                    if let Some(path) = self.recent_files.menu(ui) {
+                        self.load_from_path(path);
+                    }
                     if ui.button("save       ⌘S").clicked() {
                         self.save_project();
                         ui.close_menu();
@@ -2502,10 +3057,17 @@ impl eframe::App for SlowMidiApp {
                         self.show_save_dialog();
                         ui.close_menu();
                     }
+                    ui.separator();
+                    let has_content = !self.project.notes.is_empty() || !self.project.drums.is_empty();
+                    let can_export = has_content && self.export.is_none();
+                    if ui.add_enabled(can_export, egui::Button::new("export audio...")).clicked() {
+                        self.export = Some(ExportStage::Options);
+                        ui.close_menu();
+                    }
                 });
                 ui.menu_button("edit", |ui| {
-                    let can_undo = !self.undo_stack.is_empty();
-                    let can_redo = !self.redo_stack.is_empty();
+                    let can_undo = self.history.can_undo();
+                    let can_redo = self.history.can_redo();
                     if ui.add_enabled(can_undo, egui::Button::new("undo        ⌘Z")).clicked() {
                         self.undo();
                         ui.close_menu();
@@ -2524,19 +3086,38 @@ impl eframe::App for SlowMidiApp {
                         ui.close_menu();
                     }
                 });
+                ui.menu_button("generate", |ui| {
+                    let chord = self.selected_notes.len() >= 2;
+                    ui.add_enabled_ui(chord, |ui| {
+                        ui.menu_button("arpeggiate selection", |ui| {
+                            for direction in ArpDirection::ALL {
+                                if ui.button(direction.name()).clicked() {
+                                    self.arpeggiate_selection(direction);
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+                    if ui.button("scale run...").clicked() {
+                        self.open_generator(false);
+                        ui.close_menu();
+                    }
+                    if ui.button("euclidean rhythm...").clicked() {
+                        self.open_generator(true);
+                        ui.close_menu();
+                    }
+                });
                 ui.menu_button("view", |ui| {
                     if ui.button("piano roll  1").clicked() {
-                        if self.view_mode == ViewMode::Notation {
-                            self.scroll_y = 30.0 * KEY_HEIGHT;
-                        }
-                        self.view_mode = ViewMode::PianoRoll;
+                        self.set_view(ViewMode::PianoRoll);
                         ui.close_menu();
                     }
                     if ui.button("notation    2").clicked() {
-                        if self.view_mode == ViewMode::PianoRoll {
-                            self.scroll_y = 0.0;
-                        }
-                        self.view_mode = ViewMode::Notation;
+                        self.set_view(ViewMode::Notation);
+                        ui.close_menu();
+                    }
+                    if ui.button("drums       3").clicked() {
+                        self.set_view(ViewMode::Drums);
                         ui.close_menu();
                     }
                 });
@@ -2553,6 +3134,22 @@ impl eframe::App for SlowMidiApp {
                         ui.close_menu();
                     }
                 });
+                ui.menu_button("instrument", |ui| {
+                    if ui.radio(self.instrument.is_none(), "sine (built in)").clicked() {
+                        self.instrument = None;
+                        ui.close_menu();
+                    }
+                    for (i, plugin) in self.instruments.iter().enumerate() {
+                        if ui.radio(self.instrument == Some(i), plugin.name()).clicked() {
+                            self.instrument = Some(i);
+                            ui.close_menu();
+                        }
+                    }
+                    if self.instruments.is_empty() {
+                        ui.separator();
+                        ui.weak("more instruments come from plugins");
+                    }
+                });
                 ui.menu_button("help", |ui| {
                     if ui.button("about").clicked() {
                         self.show_about = true;
@@ -2565,11 +3162,7 @@ impl eframe::App for SlowMidiApp {
 
         match win_action {
             WindowAction::Close => {
-                if self.modified {
-                    self.show_close_confirm = true;
-                } else {
-                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                }
+                self.close_guard.request_close(ctx, self.modified);
             }
             WindowAction::Minimize => {
                 let title = self.file_path.as_ref()
@@ -2607,69 +3200,92 @@ impl eframe::App for SlowMidiApp {
                 match self.view_mode {
                     ViewMode::PianoRoll => self.render_piano_roll(ui),
                     ViewMode::Notation => self.render_notation(ui),
+                    ViewMode::Drums => self.render_drums(ui),
                 }
             });
 
-        // File browser
-        if self.show_file_browser {
-            self.render_file_browser(ctx);
-        }
-
-        // Close confirmation dialog
-        if self.show_close_confirm {
-            self.render_close_confirm(ctx);
-        }
+        // File dialog
+        self.render_file_dialog(ctx);
+        self.render_export_audio(ctx);
+        self.render_generator(ctx);
 
         // About dialog
-        if self.show_about {
-            let screen = ctx.screen_rect();
-            let max_h = (screen.height() - 60.0).max(120.0);
-
-            let resp = egui::Window::new("about slowMidi")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(300.0)
-                .max_height(max_h)
-                .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                        ui.vertical_centered(|ui| {
-                            ui.heading("slowMidi");
-                            ui.label("version 0.2.2");
-                            ui.add_space(8.0);
-                            ui.label("MIDI sequencer for slowOS");
-                        });
-                        ui.add_space(8.0);
-                        ui.separator();
-                        ui.add_space(4.0);
-                        ui.label("supported formats:");
-                        ui.label("  MIDI (.mid, .midi), JSON project");
-                        ui.add_space(4.0);
-                        ui.label("features:");
-                        ui.label("  piano roll and notation views");
-                        ui.label("  create and edit MIDI sequences");
-                        ui.label("  variable note durations");
-                        ui.add_space(4.0);
-                        ui.label("frameworks:");
-                        ui.label("  egui/eframe (MIT), midly (MIT)");
-                        ui.add_space(8.0);
-                    });
-                    ui.vertical_centered(|ui| {
-                        if ui.button("ok").clicked() {
-                            self.show_about = false;
-                        }
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
 
-        // Handle close request
-        if ctx.input(|i| i.viewport().close_requested()) {
-            if self.modified && !self.close_confirmed {
-                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
-                self.show_close_confirm = true;
-            }
+        // Unsaved-changes guard for close requests
+        if self.close_guard.update(ctx, self.modified) {
+            self.save_project();
+            self.close_guard.close_if_saved(ctx, !self.modified);
         }
 
         self.repaint.end_frame(ctx);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_formats() {
+        // A project saved before format versions, and one from version 1
+        let fixtures = [
+            r#"{"name": "scale", "tempo": 100, "time_signature_num": 3, "time_signature_den": 4,
+                "notes": [{"pitch": 60, "start": 0.0, "duration": 1.0, "velocity": 80}]}"#,
+            r#"{"format_version": 1, "name": "scale", "tempo": 100, "time_signature_num": 3, "time_signature_den": 4,
+                "notes": [{"pitch": 60, "start": 0.0, "duration": 1.0, "velocity": 80}],
+                "tempo_changes": [], "hairpins": [], "dynamic_marks": [], "time_sig_changes": [], "key_sig_changes": []}"#,
+        ];
+        for json in fixtures {
+            let project = storage::from_versioned_json::<MidiProject>(json).unwrap();
+            assert_eq!((project.tempo, project.time_signature_num), (100, 3));
+            assert_eq!(project.notes[0].pitch, 60);
+            assert!(project.tempo_changes.is_empty());
+            let again = storage::to_versioned_json(&project).unwrap();
+            assert_eq!(storage::from_versioned_json::<MidiProject>(&again).unwrap().notes.len(), 1);
+        }
+
+        // Version 2 has the percussion lane
+        let json = r#"{"format_version": 2, "name": "beat", "tempo": 90, "time_signature_num": 4, "time_signature_den": 4,
+            "notes": [], "drums": [{"sound": "closed_hat", "beat": 0.5, "velocity": 90}]}"#;
+        let project = storage::from_versioned_json::<MidiProject>(json).unwrap();
+        assert_eq!(project.drums[0].sound, DrumSound::ClosedHat);
+    }
+
+    #[test]
+    fn test_time_signature_changes() {
+        let mut project = MidiProject { time_signature_num: 3, tempo: 100, ..Default::default() };
+        project.time_sig_changes.push(TimeSigChange { beat: 6.0, num: 5, den: 8 });
+        // Two bars of 3/4, then bars of 5/8
+        assert_eq!(project.bar_lines(0.0, 12.0), vec![0.0, 3.0, 6.0, 8.5, 11.0]);
+        assert_eq!(project.bar_after(3.0), 6.0);
+        assert_eq!(project.bar_after(6.0), 8.5);
+        assert_eq!(project.bar_before(8.5), 6.0);
+        assert_eq!(project.bar_before(7.0), 6.0);
+        assert_eq!(project.bar_before(0.0), 0.0);
+
+        // Through a MIDI file and back
+        let data = project.export_midi().unwrap();
+        let mut smf = midly::Smf::parse(&data).unwrap();
+        // Broken files can have a zero tempo, which is skipped
+        let zero_tempo = midly::TrackEventKind::Meta(midly::MetaMessage::Tempo(0.into()));
+        smf.tracks[0].insert(0, midly::TrackEvent { delta: 0.into(), kind: zero_tempo });
+        let again = MidiProject::import_midi(&smf);
+        assert_eq!((again.time_signature_num, again.time_signature_den), (3, 4));
+        assert_eq!(again.tempo, 100);
+        assert!(again.tempo_changes.is_empty());
+        let change = &again.time_sig_changes[0];
+        assert_eq!((change.beat, change.num, change.den), (6.0, 5, 8));
+    }
+
+    #[test]
+    fn test_beat_to_seconds() {
+        let changes = [TempoChange { beat: 4.0, bpm: 60 }];
+        assert_eq!(beat_to_seconds(2.0, 120, &changes), 1.0);
+        // Two seconds to the change, then a second a beat
+        assert_eq!(beat_to_seconds(6.0, 120, &changes), 4.0);
+        assert_eq!(seconds_to_beat(0.0, 4.0, 120, &changes), 6.0);
+    }
+}
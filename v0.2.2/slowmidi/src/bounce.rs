@@ -0,0 +1,232 @@
+//! Bouncing a project to an audio file
+//!
+//! The notes are rendered offline on the instrument playback uses, and the
+//! drum hits on their own sounds, mixed, and written as WAV or FLAC at the
+//! chosen sample rate and bit depth. The mix is mono, at the instruments'
+//! own level, turned down only where the notes together would clip.
+//!
+//! Plugin instruments can't leave the UI thread, so a bounce renders a
+//! slice at a time between frames: as fast as the instrument goes, with
+//! the window still drawing its progress.
+
+use crate::drums::DrumSound;
+use crate::synth::{self, MAX_NOTE_SECS};
+use flacenc::component::BitRepr;
+use flacenc::error::Verify;
+use slowcore::plugins::Plugin;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Sample rates to choose from
+pub const SAMPLE_RATES: [u32; 3] = [22050, 44100, 48000];
+/// Bit depths to choose from
+pub const BIT_DEPTHS: [u16; 2] = [16, 24];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioFormat {
+    Wav,
+    Flac,
+}
+
+impl AudioFormat {
+    pub const ALL: [AudioFormat; 2] = [AudioFormat::Wav, AudioFormat::Flac];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "WAV",
+            AudioFormat::Flac => "FLAC",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Flac => "flac",
+        }
+    }
+}
+
+/// What an export writes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExportOptions {
+    pub format: AudioFormat,
+    pub sample_rate: u32,
+    pub bit_depth: u16,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self { format: AudioFormat::Wav, sample_rate: 44100, bit_depth: 16 }
+    }
+}
+
+/// What a voice plays
+#[derive(Clone, Copy, Debug)]
+pub enum Sound {
+    /// A pitch on the instrument, at a velocity
+    Note(u8, u8),
+    /// A drum hit, at a velocity
+    Drum(DrumSound, u8),
+}
+
+/// A note or hit to render, timed in seconds
+#[derive(Clone, Copy, Debug)]
+pub struct Voice {
+    pub sound: Sound,
+    pub start: f32,
+    pub length: f32,
+}
+
+/// An export under way
+pub struct Bounce {
+    options: ExportOptions,
+    /// In the order they start
+    voices: Vec<Voice>,
+    /// Voices rendered so far
+    rendered: usize,
+    mix: Vec<f32>,
+}
+
+impl Bounce {
+    pub fn new(mut voices: Vec<Voice>, options: ExportOptions) -> Self {
+        for voice in &mut voices {
+            voice.start = voice.start.max(0.0);
+            voice.length = voice.length.clamp(0.0, MAX_NOTE_SECS);
+        }
+        voices.sort_by(|a, b| a.start.total_cmp(&b.start));
+        let end = voices.iter().map(|v| v.start + v.length).fold(0.0_f32, f32::max);
+        let mix = vec![0.0; (end * options.sample_rate as f32).ceil() as usize];
+        Self { options, voices, rendered: 0, mix }
+    }
+
+    /// Render notes on `instrument` (the sine if none), and drum hits, for
+    /// up to `budget`
+    pub fn step(&mut self, instrument: Option<&Plugin>, budget: Duration) {
+        let started = Instant::now();
+        let rate = self.options.sample_rate as f32;
+        let mut note = Vec::new();
+        while let Some(voice) = self.voices.get(self.rendered) {
+            let from = ((voice.start * rate) as usize).min(self.mix.len());
+            let to = (((voice.start + voice.length) * rate) as usize).min(self.mix.len());
+            note.clear();
+            note.resize(to - from, 0.0);
+            match voice.sound {
+                Sound::Note(pitch, velocity) => synth::render_note(instrument, pitch, velocity, self.options.sample_rate, &mut note),
+                Sound::Drum(drum, velocity) => drum.render(velocity, self.options.sample_rate, &mut note),
+            }
+            for (out, sample) in self.mix[from..to].iter_mut().zip(&note) {
+                *out += sample;
+            }
+            self.rendered += 1;
+            if started.elapsed() >= budget {
+                break;
+            }
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.rendered >= self.voices.len()
+    }
+
+    /// How much is rendered, 0.0–1.0
+    pub fn progress(&self) -> f32 {
+        if self.voices.is_empty() {
+            1.0
+        } else {
+            self.rendered as f32 / self.voices.len() as f32
+        }
+    }
+
+    /// Length of the audio, in seconds
+    pub fn seconds(&self) -> f32 {
+        self.mix.len() as f32 / self.options.sample_rate as f32
+    }
+
+    /// The mix as whole numbers at the chosen bit depth
+    fn samples(&self) -> Vec<i32> {
+        let peak = self.mix.iter().fold(0.0_f32, |peak, s| peak.max(s.abs()));
+        let gain = if peak > 1.0 { 1.0 / peak } else { 1.0 };
+        let full = ((1_i32 << (self.options.bit_depth - 1)) - 1) as f32;
+        self.mix.iter().map(|s| (s * gain * full).round() as i32).collect()
+    }
+
+    /// Write the finished mix to `path`, replacing it only once the whole
+    /// file is written
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let tmp = path.with_extension(format!("{}.tmp", self.options.format.extension()));
+        let samples = self.samples();
+        let written = match self.options.format {
+            AudioFormat::Wav => write_wav(&tmp, &samples, self.options),
+            AudioFormat::Flac => write_flac(&tmp, &samples, self.options),
+        };
+        match written.and_then(|_| std::fs::rename(&tmp, path).map_err(|e| e.to_string())) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                let _ = std::fs::remove_file(&tmp);
+                Err(e)
+            }
+        }
+    }
+}
+
+fn write_wav(path: &Path, samples: &[i32], options: ExportOptions) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: options.sample_rate,
+        bits_per_sample: options.bit_depth,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).map_err(|e| e.to_string())?;
+    for &sample in samples {
+        writer.write_sample(sample).map_err(|e| e.to_string())?;
+    }
+    writer.finalize().map_err(|e| e.to_string())
+}
+
+fn write_flac(path: &Path, samples: &[i32], options: ExportOptions) -> Result<(), String> {
+    let config = flacenc::config::Encoder::default().into_verified()
+        .map_err(|_| "could not set up the FLAC encoder".to_string())?;
+    let source = flacenc::source::MemSource::from_samples(
+        samples,
+        1,
+        options.bit_depth as usize,
+        options.sample_rate as usize,
+    );
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| format!("could not encode FLAC: {:?}", e))?;
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream.write(&mut sink).map_err(|e| format!("could not encode FLAC: {:?}", e))?;
+    std::fs::write(path, sink.as_slice()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounce_mixes_notes() {
+        let voices = vec![
+            Voice { sound: Sound::Note(69, 100), start: 0.5, length: 0.5 },
+            Voice { sound: Sound::Note(60, 100), start: 0.0, length: 1.0 },
+            Voice { sound: Sound::Note(64, 100), start: 0.0, length: 60.0 },
+        ];
+        let options = ExportOptions { bit_depth: 24, ..Default::default() };
+        let mut bounce = Bounce::new(voices, options);
+        // Notes are cut off where playback cuts them off
+        assert_eq!(bounce.seconds(), MAX_NOTE_SECS);
+        bounce.step(None, Duration::ZERO);
+        assert!(!bounce.is_done() && bounce.progress() > 0.0);
+        while !bounce.is_done() {
+            bounce.step(None, Duration::from_millis(10));
+        }
+
+        let samples = bounce.samples();
+        assert_eq!(samples.len(), 8 * 44100);
+        let loudest = samples.iter().map(|s| s.abs()).max().unwrap();
+        assert!(loudest > 1 << 20 && loudest < 1 << 23);
+        // After the first second only the long note is left, and quieter
+        let second = &samples[44100..];
+        assert!(second.iter().any(|&s| s != 0));
+        assert!(second.iter().all(|s| s.abs() < loudest / 2));
+    }
+}
@@ -0,0 +1,180 @@
+//! Percussion — a lane of drum hits, each row a named drum sound
+//!
+//! Hits are programmed on a step grid rather than the piano roll, and
+//! sound on short synthesized hits: pitch-swept sines for the kick and
+//! toms, noise bursts for the snare, clap, hats and crash. In MIDI files
+//! they go on channel 10 as General MIDI percussion keys.
+
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// MIDI channel percussion plays on (channel 10, counted from 0)
+pub const DRUM_CHANNEL: u8 = 9;
+
+/// Fade at the end of every hit, in seconds, so none ends in a click
+const TAIL_FADE: f32 = 0.005;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrumSound {
+    Kick,
+    Snare,
+    Clap,
+    ClosedHat,
+    OpenHat,
+    LowTom,
+    HighTom,
+    Crash,
+}
+
+impl DrumSound {
+    /// In row order, top to bottom
+    pub const ALL: [DrumSound; 8] = [
+        DrumSound::Crash,
+        DrumSound::OpenHat,
+        DrumSound::ClosedHat,
+        DrumSound::HighTom,
+        DrumSound::LowTom,
+        DrumSound::Clap,
+        DrumSound::Snare,
+        DrumSound::Kick,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DrumSound::Kick => "kick",
+            DrumSound::Snare => "snare",
+            DrumSound::Clap => "clap",
+            DrumSound::ClosedHat => "closed hat",
+            DrumSound::OpenHat => "open hat",
+            DrumSound::LowTom => "low tom",
+            DrumSound::HighTom => "high tom",
+            DrumSound::Crash => "crash",
+        }
+    }
+
+    /// General MIDI percussion key
+    pub fn key(self) -> u8 {
+        match self {
+            DrumSound::Kick => 36,
+            DrumSound::Snare => 38,
+            DrumSound::Clap => 39,
+            DrumSound::ClosedHat => 42,
+            DrumSound::OpenHat => 46,
+            DrumSound::LowTom => 45,
+            DrumSound::HighTom => 50,
+            DrumSound::Crash => 49,
+        }
+    }
+
+    /// The sound a General MIDI percussion key is closest to, if any
+    pub fn from_key(key: u8) -> Option<Self> {
+        Some(match key {
+            35 | 36 => DrumSound::Kick,
+            37 | 38 | 40 => DrumSound::Snare,
+            39 => DrumSound::Clap,
+            42 | 44 => DrumSound::ClosedHat,
+            46 => DrumSound::OpenHat,
+            41 | 43 | 45 => DrumSound::LowTom,
+            47 | 48 | 50 => DrumSound::HighTom,
+            49 | 51 | 52 | 55 | 57 | 59 => DrumSound::Crash,
+            _ => return None,
+        })
+    }
+
+    /// How long the hit rings, in seconds
+    pub fn length(self) -> f32 {
+        match self {
+            DrumSound::Kick => 0.5,
+            DrumSound::Snare => 0.25,
+            DrumSound::Clap => 0.3,
+            DrumSound::ClosedHat => 0.08,
+            DrumSound::OpenHat => 0.5,
+            DrumSound::LowTom => 0.5,
+            DrumSound::HighTom => 0.4,
+            DrumSound::Crash => 1.5,
+        }
+    }
+
+    /// Render the hit at `velocity`, filling `out`
+    pub fn render(self, velocity: u8, sample_rate: u32, out: &mut [f32]) {
+        let rate = sample_rate as f32;
+        let level = 0.5 * velocity.min(127) as f32 / 127.0;
+        let fade = (TAIL_FADE * rate).max(1.0);
+        let len = out.len();
+        let mut noise = Noise(0x9e37_79b9);
+        let mut last = 0.0;
+        let mut phase = 0.0_f32;
+        // A sine falling from `high` to `low` Hz at `drop`
+        let mut sweep = |t: f32, low: f32, high: f32, drop: f32| {
+            phase = (phase + (low + (high - low) * (-t * drop).exp()) / rate).fract();
+            (phase * TAU).sin()
+        };
+        for (i, slot) in out.iter_mut().enumerate() {
+            let t = i as f32 / rate;
+            let white = noise.next();
+            // Differencing takes out the lows, for the hats and cymbal
+            let hiss = (white - last) * 0.5;
+            last = white;
+            let sample = match self {
+                DrumSound::Kick => sweep(t, 50.0, 150.0, 30.0) * (-t * 8.0).exp(),
+                DrumSound::Snare => 0.6 * white * (-t * 20.0).exp() + 0.4 * sweep(t, 185.0, 185.0, 0.0) * (-t * 30.0).exp(),
+                DrumSound::Clap => {
+                    // Three quick bursts, then a tail
+                    let envelope = if t < 0.03 { (-(t % 0.01) * 300.0).exp() } else { (-(t - 0.03) * 25.0).exp() };
+                    white * envelope
+                }
+                DrumSound::ClosedHat => hiss * (-t * 60.0).exp(),
+                DrumSound::OpenHat => hiss * (-t * 8.0).exp(),
+                DrumSound::LowTom => sweep(t, 90.0, 130.0, 20.0) * (-t * 9.0).exp(),
+                DrumSound::HighTom => sweep(t, 160.0, 220.0, 20.0) * (-t * 10.0).exp(),
+                DrumSound::Crash => hiss * (-t * 3.0).exp(),
+            };
+            let tail = ((len - i) as f32 / fade).min(1.0);
+            *slot = (sample * level * tail).tanh();
+        }
+    }
+}
+
+/// A drum hit in the percussion lane
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DrumHit {
+    pub sound: DrumSound,
+    /// Position in beats
+    pub beat: f32,
+    /// Velocity (0-127)
+    pub velocity: u8,
+}
+
+/// White noise, the same every time a hit plays
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        // xorshift32
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drum_sounds() {
+        for sound in DrumSound::ALL {
+            assert_eq!(DrumSound::from_key(sound.key()), Some(sound));
+            let mut hit = vec![0.0; (sound.length() * 44100.0) as usize];
+            sound.render(100, 44100, &mut hit);
+            let peak = hit.iter().fold(0.0_f32, |peak, s| peak.max(s.abs()));
+            assert!(peak > 0.05 && peak < 1.0, "{} peaks at {}", sound.name(), peak);
+            // Faded out by the end
+            assert!(hit.last().unwrap().abs() < 0.01);
+        }
+        // Not a drum General MIDI has
+        assert_eq!(DrumSound::from_key(20), None);
+    }
+}
@@ -0,0 +1,112 @@
+//! Generators — notes made from a rule rather than drawn one by one
+//!
+//! Each works in pitches and steps; the app lays what comes back out on
+//! the grid, a grid step apart.
+
+/// The order an arpeggio plays its chord in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArpDirection {
+    Up,
+    Down,
+    Random,
+}
+
+impl ArpDirection {
+    pub const ALL: [ArpDirection; 3] = [ArpDirection::Up, ArpDirection::Down, ArpDirection::Random];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ArpDirection::Up => "up",
+            ArpDirection::Down => "down",
+            ArpDirection::Random => "random",
+        }
+    }
+}
+
+/// The chord `pitches` played one at a time for `steps` steps. `seed`
+/// picks the order for `Random`, which never plays a pitch twice running.
+pub fn arpeggiate(pitches: &[u8], steps: usize, direction: ArpDirection, seed: u32) -> Vec<u8> {
+    let mut chord = pitches.to_vec();
+    chord.sort_unstable();
+    chord.dedup();
+    if chord.is_empty() {
+        return Vec::new();
+    }
+    if direction == ArpDirection::Down {
+        chord.reverse();
+    }
+    let mut state = seed.max(1);
+    let mut last = None;
+    (0..steps)
+        .map(|i| {
+            let index = match direction {
+                ArpDirection::Up | ArpDirection::Down => i % chord.len(),
+                ArpDirection::Random => loop {
+                    // xorshift32
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    let index = state as usize % chord.len();
+                    if chord.len() == 1 || last != Some(index) {
+                        break index;
+                    }
+                },
+            };
+            last = Some(index);
+            chord[index]
+        })
+        .collect()
+}
+
+/// Every pitch of the scale (`intervals` above `root`) from `from` to `to`,
+/// both included where they're in it, going up or down as they lie
+pub fn scale_run(from: u8, to: u8, root: u8, intervals: &[u8]) -> Vec<u8> {
+    let in_scale = |pitch: u8| intervals.contains(&((pitch as i32 - root as i32).rem_euclid(12) as u8));
+    let pitches: Vec<u8> = (from.min(to)..=from.max(to)).filter(|&p| in_scale(p)).collect();
+    if from > to {
+        pitches.into_iter().rev().collect()
+    } else {
+        pitches
+    }
+}
+
+/// A euclidean rhythm: `hits` spread as evenly as they go over `steps`,
+/// turned `rotation` steps later. True where a step is hit.
+pub fn euclidean(hits: usize, steps: usize, rotation: usize) -> Vec<bool> {
+    if steps == 0 {
+        return Vec::new();
+    }
+    let hits = hits.min(steps);
+    (0..steps)
+        .map(|i| {
+            let i = (i + steps - rotation % steps) % steps;
+            (i * hits) % steps < hits
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generators() {
+        let chord = [64, 60, 67];
+        assert_eq!(arpeggiate(&chord, 5, ArpDirection::Up, 0), vec![60, 64, 67, 60, 64]);
+        assert_eq!(arpeggiate(&chord, 4, ArpDirection::Down, 0), vec![67, 64, 60, 67]);
+        let random = arpeggiate(&chord, 32, ArpDirection::Random, 7);
+        assert!(random.iter().all(|p| chord.contains(p)));
+        assert!(random.windows(2).all(|w| w[0] != w[1]));
+
+        // C major from C up to the next C, and back down
+        let major = [0, 2, 4, 5, 7, 9, 11];
+        assert_eq!(scale_run(60, 72, 0, &major), vec![60, 62, 64, 65, 67, 69, 71, 72]);
+        assert_eq!(scale_run(65, 60, 0, &major), vec![65, 64, 62, 60]);
+
+        // The tresillo, and turned a step later
+        let tresillo: Vec<bool> = "x..x..x.".chars().map(|c| c == 'x').collect();
+        assert_eq!(euclidean(3, 8, 0), tresillo);
+        assert!(euclidean(3, 8, 1)[1]);
+        assert_eq!(euclidean(9, 4, 0), vec![true; 4]);
+    }
+}
@@ -1,22 +1,22 @@
 //! slowMidi — MIDI notation and sequencer
 
 mod app;
+mod bounce;
+mod drums;
+mod generators;
+mod synth;
 
 use app::SlowMidiApp;
 use slowcore::theme::SlowTheme;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let initial_file = std::env::args().nth(1).map(std::path::PathBuf::from);
 
-    let mut viewport = egui::ViewportBuilder::default()
-        .with_inner_size([760.0, 580.0])
+    let viewport = window_layout::viewport("slowmidi", Placement::RememberLast, [760.0, 580.0])
         .with_min_inner_size([560.0, 400.0])
         .with_title("slowMidi");
 
-    if let Some(pos) = slowcore::cascade_position() {
-        viewport = viewport.with_position(pos);
-    }
-
     let options = eframe::NativeOptions {
         viewport,
         ..Default::default()
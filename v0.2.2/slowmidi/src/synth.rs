@@ -0,0 +1,101 @@
+//! The instruments notes play on — the built-in sine, or a plugin
+
+use rodio::Source;
+use slowcore::plugins::Plugin;
+use std::time::Duration;
+
+/// Sample rate notes play at
+pub const PLUGIN_SAMPLE_RATE: u32 = 44100;
+/// Velocity notes play at, as new notes have
+pub const DEFAULT_VELOCITY: u8 = 100;
+/// Longest a note sounds, in seconds
+pub const MAX_NOTE_SECS: f32 = 8.0;
+
+/// A sine wave audio source for a single note
+pub struct SineWave {
+    freq: f32,
+    sample_rate: u32,
+    num_samples: usize,
+    current_sample: usize,
+    /// Length of the fade in and out, 500 samples at 44.1 kHz
+    fade: usize,
+}
+
+impl SineWave {
+    pub fn new(freq: f32, duration_ms: u32, sample_rate: u32) -> Self {
+        Self::with_samples(freq, (sample_rate as u64 * duration_ms as u64 / 1000) as usize, sample_rate)
+    }
+
+    fn with_samples(freq: f32, num_samples: usize, sample_rate: u32) -> Self {
+        Self {
+            freq,
+            sample_rate,
+            num_samples,
+            current_sample: 0,
+            fade: (500 * sample_rate as u64 / 44100).max(1) as usize,
+        }
+    }
+}
+
+impl Source for SineWave {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_millis((self.num_samples as u64 * 1000) / self.sample_rate as u64))
+    }
+}
+
+impl Iterator for SineWave {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current_sample >= self.num_samples {
+            return None;
+        }
+
+        let t = self.current_sample as f32 / self.sample_rate as f32;
+        self.current_sample += 1;
+
+        // Simple envelope: attack/decay to avoid clicks
+        let envelope = if self.current_sample < self.fade {
+            self.current_sample as f32 / self.fade as f32
+        } else if self.current_sample > self.num_samples.saturating_sub(self.fade) {
+            (self.num_samples - self.current_sample) as f32 / self.fade as f32
+        } else {
+            1.0
+        };
+
+        // Apply limiter: sine * envelope * master volume, then soft clip
+        let sample = (t * self.freq * 2.0 * std::f32::consts::PI).sin() * 0.25 * envelope;
+        // Soft limiter to prevent clipping and protect speakers
+        Some(sample.tanh())
+    }
+}
+
+/// Convert MIDI note number to frequency
+pub fn midi_to_freq(note: u8) -> f32 {
+    440.0 * 2.0_f32.powf((note as f32 - 69.0) / 12.0)
+}
+
+/// Render a note on `instrument`, or the sine if none, filling `out`
+pub fn render_note(instrument: Option<&Plugin>, pitch: u8, velocity: u8, sample_rate: u32, out: &mut [f32]) {
+    match instrument {
+        Some(plugin) => plugin.render(pitch, velocity, sample_rate, out),
+        None => {
+            let sine = SineWave::with_samples(midi_to_freq(pitch), out.len(), sample_rate);
+            for (slot, sample) in out.iter_mut().zip(sine) {
+                *slot = sample;
+            }
+        }
+    }
+}
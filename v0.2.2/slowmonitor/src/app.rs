@@ -0,0 +1,318 @@
+//! slowMonitor application
+
+use crate::stats::{format_kb, read_temperature, CpuTimes, History, MemInfo, ProcSample};
+use egui::{CentralPanel, Context, Key, Pos2, Rect, Sense, Stroke, TopBottomPanel, Vec2};
+use slowcore::repaint::RepaintController;
+use slowcore::running::RunningApp;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, alert, status_bar, window_control_buttons_with_pin, AboutInfo, SlowButton, WindowAction};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowMonitor",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "resource monitor for slowOS",
+    features: &["cpu, memory and temperature history", "per-app cpu and memory", "force quit through the desktop"],
+    ..AboutInfo::EMPTY
+};
+
+/// Sampling intervals offered in the menu
+const INTERVALS: &[(u64, &str)] = &[(1, "every second"), (2, "every 2 seconds"), (5, "every 5 seconds")];
+
+/// Readings kept for each graph
+const HISTORY_LEN: usize = 60;
+
+/// Temperatures are graphed on this scale, in °C
+const TEMP_RANGE: (f32, f32) = (30.0, 90.0);
+
+/// Height of one history graph
+const GRAPH_HEIGHT: f32 = 48.0;
+
+/// One row of the process table
+struct ProcessRow {
+    app: RunningApp,
+    /// Share of the whole machine, 0.0 to 1.0
+    cpu: f32,
+    rss_kb: u64,
+}
+
+pub struct SlowMonitorApp {
+    interval: Duration,
+    last_sample: Option<Instant>,
+    prev_cpu: Option<CpuTimes>,
+    /// Previous CPU ticks per pid, for per-process usage
+    prev_ticks: HashMap<u32, u64>,
+    cpu: History,
+    memory: History,
+    temperature: History,
+    mem_info: MemInfo,
+    processes: Vec<ProcessRow>,
+    /// Process waiting for the force-quit confirmation
+    confirm_quit: Option<RunningApp>,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowMonitorApp {
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        Self {
+            interval: Duration::from_secs(2),
+            last_sample: None,
+            prev_cpu: None,
+            prev_ticks: HashMap::new(),
+            cpu: History::new(HISTORY_LEN),
+            memory: History::new(HISTORY_LEN),
+            temperature: History::new(HISTORY_LEN),
+            mem_info: MemInfo::default(),
+            processes: Vec::new(),
+            confirm_quit: None,
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        }
+    }
+
+    /// Take a new reading of everything
+    fn sample(&mut self) {
+        self.last_sample = Some(Instant::now());
+
+        let cpu = CpuTimes::read();
+        let elapsed_ticks = match (cpu, self.prev_cpu) {
+            (Some(now), Some(prev)) => {
+                self.cpu.push(now.usage_since(&prev));
+                now.total.saturating_sub(prev.total)
+            }
+            _ => 0,
+        };
+        if cpu.is_some() {
+            self.prev_cpu = cpu;
+        }
+
+        if let Some(info) = MemInfo::read() {
+            self.mem_info = info;
+            self.memory.push(info.usage());
+        }
+        if let Some(temp) = read_temperature() {
+            let (lo, hi) = TEMP_RANGE;
+            self.temperature.push(((temp - lo) / (hi - lo)).clamp(0.0, 1.0));
+        }
+
+        let mut apps = slowcore::running::read_all();
+        // The desktop isn't in its own list; it is our parent when it started us
+        if std::env::var("SLOWOS_MANAGED").is_ok() {
+            apps.insert(0, RunningApp {
+                key: "slowdesktop".into(),
+                binary: "slowdesktop".into(),
+                name: "desktop".into(),
+                pid: std::os::unix::process::parent_id(),
+            });
+        }
+
+        let mut ticks = HashMap::new();
+        self.processes = apps
+            .into_iter()
+            .filter_map(|app| {
+                let sample = ProcSample::read(app.pid)?;
+                ticks.insert(app.pid, sample.cpu_ticks);
+                let cpu = match self.prev_ticks.get(&app.pid) {
+                    Some(&prev) if elapsed_ticks > 0 => {
+                        (sample.cpu_ticks.saturating_sub(prev) as f32 / elapsed_ticks as f32).min(1.0)
+                    }
+                    _ => 0.0,
+                };
+                Some(ProcessRow { app, cpu, rss_kb: sample.rss_kb })
+            })
+            .collect();
+        self.prev_ticks = ticks;
+    }
+
+    fn force_quit(&mut self, app: RunningApp) {
+        slowcore::running::request_quit(app.pid);
+        self.message = Some(format!("asked the desktop to quit {}", app.name));
+        self.processes.retain(|p| p.app.pid != app.pid);
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons_with_pin(ui);
+                ui.menu_button("monitor", |ui| {
+                    if ui.button("update now    ⌘R").clicked() {
+                        self.sample();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    for &(secs, label) in INTERVALS {
+                        let selected = self.interval == Duration::from_secs(secs);
+                        let text = if selected { format!("✓ {}", label) } else { format!("  {}", label) };
+                        if ui.button(text).clicked() {
+                            self.interval = Duration::from_secs(secs);
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowmonitor", "slowMonitor");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    /// A labelled history graph: black line over a dithered fill
+    fn draw_graph(ui: &mut egui::Ui, label: &str, value: &str, history: &History) {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(value);
+            });
+        });
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), GRAPH_HEIGHT), Sense::hover());
+        let painter = ui.painter();
+        painter.rect_filled(rect, 0.0, SlowColors::WHITE);
+
+        let inner = rect.shrink(1.0);
+        let step = inner.width() / (history.capacity().max(2) - 1) as f32;
+        // Newest reading at the right edge
+        let count = history.values().count();
+        let start_x = inner.max.x - step * count.saturating_sub(1) as f32;
+        let points: Vec<Pos2> = history
+            .values()
+            .enumerate()
+            .map(|(i, v)| Pos2::new(start_x + step * i as f32, inner.max.y - v * inner.height()))
+            .collect();
+
+        for pair in points.windows(2) {
+            let column = Rect::from_min_max(Pos2::new(pair[0].x, pair[1].y.max(pair[0].y)), Pos2::new(pair[1].x, inner.max.y));
+            slowcore::dither::draw_dither_rect(painter, column, SlowColors::BLACK, 2);
+            painter.line_segment([pair[0], pair[1]], Stroke::new(1.0, SlowColors::BLACK));
+        }
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+        ui.add_space(6.0);
+    }
+
+    fn draw_process_table(&mut self, ui: &mut egui::Ui) {
+        let mut quit = None;
+        egui::Grid::new("processes")
+            .num_columns(5)
+            .striped(false)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                for heading in ["app", "pid", "cpu", "memory", ""] {
+                    ui.label(egui::RichText::new(heading).strong());
+                }
+                ui.end_row();
+                for row in &self.processes {
+                    ui.label(&row.app.name);
+                    ui.label(row.app.pid.to_string());
+                    ui.label(format!("{:.0}%", row.cpu * 100.0));
+                    ui.label(format_kb(row.rss_kb));
+                    // The desktop can't be quit from here
+                    if row.app.binary != "slowdesktop" && ui.add(SlowButton::new("quit")).clicked() {
+                        quit = Some(row.app.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        if self.processes.is_empty() {
+            ui.label("no slowOS apps running");
+        }
+        if quit.is_some() {
+            self.confirm_quit = quit;
+        }
+    }
+
+    fn draw_confirm_quit(&mut self, ctx: &Context) {
+        let Some(app) = self.confirm_quit.clone() else { return };
+        let body = format!("force quit {}?\nunsaved changes will be lost.", app.name);
+        if let Some(answer) = alert(ctx, "force quit", &body, &["cancel", "force quit"]) {
+            self.confirm_quit = None;
+            if answer == 1 {
+                self.force_quit(app);
+            }
+        }
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+impl eframe::App for SlowMonitorApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowmonitor") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+
+        if ctx.input(|i| i.modifiers.command && i.key_pressed(Key::R)) {
+            self.sample();
+        }
+        if self.last_sample.map_or(true, |t| t.elapsed() >= self.interval) {
+            self.sample();
+        }
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::top("title_bar").show(ctx, |ui| {
+            slowcore::theme::SlowTheme::title_bar_frame().show(ui, |ui| {
+                ui.centered_and_justified(|ui| {
+                    ui.label("slowMonitor");
+                });
+            });
+        });
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| {
+                format!("{} apps  |  updates every {}s", self.processes.len(), self.interval.as_secs())
+            });
+            status_bar(ui, &status);
+        });
+
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(8.0)))
+            .show(ctx, |ui| {
+                let percent = |h: &History| h.latest().map(|v| format!("{:.0}%", v * 100.0)).unwrap_or_else(|| "—".into());
+                Self::draw_graph(ui, "cpu", &percent(&self.cpu), &self.cpu);
+                let memory = format!("{} of {}", format_kb(self.mem_info.used_kb()), format_kb(self.mem_info.total_kb));
+                Self::draw_graph(ui, "memory", &memory, &self.memory);
+                if let Some(latest) = self.temperature.latest() {
+                    let (lo, hi) = TEMP_RANGE;
+                    let temp = format!("{:.0}°C", lo + latest * (hi - lo));
+                    Self::draw_graph(ui, "temperature", &temp, &self.temperature);
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.draw_process_table(ui);
+                });
+            });
+
+        self.draw_confirm_quit(ctx);
+        self.draw_about(ctx);
+
+        // Only wake for the next reading; nothing else changes on its own
+        let since = self.last_sample.map(|t| t.elapsed()).unwrap_or_default();
+        ctx.request_repaint_after(self.interval.saturating_sub(since));
+        self.repaint.end_frame(ctx);
+    }
+}
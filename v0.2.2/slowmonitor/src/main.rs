@@ -0,0 +1,29 @@
+//! slowMonitor - A resource monitor for the Slow Computer
+//!
+//! CPU, memory and temperature history, and what each slowOS app is using.
+
+mod app;
+mod stats;
+
+use app::SlowMonitorApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let viewport = window_layout::viewport("slowmonitor", Placement::RememberLast, [380.0, 480.0])
+        .with_title("slowMonitor");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowMonitor",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowMonitorApp::new(cc))
+        }),
+    )
+}
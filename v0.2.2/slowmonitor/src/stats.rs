@@ -0,0 +1,198 @@
+//! System and process statistics read from /proc
+//!
+//! CPU use is worked out from the difference between two samples, so
+//! every reading here is a raw counter and the app keeps the previous one.
+//! Process CPU is reported as a share of the whole machine (all cores).
+
+use std::collections::VecDeque;
+
+/// Cumulative CPU time from the first line of /proc/stat, in ticks
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CpuTimes {
+    pub busy: u64,
+    pub total: u64,
+}
+
+impl CpuTimes {
+    /// Parse the aggregate "cpu" line of /proc/stat
+    pub fn parse(stat: &str) -> Option<Self> {
+        let line = stat.lines().find(|l| l.starts_with("cpu "))?;
+        let fields: Vec<u64> = line.split_whitespace().skip(1).filter_map(|f| f.parse().ok()).collect();
+        if fields.len() < 4 {
+            return None;
+        }
+        let total: u64 = fields.iter().sum();
+        // idle + iowait
+        let idle = fields[3] + fields.get(4).copied().unwrap_or(0);
+        Some(Self { busy: total - idle, total })
+    }
+
+    pub fn read() -> Option<Self> {
+        Self::parse(&std::fs::read_to_string("/proc/stat").ok()?)
+    }
+
+    /// Fraction of time busy since `earlier`, 0.0 to 1.0
+    pub fn usage_since(&self, earlier: &CpuTimes) -> f32 {
+        let total = self.total.saturating_sub(earlier.total);
+        if total == 0 {
+            return 0.0;
+        }
+        (self.busy.saturating_sub(earlier.busy) as f32 / total as f32).clamp(0.0, 1.0)
+    }
+}
+
+/// Memory totals from /proc/meminfo, in kB
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemInfo {
+    pub total_kb: u64,
+    pub available_kb: u64,
+}
+
+impl MemInfo {
+    pub fn parse(meminfo: &str) -> Option<Self> {
+        let field = |name: &str| {
+            meminfo
+                .lines()
+                .find_map(|l| l.strip_prefix(name))
+                .and_then(|rest| rest.trim_start_matches(':').split_whitespace().next())
+                .and_then(|kb| kb.parse::<u64>().ok())
+        };
+        Some(Self { total_kb: field("MemTotal")?, available_kb: field("MemAvailable")? })
+    }
+
+    pub fn read() -> Option<Self> {
+        Self::parse(&std::fs::read_to_string("/proc/meminfo").ok()?)
+    }
+
+    pub fn used_kb(&self) -> u64 {
+        self.total_kb.saturating_sub(self.available_kb)
+    }
+
+    /// Fraction in use, 0.0 to 1.0
+    pub fn usage(&self) -> f32 {
+        if self.total_kb == 0 {
+            return 0.0;
+        }
+        self.used_kb() as f32 / self.total_kb as f32
+    }
+}
+
+/// SoC temperature in °C, where the board reports one
+pub fn read_temperature() -> Option<f32> {
+    let milli: f32 = std::fs::read_to_string("/sys/class/thermal/thermal_zone0/temp")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(milli / 1000.0)
+}
+
+/// One process's counters
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProcSample {
+    /// utime + stime, in ticks
+    pub cpu_ticks: u64,
+    /// Resident memory in kB
+    pub rss_kb: u64,
+}
+
+impl ProcSample {
+    /// CPU ticks from the contents of /proc/<pid>/stat
+    pub fn parse_ticks(stat: &str) -> Option<u64> {
+        // The command name may contain spaces and parentheses, so count
+        // fields from the last ')'; utime and stime are fields 14 and 15
+        let rest = &stat[stat.rfind(')')? + 1..];
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        let utime: u64 = fields.get(11)?.parse().ok()?;
+        let stime: u64 = fields.get(12)?.parse().ok()?;
+        Some(utime + stime)
+    }
+
+    /// Resident memory from the contents of /proc/<pid>/status
+    pub fn parse_rss(status: &str) -> Option<u64> {
+        status
+            .lines()
+            .find_map(|l| l.strip_prefix("VmRSS:"))
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|kb| kb.parse().ok())
+    }
+
+    pub fn read(pid: u32) -> Option<Self> {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+        Some(Self {
+            cpu_ticks: Self::parse_ticks(&stat)?,
+            // Zombies have no VmRSS line
+            rss_kb: Self::parse_rss(&status).unwrap_or(0),
+        })
+    }
+}
+
+/// A fixed number of recent readings, oldest first
+#[derive(Debug, Clone)]
+pub struct History {
+    values: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self { values: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    pub fn push(&mut self, value: f32) {
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+    }
+
+    pub fn latest(&self) -> Option<f32> {
+        self.values.back().copied()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = f32> + '_ {
+        self.values.iter().copied()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// "312 MB" or "1.4 GB"
+pub fn format_kb(kb: u64) -> String {
+    if kb >= 1024 * 1024 {
+        format!("{:.1} GB", kb as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{} MB", kb / 1024)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_usage() {
+        let a = CpuTimes::parse("cpu  100 0 100 700 100 0 0 0 0 0\ncpu0 1 2 3 4\n").unwrap();
+        assert_eq!(a, CpuTimes { busy: 200, total: 1000 });
+        let b = CpuTimes { busy: 250, total: 1100 };
+        assert!((b.usage_since(&a) - 0.5).abs() < 1e-6);
+        assert_eq!(a.usage_since(&a), 0.0);
+    }
+
+    #[test]
+    fn test_meminfo() {
+        let info = MemInfo::parse("MemTotal:  4000 kB\nMemFree:  500 kB\nMemAvailable:  1000 kB\n").unwrap();
+        assert_eq!(info.used_kb(), 3000);
+        assert!((info.usage() - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_process_stat() {
+        let stat = "42 (slow (write)) S 1 42 42 0 -1 4194304 100 0 0 0 25 17 0 0 20 0 3 0 100 0 0";
+        assert_eq!(ProcSample::parse_ticks(stat), Some(42));
+        assert_eq!(ProcSample::parse_rss("Name:\tslowwrite\nVmRSS:\t  51200 kB\n"), Some(51200));
+    }
+}
@@ -2,7 +2,7 @@
 
 use egui::{ColorImage, Context, Key, TextureHandle, TextureOptions};
 use id3::TagLike;
-use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use rodio::{Decoder, OutputStreamHandle, Sink, Source};
 use symphonia::core::audio::SampleBuffer;
 use symphonia::core::codecs::DecoderOptions;
 use symphonia::core::formats::FormatOptions;
@@ -10,14 +10,28 @@ use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 use serde::{Deserialize, Serialize};
+use slowcore::drag::DropTarget;
+use slowcore::lazy::Lazy;
 use slowcore::repaint::RepaintController;
-use slowcore::storage::{config_dir, documents_dir, FileBrowser};
+use slowcore::sound::Player;
+use slowcore::storage::{config_dir, documents_dir};
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, FileDialog, FileDialogResult, FileListItem, ListSelection, VirtualList, WindowAction};
+use std::collections::{HashMap, HashSet};
 use std::io::Cursor;
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowMusic",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "music player for slowOS",
+    formats: &["MP3, WAV, FLAC, OGG, AAC"],
+    features: &["library management", "persistent playback state"],
+    credits: &["egui/eframe (MIT), rodio (MIT)", "symphonia (MPL-2.0)"],
+    ..AboutInfo::EMPTY
+};
+
 /// Metadata extracted from an audio file's ID3 tags
 #[derive(Default)]
 struct TrackMeta {
@@ -43,7 +57,45 @@ struct Library {
     tracks: Vec<TrackInfo>,
 }
 
+/// A row of the library list
+#[derive(Clone, Debug, PartialEq)]
+enum LibraryRow {
+    /// An album's heading ("album — artist"), and the album
+    Album { heading: String, album: String },
+    /// A track, by index
+    Track(usize),
+}
+
 impl Library {
+    /// The list as shown: albums by name, each heading followed by its
+    /// tracks unless folded away, then the tracks on no album
+    fn rows(&self, folded: &HashSet<String>) -> Vec<LibraryRow> {
+        let mut albums: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut ungrouped = Vec::new();
+        for (idx, track) in self.tracks.iter().enumerate() {
+            match &track.album {
+                Some(album) => albums.entry(album).or_default().push(idx),
+                None => ungrouped.push(idx),
+            }
+        }
+        let mut albums: Vec<(&str, Vec<usize>)> = albums.into_iter().collect();
+        albums.sort_by_key(|(album, _)| album.to_lowercase());
+
+        let mut rows = Vec::new();
+        for (album, indices) in albums {
+            let heading = match indices.first().and_then(|&i| self.tracks[i].artist.as_deref()) {
+                Some(artist) if !artist.is_empty() => format!("{} — {}", album, artist),
+                _ => album.to_string(),
+            };
+            rows.push(LibraryRow::Album { heading, album: album.to_string() });
+            if !folded.contains(album) {
+                rows.extend(indices.into_iter().map(LibraryRow::Track));
+            }
+        }
+        rows.extend(ungrouped.into_iter().map(LibraryRow::Track));
+        rows
+    }
+
     fn config_path() -> PathBuf {
         config_dir("slowmusic").join("library.json")
     }
@@ -69,9 +121,17 @@ impl Library {
 
 pub struct SlowMusicApp {
     library: Library,
+    /// The library list's rows, worked out again when the library or the
+    /// folding changes
+    library_rows: Option<Vec<LibraryRow>>,
+    library_selection: ListSelection,
+    /// Albums folded away in the list
+    folded_albums: HashSet<String>,
     current_track: Option<usize>,
-    _stream: Option<OutputStream>,
-    _stream_handle: Option<OutputStreamHandle>,
+    /// Shared audio output from slowcore
+    stream_handle: Lazy<Option<&'static OutputStreamHandle>>,
+    /// Registration with the system mixer (mute, per-app volume)
+    player: Player,
     sink: Option<Sink>,
     is_playing: bool,
     volume: f32,
@@ -79,8 +139,7 @@ pub struct SlowMusicApp {
     elapsed_before_pause: Duration,
     track_duration: Option<Duration>,
     repeat_mode: RepeatMode,
-    show_file_browser: bool,
-    file_browser: FileBrowser,
+    file_dialog: Option<FileDialog>,
     show_about: bool,
     error_msg: Option<String>,
     /// Metadata for the currently playing track
@@ -99,13 +158,16 @@ enum RepeatMode { None, All, One }
 
 impl SlowMusicApp {
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let (stream, handle) = OutputStream::try_default().ok().unzip();
         let library = Library::load();
         Self {
             library,
+            library_rows: None,
+            library_selection: ListSelection::default(),
+            folded_albums: HashSet::new(),
             current_track: None,
-            _stream: stream,
-            _stream_handle: handle,
+            // Opening the sound card can take a while; not before the window shows
+            stream_handle: Lazy::new("audio", slowcore::sound::output),
+            player: Player::register("slowmusic"),
             sink: None,
             is_playing: false,
             volume: 0.8,
@@ -113,9 +175,7 @@ impl SlowMusicApp {
             elapsed_before_pause: Duration::ZERO,
             track_duration: None,
             repeat_mode: RepeatMode::None,
-            show_file_browser: false,
-            file_browser: FileBrowser::new(documents_dir())
-                .with_filter(vec!["mp3".into(), "wav".into(), "flac".into(), "ogg".into(), "m4a".into(), "aac".into()]),
+            file_dialog: None,
             show_about: false,
             error_msg: None,
             current_meta: TrackMeta::default(),
@@ -227,6 +287,7 @@ impl SlowMusicApp {
             });
         self.library.tracks.push(TrackInfo { name, path, album, artist });
         self.library.save();
+        self.library_rows = None;
     }
 
     fn remove_track(&mut self, index: usize) {
@@ -239,6 +300,7 @@ impl SlowMusicApp {
             }
             self.library.tracks.remove(index);
             self.library.save();
+            self.library_rows = None;
         }
     }
 
@@ -286,10 +348,10 @@ impl SlowMusicApp {
 
     fn start_playback<S: Source<Item = f32> + Send + 'static>(&mut self, source: S, index: usize) {
         self.track_duration = source.total_duration();
-        if let Some(ref handle) = self._stream_handle {
+        if let Some(handle) = self.stream_handle.wait().and_then(|handle| *handle) {
             match Sink::try_new(handle) {
                 Ok(sink) => {
-                    sink.set_volume(self.volume);
+                    sink.set_volume(self.volume * self.player.volume());
                     sink.append(source);
                     self.sink = Some(sink);
                     self.current_track = Some(index);
@@ -375,11 +437,13 @@ impl SlowMusicApp {
 
     fn handle_keys(&mut self, ctx: &Context) {
         slowcore::theme::consume_special_keys(ctx);
+        // Letters typed into the add music dialog find files, not tracks
+        if self.file_dialog.is_some() { return; }
         ctx.input(|i| {
             if i.key_pressed(Key::Space) { self.toggle_play(); }
             if i.key_pressed(Key::N) || i.key_pressed(Key::ArrowRight) { self.next_track(); }
             if i.key_pressed(Key::P) || i.key_pressed(Key::ArrowLeft) { self.prev_track(); }
-            if i.modifiers.command && i.key_pressed(Key::O) { self.show_file_browser = true; }
+            if i.modifiers.command && i.key_pressed(Key::O) { self.show_add_dialog(); }
         });
     }
 
@@ -548,7 +612,7 @@ impl SlowMusicApp {
                     if let Some(pos) = response.interact_pointer_pos() {
                         let rel = ((pos.x - rect.min.x) / rect.width()).clamp(0.0, 1.0);
                         self.volume = rel;
-                        if let Some(ref sink) = self.sink { sink.set_volume(self.volume); }
+                        if let Some(ref sink) = self.sink { sink.set_volume(self.volume * self.player.volume()); }
                     }
                 }
             });
@@ -564,146 +628,128 @@ impl SlowMusicApp {
     fn render_library(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             ui.label(egui::RichText::new("music").strong());
-            if ui.button("add music").clicked() { self.show_file_browser = true; }
-            if ui.button("clear all").clicked() { self.library.tracks.clear(); self.library.save(); self.stop(); self.current_track = None; }
+            if ui.button("add music").clicked() { self.show_add_dialog(); }
+            if ui.button("clear all").clicked() { self.library.tracks.clear(); self.library.save(); self.library_rows = None; self.stop(); self.current_track = None; }
         });
         ui.separator();
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            if self.library.tracks.is_empty() {
-                ui.add_space(40.0);
-                ui.vertical_centered(|ui| {
-                    ui.label("want to grow your music collection?");
-                    ui.add_space(4.0);
-                    ui.label("mp3 and wave files can be bought at bandcamp.com");
-                });
-                return;
-            }
-
-            // Group tracks: albums first, then ungrouped
-            let mut album_map: std::collections::HashMap<String, Vec<usize>> = std::collections::HashMap::new();
-            let mut ungrouped: Vec<usize> = Vec::new();
+        if self.library.tracks.is_empty() {
+            ui.add_space(40.0);
+            ui.vertical_centered(|ui| {
+                ui.label("want to grow your music collection?");
+                ui.add_space(4.0);
+                ui.label("mp3 and wave files can be bought at bandcamp.com");
+            });
+            return;
+        }
 
-            for idx in 0..self.library.tracks.len() {
-                if let Some(ref album) = self.library.tracks[idx].album {
-                    album_map.entry(album.clone()).or_default().push(idx);
-                } else {
-                    ungrouped.push(idx);
-                }
-            }
-            let mut albums: Vec<(String, Vec<usize>)> = album_map.into_iter().collect();
-            albums.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
-
-            let mut play_idx = None;
-            let mut remove_idx = None;
-
-            // Render album groups
-            for (album_name, track_indices) in &albums {
-                let artist_label = track_indices.first()
-                    .and_then(|&i| self.library.tracks[i].artist.as_deref())
-                    .unwrap_or("");
-                let header = if artist_label.is_empty() {
-                    album_name.clone()
-                } else {
-                    format!("{} — {}", album_name, artist_label)
-                };
-                egui::CollapsingHeader::new(&header)
-                    .default_open(true)
-                    .show(ui, |ui| {
-                        for &idx in track_indices {
-                            let track = &self.library.tracks[idx];
-                            let current = self.current_track == Some(idx);
-                            let prefix = if current && self.is_playing { "> " } else if current { "| " } else { "  " };
-                            let label = format!("{}{}", prefix, track.name);
-                            ui.horizontal(|ui| {
-                                let r = ui.selectable_label(current, &label);
-                                if r.double_clicked() { play_idx = Some(idx); }
-                                if ui.small_button("x").on_hover_text("remove from library").clicked() {
-                                    remove_idx = Some(idx);
-                                }
-                            });
+        let rows = &*self.library_rows.get_or_insert_with(|| self.library.rows(&self.folded_albums));
+        let tracks = &self.library.tracks;
+        let (current_track, is_playing) = (self.current_track, self.is_playing);
+        let row_height = FileListItem::HEIGHT * slowcore::theme::ui_scale();
+        let mut remove_idx = None;
+        let mut fold = None;
+
+        // Only the rows on screen are laid out
+        let list = VirtualList::new("music_library")
+            .row_height(row_height)
+            .max_height(ui.available_height() / slowcore::theme::ui_scale())
+            .show(
+                ui,
+                &mut self.library_selection,
+                rows.len(),
+                |i| match &rows[i] {
+                    LibraryRow::Album { album, .. } => album.as_str(),
+                    LibraryRow::Track(idx) => tracks[*idx].name.as_str(),
+                },
+                |ui, i, selected| {
+                    let sense = egui::Sense { focusable: false, ..egui::Sense::click() };
+                    let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), row_height), sense);
+                    let (text, indent, font) = match &rows[i] {
+                        LibraryRow::Album { heading, album } => {
+                            if response.clicked() {
+                                fold = Some(album.clone());
+                            }
+                            let arrow = if self.folded_albums.contains(album) { "▸" } else { "▾" };
+                            (format!("{} {}", arrow, heading), 4.0, egui::FontId::proportional(13.0))
                         }
-                    });
-            }
-
-            // Render ungrouped tracks
-            if !ungrouped.is_empty() && !albums.is_empty() {
-                ui.separator();
-            }
-            for idx in &ungrouped {
-                let track = &self.library.tracks[*idx];
-                let current = self.current_track == Some(*idx);
-                let prefix = if current && self.is_playing { "> " } else if current { "| " } else { "  " };
-                let label = format!("{}{}", prefix, track.name);
-                ui.horizontal(|ui| {
-                    let r = ui.selectable_label(current, &label);
-                    if r.double_clicked() { play_idx = Some(*idx); }
-                    if ui.small_button("x").on_hover_text("remove from library").clicked() {
-                        remove_idx = Some(*idx);
+                        LibraryRow::Track(idx) => {
+                            let current = current_track == Some(*idx);
+                            let prefix = if current && is_playing { "> " } else if current { "| " } else { "  " };
+                            // Tracks on an album sit under its heading
+                            let indent = if tracks[*idx].album.is_some() { 16.0 } else { 4.0 };
+                            (format!("{}{}", prefix, tracks[*idx].name), indent, egui::FontId::proportional(12.0))
+                        }
+                    };
+                    if ui.is_rect_visible(rect) {
+                        let painter = ui.painter();
+                        let color = if selected {
+                            slowcore::dither::draw_dither_selection(painter, rect);
+                            SlowColors::WHITE
+                        } else {
+                            if response.hovered() {
+                                slowcore::dither::draw_dither_hover(painter, rect);
+                            }
+                            SlowColors::BLACK
+                        };
+                        painter.text(rect.left_center() + egui::vec2(indent, 0.0), egui::Align2::LEFT_CENTER, text, font, color);
                     }
-                });
+                    if let LibraryRow::Track(idx) = rows[i] {
+                        let x_rect = egui::Rect::from_min_size(rect.right_top() - egui::vec2(row_height, 0.0), egui::vec2(row_height, row_height));
+                        if ui.put(x_rect, egui::Button::new("x").small()).on_hover_text("remove from library").clicked() {
+                            remove_idx = Some(idx);
+                        }
+                    }
+                    response
+                },
+            );
+
+        if let Some(LibraryRow::Track(idx)) = list.activated.and_then(|i| rows.get(i)).cloned() {
+            self.play_track(idx);
+        }
+        if let Some(album) = fold {
+            if !self.folded_albums.remove(&album) {
+                self.folded_albums.insert(album);
             }
+            self.library_rows = None;
+        }
+        if let Some(idx) = remove_idx { self.remove_track(idx); }
+    }
 
-            if let Some(idx) = play_idx { self.play_track(idx); }
-            if let Some(idx) = remove_idx { self.remove_track(idx); }
-        });
+    fn show_add_dialog(&mut self) {
+        self.file_dialog = Some(
+            FileDialog::open(documents_dir())
+                .title("add music")
+                .filter(&["mp3", "wav", "flac", "ogg", "m4a", "aac"])
+                .multi_select(true)
+                .action("add"),
+        );
     }
 
-    fn render_file_browser(&mut self, ctx: &Context) {
-        let resp = egui::Window::new("add music").collapsible(false).resizable(false).default_width(380.0)
-            .show(ctx, |ui| {
-                ui.label(self.file_browser.current_dir.to_string_lossy().to_string());
-                ui.separator();
-                egui::ScrollArea::vertical().max_height(220.0).show(ui, |ui| {
-                    let mut clicked_idx = None;
-                    let mut nav_path = None;
-                    let mut add_path = None;
-                    for (idx, entry) in self.file_browser.entries.iter().enumerate() {
-                        let sel = self.file_browser.selected_index == Some(idx);
-                        let r = ui.add(slowcore::widgets::FileListItem::new(&entry.name, entry.is_directory).selected(sel));
-                        if r.clicked() { clicked_idx = Some(idx); }
-                        if r.double_clicked() {
-                            if entry.is_directory { nav_path = Some(entry.path.clone()); }
-                            else { add_path = Some(entry.path.clone()); }
-                        }
-                    }
-                    if let Some(idx) = clicked_idx { self.file_browser.selected_index = Some(idx); }
-                    if let Some(path) = nav_path { self.file_browser.navigate_to(path); }
-                    if let Some(path) = add_path { self.add_file(path); self.show_file_browser = false; }
-                });
-                ui.separator();
-                ui.horizontal(|ui| {
-                    if ui.button("cancel").clicked() { self.show_file_browser = false; }
-                    if ui.button("add selected").clicked() {
-                        if let Some(e) = self.file_browser.selected_entry() {
-                            if !e.is_directory { let p = e.path.clone(); self.add_file(p); self.show_file_browser = false; }
-                        }
-                    }
-                    if ui.button("add all").clicked() {
-                        let files: Vec<PathBuf> = self.file_browser.entries.iter()
-                            .filter(|e| !e.is_directory).map(|e| e.path.clone()).collect();
-                        for f in files { self.add_file(f); }
-                        self.show_file_browser = false;
-                    }
-                });
-            });
-        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+    fn render_file_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.file_dialog else { return };
+        match dialog.show(ctx) {
+            FileDialogResult::Picked(_) => {
+                let files = dialog.picked_files().to_vec();
+                self.file_dialog = None;
+                for f in files { self.add_file(f); }
+            }
+            FileDialogResult::Cancelled => self.file_dialog = None,
+            FileDialogResult::Pending => {}
+        }
     }
 }
 
 impl eframe::App for SlowMusicApp {
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
         self.repaint.begin_frame(ctx);
+        self.stream_handle.poll(ctx);
         if slowcore::minimize::check_restore_signal("slowmusic") {
             ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
             ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
         }
         // Handle drag and drop of audio files and folders
-        let dropped_paths: Vec<PathBuf> = ctx.input(|i| {
-            i.raw.dropped_files.iter()
-                .filter_map(|file| file.path.clone())
-                .collect()
-        });
+        let dropped_paths = DropTarget::new(AUDIO_EXTENSIONS).folders(true).show(ctx);
 
         // Collect all audio files (recursively scanning directories)
         if !dropped_paths.is_empty() {
@@ -725,6 +771,12 @@ impl eframe::App for SlowMusicApp {
         self.handle_keys(ctx);
         self.check_track_end();
 
+        // Follow the system mixer (mute / per-app volume)
+        self.player.set_playing(self.is_playing);
+        if let Some(ref sink) = self.sink {
+            sink.set_volume(self.volume * self.player.volume());
+        }
+
         // Load metadata for current track (lazy, once per track change)
         if let Some(idx) = self.current_track {
             if let Some(track) = self.library.tracks.get(idx) {
@@ -735,13 +787,13 @@ impl eframe::App for SlowMusicApp {
 
         // Only request timed repaints during playback (progress bar).
         // Idle display holds on e-ink; updates on next input event.
-        self.repaint.set_continuous(self.is_playing);
+        self.repaint.set_background(self.is_playing);
 
         let win_action = egui::TopBottomPanel::top("menu").show(ctx, |ui| {
             menu_bar(ui, |ui| {
                 let action = window_control_buttons(ui);
                 ui.menu_button("file", |ui| {
-                    if ui.button("add music...  ⌘o").clicked() { self.show_file_browser = true; ui.close_menu(); }
+                    if ui.button("add music...  ⌘o").clicked() { self.show_add_dialog(); ui.close_menu(); }
                 });
                 ui.menu_button("help", |ui| {
                     if ui.button("about").clicked() { self.show_about = true; ui.close_menu(); }
@@ -778,44 +830,9 @@ impl eframe::App for SlowMusicApp {
             egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(8.0))
         ).show(ctx, |ui| self.render_library(ui));
 
-        if self.show_file_browser { self.render_file_browser(ctx); }
-        if self.show_about {
-            let screen = ctx.screen_rect();
-            let max_h = (screen.height() - 60.0).max(120.0);
-
-            let resp = egui::Window::new("about slowMusic")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(300.0)
-                .max_height(max_h)
-                .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                        ui.vertical_centered(|ui| {
-                            ui.heading("slowMusic");
-                            ui.label("version 0.2.2");
-                            ui.add_space(8.0);
-                            ui.label("music player for slowOS");
-                        });
-                        ui.add_space(8.0);
-                        ui.separator();
-                        ui.add_space(4.0);
-                        ui.label("supported formats:");
-                        ui.label("  MP3, WAV, FLAC, OGG, AAC");
-                        ui.add_space(4.0);
-                        ui.label("features:");
-                        ui.label("  library management");
-                        ui.label("  persistent playback state");
-                        ui.add_space(4.0);
-                        ui.label("frameworks:");
-                        ui.label("  egui/eframe (MIT), rodio (MIT)");
-                        ui.label("  symphonia (MPL-2.0)");
-                        ui.add_space(8.0);
-                    });
-                    ui.vertical_centered(|ui| {
-                        if ui.button("ok").clicked() { self.show_about = false; }
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+        self.render_file_dialog(ctx);
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
         self.repaint.end_frame(ctx);
     }
@@ -909,12 +926,15 @@ fn decode_with_symphonia(data: Vec<u8>, ext: &str) -> Result<SamplesSource, Stri
     Ok(SamplesSource { samples, pos: 0, sample_rate, channels })
 }
 
+/// Files slowMusic plays
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "wav", "flac", "ogg", "m4a", "aac"];
+
 fn is_audio_file(path: &std::path::Path) -> bool {
     let ext = path.extension()
         .and_then(|e| e.to_str())
         .map(|e| e.to_lowercase())
         .unwrap_or_default();
-    matches!(ext.as_str(), "mp3" | "wav" | "flac" | "ogg" | "m4a" | "aac")
+    AUDIO_EXTENSIONS.contains(&ext.as_str())
 }
 
 fn collect_audio_files_recursive(dir: &std::path::Path, files: &mut Vec<PathBuf>) {
@@ -1,13 +1,13 @@
 mod app;
 use app::SlowMusicApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let initial_file = std::env::args().nth(1).map(std::path::PathBuf::from);
 
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([460.0, 480.0])
+        viewport: window_layout::viewport("slowmusic", Placement::RememberLast, [460.0, 480.0])
             .with_title("slowMusic"),
         ..Default::default()
     };
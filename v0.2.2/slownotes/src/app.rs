@@ -3,11 +3,23 @@
 use chrono::Local;
 use egui::{Context, Key};
 use serde::{Deserialize, Serialize};
+use slowcore::autocorrect::Autocorrect;
+use slowcore::ipc::{self, Command, Reply};
 use slowcore::repaint::RepaintController;
 use slowcore::storage::config_dir;
-use slowcore::text_edit::WordDragState;
+use slowcore::text_edit::{InputMethod, InputMode, WordDragState};
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, WindowAction};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowNotes",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "simple note-taking app",
+    features: &["create, search, pin notes", "deleted notes go to trash", "scriptable with slowctl"],
+    sections: &[("storage", &["JSON in config directory"])],
+    credits: &["egui/eframe (MIT), chrono (MIT)", "serde (MIT/Apache-2.0)"],
+    ..AboutInfo::EMPTY
+};
 
 /// Move note data to the slow computer trash as a .txt file.
 /// Writes directly into the trash directory to avoid cross-filesystem issues.
@@ -187,6 +199,8 @@ pub struct SlowNoteApp {
     search_query: String,
     show_about: bool,
     word_drag: WordDragState,
+    input: InputMethod,
+    autocorrect: Autocorrect,
     repaint: RepaintController,
 }
 
@@ -199,6 +213,8 @@ impl SlowNoteApp {
         Self {
             store, selected, search_query: String::new(), show_about: false,
             word_drag: WordDragState::new(),
+            input: InputMethod::new(),
+            autocorrect: Autocorrect::new("slownotes"),
             repaint: RepaintController::new(),
         }
     }
@@ -257,6 +273,56 @@ impl SlowNoteApp {
         });
     }
 
+    /// Carry out commands from scripts (slowctl). Text typed while no
+    /// field has the keyboard goes at the end of the open note. Queries:
+    /// "note" (its title), "text" (its body) and "notes" (every title).
+    fn handle_commands(&mut self, ctx: &Context) {
+        for request in ipc::take() {
+            let reply = if request.is_menu("file", "new note") {
+                self.new_note();
+                Reply::Done
+            } else if request.is_menu("file", "delete") {
+                self.delete_note();
+                Reply::Done
+            } else {
+                match &request.command {
+                    Command::Open { path } => match std::fs::read_to_string(path) {
+                        Ok(body) => {
+                            self.new_note();
+                            let note = &mut self.store.notes[0];
+                            note.title = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+                            note.body = body;
+                            self.store.save();
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                            Reply::Done
+                        }
+                        Err(e) => Reply::Failed(format!("could not read {}: {}", path.display(), e)),
+                    },
+                    Command::Type { text } => match self.selected.and_then(|i| self.store.notes.get_mut(i)) {
+                        Some(note) => {
+                            note.body.push_str(text);
+                            note.touch();
+                            self.store.save();
+                            Reply::Done
+                        }
+                        None => Reply::Failed("no note selected".into()),
+                    },
+                    Command::Query { key } => {
+                        let note = self.selected.and_then(|i| self.store.notes.get(i));
+                        match key.as_str() {
+                            "note" => Reply::Value(note.map(|n| n.title.clone()).into()),
+                            "text" => Reply::Value(note.map(|n| n.body.clone()).into()),
+                            "notes" => Reply::Value(self.store.notes.iter().map(|n| n.title.clone()).collect()),
+                            _ => Reply::Failed(format!("nothing called \"{}\" to ask about", key)),
+                        }
+                    }
+                    Command::Menu { .. } => Reply::Failed("no such menu item".into()),
+                }
+            };
+            request.reply(reply);
+        }
+    }
+
     fn render_sidebar(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             ui.label("🔍");
@@ -319,18 +385,22 @@ impl SlowNoteApp {
 
         // Body with word-level drag selection support
         let available = ui.available_size();
+        self.input.before(ui.ctx());
+        self.autocorrect.before(ui.ctx());
         let output = egui::TextEdit::multiline(&mut note.body)
             .font(egui::FontId::proportional(14.0))
             .desired_width(available.x)
             .desired_rows((available.y / 20.0).max(4.0) as usize)
             .show(ui);
+        let corrected = self.autocorrect.after(ui, &output, &mut note.body);
 
-        if output.response.changed() {
+        if output.response.changed() || corrected {
             note.touch();
             self.store.save();
         }
 
         self.word_drag.update(ui, &output, &self.store.notes[idx].body);
+        self.input.after(ui, &output);
     }
 }
 
@@ -342,6 +412,7 @@ impl eframe::App for SlowNoteApp {
             ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
         }
         self.handle_keys(ctx);
+        self.handle_commands(ctx);
 
         let win_action = egui::TopBottomPanel::top("menu").show(ctx, |ui| {
             menu_bar(ui, |ui| {
@@ -350,6 +421,14 @@ impl eframe::App for SlowNoteApp {
                     if ui.button("New Note   ⌘N").clicked() { self.new_note(); ui.close_menu(); }
                     if ui.button("Delete     ⌘⌫").clicked() { self.delete_note(); ui.close_menu(); }
                 });
+                ui.menu_button("input", |ui| {
+                    for mode in InputMode::ALL {
+                        if ui.radio(self.input.mode == mode, mode.name()).clicked() {
+                            self.input.set_mode(mode);
+                            ui.close_menu();
+                        }
+                    }
+                });
                 ui.menu_button("help", |ui| {
                     if ui.button("about").clicked() { self.show_about = true; ui.close_menu(); }
                 });
@@ -374,7 +453,11 @@ impl eframe::App for SlowNoteApp {
                 .and_then(|i| self.store.notes.get(i))
                 .map(|n| n.body.len())
                 .unwrap_or(0);
-            status_bar(ui, &format!("{} notes  |  {} characters", count, chars));
+            let input = match self.input.mode {
+                InputMode::Direct => String::new(),
+                mode => format!("  |  {}", mode.name()),
+            };
+            status_bar(ui, &format!("{} notes  |  {} characters{}", count, chars, input));
         });
 
         egui::SidePanel::left("sidebar").default_width(200.0).show(ctx, |ui| {
@@ -387,41 +470,8 @@ impl eframe::App for SlowNoteApp {
             self.render_editor(ui);
         });
 
-        if self.show_about {
-            let screen = ctx.screen_rect();
-            let max_h = (screen.height() - 60.0).max(120.0);
-            let resp = egui::Window::new("about slowNotes")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(300.0)
-                .max_height(max_h)
-                .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                        ui.vertical_centered(|ui| {
-                            ui.heading("slowNotes");
-                            ui.label("version 0.2.2");
-                            ui.add_space(8.0);
-                            ui.label("simple note-taking app");
-                        });
-                        ui.add_space(8.0);
-                        ui.separator();
-                        ui.add_space(4.0);
-                        ui.label("features:");
-                        ui.label("  create, search, pin notes");
-                        ui.label("  deleted notes go to trash");
-                        ui.add_space(4.0);
-                        ui.label("storage: JSON in config directory");
-                        ui.add_space(4.0);
-                        ui.label("frameworks:");
-                        ui.label("  egui/eframe (MIT), chrono (MIT)");
-                        ui.label("  serde (MIT/Apache-2.0)");
-                        ui.add_space(8.0);
-                        ui.vertical_centered(|ui| {
-                            if ui.button("ok").clicked() { self.show_about = false; }
-                        });
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
 
         self.repaint.end_frame(ctx);
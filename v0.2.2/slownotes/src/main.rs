@@ -1,11 +1,11 @@
 mod app;
 use app::SlowNoteApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([600.0, 380.0])
+        viewport: window_layout::viewport("slownotes", Placement::RememberLast, [600.0, 380.0])
             .with_title("slowNotes"),
         ..Default::default()
     };
@@ -3,15 +3,58 @@
 //! Black and white only. Live shape preview outlines.
 //! Pattern fills instead of colors.
 
-use crate::canvas::Canvas;
-use crate::tools::{BrushSize, Pattern, Tool, BLACK, WHITE};
+use crate::canvas::{self, Canvas};
+use crate::dynamics::{BrushDynamics, MouseFallback, Stylus};
+use crate::frames::{self, Timeline};
+use crate::icon::{self, IconMode};
+use crate::shapes::{self, Shape, Stamp};
+use crate::tools::{self, BrushSize, Mirror, Pattern, Segment, Tool, BLACK, WHITE};
 use egui::{Context, Key, Pos2, Rect, Sense, Stroke, TextureHandle, Vec2};
 use image::Rgba;
+use slowcore::confirm::CloseGuard;
+use slowcore::drag::DropTarget;
+use slowcore::lifecycle::{self, SaveState};
+use slowcore::log;
+use slowcore::plugins::{self, Plugin, PluginKind};
 use slowcore::repaint::RepaintController;
-use slowcore::storage::{FileBrowser, pictures_dir};
+use slowcore::shortcuts::{KeyCombo, Shortcuts};
+use serde::{Deserialize, Serialize};
+use slowcore::storage::{config_dir, pictures_dir, RecentFiles};
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, FileDialog, FileDialogMode, FileDialogResult, WindowAction};
 use std::path::PathBuf;
+use std::time::Instant;
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowPaint",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "bitmap editor for slowOS",
+    formats: &["PNG, BMP, JPEG (open/save)", "GIF (open/save, animated)"],
+    sections: &[
+        ("tools", &["pencil, brush, eraser, line,", "rectangle, ellipse, fill, patterns", "more tools can come from plugins"]),
+        ("icon mode", &["32, 48 and 64 pixel icons with a", "template, onion skin and export", "into the icons/ folder"]),
+        ("frames", &["a timeline with onion skin and", "playback, exported as an animated", "GIF or a sprite sheet"]),
+    ],
+    credits: &["egui/eframe (MIT), image-rs (MIT)", "tiny-skia (BSD-3)"],
+    ..AboutInfo::EMPTY
+};
+
+/// Zoom limits, and how far each step of ⌘+ and ⌘- goes
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 16.0;
+const ZOOM_STEP: f32 = 1.25;
+
+/// Zoom from which the pixel grid is drawn
+const GRID_ZOOM: f32 = 8.0;
+
+/// What the open or save dialog is for
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FilePurpose {
+    Image,
+    Icon,
+    Gif,
+    SpriteSheet,
+}
 
 pub struct SlowPaintApp {
     repaint: RepaintController,
@@ -19,11 +62,26 @@ pub struct SlowPaintApp {
     texture: Option<TextureHandle>,
     texture_dirty: bool,
     current_tool: Tool,
+    /// Paint tools from plugins, after the built-in ones in the toolbar
+    paint_tools: Vec<Plugin>,
+    /// The plugin tool in use, if one is, instead of `current_tool`
+    plugin_tool: Option<usize>,
     brush_size: BrushSize,
+    /// What a pen's pressure does to the brush
+    dynamics: BrushDynamics,
+    stylus: Stylus,
+    show_dynamics: bool,
+    /// Brush strokes painted again reflected, and wrapped round the edges
+    mirror: Mirror,
+    tile: bool,
     /// true = draw black, false = draw white (erase)
     draw_black: bool,
     /// Fill pattern for filled shapes and fill tool
     fill_pattern: Pattern,
+    /// What the shapes tool places, and whether filled or as an outline
+    shape: Shape,
+    shape_filled: bool,
+    stamps: Vec<Stamp>,
     // Drawing state
     is_drawing: bool,
     drag_start: Option<(i32, i32)>,
@@ -35,11 +93,10 @@ pub struct SlowPaintApp {
     pan_offset: Vec2,
     /// The canvas rect from last frame (for coordinate conversion)
     last_canvas_rect: Option<Rect>,
+    /// The area the canvas is shown in, from last frame
+    last_view_rect: Option<Rect>,
     // Dialogs
-    show_file_browser: bool,
-    file_browser: FileBrowser,
-    file_browser_mode: FileBrowserMode,
-    save_filename: String,
+    file_dialog: Option<FileDialog>,
     show_new_dialog: bool,
     new_width: String,
     new_height: String,
@@ -47,25 +104,92 @@ pub struct SlowPaintApp {
     resize_width: String,
     resize_height: String,
     show_about: bool,
-    show_close_confirm: bool,
-    close_confirmed: bool,
+    /// Set while the canvas is an icon
+    icon: Option<IconMode>,
+    show_icon_dialog: bool,
+    icon_size: u32,
+    /// Category index and name of the icon being exported
+    icon_export: Option<(usize, String)>,
+    file_purpose: FilePurpose,
+    message: Option<String>,
+    /// Animation frames; the canvas holds the one being drawn
+    timeline: Timeline,
+    show_timeline: bool,
+    /// Textures of the frames not on the canvas, and of the onion skin,
+    /// remade when `frames_dirty`
+    frame_textures: Vec<Option<TextureHandle>>,
+    onion_texture: Option<TextureHandle>,
+    frames_dirty: bool,
+    close_guard: CloseGuard,
+    recent_files: RecentFiles,
     show_shortcuts: bool,
+    shortcuts: Shortcuts<PaintAction>,
 }
 
-#[derive(Clone, Copy, PartialEq)]
-enum FileBrowserMode { Open, Save }
+/// Actions reachable from the keyboard
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PaintAction {
+    New,
+    Open,
+    Save,
+    SaveAs,
+    Undo,
+    Redo,
+    SelectTool(Tool),
+    SwapColors,
+    PreviousFrame,
+    NextFrame,
+    ZoomIn,
+    ZoomOut,
+    ActualSize,
+    FitToWindow,
+}
+
+fn paint_shortcuts() -> Shortcuts<PaintAction> {
+    Shortcuts::new("slowpaint")
+        .add(PaintAction::New, "new canvas", KeyCombo::cmd(Key::N))
+        .add(PaintAction::Open, "open image", KeyCombo::cmd(Key::O))
+        .add(PaintAction::Save, "save", KeyCombo::cmd(Key::S))
+        .add(PaintAction::SaveAs, "save as", KeyCombo::cmd(Key::S).shift())
+        .add(PaintAction::Undo, "undo", KeyCombo::cmd(Key::Z))
+        .add(PaintAction::Redo, "redo", KeyCombo::cmd(Key::Z).shift())
+        .add(PaintAction::SelectTool(Tool::Brush), "brush tool", KeyCombo::key(Key::B))
+        .add(PaintAction::SelectTool(Tool::Eraser), "eraser tool", KeyCombo::key(Key::E))
+        .add(PaintAction::SelectTool(Tool::Line), "line tool", KeyCombo::key(Key::L))
+        .add(PaintAction::SelectTool(Tool::Rectangle), "rectangle tool", KeyCombo::key(Key::R))
+        .add(PaintAction::SelectTool(Tool::Fill), "fill tool", KeyCombo::key(Key::G))
+        .add(PaintAction::SelectTool(Tool::Shapes), "shapes tool", KeyCombo::key(Key::S))
+        .add(PaintAction::SwapColors, "swap black/white", KeyCombo::key(Key::X))
+        .add(PaintAction::PreviousFrame, "previous frame", KeyCombo::key(Key::Comma))
+        .add(PaintAction::NextFrame, "next frame", KeyCombo::key(Key::Period))
+        .add(PaintAction::ZoomIn, "zoom in", KeyCombo::cmd(Key::Equals))
+        .add(PaintAction::ZoomIn, "zoom in", KeyCombo::cmd(Key::Plus).shift())
+        .add(PaintAction::ZoomOut, "zoom out", KeyCombo::cmd(Key::Minus))
+        .add(PaintAction::ActualSize, "actual size", KeyCombo::cmd(Key::Num0))
+        .add(PaintAction::FitToWindow, "fit to window", KeyCombo::cmd(Key::Num9))
+}
 
 impl SlowPaintApp {
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
         Self {
-            repaint: RepaintController::new(),
+            repaint: RepaintController::with_fast_interval(),
             canvas: Canvas::new(640, 480),
             texture: None,
             texture_dirty: true,
             current_tool: Tool::Brush,
+            paint_tools: plugins::load(PluginKind::PaintTool),
+            plugin_tool: None,
             brush_size: BrushSize::Size2,
+            dynamics: BrushDynamics::load(),
+            stylus: Stylus::default(),
+            show_dynamics: false,
+            mirror: Mirror::Off,
+            tile: false,
             draw_black: true,
             fill_pattern: Pattern::Solid,
+            shape: Shape::Star,
+            shape_filled: false,
+            stamps: shapes::stamps(),
             is_drawing: false,
             drag_start: None,
             last_point: None,
@@ -73,11 +197,8 @@ impl SlowPaintApp {
             zoom: 1.0,
             pan_offset: Vec2::ZERO,
             last_canvas_rect: None,
-            show_file_browser: false,
-            file_browser: FileBrowser::new(pictures_dir())
-                .with_filter(vec!["png".into(), "bmp".into(), "jpg".into(), "jpeg".into()]),
-            file_browser_mode: FileBrowserMode::Open,
-            save_filename: String::new(),
+            last_view_rect: None,
+            file_dialog: None,
             show_new_dialog: false,
             new_width: "640".to_string(),
             new_height: "480".to_string(),
@@ -85,9 +206,25 @@ impl SlowPaintApp {
             resize_width: "640".to_string(),
             resize_height: "480".to_string(),
             show_about: false,
-            show_close_confirm: false,
-            close_confirmed: false,
+            icon: None,
+            show_icon_dialog: false,
+            icon_size: 48,
+            icon_export: None,
+            file_purpose: FilePurpose::Image,
+            message: None,
+            timeline: Timeline::new(),
+            show_timeline: false,
+            frame_textures: Vec::new(),
+            onion_texture: None,
+            frames_dirty: true,
+            close_guard: CloseGuard::new(),
+            recent_files: RecentFiles::for_app("slowpaint"),
             show_shortcuts: false,
+            shortcuts: {
+                let shortcuts = paint_shortcuts();
+                shortcuts.publish();
+                shortcuts
+            },
         }
     }
 
@@ -104,48 +241,205 @@ impl SlowPaintApp {
         self.texture_dirty = true;
         self.zoom = 1.0;
         self.pan_offset = Vec2::ZERO;
+        self.icon = None;
+        self.timeline = Timeline::new();
+        self.frames_dirty = true;
+    }
+
+    fn new_icon(&mut self, size: u32) {
+        self.new_canvas(size, size);
+        self.zoom = icon::zoom_for(size);
+        self.icon = Some(IconMode::new(size));
+    }
+
+    /// Open an icon file into icon mode, at its own size if it's one of
+    /// the icon sizes
+    fn open_icon(&mut self, path: PathBuf) {
+        let size = image::image_dimensions(&path).ok().map(|(w, _)| w).filter(|w| icon::SIZES.contains(w)).unwrap_or(48);
+        match icon::load(&path, size) {
+            Ok(image) => {
+                self.new_icon(size);
+                self.canvas.image = image;
+                self.canvas.path = Some(path);
+            }
+            Err(e) => self.message = Some(format!("could not open icon: {}", e)),
+        }
+    }
+
+    fn set_onion_skin(&mut self, name: &str, path: &std::path::Path) {
+        let Some(mode) = &mut self.icon else { return };
+        match icon::load(path, mode.size) {
+            Ok(image) => mode.onion_skin = Some((name.to_string(), image)),
+            Err(e) => self.message = Some(format!("could not open icon: {}", e)),
+        }
+    }
+
+    fn export_icon(&mut self, category: usize, name: &str) {
+        let Some(mode) = &self.icon else { return };
+        let (folder, _) = icon::CATEGORIES[category.min(icon::CATEGORIES.len() - 1)];
+        match icon::export(&self.canvas.image, &icon::icons_dir(), folder, name, mode.white_is_clear) {
+            Ok(path) => self.message = Some(format!("exported {}", path.display())),
+            Err(e) => self.message = Some(format!("could not export icon: {}", e)),
+        }
     }
 
     pub fn open_file(&mut self, path: PathBuf) {
-        match Canvas::open(path) {
+        if self.load(path.clone()) {
+            self.recent_files.record(&path);
+        }
+    }
+
+    /// Put an image on the canvas; a GIF with more than one frame opens as
+    /// an animation
+    fn load(&mut self, path: PathBuf) -> bool {
+        match Canvas::open(path.clone()) {
             Ok(canvas) => {
                 self.canvas = canvas;
                 self.texture_dirty = true;
                 self.zoom = 1.0;
                 self.pan_offset = Vec2::ZERO;
+                self.icon = None;
+                self.timeline = Timeline::new();
+                self.frames_dirty = true;
+                if is_gif(&path) {
+                    match frames::read_gif(&path) {
+                        Ok((images, fps)) if images.len() > 1 => {
+                            self.timeline = Timeline::from_frames(images, fps, &mut self.canvas);
+                        }
+                        Ok(_) => {}
+                        Err(e) => log::error!("failed to read frames: {}", e),
+                    }
+                }
+                true
+            }
+            Err(e) => {
+                log::error!("failed to open: {}", e);
+                false
             }
-            Err(e) => eprintln!("failed to open: {}", e),
         }
     }
 
     fn save(&mut self) {
-        if self.canvas.path.is_some() {
-            if let Err(e) = self.canvas.save() {
-                eprintln!("Failed to save: {}", e);
-            }
+        if let Some(path) = self.canvas.path.clone() {
+            self.save_as(path);
         } else {
             self.show_save_dialog();
         }
     }
 
+    /// An animation saved as a GIF keeps all its frames; any other file
+    /// gets the frame on the canvas
     fn save_as(&mut self, path: PathBuf) {
-        if let Err(e) = self.canvas.save_as(path) {
-            eprintln!("Failed to save: {}", e);
+        let result = if self.timeline.len() > 1 && is_gif(&path) {
+            frames::write_gif(&self.timeline.all(&self.canvas), self.timeline.fps, &path).map(|()| {
+                self.canvas.path = Some(path.clone());
+                self.canvas.modified = false;
+            })
+        } else {
+            self.canvas.save_as(path.clone()).map_err(|e| e.to_string())
+        };
+        match result {
+            Ok(()) => self.recent_files.record(&path),
+            Err(e) => log::error!("Failed to save: {}", e),
+        }
+    }
+
+    fn export_gif(&mut self, path: PathBuf) {
+        match frames::write_gif(&self.timeline.all(&self.canvas), self.timeline.fps, &path) {
+            Ok(()) => self.message = Some(format!("exported {}", path.display())),
+            Err(e) => self.message = Some(format!("could not export animation: {}", e)),
+        }
+    }
+
+    fn export_sprite_sheet(&mut self, path: PathBuf) {
+        let sheet = frames::sprite_sheet(&self.timeline.all(&self.canvas));
+        match sheet.save_with_format(&path, image::ImageFormat::Png) {
+            Ok(()) => self.message = Some(format!("exported {}", path.display())),
+            Err(e) => self.message = Some(format!("could not export sprite sheet: {}", e)),
         }
     }
 
     fn show_open_dialog(&mut self) {
-        self.file_browser = FileBrowser::new(pictures_dir())
-            .with_filter(vec!["png".into(), "bmp".into(), "jpg".into(), "jpeg".into()]);
-        self.file_browser_mode = FileBrowserMode::Open;
-        self.show_file_browser = true;
+        self.file_purpose = FilePurpose::Image;
+        self.file_dialog = Some(
+            FileDialog::open(pictures_dir())
+                .title("open image")
+                .filter(&["png", "bmp", "jpg", "jpeg", "gif"]),
+        );
+    }
+
+    fn show_open_icon_dialog(&mut self) {
+        self.file_purpose = FilePurpose::Icon;
+        self.file_dialog = Some(FileDialog::open(icon::icons_dir()).title("edit icon").filter(&["png"]));
     }
 
     fn show_save_dialog(&mut self) {
-        self.file_browser = FileBrowser::new(pictures_dir());
-        self.file_browser_mode = FileBrowserMode::Save;
-        self.save_filename = "untitled.png".to_string();
-        self.show_file_browser = true;
+        self.file_purpose = FilePurpose::Image;
+        self.file_dialog = Some(
+            FileDialog::save(pictures_dir(), "untitled.png")
+                .title("save image")
+                .default_extension("png"),
+        );
+    }
+
+    fn show_export_dialog(&mut self, purpose: FilePurpose) {
+        let stem = self.canvas.path.as_ref()
+            .and_then(|p| p.file_stem())
+            .map_or("untitled".to_string(), |s| s.to_string_lossy().into_owned());
+        let (title, name, extension) = match purpose {
+            FilePurpose::SpriteSheet => ("export sprite sheet", format!("{}-sheet.png", stem), "png"),
+            _ => ("export animated GIF", format!("{}.gif", stem), "gif"),
+        };
+        self.file_purpose = purpose;
+        self.file_dialog = Some(FileDialog::save(pictures_dir(), &name).title(title).default_extension(extension));
+    }
+
+    /// Change frames with `change`, stopping playback first
+    fn edit_frames(&mut self, change: impl FnOnce(&mut Timeline, &mut Canvas)) {
+        self.timeline.playing = None;
+        let frames = self.timeline.len();
+        change(&mut self.timeline, &mut self.canvas);
+        // Adding, deleting and reordering change the document
+        self.canvas.modified |= self.timeline.len() != frames;
+        self.texture_dirty = true;
+        self.frames_dirty = true;
+    }
+
+    fn go_to_frame(&mut self, index: usize) {
+        self.edit_frames(|timeline, canvas| timeline.go_to(canvas, index));
+    }
+
+    fn shift_frame(&mut self, by: isize) {
+        self.edit_frames(|timeline, _| timeline.shift(by));
+        self.canvas.modified = true;
+    }
+
+    fn toggle_playback(&mut self) {
+        self.timeline.playing = match self.timeline.playing {
+            None if self.timeline.len() > 1 => Some(Instant::now()),
+            _ => None,
+        };
+    }
+
+    /// Remake the frame and onion skin textures after frames change
+    fn update_frame_textures(&mut self, ctx: &Context) {
+        if !self.frames_dirty {
+            return;
+        }
+        self.frames_dirty = false;
+        if self.timeline.len() < 2 {
+            self.frame_textures.clear();
+            self.onion_texture = None;
+            return;
+        }
+        self.frame_textures = (0..self.timeline.len())
+            .map(|i| {
+                let frame = self.timeline.frame(i)?;
+                Some(ctx.load_texture(format!("frame_{}", i), canvas::texture_data(frame), egui::TextureOptions::NEAREST))
+            })
+            .collect();
+        self.onion_texture = self.timeline.previous()
+            .map(|prev| ctx.load_texture("onion_skin", frames::onion_skin(prev), egui::TextureOptions::NEAREST));
     }
 
     fn update_texture(&mut self, ctx: &Context) {
@@ -156,20 +450,62 @@ impl SlowPaintApp {
         }
     }
 
+    // `canvas_rect` is already panned
     fn screen_to_canvas(&self, screen_pos: Pos2, canvas_rect: Rect) -> (i32, i32) {
-        let rel = screen_pos - canvas_rect.min - self.pan_offset;
-        let x = (rel.x / self.zoom) as i32;
-        let y = (rel.y / self.zoom) as i32;
+        let rel = screen_pos - canvas_rect.min;
+        let x = (rel.x / self.zoom).floor() as i32;
+        let y = (rel.y / self.zoom).floor() as i32;
         (x, y)
     }
 
     fn canvas_to_screen(&self, cx: i32, cy: i32, canvas_rect: Rect) -> Pos2 {
         Pos2::new(
-            canvas_rect.min.x + self.pan_offset.x + cx as f32 * self.zoom,
-            canvas_rect.min.y + self.pan_offset.y + cy as f32 * self.zoom,
+            canvas_rect.min.x + cx as f32 * self.zoom,
+            canvas_rect.min.y + cy as f32 * self.zoom,
         )
     }
 
+    /// Zoom to `zoom`, keeping the canvas point under the screen point
+    /// `anchor` where it is
+    fn zoom_about(&mut self, zoom: f32, anchor: Pos2) {
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        if let Some(view) = self.last_view_rect {
+            let point = (anchor - view.min - self.pan_offset) / self.zoom;
+            self.pan_offset = anchor - view.min - point * zoom;
+        }
+        self.zoom = zoom;
+    }
+
+    /// Zoom one step in (`steps` > 0) or out, about the middle of the view
+    fn zoom_step(&mut self, steps: i32) {
+        let anchor = self.last_view_rect.map_or(Pos2::ZERO, |r| r.center());
+        self.zoom_about(self.zoom * ZOOM_STEP.powi(steps), anchor);
+    }
+
+    fn actual_size(&mut self) {
+        self.zoom = 1.0;
+        self.pan_offset = Vec2::ZERO;
+    }
+
+    /// The whole canvas, as big as fits, in the middle of the view
+    fn fit_to_window(&mut self) {
+        let Some(view) = self.last_view_rect else { return };
+        let size = Vec2::new(self.canvas.width() as f32, self.canvas.height() as f32);
+        self.zoom = (view.width() / size.x).min(view.height() / size.y).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.pan_offset = (view.size() - size * self.zoom) / 2.0;
+    }
+
+    /// Size and density of the brush for the pen's pressure now, or the mouse's
+    fn brush(&self, ctx: &Context) -> (u32, f32) {
+        let pressure = self.stylus.pressure(ctx, self.dynamics.mouse);
+        self.dynamics.brush(pressure, self.brush_size.pixels())
+    }
+
+    /// Everywhere a brush segment is painted, with the mirror and tiling modes
+    fn brush_copies(&self, stroke: Segment, size: u32) -> Vec<Segment> {
+        tools::brush_copies(stroke, self.mirror, self.tile, self.canvas.width(), self.canvas.height(), size as i32 / 2)
+    }
+
     fn handle_drawing(&mut self, canvas_rect: Rect, response: &egui::Response) {
         // Track hover position for shape preview
         if let Some(pos) = response.hover_pos() {
@@ -179,10 +515,16 @@ impl SlowPaintApp {
             self.hover_canvas_pos = None;
         }
 
+        if let Some(index) = self.plugin_tool {
+            self.handle_plugin_drawing(index, canvas_rect, response);
+            return;
+        }
+
         if let Some(pos) = response.interact_pointer_pos() {
             let (x, y) = self.screen_to_canvas(pos, canvas_rect);
 
             if response.drag_started() {
+                self.message = None;
                 self.is_drawing = true;
                 self.drag_start = Some((x, y));
                 self.last_point = Some((x, y));
@@ -205,13 +547,17 @@ impl SlowPaintApp {
                         self.texture_dirty = true;
                     }
                     Tool::Brush => {
-                        let size = self.brush_size.pixels();
-                        self.canvas.draw_circle_filled_pattern(x, y, size as i32 / 2, self.draw_color(), &self.fill_pattern);
+                        let (size, density) = self.brush(&response.ctx);
+                        for ((cx, cy), _) in self.brush_copies(((x, y), (x, y)), size) {
+                            self.canvas.draw_dab(cx, cy, size as i32 / 2, self.draw_color(), &self.fill_pattern, density);
+                        }
                         self.texture_dirty = true;
                     }
                     Tool::Eraser => {
-                        let size = self.brush_size.pixels();
-                        self.canvas.draw_circle_filled(x, y, size as i32 / 2, self.erase_color());
+                        let (size, _) = self.brush(&response.ctx);
+                        for ((cx, cy), _) in self.brush_copies(((x, y), (x, y)), size) {
+                            self.canvas.draw_circle_filled(cx, cy, size as i32 / 2, self.erase_color());
+                        }
                         self.texture_dirty = true;
                     }
                     _ => {}
@@ -230,10 +576,13 @@ impl SlowPaintApp {
                             self.draw_color()
                         };
                         // Brush uses pattern, pencil and eraser use solid
-                        if self.current_tool == Tool::Brush {
-                            self.canvas.draw_line_pattern(lx, ly, x, y, color, self.brush_size.pixels(), &self.fill_pattern);
-                        } else {
-                            self.canvas.draw_line(lx, ly, x, y, color, self.brush_size.pixels());
+                        let (size, density) = self.brush(&response.ctx);
+                        for (from, to) in self.brush_copies(((lx, ly), (x, y)), size) {
+                            if self.current_tool == Tool::Brush {
+                                self.canvas.draw_stroke(from, to, color, size, &self.fill_pattern, density);
+                            } else {
+                                self.canvas.draw_line(from.0, from.1, to.0, to.1, color, size);
+                            }
                         }
                         self.texture_dirty = true;
                     }
@@ -278,6 +627,7 @@ impl SlowPaintApp {
                                     let ry = (fy - sy).abs() / 2;
                                     self.canvas.draw_ellipse_filled_pattern(ecx, ecy, rx, ry, color, &self.fill_pattern);
                                 }
+                                Tool::Shapes => self.place_shape((sx, sy), (fx, fy)),
                                 _ => {}
                             }
                             self.texture_dirty = true;
@@ -292,6 +642,107 @@ impl SlowPaintApp {
         }
     }
 
+    /// Drawing with a plugin tool: a dab at every point along the stroke
+    fn handle_plugin_drawing(&mut self, index: usize, canvas_rect: Rect, response: &egui::Response) {
+        let Some(pos) = response.interact_pointer_pos() else { return };
+        let (x, y) = self.screen_to_canvas(pos, canvas_rect);
+        let (size, _) = self.brush(&response.ctx);
+        let Some(plugin) = self.paint_tools.get(index) else { return };
+        if response.drag_started() {
+            self.canvas.save_undo_state();
+            for ((cx, cy), _) in self.brush_copies(((x, y), (x, y)), size) {
+                self.canvas.plugin_dab(plugin, cx, cy, size);
+            }
+            self.is_drawing = true;
+            self.last_point = Some((x, y));
+        } else if response.dragged() && self.is_drawing {
+            if let Some((lx, ly)) = self.last_point {
+                for ((lx, ly), (x, y)) in self.brush_copies(((lx, ly), (x, y)), size) {
+                    // Dabs half a brush apart, so fast strokes don't break up
+                    let steps = ((x - lx).abs().max((y - ly).abs()) / (size as i32 / 2).max(1)).max(1);
+                    for i in 1..=steps {
+                        self.canvas.plugin_dab(plugin, lx + (x - lx) * i / steps, ly + (y - ly) * i / steps, size);
+                    }
+                }
+            }
+            self.last_point = Some((x, y));
+        }
+        if response.drag_stopped() && self.is_drawing {
+            // The canvas stays black and white, whatever the plugin painted
+            self.canvas.threshold();
+            self.is_drawing = false;
+            self.last_point = None;
+        }
+        self.texture_dirty = true;
+    }
+
+    /// Lines between the pixels, once they're big enough to tell apart.
+    /// Grey, so they show over black pixels and white.
+    fn render_pixel_grid(&self, painter: &egui::Painter, canvas_rect: Rect, view: Rect) {
+        if self.zoom < GRID_ZOOM {
+            return;
+        }
+        let visible = canvas_rect.intersect(view);
+        if !visible.is_positive() {
+            return;
+        }
+        let stroke = Stroke::new(1.0, egui::Color32::from_gray(128));
+        let z = self.zoom;
+        // Only the lines in view
+        let (first_x, last_x) = (((visible.left() - canvas_rect.left()) / z).ceil() as i32, ((visible.right() - canvas_rect.left()) / z) as i32);
+        for x in first_x..=last_x {
+            let sx = canvas_rect.left() + x as f32 * z;
+            painter.line_segment([Pos2::new(sx, visible.top()), Pos2::new(sx, visible.bottom())], stroke);
+        }
+        let (first_y, last_y) = (((visible.top() - canvas_rect.top()) / z).ceil() as i32, ((visible.bottom() - canvas_rect.top()) / z) as i32);
+        for y in first_y..=last_y {
+            let sy = canvas_rect.top() + y as f32 * z;
+            painter.line_segment([Pos2::new(visible.left(), sy), Pos2::new(visible.right(), sy)], stroke);
+        }
+    }
+
+    /// Dashed lines along the axes the brush is mirrored across
+    fn render_mirror_axes(&self, painter: &egui::Painter, canvas_rect: Rect) {
+        let stroke = Stroke::new(1.0, SlowColors::BLACK);
+        let center = canvas_rect.center();
+        if self.mirror.flips_x() {
+            let axis = [Pos2::new(center.x, canvas_rect.top()), Pos2::new(center.x, canvas_rect.bottom())];
+            painter.extend(egui::Shape::dashed_line(&axis, stroke, 4.0, 4.0));
+        }
+        if self.mirror.flips_y() {
+            let axis = [Pos2::new(canvas_rect.left(), center.y), Pos2::new(canvas_rect.right(), center.y)];
+            painter.extend(egui::Shape::dashed_line(&axis, stroke, 4.0, 4.0));
+        }
+    }
+
+    /// Put the library shape dragged from `start` to `end` on the canvas
+    fn place_shape(&mut self, (sx, sy): (i32, i32), (fx, fy): (i32, i32)) {
+        let color = self.draw_color();
+        let size = self.brush_size.pixels();
+        let (rx, ry) = ((fx - sx).abs() / 2, (fy - sy).abs() / 2);
+        match self.shape {
+            Shape::Rectangle if self.shape_filled => self.canvas.draw_rect_filled_pattern(sx, sy, fx, fy, color, &self.fill_pattern),
+            Shape::Rectangle => self.canvas.draw_rect_outline(sx, sy, fx, fy, color, size, &self.fill_pattern),
+            Shape::Ellipse if self.shape_filled => self.canvas.draw_ellipse_filled_pattern((sx + fx) / 2, (sy + fy) / 2, rx, ry, color, &self.fill_pattern),
+            Shape::Ellipse => self.canvas.draw_ellipse_outline((sx + fx) / 2, (sy + fy) / 2, rx, ry, color, size, &self.fill_pattern),
+            Shape::Stamp(i) => {
+                let (width, height) = ((fx - sx).unsigned_abs() + 1, (fy - sy).unsigned_abs() + 1);
+                if let Some(stamp) = self.stamps.get(i).and_then(|s| s.render(width, height)) {
+                    self.canvas.draw_stamp(sx.min(fx), sy.min(fy), &stamp);
+                }
+            }
+            shape => {
+                // Through the middles of the first and last pixels
+                let Some(points) = shape.polygon((sx as f32 + 0.5, sy as f32 + 0.5), (fx as f32 + 0.5, fy as f32 + 0.5)) else { return };
+                if self.shape_filled {
+                    self.canvas.draw_polygon_filled_pattern(&points, color, &self.fill_pattern);
+                } else {
+                    self.canvas.draw_polygon_outline(&points, color, size, &self.fill_pattern);
+                }
+            }
+        }
+    }
+
     /// Draw a live preview outline of the shape being dragged
     fn render_shape_preview(&self, painter: &egui::Painter, canvas_rect: Rect) {
         if !self.is_drawing || !self.current_tool.is_shape() { return; }
@@ -307,7 +758,13 @@ impl SlowPaintApp {
 
         let preview_stroke = Stroke::new((self.brush_size.pixels() as f32 * self.zoom).max(1.0), SlowColors::BLACK);
 
-        match self.current_tool {
+        // Library rectangles, ellipses and stamps preview like the tools
+        let tool = match (self.current_tool, self.shape) {
+            (Tool::Shapes, Shape::Rectangle | Shape::Stamp(_)) => Tool::Rectangle,
+            (Tool::Shapes, Shape::Ellipse) => Tool::Ellipse,
+            (tool, _) => tool,
+        };
+        match tool {
             Tool::Line => {
                 let p1 = self.canvas_to_screen(sx, sy, canvas_rect);
                 let p2 = self.canvas_to_screen(ex, ey, canvas_rect);
@@ -341,117 +798,167 @@ impl SlowPaintApp {
                     painter.line_segment([pair[0], pair[1]], preview_stroke);
                 }
             }
+            Tool::Shapes => {
+                let (start, end) = ((sx as f32 + 0.5, sy as f32 + 0.5), (ex as f32 + 0.5, ey as f32 + 0.5));
+                if let Some(points) = self.shape.polygon(start, end) {
+                    let points = points.iter().map(|(x, y)| canvas_rect.min + Vec2::new(*x, *y) * self.zoom).collect();
+                    painter.add(egui::Shape::closed_line(points, preview_stroke));
+                }
+            }
             _ => {}
         }
     }
 
-    fn handle_keyboard(&mut self, ctx: &Context) {
-        slowcore::theme::consume_special_keys(ctx);
-
-        let is_image = |p: &std::path::Path| {
-            let ext = p.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default();
-            matches!(ext.as_str(), "png" | "jpg" | "jpeg" | "gif" | "bmp")
-        };
-
-        // Handle dropped image files (from OS or Files app)
-        let mut dropped: Vec<std::path::PathBuf> = ctx.input(|i| {
-            i.raw.dropped_files.iter()
-                .filter_map(|f| f.path.clone())
-                .filter(|p| is_image(p))
-                .collect()
-        });
-
-        // Check for files dragged from slowOS Files app
-        let mouse_released = ctx.input(|i| i.pointer.primary_released());
-        let mouse_in_window = ctx.input(|i| i.pointer.has_pointer());
-        if dropped.is_empty() && mouse_released && mouse_in_window {
-            if let Some(paths) = slowcore::drag::get_drag_paths() {
-                let image_paths: Vec<std::path::PathBuf> = paths.into_iter()
-                    .filter(|p| is_image(p))
-                    .collect();
-                if !image_paths.is_empty() {
-                    dropped = image_paths;
-                    slowcore::drag::end_drag();
-                }
+    /// In icon mode, the onion skin and the template over the canvas
+    fn render_icon_overlay(&self, painter: &egui::Painter, canvas_rect: Rect) {
+        let Some(mode) = &self.icon else { return };
+        let z = self.zoom;
+        let at = |x: f32, y: f32| canvas_rect.min + Vec2::new(x, y) * z;
+        if let Some((_, skin)) = &mode.onion_skin {
+            // A dot in each of the other icon's black pixels, white where
+            // this one has ink so it shows on both
+            let dot = Vec2::splat((z / 4.0).max(1.0));
+            for (x, y, _) in skin.enumerate_pixels().filter(|(_, _, p)| p.0[0] < 128) {
+                let ink = self.canvas.image.get_pixel_checked(x, y).is_some_and(|p| p.0[0] < 128);
+                let center = at(x as f32 + 0.5, y as f32 + 0.5);
+                painter.rect_filled(Rect::from_center_size(center, dot), 0.0, if ink { SlowColors::WHITE } else { SlowColors::BLACK });
             }
         }
-
-        if let Some(path) = dropped.into_iter().next() {
-            self.open_file(path);
-        }
-
-        // Check keyboard shortcuts and consume them to prevent egui from intercepting
-        let (key_n, key_o, key_s, key_shift_s, key_z, key_shift_z) = ctx.input_mut(|i| {
-            let cmd = i.modifiers.command;
-            let shift = i.modifiers.shift;
-
-            let result = (
-                cmd && i.key_pressed(Key::N),
-                cmd && i.key_pressed(Key::O),
-                cmd && !shift && i.key_pressed(Key::S),
-                cmd && shift && i.key_pressed(Key::S),
-                cmd && !shift && i.key_pressed(Key::Z),
-                cmd && shift && i.key_pressed(Key::Z),
+        if mode.show_template {
+            // Keylines: the edge margin, the largest circle, and the centre
+            let size = mode.size as f32;
+            let margin = (mode.size / 16) as f32;
+            let stroke = Stroke::new(1.0, SlowColors::BLACK);
+            let mut lines = vec![
+                vec![at(margin, margin), at(size - margin, margin), at(size - margin, size - margin), at(margin, size - margin), at(margin, margin)],
+                vec![at(size / 2.0, 0.0), at(size / 2.0, size)],
+                vec![at(0.0, size / 2.0), at(size, size / 2.0)],
+            ];
+            let radius = size / 2.0 - margin;
+            lines.push(
+                (0..=64)
+                    .map(|i| {
+                        let angle = i as f32 / 64.0 * std::f32::consts::TAU;
+                        at(size / 2.0 + radius * angle.cos(), size / 2.0 + radius * angle.sin())
+                    })
+                    .collect(),
             );
-
-            // Remove events from queue to prevent egui from also handling them
-            if cmd {
-                i.events.retain(|e| {
-                    !matches!(e, egui::Event::Key { key, modifiers, .. }
-                        if modifiers.command && matches!(key, Key::Z | Key::N | Key::O | Key::S))
-                });
+            for points in lines {
+                painter.extend(egui::Shape::dashed_line(&points, stroke, 3.0, 3.0));
             }
+        }
+    }
 
-            result
+    /// The icon at the size it will be seen, beside the onion skin icon
+    fn render_icon_preview(&self, ui: &mut egui::Ui) {
+        let Some(mode) = &self.icon else { return };
+        // One canvas pixel to one screen pixel
+        let actual = 1.0 / ui.ctx().pixels_per_point();
+        ui.label("actual size:");
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            draw_icon(ui, &self.canvas.image, actual);
+            if let Some((_, skin)) = &mode.onion_skin {
+                draw_icon(ui, skin, actual);
+            }
         });
+        if let Some((name, _)) = &mode.onion_skin {
+            ui.weak(format!("beside {}", name));
+        }
+        ui.add_space(8.0);
+        ui.label("double:");
+        ui.add_space(4.0);
+        draw_icon(ui, &self.canvas.image, actual * 2.0);
+    }
 
-        if key_n { self.show_new_dialog = true; }
-        if key_o { self.show_open_dialog(); }
-        if key_shift_s { self.show_save_dialog(); }
-        else if key_s { self.save(); }
-        if key_shift_z { self.canvas.redo(); self.texture_dirty = true; }
-        else if key_z { self.canvas.undo(); self.texture_dirty = true; }
-
-        // Tool shortcuts and other keys (read-only, not consuming)
-        ctx.input(|i| {
-            let cmd = i.modifiers.command;
+    fn handle_keyboard(&mut self, ctx: &Context) {
+        slowcore::theme::consume_special_keys(ctx);
 
-            // Tool shortcuts (only when not holding Cmd)
-            if !cmd {
-                if i.key_pressed(Key::B) { self.current_tool = Tool::Brush; }
-                if i.key_pressed(Key::E) { self.current_tool = Tool::Eraser; }
-                if i.key_pressed(Key::L) { self.current_tool = Tool::Line; }
-                if i.key_pressed(Key::R) { self.current_tool = Tool::Rectangle; }
-                if i.key_pressed(Key::G) { self.current_tool = Tool::Fill; }
-                // X to swap black/white
-                if i.key_pressed(Key::X) { self.draw_black = !self.draw_black; }
-            }
+        // Handle dropped image files (from OS or Files app)
+        let dropped = DropTarget::new(&["png", "jpg", "jpeg", "gif", "bmp"]).show(ctx);
+        if let Some(path) = dropped.into_iter().next() {
+            self.open_file(path);
+        }
 
-            // Zoom
-            if i.key_pressed(Key::Equals) || i.key_pressed(Key::Plus) {
-                self.zoom = (self.zoom * 1.5).min(16.0);
-            }
-            if i.key_pressed(Key::Minus) {
-                self.zoom = (self.zoom / 1.5).max(0.25);
-            }
-            if i.key_pressed(Key::Num0) {
-                self.zoom = 1.0;
-                self.pan_offset = Vec2::ZERO;
+        // Declared shortcuts (consumed so egui doesn't also handle them)
+        for action in self.shortcuts.handle(ctx) {
+            match action {
+                PaintAction::New => self.show_new_dialog = true,
+                PaintAction::Open => self.show_open_dialog(),
+                PaintAction::Save => self.save(),
+                PaintAction::SaveAs => self.show_save_dialog(),
+                PaintAction::Undo => { self.canvas.undo(); self.texture_dirty = true; }
+                PaintAction::Redo => { self.canvas.redo(); self.texture_dirty = true; }
+                PaintAction::SelectTool(tool) => {
+                    self.current_tool = tool;
+                    self.plugin_tool = None;
+                }
+                PaintAction::SwapColors => self.draw_black = !self.draw_black,
+                PaintAction::PreviousFrame => self.go_to_frame(self.timeline.current.saturating_sub(1)),
+                PaintAction::NextFrame => self.go_to_frame(self.timeline.current + 1),
+                PaintAction::ZoomIn => self.zoom_step(1),
+                PaintAction::ZoomOut => self.zoom_step(-1),
+                PaintAction::ActualSize => self.actual_size(),
+                PaintAction::FitToWindow => self.fit_to_window(),
             }
-        });
+        }
     }
 
     fn render_toolbar(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             for tool in Tool::all() {
-                let selected = self.current_tool == *tool;
+                let selected = self.plugin_tool.is_none() && self.current_tool == *tool;
                 // Use SlowButton for dither highlight when selected (readable text)
                 let r = ui.add(slowcore::widgets::SlowButton::new(tool.icon()).selected(selected));
                 if r.on_hover_text(tool.name()).clicked() {
                     self.current_tool = *tool;
+                    self.plugin_tool = None;
+                }
+            }
+            if !self.paint_tools.is_empty() {
+                ui.separator();
+            }
+            for (i, plugin) in self.paint_tools.iter().enumerate() {
+                let r = ui.add(slowcore::widgets::SlowButton::new(plugin.name()).selected(self.plugin_tool == Some(i)));
+                if r.on_hover_text(format!("plugin: {}", plugin.path().display())).clicked() {
+                    self.plugin_tool = Some(i);
+                    // Plugin tools paint like the brush; this keeps the
+                    // shape preview out of the way
+                    self.current_tool = Tool::Brush;
                 }
             }
         });
+        if self.plugin_tool.is_none() && self.current_tool == Tool::Shapes {
+            self.render_shape_library(ui);
+        }
+    }
+
+    /// The shapes tool's choices, under the toolbar
+    fn render_shape_library(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            for shape in Shape::all() {
+                if ui.add(slowcore::widgets::SlowButton::new(shape.name()).selected(self.shape == *shape)).clicked() {
+                    self.shape = *shape;
+                }
+            }
+            ui.separator();
+            let stamp = match self.shape {
+                Shape::Stamp(i) => self.stamps.get(i).map_or("stamp", Stamp::name),
+                _ => "stamp",
+            };
+            egui::ComboBox::from_id_source("stamp").selected_text(stamp).show_ui(ui, |ui| {
+                for (i, stamp) in self.stamps.iter().enumerate() {
+                    if ui.selectable_label(self.shape == Shape::Stamp(i), stamp.name()).clicked() {
+                        self.shape = Shape::Stamp(i);
+                    }
+                }
+            });
+            ui.separator();
+            let filled = ui.add_enabled(!matches!(self.shape, Shape::Stamp(_)), slowcore::widgets::SlowButton::new("filled").selected(self.shape_filled));
+            if filled.on_hover_text("fill with the pattern, or draw the outline at the brush size").clicked() {
+                self.shape_filled = !self.shape_filled;
+            }
+        });
     }
 
     fn render_pattern_panel(&mut self, ui: &mut egui::Ui) {
@@ -479,6 +986,25 @@ impl SlowPaintApp {
                     }
                 }
             });
+            let r = ui.add(slowcore::widgets::SlowButton::new("pressure").selected(self.show_dynamics));
+            if r.on_hover_text("brush dynamics").clicked() {
+                self.show_dynamics = !self.show_dynamics;
+            }
+
+            ui.add_space(8.0);
+            ui.label("mirror:");
+            ui.horizontal_wrapped(|ui| {
+                for mirror in Mirror::all() {
+                    let r = ui.add(slowcore::widgets::SlowButton::new(mirror.icon()).selected(self.mirror == *mirror));
+                    if r.on_hover_text(mirror.name()).clicked() {
+                        self.mirror = *mirror;
+                    }
+                }
+            });
+            let r = ui.add(slowcore::widgets::SlowButton::new("tile").selected(self.tile));
+            if r.on_hover_text("strokes wrap round the edges").clicked() {
+                self.tile = !self.tile;
+            }
 
             ui.add_space(8.0);
             ui.label("pattern:");
@@ -523,16 +1049,38 @@ impl SlowPaintApp {
 
     fn render_canvas(&mut self, ui: &mut egui::Ui, ctx: &Context) {
         self.update_texture(ctx);
+        self.update_frame_textures(ctx);
+        // While playing, the frame playback has reached
+        let playing = self.timeline.playing_frame();
+        let shown = match playing {
+            Some(i) if i != self.timeline.current => self.frame_textures.get(i).cloned().flatten(),
+            _ => self.texture.clone(),
+        };
 
         let available = ui.available_rect_before_wrap();
         let response = ui.allocate_rect(available, Sense::click_and_drag());
+        self.last_view_rect = Some(available);
+
+        // Pinch (or ⌘ and the scroll wheel) zooms about the pointer; scrolling pans
+        if let Some(pointer) = response.hover_pos() {
+            let (zoom, scroll) = ctx.input(|i| (i.zoom_delta(), i.smooth_scroll_delta));
+            if zoom != 1.0 {
+                self.zoom_about(self.zoom * zoom, pointer);
+            }
+            self.pan_offset += scroll;
+        }
+        // Holding space, a drag pans instead of drawing
+        let panning = !self.is_drawing && ctx.input(|i| i.key_down(Key::Space));
+        if panning && response.hovered() {
+            ctx.set_cursor_icon(if response.dragged() { egui::CursorIcon::Grabbing } else { egui::CursorIcon::Grab });
+        }
 
         // Background — checkerboard to show canvas bounds
         let painter = ui.painter();
         painter.rect_filled(available, 0.0, SlowColors::WHITE);
 
         // Canvas
-        if let Some(ref texture) = self.texture {
+        if let Some(texture) = shown {
             let canvas_size = Vec2::new(
                 self.canvas.width() as f32 * self.zoom,
                 self.canvas.height() as f32 * self.zoom,
@@ -551,21 +1099,86 @@ impl SlowPaintApp {
                 egui::Color32::WHITE,
             );
 
+            // The frame before, faintly, to draw this one against
+            if let (None, true, Some(onion)) = (playing, self.timeline.onion_skin, &self.onion_texture) {
+                painter.image(onion.id(), canvas_rect, Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)), egui::Color32::WHITE);
+            }
+
+            // Tiling: the picture repeated round it, faintly, to show the seams
+            if self.tile && playing.is_none() {
+                for (dx, dy) in [(-1.0, -1.0), (0.0, -1.0), (1.0, -1.0), (-1.0, 0.0), (1.0, 0.0), (-1.0, 1.0), (0.0, 1.0), (1.0, 1.0)] {
+                    let tile = canvas_rect.translate(Vec2::new(dx * canvas_size.x, dy * canvas_size.y));
+                    if available.intersects(tile) {
+                        painter.image(texture.id(), tile, Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)), egui::Color32::from_gray(180));
+                    }
+                }
+            }
+
+            self.render_pixel_grid(painter, canvas_rect, available);
+
             // Canvas border
             painter.rect_stroke(canvas_rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+            self.render_mirror_axes(painter, canvas_rect);
 
-            self.handle_drawing(canvas_rect, &response);
+            // No drawing while the animation plays
+            if playing.is_none() && !panning {
+                self.handle_drawing(canvas_rect, &response);
+            }
 
             // Draw shape preview overlay AFTER drawing handling
             self.render_shape_preview(painter, canvas_rect);
+            self.render_icon_overlay(painter, canvas_rect);
         }
 
-        // Pan with middle mouse
-        if response.dragged_by(egui::PointerButton::Middle) {
+        // Pan with middle mouse, or space and the primary button
+        if response.dragged_by(egui::PointerButton::Middle) || (panning && response.dragged_by(egui::PointerButton::Primary)) {
             self.pan_offset += response.drag_delta();
         }
     }
 
+    /// Playback controls, frame buttons and a thumbnail of every frame
+    fn render_timeline(&mut self, ui: &mut egui::Ui) {
+        let playing = self.timeline.playing.is_some();
+        ui.horizontal(|ui| {
+            let play = if playing { "stop" } else { "play" };
+            if ui.add_enabled(self.timeline.len() > 1, egui::Button::new(play)).clicked() { self.toggle_playback(); }
+            ui.label("fps:");
+            ui.add(egui::DragValue::new(&mut self.timeline.fps).clamp_range(1..=frames::MAX_FPS));
+            ui.checkbox(&mut self.timeline.onion_skin, "onion skin");
+            ui.separator();
+            if ui.button("new").on_hover_text("new blank frame").clicked() { self.edit_frames(|t, c| t.add(c, false)); }
+            if ui.button("duplicate").on_hover_text("copy this frame").clicked() { self.edit_frames(|t, c| t.add(c, true)); }
+            if ui.add_enabled(self.timeline.len() > 1, egui::Button::new("delete")).clicked() { self.edit_frames(Timeline::delete); }
+            if ui.button("◀").on_hover_text("move earlier").clicked() { self.shift_frame(-1); }
+            if ui.button("▶").on_hover_text("move later").clicked() { self.shift_frame(1); }
+        });
+        ui.add_space(4.0);
+        let shown = self.timeline.playing_frame().unwrap_or(self.timeline.current);
+        let aspect = self.canvas.width() as f32 / self.canvas.height().max(1) as f32;
+        let size = Vec2::new((48.0 * aspect).clamp(16.0, 96.0), 48.0);
+        let mut go_to = None;
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for i in 0..self.timeline.len() {
+                    let texture = if i == self.timeline.current { self.texture.as_ref() } else { self.frame_textures.get(i).and_then(Option::as_ref) };
+                    ui.vertical(|ui| {
+                        let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+                        let painter = ui.painter();
+                        painter.rect_filled(rect, 0.0, SlowColors::WHITE);
+                        if let Some(texture) = texture {
+                            painter.image(texture.id(), rect, Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)), egui::Color32::WHITE);
+                        }
+                        let width = if i == shown { 3.0 } else { 1.0 };
+                        painter.rect_stroke(rect, 0.0, Stroke::new(width, SlowColors::BLACK));
+                        if response.clicked() { go_to = Some(i); }
+                        ui.label(format!("{}", i + 1));
+                    });
+                }
+            });
+        });
+        if let Some(i) = go_to { self.go_to_frame(i); }
+    }
+
     fn render_menu_bar(&mut self, ui: &mut egui::Ui) -> WindowAction {
         let mut action = WindowAction::None;
         menu_bar(ui, |ui| {
@@ -573,9 +1186,13 @@ impl SlowPaintApp {
             ui.menu_button("file", |ui| {
                 if ui.button("new...      ⌘n").clicked() { self.show_new_dialog = true; ui.close_menu(); }
                 if ui.button("open...     ⌘o").clicked() { self.show_open_dialog(); ui.close_menu(); }
+                if let Some(path) = self.recent_files.menu(ui) { self.open_file(path); }
                 ui.separator();
                 if ui.button("save        ⌘s").clicked() { self.save(); ui.close_menu(); }
                 if ui.button("save as...  ⇧⌘s").clicked() { self.show_save_dialog(); ui.close_menu(); }
+                ui.separator();
+                if ui.button("export animated GIF...").clicked() { self.show_export_dialog(FilePurpose::Gif); ui.close_menu(); }
+                if ui.button("export sprite sheet...").clicked() { self.show_export_dialog(FilePurpose::SpriteSheet); ui.close_menu(); }
             });
 
             ui.menu_button("edit", |ui| {
@@ -586,7 +1203,7 @@ impl SlowPaintApp {
             });
 
             ui.menu_button("image", |ui| {
-                if ui.button("resize canvas...").clicked() {
+                if ui.add_enabled(self.icon.is_none(), egui::Button::new("resize canvas...")).clicked() {
                     self.resize_width = self.canvas.width().to_string();
                     self.resize_height = self.canvas.height().to_string();
                     self.show_resize_dialog = true;
@@ -600,10 +1217,67 @@ impl SlowPaintApp {
                 if ui.button("flip vertical").clicked() { self.canvas.save_undo_state(); self.canvas.flip_vertical(); self.texture_dirty = true; ui.close_menu(); }
             });
 
+            ui.menu_button("icon", |ui| {
+                if ui.button("new icon...").clicked() { self.show_icon_dialog = true; ui.close_menu(); }
+                if ui.button("edit icon...").clicked() { self.show_open_icon_dialog(); ui.close_menu(); }
+                ui.separator();
+                let in_icon = self.icon.is_some();
+                if let Some(mode) = &mut self.icon {
+                    ui.checkbox(&mut mode.show_template, "show template");
+                    ui.checkbox(&mut mode.white_is_clear, "white is see-through");
+                }
+                let mut skin = None;
+                ui.add_enabled_ui(in_icon, |ui| {
+                    ui.menu_button("onion skin", |ui| {
+                        if ui.button("none").clicked() {
+                            if let Some(mode) = &mut self.icon { mode.onion_skin = None; }
+                            ui.close_menu();
+                        }
+                        for (folder, label) in icon::CATEGORIES {
+                            ui.menu_button(label, |ui| {
+                                for (name, path) in icon::icons_in(folder) {
+                                    if ui.button(&name).clicked() { skin = Some((name, path)); ui.close_menu(); }
+                                }
+                            });
+                        }
+                    });
+                });
+                if let Some((name, path)) = skin { self.set_onion_skin(&name, &path); }
+                ui.separator();
+                if ui.add_enabled(in_icon, egui::Button::new("export to icons/...")).clicked() {
+                    // Named after the file it came from, if any
+                    let name = self.canvas.path.as_ref()
+                        .and_then(|p| p.file_stem())
+                        .map(|s| s.to_string_lossy().trim_start_matches("icons_").to_string())
+                        .unwrap_or_default();
+                    self.icon_export = Some((0, name));
+                    ui.close_menu();
+                }
+            });
+
+            ui.menu_button("frames", |ui| {
+                let several = self.timeline.len() > 1;
+                if ui.button("new frame").clicked() { self.edit_frames(|t, c| t.add(c, false)); ui.close_menu(); }
+                if ui.button("duplicate frame").clicked() { self.edit_frames(|t, c| t.add(c, true)); ui.close_menu(); }
+                if ui.add_enabled(several, egui::Button::new("delete frame")).clicked() { self.edit_frames(Timeline::delete); ui.close_menu(); }
+                ui.separator();
+                if ui.button("previous frame  ,").clicked() { self.go_to_frame(self.timeline.current.saturating_sub(1)); ui.close_menu(); }
+                if ui.button("next frame      .").clicked() { self.go_to_frame(self.timeline.current + 1); ui.close_menu(); }
+                if ui.add_enabled(several, egui::Button::new("move earlier")).clicked() { self.shift_frame(-1); ui.close_menu(); }
+                if ui.add_enabled(several, egui::Button::new("move later")).clicked() { self.shift_frame(1); ui.close_menu(); }
+                ui.separator();
+                ui.checkbox(&mut self.timeline.onion_skin, "onion skin");
+                let play = if self.timeline.playing.is_some() { "stop" } else { "play" };
+                if ui.add_enabled(several, egui::Button::new(play)).clicked() { self.toggle_playback(); ui.close_menu(); }
+                ui.separator();
+                ui.checkbox(&mut self.show_timeline, "show timeline");
+            });
+
             ui.menu_button("view", |ui| {
-                if ui.button("zoom in    +").clicked() { self.zoom = (self.zoom * 1.5).min(16.0); ui.close_menu(); }
-                if ui.button("zoom out   -").clicked() { self.zoom = (self.zoom / 1.5).max(0.25); ui.close_menu(); }
-                if ui.button("actual size 0").clicked() { self.zoom = 1.0; self.pan_offset = Vec2::ZERO; ui.close_menu(); }
+                if ui.button("zoom in        ⌘+").clicked() { self.zoom_step(1); ui.close_menu(); }
+                if ui.button("zoom out       ⌘-").clicked() { self.zoom_step(-1); ui.close_menu(); }
+                if ui.button("actual size    ⌘0").clicked() { self.actual_size(); ui.close_menu(); }
+                if ui.button("fit to window  ⌘9").clicked() { self.fit_to_window(); ui.close_menu(); }
             });
 
             ui.menu_button("help", |ui| {
@@ -651,14 +1325,24 @@ impl SlowPaintApp {
                     shortcut_row(ui, "L", "Line tool");
                     shortcut_row(ui, "R", "Rectangle tool");
                     shortcut_row(ui, "G", "Fill (paint bucket)");
+                    shortcut_row(ui, "S", "Shapes and stamps");
                     shortcut_row(ui, "X", "Swap foreground/background");
                     ui.add_space(8.0);
 
+                    ui.label(egui::RichText::new("Frames").strong());
+                    ui.separator();
+                    shortcut_row(ui, ",", "Previous frame");
+                    shortcut_row(ui, ".", "Next frame");
+                    ui.add_space(8.0);
+
                     ui.label(egui::RichText::new("View").strong());
                     ui.separator();
-                    shortcut_row(ui, "+", "Zoom in");
-                    shortcut_row(ui, "-", "Zoom out");
-                    shortcut_row(ui, "0", "Actual size");
+                    shortcut_row(ui, "⌘+", "Zoom in");
+                    shortcut_row(ui, "⌘-", "Zoom out");
+                    shortcut_row(ui, "⌘0", "Actual size");
+                    shortcut_row(ui, "⌘9", "Fit to window");
+                    shortcut_row(ui, "Pinch", "Zoom about the pointer");
+                    shortcut_row(ui, "Space drag", "Pan canvas");
                     shortcut_row(ui, "Middle drag", "Pan canvas");
                     ui.add_space(8.0);
                 });
@@ -669,6 +1353,51 @@ impl SlowPaintApp {
         if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
     }
 
+    fn render_dynamics_dialog(&mut self, ctx: &Context) {
+        let mut changed = false;
+        let resp = egui::Window::new("brush dynamics")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                let dynamics = &mut self.dynamics;
+                ui.strong("pen pressure");
+                changed |= ui.checkbox(&mut dynamics.pressure_size, "sets the brush size").changed();
+                ui.add_enabled_ui(dynamics.pressure_size, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("lightest touch:");
+                        changed |= ui.add(egui::Slider::new(&mut dynamics.min_size, 0..=100).suffix("% of size")).changed();
+                    });
+                });
+                changed |= ui.checkbox(&mut dynamics.pressure_density, "sets how dense the brush is").changed();
+                ui.horizontal(|ui| {
+                    ui.label("curve:");
+                    changed |= ui.add(egui::Slider::new(&mut dynamics.curve, 0.5..=2.0).fixed_decimals(1)).changed();
+                });
+                ui.label("above 1, it takes a firmer press to paint full.");
+                ui.add_space(8.0);
+                ui.strong("with a mouse");
+                for fallback in MouseFallback::ALL {
+                    if ui.radio(dynamics.mouse == fallback, fallback.name()).clicked() && dynamics.mouse != fallback {
+                        dynamics.mouse = fallback;
+                        changed = true;
+                    }
+                }
+                ui.add_space(8.0);
+                match self.stylus.pen_pressure() {
+                    Some(pressure) => ui.label(format!("pen pressure: {:.0}%", pressure * 100.0)),
+                    None if self.stylus.seen => ui.label("pen lifted"),
+                    None => ui.label("no pen pressure yet: the mouse settings apply."),
+                };
+                ui.add_space(4.0);
+                if ui.button("close").clicked() { self.show_dynamics = false; }
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+        if changed {
+            self.dynamics.save();
+        }
+    }
+
     fn render_new_dialog(&mut self, ctx: &Context) {
         let resp = egui::Window::new("new image")
             .collapsible(false)
@@ -719,8 +1448,9 @@ impl SlowPaintApp {
                     if ui.button("resize").clicked() {
                         if let (Ok(w), Ok(h)) = (self.resize_width.parse::<u32>(), self.resize_height.parse::<u32>()) {
                             if w > 0 && w <= 4096 && h > 0 && h <= 4096 {
-                                self.canvas.resize(w, h);
+                                self.timeline.resize(&mut self.canvas, w, h);
                                 self.texture_dirty = true;
+                                self.frames_dirty = true;
                                 self.show_resize_dialog = false;
                             }
                         }
@@ -730,151 +1460,86 @@ impl SlowPaintApp {
         if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
     }
 
-    fn render_file_browser(&mut self, ctx: &Context) {
-        let title = match self.file_browser_mode {
-            FileBrowserMode::Open => "open image",
-            FileBrowserMode::Save => "save image",
-        };
-
-        let resp = egui::Window::new(title)
+    fn render_icon_dialog(&mut self, ctx: &Context) {
+        let resp = egui::Window::new("new icon")
             .collapsible(false)
             .resizable(false)
-            .default_width(380.0)
             .show(ctx, |ui| {
+                ui.label("size:");
                 ui.horizontal(|ui| {
-                    ui.label("location:");
-                    ui.label(self.file_browser.current_dir.to_string_lossy().to_string());
-                });
-                ui.separator();
-
-                egui::ScrollArea::vertical().max_height(220.0).show(ui, |ui| {
-                    let mut clicked_idx = None;
-                    let mut nav_path = None;
-                    let mut open_path = None;
-                    for (idx, entry) in self.file_browser.entries.iter().enumerate() {
-                        let selected = self.file_browser.selected_index == Some(idx);
-                        let response = ui.add(
-                            slowcore::widgets::FileListItem::new(&entry.name, entry.is_directory).selected(selected)
-                        );
-                        if response.clicked() { clicked_idx = Some(idx); }
-                        if response.double_clicked() {
-                            if entry.is_directory {
-                                nav_path = Some(entry.path.clone());
-                            } else if self.file_browser_mode == FileBrowserMode::Open {
-                                open_path = Some(entry.path.clone());
-                            }
-                        }
-                    }
-                    if let Some(idx) = clicked_idx { self.file_browser.selected_index = Some(idx); }
-                    if let Some(path) = nav_path { self.file_browser.navigate_to(path); }
-                    if let Some(path) = open_path {
-                        self.open_file(path);
-                        self.show_file_browser = false;
+                    for size in icon::SIZES {
+                        ui.radio_value(&mut self.icon_size, size, format!("{0}×{0}", size));
                     }
                 });
-
-                if self.file_browser_mode == FileBrowserMode::Save {
-                    ui.separator();
-                    ui.horizontal(|ui| {
-                        ui.label("filename:");
-                        ui.text_edit_singleline(&mut self.save_filename);
-                    });
-                }
-
-                ui.separator();
+                ui.add_space(4.0);
+                ui.label("the desktop shows icons at 48×48.");
+                ui.add_space(4.0);
                 ui.horizontal(|ui| {
-                    if ui.button("cancel").clicked() { self.show_file_browser = false; }
-                    let action = if self.file_browser_mode == FileBrowserMode::Open { "open" } else { "save" };
-                    if ui.button(action).clicked() {
-                        match self.file_browser_mode {
-                            FileBrowserMode::Open => {
-                                if let Some(entry) = self.file_browser.selected_entry() {
-                                    if !entry.is_directory {
-                                        self.open_file(entry.path.clone());
-                                        self.show_file_browser = false;
-                                    }
-                                }
-                            }
-                            FileBrowserMode::Save => {
-                                if !self.save_filename.is_empty() {
-                                    let path = self.file_browser.save_directory().join(&self.save_filename);
-                                    self.save_as(path);
-                                    self.show_file_browser = false;
-                                }
-                            }
-                        }
+                    if ui.button("cancel").clicked() { self.show_icon_dialog = false; }
+                    if ui.button("create").clicked() {
+                        self.new_icon(self.icon_size);
+                        self.show_icon_dialog = false;
                     }
                 });
             });
         if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
     }
 
-    fn render_close_confirm(&mut self, ctx: &Context) {
-        let resp = egui::Window::new("unsaved changes")
+    fn render_icon_export_dialog(&mut self, ctx: &Context) {
+        let Some((category, name)) = &mut self.icon_export else { return };
+        let mut export = false;
+        let mut cancel = false;
+        let resp = egui::Window::new("export icon")
             .collapsible(false)
             .resizable(false)
-            .default_width(300.0)
-            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
             .show(ctx, |ui| {
-                ui.label("you have unsaved changes.");
-                ui.label("do you want to save before closing?");
-                ui.add_space(8.0);
-                ui.horizontal(|ui| {
-                    if ui.button("don't save").clicked() {
-                        self.close_confirmed = true;
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                    }
-                    if ui.button("cancel").clicked() {
-                        self.show_close_confirm = false;
-                    }
-                    if ui.button("save").clicked() {
-                        self.save();
-                        if !self.canvas.modified {
-                            self.close_confirmed = true;
-                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                        }
+                ui.label("kind:");
+                ui.horizontal_wrapped(|ui| {
+                    for (i, (_, label)) in icon::CATEGORIES.iter().enumerate() {
+                        ui.radio_value(category, i, *label);
                     }
                 });
+                ui.horizontal(|ui| {
+                    ui.label("name:");
+                    ui.text_edit_singleline(name);
+                });
+                let path = icon::icons_dir().join(icon::CATEGORIES[*category].0).join(icon::file_name(name));
+                ui.add_space(4.0);
+                ui.weak(path.display().to_string());
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() { cancel = true; }
+                    let label = if path.exists() { "replace" } else { "export" };
+                    if ui.add_enabled(!name.trim().is_empty(), egui::Button::new(label)).clicked() { export = true; }
+                });
             });
         if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+        if cancel {
+            self.icon_export = None;
+        } else if export {
+            if let Some((category, name)) = self.icon_export.take() {
+                self.export_icon(category, &name);
+            }
+        }
     }
 
-    fn render_about(&mut self, ctx: &Context) {
-        let screen = ctx.screen_rect();
-        let max_h = (screen.height() - 60.0).max(120.0);
-
-        let resp = egui::Window::new("about slowPaint")
-            .collapsible(false)
-            .resizable(false)
-            .default_width(300.0)
-            .max_height(max_h)
-            .show(ctx, |ui| {
-                egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                    ui.vertical_centered(|ui| {
-                        ui.heading("slowPaint");
-                        ui.label("version 0.2.2");
-                        ui.add_space(8.0);
-                        ui.label("bitmap editor for slowOS");
-                    });
-                    ui.add_space(8.0);
-                    ui.separator();
-                    ui.add_space(4.0);
-                    ui.label("supported formats:");
-                    ui.label("  PNG, BMP, JPEG (open/save)");
-                    ui.add_space(4.0);
-                    ui.label("frameworks:");
-                    ui.label("  egui/eframe (MIT), image-rs (MIT)");
-                    ui.label("  tiny-skia (BSD-3)");
-                    ui.add_space(4.0);
-                    ui.label("tools: pencil, brush, eraser, line,");
-                    ui.label("rectangle, ellipse, fill, patterns");
-                    ui.add_space(8.0);
-                });
-                ui.vertical_centered(|ui| {
-                    if ui.button("ok").clicked() { self.show_about = false; }
-                });
-            });
-        if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+    fn render_file_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.file_dialog else { return };
+        let mode = dialog.mode();
+        match dialog.show(ctx) {
+            FileDialogResult::Picked(path) => {
+                self.file_dialog = None;
+                match (mode, self.file_purpose) {
+                    (FileDialogMode::Open, FilePurpose::Icon) => self.open_icon(path),
+                    (FileDialogMode::Open, _) => self.open_file(path),
+                    (FileDialogMode::Save, FilePurpose::Gif) => self.export_gif(path),
+                    (FileDialogMode::Save, FilePurpose::SpriteSheet) => self.export_sprite_sheet(path),
+                    (FileDialogMode::Save, _) => self.save_as(path),
+                }
+            }
+            FileDialogResult::Cancelled => self.file_dialog = None,
+            FileDialogResult::Pending => {}
+        }
     }
 }
 
@@ -886,16 +1551,13 @@ impl eframe::App for SlowPaintApp {
             ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
         }
         self.handle_keyboard(ctx);
+        self.stylus.update(ctx);
 
         let mut win_action = WindowAction::None;
         egui::TopBottomPanel::top("menu").show(ctx, |ui| { win_action = self.render_menu_bar(ui); });
         match win_action {
             WindowAction::Close => {
-                if self.canvas.modified {
-                    self.show_close_confirm = true;
-                } else {
-                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                }
+                self.close_guard.request_close(ctx, self.canvas.modified);
             }
             WindowAction::Minimize => {
                 let title = format!("{} — slowPaint", self.canvas.display_title());
@@ -910,37 +1572,107 @@ impl eframe::App for SlowPaintApp {
                 Some((x, y)) => format!("{}, {}", x, y),
                 None => "—".into(),
             };
+            if let Some(message) = &self.message {
+                status_bar(ui, message);
+                return;
+            }
+            let frame_str = match self.timeline.len() {
+                1 => String::new(),
+                n => format!("  |  frame {}/{}", self.timeline.playing_frame().unwrap_or(self.timeline.current) + 1, n),
+            };
             status_bar(ui, &format!(
-                "{}  |  {}×{}  |  zoom: {:.0}%  |  {}  |  pos: {}",
+                "{}{}{}  |  {}×{}  |  zoom: {:.0}%  |  {}  |  pos: {}",
+                if self.icon.is_some() { "icon: " } else { "" },
                 self.canvas.display_title(),
+                frame_str,
                 self.canvas.width(),
                 self.canvas.height(),
                 self.zoom * 100.0,
-                self.current_tool.name(),
+                self.plugin_tool.and_then(|i| self.paint_tools.get(i)).map_or(self.current_tool.name(), |p| p.name()),
                 pos_str,
             ));
         });
+        if self.show_timeline || self.timeline.len() > 1 {
+            egui::TopBottomPanel::bottom("timeline").show(ctx, |ui| { self.render_timeline(ui); });
+        }
         egui::SidePanel::left("patterns").exact_width(80.0).show(ctx, |ui| { self.render_pattern_panel(ui); });
+        if let Some(mode) = &self.icon {
+            let width = (mode.size as f32 * 2.0 / ctx.pixels_per_point() + 24.0).max(120.0);
+            egui::SidePanel::right("icon_preview").exact_width(width).show(ctx, |ui| { self.render_icon_preview(ui); });
+        }
         egui::CentralPanel::default().frame(egui::Frame::none()).show(ctx, |ui| { self.render_canvas(ui, ctx); });
 
-        // No timed repaint needed — pointer movement triggers repaints.
+        // Pointer movement triggers repaints; playback needs them timed
+        self.repaint.set_continuous(self.timeline.playing.is_some());
 
         if self.show_new_dialog { self.render_new_dialog(ctx); }
         if self.show_resize_dialog { self.render_resize_dialog(ctx); }
-        if self.show_file_browser { self.render_file_browser(ctx); }
-        if self.show_close_confirm { self.render_close_confirm(ctx); }
-        if self.show_about { self.render_about(ctx); }
+        if self.show_dynamics { self.render_dynamics_dialog(ctx); }
+        if self.show_icon_dialog { self.render_icon_dialog(ctx); }
+        self.render_icon_export_dialog(ctx);
+        self.render_file_dialog(ctx);
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
         if self.show_shortcuts { self.render_shortcuts(ctx); }
 
-        // Handle close request
-        if ctx.input(|i| i.viewport().close_requested()) {
-            if self.canvas.modified && !self.close_confirmed {
-                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
-                self.show_close_confirm = true;
-            }
+        // Unsaved-changes guard for close requests
+        if self.close_guard.update(ctx, self.canvas.modified) {
+            self.save();
+            self.close_guard.close_if_saved(ctx, !self.canvas.modified);
         }
+        // Keep an unsaved sketch if the system stops the app or the battery runs out
+        lifecycle::update(ctx, "slowpaint", self);
         self.repaint.end_frame(ctx);
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.shortcuts.unpublish();
+        lifecycle::discard("slowpaint");
+    }
+}
+
+/// An unsaved sketch kept for the next launch. The picture itself goes in
+/// a file of its own next to the snapshot.
+#[derive(Serialize, Deserialize)]
+pub struct SketchState {
+    /// Where the sketch was last saved, if anywhere
+    path: Option<PathBuf>,
+    picture: PathBuf,
+}
+
+impl SaveState for SlowPaintApp {
+    type State = SketchState;
+
+    fn save_state(&self) -> Option<SketchState> {
+        if !self.canvas.modified {
+            return None;
+        }
+        let dir = config_dir("slowpaint");
+        std::fs::create_dir_all(&dir).ok()?;
+        let result = if self.timeline.len() > 1 {
+            let picture = dir.join("saved_sketch.gif");
+            frames::write_gif(&self.timeline.all(&self.canvas), self.timeline.fps, &picture).map(|()| picture)
+        } else {
+            let picture = dir.join("saved_sketch.png");
+            self.canvas.image.save(&picture).map(|()| picture).map_err(|e| e.to_string())
+        };
+        match result {
+            Ok(picture) => Some(SketchState { path: self.canvas.path.clone(), picture }),
+            Err(e) => {
+                log::error!("failed to keep the sketch: {}", e);
+                None
+            }
+        }
+    }
+
+    fn restore_state(&mut self, state: SketchState) {
+        if self.load(state.picture.clone()) {
+            self.canvas.path = state.path;
+            self.canvas.modified = true;
+        }
+        std::fs::remove_file(&state.picture).ok();
+    }
 }
 
 fn shortcut_row(ui: &mut egui::Ui, shortcut: &str, description: &str) {
@@ -950,3 +1682,19 @@ fn shortcut_row(ui: &mut egui::Ui, shortcut: &str, description: &str) {
         ui.label(description);
     });
 }
+
+/// An icon's black pixels, each `scale` points square, on white
+fn draw_icon(ui: &mut egui::Ui, image: &image::RgbaImage, scale: f32) {
+    let size = Vec2::new(image.width() as f32, image.height() as f32) * scale;
+    let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, SlowColors::WHITE);
+    for (x, y, _) in image.enumerate_pixels().filter(|(_, _, p)| p.0[0] < 128) {
+        let min = rect.min + Vec2::new(x as f32, y as f32) * scale;
+        painter.rect_filled(Rect::from_min_size(min, Vec2::splat(scale)), 0.0, SlowColors::BLACK);
+    }
+}
+
+fn is_gif(path: &std::path::Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("gif"))
+}
@@ -1,20 +1,48 @@
 //! Canvas - bitmap image representation and manipulation
 
-use image::{ImageBuffer, Rgba, RgbaImage};
-use std::collections::VecDeque;
+use crate::dynamics;
+use crate::tools::{BLACK, WHITE};
+use image::{DynamicImage, GrayAlphaImage, ImageBuffer, Rgba, RgbaImage};
+use slowcore::plugins::Plugin;
+use slowcore::undo::UndoStack;
 use std::path::PathBuf;
 
 /// Maximum undo states — 10 states × ~1.2MB each = ~12MB (down from 24MB)
 const MAX_UNDO_STATES: usize = 10;
 
+/// Convert to grayscale to reduce processing overhead, back in RGBA
+/// (all channels same value)
+pub fn to_gray(img: &DynamicImage) -> RgbaImage {
+    let gray = img.to_luma8();
+    let (w, h) = gray.dimensions();
+    let mut image = ImageBuffer::new(w, h);
+    for (x, y, pixel) in gray.enumerate_pixels() {
+        let v = pixel.0[0];
+        image.put_pixel(x, y, Rgba([v, v, v, 255]));
+    }
+    image
+}
+
+/// A copy at new dimensions: cropped if smaller, padded with white if larger
+pub fn resized(image: &RgbaImage, new_width: u32, new_height: u32) -> RgbaImage {
+    let mut new_image = ImageBuffer::from_pixel(new_width, new_height, Rgba([255, 255, 255, 255]));
+    // Copy existing pixels
+    let copy_width = image.width().min(new_width);
+    let copy_height = image.height().min(new_height);
+    for y in 0..copy_height {
+        for x in 0..copy_width {
+            new_image.put_pixel(x, y, *image.get_pixel(x, y));
+        }
+    }
+    new_image
+}
+
 /// A bitmap canvas for editing
-#[derive(Clone)]
 pub struct Canvas {
     pub image: RgbaImage,
     pub path: Option<PathBuf>,
     pub modified: bool,
-    undo_stack: VecDeque<RgbaImage>,
-    redo_stack: Vec<RgbaImage>,
+    history: UndoStack<RgbaImage>,
 }
 
 impl Canvas {
@@ -24,39 +52,20 @@ impl Canvas {
             image,
             path: None,
             modified: false,
-            undo_stack: VecDeque::new(),
-            redo_stack: Vec::new(),
+            history: UndoStack::new(MAX_UNDO_STATES),
         }
     }
     
     pub fn open(path: PathBuf) -> Result<Self, image::ImageError> {
         let img = image::open(&path)?;
-        // Convert to grayscale to reduce processing overhead
-        let gray = img.to_luma8();
-        // Convert grayscale back to RGBA (all channels same value)
-        let (w, h) = gray.dimensions();
-        let mut image = ImageBuffer::new(w, h);
-        for (x, y, pixel) in gray.enumerate_pixels() {
-            let v = pixel.0[0];
-            image.put_pixel(x, y, Rgba([v, v, v, 255]));
-        }
         Ok(Self {
-            image,
+            image: to_gray(&img),
             path: Some(path),
             modified: false,
-            undo_stack: VecDeque::new(),
-            redo_stack: Vec::new(),
+            history: UndoStack::new(MAX_UNDO_STATES),
         })
     }
     
-    pub fn save(&mut self) -> Result<(), image::ImageError> {
-        if let Some(ref path) = self.path {
-            self.image.save(path)?;
-            self.modified = false;
-        }
-        Ok(())
-    }
-    
     pub fn save_as(&mut self, path: PathBuf) -> Result<(), image::ImageError> {
         self.image.save(&path)?;
         self.path = Some(path);
@@ -70,18 +79,16 @@ impl Canvas {
     /// Resize the canvas to new dimensions. Preserves content (crops if smaller, pads with white if larger).
     pub fn resize(&mut self, new_width: u32, new_height: u32) {
         self.save_undo_state();
-        let mut new_image = ImageBuffer::from_pixel(new_width, new_height, Rgba([255, 255, 255, 255]));
-        // Copy existing pixels
-        let copy_width = self.width().min(new_width);
-        let copy_height = self.height().min(new_height);
-        for y in 0..copy_height {
-            for x in 0..copy_width {
-                new_image.put_pixel(x, y, *self.image.get_pixel(x, y));
-            }
-        }
-        self.image = new_image;
+        self.image = resized(&self.image, new_width, new_height);
         self.modified = true;
     }
+
+    /// Put another image on the canvas, e.g. another animation frame, and
+    /// hand back the one that was there. Undo history starts afresh.
+    pub fn swap_image(&mut self, image: RgbaImage) -> RgbaImage {
+        self.history.clear();
+        std::mem::replace(&mut self.image, image)
+    }
     
     pub fn display_title(&self) -> String {
         let name = self.path.as_ref()
@@ -92,29 +99,19 @@ impl Canvas {
     }
     
     pub fn save_undo_state(&mut self) {
-        self.undo_stack.push_back(self.image.clone());
-        self.redo_stack.clear();
-        while self.undo_stack.len() > MAX_UNDO_STATES {
-            self.undo_stack.pop_front(); // O(1) with VecDeque
-        }
+        self.history.save(&self.image);
     }
     
     pub fn undo(&mut self) -> bool {
-        if let Some(state) = self.undo_stack.pop_back() {
-            self.redo_stack.push(self.image.clone());
-            self.image = state;
-            self.modified = true;
-            true
-        } else { false }
+        let undone = self.history.undo(&mut self.image);
+        self.modified |= undone;
+        undone
     }
     
     pub fn redo(&mut self) -> bool {
-        if let Some(state) = self.redo_stack.pop() {
-            self.undo_stack.push_back(self.image.clone());
-            self.image = state;
-            self.modified = true;
-            true
-        } else { false }
+        let redone = self.history.redo(&mut self.image);
+        self.modified |= redone;
+        redone
     }
     
     pub fn set_pixel(&mut self, x: u32, y: u32, color: Rgba<u8>) {
@@ -182,13 +179,22 @@ impl Canvas {
     pub fn draw_circle_filled_pattern(
         &mut self, cx: i32, cy: i32, radius: i32,
         color: Rgba<u8>, pattern: &crate::tools::Pattern,
+    ) {
+        self.draw_dab(cx, cy, radius, color, pattern, 1.0);
+    }
+
+    /// A brush dab: a filled circle with a pattern, only `density` (0.0–1.0)
+    /// of its pixels painted
+    pub fn draw_dab(
+        &mut self, cx: i32, cy: i32, radius: i32,
+        color: Rgba<u8>, pattern: &crate::tools::Pattern, density: f32,
     ) {
         for dy in -radius..=radius {
             for dx in -radius..=radius {
                 if dx * dx + dy * dy <= radius * radius {
                     let px = cx + dx;
                     let py = cy + dy;
-                    if px >= 0 && py >= 0 && pattern.should_fill(px as u32, py as u32) {
+                    if px >= 0 && py >= 0 && pattern.should_fill(px as u32, py as u32) && dynamics::dense(px, py, density) {
                         self.set_pixel_safe(px, py, color);
                     }
                 }
@@ -196,6 +202,28 @@ impl Canvas {
         }
     }
 
+    /// A brush stroke: dabs of `density` along a line
+    pub fn draw_stroke(
+        &mut self, (x0, y0): (i32, i32), (x1, y1): (i32, i32),
+        color: Rgba<u8>, thickness: u32, pattern: &crate::tools::Pattern, density: f32,
+    ) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        let (mut x, mut y) = (x0, y0);
+
+        loop {
+            self.draw_dab(x, y, thickness as i32 / 2, color, pattern, density);
+            if x == x1 && y == y1 { break; }
+            let e2 = 2 * err;
+            if e2 >= dy { if x == x1 { break; } err += dy; x += sx; }
+            if e2 <= dx { if y == y1 { break; } err += dx; y += sy; }
+        }
+        self.modified = true;
+    }
+
     pub fn draw_rect_outline(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Rgba<u8>, thickness: u32, pattern: &crate::tools::Pattern) {
         let (x0, x1) = if x0 < x1 { (x0, x1) } else { (x1, x0) };
         let (y0, y1) = if y0 < y1 { (y0, y1) } else { (y1, y0) };
@@ -253,6 +281,13 @@ impl Canvas {
         self.modified = true;
     }
     
+    /// Let a plugin tool paint a dab of `size` at `x`, `y`
+    pub fn plugin_dab(&mut self, plugin: &Plugin, x: i32, y: i32, size: u32) {
+        let (width, height) = (self.width(), self.height());
+        plugin.paint(&mut self.image, width, height, x, y, size);
+        self.modified = true;
+    }
+
     /// Convert to pure black and white (threshold at 128)
     pub fn threshold(&mut self) {
         for pixel in self.image.pixels_mut() {
@@ -339,6 +374,53 @@ impl Canvas {
         self.modified = true;
     }
 
+    /// Fill a polygon with a pattern (even-odd, so a star's middle is filled
+    /// and crossed shapes get holes)
+    pub fn draw_polygon_filled_pattern(&mut self, points: &[(f32, f32)], color: Rgba<u8>, pattern: &crate::tools::Pattern) {
+        if points.len() < 3 { return; }
+        let top = points.iter().map(|p| p.1).fold(f32::MAX, f32::min).floor().max(0.0) as i32;
+        let bottom = points.iter().map(|p| p.1).fold(f32::MIN, f32::max).ceil().min(self.height() as f32) as i32;
+        for y in top..bottom {
+            // Where each edge crosses the middle of the row
+            let row = y as f32 + 0.5;
+            let mut crossings: Vec<f32> = points.iter().zip(points.iter().cycle().skip(1))
+                .filter(|(a, b)| (a.1 <= row) != (b.1 <= row))
+                .map(|(a, b)| a.0 + (row - a.1) / (b.1 - a.1) * (b.0 - a.0))
+                .collect();
+            crossings.sort_by(f32::total_cmp);
+            for span in crossings.chunks_exact(2) {
+                let (from, to) = ((span[0] - 0.5).ceil() as i32, (span[1] - 0.5).floor() as i32);
+                for x in from.max(0)..=to {
+                    if pattern.should_fill(x as u32, y as u32) {
+                        self.set_pixel_safe(x, y, color);
+                    }
+                }
+            }
+        }
+        self.modified = true;
+    }
+
+    /// Draw a polygon's edges, `thickness` wide, with a pattern
+    pub fn draw_polygon_outline(&mut self, points: &[(f32, f32)], color: Rgba<u8>, thickness: u32, pattern: &crate::tools::Pattern) {
+        let round = |p: &(f32, f32)| (p.0.round() as i32, p.1.round() as i32);
+        for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+            let ((x0, y0), (x1, y1)) = (round(a), round(b));
+            self.draw_line_pattern(x0, y0, x1, y1, color, thickness, pattern);
+        }
+    }
+
+    /// Put a stamp down with its top left at `x`, `y`: black and white as
+    /// it is, its see-through pixels left alone
+    pub fn draw_stamp(&mut self, x: i32, y: i32, stamp: &GrayAlphaImage) {
+        for (sx, sy, pixel) in stamp.enumerate_pixels() {
+            let [luma, alpha] = pixel.0;
+            if alpha >= 128 {
+                self.set_pixel_safe(x + sx as i32, y + sy as i32, if luma < 128 { BLACK } else { WHITE });
+            }
+        }
+        self.modified = true;
+    }
+
     /// Pattern-aware flood fill
     pub fn pattern_fill(
         &mut self, start_x: u32, start_y: u32,
@@ -370,10 +452,14 @@ impl Canvas {
     }
     
     pub fn to_texture_data(&self) -> egui::ColorImage {
-        let size = [self.width() as usize, self.height() as usize];
-        let pixels: Vec<egui::Color32> = self.image.pixels()
-            .map(|p| egui::Color32::from_rgba_unmultiplied(p[0], p[1], p[2], p[3]))
-            .collect();
-        egui::ColorImage { size, pixels }
+        texture_data(&self.image)
     }
 }
+
+pub fn texture_data(image: &RgbaImage) -> egui::ColorImage {
+    let size = [image.width() as usize, image.height() as usize];
+    let pixels: Vec<egui::Color32> = image.pixels()
+        .map(|p| egui::Color32::from_rgba_unmultiplied(p[0], p[1], p[2], p[3]))
+        .collect();
+    egui::ColorImage { size, pixels }
+}
@@ -0,0 +1,202 @@
+//! Brush dynamics — a pen's pressure setting the brush's size and density
+//!
+//! A pen on a tablet or touch screen reports how hard it presses with its
+//! touch events, where the platform passes that on. Pressing harder paints
+//! a wider line and, if chosen, a denser one: a light touch leaves a
+//! scattering of dots, laid out in a fixed 4×4 order so the picture stays
+//! black and white and overlapping dabs don't fill in.
+//!
+//! A mouse has no pressure. It paints at the chosen size and full density,
+//! or thinner the faster it moves, like a pen lifting off the paper.
+//!
+//! winit doesn't pass on a pen's tilt, so tilt isn't used.
+
+use egui::{Context, Event, TouchPhase};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Mouse speed, in points a second, at which a stroke is thinnest
+const FAST_STROKE: f32 = 2000.0;
+
+/// The thinnest a fast mouse stroke gets, as pressure
+const SLOWEST_PRESSURE: f32 = 0.25;
+
+/// 4×4 Bayer matrix: the order in which a dab's pixels fill as it gets denser
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// How a mouse paints, having no pressure
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseFallback {
+    /// The chosen size and full density
+    #[default]
+    Full,
+    /// Thinner and lighter the faster it moves
+    Speed,
+}
+
+impl MouseFallback {
+    pub const ALL: [MouseFallback; 2] = [MouseFallback::Full, MouseFallback::Speed];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MouseFallback::Full => "full size",
+            MouseFallback::Speed => "thinner when fast",
+        }
+    }
+}
+
+/// What pressure does to the brush, kept in ~/.config/slowpaint/dynamics.json
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrushDynamics {
+    /// Pressure sets the brush's size, from `min_size` up to the chosen size
+    #[serde(default = "default_true")]
+    pub pressure_size: bool,
+    /// Pressure sets how many of the brush's pixels are painted
+    #[serde(default)]
+    pub pressure_density: bool,
+    /// Size at the lightest touch, in percent of the chosen size
+    #[serde(default = "default_min_size")]
+    pub min_size: u8,
+    /// Power the pressure is raised to: above 1 needs a firmer press
+    #[serde(default = "default_curve")]
+    pub curve: f32,
+    #[serde(default)]
+    pub mouse: MouseFallback,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_min_size() -> u8 {
+    25
+}
+
+fn default_curve() -> f32 {
+    1.0
+}
+
+impl Default for BrushDynamics {
+    fn default() -> Self {
+        Self {
+            pressure_size: true,
+            pressure_density: false,
+            min_size: default_min_size(),
+            curve: default_curve(),
+            mouse: MouseFallback::Full,
+        }
+    }
+}
+
+impl BrushDynamics {
+    fn path() -> PathBuf {
+        slowcore::storage::config_dir("slowpaint").join("dynamics.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    /// The brush's size in pixels and its density (0.0–1.0) at `pressure`,
+    /// for a chosen size of `size` pixels
+    pub fn brush(&self, pressure: f32, size: u32) -> (u32, f32) {
+        let pressure = pressure.clamp(0.0, 1.0).powf(self.curve.max(0.1));
+        let size = if self.pressure_size {
+            let min = size as f32 * self.min_size.min(100) as f32 / 100.0;
+            (min + (size as f32 - min) * pressure).round().max(1.0) as u32
+        } else {
+            size
+        };
+        let density = if self.pressure_density { pressure.max(1.0 / 16.0) } else { 1.0 };
+        (size, density)
+    }
+}
+
+/// The pen as reported by the touch events, or the mouse standing in
+#[derive(Default)]
+pub struct Stylus {
+    /// Pressure of the pen touching now
+    pressure: Option<f32>,
+    /// A pen has reported pressure since the app started
+    pub seen: bool,
+}
+
+impl Stylus {
+    /// Read this frame's touch events
+    pub fn update(&mut self, ctx: &Context) {
+        ctx.input(|i| {
+            for event in &i.events {
+                if let Event::Touch { phase, force, .. } = event {
+                    match phase {
+                        TouchPhase::Start | TouchPhase::Move => {
+                            self.pressure = *force;
+                            self.seen |= force.is_some();
+                        }
+                        TouchPhase::End | TouchPhase::Cancel => self.pressure = None,
+                    }
+                }
+            }
+        });
+    }
+
+    /// The pen's pressure now, if a pen is down and reports it
+    pub fn pen_pressure(&self) -> Option<f32> {
+        self.pressure
+    }
+
+    /// Pressure to paint with: the pen's, or the mouse's stand-in for it
+    pub fn pressure(&self, ctx: &Context, fallback: MouseFallback) -> f32 {
+        if let Some(pressure) = self.pressure {
+            return pressure;
+        }
+        match fallback {
+            MouseFallback::Full => 1.0,
+            MouseFallback::Speed => {
+                let speed = ctx.input(|i| i.pointer.velocity().length());
+                (1.0 - speed / FAST_STROKE).max(SLOWEST_PRESSURE)
+            }
+        }
+    }
+}
+
+/// Whether the pixel at `x`, `y` is painted by a dab of `density`
+pub fn dense(x: i32, y: i32, density: f32) -> bool {
+    let order = BAYER_4X4[y.rem_euclid(4) as usize][x.rem_euclid(4) as usize];
+    (order as f32 + 0.5) / 16.0 < density
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brush_follows_pressure() {
+        let dynamics = BrushDynamics::default();
+        assert_eq!(dynamics.brush(1.0, 16), (16, 1.0));
+        assert_eq!(dynamics.brush(0.0, 16), (4, 1.0));
+        assert_eq!(dynamics.brush(0.5, 16), (10, 1.0));
+        // Never thinner than a pixel
+        assert_eq!(dynamics.brush(0.0, 1).0, 1);
+
+        let dynamics = BrushDynamics { pressure_size: false, pressure_density: true, ..Default::default() };
+        let (size, density) = dynamics.brush(0.5, 8);
+        assert_eq!(size, 8);
+        // Half the pixels of every 4×4 block
+        let painted = (0..4).flat_map(|y| (0..4).map(move |x| (x, y))).filter(|&(x, y)| dense(x, y, density)).count();
+        assert_eq!(painted, 8);
+    }
+}
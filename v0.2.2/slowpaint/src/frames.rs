@@ -0,0 +1,199 @@
+//! Frames — a timeline that turns slowPaint into a small animation tool
+//!
+//! The canvas always holds the frame being drawn; the rest wait in the
+//! timeline. Undo history belongs to the canvas, so it starts afresh on
+//! moving to another frame. Animations go out as looping GIFs or as a
+//! sprite sheet with the frames side by side, and a GIF opens back in
+//! with all its frames.
+
+use crate::canvas::{self, Canvas};
+use crate::tools::WHITE;
+use image::codecs::gif::{GifDecoder, GifEncoder, Repeat};
+use image::{AnimationDecoder, Delay, Frame, RgbaImage};
+use std::path::Path;
+use std::time::Instant;
+
+pub const MAX_FPS: u32 = 24;
+
+pub struct Timeline {
+    /// Every frame; the one at `current` is out of date while it's on
+    /// the canvas, and filled in on leaving it
+    frames: Vec<RgbaImage>,
+    pub current: usize,
+    pub fps: u32,
+    pub onion_skin: bool,
+    /// When playback started
+    pub playing: Option<Instant>,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self { frames: vec![RgbaImage::new(0, 0)], current: 0, fps: 8, onion_skin: true, playing: None }
+    }
+
+    /// A timeline of `frames`, the first of them put on the canvas
+    pub fn from_frames(mut frames: Vec<RgbaImage>, fps: u32, canvas: &mut Canvas) -> Self {
+        if let Some(first) = frames.first_mut() {
+            canvas.swap_image(std::mem::replace(first, RgbaImage::new(0, 0)));
+        }
+        Self { frames, current: 0, fps: fps.clamp(1, MAX_FPS), onion_skin: true, playing: None }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn go_to(&mut self, canvas: &mut Canvas, index: usize) {
+        if index == self.current || index >= self.frames.len() {
+            return;
+        }
+        let image = std::mem::replace(&mut self.frames[index], RgbaImage::new(0, 0));
+        self.frames[self.current] = canvas.swap_image(image);
+        self.current = index;
+    }
+
+    /// Add a frame after this one, blank or a copy of it, and go to it
+    pub fn add(&mut self, canvas: &mut Canvas, copy: bool) {
+        let frame = if copy { canvas.image.clone() } else { RgbaImage::from_pixel(canvas.width(), canvas.height(), WHITE) };
+        self.frames.insert(self.current + 1, frame);
+        self.go_to(canvas, self.current + 1);
+    }
+
+    /// Delete this frame, unless it's the only one
+    pub fn delete(&mut self, canvas: &mut Canvas) {
+        if self.frames.len() < 2 {
+            return;
+        }
+        let removed = self.current;
+        let next = if removed + 1 < self.frames.len() { removed + 1 } else { removed - 1 };
+        self.go_to(canvas, next);
+        self.frames.remove(removed);
+        if removed < self.current {
+            self.current -= 1;
+        }
+    }
+
+    /// Move this frame one place earlier (-1) or later (1)
+    pub fn shift(&mut self, by: isize) {
+        let Some(to) = self.current.checked_add_signed(by).filter(|&to| to < self.frames.len()) else { return };
+        self.frames.swap(self.current, to);
+        self.current = to;
+    }
+
+    /// A frame other than the one on the canvas
+    pub fn frame(&self, index: usize) -> Option<&RgbaImage> {
+        self.frames.get(index).filter(|_| index != self.current)
+    }
+
+    /// The frame before this one, for onion skinning
+    pub fn previous(&self) -> Option<&RgbaImage> {
+        self.current.checked_sub(1).and_then(|i| self.frames.get(i))
+    }
+
+    /// Every frame as it is now
+    pub fn all(&self, canvas: &Canvas) -> Vec<RgbaImage> {
+        let mut frames = self.frames.clone();
+        frames[self.current] = canvas.image.clone();
+        frames
+    }
+
+    /// Resize every frame. With more than one, undo can't take the
+    /// canvas back to a size the others no longer share, so history
+    /// starts afresh.
+    pub fn resize(&mut self, canvas: &mut Canvas, width: u32, height: u32) {
+        if self.frames.len() < 2 {
+            canvas.resize(width, height);
+            return;
+        }
+        for (i, frame) in self.frames.iter_mut().enumerate() {
+            if i != self.current {
+                *frame = canvas::resized(frame, width, height);
+            }
+        }
+        canvas.swap_image(canvas::resized(&canvas.image, width, height));
+        canvas.modified = true;
+    }
+
+    /// The frame playback is showing
+    pub fn playing_frame(&self) -> Option<usize> {
+        let started = self.playing?;
+        Some((started.elapsed().as_secs_f32() * self.fps as f32) as usize % self.frames.len())
+    }
+}
+
+/// The previous frame for drawing over the canvas: its black pixels as
+/// a checkerboard of dots, the rest see-through
+pub fn onion_skin(image: &RgbaImage) -> egui::ColorImage {
+    let size = [image.width() as usize, image.height() as usize];
+    let pixels = image
+        .enumerate_pixels()
+        .map(|(x, y, p)| if p.0[0] < 128 && (x + y).is_multiple_of(2) { egui::Color32::BLACK } else { egui::Color32::TRANSPARENT })
+        .collect();
+    egui::ColorImage { size, pixels }
+}
+
+/// A looping GIF of `frames` at `fps`
+pub fn write_gif(frames: &[RgbaImage], fps: u32, path: &Path) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = GifEncoder::new(std::io::BufWriter::new(file));
+    encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+    let delay = Delay::from_numer_denom_ms(1000, fps.max(1));
+    encoder
+        .encode_frames(frames.iter().map(|f| Frame::from_parts(f.clone(), 0, 0, delay)))
+        .map_err(|e| e.to_string())
+}
+
+/// The frames of a GIF in black and white, and its frame rate
+pub fn read_gif(path: &Path) -> Result<(Vec<RgbaImage>, u32), String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let decoder = GifDecoder::new(std::io::BufReader::new(file)).map_err(|e| e.to_string())?;
+    let frames = decoder.into_frames().collect_frames().map_err(|e| e.to_string())?;
+    let fps = frames.first().and_then(|f| {
+        let (numer, denom) = f.delay().numer_denom_ms();
+        (1000 * denom).checked_div(numer)
+    });
+    let fps = fps.map_or(8, |fps| fps.clamp(1, MAX_FPS));
+    Ok((frames.into_iter().map(|f| canvas::to_gray(&f.into_buffer().into())).collect(), fps))
+}
+
+/// The frames side by side, first on the left
+pub fn sprite_sheet(frames: &[RgbaImage]) -> RgbaImage {
+    let (width, height) = frames.first().map_or((0, 0), |f| f.dimensions());
+    let mut sheet = RgbaImage::from_pixel(width * frames.len() as u32, height, WHITE);
+    for (i, frame) in frames.iter().enumerate() {
+        image::imageops::replace(&mut sheet, frame, (i as u32 * width) as i64, 0);
+    }
+    sheet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::BLACK;
+
+    #[test]
+    fn test_frames_and_gif() {
+        let mut canvas = Canvas::new(8, 4);
+        let mut timeline = Timeline::new();
+        canvas.set_pixel(0, 0, BLACK);
+        timeline.add(&mut canvas, true);
+        canvas.set_pixel(1, 0, BLACK);
+        timeline.add(&mut canvas, false);
+        assert_eq!((timeline.len(), timeline.current), (3, 2));
+        timeline.go_to(&mut canvas, 0);
+        assert_eq!(*canvas.image.get_pixel(1, 0), WHITE);
+        timeline.delete(&mut canvas);
+        // The copy with two pixels is now first, and on the canvas
+        assert_eq!((timeline.len(), timeline.current), (2, 0));
+        assert_eq!(*canvas.image.get_pixel(1, 0), BLACK);
+
+        let frames = timeline.all(&canvas);
+        assert_eq!(sprite_sheet(&frames).dimensions(), (16, 4));
+        let path = std::env::temp_dir().join(format!("slowpaint-frames-{}.gif", std::process::id()));
+        write_gif(&frames, 12, &path).unwrap();
+        let (read, fps) = read_gif(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!((read.len(), fps), (2, 12));
+        assert_eq!((*read[0].get_pixel(1, 0), *read[1].get_pixel(0, 0)), (BLACK, WHITE));
+    }
+}
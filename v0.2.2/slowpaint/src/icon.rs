@@ -0,0 +1,149 @@
+//! Icon mode — small square canvases for the desktop and apps
+//!
+//! Icons are black-on-transparent PNGs kept in the source tree's icons/
+//! folder, one subfolder per kind, named icons_<name>.png, and embedded
+//! by apps with `include_bytes!`. On the canvas they're plain black and
+//! white; white turns see-through on export.
+
+use crate::tools::{BLACK, WHITE};
+use image::{GrayAlphaImage, LumaA, RgbaImage};
+use std::path::{Path, PathBuf};
+
+/// Icon sizes; the desktop draws its icons at 48
+pub const SIZES: [u32; 3] = [32, 48, 64];
+
+/// Subfolders of icons/, with the name shown for each
+pub const CATEGORIES: [(&str, &str); 5] = [
+    ("app_icons", "app"),
+    ("file_icons", "file"),
+    ("folder_icons", "folder"),
+    ("system_icons", "system"),
+    ("fun_icons", "fun"),
+];
+
+/// Zoom an icon of `size` is edited at
+pub fn zoom_for(size: u32) -> f32 {
+    (384 / size).max(1) as f32
+}
+
+/// The icon being edited and how it's shown
+pub struct IconMode {
+    pub size: u32,
+    pub show_template: bool,
+    /// Another icon shown faintly under the canvas, to match its weight
+    pub onion_skin: Option<(String, RgbaImage)>,
+    /// Export white pixels as see-through rather than opaque white
+    pub white_is_clear: bool,
+}
+
+impl IconMode {
+    pub fn new(size: u32) -> Self {
+        Self { size, show_template: true, onion_skin: None, white_is_clear: true }
+    }
+}
+
+/// The icons/ folder: the source tree's when slowPaint runs from inside
+/// one, otherwise one in Pictures laid out the same way
+pub fn icons_dir() -> PathBuf {
+    let starts = [
+        std::env::current_dir().ok(),
+        std::env::current_exe().ok().and_then(|e| e.parent().map(Path::to_path_buf)),
+    ];
+    starts
+        .iter()
+        .flatten()
+        .flat_map(|start| start.ancestors())
+        .map(|dir| dir.join("icons"))
+        .find(|dir| dir.join("app_icons").is_dir())
+        .unwrap_or_else(|| slowcore::storage::pictures_dir().join("icons"))
+}
+
+/// "icons_<name>.png", the name kept to letters, digits, `_` and `-`
+pub fn file_name(name: &str) -> String {
+    let name: String = name.trim().chars().map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' }).collect();
+    format!("icons_{}.png", name)
+}
+
+/// Icons in one category folder, by name
+pub fn icons_in(category: &str) -> Vec<(String, PathBuf)> {
+    let mut icons: Vec<(String, PathBuf)> = std::fs::read_dir(icons_dir().join(category))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.strip_prefix("icons_")?.strip_suffix(".png")?.to_string();
+            Some((name, path))
+        })
+        .collect();
+    icons.sort();
+    icons
+}
+
+/// An icon file as black and white on the canvas, see-through parts
+/// white, scaled to `size` if it isn't already
+pub fn load(path: &Path, size: u32) -> Result<RgbaImage, String> {
+    let img = image::open(path).map_err(|e| e.to_string())?.to_rgba8();
+    let mut img = RgbaImage::from_fn(img.width(), img.height(), |x, y| {
+        let [r, g, b, a] = img.get_pixel(x, y).0;
+        let luma = (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+        // Over white
+        let shown = (luma * a as u32 + 255 * (255 - a as u32)) / 255;
+        if shown < 128 { BLACK } else { WHITE }
+    });
+    if img.dimensions() != (size, size) {
+        img = image::imageops::resize(&img, size, size, image::imageops::FilterType::Nearest);
+    }
+    Ok(img)
+}
+
+/// The canvas as the icons/ folder keeps it: grey and alpha, black ink
+pub fn to_icon(image: &RgbaImage, white_is_clear: bool) -> GrayAlphaImage {
+    GrayAlphaImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, _] = image.get_pixel(x, y).0;
+        if (r as u32 + g as u32 + b as u32) / 3 < 128 {
+            LumaA([0, 255])
+        } else if white_is_clear {
+            LumaA([255, 0])
+        } else {
+            LumaA([255, 255])
+        }
+    })
+}
+
+/// Write the icon to icons/<category>/icons_<name>.png under `dir`
+pub fn export(image: &RgbaImage, dir: &Path, category: &str, name: &str, white_is_clear: bool) -> Result<PathBuf, String> {
+    if name.trim().is_empty() {
+        return Err("the icon needs a name".into());
+    }
+    let folder = dir.join(category);
+    std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+    let path = folder.join(file_name(name));
+    to_icon(image, white_is_clear)
+        .save_with_format(&path, image::ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_icon_export_roundtrip() {
+        let mut canvas = RgbaImage::from_pixel(32, 32, WHITE);
+        canvas.put_pixel(3, 4, BLACK);
+        let icon = to_icon(&canvas, true);
+        assert_eq!((icon.get_pixel(3, 4).0, icon.get_pixel(0, 0).0), ([0, 255], [255, 0]));
+        assert_eq!(to_icon(&canvas, false).get_pixel(0, 0).0, [255, 255]);
+
+        let dir = std::env::temp_dir().join(format!("slowpaint-icons-{}", std::process::id()));
+        let path = export(&canvas, &dir, "app_icons", "my app", true).unwrap();
+        assert!(path.ends_with("app_icons/icons_my_app.png"));
+        // Back in, see-through is white again, and it scales to a new size
+        let loaded = load(&path, 64).unwrap();
+        assert_eq!(loaded.dimensions(), (64, 64));
+        assert_eq!((*loaded.get_pixel(6, 8), *loaded.get_pixel(0, 0)), (BLACK, WHITE));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
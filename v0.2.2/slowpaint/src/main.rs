@@ -3,23 +3,23 @@
 //! Classic MacPaint-inspired pixel art and image editing.
 
 mod canvas;
+mod dynamics;
+mod frames;
+mod icon;
+mod shapes;
 mod tools;
 mod app;
 
 use app::SlowPaintApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let initial_file = std::env::args().nth(1).map(std::path::PathBuf::from);
 
-    let mut viewport = egui::ViewportBuilder::default()
-        .with_inner_size([740.0, 560.0])
+    let viewport = window_layout::viewport("slowpaint", Placement::RememberLast, [740.0, 560.0])
         .with_title("slowPaint");
 
-    if let Some(pos) = slowcore::cascade_position() {
-        viewport = viewport.with_position(pos);
-    }
-
     let options = NativeOptions {
         viewport,
         ..Default::default()
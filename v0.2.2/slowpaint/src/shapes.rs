@@ -0,0 +1,261 @@
+//! Shape library — polygons, arrows and stamps for the shapes tool
+//!
+//! Everything is placed by dragging out the box it fills (an arrow runs
+//! from where the drag starts to where it ends). Shapes are drawn as an
+//! outline the brush size wide, or filled with the fill pattern. Stamps
+//! are clip art: a few shaded pictures drawn here and dithered at whatever
+//! size they're dragged to, and the fun icons from icons/.
+
+use crate::icon;
+use image::{GrayAlphaImage, GrayImage, Luma, LumaA};
+use std::path::PathBuf;
+
+/// Inner points of a star, as a fraction of the outer ones
+const STAR_INNER: f32 = 0.4;
+
+/// What the shapes tool places
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shape {
+    Rectangle,
+    Ellipse,
+    Triangle,
+    Diamond,
+    Pentagon,
+    Hexagon,
+    Star,
+    Arrow,
+    /// One of the app's stamps, by index
+    Stamp(usize),
+}
+
+impl Shape {
+    /// Every shape but the stamps, in toolbar order
+    pub fn all() -> &'static [Shape] {
+        &[
+            Shape::Rectangle,
+            Shape::Ellipse,
+            Shape::Triangle,
+            Shape::Diamond,
+            Shape::Pentagon,
+            Shape::Hexagon,
+            Shape::Star,
+            Shape::Arrow,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Rectangle => "rect",
+            Shape::Ellipse => "oval",
+            Shape::Triangle => "triangle",
+            Shape::Diamond => "diamond",
+            Shape::Pentagon => "pentagon",
+            Shape::Hexagon => "hexagon",
+            Shape::Star => "star",
+            Shape::Arrow => "arrow",
+            Shape::Stamp(_) => "stamp",
+        }
+    }
+
+    /// Corners of the shape dragged from `start` to `end`, or `None` for
+    /// the ones that aren't polygons (rectangles, ellipses and stamps)
+    pub fn polygon(&self, start: (f32, f32), end: (f32, f32)) -> Option<Vec<(f32, f32)>> {
+        let (x0, y0) = (start.0.min(end.0), start.1.min(end.1));
+        let (w, h) = ((end.0 - start.0).abs(), (end.1 - start.1).abs());
+        // From fractions of the box
+        let place = |points: &[(f32, f32)]| points.iter().map(|(u, v)| (x0 + u * w, y0 + v * h)).collect();
+        let points = match self {
+            Shape::Triangle => place(&[(0.5, 0.0), (1.0, 1.0), (0.0, 1.0)]),
+            Shape::Diamond => place(&[(0.5, 0.0), (1.0, 0.5), (0.5, 1.0), (0.0, 0.5)]),
+            Shape::Pentagon => place(&ring(5, &[1.0])),
+            Shape::Hexagon => place(&ring(6, &[1.0])),
+            Shape::Star => place(&ring(10, &[1.0, STAR_INNER])),
+            Shape::Arrow => arrow(start, end),
+            Shape::Rectangle | Shape::Ellipse | Shape::Stamp(_) => return None,
+        };
+        Some(points)
+    }
+}
+
+/// `n` points round the unit box, the first at the top, their distances
+/// from the middle taken in turn from `radii`
+fn ring(n: usize, radii: &[f32]) -> Vec<(f32, f32)> {
+    (0..n)
+        .map(|i| {
+            let angle = (i as f32 / n as f32 * 360.0 - 90.0).to_radians();
+            let r = radii[i % radii.len()] * 0.5;
+            (0.5 + r * angle.cos(), 0.5 + r * angle.sin())
+        })
+        .collect()
+}
+
+/// An arrow from `start` pointing at `end`: a shaft and a head a third of
+/// its length
+fn arrow(start: (f32, f32), end: (f32, f32)) -> Vec<(f32, f32)> {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = (dx * dx + dy * dy).sqrt().max(1.0);
+    let (ux, uy) = (dx / length, dy / length);
+    // Across the arrow
+    let (nx, ny) = (-uy, ux);
+    let head = length / 3.0;
+    let (shaft, barb) = (head / 4.0, head / 2.0);
+    let neck = (end.0 - ux * head, end.1 - uy * head);
+    let at = |p: (f32, f32), side: f32| (p.0 + nx * side, p.1 + ny * side);
+    vec![
+        at(start, shaft),
+        at(neck, shaft),
+        at(neck, barb),
+        end,
+        at(neck, -barb),
+        at(neck, -shaft),
+        at(start, -shaft),
+    ]
+}
+
+/// Clip art drawn here, shaded and then dithered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Clipart {
+    Ball,
+    Heart,
+    Cloud,
+    Moon,
+    Drop,
+}
+
+impl Clipart {
+    const ALL: [Clipart; 5] = [Clipart::Ball, Clipart::Heart, Clipart::Cloud, Clipart::Moon, Clipart::Drop];
+
+    fn name(self) -> &'static str {
+        match self {
+            Clipart::Ball => "ball",
+            Clipart::Heart => "heart",
+            Clipart::Cloud => "cloud",
+            Clipart::Moon => "moon",
+            Clipart::Drop => "drop",
+        }
+    }
+
+    /// Whether the point `x`, `y` (-1.0 to 1.0 across the box, down the
+    /// way) is inside, and if so its grey
+    fn shade(self, x: f32, y: f32) -> Option<f32> {
+        // Lit from the top left
+        let lit = |x: f32, y: f32| (0.75 - 0.35 * (x + y)).clamp(0.0, 1.0);
+        let inside = match self {
+            Clipart::Ball => x * x + y * y <= 1.0,
+            Clipart::Heart => {
+                // (x² + y² - 1)³ ≤ x²y³, upright and filling the box
+                let (x, y) = (x * 1.15, 0.125 - y * 1.125);
+                (x * x + y * y - 1.0).powi(3) <= x * x * y * y * y
+            }
+            Clipart::Cloud => {
+                let puff = |cx: f32, cy: f32, r: f32| (x - cx).powi(2) + (y - cy).powi(2) <= r * r;
+                puff(-0.5, 0.25, 0.5) || puff(0.05, -0.1, 0.65) || puff(0.55, 0.3, 0.45) || ((0.25..=0.75).contains(&y) && x.abs() <= 0.55)
+            }
+            Clipart::Moon => x * x + y * y <= 1.0 && (x - 0.45).powi(2) + (y + 0.2).powi(2) > 0.6,
+            Clipart::Drop => {
+                // A round bottom and a point at the top
+                let bottom = x * x + (y - 0.35).powi(2) <= 0.42;
+                let top = (-1.0..=0.35).contains(&y) && x.abs() <= 0.65 * (y + 1.0) / 1.35;
+                bottom || top
+            }
+        };
+        if !inside {
+            return None;
+        }
+        Some(match self {
+            // A highlight, falling off round the edge
+            Clipart::Ball => {
+                let d = ((x + 0.35).powi(2) + (y + 0.35).powi(2)).sqrt();
+                (1.0 - d * 0.75).clamp(0.0, 1.0)
+            }
+            Clipart::Cloud => 0.6 + 0.4 * lit(x, y),
+            _ => lit(x, y),
+        })
+    }
+
+    /// The picture at `width`×`height`, with a black outline
+    fn render(self, width: u32, height: u32) -> GrayAlphaImage {
+        let at = |x: u32, y: u32| {
+            let u = (x as f32 + 0.5) / width as f32 * 2.0 - 1.0;
+            let v = (y as f32 + 0.5) / height as f32 * 2.0 - 1.0;
+            self.shade(u, v)
+        };
+        let mut grey = GrayImage::from_fn(width, height, |x, y| Luma([at(x, y).map_or(255, |g| (g * 255.0) as u8)]));
+        slowcore::dither::atkinson(&mut grey);
+        let inside = |x: i64, y: i64| x >= 0 && y >= 0 && x < width as i64 && y < height as i64 && at(x as u32, y as u32).is_some();
+        GrayAlphaImage::from_fn(width, height, |x, y| {
+            let (xi, yi) = (x as i64, y as i64);
+            if !inside(xi, yi) {
+                return LumaA([255, 0]);
+            }
+            let edge = !(inside(xi - 1, yi) && inside(xi + 1, yi) && inside(xi, yi - 1) && inside(xi, yi + 1));
+            LumaA([if edge { 0 } else { grey.get_pixel(x, y).0[0] }, 255])
+        })
+    }
+}
+
+/// A picture the shapes tool can stamp
+#[derive(Clone, Debug)]
+pub enum Stamp {
+    Clipart(Clipart),
+    /// An icon file, its see-through parts left alone
+    Icon { name: String, path: PathBuf },
+}
+
+impl Stamp {
+    pub fn name(&self) -> &str {
+        match self {
+            Stamp::Clipart(art) => art.name(),
+            Stamp::Icon { name, .. } => name,
+        }
+    }
+
+    /// The stamp at `width`×`height`: grey for black or white, alpha for
+    /// whether it's painted at all
+    pub fn render(&self, width: u32, height: u32) -> Option<GrayAlphaImage> {
+        if width == 0 || height == 0 {
+            return None;
+        }
+        match self {
+            Stamp::Clipart(art) => Some(art.render(width, height)),
+            Stamp::Icon { path, .. } => {
+                let picture = image::open(path).ok()?.to_luma_alpha8();
+                let mut picture = image::imageops::resize(&picture, width, height, image::imageops::FilterType::Nearest);
+                for pixel in picture.pixels_mut() {
+                    let [luma, alpha] = pixel.0;
+                    *pixel = LumaA([if luma < 128 { 0 } else { 255 }, if alpha < 128 { 0 } else { 255 }]);
+                }
+                Some(picture)
+            }
+        }
+    }
+}
+
+/// The built-in clip art, then the fun icons
+pub fn stamps() -> Vec<Stamp> {
+    let icons = icon::icons_in("fun_icons").into_iter().map(|(name, path)| Stamp::Icon { name, path });
+    Clipart::ALL.into_iter().map(Stamp::Clipart).chain(icons).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shapes_and_clipart() {
+        let star = Shape::Star.polygon((0.0, 0.0), (100.0, 100.0)).unwrap();
+        assert_eq!(star.len(), 10);
+        // The first point is at the top middle
+        assert_eq!((star[0].0.round(), star[0].1.round()), (50.0, 0.0));
+        assert!(Shape::Rectangle.polygon((0.0, 0.0), (10.0, 10.0)).is_none());
+        // An arrow ends at the drag's end
+        assert!(Shape::Arrow.polygon((0.0, 0.0), (90.0, 0.0)).unwrap().contains(&(90.0, 0.0)));
+
+        let ball = Stamp::Clipart(Clipart::Ball).render(32, 32).unwrap();
+        // The corners are left alone, the middle is shaded in black and white
+        assert_eq!(ball.get_pixel(0, 0).0[1], 0);
+        let inside: Vec<u8> = ball.pixels().filter(|p| p.0[1] == 255).map(|p| p.0[0]).collect();
+        assert!(inside.contains(&0) && inside.contains(&255));
+        assert!(inside.iter().all(|&g| g == 0 || g == 255));
+    }
+}
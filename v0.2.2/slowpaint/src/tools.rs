@@ -18,6 +18,8 @@ pub enum Tool {
     FilledRectangle,
     Ellipse,
     FilledEllipse,
+    /// Polygons, arrows and stamps from the shape library
+    Shapes,
     Fill,
 }
 
@@ -31,6 +33,7 @@ impl Tool {
             Tool::FilledRectangle => "filled rect",
             Tool::Ellipse => "ellipse",
             Tool::FilledEllipse => "filled ellipse",
+            Tool::Shapes => "shapes",
             Tool::Fill => "fill",
         }
     }
@@ -44,6 +47,7 @@ impl Tool {
             Tool::FilledRectangle => "f.rect",
             Tool::Ellipse => "oval",
             Tool::FilledEllipse => "f.oval",
+            Tool::Shapes => "shapes",
             Tool::Fill => "fill",
         }
     }
@@ -58,6 +62,7 @@ impl Tool {
             Tool::FilledRectangle,
             Tool::Ellipse,
             Tool::FilledEllipse,
+            Tool::Shapes,
             Tool::Fill,
         ]
     }
@@ -71,7 +76,7 @@ impl Tool {
     pub fn is_shape(&self) -> bool {
         matches!(
             self,
-            Tool::Line | Tool::Rectangle | Tool::FilledRectangle | Tool::Ellipse | Tool::FilledEllipse
+            Tool::Line | Tool::Rectangle | Tool::FilledRectangle | Tool::Ellipse | Tool::FilledEllipse | Tool::Shapes
         )
     }
 }
@@ -108,6 +113,89 @@ impl BrushSize {
     }
 }
 
+/// Mirror modes for the brush: each stroke is painted again reflected
+/// across the canvas's middle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mirror {
+    Off,
+    /// Left to right, across an upright axis
+    Horizontal,
+    /// Top to bottom, across a level axis
+    Vertical,
+    /// Both, four strokes at once
+    Quad,
+}
+
+impl Mirror {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mirror::Off => "no mirror",
+            Mirror::Horizontal => "mirror left to right",
+            Mirror::Vertical => "mirror top to bottom",
+            Mirror::Quad => "mirror four ways",
+        }
+    }
+
+    /// The axes, as drawn over the canvas
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Mirror::Off => "off",
+            Mirror::Horizontal => "|",
+            Mirror::Vertical => "—",
+            Mirror::Quad => "+",
+        }
+    }
+
+    pub fn all() -> &'static [Mirror] {
+        &[Mirror::Off, Mirror::Horizontal, Mirror::Vertical, Mirror::Quad]
+    }
+
+    pub fn flips_x(&self) -> bool {
+        matches!(self, Mirror::Horizontal | Mirror::Quad)
+    }
+
+    pub fn flips_y(&self) -> bool {
+        matches!(self, Mirror::Vertical | Mirror::Quad)
+    }
+}
+
+/// A brush segment, from one canvas point to another
+pub type Segment = ((i32, i32), (i32, i32));
+
+/// Every place the brush segment `stroke` is painted on a `width`×`height`
+/// canvas: reflected as `mirror` says, and with `tile` on, again on the far
+/// side of any edge it runs over, so the picture repeats without a seam.
+/// `reach` is how far the brush paints from its centre.
+pub fn brush_copies(stroke: Segment, mirror: Mirror, tile: bool, width: u32, height: u32, reach: i32) -> Vec<Segment> {
+    let (w, h) = (width as i32, height as i32);
+    let flip_x = |((ax, ay), (bx, by)): Segment| ((w - 1 - ax, ay), (w - 1 - bx, by));
+    let flip_y = |((ax, ay), (bx, by)): Segment| ((ax, h - 1 - ay), (bx, h - 1 - by));
+    let mut copies = vec![stroke];
+    if mirror.flips_x() {
+        copies.push(flip_x(stroke));
+    }
+    if mirror.flips_y() {
+        copies.extend(copies.clone().into_iter().map(flip_y));
+    }
+    if !tile {
+        return copies;
+    }
+    let mut tiled = Vec::new();
+    for ((ax, ay), (bx, by)) in copies {
+        for ox in [-w, 0, w] {
+            for oy in [-h, 0, h] {
+                // Only where the shifted segment, brush and all, reaches the canvas
+                let (x0, x1) = (ax.min(bx) + ox - reach, ax.max(bx) + ox + reach);
+                let (y0, y1) = (ay.min(by) + oy - reach, ay.max(by) + oy + reach);
+                if x1 >= 0 && x0 < w && y1 >= 0 && y0 < h {
+                    tiled.push(((ax + ox, ay + oy), (bx + ox, by + oy)));
+                }
+            }
+        }
+    }
+    tiled
+}
+
 /// Fill pattern options (classic MacPaint style)
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Pattern {
@@ -158,3 +246,25 @@ impl Pattern {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brush_copies() {
+        let dab = ((10, 20), (10, 20));
+        assert_eq!(brush_copies(dab, Mirror::Off, false, 100, 50, 2), vec![dab]);
+        assert_eq!(
+            brush_copies(dab, Mirror::Quad, false, 100, 50, 2),
+            vec![dab, ((89, 20), (89, 20)), ((10, 29), (10, 29)), ((89, 29), (89, 29))]
+        );
+        // Away from the edges a tiled dab is painted once; over the right
+        // edge it comes back on the left
+        assert_eq!(brush_copies(dab, Mirror::Off, true, 100, 50, 2).len(), 1);
+        let edge = ((99, 20), (99, 20));
+        assert_eq!(brush_copies(edge, Mirror::Off, true, 100, 50, 2), vec![((-1, 20), (-1, 20)), edge]);
+        // In a corner it comes back in all four
+        assert_eq!(brush_copies(((0, 0), (0, 0)), Mirror::Off, true, 100, 50, 2).len(), 4);
+    }
+}
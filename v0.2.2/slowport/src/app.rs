@@ -0,0 +1,526 @@
+//! slowPort application
+
+use crate::document::{self, Line};
+use crate::net::{self, FetchError, KnownHosts, Response};
+use crate::places::Places;
+use egui::{Align2, CentralPanel, Context, Key, TopBottomPanel, Vec2};
+use slowcore::repaint::RepaintController;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, SlowButton, WindowAction};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowPort",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "a browser for the small internet",
+    features: &["gemini and gopher", "trust-on-first-use certificates", "bookmarks and history", "downloads open in files"],
+    ..AboutInfo::EMPTY
+};
+
+/// How a finished load changes back/forward history
+#[derive(Clone, Copy, PartialEq)]
+enum Nav {
+    /// A new page: the current one goes on the back stack
+    Push,
+    /// Back, forward or reload: the stacks were already updated
+    Replace,
+}
+
+/// A server asking for a line of input
+struct InputPrompt {
+    url: Url,
+    prompt: String,
+    sensitive: bool,
+    text: String,
+}
+
+/// A gemini host whose certificate no longer matches the one we trusted
+struct CertChanged {
+    host: String,
+    fingerprint: String,
+    url: String,
+}
+
+struct Loading {
+    url: String,
+    nav: Nav,
+    result: Receiver<Result<Response, FetchError>>,
+}
+
+pub struct SlowPortApp {
+    ctx: Context,
+    address: String,
+    /// None while showing the start page
+    current: Option<String>,
+    lines: Vec<Line>,
+    title: String,
+    back: Vec<Option<String>>,
+    forward: Vec<Option<String>>,
+    places: Places,
+    known_hosts: Arc<Mutex<KnownHosts>>,
+    loading: Option<Loading>,
+    input: Option<InputPrompt>,
+    cert_changed: Option<CertChanged>,
+    last_download: Option<PathBuf>,
+    message: Option<String>,
+    focus_address: bool,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowPortApp {
+    pub fn new(cc: &eframe::CreationContext<'_>, start: Option<String>) -> Self {
+        let mut app = Self {
+            ctx: cc.egui_ctx.clone(),
+            address: String::new(),
+            current: None,
+            lines: Vec::new(),
+            title: "slowPort".into(),
+            back: Vec::new(),
+            forward: Vec::new(),
+            places: Places::load(),
+            known_hosts: Arc::new(Mutex::new(KnownHosts::load())),
+            loading: None,
+            input: None,
+            cert_changed: None,
+            last_download: None,
+            message: None,
+            focus_address: false,
+            show_about: false,
+            repaint: RepaintController::new(),
+        };
+        app.show_start_page();
+        if let Some(url) = start {
+            app.open(&url, Nav::Push);
+        }
+        app
+    }
+
+    /// The built-in page shown at launch and by "home"
+    fn show_start_page(&mut self) {
+        let mut lines = vec![
+            Line::Heading(1, "slowPort".into()),
+            Line::Text("a browser for the small internet: gemini and gopher.".into()),
+            Line::Text(String::new()),
+            Line::Heading(2, "bookmarks".into()),
+        ];
+        if self.places.bookmarks.is_empty() {
+            lines.push(Line::Text("no bookmarks yet. press \u{2318}D on a page to add one.".into()));
+        }
+        for b in &self.places.bookmarks {
+            lines.push(Line::Link { url: b.url.clone(), label: b.title.clone() });
+        }
+        self.lines = lines;
+        self.current = None;
+        self.address.clear();
+        self.title = "slowPort".into();
+    }
+
+    /// Start loading `address` in the background
+    fn open(&mut self, address: &str, nav: Nav) {
+        let address = address.trim();
+        if address.is_empty() {
+            return;
+        }
+        let url = if address.contains("://") { address.to_string() } else { format!("gemini://{}", address) };
+        self.address = url.clone();
+        self.message = None;
+
+        let (tx, rx) = mpsc::channel();
+        let known_hosts = self.known_hosts.clone();
+        let ctx = self.ctx.clone();
+        let target = url.clone();
+        std::thread::spawn(move || {
+            let result = match known_hosts.lock() {
+                Ok(mut hosts) => net::fetch(&target, &mut hosts),
+                Err(_) => Err(FetchError::Network("internal error".into())),
+            };
+            let _ = tx.send(result);
+            ctx.request_repaint();
+        });
+        self.loading = Some(Loading { url, nav, result: rx });
+    }
+
+    fn poll_loading(&mut self) {
+        let Some(loading) = &self.loading else { return };
+        let Ok(result) = loading.result.try_recv() else { return };
+        let Some(loading) = self.loading.take() else { return };
+
+        match result {
+            Ok(Response::Page { url, lines }) => {
+                if loading.nav == Nav::Push && self.current.as_deref() != Some(url.as_str()) {
+                    self.back.push(self.current.clone());
+                    self.forward.clear();
+                }
+                self.title = document::title(&lines).unwrap_or_else(|| url.host_str().unwrap_or("").to_string());
+                self.lines = lines;
+                self.address = url.to_string();
+                self.current = Some(url.to_string());
+                self.places.visit(url.as_str(), &self.title);
+            }
+            Ok(Response::Input { url, prompt, sensitive }) => {
+                self.input = Some(InputPrompt { url, prompt, sensitive, text: String::new() });
+            }
+            Ok(Response::Download { name, bytes, .. }) => self.save_download(&name, &bytes),
+            Err(FetchError::CertificateChanged { host, fingerprint }) => {
+                self.cert_changed = Some(CertChanged { host, fingerprint, url: loading.url });
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                self.address = self.current.clone().unwrap_or_default();
+            }
+        }
+    }
+
+    fn save_download(&mut self, name: &str, bytes: &[u8]) {
+        let dir = slowcore::storage::downloads_dir();
+        let (stem, ext) = match name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), format!(".{}", ext)),
+            _ => (name.to_string(), String::new()),
+        };
+        let mut path = dir.join(name);
+        let mut n = 2;
+        while path.exists() {
+            path = dir.join(format!("{} {}{}", stem, n, ext));
+            n += 1;
+        }
+        match std::fs::write(&path, bytes) {
+            Ok(()) => {
+                let saved = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                self.message = Some(format!("saved {} to Downloads", saved));
+                slowcore::notify::post_with_reveal("slowport", &format!("downloaded {}", saved), &path);
+                self.last_download = Some(path);
+            }
+            Err(e) => self.message = Some(format!("could not save {}: {}", name, e)),
+        }
+        self.address = self.current.clone().unwrap_or_default();
+    }
+
+    /// Hand the downloads folder to slowFiles
+    fn show_downloads(&self) {
+        let dir = self
+            .last_download
+            .as_ref()
+            .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+            .unwrap_or_else(slowcore::storage::downloads_dir);
+        let sibling = std::env::current_exe().ok().and_then(|e| e.parent().map(|p| p.join("slowfiles")));
+        let binary = sibling.filter(|p| p.exists()).unwrap_or_else(|| PathBuf::from("slowfiles"));
+        let _ = std::process::Command::new(binary)
+            .arg(&dir)
+            .env("SLOWOS_MANAGED", "1")
+            .stdin(std::process::Stdio::null())
+            .spawn();
+    }
+
+    fn go_back(&mut self) {
+        if let Some(prev) = self.back.pop() {
+            self.forward.push(self.current.clone());
+            self.go_to(prev);
+        }
+    }
+
+    fn go_forward(&mut self) {
+        if let Some(next) = self.forward.pop() {
+            self.back.push(self.current.clone());
+            self.go_to(next);
+        }
+    }
+
+    fn go_to(&mut self, place: Option<String>) {
+        match place {
+            Some(url) => self.open(&url, Nav::Replace),
+            None => self.show_start_page(),
+        }
+    }
+
+    fn reload(&mut self) {
+        match self.current.clone() {
+            Some(url) => self.open(&url, Nav::Replace),
+            None => self.show_start_page(),
+        }
+    }
+
+    fn go_home(&mut self) {
+        if self.current.is_some() {
+            self.back.push(self.current.clone());
+            self.forward.clear();
+        }
+        self.show_start_page();
+    }
+
+    fn toggle_bookmark(&mut self) {
+        let Some(url) = self.current.clone() else { return };
+        self.places.toggle_bookmark(&url, &self.title);
+        self.message = Some(if self.places.is_bookmarked(&url) { "bookmarked".into() } else { "bookmark removed".into() });
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        let (cmd, key) = ctx.input(|i| {
+            let key = [Key::L, Key::R, Key::D, Key::OpenBracket, Key::CloseBracket]
+                .into_iter()
+                .find(|k| i.key_pressed(*k));
+            (i.modifiers.command, key)
+        });
+        if !cmd {
+            return;
+        }
+        match key {
+            Some(Key::L) => self.focus_address = true,
+            Some(Key::R) => self.reload(),
+            Some(Key::D) => self.toggle_bookmark(),
+            Some(Key::OpenBracket) => self.go_back(),
+            Some(Key::CloseBracket) => self.go_forward(),
+            _ => {}
+        }
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("file", |ui| {
+                    if ui.button("open location  ⌘L").clicked() {
+                        self.focus_address = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("reload         ⌘R").clicked() {
+                        self.reload();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("show downloads").clicked() {
+                        self.show_downloads();
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("go", |ui| {
+                    if ui.add_enabled(!self.back.is_empty(), egui::Button::new("back     ⌘[")).clicked() {
+                        self.go_back();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(!self.forward.is_empty(), egui::Button::new("forward  ⌘]")).clicked() {
+                        self.go_forward();
+                        ui.close_menu();
+                    }
+                    if ui.button("home").clicked() {
+                        self.go_home();
+                        ui.close_menu();
+                    }
+                    if !self.places.history.is_empty() {
+                        ui.separator();
+                        for place in self.places.history.clone() {
+                            if ui.button(&place.title).on_hover_text(&place.url).clicked() {
+                                self.open(&place.url, Nav::Push);
+                                ui.close_menu();
+                            }
+                        }
+                    }
+                });
+                ui.menu_button("bookmarks", |ui| {
+                    let bookmarked = self.current.as_ref().is_some_and(|u| self.places.is_bookmarked(u));
+                    let label = if bookmarked { "remove bookmark  ⌘D" } else { "add bookmark     ⌘D" };
+                    if ui.add_enabled(self.current.is_some(), egui::Button::new(label)).clicked() {
+                        self.toggle_bookmark();
+                        ui.close_menu();
+                    }
+                    if !self.places.bookmarks.is_empty() {
+                        ui.separator();
+                        for place in self.places.bookmarks.clone() {
+                            if ui.button(&place.title).on_hover_text(&place.url).clicked() {
+                                self.open(&place.url, Nav::Push);
+                                ui.close_menu();
+                            }
+                        }
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowport", "slowPort");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_address_bar(&mut self, ctx: &Context) {
+        TopBottomPanel::top("address_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!self.back.is_empty(), SlowButton::new("\u{2190}")).clicked() {
+                    self.go_back();
+                }
+                if ui.add_enabled(!self.forward.is_empty(), SlowButton::new("\u{2192}")).clicked() {
+                    self.go_forward();
+                }
+                let resp = ui.add(
+                    egui::TextEdit::singleline(&mut self.address)
+                        .hint_text("gemini:// or gopher:// address")
+                        .desired_width(f32::INFINITY),
+                );
+                if self.focus_address {
+                    resp.request_focus();
+                    self.focus_address = false;
+                }
+                if resp.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    let address = self.address.clone();
+                    self.open(&address, Nav::Push);
+                }
+            });
+        });
+    }
+
+    fn draw_input_prompt(&mut self, ctx: &Context) {
+        let Some(input) = &mut self.input else { return };
+        let mut submit = false;
+        let mut cancel = false;
+        let resp = egui::Window::new("input")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(320.0)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(if input.prompt.is_empty() { "this page asks for input" } else { input.prompt.as_str() });
+                ui.add_space(4.0);
+                let field = ui.add(
+                    egui::TextEdit::singleline(&mut input.text)
+                        .password(input.sensitive)
+                        .desired_width(f32::INFINITY),
+                );
+                field.request_focus();
+                if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    submit = true;
+                }
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        cancel = true;
+                    }
+                    if ui.button("send").clicked() {
+                        submit = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+
+        if submit {
+            if let Some(input) = self.input.take() {
+                let url = net::with_query(&input.url, &input.text);
+                self.open(url.as_str(), Nav::Push);
+            }
+        } else if cancel {
+            self.input = None;
+            self.address = self.current.clone().unwrap_or_default();
+        }
+    }
+
+    fn draw_cert_changed(&mut self, ctx: &Context) {
+        let Some(changed) = &self.cert_changed else { return };
+        let mut trust = false;
+        let mut cancel = false;
+        let resp = egui::Window::new("certificate changed")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(340.0)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("{} is showing a different certificate from last time.", changed.host));
+                ui.label("capsules renew their certificates now and then, but this can also mean someone is intercepting the connection.");
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        cancel = true;
+                    }
+                    if ui.button("trust new certificate").clicked() {
+                        trust = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+
+        if trust {
+            if let Some(changed) = self.cert_changed.take() {
+                if let Ok(mut hosts) = self.known_hosts.lock() {
+                    hosts.trust(&changed.host, &changed.fingerprint);
+                }
+                self.open(&changed.url, Nav::Push);
+            }
+        } else if cancel {
+            self.cert_changed = None;
+            self.address = self.current.clone().unwrap_or_default();
+        }
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+impl eframe::App for SlowPortApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowport") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.poll_loading();
+        self.handle_keys(ctx);
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::top("title_bar").show(ctx, |ui| {
+            slowcore::theme::SlowTheme::title_bar_frame().show(ui, |ui| {
+                ui.centered_and_justified(|ui| {
+                    ui.label(&self.title);
+                });
+            });
+        });
+        self.draw_address_bar(ctx);
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = match (&self.loading, &self.message) {
+                (Some(loading), _) => format!("loading {}...", loading.url),
+                (None, Some(message)) => message.clone(),
+                (None, None) => self.current.clone().unwrap_or_else(|| "start page".into()),
+            };
+            status_bar(ui, &status);
+        });
+
+        let mut clicked = None;
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(12.0)))
+            .show(ctx, |ui| {
+                egui::ScrollArea::both()
+                    .id_source(self.current.clone().unwrap_or_default())
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        clicked = crate::render::draw_page(ui, &self.lines);
+                    });
+            });
+        if let Some(url) = clicked {
+            self.open(&url, Nav::Push);
+        }
+
+        self.draw_input_prompt(ctx);
+        self.draw_cert_changed(ctx);
+        self.draw_about(ctx);
+        self.repaint.end_frame(ctx);
+    }
+}
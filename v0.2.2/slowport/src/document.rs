@@ -0,0 +1,141 @@
+//! Pages as a list of lines
+//!
+//! Gemtext is already line-based, and gopher menus and text files map onto
+//! the same handful of line kinds, so one renderer draws everything.
+
+use url::Url;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Line {
+    Text(String),
+    /// Level 1 to 3
+    Heading(u8, String),
+    Link { url: String, label: String },
+    ListItem(String),
+    Quote(String),
+    /// Inside a ``` block, drawn in monospace and never wrapped
+    Preformatted(String),
+}
+
+/// The first heading, for the window title and bookmarks
+pub fn title(lines: &[Line]) -> Option<String> {
+    lines.iter().find_map(|line| match line {
+        Line::Heading(_, text) if !text.is_empty() => Some(text.clone()),
+        _ => None,
+    })
+}
+
+/// Parse a text/gemini body. Relative links are resolved against `base`.
+pub fn parse_gemtext(body: &str, base: &Url) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut preformatted = false;
+    for raw in body.lines() {
+        let raw = raw.trim_end_matches('\r');
+        if raw.starts_with("```") {
+            preformatted = !preformatted;
+            continue;
+        }
+        if preformatted {
+            lines.push(Line::Preformatted(raw.to_string()));
+        } else if let Some(rest) = raw.strip_prefix("=>") {
+            let rest = rest.trim();
+            let (target, label) = match rest.split_once(char::is_whitespace) {
+                Some((target, label)) => (target, label.trim()),
+                None => (rest, ""),
+            };
+            let url = base.join(target).map(|u| u.to_string()).unwrap_or_else(|_| target.to_string());
+            let label = if label.is_empty() { target.to_string() } else { label.to_string() };
+            lines.push(Line::Link { url, label });
+        } else if let Some(rest) = raw.strip_prefix("###") {
+            lines.push(Line::Heading(3, rest.trim().to_string()));
+        } else if let Some(rest) = raw.strip_prefix("##") {
+            lines.push(Line::Heading(2, rest.trim().to_string()));
+        } else if let Some(rest) = raw.strip_prefix('#') {
+            lines.push(Line::Heading(1, rest.trim().to_string()));
+        } else if let Some(rest) = raw.strip_prefix("* ") {
+            lines.push(Line::ListItem(rest.to_string()));
+        } else if let Some(rest) = raw.strip_prefix('>') {
+            lines.push(Line::Quote(rest.trim().to_string()));
+        } else {
+            lines.push(Line::Text(raw.to_string()));
+        }
+    }
+    lines
+}
+
+/// Parse a gopher menu (item types 1 and 7)
+pub fn parse_gopher_menu(body: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    for raw in body.lines() {
+        let raw = raw.trim_end_matches('\r');
+        if raw == "." {
+            break;
+        }
+        let mut chars = raw.chars();
+        let Some(kind) = chars.next() else {
+            lines.push(Line::Text(String::new()));
+            continue;
+        };
+        let fields: Vec<&str> = chars.as_str().split('\t').collect();
+        let display = fields[0].to_string();
+        match (kind, fields.as_slice()) {
+            ('i', _) | ('3', _) => lines.push(Line::Text(display)),
+            // "URL:" selectors point outside gopherspace
+            ('h', [_, selector, ..]) if selector.starts_with("URL:") => {
+                lines.push(Line::Link { url: selector[4..].to_string(), label: display });
+            }
+            (_, [_, selector, host, port, ..]) => {
+                let port = port.trim();
+                let port = if port == "70" || port.is_empty() { String::new() } else { format!(":{}", port) };
+                let url = format!("gopher://{}{}/{}{}", host, port, kind, selector);
+                lines.push(Line::Link { url, label: display });
+            }
+            _ => lines.push(Line::Text(display)),
+        }
+    }
+    lines
+}
+
+/// Any other text: one line each, shown in monospace since plain text
+/// from gopher is usually laid out for a fixed-width terminal
+pub fn parse_plain(body: &str) -> Vec<Line> {
+    body.lines()
+        .map(|l| l.trim_end_matches('\r'))
+        // Gopher text files end with a lone "."
+        .take_while(|l| *l != ".")
+        .map(|l| Line::Preformatted(l.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gemtext() {
+        let base = Url::parse("gemini://example.org/dir/page.gmi").unwrap();
+        let body = "# hello\n=> other.gmi  next page\n=> gemini://x.org/\n* item\n> quoted\n```\n# not a heading\n```\nplain";
+        let lines = parse_gemtext(body, &base);
+        assert_eq!(lines, vec![
+            Line::Heading(1, "hello".into()),
+            Line::Link { url: "gemini://example.org/dir/other.gmi".into(), label: "next page".into() },
+            Line::Link { url: "gemini://x.org/".into(), label: "gemini://x.org/".into() },
+            Line::ListItem("item".into()),
+            Line::Quote("quoted".into()),
+            Line::Preformatted("# not a heading".into()),
+            Line::Text("plain".into()),
+        ]);
+        assert_eq!(title(&lines).as_deref(), Some("hello"));
+    }
+
+    #[test]
+    fn test_gopher_menu() {
+        let body = "iwelcome\tfake\t(NULL)\t0\r\n1phlog\t/phlog\tsdf.org\t70\r\n0about\t/about.txt\tsdf.org\t7070\r\nhweb\tURL:https://sdf.org\tsdf.org\t70\r\n.\r\n";
+        assert_eq!(parse_gopher_menu(body), vec![
+            Line::Text("welcome".into()),
+            Line::Link { url: "gopher://sdf.org/1/phlog".into(), label: "phlog".into() },
+            Line::Link { url: "gopher://sdf.org:7070/0/about.txt".into(), label: "about".into() },
+            Line::Link { url: "https://sdf.org".into(), label: "web".into() },
+        ]);
+    }
+}
@@ -0,0 +1,34 @@
+//! slowPort - A gemini and gopher browser for the Slow Computer
+//!
+//! Text-first pages from the small internet, drawn in black and white.
+
+mod app;
+mod document;
+mod net;
+mod places;
+mod render;
+
+use app::SlowPortApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let start = std::env::args().nth(1);
+
+    let viewport = window_layout::viewport("slowport", Placement::RememberLast, [640.0, 520.0])
+        .with_title("slowPort");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowPort",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowPortApp::new(cc, start))
+        }),
+    )
+}
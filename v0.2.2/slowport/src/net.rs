@@ -0,0 +1,359 @@
+//! Gemini and gopher clients
+//!
+//! Both are one request per connection: send a line, read until the
+//! server closes. Gemini runs over TLS with trust-on-first-use — most
+//! capsules use self-signed certificates, so instead of checking a CA
+//! chain we remember each host's certificate fingerprint the first time
+//! and refuse to continue if it later changes.
+
+use crate::document::{self, Line};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, ClientConnection, DigitallySignedStruct, SignatureScheme, StreamOwned};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const READ_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Responses larger than this are cut off
+const MAX_RESPONSE: u64 = 16 * 1024 * 1024;
+
+/// Gemini allows up to 5 redirects in a row
+const MAX_REDIRECTS: usize = 5;
+
+/// What a request came back with
+#[derive(Debug, Clone)]
+pub enum Response {
+    Page { url: Url, lines: Vec<Line> },
+    /// The server wants a line of input (gemini 1x, or a gopher search)
+    Input { url: Url, prompt: String, sensitive: bool },
+    /// Anything that isn't text, handed to slowFiles once saved
+    Download { url: Url, name: String, bytes: Vec<u8> },
+}
+
+#[derive(Debug, Clone)]
+pub enum FetchError {
+    BadUrl(String),
+    Unsupported(String),
+    Network(String),
+    /// The host presented a different certificate from the one we trusted
+    CertificateChanged { host: String, fingerprint: String },
+    /// A gemini 4x, 5x or 6x status
+    Server(u8, String),
+    TooManyRedirects,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::BadUrl(url) => write!(f, "not a valid address: {}", url),
+            FetchError::Unsupported(scheme) => write!(f, "{} links can't be opened here", scheme),
+            FetchError::Network(e) => write!(f, "{}", e),
+            FetchError::CertificateChanged { host, .. } => write!(f, "the certificate for {} has changed", host),
+            FetchError::Server(60..=69, _) => write!(f, "this page needs a client certificate"),
+            FetchError::Server(code, meta) if meta.is_empty() => write!(f, "server error {}", code),
+            FetchError::Server(code, meta) => write!(f, "server error {}: {}", code, meta),
+            FetchError::TooManyRedirects => write!(f, "too many redirects"),
+        }
+    }
+}
+
+impl From<io::Error> for FetchError {
+    fn from(e: io::Error) -> Self {
+        FetchError::Network(e.to_string())
+    }
+}
+
+/// Certificate fingerprints by "host:port"
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct KnownHosts {
+    hosts: HashMap<String, String>,
+}
+
+impl KnownHosts {
+    fn path() -> PathBuf {
+        slowcore::storage::config_dir("slowport").join("known_hosts.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = slowcore::storage::save_atomic(&path, json.as_bytes());
+        }
+    }
+
+    /// Trust `fingerprint` for `host` from now on
+    pub fn trust(&mut self, host: &str, fingerprint: &str) {
+        self.hosts.insert(host.to_string(), fingerprint.to_string());
+        self.save();
+    }
+
+    fn check(&mut self, host: &str, fingerprint: &str) -> Result<(), FetchError> {
+        match self.hosts.get(host) {
+            Some(known) if known == fingerprint => Ok(()),
+            Some(_) => Err(FetchError::CertificateChanged { host: host.to_string(), fingerprint: fingerprint.to_string() }),
+            None => {
+                self.trust(host, fingerprint);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Accepts any certificate; [`KnownHosts`] does the checking once the
+/// handshake is done. Handshake signatures are still verified, so the
+/// server must hold the key for the certificate it shows.
+#[derive(Debug)]
+struct TrustOnFirstUse(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for TrustOnFirstUse {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+fn tls_config() -> Result<Arc<ClientConfig>, FetchError> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| FetchError::Network(e.to_string()))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(TrustOnFirstUse(provider)))
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+fn connect(host: &str, port: u16) -> Result<TcpStream, FetchError> {
+    let addr = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| FetchError::Network(format!("could not find {}", host)))?;
+    let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(READ_TIMEOUT))?;
+    Ok(stream)
+}
+
+/// Read until the server closes. Many gemini servers hang up without a
+/// TLS close_notify, which rustls reports as an unexpected EOF; by then
+/// the whole body has arrived, so that counts as the end too.
+fn read_to_close(reader: impl Read) -> Result<Vec<u8>, FetchError> {
+    let mut body = Vec::new();
+    match reader.take(MAX_RESPONSE).read_to_end(&mut body) {
+        Ok(_) => Ok(body),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(body),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The last path segment, or a fallback, for saving downloads
+fn file_name(url: &Url) -> String {
+    url.path_segments()
+        .and_then(|mut s| s.next_back())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "download".to_string())
+}
+
+/// Fetch any supported URL, following gemini redirects
+pub fn fetch(url: &str, known_hosts: &mut KnownHosts) -> Result<Response, FetchError> {
+    let mut url = Url::parse(url).map_err(|_| FetchError::BadUrl(url.to_string()))?;
+    for _ in 0..=MAX_REDIRECTS {
+        match url.scheme() {
+            "gemini" => match fetch_gemini(&url, known_hosts)? {
+                Gemini::Redirect(target) => {
+                    url = url.join(&target).map_err(|_| FetchError::BadUrl(target))?;
+                }
+                Gemini::Done(response) => return Ok(response),
+            },
+            "gopher" => return fetch_gopher(&url),
+            other => return Err(FetchError::Unsupported(other.to_string())),
+        }
+    }
+    Err(FetchError::TooManyRedirects)
+}
+
+enum Gemini {
+    Done(Response),
+    Redirect(String),
+}
+
+fn fetch_gemini(url: &Url, known_hosts: &mut KnownHosts) -> Result<Gemini, FetchError> {
+    let host = url.host_str().ok_or_else(|| FetchError::BadUrl(url.to_string()))?;
+    let port = url.port().unwrap_or(1965);
+    let name = ServerName::try_from(host.to_string()).map_err(|_| FetchError::BadUrl(url.to_string()))?;
+    let conn = ClientConnection::new(tls_config()?, name).map_err(|e| FetchError::Network(e.to_string()))?;
+    let mut tls = StreamOwned::new(conn, connect(host, port)?);
+
+    // Finish the handshake and check the certificate before sending anything
+    while tls.conn.is_handshaking() {
+        tls.conn.complete_io(&mut tls.sock)?;
+    }
+    let cert = tls
+        .conn
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .ok_or_else(|| FetchError::Network("server sent no certificate".into()))?;
+    let fingerprint = hex(ring::digest::digest(&ring::digest::SHA256, cert.as_ref()).as_ref());
+    known_hosts.check(&format!("{}:{}", host, port), &fingerprint)?;
+
+    tls.write_all(format!("{}\r\n", url).as_bytes())?;
+    tls.flush()?;
+    let response = read_to_close(&mut tls)?;
+
+    let header_end = response
+        .windows(2)
+        .position(|w| w == b"\r\n")
+        .ok_or_else(|| FetchError::Network("malformed response".into()))?;
+    let header = String::from_utf8_lossy(&response[..header_end]).to_string();
+    let body = &response[header_end + 2..];
+    let (status, meta) = header.split_once(' ').unwrap_or((header.as_str(), ""));
+    let status: u8 = status.parse().map_err(|_| FetchError::Network("malformed response".into()))?;
+    let meta = meta.trim().to_string();
+
+    Ok(match status {
+        10..=19 => Gemini::Done(Response::Input { url: url.clone(), prompt: meta, sensitive: status == 11 }),
+        20..=29 => {
+            let mime = meta.split(';').next().unwrap_or("").trim();
+            let mime = if mime.is_empty() { "text/gemini" } else { mime };
+            let text = || String::from_utf8_lossy(body).to_string();
+            Gemini::Done(match mime {
+                "text/gemini" => Response::Page { url: url.clone(), lines: document::parse_gemtext(&text(), url) },
+                m if m.starts_with("text/") => Response::Page { url: url.clone(), lines: document::parse_plain(&text()) },
+                _ => Response::Download { url: url.clone(), name: file_name(url), bytes: body.to_vec() },
+            })
+        }
+        30..=39 => Gemini::Redirect(meta),
+        _ => return Err(FetchError::Server(status, meta)),
+    })
+}
+
+fn fetch_gopher(url: &Url) -> Result<Response, FetchError> {
+    let host = url.host_str().ok_or_else(|| FetchError::BadUrl(url.to_string()))?;
+    let port = url.port().unwrap_or(70);
+    // The path is "/<type><selector>"; an empty path is the root menu
+    let path = percent_decode(url.path());
+    let path = path.strip_prefix('/').unwrap_or(&path);
+    let mut chars = path.chars();
+    let kind = chars.next().unwrap_or('1');
+    let selector = chars.as_str();
+
+    // A search with no query yet asks the user for one
+    if kind == '7' && url.query().is_none() {
+        return Ok(Response::Input { url: url.clone(), prompt: "search".into(), sensitive: false });
+    }
+    let request = match url.query() {
+        Some(query) if kind == '7' => format!("{}\t{}\r\n", selector, percent_decode(query)),
+        _ => format!("{}\r\n", selector),
+    };
+
+    let mut stream = connect(host, port)?;
+    stream.write_all(request.as_bytes())?;
+    let body = read_to_close(&mut stream)?;
+
+    Ok(match kind {
+        '1' | '7' => Response::Page { url: url.clone(), lines: document::parse_gopher_menu(&String::from_utf8_lossy(&body)) },
+        '0' => Response::Page { url: url.clone(), lines: document::parse_plain(&String::from_utf8_lossy(&body)) },
+        _ => Response::Download { url: url.clone(), name: file_name(url), bytes: body },
+    })
+}
+
+/// Decode %XX escapes, which the url crate leaves in paths and queries
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let digits = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(b) = u8::from_str_radix(digits, 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// `url` with `input` as its query, for answering an input prompt
+pub fn with_query(url: &Url, input: &str) -> Url {
+    let mut url = url.clone();
+    let encoded: String = input
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect();
+    url.set_query(Some(&encoded));
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_round_trip() {
+        let url = Url::parse("gopher://example.org/7/search").unwrap();
+        let searched = with_query(&url, "slow computing & you");
+        assert_eq!(searched.query(), Some("slow%20computing%20%26%20you"));
+        assert_eq!(percent_decode(searched.query().unwrap()), "slow computing & you");
+        assert_eq!(file_name(&Url::parse("gemini://x.org/files/song.ogg").unwrap()), "song.ogg");
+    }
+}
@@ -0,0 +1,66 @@
+//! Draws a page, one line at a time
+
+use crate::document::Line;
+use egui::{FontFamily, FontId, RichText, Sense, Stroke};
+use slowcore::theme::SlowColors;
+
+const BODY_SIZE: f32 = 15.0;
+const HEADING_SIZES: [f32; 3] = [24.0, 19.0, 16.0];
+
+/// Draw `lines`, returning the URL of a link that was clicked
+pub fn draw_page(ui: &mut egui::Ui, lines: &[Line]) -> Option<String> {
+    let mut clicked = None;
+    let body = FontId::new(BODY_SIZE, FontFamily::Proportional);
+    let mono = FontId::new(BODY_SIZE - 2.0, FontFamily::Monospace);
+    ui.spacing_mut().item_spacing.y = 4.0;
+
+    for line in lines {
+        match line {
+            Line::Text(text) if text.trim().is_empty() => {
+                ui.add_space(BODY_SIZE * 0.6);
+            }
+            Line::Text(text) => {
+                ui.add(egui::Label::new(RichText::new(text).font(body.clone())).wrap(true));
+            }
+            Line::Heading(level, text) => {
+                let size = HEADING_SIZES[(*level as usize).clamp(1, 3) - 1];
+                ui.add_space(4.0);
+                ui.add(egui::Label::new(RichText::new(text).size(size).strong()).wrap(true));
+            }
+            Line::Link { url, label } => {
+                let resp = ui
+                    .add(egui::Label::new(RichText::new(format!("\u{2192} {}", label)).font(body.clone())).sense(Sense::click()))
+                    .on_hover_text(url);
+                if resp.hovered() {
+                    let r = resp.rect;
+                    ui.painter().line_segment(
+                        [r.left_bottom(), r.right_bottom()],
+                        Stroke::new(1.0, SlowColors::BLACK),
+                    );
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+                }
+                if resp.clicked() {
+                    clicked = Some(url.clone());
+                }
+            }
+            Line::ListItem(text) => {
+                ui.add(egui::Label::new(RichText::new(format!("\u{2022} {}", text)).font(body.clone())).wrap(true));
+            }
+            Line::Quote(text) => {
+                ui.horizontal(|ui| {
+                    ui.add_space(12.0);
+                    let resp = ui.add(egui::Label::new(RichText::new(text).font(body.clone()).italics()).wrap(true));
+                    let r = resp.rect;
+                    ui.painter().line_segment(
+                        [r.left_top() - egui::vec2(6.0, 0.0), r.left_bottom() - egui::vec2(6.0, 0.0)],
+                        Stroke::new(2.0, SlowColors::BLACK),
+                    );
+                });
+            }
+            Line::Preformatted(text) => {
+                ui.add(egui::Label::new(RichText::new(text).font(mono.clone())).wrap(false));
+            }
+        }
+    }
+    clicked
+}
@@ -1,15 +1,63 @@
 //! SlowRead application
 
-use crate::book::Book;
-use crate::library::Library;
+use crate::book::{Book, BookMetadata};
+use crate::comic::{self, Comic, ComicViewer};
+use crate::goals::{self, Goal, Reading, ReadingLog};
+use crate::import::Watcher;
+use crate::library::{Bookmark, Library};
+use crate::opds::Browser;
 use crate::reader::Reader;
+use chrono::{Datelike, Local, NaiveDate};
 use egui::{Context, Key, Rect, Sense, Stroke, Vec2};
+use slowcore::display::{self, Rotation};
+use slowcore::drag::DropTarget;
+use slowcore::ipc::{self, Command, Reply};
+use slowcore::lazy::Lazy;
+use slowcore::log;
 use slowcore::repaint::RepaintController;
-use slowcore::storage::{documents_dir, FileBrowser};
+use slowcore::storage::documents_dir;
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
+use slowcore::tts::{self, Speaker};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, FileDialog, FileDialogResult, ListSelection, VirtualList, WindowAction};
 use std::collections::HashSet;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowReader",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "ebook reader for slowOS",
+    formats: &["EPUB (.epub)", "comics (.cbz, .cbr)"],
+    features: &[
+        "chapter navigation, bookmarks",
+        "daily reading goals, yearly log",
+        "OPDS catalog browsing and downloads",
+        "imports new books from Downloads",
+        "comic spreads, right to left",
+        "CJK font support",
+    ],
+    credits: &["egui/eframe (MIT), epub-rs (MIT)"],
+    ..AboutInfo::EMPTY
+};
+
+/// Size of a book cover in the library
+const BOOK_WIDTH: f32 = 100.0;
+const BOOK_HEIGHT: f32 = 140.0;
+/// Gap between covers, and under each shelf
+const SHELF_PADDING: f32 = 10.0;
+/// Room above each shelf for its section heading
+const SHELF_HEADING: f32 = 28.0;
+const SHELF_HEIGHT: f32 = SHELF_HEADING + BOOK_HEIGHT + SHELF_PADDING;
+
+/// A row of covers in the library
+struct Shelf {
+    /// Heading of the section, on its first shelf
+    heading: Option<&'static str>,
+    /// 0 for the user's books, 1 for slowLibrary
+    section: usize,
+    /// The books on the shelf; empty for a section with no books
+    books: std::ops::Range<usize>,
+}
 
 /// Path to the slowLibrary folder with pre-installed ebooks
 fn slow_library_dir() -> PathBuf {
@@ -73,11 +121,44 @@ fn scan_slow_library() -> Vec<(PathBuf, String)> {
     books
 }
 
+/// A book's title, or its file name when it has none
+fn book_title(book: &Book) -> String {
+    if book.metadata.title.is_empty() {
+        book.path.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "unknown".to_string())
+    } else {
+        book.metadata.title.clone()
+    }
+}
+
+/// Library details for a comic, which has only its file name to go by
+fn comic_metadata(comic: &Comic) -> BookMetadata {
+    BookMetadata { title: comic.title.clone(), author: String::new(), language: String::new(), description: String::new() }
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// A bar filled `fraction` of the way
+fn progress_bar(ui: &mut egui::Ui, width: f32, fraction: f32) {
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(width, 8.0), Sense::hover());
+    let filled = rect.width() * fraction.clamp(0.0, 1.0);
+    ui.painter().rect_filled(Rect::from_min_size(rect.min, Vec2::new(filled, rect.height())), 0.0, SlowColors::BLACK);
+    ui.painter().rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 { format!("{} B", bytes) }
+    else if bytes < 1024 * 1024 { format!("{:.1} KB", bytes as f64 / 1024.0) }
+    else { format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0)) }
+}
+
 /// Application view
 #[derive(Clone, Copy, PartialEq)]
 enum View {
     Library,
     Reader,
+    Comic,
 }
 
 pub struct SlowReaderApp {
@@ -85,15 +166,15 @@ pub struct SlowReaderApp {
     view: View,
     library: Library,
     current_book: Option<Book>,
+    comic: Option<ComicViewer>,
     reader: Reader,
-    show_file_browser: bool,
-    file_browser: FileBrowser,
+    file_dialog: Option<FileDialog>,
     show_toc: bool,
     show_settings: bool,
     show_about: bool,
     show_shortcuts: bool,
     /// Cached list of books from slowLibrary folder
-    slow_library_books: Vec<(PathBuf, String)>,
+    slow_library_books: Lazy<Vec<(PathBuf, String)>>,
     /// Show search bar
     show_search: bool,
     /// Search query
@@ -110,26 +191,59 @@ pub struct SlowReaderApp {
     selected_books: HashSet<PathBuf>,
     /// Delete mode - when true, show selection circles on user books
     delete_mode: bool,
+    /// Shelf the library list is at, for its keys and type-ahead
+    library_selection: ListSelection,
+    /// Reads the book aloud
+    speaker: Speaker,
+    /// Reading aloud goes on into the next chapter
+    reading_aloud: bool,
+    /// Where each block starts in the text being read: (block_idx, offset)
+    speech_starts: Vec<(usize, usize)>,
+    /// Why reading aloud stopped, if it went wrong
+    speech_error: Option<String>,
+    /// Time and pages read each day, and the daily goal
+    reading_log: ReadingLog,
+    /// Reading not yet added to the log
+    unlogged_secs: f64,
+    unlogged_pages: u32,
+    /// When reading time was last counted
+    last_tick: Option<Instant>,
+    /// (chapter, page) when pages were last counted
+    last_position: (usize, usize),
+    /// Add-bookmark dialog: the name being typed, when open
+    bookmark_name: Option<String>,
+    show_bookmarks: bool,
+    /// Bookmark being renamed in the bookmarks window, and its new name
+    renaming_bookmark: Option<(usize, String)>,
+    show_catalogs: bool,
+    catalogs: Browser,
+    /// Address typed in to add a catalog
+    new_catalog: String,
+    /// Copies new books from ~/Downloads and the drop folder into ~/Books
+    importer: Watcher,
+    /// Drop folder as typed in the settings
+    drop_folder: String,
 }
 
 impl SlowReaderApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let slow_library_books = scan_slow_library();
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let importer = Watcher::start(cc.egui_ctx.clone());
+        let drop_folder = importer.settings().drop_folder.map(|p| p.display().to_string()).unwrap_or_default();
 
         Self {
             repaint: RepaintController::new(),
             view: View::Library,
             library: Library::load(),
             current_book: None,
+            comic: None,
             reader: Reader::new(),
-            show_file_browser: false,
-            file_browser: FileBrowser::new(documents_dir())
-                .with_filter(vec!["epub".into(), "txt".into(), "pdf".into()]),
+            file_dialog: None,
             show_toc: false,
             show_settings: false,
             show_about: false,
             show_shortcuts: false,
-            slow_library_books,
+            // Reading the folder waits until the window has shown
+            slow_library_books: Lazy::new("library scan", scan_slow_library),
             show_search: false,
             search_query: String::new(),
             search_results: Vec::new(),
@@ -137,7 +251,25 @@ impl SlowReaderApp {
             fullscreen: false,
             fullscreen_menu_visible: false,
             selected_books: HashSet::new(),
+            library_selection: ListSelection::default(),
             delete_mode: false,
+            speaker: Speaker::new("slowreader"),
+            reading_aloud: false,
+            speech_starts: Vec::new(),
+            speech_error: None,
+            reading_log: ReadingLog::load(),
+            unlogged_secs: 0.0,
+            unlogged_pages: 0,
+            last_tick: None,
+            last_position: (0, 0),
+            bookmark_name: None,
+            show_bookmarks: false,
+            renaming_bookmark: None,
+            show_catalogs: false,
+            catalogs: Browser::new(),
+            new_catalog: String::new(),
+            importer,
+            drop_folder,
         }
     }
 
@@ -156,6 +288,12 @@ impl SlowReaderApp {
         if self.library.books.iter().any(|b| b.path == path) {
             return;
         }
+        if comic::is_comic(&path) {
+            if let Ok(comic) = Comic::open(&path) {
+                self.library.add_book(path, comic_metadata(&comic), comic.page_count());
+            }
+            return;
+        }
 
         let result = if path.extension().map(|e| e == "epub").unwrap_or(false) {
             Book::open_epub(path.clone())
@@ -168,7 +306,35 @@ impl SlowReaderApp {
         }
     }
 
+    /// Add what the import watcher copied into ~/Books and say so. PDFs
+    /// aren't read here, so the library leaves them out, but "open now"
+    /// still takes them to slowView.
+    fn take_imports(&mut self) {
+        for path in self.importer.poll() {
+            self.add_book_to_library(path.clone());
+            let title = self.library.books.iter()
+                .find(|b| b.path == path && !b.metadata.title.is_empty())
+                .map(|b| b.metadata.title.clone())
+                .or_else(|| path.file_stem().map(|n| n.to_string_lossy().to_string()))
+                .unwrap_or_default();
+            slowcore::notify::post_with_open("slowreader", &format!("added {} to your books", title), &path);
+        }
+    }
+
+    /// The file open for reading, book or comic
+    fn open_path(&self) -> Option<&PathBuf> {
+        match self.view {
+            View::Reader => self.current_book.as_ref().map(|b| &b.path),
+            View::Comic => self.comic.as_ref().map(|v| &v.comic.path),
+            View::Library => None,
+        }
+    }
+
     pub fn open_book(&mut self, path: PathBuf) {
+        if comic::is_comic(&path) {
+            self.open_comic(path);
+            return;
+        }
         let result = if path.extension().map(|e| e == "epub").unwrap_or(false) {
             Book::open_epub(path.clone())
         } else {
@@ -189,16 +355,22 @@ impl SlowReaderApp {
                 // Add to library
                 self.library.add_book(path, book.metadata.clone(), book.chapter_count());
 
+                self.close_comic();
+                self.log_reading();
+                self.last_position = (self.reader.position.chapter, self.reader.position.page);
+                self.last_tick = None;
                 self.current_book = Some(book);
                 self.view = View::Reader;
             }
             Err(_e) => {
-                eprintln!("Failed to open book");
+                log::error!("Failed to open book");
             }
         }
     }
     
     fn close_book(&mut self) {
+        self.stop_reading_aloud();
+        self.log_reading();
         // Save position
         if let Some(ref book) = self.current_book {
             self.library.update_position(
@@ -209,22 +381,231 @@ impl SlowReaderApp {
         }
 
         self.current_book = None;
+        self.close_comic();
         self.view = View::Library;
     }
+
+    /// Open a .cbz or .cbr at the page it was left on
+    fn open_comic(&mut self, path: PathBuf) {
+        match Comic::open(&path) {
+            Ok(comic) => {
+                if self.current_book.is_some() {
+                    self.close_book();
+                }
+                self.close_comic();
+                let page = self.library.get_position(&path).map_or(0, |(page, _)| page);
+                let right_to_left = self.library.right_to_left(&path);
+                self.library.add_book(path, comic_metadata(&comic), comic.page_count());
+                let viewer = ComicViewer::new(comic, page, right_to_left);
+                self.last_position = (0, viewer.page);
+                self.last_tick = None;
+                self.comic = Some(viewer);
+                self.view = View::Comic;
+            }
+            Err(e) => {
+                log::error!("Failed to open comic: {}", e);
+                slowcore::notify::post("slowreader", &e);
+            }
+        }
+    }
+
+    fn close_comic(&mut self) {
+        self.log_reading();
+        if let Some(viewer) = self.comic.take() {
+            self.library.update_position(&viewer.comic.path, viewer.page, 0.0);
+        }
+        if self.view == View::Comic {
+            self.view = View::Library;
+        }
+    }
+
+    fn toggle_right_to_left(&mut self) {
+        if let Some(viewer) = &mut self.comic {
+            viewer.right_to_left = !viewer.right_to_left;
+            self.library.set_right_to_left(&viewer.comic.path, viewer.right_to_left);
+        }
+    }
+
+    /// The title of what's open, book or comic
+    fn open_title(&self) -> Option<String> {
+        match (&self.current_book, &self.comic) {
+            (Some(book), _) => Some(book_title(book)),
+            (None, Some(viewer)) => Some(viewer.comic.title.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// What's being read: (chapter, page), and whether the last page is
+    /// showing
+    fn reading_position(&self) -> Option<((usize, usize), bool)> {
+        match self.view {
+            View::Reader => {
+                let book = self.current_book.as_ref()?;
+                let position = (self.reader.position.chapter, self.reader.position.page);
+                let (page, total) = self.reader.page_info();
+                Some((position, position.0 + 1 >= book.chapter_count() && page >= total))
+            }
+            View::Comic => self.comic.as_ref().map(|viewer| ((0, viewer.page), viewer.at_end())),
+            View::Library => None,
+        }
+    }
     
+    /// Count time with the book open, up to a limit between frames, and
+    /// pages turned forward, towards today's reading
+    fn track_reading(&mut self, ctx: &Context) {
+        let now = Instant::now();
+        let last = self.last_tick.take();
+        let Some((position, at_end)) = self.reading_position() else { return };
+        if !ctx.input(|i| i.focused) {
+            return;
+        }
+        if let Some(last) = last {
+            self.unlogged_secs += now.duration_since(last).as_secs_f64().min(goals::IDLE_LIMIT_SECS);
+        }
+        self.last_tick = Some(now);
+
+        if position > self.last_position {
+            self.unlogged_pages += 1;
+        }
+        self.last_position = position;
+        if let Some(title) = self.open_title().filter(|_| at_end) {
+            if self.reading_log.finish(&title, today()) {
+                self.reading_log.save();
+            }
+        }
+        if self.unlogged_pages > 0 {
+            self.log_reading();
+        }
+    }
+
+    /// Move the reading counted so far into the log and save it
+    fn log_reading(&mut self) {
+        let Some(title) = self.open_title() else { return };
+        let seconds = self.unlogged_secs.floor();
+        let reading = Reading { seconds: seconds as u64, pages: self.unlogged_pages };
+        self.unlogged_secs -= seconds;
+        self.unlogged_pages = 0;
+        if reading != Reading::default() {
+            self.reading_log.add(today(), &title, reading);
+            self.reading_log.save();
+        }
+    }
+
+    /// Today's reading, counted and not yet logged
+    fn today_reading(&self) -> Reading {
+        let logged = self.reading_log.day(today());
+        Reading { seconds: logged.seconds + self.unlogged_secs as u64, pages: logged.pages + self.unlogged_pages }
+    }
+
+    /// How today's reading stands against the goal, if there is one
+    fn goal_progress(&self) -> Option<(Goal, u32)> {
+        let goal = self.reading_log.goal?;
+        Some((goal, goal.progress(self.today_reading())))
+    }
+
+    fn goal_text(&self) -> Option<String> {
+        let (goal, done) = self.goal_progress()?;
+        Some(if done >= goal.target() {
+            format!("today's goal met, {} {}", done, goal.unit())
+        } else {
+            format!("today {} of {} {}", done, goal.target(), goal.unit())
+        })
+    }
+
+    fn export_reading_log(&self) {
+        let year = today().year();
+        let message = match self.reading_log.export_year(year) {
+            Ok(path) => format!("saved {} in Documents", path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()),
+            Err(e) => format!("could not export reading log: {}", e),
+        };
+        slowcore::notify::post("slowreader", &message);
+    }
+
+    /// The chapter and page, to name a new bookmark by
+    fn default_bookmark_name(&self) -> String {
+        let chapter = self.reader.position.chapter;
+        let title = self.current_book.as_ref()
+            .and_then(|b| b.chapters.get(chapter))
+            .map(|c| c.title.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| format!("chapter {}", chapter + 1));
+        format!("{}, page {}", title, self.reader.page_info().0)
+    }
+
+    fn add_bookmark(&mut self, name: &str) {
+        let name = if name.trim().is_empty() { self.default_bookmark_name() } else { name.trim().to_string() };
+        let Some(book) = &self.current_book else { return };
+        let (block, offset) = self.reader.page_anchor().unwrap_or((0, 0));
+        let bookmark = Bookmark { name, chapter: self.reader.position.chapter, block, offset };
+        self.library.add_bookmark(&book.path, bookmark);
+    }
+
+    fn go_to_bookmark(&mut self, bookmark: &Bookmark) {
+        let Some(book) = &self.current_book else { return };
+        self.reader.go_to_anchor(bookmark.chapter, bookmark.block, bookmark.offset, book);
+        self.view = View::Reader;
+    }
+
+    /// Read the current chapter aloud from the top of the page
+    fn read_aloud(&mut self) {
+        let Some(book) = &self.current_book else { return };
+        let (text, starts) = self.reader.chapter_speech(book);
+        let from = self.reader.speech_start(&starts);
+        self.speaker.play(&text, from);
+        self.speech_starts = starts;
+        self.reading_aloud = true;
+        self.speech_error = None;
+    }
+
+    fn stop_reading_aloud(&mut self) {
+        self.speaker.stop();
+        self.reading_aloud = false;
+        self.reader.set_speaking(None, &[]);
+    }
+
+    /// Follow the speech: underline the sentence being read and go on into
+    /// the next chapter at the end of this one
+    fn update_reading_aloud(&mut self, ctx: &Context) {
+        if !self.reading_aloud {
+            return;
+        }
+        if let Some(range) = self.speaker.current() {
+            self.reader.set_speaking(Some(range), &self.speech_starts);
+            ctx.request_repaint_after(Duration::from_millis(250));
+            return;
+        }
+        if let Some(error) = self.speaker.error() {
+            self.speech_error = Some(error);
+            self.stop_reading_aloud();
+            return;
+        }
+        let Some(book) = &self.current_book else { return };
+        if self.reader.position.chapter + 1 < book.chapter_count() {
+            self.reader.next_chapter(book);
+            let (text, starts) = self.reader.chapter_speech(book);
+            self.speaker.play(&text, 0);
+            self.speech_starts = starts;
+            ctx.request_repaint();
+        } else {
+            self.stop_reading_aloud();
+        }
+    }
+
+    fn toggle_reading_aloud(&mut self) {
+        if !self.speaker.is_active() {
+            self.read_aloud();
+        } else if self.speaker.is_paused() {
+            self.speaker.resume();
+        } else {
+            self.speaker.pause();
+        }
+    }
+
     fn handle_keyboard(&mut self, ctx: &Context) {
         slowcore::theme::consume_special_keys(ctx);
 
         // Handle dropped files (drag-and-drop epub)
-        let dropped: Vec<PathBuf> = ctx.input(|i| {
-            i.raw.dropped_files.iter()
-                .filter_map(|f| f.path.clone())
-                .filter(|p| {
-                    let ext = p.extension().and_then(|e| e.to_str()).unwrap_or("");
-                    ext == "epub" || ext == "txt"
-                })
-                .collect()
-        });
+        let dropped = DropTarget::new(&["epub", "txt", "cbz", "cbr"]).show(ctx);
 
         if !dropped.is_empty() {
             // Add all dropped books to library
@@ -237,15 +618,18 @@ impl SlowReaderApp {
             }
         }
         
+        // Letter keys go to dialogs' text fields while one is being typed in
+        let typing = ctx.wants_keyboard_input();
+        let mut add_bookmark = false;
         ctx.input(|i| {
             let cmd = i.modifiers.command;
             let shift = i.modifiers.shift;
             
             // Global shortcuts
             if cmd && i.key_pressed(Key::O) {
-                self.show_file_browser = true;
+                self.show_open_dialog();
             }
-            if cmd && i.key_pressed(Key::W) && self.current_book.is_some() {
+            if cmd && i.key_pressed(Key::W) && (self.current_book.is_some() || self.comic.is_some()) {
                 self.close_book();
             }
             // Ctrl+F / Cmd+F for search
@@ -257,7 +641,7 @@ impl SlowReaderApp {
             }
             
             // Reader shortcuts - horizontal page flipping only
-            if self.view == View::Reader && self.current_book.is_some() {
+            if self.view == View::Reader && self.current_book.is_some() && !typing {
                 let book = self.current_book.as_ref().unwrap();
 
                 // Page navigation - all directions flip pages
@@ -290,6 +674,13 @@ impl SlowReaderApp {
                 if i.key_pressed(Key::T) {
                     self.show_toc = !self.show_toc;
                 }
+                if i.key_pressed(Key::B) && !cmd {
+                    add_bookmark = true;
+                }
+                // R reads aloud, and pauses and resumes
+                if i.key_pressed(Key::R) && !cmd {
+                    self.toggle_reading_aloud();
+                }
                 // F for fullscreen (without cmd, to not conflict with Cmd+F search)
                 if i.key_pressed(Key::F) && !cmd {
                     self.fullscreen = !self.fullscreen;
@@ -304,20 +695,106 @@ impl SlowReaderApp {
             }
         });
 
+        // Comic shortcuts: arrows turn towards their side of the screen
+        if self.view == View::Comic && !typing {
+            let mut toggle_direction = false;
+            let mut toggle_fullscreen = false;
+            let mut close = false;
+            if let Some(viewer) = &mut self.comic {
+                ctx.input(|i| {
+                    let back = i.key_pressed(Key::PageUp) || (i.modifiers.shift && i.key_pressed(Key::Space));
+                    if back {
+                        viewer.prev_page();
+                    } else if i.key_pressed(Key::PageDown) || i.key_pressed(Key::Space) {
+                        viewer.next_page();
+                    }
+                    if i.key_pressed(Key::ArrowLeft) {
+                        viewer.turn_left();
+                    }
+                    if i.key_pressed(Key::ArrowRight) {
+                        viewer.turn_right();
+                    }
+                    if i.key_pressed(Key::Home) {
+                        viewer.first_page();
+                    }
+                    if i.key_pressed(Key::End) {
+                        viewer.last_page();
+                    }
+                    if i.modifiers.is_none() {
+                        if i.key_pressed(Key::D) {
+                            viewer.toggle_spread();
+                        }
+                        toggle_direction = i.key_pressed(Key::R);
+                        toggle_fullscreen = i.key_pressed(Key::F);
+                    }
+                    close = i.key_pressed(Key::Escape);
+                });
+            }
+            if toggle_direction {
+                self.toggle_right_to_left();
+            }
+            if toggle_fullscreen {
+                self.fullscreen = !self.fullscreen;
+            }
+            if close {
+                if self.fullscreen {
+                    self.fullscreen = false;
+                } else {
+                    self.close_book();
+                }
+            }
+        }
+
+        if add_bookmark {
+            self.bookmark_name = Some(self.default_bookmark_name());
+            // Keep the B out of the name field that's about to take focus
+            ctx.input_mut(|i| i.events.retain(|e| !matches!(e, egui::Event::Text(_))));
+        }
+
         // Apply OS-level fullscreen
         ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(self.fullscreen));
     }
 
+    /// Carry out commands from the desktop and scripts (slowctl): open a
+    /// book or comic, and the query "book" (the path of the open one)
+    fn handle_commands(&mut self, ctx: &Context) {
+        for request in ipc::take() {
+            let reply = match &request.command {
+                Command::Open { path } => {
+                    self.open_book(path.clone());
+                    if self.open_path() == Some(path) {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                        Reply::Done
+                    } else {
+                        Reply::Failed(format!("could not open {}", path.display()))
+                    }
+                }
+                Command::Query { key } if key == "book" => {
+                    Reply::Value(self.open_path().map(|p| p.display().to_string()).into())
+                }
+                Command::Query { key } => Reply::Failed(format!("nothing called \"{}\" to ask about", key)),
+                Command::Type { .. } => Reply::Failed("slowReader takes no typing".into()),
+                Command::Menu { .. } => Reply::Failed("no such menu item".into()),
+            };
+            request.reply(reply);
+        }
+    }
+
     fn render_menu_bar(&mut self, ui: &mut egui::Ui) -> WindowAction {
         let mut action = WindowAction::None;
         menu_bar(ui, |ui| {
             action = window_control_buttons(ui);
             ui.menu_button("file", |ui| {
                 if ui.button("open...     ⌘o").clicked() {
-                    self.show_file_browser = true;
+                    self.show_open_dialog();
                     ui.close_menu();
                 }
-                if self.current_book.is_some() {
+                if ui.button("browse catalogs...").clicked() {
+                    self.show_catalogs = true;
+                    ui.close_menu();
+                }
+                if self.current_book.is_some() || self.comic.is_some() {
                     if ui.button("close book  ⌘W").clicked() {
                         self.close_book();
                         ui.close_menu();
@@ -328,6 +805,11 @@ impl SlowReaderApp {
                     self.view = View::Library;
                     ui.close_menu();
                 }
+                if ui.button("export reading log").clicked() {
+                    self.log_reading();
+                    self.export_reading_log();
+                    ui.close_menu();
+                }
                 // Delete mode toggle (only when in library view)
                 if self.view == View::Library {
                     ui.separator();
@@ -385,8 +867,46 @@ impl SlowReaderApp {
                         self.show_settings = true;
                         ui.close_menu();
                     }
+                    ui.separator();
+                    // Turns the whole screen, like settings → display
+                    let portrait = display::saved_rotation().is_portrait();
+                    let rotate_label = if portrait { "landscape screen" } else { "portrait screen" };
+                    if ui.button(rotate_label).clicked() {
+                        let rotation = if portrait { Rotation::Normal } else { Rotation::Left };
+                        match display::set_rotation(rotation) {
+                            Ok(()) => display::save_rotation(rotation),
+                            Err(e) => log::warn!("couldn't rotate the screen: {}", e),
+                        }
+                        ui.close_menu();
+                    }
                 });
                 
+                ui.menu_button("speech", |ui| {
+                    let label = if !self.speaker.is_active() {
+                        "read aloud      r"
+                    } else if self.speaker.is_paused() {
+                        "resume          r"
+                    } else {
+                        "pause           r"
+                    };
+                    if ui.button(label).clicked() {
+                        self.toggle_reading_aloud();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.speaker.is_active(), egui::Button::new("stop reading")).clicked() {
+                        self.stop_reading_aloud();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    for speed in tts::SPEEDS {
+                        let selected = (self.speaker.speed() - speed).abs() < 0.01;
+                        if ui.radio(selected, format!("{}× speed", speed)).clicked() {
+                            self.speaker.set_speed(speed);
+                            ui.close_menu();
+                        }
+                    }
+                });
+
                 ui.menu_button("go", |ui| {
                     if ui.button("next page        →").clicked() {
                         if let Some(ref book) = self.current_book {
@@ -414,8 +934,72 @@ impl SlowReaderApp {
                         ui.close_menu();
                     }
                 });
+
+                ui.menu_button("bookmarks", |ui| {
+                    if ui.button("add bookmark...  b").clicked() {
+                        self.bookmark_name = Some(self.default_bookmark_name());
+                        ui.close_menu();
+                    }
+                    let marks = self.current_book.as_ref().map(|b| self.library.bookmarks(&b.path).to_vec()).unwrap_or_default();
+                    if ui.add_enabled(!marks.is_empty(), egui::Button::new("edit bookmarks...")).clicked() {
+                        self.show_bookmarks = true;
+                        ui.close_menu();
+                    }
+                    if !marks.is_empty() {
+                        ui.separator();
+                    }
+                    for mark in &marks {
+                        if ui.button(&mark.name).clicked() {
+                            self.go_to_bookmark(mark);
+                            ui.close_menu();
+                        }
+                    }
+                });
             }
             
+            if self.comic.is_some() {
+                ui.menu_button("view", |ui| {
+                    let fullscreen_label = if self.fullscreen { "exit fullscreen    F" } else { "fullscreen         F" };
+                    if ui.button(fullscreen_label).clicked() {
+                        self.fullscreen = !self.fullscreen;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    let Some(viewer) = &mut self.comic else { return };
+                    let spread = if viewer.spread { "> two-page spread  d" } else { "  two-page spread  d" };
+                    if ui.button(spread).clicked() {
+                        viewer.toggle_spread();
+                        ui.close_menu();
+                    }
+                    let direction = if viewer.right_to_left { "> right to left    r" } else { "  right to left    r" };
+                    if ui.button(direction).clicked() {
+                        self.toggle_right_to_left();
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button("go", |ui| {
+                    let Some(viewer) = &mut self.comic else { return };
+                    if ui.button("next page      space").clicked() {
+                        viewer.next_page();
+                        ui.close_menu();
+                    }
+                    if ui.button("previous page  \u{21e7}space").clicked() {
+                        viewer.prev_page();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("first page     home").clicked() {
+                        viewer.first_page();
+                        ui.close_menu();
+                    }
+                    if ui.button("last page      end").clicked() {
+                        viewer.last_page();
+                        ui.close_menu();
+                    }
+                });
+            }
+
             ui.menu_button("help", |ui| {
                 if ui.button("keyboard shortcuts").clicked() {
                     self.show_shortcuts = true;
@@ -438,7 +1022,17 @@ impl SlowReaderApp {
             ui.add_space(5.0);
 
             if ui.button("open book...").clicked() {
-                self.show_file_browser = true;
+                self.show_open_dialog();
+            }
+            if ui.button("browse catalogs...").clicked() {
+                self.show_catalogs = true;
+            }
+
+            // Today's reading against the goal, as a bar that fills
+            if let (Some((goal, done)), Some(text)) = (self.goal_progress(), self.goal_text()) {
+                ui.add_space(8.0);
+                progress_bar(ui, 160.0, done as f32 / goal.target().max(1) as f32);
+                ui.label(text);
             }
 
             ui.add_space(10.0);
@@ -449,7 +1043,8 @@ impl SlowReaderApp {
         // Collect user books (from recent/opened) with progress info
         // (path, title, progress_percent: Option<u8>)
         let slow_lib_dir = slow_library_dir();
-        let slow_lib_paths: HashSet<&PathBuf> = self.slow_library_books.iter().map(|(p, _)| p).collect();
+        let slow_library_books = self.slow_library_books.get().map_or(&[][..], Vec::as_slice);
+        let slow_lib_paths: HashSet<&PathBuf> = slow_library_books.iter().map(|(p, _)| p).collect();
         let mut user_books: Vec<(PathBuf, String, Option<u8>)> = Vec::new();
         for entry in self.library.recent_books() {
             let title = if entry.metadata.title.is_empty() {
@@ -475,7 +1070,7 @@ impl SlowReaderApp {
 
         // Collect library books with progress info, sorted by last read (recent first)
         let books_by_path: std::collections::HashMap<&PathBuf, &crate::library::LibraryEntry> = self.library.books.iter().map(|b| (&b.path, b)).collect();
-        let mut library_books: Vec<(PathBuf, String, Option<u8>, u64)> = self.slow_library_books.iter().map(|(path, title)| {
+        let mut library_books: Vec<(PathBuf, String, Option<u8>, u64)> = slow_library_books.iter().map(|(path, title)| {
             // Look up progress in library
             let (progress, last_read) = books_by_path.get(path)
                 .map(|entry| {
@@ -499,50 +1094,101 @@ impl SlowReaderApp {
         let mut book_to_open: Option<PathBuf> = None;
         let mut toggle_selection: Option<PathBuf> = None;
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            // --- User Library section ---
-            ui.add_space(8.0);
-            ui.horizontal(|ui| {
-                ui.add_space(12.0);
-                ui.label(egui::RichText::new("user library").strong());
-            });
-            ui.add_space(4.0);
-
-            if user_books.is_empty() {
-                ui.add_space(20.0);
-                ui.vertical_centered(|ui| {
-                    ui.label("want to grow your book collection?");
-                    ui.add_space(4.0);
-                    ui.label("ePub files can be bought at ebooks.com");
-                });
-                ui.add_space(20.0);
-            } else {
-                // User books can be selected only when in delete mode
-                Self::render_book_grid(ui, &user_books, &mut book_to_open, &mut toggle_selection, &self.selected_books, self.delete_mode);
+        // Lay the books out in shelves so that only the shelves in view are
+        // drawn; the first shelf of each section carries its heading
+        let cols = ((ui.available_width() - ui.spacing().scroll.bar_width - SHELF_PADDING)
+            / (BOOK_WIDTH + SHELF_PADDING)).max(1.0) as usize;
+        let sections: [&[(PathBuf, String, Option<u8>)]; 2] = [&user_books, &library_books];
+        let mut shelves: Vec<Shelf> = Vec::new();
+        for (section, books) in sections.iter().enumerate() {
+            let heading = if section == 0 { "user library" } else { "slowLibrary" };
+            if books.is_empty() {
+                shelves.push(Shelf { heading: Some(heading), section, books: 0..0 });
             }
-
-            ui.add_space(8.0);
-            ui.separator();
-
-            // --- slowLibrary section ---
-            ui.add_space(8.0);
-            ui.horizontal(|ui| {
-                ui.add_space(12.0);
-                ui.label(egui::RichText::new("slowLibrary").strong());
-            });
-            ui.add_space(4.0);
-
-            if library_books.is_empty() {
-                ui.add_space(20.0);
-                ui.vertical_centered(|ui| {
-                    ui.label("no public domain books found.");
+            for start in (0..books.len()).step_by(cols) {
+                shelves.push(Shelf {
+                    heading: (start == 0).then_some(heading),
+                    section,
+                    books: start..(start + cols).min(books.len()),
                 });
-                ui.add_space(20.0);
-            } else {
-                // slowLibrary books cannot be selected for deletion
-                Self::render_book_grid(ui, &library_books, &mut book_to_open, &mut None, &HashSet::new(), false);
             }
-        });
+        }
+
+        let selected_books = &self.selected_books;
+        let delete_mode = self.delete_mode;
+        VirtualList::new("reader_library")
+            .row_height(SHELF_HEIGHT)
+            .max_height(ui.available_height() / slowcore::theme::ui_scale())
+            .show(
+                ui,
+                &mut self.library_selection,
+                shelves.len(),
+                // Type-ahead jumps to the shelf starting with a title
+                |i| {
+                    let shelf = &shelves[i];
+                    sections[shelf.section].get(shelf.books.start).map_or("", |b| b.1.as_str())
+                },
+                |ui, i, _| {
+                    let shelf = &shelves[i];
+                    let (rect, response) = ui.allocate_exact_size(
+                        Vec2::new(ui.available_width(), SHELF_HEIGHT),
+                        Sense::hover(),
+                    );
+                    if let Some(heading) = shelf.heading {
+                        let top = if shelf.section == 0 {
+                            rect.min.y
+                        } else {
+                            // A rule between the two sections
+                            ui.painter().hline(rect.x_range(), rect.min.y, Stroke::new(1.0, SlowColors::BLACK));
+                            rect.min.y + 1.0
+                        };
+                        ui.painter().text(
+                            egui::pos2(rect.min.x + 12.0, top + SHELF_HEADING / 2.0),
+                            egui::Align2::LEFT_CENTER,
+                            heading,
+                            egui::FontId::proportional(14.0),
+                            SlowColors::BLACK,
+                        );
+                    }
+                    let books_top = rect.min.y + SHELF_HEADING;
+                    if shelf.books.is_empty() {
+                        let note: &[&str] = if shelf.section == 0 {
+                            &["want to grow your book collection?", "ePub files can be bought at ebooks.com", "or downloaded free from the catalogs"]
+                        } else {
+                            &["no public domain books found."]
+                        };
+                        for (n, line) in note.iter().enumerate() {
+                            ui.painter().text(
+                                egui::pos2(rect.center().x, books_top + 20.0 + n as f32 * 18.0),
+                                egui::Align2::CENTER_TOP,
+                                line,
+                                egui::FontId::proportional(14.0),
+                                SlowColors::BLACK,
+                            );
+                        }
+                    } else {
+                        // User books can be selected only when in delete mode;
+                        // slowLibrary books never can
+                        let books = &sections[shelf.section][shelf.books.clone()];
+                        let selectable = shelf.section == 0 && delete_mode;
+                        for (col, book) in books.iter().enumerate() {
+                            let min = egui::pos2(
+                                rect.min.x + SHELF_PADDING + col as f32 * (BOOK_WIDTH + SHELF_PADDING),
+                                books_top,
+                            );
+                            let book_rect = Rect::from_min_size(min, Vec2::new(BOOK_WIDTH, BOOK_HEIGHT));
+                            let clicked = Self::render_book(ui, book_rect, book, selectable.then(|| selected_books.contains(&book.0)));
+                            if clicked && selectable {
+                                // In delete mode, clicking anywhere on the book toggles selection
+                                toggle_selection = Some(book.0.clone());
+                            } else if clicked {
+                                book_to_open = Some(book.0.clone());
+                            }
+                        }
+                    }
+                    response
+                },
+            );
 
         // Toggle selection after the loop
         if let Some(path) = toggle_selection {
@@ -559,163 +1205,122 @@ impl SlowReaderApp {
         }
     }
 
-    fn render_book_grid(
+    /// Draw one book cover at `rect`, with a selection circle when `selected`
+    /// is given. True if the book was clicked.
+    fn render_book(
         ui: &mut egui::Ui,
-        books: &[(PathBuf, String, Option<u8>)],
-        book_to_open: &mut Option<PathBuf>,
-        toggle_selection: &mut Option<PathBuf>,
-        selected_books: &HashSet<PathBuf>,
-        show_selection_circles: bool,
-    ) {
-        let available_width = ui.available_width();
-        let book_width: f32 = 100.0;
-        let book_height: f32 = 140.0;
-        let padding: f32 = 10.0;
-        let cols = ((available_width - padding) / (book_width + padding)).max(1.0) as usize;
-
-        let rows = (books.len() + cols - 1) / cols;
-        for row in 0..rows {
-            ui.horizontal(|ui| {
-                ui.add_space(padding);
-                for col in 0..cols {
-                    let idx = row * cols + col;
-                    if idx >= books.len() {
-                        break;
-                    }
-
-                    let (path, title, progress) = &books[idx];
-
-                    // Draw book cover placeholder
-                    let (rect, response) = ui.allocate_exact_size(
-                        Vec2::new(book_width, book_height),
-                        Sense::click(),
-                    );
-
-                    if ui.is_rect_visible(rect) {
-                        let painter = ui.painter();
-
-                        // Book background
-                        painter.rect_filled(rect, 2.0, SlowColors::WHITE);
-                        painter.rect_stroke(rect, 2.0, Stroke::new(2.0, SlowColors::BLACK));
-
-                        // Hover/selection effect
-                        if response.hovered() {
-                            slowcore::dither::draw_dither_hover(painter, rect);
-                        }
-
-                        // Book spine decoration
-                        let spine_rect = Rect::from_min_size(
-                            rect.min,
-                            Vec2::new(8.0, rect.height()),
-                        );
-                        painter.rect_filled(spine_rect, 0.0, SlowColors::BLACK);
-
-                        // Title text (wrapped)
-                        let title_rect = Rect::from_min_max(
-                            egui::pos2(rect.min.x + 12.0, rect.min.y + 10.0),
-                            egui::pos2(rect.max.x - 4.0, rect.max.y - 10.0),
-                        );
-
-                        // Simple word wrap for title
-                        let words: Vec<&str> = title.split_whitespace().collect();
-                        let mut lines: Vec<String> = Vec::new();
-                        let mut current_line = String::new();
-                        let max_chars_per_line = 10;
-
-                        for word in words {
-                            if current_line.len() + word.len() + 1 > max_chars_per_line && !current_line.is_empty() {
-                                lines.push(current_line);
-                                current_line = word.to_string();
-                            } else {
-                                if !current_line.is_empty() {
-                                    current_line.push(' ');
-                                }
-                                current_line.push_str(word);
-                            }
-                        }
-                        if !current_line.is_empty() {
-                            lines.push(current_line);
-                        }
-
-                        // Draw title lines
-                        for (i, line) in lines.iter().take(5).enumerate() {
-                            painter.text(
-                                egui::pos2(title_rect.min.x, title_rect.min.y + i as f32 * 14.0),
-                                egui::Align2::LEFT_TOP,
-                                line,
-                                egui::FontId::proportional(11.0),
-                                SlowColors::BLACK,
-                            );
-                        }
+        rect: Rect,
+        (path, title, progress): &(PathBuf, String, Option<u8>),
+        selected: Option<bool>,
+    ) -> bool {
+        let response = ui.interact(rect, ui.id().with(path), Sense::click());
+        let painter = ui.painter();
+
+        // Book background
+        painter.rect_filled(rect, 2.0, SlowColors::WHITE);
+        painter.rect_stroke(rect, 2.0, Stroke::new(2.0, SlowColors::BLACK));
+
+        // Hover/selection effect
+        if response.hovered() {
+            slowcore::dither::draw_dither_hover(painter, rect);
+        }
 
-                        // Draw bookmark with reading progress (if available)
-                        if let Some(pct) = progress {
-                            // Bookmark shape on top-right corner
-                            let bm_width = 22.0;
-                            let bm_height = 32.0;
-                            let bm_x = rect.max.x - bm_width - 2.0;
-                            let bm_y = rect.min.y - 1.0; // Slightly above to drape over edge
-
-                            // Draw bookmark ribbon shape (rectangle with pointed bottom)
-                            let bm_points = [
-                                egui::pos2(bm_x, bm_y),                           // top-left
-                                egui::pos2(bm_x + bm_width, bm_y),                // top-right
-                                egui::pos2(bm_x + bm_width, bm_y + bm_height),    // bottom-right
-                                egui::pos2(bm_x + bm_width / 2.0, bm_y + bm_height - 6.0), // bottom-center (notch)
-                                egui::pos2(bm_x, bm_y + bm_height),               // bottom-left
-                            ];
-                            painter.add(egui::Shape::convex_polygon(
-                                bm_points.to_vec(),
-                                SlowColors::BLACK,
-                                Stroke::NONE,
-                            ));
-
-                            // Draw percentage text in white
-                            let pct_text = format!("{}%", pct);
-                            painter.text(
-                                egui::pos2(bm_x + bm_width / 2.0, bm_y + bm_height / 2.0 - 2.0),
-                                egui::Align2::CENTER_CENTER,
-                                &pct_text,
-                                egui::FontId::proportional(9.0),
-                                SlowColors::WHITE,
-                            );
-                        }
+        // Book spine decoration
+        let spine_rect = Rect::from_min_size(
+            rect.min,
+            Vec2::new(8.0, rect.height()),
+        );
+        painter.rect_filled(spine_rect, 0.0, SlowColors::BLACK);
+
+        // Title text (wrapped)
+        let title_rect = Rect::from_min_max(
+            egui::pos2(rect.min.x + 12.0, rect.min.y + 10.0),
+            egui::pos2(rect.max.x - 4.0, rect.max.y - 10.0),
+        );
+
+        // Simple word wrap for title
+        let words: Vec<&str> = title.split_whitespace().collect();
+        let mut lines: Vec<String> = Vec::new();
+        let mut current_line = String::new();
+        let max_chars_per_line = 10;
+
+        for word in words {
+            if current_line.len() + word.len() + 1 > max_chars_per_line && !current_line.is_empty() {
+                lines.push(current_line);
+                current_line = word.to_string();
+            } else {
+                if !current_line.is_empty() {
+                    current_line.push(' ');
+                }
+                current_line.push_str(word);
+            }
+        }
+        if !current_line.is_empty() {
+            lines.push(current_line);
+        }
 
-                        // Draw selection circle in bottom-right corner (only for user books)
-                        if show_selection_circles {
-                            let circle_radius = 8.0;
-                            let circle_center = egui::pos2(
-                                rect.max.x - circle_radius - 4.0,
-                                rect.max.y - circle_radius - 4.0,
-                            );
-                            let is_selected = selected_books.contains(path);
+        // Draw title lines
+        for (i, line) in lines.iter().take(5).enumerate() {
+            painter.text(
+                egui::pos2(title_rect.min.x, title_rect.min.y + i as f32 * 14.0),
+                egui::Align2::LEFT_TOP,
+                line,
+                egui::FontId::proportional(11.0),
+                SlowColors::BLACK,
+            );
+        }
 
-                            if is_selected {
-                                // Filled circle for selected
-                                painter.circle_filled(circle_center, circle_radius, SlowColors::BLACK);
-                            } else {
-                                // Empty circle for unselected
-                                painter.circle_stroke(circle_center, circle_radius, Stroke::new(1.5, SlowColors::BLACK));
-                            }
-                        }
-                    }
+        // Draw bookmark with reading progress (if available)
+        if let Some(pct) = progress {
+            // Bookmark shape on top-right corner
+            let bm_width = 22.0;
+            let bm_height = 32.0;
+            let bm_x = rect.max.x - bm_width - 2.0;
+            let bm_y = rect.min.y - 1.0; // Slightly above to drape over edge
+
+            // Draw bookmark ribbon shape (rectangle with pointed bottom)
+            let bm_points = [
+                egui::pos2(bm_x, bm_y),                           // top-left
+                egui::pos2(bm_x + bm_width, bm_y),                // top-right
+                egui::pos2(bm_x + bm_width, bm_y + bm_height),    // bottom-right
+                egui::pos2(bm_x + bm_width / 2.0, bm_y + bm_height - 6.0), // bottom-center (notch)
+                egui::pos2(bm_x, bm_y + bm_height),               // bottom-left
+            ];
+            painter.add(egui::Shape::convex_polygon(
+                bm_points.to_vec(),
+                SlowColors::BLACK,
+                Stroke::NONE,
+            ));
+
+            // Draw percentage text in white
+            let pct_text = format!("{}%", pct);
+            painter.text(
+                egui::pos2(bm_x + bm_width / 2.0, bm_y + bm_height / 2.0 - 2.0),
+                egui::Align2::CENTER_CENTER,
+                &pct_text,
+                egui::FontId::proportional(9.0),
+                SlowColors::WHITE,
+            );
+        }
 
-                    // Handle book clicks
-                    if response.clicked() {
-                        if show_selection_circles {
-                            // In delete mode, clicking anywhere on the book toggles selection
-                            *toggle_selection = Some(path.clone());
-                        } else {
-                            // Normal mode: open the book
-                            *book_to_open = Some(path.clone());
-                        }
-                    }
+        // Draw selection circle in bottom-right corner (only for user books)
+        if let Some(is_selected) = selected {
+            let circle_radius = 8.0;
+            let circle_center = egui::pos2(
+                rect.max.x - circle_radius - 4.0,
+                rect.max.y - circle_radius - 4.0,
+            );
 
-                    ui.add_space(padding);
-                }
-            });
-            ui.add_space(padding);
+            if is_selected {
+                // Filled circle for selected
+                painter.circle_filled(circle_center, circle_radius, SlowColors::BLACK);
+            } else {
+                // Empty circle for unselected
+                painter.circle_stroke(circle_center, circle_radius, Stroke::new(1.5, SlowColors::BLACK));
+            }
         }
+
+        response.clicked()
     }
     
     fn render_reader(&mut self, ui: &mut egui::Ui) {
@@ -777,141 +1382,370 @@ impl SlowReaderApp {
         }
     }
     
-    fn render_file_browser(&mut self, ctx: &Context) {
-        let resp = egui::Window::new("open book")
+    fn show_open_dialog(&mut self) {
+        self.file_dialog = Some(
+            FileDialog::open(documents_dir())
+                .title("open book")
+                .filter(&["epub", "txt", "pdf", "cbz", "cbr"]),
+        );
+    }
+
+    fn render_file_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.file_dialog else { return };
+        match dialog.show(ctx) {
+            FileDialogResult::Picked(path) => {
+                self.file_dialog = None;
+                self.open_book(path);
+            }
+            FileDialogResult::Cancelled => self.file_dialog = None,
+            FileDialogResult::Pending => {}
+        }
+    }
+
+    fn render_settings(&mut self, ctx: &Context) {
+        let resp = egui::Window::new("reading settings")
             .collapsible(false)
             .resizable(false)
-            .default_width(380.0)
             .show(ctx, |ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Location:");
-                    ui.label(self.file_browser.current_dir.to_string_lossy().to_string());
+                    ui.label("font size:");
+                    ui.add(egui::Slider::new(&mut self.reader.settings.font_size, 12.0..=32.0));
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.label("line height:");
+                    ui.add(egui::Slider::new(&mut self.reader.settings.line_height, 1.0..=2.5));
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.label("margin:");
+                    ui.add(egui::Slider::new(&mut self.reader.settings.margin, 10.0..=100.0));
                 });
 
                 ui.separator();
-
-                egui::ScrollArea::vertical().max_height(220.0).show(ui, |ui| {
-                    let mut clicked_idx = None;
-                    let mut nav_path = None;
-                    let mut open_path = None;
-                    for (idx, entry) in self.file_browser.entries.iter().enumerate() {
-                        let selected = self.file_browser.selected_index == Some(idx);
-                        let response = ui.add(
-                            slowcore::widgets::FileListItem::new(&entry.name, entry.is_directory)
-                                .selected(selected)
-                        );
-
-                        if response.clicked() {
-                            clicked_idx = Some(idx);
-                        }
-
-                        if response.double_clicked() {
-                            if entry.is_directory {
-                                nav_path = Some(entry.path.clone());
-                            } else {
-                                open_path = Some(entry.path.clone());
-                            }
-                        }
+                ui.label("daily reading goal:");
+                let goal = self.reading_log.goal;
+                let mut amount = goal.map_or(20, Goal::target);
+                ui.horizontal(|ui| {
+                    let mut pick = goal;
+                    ui.radio_value(&mut pick, None, "none");
+                    if ui.radio(matches!(goal, Some(Goal::Minutes(_))), "minutes").clicked() {
+                        pick = Some(Goal::Minutes(amount));
+                    }
+                    if ui.radio(matches!(goal, Some(Goal::Pages(_))), "pages").clicked() {
+                        pick = Some(Goal::Pages(amount));
                     }
-                    if let Some(idx) = clicked_idx { self.file_browser.selected_index = Some(idx); }
-                    if let Some(path) = nav_path { self.file_browser.navigate_to(path); }
-                    if let Some(path) = open_path {
-                        self.open_book(path);
-                        self.show_file_browser = false;
+                    if pick.is_some() && ui.add(egui::DragValue::new(&mut amount).clamp_range(1..=999)).changed() {
+                        pick = pick.map(|g| match g {
+                            Goal::Minutes(_) => Goal::Minutes(amount),
+                            Goal::Pages(_) => Goal::Pages(amount),
+                        });
+                    }
+                    if pick != goal {
+                        self.reading_log.goal = pick;
+                        self.reading_log.save();
                     }
                 });
-                
+
+                ui.separator();
+                ui.label("add new books to the library from:");
+                let mut import = self.importer.settings();
+                let mut changed = ui.checkbox(&mut import.watch_downloads, "Downloads").changed();
+                ui.horizontal(|ui| {
+                    ui.label("drop folder:");
+                    let resp = ui.add(egui::TextEdit::singleline(&mut self.drop_folder).hint_text("none").desired_width(180.0));
+                    if resp.lost_focus() {
+                        let typed = self.drop_folder.trim();
+                        let folder = (!typed.is_empty()).then(|| PathBuf::from(typed));
+                        changed |= folder != import.drop_folder;
+                        import.drop_folder = folder;
+                    }
+                });
+                if changed {
+                    self.importer.set_settings(import);
+                }
+
                 ui.separator();
                 
+                if ui.button("close").clicked() {
+                    self.show_settings = false;
+                }
+            });
+        if let Some(r) = &resp {
+            slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+        }
+    }
+
+    fn render_bookmark_dialog(&mut self, ctx: &Context) {
+        let Some(name) = &mut self.bookmark_name else { return };
+        let mut add = false;
+        let mut close = false;
+        let resp = egui::Window::new("add bookmark")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                ui.label("name:");
+                let field = ui.text_edit_singleline(name);
+                field.request_focus();
+                if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    add = true;
+                }
+                ui.separator();
                 ui.horizontal(|ui| {
                     if ui.button("cancel").clicked() {
-                        self.show_file_browser = false;
+                        close = true;
                     }
-                    if ui.button("open").clicked() {
-                        if let Some(entry) = self.file_browser.selected_entry() {
-                            if !entry.is_directory {
-                                let path = entry.path.clone();
-                                self.open_book(path);
-                                self.show_file_browser = false;
-                            }
-                        }
+                    if ui.button("add").clicked() {
+                        add = true;
                     }
                 });
             });
         if let Some(r) = &resp {
             slowcore::dither::draw_window_shadow(ctx, r.response.rect);
         }
+        if add {
+            if let Some(name) = self.bookmark_name.take() {
+                self.add_bookmark(&name);
+            }
+        } else if close {
+            self.bookmark_name = None;
+        }
     }
-    
-    fn render_settings(&mut self, ctx: &Context) {
-        let resp = egui::Window::new("reading settings")
+
+    fn render_bookmarks(&mut self, ctx: &Context) {
+        let Some(path) = self.current_book.as_ref().map(|b| b.path.clone()) else {
+            self.show_bookmarks = false;
+            return;
+        };
+        let marks = self.library.bookmarks(&path).to_vec();
+        let mut go_to = None;
+        let mut remove = None;
+        let mut rename = None;
+        let resp = egui::Window::new("bookmarks")
             .collapsible(false)
             .resizable(false)
+            .default_width(320.0)
             .show(ctx, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label("font size:");
-                    ui.add(egui::Slider::new(&mut self.reader.settings.font_size, 12.0..=32.0));
-                });
-                
-                ui.horizontal(|ui| {
-                    ui.label("line height:");
-                    ui.add(egui::Slider::new(&mut self.reader.settings.line_height, 1.0..=2.5));
-                });
-                
-                ui.horizontal(|ui| {
-                    ui.label("margin:");
-                    ui.add(egui::Slider::new(&mut self.reader.settings.margin, 10.0..=100.0));
+                egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+                    if marks.is_empty() {
+                        ui.label("no bookmarks yet; press b on a page to add one");
+                    }
+                    for (i, mark) in marks.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            match &mut self.renaming_bookmark {
+                                Some((j, name)) if *j == i => {
+                                    let field = ui.text_edit_singleline(name);
+                                    field.request_focus();
+                                    if field.lost_focus() {
+                                        rename = Some(i);
+                                    }
+                                }
+                                _ => {
+                                    if ui.selectable_label(false, &mark.name).clicked() {
+                                        go_to = Some(i);
+                                    }
+                                    ui.label(format!("ch. {}", mark.chapter + 1));
+                                    if ui.small_button("rename").clicked() {
+                                        self.renaming_bookmark = Some((i, mark.name.clone()));
+                                    }
+                                    if ui.small_button("\u{d7}").clicked() {
+                                        remove = Some(i);
+                                    }
+                                }
+                            }
+                        });
+                    }
                 });
-                
                 ui.separator();
-                
                 if ui.button("close").clicked() {
-                    self.show_settings = false;
+                    self.show_bookmarks = false;
                 }
             });
         if let Some(r) = &resp {
             slowcore::dither::draw_window_shadow(ctx, r.response.rect);
         }
+
+        if let Some((i, name)) = rename.and(self.renaming_bookmark.take()) {
+            if !name.trim().is_empty() {
+                self.library.rename_bookmark(&path, i, name.trim());
+            }
+        }
+        if let Some(i) = remove {
+            self.library.remove_bookmark(&path, i);
+            self.renaming_bookmark = None;
+        }
+        if let Some(mark) = go_to.and_then(|i| marks.get(i)) {
+            self.go_to_bookmark(mark);
+        }
     }
 
-    fn render_about(&mut self, ctx: &Context) {
-        let screen = ctx.screen_rect();
-        let max_h = (screen.height() - 60.0).max(120.0);
-        let resp = egui::Window::new("about slowReader")
+    /// Browse OPDS catalogs, download books from them, and follow the
+    /// downloads
+    fn render_catalogs(&mut self, ctx: &Context) {
+        let mut open = None;
+        let mut back = false;
+        let mut download = None;
+        let mut add_catalog = false;
+        let mut remove_catalog = None;
+        let mut stop = None;
+        let mut resume = None;
+        let mut cancel = None;
+        let mut close = false;
+        let browser = &self.catalogs;
+        let new_catalog = &mut self.new_catalog;
+        let resp = egui::Window::new("catalogs")
             .collapsible(false)
-            .resizable(false)
-            .default_width(300.0)
-            .max_height(max_h)
+            .resizable(true)
+            .default_width(440.0)
             .show(ctx, |ui| {
-                egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                    ui.vertical_centered(|ui| {
-                        ui.heading("slowReader");
-                        ui.label("version 0.2.2");
-                        ui.add_space(8.0);
-                        ui.label("ebook reader for slowOS");
-                    });
-                    ui.add_space(8.0);
+                match browser.trail.last() {
+                    None => {
+                        ui.label("online libraries to download books from:");
+                        ui.add_space(4.0);
+                        for (i, catalog) in browser.catalogs.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.button(&catalog.name).clicked() {
+                                    open = Some(catalog.url.clone());
+                                }
+                                ui.label(egui::RichText::new(&catalog.url).small());
+                                if ui.small_button("\u{d7}").clicked() {
+                                    remove_catalog = Some(i);
+                                }
+                            });
+                        }
+                        ui.add_space(4.0);
+                        ui.horizontal(|ui| {
+                            ui.label("add OPDS address:");
+                            let field = ui.text_edit_singleline(new_catalog);
+                            if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                                add_catalog = true;
+                            }
+                            if ui.button("add").clicked() {
+                                add_catalog = true;
+                            }
+                        });
+                    }
+                    Some(feed) => {
+                        ui.horizontal(|ui| {
+                            if ui.button("< back").clicked() {
+                                back = true;
+                            }
+                            ui.label(egui::RichText::new(&feed.title).strong());
+                        });
+                        ui.separator();
+                        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                            if feed.entries.is_empty() {
+                                ui.label("nothing here");
+                            }
+                            for (i, entry) in feed.entries.iter().enumerate() {
+                                ui.label(egui::RichText::new(&entry.title).strong());
+                                if !entry.author.is_empty() {
+                                    ui.label(&entry.author);
+                                }
+                                if !entry.summary.is_empty() {
+                                    let mut summary: String = entry.summary.chars().take(240).collect();
+                                    if summary.len() < entry.summary.len() {
+                                        summary.push('\u{2026}');
+                                    }
+                                    ui.label(egui::RichText::new(summary).small());
+                                }
+                                if let Some(url) = &entry.epub {
+                                    if browser.download_of(url).is_some() {
+                                        ui.label("in downloads");
+                                    } else if ui.button("download").clicked() {
+                                        download = Some(i);
+                                    }
+                                } else if let Some(url) = &entry.feed {
+                                    if ui.button("open").clicked() {
+                                        open = Some(url.clone());
+                                    }
+                                }
+                                ui.add_space(8.0);
+                            }
+                            if let Some(next) = &feed.next {
+                                if ui.button("next page").clicked() {
+                                    open = Some(next.clone());
+                                }
+                            }
+                        });
+                    }
+                }
+                if browser.is_loading() {
+                    ui.label("loading...");
+                }
+                if let Some(error) = &browser.error {
+                    ui.label(error);
+                }
+
+                if !browser.downloads.is_empty() {
                     ui.separator();
-                    ui.add_space(4.0);
-                    ui.label("supported formats:");
-                    ui.label("  EPUB (.epub)");
-                    ui.add_space(4.0);
-                    ui.label("features:");
-                    ui.label("  chapter navigation, bookmarks");
-                    ui.label("  CJK font support");
-                    ui.add_space(4.0);
-                    ui.label("frameworks:");
-                    ui.label("  egui/eframe (MIT), epub-rs (MIT)");
-                    ui.add_space(8.0);
-                    ui.vertical_centered(|ui| {
-                        if ui.button("ok").clicked() {
-                            self.show_about = false;
+                    ui.label(egui::RichText::new("downloads").strong());
+                    for (i, d) in browser.downloads.iter().enumerate() {
+                        ui.label(&d.title);
+                        ui.horizontal(|ui| {
+                            progress_bar(ui, 160.0, d.total.map_or(0.0, |t| d.done as f32 / t.max(1) as f32));
+                            match d.total {
+                                Some(total) => ui.label(format!("{} of {}", format_size(d.done), format_size(total))),
+                                None => ui.label(format_size(d.done)),
+                            };
+                            if d.is_running() {
+                                if ui.small_button("stop").clicked() {
+                                    stop = Some(i);
+                                }
+                            } else if ui.small_button("resume").clicked() {
+                                resume = Some(i);
+                            }
+                            if ui.small_button("\u{d7}").clicked() {
+                                cancel = Some(i);
+                            }
+                        });
+                        if let Some(error) = &d.error {
+                            ui.label(egui::RichText::new(error).small());
+                        } else if !d.is_running() {
+                            ui.label(egui::RichText::new("stopped").small());
                         }
-                    });
-                });
+                    }
+                }
+
+                ui.separator();
+                if ui.button("close").clicked() {
+                    close = true;
+                }
             });
         if let Some(r) = &resp {
-            slowcore::dither::draw_window_shadow_large(ctx, r.response.rect);
+            slowcore::dither::draw_window_shadow(ctx, r.response.rect);
+        }
+
+        if let Some(url) = open {
+            self.catalogs.open(&url, ctx);
+        }
+        if back {
+            self.catalogs.back();
+        }
+        if let Some(entry) = download.and_then(|i| self.catalogs.trail.last()?.entries.get(i).cloned()) {
+            self.catalogs.download(&entry, ctx);
+        }
+        if add_catalog {
+            match self.catalogs.add_catalog(&self.new_catalog) {
+                Ok(()) => self.new_catalog.clear(),
+                Err(e) => self.catalogs.error = Some(e),
+            }
+        }
+        if let Some(i) = remove_catalog {
+            self.catalogs.remove_catalog(i);
+        }
+        if let Some(download) = stop.and_then(|i| self.catalogs.downloads.get(i)) {
+            download.stop();
+        }
+        if let Some(i) = resume {
+            self.catalogs.resume(i, ctx);
+        }
+        if let Some(i) = cancel {
+            self.catalogs.cancel(i);
+        }
+        if close {
+            self.show_catalogs = false;
         }
     }
 
@@ -1074,6 +1908,12 @@ impl SlowReaderApp {
                     ui.label("T");
                     ui.label("toggle table of contents");
                     ui.end_row();
+                    ui.label("B");
+                    ui.label("add bookmark");
+                    ui.end_row();
+                    ui.label("R");
+                    ui.label("read aloud / pause");
+                    ui.end_row();
                     ui.label("Escape");
                     ui.label("close book / return to library");
                     ui.end_row();
@@ -1091,6 +1931,24 @@ impl SlowReaderApp {
                     ui.end_row();
                 });
 
+                ui.add_space(12.0);
+                ui.heading("comics");
+                ui.add_space(4.0);
+                egui::Grid::new("comic_shortcuts").show(ui, |ui| {
+                    ui.label("← / →");
+                    ui.label("turn towards that side");
+                    ui.end_row();
+                    ui.label("Home / End");
+                    ui.label("first / last page");
+                    ui.end_row();
+                    ui.label("D");
+                    ui.label("two-page spread");
+                    ui.end_row();
+                    ui.label("R");
+                    ui.label("right to left");
+                    ui.end_row();
+                });
+
                 ui.add_space(12.0);
                 ui.heading("file");
                 ui.add_space(4.0);
@@ -1121,11 +1979,19 @@ impl SlowReaderApp {
 impl eframe::App for SlowReaderApp {
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
         self.repaint.begin_frame(ctx);
+        self.slow_library_books.poll(ctx);
         if slowcore::minimize::check_restore_signal("slowreader") {
             ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
             ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
         }
+        self.handle_commands(ctx);
         self.handle_keyboard(ctx);
+        self.update_reading_aloud(ctx);
+        self.take_imports();
+        for (title, path) in self.catalogs.poll() {
+            self.add_book_to_library(path);
+            slowcore::notify::post("slowreader", &format!("downloaded {}", title));
+        }
 
         // Auto-save position periodically when reading
         if self.view == View::Reader {
@@ -1137,6 +2003,9 @@ impl eframe::App for SlowReaderApp {
                 );
             }
         }
+        if let Some(viewer) = self.comic.as_ref().filter(|_| self.view == View::Comic) {
+            self.library.update_position(&viewer.comic.path, viewer.page, 0.0);
+        }
 
         // Menu bar: always visible in normal mode, hover-to-show in fullscreen
         // Keep visible while any dropdown menu is open so it doesn't vanish mid-use
@@ -1171,11 +2040,14 @@ impl eframe::App for SlowReaderApp {
         if self.view == View::Reader && !self.fullscreen {
             if let Some(ref book) = self.current_book {
                 egui::TopBottomPanel::top("title").show(ctx, |ui| {
-                    slowcore::theme::SlowTheme::title_bar_frame().show(ui, |ui| {
-                        ui.centered_and_justified(|ui| {
-                            ui.label(&book.metadata.title);
-                        });
-                    });
+                    slowcore::widgets::document_title_bar(ui, &book.metadata.title, Some(&book.path), false);
+                });
+            }
+        }
+        if self.view == View::Comic && !self.fullscreen {
+            if let Some(viewer) = &self.comic {
+                egui::TopBottomPanel::top("title").show(ctx, |ui| {
+                    slowcore::widgets::document_title_bar(ui, &viewer.comic.title, Some(&viewer.comic.path), false);
                 });
             }
         }
@@ -1186,26 +2058,52 @@ impl eframe::App for SlowReaderApp {
             let status = if self.view == View::Reader {
                 if let Some(ref book) = self.current_book {
                     let (page, total) = self.reader.page_info();
+                    let goal = self.goal_text();
+                    let hint = if let Some(error) = &self.speech_error {
+                        error.as_str()
+                    } else if self.speaker.is_paused() {
+                        "reading aloud paused"
+                    } else if self.speaker.is_active() {
+                        "reading aloud"
+                    } else if let Some(goal) = &goal {
+                        goal.as_str()
+                    } else {
+                        "←/→ or click to turn"
+                    };
                     format!(
-                        "chapter {} of {}  |  page {} of {}  |  ←/→ or click to turn",
+                        "chapter {} of {}  |  page {} of {}  |  {}",
                         self.reader.position.chapter + 1,
                         book.chapter_count(),
                         page,
-                        total
+                        total,
+                        hint
                     )
                 } else {
                     String::new()
                 }
+            } else if let Some(viewer) = self.comic.as_ref().filter(|_| self.view == View::Comic) {
+                let shown = viewer.shown();
+                let pages = match shown.as_slice() {
+                    [a, b] => format!("pages {}\u{2013}{}", a + 1, b + 1),
+                    _ => format!("page {}", viewer.page + 1),
+                };
+                let direction = if viewer.right_to_left { "right to left" } else { "left to right" };
+                format!("{} of {}  |  {}  |  click a side to turn", pages, viewer.comic.page_count(), direction)
             } else {
-                format!("{} books in library", self.library.books.len() + self.slow_library_books.len())
+                let books = format!("{} books in library", self.library.books.len() + self.slow_library_books.get().map_or(0, Vec::len));
+                match self.goal_text() {
+                    Some(goal) => format!("{}  |  {}", books, goal),
+                    None => books,
+                }
             };
             status_bar(ui, &status);
             });
         }
 
         // Suppress reader click-to-turn-page when any dialog is open
-        self.reader.suppress_clicks = self.show_toc || self.show_file_browser
-            || self.show_settings || self.show_about || self.show_shortcuts || self.show_search;
+        self.reader.suppress_clicks = self.show_toc || self.file_dialog.is_some()
+            || self.show_settings || self.show_about || self.show_shortcuts || self.show_search
+            || self.show_bookmarks || self.bookmark_name.is_some() || self.show_catalogs;
 
         // Main content
         egui::CentralPanel::default()
@@ -1214,25 +2112,37 @@ impl eframe::App for SlowReaderApp {
                 match self.view {
                     View::Library => self.render_library(ui),
                     View::Reader => self.render_reader(ui),
+                    View::Comic => {
+                        let suppress = self.reader.suppress_clicks;
+                        if let Some(viewer) = &mut self.comic {
+                            viewer.render(ui, suppress);
+                        }
+                    }
                 }
             });
+        self.track_reading(ctx);
 
         // Dialogs
-        if self.show_file_browser {
-            self.render_file_browser(ctx);
-        }
+        self.render_file_dialog(ctx);
         if self.show_toc {
             self.render_toc(ctx);
         }
         if self.show_settings {
             self.render_settings(ctx);
         }
-        if self.show_about {
-            self.render_about(ctx);
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
         if self.show_shortcuts {
             self.render_shortcuts(ctx);
         }
+        if self.show_bookmarks {
+            self.render_bookmarks(ctx);
+        }
+        self.render_bookmark_dialog(ctx);
+        if self.show_catalogs {
+            self.render_catalogs(ctx);
+        }
 
         // Search dialog (Ctrl+F)
         if self.show_search {
@@ -1242,6 +2152,8 @@ impl eframe::App for SlowReaderApp {
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.close_comic();
+        self.log_reading();
         // Save position on exit
         if let Some(ref book) = self.current_book {
             self.library.update_position(
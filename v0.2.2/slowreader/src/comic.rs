@@ -0,0 +1,418 @@
+//! Comics — .cbz and .cbr archives of page images
+//!
+//! A comic's pages are the images in its archive, in name order with
+//! numbers counted ("page2" before "page10"). A .cbz is a zip and is read
+//! in place; a .cbr is usually a rar, unpacked once with whichever of
+//! unrar, bsdtar or 7z is installed.
+//!
+//! Pages are fitted whole to the screen, one at a time or two side by
+//! side, and are read left to right or, for manga, right to left. Each is
+//! made 1-bit when first shown: comics are mostly line art on paper, so
+//! the page's levels are stretched until the paper is white and the ink
+//! black, and Atkinson dithering, which leaves flat areas clean, renders
+//! whatever greys are left.
+
+use egui::{Color32, ColorImage, Rect, Sense, Stroke, TextureHandle, Ui, Vec2};
+use image::GrayImage;
+use slowcore::dither::atkinson;
+use slowcore::theme::SlowColors;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::Read;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::Chars;
+
+const PAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "gif", "bmp", "webp"];
+
+/// Room left around the pages
+const MARGIN: f32 = 8.0;
+
+pub fn is_comic(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("cbz") || e.eq_ignore_ascii_case("cbr"))
+}
+
+fn is_page(name: &str) -> bool {
+    let name = name.rsplit('/').next().unwrap_or(name);
+    let ext = name.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+    !name.starts_with('.') && PAGE_EXTENSIONS.contains(&ext.as_str())
+}
+
+fn digits(chars: &mut Peekable<Chars>) -> String {
+    let mut run = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+        run.push(c);
+    }
+    run
+}
+
+/// Compare names the way people count: runs of digits by their value,
+/// the rest ignoring case
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let order = match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, y) = (digits(&mut a), digits(&mut b));
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            (Some(x), Some(y)) => {
+                let order = x.to_ascii_lowercase().cmp(&y.to_ascii_lowercase());
+                a.next();
+                b.next();
+                order
+            }
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+}
+
+/// Where the pages are read from
+enum Source {
+    Zip(PathBuf),
+    /// An unpacked .cbr
+    Folder(PathBuf),
+}
+
+pub struct Comic {
+    pub path: PathBuf,
+    pub title: String,
+    source: Source,
+    /// Page names inside the archive, or paths inside the folder
+    pages: Vec<String>,
+}
+
+impl Comic {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let (source, mut pages) = match zip_pages(path) {
+            Ok(pages) => (Source::Zip(path.to_path_buf()), pages),
+            Err(_) => {
+                let folder = unpack(path)?;
+                let pages = folder_pages(&folder);
+                (Source::Folder(folder), pages)
+            }
+        };
+        pages.retain(|p| is_page(p) && !p.starts_with("__MACOSX/"));
+        pages.sort_by(|a, b| natural_cmp(a, b));
+        if pages.is_empty() {
+            return Err("no pages in this comic".into());
+        }
+        let title = path.file_stem().map(|s| s.to_string_lossy().replace('_', " ")).unwrap_or_default();
+        Ok(Self { path: path.to_path_buf(), title, source, pages })
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// The image file of page `index`
+    fn page_bytes(&self, index: usize) -> Option<Vec<u8>> {
+        let name = self.pages.get(index)?;
+        let mut bytes = Vec::new();
+        match &self.source {
+            Source::Zip(path) => {
+                let mut archive = zip::ZipArchive::new(std::fs::File::open(path).ok()?).ok()?;
+                archive.by_name(name).ok()?.read_to_end(&mut bytes).ok()?;
+            }
+            Source::Folder(folder) => bytes = std::fs::read(folder.join(name)).ok()?,
+        }
+        Some(bytes)
+    }
+}
+
+fn zip_pages(path: &Path) -> Result<Vec<String>, String> {
+    let archive = zip::ZipArchive::new(std::fs::File::open(path).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+    Ok(archive.file_names().map(str::to_string).collect())
+}
+
+/// Paths of the files under `folder`, relative to it, with `/` between
+fn folder_pages(folder: &Path) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut dirs = vec![folder.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if let Ok(relative) = path.strip_prefix(folder) {
+                pages.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    pages
+}
+
+/// Unpack a rar into a folder of its own under the temp dir, unless
+/// that's been done already
+fn unpack(path: &Path) -> Result<PathBuf, String> {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    path.hash(&mut hasher);
+    std::fs::metadata(path).and_then(|m| m.modified()).ok().hash(&mut hasher);
+    let folder = std::env::temp_dir().join(format!("slowreader-comic-{:x}", hasher.finish()));
+    if folder.join(".unpacked").exists() {
+        return Ok(folder);
+    }
+    std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+
+    let out = folder.to_string_lossy().to_string();
+    let file = path.to_string_lossy().to_string();
+    let tools: [(&str, Vec<String>); 3] = [
+        ("unrar", vec!["x".into(), "-o+".into(), "-y".into(), "-inul".into(), file.clone(), format!("{}/", out)]),
+        ("bsdtar", vec!["-xf".into(), file.clone(), "-C".into(), out.clone()]),
+        ("7z", vec!["x".into(), "-y".into(), format!("-o{}", out), file]),
+    ];
+    for (tool, args) in tools {
+        if let Ok(status) = Command::new(tool).args(&args).stdout(std::process::Stdio::null()).stderr(std::process::Stdio::null()).status() {
+            if status.success() {
+                let _ = std::fs::write(folder.join(".unpacked"), "");
+                return Ok(folder);
+            }
+        }
+    }
+    let _ = std::fs::remove_dir_all(&folder);
+    Err("can't open this comic: it needs unrar, bsdtar or 7z to unpack".into())
+}
+
+/// Stretch `image`'s levels so the paper comes out white and the ink
+/// black. Paper is taken as the tone nine tenths of the page is darker
+/// than, ink as the darkest fiftieth; both are held back from grey so a
+/// dark or washed-out page isn't pushed too far.
+fn stretch_levels(image: &mut GrayImage) {
+    let mut histogram = [0usize; 256];
+    for pixel in image.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+    let total = image.width() as usize * image.height() as usize;
+    let level_at = |fraction: f64| {
+        let target = (total as f64 * fraction) as usize;
+        let mut seen = 0;
+        for (level, count) in histogram.iter().enumerate() {
+            seen += count;
+            if seen > target {
+                return level as i32;
+            }
+        }
+        255
+    };
+    let ink = level_at(0.02).min(80);
+    let paper = level_at(0.90).max(176);
+    for pixel in image.pixels_mut() {
+        pixel.0[0] = ((pixel.0[0] as i32 - ink) * 255 / (paper - ink)).clamp(0, 255) as u8;
+    }
+}
+
+/// Decode a page, fit it within `size` pixels and make it 1-bit
+pub fn prepare_page(bytes: &[u8], size: [u32; 2]) -> Option<GrayImage> {
+    let decoded = image::load_from_memory(bytes).ok()?;
+    let scale = (size[0] as f32 / decoded.width() as f32).min(size[1] as f32 / decoded.height() as f32);
+    let width = ((decoded.width() as f32 * scale).round() as u32).max(1);
+    let height = ((decoded.height() as f32 * scale).round() as u32).max(1);
+    let mut grey = decoded.resize_exact(width, height, image::imageops::FilterType::CatmullRom).to_luma8();
+    stretch_levels(&mut grey);
+    atkinson(&mut grey);
+    Some(grey)
+}
+
+/// An open comic and how it's being read
+pub struct ComicViewer {
+    pub comic: Comic,
+    /// The first page showing
+    pub page: usize,
+    /// Two pages side by side, after the cover on its own
+    pub spread: bool,
+    pub right_to_left: bool,
+    /// Pages made ready to show, with the size they were fitted to; None
+    /// where the page couldn't be read
+    textures: HashMap<usize, ([u32; 2], Option<TextureHandle>)>,
+}
+
+impl ComicViewer {
+    pub fn new(comic: Comic, page: usize, right_to_left: bool) -> Self {
+        let page = page.min(comic.page_count() - 1);
+        Self { comic, page, spread: false, right_to_left, textures: HashMap::new() }
+    }
+
+    /// The first page of the spread holding `page`
+    fn spread_start(&self, page: usize) -> usize {
+        if !self.spread || page == 0 {
+            page
+        } else {
+            page - (page + 1) % 2
+        }
+    }
+
+    /// The pages showing, in reading order
+    pub fn shown(&self) -> Vec<usize> {
+        let first = self.spread_start(self.page);
+        if self.spread && first > 0 && first + 1 < self.comic.page_count() {
+            vec![first, first + 1]
+        } else {
+            vec![first]
+        }
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.shown().last().is_some_and(|&p| p + 1 >= self.comic.page_count())
+    }
+
+    pub fn next_page(&mut self) {
+        let last = self.shown().last().copied().unwrap_or(self.page);
+        if last + 1 < self.comic.page_count() {
+            self.page = last + 1;
+        }
+    }
+
+    pub fn prev_page(&mut self) {
+        let first = self.spread_start(self.page);
+        if first > 0 {
+            self.page = self.spread_start(first - 1);
+        }
+    }
+
+    pub fn first_page(&mut self) {
+        self.page = 0;
+    }
+
+    pub fn last_page(&mut self) {
+        self.page = self.spread_start(self.comic.page_count() - 1);
+    }
+
+    pub fn toggle_spread(&mut self) {
+        self.spread = !self.spread;
+        self.page = self.spread_start(self.page);
+    }
+
+    /// Turn towards the left-hand side of the screen: back, or on when
+    /// reading right to left
+    pub fn turn_left(&mut self) {
+        if self.right_to_left { self.next_page() } else { self.prev_page() }
+    }
+
+    pub fn turn_right(&mut self) {
+        if self.right_to_left { self.prev_page() } else { self.next_page() }
+    }
+
+    fn texture(&mut self, ui: &Ui, page: usize, size: [u32; 2]) -> Option<TextureHandle> {
+        if self.textures.get(&page).is_none_or(|(fitted, _)| *fitted != size) {
+            let texture = self.comic.page_bytes(page).and_then(|bytes| prepare_page(&bytes, size)).map(|grey| {
+                let image = ColorImage::from_gray([grey.width() as usize, grey.height() as usize], grey.as_raw());
+                ui.ctx().load_texture(format!("comic_page_{}", page), image, egui::TextureOptions::NEAREST)
+            });
+            self.textures.insert(page, (size, texture));
+        }
+        self.textures.get(&page).and_then(|(_, texture)| texture.clone())
+    }
+
+    /// Draw the pages showing, fitted to the space left in `ui`. Clicks on
+    /// the left or right half turn that way, unless a dialog is open.
+    pub fn render(&mut self, ui: &mut Ui, suppress_clicks: bool) {
+        let rect = ui.available_rect_before_wrap();
+        let response = ui.allocate_rect(rect, Sense::click());
+        ui.painter().rect_filled(rect, 0.0, SlowColors::WHITE);
+        let area = rect.shrink(MARGIN);
+        let ppp = ui.ctx().pixels_per_point();
+
+        let mut shown = self.shown();
+        if self.right_to_left {
+            shown.reverse();
+        }
+        // Pages in a spread meet at the middle, a single page is centred
+        let slots: Vec<(Rect, f32)> = if shown.len() == 2 {
+            let (left, right) = area.split_left_right_at_fraction(0.5);
+            vec![(left, 1.0), (right, 0.0)]
+        } else {
+            vec![(area, 0.5)]
+        };
+        for (&page, (slot, align)) in shown.iter().zip(slots) {
+            let size = [(slot.width() * ppp) as u32, (slot.height() * ppp) as u32];
+            if size[0] == 0 || size[1] == 0 {
+                continue;
+            }
+            match self.texture(ui, page, size) {
+                Some(texture) => {
+                    let image_size = texture.size_vec2() / ppp;
+                    let min = slot.min + Vec2::new((slot.width() - image_size.x) * align, (slot.height() - image_size.y) / 2.0);
+                    let image_rect = Rect::from_min_size(min, image_size);
+                    ui.painter().image(texture.id(), image_rect, Rect::from_min_max([0.0, 0.0].into(), [1.0, 1.0].into()), Color32::WHITE);
+                    ui.painter().rect_stroke(image_rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+                }
+                None => {
+                    ui.painter().text(
+                        slot.center(),
+                        egui::Align2::CENTER_CENTER,
+                        format!("page {} can't be shown", page + 1),
+                        egui::FontId::proportional(14.0),
+                        SlowColors::BLACK,
+                    );
+                }
+            }
+        }
+
+        // Keep only the pages near the ones showing
+        let first = self.spread_start(self.page);
+        self.textures.retain(|&p, _| p + 4 >= first && p <= first + 5);
+
+        if response.clicked() && !suppress_clicks {
+            if let Some(pos) = response.interact_pointer_pos() {
+                if pos.x < rect.center().x {
+                    self.turn_left();
+                } else {
+                    self.turn_right();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::ImageFormat;
+    use std::io::Write;
+
+    #[test]
+    fn test_comic() {
+        let mut names = vec!["Page10.png", "page2.png", "page1.png", "cover.png"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, ["cover.png", "page1.png", "page2.png", "Page10.png"]);
+        assert_eq!(natural_cmp("p007", "p7"), Ordering::Equal);
+
+        // A zip of three pages, with things that aren't pages
+        let mut png = Vec::new();
+        GrayImage::from_pixel(40, 60, image::Luma([200])).write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png).unwrap();
+        let path = std::env::temp_dir().join(format!("slowreader-comic-{}.cbz", std::process::id()));
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for name in ["p10.png", "p2.png", "__MACOSX/._p2.png", "p1.png", "ComicInfo.xml"] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(&png).unwrap();
+        }
+        zip.finish().unwrap();
+        let comic = Comic::open(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(comic.pages, ["p1.png", "p2.png", "p10.png"]);
+
+        // Pale grey paper comes out white, and fits the height given
+        let page = prepare_page(&png, [100, 30]).unwrap();
+        assert_eq!((page.width(), page.height()), (20, 30));
+        assert!(page.pixels().all(|p| p.0[0] == 255));
+
+        // Spreads: the cover alone, then pairs; right to left turns the
+        // other way
+        let mut viewer = ComicViewer::new(comic, 0, true);
+        viewer.spread = true;
+        viewer.turn_left();
+        assert_eq!(viewer.shown(), [1, 2]);
+        assert!(viewer.at_end());
+        viewer.turn_right();
+        assert_eq!(viewer.shown(), [0]);
+    }
+}
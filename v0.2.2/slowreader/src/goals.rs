@@ -0,0 +1,229 @@
+//! Reading goals and the reading log
+//!
+//! Time spent with a book open and pages turned forward are added up
+//! per day and per book in ~/.config/slowreader/log.json. A daily goal,
+//! in minutes or pages, is measured against today's total, and a year
+//! of the log can be written out as a text file.
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use slowcore::storage::{config_dir, documents_dir, unique_path};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// A longer wait between frames counts as stepping away: only this
+/// much of it is taken as reading
+pub const IDLE_LIMIT_SECS: f64 = 300.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Goal {
+    Minutes(u32),
+    Pages(u32),
+}
+
+impl Goal {
+    pub fn target(self) -> u32 {
+        match self {
+            Goal::Minutes(n) | Goal::Pages(n) => n,
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            Goal::Minutes(_) => "minutes",
+            Goal::Pages(_) => "pages",
+        }
+    }
+
+    /// How far `reading` has come towards the goal, in its unit
+    pub fn progress(self, reading: Reading) -> u32 {
+        match self {
+            Goal::Minutes(_) => (reading.seconds / 60) as u32,
+            Goal::Pages(_) => reading.pages,
+        }
+    }
+}
+
+/// Reading on one day, of one book or all of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Reading {
+    pub seconds: u64,
+    pub pages: u32,
+}
+
+impl Reading {
+    fn add(&mut self, other: Reading) {
+        self.seconds += other.seconds;
+        self.pages += other.pages;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReadingLog {
+    #[serde(default)]
+    pub goal: Option<Goal>,
+    /// Each day's reading, by book title
+    #[serde(default)]
+    pub days: BTreeMap<NaiveDate, BTreeMap<String, Reading>>,
+    /// When each book was first read to its last page, by title
+    #[serde(default)]
+    pub finished: BTreeMap<String, NaiveDate>,
+}
+
+fn log_path() -> PathBuf {
+    config_dir("slowreader").join("log.json")
+}
+
+impl ReadingLog {
+    pub fn load() -> Self {
+        std::fs::read_to_string(log_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = log_path();
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    pub fn add(&mut self, date: NaiveDate, book: &str, reading: Reading) {
+        if reading == Reading::default() {
+            return;
+        }
+        self.days.entry(date).or_default().entry(book.to_string()).or_default().add(reading);
+    }
+
+    /// All of `date`'s reading
+    pub fn day(&self, date: NaiveDate) -> Reading {
+        let mut total = Reading::default();
+        for reading in self.days.get(&date).into_iter().flat_map(|books| books.values()) {
+            total.add(*reading);
+        }
+        total
+    }
+
+    /// Note `book` as finished on `date`, unless it was finished before
+    pub fn finish(&mut self, book: &str, date: NaiveDate) -> bool {
+        if self.finished.contains_key(book) {
+            return false;
+        }
+        self.finished.insert(book.to_string(), date);
+        true
+    }
+
+    /// A year of reading as plain text: totals, books finished, each book
+    /// and each month
+    pub fn year_report(&self, year: i32) -> String {
+        let days: Vec<(&NaiveDate, &BTreeMap<String, Reading>)> = self.days.iter().filter(|(d, _)| d.year() == year).collect();
+        let mut total = Reading::default();
+        let mut books: BTreeMap<&str, (Reading, usize)> = BTreeMap::new();
+        let mut months: BTreeMap<u32, Reading> = BTreeMap::new();
+        for (date, day) in &days {
+            for (book, reading) in day.iter() {
+                total.add(*reading);
+                let entry = books.entry(book.as_str()).or_default();
+                entry.0.add(*reading);
+                entry.1 += 1;
+                months.entry(date.month()).or_default().add(*reading);
+            }
+        }
+
+        let mut out = format!("Reading log {}\n\n", year);
+        out.push_str(&format!(
+            "{} book{}, {} and {} page{} over {} day{}\n",
+            books.len(),
+            plural(books.len()),
+            duration(total.seconds),
+            total.pages,
+            plural(total.pages as usize),
+            days.len(),
+            plural(days.len()),
+        ));
+        let finished: Vec<(&String, &NaiveDate)> = self.finished.iter().filter(|(_, d)| d.year() == year).collect();
+        if !finished.is_empty() {
+            let mut finished = finished;
+            finished.sort_by_key(|&(_, d)| *d);
+            out.push_str("\nFinished\n");
+            for (book, date) in finished {
+                out.push_str(&format!("  {}  {}\n", date.format("%e %B"), book));
+            }
+        }
+        if !books.is_empty() {
+            out.push_str("\nBooks\n");
+            for (book, (reading, days)) in &books {
+                out.push_str(&format!(
+                    "  {}: {}, {} page{} on {} day{}\n",
+                    book,
+                    duration(reading.seconds),
+                    reading.pages,
+                    plural(reading.pages as usize),
+                    days,
+                    plural(*days),
+                ));
+            }
+            out.push_str("\nMonths\n");
+            for (month, reading) in &months {
+                let name = NaiveDate::from_ymd_opt(year, *month, 1).map(|d| d.format("%B").to_string()).unwrap_or_default();
+                out.push_str(&format!("  {:<10} {}, {} page{}\n", name, duration(reading.seconds), reading.pages, plural(reading.pages as usize)));
+            }
+        }
+        out
+    }
+
+    /// Write `year_report` to a new file in Documents, returning its path
+    pub fn export_year(&self, year: i32) -> Result<PathBuf, String> {
+        let path = unique_path(&documents_dir(), &format!("reading log {}.txt", year));
+        std::fs::write(&path, self.year_report(year)).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+}
+
+fn plural(n: usize) -> &'static str {
+    if n == 1 { "" } else { "s" }
+}
+
+/// "45 minutes", "3 hours 5 minutes"
+pub fn duration(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{} minute{}", m, plural(m as usize)),
+        (h, 0) => format!("{} hour{}", h, plural(h as usize)),
+        (h, m) => format!("{} hour{} {} minute{}", h, plural(h as usize), m, plural(m as usize)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_log() {
+        let day = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+        let mut log = ReadingLog::default();
+        log.add(day(1, 5), "Emma", Reading { seconds: 1200, pages: 14 });
+        log.add(day(1, 5), "Emma", Reading { seconds: 600, pages: 6 });
+        log.add(day(1, 5), "Walden", Reading { seconds: 300, pages: 2 });
+        log.add(day(3, 2), "Emma", Reading { seconds: 3660, pages: 40 });
+        log.add(day(3, 3), "Emma", Reading::default());
+        assert_eq!(log.day(day(1, 5)), Reading { seconds: 2100, pages: 22 });
+        assert_eq!(log.days.len(), 2);
+        assert_eq!(Goal::Minutes(30).progress(log.day(day(1, 5))), 35);
+        assert_eq!(Goal::Pages(30).progress(log.day(day(1, 5))), 22);
+
+        assert!(log.finish("Emma", day(3, 2)));
+        assert!(!log.finish("Emma", day(4, 1)));
+
+        let report = log.year_report(2025);
+        assert!(report.contains("2 books, 1 hour 36 minutes and 62 pages over 2 days"));
+        assert!(report.contains(" 2 March  Emma"));
+        assert!(report.contains("  Emma: 1 hour 31 minutes, 60 pages on 2 days"));
+        assert!(report.contains("  March      1 hour 1 minute, 40 pages"));
+        assert!(log.year_report(2024).contains("0 books"));
+    }
+}
@@ -0,0 +1,243 @@
+//! Import watcher: books that turn up in ~/Downloads, or in a drop folder
+//! of one's choosing (a synced folder a phone sends to, say), are copied
+//! into ~/Books and added to the library without asking.
+//!
+//! The folders are looked at every few seconds on a thread of their own. A
+//! file is taken once its size has held still between two looks, so one
+//! still being downloaded is left alone until it's done.
+
+use crate::opds::books_dir;
+use serde::{Deserialize, Serialize};
+use slowcore::log;
+use slowcore::storage::{config_dir, downloads_dir, unique_path};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Kinds of file that are imported
+const KINDS: [&str; 4] = ["epub", "pdf", "cbz", "cbr"];
+
+/// How often the folders are looked at
+const POLL: Duration = Duration::from_secs(3);
+
+/// How often the time of the last look is written down
+const SAVE_EVERY: Duration = Duration::from_secs(60);
+
+fn settings_path() -> PathBuf {
+    config_dir("slowreader").join("import.json")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn yes() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSettings {
+    #[serde(default = "yes")]
+    pub watch_downloads: bool,
+    #[serde(default)]
+    pub drop_folder: Option<PathBuf>,
+    /// Unix seconds of the last look: files older than this were there
+    /// before and aren't imported when slowReader starts again
+    #[serde(default)]
+    pub checked_at: u64,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self { watch_downloads: true, drop_folder: None, checked_at: 0 }
+    }
+}
+
+impl ImportSettings {
+    pub fn load() -> Self {
+        let mut settings: Self = std::fs::read_to_string(settings_path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        // The first time, what's already downloaded stays where it is
+        if settings.checked_at == 0 {
+            settings.checked_at = now_secs();
+            settings.save();
+        }
+        settings
+    }
+
+    pub fn save(&self) {
+        let path = settings_path();
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    /// The folders being watched; never ~/Books itself
+    fn folders(&self) -> Vec<PathBuf> {
+        let books = books_dir();
+        let mut folders = Vec::new();
+        if self.watch_downloads {
+            folders.push(downloads_dir());
+        }
+        folders.extend(self.drop_folder.clone());
+        folders.retain(|f| f.is_dir() && *f != books);
+        folders.dedup();
+        folders
+    }
+}
+
+fn is_importable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| KINDS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Books in `folders` with their sizes
+fn list(folders: &[PathBuf]) -> Vec<(PathBuf, u64, SystemTime)> {
+    folders
+        .iter()
+        .filter_map(|f| std::fs::read_dir(f).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|e| e.path())
+        .filter(|p| is_importable(p))
+        .filter_map(|p| {
+            let meta = std::fs::metadata(&p).ok().filter(|m| m.is_file())?;
+            Some((p, meta.len(), meta.modified().unwrap_or(UNIX_EPOCH)))
+        })
+        .collect()
+}
+
+/// Copy `path` into `books`, unless the same book is already there.
+/// Returns where it went.
+fn import(path: &Path, size: u64, books: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let existing = books.join(&name);
+    if std::fs::metadata(&existing).is_ok_and(|m| m.len() == size) {
+        return None;
+    }
+    std::fs::create_dir_all(books).ok()?;
+    let dest = unique_path(books, &name);
+    match std::fs::copy(path, &dest) {
+        Ok(_) => Some(dest),
+        Err(e) => {
+            log::error!("could not import {}: {}", path.display(), e);
+            let _ = std::fs::remove_file(&dest);
+            None
+        }
+    }
+}
+
+fn watch(settings: Arc<Mutex<ImportSettings>>, imported: Sender<PathBuf>, ctx: egui::Context) {
+    // Files already there the last time around are left alone
+    let (folders, since) = {
+        let s = settings.lock().unwrap();
+        (s.folders(), s.checked_at)
+    };
+    let since = UNIX_EPOCH + Duration::from_secs(since);
+    let mut seen: HashSet<PathBuf> =
+        list(&folders).into_iter().filter(|(_, _, modified)| *modified < since).map(|(p, _, _)| p).collect();
+    // New files and their size at the last look
+    let mut pending: HashMap<PathBuf, u64> = HashMap::new();
+    let mut saved_at = Instant::now();
+    let mut watched = folders;
+
+    loop {
+        std::thread::sleep(POLL);
+        let folders = settings.lock().unwrap().folders();
+        let files = list(&folders);
+        // A folder just added to the watch has nothing new in it yet
+        let added: Vec<PathBuf> = folders.iter().filter(|f| !watched.contains(f)).cloned().collect();
+        seen.extend(files.iter().filter(|(p, _, _)| p.parent().is_some_and(|d| added.iter().any(|f| f == d))).map(|(p, _, _)| p.clone()));
+        watched = folders;
+        let present: HashSet<&PathBuf> = files.iter().map(|(p, _, _)| p).collect();
+        // A file taken away and downloaded again counts as new
+        seen.retain(|p| present.contains(p));
+        pending.retain(|p, _| present.contains(p));
+
+        for (path, size, _) in &files {
+            if seen.contains(path) {
+                continue;
+            }
+            if pending.get(path) != Some(size) {
+                pending.insert(path.clone(), *size);
+                continue;
+            }
+            pending.remove(path);
+            seen.insert(path.clone());
+            if let Some(dest) = import(path, *size, &books_dir()) {
+                if imported.send(dest).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+        }
+
+        if saved_at.elapsed() >= SAVE_EVERY {
+            let mut s = settings.lock().unwrap();
+            s.checked_at = now_secs();
+            s.save();
+            saved_at = Instant::now();
+        }
+    }
+}
+
+/// Watches the folders and hands over what it copied into ~/Books
+pub struct Watcher {
+    settings: Arc<Mutex<ImportSettings>>,
+    imported: Receiver<PathBuf>,
+}
+
+impl Watcher {
+    pub fn start(ctx: egui::Context) -> Self {
+        let settings = Arc::new(Mutex::new(ImportSettings::load()));
+        let (tx, rx) = mpsc::channel();
+        let shared = settings.clone();
+        std::thread::spawn(move || watch(shared, tx, ctx));
+        Self { settings, imported: rx }
+    }
+
+    pub fn settings(&self) -> ImportSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set_settings(&self, settings: ImportSettings) {
+        settings.save();
+        *self.settings.lock().unwrap() = settings;
+    }
+
+    /// Books imported since the last call
+    pub fn poll(&self) -> Vec<PathBuf> {
+        self.imported.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import() {
+        let dir = std::env::temp_dir().join(format!("slowreader_import_{}", std::process::id()));
+        let downloads = dir.join("downloads");
+        let books = dir.join("books");
+        std::fs::create_dir_all(&downloads).unwrap();
+        std::fs::write(downloads.join("novel.epub"), b"PK book").unwrap();
+        std::fs::write(downloads.join("notes.txt"), b"not a book").unwrap();
+
+        let files = list(&[downloads]);
+        assert_eq!(files.len(), 1);
+        let (path, size, _) = &files[0];
+        assert_eq!(import(path, *size, &books), Some(books.join("novel.epub")));
+        // The same book again is skipped
+        assert_eq!(import(path, *size, &books), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
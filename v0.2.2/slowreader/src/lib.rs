@@ -0,0 +1,6 @@
+//! slowReader's book model and paginated layout
+//!
+//! Shared with slowWeb, which turns web pages into a one-chapter book.
+
+pub mod book;
+pub mod reader;
@@ -5,6 +5,17 @@ use serde::{Deserialize, Serialize};
 use slowcore::storage::config_dir;
 use std::path::PathBuf;
 
+/// A named place in a book
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub chapter: usize,
+    /// Block and character the marked page starts at, so the mark stays
+    /// put when the font size or window changes
+    pub block: usize,
+    pub offset: usize,
+}
+
 /// A book entry in the library
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LibraryEntry {
@@ -17,6 +28,12 @@ pub struct LibraryEntry {
     /// Total number of chapters in the book (for progress calculation)
     #[serde(default)]
     pub total_chapters: usize,
+    /// In reading order
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    /// A comic read right to left, like manga
+    #[serde(default)]
+    pub right_to_left: bool,
 }
 
 /// The user's book library
@@ -67,6 +84,8 @@ impl Library {
                 added_date: now,
                 last_read: now,
                 total_chapters,
+                bookmarks: Vec::new(),
+                right_to_left: false,
             });
         }
 
@@ -94,6 +113,49 @@ impl Library {
             .map(|b| (b.last_chapter, b.last_scroll))
     }
     
+    pub fn right_to_left(&self, path: &PathBuf) -> bool {
+        self.books.iter().any(|b| &b.path == path && b.right_to_left)
+    }
+
+    pub fn set_right_to_left(&mut self, path: &PathBuf, right_to_left: bool) {
+        if let Some(entry) = self.books.iter_mut().find(|b| &b.path == path) {
+            entry.right_to_left = right_to_left;
+            self.save();
+        }
+    }
+
+    /// Bookmarks in a book, in reading order
+    pub fn bookmarks(&self, path: &PathBuf) -> &[Bookmark] {
+        self.books.iter().find(|b| &b.path == path).map_or(&[], |b| b.bookmarks.as_slice())
+    }
+
+    /// Add a bookmark in its place in reading order
+    pub fn add_bookmark(&mut self, path: &PathBuf, bookmark: Bookmark) {
+        if let Some(entry) = self.books.iter_mut().find(|b| &b.path == path) {
+            let at = entry.bookmarks.partition_point(|b| {
+                (b.chapter, b.block, b.offset) <= (bookmark.chapter, bookmark.block, bookmark.offset)
+            });
+            entry.bookmarks.insert(at, bookmark);
+            self.save();
+        }
+    }
+
+    pub fn rename_bookmark(&mut self, path: &PathBuf, index: usize, name: &str) {
+        if let Some(bookmark) = self.books.iter_mut().find(|b| &b.path == path).and_then(|b| b.bookmarks.get_mut(index)) {
+            bookmark.name = name.to_string();
+            self.save();
+        }
+    }
+
+    pub fn remove_bookmark(&mut self, path: &PathBuf, index: usize) {
+        if let Some(entry) = self.books.iter_mut().find(|b| &b.path == path) {
+            if index < entry.bookmarks.len() {
+                entry.bookmarks.remove(index);
+                self.save();
+            }
+        }
+    }
+
     /// Get recently read books
     pub fn recent_books(&self) -> Vec<&LibraryEntry> {
         let mut sorted: Vec<_> = self.books.iter().collect();
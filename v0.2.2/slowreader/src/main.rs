@@ -2,20 +2,25 @@
 //!
 //! Focused reading experience for EPUB and text files.
 
-mod book;
-mod reader;
+mod comic;
+mod goals;
+mod import;
 mod library;
+mod opds;
 mod app;
 
+// The book model and page layout live in the library so slowWeb can use them
+use slowreader::{book, reader};
+
 use app::SlowReaderApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let initial_file = std::env::args().nth(1).map(std::path::PathBuf::from);
 
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([640.0, 440.0])
+        viewport: window_layout::viewport("slowreader", Placement::RememberLast, [640.0, 440.0])
             .with_title("slowReader"),
         ..Default::default()
     };
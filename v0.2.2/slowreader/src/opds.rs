@@ -0,0 +1,499 @@
+//! OPDS catalogs — online libraries to browse and download EPUBs from
+//!
+//! A catalog is a tree of Atom feeds: navigation feeds lead on to other
+//! feeds, acquisition feeds list books with links to their files. The
+//! catalogs are kept in ~/.config/slowreader/catalogs.json.
+//!
+//! Feeds and books are fetched on worker threads. Books are written to a
+//! .part file in ~/Books first; a download that breaks off or is stopped
+//! is remembered in downloads.json and picks up where it left off.
+
+use roxmltree::Node;
+use serde::{Deserialize, Serialize};
+use slowcore::storage::{config_dir, save_atomic, unique_path};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+const USER_AGENT: &str = "slowReader/0.2.2";
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Feeds larger than this are refused
+const MAX_FEED: u64 = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Catalog {
+    pub name: String,
+    pub url: String,
+}
+
+/// Catalogs offered until the user changes the list
+fn default_catalogs() -> Vec<Catalog> {
+    vec![
+        Catalog { name: "Standard Ebooks".into(), url: "https://standardebooks.org/feeds/opds".into() },
+        Catalog { name: "Project Gutenberg".into(), url: "https://www.gutenberg.org/ebooks.opds/".into() },
+    ]
+}
+
+/// ~/Books, where downloaded books go
+pub fn books_dir() -> PathBuf {
+    std::env::var("HOME").map(PathBuf::from).unwrap_or_default().join("Books")
+}
+
+/// One page of a catalog
+#[derive(Debug, Clone, Default)]
+pub struct Feed {
+    pub title: String,
+    pub entries: Vec<Entry>,
+    /// The feed's next page
+    pub next: Option<String>,
+}
+
+/// A book, or a way on to another feed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Entry {
+    pub title: String,
+    pub author: String,
+    pub summary: String,
+    /// The EPUB to download
+    pub epub: Option<String>,
+    /// A feed this entry leads to
+    pub feed: Option<String>,
+}
+
+/// Text with markup taken out and runs of whitespace made single spaces
+fn plain(text: &str) -> String {
+    let mut out = String::new();
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                out.push(' ');
+            }
+            c if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn children<'a, 'i>(node: Node<'a, 'i>, name: &'static str) -> impl Iterator<Item = Node<'a, 'i>> {
+    node.children().filter(move |c| c.is_element() && c.tag_name().name() == name)
+}
+
+/// The plain text of `node`'s first `name` element
+fn text(node: Node, name: &'static str) -> String {
+    children(node, name)
+        .next()
+        .map(|c| plain(&c.descendants().filter(|d| d.is_text()).filter_map(|d| d.text()).collect::<String>()))
+        .unwrap_or_default()
+}
+
+/// (rel, type, href) of each of `node`'s links
+fn links<'a>(node: Node<'a, '_>) -> Vec<(&'a str, &'a str, &'a str)> {
+    children(node, "link")
+        .filter_map(|l| Some((l.attribute("rel").unwrap_or(""), l.attribute("type").unwrap_or(""), l.attribute("href")?)))
+        .collect()
+}
+
+/// Read an Atom feed fetched from `url`, resolving its links against it
+pub fn parse_feed(xml: &str, url: &str) -> Result<Feed, String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|_| "not a catalog feed".to_string())?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "feed" {
+        return Err("not a catalog feed".into());
+    }
+    let base = Url::parse(url).map_err(|e| e.to_string())?;
+    let resolve = |href: &str| base.join(href).ok().map(|u| u.to_string());
+
+    let entries = children(root, "entry")
+        .map(|entry| {
+            let links = links(entry);
+            let epub = links
+                .iter()
+                .find(|(rel, kind, _)| rel.contains("opds-spec.org/acquisition") && kind.starts_with("application/epub+zip"))
+                .and_then(|(_, _, href)| resolve(href));
+            let feed = links
+                .iter()
+                .find(|(rel, kind, _)| *rel != "self" && kind.starts_with("application/atom+xml"))
+                .and_then(|(_, _, href)| resolve(href));
+            let author = children(entry, "author").map(|a| text(a, "name")).filter(|n| !n.is_empty()).collect::<Vec<_>>().join(", ");
+            let summary = Some(text(entry, "summary")).filter(|s| !s.is_empty()).unwrap_or_else(|| text(entry, "content"));
+            Entry { title: text(entry, "title"), author, summary, epub, feed }
+        })
+        .filter(|e| e.epub.is_some() || e.feed.is_some())
+        .collect();
+    let next = links(root).iter().find(|(rel, _, _)| *rel == "next").and_then(|(_, _, href)| resolve(href));
+    Ok(Feed { title: text(root, "title"), entries, next })
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().user_agent(USER_AGENT).timeout_connect(TIMEOUT).timeout_read(TIMEOUT).build()
+}
+
+fn error_text(e: ureq::Error) -> String {
+    match e {
+        ureq::Error::Status(code, _) => format!("the server answered {}", code),
+        ureq::Error::Transport(t) => t.to_string(),
+    }
+}
+
+fn fetch_feed(url: &str) -> Result<Feed, String> {
+    let response = agent().get(url).call().map_err(error_text)?;
+    let mut xml = String::new();
+    response.into_reader().take(MAX_FEED).read_to_string(&mut xml).map_err(|e| e.to_string())?;
+    parse_feed(&xml, url)
+}
+
+/// What a download thread reports
+enum Progress {
+    /// Bytes on disk so far, and how many there'll be if the server said
+    Bytes(u64, Option<u64>),
+    Done,
+    Failed(String),
+}
+
+/// A book being downloaded, or one that stopped before it finished
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Download {
+    pub title: String,
+    pub url: String,
+    /// Where the book goes once it's all here
+    pub path: PathBuf,
+    #[serde(skip)]
+    pub done: u64,
+    #[serde(skip)]
+    pub total: Option<u64>,
+    #[serde(skip)]
+    pub error: Option<String>,
+    #[serde(skip)]
+    running: Option<(Receiver<Progress>, Arc<AtomicBool>)>,
+}
+
+impl Download {
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    fn part_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".part");
+        self.path.with_file_name(name)
+    }
+
+    fn start(&mut self, ctx: &egui::Context) {
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let (url, part, flag, ctx) = (self.url.clone(), self.part_path(), stop.clone(), ctx.clone());
+        std::thread::spawn(move || {
+            let result = fetch_book(&url, &part, &flag, &tx, &ctx)
+                .and_then(|()| std::fs::rename(&part, part.with_extension("")).map_err(|e| e.to_string()));
+            let _ = tx.send(match result {
+                Ok(()) => Progress::Done,
+                Err(e) => Progress::Failed(e),
+            });
+            ctx.request_repaint();
+        });
+        self.done = std::fs::metadata(self.part_path()).map(|m| m.len()).unwrap_or(0);
+        self.error = None;
+        self.running = Some((rx, stop));
+    }
+
+    /// Ask the download to stop; what's here is kept for later
+    pub fn stop(&self) {
+        if let Some((_, stop)) = &self.running {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Download `url` into `part`, carrying on from what's already there
+/// when the server allows it
+fn fetch_book(url: &str, part: &Path, stop: &AtomicBool, tx: &Sender<Progress>, ctx: &egui::Context) -> Result<(), String> {
+    let have = std::fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+    let mut request = agent().get(url);
+    if have > 0 {
+        request = request.set("Range", &format!("bytes={}-", have));
+    }
+    let response = match request.call() {
+        Ok(response) => response,
+        // Everything was already here
+        Err(ureq::Error::Status(416, _)) if have > 0 => return Ok(()),
+        Err(e) => return Err(error_text(e)),
+    };
+    let resumed = response.status() == 206;
+    let mut done = if resumed { have } else { 0 };
+    let total = response.header("Content-Length").and_then(|l| l.parse::<u64>().ok()).map(|l| l + done);
+    if let Some(parent) = part.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(part)
+        .map_err(|e| e.to_string())?;
+
+    let mut reader = response.into_reader();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return Err("stopped".into());
+        }
+        let n = reader.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n]).map_err(|e| e.to_string())?;
+        done += n as u64;
+        let _ = tx.send(Progress::Bytes(done, total));
+        ctx.request_repaint();
+    }
+    if total.is_some_and(|t| done < t) {
+        return Err("the connection closed early".into());
+    }
+    drop(file);
+
+    // An EPUB is a zip; anything else is likely a sign-in or error page
+    let mut magic = [0; 2];
+    let is_zip = std::fs::File::open(part).and_then(|mut f| f.read_exact(&mut magic)).is_ok() && &magic == b"PK";
+    if !is_zip {
+        let _ = std::fs::remove_file(part);
+        return Err("that wasn't an EPUB".into());
+    }
+    Ok(())
+}
+
+/// Catalogs, the feeds followed from one, and downloads
+pub struct Browser {
+    pub catalogs: Vec<Catalog>,
+    /// Feeds followed from the catalog, the one showing last
+    pub trail: Vec<Feed>,
+    loading: Option<Receiver<Result<Feed, String>>>,
+    pub error: Option<String>,
+    pub downloads: Vec<Download>,
+}
+
+fn catalogs_path() -> PathBuf {
+    config_dir("slowreader").join("catalogs.json")
+}
+
+fn downloads_path() -> PathBuf {
+    config_dir("slowreader").join("downloads.json")
+}
+
+fn load_json<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+    std::fs::read_to_string(path).ok().and_then(|s| serde_json::from_str(&s).ok())
+}
+
+fn save_json<T: Serialize>(path: &Path, value: &T) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(value) {
+        let _ = save_atomic(path, json.as_bytes());
+    }
+}
+
+impl Browser {
+    pub fn new() -> Self {
+        Self {
+            catalogs: load_json(&catalogs_path()).unwrap_or_else(default_catalogs),
+            trail: Vec::new(),
+            loading: None,
+            error: None,
+            downloads: load_json(&downloads_path()).unwrap_or_default(),
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    /// Fetch the feed at `url` and show it next
+    pub fn open(&mut self, url: &str, ctx: &egui::Context) {
+        let (tx, rx) = mpsc::channel();
+        let (url, ctx) = (url.to_string(), ctx.clone());
+        std::thread::spawn(move || {
+            let _ = tx.send(fetch_feed(&url));
+            ctx.request_repaint();
+        });
+        self.error = None;
+        self.loading = Some(rx);
+    }
+
+    /// Back to the previous feed, or the list of catalogs
+    pub fn back(&mut self) {
+        self.trail.pop();
+        self.loading = None;
+        self.error = None;
+    }
+
+    /// Add a catalog by its address, named for its site
+    pub fn add_catalog(&mut self, url: &str) -> Result<(), String> {
+        let parsed = Url::parse(url.trim()).map_err(|_| format!("not a valid address: {}", url.trim()))?;
+        let name = parsed.host_str().unwrap_or("catalog").trim_start_matches("www.").to_string();
+        self.catalogs.push(Catalog { name, url: parsed.to_string() });
+        self.save_catalogs();
+        Ok(())
+    }
+
+    pub fn remove_catalog(&mut self, index: usize) {
+        if index < self.catalogs.len() {
+            self.catalogs.remove(index);
+            self.save_catalogs();
+        }
+    }
+
+    fn save_catalogs(&self) {
+        save_json(&catalogs_path(), &self.catalogs);
+    }
+
+    fn save_downloads(&self) {
+        save_json(&downloads_path(), &self.downloads);
+    }
+
+    /// The download of `url`, if there is one
+    pub fn download_of(&self, url: &str) -> Option<&Download> {
+        self.downloads.iter().find(|d| d.url == url)
+    }
+
+    /// Start downloading `entry`'s EPUB into ~/Books
+    pub fn download(&mut self, entry: &Entry, ctx: &egui::Context) {
+        let Some(url) = &entry.epub else { return };
+        if self.download_of(url).is_some() {
+            return;
+        }
+        let name = entry.title.trim().replace(['/', '\\', ':'], "-");
+        let name = if name.is_empty() { "book".to_string() } else { name };
+        let mut download = Download {
+            title: entry.title.clone(),
+            url: url.clone(),
+            path: unique_path(&books_dir(), &format!("{}.epub", name)),
+            done: 0,
+            total: None,
+            error: None,
+            running: None,
+        };
+        download.start(ctx);
+        self.downloads.push(download);
+        self.save_downloads();
+    }
+
+    pub fn resume(&mut self, index: usize, ctx: &egui::Context) {
+        if let Some(download) = self.downloads.get_mut(index).filter(|d| !d.is_running()) {
+            download.start(ctx);
+        }
+    }
+
+    /// Give up on a download and delete what came of it
+    pub fn cancel(&mut self, index: usize) {
+        if index < self.downloads.len() {
+            let download = self.downloads.remove(index);
+            download.stop();
+            let _ = std::fs::remove_file(download.part_path());
+            self.save_downloads();
+        }
+    }
+
+    /// Take in what the worker threads have sent, returning the books
+    /// that have finished downloading
+    pub fn poll(&mut self) -> Vec<(String, PathBuf)> {
+        if let Some(rx) = &self.loading {
+            match rx.try_recv() {
+                Ok(Ok(feed)) => {
+                    self.trail.push(feed);
+                    self.loading = None;
+                }
+                Ok(Err(e)) => {
+                    self.error = Some(e);
+                    self.loading = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.loading = None,
+            }
+        }
+
+        let mut finished = Vec::new();
+        for download in &mut self.downloads {
+            let Some((rx, _)) = &download.running else { continue };
+            let mut ended = false;
+            while let Ok(progress) = rx.try_recv() {
+                match progress {
+                    Progress::Bytes(done, total) => {
+                        download.done = done;
+                        download.total = total;
+                    }
+                    Progress::Done => {
+                        finished.push((download.title.clone(), download.path.clone()));
+                        ended = true;
+                    }
+                    Progress::Failed(e) => {
+                        download.error = Some(e);
+                        ended = true;
+                    }
+                }
+            }
+            if ended {
+                download.running = None;
+            }
+        }
+        if !finished.is_empty() {
+            self.downloads.retain(|d| !finished.iter().any(|(_, path)| *path == d.path));
+            self.save_downloads();
+        }
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feed() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>New releases</title>
+  <link rel="self" href="/feeds/new" type="application/atom+xml;profile=opds-catalog"/>
+  <link rel="next" href="/feeds/new?page=2" type="application/atom+xml;profile=opds-catalog"/>
+  <entry>
+    <title>Emma</title>
+    <author><name>Jane Austen</name></author>
+    <summary type="html">A &lt;i&gt;comedy&lt;/i&gt;
+      of manners.</summary>
+    <link rel="http://opds-spec.org/acquisition/open-access" type="application/epub+zip" href="/books/emma.epub"/>
+    <link rel="alternate" type="application/atom+xml;type=entry" href="/books/emma"/>
+  </entry>
+  <entry>
+    <title>Poetry</title>
+    <content type="xhtml"><div xmlns="http://www.w3.org/1999/xhtml">Verse, <b>old</b> and new</div></content>
+    <link rel="subsection" type="application/atom+xml;profile=opds-catalog;kind=acquisition" href="poetry"/>
+  </entry>
+  <entry>
+    <title>Only a PDF</title>
+    <link rel="http://opds-spec.org/acquisition" type="application/pdf" href="/books/only.pdf"/>
+  </entry>
+</feed>"#;
+        let feed = parse_feed(xml, "https://example.org/feeds/new").unwrap();
+        assert_eq!(feed.title, "New releases");
+        assert_eq!(feed.next.as_deref(), Some("https://example.org/feeds/new?page=2"));
+        assert_eq!(feed.entries.len(), 2);
+        let emma = &feed.entries[0];
+        assert_eq!(emma.author, "Jane Austen");
+        assert_eq!(emma.summary, "A comedy of manners.");
+        assert_eq!(emma.epub.as_deref(), Some("https://example.org/books/emma.epub"));
+        let poetry = &feed.entries[1];
+        assert_eq!(poetry.summary, "Verse, old and new");
+        assert_eq!((poetry.epub.as_deref(), poetry.feed.as_deref()), (None, Some("https://example.org/feeds/poetry")));
+
+        assert!(parse_feed("<html><body>sign in</body></html>", "https://example.org/").is_err());
+    }
+}
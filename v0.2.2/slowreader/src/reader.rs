@@ -1,10 +1,11 @@
 //! Reader - page-based text rendering (horizontal navigation only)
 
 use crate::book::{Book, ContentBlock};
-use egui::{ColorImage, FontId, Pos2, Rect, Response, Sense, Stroke, TextureHandle, Ui, Vec2};
+use egui::{ColorImage, FontId, Pos2, Rect, Response, Sense, Stroke, Ui, Vec2};
 use serde::{Deserialize, Serialize};
+use slowcore::textures::TextureCache;
 use slowcore::theme::SlowColors;
-use std::collections::HashMap;
+use std::ops::Range;
 
 /// Reading position
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
@@ -39,8 +40,9 @@ pub struct Reader {
     pub settings: ReaderSettings,
     /// Total pages in current chapter (calculated during render)
     total_pages: usize,
-    /// Cached image textures (keyed by image data hash)
-    image_cache: HashMap<u64, (TextureHandle, [u32; 2])>,
+    /// Cached image textures (keyed by image data hash), kept under the
+    /// app's texture budget
+    image_cache: TextureCache<u64>,
     /// Last view dimensions for calculations
     last_view_width: f32,
     last_view_height: f32,
@@ -58,6 +60,9 @@ pub struct Reader {
     pending_anchor: Option<(usize, usize)>,
     /// Suppress click-to-turn-page (set when dialogs are open over the reader)
     pub suppress_clicks: bool,
+    /// Sentence being read aloud: (block_idx, byte range in the block's text
+    /// with its whitespace collapsed, as wrapped)
+    speaking: Option<(usize, Range<usize>)>,
 }
 
 impl Default for Reader {
@@ -72,7 +77,7 @@ impl Reader {
             position: ReadingPosition::default(),
             settings: ReaderSettings::default(),
             total_pages: 1,
-            image_cache: HashMap::new(),
+            image_cache: TextureCache::new(),
             last_view_width: 600.0,
             last_view_height: 400.0,
             selected_word: None,
@@ -82,6 +87,7 @@ impl Reader {
             current_page_anchor: None,
             pending_anchor: None,
             suppress_clicks: false,
+            speaking: None,
         }
     }
 
@@ -147,6 +153,73 @@ impl Reader {
         self.pending_anchor = self.current_page_anchor;
     }
 
+    /// The current chapter's text for reading aloud, one block per line,
+    /// with where each block starts: (block_idx, byte offset)
+    pub fn chapter_speech(&self, book: &Book) -> (String, Vec<(usize, usize)>) {
+        let mut text = String::new();
+        let mut starts = Vec::new();
+        let Some(chapter) = book.chapters.get(self.position.chapter) else { return (text, starts) };
+        for (block_idx, block) in chapter.content.iter().enumerate() {
+            let block_text = match block {
+                ContentBlock::Heading { text, .. }
+                | ContentBlock::Paragraph(text)
+                | ContentBlock::Quote(text)
+                | ContentBlock::ListItem(text) => text,
+                ContentBlock::Code(_) | ContentBlock::HorizontalRule | ContentBlock::Image { .. } => continue,
+            };
+            starts.push((block_idx, text.len()));
+            // Collapsed like wrap_text does, so offsets match the page
+            text.push_str(&block_text.split_whitespace().collect::<Vec<_>>().join(" "));
+            text.push('\n');
+        }
+        (text, starts)
+    }
+
+    /// Where the current page starts in `chapter_speech`'s text
+    pub fn speech_start(&self, starts: &[(usize, usize)]) -> usize {
+        let Some((block_idx, char_offset)) = self.current_page_anchor else { return 0 };
+        starts
+            .iter()
+            .find(|&&(b, _)| b >= block_idx)
+            .map(|&(b, start)| if b == block_idx { start + char_offset } else { start })
+            .unwrap_or(0)
+    }
+
+    /// Underline the sentence being read, given as a range of
+    /// `chapter_speech`'s text, and turn to its page
+    pub fn set_speaking(&mut self, range: Option<Range<usize>>, starts: &[(usize, usize)]) {
+        let speaking = range.and_then(|range| {
+            let &(block_idx, start) = starts.iter().take_while(|&&(_, start)| start <= range.start).last()?;
+            Some((block_idx, range.start - start..range.end - start))
+        });
+        if speaking != self.speaking {
+            if let Some((block_idx, range)) = &speaking {
+                self.pending_anchor = Some((*block_idx, range.start));
+            }
+            self.speaking = speaking;
+        }
+    }
+
+    /// The part of block `block_idx` being read aloud
+    fn spoken_in(&self, block_idx: usize) -> Option<Range<usize>> {
+        self.speaking.as_ref().filter(|(b, _)| *b == block_idx).map(|(_, r)| r.clone())
+    }
+
+    /// The block and character the current page starts at
+    pub fn page_anchor(&self) -> Option<(usize, usize)> {
+        self.current_page_anchor
+    }
+
+    /// Go to the page holding character `offset` of block `block` in
+    /// `chapter`
+    pub fn go_to_anchor(&mut self, chapter: usize, block: usize, offset: usize, book: &Book) {
+        if chapter < book.chapter_count() {
+            self.position.chapter = chapter;
+            self.position.page = 0;
+            self.pending_anchor = Some((block, offset));
+        }
+    }
+
     /// Get current page info for status bar
     pub fn page_info(&self) -> (usize, usize) {
         (self.position.page + 1, self.total_pages.max(1))
@@ -246,6 +319,7 @@ impl Reader {
                 let mut y = text_rect.min.y;
                 for (block_idx, start_line, end_line) in page_content {
                     if let Some(block) = chapter.content.get(*block_idx) {
+                        let highlight = self.spoken_in(*block_idx);
                         y += self.render_block_lines_with_tracking(
                             &painter,
                             block,
@@ -254,6 +328,7 @@ impl Reader {
                             *start_line,
                             *end_line,
                             rect,
+                            highlight,
                         );
                         y += self.settings.paragraph_spacing;
                     }
@@ -266,6 +341,7 @@ impl Reader {
                 let mut y = text_rect.min.y;
                 for (block_idx, start_line, end_line) in page_content {
                     if let Some(block) = chapter.content.get(*block_idx) {
+                        let highlight = self.spoken_in(*block_idx);
                         y += self.render_block_lines_with_tracking(
                             &painter,
                             block,
@@ -274,6 +350,7 @@ impl Reader {
                             *start_line,
                             *end_line,
                             rect,
+                            highlight,
                         );
                         y += self.settings.paragraph_spacing;
                     }
@@ -332,6 +409,7 @@ impl Reader {
                 let mut y = text_rect.min.y;
                 for (block_idx, start_line, end_line) in page_content {
                     if let Some(block) = chapter.content.get(*block_idx) {
+                        let highlight = self.spoken_in(*block_idx);
                         y += self.render_block_lines_with_tracking(
                             &painter,
                             block,
@@ -340,6 +418,7 @@ impl Reader {
                             *start_line,
                             *end_line,
                             rect,
+                            highlight,
                         );
                         y += self.settings.paragraph_spacing;
                     }
@@ -438,6 +517,7 @@ impl Reader {
         start_line: usize,
         end_line: usize,
         clip_rect: Rect,
+        highlight: Option<Range<usize>>,
     ) -> f32 {
         match block {
             ContentBlock::Heading { level, text } => {
@@ -447,10 +527,10 @@ impl Reader {
                     3 => self.settings.font_size * 1.3,
                     _ => self.settings.font_size * 1.1,
                 };
-                self.render_text_lines_with_tracking(painter, text, pos, max_width, font_size, true, start_line, end_line, clip_rect)
+                self.render_text_lines_with_tracking(painter, text, pos, max_width, font_size, true, start_line, end_line, clip_rect, highlight)
             }
             ContentBlock::Paragraph(text) => {
-                self.render_text_lines_with_tracking(painter, text, pos, max_width, self.settings.font_size, false, start_line, end_line, clip_rect)
+                self.render_text_lines_with_tracking(painter, text, pos, max_width, self.settings.font_size, false, start_line, end_line, clip_rect, highlight)
             }
             ContentBlock::Quote(text) => {
                 let indent = 30.0;
@@ -465,10 +545,10 @@ impl Reader {
                     Stroke::new(2.0, SlowColors::BLACK),
                 );
 
-                self.render_text_lines_with_tracking(painter, text, quote_pos, max_width - indent, self.settings.font_size, false, start_line, end_line, clip_rect)
+                self.render_text_lines_with_tracking(painter, text, quote_pos, max_width - indent, self.settings.font_size, false, start_line, end_line, clip_rect, highlight)
             }
             ContentBlock::Code(text) => {
-                self.render_text_lines_with_tracking(painter, text, pos, max_width, self.settings.font_size * 0.9, false, start_line, end_line, clip_rect)
+                self.render_text_lines_with_tracking(painter, text, pos, max_width, self.settings.font_size * 0.9, false, start_line, end_line, clip_rect, highlight)
             }
             ContentBlock::ListItem(text) => {
                 let text_pos = Pos2::new(pos.x + 25.0, pos.y);
@@ -484,7 +564,7 @@ impl Reader {
                     );
                 }
 
-                self.render_text_lines_with_tracking(painter, text, text_pos, max_width - 25.0, self.settings.font_size, false, start_line, end_line, clip_rect)
+                self.render_text_lines_with_tracking(painter, text, text_pos, max_width - 25.0, self.settings.font_size, false, start_line, end_line, clip_rect, highlight)
             }
             ContentBlock::HorizontalRule => {
                 painter.hline(
@@ -513,6 +593,7 @@ impl Reader {
         start_line: usize,
         end_line: usize,
         _clip_rect: Rect,
+        highlight: Option<Range<usize>>,
     ) -> f32 {
         let font = if bold {
             FontId::new(font_size, egui::FontFamily::Monospace)
@@ -530,9 +611,23 @@ impl Reader {
 
         let lines = wrap_text(text, chars_per_line);
         let mut y = pos.y;
+        let mut line_start = 0;
 
         for (i, line) in lines.iter().enumerate() {
+            let line_range = line_start..line_start + line.len();
+            line_start = line_range.end + 1;
             if i >= start_line && i < end_line {
+                // Underline the part of the sentence being read aloud
+                if let Some(spoken) = &highlight {
+                    let from = spoken.start.max(line_range.start);
+                    let to = spoken.end.min(line_range.end);
+                    if from < to {
+                        let x0 = pos.x + (from - line_range.start) as f32 * char_width;
+                        let x1 = pos.x + (to - line_range.start) as f32 * char_width;
+                        painter.hline(x0..=x1, y + font_size + 2.0, Stroke::new(2.0, SlowColors::BLACK));
+                    }
+                }
+
                 // Render the line
                 painter.text(
                     Pos2::new(pos.x, y),
@@ -693,12 +788,12 @@ impl Reader {
                         }
                         hasher.finish()
                     };
-                    if let Some((_, [w, h])) = self.image_cache.get(&hash) {
+                    if let Some([w, h]) = self.image_cache.peek(&hash).map(|tex| tex.size()) {
                         let max_h = (self.last_view_height - self.settings.paragraph_spacing * 2.0).max(100.0);
-                        let scale_w = (self.last_view_width / (*w as f32).max(1.0)).min(1.0);
-                        let scale_h = (max_h / (*h as f32).max(1.0)).min(1.0);
+                        let scale_w = (self.last_view_width / (w as f32).max(1.0)).min(1.0);
+                        let scale_h = (max_h / (h as f32).max(1.0)).min(1.0);
                         let scale = scale_w.min(scale_h);
-                        (*h as f32 * scale + 8.0).min(max_h)
+                        (h as f32 * scale + 8.0).min(max_h)
                     } else {
                         200.0
                     }
@@ -844,7 +939,7 @@ impl Reader {
                                     color_image,
                                     egui::TextureOptions::NEAREST,
                                 );
-                                self.image_cache.insert(hash, (tex, [width, height]));
+                                self.image_cache.insert(hash, tex);
                             }
                         } else if let Ok(img) = image::load_from_memory(img_data) {
                             let grey = img.grayscale();
@@ -867,18 +962,19 @@ impl Reader {
                                 color_image,
                                 egui::TextureOptions::NEAREST,
                             );
-                            self.image_cache.insert(hash, (tex, [dw, dh]));
+                            self.image_cache.insert(hash, tex);
                         }
                     }
 
-                    if let Some((tex, [w, h])) = self.image_cache.get(&hash) {
+                    if let Some(tex) = self.image_cache.get(&hash) {
+                        let [w, h] = tex.size();
                         // Clamp display to both available width and height
                         let max_h = (self.last_view_height - self.settings.paragraph_spacing * 2.0).max(100.0);
-                        let scale_w = (max_width / (*w as f32).max(1.0)).min(1.0);
-                        let scale_h = (max_h / (*h as f32).max(1.0)).min(1.0);
+                        let scale_w = (max_width / (w as f32).max(1.0)).min(1.0);
+                        let scale_h = (max_h / (h as f32).max(1.0)).min(1.0);
                         let scale = scale_w.min(scale_h);
-                        let final_w = *w as f32 * scale;
-                        let display_h = *h as f32 * scale;
+                        let final_w = w as f32 * scale;
+                        let display_h = h as f32 * scale;
                         let img_rect = Rect::from_min_size(pos, Vec2::new(final_w, display_h));
                         painter.image(tex.id(), img_rect, Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)), egui::Color32::WHITE);
                         display_h + 8.0
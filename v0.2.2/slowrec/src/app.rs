@@ -0,0 +1,469 @@
+//! slowRec application
+
+use crate::clip::{self, Clip, Format, Recording};
+use crate::recorder::Recorder;
+use chrono::Local;
+use egui::{Align2, CentralPanel, Context, Key, Rect, Sense, SidePanel, Stroke, TopBottomPanel, Vec2};
+use slowcore::repaint::RepaintController;
+use slowcore::sound::Player;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, WindowAction};
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowRec",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "sound recorder for slowOS",
+    features: &["level meter", "record, pause and stop", "WAV or FLAC", "play back and trim"],
+    ..AboutInfo::EMPTY
+};
+
+/// How often the clock and level meter move while recording or playing
+const METER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How far the meter falls each frame, so peaks can be read
+const METER_FALL: f32 = 0.08;
+
+/// How long closing the window waits for a recording to be saved
+const SAVE_WAIT: Duration = Duration::from_secs(30);
+
+const WAVEFORM_HEIGHT: f32 = 96.0;
+
+/// 1:05, or 1:02:05 past an hour
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+pub struct SlowRecApp {
+    recordings: Vec<Recording>,
+    selected: Option<PathBuf>,
+    /// The selected recording, read in for its waveform and trimming
+    clip: Option<Clip>,
+    /// The waveform's peaks and the width they were worked out for
+    waveform: Option<(usize, Vec<f32>)>,
+    recorder: Option<Recorder>,
+    /// A stopped recording being finished on the writer thread
+    saving: Option<Receiver<Result<PathBuf, String>>>,
+    format: Format,
+    /// The level shown, falling slowly after each peak
+    meter: f32,
+    sink: Option<rodio::Sink>,
+    player: Player,
+    /// Trim markers, as fractions of the selected recording
+    trim: (f32, f32),
+    confirm_trim: bool,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowRecApp {
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        let recordings = clip::list();
+        let mut app = Self {
+            selected: None,
+            recordings,
+            clip: None,
+            waveform: None,
+            recorder: None,
+            saving: None,
+            format: Format::Flac,
+            meter: 0.0,
+            sink: None,
+            player: Player::register("slowrec"),
+            trim: (0.0, 1.0),
+            confirm_trim: false,
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        };
+        if let Some(path) = app.recordings.first().map(|r| r.path.clone()) {
+            app.select(path);
+        }
+        app
+    }
+
+    fn select(&mut self, path: PathBuf) {
+        self.stop_playing();
+        self.trim = (0.0, 1.0);
+        self.message = None;
+        self.waveform = None;
+        self.clip = match Clip::load(&path) {
+            Ok(clip) => Some(clip),
+            Err(e) => {
+                self.message = Some(format!("could not read recording: {}", e));
+                None
+            }
+        };
+        self.selected = Some(path);
+    }
+
+    fn start_recording(&mut self) {
+        self.stop_playing();
+        let name = format!("memo {}.{}", Local::now().format("%Y-%m-%d %H.%M"), self.format.extension());
+        let path = slowcore::storage::unique_path(&clip::recordings_dir(), &name);
+        match Recorder::start(path, self.format) {
+            Ok(recorder) => {
+                self.recorder = Some(recorder);
+                self.message = None;
+            }
+            Err(e) => self.message = Some(format!("could not record: {}", e)),
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            self.saving = Some(recorder.stop());
+            self.meter = 0.0;
+            self.message = Some("saving…".into());
+        }
+    }
+
+    fn toggle_recording(&mut self) {
+        if self.recorder.is_some() {
+            self.stop_recording();
+        } else if self.saving.is_none() {
+            self.start_recording();
+        }
+    }
+
+    /// Pick up the file the writer thread finished
+    fn check_saved(&mut self) {
+        let Some(saving) = &self.saving else { return };
+        let Ok(result) = saving.try_recv() else { return };
+        self.saving = None;
+        self.recordings = clip::list();
+        match result {
+            Ok(path) => self.select(path),
+            Err(e) => self.message = Some(format!("could not save recording: {}", e)),
+        }
+    }
+
+    /// Play the selected recording between the trim markers
+    fn play(&mut self) {
+        let (Some(path), Some(clip)) = (&self.selected, &self.clip) else { return };
+        let Some(sink) = slowcore::preview::play_sound(path) else {
+            self.message = Some("could not play recording".into());
+            return;
+        };
+        if self.trim.0 > 0.0 {
+            let _ = sink.try_seek(clip.duration().mul_f32(self.trim.0));
+        }
+        self.sink = Some(sink);
+    }
+
+    fn stop_playing(&mut self) {
+        if let Some(sink) = self.sink.take() {
+            sink.stop();
+        }
+    }
+
+    fn is_playing(&self) -> bool {
+        self.sink.as_ref().is_some_and(|s| !s.empty())
+    }
+
+    /// Where playback is, as a fraction of the recording
+    fn playhead(&self) -> Option<f32> {
+        let (sink, clip) = (self.sink.as_ref()?, self.clip.as_ref()?);
+        let length = clip.duration().as_secs_f32();
+        (length > 0.0).then(|| sink.get_pos().as_secs_f32() / length)
+    }
+
+    fn apply_trim(&mut self) {
+        let Some(path) = self.selected.clone() else { return };
+        self.stop_playing();
+        match clip::trim(&path, self.trim.0, self.trim.1) {
+            Ok(()) => {
+                self.select(path);
+                self.message = Some("trimmed".into());
+            }
+            Err(e) => self.message = Some(format!("could not trim: {}", e)),
+        }
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        if ctx.input(|i| i.modifiers.command && i.key_pressed(Key::R)) {
+            self.toggle_recording();
+        }
+        if ctx.memory(|m| m.focused().is_some()) || self.recorder.is_some() {
+            return;
+        }
+        if ctx.input(|i| i.key_pressed(Key::Space)) {
+            if self.is_playing() { self.stop_playing() } else { self.play() }
+        }
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("file", |ui| {
+                    let label = if self.recorder.is_some() { "stop recording  ⌘R" } else { "record         ⌘R" };
+                    if ui.button(label).clicked() {
+                        self.toggle_recording();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    ui.label("save new recordings as:");
+                    for format in [Format::Flac, Format::Wav] {
+                        if ui.radio(self.format == format, format.label()).clicked() {
+                            self.format = format;
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => {
+                self.finish_before_closing();
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowrec", "slowRec");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    /// Don't lose a recording to the window closing
+    fn finish_before_closing(&mut self) {
+        self.stop_recording();
+        if let Some(saving) = self.saving.take() {
+            let _ = saving.recv_timeout(SAVE_WAIT);
+        }
+    }
+
+    fn draw_list(&mut self, ui: &mut egui::Ui) {
+        let mut picked = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if self.recordings.is_empty() {
+                ui.weak("no recordings yet");
+            }
+            for recording in &self.recordings {
+                let selected = self.selected.as_ref() == Some(&recording.path);
+                if ui.selectable_label(selected, &recording.name).clicked() {
+                    picked = Some(recording.path.clone());
+                }
+            }
+        });
+        if let Some(path) = picked {
+            if self.recorder.is_none() {
+                self.select(path);
+            }
+        }
+    }
+
+    fn draw_recorder(&mut self, ui: &mut egui::Ui) {
+        let Some(recorder) = &self.recorder else { return };
+        let (elapsed, paused) = (recorder.elapsed(), recorder.is_paused());
+        self.meter = recorder.level().max(self.meter - METER_FALL).max(0.0);
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(24.0);
+            ui.label(egui::RichText::new(format_duration(elapsed)).size(40.0).color(SlowColors::BLACK));
+            ui.weak(if paused { "paused" } else { "recording" });
+            ui.add_space(16.0);
+
+            // Level meter: a black bar as loud as the microphone
+            let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width().min(360.0), 16.0), Sense::hover());
+            let painter = ui.painter();
+            painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+            let mut filled = rect.shrink(2.0);
+            filled.set_width(filled.width() * self.meter.sqrt().min(1.0));
+            painter.rect_filled(filled, 0.0, SlowColors::BLACK);
+            ui.add_space(16.0);
+
+            ui.horizontal(|ui| {
+                ui.add_space((ui.available_width() - 140.0).max(0.0) / 2.0);
+                if ui.button(if paused { "resume" } else { "pause" }).clicked() {
+                    if let Some(recorder) = &mut self.recorder {
+                        if paused { recorder.resume() } else { recorder.pause() }
+                    }
+                }
+                if ui.button("stop").clicked() {
+                    self.stop_recording();
+                }
+            });
+        });
+    }
+
+    fn draw_recording(&mut self, ui: &mut egui::Ui) {
+        let Some(clip) = &self.clip else {
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 3.0);
+                if ui.button("record").clicked() {
+                    self.start_recording();
+                }
+                ui.weak("⌘R starts and stops recording");
+            });
+            return;
+        };
+        let name = self.selected.as_ref().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let length = clip.duration();
+        ui.label(egui::RichText::new(&name).strong());
+        ui.weak(format_duration(length));
+        ui.add_space(8.0);
+
+        // Waveform, with the parts outside the markers dithered over
+        let (rect, response) = ui.allocate_exact_size(Vec2::new(ui.available_width(), WAVEFORM_HEIGHT), Sense::click_and_drag());
+        let painter = ui.painter_at(rect);
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+        let width = rect.width() as usize;
+        if self.waveform.as_ref().is_none_or(|(w, _)| *w != width) {
+            self.waveform = Some((width, clip.peaks(width)));
+        }
+        let peaks = self.waveform.as_ref().map(|(_, p)| p.as_slice()).unwrap_or_default();
+        for (i, peak) in peaks.iter().enumerate() {
+            let x = rect.left() + i as f32 + 0.5;
+            let half = peak * (rect.height() / 2.0 - 4.0);
+            painter.line_segment([egui::pos2(x, rect.center().y - half), egui::pos2(x, rect.center().y + half)], Stroke::new(1.0, SlowColors::BLACK));
+        }
+        let x_at = |fraction: f32| rect.left() + fraction * rect.width();
+        let (start, end) = self.trim;
+        slowcore::dither::draw_dither_rect(&painter, Rect::from_x_y_ranges(rect.left()..=x_at(start), rect.y_range()), SlowColors::BLACK, 2);
+        slowcore::dither::draw_dither_rect(&painter, Rect::from_x_y_ranges(x_at(end)..=rect.right(), rect.y_range()), SlowColors::BLACK, 2);
+        for x in [x_at(start), x_at(end)] {
+            painter.line_segment([egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())], Stroke::new(2.0, SlowColors::BLACK));
+            painter.rect_filled(Rect::from_center_size(egui::pos2(x, rect.top() + 5.0), Vec2::splat(8.0)), 0.0, SlowColors::BLACK);
+        }
+        if let Some(at) = self.playhead() {
+            let x = x_at(at.min(1.0));
+            painter.line_segment([egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())], Stroke::new(1.0, SlowColors::BLACK));
+        }
+        // Drag the nearer marker
+        if let Some(pos) = response.interact_pointer_pos() {
+            let fraction = ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+            if (fraction - start).abs() <= (fraction - end).abs() {
+                self.trim.0 = fraction.min(end);
+            } else {
+                self.trim.1 = fraction.max(start);
+            }
+        }
+        ui.add_space(4.0);
+        let kept = length.mul_f32(self.trim.1 - self.trim.0);
+        ui.weak(format!("keeping {} from {}", format_duration(kept), format_duration(length.mul_f32(self.trim.0))));
+        ui.add_space(8.0);
+
+        let trimmed = self.trim != (0.0, 1.0);
+        let playing = self.is_playing();
+        ui.horizontal(|ui| {
+            if ui.button(if playing { "stop" } else { "play" }).clicked() {
+                if playing { self.stop_playing() } else { self.play() }
+            }
+            if ui.add_enabled(trimmed, egui::Button::new("reset markers")).clicked() {
+                self.trim = (0.0, 1.0);
+            }
+            if ui.add_enabled(trimmed, egui::Button::new("trim…")).clicked() {
+                self.confirm_trim = true;
+            }
+            ui.separator();
+            if ui.button("record new").clicked() {
+                self.start_recording();
+            }
+        });
+    }
+
+    fn draw_confirm_trim(&mut self, ctx: &Context) {
+        if !self.confirm_trim {
+            return;
+        }
+        let resp = egui::Window::new("trim recording")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("cut off everything outside the markers?");
+                ui.weak("the parts cut off can't be brought back");
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        self.confirm_trim = false;
+                    }
+                    if ui.button("trim").clicked() {
+                        self.confirm_trim = false;
+                        self.apply_trim();
+                    }
+                });
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+impl eframe::App for SlowRecApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowrec") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.check_saved();
+        self.handle_keys(ctx);
+
+        // Stop at the end marker
+        if self.playhead().is_some_and(|at| at >= self.trim.1) || (self.sink.is_some() && !self.is_playing()) {
+            self.stop_playing();
+        }
+        self.player.set_playing(self.is_playing());
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| {
+                let count = match self.recordings.len() {
+                    1 => "1 recording".to_string(),
+                    n => format!("{} recordings", n),
+                };
+                format!("{}  |  new recordings as {}", count, self.format.extension().to_uppercase())
+            });
+            status_bar(ui, &status);
+        });
+
+        SidePanel::left("recordings")
+            .resizable(false)
+            .exact_width(170.0)
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(6.0)))
+            .show(ctx, |ui| self.draw_list(ui));
+
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(12.0)))
+            .show(ctx, |ui| {
+                if self.recorder.is_some() {
+                    self.draw_recorder(ui);
+                } else {
+                    self.draw_recording(ui);
+                }
+            });
+
+        self.draw_confirm_trim(ctx);
+        self.draw_about(ctx);
+
+        if self.recorder.is_some() || self.sink.is_some() || self.saving.is_some() {
+            ctx.request_repaint_after(METER_INTERVAL);
+        }
+        self.repaint.end_frame(ctx);
+    }
+}
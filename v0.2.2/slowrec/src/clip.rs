@@ -0,0 +1,186 @@
+//! Recordings on disk — listing, reading, writing and trimming
+//!
+//! Recordings are mono, 16-bit, at whatever rate the microphone gives,
+//! kept in ~/Music/recordings as WAV or FLAC.
+
+use flacenc::component::BitRepr;
+use flacenc::error::Verify;
+use rodio::Source;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Wav,
+    Flac,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Wav => "wav",
+            Format::Flac => "flac",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Format::Wav => "WAV (larger, quick to save)",
+            Format::Flac => "FLAC (about half the size)",
+        }
+    }
+
+    fn of(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "wav" => Some(Format::Wav),
+            "flac" => Some(Format::Flac),
+            _ => None,
+        }
+    }
+}
+
+/// A recording in the list
+pub struct Recording {
+    pub path: PathBuf,
+    pub name: String,
+    pub modified: SystemTime,
+}
+
+/// Where recordings are kept
+pub fn recordings_dir() -> PathBuf {
+    let dir = slowcore::storage::music_dir().join("recordings");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// The recordings kept, newest first
+pub fn list() -> Vec<Recording> {
+    let Ok(entries) = std::fs::read_dir(recordings_dir()) else { return Vec::new() };
+    let mut recordings: Vec<Recording> = entries
+        .flatten()
+        .filter(|e| Format::of(&e.path()).is_some())
+        .filter_map(|e| {
+            let path = e.path();
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            Some(Recording { modified: e.metadata().ok()?.modified().ok()?, path, name })
+        })
+        .collect();
+    recordings.sort_by_key(|r| std::cmp::Reverse(r.modified));
+    recordings
+}
+
+/// A recording read into memory, mixed to mono
+pub struct Clip {
+    pub samples: Vec<i16>,
+    pub sample_rate: u32,
+}
+
+impl Clip {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+        let decoder = rodio::Decoder::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+        let channels = decoder.channels().max(1) as usize;
+        let sample_rate = decoder.sample_rate();
+        let all: Vec<i16> = decoder.collect();
+        let samples = all
+            .chunks(channels)
+            .map(|frame| (frame.iter().map(|&s| s as i32).sum::<i32>() / frame.len() as i32) as i16)
+            .collect();
+        Ok(Self { samples, sample_rate })
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.samples.len() as f64 / self.sample_rate.max(1) as f64)
+    }
+
+    /// The loudest sample in each of `buckets` equal slices, from 0 to 1,
+    /// for drawing the waveform
+    pub fn peaks(&self, buckets: usize) -> Vec<f32> {
+        if buckets == 0 || self.samples.is_empty() {
+            return Vec::new();
+        }
+        let per = self.samples.len().div_ceil(buckets);
+        self.samples
+            .chunks(per)
+            .map(|slice| slice.iter().map(|s| s.unsigned_abs()).max().unwrap_or(0) as f32 / i16::MAX as f32)
+            .collect()
+    }
+}
+
+/// Write mono samples to `path` in `format`, replacing it only once the
+/// whole file is written
+pub fn write(path: &Path, format: Format, samples: &[i16], sample_rate: u32) -> Result<(), String> {
+    let tmp = path.with_extension(format!("{}.tmp", format.extension()));
+    let written = match format {
+        Format::Wav => write_wav(&tmp, samples, sample_rate),
+        Format::Flac => write_flac(&tmp, samples, sample_rate),
+    };
+    match written.and_then(|_| std::fs::rename(&tmp, path).map_err(|e| e.to_string())) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+pub fn wav_spec(sample_rate: u32) -> hound::WavSpec {
+    hound::WavSpec { channels: 1, sample_rate, bits_per_sample: 16, sample_format: hound::SampleFormat::Int }
+}
+
+fn write_wav(path: &Path, samples: &[i16], sample_rate: u32) -> Result<(), String> {
+    let mut writer = hound::WavWriter::create(path, wav_spec(sample_rate)).map_err(|e| e.to_string())?;
+    for &sample in samples {
+        writer.write_sample(sample).map_err(|e| e.to_string())?;
+    }
+    writer.finalize().map_err(|e| e.to_string())
+}
+
+fn write_flac(path: &Path, samples: &[i16], sample_rate: u32) -> Result<(), String> {
+    let config = flacenc::config::Encoder::default().into_verified()
+        .map_err(|_| "could not set up the FLAC encoder".to_string())?;
+    let wide: Vec<i32> = samples.iter().map(|&s| s as i32).collect();
+    let source = flacenc::source::MemSource::from_samples(&wide, 1, 16, sample_rate as usize);
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| format!("could not encode FLAC: {:?}", e))?;
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream.write(&mut sink).map_err(|e| format!("could not encode FLAC: {:?}", e))?;
+    std::fs::write(path, sink.as_slice()).map_err(|e| e.to_string())
+}
+
+/// Keep only the part of the recording at `path` between `start` and
+/// `end`, as fractions of its length
+pub fn trim(path: &Path, start: f32, end: f32) -> Result<(), String> {
+    let format = Format::of(path).ok_or("not a recording")?;
+    let clip = Clip::load(path)?;
+    let (from, to) = trim_range(clip.samples.len(), start, end);
+    if from >= to {
+        return Err("nothing left to keep".into());
+    }
+    write(path, format, &clip.samples[from..to], clip.sample_rate)
+}
+
+/// The samples kept when trimming `len` to `start`–`end`
+fn trim_range(len: usize, start: f32, end: f32) -> (usize, usize) {
+    let at = |fraction: f32| ((fraction.clamp(0.0, 1.0) * len as f32).round() as usize).min(len);
+    (at(start), at(end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_wav() {
+        let path = std::env::temp_dir().join(format!("slowrec_trim_{}.wav", std::process::id()));
+        let samples: Vec<i16> = (0..1000).map(|i| (i * 30) as i16).collect();
+        write(&path, Format::Wav, &samples, 8000).unwrap();
+        trim(&path, 0.25, 0.5).unwrap();
+        let clip = Clip::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(clip.sample_rate, 8000);
+        assert_eq!(clip.samples, samples[250..500]);
+        assert_eq!(trim_range(10, -1.0, 2.0), (0, 10));
+    }
+}
@@ -0,0 +1,31 @@
+//! slowRec - A sound recorder for the Slow Computer
+//!
+//! Voice memos from the microphone, kept in ~/Music/recordings as WAV or
+//! FLAC, with a list of past recordings to play back and trim.
+
+mod app;
+mod clip;
+mod recorder;
+
+use app::SlowRecApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let viewport = window_layout::viewport("slowrec", Placement::RememberLast, [600.0, 420.0])
+        .with_title("slowRec");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowRec",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowRecApp::new(cc))
+        }),
+    )
+}
@@ -0,0 +1,154 @@
+//! Recording from the microphone
+//!
+//! cpal calls back with the microphone's samples on its own thread; they
+//! are mixed to mono and passed to a writer thread, so a slow SD card
+//! never drops sound. WAV is written as it arrives. FLAC is kept in
+//! memory and encoded when recording stops.
+
+use crate::clip::{self, Format};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A recording in progress. Call [`Recorder::stop`] to finish the file.
+pub struct Recorder {
+    stream: cpal::Stream,
+    /// Loudest sample of the last callback, 0 to 1, as f32 bits
+    level: Arc<AtomicU32>,
+    paused: Arc<AtomicBool>,
+    /// Time recorded before the last pause
+    recorded: Duration,
+    /// When recording last started or resumed; `None` while paused
+    resumed: Option<Instant>,
+    saved: Receiver<Result<PathBuf, String>>,
+}
+
+impl Recorder {
+    /// Start recording the default microphone to `path`
+    pub fn start(path: PathBuf, format: Format) -> Result<Self, String> {
+        let device = cpal::default_host().default_input_device().ok_or("no microphone found")?;
+        let config = device.default_input_config().map_err(|e| e.to_string())?;
+        let sample_format = config.sample_format();
+        let config: cpal::StreamConfig = config.into();
+
+        let level = Arc::new(AtomicU32::new(0));
+        let paused = Arc::new(AtomicBool::new(false));
+        let (chunks, received) = mpsc::channel();
+
+        let stream = match sample_format {
+            cpal::SampleFormat::F32 => build::<f32>(&device, &config, chunks, &level, &paused),
+            cpal::SampleFormat::I16 => build::<i16>(&device, &config, chunks, &level, &paused),
+            cpal::SampleFormat::U16 => build::<u16>(&device, &config, chunks, &level, &paused),
+            cpal::SampleFormat::I32 => build::<i32>(&device, &config, chunks, &level, &paused),
+            other => return Err(format!("the microphone's {} samples aren't supported", other)),
+        }?;
+        stream.play().map_err(|e| e.to_string())?;
+        // Only now that sound is coming is there a file to write
+        let (done, saved) = mpsc::channel();
+        let sample_rate = config.sample_rate.0;
+        let _ = std::thread::Builder::new().name("slowrec-writer".into()).spawn(move || {
+            let _ = done.send(write(received, &path, format, sample_rate).map(|_| path));
+        });
+        Ok(Self { stream, level, paused, recorded: Duration::ZERO, resumed: Some(Instant::now()), saved })
+    }
+
+    /// How loud the microphone is now, 0 to 1
+    pub fn level(&self) -> f32 {
+        f32::from_bits(self.level.load(Ordering::Relaxed))
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.recorded + self.resumed.map(|t| t.elapsed()).unwrap_or_default()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.resumed.is_none()
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(resumed) = self.resumed.take() {
+            self.recorded += resumed.elapsed();
+            self.paused.store(true, Ordering::Relaxed);
+            self.level.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.resumed.is_none() {
+            self.resumed = Some(Instant::now());
+            self.paused.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Stop recording. The file is finished on the writer thread; the
+    /// receiver gets its path, or why it couldn't be saved.
+    pub fn stop(self) -> Receiver<Result<PathBuf, String>> {
+        let _ = self.stream.pause();
+        // Dropping the stream drops its sender, which ends the writer
+        drop(self.stream);
+        self.saved
+    }
+}
+
+/// Open the input stream for samples of type `T`
+fn build<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    chunks: Sender<Vec<i16>>,
+    level: &Arc<AtomicU32>,
+    paused: &Arc<AtomicBool>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    let (level, paused) = (level.clone(), paused.clone());
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                if paused.load(Ordering::Relaxed) {
+                    return;
+                }
+                let mono: Vec<i16> = data
+                    .chunks(channels)
+                    .map(|frame| {
+                        let mixed = frame.iter().map(|&s| f32::from_sample_(s)).sum::<f32>() / frame.len() as f32;
+                        (mixed.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+                    })
+                    .collect();
+                let peak = mono.iter().map(|s| s.unsigned_abs()).max().unwrap_or(0) as f32 / i16::MAX as f32;
+                level.store(peak.to_bits(), Ordering::Relaxed);
+                let _ = chunks.send(mono);
+            },
+            |e| slowcore::log::warn!("recording: {}", e),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Write what arrives on `received` until the stream is dropped
+fn write(received: Receiver<Vec<i16>>, path: &std::path::Path, format: Format, sample_rate: u32) -> Result<(), String> {
+    match format {
+        Format::Flac => {
+            let samples: Vec<i16> = received.iter().flatten().collect();
+            clip::write(path, format, &samples, sample_rate)
+        }
+        Format::Wav => {
+            let tmp = path.with_extension("wav.tmp");
+            let mut writer = hound::WavWriter::create(&tmp, clip::wav_spec(sample_rate)).map_err(|e| e.to_string())?;
+            for chunk in received.iter() {
+                for sample in chunk {
+                    writer.write_sample(sample).map_err(|e| e.to_string())?;
+                }
+            }
+            writer.finalize().map_err(|e| e.to_string())?;
+            std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+        }
+    }
+}
@@ -4,13 +4,30 @@ use egui::{
 };
 use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
+use slowcore::lifecycle::{self, SaveState};
 use slowcore::repaint::RepaintController;
 use slowcore::storage::config_dir;
 use slowcore::theme::SlowColors;
-use slowcore::widgets::{window_control_buttons, WindowAction};
+use slowcore::widgets::{about_dialog, window_control_buttons, AboutInfo, WindowAction};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+const ABOUT: AboutInfo = AboutInfo {
+    name: "solitaire",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "klondike solitaire",
+    sections: &[(
+        "how to play",
+        &[
+            "click a card to select it,",
+            "then click where to place it",
+            "double-click to send to foundation",
+            "click the stock pile to draw",
+        ],
+    )],
+    ..AboutInfo::EMPTY
+};
+
 // ---------------------------------------------------------------------------
 // Card model
 // ---------------------------------------------------------------------------
@@ -177,7 +194,7 @@ enum DragSource {
     Foundation(usize),
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SolitaireGame {
     /// Stock pile (face-down, draw from here)
     pub stock: Vec<Card>,
@@ -1070,39 +1087,9 @@ impl SlowSolitaireApp {
     }
 
     fn draw_about(&mut self, ctx: &Context) {
-        if !self.show_about {
-            return;
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
-        let screen = ctx.screen_rect();
-        let max_h = (screen.height() - 60.0).max(120.0);
-        let resp = egui::Window::new("about solitaire")
-            .collapsible(false)
-            .resizable(false)
-            .default_width(280.0)
-            .max_height(max_h)
-            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
-            .show(ctx, |ui| {
-                egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                    ui.vertical_centered(|ui| {
-                        ui.add_space(8.0);
-                        ui.heading("solitaire");
-                        ui.add_space(4.0);
-                        ui.label("klondike solitaire");
-                        ui.add_space(8.0);
-                        ui.label("click a card to select it,");
-                        ui.label("then click where to place it.");
-                        ui.label("double-click to send to foundation.");
-                        ui.add_space(8.0);
-                        ui.label("click the stock pile to draw.");
-                        ui.add_space(12.0);
-                        if ui.button("ok").clicked() {
-                            self.show_about = false;
-                        }
-                        ui.add_space(4.0);
-                    });
-                });
-            });
-        if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
     }
 
     fn draw_win(&mut self, ctx: &Context) {
@@ -1131,6 +1118,21 @@ impl SlowSolitaireApp {
     }
 }
 
+impl SaveState for SlowSolitaireApp {
+    type State = SolitaireGame;
+
+    fn save_state(&self) -> Option<SolitaireGame> {
+        (!self.won).then(|| self.game.clone())
+    }
+
+    fn restore_state(&mut self, game: SolitaireGame) {
+        self.game = game;
+        self.selected = None;
+        self.won = self.game.is_won();
+        self.auto_finishing = false;
+    }
+}
+
 impl eframe::App for SlowSolitaireApp {
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
         self.repaint.begin_frame(ctx);
@@ -1184,10 +1186,13 @@ impl eframe::App for SlowSolitaireApp {
 
         self.draw_about(ctx);
         self.draw_win(ctx);
+        // Keep the game if the system stops the app or the battery runs out
+        lifecycle::update(ctx, "slowsolitaire", self);
         self.repaint.end_frame(ctx);
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        lifecycle::discard("slowsolitaire");
         if !self.won {
             self.save_game();
         } else {
@@ -1,11 +1,11 @@
 mod app;
 use app::SlowSolitaireApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([740.0, 560.0])
+        viewport: window_layout::viewport("slowsolitaire", Placement::RememberLast, [740.0, 560.0])
             .with_title("solitaire"),
         ..Default::default()
     };
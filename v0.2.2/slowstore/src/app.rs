@@ -0,0 +1,444 @@
+//! slowStore application
+
+use crate::store::{self, Index, Installed, Kind, Package, StoreConfig};
+use egui::{Align2, CentralPanel, Context, Key, SidePanel, TopBottomPanel, Vec2};
+use slowcore::repaint::RepaintController;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, WindowAction};
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowStore",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "optional apps, fonts and sound themes for slowOS",
+    features: &["indexes on the web or a USB stick", "installs what a package needs with it", "checks for room before installing", "installed apps open their kinds of file"],
+    ..AboutInfo::EMPTY
+};
+
+/// Something finishing in the background
+enum Done {
+    Loaded(Result<Index, String>),
+    /// What was installed ("slowSheets")
+    Installed(Result<String, String>),
+}
+
+enum Dialog {
+    /// The index's address being typed
+    Source(String),
+    /// Packages about to be installed, needs first
+    Install(Vec<Package>),
+    Remove(String),
+}
+
+/// Which list is showing
+#[derive(Clone, Copy, PartialEq)]
+enum Shelf {
+    Kind(Kind),
+    Installed,
+}
+
+pub struct SlowStoreApp {
+    config: StoreConfig,
+    index: Option<Index>,
+    installed: BTreeMap<String, Installed>,
+    shelf: Shelf,
+    /// Id of the package shown on the right
+    selected: Option<String>,
+    task: Option<mpsc::Receiver<Done>>,
+    dialog: Option<Dialog>,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+fn run_in_background(ctx: &Context, task: impl FnOnce() -> Done + Send + 'static) -> mpsc::Receiver<Done> {
+    let (tx, rx) = mpsc::channel();
+    let ctx = ctx.clone();
+    std::thread::spawn(move || {
+        let _ = tx.send(task());
+        ctx.request_repaint();
+    });
+    rx
+}
+
+impl SlowStoreApp {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self {
+            config: StoreConfig::load(),
+            index: None,
+            installed: store::installed(),
+            shelf: Shelf::Kind(Kind::App),
+            selected: None,
+            task: None,
+            dialog: None,
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        };
+        if app.config.source.is_empty() {
+            app.dialog = Some(Dialog::Source(String::new()));
+        } else {
+            app.load_index(&cc.egui_ctx);
+        }
+        app
+    }
+
+    fn load_index(&mut self, ctx: &Context) {
+        if self.task.is_some() {
+            return;
+        }
+        let source = self.config.source.clone();
+        self.message = Some("reading the index...".into());
+        self.task = Some(run_in_background(ctx, move || Done::Loaded(store::load_index(&source))));
+    }
+
+    fn poll(&mut self) {
+        let Some(rx) = &self.task else { return };
+        let Ok(done) = rx.try_recv() else { return };
+        self.task = None;
+        match done {
+            Done::Loaded(Ok(index)) => {
+                self.index = Some(index);
+                self.message = None;
+            }
+            Done::Installed(Ok(names)) => {
+                self.installed = store::installed();
+                self.message = Some(format!("installed {}", names));
+            }
+            Done::Loaded(Err(e)) => self.message = Some(e),
+            Done::Installed(Err(e)) => {
+                self.installed = store::installed();
+                self.message = Some(format!("could not install {}", e));
+            }
+        }
+    }
+
+    /// Work out what installing `id` takes, and ask
+    fn ask_install(&mut self, id: &str) {
+        let Some(index) = &self.index else { return };
+        let plan = match store::install_plan(index, id, &self.installed).and_then(|plan| store::check_space(&plan).map(|_| plan)) {
+            Ok(plan) => plan,
+            Err(e) => {
+                self.message = Some(format!("can't install: {}", e));
+                return;
+            }
+        };
+        self.dialog = Some(Dialog::Install(plan.into_iter().cloned().collect()));
+    }
+
+    fn install(&mut self, ctx: &Context, plan: Vec<Package>) {
+        let Some(index) = &self.index else { return };
+        if self.task.is_some() {
+            return;
+        }
+        let source = index.source.clone();
+        let names = plan.iter().map(|p| p.name.clone()).collect::<Vec<_>>().join(", ");
+        self.message = Some(format!("installing {}...", names));
+        self.task = Some(run_in_background(ctx, move || Done::Installed(store::install(&source, &plan).map(|_| names))));
+    }
+
+    fn ask_remove(&mut self, id: &str) {
+        let needed = store::needed_by(id, &self.installed);
+        if needed.is_empty() {
+            self.dialog = Some(Dialog::Remove(id.to_string()));
+        } else {
+            self.message = Some(format!("can't remove it: {} needs it", needed.join(", ")));
+        }
+    }
+
+    fn remove(&mut self, id: &str) {
+        let name = self.installed.get(id).map(|p| p.name.clone()).unwrap_or_default();
+        self.message = Some(match store::remove(id) {
+            Ok(()) => format!("removed {}", name),
+            Err(e) => format!("could not remove {}: {}", name, e),
+        });
+        self.installed = store::installed();
+    }
+
+    /// Name of the package `id`, from the index or what's installed
+    fn name_of(&self, id: &str) -> String {
+        self.index
+            .as_ref()
+            .and_then(|i| i.get(id))
+            .map(|p| p.name.clone())
+            .or_else(|| self.installed.get(id).map(|p| p.name.clone()))
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let busy = self.task.is_some();
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("store", |ui| {
+                    if ui.button("index…").clicked() {
+                        self.dialog = Some(Dialog::Source(self.config.source.clone()));
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(!busy && !self.config.source.is_empty(), egui::Button::new("read again")).clicked() {
+                        self.load_index(ui.ctx());
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowstore", "slowStore");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_shelves(&mut self, ui: &mut egui::Ui) {
+        for kind in Kind::ALL {
+            if ui.selectable_label(self.shelf == Shelf::Kind(kind), kind.label()).clicked() {
+                self.shelf = Shelf::Kind(kind);
+                self.selected = None;
+            }
+        }
+        ui.separator();
+        let label = format!("installed ({})", self.installed.len());
+        if ui.selectable_label(self.shelf == Shelf::Installed, label).clicked() {
+            self.shelf = Shelf::Installed;
+            self.selected = None;
+        }
+    }
+
+    /// (id, name, version, what's going on with it) for the list showing
+    fn rows(&self) -> Vec<(String, String, String, &'static str)> {
+        match self.shelf {
+            Shelf::Kind(kind) => self
+                .index
+                .iter()
+                .flat_map(|i| &i.packages)
+                .filter(|p| p.kind == kind)
+                .map(|p| {
+                    let state = match self.installed.get(&p.id) {
+                        Some(i) if slowcore::update::is_newer(&p.version, &i.version) => "update",
+                        Some(_) => "installed",
+                        None => "",
+                    };
+                    (p.id.clone(), p.name.clone(), p.version.clone(), state)
+                })
+                .collect(),
+            Shelf::Installed => self
+                .installed
+                .iter()
+                .map(|(id, p)| (id.clone(), p.name.clone(), p.version.clone(), ""))
+                .collect(),
+        }
+    }
+
+    fn draw_list(&mut self, ui: &mut egui::Ui) {
+        let rows = self.rows();
+        let mut picked = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if rows.is_empty() {
+                ui.weak(match (self.shelf, &self.index) {
+                    (Shelf::Installed, _) => "nothing installed yet",
+                    (_, None) => "no index read yet",
+                    _ => "nothing here in this index",
+                });
+            }
+            for (id, name, version, state) in &rows {
+                let label = match *state {
+                    "" => format!("{}  {}", name, version),
+                    state => format!("{}  {}  ({})", name, version, state),
+                };
+                if ui.selectable_label(self.selected.as_ref() == Some(id), label).clicked() {
+                    picked = Some(id.clone());
+                }
+            }
+        });
+        if picked.is_some() {
+            self.selected = picked;
+        }
+    }
+
+    fn draw_details(&mut self, ui: &mut egui::Ui) {
+        let Some(id) = self.selected.clone() else { return };
+        let package = self.index.as_ref().and_then(|i| i.get(&id)).cloned();
+        let installed = self.installed.get(&id).cloned();
+        let busy = self.task.is_some();
+        let (mut install, mut remove) = (false, false);
+
+        if let Some(package) = &package {
+            ui.label(egui::RichText::new(&package.name).strong().size(16.0));
+            match &installed {
+                Some(i) if i.version != package.version => ui.weak(format!("version {} installed, {} available", i.version, package.version)),
+                _ => ui.weak(format!("version {}", package.version)),
+            };
+            ui.add_space(6.0);
+            if !package.description.is_empty() {
+                ui.add(egui::Label::new(&package.description).wrap(true));
+                ui.add_space(6.0);
+            }
+            if !package.depends.is_empty() {
+                let needs: Vec<String> = package.depends.iter().map(|d| self.name_of(d)).collect();
+                ui.label(format!("needs {}", needs.join(", ")));
+            }
+            if !package.opens.is_empty() {
+                let kinds: Vec<String> = package.opens.iter().map(|e| format!(".{}", e.trim_start_matches('.'))).collect();
+                ui.label(format!("opens {} files", kinds.join(" ")));
+            }
+            if package.size > 0 {
+                ui.label(format!("takes {}", store::format_size(package.size)));
+            }
+            ui.add_space(8.0);
+            let action = match &installed {
+                None => Some("install"),
+                Some(i) if slowcore::update::is_newer(&package.version, &i.version) => Some("update"),
+                Some(_) => None,
+            };
+            if let Some(action) = action {
+                install = ui.add_enabled(!busy, egui::Button::new(action)).clicked();
+            }
+        } else if let Some(installed) = &installed {
+            ui.label(egui::RichText::new(&installed.name).strong().size(16.0));
+            ui.weak(format!("version {}", installed.version));
+            ui.add_space(8.0);
+        }
+        if installed.is_some() {
+            remove = ui.add_enabled(!busy, egui::Button::new("remove")).clicked();
+        }
+
+        if install {
+            self.ask_install(&id);
+        }
+        if remove {
+            self.ask_remove(&id);
+        }
+    }
+
+    fn draw_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.dialog else { return };
+        let title = match dialog {
+            Dialog::Source(_) => "index",
+            Dialog::Install(_) => "install",
+            Dialog::Remove(_) => "remove",
+        };
+        let mut ok = false;
+        let mut cancel = false;
+        let names = |plan: &[Package]| plan.iter().map(|p| p.name.clone()).collect::<Vec<_>>().join(", ");
+        let resp = egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                let action = match dialog {
+                    Dialog::Source(source) => {
+                        ui.label("address of the index, or where it is on a disk:");
+                        let field = ui.add(egui::TextEdit::singleline(source).desired_width(320.0));
+                        if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                            ok = true;
+                        }
+                        ui.weak("for example /media/usb/slowos-store/index.json");
+                        "read"
+                    }
+                    Dialog::Install(plan) => {
+                        if let Some((package, needs)) = plan.split_last() {
+                            ui.label(format!("install {} {}?", package.name, package.version));
+                            if !needs.is_empty() {
+                                ui.label(format!("it needs {}, which will be installed too", names(needs)));
+                            }
+                        }
+                        let size: u64 = plan.iter().map(|p| p.size).sum();
+                        if size > 0 {
+                            ui.weak(format!("takes {}", store::format_size(size)));
+                        }
+                        "install"
+                    }
+                    Dialog::Remove(id) => {
+                        let name = self.installed.get(id.as_str()).map(|p| p.name.as_str()).unwrap_or(id);
+                        ui.label(format!("remove {}?", name));
+                        "remove"
+                    }
+                };
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        cancel = true;
+                    }
+                    if ui.button(action).clicked() {
+                        ok = true;
+                    }
+                });
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+
+        if cancel || ctx.input(|i| i.key_pressed(Key::Escape)) {
+            self.dialog = None;
+        } else if ok {
+            match self.dialog.take() {
+                Some(Dialog::Source(source)) => {
+                    self.config.source = source.trim().to_string();
+                    self.config.save();
+                    self.index = None;
+                    self.selected = None;
+                    self.load_index(ctx);
+                }
+                Some(Dialog::Install(plan)) => self.install(ctx, plan),
+                Some(Dialog::Remove(id)) => self.remove(&id),
+                None => {}
+            }
+        }
+    }
+}
+
+impl eframe::App for SlowStoreApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowstore") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.poll();
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| match &self.index {
+                Some(index) => format!("{} packages  |  {} installed", index.packages.len(), self.installed.len()),
+                None => format!("{} installed", self.installed.len()),
+            });
+            status_bar(ui, &status);
+        });
+
+        SidePanel::left("shelves")
+            .resizable(false)
+            .exact_width(140.0)
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(6.0)))
+            .show(ctx, |ui| self.draw_shelves(ui));
+
+        if self.selected.is_some() {
+            SidePanel::right("details")
+                .resizable(false)
+                .exact_width(230.0)
+                .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(12.0)))
+                .show(ctx, |ui| self.draw_details(ui));
+        }
+
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(12.0)))
+            .show(ctx, |ui| self.draw_list(ui));
+
+        self.draw_dialog(ctx);
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+        self.repaint.end_frame(ctx);
+    }
+}
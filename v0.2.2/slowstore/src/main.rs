@@ -0,0 +1,31 @@
+//! slowStore - Optional apps, fonts and sound themes for the Slow Computer
+//!
+//! Browses an index of packages on the web or a USB stick and installs
+//! them, with whatever they need, after checking there is room. Apps go
+//! in ~/Applications and open their kinds of file from the desktop.
+
+mod app;
+mod store;
+
+use app::SlowStoreApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let viewport = window_layout::viewport("slowstore", Placement::RememberLast, [680.0, 460.0])
+        .with_title("slowStore");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowStore",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowStoreApp::new(cc))
+        }),
+    )
+}
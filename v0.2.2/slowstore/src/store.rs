@@ -0,0 +1,438 @@
+//! The index of optional packages, and installing and removing them
+//!
+//! An index is a JSON file on the web, or on a disk such as a USB stick:
+//!
+//! ```json
+//! { "packages": [ { "id": "slowsheets", "kind": "app", "name": "slowSheets",
+//!                   "version": "0.1.0", "description": "spreadsheets", "size": 4200000,
+//!                   "depends": ["plex-mono"], "opens": ["csv", "tsv"], "icon": "S",
+//!                   "files": [ { "file": "slowsheets", "sha256": "…" } ] } ] }
+//! ```
+//!
+//! Files are named relative to the index, or by full address. Indexes and
+//! files on the web must be at https addresses, and an app's file must
+//! have a `sha256`; both are checked before anything is put in place. Apps go in
+//! ~/Applications and into the app registry, so slowDesktop shows them and
+//! opens their kinds of file with them; fonts go in the user fonts folder
+//! and sound themes in a folder of their own. What was installed, and
+//! where its files went, is kept in installed.json.
+
+use serde::{Deserialize, Serialize};
+use slowcore::app_registry::{self, InstalledApp};
+use slowcore::download::{self, Origin};
+use slowcore::sound::sound_packs_dir;
+use slowcore::storage::config_dir;
+use slowcore::theme;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Largest file accepted
+const MAX_FILE: u64 = 256 * 1024 * 1024;
+
+/// Room left on the disk after an install
+const SPARE_SPACE: u64 = 50 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    App,
+    Font,
+    Sound,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 3] = [Kind::App, Kind::Font, Kind::Sound];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Kind::App => "apps",
+            Kind::Font => "fonts",
+            Kind::Sound => "sound themes",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PackageFile {
+    /// Relative to the index, or a full address
+    pub file: String,
+    /// Checked after downloading when given; apps must give one
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+/// One entry in the index
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Package {
+    /// Binary name for apps; unique across the index
+    pub id: String,
+    pub kind: Kind,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// Bytes once installed
+    #[serde(default)]
+    pub size: u64,
+    /// Ids of packages it needs
+    #[serde(default)]
+    pub depends: Vec<String>,
+    /// For apps: file extensions it opens
+    #[serde(default)]
+    pub opens: Vec<String>,
+    /// For apps: the glyph on its desktop icon
+    #[serde(default)]
+    pub icon: String,
+    pub files: Vec<PackageFile>,
+}
+
+/// A name that can't reach outside the folder it's put in
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\'])
+}
+
+fn has_extension(name: &str, extensions: &[&str]) -> bool {
+    name.rsplit_once('.').is_some_and(|(_, ext)| extensions.contains(&ext.to_lowercase().as_str()))
+}
+
+impl Package {
+    fn is_valid(&self) -> bool {
+        let id_ok = self.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        let files_ok = match self.kind {
+            Kind::App => self.files.len() == 1 && self.files[0].sha256.as_ref().is_some_and(|s| !s.trim().is_empty()),
+            Kind::Font => self.files.iter().all(|f| has_extension(file_name(&f.file), &["ttf", "otf"])),
+            Kind::Sound => self.files.iter().all(|f| has_extension(file_name(&f.file), &["wav"])),
+        };
+        is_plain_name(&self.id) && id_ok && !self.files.is_empty() && files_ok
+    }
+
+    /// Where each of its files goes
+    fn destinations(&self) -> Vec<PathBuf> {
+        match self.kind {
+            Kind::App => vec![app_registry::applications_dir().join(&self.id)],
+            Kind::Font => self.files.iter().map(|f| theme::fonts_dir().join(file_name(&f.file))).collect(),
+            Kind::Sound => self.files.iter().map(|f| sound_packs_dir().join(&self.id).join(file_name(&f.file))).collect(),
+        }
+    }
+}
+
+/// The last part of a file's name in the index
+fn file_name(file: &str) -> &str {
+    file.rsplit('/').next().unwrap_or(file)
+}
+
+#[derive(Deserialize, Default)]
+struct IndexFile {
+    #[serde(default)]
+    packages: Vec<Package>,
+}
+
+/// An index as read
+#[derive(Debug, Clone)]
+pub struct Index {
+    /// Address or path it was read from
+    pub source: String,
+    pub packages: Vec<Package>,
+}
+
+impl Index {
+    pub fn get(&self, id: &str) -> Option<&Package> {
+        self.packages.iter().find(|p| p.id == id)
+    }
+}
+
+/// Read the index at `source`, an https address or a path. Slow: call
+/// off the UI thread.
+pub fn load_index(source: &str) -> Result<Index, String> {
+    let source = source.trim();
+    if source.is_empty() {
+        return Err("no index to read: choose one from the store menu".into());
+    }
+    let json = if download::is_web(source) {
+        download::get_text(source)?
+    } else {
+        std::fs::read_to_string(source).map_err(|e| format!("can't read {}: {}", source, e))?
+    };
+    let index: IndexFile = serde_json::from_str(&json).map_err(|e| format!("the index can't be read: {}", e))?;
+    let mut seen = HashSet::new();
+    let packages = index.packages.into_iter().filter(|p| p.is_valid() && seen.insert(p.id.clone())).collect();
+    Ok(Index { source: source.to_string(), packages })
+}
+
+/// Where the index lives, chosen by the user
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StoreConfig {
+    #[serde(default)]
+    pub source: String,
+}
+
+impl StoreConfig {
+    fn path() -> PathBuf {
+        config_dir("slowstore").join("store.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        write_json(&Self::path(), self);
+    }
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(value) {
+        let _ = slowcore::storage::save_atomic(path, json.as_bytes());
+    }
+}
+
+/// A package on this computer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Installed {
+    pub kind: Kind,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub depends: Vec<String>,
+    /// Every file it put in place
+    pub files: Vec<PathBuf>,
+}
+
+fn installed_path() -> PathBuf {
+    config_dir("slowstore").join("installed.json")
+}
+
+/// Installed packages by id
+pub fn installed() -> BTreeMap<String, Installed> {
+    std::fs::read_to_string(installed_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// The packages to install for `id`, needs first: `id` itself, and what
+/// it needs that isn't installed yet
+pub fn install_plan<'a>(index: &'a Index, id: &str, installed: &BTreeMap<String, Installed>) -> Result<Vec<&'a Package>, String> {
+    fn visit<'a>(
+        index: &'a Index,
+        id: &str,
+        installed: &BTreeMap<String, Installed>,
+        visiting: &mut HashSet<String>,
+        plan: &mut Vec<&'a Package>,
+    ) -> Result<(), String> {
+        if plan.iter().any(|p| p.id == id) || !visiting.insert(id.to_string()) {
+            return Ok(());
+        }
+        let package = index.get(id).ok_or_else(|| format!("it needs {}, which isn't in the index", id))?;
+        for need in &package.depends {
+            if !installed.contains_key(need) {
+                visit(index, need, installed, visiting, plan)?;
+            }
+        }
+        plan.push(package);
+        Ok(())
+    }
+    let mut plan = Vec::new();
+    visit(index, id, installed, &mut HashSet::new(), &mut plan)?;
+    Ok(plan)
+}
+
+/// Names of installed packages that need `id`
+pub fn needed_by(id: &str, installed: &BTreeMap<String, Installed>) -> Vec<String> {
+    installed.iter().filter(|(other, p)| *other != id && p.depends.iter().any(|d| d == id)).map(|(_, p)| p.name.clone()).collect()
+}
+
+/// Bytes free on the disk holding `path`
+pub fn free_space(path: &Path) -> Option<u64> {
+    let out = Command::new("df").arg("-Pk").arg(path).output().ok()?;
+    let text = String::from_utf8_lossy(&out.stdout);
+    let kib: u64 = text.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// "4.2 MB"
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1024 * 1024 => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{} KB", b / 1024),
+        b => format!("{} bytes", b),
+    }
+}
+
+/// Make sure `plan` fits, with room to spare
+pub fn check_space(plan: &[&Package]) -> Result<(), String> {
+    let needed: u64 = plan.iter().map(|p| p.size).sum();
+    let home = app_registry::applications_dir().parent().map(Path::to_path_buf).unwrap_or_default();
+    let Some(free) = free_space(&home) else { return Ok(()) };
+    if needed + SPARE_SPACE > free {
+        return Err(format!("not enough room: needs {}, {} free", format_size(needed), format_size(free.saturating_sub(SPARE_SPACE))));
+    }
+    Ok(())
+}
+
+fn fetch(source: &str, file: &PackageFile, dest: &Path) -> Result<(), String> {
+    let sha256 = file.sha256.as_deref();
+    if !download::is_web(source) && !file.file.contains("://") {
+        if file.file.split('/').any(|part| part == "..") {
+            return Err("the index points outside its folder".into());
+        }
+        let folder = Path::new(source).parent().unwrap_or(Path::new("."));
+        return download::fetch(Origin::File(&folder.join(&file.file)), dest, MAX_FILE, sha256);
+    }
+    download::fetch(Origin::Web(&download::resolve(source, &file.file)), dest, MAX_FILE, sha256)
+}
+
+/// Download one package's files next to where they go, then move them
+/// all into place
+fn install_one(source: &str, package: &Package) -> Result<Vec<PathBuf>, String> {
+    let destinations = package.destinations();
+    let mut parts = Vec::new();
+    let result = package.files.iter().zip(&destinations).try_for_each(|(file, dest)| {
+        let dir = dest.parent().ok_or("nowhere to put it")?;
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        let part = dir.join(format!(".{}.part", file_name(&file.file)));
+        parts.push(part.clone());
+        fetch(source, file, &part).map_err(|e| format!("{}: {}", file_name(&file.file), e))
+    });
+    if let Err(e) = result {
+        for part in &parts {
+            let _ = std::fs::remove_file(part);
+        }
+        return Err(e);
+    }
+    for (part, dest) in parts.iter().zip(&destinations) {
+        if package.kind == Kind::App {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(part, std::fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+        }
+        std::fs::rename(part, dest).map_err(|e| e.to_string())?;
+    }
+    Ok(destinations)
+}
+
+/// Install `plan` in order, registering apps as they go in. Slow: call
+/// off the UI thread.
+pub fn install(source: &str, plan: &[Package]) -> Result<(), String> {
+    for package in plan {
+        let files = install_one(source, package).map_err(|e| format!("{}: {}", package.name, e))?;
+        if package.kind == Kind::App {
+            app_registry::register(InstalledApp {
+                binary: package.id.clone(),
+                name: package.name.clone(),
+                description: package.description.clone(),
+                icon_label: package.icon.clone(),
+                version: package.version.clone(),
+                opens: package.opens.iter().map(|e| e.trim_start_matches('.').to_lowercase()).collect(),
+            })
+            .map_err(|e| e.to_string())?;
+        }
+        let mut installed = installed();
+        // Files the old version had and this one doesn't
+        if let Some(old) = installed.get(&package.id) {
+            for file in old.files.iter().filter(|f| !files.contains(f)) {
+                let _ = std::fs::remove_file(file);
+            }
+        }
+        installed.insert(
+            package.id.clone(),
+            Installed {
+                kind: package.kind,
+                name: package.name.clone(),
+                version: package.version.clone(),
+                depends: package.depends.clone(),
+                files,
+            },
+        );
+        write_json(&installed_path(), &installed);
+    }
+    Ok(())
+}
+
+/// Remove an installed package. Refused while another one needs it.
+pub fn remove(id: &str) -> Result<(), String> {
+    let mut installed = installed();
+    let needed = needed_by(id, &installed);
+    if !needed.is_empty() {
+        return Err(format!("{} needs it", needed.join(", ")));
+    }
+    let Some(package) = installed.remove(id) else { return Ok(()) };
+    for file in &package.files {
+        if let Err(e) = std::fs::remove_file(file) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Err(e.to_string());
+            }
+        }
+    }
+    match package.kind {
+        Kind::App => app_registry::unregister(id).map_err(|e| e.to_string())?,
+        Kind::Font => {
+            // Back to IBM Plex Sans if it was the system font
+            let names: Vec<String> = package.files.iter().filter_map(|f| f.file_name()).map(|n| n.to_string_lossy().to_string()).collect();
+            if theme::preferred_system_font().is_some_and(|f| names.contains(&f)) {
+                theme::set_preferred_system_font(None);
+            }
+        }
+        Kind::Sound => {
+            let _ = std::fs::remove_dir(sound_packs_dir().join(id));
+        }
+    }
+    write_json(&installed_path(), &installed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(id: &str, depends: &[&str]) -> Package {
+        Package {
+            id: id.into(),
+            kind: Kind::Font,
+            name: id.into(),
+            version: "1.0".into(),
+            description: String::new(),
+            size: 0,
+            depends: depends.iter().map(|d| d.to_string()).collect(),
+            opens: Vec::new(),
+            icon: String::new(),
+            files: vec![PackageFile { file: format!("{}.ttf", id), sha256: None }],
+        }
+    }
+
+    #[test]
+    fn test_install_plan() {
+        let index = Index {
+            source: "https://example.org/store/index.json".into(),
+            packages: vec![package("a", &["b", "c"]), package("b", &["c"]), package("c", &[]), package("d", &["missing"])],
+        };
+        let ids = |plan: Vec<&Package>| plan.iter().map(|p| p.id.clone()).collect::<Vec<_>>();
+
+        let mut installed = BTreeMap::new();
+        assert_eq!(ids(install_plan(&index, "a", &installed).unwrap()), ["c", "b", "a"]);
+        assert!(install_plan(&index, "d", &installed).is_err());
+
+        installed.insert("c".to_string(), Installed { kind: Kind::Font, name: "c".into(), version: "1.0".into(), depends: vec![], files: vec![] });
+        installed.insert("b".to_string(), Installed { kind: Kind::Font, name: "b".into(), version: "1.0".into(), depends: vec!["c".into()], files: vec![] });
+        assert_eq!(ids(install_plan(&index, "a", &installed).unwrap()), ["a"]);
+        assert_eq!(needed_by("c", &installed), ["b"]);
+        assert!(needed_by("b", &installed).is_empty());
+
+        assert_eq!(download::resolve(&index.source, "fonts/a.ttf"), "https://example.org/store/fonts/a.ttf");
+        assert!(!package("../x", &[]).is_valid());
+        assert!(package("x", &[]).is_valid());
+
+        // An app has to come with its checksum
+        let mut app = Package { kind: Kind::App, files: vec![PackageFile { file: "x".into(), sha256: None }], ..package("x", &[]) };
+        assert!(!app.is_valid());
+        app.files[0].sha256 = Some("ab12".into());
+        assert!(app.is_valid());
+        assert!(load_index("http://example.org/store/index.json").is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! What each side looks like, and what to do about the differences
+//!
+//! A file is compared by size and modification time. After every run both
+//! listings are kept as the base, so the next run can tell which side
+//! changed: a change on one side is copied over, a change on both sides
+//! makes a conflict copy, and a file missing from one side is only deleted
+//! on the other if it wasn't touched there since.
+
+use serde::{Deserialize, Serialize};
+use slowcore::sync::Folder;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Stat {
+    pub size: u64,
+    /// Unix seconds
+    pub mtime: u64,
+}
+
+/// Files by path relative to the folder, with "/" between parts
+pub type Listing = BTreeMap<String, Stat>;
+
+/// Both sides as they were at the end of the last run
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Base {
+    pub local: Listing,
+    pub remote: Listing,
+}
+
+impl Base {
+    fn path(folder: Folder) -> std::path::PathBuf {
+        slowcore::sync::sync_dir().join(format!("{}.index.json", folder.name()))
+    }
+
+    pub fn load(folder: Folder) -> Self {
+        std::fs::read_to_string(Self::path(folder))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, folder: Folder) {
+        if let Ok(json) = serde_json::to_string(self) {
+            let _ = std::fs::write(Self::path(folder), json);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Push(String),
+    Pull(String),
+    DeleteLocal(String),
+    DeleteRemote(String),
+    /// Changed on both sides; the newer copy keeps the name
+    Conflict { path: String, local_newer: bool },
+}
+
+/// Every regular file under `dir`, skipping hidden files and folders
+pub fn scan_local(dir: &Path) -> Listing {
+    let mut listing = Listing::new();
+    scan_into(dir, "", &mut listing);
+    listing
+}
+
+fn scan_into(dir: &Path, prefix: &str, listing: &mut Listing) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else { continue };
+        let rel = format!("{}{}", prefix, name);
+        if file_type.is_dir() {
+            scan_into(&entry.path(), &format!("{}/", rel), listing);
+        } else if file_type.is_file() {
+            if let Ok(meta) = entry.metadata() {
+                let mtime = meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                listing.insert(rel, Stat { size: meta.len(), mtime });
+            }
+        }
+    }
+}
+
+/// Work out what to copy and delete, given both sides now and at the last run
+pub fn plan(local: &Listing, remote: &Listing, base: &Base) -> Vec<Action> {
+    let paths: BTreeSet<&String> = local.keys().chain(remote.keys()).collect();
+    let mut actions = Vec::new();
+    for path in paths {
+        let l = local.get(path);
+        let r = remote.get(path);
+        let local_changed = l != base.local.get(path);
+        let remote_changed = r != base.remote.get(path);
+        let known = base.local.contains_key(path) || base.remote.contains_key(path);
+        let action = match (l, r) {
+            (Some(l), Some(r)) if !known => {
+                // New on both sides: the same file if it looks the same
+                (l != r).then(|| Action::Conflict { path: path.clone(), local_newer: l.mtime >= r.mtime })
+            }
+            (Some(l), Some(r)) => match (local_changed, remote_changed) {
+                (false, false) => None,
+                (true, false) => Some(Action::Push(path.clone())),
+                (false, true) => Some(Action::Pull(path.clone())),
+                (true, true) => Some(Action::Conflict { path: path.clone(), local_newer: l.mtime >= r.mtime }),
+            },
+            // An edit beats a delete
+            (Some(_), None) if known && !local_changed => Some(Action::DeleteLocal(path.clone())),
+            (Some(_), None) => Some(Action::Push(path.clone())),
+            (None, Some(_)) if known && !remote_changed => Some(Action::DeleteRemote(path.clone())),
+            (None, Some(_)) => Some(Action::Pull(path.clone())),
+            (None, None) => None,
+        };
+        actions.extend(action);
+    }
+    actions
+}
+
+/// "letters/june (conflict 2026-10-16 14.05).txt" for "letters/june.txt"
+pub fn conflict_name(path: &str, when: &str) -> String {
+    let (dir, file) = match path.rsplit_once('/') {
+        Some((dir, file)) => (format!("{}/", dir), file),
+        None => (String::new(), path),
+    };
+    match file.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{}{} (conflict {}).{}", dir, stem, when, ext),
+        _ => format!("{}{} (conflict {})", dir, file, when),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(size: u64, mtime: u64) -> Stat {
+        Stat { size, mtime }
+    }
+
+    #[test]
+    fn test_plan() {
+        let mut base = Base::default();
+        for name in ["same", "edited here", "edited there", "edited both", "gone here", "gone there", "dropped there"] {
+            base.local.insert(name.into(), stat(1, 100));
+            base.remote.insert(name.into(), stat(1, 100));
+        }
+        let mut local = Listing::new();
+        let mut remote = Listing::new();
+        local.insert("same".into(), stat(1, 100));
+        remote.insert("same".into(), stat(1, 100));
+        local.insert("edited here".into(), stat(2, 200));
+        remote.insert("edited here".into(), stat(1, 100));
+        local.insert("edited there".into(), stat(1, 100));
+        remote.insert("edited there".into(), stat(3, 300));
+        local.insert("edited both".into(), stat(2, 200));
+        remote.insert("edited both".into(), stat(3, 300));
+        remote.insert("gone here".into(), stat(1, 100));
+        local.insert("gone there".into(), stat(5, 500));
+        local.insert("dropped there".into(), stat(1, 100));
+        local.insert("new".into(), stat(1, 100));
+
+        let actions = plan(&local, &remote, &base);
+        assert_eq!(
+            actions,
+            vec![
+                Action::DeleteLocal("dropped there".into()),
+                Action::Conflict { path: "edited both".into(), local_newer: false },
+                Action::Push("edited here".into()),
+                Action::Pull("edited there".into()),
+                Action::DeleteRemote("gone here".into()),
+                Action::Push("gone there".into()),
+                Action::Push("new".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conflict_name() {
+        assert_eq!(conflict_name("letters/june.txt", "2026-10-16 14.05"), "letters/june (conflict 2026-10-16 14.05).txt");
+        assert_eq!(conflict_name("README", "x"), "README (conflict x)");
+        assert_eq!(conflict_name(".hidden", "x"), ".hidden (conflict x)");
+    }
+}
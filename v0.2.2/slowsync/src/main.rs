@@ -0,0 +1,138 @@
+//! slowSync - Background sync of Documents and notes for the Slow Computer
+//!
+//! Started by slowDesktop and runs without a window. Every few minutes, or
+//! when settings asks, it brings each enabled folder in step with the copy
+//! on another machine and shows how that went in the menu bar.
+
+mod index;
+mod remote;
+
+use index::{Action, Base};
+use remote::Remote;
+use slowcore::menu_extra::MenuExtra;
+use slowcore::sync::{Folder, SyncConfig, SyncState, SyncStatus};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How often the config and "sync now" requests are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// After a failed run, try again this soon instead of the full interval
+const RETRY_AFTER: Duration = Duration::from_secs(120);
+
+fn main() {
+    let mut last_config: Option<SyncConfig> = None;
+    let mut next_run = Instant::now();
+    loop {
+        let config = SyncConfig::load();
+        let requested = slowcore::sync::take_sync_request();
+
+        if !config.is_active() {
+            if last_config.as_ref() != Some(&config) {
+                SyncStatus::default().save();
+                slowcore::menu_extra::clear("slowsync");
+            }
+        } else if requested || Instant::now() >= next_run || last_config.as_ref() != Some(&config) {
+            let ok = run(&config);
+            let wait = if ok { Duration::from_secs(config.interval_mins.max(1) as u64 * 60) } else { RETRY_AFTER };
+            next_run = Instant::now() + wait;
+        }
+        last_config = Some(config);
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// One run over every enabled folder; false if it failed
+fn run(config: &SyncConfig) -> bool {
+    let mut status = SyncStatus::load();
+    status.state = SyncState::Syncing;
+    status.save();
+    MenuExtra::label("slowsync", "syncing").publish();
+
+    let result = Remote::parse(&config.remote).and_then(|remote| {
+        let mut conflicts = 0;
+        for &folder in &config.folders {
+            conflicts += sync_folder(&remote, folder)
+                .map_err(|e| format!("{}: {}", folder.name(), e))?;
+        }
+        Ok(conflicts)
+    });
+
+    let ok = result.is_ok();
+    match result {
+        Ok(conflicts) => {
+            status.state = SyncState::Idle;
+            status.last_sync = Some(unix_now());
+            status.conflicts = conflicts;
+            status.message = match conflicts {
+                0 => "in step".to_string(),
+                1 => "made 1 conflict copy".to_string(),
+                n => format!("made {} conflict copies", n),
+            };
+            if conflicts > 0 {
+                slowcore::notify::post("slowsync", &format!("sync {}", status.message));
+            }
+            MenuExtra::label("slowsync", "synced").publish();
+        }
+        Err(e) => {
+            status.state = SyncState::Error;
+            status.message = e;
+            MenuExtra::label("slowsync", "sync failed").publish();
+        }
+    }
+    status.save();
+    ok
+}
+
+/// Bring one folder in step, returning how many conflict copies were made
+fn sync_folder(remote: &Remote, folder: Folder) -> Result<usize, String> {
+    let local_dir = folder.local_path();
+    std::fs::create_dir_all(&local_dir).map_err(|e| e.to_string())?;
+
+    let local = index::scan_local(&local_dir);
+    let listing = remote.list(folder)?;
+    let actions = index::plan(&local, &listing, &Base::load(folder));
+
+    let when = chrono::Local::now().format("%Y-%m-%d %H.%M").to_string();
+    let mut push = Vec::new();
+    let mut pull = Vec::new();
+    let mut delete_remote = Vec::new();
+    let mut conflicts = 0;
+    for action in actions {
+        match action {
+            Action::Push(path) => push.push(path),
+            Action::Pull(path) => pull.push(path),
+            Action::DeleteRemote(path) => delete_remote.push(path),
+            Action::DeleteLocal(path) => {
+                // Into the trash, in case the other side got it wrong
+                let _ = trash::move_to_trash(&local_dir.join(&path));
+            }
+            Action::Conflict { path, local_newer } => {
+                let copy = index::conflict_name(&path, &when);
+                if local_newer {
+                    // Theirs becomes the copy
+                    remote.pull_as(folder, &path, &local_dir.join(&copy))?;
+                    push.push(path);
+                } else {
+                    // Ours becomes the copy
+                    std::fs::rename(local_dir.join(&path), local_dir.join(&copy)).map_err(|e| e.to_string())?;
+                    pull.push(path);
+                }
+                push.push(copy);
+                conflicts += 1;
+            }
+        }
+    }
+
+    remote.pull(folder, &pull, &local_dir)?;
+    remote.push(folder, &push, &local_dir)?;
+    remote.delete(folder, &delete_remote)?;
+
+    // Both sides as they are now are the base for next time
+    let base = Base { local: index::scan_local(&local_dir), remote: remote.list(folder)? };
+    base.save(folder);
+    Ok(conflicts)
+}
@@ -0,0 +1,177 @@
+//! The other copy, reached with ssh and rsync
+//!
+//! Each synced folder is a directory of the same name under the remote
+//! path ("me@server:slowbook" keeps documents in ~/slowbook/documents).
+//! ssh runs in batch mode, so a key has to be set up beforehand; a password
+//! prompt would just hang a background service.
+
+use crate::index::{Listing, Stat};
+use slowcore::sync::Folder;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const SSH: &str = "ssh -o BatchMode=yes -o ConnectTimeout=15";
+
+pub struct Remote {
+    /// "user@host"
+    host: String,
+    /// Base directory on the host; relative paths start at the home folder
+    path: String,
+}
+
+impl Remote {
+    /// Parse "user@host:path". A user or host starting with '-' is
+    /// refused, as ssh would take it for an option.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        match spec.split_once(':') {
+            Some((host, path))
+                if !host.is_empty()
+                    && !host.contains(['/', ' '])
+                    && !host.split('@').any(|part| part.is_empty() || part.starts_with('-')) =>
+            {
+                Ok(Self { host: host.to_string(), path: path.trim_end_matches('/').to_string() })
+            }
+            _ => Err(format!("\u{201c}{}\u{201d} should look like user@host:folder", spec)),
+        }
+    }
+
+    /// The folder's directory on the host
+    fn dir(&self, folder: Folder) -> String {
+        if self.path.is_empty() {
+            folder.name().to_string()
+        } else {
+            format!("{}/{}", self.path, folder.name())
+        }
+    }
+
+    fn ssh(&self, script: &str) -> Command {
+        let mut cmd = Command::new("ssh");
+        cmd.args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=15", "--", &self.host, script]);
+        cmd
+    }
+
+    fn rsync() -> Command {
+        let mut cmd = Command::new("rsync");
+        // --protect-args keeps spaces in remote names from the remote shell
+        cmd.args(["-a", "--protect-args", "-e", SSH]);
+        cmd
+    }
+
+    /// Every file in the folder on the host, creating the folder if needed
+    pub fn list(&self, folder: Folder) -> Result<Listing, String> {
+        let dir = quote(&self.dir(folder));
+        let script = format!(
+            "mkdir -p {dir} && cd {dir} && find . -type f ! -path '*/.*' -printf '%P\\0%s\\0%T@\\0'",
+            dir = dir
+        );
+        let out = run(self.ssh(&script), None)?;
+        let mut listing = Listing::new();
+        let mut fields = out.split(|&b| b == 0);
+        while let (Some(path), Some(size), Some(mtime)) = (fields.next(), fields.next(), fields.next()) {
+            let path = String::from_utf8_lossy(path).to_string();
+            let size = String::from_utf8_lossy(size).parse().unwrap_or(0);
+            let mtime = String::from_utf8_lossy(mtime).parse::<f64>().unwrap_or(0.0) as u64;
+            listing.insert(path, Stat { size, mtime });
+        }
+        Ok(listing)
+    }
+
+    /// Copy `files` from the host into `local`
+    pub fn pull(&self, folder: Folder, files: &[String], local: &Path) -> Result<(), String> {
+        if files.is_empty() {
+            return Ok(());
+        }
+        let mut cmd = Self::rsync();
+        cmd.args(["--from0", "--files-from=-", "--"])
+            .arg(format!("{}:{}/", self.host, self.dir(folder)))
+            .arg(with_slash(local));
+        run(cmd, Some(&file_list(files))).map(|_| ())
+    }
+
+    /// Copy `files` from `local` to the host
+    pub fn push(&self, folder: Folder, files: &[String], local: &Path) -> Result<(), String> {
+        if files.is_empty() {
+            return Ok(());
+        }
+        let mut cmd = Self::rsync();
+        cmd.args(["--from0", "--files-from=-", "--"])
+            .arg(with_slash(local))
+            .arg(format!("{}:{}/", self.host, self.dir(folder)));
+        run(cmd, Some(&file_list(files))).map(|_| ())
+    }
+
+    /// Copy one file from the host to `dest`, under whatever name that has
+    pub fn pull_as(&self, folder: Folder, file: &str, dest: &Path) -> Result<(), String> {
+        let mut cmd = Self::rsync();
+        cmd.arg("--").arg(format!("{}:{}/{}", self.host, self.dir(folder), file)).arg(dest);
+        run(cmd, None).map(|_| ())
+    }
+
+    pub fn delete(&self, folder: Folder, files: &[String]) -> Result<(), String> {
+        if files.is_empty() {
+            return Ok(());
+        }
+        let script = format!("cd {} && xargs -0 rm -f --", quote(&self.dir(folder)));
+        run(self.ssh(&script), Some(&file_list(files))).map(|_| ())
+    }
+}
+
+/// Quote for a POSIX shell
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// rsync copies a directory's contents only when it ends in a slash
+fn with_slash(dir: &Path) -> String {
+    format!("{}/", dir.to_string_lossy().trim_end_matches('/'))
+}
+
+fn file_list(files: &[String]) -> Vec<u8> {
+    let mut list = Vec::new();
+    for file in files {
+        list.extend_from_slice(file.as_bytes());
+        list.push(0);
+    }
+    list
+}
+
+/// Run a command, feeding it `input`, and return what it printed
+fn run(mut cmd: Command, input: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    cmd.stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = cmd.spawn().map_err(|e| format!("can't run {}: {}", program, e))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input).map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("failed");
+        Err(format!("{}: {}", program, last.trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_remote() {
+        let remote = Remote::parse("me@server:slowbook/").unwrap();
+        assert_eq!(remote.host, "me@server");
+        assert_eq!(remote.dir(Folder::Documents), "slowbook/documents");
+        assert_eq!(Remote::parse("server:").unwrap().dir(Folder::Notes), "notes");
+        assert!(Remote::parse("/home/me").is_err());
+        assert!(Remote::parse("-oProxyCommand=sh:x").is_err());
+        assert!(Remote::parse("-l@server:x").is_err());
+        assert!(Remote::parse("me@-oProxyCommand=sh:x").is_err());
+        assert!(Remote::parse("me@:x").is_err());
+        assert_eq!(quote("it's"), "'it'\\''s'");
+    }
+}
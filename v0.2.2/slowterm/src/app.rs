@@ -8,7 +8,7 @@ use egui::{Context, FontFamily, FontId, Key, Pos2, Rect, Sense, Stroke};
 use slowcore::repaint::RepaintController;
 use slowcore::safety::snap_to_char_boundary;
 use slowcore::theme::SlowColors;
-use slowcore::widgets::{window_control_buttons, WindowAction};
+use slowcore::widgets::{about_dialog, window_control_buttons, AboutInfo, WindowAction};
 use std::env;
 use std::io::Read;
 use std::path::PathBuf;
@@ -16,6 +16,15 @@ use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex};
 use std::thread;
 
+const ABOUT: AboutInfo = AboutInfo {
+    name: "terminal",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "terminal emulator for slowOS",
+    features: &["shell command execution", "command history, autocomplete", "Ctrl+C interrupt support"],
+    credits: &["egui/eframe (MIT)"],
+    ..AboutInfo::EMPTY
+};
+
 /// A single line in the terminal output
 #[derive(Clone, Debug)]
 struct TermLine {
@@ -579,7 +588,7 @@ impl eframe::App for SlowTermApp {
         self.poll_output();
 
         // Enable continuous repaint while terminal command is running
-        self.repaint.set_continuous(self.running);
+        self.repaint.set_background(self.running);
 
         let font = FontId::new(self.font_size, FontFamily::Monospace);
         let line_height = self.font_size * 1.4;
@@ -748,39 +757,8 @@ impl eframe::App for SlowTermApp {
             });
 
         // About dialog
-        if self.show_about {
-            let screen = ctx.screen_rect();
-            let max_h = (screen.height() - 60.0).max(120.0);
-            let resp = egui::Window::new("about terminal")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(300.0)
-                .max_height(max_h)
-                .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                        ui.vertical_centered(|ui| {
-                            ui.heading("terminal");
-                            ui.label("version 0.2.2");
-                            ui.add_space(8.0);
-                            ui.label("terminal emulator for slowOS");
-                        });
-                        ui.add_space(8.0);
-                        ui.separator();
-                        ui.add_space(4.0);
-                        ui.label("features:");
-                        ui.label("  shell command execution");
-                        ui.label("  command history, autocomplete");
-                        ui.label("  Ctrl+C interrupt support");
-                        ui.add_space(4.0);
-                        ui.label("frameworks:");
-                        ui.label("  egui/eframe (MIT)");
-                        ui.add_space(8.0);
-                        ui.vertical_centered(|ui| {
-                            if ui.button("ok").clicked() { self.show_about = false; }
-                        });
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
         self.repaint.end_frame(ctx);
     }
@@ -4,11 +4,11 @@ mod app;
 
 use app::SlowTermApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([520.0, 380.0])
+        viewport: window_layout::viewport("slowterm", Placement::RememberLast, [520.0, 380.0])
             .with_title("terminal"),
         ..Default::default()
     };
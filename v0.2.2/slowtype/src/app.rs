@@ -0,0 +1,453 @@
+//! slowType application
+
+use crate::lessons::{self, KeyStat, Progress, LESSONS, PASS_ACCURACY};
+use crate::practice::{self, Source};
+use crate::session::Session;
+use egui::{Align2, CentralPanel, Context, FontId, Key, Pos2, Rect, Sense, Stroke, TopBottomPanel, Vec2};
+use rand::seq::SliceRandom;
+use slowcore::repaint::RepaintController;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, WindowAction};
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowType",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "typing tutor for slowOS",
+    features: &[
+        "lessons, a few keys at a time",
+        "live words per minute and accuracy",
+        "heatmap of missed keys",
+        "practice on your documents and books",
+    ],
+    ..AboutInfo::EMPTY
+};
+
+/// How often the live numbers move while typing
+const STATS_INTERVAL: Duration = Duration::from_millis(500);
+
+const TEXT_SIZE: f32 = 20.0;
+const LINE_SPACING: f32 = 1.5;
+
+/// Keyboard rows for the heatmap, and how far each is set in, in keys
+const KEY_ROWS: [(&str, f32); 4] = [("1234567890-", 0.0), ("qwertyuiop", 0.5), ("asdfghjkl;'", 0.75), ("zxcvbnm,./", 1.25)];
+
+/// The unshifted key a character is typed with
+fn base_key(c: char) -> char {
+    match c {
+        '!' => '1',
+        '(' => '9',
+        ')' => '0',
+        '_' => '-',
+        ':' => ';',
+        '"' => '\'',
+        '?' => '/',
+        '<' => ',',
+        '>' => '.',
+        c => c.to_ascii_lowercase(),
+    }
+}
+
+enum Mode {
+    Lesson(usize),
+    Practice(Source),
+}
+
+pub struct SlowTypeApp {
+    mode: Mode,
+    session: Session,
+    progress: Progress,
+    sources: Vec<Source>,
+    /// A passage being read from a document or book
+    loading: Option<Receiver<Result<String, String>>>,
+    /// What to say when a run is over
+    outcome: Option<String>,
+    show_heatmap: bool,
+    message: Option<String>,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+impl SlowTypeApp {
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        let progress = Progress::load();
+        let lesson = progress.passed.min(LESSONS.len() - 1);
+        Self {
+            mode: Mode::Lesson(lesson),
+            session: Session::new(&lessons::drill(lesson, &mut rand::thread_rng())),
+            progress,
+            sources: practice::sources(),
+            loading: None,
+            outcome: None,
+            show_heatmap: true,
+            message: None,
+            show_about: false,
+            repaint: RepaintController::new(),
+        }
+    }
+
+    fn start_lesson(&mut self, lesson: usize) {
+        self.mode = Mode::Lesson(lesson);
+        self.session = Session::new(&lessons::drill(lesson, &mut rand::thread_rng()));
+        self.outcome = None;
+        self.message = None;
+    }
+
+    /// Read a passage from `source`, or from any source, on a thread
+    fn start_practice(&mut self, source: Option<Source>) {
+        let source = match source {
+            Some(source) => source,
+            None => {
+                self.sources = practice::sources();
+                match self.sources.choose(&mut rand::thread_rng()) {
+                    Some(source) => source.clone(),
+                    None => {
+                        self.message = Some("no documents or books to practise on".into());
+                        return;
+                    }
+                }
+            }
+        };
+        self.mode = Mode::Practice(source.clone());
+        self.session = Session::new("");
+        self.outcome = None;
+        self.message = Some("reading…".into());
+        let (sender, receiver) = mpsc::channel();
+        let _ = std::thread::Builder::new().name("slowtype-passage".into()).spawn(move || {
+            let _ = sender.send(practice::passage(&source, &mut rand::thread_rng()));
+        });
+        self.loading = Some(receiver);
+    }
+
+    fn check_loaded(&mut self) {
+        let Some(loading) = &self.loading else { return };
+        let Ok(result) = loading.try_recv() else { return };
+        self.loading = None;
+        match result {
+            Ok(text) => {
+                self.session = Session::new(&text);
+                self.message = None;
+            }
+            Err(e) => self.message = Some(format!("could not read it: {}", e)),
+        }
+    }
+
+    /// The same again: a fresh drill, or another passage from the same place
+    fn next_run(&mut self, advance: bool) {
+        match &self.mode {
+            Mode::Lesson(lesson) => {
+                let next = if advance && self.progress.is_open(lesson + 1) && lesson + 1 < LESSONS.len() { lesson + 1 } else { *lesson };
+                self.start_lesson(next);
+            }
+            Mode::Practice(source) => self.start_practice(Some(source.clone())),
+        }
+    }
+
+    fn finish(&mut self) {
+        let (wpm, accuracy) = (self.session.wpm(), self.session.accuracy());
+        self.progress.add_keys(self.session.keys());
+        let mut outcome = format!("{:.0} wpm, {:.0}% accuracy", wpm, accuracy);
+        if let Mode::Lesson(lesson) = self.mode {
+            if self.progress.finish_lesson(lesson, wpm, accuracy) {
+                outcome.push_str(&format!(" — passed! \"{}\" is open", LESSONS[lesson + 1].name));
+            } else if accuracy >= PASS_ACCURACY {
+                outcome.push_str(" — passed");
+            } else {
+                outcome.push_str(&format!(" — {:.0}% passes", PASS_ACCURACY));
+            }
+        }
+        self.progress.save();
+        self.outcome = Some(outcome);
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        if self.show_about || self.loading.is_some() {
+            return;
+        }
+        if self.session.is_finished() {
+            if ctx.input(|i| i.key_pressed(Key::Enter)) {
+                self.next_run(true);
+            }
+            return;
+        }
+        let events = ctx.input(|i| i.events.clone());
+        for event in events {
+            match event {
+                egui::Event::Text(text) => {
+                    for c in text.chars() {
+                        self.session.type_char(c);
+                    }
+                }
+                egui::Event::Key { key: Key::Backspace, pressed: true, .. } => self.session.backspace(),
+                _ => {}
+            }
+        }
+        if self.session.is_finished() {
+            self.finish();
+        }
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("lessons", |ui| {
+                    for (i, lesson) in LESSONS.iter().enumerate() {
+                        let mark = if i < self.progress.passed { "✓" } else { " " };
+                        let label = format!("{} {:>2}  {}", mark, i + 1, lesson.name);
+                        let current = matches!(self.mode, Mode::Lesson(l) if l == i);
+                        if ui.add_enabled(self.progress.is_open(i), egui::SelectableLabel::new(current, label)).clicked() {
+                            self.start_lesson(i);
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.menu_button("practice", |ui| {
+                    if ui.button("random passage").clicked() {
+                        self.start_practice(None);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if self.sources.is_empty() {
+                        ui.weak("no .txt or .md documents and no books");
+                    }
+                    let mut picked = None;
+                    egui::ScrollArea::vertical().max_height(280.0).show(ui, |ui| {
+                        for source in &self.sources {
+                            if ui.button(practice::describe(source)).clicked() {
+                                picked = Some(source.clone());
+                            }
+                        }
+                    });
+                    if let Some(source) = picked {
+                        self.start_practice(Some(source));
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("look again").clicked() {
+                        self.sources = practice::sources();
+                    }
+                });
+                ui.menu_button("view", |ui| {
+                    if ui.checkbox(&mut self.show_heatmap, "key heatmap").clicked() {
+                        ui.close_menu();
+                    }
+                    if ui.button("forget missed keys").clicked() {
+                        self.progress.keys.clear();
+                        self.progress.save();
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowtype", "slowType");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_stats(&self, ui: &mut egui::Ui) {
+        let secs = self.session.elapsed().as_secs();
+        ui.horizontal(|ui| {
+            for (label, value) in [
+                ("wpm", format!("{:.0}", self.session.wpm())),
+                ("accuracy", format!("{:.0}%", self.session.accuracy())),
+                ("time", format!("{}:{:02}", secs / 60, secs % 60)),
+            ] {
+                ui.label(egui::RichText::new(value).size(22.0).color(SlowColors::BLACK));
+                ui.weak(label);
+                ui.add_space(16.0);
+            }
+        });
+    }
+
+    /// The text, wrapped at words, with the next key underlined and
+    /// mistakes shown white on black
+    fn draw_text(&self, ui: &mut egui::Ui) {
+        let font = FontId::monospace(TEXT_SIZE);
+        let char_width = ui.fonts(|f| f.glyph_width(&font, 'M'));
+        let line_height = TEXT_SIZE * LINE_SPACING;
+        let columns = ((ui.available_width() / char_width) as usize).max(8);
+        let text = self.session.text();
+
+        // Break after the last space that fits
+        let mut lines = Vec::new();
+        let mut start = 0;
+        while start < text.len() {
+            let mut end = (start + columns).min(text.len());
+            if end < text.len() {
+                if let Some(space) = text[start..end].iter().rposition(|&c| c == ' ') {
+                    end = start + space + 1;
+                }
+            }
+            lines.push(start..end);
+            start = end;
+        }
+
+        let size = Vec2::new(ui.available_width(), line_height * lines.len().max(1) as f32);
+        let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+        let painter = ui.painter_at(rect);
+        let position = self.session.position();
+        for (row, line) in lines.into_iter().enumerate() {
+            for (column, i) in line.enumerate() {
+                let cell = Rect::from_min_size(
+                    Pos2::new(rect.left() + column as f32 * char_width, rect.top() + row as f32 * line_height),
+                    Vec2::new(char_width, line_height),
+                );
+                let mut color = SlowColors::BLACK;
+                if self.session.is_wrong(i) {
+                    painter.rect_filled(cell.shrink2(Vec2::new(0.0, 2.0)), 0.0, SlowColors::BLACK);
+                    color = SlowColors::WHITE;
+                }
+                if i == position {
+                    let y = cell.bottom() - 3.0;
+                    painter.line_segment([Pos2::new(cell.left(), y), Pos2::new(cell.right(), y)], Stroke::new(2.0, SlowColors::BLACK));
+                }
+                painter.text(cell.center(), Align2::CENTER_CENTER, text[i], font.clone(), color);
+            }
+        }
+    }
+
+    /// The keyboard, each key dithered darker the more often it was missed
+    fn draw_heatmap(&self, ui: &mut egui::Ui) {
+        let mut keys: BTreeMap<char, KeyStat> = BTreeMap::new();
+        for (c, stat) in &self.progress.keys {
+            let total = keys.entry(base_key(*c)).or_default();
+            total.typed += stat.typed;
+            total.missed += stat.missed;
+        }
+        let key_size = (ui.available_width() / 12.0).min(32.0);
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(key_size * 12.0, key_size * 5.0), Sense::hover());
+        let painter = ui.painter_at(rect);
+        let draw_key = |key: Rect, c: char, label: &str| {
+            let stat = keys.get(&c).copied().unwrap_or_default();
+            let rate = stat.error_rate();
+            let mut text_color = SlowColors::BLACK;
+            match rate {
+                r if r >= 0.3 => {
+                    painter.rect_filled(key, 0.0, SlowColors::BLACK);
+                    text_color = SlowColors::WHITE;
+                }
+                r if r >= 0.15 => slowcore::dither::draw_dither_rect(&painter, key, SlowColors::BLACK, 1),
+                r if r >= 0.05 => slowcore::dither::draw_dither_rect(&painter, key, SlowColors::BLACK, 2),
+                r if r > 0.0 => slowcore::dither::draw_dither_rect(&painter, key, SlowColors::BLACK, 3),
+                _ => {}
+            }
+            painter.rect_stroke(key, 2.0, Stroke::new(1.0, SlowColors::BLACK));
+            painter.text(key.center(), Align2::CENTER_CENTER, label, FontId::monospace(12.0), text_color);
+            if stat.typed > 0 {
+                ui.interact(key, ui.id().with(("key", c)), Sense::hover())
+                    .on_hover_text(format!("{}: {} of {} missed", label, stat.missed, stat.typed));
+            }
+        };
+        for (row, (chars, indent)) in KEY_ROWS.iter().enumerate() {
+            for (column, c) in chars.chars().enumerate() {
+                let min = rect.min + Vec2::new((indent + column as f32) * key_size, row as f32 * key_size);
+                draw_key(Rect::from_min_size(min, Vec2::splat(key_size)).shrink(2.0), c, &c.to_string());
+            }
+        }
+        let space = Rect::from_min_size(rect.min + Vec2::new(3.0 * key_size, 4.0 * key_size), Vec2::new(6.0 * key_size, key_size));
+        draw_key(space.shrink(2.0), ' ', "space");
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+impl eframe::App for SlowTypeApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowtype") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.check_loaded();
+        self.handle_keys(ctx);
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = self.message.clone().unwrap_or_else(|| {
+                let passed = format!("{} of {} lessons passed", self.progress.passed, LESSONS.len());
+                match &self.mode {
+                    Mode::Lesson(lesson) => match self.progress.best_wpm.get(lesson) {
+                        Some(best) => format!("{}  |  best here {:.0} wpm", passed, best),
+                        None => passed,
+                    },
+                    Mode::Practice(_) => format!("{}  |  {} texts to practise on", passed, self.sources.len()),
+                }
+            });
+            status_bar(ui, &status);
+        });
+
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(16.0)))
+            .show(ctx, |ui| {
+                let title = match &self.mode {
+                    Mode::Lesson(lesson) => format!("lesson {}: {}", lesson + 1, LESSONS[*lesson].name),
+                    Mode::Practice(source) => format!("practice: {}", practice::describe(source)),
+                };
+                ui.label(egui::RichText::new(title).strong());
+                if let Mode::Lesson(lesson) = self.mode {
+                    let keys: String = LESSONS[lesson].keys.chars().map(|c| format!("{} ", c)).collect();
+                    ui.weak(format!("new keys: {}", keys.trim_end()));
+                }
+                ui.add_space(8.0);
+                self.draw_stats(ui);
+                ui.add_space(12.0);
+                self.draw_text(ui);
+                ui.add_space(12.0);
+
+                if let Some(outcome) = self.outcome.clone() {
+                    ui.label(egui::RichText::new(outcome).strong());
+                    ui.horizontal(|ui| {
+                        if ui.button("again").clicked() {
+                            self.next_run(false);
+                        }
+                        let next = match self.mode {
+                            Mode::Lesson(lesson) => self.progress.is_open(lesson + 1) && lesson + 1 < LESSONS.len(),
+                            Mode::Practice(_) => false,
+                        };
+                        if next && ui.button("next lesson").clicked() {
+                            self.next_run(true);
+                        }
+                    });
+                    ui.weak("press return to go on");
+                } else if self.loading.is_none() && !self.session.is_started() {
+                    ui.weak("start typing; the clock starts at the first key");
+                }
+
+                if self.show_heatmap {
+                    ui.add_space(16.0);
+                    ui.weak("missed keys");
+                    ui.add_space(4.0);
+                    self.draw_heatmap(ui);
+                }
+            });
+
+        self.draw_about(ctx);
+
+        if self.loading.is_some() || (self.session.is_started() && !self.session.is_finished()) {
+            ctx.request_repaint_after(STATS_INTERVAL);
+        }
+        self.repaint.end_frame(ctx);
+    }
+}
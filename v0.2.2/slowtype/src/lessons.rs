@@ -0,0 +1,204 @@
+//! Lessons and progress
+//!
+//! Each lesson adds a few keys to the ones learned before. Its drill is
+//! made of common words that use only known keys, leaning on the new ones,
+//! with letter groups standing in while there are too few words. Passing a
+//! lesson at PASS_ACCURACY opens the next. Progress and every key's misses
+//! are kept in ~/.config/slowtype/progress.json.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use slowcore::storage::config_dir;
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+/// Accuracy needed to pass a lesson, in percent
+pub const PASS_ACCURACY: f32 = 95.0;
+
+/// Words in one drill
+const DRILL_WORDS: usize = 24;
+
+/// With fewer known words than this, letter groups fill in
+const MIN_WORDS: usize = 6;
+
+pub struct Lesson {
+    pub name: &'static str,
+    /// The keys this lesson adds
+    pub keys: &'static str,
+}
+
+pub const LESSONS: &[Lesson] = &[
+    Lesson { name: "home row", keys: "asdfjkl;" },
+    Lesson { name: "g and h", keys: "gh" },
+    Lesson { name: "e and i", keys: "ei" },
+    Lesson { name: "r and u", keys: "ru" },
+    Lesson { name: "t and o", keys: "to" },
+    Lesson { name: "top row", keys: "qwyp" },
+    Lesson { name: "n and m", keys: "nm" },
+    Lesson { name: "c and comma", keys: "c," },
+    Lesson { name: "bottom row", keys: "zxvb./" },
+    Lesson { name: "capitals", keys: "ABCDEFGHIJKLMNOPQRSTUVWXYZ" },
+    Lesson { name: "numbers", keys: "1234567890" },
+    Lesson { name: "punctuation", keys: "'-?!:" },
+];
+
+/// Common words the drills are drawn from
+const WORDS: &str = "a as ask all add sad dad fall lass flask salsa alfalfa \
+    had has hall half glad flag gas shall dash lash gash \
+    he she see seed feel field life like side idea said shed heel jade \
+    are our rule sure ride red read rush user rug huge fur drug sugar \
+    the to too that this there other those their three start stood hot \
+    first after often story sort short forest \
+    quiet quite query we way what when where who why write word work worry \
+    play party happy type key yes you your people power paper \
+    and not no new now name man many most more mind moment mother \
+    can come could cold each much such since once place face \
+    zone size box next very over every above below back both black \
+    about because before between never number question world water year \
+    house small great little right thing think through while again";
+
+fn letters(lesson: usize) -> HashSet<char> {
+    LESSONS[..=lesson.min(LESSONS.len() - 1)].iter().flat_map(|l| l.keys.chars()).collect()
+}
+
+/// A fresh drill for lesson `lesson`
+pub fn drill(lesson: usize, rng: &mut impl Rng) -> String {
+    let known = letters(lesson);
+    let new: Vec<char> = LESSONS[lesson.min(LESSONS.len() - 1)].keys.chars().collect();
+    let words: Vec<&str> = WORDS.split_whitespace().filter(|w| w.chars().all(|c| known.contains(&c))).collect();
+    let fresh: Vec<&str> = words.iter().copied().filter(|w| w.chars().any(|c| new.contains(&c))).collect();
+    let lower: Vec<char> = known.iter().copied().filter(|c| !c.is_ascii_uppercase() && !c.is_ascii_digit() && !"'-?!:".contains(*c)).collect();
+    let digits: Vec<char> = known.iter().copied().filter(|c| c.is_ascii_digit()).collect();
+    let marks: Vec<char> = known.iter().copied().filter(|c| "'-?!:".contains(*c)).collect();
+    let capitals = known.contains(&'A');
+
+    let mut tokens = Vec::with_capacity(DRILL_WORDS);
+    for _ in 0..DRILL_WORDS {
+        let mut token = if !digits.is_empty() && rng.gen_ratio(1, 5) {
+            (0..rng.gen_range(1..=4)).map(|_| *digits.choose(rng).unwrap_or(&'0')).collect()
+        } else if words.len() < MIN_WORDS {
+            letter_group(&lower, &new, rng)
+        } else if !fresh.is_empty() && rng.gen_bool(0.5) {
+            fresh.choose(rng).map(|w| w.to_string()).unwrap_or_default()
+        } else {
+            words.choose(rng).map(|w| w.to_string()).unwrap_or_default()
+        };
+        if capitals && rng.gen_ratio(1, 4) {
+            let mut chars = token.chars();
+            token = chars.next().map(|c| c.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default();
+        }
+        if let Some(mark) = marks.choose(rng).filter(|_| rng.gen_ratio(1, 5)) {
+            token.push(*mark);
+        }
+        tokens.push(token);
+    }
+    tokens.join(" ")
+}
+
+/// Two to five known letters, at least one of them new
+fn letter_group(known: &[char], new: &[char], rng: &mut impl Rng) -> String {
+    let len = rng.gen_range(2..=5);
+    let must = rng.gen_range(0..len);
+    (0..len)
+        .filter_map(|i| if i == must { new.choose(rng) } else { known.choose(rng) })
+        .collect()
+}
+
+/// Times a key was typed and missed
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct KeyStat {
+    pub typed: u32,
+    pub missed: u32,
+}
+
+impl KeyStat {
+    /// Misses as a fraction of tries
+    pub fn error_rate(&self) -> f32 {
+        if self.typed == 0 { 0.0 } else { self.missed as f32 / self.typed as f32 }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Progress {
+    /// Lessons passed, in order; the one after is the furthest open
+    pub passed: usize,
+    /// Best words per minute in each lesson passed or tried
+    #[serde(default)]
+    pub best_wpm: BTreeMap<usize, f32>,
+    /// Every key ever asked for, by its lowercase character
+    #[serde(default)]
+    pub keys: BTreeMap<char, KeyStat>,
+}
+
+fn progress_path() -> PathBuf {
+    config_dir("slowtype").join("progress.json")
+}
+
+impl Progress {
+    pub fn load() -> Self {
+        std::fs::read_to_string(progress_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = progress_path();
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    pub fn is_open(&self, lesson: usize) -> bool {
+        lesson <= self.passed
+    }
+
+    /// Note a finished lesson; true if it opened the next one
+    pub fn finish_lesson(&mut self, lesson: usize, wpm: f32, accuracy: f32) -> bool {
+        let best = self.best_wpm.entry(lesson).or_insert(0.0);
+        *best = best.max(wpm);
+        let opened = accuracy >= PASS_ACCURACY && lesson == self.passed && lesson + 1 < LESSONS.len();
+        if accuracy >= PASS_ACCURACY {
+            self.passed = self.passed.max(lesson + 1);
+        }
+        opened
+    }
+
+    pub fn add_keys(&mut self, keys: &BTreeMap<char, KeyStat>) {
+        for (key, stat) in keys {
+            let total = self.keys.entry(*key).or_default();
+            total.typed += stat.typed;
+            total.missed += stat.missed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_drill_uses_known_keys() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let home = drill(0, &mut rng);
+        assert!(home.chars().all(|c| c == ' ' || "asdfjkl;".contains(c)), "{}", home);
+        assert_eq!(home.split(' ').count(), DRILL_WORDS);
+
+        let gh = drill(1, &mut rng);
+        assert!(gh.chars().any(|c| c == 'g' || c == 'h'), "{}", gh);
+        assert!(gh.chars().all(|c| c == ' ' || "asdfjkl;gh".contains(c)), "{}", gh);
+
+        let mut progress = Progress::default();
+        assert!(!progress.finish_lesson(0, 20.0, 80.0));
+        assert!(!progress.is_open(1));
+        assert!(progress.finish_lesson(0, 18.0, 97.0));
+        assert!(progress.is_open(1));
+        assert_eq!(progress.best_wpm[&0], 20.0);
+    }
+}
@@ -0,0 +1,33 @@
+//! slowType - A typing tutor for the Slow Computer
+//!
+//! Touch-typing lessons that add a few keys at a time, live words per
+//! minute and accuracy, a heatmap of the keys most often missed, and
+//! practice on passages from the user's own documents and books.
+
+mod app;
+mod lessons;
+mod practice;
+mod session;
+
+use app::SlowTypeApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let viewport = window_layout::viewport("slowtype", Placement::RememberLast, [640.0, 540.0])
+        .with_title("slowType");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowType",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowTypeApp::new(cc))
+        }),
+    )
+}
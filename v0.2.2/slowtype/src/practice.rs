@@ -0,0 +1,202 @@
+//! Practice text from the user's own documents and slowReader books
+//!
+//! A passage is a run of whole sentences from somewhere in the text,
+//! cleaned up so every character is on the keyboard: curly quotes and
+//! dashes become plain ones, anything else unusual is dropped.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+use slowcore::storage::{config_dir, documents_dir};
+use slowreader::book::{Book, BookMetadata, ContentBlock};
+use std::path::PathBuf;
+
+/// Roughly how long a passage is, in characters
+const PASSAGE_LENGTH: usize = 300;
+
+/// Texts shorter than this aren't worth practising on
+const MIN_TEXT: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Document,
+    Book,
+}
+
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub path: PathBuf,
+    pub title: String,
+    pub kind: Kind,
+}
+
+/// The part of slowReader's library.json needed here
+#[derive(Deserialize)]
+struct LibraryEntry {
+    path: PathBuf,
+    metadata: BookMetadata,
+}
+
+#[derive(Deserialize)]
+struct Library {
+    books: Vec<LibraryEntry>,
+}
+
+/// Text files in Documents and the folders just inside it
+fn documents() -> Vec<Source> {
+    let mut found = Vec::new();
+    let mut dirs = vec![(documents_dir(), 0)];
+    while let Some((dir, depth)) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for path in entries.flatten().map(|e| e.path()) {
+            let hidden = path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'));
+            if hidden {
+                continue;
+            }
+            if path.is_dir() && depth < 1 {
+                dirs.push((path, depth + 1));
+            } else if matches!(path.extension().and_then(|e| e.to_str()), Some("txt" | "md")) {
+                let title = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+                found.push(Source { path, title, kind: Kind::Document });
+            }
+        }
+    }
+    found.sort_by_key(|s| s.title.to_lowercase());
+    found
+}
+
+/// Books in slowReader's library that are still there
+fn books() -> Vec<Source> {
+    let library: Option<Library> = std::fs::read_to_string(config_dir("slowreader").join("library.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok());
+    let mut found: Vec<Source> = library
+        .map(|l| l.books)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|b| b.path.exists())
+        .map(|b| Source { path: b.path, title: b.metadata.title, kind: Kind::Book })
+        .collect();
+    found.sort_by_key(|s| s.title.to_lowercase());
+    found
+}
+
+/// Everything there is to practise on, documents first
+pub fn sources() -> Vec<Source> {
+    let mut all = documents();
+    all.extend(books());
+    all
+}
+
+/// The whole text of a source, as paragraphs
+fn read(source: &Source) -> Result<Vec<String>, String> {
+    match source.kind {
+        Kind::Document => {
+            let text = std::fs::read_to_string(&source.path).map_err(|e| e.to_string())?;
+            Ok(text.split("\n\n").map(strip_markdown).collect())
+        }
+        Kind::Book => {
+            let book = if source.path.extension().is_some_and(|e| e.eq_ignore_ascii_case("epub")) {
+                Book::open_epub(source.path.clone())
+            } else {
+                Book::open_text(source.path.clone())
+            };
+            let book = book.map_err(|_| "the book couldn't be opened".to_string())?;
+            Ok(book
+                .chapters
+                .into_iter()
+                .flat_map(|c| c.content)
+                .filter_map(|block| match block {
+                    ContentBlock::Paragraph(text) | ContentBlock::Quote(text) => Some(text),
+                    _ => None,
+                })
+                .collect())
+        }
+    }
+}
+
+/// A passage from somewhere in `source`
+pub fn passage(source: &Source, rng: &mut impl Rng) -> Result<String, String> {
+    let paragraphs: Vec<String> = read(source)?
+        .iter()
+        .map(|p| typeable(p))
+        .filter(|p| p.len() >= MIN_TEXT)
+        .collect();
+    let paragraph = paragraphs.choose(rng).ok_or("there's not enough text in it")?;
+    Ok(sentences_from(paragraph, rng.gen_range(0..paragraph.len())))
+}
+
+/// Whole sentences starting at the first one after `from`
+fn sentences_from(text: &str, from: usize) -> String {
+    let starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices(". ").map(|(i, _)| i + 2))
+        .filter(|&i| i < text.len())
+        .collect();
+    // Leave room for a full passage where possible
+    let latest = starts.iter().copied().rfind(|&i| text.len() - i >= PASSAGE_LENGTH).unwrap_or(0);
+    let start = starts.iter().copied().find(|&i| i >= from).unwrap_or(latest).min(latest);
+    let rest = &text[start..];
+    if rest.len() <= PASSAGE_LENGTH {
+        return rest.to_string();
+    }
+    match rest[PASSAGE_LENGTH..].find(". ") {
+        Some(end) => rest[..PASSAGE_LENGTH + end + 1].to_string(),
+        None => rest.to_string(),
+    }
+}
+
+/// Headings, list markers and emphasis taken out of a Markdown paragraph
+fn strip_markdown(paragraph: &str) -> String {
+    paragraph
+        .lines()
+        .map(|line| line.trim_start().trim_start_matches(['#', '>', '-', '*']).trim())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("**", "")
+        .replace(['`', '_'], "")
+}
+
+/// Only characters on the keyboard, on one line with single spaces
+fn typeable(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\u{2018}' | '\u{2019}' => plain.push('\''),
+            '\u{201C}' | '\u{201D}' => plain.push('"'),
+            '\u{2013}' | '\u{2014}' => plain.push('-'),
+            '\u{2026}' => plain.push_str("..."),
+            c if c.is_whitespace() => plain.push(' '),
+            c if c.is_ascii_graphic() => plain.push(c),
+            _ => {}
+        }
+    }
+    plain.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The title shown for a source, and what it is
+pub fn describe(source: &Source) -> String {
+    let kind = match source.kind {
+        Kind::Document => "document",
+        Kind::Book => "book",
+    };
+    format!("{} ({})", source.title, kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typeable_passage() {
+        assert_eq!(typeable("\u{201C}Well\u{2014}yes,\u{201D}\n  she\u{00A0}said\u{2026} \u{263A}"), "\"Well-yes,\" she said...");
+        assert_eq!(strip_markdown("## A **bold** `idea`\n- and more"), "A bold idea and more");
+
+        let text = "One short one. ".repeat(40);
+        let text = text.trim_end();
+        let passage = sentences_from(text, 37);
+        assert!(passage.starts_with("One") && passage.ends_with('.'), "{}", passage);
+        assert!(passage.len() >= PASSAGE_LENGTH && passage.len() < PASSAGE_LENGTH + 20);
+        // Near the end it starts early enough to still be full length
+        assert!(sentences_from(text, text.len() - 5).len() >= PASSAGE_LENGTH);
+    }
+}
@@ -0,0 +1,107 @@
+//! One run through a piece of text
+//!
+//! The clock starts at the first key. Mistakes stay marked until they are
+//! backspaced over, and count against accuracy even once corrected.
+
+use crate::lessons::KeyStat;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+pub struct Session {
+    text: Vec<char>,
+    /// What was typed at each position so far
+    typed: Vec<char>,
+    started: Option<Instant>,
+    /// How long it took, once the end is reached
+    finished: Option<Duration>,
+    keystrokes: u32,
+    mistakes: u32,
+    keys: BTreeMap<char, KeyStat>,
+}
+
+impl Session {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.chars().collect(),
+            typed: Vec::new(),
+            started: None,
+            finished: None,
+            keystrokes: 0,
+            mistakes: 0,
+            keys: BTreeMap::new(),
+        }
+    }
+
+    pub fn text(&self) -> &[char] {
+        &self.text
+    }
+
+    /// Where the next character goes
+    pub fn position(&self) -> usize {
+        self.typed.len()
+    }
+
+    /// Whether the character at `i` was typed wrong
+    pub fn is_wrong(&self, i: usize) -> bool {
+        self.typed.get(i).is_some_and(|&c| Some(&c) != self.text.get(i))
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started.is_some()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.is_some()
+    }
+
+    pub fn type_char(&mut self, c: char) {
+        if self.is_finished() {
+            return;
+        }
+        let Some(&expected) = self.text.get(self.typed.len()) else { return };
+        let start = *self.started.get_or_insert_with(Instant::now);
+        self.keystrokes += 1;
+        let stat = self.keys.entry(expected.to_ascii_lowercase()).or_default();
+        stat.typed += 1;
+        if c != expected {
+            stat.missed += 1;
+            self.mistakes += 1;
+        }
+        self.typed.push(c);
+        if self.typed.len() == self.text.len() {
+            self.finished = Some(start.elapsed());
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if !self.is_finished() {
+            self.typed.pop();
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.finished.or_else(|| self.started.map(|t| t.elapsed())).unwrap_or_default()
+    }
+
+    /// Words per minute, counting five correct characters as a word
+    pub fn wpm(&self) -> f32 {
+        let minutes = self.elapsed().as_secs_f32() / 60.0;
+        let correct = (0..self.typed.len()).filter(|&i| !self.is_wrong(i)).count();
+        if minutes > 0.0 { correct as f32 / 5.0 / minutes } else { 0.0 }
+    }
+
+    /// Keys right the first time, in percent
+    pub fn accuracy(&self) -> f32 {
+        if self.keystrokes == 0 {
+            100.0
+        } else {
+            (self.keystrokes - self.mistakes) as f32 * 100.0 / self.keystrokes as f32
+        }
+    }
+
+    /// Tries and misses of each key asked for
+    pub fn keys(&self) -> &BTreeMap<char, KeyStat> {
+        &self.keys
+    }
+}
+
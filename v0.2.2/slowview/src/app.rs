@@ -4,18 +4,44 @@
 //! Loads images at display resolution (max 640x480) to stay within
 //! the constraints of e-ink and Raspberry Pi hardware.
 
+use crate::compare::{self, CompareMode, CompareState};
 use crate::loader::{self, LoadedImage};
+use crate::pdf::{self, DocPositions, SearchHit};
 use egui::{
     ColorImage, Context, Key, Rect, Stroke, TextureHandle,
     TextureOptions, Vec2,
 };
+use slowcore::drag::DropTarget;
+use slowcore::preview;
 use slowcore::repaint::RepaintController;
-use slowcore::storage::{documents_dir, FileBrowser};
+use slowcore::storage::documents_dir;
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
+use slowcore::thumbnails::Thumbnails;
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, FileDialog, FileDialogResult, WindowAction};
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowView",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "image and PDF viewer for slowOS",
+    formats: &["PNG, JPEG, GIF, BMP, TIFF, WebP, PDF"],
+    credits: &["egui/eframe (MIT), image-rs (MIT)", "hayro (Apache-2.0/MIT)"],
+    ..AboutInfo::EMPTY
+};
+
+/// Size of the picture shown under the file browser's list
+const BROWSER_THUMBNAIL_SIZE: u32 = 96;
+
+/// PDF thumbnail render scale (1.0 = 72 DPI); ~80px wide for a letter page
+const THUMBNAIL_SCALE: f32 = 0.15;
+/// Thumbnail frame in the sidebar
+const THUMBNAIL_BOX: Vec2 = Vec2::new(80.0, 104.0);
+/// Sidebar row height: frame, padding and page number
+const THUMBNAIL_ROW_HEIGHT: f32 = 128.0;
+/// Text layer pages extracted per frame while indexing for search
+const TEXT_PAGES_PER_FRAME: usize = 8;
+
 /// Undoable file operation
 #[derive(Clone)]
 enum UndoAction {
@@ -43,6 +69,54 @@ struct PdfContent {
     page_text: HashMap<usize, String>,
     /// Raw PDF data for hayro rendering
     pdf_data: Vec<u8>,
+    /// Small page renders for the sidebar
+    thumbnails: HashMap<usize, TextureHandle>,
+    /// Thumbnails that failed to render (don't retry)
+    failed_thumbnails: HashSet<usize>,
+    /// Text layer per page, filled in lazily when the user searches.
+    /// `None` entries haven't been extracted yet.
+    text_layer: Vec<Option<String>>,
+    /// Parsed document used for text extraction (loaded on first search)
+    text_doc: Option<Box<lopdf::Document>>,
+}
+
+impl PdfContent {
+    /// Extract one page's text layer via lopdf (no-op if already done)
+    fn extract_text_page(&mut self, page: usize) {
+        if !matches!(self.text_layer.get(page), Some(None)) {
+            return;
+        }
+        if self.text_doc.is_none() {
+            self.text_doc = lopdf::Document::load(&self.path).ok().map(Box::new);
+        }
+        let text = self.text_doc.as_ref()
+            .and_then(|doc| doc.extract_text(&[(page + 1) as u32]).ok())
+            .unwrap_or_default();
+        self.text_layer[page] = Some(text);
+    }
+
+    /// True once every page's text layer has been extracted
+    fn text_indexed(&self) -> bool {
+        self.text_layer.iter().all(|t| t.is_some())
+    }
+
+    /// Whether any page has extractable text
+    fn has_text_layer(&self) -> bool {
+        self.text_layer.iter().flatten().any(|t| !t.trim().is_empty())
+    }
+}
+
+/// Text search state for the open PDF
+#[derive(Default)]
+struct PdfSearch {
+    query: String,
+    /// Query the current hits were computed for
+    searched: String,
+    hits: Vec<SearchHit>,
+    /// Index into `hits` of the focused result
+    current: usize,
+    /// Move keyboard focus to the search field next frame
+    focus_field: bool,
 }
 
 pub struct SlowViewApp {
@@ -57,9 +131,8 @@ pub struct SlowViewApp {
     current_index: usize,
     /// Error message from last load attempt
     error: Option<String>,
-    /// File browser dialog
-    show_file_browser: bool,
-    file_browser: FileBrowser,
+    /// Open (or "compare with") dialog
+    file_dialog: Option<FileDialog>,
     /// Info panel
     show_info: bool,
     /// About dialog
@@ -82,16 +155,33 @@ pub struct SlowViewApp {
     fullscreen: bool,
     /// Show menu bar temporarily in fullscreen when cursor near top
     fullscreen_menu_visible: bool,
+    /// PDF page thumbnail sidebar
+    show_thumbnails: bool,
+    /// Scroll the thumbnail sidebar to the current page next frame
+    follow_thumbnail: bool,
+    /// Show the PDF text layer (with search highlights) instead of the render
+    show_text_layer: bool,
+    /// Go-to-page field contents
+    goto_input: String,
+    /// Move keyboard focus to the go-to-page field next frame
+    focus_goto: bool,
+    /// PDF text search bar
+    show_search: bool,
+    search: PdfSearch,
+    /// Remembered page/zoom/scroll per PDF
+    positions: DocPositions,
+    /// Two-image comparison (side by side or flicker)
+    compare: Option<CompareState>,
+    /// The file browser is choosing a comparison image rather than opening
+    picking_compare: bool,
+    /// Short message for the status bar (e.g. a comparison image failed to load)
+    notice: Option<String>,
+    /// Thumbnails of pictures in the file browser
+    thumbnails: Thumbnails,
 }
 
 impl SlowViewApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>, initial_path: Option<PathBuf>) -> Self {
-        let mut extensions: Vec<String> = loader::supported_extensions()
-            .iter()
-            .map(|s| s.to_string())
-            .collect();
-        extensions.push("pdf".to_string());
-
+    pub fn new(cc: &eframe::CreationContext<'_>, initial_path: Option<PathBuf>) -> Self {
         let mut app = Self {
             repaint: RepaintController::new(),
             current: None,
@@ -99,8 +189,7 @@ impl SlowViewApp {
             siblings: Vec::new(),
             current_index: 0,
             error: None,
-            show_file_browser: false,
-            file_browser: FileBrowser::new(documents_dir()).with_filter(extensions),
+            file_dialog: None,
             show_info: false,
             show_about: false,
             show_shortcuts: false,
@@ -112,6 +201,18 @@ impl SlowViewApp {
             undo_stack: Vec::new(),
             fullscreen: false,
             fullscreen_menu_visible: false,
+            show_thumbnails: true,
+            follow_thumbnail: true,
+            show_text_layer: false,
+            goto_input: String::new(),
+            focus_goto: false,
+            show_search: false,
+            search: PdfSearch::default(),
+            positions: DocPositions::load(),
+            compare: None,
+            picking_compare: false,
+            notice: None,
+            thumbnails: Thumbnails::new(&cc.egui_ctx, BROWSER_THUMBNAIL_SIZE),
         };
 
         if let Some(path) = initial_path {
@@ -129,6 +230,13 @@ impl SlowViewApp {
     }
 
     fn open_file(&mut self, path: PathBuf) {
+        self.remember_pdf_position();
+        self.search = PdfSearch::default();
+        self.notice = None;
+        if Self::is_pdf(&path) {
+            self.compare = None;
+        }
+        self.show_text_layer = false;
         self.zoom = 1.0;
         self.prev_zoom = 1.0;
         self.scroll_center = Vec2::new(0.5, 0.5);
@@ -162,8 +270,19 @@ impl SlowViewApp {
                             .position(|p| p == &path)
                             .unwrap_or(0);
 
+                        // Restore where the user left off in this document
+                        let mut current_page = 0;
+                        if let Some(pos) = self.positions.get(&path) {
+                            current_page = pos.page.min(total_pages.saturating_sub(1));
+                            self.zoom = pos.zoom.clamp(0.25, 5.0);
+                            self.prev_zoom = self.zoom;
+                            self.scroll_center.y = pos.scroll_y.clamp(0.0, 1.0);
+                        }
+                        self.goto_input = (current_page + 1).to_string();
+                        self.follow_thumbnail = true;
+
                         self.view_content = Some(ViewContent::Pdf(PdfContent {
-                            current_page: 0,
+                            current_page,
                             total_pages,
                             path,
                             file_size,
@@ -171,6 +290,10 @@ impl SlowViewApp {
                             failed_pages: HashSet::new(),
                             page_text: HashMap::new(),
                             pdf_data: data,
+                            thumbnails: HashMap::new(),
+                            failed_thumbnails: HashSet::new(),
+                            text_layer: vec![None; total_pages],
+                            text_doc: None,
                         }));
                         self.loading = false;
                     }
@@ -198,66 +321,31 @@ impl SlowViewApp {
 
             let mut rendered = false;
 
-            // Re-parse PDF (hayro doesn't store parsed state across borrows)
-            use hayro::hayro_syntax::Pdf;
-            use hayro::hayro_interpret::InterpreterSettings;
-            use hayro::RenderSettings;
-            use std::sync::Arc;
-
-            let arc_data: Arc<dyn AsRef<[u8]> + Send + Sync> = Arc::new(pdf.pdf_data.clone());
-            if let Ok(pdf_doc) = Pdf::new(arc_data) {
-                if let Some(pdf_page) = pdf_doc.pages().get(page) {
-                    // Render at 150 DPI scale
-                    let scale = 150.0 / 72.0; // 72 is standard PDF points per inch
-
-                    let interpreter_settings = InterpreterSettings::default();
-                    let render_settings = RenderSettings {
-                        x_scale: scale,
-                        y_scale: scale,
-                        ..Default::default()
-                    };
-
-                    let pixmap = hayro::render(pdf_page, &interpreter_settings, &render_settings);
-                    let width = pixmap.width() as usize;
-                    let height = pixmap.height() as usize;
-
-                    // Convert pixmap to PNG then load as image
-                    if let Ok(png_data) = pixmap.into_png() {
-                        if let Ok(img) = image::load_from_memory(&png_data) {
-                            // Convert to grayscale for e-ink display
-                            let grey = img.grayscale();
-                            let mut rgba = grey.to_rgba8();
-                            let (mut w, mut h) = rgba.dimensions();
-
-                            // Limit texture size to GPU maximum (16384 pixels)
-                            const MAX_TEXTURE_SIZE: u32 = 16384;
-                            if w > MAX_TEXTURE_SIZE || h > MAX_TEXTURE_SIZE {
-                                let scale = (MAX_TEXTURE_SIZE as f32 / w.max(h) as f32).min(1.0);
-                                let new_w = (w as f32 * scale) as u32;
-                                let new_h = (h as f32 * scale) as u32;
-                                rgba = image::imageops::resize(&rgba, new_w, new_h, image::imageops::FilterType::Nearest);
-                                w = new_w;
-                                h = new_h;
-                            }
-
-                            let color_image = ColorImage::from_rgba_unmultiplied(
-                                [w as usize, h as usize],
-                                rgba.as_raw(),
-                            );
-                            let texture = ctx.load_texture(
-                                format!("pdf_page_{}", page),
-                                color_image,
-                                TextureOptions::NEAREST,
-                            );
-                            pdf.page_textures.insert(page, texture);
-                            rendered = true;
-                        }
-                    } else {
-                        // Try to get raw pixel data directly if PNG encoding fails
-                        // (This shouldn't happen, but just in case)
-                        let _ = width;
-                        let _ = height;
+            // Render at 150 DPI scale (72 is standard PDF points per inch)
+            if let Some((mut w, mut h, data)) = preview::render_pdf_page(&pdf.pdf_data, page, 150.0 / 72.0) {
+                if let Some(mut rgba) = image::RgbaImage::from_raw(w, h, data) {
+                    // Limit texture size to GPU maximum (16384 pixels)
+                    const MAX_TEXTURE_SIZE: u32 = 16384;
+                    if w > MAX_TEXTURE_SIZE || h > MAX_TEXTURE_SIZE {
+                        let scale = (MAX_TEXTURE_SIZE as f32 / w.max(h) as f32).min(1.0);
+                        let new_w = (w as f32 * scale) as u32;
+                        let new_h = (h as f32 * scale) as u32;
+                        rgba = image::imageops::resize(&rgba, new_w, new_h, image::imageops::FilterType::Nearest);
+                        w = new_w;
+                        h = new_h;
                     }
+
+                    let color_image = ColorImage::from_rgba_unmultiplied(
+                        [w as usize, h as usize],
+                        rgba.as_raw(),
+                    );
+                    let texture = ctx.load_texture(
+                        format!("pdf_page_{}", page),
+                        color_image,
+                        TextureOptions::NEAREST,
+                    );
+                    pdf.page_textures.insert(page, texture);
+                    rendered = true;
                 }
             }
 
@@ -350,6 +438,107 @@ impl SlowViewApp {
         self.scroll_center = Vec2::new(0.5, 0.5);
     }
 
+    /// Load `path` as the comparison image and enter compare mode
+    fn start_compare(&mut self, path: PathBuf) {
+        if !loader::is_image(&path) {
+            self.notice = Some("only images can be compared".into());
+            return;
+        }
+        match LoadedImage::open(&path) {
+            Ok(other) => {
+                match self.compare {
+                    Some(ref mut cmp) => cmp.set_other(other),
+                    None => self.compare = Some(CompareState::new(other)),
+                }
+                self.notice = None;
+            }
+            Err(e) => self.notice = Some(format!("can't compare: {}", e)),
+        }
+    }
+
+    /// Compare against the next/previous image in the folder. Steps from the
+    /// current comparison image if there is one, skipping the main image.
+    fn compare_with_sibling(&mut self, forward: bool) {
+        let images: Vec<&PathBuf> = self.siblings.iter().filter(|p| loader::is_image(p)).collect();
+        let current = self.current.as_ref().map(|img| img.path.clone());
+        if images.len() < 2 {
+            self.notice = Some("no other image in this folder".into());
+            return;
+        }
+        let from = self.compare.as_ref()
+            .map(|c| c.other.path.clone())
+            .or_else(|| current.clone());
+        let start = from.and_then(|f| images.iter().position(|p| **p == f)).unwrap_or(0);
+        let n = images.len();
+        let mut idx = start;
+        for _ in 0..n {
+            idx = if forward { (idx + 1) % n } else { (idx + n - 1) % n };
+            if Some(images[idx]) != current.as_ref() {
+                break;
+            }
+        }
+        let path = images[idx].clone();
+        self.start_compare(path);
+    }
+
+    fn render_compare(&mut self, ui: &mut egui::Ui, rect: Rect) {
+        let (Some(tex_a), Some(cmp)) = (self.texture.clone(), self.compare.as_ref()) else {
+            return;
+        };
+        let Some(tex_b) = cmp.texture.clone() else {
+            return;
+        };
+        let name = |p: &std::path::Path| p.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name_a = self.current.as_ref().map(|img| name(&img.path)).unwrap_or_default();
+        let name_b = name(&cmp.other.path);
+        let fill = self.fullscreen;
+        let zoom = self.zoom;
+        let center = self.scroll_center;
+
+        let panes: Vec<(Rect, TextureHandle, String)> = match cmp.mode {
+            CompareMode::SideBySide => {
+                let mid = rect.center().x;
+                let left = Rect::from_min_max(rect.min, egui::pos2(mid - 1.0, rect.max.y));
+                let right = Rect::from_min_max(egui::pos2(mid + 1.0, rect.min.y), rect.max);
+                vec![
+                    (left, tex_a, format!("A  {}", name_a)),
+                    (right, tex_b, format!("B  {}", name_b)),
+                ]
+            }
+            CompareMode::Flicker => {
+                if cmp.showing_other {
+                    vec![(rect, tex_b, format!("B  {}", name_b))]
+                } else {
+                    vec![(rect, tex_a, format!("A  {}", name_a))]
+                }
+            }
+        };
+
+        let mut pan = Vec2::ZERO;
+        for (pane, tex, label) in &panes {
+            let resp = compare::paint_pane(ui, *pane, tex, zoom, center, fill, label);
+            let shown = compare::display_size(*pane, tex.size_vec2(), zoom, fill);
+            if resp.dragged() {
+                pan += compare::pan_delta(*pane, shown, resp.drag_delta());
+            }
+            if resp.hovered() {
+                let scroll = ui.input(|i| i.raw_scroll_delta);
+                if scroll != Vec2::ZERO {
+                    pan += compare::pan_delta(*pane, shown, scroll);
+                }
+            }
+        }
+        if panes.len() == 2 {
+            ui.painter().vline(rect.center().x, rect.y_range(), Stroke::new(1.0, SlowColors::BLACK));
+        }
+        if pan != Vec2::ZERO {
+            self.scroll_center.x = (self.scroll_center.x + pan.x).clamp(0.0, 1.0);
+            self.scroll_center.y = (self.scroll_center.y + pan.y).clamp(0.0, 1.0);
+        }
+    }
+
     fn delete_current(&mut self) {
         let path = match &self.current {
             Some(img) => img.path.clone(),
@@ -364,6 +553,7 @@ impl SlowViewApp {
 
         // Try to move to trash
         if trash::move_to_trash(&path).is_ok() {
+            trash::notify_trashed("slowview", std::slice::from_ref(&path));
             // Add to undo stack
             self.undo_stack.push(UndoAction::Trashed(path.clone()));
 
@@ -413,14 +603,41 @@ impl SlowViewApp {
 
         // Check if we're viewing a PDF (arrow keys navigate pages, not files)
         let is_pdf = matches!(self.view_content, Some(ViewContent::Pdf(_)));
+        // Plain-key shortcuts are suspended while typing in the find/page fields
+        let typing = ctx.wants_keyboard_input();
 
+        let mut search_step = None;
+        let mut compare_step = None;
+        let mut page_step: Option<isize> = None;
+        let mut page_jump = None;
         ctx.input(|i| {
             let cmd = i.modifiers.command;
             let shift = i.modifiers.shift;
 
             if cmd && i.key_pressed(Key::O) {
-                self.show_file_browser = true;
+                self.show_open_dialog(false);
+            }
+            if is_pdf && cmd && i.key_pressed(Key::F) {
+                self.show_search = true;
+                self.search.focus_field = true;
+            }
+            if is_pdf && cmd && i.key_pressed(Key::G) {
+                search_step = Some(!shift);
             }
+            if i.key_pressed(Key::Escape) {
+                if self.fullscreen { self.fullscreen = false; }
+                else if self.compare.is_some() { self.compare = None; }
+                else if self.show_search { self.show_search = false; }
+                else if self.show_info { self.show_info = false; }
+            }
+            // Undo with Cmd+Z
+            if cmd && i.key_pressed(Key::Z) {
+                self.undo_last();
+            }
+            if typing || cmd {
+                return;
+            }
+
             if i.key_pressed(Key::I) {
                 self.show_info = !self.show_info;
             }
@@ -437,23 +654,52 @@ impl SlowViewApp {
             if i.key_pressed(Key::F) {
                 self.fullscreen = !self.fullscreen;
             }
-            if i.key_pressed(Key::Escape) {
-                if self.fullscreen { self.fullscreen = false; }
-                else if self.show_info { self.show_info = false; }
-                else if self.show_file_browser { self.show_file_browser = false; }
-            }
             // Delete current file (move to trash)
             if i.key_pressed(Key::Backspace) || i.key_pressed(Key::Delete) {
                 self.delete_current();
             }
-            // Undo with Cmd+Z
-            if cmd && i.key_pressed(Key::Z) {
-                self.undo_last();
+
+            if !is_pdf && i.key_pressed(Key::C) {
+                if self.compare.is_some() {
+                    self.compare = None;
+                } else {
+                    compare_step = Some(true);
+                }
+            }
+            if let Some(ref mut cmp) = self.compare {
+                // M: switch layout; Space: swap images; A: auto-flicker
+                if i.key_pressed(Key::M) {
+                    cmp.toggle_mode();
+                }
+                if cmp.mode == CompareMode::Flicker {
+                    if i.key_pressed(Key::Space) {
+                        cmp.flip();
+                    }
+                    if i.key_pressed(Key::A) {
+                        cmp.auto_flicker = !cmp.auto_flicker;
+                        cmp.last_flip = std::time::Instant::now();
+                    }
+                }
+            }
+
+            if is_pdf {
+                // G: type a page number; T: toggle the thumbnail sidebar
+                if i.key_pressed(Key::G) {
+                    self.focus_goto = true;
+                }
+                if i.key_pressed(Key::T) {
+                    self.show_thumbnails = !self.show_thumbnails;
+                    self.follow_thumbnail = true;
+                }
+                if i.key_pressed(Key::PageUp) { page_step = Some(-1); }
+                if i.key_pressed(Key::PageDown) { page_step = Some(1); }
+                if i.key_pressed(Key::Home) { page_jump = Some(0); }
+                if i.key_pressed(Key::End) { page_jump = Some(usize::MAX); }
             }
 
             // Spacebar/arrows for scrolling within content
             // Spacebar: jump to bottom (or top with shift)
-            if i.key_pressed(Key::Space) {
+            if i.key_pressed(Key::Space) && self.compare.is_none() {
                 if shift {
                     self.scroll_center.y = 0.0; // Top
                 } else {
@@ -469,9 +715,20 @@ impl SlowViewApp {
             }
         });
 
+        if let Some(forward) = search_step {
+            self.step_search(forward);
+        }
+        if let Some(forward) = compare_step {
+            self.compare_with_sibling(forward);
+        }
+
         // Apply OS-level fullscreen
         ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(self.fullscreen));
 
+        if typing {
+            return;
+        }
+
         // Left/Right arrow key navigation (for page changes)
         let (left, right) = ctx.input(|i| {
             (i.key_pressed(Key::ArrowLeft), i.key_pressed(Key::ArrowRight))
@@ -479,16 +736,21 @@ impl SlowViewApp {
 
         if is_pdf {
             // PDF mode: left/right arrows navigate pages within the PDF
-            if let Some(ViewContent::Pdf(ref mut pdf)) = self.view_content {
-                if left && pdf.current_page > 0 {
-                    pdf.current_page -= 1;
-                    self.scroll_center.y = 0.0; // Reset to top of new page
-                }
-                if right && pdf.current_page + 1 < pdf.total_pages {
-                    pdf.current_page += 1;
-                    self.scroll_center.y = 0.0; // Reset to top of new page
+            if left { page_step = Some(-1); }
+            if right { page_step = Some(1); }
+            if let Some(ViewContent::Pdf(ref pdf)) = self.view_content {
+                let current = pdf.current_page;
+                if let Some(step) = page_step {
+                    page_jump = Some(current.saturating_add_signed(step));
                 }
             }
+            if let Some(page) = page_jump {
+                self.go_to_page(page);
+            }
+        } else if self.compare.is_some() {
+            // Comparing: arrow keys step the comparison image through the folder
+            if left { self.compare_with_sibling(false); }
+            if right { self.compare_with_sibling(true); }
         } else {
             // Image mode: arrow keys navigate between files
             if left { self.prev_file(); }
@@ -502,7 +764,7 @@ impl SlowViewApp {
             action = window_control_buttons(ui);
             ui.menu_button("file", |ui| {
                 if ui.button("open...  ⌘O").clicked() {
-                    self.show_file_browser = true;
+                    self.show_open_dialog(false);
                     ui.close_menu();
                 }
                 ui.separator();
@@ -547,6 +809,65 @@ impl SlowViewApp {
                     self.zoom_reset();
                     ui.close_menu();
                 }
+                if matches!(self.view_content, Some(ViewContent::Image)) {
+                    ui.separator();
+                    if ui.button("compare with next  C").clicked() {
+                        self.compare_with_sibling(true);
+                        ui.close_menu();
+                    }
+                    if ui.button("compare with...").clicked() {
+                        self.show_open_dialog(true);
+                        ui.close_menu();
+                    }
+                    if let Some(ref mut cmp) = self.compare {
+                        let mode_label = match cmp.mode {
+                            CompareMode::SideBySide => "flicker view      M",
+                            CompareMode::Flicker => "side by side      M",
+                        };
+                        if ui.button(mode_label).clicked() {
+                            cmp.toggle_mode();
+                            ui.close_menu();
+                        }
+                        if cmp.mode == CompareMode::Flicker {
+                            let auto_label = if cmp.auto_flicker { "stop auto flicker A" } else { "auto flicker      A" };
+                            if ui.button(auto_label).clicked() {
+                                cmp.auto_flicker = !cmp.auto_flicker;
+                                ui.close_menu();
+                            }
+                        }
+                        if ui.button("stop comparing  Esc").clicked() {
+                            self.compare = None;
+                            ui.close_menu();
+                        }
+                    }
+                }
+                if matches!(self.view_content, Some(ViewContent::Pdf(_))) {
+                    ui.separator();
+                    let thumbs_label = if self.show_thumbnails { "hide thumbnails  T" } else { "show thumbnails  T" };
+                    if ui.button(thumbs_label).clicked() {
+                        self.show_thumbnails = !self.show_thumbnails;
+                        self.follow_thumbnail = true;
+                        ui.close_menu();
+                    }
+                    let text_label = if self.show_text_layer { "page view" } else { "text view" };
+                    if ui.button(text_label).clicked() {
+                        self.show_text_layer = !self.show_text_layer;
+                        ui.close_menu();
+                    }
+                    if ui.button("go to page...    G").clicked() {
+                        self.focus_goto = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("find...         ⌘F").clicked() {
+                        self.show_search = true;
+                        self.search.focus_field = true;
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(!self.search.hits.is_empty(), egui::Button::new("find next       ⌘G")).clicked() {
+                        self.step_search(true);
+                        ui.close_menu();
+                    }
+                }
                 ui.separator();
                 if ui.button("file info    I").clicked() {
                     self.show_info = !self.show_info;
@@ -572,6 +893,7 @@ impl SlowViewApp {
         let rect = ui.available_rect_before_wrap();
 
         match &self.view_content {
+            Some(ViewContent::Image) if self.compare.is_some() => self.render_compare(ui, rect),
             Some(ViewContent::Image) => self.render_image(ui, rect),
             Some(ViewContent::Pdf(_)) => self.render_pdf(ui, rect),
             None => {
@@ -591,13 +913,13 @@ impl SlowViewApp {
         }
 
         // Show error
-        if let Some(ref err) = self.error {
+        if let Some(err) = self.error.clone() {
             ui.vertical_centered(|ui| {
                 ui.add_space(rect.height() / 3.0);
                 ui.label(format!("error: {}", err));
                 ui.add_space(10.0);
                 if ui.button("open another file").clicked() {
-                    self.show_file_browser = true;
+                    self.show_open_dialog(false);
                 }
             });
         }
@@ -704,21 +1026,55 @@ impl SlowViewApp {
     }
 
     fn render_pdf(&mut self, ui: &mut egui::Ui, rect: Rect) {
+        let mut target_page = None;
         if let Some(ViewContent::Pdf(ref mut pdf)) = self.view_content {
             // Page navigation header
             ui.horizontal(|ui| {
                 if ui.add_enabled(pdf.current_page > 0, egui::Button::new("◀ prev")).clicked() {
-                    pdf.current_page -= 1;
+                    target_page = Some(pdf.current_page - 1);
                 }
-                ui.label(format!("page {} of {}", pdf.current_page + 1, pdf.total_pages));
+                ui.label("page");
+                let goto = ui.add(
+                    egui::TextEdit::singleline(&mut self.goto_input)
+                        .id(egui::Id::new("pdf_goto_page"))
+                        .desired_width(36.0)
+                        .horizontal_align(egui::Align::Center),
+                );
+                if self.focus_goto {
+                    goto.request_focus();
+                    self.focus_goto = false;
+                }
+                if goto.lost_focus() {
+                    let entered = ui.input(|i| i.key_pressed(Key::Enter));
+                    match self.goto_input.trim().parse::<usize>() {
+                        Ok(n) if entered && n >= 1 => target_page = Some(n - 1),
+                        _ => self.goto_input = (pdf.current_page + 1).to_string(),
+                    }
+                }
+                ui.label(format!("of {}", pdf.total_pages));
                 if ui.add_enabled(pdf.current_page + 1 < pdf.total_pages, egui::Button::new("next ▶")).clicked() {
-                    pdf.current_page += 1;
+                    target_page = Some(pdf.current_page + 1);
                 }
             });
             ui.separator();
 
-            // Rendered page image
             let page = pdf.current_page;
+            if self.show_text_layer {
+                pdf.extract_text_page(page);
+                let text = pdf.text_layer.get(page).cloned().flatten().unwrap_or_default();
+                let page_hits: Vec<SearchHit> = self.search.hits.iter()
+                    .filter(|h| h.page == page)
+                    .copied()
+                    .collect();
+                let current_hit = self.search.hits.get(self.search.current).copied();
+                render_text_layer(ui, &text, &page_hits, current_hit);
+                if let Some(p) = target_page {
+                    self.go_to_page(p);
+                }
+                return;
+            }
+
+            // Rendered page image
             let zoom = self.zoom;
             let scroll_center_y = self.scroll_center.y;
             if let Some(tex) = pdf.page_textures.get(&page) {
@@ -798,6 +1154,259 @@ impl SlowViewApp {
                 });
             }
         }
+        if let Some(p) = target_page {
+            self.go_to_page(p);
+        }
+    }
+
+    /// Jump to a page in the open PDF (clamped to the document)
+    fn go_to_page(&mut self, page: usize) {
+        if let Some(ViewContent::Pdf(ref mut pdf)) = self.view_content {
+            let page = page.min(pdf.total_pages.saturating_sub(1));
+            if page != pdf.current_page {
+                pdf.current_page = page;
+                self.scroll_center.y = 0.0; // Reset to top of new page
+                self.follow_thumbnail = true;
+            }
+            self.goto_input = (page + 1).to_string();
+        }
+    }
+
+    /// Store the open PDF's page, zoom and scroll so reopening resumes there
+    fn remember_pdf_position(&mut self) {
+        if let Some(ViewContent::Pdf(ref pdf)) = self.view_content {
+            self.positions.remember(&pdf.path, pdf.current_page, self.zoom, self.scroll_center.y);
+            self.positions.save();
+        }
+    }
+
+    /// Render a sidebar thumbnail. Returns true if a render was attempted.
+    fn ensure_thumbnail(&mut self, ctx: &Context, page: usize) -> bool {
+        if let Some(ViewContent::Pdf(ref mut pdf)) = self.view_content {
+            if pdf.thumbnails.contains_key(&page) || pdf.failed_thumbnails.contains(&page) {
+                return false;
+            }
+            match preview::render_pdf_page(&pdf.pdf_data, page, THUMBNAIL_SCALE) {
+                Some((w, h, rgba)) => {
+                    let color_image = ColorImage::from_rgba_unmultiplied([w as usize, h as usize], &rgba);
+                    let texture = ctx.load_texture(
+                        format!("pdf_thumb_{}", page),
+                        color_image,
+                        TextureOptions::NEAREST,
+                    );
+                    pdf.thumbnails.insert(page, texture);
+                }
+                None => {
+                    pdf.failed_thumbnails.insert(page);
+                }
+            }
+            return true;
+        }
+        false
+    }
+
+    fn render_thumbnails(&mut self, ui: &mut egui::Ui) {
+        let mut clicked = None;
+        let mut wanted = Vec::new();
+        if let Some(ViewContent::Pdf(ref pdf)) = self.view_content {
+            let hit_pages: HashSet<usize> = self.search.hits.iter().map(|h| h.page).collect();
+            let mut scroll = egui::ScrollArea::vertical().auto_shrink([false, false]);
+            if self.follow_thumbnail {
+                let offset = (pdf.current_page as f32 * THUMBNAIL_ROW_HEIGHT - THUMBNAIL_ROW_HEIGHT).max(0.0);
+                scroll = scroll.vertical_scroll_offset(offset);
+                self.follow_thumbnail = false;
+            }
+            scroll.show_rows(ui, THUMBNAIL_ROW_HEIGHT, pdf.total_pages, |ui, range| {
+                for page in range {
+                    let (row, resp) = ui.allocate_exact_size(
+                        egui::vec2(ui.available_width(), THUMBNAIL_ROW_HEIGHT),
+                        egui::Sense::click(),
+                    );
+                    let painter = ui.painter_at(row);
+                    let frame = Rect::from_center_size(
+                        egui::pos2(row.center().x, row.min.y + 4.0 + THUMBNAIL_BOX.y / 2.0),
+                        THUMBNAIL_BOX,
+                    );
+                    painter.rect_filled(frame, 0.0, SlowColors::WHITE);
+                    if let Some(tex) = pdf.thumbnails.get(&page) {
+                        let size = tex.size_vec2();
+                        let scale = (THUMBNAIL_BOX.x / size.x).min(THUMBNAIL_BOX.y / size.y);
+                        let img_rect = Rect::from_center_size(frame.center(), size * scale);
+                        painter.image(
+                            tex.id(),
+                            img_rect,
+                            Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                            egui::Color32::WHITE,
+                        );
+                    } else if !pdf.failed_thumbnails.contains(&page) {
+                        wanted.push(page);
+                    }
+                    painter.rect_stroke(frame, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+                    if page == pdf.current_page {
+                        slowcore::dither::draw_dither_outline(&painter, frame.expand(3.0), 2.0);
+                    } else if resp.hovered() {
+                        slowcore::dither::draw_dither_hover(&painter, frame);
+                    }
+                    // Pages containing search results get a marker
+                    if hit_pages.contains(&page) {
+                        painter.circle_filled(frame.right_top() + egui::vec2(-6.0, 6.0), 4.0, SlowColors::BLACK);
+                    }
+                    painter.text(
+                        egui::pos2(row.center().x, frame.max.y + 3.0),
+                        egui::Align2::CENTER_TOP,
+                        (page + 1).to_string(),
+                        egui::FontId::proportional(11.0),
+                        SlowColors::BLACK,
+                    );
+                    if resp.clicked() {
+                        clicked = Some(page);
+                    }
+                }
+            });
+        }
+        if let Some(page) = clicked {
+            self.go_to_page(page);
+        }
+        // One thumbnail per frame keeps the UI responsive on the Pi
+        if let Some(&page) = wanted.first() {
+            if self.ensure_thumbnail(ui.ctx(), page) {
+                self.repaint.mark_needs_repaint();
+            }
+        }
+    }
+
+    /// Extract text layers a few pages per frame, then (re)run the search
+    fn update_search(&mut self) {
+        let query = self.search.query.trim().to_string();
+        if query.is_empty() {
+            self.search.hits.clear();
+            self.search.searched.clear();
+            return;
+        }
+        if let Some(ViewContent::Pdf(ref mut pdf)) = self.view_content {
+            if !pdf.text_indexed() {
+                let pending: Vec<usize> = (0..pdf.total_pages)
+                    .filter(|&p| pdf.text_layer[p].is_none())
+                    .take(TEXT_PAGES_PER_FRAME)
+                    .collect();
+                for page in pending {
+                    pdf.extract_text_page(page);
+                }
+                self.repaint.mark_needs_repaint();
+                return;
+            }
+            if query == self.search.searched {
+                return;
+            }
+            let mut hits = Vec::new();
+            for (page, text) in pdf.text_layer.iter().enumerate() {
+                if let Some(text) = text {
+                    for (start, end) in pdf::find_matches(text, &query) {
+                        hits.push(SearchHit { page, start, end });
+                    }
+                }
+            }
+            // Start at the first result on or after the current page
+            let current_page = pdf.current_page;
+            self.search.current = hits.iter().position(|h| h.page >= current_page).unwrap_or(0);
+            self.search.hits = hits;
+            self.search.searched = query;
+            if let Some(hit) = self.search.hits.get(self.search.current).copied() {
+                self.go_to_page(hit.page);
+            }
+        }
+    }
+
+    /// Move to the next (or previous) search result
+    fn step_search(&mut self, forward: bool) {
+        let count = self.search.hits.len();
+        if count == 0 {
+            return;
+        }
+        self.search.current = if forward {
+            (self.search.current + 1) % count
+        } else {
+            (self.search.current + count - 1) % count
+        };
+        let page = self.search.hits[self.search.current].page;
+        self.go_to_page(page);
+    }
+
+    fn render_search_bar(&mut self, ui: &mut egui::Ui) {
+        let mut step = None;
+        ui.horizontal(|ui| {
+            ui.label("find:");
+            let field = ui.add(
+                egui::TextEdit::singleline(&mut self.search.query)
+                    .id(egui::Id::new("pdf_search_field"))
+                    .desired_width(160.0),
+            );
+            if self.search.focus_field {
+                field.request_focus();
+                self.search.focus_field = false;
+            }
+            if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                step = Some(!ui.input(|i| i.modifiers.shift));
+                field.request_focus();
+            }
+            if ui.button("◀").clicked() {
+                step = Some(false);
+            }
+            if ui.button("▶").clicked() {
+                step = Some(true);
+            }
+
+            let status = match &self.view_content {
+                Some(ViewContent::Pdf(pdf)) if !self.search.query.trim().is_empty() && !pdf.text_indexed() => {
+                    let done = pdf.text_layer.iter().filter(|t| t.is_some()).count();
+                    format!("indexing {}/{}...", done, pdf.total_pages)
+                }
+                Some(ViewContent::Pdf(pdf)) if !self.search.searched.is_empty() && !pdf.has_text_layer() => {
+                    "no text layer".to_string()
+                }
+                _ if self.search.searched.is_empty() => String::new(),
+                _ if self.search.hits.is_empty() => "no matches".to_string(),
+                _ => {
+                    let hit = self.search.hits[self.search.current];
+                    format!("{} of {}  (page {})", self.search.current + 1, self.search.hits.len(), hit.page + 1)
+                }
+            };
+            ui.label(status);
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("close").clicked() {
+                    self.show_search = false;
+                }
+                let label = if self.show_text_layer { "page view" } else { "text view" };
+                if ui.button(label).clicked() {
+                    self.show_text_layer = !self.show_text_layer;
+                }
+            });
+        });
+
+        // Context for the focused result
+        if let (Some(hit), Some(ViewContent::Pdf(pdf))) =
+            (self.search.hits.get(self.search.current), &self.view_content)
+        {
+            if let Some(Some(text)) = pdf.text_layer.get(hit.page) {
+                let (before, matched, after) = pdf::snippet(text, hit.start, hit.end, 40);
+                let mut job = egui::text::LayoutJob::default();
+                let font = egui::FontId::proportional(11.0);
+                let plain = egui::TextFormat::simple(font.clone(), SlowColors::BLACK);
+                let highlight = egui::TextFormat {
+                    background: SlowColors::BLACK,
+                    ..egui::TextFormat::simple(font, SlowColors::WHITE)
+                };
+                job.append(&before, 0.0, plain.clone());
+                job.append(&matched, 0.0, highlight);
+                job.append(&after, 0.0, plain);
+                ui.label(job);
+            }
+        }
+
+        if let Some(forward) = step {
+            self.step_search(forward);
+        }
     }
 
     fn render_info_panel(&mut self, ctx: &Context) {
@@ -889,68 +1498,46 @@ impl SlowViewApp {
         }
     }
 
-    fn render_file_browser(&mut self, ctx: &Context) {
-        let resp = egui::Window::new("open file")
-            .collapsible(false)
-            .resizable(false)
-            .default_width(450.0)
-            .show(ctx, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label("location:");
-                    ui.label(self.file_browser.current_dir.to_string_lossy().to_string());
-                });
-
-                ui.separator();
-
-                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
-                    let mut clicked_idx = None;
-                    let mut nav_path = None;
-                    let mut open_path = None;
-                    for (idx, entry) in self.file_browser.entries.iter().enumerate() {
-                        let selected = self.file_browser.selected_index == Some(idx);
-                        let response = ui.add(
-                            slowcore::widgets::FileListItem::new(&entry.name, entry.is_directory)
-                                .selected(selected),
-                        );
-
-                        if response.clicked() {
-                            clicked_idx = Some(idx);
-                        }
-
-                        if response.double_clicked() {
-                            if entry.is_directory {
-                                nav_path = Some(entry.path.clone());
-                            } else {
-                                open_path = Some(entry.path.clone());
-                            }
-                        }
-                    }
-                    if let Some(idx) = clicked_idx { self.file_browser.selected_index = Some(idx); }
-                    if let Some(path) = nav_path { self.file_browser.navigate_to(path); }
-                    if let Some(path) = open_path {
-                        self.open_file(path);
-                        self.show_file_browser = false;
-                    }
-                });
-
-                ui.separator();
+    /// Open a picture, or (`compare`) pick one to compare the current one with
+    fn show_open_dialog(&mut self, compare: bool) {
+        let mut extensions = loader::supported_extensions().to_vec();
+        extensions.push("pdf");
+        let dialog = FileDialog::open(documents_dir()).filter(&extensions);
+        self.file_dialog = Some(if compare { dialog.title("compare with").action("compare") } else { dialog });
+        self.picking_compare = compare;
+    }
 
-                ui.horizontal(|ui| {
-                    if ui.button("cancel").clicked() {
-                        self.show_file_browser = false;
-                    }
-                    if ui.button("open").clicked() {
-                        if let Some(entry) = self.file_browser.selected_entry() {
-                            if !entry.is_directory {
-                                let path = entry.path.clone();
-                                self.open_file(path);
-                                self.show_file_browser = false;
-                            }
-                        }
-                    }
-                });
-            });
-        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+    fn render_file_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.file_dialog else { return };
+        // The highlighted picture, to tell photos apart by more than name
+        let thumbnails = &mut self.thumbnails;
+        let result = dialog.show_with_preview(ctx, |ui, picture| {
+            let (slot, _) = ui.allocate_exact_size(
+                Vec2::new(ui.available_width(), BROWSER_THUMBNAIL_SIZE as f32),
+                egui::Sense::hover(),
+            );
+            if let Some(texture) = picture.and_then(|p| thumbnails.get(ui.ctx(), p)) {
+                let rect = Rect::from_center_size(slot.center(), texture.size_vec2());
+                let uv = Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                ui.painter().image(texture.id(), rect, uv, egui::Color32::WHITE);
+            }
+        });
+        match result {
+            FileDialogResult::Picked(path) => {
+                self.file_dialog = None;
+                if self.picking_compare {
+                    self.start_compare(path);
+                } else {
+                    self.open_file(path);
+                }
+                self.picking_compare = false;
+            }
+            FileDialogResult::Cancelled => {
+                self.file_dialog = None;
+                self.picking_compare = false;
+            }
+            FileDialogResult::Pending => {}
+        }
     }
 
     fn render_shortcuts(&mut self, ctx: &Context) {
@@ -976,6 +1563,25 @@ impl SlowViewApp {
                     shortcut(ui, "Space", "jump to bottom");
                     shortcut(ui, "Shift+Space", "jump to top");
 
+                    ui.add_space(6.0);
+                    ui.strong("compare");
+                    shortcut(ui, "C", "compare with next / stop");
+                    shortcut(ui, "← / →", "change comparison image");
+                    shortcut(ui, "M", "side by side / flicker");
+                    shortcut(ui, "Space", "swap images (flicker)");
+                    shortcut(ui, "A", "auto flicker");
+                    shortcut(ui, "drag", "pan both images");
+
+                    ui.add_space(6.0);
+                    ui.strong("pdf");
+                    shortcut(ui, "← / →", "prev / next page");
+                    shortcut(ui, "PgUp / PgDn", "prev / next page");
+                    shortcut(ui, "Home / End", "first / last page");
+                    shortcut(ui, "G", "go to page");
+                    shortcut(ui, "T", "toggle thumbnails");
+                    shortcut(ui, "⌘F", "find text");
+                    shortcut(ui, "⌘G / ⇧⌘G", "next / prev match");
+
                     ui.add_space(6.0);
                     ui.strong("view");
                     shortcut(ui, "+ / =", "zoom in");
@@ -998,41 +1604,6 @@ impl SlowViewApp {
             });
         if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
     }
-
-    fn render_about(&mut self, ctx: &Context) {
-        let screen = ctx.screen_rect();
-        let max_h = (screen.height() - 60.0).max(120.0);
-
-        let resp = egui::Window::new("about slowView")
-            .collapsible(false)
-            .resizable(false)
-            .default_width(300.0)
-            .max_height(max_h)
-            .show(ctx, |ui| {
-                egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                    ui.vertical_centered(|ui| {
-                        ui.heading("slowView");
-                        ui.label("version 0.2.2");
-                        ui.add_space(8.0);
-                        ui.label("image and PDF viewer for slowOS");
-                    });
-                    ui.add_space(8.0);
-                    ui.separator();
-                    ui.add_space(4.0);
-                    ui.label("supported formats:");
-                    ui.label("  PNG, JPEG, GIF, BMP, TIFF, WebP, PDF");
-                    ui.add_space(4.0);
-                    ui.label("frameworks:");
-                    ui.label("  egui/eframe (MIT), image-rs (MIT)");
-                    ui.label("  hayro (Apache-2.0/MIT)");
-                    ui.add_space(8.0);
-                });
-                ui.vertical_centered(|ui| {
-                    if ui.button("ok").clicked() { self.show_about = false; }
-                });
-            });
-        if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
-    }
 }
 
 impl eframe::App for SlowViewApp {
@@ -1052,30 +1623,23 @@ impl eframe::App for SlowViewApp {
                 self.ensure_pdf_page_texture(ctx, page);
             }
         }
-
-        // Handle dropped files (from OS or from Files app)
-        let mut dropped: Option<PathBuf> = ctx.input(|i| {
-            i.raw.dropped_files.first()
-                .and_then(|f| f.path.clone())
-                .filter(|p| loader::is_image(p) || Self::is_pdf(p))
-        });
-
-        // Check for files dragged from slowOS Files app
-        let mouse_released = ctx.input(|i| i.pointer.primary_released());
-        let mouse_in_window = ctx.input(|i| i.pointer.has_pointer());
-        if dropped.is_none() && mouse_released && mouse_in_window {
-            if let Some(paths) = slowcore::drag::get_drag_paths() {
-                // Take the first supported file
-                let valid = paths.into_iter()
-                    .find(|p| loader::is_image(p) || Self::is_pdf(p));
-                if valid.is_some() {
-                    dropped = valid;
-                    slowcore::drag::end_drag();
-                }
-            }
+        if self.show_search {
+            self.update_search();
+        }
+        if !matches!(self.view_content, Some(ViewContent::Image)) {
+            self.compare = None;
         }
+        let flickering = match self.compare {
+            Some(ref mut cmp) => {
+                cmp.ensure_texture(ctx);
+                cmp.tick()
+            }
+            None => false,
+        };
+        self.repaint.set_continuous(flickering);
 
-        if let Some(path) = dropped {
+        // Handle dropped files (from OS or from Files app)
+        if let Some(path) = DropTarget::new(VIEWABLE_EXTENSIONS).show(ctx).into_iter().next() {
             self.open_file(path);
         }
 
@@ -1113,6 +1677,27 @@ impl eframe::App for SlowViewApp {
         if !self.fullscreen {
             egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
             let status = match &self.view_content {
+                _ if self.notice.is_some() => self.notice.clone().unwrap_or_default(),
+                Some(ViewContent::Image) if self.compare.is_some() => {
+                    let name = |p: &std::path::Path| p.file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let a = self.current.as_ref().map(|img| name(&img.path)).unwrap_or_default();
+                    match self.compare {
+                        Some(ref cmp) => {
+                            let mode = match cmp.mode {
+                                CompareMode::SideBySide => "side by side".to_string(),
+                                CompareMode::Flicker => format!(
+                                    "flicker: showing {}{}",
+                                    if cmp.showing_other { "B" } else { "A" },
+                                    if cmp.auto_flicker { " (auto)" } else { "" },
+                                ),
+                            };
+                            format!("A: {}  vs  B: {}  |  {}", a, name(&cmp.other.path), mode)
+                        }
+                        None => String::new(),
+                    }
+                }
                 Some(ViewContent::Image) => {
                     if let Some(ref img) = self.current {
                         let filename = img.path.file_name()
@@ -1160,6 +1745,22 @@ impl eframe::App for SlowViewApp {
             });
         }
 
+        let is_pdf = matches!(self.view_content, Some(ViewContent::Pdf(_)));
+        if is_pdf && self.show_search {
+            egui::TopBottomPanel::top("pdf_search").show(ctx, |ui| {
+                self.render_search_bar(ui);
+            });
+        }
+        if is_pdf && self.show_thumbnails && !self.fullscreen {
+            egui::SidePanel::left("pdf_thumbnails")
+                .resizable(false)
+                .exact_width(THUMBNAIL_BOX.x + 24.0)
+                .frame(egui::Frame::none().fill(SlowColors::WHITE).stroke(Stroke::new(1.0, SlowColors::BLACK)))
+                .show(ctx, |ui| {
+                    self.render_thumbnails(ui);
+                });
+        }
+
         // Main content
         egui::CentralPanel::default()
             .frame(egui::Frame::none().fill(SlowColors::WHITE))
@@ -1168,20 +1769,65 @@ impl eframe::App for SlowViewApp {
             });
 
         // Dialogs
-        if self.show_file_browser {
-            self.render_file_browser(ctx);
-        }
+        self.render_file_dialog(ctx);
         if self.show_info {
             self.render_info_panel(ctx);
         }
-        if self.show_about {
-            self.render_about(ctx);
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
         if self.show_shortcuts {
             self.render_shortcuts(ctx);
         }
         self.repaint.end_frame(ctx);
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.remember_pdf_position();
+    }
+}
+
+/// Show a page's text layer with search matches highlighted.
+/// The focused match is drawn inverted, other matches on grey.
+fn render_text_layer(ui: &mut egui::Ui, text: &str, hits: &[SearchHit], current: Option<SearchHit>) {
+    if text.trim().is_empty() {
+        ui.vertical_centered(|ui| {
+            ui.add_space(40.0);
+            ui.label("this page has no text layer");
+        });
+        return;
+    }
+
+    let font = egui::FontId::proportional(14.0);
+    let plain = egui::TextFormat::simple(font.clone(), SlowColors::BLACK);
+    let highlight = egui::TextFormat {
+        background: egui::Color32::from_rgb(160, 160, 160),
+        ..plain.clone()
+    };
+    let focused = egui::TextFormat {
+        background: SlowColors::BLACK,
+        ..egui::TextFormat::simple(font, SlowColors::WHITE)
+    };
+
+    let mut job = egui::text::LayoutJob::default();
+    job.wrap.max_width = ui.available_width() - 16.0;
+    let mut pos = 0;
+    for hit in hits {
+        if hit.start < pos || hit.end > text.len() {
+            continue;
+        }
+        job.append(&text[pos..hit.start], 0.0, plain.clone());
+        let format = if Some(*hit) == current { focused.clone() } else { highlight.clone() };
+        job.append(&text[hit.start..hit.end], 0.0, format);
+        pos = hit.end;
+    }
+    job.append(&text[pos..], 0.0, plain);
+
+    egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+        egui::Frame::none().inner_margin(egui::Margin::same(8.0)).show(ui, |ui| {
+            ui.label(job);
+        });
+    });
 }
 
 fn format_size(bytes: u64) -> String {
@@ -1191,6 +1837,9 @@ fn format_size(bytes: u64) -> String {
     else { format!("{:.1} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0)) }
 }
 
+/// Files slowView opens: `loader::supported_extensions` and PDF
+const VIEWABLE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "tiff", "tif", "webp", "pdf"];
+
 /// Check if a path is a viewable file (image or PDF)
 fn is_viewable(path: &std::path::Path) -> bool {
     loader::is_image(path) || path.extension()
@@ -0,0 +1,151 @@
+//! Image comparison for slowView
+//!
+//! Shows two images either side by side or flickering in one pane, so
+//! before/after versions (e.g. an original and its dithered copy) can be
+//! compared. Both panes share one zoom level and one pan position: dragging
+//! or scrolling either pane moves both.
+
+use crate::loader::LoadedImage;
+use egui::{ColorImage, Context, Rect, Response, Stroke, TextureHandle, TextureOptions, Vec2};
+use slowcore::theme::SlowColors;
+use std::time::{Duration, Instant};
+
+/// How often the flicker view swaps images when auto-flicker is on
+pub const FLICKER_INTERVAL: Duration = Duration::from_millis(800);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareMode {
+    /// Two panes next to each other
+    SideBySide,
+    /// One pane alternating between the two images
+    Flicker,
+}
+
+/// The second image and how the two are being compared
+pub struct CompareState {
+    /// Comparison image ("B"); the main viewer image is "A"
+    pub other: LoadedImage,
+    pub texture: Option<TextureHandle>,
+    pub mode: CompareMode,
+    /// In flicker mode: whether B is currently shown
+    pub showing_other: bool,
+    /// Swap automatically every FLICKER_INTERVAL
+    pub auto_flicker: bool,
+    pub last_flip: Instant,
+}
+
+impl CompareState {
+    pub fn new(other: LoadedImage) -> Self {
+        Self {
+            other,
+            texture: None,
+            mode: CompareMode::SideBySide,
+            showing_other: false,
+            auto_flicker: false,
+            last_flip: Instant::now(),
+        }
+    }
+
+    /// Replace the comparison image, keeping the mode
+    pub fn set_other(&mut self, other: LoadedImage) {
+        self.other = other;
+        self.texture = None;
+    }
+
+    pub fn ensure_texture(&mut self, ctx: &Context) {
+        if self.texture.is_some() {
+            return;
+        }
+        let rgba = self.other.rgba_bytes();
+        let color_image = ColorImage::from_rgba_unmultiplied(
+            [self.other.display_width as usize, self.other.display_height as usize],
+            &rgba,
+        );
+        self.texture = Some(ctx.load_texture("slowview_compare", color_image, TextureOptions::NEAREST));
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            CompareMode::SideBySide => CompareMode::Flicker,
+            CompareMode::Flicker => CompareMode::SideBySide,
+        };
+    }
+
+    pub fn flip(&mut self) {
+        self.showing_other = !self.showing_other;
+        self.last_flip = Instant::now();
+    }
+
+    /// Advance auto-flicker. Returns true while auto-flicker needs timed repaints.
+    pub fn tick(&mut self) -> bool {
+        if self.mode != CompareMode::Flicker || !self.auto_flicker {
+            return false;
+        }
+        if self.last_flip.elapsed() >= FLICKER_INTERVAL {
+            self.flip();
+        }
+        true
+    }
+}
+
+/// Paint one texture into `rect` using the shared zoom and pan.
+///
+/// `center` is the pan position as a fraction of the scrollable range
+/// (0.5 = centered), matching slowView's `scroll_center`. The returned
+/// response senses drags so the caller can pan both panes together.
+pub fn paint_pane(
+    ui: &mut egui::Ui,
+    rect: Rect,
+    tex: &TextureHandle,
+    zoom: f32,
+    center: Vec2,
+    fill: bool,
+    label: &str,
+) -> Response {
+    let response = ui.allocate_rect(rect, egui::Sense::drag());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, SlowColors::WHITE);
+
+    let display_size = display_size(rect, tex.size_vec2(), zoom, fill);
+    let overflow = display_size - rect.size();
+    let min = egui::pos2(
+        if overflow.x > 0.0 { rect.min.x - overflow.x * center.x } else { rect.center().x - display_size.x / 2.0 },
+        if overflow.y > 0.0 { rect.min.y - overflow.y * center.y } else { rect.center().y - display_size.y / 2.0 },
+    );
+    let img_rect = Rect::from_min_size(min, display_size);
+    painter.image(
+        tex.id(),
+        img_rect,
+        Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+        egui::Color32::WHITE,
+    );
+    painter.rect_stroke(img_rect, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+
+    // File name tag in the top-left corner
+    let font = egui::FontId::proportional(11.0);
+    let galley = painter.layout_no_wrap(label.to_string(), font, SlowColors::BLACK);
+    let tag = Rect::from_min_size(rect.min + egui::vec2(4.0, 4.0), galley.size() + egui::vec2(8.0, 4.0));
+    painter.rect_filled(tag, 0.0, SlowColors::WHITE);
+    painter.rect_stroke(tag, 0.0, Stroke::new(1.0, SlowColors::BLACK));
+    painter.galley(tag.min + egui::vec2(4.0, 2.0), galley, SlowColors::BLACK);
+
+    response
+}
+
+/// Size of the image on screen: fit to the pane (never upscaled unless
+/// `fill`), then multiplied by the zoom factor.
+pub fn display_size(rect: Rect, tex_size: Vec2, zoom: f32, fill: bool) -> Vec2 {
+    let fit = (rect.width() / tex_size.x).min(rect.height() / tex_size.y);
+    let fit = if fill { fit } else { fit.min(1.0) };
+    tex_size * fit * zoom
+}
+
+/// Convert a drag/scroll delta in points into a change of the shared pan
+/// position for a pane showing `display` pixels inside `rect`.
+pub fn pan_delta(rect: Rect, display: Vec2, delta: Vec2) -> Vec2 {
+    let overflow = display - rect.size();
+    Vec2::new(
+        if overflow.x > 0.0 { -delta.x / overflow.x } else { 0.0 },
+        if overflow.y > 0.0 { -delta.y / overflow.y } else { 0.0 },
+    )
+}
@@ -91,8 +91,11 @@ impl LoadedImage {
             full_image
         };
 
-        // Convert to greyscale for e-ink aesthetic
-        let display = DynamicImage::ImageLuma8(resized.to_luma8());
+        // Convert to greyscale for e-ink aesthetic, dithered to the greys
+        // the screen shows (black and white unless set in Settings)
+        let mut grey = resized.to_luma8();
+        slowcore::dither::photo(&mut grey);
+        let display = DynamicImage::ImageLuma8(grey);
 
         Ok(LoadedImage {
             display,
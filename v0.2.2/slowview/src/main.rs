@@ -1,23 +1,21 @@
 //! slowView — a minimal image and PDF viewer for the Slow Computer
 
 mod app;
+mod compare;
 mod loader;
+mod pdf;
 
 use app::SlowViewApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     // Check if a file path was passed as argument
     let initial_path = std::env::args().nth(1).map(std::path::PathBuf::from);
 
-    let mut viewport = egui::ViewportBuilder::default()
-        .with_inner_size([520.0, 400.0])
+    let viewport = window_layout::viewport("slowview", Placement::RememberLast, [520.0, 400.0])
         .with_title("slowView");
 
-    if let Some(pos) = slowcore::cascade_position() {
-        viewport = viewport.with_position(pos);
-    }
-
     let options = NativeOptions {
         viewport,
         ..Default::default()
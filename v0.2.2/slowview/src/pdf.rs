@@ -0,0 +1,169 @@
+//! PDF helpers for slowView
+//!
+//! - Pages are rasterized by `slowcore::preview::render_pdf_page` (shared
+//!   by the page view, thumbnails and slowFiles' preview)
+//! - Text search over the lopdf text layer
+//! - Remembered page/zoom/scroll per document
+
+use serde::{Deserialize, Serialize};
+use slowcore::safety::snap_to_char_boundary;
+use slowcore::storage::config_dir;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Maximum number of documents whose position we remember
+const MAX_REMEMBERED: usize = 200;
+
+/// A single search match inside a page's text layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchHit {
+    /// Zero-based page index
+    pub page: usize,
+    /// Byte offset of the match in the page text
+    pub start: usize,
+    /// Byte offset one past the end of the match
+    pub end: usize,
+}
+
+/// Find all case-insensitive occurrences of `query` in `text`.
+/// Returns (start, end) byte ranges into the original `text`.
+pub fn find_matches(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let query: String = query.chars().flat_map(|c| c.to_lowercase()).collect();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    // Lowercase the text while remembering which original byte each
+    // lowered byte came from — lowercasing can change byte lengths.
+    let mut lowered = String::with_capacity(text.len());
+    let mut origin: Vec<usize> = Vec::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        for lc in c.to_lowercase() {
+            lowered.push(lc);
+            for _ in 0..lc.len_utf8() {
+                origin.push(i);
+            }
+        }
+    }
+
+    let mut matches = Vec::new();
+    let mut from = 0;
+    while let Some(pos) = lowered[from..].find(&query) {
+        let start = from + pos;
+        let end = start + query.len();
+        let orig_start = origin[start];
+        let orig_end = origin.get(end).copied().unwrap_or(text.len());
+        matches.push((orig_start, orig_end.max(orig_start)));
+        from = end;
+    }
+    matches
+}
+
+/// Cut a short one-line snippet around a match: (before, matched, after).
+pub fn snippet(text: &str, start: usize, end: usize, radius: usize) -> (String, String, String) {
+    let from = snap_to_char_boundary(text, start.saturating_sub(radius));
+    let to = snap_to_char_boundary(text, (end + radius).min(text.len()));
+    let start = snap_to_char_boundary(text, start);
+    let end = snap_to_char_boundary(text, end);
+    let flatten = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut before = flatten(&text[from..start]);
+    let mut after = flatten(&text[end..to]);
+    if from > 0 { before.insert(0, '…'); }
+    if to < text.len() { after.push('…'); }
+    if text[from..start].ends_with(char::is_whitespace) { before.push(' '); }
+    if text[end..to].starts_with(char::is_whitespace) { after.insert(0, ' '); }
+    (before, flatten(&text[start..end]), after)
+}
+
+/// Last viewed page, zoom and scroll for one document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocPosition {
+    pub page: usize,
+    pub zoom: f32,
+    pub scroll_y: f32,
+    /// Unix seconds, used to drop the oldest entries
+    pub last_viewed: u64,
+}
+
+/// Remembered positions for every PDF the user has opened
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocPositions {
+    pub docs: HashMap<String, DocPosition>,
+}
+
+impl DocPositions {
+    pub fn load() -> Self {
+        let path = config_dir("slowview").join("pdf_positions.json");
+        std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = config_dir("slowview").join("pdf_positions.json");
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(&path, json);
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&DocPosition> {
+        self.docs.get(&path.to_string_lossy().to_string())
+    }
+
+    /// Record a position and prune the oldest entries past the limit
+    pub fn remember(&mut self, path: &Path, page: usize, zoom: f32, scroll_y: f32) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.docs.insert(
+            path.to_string_lossy().to_string(),
+            DocPosition { page, zoom, scroll_y, last_viewed: now },
+        );
+        if self.docs.len() > MAX_REMEMBERED {
+            let mut by_age: Vec<(String, u64)> = self.docs.iter()
+                .map(|(k, v)| (k.clone(), v.last_viewed))
+                .collect();
+            by_age.sort_by_key(|(_, t)| *t);
+            let excess = self.docs.len() - MAX_REMEMBERED;
+            for (key, _) in by_age.into_iter().take(excess) {
+                self.docs.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_matches_case_insensitive() {
+        let text = "The cat sat. THE END";
+        assert_eq!(find_matches(text, "the"), vec![(0, 3), (13, 16)]);
+        assert!(find_matches(text, "").is_empty());
+        assert!(find_matches(text, "dog").is_empty());
+    }
+
+    #[test]
+    fn test_find_matches_multibyte() {
+        let text = "Café CAFÉ";
+        let hits = find_matches(text, "café");
+        assert_eq!(hits.len(), 2);
+        assert_eq!(&text[hits[0].0..hits[0].1], "Café");
+        assert_eq!(&text[hits[1].0..hits[1].1], "CAFÉ");
+    }
+
+    #[test]
+    fn test_snippet() {
+        let text = "one two three four five";
+        let (before, hit, after) = snippet(text, 8, 13, 4);
+        assert_eq!(hit, "three");
+        assert_eq!(before, "…two ");
+        assert_eq!(after, " fou…");
+    }
+}
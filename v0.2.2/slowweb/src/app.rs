@@ -0,0 +1,485 @@
+//! slowWeb application
+
+use crate::fetch::{self, Page};
+use crate::places::Places;
+use crate::readability::{Article, Link};
+use egui::{CentralPanel, Context, Key, SidePanel, TopBottomPanel};
+use slowcore::repaint::RepaintController;
+use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
+use slowcore::widgets::{about_dialog, status_bar, window_control_buttons, AboutInfo, SlowButton, WindowAction};
+use slowreader::book::{Book, BookMetadata, Chapter, ContentBlock};
+use slowreader::reader::{Reader, ReadingPosition};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowWeb",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "the web, in reader mode",
+    features: &["readable text, no javascript", "dithered images", "numbered links", "bookmarks and history"],
+    ..AboutInfo::EMPTY
+};
+
+/// What the worker thread reports back
+enum Loaded {
+    Page(Result<Page, String>),
+    /// Dithered image data for one image block
+    Image { block: usize, data: Vec<u8> },
+}
+
+/// How a finished load changes back/forward history
+#[derive(Clone, Copy, PartialEq)]
+enum Nav {
+    Push,
+    Replace,
+}
+
+struct Loading {
+    url: String,
+    nav: Nav,
+    /// False once the page itself is in and only images are coming
+    page_pending: bool,
+    result: Receiver<Loaded>,
+}
+
+pub struct SlowWebApp {
+    ctx: Context,
+    address: String,
+    /// None while showing the start page
+    current: Option<String>,
+    book: Book,
+    links: Vec<Link>,
+    back: Vec<Option<String>>,
+    forward: Vec<Option<String>>,
+    places: Places,
+    reader: Reader,
+    loading: Option<Loading>,
+    message: Option<String>,
+    show_links: bool,
+    focus_address: bool,
+    show_about: bool,
+    repaint: RepaintController,
+}
+
+/// A one-chapter book for slowReader's layout engine
+fn book_for(article: &Article, url: &str) -> Book {
+    Book {
+        path: PathBuf::from(url),
+        metadata: BookMetadata { title: article.title.clone(), ..Default::default() },
+        chapters: vec![Chapter { title: article.title.clone(), content: article.blocks.clone() }],
+    }
+}
+
+impl SlowWebApp {
+    pub fn new(cc: &eframe::CreationContext<'_>, start: Option<String>) -> Self {
+        let empty = Article { title: "slowWeb".into(), blocks: Vec::new(), links: Vec::new(), images: Vec::new() };
+        let mut app = Self {
+            ctx: cc.egui_ctx.clone(),
+            address: String::new(),
+            current: None,
+            book: book_for(&empty, ""),
+            links: Vec::new(),
+            back: Vec::new(),
+            forward: Vec::new(),
+            places: Places::load(),
+            reader: Reader::new(),
+            loading: None,
+            message: None,
+            show_links: true,
+            focus_address: false,
+            show_about: false,
+            repaint: RepaintController::new(),
+        };
+        app.show_start_page();
+        if let Some(address) = start {
+            app.open(&address, Nav::Push);
+        }
+        app
+    }
+
+    fn show_article(&mut self, article: &Article, url: Option<String>) {
+        self.book = book_for(article, url.as_deref().unwrap_or(""));
+        self.links = article.links.clone();
+        self.reader.position = ReadingPosition::default();
+        self.address = url.clone().unwrap_or_default();
+        self.current = url;
+    }
+
+    /// The built-in page shown at launch and by "home"
+    fn show_start_page(&mut self) {
+        let mut blocks = vec![
+            ContentBlock::Heading { level: 1, text: "slowWeb".into() },
+            ContentBlock::Paragraph(
+                "the web as plain reading: pages are stripped to their text and images, with no scripts. \
+                 links are numbered in the text and listed on the right."
+                    .into(),
+            ),
+            ContentBlock::Heading { level: 2, text: "bookmarks".into() },
+        ];
+        let mut links = Vec::new();
+        for place in &self.places.bookmarks {
+            links.push(Link { url: place.url.clone(), text: place.title.clone() });
+            blocks.push(ContentBlock::ListItem(format!("{} [{}]", place.title, links.len())));
+        }
+        if links.is_empty() {
+            blocks.push(ContentBlock::Paragraph("no bookmarks yet. press \u{2318}D on a page to add one.".into()));
+        }
+        let article = Article { title: "slowWeb".into(), blocks, links, images: Vec::new() };
+        self.show_article(&article, None);
+    }
+
+    /// Start loading `address` in the background
+    fn open(&mut self, address: &str, nav: Nav) {
+        let url = match fetch::parse_address(address) {
+            Ok(url) => url,
+            Err(e) => {
+                self.message = Some(e);
+                return;
+            }
+        };
+        self.address = url.to_string();
+        self.message = None;
+
+        let (tx, rx) = mpsc::channel();
+        let ctx = self.ctx.clone();
+        let target = url.clone();
+        std::thread::spawn(move || {
+            let page = fetch::load(&target);
+            let images = page.as_ref().map(|p| p.article.images.clone()).unwrap_or_default();
+            if tx.send(Loaded::Page(page)).is_err() {
+                return;
+            }
+            ctx.request_repaint();
+            for image in images {
+                if let Some(data) = fetch::load_image(&image.url) {
+                    // The user has moved on to another page
+                    if tx.send(Loaded::Image { block: image.block, data }).is_err() {
+                        return;
+                    }
+                    ctx.request_repaint();
+                }
+            }
+        });
+        self.loading = Some(Loading { url: url.to_string(), nav, page_pending: true, result: rx });
+    }
+
+    fn poll_loading(&mut self) {
+        let Some(loading) = &mut self.loading else { return };
+        let mut page = None;
+        let mut done = false;
+        loop {
+            match loading.result.try_recv() {
+                Ok(Loaded::Page(result)) => {
+                    loading.page_pending = false;
+                    page = Some((result, loading.nav));
+                }
+                Ok(Loaded::Image { block, data }) => {
+                    if let Some(ContentBlock::Image { data: slot, .. }) = self.book.chapters[0].content.get_mut(block) {
+                        *slot = Some(data);
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    done = true;
+                    break;
+                }
+            }
+        }
+        if done {
+            self.loading = None;
+        }
+
+        match page {
+            Some((Ok(page), nav)) => {
+                if nav == Nav::Push && self.current.as_deref() != Some(page.url.as_str()) {
+                    self.back.push(self.current.clone());
+                    self.forward.clear();
+                }
+                self.places.visit(&page.url, &page.article.title);
+                self.show_article(&page.article, Some(page.url));
+            }
+            Some((Err(e), _)) => {
+                self.message = Some(e);
+                self.address = self.current.clone().unwrap_or_default();
+                self.loading = None;
+            }
+            None => {}
+        }
+    }
+
+    fn go_back(&mut self) {
+        if let Some(prev) = self.back.pop() {
+            self.forward.push(self.current.clone());
+            self.go_to(prev);
+        }
+    }
+
+    fn go_forward(&mut self) {
+        if let Some(next) = self.forward.pop() {
+            self.back.push(self.current.clone());
+            self.go_to(next);
+        }
+    }
+
+    fn go_to(&mut self, place: Option<String>) {
+        match place {
+            Some(url) => self.open(&url, Nav::Replace),
+            None => self.show_start_page(),
+        }
+    }
+
+    fn reload(&mut self) {
+        let current = self.current.clone();
+        self.go_to(current);
+    }
+
+    fn go_home(&mut self) {
+        if self.current.is_some() {
+            self.back.push(self.current.clone());
+            self.forward.clear();
+        }
+        self.show_start_page();
+    }
+
+    fn toggle_bookmark(&mut self) {
+        let Some(url) = self.current.clone() else { return };
+        self.places.toggle_bookmark(&url, &self.book.metadata.title);
+        self.message = Some(if self.places.is_bookmarked(&url) { "bookmarked".into() } else { "bookmark removed".into() });
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        let typing = ctx.wants_keyboard_input();
+        let (cmd, shift) = ctx.input(|i| (i.modifiers.command, i.modifiers.shift));
+        let pressed = |key: Key| ctx.input(|i| i.key_pressed(key));
+        if cmd {
+            if pressed(Key::L) { self.focus_address = true; }
+            if pressed(Key::R) { self.reload(); }
+            if pressed(Key::D) { self.toggle_bookmark(); }
+            if pressed(Key::K) { self.show_links = !self.show_links; }
+            if pressed(Key::OpenBracket) { self.go_back(); }
+            if pressed(Key::CloseBracket) { self.go_forward(); }
+            return;
+        }
+        if typing {
+            return;
+        }
+        if pressed(Key::ArrowRight) || pressed(Key::PageDown) || (pressed(Key::Space) && !shift) {
+            self.reader.next_page(&self.book);
+        }
+        if pressed(Key::ArrowLeft) || pressed(Key::PageUp) || (pressed(Key::Space) && shift) {
+            self.reader.prev_page(&self.book);
+        }
+        if pressed(Key::Plus) || pressed(Key::Equals) {
+            self.reader.increase_font_size();
+        }
+        if pressed(Key::Minus) {
+            self.reader.decrease_font_size();
+        }
+    }
+
+    fn draw_menu_bar(&mut self, ctx: &Context) {
+        let win_action = TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            menu_bar(ui, |ui| {
+                let action = window_control_buttons(ui);
+                ui.menu_button("file", |ui| {
+                    if ui.button("open location  ⌘L").clicked() {
+                        self.focus_address = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("reload         ⌘R").clicked() {
+                        self.reload();
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("view", |ui| {
+                    let label = if self.show_links { "hide links  ⌘K" } else { "show links  ⌘K" };
+                    if ui.button(label).clicked() {
+                        self.show_links = !self.show_links;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("larger text   +").clicked() {
+                        self.reader.increase_font_size();
+                        ui.close_menu();
+                    }
+                    if ui.button("smaller text  -").clicked() {
+                        self.reader.decrease_font_size();
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("go", |ui| {
+                    if ui.add_enabled(!self.back.is_empty(), egui::Button::new("back     ⌘[")).clicked() {
+                        self.go_back();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(!self.forward.is_empty(), egui::Button::new("forward  ⌘]")).clicked() {
+                        self.go_forward();
+                        ui.close_menu();
+                    }
+                    if ui.button("home").clicked() {
+                        self.go_home();
+                        ui.close_menu();
+                    }
+                    if !self.places.history.is_empty() {
+                        ui.separator();
+                        for place in self.places.history.clone() {
+                            if ui.button(&place.title).on_hover_text(&place.url).clicked() {
+                                self.open(&place.url, Nav::Push);
+                                ui.close_menu();
+                            }
+                        }
+                    }
+                });
+                ui.menu_button("bookmarks", |ui| {
+                    let bookmarked = self.current.as_ref().is_some_and(|u| self.places.is_bookmarked(u));
+                    let label = if bookmarked { "remove bookmark  ⌘D" } else { "add bookmark     ⌘D" };
+                    if ui.add_enabled(self.current.is_some(), egui::Button::new(label)).clicked() {
+                        self.toggle_bookmark();
+                        ui.close_menu();
+                    }
+                    if !self.places.bookmarks.is_empty() {
+                        ui.separator();
+                        for place in self.places.bookmarks.clone() {
+                            if ui.button(&place.title).on_hover_text(&place.url).clicked() {
+                                self.open(&place.url, Nav::Push);
+                                ui.close_menu();
+                            }
+                        }
+                    }
+                });
+                ui.menu_button("help", |ui| {
+                    if ui.button("about").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+                action
+            }).inner
+        }).inner;
+
+        match win_action {
+            WindowAction::Close => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            WindowAction::Minimize => {
+                slowcore::minimize::write_minimized("slowweb", "slowWeb");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            WindowAction::None => {}
+        }
+    }
+
+    fn draw_address_bar(&mut self, ctx: &Context) {
+        TopBottomPanel::top("address_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!self.back.is_empty(), SlowButton::new("\u{2190}")).clicked() {
+                    self.go_back();
+                }
+                if ui.add_enabled(!self.forward.is_empty(), SlowButton::new("\u{2192}")).clicked() {
+                    self.go_forward();
+                }
+                let resp = ui.add(
+                    egui::TextEdit::singleline(&mut self.address)
+                        .hint_text("address or file path")
+                        .desired_width(f32::INFINITY),
+                );
+                if self.focus_address {
+                    resp.request_focus();
+                    self.focus_address = false;
+                }
+                if resp.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    let address = self.address.clone();
+                    self.open(&address, Nav::Push);
+                }
+            });
+        });
+    }
+
+    fn draw_links(&mut self, ctx: &Context) {
+        if !self.show_links {
+            return;
+        }
+        let mut follow = None;
+        SidePanel::right("links")
+            .resizable(false)
+            .exact_width(180.0)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new("links").strong());
+                ui.separator();
+                if self.links.is_empty() {
+                    ui.label("no links on this page");
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (i, link) in self.links.iter().enumerate() {
+                        let text = format!("[{}] {}", i + 1, link.text);
+                        let resp = ui.add(egui::Label::new(text).wrap(true).sense(egui::Sense::click()));
+                        if resp.hovered() {
+                            slowcore::dither::draw_dither_hover(ui.painter(), resp.rect);
+                        }
+                        if resp.on_hover_text(&link.url).clicked() {
+                            follow = Some(link.url.clone());
+                        }
+                    }
+                });
+            });
+        if let Some(url) = follow {
+            self.open(&url, Nav::Push);
+        }
+    }
+
+    fn draw_about(&mut self, ctx: &Context) {
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
+    }
+}
+
+impl eframe::App for SlowWebApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.repaint.begin_frame(ctx);
+        if slowcore::minimize::check_restore_signal("slowweb") {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        consume_special_keys(ctx);
+        self.poll_loading();
+        self.handle_keys(ctx);
+
+        self.draw_menu_bar(ctx);
+
+        TopBottomPanel::top("title_bar").show(ctx, |ui| {
+            slowcore::theme::SlowTheme::title_bar_frame().show(ui, |ui| {
+                ui.centered_and_justified(|ui| {
+                    ui.label(&self.book.metadata.title);
+                });
+            });
+        });
+        self.draw_address_bar(ctx);
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let status = match (&self.loading, &self.message) {
+                (Some(loading), _) if loading.page_pending => format!("loading {}...", loading.url),
+                (_, Some(message)) => message.clone(),
+                (Some(_), None) => "loading images...".into(),
+                (None, None) => {
+                    let (page, total) = self.reader.page_info();
+                    format!("page {} of {}  |  ←/→ or click to turn", page, total)
+                }
+            };
+            status_bar(ui, &status);
+        });
+
+        self.draw_links(ctx);
+
+        self.reader.suppress_clicks = self.show_about;
+        CentralPanel::default()
+            .frame(egui::Frame::none().fill(SlowColors::WHITE))
+            .show(ctx, |ui| {
+                let rect = ui.available_rect_before_wrap();
+                self.reader.render(ui, &self.book, rect);
+            });
+
+        self.draw_about(ctx);
+        self.repaint.end_frame(ctx);
+    }
+}
@@ -0,0 +1,22 @@
+//! 1-bit page images, dithered with `slowcore::dither::atkinson`
+
+use slowcore::dither::atkinson;
+
+/// Images wider than this are scaled down before dithering
+pub const MAX_WIDTH: u32 = 560;
+
+/// Decode an image, scale it to fit the page and dither it, returning
+/// PNG bytes for slowReader's image blocks
+pub fn page_image(bytes: &[u8]) -> Option<Vec<u8>> {
+    let decoded = image::load_from_memory(bytes).ok()?;
+    let decoded = if decoded.width() > MAX_WIDTH {
+        decoded.resize(MAX_WIDTH, u32::MAX, image::imageops::FilterType::Triangle)
+    } else {
+        decoded
+    };
+    let mut grey = decoded.to_luma8();
+    atkinson(&mut grey);
+    let mut png = Vec::new();
+    grey.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png).ok()?;
+    Some(png)
+}
@@ -0,0 +1,105 @@
+//! Loading pages over http(s) or from local files
+//!
+//! Runs on a worker thread: the page, then its images one by one.
+
+use crate::dither;
+use crate::readability::{self, Article};
+use slowreader::book::ContentBlock;
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+const USER_AGENT: &str = "slowWeb/0.2.2 (text-only; no javascript)";
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Pages and images larger than this are refused
+const MAX_PAGE: u64 = 5 * 1024 * 1024;
+const MAX_IMAGE: u64 = 2 * 1024 * 1024;
+
+/// A loaded page
+pub struct Page {
+    /// Where the page ended up, after redirects
+    pub url: String,
+    pub article: Article,
+}
+
+/// Turn what was typed in the address bar into a URL
+pub fn parse_address(address: &str) -> Result<Url, String> {
+    let address = address.trim();
+    if address.starts_with('/') || address.starts_with('~') {
+        let path = match address.strip_prefix('~') {
+            Some(rest) => std::env::var("HOME").map(|h| format!("{}{}", h, rest)).unwrap_or_else(|_| address.to_string()),
+            None => address.to_string(),
+        };
+        return Url::from_file_path(&path).map_err(|_| format!("not a valid path: {}", address));
+    }
+    let with_scheme = if address.contains("://") { address.to_string() } else { format!("https://{}", address) };
+    Url::parse(&with_scheme).map_err(|_| format!("not a valid address: {}", address))
+}
+
+/// Fetch bytes and a content type
+fn get(url: &Url, limit: u64) -> Result<(String, String, Vec<u8>), String> {
+    let mut bytes = Vec::new();
+    match url.scheme() {
+        "http" | "https" => {
+            let response = ureq::get(url.as_str())
+                .set("User-Agent", USER_AGENT)
+                .timeout(TIMEOUT)
+                .call()
+                .map_err(|e| match e {
+                    ureq::Error::Status(code, _) => format!("the server answered {}", code),
+                    ureq::Error::Transport(t) => t.to_string(),
+                })?;
+            let final_url = response.get_url().to_string();
+            let content_type = response.content_type().to_string();
+            response.into_reader().take(limit).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+            Ok((final_url, content_type, bytes))
+        }
+        "file" => {
+            let path: PathBuf = url.to_file_path().map_err(|_| format!("not a local file: {}", url))?;
+            let file = std::fs::File::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            file.take(limit).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+            let content_type = match ext.as_str() {
+                "html" | "htm" | "xhtml" => "text/html",
+                "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" => "image",
+                _ => "text/plain",
+            };
+            Ok((url.to_string(), content_type.to_string(), bytes))
+        }
+        other => Err(format!("{} links can't be opened here", other)),
+    }
+}
+
+/// Fetch a page and run the readability pass over it
+pub fn load(url: &Url) -> Result<Page, String> {
+    let (final_url, content_type, bytes) = get(url, MAX_PAGE)?;
+    let base = Url::parse(&final_url).ok();
+    let name = base
+        .as_ref()
+        .and_then(|u| u.path_segments().and_then(|mut s| s.next_back()).map(|s| s.to_string()))
+        .filter(|s| !s.is_empty())
+        .or_else(|| base.as_ref().and_then(|u| u.host_str().map(|h| h.to_string())))
+        .unwrap_or_else(|| "untitled".into());
+
+    let article = if content_type.contains("html") {
+        readability::extract(&String::from_utf8_lossy(&bytes), base.as_ref())
+    } else if content_type.starts_with("text/") {
+        readability::plain_text(&String::from_utf8_lossy(&bytes), &name)
+    } else if content_type.starts_with("image") {
+        // An image on its own: a page with just that image, dithered
+        let blocks = vec![ContentBlock::Image { alt: name.clone(), data: dither::page_image(&bytes) }];
+        Article { title: name, blocks, links: Vec::new(), images: Vec::new() }
+    } else {
+        return Err(format!("can't show {} pages", content_type));
+    };
+    Ok(Page { url: final_url, article })
+}
+
+/// Fetch and dither one image; None if it can't be shown
+pub fn load_image(url: &str) -> Option<Vec<u8>> {
+    let url = Url::parse(url).ok()?;
+    let (_, _, bytes) = get(&url, MAX_IMAGE).ok()?;
+    dither::page_image(&bytes)
+}
@@ -0,0 +1,35 @@
+//! slowWeb - A reader-mode web viewer for the Slow Computer
+//!
+//! Pages are reduced to their text and dithered images and laid out by
+//! slowReader. No JavaScript, by design.
+
+mod app;
+mod dither;
+mod fetch;
+mod places;
+mod readability;
+
+use app::SlowWebApp;
+use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
+
+fn main() -> eframe::Result<()> {
+    let start = std::env::args().nth(1);
+
+    let viewport = window_layout::viewport("slowweb", Placement::RememberLast, [720.0, 520.0])
+        .with_title("slowWeb");
+
+    let options = NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "slowWeb",
+        options,
+        Box::new(|cc| {
+            slowcore::SlowTheme::default().apply(&cc.egui_ctx);
+            Box::new(SlowWebApp::new(cc, start))
+        }),
+    )
+}
@@ -0,0 +1,78 @@
+//! Bookmarks and history, saved between sessions
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Visited pages kept in the go menu
+const HISTORY_LEN: usize = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Place {
+    pub url: String,
+    pub title: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Places {
+    pub bookmarks: Vec<Place>,
+    /// Most recent first
+    pub history: Vec<Place>,
+}
+
+impl Default for Places {
+    fn default() -> Self {
+        let place = |url: &str, title: &str| Place { url: url.into(), title: title.into() };
+        Self {
+            bookmarks: vec![
+                place("https://text.npr.org/", "npr text"),
+                place("https://lite.cnn.com/", "cnn lite"),
+                place("https://en.wikipedia.org/wiki/Slow_movement_(culture)", "the slow movement"),
+            ],
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Places {
+    fn path() -> PathBuf {
+        slowcore::storage::config_dir("slowweb").join("places.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    pub fn is_bookmarked(&self, url: &str) -> bool {
+        self.bookmarks.iter().any(|b| b.url == url)
+    }
+
+    /// Add or remove a bookmark for `url`
+    pub fn toggle_bookmark(&mut self, url: &str, title: &str) {
+        if self.is_bookmarked(url) {
+            self.bookmarks.retain(|b| b.url != url);
+        } else {
+            self.bookmarks.push(Place { url: url.into(), title: title.into() });
+        }
+        self.save();
+    }
+
+    pub fn visit(&mut self, url: &str, title: &str) {
+        self.history.retain(|p| p.url != url);
+        self.history.insert(0, Place { url: url.into(), title: title.into() });
+        self.history.truncate(HISTORY_LEN);
+        self.save();
+    }
+}
@@ -0,0 +1,422 @@
+//! Readability pass: strip a web page down to its article
+//!
+//! Scripts, styles and page furniture (navigation, headers, footers,
+//! sidebars, forms) are dropped. The element holding the most paragraph
+//! text is taken as the article, and its contents become slowReader
+//! content blocks. Links are numbered inline ("some text [3]") and listed
+//! separately so they can be followed without a mouse-driven layout.
+
+use html5ever::parse_document;
+use html5ever::tendril::TendrilSink;
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use slowreader::book::ContentBlock;
+use std::collections::HashMap;
+use std::rc::Rc;
+use url::Url;
+
+/// Never part of the readable text
+const SKIP_TAGS: &[&str] = &[
+    "script", "style", "noscript", "nav", "header", "footer", "aside", "form", "iframe", "svg",
+    "button", "select", "textarea", "template", "canvas", "video", "audio", "object", "head",
+];
+
+/// Elements whose text flows into the surrounding paragraph
+const INLINE_TAGS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "br", "cite", "code", "data", "del", "dfn", "em", "font", "i",
+    "ins", "kbd", "label", "mark", "q", "s", "samp", "small", "span", "strong", "sub", "sup",
+    "time", "u", "var", "wbr",
+];
+
+/// Paragraphs shorter than this don't count toward an article's score
+const MIN_PARAGRAPH: usize = 25;
+
+/// An article needs at least this much text, or the whole body is used
+const MIN_ARTICLE: usize = 250;
+
+/// Pages can have hundreds of images; only the first few are fetched
+const MAX_IMAGES: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub url: String,
+    pub text: String,
+}
+
+/// An image block whose data still has to be fetched
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingImage {
+    /// Index into `Article::blocks`
+    pub block: usize,
+    pub url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Article {
+    pub title: String,
+    pub blocks: Vec<ContentBlock>,
+    /// Link `n` in the text is `links[n - 1]`
+    pub links: Vec<Link>,
+    pub images: Vec<PendingImage>,
+}
+
+fn tag_name(handle: &Handle) -> Option<String> {
+    match &handle.data {
+        NodeData::Element { name, .. } => Some(name.local.as_ref().to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+fn attr(handle: &Handle, key: &str) -> Option<String> {
+    match &handle.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .find(|a| a.name.local.as_ref() == key)
+            .map(|a| a.value.to_string()),
+        _ => None,
+    }
+}
+
+fn is_skipped(handle: &Handle) -> bool {
+    tag_name(handle).is_some_and(|t| SKIP_TAGS.contains(&t.as_str()))
+        || attr(handle, "hidden").is_some()
+        || attr(handle, "aria-hidden").as_deref() == Some("true")
+}
+
+/// All text under `handle`, whitespace not yet collapsed
+fn raw_text(handle: &Handle, out: &mut String) {
+    match &handle.data {
+        NodeData::Text { contents } => out.push_str(&contents.borrow()),
+        NodeData::Element { .. } if is_skipped(handle) => {}
+        _ => {
+            for child in handle.children.borrow().iter() {
+                raw_text(child, out);
+            }
+        }
+    }
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn text_len(handle: &Handle) -> usize {
+    let mut text = String::new();
+    raw_text(handle, &mut text);
+    collapse(&text).chars().count()
+}
+
+fn find_first(handle: &Handle, tag: &str) -> Option<Handle> {
+    if tag_name(handle).as_deref() == Some(tag) {
+        return Some(handle.clone());
+    }
+    handle.children.borrow().iter().find_map(|c| find_first(c, tag))
+}
+
+/// Share of an element's text that sits inside links
+fn link_density(handle: &Handle) -> f32 {
+    fn link_text(handle: &Handle) -> usize {
+        if tag_name(handle).as_deref() == Some("a") {
+            return text_len(handle);
+        }
+        handle.children.borrow().iter().map(link_text).sum()
+    }
+    let total = text_len(handle);
+    if total == 0 {
+        return 1.0;
+    }
+    link_text(handle) as f32 / total as f32
+}
+
+/// Score paragraph-holding elements: every paragraph adds to its parent
+/// and half as much to its grandparent
+fn score(handle: &Handle, ancestors: &mut Vec<Handle>, scores: &mut HashMap<usize, (Handle, f32)>) {
+    if is_skipped(handle) {
+        return;
+    }
+    if matches!(tag_name(handle).as_deref(), Some("p") | Some("pre")) {
+        let mut text = String::new();
+        raw_text(handle, &mut text);
+        let text = collapse(&text);
+        let len = text.chars().count();
+        if len >= MIN_PARAGRAPH {
+            let points = 1.0 + text.matches(',').count() as f32 + (len / 100).min(3) as f32;
+            for (depth, ancestor) in ancestors.iter().rev().take(2).enumerate() {
+                let entry = scores.entry(Rc::as_ptr(ancestor) as usize).or_insert((ancestor.clone(), 0.0));
+                entry.1 += if depth == 0 { points } else { points / 2.0 };
+            }
+        }
+        return;
+    }
+    ancestors.push(handle.clone());
+    for child in handle.children.borrow().iter() {
+        score(child, ancestors, scores);
+    }
+    ancestors.pop();
+}
+
+/// The element most likely to hold the article
+fn article_root(document: &Handle) -> Option<Handle> {
+    for tag in ["article", "main"] {
+        if let Some(found) = find_first(document, tag) {
+            if text_len(&found) >= MIN_ARTICLE {
+                return Some(found);
+            }
+        }
+    }
+    let mut scores = HashMap::new();
+    score(document, &mut Vec::new(), &mut scores);
+    scores
+        .into_values()
+        .map(|(handle, points)| {
+            let density = link_density(&handle);
+            (handle, points * (1.0 - density))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(handle, _)| handle)
+        .filter(|handle| text_len(handle) >= MIN_ARTICLE)
+        .or_else(|| find_first(document, "body"))
+}
+
+struct Extractor {
+    base: Option<Url>,
+    blocks: Vec<ContentBlock>,
+    links: Vec<Link>,
+    images: Vec<PendingImage>,
+    /// Images met inside a paragraph, placed after its text
+    deferred_images: Vec<(String, String)>,
+}
+
+impl Extractor {
+    fn resolve(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") || href.starts_with("mailto:") {
+            return None;
+        }
+        match &self.base {
+            Some(base) => base.join(href).ok().map(|u| u.to_string()),
+            None => Url::parse(href).ok().map(|u| u.to_string()),
+        }
+    }
+
+    fn push_image(&mut self, alt: String, src: &str) {
+        let Some(url) = self.resolve(src) else { return };
+        if self.images.len() >= MAX_IMAGES {
+            return;
+        }
+        self.images.push(PendingImage { block: self.blocks.len(), url });
+        self.blocks.push(ContentBlock::Image { alt, data: None });
+    }
+
+    fn image(&mut self, handle: &Handle) -> Option<(String, String)> {
+        let src = attr(handle, "src").or_else(|| attr(handle, "data-src"))?;
+        let alt = attr(handle, "alt").filter(|a| !a.trim().is_empty()).unwrap_or_else(|| "image".into());
+        Some((alt, src))
+    }
+
+    /// Text of an inline run, with links numbered
+    fn inline(&mut self, handle: &Handle, out: &mut String) {
+        match &handle.data {
+            NodeData::Text { contents } => out.push_str(&contents.borrow()),
+            NodeData::Element { .. } if is_skipped(handle) => {}
+            NodeData::Element { .. } => match tag_name(handle).as_deref() {
+                Some("br") => out.push(' '),
+                Some("img") => {
+                    if let Some(image) = self.image(handle) {
+                        self.deferred_images.push(image);
+                    }
+                }
+                Some("a") => {
+                    let mut text = String::new();
+                    for child in handle.children.borrow().iter() {
+                        self.inline(child, &mut text);
+                    }
+                    let text = collapse(&text);
+                    out.push(' ');
+                    out.push_str(&text);
+                    if let Some(url) = attr(handle, "href").and_then(|h| self.resolve(&h)) {
+                        if !text.is_empty() {
+                            self.links.push(Link { url, text });
+                            out.push_str(&format!(" [{}]", self.links.len()));
+                        }
+                    }
+                    out.push(' ');
+                }
+                _ => {
+                    for child in handle.children.borrow().iter() {
+                        self.inline(child, out);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    fn inline_text(&mut self, handle: &Handle) -> String {
+        let mut text = String::new();
+        for child in handle.children.borrow().iter() {
+            self.inline(child, &mut text);
+        }
+        collapse(&text).replace(" ,", ",").replace(" .", ".")
+    }
+
+    fn push_text(&mut self, block: impl FnOnce(String) -> ContentBlock, text: String) {
+        if !text.is_empty() {
+            self.blocks.push(block(text));
+        }
+        for (alt, src) in std::mem::take(&mut self.deferred_images) {
+            self.push_image(alt, &src);
+        }
+    }
+
+    /// Children of a block element: loose text and inline elements are
+    /// gathered into paragraphs between the block children
+    fn container(&mut self, handle: &Handle) {
+        let mut pending = String::new();
+        for child in handle.children.borrow().iter() {
+            let is_inline = match &child.data {
+                NodeData::Text { .. } => true,
+                NodeData::Element { .. } => {
+                    tag_name(child).is_some_and(|t| INLINE_TAGS.contains(&t.as_str()) || t == "img")
+                }
+                _ => continue,
+            };
+            if is_inline {
+                self.inline(child, &mut pending);
+            } else {
+                let text = collapse(&std::mem::take(&mut pending));
+                self.push_text(ContentBlock::Paragraph, text);
+                self.block(child);
+            }
+        }
+        let text = collapse(&pending);
+        self.push_text(ContentBlock::Paragraph, text);
+    }
+
+    fn block(&mut self, handle: &Handle) {
+        if is_skipped(handle) {
+            return;
+        }
+        let Some(tag) = tag_name(handle) else { return };
+        match tag.as_str() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = tag[1..].parse().unwrap_or(3);
+                let text = self.inline_text(handle);
+                self.push_text(|text| ContentBlock::Heading { level, text }, text);
+            }
+            "p" | "dt" | "dd" | "figcaption" | "caption" => {
+                let text = self.inline_text(handle);
+                self.push_text(ContentBlock::Paragraph, text);
+            }
+            "blockquote" => {
+                let text = self.inline_text(handle);
+                self.push_text(ContentBlock::Quote, text);
+            }
+            "pre" => {
+                let mut text = String::new();
+                raw_text(handle, &mut text);
+                let text = text.trim_matches('\n').to_string();
+                self.push_text(ContentBlock::Code, text);
+            }
+            "li" => {
+                let text = self.inline_text(handle);
+                self.push_text(ContentBlock::ListItem, text);
+            }
+            "hr" => self.blocks.push(ContentBlock::HorizontalRule),
+            "img" => {
+                if let Some((alt, src)) = self.image(handle) {
+                    self.push_image(alt, &src);
+                }
+            }
+            "tr" => {
+                let cells: Vec<String> = handle
+                    .children
+                    .borrow()
+                    .iter()
+                    .filter(|c| matches!(tag_name(c).as_deref(), Some("td") | Some("th")))
+                    .map(|c| self.inline_text(c))
+                    .filter(|t| !t.is_empty())
+                    .collect();
+                self.push_text(ContentBlock::Paragraph, cells.join("  |  "));
+            }
+            _ => self.container(handle),
+        }
+    }
+}
+
+/// Run the readability pass over an HTML page fetched from `base`
+pub fn extract(html: &str, base: Option<&Url>) -> Article {
+    let dom = parse_document(RcDom::default(), Default::default())
+        .from_utf8()
+        .read_from(&mut html.as_bytes())
+        .expect("reading from memory can't fail");
+
+    let title = find_first(&dom.document, "title")
+        .map(|t| {
+            let mut text = String::new();
+            raw_text(&t, &mut text);
+            collapse(&text)
+        })
+        .unwrap_or_default();
+
+    let mut extractor = Extractor {
+        base: base.cloned(),
+        blocks: Vec::new(),
+        links: Vec::new(),
+        images: Vec::new(),
+        deferred_images: Vec::new(),
+    };
+    if let Some(root) = article_root(&dom.document) {
+        extractor.container(&root);
+    }
+
+    let title = if title.is_empty() {
+        extractor
+            .blocks
+            .iter()
+            .find_map(|b| match b {
+                ContentBlock::Heading { text, .. } => Some(text.clone()),
+                _ => None,
+            })
+            .unwrap_or_else(|| "untitled".into())
+    } else {
+        title
+    };
+    Article { title, blocks: extractor.blocks, links: extractor.links, images: extractor.images }
+}
+
+/// A plain text page: one paragraph per blank-line-separated run
+pub fn plain_text(text: &str, title: &str) -> Article {
+    let blocks = text
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| ContentBlock::Paragraph(p.to_string()))
+        .collect();
+    Article { title: title.to_string(), blocks, links: Vec::new(), images: Vec::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extracts_article() {
+        let body = "A paragraph of article text, long enough to count, with commas, and more. ".repeat(3);
+        let html = format!(
+            "<html><head><title>a page</title><script>var x = 1;</script></head><body>\
+             <nav><a href=\"/home\">home</a> <a href=\"/about\">about</a></nav>\
+             <div id=\"content\"><h2>heading</h2><p>{body}</p><p>{body} see <a href=\"next.html\">the next page</a>.</p>\
+             <img src=\"pic.png\" alt=\"a picture\"></div>\
+             <footer>copyright</footer></body></html>"
+        );
+        let base = Url::parse("https://example.org/dir/page.html").unwrap();
+        let article = extract(&html, Some(&base));
+        assert_eq!(article.title, "a page");
+        assert!(matches!(&article.blocks[0], ContentBlock::Heading { level: 2, text } if text == "heading"));
+        assert_eq!(article.links, vec![Link { url: "https://example.org/dir/next.html".into(), text: "the next page".into() }]);
+        assert!(matches!(&article.blocks[2], ContentBlock::Paragraph(p) if p.ends_with("the next page [1].")));
+        assert_eq!(article.images, vec![PendingImage { block: 3, url: "https://example.org/dir/pic.png".into() }]);
+        assert_eq!(article.blocks.len(), 4);
+    }
+}
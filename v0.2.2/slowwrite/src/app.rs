@@ -4,14 +4,44 @@
 //! double-click-drag word selection. Per-character styling is maintained
 //! for save/load but TextEdit renders plain visually.
 
+use crate::booklet;
+use crate::merge::{self, DataSource};
 use crate::rich_text::{FontFamily, RichDocument, load_rich_document, save_rich_document, save_as_rtf, load_rtf};
-use egui::{Align2, Context, Key, Stroke};
+use crate::templates::{self, Template};
+use egui::{Context, Key, Stroke};
+use slowcore::confirm::CloseGuard;
+use slowcore::drag::DropTarget;
+use slowcore::ipc::{self, Command, Reply};
+use slowcore::autocorrect::Autocorrect;
+use slowcore::log;
 use slowcore::repaint::RepaintController;
-use slowcore::storage::{config_dir, documents_dir, FileBrowser, RecentFiles};
-use slowcore::text_edit::WordDragState;
+use slowcore::storage::{self, documents_dir, RecentFiles, KEEP_VERSIONS};
+use slowcore::text_edit::{InputMethod, InputMode, WordDragState};
 use slowcore::theme::{consume_special_keys, menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
+use slowcore::tts::{self, Speaker};
+use slowcore::widgets::{about_dialog, document_title_bar, status_bar, window_control_buttons, AboutInfo, FileDialog, FileDialogMode, FileDialogResult, WindowAction};
+use std::ops::{Range, RangeInclusive};
 use std::path::PathBuf;
+use std::time::Duration;
+
+const ABOUT: AboutInfo = AboutInfo {
+    name: "slowWrite",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "rich text editor for slowOS",
+    formats: &[".txt, .md (plain text)", ".rtf (import only)", ".swd (slowWrite rich document)"],
+    features: &[
+        "per-character styling",
+        "bold, italic, underline, strikethrough",
+        "variable font sizes (8-72pt)",
+        "proportional & monospace fonts",
+        "double-click-drag word selection",
+        "letter, memo and CV templates",
+        "booklet PDFs for zines",
+        "mail merge from CSV",
+        "scriptable with slowctl",
+    ],
+    ..AboutInfo::EMPTY
+};
 
 /// RTF stripping for importing existing .rtf files
 fn strip_rtf(input: &str) -> String {
@@ -120,10 +150,19 @@ fn strip_rtf(input: &str) -> String {
     final_result
 }
 
-#[derive(Clone, Copy, PartialEq)]
-enum FileBrowserMode {
-    Open,
-    Save,
+/// Characters of the paragraph holding character `index`: from its first
+/// to the newline that ends it, or the end of the text
+fn paragraph_at(text: &str, index: usize) -> RangeInclusive<usize> {
+    let mut start = 0;
+    for (i, c) in text.chars().enumerate() {
+        if c == '\n' {
+            if i >= index {
+                return start..=i;
+            }
+            start = i + 1;
+        }
+    }
+    start..=text.chars().count()
 }
 
 /// Editor mode: plain text (default) or rich text
@@ -133,6 +172,17 @@ pub enum EditorMode {
     RichText,
 }
 
+/// The mail merge dialog
+struct MergeDialog {
+    source: Option<DataSource>,
+    /// Why the data couldn't be read
+    error: Option<String>,
+    /// Row shown in the preview
+    row: usize,
+    /// Print the copies rather than save them as documents
+    print: bool,
+}
+
 /// Application state
 pub struct SlowWriteApp {
     doc: RichDocument,
@@ -140,13 +190,9 @@ pub struct SlowWriteApp {
     file_title: String,
     modified: bool,
     recent_files: RecentFiles,
-    show_file_browser: bool,
-    file_browser: FileBrowser,
-    file_browser_mode: FileBrowserMode,
-    save_filename: String,
+    file_dialog: Option<FileDialog>,
     show_about: bool,
-    show_close_confirm: bool,
-    close_confirmed: bool,
+    close_guard: CloseGuard,
     show_shortcuts: bool,
     /// Show the formatting toolbar (only in rich text mode)
     show_toolbar: bool,
@@ -156,44 +202,85 @@ pub struct SlowWriteApp {
     mode: EditorMode,
     /// Word-selection drag state
     word_drag: WordDragState,
+    /// Dead keys and pinyin/romaji for the editor
+    input: InputMethod,
+    /// Typo fixes and capitals as the editor is typed in
+    autocorrect: Autocorrect,
     repaint: RepaintController,
+    /// Reads the document aloud
+    speaker: Speaker,
+    /// Sentence being read aloud, as a byte range of the text
+    spoken: Option<Range<usize>>,
+    /// The spoken sentence changed; scroll it into view
+    scroll_to_spoken: bool,
+    /// Text cursor as a byte offset, where reading aloud starts
+    cursor_byte: usize,
+    /// Why reading aloud stopped, if it went wrong
+    speech_error: Option<String>,
+    /// Keep the caret's line in the middle of the window
+    typewriter: bool,
+    /// Caret position the view was last centred on
+    typewriter_caret: Option<usize>,
+    /// Fade every paragraph but the one being written
+    focus_mode: bool,
+    /// Booklet export dialog: half-letter pages in the document, when open
+    booklet_pages: Option<usize>,
+    /// Sheets folded together, `None` for all of them
+    booklet_signature: Option<usize>,
+    booklet_crop_marks: bool,
+    /// Template gallery: every template, when open
+    templates: Option<Vec<Template>>,
+    selected_template: usize,
+    /// Asking before deleting the selected template
+    deleting_template: bool,
+    /// Save-as-template dialog: the name being typed, when open
+    template_name: Option<String>,
+    merge: Option<MergeDialog>,
+    /// The file dialog is choosing merge data, not a document to open
+    picking_merge_data: bool,
 }
 
 impl SlowWriteApp {
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let config_path = config_dir("slowwrite").join("recent.json");
-        let recent_files =
-            RecentFiles::load(&config_path).unwrap_or_else(|_| RecentFiles::new(10));
-
         Self {
             doc: RichDocument::new(),
             file_path: None,
             file_title: "untitled".to_string(),
             modified: false,
-            recent_files,
-            show_file_browser: false,
-            file_browser: FileBrowser::new(documents_dir())
-                .with_filter(vec![
-                    "txt".to_string(),
-                    "md".to_string(),
-                    "rtf".to_string(),
-                    "swd".to_string(),
-                ]),
-            file_browser_mode: FileBrowserMode::Open,
-            save_filename: String::new(),
+            recent_files: RecentFiles::for_app("slowwrite"),
+            file_dialog: None,
             show_about: false,
-            show_close_confirm: false,
-            close_confirmed: false,
+            close_guard: CloseGuard::new(),
             show_shortcuts: false,
             show_toolbar: true,
             font_sizes: vec![8.0, 10.0, 12.0, 14.0, 16.0, 18.0, 20.0, 24.0, 28.0, 32.0, 36.0, 48.0, 64.0, 72.0],
             mode: EditorMode::PlainText,
             word_drag: WordDragState::new(),
+            input: InputMethod::new(),
+            autocorrect: Autocorrect::new("slowwrite"),
             repaint: RepaintController::new(),
+            speaker: Speaker::new("slowwrite"),
+            spoken: None,
+            scroll_to_spoken: false,
+            cursor_byte: 0,
+            speech_error: None,
+            typewriter: false,
+            typewriter_caret: None,
+            focus_mode: false,
+            booklet_pages: None,
+            booklet_signature: None,
+            booklet_crop_marks: true,
+            templates: None,
+            selected_template: 0,
+            deleting_template: false,
+            template_name: None,
+            merge: None,
+            picking_merge_data: false,
         }
     }
 
     fn new_document(&mut self) {
+        self.stop_reading_aloud();
         self.doc = RichDocument::new();
         self.file_path = None;
         self.file_title = "untitled".to_string();
@@ -201,7 +288,30 @@ impl SlowWriteApp {
         self.word_drag = WordDragState::new();
     }
 
+    /// Start an untitled copy of a template
+    fn new_from_template(&mut self, template: &Template) {
+        self.new_document();
+        self.doc = template.doc.clone();
+        self.mode = EditorMode::RichText;
+        self.show_toolbar = true;
+    }
+
+    fn show_template_gallery(&mut self) {
+        self.templates = Some(templates::load_all());
+        self.selected_template = 0;
+        self.deleting_template = false;
+    }
+
+    fn save_as_template(&mut self, name: &str) {
+        let message = match templates::save(name, &self.doc) {
+            Ok(name) => format!("saved template \"{}\"", name),
+            Err(e) => format!("could not save template: {}", e),
+        };
+        slowcore::notify::post("slowwrite", &message);
+    }
+
     pub fn open_file(&mut self, path: PathBuf) {
+        self.stop_reading_aloud();
         let ext = path
             .extension()
             .map(|e| e.to_string_lossy().to_lowercase())
@@ -219,7 +329,7 @@ impl SlowWriteApp {
                         self.mode = EditorMode::RichText;
                     }
                     Err(e) => {
-                        eprintln!("failed to open: {}", e);
+                        log::error!("failed to open: {}", e);
                         return;
                     }
                 }
@@ -237,7 +347,7 @@ impl SlowWriteApp {
                         }
                     }
                     Err(e) => {
-                        eprintln!("failed to open RTF: {}", e);
+                        log::error!("failed to open RTF: {}", e);
                         return;
                     }
                 }
@@ -248,7 +358,7 @@ impl SlowWriteApp {
                         self.doc = RichDocument::from_plain_text(text);
                     }
                     Err(e) => {
-                        eprintln!("failed to open: {}", e);
+                        log::error!("failed to open: {}", e);
                         return;
                     }
                 }
@@ -262,8 +372,7 @@ impl SlowWriteApp {
         self.file_path = Some(path.clone());
         self.modified = false;
         self.word_drag = WordDragState::new();
-        self.recent_files.add(path);
-        self.save_recent_files();
+        self.recent_files.record(&path);
     }
 
     fn save_content_for_path(&self, path: &std::path::Path) -> String {
@@ -281,8 +390,8 @@ impl SlowWriteApp {
     fn save_document(&mut self) {
         if let Some(ref path) = self.file_path {
             let content = self.save_content_for_path(path);
-            if let Err(e) = std::fs::write(path, &content) {
-                eprintln!("failed to save: {}", e);
+            if let Err(e) = storage::save_versioned(path, content.as_bytes(), KEEP_VERSIONS) {
+                log::error!("failed to save: {}", e);
             } else {
                 self.modified = false;
             }
@@ -293,8 +402,8 @@ impl SlowWriteApp {
 
     fn save_document_as(&mut self, path: PathBuf) {
         let content = self.save_content_for_path(&path);
-        if let Err(e) = std::fs::write(&path, &content) {
-            eprintln!("failed to save: {}", e);
+        if let Err(e) = storage::save_versioned(&path, content.as_bytes(), KEEP_VERSIONS) {
+            log::error!("failed to save: {}", e);
         } else {
             self.file_title = path
                 .file_name()
@@ -302,49 +411,139 @@ impl SlowWriteApp {
                 .unwrap_or("untitled".to_string());
             self.file_path = Some(path.clone());
             self.modified = false;
-            self.recent_files.add(path);
-            self.save_recent_files();
+            self.recent_files.record(&path);
+        }
+    }
+
+    /// Print to numbered PNGs in ~/Documents/Printouts
+    fn export_page_images(&self) {
+        let message = match slowcore::print::export_page_images(self.document_stem(), &self.doc.text) {
+            Ok(pages) if pages.len() == 1 => "printed 1 page to Printouts".to_string(),
+            Ok(pages) => format!("printed {} pages to Printouts", pages.len()),
+            Err(e) => format!("could not print: {}", e),
+        };
+        slowcore::notify::post("slowwrite", &message);
+    }
+
+    fn document_stem(&self) -> &str {
+        self.file_title.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(&self.file_title)
+    }
+
+    /// Print as a folded booklet to a PDF in ~/Documents/Printouts
+    fn export_booklet(&self) -> Result<String, String> {
+        let (path, sheets) = booklet::export(self.document_stem(), &self.doc.text, self.booklet_signature, self.booklet_crop_marks)?;
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        Ok(format!("{} to Printouts, {} sheet{}", name, sheets, if sheets == 1 { "" } else { "s" }))
+    }
+
+    fn show_booklet_dialog(&mut self) {
+        let pages = slowcore::print::Printer::new(slowcore::print::PageSetup::HALF_LETTER).layout(&self.doc.text).len();
+        self.booklet_pages = Some(pages);
+    }
+
+    fn show_merge_dialog(&mut self) {
+        self.merge = Some(MergeDialog { source: None, error: None, row: 0, print: false });
+    }
+
+    fn show_merge_data_dialog(&mut self) {
+        self.file_dialog = Some(
+            FileDialog::open(documents_dir())
+                .title("choose merge data")
+                .filter(&["csv", "tsv"]),
+        );
+        self.picking_merge_data = true;
+    }
+
+    /// Put `{{field}}` in at the cursor, in the style being typed
+    fn insert_placeholder(&mut self, field: &str) {
+        let placeholder = format!("{{{{{}}}}}", field);
+        let at = self.cursor_byte.min(self.doc.text.len());
+        let index = self.doc.text[..at].chars().count().min(self.doc.styles.len());
+        let style = self.doc.cursor_style.clone();
+        self.doc.styles.splice(index..index, std::iter::repeat_n(style, placeholder.chars().count()));
+        self.doc.text.insert_str(at, &placeholder);
+        self.cursor_byte = at + placeholder.len();
+        self.modified = true;
+    }
+
+    /// Save or print a copy of the document for every row of `source`
+    fn run_merge(&self, source: &DataSource, print: bool) -> Result<String, String> {
+        let rows = source.rows.len();
+        let copies = format!("{} cop{}", rows, if rows == 1 { "y" } else { "ies" });
+        if print {
+            let pages = merge::print_pages(&self.doc, source, self.document_stem())?;
+            Ok(format!("printed {} on {} page{} to Printouts", copies, pages, if pages == 1 { "" } else { "s" }))
+        } else {
+            let folder = merge::write_documents(&self.doc, source, self.document_stem(), self.mode == EditorMode::RichText)?;
+            let name = folder.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            Ok(format!("saved {} in {}", copies, name))
         }
     }
 
     fn show_open_dialog(&mut self) {
-        self.file_browser = FileBrowser::new(documents_dir()).with_filter(vec![
-            "txt".to_string(),
-            "md".to_string(),
-            "rtf".to_string(),
-            "swd".to_string(),
-        ]);
-        self.file_browser_mode = FileBrowserMode::Open;
-        self.show_file_browser = true;
+        self.file_dialog = Some(
+            FileDialog::open(documents_dir())
+                .title("open document")
+                .filter(&["txt", "md", "rtf", "swd"]),
+        );
     }
 
     fn show_save_as_dialog(&mut self) {
-        self.file_browser = FileBrowser::new(documents_dir());
-        self.file_browser_mode = FileBrowserMode::Save;
-        self.save_filename = self.file_title.clone();
-        let has_ext = self.save_filename.ends_with(".txt")
-            || self.save_filename.ends_with(".md")
-            || self.save_filename.ends_with(".swd")
-            || self.save_filename.ends_with(".rtf");
+        let mut filename = self.file_title.clone();
+        let has_ext = filename.ends_with(".txt")
+            || filename.ends_with(".md")
+            || filename.ends_with(".swd")
+            || filename.ends_with(".rtf");
+        let ext = match self.mode {
+            EditorMode::RichText => "rtf",
+            EditorMode::PlainText => "txt",
+        };
         if !has_ext {
-            match self.mode {
-                EditorMode::RichText => self.save_filename.push_str(".rtf"),
-                EditorMode::PlainText => self.save_filename.push_str(".txt"),
-            }
+            filename = format!("{}.{}", filename, ext);
         }
-        self.show_file_browser = true;
+        self.file_dialog = Some(
+            FileDialog::save(documents_dir(), &filename)
+                .title("save document")
+                .default_extension(ext),
+        );
     }
 
-    fn save_recent_files(&self) {
-        let config_path = config_dir("slowwrite").join("recent.json");
-        let _ = self.recent_files.save(&config_path);
+    /// Read aloud from the cursor (from the top when it's at the end)
+    fn toggle_reading_aloud(&mut self) {
+        if self.speaker.is_active() {
+            if self.speaker.is_paused() { self.speaker.resume(); } else { self.speaker.pause(); }
+            return;
+        }
+        let from = if self.cursor_byte >= self.doc.text.trim_end().len() { 0 } else { self.cursor_byte };
+        self.speaker.play(&self.doc.text, from);
+        self.speech_error = None;
     }
 
-    fn display_title(&self) -> String {
-        if self.modified {
-            format!("{}*", self.file_title)
+    fn toggle_typewriter(&mut self) {
+        self.typewriter = !self.typewriter;
+        // Centre the caret straight away
+        self.typewriter_caret = None;
+    }
+
+    fn stop_reading_aloud(&mut self) {
+        self.speaker.stop();
+        self.spoken = None;
+    }
+
+    /// Follow the speech with the highlighted sentence
+    fn update_reading_aloud(&mut self, ctx: &Context) {
+        if !self.speaker.is_active() {
+            return;
+        }
+        let spoken = self.speaker.current();
+        if spoken.is_some() {
+            ctx.request_repaint_after(Duration::from_millis(250));
         } else {
-            self.file_title.clone()
+            self.speech_error = self.speaker.error();
+        }
+        if spoken != self.spoken {
+            self.scroll_to_spoken = spoken.is_some();
+            self.spoken = spoken;
         }
     }
 
@@ -369,10 +568,16 @@ impl SlowWriteApp {
                     egui::Event::Key { key, pressed: true, .. } => {
                         match key {
                             // File operations
+                            Key::N if cmd && shift => { handled = true; actions.push(Box::new(|s| s.show_template_gallery())); }
                             Key::N if cmd => { handled = true; actions.push(Box::new(|s| s.new_document())); }
                             Key::O if cmd => { handled = true; actions.push(Box::new(|s| s.show_open_dialog())); }
                             Key::S if cmd && shift => { handled = true; actions.push(Box::new(|s| s.show_save_as_dialog())); }
                             Key::S if cmd => { handled = true; actions.push(Box::new(|s| s.save_document())); }
+                            // Read aloud
+                            Key::R if cmd && shift => { handled = true; actions.push(Box::new(|s| s.toggle_reading_aloud())); }
+                            // Distraction-free writing
+                            Key::T if cmd && shift => { handled = true; actions.push(Box::new(|s| s.toggle_typewriter())); }
+                            Key::F if cmd && shift => { handled = true; actions.push(Box::new(|s| s.focus_mode = !s.focus_mode)); }
                             // Formatting (rich text mode)
                             Key::B if cmd => { handled = true; actions.push(Box::new(|s| {
                                 s.doc.cursor_style.bold = !s.doc.cursor_style.bold;
@@ -400,6 +605,71 @@ impl SlowWriteApp {
         }
     }
 
+    /// Carry out commands from scripts (slowctl). Text typed while the
+    /// editor doesn't have the keyboard goes in at the cursor. Queries:
+    /// "document" (its path), "modified", "text" and "words". A template
+    /// is picked by name: `["file", "new from template", "memo"]`.
+    fn handle_commands(&mut self, ctx: &Context) {
+        for request in ipc::take() {
+            let reply = if request.is_menu("file", "new") {
+                self.new_document();
+                Reply::Done
+            } else if request.is_menu("file", "export booklet") {
+                match self.export_booklet() {
+                    Ok(_) => Reply::Done,
+                    Err(e) => Reply::Failed(e),
+                }
+            } else if request.is_menu("file", "save") {
+                if self.file_path.is_some() {
+                    self.save_document();
+                }
+                match (&self.file_path, self.modified) {
+                    (None, _) => Reply::Failed("the document has no file yet".into()),
+                    (Some(_), true) => Reply::Failed("could not save".into()),
+                    (Some(_), false) => Reply::Done,
+                }
+            } else {
+                match &request.command {
+                    Command::Open { path } => {
+                        self.open_file(path.clone());
+                        if self.file_path.as_ref() == Some(path) {
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                            Reply::Done
+                        } else {
+                            Reply::Failed(format!("could not open {}", path.display()))
+                        }
+                    }
+                    Command::Type { text } => {
+                        self.stop_reading_aloud();
+                        let at = self.cursor_byte.min(self.doc.text.len());
+                        self.doc.text.insert_str(at, text);
+                        self.cursor_byte = at + text.len();
+                        self.modified = true;
+                        Reply::Done
+                    }
+                    Command::Query { key } => match key.as_str() {
+                        "document" => Reply::Value(self.file_path.as_ref().map(|p| p.display().to_string()).into()),
+                        "modified" => Reply::Value(self.modified.into()),
+                        "text" => Reply::Value(self.doc.text.clone().into()),
+                        "words" => Reply::Value(self.doc.word_count().into()),
+                        _ => Reply::Failed(format!("nothing called \"{}\" to ask about", key)),
+                    },
+                    Command::Menu { item } if item.len() == 3 && item[1].eq_ignore_ascii_case("new from template") => {
+                        match templates::load_all().into_iter().find(|t| t.name.eq_ignore_ascii_case(&item[2])) {
+                            Some(template) => {
+                                self.new_from_template(&template);
+                                Reply::Done
+                            }
+                            None => Reply::Failed(format!("no template called \"{}\"", item[2])),
+                        }
+                    }
+                    Command::Menu { .. } => Reply::Failed("no such menu item".into()),
+                }
+            };
+            request.reply(reply);
+        }
+    }
+
     fn render_menu_bar(&mut self, ui: &mut egui::Ui) -> WindowAction {
         let mut action = WindowAction::None;
         menu_bar(ui, |ui| {
@@ -409,26 +679,17 @@ impl SlowWriteApp {
                     self.new_document();
                     ui.close_menu();
                 }
+                if ui.button("new from template... \u{21e7}\u{2318}n").clicked() {
+                    self.show_template_gallery();
+                    ui.close_menu();
+                }
                 if ui.button("open...    \u{2318}o").clicked() {
                     self.show_open_dialog();
                     ui.close_menu();
                 }
-                ui.menu_button("open recent", |ui| {
-                    if self.recent_files.files.is_empty() {
-                        ui.label("no recent files");
-                    } else {
-                        for path in self.recent_files.files.clone() {
-                            let name = path
-                                .file_name()
-                                .map(|n| n.to_string_lossy().to_string())
-                                .unwrap_or("unknown".to_string());
-                            if ui.button(&name).clicked() {
-                                self.open_file(path);
-                                ui.close_menu();
-                            }
-                        }
-                    }
-                });
+                if let Some(path) = self.recent_files.menu(ui) {
+                    self.open_file(path);
+                }
                 ui.separator();
                 if ui.button("save       \u{2318}s").clicked() {
                     self.save_document();
@@ -438,6 +699,36 @@ impl SlowWriteApp {
                     self.show_save_as_dialog();
                     ui.close_menu();
                 }
+                if ui.button("save as template...").clicked() {
+                    self.template_name = Some(self.document_stem().to_string());
+                    ui.close_menu();
+                }
+                let saved_before = self.file_path.as_ref().filter(|p| !storage::versions(p).is_empty()).cloned();
+                if ui
+                    .add_enabled(saved_before.is_some() && !self.modified, egui::Button::new("revert to previous save"))
+                    .clicked()
+                {
+                    if let Some(path) = saved_before {
+                        match storage::restore_version(&path, 1) {
+                            Ok(()) => self.open_file(path),
+                            Err(e) => log::error!("failed to revert: {}", e),
+                        }
+                    }
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui.button("export page images").clicked() {
+                    self.export_page_images();
+                    ui.close_menu();
+                }
+                if ui.button("export booklet PDF...").clicked() {
+                    self.show_booklet_dialog();
+                    ui.close_menu();
+                }
+                if ui.button("mail merge...").clicked() {
+                    self.show_merge_dialog();
+                    ui.close_menu();
+                }
             });
 
             ui.menu_button("edit", |ui| {
@@ -481,6 +772,15 @@ impl SlowWriteApp {
                     });
                     ui.close_menu();
                 }
+                ui.separator();
+                ui.menu_button("input", |ui| {
+                    for mode in InputMode::ALL {
+                        if ui.radio(self.input.mode == mode, mode.name()).clicked() {
+                            self.input.set_mode(mode);
+                            ui.close_menu();
+                        }
+                    }
+                });
             });
 
             ui.menu_button("view", |ui| {
@@ -495,6 +795,43 @@ impl SlowWriteApp {
                     self.show_toolbar = true;
                     ui.close_menu();
                 }
+                ui.separator();
+                let typewriter_label = if self.typewriter { "> typewriter  \u{21e7}\u{2318}t" } else { "  typewriter  \u{21e7}\u{2318}t" };
+                let focus_label = if self.focus_mode { "> focus       \u{21e7}\u{2318}f" } else { "  focus       \u{21e7}\u{2318}f" };
+                if ui.button(typewriter_label).clicked() {
+                    self.toggle_typewriter();
+                    ui.close_menu();
+                }
+                if ui.button(focus_label).clicked() {
+                    self.focus_mode = !self.focus_mode;
+                    ui.close_menu();
+                }
+            });
+
+            ui.menu_button("speech", |ui| {
+                let label = if !self.speaker.is_active() {
+                    "read aloud  \u{21e7}\u{2318}r"
+                } else if self.speaker.is_paused() {
+                    "resume      \u{21e7}\u{2318}r"
+                } else {
+                    "pause       \u{21e7}\u{2318}r"
+                };
+                if ui.button(label).clicked() {
+                    self.toggle_reading_aloud();
+                    ui.close_menu();
+                }
+                if ui.add_enabled(self.speaker.is_active(), egui::Button::new("stop reading")).clicked() {
+                    self.stop_reading_aloud();
+                    ui.close_menu();
+                }
+                ui.separator();
+                for speed in tts::SPEEDS {
+                    let selected = (self.speaker.speed() - speed).abs() < 0.01;
+                    if ui.radio(selected, format!("{}\u{d7} speed", speed)).clicked() {
+                        self.speaker.set_speed(speed);
+                        ui.close_menu();
+                    }
+                }
             });
 
             if self.mode == EditorMode::RichText {
@@ -609,135 +946,352 @@ impl SlowWriteApp {
     /// Render the editor using egui's built-in TextEdit::multiline
     fn render_editor(&mut self, ui: &mut egui::Ui) {
         let available = ui.available_size();
+        // Room above the first line and below the last, so either can
+        // come to the middle in typewriter mode
+        let overscroll = if self.typewriter { available.y / 2.0 } else { 0.0 };
 
         egui::ScrollArea::vertical()
             .auto_shrink([false, false])
             .show(ui, |ui| {
+                ui.add_space(overscroll);
+                self.input.before(ui.ctx());
+                self.autocorrect.before(ui.ctx());
                 let output = egui::TextEdit::multiline(&mut self.doc.text)
                     .font(egui::FontId::proportional(16.0))
                     .desired_width(available.x)
                     .desired_rows((available.y / 20.0).max(4.0) as usize)
                     .frame(false)
                     .show(ui);
+                let corrected = self.autocorrect.after(ui, &output, &mut self.doc.text);
 
                 // Detect text changes from TextEdit (typing, paste, delete, etc.)
-                if output.response.changed() {
+                if output.response.changed() || corrected {
                     self.modified = true;
+                    // The spoken offsets no longer match the text
+                    self.stop_reading_aloud();
+                }
+                if let Some(range) = &output.cursor_range {
+                    let index = range.primary.ccursor.index;
+                    self.cursor_byte = self.doc.text.char_indices().nth(index).map_or(self.doc.text.len(), |(b, _)| b);
+                    if self.typewriter && self.typewriter_caret != Some(index) {
+                        let caret = output.galley.pos_from_cursor(&range.primary).translate(output.galley_pos.to_vec2());
+                        ui.scroll_to_rect(caret, Some(egui::Align::Center));
+                        self.typewriter_caret = Some(index);
+                    }
+                }
+
+                // Fade every row outside the caret's paragraph
+                if let Some(range) = output.cursor_range.filter(|_| self.focus_mode) {
+                    let paragraph = paragraph_at(&self.doc.text, range.primary.ccursor.index);
+                    let painter = ui.painter_at(output.text_clip_rect);
+                    let mut row_start = 0;
+                    for row in &output.galley.rows {
+                        if !paragraph.contains(&row_start) {
+                            let rect = row.rect.translate(output.galley_pos.to_vec2());
+                            let rect = egui::Rect::from_x_y_ranges(output.text_clip_rect.x_range(), rect.y_range());
+                            slowcore::dither::draw_dither_fade(&painter, rect);
+                        }
+                        row_start += row.char_count_including_newline();
+                    }
+                }
+
+                // Underline the sentence being read aloud, row by row
+                if let Some(spoken) = &self.spoken {
+                    let start = self.doc.text[..spoken.start].chars().count();
+                    let end = start + self.doc.text[spoken.clone()].chars().count();
+                    let painter = ui.painter_at(output.text_clip_rect);
+                    let mut row_start = 0;
+                    let mut spoken_rect: Option<egui::Rect> = None;
+                    for row in &output.galley.rows {
+                        let row_end = row_start + row.char_count_excluding_newline();
+                        let (from, to) = (start.max(row_start), end.min(row_end));
+                        if from < to {
+                            let x0 = output.galley_pos.x + row.x_offset(from - row_start);
+                            let x1 = output.galley_pos.x + row.x_offset(to - row_start);
+                            let y = output.galley_pos.y + row.rect.bottom();
+                            painter.hline(x0..=x1, y, Stroke::new(2.0, SlowColors::BLACK));
+                            let rect = row.rect.translate(output.galley_pos.to_vec2());
+                            spoken_rect = Some(spoken_rect.map_or(rect, |r| r.union(rect)));
+                        }
+                        row_start += row.char_count_including_newline();
+                    }
+                    if let Some(rect) = spoken_rect.filter(|_| self.scroll_to_spoken) {
+                        ui.scroll_to_rect(rect, None);
+                        self.scroll_to_spoken = false;
+                    }
                 }
 
                 // Double-click-drag word selection (via slowcore)
                 self.word_drag.update(ui, &output, &self.doc.text);
+                self.input.after(ui, &output);
+                ui.add_space(overscroll);
             });
     }
 
-    fn render_file_browser(&mut self, ctx: &Context) {
-        let title = match self.file_browser_mode {
-            FileBrowserMode::Open => "open document",
-            FileBrowserMode::Save => "save document",
-        };
-        let resp = egui::Window::new(title)
-            .collapsible(false)
-            .resizable(false)
-            .default_width(380.0)
+    fn render_file_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.file_dialog else { return };
+        let mode = dialog.mode();
+        match dialog.show(ctx) {
+            FileDialogResult::Picked(path) => {
+                self.file_dialog = None;
+                match mode {
+                    FileDialogMode::Open if self.picking_merge_data => {
+                        if let Some(merge) = &mut self.merge {
+                            match DataSource::load(&path) {
+                                Ok(source) => {
+                                    merge.source = Some(source);
+                                    merge.error = None;
+                                    merge.row = 0;
+                                }
+                                Err(e) => merge.error = Some(format!("could not read {}: {}", path.display(), e)),
+                            }
+                        }
+                    }
+                    FileDialogMode::Open => self.open_file(path),
+                    FileDialogMode::Save => self.save_document_as(path),
+                }
+                self.picking_merge_data = false;
+            }
+            FileDialogResult::Cancelled => {
+                self.file_dialog = None;
+                self.picking_merge_data = false;
+            }
+            FileDialogResult::Pending => {}
+        }
+    }
+
+    fn render_booklet_dialog(&mut self, ctx: &Context) {
+        let Some(pages) = self.booklet_pages else { return };
+        let mut close = false;
+        let resp = egui::Window::new("export booklet")
+            .collapsible(false).resizable(false).default_width(300.0)
             .show(ctx, |ui| {
+                ui.label("half-letter pages, two to a letter sheet");
+                ui.add_space(8.0);
+                ui.label("signature:");
+                for (signature, name) in booklet::SIGNATURES {
+                    ui.radio_value(&mut self.booklet_signature, signature, name);
+                }
+                ui.add_space(4.0);
+                ui.checkbox(&mut self.booklet_crop_marks, "crop marks");
+                ui.add_space(8.0);
+                let sheets = booklet::impose(pages, self.booklet_signature).len();
+                ui.label(format!("{} page{} on {} sheet{}", pages, if pages == 1 { "" } else { "s" }, sheets, if sheets == 1 { "" } else { "s" }));
+                ui.label("print double-sided, flipping on the short edge");
+                ui.separator();
                 ui.horizontal(|ui| {
-                    ui.label("location:");
-                    ui.label(self.file_browser.current_dir.to_string_lossy().to_string());
+                    if ui.button("cancel").clicked() {
+                        close = true;
+                    }
+                    if ui.button("export").clicked() {
+                        let message = self.export_booklet().unwrap_or_else(|e| format!("could not export booklet: {}", e));
+                        slowcore::notify::post("slowwrite", &message);
+                        close = true;
+                    }
                 });
-                ui.separator();
-                egui::ScrollArea::vertical()
-                    .max_height(220.0)
-                    .show(ui, |ui| {
-                        let entries = self.file_browser.entries.clone();
-                        for (idx, entry) in entries.iter().enumerate() {
-                            let selected = self.file_browser.selected_index == Some(idx);
-                            let response = ui.add(
-                                slowcore::widgets::FileListItem::new(&entry.name, entry.is_directory)
-                                    .selected(selected),
-                            );
-                            if response.clicked() { self.file_browser.selected_index = Some(idx); }
-                            if response.double_clicked() {
-                                if entry.is_directory {
-                                    self.file_browser.navigate_to(entry.path.clone());
-                                } else if self.file_browser_mode == FileBrowserMode::Open {
-                                    let p = entry.path.clone();
-                                    self.show_file_browser = false;
-                                    self.open_file(p);
-                                }
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+        if close {
+            self.booklet_pages = None;
+        }
+    }
+
+    fn render_merge_dialog(&mut self, ctx: &Context) {
+        let Some(mut dialog) = self.merge.take() else { return };
+        let mut choose = false;
+        let mut insert = None;
+        let mut run = false;
+        let mut close = false;
+        let resp = egui::Window::new("mail merge")
+            .collapsible(false).resizable(false).default_width(340.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("data:");
+                    ui.label(dialog.source.as_ref().map_or("none".to_string(), |s| s.name()));
+                    if ui.button("choose...").clicked() {
+                        choose = true;
+                    }
+                });
+                if let Some(error) = &dialog.error {
+                    ui.label(error);
+                }
+                if let Some(source) = &dialog.source {
+                    let rows = source.rows.len();
+                    ui.label(format!("{} row{}", rows, if rows == 1 { "" } else { "s" }));
+                    ui.add_space(4.0);
+                    ui.label("put a field in at the cursor:");
+                    ui.horizontal_wrapped(|ui| {
+                        for field in &source.fields {
+                            if ui.small_button(field).clicked() {
+                                insert = Some(field.clone());
                             }
                         }
                     });
-                if self.file_browser_mode == FileBrowserMode::Save {
+                    let used = merge::placeholders(&self.doc.text);
+                    let missing: Vec<String> =
+                        used.iter().filter(|f| source.field(f).is_none()).map(|f| format!("{{{{{}}}}}", f)).collect();
+                    if used.is_empty() {
+                        ui.label("no {{fields}} in the document yet");
+                    } else if !missing.is_empty() {
+                        ui.label(format!("no data for {}", missing.join(", ")));
+                    }
                     ui.separator();
+
                     ui.horizontal(|ui| {
-                        ui.label("filename:");
-                        ui.text_edit_singleline(&mut self.save_filename);
+                        if ui.add_enabled(dialog.row > 0, egui::Button::new("<")).clicked() {
+                            dialog.row -= 1;
+                        }
+                        if ui.add_enabled(dialog.row + 1 < rows, egui::Button::new(">")).clicked() {
+                            dialog.row += 1;
+                        }
+                        ui.label(format!("row {} of {}", dialog.row + 1, rows));
                     });
+                    let filled = merge::fill(&self.doc, source, dialog.row).text;
+                    let preview: Vec<&str> = filled.lines().filter(|l| !l.trim().is_empty()).take(4).collect();
+                    egui::Frame::none()
+                        .stroke(Stroke::new(1.0, SlowColors::BLACK))
+                        .inner_margin(egui::Margin::same(6.0))
+                        .show(ui, |ui| {
+                            ui.set_width(ui.available_width());
+                            ui.label(preview.join("\n"));
+                        });
+                    ui.add_space(4.0);
+                    ui.radio_value(&mut dialog.print, false, "a document for each row");
+                    ui.radio_value(&mut dialog.print, true, "print them to Printouts");
+                } else {
+                    ui.label("a CSV or tab-separated file with the field names in its first row");
                 }
                 ui.separator();
                 ui.horizontal(|ui| {
-                    if ui.button("cancel").clicked() { self.show_file_browser = false; }
-                    let action_text = match self.file_browser_mode {
-                        FileBrowserMode::Open => "open",
-                        FileBrowserMode::Save => "save",
-                    };
-                    if ui.button(action_text).clicked() {
-                        match self.file_browser_mode {
-                            FileBrowserMode::Open => {
-                                if let Some(entry) = self.file_browser.selected_entry() {
-                                    if !entry.is_directory {
-                                        let p = entry.path.clone();
-                                        self.show_file_browser = false;
-                                        self.open_file(p);
-                                    }
-                                }
-                            }
-                            FileBrowserMode::Save => {
-                                if !self.save_filename.is_empty() {
-                                    let path = self.file_browser.save_directory().join(&self.save_filename);
-                                    self.show_file_browser = false;
-                                    self.save_document_as(path);
-                                }
-                            }
-                        }
+                    if ui.button("cancel").clicked() {
+                        close = true;
+                    }
+                    if ui.add_enabled(dialog.source.is_some(), egui::Button::new("merge")).clicked() {
+                        run = true;
                     }
                 });
             });
         if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+
+        if let Some(field) = insert {
+            self.insert_placeholder(&field);
+        }
+        if choose {
+            self.show_merge_data_dialog();
+        }
+        if run {
+            if let Some(source) = &dialog.source {
+                let message = self.run_merge(source, dialog.print).unwrap_or_else(|e| format!("could not merge: {}", e));
+                slowcore::notify::post("slowwrite", &message);
+            }
+        } else if !close {
+            self.merge = Some(dialog);
+        }
     }
 
-    fn render_about(&mut self, ctx: &Context) {
-        let max_height = (ctx.screen_rect().height() - 80.0).max(200.0);
-        let resp = egui::Window::new("about slowWrite")
-            .collapsible(false).resizable(false).default_width(300.0).max_height(max_height)
+    fn render_template_gallery(&mut self, ctx: &Context) {
+        let Some(list) = &self.templates else { return };
+        let selected = list.get(self.selected_template);
+        let mut open = None;
+        let mut delete = false;
+        let mut close = false;
+        let resp = egui::Window::new("new from template")
+            .collapsible(false).resizable(false).default_width(440.0)
             .show(ctx, |ui| {
-                egui::ScrollArea::vertical().max_height(max_height - 60.0).show(ui, |ui| {
-                    ui.vertical_centered(|ui| {
-                        ui.heading("slowWrite");
-                        ui.label("version 0.2.2");
-                        ui.add_space(8.0);
-                        ui.label("rich text editor for slowOS");
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        for (i, template) in list.iter().enumerate() {
+                            let card = template_card(ui, template, i == self.selected_template);
+                            if card.clicked() {
+                                self.selected_template = i;
+                                self.deleting_template = false;
+                            }
+                            if card.double_clicked() {
+                                open = Some(i);
+                            }
+                        }
                     });
-                    ui.add_space(8.0);
-                    ui.separator();
-                    ui.label("supported formats:");
-                    ui.label("  .txt, .md (plain text)");
-                    ui.label("  .rtf (import only)");
-                    ui.label("  .swd (slowWrite rich document)");
-                    ui.add_space(4.0);
-                    ui.label("features:");
-                    ui.label("  per-character styling");
-                    ui.label("  bold, italic, underline, strikethrough");
-                    ui.label("  variable font sizes (8-72pt)");
-                    ui.label("  proportional & monospace fonts");
-                    ui.label("  double-click-drag word selection");
-                    ui.add_space(8.0);
                 });
-                ui.vertical_centered(|ui| {
-                    if ui.button("ok").clicked() { self.show_about = false; }
+                ui.separator();
+                if self.deleting_template {
+                    ui.label(format!("delete the template \"{}\"?", selected.map_or("", |t| t.name.as_str())));
+                    ui.horizontal(|ui| {
+                        if ui.button("cancel").clicked() {
+                            self.deleting_template = false;
+                        }
+                        if ui.button("delete").clicked() {
+                            delete = true;
+                        }
+                    });
+                } else {
+                    ui.horizontal(|ui| {
+                        if ui.button("cancel").clicked() {
+                            close = true;
+                        }
+                        let own = selected.is_some_and(|t| !t.bundled);
+                        if ui.add_enabled(own, egui::Button::new("delete...")).clicked() {
+                            self.deleting_template = true;
+                        }
+                        if ui.add_enabled(selected.is_some(), egui::Button::new("new document")).clicked() {
+                            open = Some(self.selected_template);
+                        }
+                    });
+                }
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+
+        if let Some(template) = open.and_then(|i| list.get(i)).cloned() {
+            self.templates = None;
+            self.new_from_template(&template);
+        } else if delete {
+            if let Some(name) = selected.map(|t| t.name.clone()) {
+                if let Err(e) = templates::delete(&name) {
+                    slowcore::notify::post("slowwrite", &format!("could not delete template: {}", e));
+                }
+            }
+            let list = templates::load_all();
+            self.selected_template = self.selected_template.min(list.len().saturating_sub(1));
+            self.templates = Some(list);
+            self.deleting_template = false;
+        } else if close {
+            self.templates = None;
+        }
+    }
+
+    fn render_template_name_dialog(&mut self, ctx: &Context) {
+        let Some(name) = &mut self.template_name else { return };
+        let mut save = false;
+        let mut close = false;
+        let resp = egui::Window::new("save as template")
+            .collapsible(false).resizable(false).default_width(280.0)
+            .show(ctx, |ui| {
+                ui.label("name:");
+                let field = ui.text_edit_singleline(name);
+                field.request_focus();
+                if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    save = true;
+                }
+                if templates::exists(name) {
+                    ui.label("replaces the template of that name");
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("cancel").clicked() {
+                        close = true;
+                    }
+                    if ui.add_enabled(!storage::clean_name(name).is_empty(), egui::Button::new("save")).clicked() {
+                        save = true;
+                    }
                 });
             });
-        if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+        if save {
+            if let Some(name) = self.template_name.take() {
+                self.save_as_template(&name);
+            }
+        } else if close {
+            self.template_name = None;
+        }
     }
 
     fn render_shortcuts(&mut self, ctx: &Context) {
@@ -751,6 +1305,7 @@ impl SlowWriteApp {
                     ui.label(egui::RichText::new("File").strong());
                     ui.separator();
                     shortcut_row(ui, "\u{2318}N", "New document");
+                    shortcut_row(ui, "\u{21e7}\u{2318}N", "New from template");
                     shortcut_row(ui, "\u{2318}O", "Open file");
                     shortcut_row(ui, "\u{2318}S", "Save");
                     shortcut_row(ui, "\u{21e7}\u{2318}S", "Save as");
@@ -774,6 +1329,15 @@ impl SlowWriteApp {
                     shortcut_row(ui, "Dbl-click drag", "Select words");
                     shortcut_row(ui, "\u{21e7}+Click", "Extend selection");
                     ui.add_space(8.0);
+                    ui.label(egui::RichText::new("Speech").strong());
+                    ui.separator();
+                    shortcut_row(ui, "\u{21e7}\u{2318}R", "Read aloud / pause");
+                    ui.add_space(8.0);
+                    ui.label(egui::RichText::new("View").strong());
+                    ui.separator();
+                    shortcut_row(ui, "\u{21e7}\u{2318}T", "Typewriter scrolling");
+                    shortcut_row(ui, "\u{21e7}\u{2318}F", "Focus on paragraph");
+                    ui.add_space(8.0);
                 });
                 ui.vertical_centered(|ui| {
                     if ui.button("ok").clicked() { self.show_shortcuts = false; }
@@ -782,31 +1346,53 @@ impl SlowWriteApp {
         if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
     }
 
-    fn render_close_confirm(&mut self, ctx: &Context) {
-        let resp = egui::Window::new("unsaved changes")
-            .collapsible(false).resizable(false).default_width(300.0)
-            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
-            .show(ctx, |ui| {
-                ui.label("you have unsaved changes.");
-                ui.label("do you want to save before closing?");
-                ui.add_space(8.0);
-                ui.horizontal(|ui| {
-                    if ui.button("don't save").clicked() {
-                        self.close_confirmed = true;
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                    }
-                    if ui.button("cancel").clicked() { self.show_close_confirm = false; }
-                    if ui.button("save").clicked() {
-                        self.save_document();
-                        if !self.modified {
-                            self.close_confirmed = true;
-                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                        }
-                    }
-                });
-            });
-        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+}
+
+/// A template in the gallery: its first page in miniature, each line a
+/// bar as tall as its type and bold lines solid, over its name
+fn template_card(ui: &mut egui::Ui, template: &Template, selected: bool) -> egui::Response {
+    const SCALE: f32 = 0.2;
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(100.0, 140.0), egui::Sense::click());
+    let painter = ui.painter_at(rect);
+    let page = egui::Rect::from_min_size(rect.min + egui::vec2(10.0, 4.0), egui::vec2(80.0, 104.0));
+    painter.rect_filled(page.translate(egui::vec2(2.0, 2.0)), 0.0, SlowColors::BLACK);
+    painter.rect(page, 0.0, SlowColors::WHITE, Stroke::new(1.0, SlowColors::BLACK));
+
+    let inner = page.shrink(8.0);
+    let mut y = inner.top();
+    let mut styles = template.doc.styles.iter();
+    for line in template.doc.text.split('\n') {
+        let chars = line.chars().count();
+        let style = styles.next().cloned().unwrap_or_default();
+        if chars > 0 {
+            // The rest of the line and its newline
+            styles.nth(chars - 1);
+        }
+        let size = style.font_size * SCALE;
+        if y + size > inner.bottom() {
+            break;
+        }
+        if !line.trim().is_empty() {
+            let width = (chars as f32 * style.font_size * 0.5 * SCALE).min(inner.width());
+            let bar = egui::Rect::from_min_size(egui::pos2(inner.left(), y + size * 0.3), egui::vec2(width, (size * 0.6).max(1.0)));
+            if style.bold {
+                painter.rect_filled(bar, 0.0, SlowColors::BLACK);
+            } else {
+                painter.hline(bar.x_range(), bar.center().y, Stroke::new(1.0, SlowColors::BLACK));
+            }
+        }
+        y += size * 1.4;
     }
+
+    let label = egui::Rect::from_min_max(egui::pos2(rect.left(), page.bottom() + 8.0), rect.max);
+    let name_color = if selected {
+        painter.rect_filled(label, 0.0, SlowColors::BLACK);
+        SlowColors::WHITE
+    } else {
+        SlowColors::BLACK
+    };
+    painter.text(label.center(), egui::Align2::CENTER_CENTER, &template.name, egui::FontId::proportional(13.0), name_color);
+    response
 }
 
 fn shortcut_row(ui: &mut egui::Ui, shortcut: &str, description: &str) {
@@ -825,15 +1411,11 @@ impl eframe::App for SlowWriteApp {
             ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
         }
         self.handle_keyboard(ctx);
+        self.handle_commands(ctx);
+        self.update_reading_aloud(ctx);
 
-        let dropped: Vec<PathBuf> = ctx.input(|i| {
-            i.raw.dropped_files.iter().filter_map(|f| f.path.clone()).collect()
-        });
-        if let Some(path) = dropped.into_iter().next() {
-            let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
-            if ext == "txt" || ext == "md" || ext == "rtf" || ext == "swd" {
-                self.open_file(path);
-            }
+        if let Some(path) = DropTarget::new(&["txt", "md", "rtf", "swd"]).show(ctx).into_iter().next() {
+            self.open_file(path);
         }
 
         self.doc.sync_styles();
@@ -842,11 +1424,7 @@ impl eframe::App for SlowWriteApp {
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| { win_action = self.render_menu_bar(ui); });
         match win_action {
             WindowAction::Close => {
-                if self.modified {
-                    self.show_close_confirm = true;
-                } else {
-                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                }
+                self.close_guard.request_close(ctx, self.modified);
             }
             WindowAction::Minimize => {
                 let title = if self.file_title == "untitled" {
@@ -860,30 +1438,46 @@ impl eframe::App for SlowWriteApp {
             WindowAction::None => {}
         }
         egui::TopBottomPanel::top("title_bar").show(ctx, |ui| {
-            slowcore::theme::SlowTheme::title_bar_frame().show(ui, |ui| {
-                ui.centered_and_justified(|ui| { ui.label(self.display_title()); });
-            });
+            document_title_bar(ui, &self.file_title, self.file_path.as_deref(), self.modified);
         });
         egui::TopBottomPanel::top("toolbar").show(ctx, |ui| { self.render_toolbar(ui); });
         egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
-            let status = format!("{} lines  |  {} words, {} chars",
+            let mut status = format!("{} lines  |  {} words, {} chars",
                 self.doc.line_count(), self.doc.word_count(), self.doc.char_count());
+            if self.typewriter {
+                status.push_str("  |  typewriter");
+            }
+            if self.focus_mode {
+                status.push_str("  |  focus");
+            }
+            if self.input.mode != InputMode::Direct {
+                status.push_str(&format!("  |  {}", self.input.mode.name()));
+            }
+            if let Some(error) = &self.speech_error {
+                status.push_str(&format!("  |  {}", error));
+            } else if self.speaker.is_active() {
+                status.push_str(if self.speaker.is_paused() { "  |  reading aloud paused" } else { "  |  reading aloud" });
+            }
             status_bar(ui, &status);
         });
         egui::CentralPanel::default()
             .frame(egui::Frame::none().fill(SlowColors::WHITE).inner_margin(egui::Margin::same(0.0)))
             .show(ctx, |ui| { self.render_editor(ui); });
 
-        if self.show_file_browser { self.render_file_browser(ctx); }
-        if self.show_close_confirm { self.render_close_confirm(ctx); }
-        if self.show_about { self.render_about(ctx); }
+        self.render_file_dialog(ctx);
+        self.render_booklet_dialog(ctx);
+        self.render_merge_dialog(ctx);
+        self.render_template_gallery(ctx);
+        self.render_template_name_dialog(ctx);
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
+        }
         if self.show_shortcuts { self.render_shortcuts(ctx); }
 
-        if ctx.input(|i| i.viewport().close_requested()) {
-            if self.modified && !self.close_confirmed {
-                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
-                self.show_close_confirm = true;
-            }
+        // Unsaved-changes guard for close requests
+        if self.close_guard.update(ctx, self.modified) {
+            self.save_document();
+            self.close_guard.close_if_saved(ctx, !self.modified);
         }
 
         self.repaint.end_frame(ctx);
@@ -0,0 +1,173 @@
+//! Booklets — a document imposed two pages to a sheet for folding into
+//! a zine
+//!
+//! Pages are printed at half letter and set side by side on landscape
+//! letter sheets in booklet order. Print the PDF double-sided, flipping
+//! on the short edge, fold each signature's sheets in half together,
+//! stack the signatures and staple or sew along the fold. Pages are
+//! shrunk a little so home printers, which can't print to the edge,
+//! don't cut them off; crop marks show where to trim.
+
+use printpdf::{Color, ColorBits, ColorSpace, Greyscale, Image, ImageTransform, ImageXObject, Line, Mm, PdfDocument, Point, Px};
+use slowcore::print::{self, Page, PageSetup, Printer};
+use std::path::{Path, PathBuf};
+
+/// Signature sizes offered, in sheets; `None` folds every sheet into one
+pub const SIGNATURES: [(Option<usize>, &str); 5] = [
+    (None, "all sheets in one"),
+    (Some(1), "1 sheet (4 pages)"),
+    (Some(2), "2 sheets (8 pages)"),
+    (Some(4), "4 sheets (16 pages)"),
+    (Some(8), "8 sheets (32 pages)"),
+];
+
+/// Landscape letter
+const SHEET_WIDTH: f32 = 279.4;
+const SHEET_HEIGHT: f32 = 215.9;
+/// Unprinted border above and below the pages
+const SHEET_MARGIN: f32 = 12.7;
+/// Crop marks stand off this far from the trim and run this long
+const MARK_GAP: f32 = 1.5;
+const MARK_LENGTH: f32 = 6.0;
+
+/// Pages (numbered from 0) on both sides of one sheet, left then right;
+/// `None` is a blank
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sheet {
+    pub front: [Option<usize>; 2],
+    pub back: [Option<usize>; 2],
+}
+
+/// Put `pages` in booklet order on sheets, `signature` sheets folded
+/// together at a time. Each signature is padded with blanks at its end
+/// to a whole number of sheets.
+pub fn impose(pages: usize, signature: Option<usize>) -> Vec<Sheet> {
+    let pages_per_signature = signature.unwrap_or(pages.div_ceil(4)).max(1) * 4;
+    let mut sheets = Vec::new();
+    let mut first = 0;
+    loop {
+        let count = pages_per_signature.min(pages.saturating_sub(first)).max(1).next_multiple_of(4);
+        let page = |i: usize| Some(first + i).filter(|&p| p < pages);
+        for k in 0..count / 4 {
+            sheets.push(Sheet {
+                front: [page(count - 1 - 2 * k), page(2 * k)],
+                back: [page(2 * k + 1), page(count - 2 - 2 * k)],
+            });
+        }
+        first += pages_per_signature;
+        if first >= pages {
+            return sheets;
+        }
+    }
+}
+
+/// Write `sheets` of `pages` to a PDF, each side of a sheet a page of it
+pub fn write_pdf(pages: &[Page], sheets: &[Sheet], crop_marks: bool, path: &Path) -> Result<(), String> {
+    let (doc, first_page, first_layer) = PdfDocument::new("booklet", Mm(SHEET_WIDTH), Mm(SHEET_HEIGHT), "sheet 1 front");
+    let trim_height = SHEET_HEIGHT - SHEET_MARGIN * 2.0;
+    let trim_width = pages.first().map_or(0.0, |p| trim_height * p.width as f32 / p.height as f32);
+    let fold = SHEET_WIDTH / 2.0;
+
+    for (i, sheet) in sheets.iter().enumerate() {
+        for (side, faces) in [("front", sheet.front), ("back", sheet.back)] {
+            let name = format!("sheet {} {}", i + 1, side);
+            let (page_index, layer_index) = if i == 0 && side == "front" {
+                (first_page, first_layer)
+            } else {
+                doc.add_page(Mm(SHEET_WIDTH), Mm(SHEET_HEIGHT), name)
+            };
+            let layer = doc.get_page(page_index).get_layer(layer_index);
+            for (left, face) in [(fold - trim_width, faces[0]), (fold, faces[1])] {
+                let Some(page) = face.and_then(|n| pages.get(n)) else { continue };
+                let image = Image::from(ImageXObject {
+                    width: Px(page.width as usize),
+                    height: Px(page.height as usize),
+                    color_space: ColorSpace::Greyscale,
+                    bits_per_component: ColorBits::Bit1,
+                    interpolate: false,
+                    image_data: page.packed_rows(),
+                    image_filter: None,
+                    smask: None,
+                    clipping_bbox: None,
+                });
+                image.add_to_layer(layer.clone(), ImageTransform {
+                    translate_x: Some(Mm(left)),
+                    translate_y: Some(Mm(SHEET_MARGIN)),
+                    dpi: Some(page.height as f32 / (trim_height / 25.4)),
+                    ..Default::default()
+                });
+            }
+            if crop_marks && side == "front" {
+                layer.set_outline_color(Color::Greyscale(Greyscale::new(0.0, None)));
+                layer.set_outline_thickness(0.5);
+                let (left, right) = (fold - trim_width, fold + trim_width);
+                let (bottom, top) = (SHEET_MARGIN, SHEET_HEIGHT - SHEET_MARGIN);
+                let mut marks = Vec::new();
+                for (y, outward) in [(bottom, -1.0), (top, 1.0)] {
+                    // Up and down from each outer corner, and out along
+                    // the fold
+                    for x in [left, fold, right] {
+                        marks.push(((x, y + outward * MARK_GAP), (x, y + outward * (MARK_GAP + MARK_LENGTH))));
+                    }
+                    // Sideways from each outer corner
+                    marks.push(((left - MARK_GAP, y), (left - MARK_GAP - MARK_LENGTH, y)));
+                    marks.push(((right + MARK_GAP, y), (right + MARK_GAP + MARK_LENGTH, y)));
+                }
+                for ((x0, y0), (x1, y1)) in marks {
+                    layer.add_line(Line {
+                        points: vec![(Point::new(Mm(x0), Mm(y0)), false), (Point::new(Mm(x1), Mm(y1)), false)],
+                        is_closed: false,
+                    });
+                }
+            }
+        }
+    }
+
+    let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+    doc.save(&mut std::io::BufWriter::new(file)).map_err(|e| e.to_string())
+}
+
+/// Print `text` as a booklet PDF in Printouts, returning where it went
+/// and how many sheets it takes
+pub fn export(title: &str, text: &str, signature: Option<usize>, crop_marks: bool) -> Result<(PathBuf, usize), String> {
+    let dir = print::printouts_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let stem = print::free_stem(&dir, title, " booklet.pdf");
+    let path = dir.join(format!("{} booklet.pdf", stem));
+    let pages = Printer::new(PageSetup::HALF_LETTER).render(text);
+    let sheets = impose(pages.len(), signature);
+    write_pdf(&pages, &sheets, crop_marks, &path)?;
+    Ok((path, sheets.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_impose() {
+        // Eight pages folded together: the outer sheet holds the covers
+        let sheets = impose(8, None);
+        assert_eq!(sheets, vec![
+            Sheet { front: [Some(7), Some(0)], back: [Some(1), Some(6)] },
+            Sheet { front: [Some(5), Some(2)], back: [Some(3), Some(4)] },
+        ]);
+
+        // Ten pages in one-sheet signatures: three of them, the last
+        // with two blanks at its end
+        let sheets = impose(10, Some(1));
+        assert_eq!(sheets.len(), 3);
+        assert_eq!(sheets[1], Sheet { front: [Some(7), Some(4)], back: [Some(5), Some(6)] });
+        assert_eq!(sheets[2], Sheet { front: [None, Some(8)], back: [Some(9), None] });
+
+        // A single page still makes a sheet
+        assert_eq!(impose(1, None), vec![Sheet { front: [None, Some(0)], back: [None, None] }]);
+
+        let pages = Printer::new(PageSetup::HALF_LETTER).render("a zine");
+        let path = std::env::temp_dir().join(format!("slowwrite-booklet-{}.pdf", std::process::id()));
+        write_pdf(&pages, &impose(pages.len(), None), true, &path).unwrap();
+        let written = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(written.starts_with(b"%PDF"));
+    }
+}
@@ -4,22 +4,21 @@
 //! variable font sizes and families. Double-click-drag word selection.
 
 mod app;
+mod booklet;
+mod merge;
 mod rich_text;
+mod templates;
 
 use app::SlowWriteApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let initial_file = std::env::args().nth(1).map(std::path::PathBuf::from);
 
-    let mut viewport = egui::ViewportBuilder::default()
-        .with_inner_size([580.0, 440.0])
+    let viewport = window_layout::viewport("slowwrite", Placement::RememberLast, [580.0, 440.0])
         .with_title("slowWrite");
 
-    if let Some(pos) = slowcore::cascade_position() {
-        viewport = viewport.with_position(pos);
-    }
-
     let options = NativeOptions {
         viewport,
         ..Default::default()
@@ -0,0 +1,191 @@
+//! Mail merge — a copy of the document for each row of a table
+//!
+//! The rows come from a CSV or tab-separated file, as spreadsheets
+//! export them, whose first row names the fields. `{{name}}` anywhere in
+//! the document is filled in from each row in turn; the copies are saved
+//! as documents in a new folder or printed to Printouts, each starting
+//! on a fresh page.
+
+use crate::rich_text::{save_rich_document, CharStyle, RichDocument};
+use slowcore::print;
+use slowcore::storage::{documents_dir, unique_path};
+use std::path::{Path, PathBuf};
+
+/// A table to merge from
+#[derive(Debug, Clone)]
+pub struct DataSource {
+    pub path: PathBuf,
+    pub fields: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl DataSource {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let tabs = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("tsv") || e.eq_ignore_ascii_case("tab"));
+        let mut rows = parse_table(&text, if tabs { '\t' } else { ',' })
+            .into_iter()
+            .filter(|r| r.iter().any(|f| !f.trim().is_empty()));
+        let fields: Vec<String> = rows.next().unwrap_or_default().iter().map(|f| f.trim().to_string()).collect();
+        let rows: Vec<Vec<String>> = rows.collect();
+        if rows.is_empty() {
+            return Err("no rows below the field names".into());
+        }
+        Ok(Self { path: path.to_path_buf(), fields, rows })
+    }
+
+    pub fn name(&self) -> String {
+        self.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    /// The column of the field called `name`, ignoring case
+    pub fn field(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|f| f.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// `row`'s value for the field in `column`; blank where the row is short
+    pub fn value(&self, row: usize, column: usize) -> &str {
+        self.rows.get(row).and_then(|r| r.get(column)).map_or("", |v| v.as_str())
+    }
+}
+
+/// Rows of `sep`-separated fields; quoted fields may hold the separator,
+/// line breaks and doubled quotes
+pub fn parse_table(text: &str, sep: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == sep && !quoted => row.push(std::mem::take(&mut field)),
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            '\r' if !quoted => {}
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Byte ranges of each `{{…}}` in `text` with the name inside it
+fn placeholder_spans(text: &str) -> Vec<(std::ops::Range<usize>, &str)> {
+    let mut spans = Vec::new();
+    let mut from = 0;
+    while let Some(open) = text[from..].find("{{").map(|i| from + i) {
+        let Some(close) = text[open + 2..].find("}}").map(|i| open + 2 + i) else { break };
+        spans.push((open..close + 2, text[open + 2..close].trim()));
+        from = close + 2;
+    }
+    spans
+}
+
+/// The names in the document's placeholders, each once, in order
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (_, name) in placeholder_spans(text) {
+        if !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// `doc` with its placeholders filled in from `row`. What goes in takes
+/// the style of the placeholder's opening brace; placeholders naming no
+/// field are left as they are.
+pub fn fill(doc: &RichDocument, source: &DataSource, row: usize) -> RichDocument {
+    let mut out = RichDocument::new();
+    let style_at = |i: usize| doc.styles.get(i).cloned().unwrap_or_default();
+    let copy = |out: &mut RichDocument, text: &str, first: usize| {
+        out.text.push_str(text);
+        out.styles.extend((first..first + text.chars().count()).map(style_at));
+    };
+    let mut byte = 0;
+    let mut char_index = 0;
+    for (span, name) in placeholder_spans(&doc.text) {
+        let before = &doc.text[byte..span.start];
+        copy(&mut out, before, char_index);
+        char_index += before.chars().count();
+        let placeholder = &doc.text[span.clone()];
+        match source.field(name) {
+            Some(column) => {
+                let value = source.value(row, column);
+                out.text.push_str(value);
+                out.styles.extend(std::iter::repeat_n(style_at(char_index), value.chars().count()));
+            }
+            None => copy(&mut out, placeholder, char_index),
+        }
+        char_index += placeholder.chars().count();
+        byte = span.end;
+    }
+    copy(&mut out, &doc.text[byte..], char_index);
+    out.cursor_style = CharStyle::default();
+    out
+}
+
+/// Save a filled-in copy for every row in a new folder in Documents,
+/// as rich documents or plain text, returning the folder
+pub fn write_documents(doc: &RichDocument, source: &DataSource, title: &str, rich: bool) -> Result<PathBuf, String> {
+    let folder = unique_path(&documents_dir(), &format!("{} merged", title));
+    std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+    let ext = if rich { "swd" } else { "txt" };
+    for row in 0..source.rows.len() {
+        let filled = fill(doc, source, row);
+        let content = if rich { save_rich_document(&filled) } else { filled.text };
+        let path = folder.join(format!("{} {:02}.{}", title, row + 1, ext));
+        std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    }
+    Ok(folder)
+}
+
+/// Print a filled-in copy for every row to Printouts, returning the
+/// number of pages
+pub fn print_pages(doc: &RichDocument, source: &DataSource, title: &str) -> Result<usize, String> {
+    let texts: Vec<String> = (0..source.rows.len()).map(|row| fill(doc, source, row).text).collect();
+    let texts: Vec<&str> = texts.iter().map(|t| t.as_str()).collect();
+    print::export_documents(title, &texts).map(|pages| pages.len()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let table = parse_table("name,town\r\nAda,\"London, UK\"\n\"Grace \"\"Amazing\"\"\",Arlington", ',');
+        assert_eq!(table[1], ["Ada", "London, UK"]);
+        assert_eq!(table[2], ["Grace \"Amazing\"", "Arlington"]);
+        let source = DataSource {
+            path: PathBuf::from("people.csv"),
+            fields: table[0].clone(),
+            rows: table[1..].to_vec(),
+        };
+
+        // The name goes in bold, like its placeholder; the unknown field
+        // stays put
+        let mut doc = RichDocument::from_plain_text("Dear {{Name}}, of {{ town }}. {{pet}}".to_string());
+        for style in &mut doc.styles[5..13] {
+            style.bold = true;
+        }
+        assert_eq!(placeholders(&doc.text), ["Name", "town", "pet"]);
+        let filled = fill(&doc, &source, 0);
+        assert_eq!(filled.text, "Dear Ada, of London, UK. {{pet}}");
+        assert_eq!(filled.styles.len(), filled.char_count());
+        assert!(filled.styles[5..8].iter().all(|s| s.bold));
+        assert!(!filled.styles[8].bold);
+        assert_eq!(fill(&doc, &source, 1).text, "Dear Grace \"Amazing\", of Arlington. {{pet}}");
+    }
+}
@@ -0,0 +1,183 @@
+//! Templates — documents to start new ones from
+//!
+//! A letter, a memo and a CV come with slowWrite. Any document can be
+//! saved as a template of its own, kept as a .swd file in
+//! ~/.config/slowwrite/templates. A new document from a template is an
+//! untitled copy, so the template itself is never written over.
+
+use crate::rich_text::{load_rich_document, save_rich_document, CharStyle, RichDocument};
+use slowcore::storage::{clean_name, config_dir};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub name: String,
+    pub doc: RichDocument,
+    /// Came with slowWrite, so it can't be deleted
+    pub bundled: bool,
+}
+
+pub fn templates_dir() -> PathBuf {
+    config_dir("slowwrite").join("templates")
+}
+
+fn template_path(name: &str) -> PathBuf {
+    templates_dir().join(format!("{}.swd", name))
+}
+
+/// How a line of a bundled template is set
+#[derive(Clone, Copy)]
+enum Line {
+    Title,
+    Heading,
+    Body,
+    Small,
+}
+
+impl Line {
+    fn style(self) -> CharStyle {
+        let (bold, font_size) = match self {
+            Line::Title => (true, 28.0),
+            Line::Heading => (true, 18.0),
+            Line::Body => (false, 16.0),
+            Line::Small => (false, 12.0),
+        };
+        CharStyle { bold, font_size, ..CharStyle::default() }
+    }
+}
+
+/// A document of `lines`, each styled whole
+fn build(lines: &[(Line, &str)]) -> RichDocument {
+    let mut doc = RichDocument::new();
+    for (i, &(line, text)) in lines.iter().enumerate() {
+        let text = if i + 1 < lines.len() { format!("{}\n", text) } else { text.to_string() };
+        doc.styles.extend(std::iter::repeat_n(line.style(), text.chars().count()));
+        doc.text.push_str(&text);
+    }
+    doc
+}
+
+/// The templates that come with slowWrite
+pub fn bundled() -> Vec<Template> {
+    use Line::*;
+    let letter = build(&[
+        (Heading, "Your Name"),
+        (Small, "Street and number"),
+        (Small, "Town, postcode"),
+        (Body, ""),
+        (Body, "1 January 2025"),
+        (Body, ""),
+        (Body, "Their Name"),
+        (Body, "Street and number"),
+        (Body, "Town, postcode"),
+        (Body, ""),
+        (Body, "Dear ...,"),
+        (Body, ""),
+        (Body, "Say why you're writing in the first paragraph."),
+        (Body, ""),
+        (Body, "Then say the rest."),
+        (Body, ""),
+        (Body, "With best wishes,"),
+        (Body, ""),
+        (Body, ""),
+        (Body, "Your Name"),
+    ]);
+    let memo = build(&[
+        (Title, "Memo"),
+        (Body, ""),
+        (Heading, "To:"),
+        (Heading, "From:"),
+        (Heading, "Date:"),
+        (Heading, "Subject:"),
+        (Body, ""),
+        (Body, "What this is about, in a sentence."),
+        (Body, ""),
+        (Body, "The details, and what needs doing by whom."),
+    ]);
+    let cv = build(&[
+        (Title, "Your Name"),
+        (Small, "email  \u{b7}  phone  \u{b7}  town"),
+        (Body, ""),
+        (Heading, "Profile"),
+        (Body, "A sentence or two about who you are and what you're after."),
+        (Body, ""),
+        (Heading, "Experience"),
+        (Body, "Job title, Employer \u{2014} 2021 to now"),
+        (Small, "What you did there and what came of it."),
+        (Body, "Job title, Employer \u{2014} 2018 to 2021"),
+        (Small, "What you did there and what came of it."),
+        (Body, ""),
+        (Heading, "Education"),
+        (Body, "Course, School \u{2014} 2018"),
+        (Body, ""),
+        (Heading, "Skills"),
+        (Body, "Languages, tools, licences"),
+    ]);
+    [("letter", letter), ("memo", memo), ("CV", cv)]
+        .into_iter()
+        .map(|(name, doc)| Template { name: name.to_string(), doc, bundled: true })
+        .collect()
+}
+
+/// The bundled templates, then the user's by name
+pub fn load_all() -> Vec<Template> {
+    let mut own: Vec<Template> = std::fs::read_dir(templates_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "swd"))
+        .filter_map(|path| {
+            let doc = load_rich_document(&std::fs::read_to_string(&path).ok()?)?;
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            Some(Template { name, doc, bundled: false })
+        })
+        .collect();
+    own.sort_by_key(|t| t.name.to_lowercase());
+    let mut templates = bundled();
+    templates.append(&mut own);
+    templates
+}
+
+/// Keep `doc` as a template called `name`, replacing one of that name
+pub fn save(name: &str, doc: &RichDocument) -> Result<String, String> {
+    let name = clean_name(name);
+    if name.is_empty() {
+        return Err("a template needs a name".into());
+    }
+    std::fs::create_dir_all(templates_dir()).map_err(|e| e.to_string())?;
+    let path = template_path(&name);
+    let tmp = path.with_extension("swd.tmp");
+    std::fs::write(&tmp, save_rich_document(doc)).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+    Ok(name)
+}
+
+pub fn delete(name: &str) -> Result<(), String> {
+    std::fs::remove_file(template_path(name)).map_err(|e| e.to_string())
+}
+
+/// Whether there's a template of the user's called `name`
+pub fn exists(name: &str) -> bool {
+    template_path(&clean_name(name)).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_templates() {
+        let templates = bundled();
+        let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["letter", "memo", "CV"]);
+        for t in &templates {
+            assert_eq!(t.doc.styles.len(), t.doc.char_count(), "{} has a style per character", t.name);
+            assert!(!t.doc.text.ends_with('\n'));
+        }
+        // The memo's title is set large and bold, its body plain
+        let memo = &templates[1].doc;
+        assert!(memo.styles[0].bold && memo.styles[0].font_size == 28.0);
+        assert_eq!(memo.styles.last(), Some(&CharStyle::default()));
+    }
+}
@@ -3,6 +3,10 @@
 //! Files deleted from other slow computer apps land here.
 //! Users can restore files to their original location or permanently delete them.
 //!
+//! Items can be purged automatically once they are older than a set number
+//! of days, or (oldest first) when the trash grows past a size quota. Both
+//! limits are off by default and set from the trash window.
+//!
 //! Note: this crate is both a library (move_to_trash, restore_from_trash used by
 //! other apps) and a binary (the TrashApp UI). The dual target causes false
 //! dead-code warnings — each target can't see the other's usage.
@@ -11,12 +15,23 @@
 use chrono::Local;
 use egui::{Context, Key};
 use serde::{Deserialize, Serialize};
+use slowcore::notify::Undo;
 use slowcore::repaint::RepaintController;
 use slowcore::storage::config_dir;
 use slowcore::theme::{menu_bar, SlowColors};
-use slowcore::widgets::{status_bar, window_control_buttons, WindowAction};
+use slowcore::widgets::{about_dialog, alert, status_bar, window_control_buttons, AboutInfo, WindowAction};
 use std::path::PathBuf;
 
+const ABOUT: AboutInfo = AboutInfo {
+    name: "trash",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "trash bin for slowOS",
+    features: &["view deleted items", "restore or permanently delete", "empty all trash", "auto-purge by age or size"],
+    sections: &[("location", &["~/.local/share/Trash"])],
+    credits: &["egui/eframe (MIT), chrono (MIT)"],
+    ..AboutInfo::EMPTY
+};
+
 /// Metadata for a trashed file
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct TrashEntry {
@@ -26,9 +41,12 @@ struct TrashEntry {
     original_path: PathBuf,
     /// Path inside trash directory
     trash_path: PathBuf,
-    /// When the file was trashed
+    /// When the file was trashed (for display)
     trashed_at: String,
-    /// File size in bytes
+    /// When the file was trashed, Unix seconds (for auto-purge)
+    #[serde(default)]
+    deleted_at: u64,
+    /// Size in bytes (whole tree for folders)
     size: u64,
 }
 
@@ -40,10 +58,21 @@ struct TrashManifest {
 
 impl TrashManifest {
     fn load(path: &std::path::Path) -> Self {
-        std::fs::read_to_string(path)
+        let mut manifest: Self = std::fs::read_to_string(path)
             .ok()
             .and_then(|s| serde_json::from_str(&s).ok())
-            .unwrap_or_default()
+            .unwrap_or_default();
+        // Entries from before deleted_at was recorded: use the display date
+        for entry in &mut manifest.entries {
+            if entry.deleted_at == 0 {
+                entry.deleted_at = chrono::NaiveDateTime::parse_from_str(&entry.trashed_at, "%Y-%m-%d %H:%M")
+                    .ok()
+                    .and_then(|t| t.and_local_timezone(Local).single())
+                    .map(|t| t.timestamp().max(0) as u64)
+                    .unwrap_or_else(now_secs);
+            }
+        }
+        manifest
     }
 
     fn save(&self, path: &std::path::Path) {
@@ -54,11 +83,108 @@ impl TrashManifest {
             let _ = std::fs::write(path, json);
         }
     }
+
+    fn total_size(&self) -> u64 {
+        self.entries.iter().map(|e| e.size).sum()
+    }
+
+    /// Permanently delete items past the age limit, then the oldest items
+    /// until the trash fits the quota. The newest item is never purged for
+    /// size, so trashing one big file doesn't destroy it on the spot.
+    /// Returns the number of items removed.
+    fn purge(&mut self, settings: &TrashSettings, now: u64) -> usize {
+        let before = self.entries.len();
+        if let Some(days) = settings.max_age_days {
+            let cutoff = now.saturating_sub(days as u64 * 24 * 60 * 60);
+            self.entries.retain(|e| {
+                let keep = e.deleted_at >= cutoff;
+                if !keep {
+                    remove_trashed(&e.trash_path);
+                }
+                keep
+            });
+        }
+        if let Some(quota) = settings.quota_bytes() {
+            // Oldest first, leaving the manifest in the order it was in
+            let mut order: Vec<usize> = (0..self.entries.len()).collect();
+            order.sort_by_key(|&i| self.entries[i].deleted_at);
+            order.pop();
+            let mut total = self.total_size();
+            let mut purged = vec![false; self.entries.len()];
+            for i in order {
+                if total <= quota {
+                    break;
+                }
+                total -= self.entries[i].size;
+                purged[i] = true;
+                remove_trashed(&self.entries[i].trash_path);
+            }
+            let mut purged = purged.into_iter();
+            self.entries.retain(|_| !purged.next().unwrap_or(false));
+        }
+        before - self.entries.len()
+    }
+}
+
+/// Auto-purge limits, set from the trash window
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrashSettings {
+    /// Purge items trashed more than this many days ago
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+    /// Keep the trash under this many megabytes
+    #[serde(default)]
+    pub quota_mb: Option<u64>,
+}
+
+impl TrashSettings {
+    pub fn load() -> Self {
+        std::fs::read_to_string(config_dir("trash").join("settings.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = config_dir("trash").join("settings.json");
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    fn quota_bytes(&self) -> Option<u64> {
+        self.quota_mb.map(|mb| mb * 1024 * 1024)
+    }
+}
+
+/// How full the trash is, for the desktop status bar
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrashUsage {
+    pub items: usize,
+    pub bytes: u64,
+    /// Size quota, if one is set
+    pub quota_bytes: Option<u64>,
+}
+
+impl TrashUsage {
+    /// "3 items, 1.2 MB" or "3 items, 1.2 MB of 500.0 MB"
+    pub fn label(&self) -> String {
+        let items = if self.items == 1 { "1 item".to_string() } else { format!("{} items", self.items) };
+        match self.quota_bytes {
+            Some(q) => format!("{}, {} of {}", items, format_size(self.bytes), format_size(q)),
+            None => format!("{}, {}", items, format_size(self.bytes)),
+        }
+    }
 }
 
 pub struct TrashApp {
     manifest: TrashManifest,
     manifest_path: PathBuf,
+    settings: TrashSettings,
+    show_settings: bool,
     selected: Option<usize>,
     show_about: bool,
     show_confirm_empty: bool,
@@ -73,18 +199,22 @@ impl TrashApp {
         let _ = std::fs::create_dir_all(&trash_dir);
         let manifest_path = trash_dir.join("manifest.json");
         let mut manifest = TrashManifest::load(&manifest_path);
+        let settings = TrashSettings::load();
 
         // Prune entries whose trash files no longer exist
         manifest.entries.retain(|e| e.trash_path.exists());
+        let purged = manifest.purge(&settings, now_secs());
 
         let app = Self {
             manifest,
             manifest_path,
+            settings,
+            show_settings: false,
             selected: None,
             show_about: false,
             show_confirm_empty: false,
             show_confirm_delete: false,
-            message: None,
+            message: (purged > 0).then(|| format!("auto-purged {} old items", purged)),
             repaint: RepaintController::new(),
         };
         app.save_manifest();
@@ -109,34 +239,20 @@ impl TrashApp {
         if let Some(idx) = self.selected {
             if idx < self.manifest.entries.len() {
                 let entry = &self.manifest.entries[idx];
-                let dest = &entry.original_path;
-
-                // Ensure parent directory exists
-                if let Some(parent) = dest.parent() {
-                    let _ = std::fs::create_dir_all(parent);
-                }
-
-                match std::fs::rename(&entry.trash_path, dest) {
-                    Ok(()) => {
-                        self.message = Some(format!("restored: {}", entry.original_name));
+                match move_back(entry) {
+                    Ok(dest) => {
+                        self.message = Some(if dest == entry.original_path {
+                            format!("restored: {}", entry.original_name)
+                        } else {
+                            let name = dest.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                            format!("restored as: {}", name)
+                        });
                         self.manifest.entries.remove(idx);
                         self.selected = None;
                         self.save_manifest();
                     }
                     Err(e) => {
-                        // rename fails across filesystems; fall back to copy+delete
-                        match std::fs::copy(&entry.trash_path, dest) {
-                            Ok(_) => {
-                                let _ = std::fs::remove_file(&entry.trash_path);
-                                self.message = Some(format!("restored: {}", entry.original_name));
-                                self.manifest.entries.remove(idx);
-                                self.selected = None;
-                                self.save_manifest();
-                            }
-                            Err(_) => {
-                                self.message = Some(format!("restore failed: {}", e));
-                            }
-                        }
+                        self.message = Some(format!("restore failed: {}", e));
                     }
                 }
             }
@@ -147,11 +263,7 @@ impl TrashApp {
         if let Some(idx) = self.selected {
             if idx < self.manifest.entries.len() {
                 let entry = &self.manifest.entries[idx];
-                if entry.trash_path.is_dir() {
-                    let _ = std::fs::remove_dir_all(&entry.trash_path);
-                } else {
-                    let _ = std::fs::remove_file(&entry.trash_path);
-                }
+                remove_trashed(&entry.trash_path);
                 let name = entry.original_name.clone();
                 self.manifest.entries.remove(idx);
                 self.selected = None;
@@ -163,31 +275,72 @@ impl TrashApp {
 
     fn empty_trash(&mut self) {
         for entry in &self.manifest.entries {
-            if entry.trash_path.is_dir() {
-                let _ = std::fs::remove_dir_all(&entry.trash_path);
-            } else {
-                let _ = std::fs::remove_file(&entry.trash_path);
-            }
+            remove_trashed(&entry.trash_path);
         }
         self.manifest.entries.clear();
         self.selected = None;
         self.save_manifest();
         self.message = Some("trash emptied".to_string());
+        slowcore::sound::trash();
     }
 
-    fn total_size(&self) -> u64 {
-        self.manifest.entries.iter().map(|e| e.size).sum()
+    /// Apply changed limits right away
+    fn apply_settings(&mut self) {
+        self.settings.save();
+        let purged = self.manifest.purge(&self.settings, now_secs());
+        if purged > 0 {
+            self.selected = None;
+            self.save_manifest();
+            self.message = Some(format!("auto-purged {} old items", purged));
+        }
     }
 
-    fn format_size(bytes: u64) -> String {
-        if bytes < 1024 {
-            format!("{} B", bytes)
-        } else if bytes < 1024 * 1024 {
-            format!("{:.1} KB", bytes as f64 / 1024.0)
-        } else if bytes < 1024 * 1024 * 1024 {
-            format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
-        } else {
-            format!("{:.1} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+    fn render_settings(&mut self, ctx: &Context) {
+        let mut changed = false;
+        let resp = egui::Window::new("trash settings")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut by_age = self.settings.max_age_days.is_some();
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut by_age, "delete items after").changed() {
+                        self.settings.max_age_days = by_age.then_some(30);
+                        changed = true;
+                    }
+                    let mut days = self.settings.max_age_days.unwrap_or(30);
+                    let r = ui.add_enabled(by_age, egui::DragValue::new(&mut days).clamp_range(1..=365));
+                    if by_age && r.changed() {
+                        self.settings.max_age_days = Some(days);
+                    }
+                    changed |= r.drag_stopped() || r.lost_focus();
+                    ui.label("days");
+                });
+                let mut by_size = self.settings.quota_mb.is_some();
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut by_size, "keep trash under").changed() {
+                        self.settings.quota_mb = by_size.then_some(1024);
+                        changed = true;
+                    }
+                    let mut mb = self.settings.quota_mb.unwrap_or(1024);
+                    let r = ui.add_enabled(by_size, egui::DragValue::new(&mut mb).clamp_range(10..=100_000).speed(10));
+                    if by_size && r.changed() {
+                        self.settings.quota_mb = Some(mb);
+                    }
+                    changed |= r.drag_stopped() || r.lost_focus();
+                    ui.label("MB");
+                });
+                ui.add_space(4.0);
+                ui.label("the oldest items are deleted first.");
+                ui.add_space(8.0);
+                ui.vertical_centered(|ui| {
+                    if ui.button("ok").clicked() {
+                        self.show_settings = false;
+                    }
+                });
+            });
+        if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+        if changed {
+            self.apply_settings();
         }
     }
 }
@@ -237,6 +390,11 @@ impl eframe::App for TrashApp {
                         self.show_confirm_empty = true;
                         ui.close_menu();
                     }
+                    ui.separator();
+                    if ui.button("trash settings...").clicked() {
+                        self.show_settings = true;
+                        ui.close_menu();
+                    }
                 });
                 ui.menu_button("help", |ui| {
                     if ui.button("about").clicked() {
@@ -282,7 +440,10 @@ impl eframe::App for TrashApp {
         // Status bar
         egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
             let count = self.manifest.entries.len();
-            let size = Self::format_size(self.total_size());
+            let size = match self.settings.quota_bytes() {
+                Some(q) => format!("{} of {}", format_size(self.manifest.total_size()), format_size(q)),
+                None => format_size(self.manifest.total_size()),
+            };
             let msg = self.message.as_deref().unwrap_or("");
             status_bar(ui, &format!("{} items  |  {}  {}", count, size, msg));
         });
@@ -364,7 +525,7 @@ impl eframe::App for TrashApp {
                             painter.text(
                                 egui::Pos2::new(rect.min.x + w * 0.80, y),
                                 egui::Align2::LEFT_CENTER,
-                                &Self::format_size(entry.size),
+                                &format_size(entry.size),
                                 egui::FontId::proportional(12.0),
                                 fg,
                             );
@@ -380,24 +541,13 @@ impl eframe::App for TrashApp {
 
         // Confirm empty dialog
         if self.show_confirm_empty {
-            let resp = egui::Window::new("empty trash")
-                .collapsible(false)
-                .resizable(false)
-                .show(ctx, |ui| {
-                    ui.label("permanently delete all items in trash?");
-                    ui.label("this cannot be undone.");
-                    ui.add_space(8.0);
-                    ui.horizontal(|ui| {
-                        if ui.button("cancel").clicked() {
-                            self.show_confirm_empty = false;
-                        }
-                        if ui.button("empty trash").clicked() {
-                            self.empty_trash();
-                            self.show_confirm_empty = false;
-                        }
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+            let body = "permanently delete all items in trash?\nthis cannot be undone.";
+            if let Some(answer) = alert(ctx, "empty trash", body, &["cancel", "empty trash"]) {
+                if answer == 1 {
+                    self.empty_trash();
+                }
+                self.show_confirm_empty = false;
+            }
         }
 
         // Confirm single delete dialog
@@ -406,62 +556,22 @@ impl eframe::App for TrashApp {
                 .and_then(|i| self.manifest.entries.get(i))
                 .map(|e| e.original_name.clone())
                 .unwrap_or_default();
-            let resp = egui::Window::new("delete permanently")
-                .collapsible(false)
-                .resizable(false)
-                .show(ctx, |ui| {
-                    ui.label(format!("permanently delete \"{}\"?", name));
-                    ui.label("this cannot be undone.");
-                    ui.add_space(8.0);
-                    ui.horizontal(|ui| {
-                        if ui.button("cancel").clicked() {
-                            self.show_confirm_delete = false;
-                        }
-                        if ui.button("delete").clicked() {
-                            self.delete_selected_permanently();
-                            self.show_confirm_delete = false;
-                        }
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow(ctx, r.response.rect); }
+            let body = format!("permanently delete \"{}\"?\nthis cannot be undone.", name);
+            if let Some(answer) = alert(ctx, "delete permanently", &body, &["cancel", "delete"]) {
+                if answer == 1 {
+                    self.delete_selected_permanently();
+                }
+                self.show_confirm_delete = false;
+            }
+        }
+
+        if self.show_settings {
+            self.render_settings(ctx);
         }
 
         // About dialog
-        if self.show_about {
-            let screen = ctx.screen_rect();
-            let max_h = (screen.height() - 60.0).max(120.0);
-            let resp = egui::Window::new("about trash")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(300.0)
-                .max_height(max_h)
-                .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().max_height(max_h - 50.0).show(ui, |ui| {
-                        ui.vertical_centered(|ui| {
-                            ui.heading("trash");
-                            ui.label("version 0.2.2");
-                            ui.add_space(8.0);
-                            ui.label("trash bin for slowOS");
-                        });
-                        ui.add_space(8.0);
-                        ui.separator();
-                        ui.add_space(4.0);
-                        ui.label("features:");
-                        ui.label("  view deleted items");
-                        ui.label("  restore or permanently delete");
-                        ui.label("  empty all trash");
-                        ui.add_space(4.0);
-                        ui.label("location: ~/.local/share/Trash");
-                        ui.add_space(4.0);
-                        ui.label("frameworks:");
-                        ui.label("  egui/eframe (MIT), chrono (MIT)");
-                        ui.add_space(8.0);
-                        ui.vertical_centered(|ui| {
-                            if ui.button("ok").clicked() { self.show_about = false; }
-                        });
-                    });
-                });
-            if let Some(r) = &resp { slowcore::dither::draw_window_shadow_large(ctx, r.response.rect); }
+        if self.show_about && about_dialog(ctx, ABOUT) {
+            self.show_about = false;
         }
         self.repaint.end_frame(ctx);
     }
@@ -472,6 +582,129 @@ pub fn trash_dir() -> PathBuf {
     config_dir("trash").join("files")
 }
 
+fn manifest_path() -> PathBuf {
+    trash_dir().join("manifest.json")
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// "12 B", "3.4 KB", "1.2 MB", "2.0 GB"
+pub fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else if bytes < 1024 * 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.1} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+    }
+}
+
+/// Size of a file, or of everything inside a folder
+fn disk_size(path: &std::path::Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else { return 0 };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| disk_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Permanently delete something inside the trash directory
+fn remove_trashed(path: &std::path::Path) {
+    if path.is_dir() {
+        let _ = std::fs::remove_dir_all(path);
+    } else {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// `path`, or "name (restored).ext", "name (restored 2).ext"… if taken
+fn free_restore_path(path: &std::path::Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "file".into());
+    let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    let parent = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+    let mut n = 1u32;
+    loop {
+        let name = if n == 1 {
+            format!("{} (restored){}", stem, ext)
+        } else {
+            format!("{} (restored {}){}", stem, n, ext)
+        };
+        let candidate = parent.join(name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// Move a trashed item back to its original location, renaming it if
+/// something else now has that name. Returns where it ended up.
+fn move_back(entry: &TrashEntry) -> Result<PathBuf, std::io::Error> {
+    if let Some(parent) = entry.original_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let dest = free_restore_path(&entry.original_path);
+    std::fs::rename(&entry.trash_path, &dest).or_else(|_| {
+        // Cross-filesystem: copy then delete
+        if entry.trash_path.is_dir() {
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "cannot restore directory across filesystems"))
+        } else {
+            std::fs::copy(&entry.trash_path, &dest)?;
+            std::fs::remove_file(&entry.trash_path)
+        }
+    })?;
+    Ok(dest)
+}
+
+/// Tell the desktop that `originals` were just trashed, so it shows a
+/// toast with an "undo" button (see `perform_undo`).
+pub fn notify_trashed(app: &str, originals: &[PathBuf]) {
+    let message = match originals {
+        [] => return,
+        [one] => format!(
+            "moved \u{201c}{}\u{201d} to trash",
+            one.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+        ),
+        many => format!("moved {} items to trash", many.len()),
+    };
+    slowcore::notify::post_with_undo(app, &message, Undo::Trash { originals: originals.to_vec() });
+}
+
+/// Reverse a file operation from a notification's "undo" button.
+/// Returns how many items were put back.
+pub fn perform_undo(undo: &Undo) -> usize {
+    match undo {
+        Undo::Trash { originals } => originals.iter()
+            .filter(|p| restore_from_trash(p).is_ok())
+            .count(),
+        Undo::Move { moves } => moves.iter()
+            .filter(|m| !m.from.exists() && std::fs::rename(&m.to, &m.from).is_ok())
+            .count(),
+    }
+}
+
+/// Number of items and bytes in the trash
+pub fn trash_usage() -> TrashUsage {
+    let manifest = TrashManifest::load(&manifest_path());
+    TrashUsage {
+        items: manifest.entries.len(),
+        bytes: manifest.total_size(),
+        quota_bytes: TrashSettings::load().quota_bytes(),
+    }
+}
+
 /// Move a file to the slow computer trash.
 /// Called by other apps to trash files instead of deleting them.
 /// Returns Ok(()) on success.
@@ -505,8 +738,7 @@ pub fn move_to_trash(source: &std::path::Path) -> Result<(), std::io::Error> {
         counter += 1;
     }
 
-    // Get file size
-    let size = std::fs::metadata(source).map(|m| m.len()).unwrap_or(0);
+    let size = disk_size(source);
 
     // Move file
     std::fs::rename(source, &dest).or_else(|_| {
@@ -520,52 +752,108 @@ pub fn move_to_trash(source: &std::path::Path) -> Result<(), std::io::Error> {
         }
     })?;
 
-    // Update manifest
-    let manifest_path = config_dir("trash").join("files").join("manifest.json");
+    // Update manifest, then enforce the auto-purge limits
+    let manifest_path = manifest_path();
     let mut manifest = TrashManifest::load(&manifest_path);
     manifest.entries.push(TrashEntry {
         original_name: filename,
         original_path: source.to_path_buf(),
         trash_path: dest,
         trashed_at: Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        deleted_at: now_secs(),
         size,
     });
+    manifest.purge(&TrashSettings::load(), now_secs());
     manifest.save(&manifest_path);
 
     Ok(())
 }
 
 /// Restore a file from trash to its original location.
-/// Searches the manifest for a file with the given original path.
-pub fn restore_from_trash(original_path: &std::path::Path) -> Result<(), std::io::Error> {
-    let manifest_path = config_dir("trash").join("files").join("manifest.json");
+/// Searches the manifest for a file with the given original path (the most
+/// recently trashed one, if there are several). If that name is taken again
+/// the file comes back as "name (restored).ext"; returns the path used.
+pub fn restore_from_trash(original_path: &std::path::Path) -> Result<PathBuf, std::io::Error> {
+    let manifest_path = manifest_path();
     let mut manifest = TrashManifest::load(&manifest_path);
 
     // Find the entry with matching original path
-    let idx = manifest.entries.iter().position(|e| e.original_path == original_path);
+    let idx = manifest.entries.iter().rposition(|e| e.original_path == original_path);
 
     if let Some(idx) = idx {
-        let entry = manifest.entries.remove(idx);
+        let dest = move_back(&manifest.entries[idx])?;
+        manifest.entries.remove(idx);
+        manifest.save(&manifest_path);
+        Ok(dest)
+    } else {
+        Err(std::io::Error::new(std::io::ErrorKind::NotFound, "file not found in trash"))
+    }
+}
 
-        // Ensure parent directory exists
-        if let Some(parent) = entry.original_path.parent() {
-            std::fs::create_dir_all(parent)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+    const MB: u64 = 1024 * 1024;
+
+    fn entry(name: &str, deleted_at: u64, size: u64) -> TrashEntry {
+        TrashEntry {
+            original_name: name.to_string(),
+            original_path: PathBuf::from("/nowhere").join(name),
+            trash_path: PathBuf::from("/nowhere/trash").join(name),
+            trashed_at: String::new(),
+            deleted_at,
+            size,
         }
+    }
 
-        // Try to move the file back
-        std::fs::rename(&entry.trash_path, &entry.original_path).or_else(|_| {
-            // Cross-filesystem: copy then delete
-            if entry.trash_path.is_dir() {
-                Err(std::io::Error::new(std::io::ErrorKind::Other, "cannot restore directory across filesystems"))
-            } else {
-                std::fs::copy(&entry.trash_path, &entry.original_path)?;
-                std::fs::remove_file(&entry.trash_path)
-            }
-        })?;
+    fn names(manifest: &TrashManifest) -> Vec<&str> {
+        manifest.entries.iter().map(|e| e.original_name.as_str()).collect()
+    }
 
-        manifest.save(&manifest_path);
-        Ok(())
-    } else {
-        Err(std::io::Error::new(std::io::ErrorKind::NotFound, "file not found in trash"))
+    #[test]
+    fn test_purge() {
+        let now = 100 * DAY;
+        let trash = TrashManifest {
+            entries: vec![entry("b", now - 2 * DAY, MB), entry("a", now - 40 * DAY, MB), entry("c", now - DAY, MB)],
+        };
+
+        // Past the age limit, and just inside it
+        let mut manifest = trash.clone();
+        let by_age = TrashSettings { max_age_days: Some(30), quota_mb: None };
+        assert_eq!(manifest.purge(&by_age, now), 1);
+        assert_eq!(names(&manifest), ["b", "c"]);
+        let mut manifest = trash.clone();
+        assert_eq!(manifest.purge(&TrashSettings { max_age_days: Some(40), ..by_age }, now), 0);
+
+        // Over quota the oldest go first, and the rest keep their order
+        let mut manifest = trash.clone();
+        assert_eq!(manifest.purge(&TrashSettings { max_age_days: None, quota_mb: Some(2) }, now), 1);
+        assert_eq!(names(&manifest), ["b", "c"]);
+        let mut manifest = trash.clone();
+        assert_eq!(manifest.purge(&TrashSettings { max_age_days: None, quota_mb: Some(1) }, now), 2);
+        assert_eq!(names(&manifest), ["c"]);
+
+        // The newest item stays even when it alone is over quota
+        let mut manifest = TrashManifest { entries: vec![entry("big", now, 5 * MB), entry("old", now - DAY, MB)] };
+        assert_eq!(manifest.purge(&TrashSettings { max_age_days: None, quota_mb: Some(1) }, now), 1);
+        assert_eq!(names(&manifest), ["big"]);
+    }
+
+    #[test]
+    fn test_free_restore_path() {
+        let dir = std::env::temp_dir().join(format!("trash-restore-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.txt");
+        assert_eq!(free_restore_path(&path), path);
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(free_restore_path(&path), dir.join("notes (restored).txt"));
+        std::fs::write(dir.join("notes (restored).txt"), "").unwrap();
+        assert_eq!(free_restore_path(&path), dir.join("notes (restored 2).txt"));
+        // Folders have no extension to keep
+        std::fs::create_dir_all(dir.join("photos")).unwrap();
+        assert_eq!(free_restore_path(&dir.join("photos")), dir.join("photos (restored)"));
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -5,3 +5,4 @@ mod app;
 pub use app::move_to_trash;
 pub use app::trash_dir;
 pub use app::restore_from_trash;
+pub use app::{format_size, notify_trashed, perform_undo, trash_usage, TrashSettings, TrashUsage};
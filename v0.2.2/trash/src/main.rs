@@ -1,11 +1,11 @@
 mod app;
 use app::TrashApp;
 use eframe::NativeOptions;
+use slowcore::window_layout::{self, Placement};
 
 fn main() -> eframe::Result<()> {
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([480.0, 360.0])
+        viewport: window_layout::viewport("trash", Placement::RememberLast, [480.0, 360.0])
             .with_title("trash"),
         ..Default::default()
     };